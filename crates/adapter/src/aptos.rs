@@ -1,79 +1,623 @@
 //! Aptos 适配器
+//!
+//! 基于 Aptos 全节点 REST API（`rpc_url` 为 `/v1` 根地址）实现：
+//! - 合约元数据：`/accounts/{address}/modules`，每个模块的字节码与 ABI 打包为多模块 [`ContractMeta`]
+//! - 交易回执：`/transactions/by_hash/{hash}`，Aptos 交易按账本版本排序，回执中的
+//!   `block_number` 为交易的账本版本
+//! - 余额：`0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>` 资源
+//! - 订阅：轮询账本信息，新区块按区块高度、新交易按账本版本推进
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use serde_json::{Map, Value};
+use std::time::Duration;
 use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
 
+use crate::error::{AdapterError, AdapterResult};
 use crate::traits::ChainAdapter;
 use crate::types::*;
 
+/// 账本轮询间隔
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 交易订阅单次拉取的交易数
+const TRANSACTION_PAGE_SIZE: u64 = 100;
+
+/// APT 的 CoinStore 资源类型
+const APT_COIN_STORE: &str = "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>";
+
+/// 发布 Move 包的入口函数
+const PUBLISH_PACKAGE_FUNCTION: &str = "0x1::code::publish_package_txn";
+
+/// 账本信息（REST 根路径）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LedgerInfo {
+    pub chain_id: u64,
+    pub ledger_version: u64,
+    pub block_height: u64,
+}
+
 pub struct AptosAdapter {
     config: AptosConfig,
+    client: Client,
+    poll_interval: Duration,
 }
 
 impl AptosAdapter {
     pub async fn new(config: AptosConfig) -> Result<Self> {
-        Ok(Self { config })
+        info!("Aptos adapter initialized: {}", config.rpc_url);
+
+        Ok(Self {
+            config,
+            client: Client::new(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        })
+    }
+
+    /// 设置账本轮询间隔
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// 获取账本信息
+    pub async fn get_ledger_info(&self) -> Result<LedgerInfo> {
+        Self::ledger_info(&self.client, &self.config).await
+    }
+
+    async fn ledger_info(client: &Client, config: &AptosConfig) -> Result<LedgerInfo> {
+        let info = Self::get(client, config, "")
+            .await?
+            .ok_or_else(|| anyhow!("Aptos ledger info is not available"))?;
+        parse_ledger_info(&info)
+    }
+
+    async fn get_json(&self, path: &str) -> Result<Option<Value>> {
+        Self::get(&self.client, &self.config, path).await
+    }
+
+    /// GET 请求，404 返回 None
+    async fn get(client: &Client, config: &AptosConfig, path: &str) -> Result<Option<Value>> {
+        let base = config.rpc_url.trim_end_matches('/');
+        let url = if path.is_empty() {
+            base.to_string()
+        } else {
+            format!("{}/{}", base, path)
+        };
+
+        let response = client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| AdapterError::transport(ChainType::Aptos, e))?;
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| AdapterError::transport(ChainType::Aptos, e))?;
+        if !status.is_success() {
+            return Err(AdapterError::Rpc {
+                chain: ChainType::Aptos,
+                message: format!(
+                    "{} {}",
+                    status,
+                    body["message"].as_str().unwrap_or_default()
+                ),
+            }
+            .into());
+        }
+        Ok(Some(body))
     }
 }
 
 #[async_trait]
 impl ChainAdapter for AptosAdapter {
-    async fn get_contract_meta(&self, _address: &str) -> Result<ContractMeta> {
-        // TODO: Implement Aptos Move module extraction
+    async fn get_contract_meta(&self, address: &str) -> AdapterResult<ContractMeta> {
+        info!("Getting Aptos modules for: {}", address);
+
+        let not_found = || AdapterError::ContractNotFound {
+            chain: ChainType::Aptos,
+            address: address.to_string(),
+        };
+        let response = self
+            .get_json(&format!("accounts/{}/modules", address))
+            .await?
+            .ok_or_else(not_found)?;
+        let modules = parse_account_modules(&response)?;
+        if modules.is_empty() {
+            return Err(not_found());
+        }
+
+        // 与 Sui 的规范化模块一致：模块名 → 模块 ABI
+        let abi: Map<String, Value> = modules
+            .iter()
+            .filter_map(|module| {
+                let abi = serde_json::from_str(module.abi.as_deref()?).ok()?;
+                Some((module.name.clone(), abi))
+            })
+            .collect();
+        let bytecode = modules
+            .iter()
+            .flat_map(|module| module.bytecode.iter().copied())
+            .collect();
+
         Ok(ContractMeta {
-            address: "0x1".to_string(),
+            address: address.to_string(),
             chain_type: ChainType::Aptos,
             contract_type: ContractType::Move,
-            bytecode: vec![],
-            abi: None,
+            bytecode,
+            abi: Some(Value::Object(abi).to_string()),
             source_code: None,
-            compiler_version: None,
-            created_at: 0,
-            creator: None,
+            compiler_version: Some("move".to_string()),
+            created_at: chrono::Utc::now().timestamp() as u64,
+            creator: Some(address.to_string()),
+            abi_source: Some(AbiSource::Verified),
+            modules,
         })
     }
 
-    async fn get_transaction_receipt(&self, _tx_hash: &str) -> Result<TransactionReceipt> {
-        // TODO: Implement Aptos transaction receipt
-        Ok(TransactionReceipt {
-            tx_hash: "0x0".to_string(),
-            block_hash: "0x0".to_string(),
+    async fn get_transaction_receipt(&self, tx_hash: &str) -> AdapterResult<TransactionReceipt> {
+        info!("Getting Aptos transaction: {}", tx_hash);
+
+        let transaction = self
+            .get_json(&format!("transactions/by_hash/{}", tx_hash))
+            .await?
+            .ok_or_else(|| anyhow!("Aptos transaction {} not found", tx_hash))?;
+        debug!("Aptos transaction: {}", transaction);
+
+        let receipt = parse_transaction(&transaction)?;
+        if matches!(receipt.status, TransactionStatus::Failed) {
+            warn!(
+                "Aptos transaction {} failed: {}",
+                tx_hash,
+                transaction["vm_status"].as_str().unwrap_or("unknown")
+            );
+        }
+        Ok(receipt)
+    }
+
+    /// APT 余额（octas）；没有 CoinStore 资源的账户余额为 0
+    async fn get_balance(&self, address: &str) -> AdapterResult<u64> {
+        let balance = match self
+            .get_json(&format!("accounts/{}/resource/{}", address, APT_COIN_STORE))
+            .await?
+        {
+            Some(resource) => parse_coin_store(&resource)?,
+            None => 0,
+        };
+
+        debug!("Aptos balance for {}: {}", address, balance);
+        Ok(balance)
+    }
+
+    /// 账户序列号；不存在的账户为 0
+    async fn get_nonce(&self, address: &str) -> AdapterResult<u64> {
+        match self.get_json(&format!("accounts/{}", address)).await? {
+            Some(account) => string_u64(&account["sequence_number"])
+                .ok_or_else(|| anyhow!("Failed to parse Aptos sequence number").into()),
+            None => Ok(0),
+        }
+    }
+
+    async fn get_block_number(&self) -> AdapterResult<u64> {
+        Ok(self.get_ledger_info().await?.block_height)
+    }
+
+    async fn subscribe_new_blocks(&self) -> AdapterResult<mpsc::Receiver<String>> {
+        info!("Starting Aptos block subscription");
+        let (tx, rx) = mpsc::channel(1000);
+        let client = self.client.clone();
+        let config = self.config.clone();
+        let poll_interval = self.poll_interval;
+
+        // 轮询账本信息，逐个推送新区块高度
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(poll_interval);
+            let mut cursor: Option<u64> = None;
+
+            loop {
+                interval.tick().await;
+
+                let height = match Self::ledger_info(&client, &config).await {
+                    Ok(info) => info.block_height,
+                    Err(e) => {
+                        error!("Failed to get Aptos ledger info: {}", e);
+                        continue;
+                    }
+                };
+                let last = *cursor.get_or_insert(height);
+
+                for block in (last + 1)..=height {
+                    if tx.send(block.to_string()).await.is_err() {
+                        warn!("Aptos block subscription channel closed");
+                        return;
+                    }
+                    cursor = Some(block);
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    async fn subscribe_new_transactions(&self) -> AdapterResult<mpsc::Receiver<String>> {
+        info!("Starting Aptos transaction subscription");
+        let (tx, rx) = mpsc::channel(1000);
+        let client = self.client.clone();
+        let config = self.config.clone();
+        let poll_interval = self.poll_interval;
+
+        // 游标为已推送的账本版本，按页拉取其后的交易，只推送用户交易的哈希
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(poll_interval);
+            let mut cursor: Option<u64> = None;
+
+            loop {
+                interval.tick().await;
+
+                let version = match Self::ledger_info(&client, &config).await {
+                    Ok(info) => info.ledger_version,
+                    Err(e) => {
+                        error!("Failed to get Aptos ledger info: {}", e);
+                        continue;
+                    }
+                };
+                let mut last = *cursor.get_or_insert(version);
+
+                // 拉取失败时停在当前版本，下次轮询重试
+                while last < version {
+                    let path = format!(
+                        "transactions?start={}&limit={}",
+                        last + 1,
+                        TRANSACTION_PAGE_SIZE.min(version - last)
+                    );
+                    let page = match Self::get(&client, &config, &path).await {
+                        Ok(Some(page)) => page,
+                        Ok(None) => break,
+                        Err(e) => {
+                            error!("Failed to get Aptos transactions after {}: {}", last, e);
+                            break;
+                        }
+                    };
+                    let transactions = page.as_array().cloned().unwrap_or_default();
+                    if transactions.is_empty() {
+                        break;
+                    }
+                    for transaction in &transactions {
+                        let Some(transaction_version) = string_u64(&transaction["version"]) else {
+                            continue;
+                        };
+                        if transaction["type"] == "user_transaction" {
+                            if let Some(hash) = transaction["hash"].as_str() {
+                                if tx.send(hash.to_string()).await.is_err() {
+                                    warn!("Aptos transaction subscription channel closed");
+                                    return;
+                                }
+                            }
+                        }
+                        last = last.max(transaction_version);
+                    }
+                    cursor = Some(last);
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+}
+
+/// Aptos REST API 以字符串表示 u64
+fn string_u64(value: &Value) -> Option<u64> {
+    value
+        .as_str()
+        .and_then(|text| text.parse().ok())
+        .or_else(|| value.as_u64())
+}
+
+/// 解析账本信息
+fn parse_ledger_info(info: &Value) -> Result<LedgerInfo> {
+    let field = |name: &str| {
+        string_u64(&info[name]).ok_or_else(|| anyhow!("Aptos ledger info without {}", name))
+    };
+    Ok(LedgerInfo {
+        chain_id: field("chain_id")?,
+        ledger_version: field("ledger_version")?,
+        block_height: field("block_height")?,
+    })
+}
+
+/// 解析 `/accounts/{address}/modules`：十六进制字节码与模块 ABI，按模块名排序
+fn parse_account_modules(response: &Value) -> Result<Vec<ModuleArtifact>> {
+    let entries = response
+        .as_array()
+        .ok_or_else(|| anyhow!("Unexpected Aptos modules response"))?;
+
+    let mut modules = entries
+        .iter()
+        .map(|entry| {
+            let abi = &entry["abi"];
+            let name = abi["name"]
+                .as_str()
+                .ok_or_else(|| anyhow!("Aptos module without ABI name"))?;
+            let encoded = entry["bytecode"]
+                .as_str()
+                .ok_or_else(|| anyhow!("Aptos module {} without bytecode", name))?;
+            let bytecode = hex::decode(encoded.strip_prefix("0x").unwrap_or(encoded))
+                .map_err(|e| anyhow!("Invalid bytecode for Aptos module {}: {}", name, e))?;
+            Ok(ModuleArtifact {
+                name: name.to_string(),
+                bytecode,
+                abi: Some(abi.to_string()),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    modules.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(modules)
+}
+
+/// 解析 `/transactions/by_hash/{hash}`
+fn parse_transaction(transaction: &Value) -> Result<TransactionReceipt> {
+    let tx_hash = transaction["hash"]
+        .as_str()
+        .ok_or_else(|| anyhow!("Aptos transaction without hash"))?
+        .to_string();
+    let from = transaction["sender"].as_str().unwrap_or("0x0").to_string();
+    let payload = &transaction["payload"];
+    let function = payload["function"].as_str();
+    let to = function
+        .and_then(|function| function.split("::").next())
+        .map(|address| address.to_string());
+
+    // 内存池中的交易还没有版本与执行结果
+    if transaction["type"] == "pending_transaction" {
+        return Ok(TransactionReceipt {
+            tx_hash,
+            block_hash: String::new(),
             block_number: 0,
             transaction_index: 0,
-            from: "0x0".to_string(),
-            to: None,
+            from,
+            to,
             gas_used: 0,
-            status: TransactionStatus::Success,
+            status: TransactionStatus::Pending,
             logs: vec![],
             contract_address: None,
-        })
+        });
     }
 
-    async fn get_balance(&self, _address: &str) -> Result<u64> {
-        // TODO: Implement Aptos balance query
-        Ok(0)
+    let version = string_u64(&transaction["version"])
+        .ok_or_else(|| anyhow!("Aptos transaction {} without version", tx_hash))?;
+    let status = if transaction["success"].as_bool() == Some(true) {
+        TransactionStatus::Success
+    } else {
+        TransactionStatus::Failed
+    };
+
+    let logs = transaction["events"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|event| EventLog {
+            address: event["guid"]["account_address"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            topics: vec![event["type"].as_str().unwrap_or_default().to_string()],
+            data: event["data"].to_string(),
+        })
+        .collect();
+
+    // 发布模块的交易以发送方地址作为合约地址
+    let publishes =
+        function == Some(PUBLISH_PACKAGE_FUNCTION) || payload["type"] == "module_bundle_payload";
+
+    Ok(TransactionReceipt {
+        tx_hash,
+        block_hash: transaction["accumulator_root_hash"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        block_number: version,
+        transaction_index: 0,
+        contract_address: publishes.then(|| from.clone()),
+        from,
+        to,
+        gas_used: string_u64(&transaction["gas_used"]).unwrap_or(0),
+        status,
+        logs,
+    })
+}
+
+/// 解析 CoinStore 资源中的余额
+fn parse_coin_store(resource: &Value) -> Result<u64> {
+    string_u64(&resource["data"]["coin"]["value"])
+        .ok_or_else(|| anyhow!("Unexpected Aptos CoinStore resource"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEDGER_INFO: &str = r#"{
+        "chain_id": 1,
+        "epoch": "9012",
+        "ledger_version": "1753002187",
+        "oldest_ledger_version": "0",
+        "ledger_timestamp": "1729042611429463",
+        "node_role": "full_node",
+        "oldest_block_height": "0",
+        "block_height": "274500377",
+        "git_hash": "0b3a5f06c3c0b2b4b0c8f0c2d3e5e2b1e5c0f7d4"
+    }"#;
+
+    const ACCOUNT_MODULES: &str = r#"[
+        {
+            "bytecode": "0xa11ceb0b0600000001",
+            "abi": {
+                "address": "0xa1",
+                "name": "pool",
+                "friends": [],
+                "exposed_functions": [
+                    {
+                        "name": "swap",
+                        "visibility": "public",
+                        "is_entry": true,
+                        "is_view": false,
+                        "generic_type_params": [],
+                        "params": ["&signer", "u64"],
+                        "return": []
+                    }
+                ],
+                "structs": []
+            }
+        },
+        {
+            "bytecode": "0xa11ceb0b0600000002",
+            "abi": {
+                "address": "0xa1",
+                "name": "coin",
+                "friends": ["0xa1::pool"],
+                "exposed_functions": [],
+                "structs": []
+            }
+        }
+    ]"#;
+
+    const USER_TRANSACTION: &str = r#"{
+        "version": "1753002100",
+        "hash": "0x5a0bc5d0b8b3c2a98f3e3f0e6e7f0f6c1b8d7a4e2c9f0a1b2c3d4e5f6a7b8c9d",
+        "state_change_hash": "0x01",
+        "event_root_hash": "0x02",
+        "gas_used": "12",
+        "success": true,
+        "vm_status": "Executed successfully",
+        "accumulator_root_hash": "0xacc0",
+        "changes": [],
+        "sender": "0xb0b",
+        "sequence_number": "7",
+        "payload": {
+            "function": "0x1::aptos_account::transfer",
+            "type_arguments": [],
+            "arguments": ["0xa11ce", "1000"],
+            "type": "entry_function_payload"
+        },
+        "events": [
+            {
+                "guid": { "creation_number": "2", "account_address": "0xb0b" },
+                "sequence_number": "3",
+                "type": "0x1::coin::WithdrawEvent",
+                "data": { "amount": "1000" }
+            }
+        ],
+        "timestamp": "1729042611000000",
+        "type": "user_transaction"
+    }"#;
+
+    const FAILED_PUBLISH: &str = r#"{
+        "version": "1753002101",
+        "hash": "0x6b",
+        "gas_used": "530",
+        "success": false,
+        "vm_status": "Move abort in 0x1::code: EMODULE_NAME_CLASH(0x10001)",
+        "accumulator_root_hash": "0xacc1",
+        "sender": "0xa1",
+        "payload": {
+            "function": "0x1::code::publish_package_txn",
+            "type_arguments": [],
+            "arguments": ["0x00", ["0xa11ceb0b"]],
+            "type": "entry_function_payload"
+        },
+        "events": [],
+        "type": "user_transaction"
+    }"#;
+
+    const PENDING_TRANSACTION: &str = r#"{
+        "hash": "0x7c",
+        "sender": "0xb0b",
+        "sequence_number": "8",
+        "payload": {
+            "function": "0xa1::pool::swap",
+            "type_arguments": [],
+            "arguments": ["5"],
+            "type": "entry_function_payload"
+        },
+        "type": "pending_transaction"
+    }"#;
+
+    const COIN_STORE: &str = r#"{
+        "type": "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+        "data": {
+            "coin": { "value": "123456789" },
+            "deposit_events": { "counter": "1", "guid": { "id": { "addr": "0xb0b", "creation_num": "2" } } },
+            "frozen": false,
+            "withdraw_events": { "counter": "1", "guid": { "id": { "addr": "0xb0b", "creation_num": "3" } } }
+        }
+    }"#;
+
+    fn fixture(json: &str) -> Value {
+        serde_json::from_str(json).unwrap()
     }
 
-    async fn get_nonce(&self, _address: &str) -> Result<u64> {
-        // TODO: Implement Aptos sequence number query
-        Ok(0)
+    #[test]
+    fn test_parse_ledger_info() {
+        assert_eq!(
+            parse_ledger_info(&fixture(LEDGER_INFO)).unwrap(),
+            LedgerInfo {
+                chain_id: 1,
+                ledger_version: 1_753_002_187,
+                block_height: 274_500_377,
+            }
+        );
+        assert!(parse_ledger_info(&serde_json::json!({ "chain_id": 1 })).is_err());
     }
 
-    async fn get_block_number(&self) -> Result<u64> {
-        // TODO: Implement Aptos block height query
-        Ok(0)
+    #[test]
+    fn test_parse_account_modules() {
+        let modules = parse_account_modules(&fixture(ACCOUNT_MODULES)).unwrap();
+        let names: Vec<&str> = modules.iter().map(|module| module.name.as_str()).collect();
+        assert_eq!(names, vec!["coin", "pool"]);
+        assert_eq!(
+            modules[1].bytecode,
+            vec![0xa1, 0x1c, 0xeb, 0x0b, 6, 0, 0, 0, 1]
+        );
+        let abi: Value = serde_json::from_str(modules[1].abi.as_deref().unwrap()).unwrap();
+        assert_eq!(abi["exposed_functions"][0]["name"], "swap");
+
+        let invalid = serde_json::json!([{ "bytecode": "0xzz", "abi": { "name": "bad" } }]);
+        assert!(parse_account_modules(&invalid).is_err());
     }
 
-    async fn subscribe_new_blocks(&self) -> Result<mpsc::Receiver<String>> {
-        // TODO: Implement Aptos new block subscription
-        let (_tx, rx) = mpsc::channel(1000);
-        Ok(rx)
+    #[test]
+    fn test_parse_transactions() {
+        let receipt = parse_transaction(&fixture(USER_TRANSACTION)).unwrap();
+        assert!(matches!(receipt.status, TransactionStatus::Success));
+        assert_eq!(receipt.block_number, 1_753_002_100);
+        assert_eq!(receipt.block_hash, "0xacc0");
+        assert_eq!(receipt.from, "0xb0b");
+        assert_eq!(receipt.to.as_deref(), Some("0x1"));
+        assert_eq!(receipt.gas_used, 12);
+        assert_eq!(receipt.logs.len(), 1);
+        assert_eq!(receipt.logs[0].topics, vec!["0x1::coin::WithdrawEvent"]);
+        assert_eq!(receipt.logs[0].data, r#"{"amount":"1000"}"#);
+        assert!(receipt.contract_address.is_none());
+
+        let failed = parse_transaction(&fixture(FAILED_PUBLISH)).unwrap();
+        assert!(matches!(failed.status, TransactionStatus::Failed));
+        assert_eq!(failed.contract_address.as_deref(), Some("0xa1"));
+
+        let pending = parse_transaction(&fixture(PENDING_TRANSACTION)).unwrap();
+        assert!(matches!(pending.status, TransactionStatus::Pending));
+        assert_eq!(pending.to.as_deref(), Some("0xa1"));
+        assert_eq!(pending.block_number, 0);
     }
 
-    async fn subscribe_new_transactions(&self) -> Result<mpsc::Receiver<String>> {
-        // TODO: Implement Aptos new transaction subscription
-        let (_tx, rx) = mpsc::channel(1000);
-        Ok(rx)
+    #[test]
+    fn test_parse_coin_store() {
+        assert_eq!(parse_coin_store(&fixture(COIN_STORE)).unwrap(), 123_456_789);
+        assert!(parse_coin_store(&serde_json::json!({ "data": {} })).is_err());
     }
 }
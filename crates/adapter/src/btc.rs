@@ -1,49 +1,463 @@
 //! Bitcoin 适配器
+//!
+//! 支持观察地址登记、UTXO 查询与手续费估算，数据源可选 bitcoind JSON-RPC 或 Esplora HTTP API
 
+use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
-use anyhow::Result;
-use tokio::sync::mpsc;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use tokio::sync::{mpsc, RwLock};
+use tracing::{debug, error, info, warn};
 
+use crate::error::{AdapterError, AdapterResult};
 use crate::traits::ChainAdapter;
 use crate::types::*;
 
+/// 链头轮询间隔
+const TIP_POLL_INTERVAL_SECS: u64 = 10;
+
+/// 观察地址在 bitcoind 钱包中的标签
+const WATCH_LABEL: &str = "dubhe-watch";
+
+/// 未花费输出
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Utxo {
+    pub txid: String,
+    pub vout: u32,
+    pub value_sats: u64,
+    pub confirmations: u32, // 0 表示仍在内存池中
+}
+
 pub struct BitcoinAdapter {
     config: BitcoinConfig,
+    client: Client,
+    watched: RwLock<BTreeSet<String>>,
 }
 
 impl BitcoinAdapter {
     pub async fn new(config: BitcoinConfig) -> Result<Self> {
-        Ok(Self { config })
+        info!(
+            "Bitcoin adapter initialized ({:?}): {}",
+            config.backend, config.rpc_url
+        );
+
+        Ok(Self {
+            config,
+            client: Client::new(),
+            watched: RwLock::new(BTreeSet::new()),
+        })
+    }
+
+    /// 登记观察地址
+    ///
+    /// bitcoind 后端会把地址以描述符形式导入钱包（不重新扫描历史）
+    pub async fn register_watch_address(&self, address: &str) -> Result<()> {
+        if self.watched.read().await.contains(address) {
+            return Ok(());
+        }
+
+        if self.config.backend == BitcoinBackend::BitcoindRpc {
+            let info = self
+                .call_rpc("getdescriptorinfo", json!([format!("addr({})", address)]))
+                .await?;
+            let descriptor = info["descriptor"]
+                .as_str()
+                .ok_or_else(|| anyhow!("Invalid descriptor for address {}", address))?;
+
+            let imported = self
+                .call_rpc(
+                    "importdescriptors",
+                    json!([[{ "desc": descriptor, "timestamp": "now", "label": WATCH_LABEL }]]),
+                )
+                .await?;
+            if imported[0]["success"].as_bool() != Some(true) {
+                return Err(anyhow!(
+                    "Failed to import watch address {}: {}",
+                    address,
+                    imported[0]["error"]
+                ));
+            }
+        }
+
+        self.watched.write().await.insert(address.to_string());
+        info!("Registered Bitcoin watch address: {}", address);
+        Ok(())
+    }
+
+    /// 已登记的观察地址
+    pub async fn watched_addresses(&self) -> Vec<String> {
+        self.watched.read().await.iter().cloned().collect()
+    }
+
+    /// 查询地址的 UTXO 集合及确认数
+    pub async fn get_utxos(&self, address: &str) -> Result<Vec<Utxo>> {
+        debug!("Getting UTXOs for {}", address);
+
+        match self.config.backend {
+            BitcoinBackend::Esplora => {
+                let utxos = self.esplora_json(&format!("address/{}/utxo", address)).await?;
+                let tip_height = self.get_block_number().await?;
+                parse_esplora_utxos(&utxos, tip_height)
+            }
+            BitcoinBackend::BitcoindRpc => {
+                // listunspent 只能看到钱包中的地址
+                if !self.watched.read().await.contains(address) {
+                    return Err(anyhow!("Address {} is not a registered watch address", address));
+                }
+                let utxos = self
+                    .call_rpc("listunspent", json!([0, 9_999_999, [address]]))
+                    .await?;
+                parse_bitcoind_utxos(&utxos)
+            }
+        }
+    }
+
+    /// 估算在 `target_blocks` 个区块内确认所需的费率（sat/vB）
+    pub async fn get_fee_estimate(&self, target_blocks: u16) -> Result<f64> {
+        match self.config.backend {
+            BitcoinBackend::Esplora => {
+                let estimates = self.esplora_json("fee-estimates").await?;
+                select_fee_estimate(&estimates, target_blocks)
+                    .ok_or_else(|| anyhow!("No fee estimate available"))
+            }
+            BitcoinBackend::BitcoindRpc => {
+                let estimate = self
+                    .call_rpc("estimatesmartfee", json!([target_blocks]))
+                    .await?;
+                // bitcoind 返回 BTC/kvB
+                estimate["feerate"]
+                    .as_f64()
+                    .map(|btc_per_kvb| btc_per_kvb * 100_000_000.0 / 1000.0)
+                    .ok_or_else(|| anyhow!("No fee estimate available: {}", estimate["errors"]))
+            }
+        }
+    }
+
+    /// 获取链头区块哈希
+    pub async fn get_tip_hash(&self) -> Result<String> {
+        Self::tip_hash(&self.client, &self.config).await
+    }
+
+    async fn tip_hash(client: &Client, config: &BitcoinConfig) -> Result<String> {
+        match config.backend {
+            BitcoinBackend::Esplora => Self::esplora_get(client, config, "blocks/tip/hash").await,
+            BitcoinBackend::BitcoindRpc => Self::rpc(client, config, "getbestblockhash", json!([]))
+                .await?
+                .as_str()
+                .map(|hash| hash.to_string())
+                .ok_or_else(|| anyhow!("Failed to parse best block hash")),
+        }
+    }
+
+    /// 调用 bitcoind JSON-RPC 方法
+    async fn call_rpc(&self, method: &str, params: Value) -> Result<Value> {
+        Self::rpc(&self.client, &self.config, method, params).await
+    }
+
+    async fn rpc(
+        client: &Client,
+        config: &BitcoinConfig,
+        method: &str,
+        params: Value,
+    ) -> Result<Value> {
+        let request = json!({
+            "jsonrpc": "1.0",
+            "id": "dubhe",
+            "method": method,
+            "params": params
+        });
+
+        let response_json: Value = client
+            .post(&config.rpc_url)
+            .basic_auth(&config.rpc_user, Some(&config.rpc_password))
+            .json(&request)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if !response_json["error"].is_null() {
+            return Err(anyhow!("Bitcoin RPC error: {}", response_json["error"]));
+        }
+
+        Ok(response_json["result"].clone())
+    }
+
+    async fn esplora_get(client: &Client, config: &BitcoinConfig, path: &str) -> Result<String> {
+        let url = format!("{}/{}", config.rpc_url.trim_end_matches('/'), path);
+        Ok(client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?)
+    }
+
+    async fn esplora_json(&self, path: &str) -> Result<Value> {
+        let body = Self::esplora_get(&self.client, &self.config, path).await?;
+        Ok(serde_json::from_str(&body)?)
     }
 }
 
 #[async_trait]
 impl ChainAdapter for BitcoinAdapter {
-    async fn get_contract_meta(&self, address: &str) -> Result<ContractMeta> {
-        todo!("Implement Bitcoin script extraction")
+    async fn get_contract_meta(&self, address: &str) -> AdapterResult<ContractMeta> {
+        Err(AdapterError::Unsupported(format!(
+            "Bitcoin script extraction (address: {})",
+            address
+        )))
     }
 
-    async fn get_transaction_receipt(&self, tx_hash: &str) -> Result<TransactionReceipt> {
-        todo!("Implement Bitcoin transaction info")
+    async fn get_transaction_receipt(&self, tx_hash: &str) -> AdapterResult<TransactionReceipt> {
+        Err(AdapterError::Unsupported(format!(
+            "Bitcoin transaction receipts (tx: {})",
+            tx_hash
+        )))
     }
 
-    async fn get_balance(&self, address: &str) -> Result<u64> {
-        todo!("Implement Bitcoin UTXO balance query")
+    /// 已确认 UTXO 的总额（sats）
+    async fn get_balance(&self, address: &str) -> AdapterResult<u64> {
+        let balance = confirmed_balance(&self.get_utxos(address).await?);
+
+        debug!("Bitcoin confirmed balance for {}: {}", address, balance);
+        Ok(balance)
     }
 
-    async fn get_nonce(&self, address: &str) -> Result<u64> {
-        todo!("Bitcoin doesn't use nonce")
+    async fn get_nonce(&self, _address: &str) -> AdapterResult<u64> {
+        // Bitcoin 基于 UTXO，没有账户 nonce
+        Ok(0)
     }
 
-    async fn get_block_number(&self) -> Result<u64> {
-        todo!("Implement Bitcoin block height query")
+    async fn get_block_number(&self) -> AdapterResult<u64> {
+        match self.config.backend {
+            BitcoinBackend::Esplora => {
+                let height =
+                    Self::esplora_get(&self.client, &self.config, "blocks/tip/height").await?;
+                Ok(height
+                    .trim()
+                    .parse()
+                    .with_context(|| format!("Invalid Esplora tip height: {}", height))?)
+            }
+            BitcoinBackend::BitcoindRpc => self
+                .call_rpc("getblockcount", json!([]))
+                .await?
+                .as_u64()
+                .ok_or_else(|| anyhow!("Failed to parse block count").into()),
+        }
     }
 
-    async fn subscribe_new_blocks(&self) -> Result<mpsc::Receiver<String>> {
-        todo!("Implement Bitcoin new block subscription")
+    async fn subscribe_new_blocks(&self) -> AdapterResult<mpsc::Receiver<String>> {
+        info!("Starting Bitcoin tip subscription");
+        let (tx, rx) = mpsc::channel(1000);
+
+        // 轮询链头哈希，变化时推送（包括重组后的新链头）
+        let config = self.config.clone();
+        let client = self.client.clone();
+
+        tokio::spawn(async move {
+            let mut last_tip: Option<String> = None;
+            let mut interval =
+                tokio::time::interval(tokio::time::Duration::from_secs(TIP_POLL_INTERVAL_SECS));
+
+            loop {
+                interval.tick().await;
+
+                match Self::tip_hash(&client, &config).await {
+                    Ok(tip) => {
+                        if last_tip.as_ref().is_some_and(|last| *last != tip)
+                            && tx.send(tip.clone()).await.is_err()
+                        {
+                            warn!("Bitcoin block subscription channel closed");
+                            return;
+                        }
+                        last_tip = Some(tip);
+                    }
+                    Err(e) => {
+                        error!("Failed to get Bitcoin tip: {}", e);
+                    }
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    async fn subscribe_new_transactions(&self) -> AdapterResult<mpsc::Receiver<String>> {
+        Err(AdapterError::Unsupported(
+            "Bitcoin transaction subscription".to_string(),
+        ))
+    }
+}
+
+/// 已确认（至少一个确认）UTXO 的总额
+pub fn confirmed_balance(utxos: &[Utxo]) -> u64 {
+    utxos
+        .iter()
+        .filter(|utxo| utxo.confirmations > 0)
+        .map(|utxo| utxo.value_sats)
+        .sum()
+}
+
+/// 解析 Esplora `/address/:address/utxo` 响应
+pub fn parse_esplora_utxos(utxos: &Value, tip_height: u64) -> Result<Vec<Utxo>> {
+    let entries = utxos
+        .as_array()
+        .ok_or_else(|| anyhow!("Unexpected Esplora UTXO response"))?;
+
+    entries
+        .iter()
+        .map(|entry| {
+            let status = &entry["status"];
+            let confirmations = match status["block_height"].as_u64() {
+                Some(height) if status["confirmed"].as_bool() == Some(true) => {
+                    tip_height.saturating_sub(height) + 1
+                }
+                _ => 0,
+            };
+
+            Ok(Utxo {
+                txid: entry["txid"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("UTXO without txid"))?
+                    .to_string(),
+                vout: entry["vout"]
+                    .as_u64()
+                    .ok_or_else(|| anyhow!("UTXO without vout"))? as u32,
+                value_sats: entry["value"]
+                    .as_u64()
+                    .ok_or_else(|| anyhow!("UTXO without value"))?,
+                confirmations: confirmations as u32,
+            })
+        })
+        .collect()
+}
+
+/// 解析 bitcoind `listunspent` 响应（金额单位为 BTC）
+pub fn parse_bitcoind_utxos(utxos: &Value) -> Result<Vec<Utxo>> {
+    let entries = utxos
+        .as_array()
+        .ok_or_else(|| anyhow!("Unexpected listunspent response"))?;
+
+    entries
+        .iter()
+        .map(|entry| {
+            let amount = entry["amount"]
+                .as_f64()
+                .ok_or_else(|| anyhow!("UTXO without amount"))?;
+
+            Ok(Utxo {
+                txid: entry["txid"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("UTXO without txid"))?
+                    .to_string(),
+                vout: entry["vout"]
+                    .as_u64()
+                    .ok_or_else(|| anyhow!("UTXO without vout"))? as u32,
+                value_sats: (amount * 100_000_000.0).round() as u64,
+                confirmations: entry["confirmations"].as_u64().unwrap_or(0) as u32,
+            })
+        })
+        .collect()
+}
+
+/// 从 Esplora `/fee-estimates` 中选取费率
+///
+/// 取不小于目标的最近一档；目标超出所有档位时取最慢一档
+pub fn select_fee_estimate(estimates: &Value, target_blocks: u16) -> Option<f64> {
+    let mut tiers: Vec<(u16, f64)> = estimates
+        .as_object()?
+        .iter()
+        .filter_map(|(target, rate)| Some((target.parse().ok()?, rate.as_f64()?)))
+        .collect();
+    tiers.sort_by_key(|(target, _)| *target);
+
+    tiers
+        .iter()
+        .find(|(target, _)| *target >= target_blocks)
+        .or(tiers.last())
+        .map(|(_, rate)| *rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ESPLORA_UTXOS: &str = r#"[
+        {
+            "txid": "9d3a5f06c3c0b2b4b0c8f0c2d3e5e2b1e5c0f7d4b1a0c9e8d7f6a5b4c3d2e1f0",
+            "vout": 0,
+            "status": {
+                "confirmed": true,
+                "block_height": 840000,
+                "block_hash": "0000000000000000000320283a032748cef8227873ff4872689bf23f1cda83a5",
+                "block_time": 1713571767
+            },
+            "value": 150000
+        },
+        {
+            "txid": "1f2e3d4c5b6a79880716253443526170e8f9a0b1c2d3e4f5a6b7c8d9e0f1a2b3",
+            "vout": 2,
+            "status": { "confirmed": false },
+            "value": 2500
+        }
+    ]"#;
+
+    const ESPLORA_FEE_ESTIMATES: &str =
+        r#"{"1": 87.882, "2": 87.882, "3": 87.882, "6": 68.285, "144": 1.027, "1008": 1.0}"#;
+
+    const LISTUNSPENT: &str = r#"[
+        {
+            "txid": "9d3a5f06c3c0b2b4b0c8f0c2d3e5e2b1e5c0f7d4b1a0c9e8d7f6a5b4c3d2e1f0",
+            "vout": 1,
+            "address": "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+            "label": "dubhe-watch",
+            "amount": 0.00150000,
+            "confirmations": 6,
+            "spendable": false,
+            "solvable": true,
+            "safe": true
+        }
+    ]"#;
+
+    #[test]
+    fn test_parse_esplora_utxos() {
+        let utxos = parse_esplora_utxos(&serde_json::from_str(ESPLORA_UTXOS).unwrap(), 840005)
+            .unwrap();
+
+        assert_eq!(utxos.len(), 2);
+        assert_eq!(utxos[0].value_sats, 150000);
+        assert_eq!(utxos[0].confirmations, 6);
+        assert_eq!(utxos[1].vout, 2);
+        assert_eq!(utxos[1].confirmations, 0);
+
+        // 未确认的输出不计入余额
+        assert_eq!(confirmed_balance(&utxos), 150000);
+    }
+
+    #[test]
+    fn test_parse_bitcoind_utxos() {
+        let utxos = parse_bitcoind_utxos(&serde_json::from_str(LISTUNSPENT).unwrap()).unwrap();
+        assert_eq!(
+            utxos,
+            vec![Utxo {
+                txid: "9d3a5f06c3c0b2b4b0c8f0c2d3e5e2b1e5c0f7d4b1a0c9e8d7f6a5b4c3d2e1f0"
+                    .to_string(),
+                vout: 1,
+                value_sats: 150000,
+                confirmations: 6,
+            }]
+        );
     }
 
-    async fn subscribe_new_transactions(&self) -> Result<mpsc::Receiver<String>> {
-        todo!("Implement Bitcoin new transaction subscription")
+    #[test]
+    fn test_select_fee_estimate() {
+        let estimates: Value = serde_json::from_str(ESPLORA_FEE_ESTIMATES).unwrap();
+        assert_eq!(select_fee_estimate(&estimates, 1), Some(87.882));
+        assert_eq!(select_fee_estimate(&estimates, 4), Some(68.285));
+        assert_eq!(select_fee_estimate(&estimates, 2000), Some(1.0));
+        assert_eq!(select_fee_estimate(&json!({}), 6), None);
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,37 @@
+//! 订阅游标
+//!
+//! 轮询式订阅把最后投递的检查点（区块）号持久化到游标存储，
+//! 重启后从下一个检查点继续投递，既不重放整条链也不跳过停机期间的数据
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// 游标存储，键区分链、网络与订阅流
+pub trait CursorStore: Send + Sync {
+    /// 读取最后投递的检查点号
+    fn load(&self, key: &str) -> Result<Option<u64>>;
+
+    /// 记录最后投递的检查点号
+    fn save(&self, key: &str, checkpoint: u64) -> Result<()>;
+}
+
+/// 进程内游标存储，重启后丢失
+#[derive(Debug, Default)]
+pub struct MemoryCursorStore {
+    cursors: Mutex<HashMap<String, u64>>,
+}
+
+impl CursorStore for MemoryCursorStore {
+    fn load(&self, key: &str) -> Result<Option<u64>> {
+        Ok(self.cursors.lock().unwrap().get(key).copied())
+    }
+
+    fn save(&self, key: &str, checkpoint: u64) -> Result<()> {
+        self.cursors
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), checkpoint);
+        Ok(())
+    }
+}
@@ -0,0 +1,639 @@
+//! RPC 端点池
+//!
+//! 一条链可以配置多个 RPC 端点：请求按权重轮询分配，幂等的读请求在传输失败时
+//! 依次改用下一个端点重试。端点连续失败达到阈值后熔断、移出轮询，
+//! 冷却期过后由 [`HealthChecker`] 重新探测，探测成功才恢复
+
+use async_trait::async_trait;
+use reqwest::{Client, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::error::AdapterError;
+use crate::types::ChainType;
+
+/// 带权重的 RPC 端点
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcEndpoint {
+    pub url: String,
+    /// 轮询权重，权重为 2 的端点分到的请求是权重为 1 的两倍
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// 健康检查与熔断参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthCheckConfig {
+    /// 连续失败多少次后熔断
+    pub failure_threshold: u32,
+    /// 熔断后至少等待多久再探测（毫秒）
+    pub cooldown_ms: u64,
+    /// 健康探测间隔（毫秒）
+    pub probe_interval_ms: u64,
+    /// 单次请求超时（毫秒）
+    pub request_timeout_ms: u64,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown_ms: 30_000,
+            probe_interval_ms: 15_000,
+            request_timeout_ms: 10_000,
+        }
+    }
+}
+
+impl HealthCheckConfig {
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_millis(self.request_timeout_ms.max(1))
+    }
+
+    fn cooldown(&self) -> Duration {
+        Duration::from_millis(self.cooldown_ms)
+    }
+
+    fn probe_interval(&self) -> Duration {
+        Duration::from_millis(self.probe_interval_ms.max(1))
+    }
+}
+
+/// 端点健康快照
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointHealth {
+    pub url: String,
+    pub weight: u32,
+    /// 是否在轮询中（未熔断）
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    /// 最近一次成功请求或探测的延迟
+    pub latency_ms: Option<u64>,
+    pub last_error: Option<String>,
+    pub requests: u64,
+    pub failures: u64,
+}
+
+#[derive(Debug, Default)]
+struct EndpointState {
+    consecutive_failures: u32,
+    /// 熔断时刻，`None` 表示在轮询中
+    opened_at: Option<Instant>,
+    latency: Option<Duration>,
+    last_error: Option<String>,
+    requests: u64,
+    failures: u64,
+}
+
+struct Endpoint {
+    config: RpcEndpoint,
+    state: Mutex<EndpointState>,
+}
+
+/// 一条链的 RPC 端点池
+pub struct EndpointPool {
+    chain: ChainType,
+    endpoints: Vec<Endpoint>,
+    /// 按权重展开的轮询序列
+    schedule: Vec<usize>,
+    next: AtomicUsize,
+    config: HealthCheckConfig,
+}
+
+impl EndpointPool {
+    /// `primary` 为配置中的 `rpc_url`，`extra` 中的同名端点覆盖其权重
+    pub fn new(
+        chain: ChainType,
+        primary: &str,
+        extra: &[RpcEndpoint],
+        config: HealthCheckConfig,
+    ) -> Self {
+        let mut configs: Vec<RpcEndpoint> = Vec::new();
+        if !extra.iter().any(|endpoint| endpoint.url == primary) {
+            configs.push(RpcEndpoint {
+                url: primary.to_string(),
+                weight: default_weight(),
+            });
+        }
+        for endpoint in extra {
+            if !configs.iter().any(|known| known.url == endpoint.url) {
+                configs.push(endpoint.clone());
+            }
+        }
+
+        let schedule = configs
+            .iter()
+            .enumerate()
+            .flat_map(|(index, endpoint)| {
+                std::iter::repeat_n(index, endpoint.weight.max(1) as usize)
+            })
+            .collect();
+        Self {
+            chain,
+            endpoints: configs
+                .into_iter()
+                .map(|config| Endpoint {
+                    config,
+                    state: Mutex::new(EndpointState::default()),
+                })
+                .collect(),
+            schedule,
+            next: AtomicUsize::new(0),
+            config,
+        }
+    }
+
+    pub fn chain(&self) -> ChainType {
+        self.chain
+    }
+
+    pub fn config(&self) -> &HealthCheckConfig {
+        &self.config
+    }
+
+    /// 所有端点的地址
+    pub fn urls(&self) -> Vec<&str> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| endpoint.config.url.as_str())
+            .collect()
+    }
+
+    /// 发送请求，幂等请求在传输失败时改用下一个端点重试
+    ///
+    /// RPC 层错误说明端点本身可用，直接返回且不计入失败
+    pub async fn call<T, F, Fut>(&self, idempotent: bool, request: F) -> Result<T, AdapterError>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<T, AdapterError>>,
+    {
+        let mut last_error = None;
+        for index in self.candidates() {
+            let url = &self.endpoints[index].config.url;
+            let started = Instant::now();
+            match request(url.clone()).await {
+                Err(e) if e.is_transport() => {
+                    self.record_failure(index, &e);
+                    if !idempotent {
+                        return Err(e);
+                    }
+                    warn!(
+                        "⚠️ {:?} request to {} failed, trying next endpoint: {}",
+                        self.chain,
+                        url,
+                        error_message(&e)
+                    );
+                    last_error = Some(e);
+                }
+                result => {
+                    self.record_success(index, started.elapsed());
+                    return result;
+                }
+            }
+        }
+        Err(last_error.expect("endpoint pool is never empty"))
+    }
+
+    /// 各端点的健康状态
+    pub fn health(&self) -> Vec<EndpointHealth> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                let state = endpoint.state.lock().unwrap();
+                EndpointHealth {
+                    url: endpoint.config.url.clone(),
+                    weight: endpoint.config.weight,
+                    healthy: state.opened_at.is_none(),
+                    consecutive_failures: state.consecutive_failures,
+                    latency_ms: state.latency.map(|latency| latency.as_millis() as u64),
+                    last_error: state.last_error.clone(),
+                    requests: state.requests,
+                    failures: state.failures,
+                }
+            })
+            .collect()
+    }
+
+    /// 本次请求依次尝试的端点：按权重轮询选出首个未熔断的端点，随后是其余未熔断的端点；
+    /// 全部熔断时退而尝试所有端点
+    fn candidates(&self) -> Vec<usize> {
+        let healthy: Vec<bool> = self
+            .endpoints
+            .iter()
+            .map(|endpoint| endpoint.state.lock().unwrap().opened_at.is_none())
+            .collect();
+        if !healthy.contains(&true) {
+            return (0..self.endpoints.len()).collect();
+        }
+
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let first = (0..self.schedule.len())
+            .map(|offset| self.schedule[(start + offset) % self.schedule.len()])
+            .find(|&index| healthy[index])
+            .expect("at least one endpoint is in rotation");
+        let count = self.endpoints.len();
+        std::iter::once(first)
+            .chain(
+                (1..count)
+                    .map(|offset| (first + offset) % count)
+                    .filter(|&index| healthy[index]),
+            )
+            .collect()
+    }
+
+    fn record_success(&self, index: usize, latency: Duration) {
+        let endpoint = &self.endpoints[index];
+        let mut state = endpoint.state.lock().unwrap();
+        state.requests += 1;
+        state.consecutive_failures = 0;
+        state.latency = Some(latency);
+        if state.opened_at.take().is_some() {
+            info!(
+                "✅ {:?} endpoint {} is back in rotation",
+                self.chain, endpoint.config.url
+            );
+        }
+    }
+
+    fn record_failure(&self, index: usize, error: &AdapterError) {
+        let endpoint = &self.endpoints[index];
+        let mut state = endpoint.state.lock().unwrap();
+        state.requests += 1;
+        state.failures += 1;
+        state.consecutive_failures += 1;
+        state.last_error = Some(error_message(error));
+        if state.opened_at.is_some() {
+            // 探测仍失败，重新计算冷却期
+            state.opened_at = Some(Instant::now());
+        } else if state.consecutive_failures >= self.config.failure_threshold.max(1) {
+            state.opened_at = Some(Instant::now());
+            warn!(
+                "🔌 Taking {:?} endpoint {} out of rotation after {} consecutive failures",
+                self.chain, endpoint.config.url, state.consecutive_failures
+            );
+        }
+    }
+
+    /// 需要探测的端点：未熔断的端点，以及冷却期已过的熔断端点
+    fn probe_due(&self, index: usize) -> bool {
+        match self.endpoints[index].state.lock().unwrap().opened_at {
+            Some(opened_at) => opened_at.elapsed() >= self.config.cooldown(),
+            None => true,
+        }
+    }
+}
+
+/// 带上底层原因的错误描述
+fn error_message(error: &AdapterError) -> String {
+    match std::error::Error::source(error) {
+        Some(source) => format!("{}: {}", error, source),
+        None => error.to_string(),
+    }
+}
+
+/// 向单个端点发送 JSON-RPC 请求，返回 `result`
+pub async fn post_json_rpc(
+    client: &Client,
+    chain: ChainType,
+    url: String,
+    method: &str,
+    params: &Value,
+) -> Result<Value, AdapterError> {
+    let transport = |e: reqwest::Error| AdapterError::transport(chain, e);
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params
+    });
+
+    // 限流（429）与网关错误（5xx）同样视为端点不可用
+    let response = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .json(&request)
+        .send()
+        .await
+        .and_then(Response::error_for_status)
+        .map_err(transport)?;
+    let response_json: Value = response.json().await.map_err(transport)?;
+
+    if let Some(error) = response_json.get("error") {
+        return Err(AdapterError::Rpc {
+            chain,
+            message: error.to_string(),
+        });
+    }
+    Ok(response_json["result"].clone())
+}
+
+/// 端点健康探测
+#[async_trait]
+pub trait EndpointProbe: Send + Sync {
+    async fn probe(&self, url: &str) -> Result<(), AdapterError>;
+}
+
+/// 以开销最小的 JSON-RPC 方法探测端点
+pub struct JsonRpcProbe {
+    client: Client,
+    chain: ChainType,
+    method: &'static str,
+}
+
+impl JsonRpcProbe {
+    pub fn new(client: Client, chain: ChainType, method: &'static str) -> Self {
+        Self {
+            client,
+            chain,
+            method,
+        }
+    }
+}
+
+#[async_trait]
+impl EndpointProbe for JsonRpcProbe {
+    async fn probe(&self, url: &str) -> Result<(), AdapterError> {
+        post_json_rpc(
+            &self.client,
+            self.chain,
+            url.to_string(),
+            self.method,
+            &json!([]),
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+/// 周期探测端点池中的端点，记录延迟并恢复冷却期已过的熔断端点
+#[derive(Clone)]
+pub struct HealthChecker {
+    endpoints: Arc<EndpointPool>,
+    probe: Arc<dyn EndpointProbe>,
+}
+
+impl HealthChecker {
+    pub fn new(endpoints: Arc<EndpointPool>, probe: Arc<dyn EndpointProbe>) -> Self {
+        Self { endpoints, probe }
+    }
+
+    pub fn endpoints(&self) -> &Arc<EndpointPool> {
+        &self.endpoints
+    }
+
+    /// 探测一轮
+    pub async fn check(&self) {
+        let pool = &self.endpoints;
+        for (index, endpoint) in pool.endpoints.iter().enumerate() {
+            if !pool.probe_due(index) {
+                continue;
+            }
+            let started = Instant::now();
+            match self.probe.probe(&endpoint.config.url).await {
+                Ok(()) => pool.record_success(index, started.elapsed()),
+                Err(e) => pool.record_failure(index, &e),
+            }
+        }
+    }
+
+    /// 后台周期探测；`shutdown` 置位或其发送端释放时退出
+    pub fn spawn(self, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.endpoints.config.probe_interval());
+            while !*shutdown.borrow() {
+                tokio::select! {
+                    _ = shutdown.changed() => return,
+                    _ = interval.tick() => self.check().await,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// JSON-RPC 模拟端点：`up` 为 false 时返回 503，`hits` 统计收到的请求
+    async fn serve(up: Arc<AtomicBool>, hits: Arc<AtomicUsize>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let up = up.clone();
+                let hits = hits.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    loop {
+                        let n = socket.read(&mut buf).await.unwrap_or(0);
+                        if n == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&buf[..n]);
+                        let text = String::from_utf8_lossy(&request).to_lowercase();
+                        if let Some(end) = text.find("\r\n\r\n") {
+                            let length = text
+                                .lines()
+                                .find_map(|line| line.strip_prefix("content-length:"))
+                                .and_then(|value| value.trim().parse::<usize>().ok())
+                                .unwrap_or(0);
+                            if request.len() >= end + 4 + length {
+                                break;
+                            }
+                        }
+                    }
+                    hits.fetch_add(1, Ordering::SeqCst);
+                    let response = if up.load(Ordering::SeqCst) {
+                        let body = json!({"jsonrpc": "2.0", "id": 1, "result": "ok"}).to_string();
+                        format!(
+                            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                            body.len(),
+                            body
+                        )
+                    } else {
+                        "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                            .to_string()
+                    };
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        url
+    }
+
+    struct Node {
+        url: String,
+        up: Arc<AtomicBool>,
+        hits: Arc<AtomicUsize>,
+    }
+
+    impl Node {
+        async fn start() -> Self {
+            let up = Arc::new(AtomicBool::new(true));
+            let hits = Arc::new(AtomicUsize::new(0));
+            Self {
+                url: serve(up.clone(), hits.clone()).await,
+                up,
+                hits,
+            }
+        }
+
+        fn hits(&self) -> usize {
+            self.hits.load(Ordering::SeqCst)
+        }
+    }
+
+    fn pool(primary: &Node, secondary: &Node, cooldown_ms: u64) -> Arc<EndpointPool> {
+        Arc::new(EndpointPool::new(
+            ChainType::Sui,
+            &primary.url,
+            &[RpcEndpoint {
+                url: secondary.url.clone(),
+                weight: 1,
+            }],
+            HealthCheckConfig {
+                failure_threshold: 2,
+                cooldown_ms,
+                ..HealthCheckConfig::default()
+            },
+        ))
+    }
+
+    async fn request(pool: &EndpointPool, idempotent: bool) -> Result<Value, AdapterError> {
+        let client = Client::new();
+        pool.call(idempotent, |url| {
+            post_json_rpc(
+                &client,
+                ChainType::Sui,
+                url,
+                "sui_getChainIdentifier",
+                &json!([]),
+            )
+        })
+        .await
+    }
+
+    #[test]
+    fn test_weighted_rotation() {
+        let endpoints = EndpointPool::new(
+            ChainType::Solana,
+            "http://a",
+            &[
+                RpcEndpoint {
+                    url: "http://b".to_string(),
+                    weight: 2,
+                },
+                RpcEndpoint {
+                    url: "http://a".to_string(),
+                    weight: 1,
+                },
+            ],
+            HealthCheckConfig::default(),
+        );
+        assert_eq!(endpoints.urls(), vec!["http://b", "http://a"]);
+        let firsts: Vec<usize> = (0..6).map(|_| endpoints.candidates()[0]).collect();
+        assert_eq!(firsts, vec![0, 0, 1, 0, 0, 1]);
+        assert_eq!(endpoints.candidates().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failover_and_circuit_breaking() {
+        let primary = Node::start().await;
+        let secondary = Node::start().await;
+        let endpoints = pool(&primary, &secondary, 60_000);
+
+        // 两个端点轮流承接请求
+        for _ in 0..4 {
+            assert_eq!(request(&endpoints, true).await.unwrap(), "ok");
+        }
+        assert_eq!((primary.hits(), secondary.hits()), (2, 2));
+
+        // 主端点中途开始失败，读请求无感知地转到备用端点
+        primary.up.store(false, Ordering::SeqCst);
+        for _ in 0..6 {
+            assert_eq!(request(&endpoints, true).await.unwrap(), "ok");
+        }
+        let health = endpoints.health();
+        assert!(!health[0].healthy);
+        assert_eq!(health[0].consecutive_failures, 2);
+        assert!(health[0].last_error.as_deref().unwrap().contains("503"));
+        assert!(health[1].healthy);
+        // 熔断后不再向主端点发请求
+        assert_eq!(primary.hits(), 4);
+        assert_eq!(secondary.hits(), 8);
+
+        // 冷却期内不探测熔断端点
+        primary.up.store(true, Ordering::SeqCst);
+        let checker = HealthChecker::new(
+            endpoints.clone(),
+            Arc::new(JsonRpcProbe::new(
+                Client::new(),
+                ChainType::Sui,
+                "sui_getLatestCheckpointSequenceNumber",
+            )),
+        );
+        checker.check().await;
+        assert_eq!(primary.hits(), 4);
+        assert!(!endpoints.health()[0].healthy);
+    }
+
+    #[tokio::test]
+    async fn test_probe_reinstates_endpoint_after_cooldown() {
+        let primary = Node::start().await;
+        let secondary = Node::start().await;
+        let endpoints = pool(&primary, &secondary, 0);
+        let checker = HealthChecker::new(
+            endpoints.clone(),
+            Arc::new(JsonRpcProbe::new(
+                Client::new(),
+                ChainType::Sui,
+                "sui_getLatestCheckpointSequenceNumber",
+            )),
+        );
+
+        primary.up.store(false, Ordering::SeqCst);
+        checker.check().await;
+        checker.check().await;
+        assert!(!endpoints.health()[0].healthy);
+        // 探测仍失败时保持熔断
+        checker.check().await;
+        assert!(!endpoints.health()[0].healthy);
+
+        primary.up.store(true, Ordering::SeqCst);
+        checker.check().await;
+        let health = endpoints.health();
+        assert!(health[0].healthy);
+        assert_eq!(health[0].consecutive_failures, 0);
+        assert!(health[0].latency_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_writes_are_not_retried() {
+        let primary = Node::start().await;
+        let secondary = Node::start().await;
+        let endpoints = pool(&primary, &secondary, 60_000);
+        primary.up.store(false, Ordering::SeqCst);
+
+        assert!(request(&endpoints, false).await.unwrap_err().is_transport());
+        assert_eq!((primary.hits(), secondary.hits()), (1, 0));
+        // 下一次写请求轮到备用端点
+        assert_eq!(request(&endpoints, false).await.unwrap(), "ok");
+        assert_eq!(secondary.hits(), 1);
+    }
+}
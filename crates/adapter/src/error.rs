@@ -0,0 +1,74 @@
+//! Adapter 错误类型
+//!
+//! 适配器接口返回 [`AdapterResult`]。解析、编码等未分类的失败经 `From<anyhow::Error>`
+//! 归入 [`AdapterError::Internal`] 并保留原始错误链；链上已是 `AdapterError` 的错误原样取出
+
+use thiserror::Error;
+
+use crate::types::ChainType;
+
+#[derive(Error, Debug)]
+pub enum AdapterError {
+    #[error("No adapter found for chain type: {0:?}")]
+    AdapterNotFound(ChainType),
+
+    #[error("Contract {address} not found on {chain:?}")]
+    ContractNotFound { chain: ChainType, address: String },
+
+    #[error("Object {0} not found")]
+    ObjectNotFound(String),
+
+    #[error("{chain:?} RPC request timed out")]
+    Timeout {
+        chain: ChainType,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    #[error("{chain:?} RPC transport error")]
+    Transport {
+        chain: ChainType,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    #[error("{chain:?} RPC error: {message}")]
+    Rpc { chain: ChainType, message: String },
+
+    #[error("No {0:?} signer configured")]
+    SignerUnavailable(ChainType),
+
+    #[error("{0} is not supported by this adapter")]
+    Unsupported(String),
+
+    /// 未分类的内部错误
+    #[error(transparent)]
+    Internal(anyhow::Error),
+}
+
+pub type AdapterResult<T> = std::result::Result<T, AdapterError>;
+
+impl From<anyhow::Error> for AdapterError {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast::<AdapterError>() {
+            Ok(error) => error,
+            Err(error) => Self::Internal(error),
+        }
+    }
+}
+
+impl AdapterError {
+    /// 按 reqwest 错误区分超时与其他传输错误
+    pub fn transport(chain: ChainType, source: reqwest::Error) -> Self {
+        if source.is_timeout() {
+            Self::Timeout { chain, source }
+        } else {
+            Self::Transport { chain, source }
+        }
+    }
+
+    /// 端点不可用（超时、连接失败、限流或网关错误），换一个端点可能成功
+    pub fn is_transport(&self) -> bool {
+        matches!(self, Self::Timeout { .. } | Self::Transport { .. })
+    }
+}
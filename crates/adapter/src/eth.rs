@@ -2,24 +2,40 @@
 //!
 //! 基于 ethers-rs 实现的以太坊轻节点客户端
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 // Temporarily disable ethers imports until dependency is resolved
 // use ethers::{
 //     providers::{Provider, Http, Ws, Middleware},
 //     types::{Address, H256, U64, TransactionReceipt as EthTransactionReceipt},
 // };
-use tokio::sync::mpsc;
-use tracing::{error, info};
+use tokio::sync::{mpsc, RwLock};
+use tracing::{debug, error, info, warn};
 
-use crate::traits::ChainAdapter;
+use crate::error::{AdapterError, AdapterResult};
+use crate::traits::{receipts_one_by_one, ChainAdapter};
 use crate::types::*;
 
+/// JSON-RPC 请求超时
+const RPC_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// JSON-RPC 方法不存在的错误码
+const METHOD_NOT_FOUND: i64 = -32601;
+
 /// 以太坊适配器
 pub struct EthereumAdapter {
     // provider: Provider<Http>,
     // ws_provider: Option<Provider<Ws>>,
     config: EthereumConfig,
+    client: reqwest::Client,
+    abi_resolver: AbiResolver,
+    /// 节点是否支持 `eth_getBlockReceipts`，首次返回方法不存在后改为逐笔获取
+    block_receipts_supported: AtomicBool,
 }
 
 impl EthereumAdapter {
@@ -38,101 +54,664 @@ impl EthereumAdapter {
         Ok(Self {
             // provider,
             // ws_provider,
+            client: reqwest::Client::builder().timeout(RPC_TIMEOUT).build()?,
+            abi_resolver: AbiResolver::from_config(&config),
+            block_receipts_supported: AtomicBool::new(true),
             config,
         })
     }
+
+    async fn call_rpc(&self, method: &str, params: Value) -> Result<Value> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let response: Value = self
+            .client
+            .post(&self.config.rpc_url)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| AdapterError::transport(ChainType::Ethereum, e))?
+            .json()
+            .await
+            .map_err(|e| AdapterError::transport(ChainType::Ethereum, e))?;
+
+        if response["error"]["code"].as_i64() == Some(METHOD_NOT_FOUND) {
+            return Err(AdapterError::Unsupported(method.to_string()).into());
+        }
+        if !response["error"].is_null() {
+            return Err(AdapterError::Rpc {
+                chain: ChainType::Ethereum,
+                message: response["error"].to_string(),
+            }
+            .into());
+        }
+        Ok(response["result"].clone())
+    }
+
+    /// 通过 `eth_getCode` 获取合约的运行时字节码；外部账户返回空字节码
+    async fn fetch_code(&self, address: &str) -> Result<Vec<u8>> {
+        let result = self
+            .call_rpc("eth_getCode", json!([address, "latest"]))
+            .await?;
+        let code = result
+            .as_str()
+            .ok_or_else(|| anyhow!("eth_getCode returned no code for {}", address))?;
+        Ok(hex::decode(code.trim_start_matches("0x"))?)
+    }
 }
 
 #[async_trait]
 impl ChainAdapter for EthereumAdapter {
-    async fn get_contract_meta(&self, address: &str) -> Result<ContractMeta> {
-        // TODO: Implement when ethers dependency is available
+    async fn get_contract_meta(&self, address: &str) -> AdapterResult<ContractMeta> {
+        let bytecode = self.fetch_code(address).await?;
+        let resolved = self.abi_resolver.resolve(address, &bytecode).await;
         Ok(ContractMeta {
             address: address.to_string(),
             chain_type: ChainType::Ethereum,
             contract_type: ContractType::EVM,
-            bytecode: vec![0x60, 0x80, 0x60, 0x40], // Placeholder bytecode
-            abi: None,
+            bytecode,
+            abi: resolved.as_ref().map(|resolved| resolved.abi.clone()),
             source_code: None,
             compiler_version: None,
             created_at: chrono::Utc::now().timestamp() as u64,
             creator: None,
+            abi_source: resolved.map(|resolved| resolved.source),
+            modules: vec![],
         })
     }
 
-    async fn get_transaction_receipt(&self, _tx_hash: &str) -> Result<TransactionReceipt> {
-        // TODO: Implement when ethers dependency is available
-        Ok(TransactionReceipt {
-            tx_hash: "0x0".to_string(),
-            block_hash: "0x0".to_string(),
-            block_number: 0,
-            transaction_index: 0,
-            from: "0x0".to_string(),
-            to: None,
-            gas_used: 21000,
-            status: TransactionStatus::Success,
-            logs: vec![],
-            contract_address: None,
-        })
+    async fn get_transaction_receipt(&self, tx_hash: &str) -> AdapterResult<TransactionReceipt> {
+        let result = self
+            .call_rpc("eth_getTransactionReceipt", json!([tx_hash]))
+            .await?;
+        if result.is_null() {
+            return Err(anyhow!("Ethereum transaction receipt not found: {}", tx_hash).into());
+        }
+        Ok(parse_receipt(&result)?)
     }
 
-    async fn get_balance(&self, _address: &str) -> Result<u64> {
+    async fn get_balance(&self, _address: &str) -> AdapterResult<u64> {
         // TODO: Implement when ethers dependency is available
         Ok(0)
     }
 
-    async fn get_nonce(&self, _address: &str) -> Result<u64> {
+    async fn get_nonce(&self, _address: &str) -> AdapterResult<u64> {
         // TODO: Implement when ethers dependency is available
         Ok(0)
     }
 
-    async fn get_block_number(&self) -> Result<u64> {
-        // TODO: Implement when ethers dependency is available
-        Ok(1)
+    async fn get_block_number(&self) -> AdapterResult<u64> {
+        let result = self.call_rpc("eth_blockNumber", json!([])).await?;
+        quantity(&result)
+            .ok_or_else(|| anyhow!("Invalid eth_blockNumber result: {}", result).into())
     }
 
-    async fn subscribe_new_blocks(&self) -> Result<mpsc::Receiver<String>> {
+    async fn get_block(&self, block: BlockId) -> AdapterResult<BlockData> {
+        let result = match &block {
+            BlockId::Number(number) => {
+                self.call_rpc(
+                    "eth_getBlockByNumber",
+                    json!([format!("0x{:x}", number), false]),
+                )
+                .await?
+            }
+            BlockId::Hash(hash) => {
+                self.call_rpc("eth_getBlockByHash", json!([hash, false]))
+                    .await?
+            }
+        };
+        if result.is_null() {
+            return Err(anyhow!("Ethereum block not found: {:?}", block).into());
+        }
+        Ok(parse_block(&result)?)
+    }
+
+    /// 优先使用 `eth_getBlockReceipts` 一次取回整个区块，节点不支持时逐笔获取
+    async fn get_block_receipts(&self, number: u64) -> AdapterResult<BlockReceipts> {
+        if self.block_receipts_supported.load(Ordering::Relaxed) {
+            match self
+                .call_rpc("eth_getBlockReceipts", json!([format!("0x{:x}", number)]))
+                .await
+            {
+                Ok(result) if result.is_null() => {
+                    return Err(anyhow!("Ethereum block not found: {}", number).into());
+                }
+                Ok(result) => {
+                    let receipts = result
+                        .as_array()
+                        .ok_or_else(|| anyhow!("Invalid eth_getBlockReceipts result: {}", result))?
+                        .iter()
+                        .map(parse_receipt)
+                        .collect::<Result<Vec<_>>>()?;
+                    return Ok(BlockReceipts {
+                        block_number: number,
+                        receipts,
+                        missing: vec![],
+                    });
+                }
+                Err(e) if matches!(e.downcast_ref(), Some(AdapterError::Unsupported(_))) => {
+                    warn!("⚠️ eth_getBlockReceipts not supported, fetching receipts one by one");
+                    self.block_receipts_supported
+                        .store(false, Ordering::Relaxed);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let block = self.get_block(BlockId::Number(number)).await?;
+        receipts_one_by_one(self, &block).await
+    }
+
+    async fn subscribe_new_blocks(&self) -> AdapterResult<mpsc::Receiver<String>> {
         // TODO: Implement when ethers dependency is available
         let (_tx, rx) = mpsc::channel(1000);
         Ok(rx)
     }
 
-    async fn subscribe_new_transactions(&self) -> Result<mpsc::Receiver<String>> {
+    async fn subscribe_new_transactions(&self) -> AdapterResult<mpsc::Receiver<String>> {
         // TODO: Implement when ethers dependency is available
         let (_tx, rx) = mpsc::channel(1000);
         Ok(rx)
     }
 }
 
-impl EthereumAdapter {
-    // TODO: Re-enable when ethers dependency is available
-    // fn convert_receipt(&self, receipt: EthTransactionReceipt) -> TransactionReceipt {
-    //     TransactionReceipt {
-    //         tx_hash: format!("{:?}", receipt.transaction_hash),
-    //         block_hash: receipt
-    //             .block_hash
-    //             .map(|h| format!("{:?}", h))
-    //             .unwrap_or_default(),
-    //         block_number: receipt.block_number.map(|n| n.as_u64()).unwrap_or_default(),
-    //         transaction_index: receipt.transaction_index.as_u32(),
-    //         from: format!("{:?}", receipt.from),
-    //         to: receipt.to.map(|addr| format!("{:?}", addr)),
-    //         gas_used: receipt.gas_used.map(|g| g.as_u64()).unwrap_or_default(),
-    //         status: if receipt.status == Some(U64::from(1)) {
-    //             TransactionStatus::Success
-    //         } else {
-    //             TransactionStatus::Failed
-    //         },
-    //         logs: receipt
-    //             .logs
-    //             .into_iter()
-    //             .map(|log| EventLog {
-    //                 address: format!("{:?}", log.address),
-    //                 topics: log.topics.into_iter().map(|t| format!("{:?}", t)).collect(),
-    //                 data: format!("{:?}", log.data),
-    //             })
-    //             .collect(),
-    //         contract_address: receipt.contract_address.map(|addr| format!("{:?}", addr)),
-    //     }
-    // }
+/// 十六进制数量（`0x1b4`）
+fn quantity(value: &Value) -> Option<u64> {
+    value
+        .as_str()
+        .and_then(|hex| u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok())
+}
+
+/// 解析 `eth_getBlockByNumber` / `eth_getBlockByHash`（不含交易详情）的结果
+pub fn parse_block(result: &Value) -> Result<BlockData> {
+    let field = |name: &str| {
+        quantity(&result[name]).ok_or_else(|| anyhow!("Invalid block {}: {}", name, result[name]))
+    };
+    Ok(BlockData {
+        number: field("number")?,
+        hash: result["hash"].as_str().unwrap_or_default().to_string(),
+        parent_hash: result["parentHash"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        timestamp: field("timestamp")?,
+        transactions: result["transactions"]
+            .as_array()
+            .map(|txs| {
+                txs.iter()
+                    .filter_map(|tx| tx.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    })
+}
+
+/// 解析 `eth_getTransactionReceipt` 或 `eth_getBlockReceipts` 中的单个回执
+pub fn parse_receipt(result: &Value) -> Result<TransactionReceipt> {
+    let tx_hash = result["transactionHash"]
+        .as_str()
+        .ok_or_else(|| anyhow!("Receipt without transactionHash: {}", result))?;
+    let string = |name: &str| result[name].as_str().map(|s| s.to_string());
+
+    Ok(TransactionReceipt {
+        tx_hash: tx_hash.to_string(),
+        block_hash: string("blockHash").unwrap_or_default(),
+        block_number: quantity(&result["blockNumber"]).unwrap_or_default(),
+        transaction_index: quantity(&result["transactionIndex"]).unwrap_or_default() as u32,
+        from: string("from").unwrap_or_default(),
+        to: string("to"),
+        gas_used: quantity(&result["gasUsed"]).unwrap_or_default(),
+        // 拜占庭分叉前的回执没有 status 字段
+        status: match quantity(&result["status"]) {
+            Some(0) => TransactionStatus::Failed,
+            _ => TransactionStatus::Success,
+        },
+        logs: result["logs"]
+            .as_array()
+            .map(|logs| {
+                logs.iter()
+                    .map(|log| EventLog {
+                        address: log["address"].as_str().unwrap_or_default().to_string(),
+                        topics: log["topics"]
+                            .as_array()
+                            .map(|topics| {
+                                topics
+                                    .iter()
+                                    .filter_map(|t| t.as_str().map(|s| s.to_string()))
+                                    .collect()
+                            })
+                            .unwrap_or_default(),
+                        data: log["data"].as_str().unwrap_or_default().to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        contract_address: string("contractAddress"),
+    })
+}
+
+/// 解析得到的 ABI
+#[derive(Debug, Clone)]
+pub struct ResolvedAbi {
+    pub abi: String,
+    pub source: AbiSource,
+}
+
+/// Etherscan 兼容接口的响应
+#[derive(Debug, Deserialize)]
+struct ExplorerResponse {
+    status: String,
+    message: String,
+    result: String,
+}
+
+/// 合约 ABI 解析器
+///
+/// 优先查询 Etherscan 兼容接口的已验证源码 ABI（结果按地址缓存），
+/// 失败时扫描字节码中的函数分发表推断出仅含选择器的部分 ABI
+pub struct AbiResolver {
+    client: reqwest::Client,
+    api_url: Option<String>,
+    api_key: Option<String>,
+    cache: RwLock<HashMap<String, String>>,
+}
+
+impl AbiResolver {
+    pub fn new(api_url: Option<String>, api_key: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_url,
+            api_key,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_config(config: &EthereumConfig) -> Self {
+        Self::new(
+            config.explorer_api_url.clone(),
+            config.explorer_api_key.clone(),
+        )
+    }
+
+    /// 解析合约 ABI，两种途径都失败时返回 None
+    pub async fn resolve(&self, address: &str, bytecode: &[u8]) -> Option<ResolvedAbi> {
+        match self.verified_abi(address).await {
+            Ok(abi) => {
+                return Some(ResolvedAbi {
+                    abi,
+                    source: AbiSource::Verified,
+                })
+            }
+            Err(e) => debug!("No verified ABI for {}: {}", address, e),
+        }
+
+        let abi = heuristic_abi(bytecode)?;
+        info!("Derived heuristic ABI for {} from bytecode", address);
+        Some(ResolvedAbi {
+            abi,
+            source: AbiSource::Heuristic,
+        })
+    }
+
+    async fn verified_abi(&self, address: &str) -> Result<String> {
+        let key = address.to_lowercase();
+        if let Some(abi) = self.cache.read().await.get(&key) {
+            return Ok(abi.clone());
+        }
+
+        let api_url = self
+            .api_url
+            .as_deref()
+            .ok_or_else(|| anyhow!("No verified-source endpoint configured"))?;
+
+        let mut query = vec![
+            ("module", "contract"),
+            ("action", "getabi"),
+            ("address", address),
+        ];
+        if let Some(api_key) = &self.api_key {
+            query.push(("apikey", api_key.as_str()));
+        }
+
+        let body = self
+            .client
+            .get(api_url)
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        let abi = parse_explorer_response(&body).map_err(|e| {
+            warn!("Verified ABI lookup for {} failed: {}", address, e);
+            e
+        })?;
+        self.cache.write().await.insert(key, abi.clone());
+        Ok(abi)
+    }
+}
+
+/// 解析 `module=contract&action=getabi` 的响应，返回 JSON ABI
+pub fn parse_explorer_response(body: &str) -> Result<String> {
+    let response: ExplorerResponse = serde_json::from_str(body)?;
+    if response.status != "1" {
+        return Err(anyhow!("{}: {}", response.message, response.result));
+    }
+
+    // result 本身是 ABI 的 JSON 字符串
+    let abi: serde_json::Value = serde_json::from_str(&response.result)?;
+    if !abi.is_array() {
+        return Err(anyhow!("ABI is not a JSON array"));
+    }
+    Ok(response.result)
+}
+
+/// 从字节码推断部分 ABI：每个选择器对应一个名称未知的函数
+pub fn heuristic_abi(bytecode: &[u8]) -> Option<String> {
+    let selectors = extract_selectors(bytecode);
+    if selectors.is_empty() {
+        return None;
+    }
+
+    let entries: Vec<serde_json::Value> = selectors
+        .iter()
+        .map(|selector| {
+            let selector = format!("0x{}", hex::encode(selector));
+            serde_json::json!({
+                "type": "function",
+                "name": selector,
+                "selector": selector,
+                "inputs": [],
+                "outputs": [],
+                "stateMutability": "nonpayable",
+            })
+        })
+        .collect();
+    Some(serde_json::Value::Array(entries).to_string())
+}
+
+const OP_DUP2: u8 = 0x81;
+const OP_EQ: u8 = 0x14;
+const OP_JUMPI: u8 = 0x57;
+const OP_PUSH1: u8 = 0x60;
+const OP_PUSH2: u8 = 0x61;
+const OP_PUSH4: u8 = 0x63;
+const OP_PUSH32: u8 = 0x7f;
+
+/// 扫描 Solidity/Vyper 风格的分发表：`PUSH4 selector [DUP2] EQ PUSH1/PUSH2 dest JUMPI`
+///
+/// 按操作码遍历，跳过 PUSH 的立即数，避免把数据误认为指令
+pub fn extract_selectors(bytecode: &[u8]) -> Vec<[u8; 4]> {
+    let mut selectors = Vec::new();
+    let mut pc = 0;
+
+    while pc < bytecode.len() {
+        let op = bytecode[pc];
+        if op == OP_PUSH4 {
+            if let Some(selector) = match_dispatch(&bytecode[pc..]) {
+                if !selectors.contains(&selector) {
+                    selectors.push(selector);
+                }
+            }
+        }
+
+        pc += 1;
+        if (OP_PUSH1..=OP_PUSH32).contains(&op) {
+            pc += (op - OP_PUSH1 + 1) as usize;
+        }
+    }
+
+    selectors
+}
+
+/// 从 PUSH4 开始匹配一条分发项
+fn match_dispatch(code: &[u8]) -> Option<[u8; 4]> {
+    let selector: [u8; 4] = code.get(1..5)?.try_into().ok()?;
+    let mut rest = code.get(5..)?;
+
+    if rest.first() == Some(&OP_DUP2) {
+        rest = &rest[1..];
+    }
+    if rest.first() != Some(&OP_EQ) {
+        return None;
+    }
+
+    let dest_len = match rest.get(1)? {
+        &OP_PUSH1 => 1,
+        &OP_PUSH2 => 2,
+        _ => return None,
+    };
+    (rest.get(2 + dest_len) == Some(&OP_JUMPI)).then_some(selector)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const VERIFIED_RESPONSE: &str = r#"{"status":"1","message":"OK","result":"[{\"type\":\"function\",\"name\":\"transfer\",\"inputs\":[{\"name\":\"to\",\"type\":\"address\"},{\"name\":\"value\",\"type\":\"uint256\"}],\"outputs\":[{\"name\":\"\",\"type\":\"bool\"}],\"stateMutability\":\"nonpayable\"}]"}"#;
+
+    const UNVERIFIED_RESPONSE: &str =
+        r#"{"status":"0","message":"NOTOK","result":"Contract source code not verified"}"#;
+
+    /// solc 生成的分发表片段：transfer(address,uint256) 与 balanceOf(address)
+    fn dispatcher_bytecode() -> Vec<u8> {
+        let mut code = vec![
+            0x60, 0x80, 0x60, 0x40, 0x52, // 内存初始化
+            0x60, 0x04, 0x36, 0x10, 0x61, 0x00, 0x41, 0x57, // calldata 长度检查
+            0x60, 0x00, 0x35, 0x60, 0xe0, 0x1c, // 取选择器
+            0x80, 0x63, 0xa9, 0x05, 0x9c, 0xbb, 0x14, 0x61, 0x00, 0x46, 0x57, // transfer
+            0x63, 0x70, 0xa0, 0x82, 0x31, 0x81, 0x14, 0x60, 0x5a, 0x57, // balanceOf（DUP2 形式）
+        ];
+        // PUSH32 立即数中出现的同样模式不应被识别
+        code.push(OP_PUSH32);
+        code.extend([0x63, 0xde, 0xad, 0xbe, 0xef, 0x14, 0x60, 0x00, 0x57]);
+        code.extend([0u8; 23]);
+        code.extend([0x5b, 0x60, 0x00, 0x80, 0xfd]);
+        code
+    }
+
+    const BLOCK: &str = r#"{
+        "number": "0x12d687",
+        "hash": "0x5c2e2b7f1a9d0f6b6f1d0f0c9a3a5d8c0a1b2c3d4e5f60718293a4b5c6d7e8f9",
+        "parentHash": "0x1f0e2d3c4b5a69788796a5b4c3d2e1f00112233445566778899aabbccddeeff0",
+        "timestamp": "0x6553f100",
+        "transactions": [
+            "0xaaa0000000000000000000000000000000000000000000000000000000000001",
+            "0xaaa0000000000000000000000000000000000000000000000000000000000002"
+        ]
+    }"#;
+
+    /// `eth_getBlockReceipts` 的录制结果：一笔带日志的调用与一笔失败的合约创建
+    const BLOCK_RECEIPTS: &str = r#"[
+        {
+            "transactionHash": "0xaaa0000000000000000000000000000000000000000000000000000000000001",
+            "blockHash": "0x5c2e2b7f1a9d0f6b6f1d0f0c9a3a5d8c0a1b2c3d4e5f60718293a4b5c6d7e8f9",
+            "blockNumber": "0x12d687",
+            "transactionIndex": "0x0",
+            "from": "0x1111111111111111111111111111111111111111",
+            "to": "0x2222222222222222222222222222222222222222",
+            "gasUsed": "0xb411",
+            "status": "0x1",
+            "contractAddress": null,
+            "logs": [
+                {
+                    "address": "0x2222222222222222222222222222222222222222",
+                    "topics": [
+                        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+                        "0x0000000000000000000000001111111111111111111111111111111111111111"
+                    ],
+                    "data": "0x00000000000000000000000000000000000000000000000000000000000003e8"
+                }
+            ]
+        },
+        {
+            "transactionHash": "0xaaa0000000000000000000000000000000000000000000000000000000000002",
+            "blockHash": "0x5c2e2b7f1a9d0f6b6f1d0f0c9a3a5d8c0a1b2c3d4e5f60718293a4b5c6d7e8f9",
+            "blockNumber": "0x12d687",
+            "transactionIndex": "0x1",
+            "from": "0x3333333333333333333333333333333333333333",
+            "to": null,
+            "gasUsed": "0x30d40",
+            "status": "0x0",
+            "contractAddress": "0x4444444444444444444444444444444444444444",
+            "logs": []
+        }
+    ]"#;
+
+    #[test]
+    fn test_parse_block() {
+        let block = parse_block(&serde_json::from_str(BLOCK).unwrap()).unwrap();
+        assert_eq!(block.number, 1_234_567);
+        assert_eq!(block.timestamp, 1_700_000_000);
+        assert!(block.parent_hash.starts_with("0x1f0e"));
+        assert_eq!(block.transactions.len(), 2);
+
+        assert!(parse_block(&json!({ "hash": "0x1" })).is_err());
+    }
+
+    #[test]
+    fn test_parse_block_receipts() {
+        let result: Value = serde_json::from_str(BLOCK_RECEIPTS).unwrap();
+        let receipts: Vec<TransactionReceipt> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|receipt| parse_receipt(receipt).unwrap())
+            .collect();
+
+        let call = &receipts[0];
+        assert_eq!(call.block_number, 1_234_567);
+        assert_eq!(call.gas_used, 46_097);
+        assert!(matches!(call.status, TransactionStatus::Success));
+        assert_eq!(call.logs.len(), 1);
+        assert_eq!(call.logs[0].topics.len(), 2);
+
+        let create = &receipts[1];
+        assert_eq!(create.transaction_index, 1);
+        assert!(create.to.is_none());
+        assert!(matches!(create.status, TransactionStatus::Failed));
+        assert_eq!(
+            create.contract_address.as_deref(),
+            Some("0x4444444444444444444444444444444444444444")
+        );
+
+        assert!(parse_receipt(&json!({ "blockNumber": "0x1" })).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_block_receipts_method_not_found() {
+        let url = serve_once(
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"the method eth_getBlockReceipts does not exist/is not available"}}"#,
+        )
+        .await;
+        let adapter = EthereumAdapter::new(EthereumConfig {
+            enabled: true,
+            rpc_url: url,
+            ws_url: None,
+            chain_id: 1,
+            explorer_api_url: None,
+            explorer_api_key: None,
+        })
+        .await
+        .unwrap();
+
+        // 回退路径需要的 eth_getBlockByNumber 已无服务应答，这里只检查回退开关
+        assert!(adapter.get_block_receipts(1).await.is_err());
+        assert!(!adapter.block_receipts_supported.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_parse_explorer_response() {
+        let abi = parse_explorer_response(VERIFIED_RESPONSE).unwrap();
+        let abi: serde_json::Value = serde_json::from_str(&abi).unwrap();
+        assert_eq!(abi[0]["name"], "transfer");
+
+        let error = parse_explorer_response(UNVERIFIED_RESPONSE).unwrap_err();
+        assert!(error.to_string().contains("not verified"));
+    }
+
+    #[test]
+    fn test_extract_selectors() {
+        assert_eq!(
+            extract_selectors(&dispatcher_bytecode()),
+            vec![[0xa9, 0x05, 0x9c, 0xbb], [0x70, 0xa0, 0x82, 0x31]]
+        );
+        assert!(extract_selectors(&[0x60, 0x80, 0x60, 0x40]).is_empty());
+
+        let abi: serde_json::Value =
+            serde_json::from_str(&heuristic_abi(&dispatcher_bytecode()).unwrap()).unwrap();
+        assert_eq!(abi[0]["selector"], "0xa9059cbb");
+    }
+
+    /// 只应答一次的 HTTP 服务，返回录制的响应
+    async fn serve_once(body: &'static str) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 4096];
+            let _ = socket.read(&mut request).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_resolver_prefers_verified_and_caches() {
+        let url = serve_once(VERIFIED_RESPONSE).await;
+        let resolver = AbiResolver::new(Some(url), Some("key".to_string()));
+
+        let resolved = resolver.resolve("0xABC", &dispatcher_bytecode()).await.unwrap();
+        assert_eq!(resolved.source, AbiSource::Verified);
+
+        // 服务已关闭，第二次命中缓存
+        let cached = resolver.resolve("0xabc", &[]).await.unwrap();
+        assert_eq!(cached.source, AbiSource::Verified);
+        assert_eq!(cached.abi, resolved.abi);
+    }
+
+    #[tokio::test]
+    async fn test_resolver_falls_back_to_heuristic() {
+        let url = serve_once(UNVERIFIED_RESPONSE).await;
+        let resolver = AbiResolver::new(Some(url), None);
+
+        let resolved = resolver.resolve("0xabc", &dispatcher_bytecode()).await.unwrap();
+        assert_eq!(resolved.source, AbiSource::Heuristic);
+
+        let unconfigured = AbiResolver::new(None, None);
+        assert!(unconfigured.resolve("0xabc", &[0x00]).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_contract_meta_uses_deployed_code() {
+        // PUSH4 transfer EQ PUSH1 0 JUMPI
+        let url = serve_once(r#"{"jsonrpc":"2.0","id":1,"result":"0x63a9059cbb14600057"}"#).await;
+        let adapter = EthereumAdapter::new(EthereumConfig {
+            enabled: true,
+            rpc_url: url,
+            ws_url: None,
+            chain_id: 1,
+            explorer_api_url: None,
+            explorer_api_key: None,
+        })
+        .await
+        .unwrap();
+
+        let meta = adapter.get_contract_meta("0xabc").await.unwrap();
+        assert_eq!(meta.bytecode, hex::decode("63a9059cbb14600057").unwrap());
+        assert_eq!(meta.abi_source, Some(AbiSource::Heuristic));
+        let abi: serde_json::Value = serde_json::from_str(meta.abi.as_deref().unwrap()).unwrap();
+        assert_eq!(abi[0]["selector"], "0xa9059cbb");
+    }
 }
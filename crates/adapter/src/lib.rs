@@ -5,13 +5,28 @@
 
 pub mod aptos;
 pub mod btc;
+pub mod cursor;
+pub mod endpoint;
+pub mod error;
 pub mod eth;
+pub mod lifecycle;
+pub mod signer;
 pub mod solana;
+pub mod subscription;
 pub mod sui;
+pub mod sui_tx;
 pub mod sui_types;
 pub mod traits;
 pub mod types;
 
+pub use cursor::{CursorStore, MemoryCursorStore};
+pub use endpoint::{
+    EndpointHealth, EndpointPool, EndpointProbe, HealthCheckConfig, HealthChecker, RpcEndpoint,
+};
+pub use error::{AdapterError, AdapterResult};
+pub use lifecycle::{AdapterFactory, AdapterFuture, AdapterState, ChainStatus};
+pub use signer::{Ed25519Signer, KeystoreSigner, Signer};
+pub use subscription::SubscriptionBackoff;
 pub use traits::*;
 pub use types::*;
 
@@ -20,21 +35,48 @@ pub use types::SuiNetworkType;
 
 use anyhow::Result;
 use std::collections::HashMap;
-use tokio::sync::RwLock;
-use tracing::info;
+use std::sync::Arc;
+use tokio::sync::{broadcast, watch, Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// 事件总线容量，落后超过该数量的订阅者会丢失最旧的事件
+const EVENT_BUS_CAPACITY: usize = 1024;
 
 /// 多链适配器管理器
 pub struct AdapterManager {
-    adapters: RwLock<HashMap<ChainType, Box<dyn ChainAdapter + Send + Sync>>>,
+    adapters: RwLock<HashMap<ChainType, Arc<dyn ChainAdapter + Send + Sync>>>,
+    events: broadcast::Sender<ChainEvent>,
+    backoff: SubscriptionBackoff,
+    shutdown: watch::Sender<bool>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+    /// 各链的启用与初始化状态
+    statuses: RwLock<HashMap<ChainType, ChainStatus>>,
+    /// 降级适配器的后台重试任务
+    retries: Mutex<Vec<JoinHandle<()>>>,
 }
 
 impl AdapterManager {
     pub fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        let (shutdown, _) = watch::channel(false);
         Self {
             adapters: RwLock::new(HashMap::new()),
+            events,
+            backoff: SubscriptionBackoff::default(),
+            shutdown,
+            tasks: Mutex::new(Vec::new()),
+            statuses: RwLock::new(HashMap::new()),
+            retries: Mutex::new(Vec::new()),
         }
     }
 
+    /// 设置订阅重连的退避参数
+    pub fn with_backoff(mut self, backoff: SubscriptionBackoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
     /// 注册链适配器
     pub async fn register_adapter(
         &self,
@@ -42,7 +84,22 @@ impl AdapterManager {
         adapter: Box<dyn ChainAdapter + Send + Sync>,
     ) {
         info!("Registering adapter for {:?}", chain_type);
-        self.adapters.write().await.insert(chain_type, adapter);
+        self.adapters
+            .write()
+            .await
+            .insert(chain_type, Arc::from(adapter));
+        self.set_status(chain_type, AdapterState::Active, None)
+            .await;
+    }
+
+    /// 已注册的链
+    pub async fn chain_types(&self) -> Vec<ChainType> {
+        self.adapters.read().await.keys().copied().collect()
+    }
+
+    /// 订阅所有适配器的链事件（需先调用 [`Self::start_background_tasks`]）
+    pub fn subscribe_events(&self) -> broadcast::Receiver<ChainEvent> {
+        self.events.subscribe()
     }
 
     /// 获取合约元数据
@@ -50,14 +107,11 @@ impl AdapterManager {
         &self,
         chain_type: ChainType,
         address: &str,
-    ) -> Result<ContractMeta> {
+    ) -> AdapterResult<ContractMeta> {
         let adapters = self.adapters.read().await;
         match adapters.get(&chain_type) {
             Some(adapter) => adapter.get_contract_meta(address).await,
-            None => Err(anyhow::anyhow!(
-                "No adapter found for chain type: {:?}",
-                chain_type
-            )),
+            None => Err(AdapterError::AdapterNotFound(chain_type)),
         }
     }
 
@@ -66,24 +120,157 @@ impl AdapterManager {
         &self,
         chain_type: ChainType,
         tx_hash: &str,
-    ) -> Result<TransactionReceipt> {
+    ) -> AdapterResult<TransactionReceipt> {
         let adapters = self.adapters.read().await;
         match adapters.get(&chain_type) {
             Some(adapter) => adapter.get_transaction_receipt(tx_hash).await,
-            None => Err(anyhow::anyhow!(
-                "No adapter found for chain type: {:?}",
-                chain_type
-            )),
+            None => Err(AdapterError::AdapterNotFound(chain_type)),
+        }
+    }
+
+    /// 获取交易引用的合约包
+    pub async fn get_transaction_packages(
+        &self,
+        chain_type: ChainType,
+        tx_hash: &str,
+    ) -> AdapterResult<Vec<String>> {
+        let adapters = self.adapters.read().await;
+        match adapters.get(&chain_type) {
+            Some(adapter) => adapter.get_transaction_packages(tx_hash).await,
+            None => Err(AdapterError::AdapterNotFound(chain_type)),
+        }
+    }
+
+    /// 获取区块头与交易列表
+    pub async fn get_block(
+        &self,
+        chain_type: ChainType,
+        block: BlockId,
+    ) -> AdapterResult<BlockData> {
+        let adapters = self.adapters.read().await;
+        match adapters.get(&chain_type) {
+            Some(adapter) => adapter.get_block(block).await,
+            None => Err(AdapterError::AdapterNotFound(chain_type)),
+        }
+    }
+
+    /// 获取区块内全部交易的回执
+    pub async fn get_block_receipts(
+        &self,
+        chain_type: ChainType,
+        number: u64,
+    ) -> AdapterResult<BlockReceipts> {
+        let adapters = self.adapters.read().await;
+        match adapters.get(&chain_type) {
+            Some(adapter) => adapter.get_block_receipts(number).await,
+            None => Err(AdapterError::AdapterNotFound(chain_type)),
         }
     }
 
+    /// 获取账户余额
+    pub async fn get_balance(&self, chain_type: ChainType, address: &str) -> AdapterResult<u64> {
+        let adapters = self.adapters.read().await;
+        match adapters.get(&chain_type) {
+            Some(adapter) => adapter.get_balance(address).await,
+            None => Err(AdapterError::AdapterNotFound(chain_type)),
+        }
+    }
+
+    /// 获取账户 nonce
+    pub async fn get_nonce(&self, chain_type: ChainType, address: &str) -> AdapterResult<u64> {
+        let adapters = self.adapters.read().await;
+        match adapters.get(&chain_type) {
+            Some(adapter) => adapter.get_nonce(address).await,
+            None => Err(AdapterError::AdapterNotFound(chain_type)),
+        }
+    }
+
+    /// 获取当前区块高度
+    pub async fn get_block_number(&self, chain_type: ChainType) -> AdapterResult<u64> {
+        let adapters = self.adapters.read().await;
+        match adapters.get(&chain_type) {
+            Some(adapter) => adapter.get_block_number().await,
+            None => Err(AdapterError::AdapterNotFound(chain_type)),
+        }
+    }
+
+    /// 各链 RPC 端点的健康状态，未使用端点池的适配器不在其中
+    pub async fn get_adapter_health(&self) -> HashMap<ChainType, Vec<EndpointHealth>> {
+        self.adapters
+            .read()
+            .await
+            .iter()
+            .filter_map(|(chain_type, adapter)| {
+                let checker = adapter.health_checker()?;
+                Some((*chain_type, checker.endpoints().health()))
+            })
+            .collect()
+    }
+
     /// 启动所有适配器的后台任务
+    ///
+    /// 为每个已注册适配器的新区块、新交易订阅各启动一个受监管的任务，
+    /// 事件汇总到 [`Self::subscribe_events`]，并为使用端点池的适配器启动健康探测。
+    /// 之后调用 [`Self::register_adapter`] 注册的适配器不会被监听，降级后恢复的适配器由重试任务补上
     pub async fn start_background_tasks(&self) -> Result<()> {
+        let mut tasks = self.tasks.lock().await;
+        if !tasks.is_empty() {
+            warn!("⚠️ Adapter background tasks are already running");
+            return Ok(());
+        }
         info!("Starting adapter background tasks...");
+        self.shutdown.send_replace(false);
 
-        // TODO: 启动各个适配器的监听任务
-        // 比如监听新区块、新交易等
+        let mut probes = 0;
+        for (chain_type, adapter) in self.adapters.read().await.iter() {
+            probes += self.spawn_adapter_tasks(*chain_type, adapter, &mut tasks);
+        }
 
+        info!(
+            "🔗 Supervising {} adapter subscriptions and {} endpoint health checks",
+            tasks.len() - probes,
+            probes
+        );
         Ok(())
     }
+
+    /// 为一个适配器启动订阅监管与端点健康探测，返回启动的探测任务数
+    fn spawn_adapter_tasks(
+        &self,
+        chain_type: ChainType,
+        adapter: &Arc<dyn ChainAdapter + Send + Sync>,
+        tasks: &mut Vec<JoinHandle<()>>,
+    ) -> usize {
+        let mut probes = 0;
+        if let Some(checker) = adapter.health_checker() {
+            tasks.push(checker.spawn(self.shutdown.subscribe()));
+            probes += 1;
+        }
+        for kind in [ChainEventKind::NewBlock, ChainEventKind::NewTransaction] {
+            tasks.push(tokio::spawn(subscription::supervise(
+                adapter.clone(),
+                chain_type,
+                kind,
+                self.events.clone(),
+                self.backoff,
+                self.shutdown.subscribe(),
+            )));
+        }
+        probes
+    }
+
+    /// 停止所有后台任务（含降级适配器的重试）并等待其退出
+    pub async fn stop(&self) {
+        self.shutdown.send_replace(true);
+        for retry in std::mem::take(&mut *self.retries.lock().await) {
+            retry.abort();
+        }
+        let tasks = std::mem::take(&mut *self.tasks.lock().await);
+        for task in tasks {
+            if let Err(e) = task.await {
+                warn!("⚠️ Adapter background task failed: {}", e);
+            }
+        }
+        info!("Adapter background tasks stopped");
+    }
 }
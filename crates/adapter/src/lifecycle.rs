@@ -0,0 +1,337 @@
+//! 适配器初始化与降级
+//!
+//! 节点只初始化配置中启用的链。初始化包括构造适配器并查询一次区块高度确认端点可达；
+//! 失败时按 `strict_startup` 决定拒绝启动，或把该链标记为降级并按订阅退避参数在后台重试，
+//! 恢复后注册适配器并补上事件订阅
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::traits::ChainAdapter;
+use crate::types::ChainType;
+use crate::AdapterManager;
+
+/// 构造适配器的 future
+pub type AdapterFuture =
+    Pin<Box<dyn Future<Output = Result<Box<dyn ChainAdapter + Send + Sync>>> + Send>>;
+
+/// 适配器工厂，降级后的每次重试都重新调用
+pub type AdapterFactory = Arc<dyn Fn() -> AdapterFuture + Send + Sync>;
+
+/// 链的适配器状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AdapterState {
+    /// 已注册，正常服务
+    Active,
+    /// 初始化失败，后台重试中
+    Degraded,
+    /// 未配置或配置中未启用
+    Disabled,
+}
+
+/// 一条链的状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainStatus {
+    pub chain_type: ChainType,
+    pub state: AdapterState,
+    /// 最近一次初始化失败的原因
+    pub error: Option<String>,
+    /// 已尝试初始化的次数
+    pub attempts: u32,
+}
+
+/// 构造适配器并确认端点可达
+async fn connect(
+    chain_type: ChainType,
+    factory: &AdapterFactory,
+) -> Result<Box<dyn ChainAdapter + Send + Sync>> {
+    let adapter = factory()
+        .await
+        .with_context(|| format!("Failed to construct {:?} adapter", chain_type))?;
+    adapter
+        .get_block_number()
+        .await
+        .with_context(|| format!("{:?} endpoint is unreachable", chain_type))?;
+    Ok(adapter)
+}
+
+impl AdapterManager {
+    /// 初始化一条已启用的链
+    ///
+    /// `strict` 为 true 时初始化失败直接返回错误；否则该链标记为降级、在后台重试，并返回 `Ok`
+    pub async fn initialize_adapter(
+        self: &Arc<Self>,
+        chain_type: ChainType,
+        factory: AdapterFactory,
+        strict: bool,
+    ) -> Result<()> {
+        self.record_attempt(chain_type).await;
+        let error = match connect(chain_type, &factory).await {
+            Ok(adapter) => {
+                self.register_adapter(chain_type, adapter).await;
+                return Ok(());
+            }
+            Err(e) if strict => return Err(e),
+            Err(e) => e,
+        };
+
+        warn!(
+            "⚠️ {:?} adapter degraded: {:#}, retrying in background",
+            chain_type, error
+        );
+        self.set_status(
+            chain_type,
+            AdapterState::Degraded,
+            Some(format!("{:#}", error)),
+        )
+        .await;
+        let retry = tokio::spawn(self.clone().retry_adapter(
+            chain_type,
+            factory,
+            self.shutdown.subscribe(),
+        ));
+        self.retries.lock().await.push(retry);
+        Ok(())
+    }
+
+    /// 记录未启用的链
+    pub async fn disable_chain(&self, chain_type: ChainType) {
+        self.set_status(chain_type, AdapterState::Disabled, None)
+            .await;
+    }
+
+    /// 各链状态，按链名排序
+    pub async fn chain_statuses(&self) -> Vec<ChainStatus> {
+        let mut statuses: Vec<ChainStatus> = self.statuses.read().await.values().cloned().collect();
+        statuses.sort_by_key(|status| format!("{:?}", status.chain_type));
+        statuses
+    }
+
+    /// 按退避间隔重试初始化，成功后注册适配器；后台任务已在运行时为其启动订阅监管
+    async fn retry_adapter(
+        self: Arc<Self>,
+        chain_type: ChainType,
+        factory: AdapterFactory,
+        mut shutdown: watch::Receiver<bool>,
+    ) {
+        let mut delay = self.backoff.initial();
+        loop {
+            tokio::select! {
+                changed = shutdown.changed() => {
+                    // start_background_tasks 会重置停止信号，只在真正停止时退出
+                    if changed.is_err() || *shutdown.borrow() {
+                        return;
+                    }
+                    continue;
+                }
+                _ = tokio::time::sleep(delay) => {}
+            }
+
+            let attempts = self.record_attempt(chain_type).await;
+            match connect(chain_type, &factory).await {
+                Ok(adapter) => {
+                    // 持有任务列表的锁注册，避免与 start_background_tasks 重复监听
+                    let mut tasks = self.tasks.lock().await;
+                    self.register_adapter(chain_type, adapter).await;
+                    if !tasks.is_empty() {
+                        if let Some(adapter) = self.adapters.read().await.get(&chain_type) {
+                            self.spawn_adapter_tasks(chain_type, adapter, &mut tasks);
+                        }
+                    }
+                    info!(
+                        "✅ {:?} adapter recovered after {} attempts",
+                        chain_type, attempts
+                    );
+                    return;
+                }
+                Err(e) => {
+                    delay = self.backoff.next(delay);
+                    warn!(
+                        "⚠️ {:?} adapter still degraded (attempt {}): {:#}, retrying in {:?}",
+                        chain_type, attempts, e, delay
+                    );
+                    self.set_status(chain_type, AdapterState::Degraded, Some(format!("{:#}", e)))
+                        .await;
+                }
+            }
+        }
+    }
+
+    /// 初始化次数加一，返回累计次数
+    async fn record_attempt(&self, chain_type: ChainType) -> u32 {
+        let mut statuses = self.statuses.write().await;
+        let status = statuses.entry(chain_type).or_insert(ChainStatus {
+            chain_type,
+            state: AdapterState::Degraded,
+            error: None,
+            attempts: 0,
+        });
+        status.attempts += 1;
+        status.attempts
+    }
+
+    pub(crate) async fn set_status(
+        &self,
+        chain_type: ChainType,
+        state: AdapterState,
+        error: Option<String>,
+    ) {
+        let mut statuses = self.statuses.write().await;
+        let status = statuses.entry(chain_type).or_insert(ChainStatus {
+            chain_type,
+            state,
+            error: None,
+            attempts: 0,
+        });
+        status.state = state;
+        status.error = error;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{AdapterError, AdapterResult};
+    use crate::eth::EthereumAdapter;
+    use crate::subscription::SubscriptionBackoff;
+    use crate::types::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    /// 只报告固定区块高度的适配器
+    struct StubAdapter;
+
+    #[async_trait::async_trait]
+    impl ChainAdapter for StubAdapter {
+        async fn get_contract_meta(&self, _address: &str) -> AdapterResult<ContractMeta> {
+            Err(AdapterError::Unsupported("get_contract_meta".to_string()))
+        }
+
+        async fn get_transaction_receipt(
+            &self,
+            _tx_hash: &str,
+        ) -> AdapterResult<TransactionReceipt> {
+            Err(AdapterError::Unsupported(
+                "get_transaction_receipt".to_string(),
+            ))
+        }
+
+        async fn get_balance(&self, _address: &str) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn get_nonce(&self, _address: &str) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn get_block_number(&self) -> AdapterResult<u64> {
+            Ok(1)
+        }
+
+        async fn subscribe_new_blocks(&self) -> AdapterResult<mpsc::Receiver<String>> {
+            Err(AdapterError::Unsupported(
+                "subscribe_new_blocks".to_string(),
+            ))
+        }
+
+        async fn subscribe_new_transactions(&self) -> AdapterResult<mpsc::Receiver<String>> {
+            Err(AdapterError::Unsupported(
+                "subscribe_new_transactions".to_string(),
+            ))
+        }
+    }
+
+    /// 前 `failures` 次构造失败的工厂
+    fn flaky_factory(failures: u32) -> AdapterFactory {
+        let calls = Arc::new(AtomicU32::new(0));
+        Arc::new(move || {
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                if call < failures {
+                    anyhow::bail!("connection refused");
+                }
+                Ok(Box::new(StubAdapter) as Box<dyn ChainAdapter + Send + Sync>)
+            })
+        })
+    }
+
+    fn dead_ethereum() -> AdapterFactory {
+        Arc::new(|| {
+            Box::pin(async {
+                let adapter = EthereumAdapter::new(EthereumConfig {
+                    enabled: true,
+                    rpc_url: "http://127.0.0.1:1".to_string(),
+                    ws_url: None,
+                    chain_id: 1,
+                    explorer_api_url: None,
+                    explorer_api_key: None,
+                })
+                .await?;
+                Ok(Box::new(adapter) as Box<dyn ChainAdapter + Send + Sync>)
+            })
+        })
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_chain_is_degraded_unless_strict() {
+        let manager = Arc::new(AdapterManager::new());
+        manager
+            .initialize_adapter(ChainType::Ethereum, dead_ethereum(), false)
+            .await
+            .unwrap();
+        let statuses = manager.chain_statuses().await;
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].state, AdapterState::Degraded);
+        assert!(statuses[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("Ethereum endpoint is unreachable"));
+        assert!(manager.chain_types().await.is_empty());
+        manager.stop().await;
+
+        let strict = Arc::new(AdapterManager::new());
+        let error = strict
+            .initialize_adapter(ChainType::Ethereum, dead_ethereum(), true)
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", error).contains("Ethereum"));
+    }
+
+    #[tokio::test]
+    async fn test_degraded_chain_recovers_in_background() {
+        let manager = Arc::new(AdapterManager::new().with_backoff(SubscriptionBackoff {
+            initial_ms: 5,
+            max_ms: 10,
+        }));
+        manager
+            .initialize_adapter(ChainType::Solana, flaky_factory(3), false)
+            .await
+            .unwrap();
+        manager.disable_chain(ChainType::Aptos).await;
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while manager.chain_types().await.is_empty() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("adapter did not recover");
+
+        let statuses = manager.chain_statuses().await;
+        assert_eq!(statuses[0].chain_type, ChainType::Aptos);
+        assert_eq!(statuses[0].state, AdapterState::Disabled);
+        assert_eq!(statuses[1].state, AdapterState::Active);
+        assert_eq!(statuses[1].attempts, 4);
+        assert_eq!(statuses[1].error, None);
+        manager.stop().await;
+    }
+}
@@ -0,0 +1,177 @@
+//! 交易签名
+//!
+//! 本地构建的 Sui 交易由 [`Signer`] 签名。节点使用 [`KeystoreSigner`]，
+//! 私钥保存在加密密钥库中、按名称引用；[`Ed25519Signer`] 直接持有私钥，用于测试与工具
+
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Digest};
+use dubhe_security::KeyHandle;
+use ed25519_dalek::{Signer as _, SigningKey};
+
+use crate::sui_tx::signing_digest;
+
+/// Ed25519 签名方案标志
+pub const ED25519_FLAG: u8 = 0x00;
+
+type Blake2b256 = Blake2b<U32>;
+
+/// Sui 交易签名者
+pub trait Signer: Send + Sync {
+    /// 签名者的 Sui 地址
+    fn address(&self) -> String;
+
+    /// 对 `TransactionData` 的 BCS 字节签名，返回 Sui 序列化签名
+    /// `base64(flag || signature || public_key)`
+    fn sign_transaction(&self, tx_bytes: &[u8]) -> Result<String>;
+}
+
+/// Ed25519 签名者
+#[derive(Clone)]
+pub struct Ed25519Signer {
+    key: SigningKey,
+}
+
+impl Ed25519Signer {
+    pub fn from_secret(secret: [u8; 32]) -> Self {
+        Self {
+            key: SigningKey::from_bytes(&secret),
+        }
+    }
+
+    /// 解析 keystore 条目：base64 编码的 `[flag][32 字节私钥]`
+    pub fn from_encoded(encoded: &str) -> Result<Self> {
+        let bytes = base64::engine::general_purpose::STANDARD.decode(encoded.trim())?;
+        match bytes.split_first() {
+            Some((&ED25519_FLAG, secret)) => {
+                let secret: [u8; 32] = secret
+                    .try_into()
+                    .map_err(|_| anyhow!("Ed25519 key must be 32 bytes"))?;
+                Ok(Self::from_secret(secret))
+            }
+            _ => Err(anyhow!("Only Ed25519 signer keys are supported")),
+        }
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.key.verifying_key().to_bytes()
+    }
+}
+
+impl Signer for Ed25519Signer {
+    fn address(&self) -> String {
+        sui_address(&self.public_key())
+    }
+
+    fn sign_transaction(&self, tx_bytes: &[u8]) -> Result<String> {
+        let signature = self.key.sign(&signing_digest(tx_bytes));
+        Ok(serialize_signature(
+            &signature.to_bytes(),
+            &self.public_key(),
+        ))
+    }
+}
+
+/// 通过密钥库签名，私钥不离开密钥库
+#[derive(Debug, Clone)]
+pub struct KeystoreSigner {
+    handle: KeyHandle,
+    public_key: [u8; 32],
+}
+
+impl KeystoreSigner {
+    pub fn new(handle: KeyHandle) -> Result<Self> {
+        let public_key = handle.public_key()?;
+        Ok(Self { handle, public_key })
+    }
+}
+
+impl Signer for KeystoreSigner {
+    fn address(&self) -> String {
+        sui_address(&self.public_key)
+    }
+
+    /// 轮换后公钥随当前密钥变化，因此签名时重新读取
+    fn sign_transaction(&self, tx_bytes: &[u8]) -> Result<String> {
+        let signature = self.handle.sign(&signing_digest(tx_bytes))?;
+        Ok(serialize_signature(&signature, &self.handle.public_key()?))
+    }
+}
+
+/// `blake2b256(flag || 公钥)`
+fn sui_address(public_key: &[u8; 32]) -> String {
+    let mut hasher = Blake2b256::new();
+    hasher.update([ED25519_FLAG]);
+    hasher.update(public_key);
+    format!("0x{}", hex::encode(hasher.finalize()))
+}
+
+/// `base64(flag || signature || public_key)`
+fn serialize_signature(signature: &[u8; 64], public_key: &[u8; 32]) -> String {
+    let mut serialized = Vec::with_capacity(1 + 64 + 32);
+    serialized.push(ED25519_FLAG);
+    serialized.extend_from_slice(signature);
+    serialized.extend_from_slice(public_key);
+    base64::engine::general_purpose::STANDARD.encode(serialized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 8032 测试向量 1 的私钥
+    const KEYSTORE_ENTRY: &str = "AJ1hsZ3v/VpguoRK9JLsLMREScVpezJpGXA7rAMcrn9g";
+
+    const SIGNATURE: &str = "ALLzL5WY6CVevi3cZue3Vdrp50SldDInMLfUd8HsJIHo9+ht/pu3ZNpvSJhAv86En4/22VxrS7iIs1LHw64dUAnXWpgBgrEKt9VL/tPJZAc6DuFy89qmIyWvAhpo9wdRGg==";
+
+    #[test]
+    fn test_signer_known_vector() {
+        let signer = Ed25519Signer::from_encoded(KEYSTORE_ENTRY).unwrap();
+        assert_eq!(
+            hex::encode(signer.public_key()),
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+        );
+        assert_eq!(
+            signer.address(),
+            "0x304af458e90e97c841685b8cbbc59b909f3e2cf150df590ada4c81452c29737d"
+        );
+
+        // 签名覆盖 blake2b256(intent || tx_bytes)
+        assert_eq!(signer.sign_transaction(b"dubhe").unwrap(), SIGNATURE);
+    }
+
+    #[test]
+    fn test_keystore_signer_matches_raw_key() {
+        use dubhe_security::{Keystore, Passphrase};
+        use std::sync::Arc;
+
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = Keystore::open(dir.path(), Passphrase::new("dubhe"))
+            .unwrap()
+            .with_kdf_iterations(1_000);
+        let entry = base64::engine::general_purpose::STANDARD
+            .decode(KEYSTORE_ENTRY)
+            .unwrap();
+        let secret: [u8; 32] = entry[1..].try_into().unwrap();
+        keystore.import("sui-signer", secret.into()).unwrap();
+
+        let keystore = Arc::new(keystore);
+        let signer = KeystoreSigner::new(keystore.handle("sui-signer").unwrap()).unwrap();
+        assert_eq!(
+            signer.address(),
+            Ed25519Signer::from_encoded(KEYSTORE_ENTRY)
+                .unwrap()
+                .address()
+        );
+        assert_eq!(signer.sign_transaction(b"dubhe").unwrap(), SIGNATURE);
+    }
+
+    #[test]
+    fn test_rejects_non_ed25519_keys() {
+        let mut secp256k1 = vec![0x01];
+        secp256k1.extend_from_slice(&[7u8; 32]);
+        let encoded = base64::engine::general_purpose::STANDARD.encode(secp256k1);
+        assert!(Ed25519Signer::from_encoded(&encoded).is_err());
+    }
+}
@@ -1,60 +1,712 @@
 //! Solana 适配器
-//! 
-//! 基于 solana-client 实现的 Solana 轻节点客户端
+//!
+//! 基于 Solana JSON-RPC 实现的 Solana 轻节点客户端
 
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use anyhow::Result;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::sync::Arc;
 use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
 
+use crate::endpoint::{post_json_rpc, EndpointPool, HealthChecker, JsonRpcProbe};
+use crate::error::{AdapterError, AdapterResult};
 use crate::traits::ChainAdapter;
 use crate::types::*;
 
+/// slot 轮询间隔（Solana 出块约 400ms）
+const SLOT_POLL_INTERVAL_MS: u64 = 1000;
+
 /// Solana 适配器
 pub struct SolanaAdapter {
     config: SolanaConfig,
+    client: Client,
+    endpoints: Arc<EndpointPool>,
 }
 
 impl SolanaAdapter {
     pub async fn new(config: SolanaConfig) -> Result<Self> {
-        // TODO: 初始化 Solana RPC 客户端
-        Ok(Self { config })
+        let client = Client::builder()
+            .timeout(config.health_check.request_timeout())
+            .build()?;
+        let endpoints = Arc::new(EndpointPool::new(
+            ChainType::Solana,
+            &config.rpc_url,
+            &config.rpc_urls,
+            config.health_check,
+        ));
+
+        info!(
+            "Solana adapter initialized: {} (commitment: {})",
+            endpoints.urls().join(", "),
+            config.commitment
+        );
+
+        Ok(Self {
+            config,
+            client,
+            endpoints,
+        })
+    }
+
+    /// 调用 Solana JSON-RPC 方法
+    async fn call_rpc(&self, method: &str, params: Value) -> Result<Value> {
+        Self::rpc(&self.client, &self.endpoints, method, params).await
+    }
+
+    /// 经端点池发送请求，交易提交以外的方法在端点故障时转到下一个端点重试
+    async fn rpc(
+        client: &Client,
+        endpoints: &EndpointPool,
+        method: &str,
+        params: Value,
+    ) -> Result<Value> {
+        let idempotent = method != "sendTransaction";
+        Ok(endpoints
+            .call(idempotent, |url| {
+                post_json_rpc(client, ChainType::Solana, url, method, &params)
+            })
+            .await?)
+    }
+
+    /// 获取当前 slot
+    async fn get_slot(client: &Client, endpoints: &EndpointPool, commitment: &str) -> Result<u64> {
+        Self::rpc(
+            client,
+            endpoints,
+            "getSlot",
+            json!([{ "commitment": commitment }]),
+        )
+        .await?
+        .as_u64()
+        .ok_or_else(|| anyhow!("Failed to parse slot"))
+    }
+
+    /// 获取区块中的交易签名（被跳过的 slot 返回错误）
+    async fn get_block_signatures(
+        client: &Client,
+        endpoints: &EndpointPool,
+        commitment: &str,
+        slot: u64,
+    ) -> Result<Vec<String>> {
+        let block = Self::rpc(
+            client,
+            endpoints,
+            "getBlock",
+            json!([
+                slot,
+                {
+                    "commitment": commitment,
+                    "transactionDetails": "signatures",
+                    "maxSupportedTransactionVersion": 0,
+                    "rewards": false
+                }
+            ]),
+        )
+        .await?;
+
+        Ok(block["signatures"]
+            .as_array()
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|sig| sig.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default())
     }
 }
 
 #[async_trait]
 impl ChainAdapter for SolanaAdapter {
-    async fn get_contract_meta(&self, address: &str) -> Result<ContractMeta> {
-        // TODO: 实现 Solana 程序元数据获取
-        todo!("Implement Solana contract meta extraction")
+    async fn get_contract_meta(&self, address: &str) -> AdapterResult<ContractMeta> {
+        info!("Getting Solana program account: {}", address);
+
+        let account_info = self
+            .call_rpc(
+                "getAccountInfo",
+                json!([
+                    address,
+                    {
+                        "encoding": "base64",
+                        "commitment": self.config.commitment
+                    }
+                ]),
+            )
+            .await?;
+
+        Ok(parse_account_info(address, &account_info)?)
+    }
+
+    async fn get_transaction_receipt(&self, tx_hash: &str) -> AdapterResult<TransactionReceipt> {
+        info!("Getting Solana transaction: {}", tx_hash);
+
+        let tx_info = self
+            .call_rpc(
+                "getTransaction",
+                json!([
+                    tx_hash,
+                    {
+                        "encoding": "jsonParsed",
+                        "commitment": self.config.commitment,
+                        "maxSupportedTransactionVersion": 0
+                    }
+                ]),
+            )
+            .await?;
+
+        debug!("Solana transaction info: {}", tx_info);
+        Ok(parse_transaction(tx_hash, &tx_info)?)
+    }
+
+    async fn get_balance(&self, address: &str) -> AdapterResult<u64> {
+        info!("Getting Solana balance for: {}", address);
+
+        let balance_info = self
+            .call_rpc(
+                "getBalance",
+                json!([address, { "commitment": self.config.commitment }]),
+            )
+            .await?;
+
+        // 单位为 lamports
+        let balance = balance_info["value"]
+            .as_u64()
+            .ok_or_else(|| anyhow!("Failed to parse balance for {}", address))?;
+
+        debug!("Solana balance for {}: {}", address, balance);
+        Ok(balance)
+    }
+
+    async fn get_nonce(&self, _address: &str) -> AdapterResult<u64> {
+        // Solana 账户没有递增 nonce，交易防重放依赖 recent blockhash
+        Ok(0)
+    }
+
+    async fn get_block_number(&self) -> AdapterResult<u64> {
+        info!("Getting latest Solana slot");
+
+        let slot = Self::get_slot(&self.client, &self.endpoints, &self.config.commitment).await?;
+
+        debug!("Latest Solana slot: {}", slot);
+        Ok(slot)
+    }
+
+    async fn subscribe_new_blocks(&self) -> AdapterResult<mpsc::Receiver<String>> {
+        info!("Starting Solana slot subscription");
+        let (tx, rx) = mpsc::channel(1000);
+
+        // 启动轮询任务来模拟订阅，从订阅时的 slot 之后开始推送
+        let config = self.config.clone();
+        let client = self.client.clone();
+        let endpoints = self.endpoints.clone();
+
+        tokio::spawn(async move {
+            let mut last_slot: Option<u64> = None;
+            let mut interval =
+                tokio::time::interval(tokio::time::Duration::from_millis(SLOT_POLL_INTERVAL_MS));
+
+            loop {
+                interval.tick().await;
+
+                match Self::get_slot(&client, &endpoints, &config.commitment).await {
+                    Ok(current_slot) => {
+                        if let Some(last) = last_slot {
+                            for slot in (last + 1)..=current_slot {
+                                if tx.send(slot.to_string()).await.is_err() {
+                                    warn!("Solana slot subscription channel closed");
+                                    return;
+                                }
+                            }
+                        }
+                        last_slot = Some(last_slot.map_or(current_slot, |l| l.max(current_slot)));
+                    }
+                    Err(e) => {
+                        error!("Failed to get latest Solana slot: {}", e);
+                    }
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    async fn get_block(&self, block: BlockId) -> AdapterResult<BlockData> {
+        let BlockId::Number(slot) = block else {
+            return Err(AdapterError::Unsupported(
+                "Solana block lookup by hash".to_string(),
+            ));
+        };
+        let result = self
+            .call_rpc(
+                "getBlock",
+                json!([
+                    slot,
+                    {
+                        "commitment": self.config.commitment,
+                        "transactionDetails": "signatures",
+                        "maxSupportedTransactionVersion": 0,
+                        "rewards": false
+                    }
+                ]),
+            )
+            .await?;
+        Ok(parse_block(slot, &result)?)
+    }
+
+    /// 一次 `getBlock` 取回 slot 内全部交易的详情，不再逐笔请求
+    async fn get_block_receipts(&self, slot: u64) -> AdapterResult<BlockReceipts> {
+        let result = self
+            .call_rpc(
+                "getBlock",
+                json!([
+                    slot,
+                    {
+                        "commitment": self.config.commitment,
+                        "encoding": "jsonParsed",
+                        "transactionDetails": "full",
+                        "maxSupportedTransactionVersion": 0,
+                        "rewards": false
+                    }
+                ]),
+            )
+            .await?;
+        Ok(parse_block_receipts(slot, &result)?)
+    }
+
+    fn health_checker(&self) -> Option<HealthChecker> {
+        let probe = JsonRpcProbe::new(self.client.clone(), ChainType::Solana, "getHealth");
+        Some(HealthChecker::new(self.endpoints.clone(), Arc::new(probe)))
+    }
+
+    async fn subscribe_new_transactions(&self) -> AdapterResult<mpsc::Receiver<String>> {
+        info!("Starting Solana transaction subscription");
+        let (tx, rx) = mpsc::channel(1000);
+
+        let config = self.config.clone();
+        let client = self.client.clone();
+        let endpoints = self.endpoints.clone();
+
+        tokio::spawn(async move {
+            let mut last_slot: Option<u64> = None;
+            let mut interval =
+                tokio::time::interval(tokio::time::Duration::from_millis(SLOT_POLL_INTERVAL_MS));
+
+            loop {
+                interval.tick().await;
+
+                let current_slot =
+                    match Self::get_slot(&client, &endpoints, &config.commitment).await {
+                        Ok(slot) => slot,
+                        Err(e) => {
+                            error!("Failed to get Solana transactions: {}", e);
+                            continue;
+                        }
+                    };
+
+                let Some(last) = last_slot else {
+                    last_slot = Some(current_slot);
+                    continue;
+                };
+
+                for slot in (last + 1)..=current_slot {
+                    // 被跳过的 slot 没有区块
+                    let Ok(signatures) =
+                        Self::get_block_signatures(&client, &endpoints, &config.commitment, slot)
+                            .await
+                    else {
+                        continue;
+                    };
+
+                    for signature in signatures {
+                        if tx.send(signature).await.is_err() {
+                            warn!("Solana transaction subscription channel closed");
+                            return;
+                        }
+                    }
+                }
+                last_slot = Some(last.max(current_slot));
+            }
+        });
+
+        Ok(rx)
+    }
+}
+
+/// 解析 `getAccountInfo`（base64 编码）的结果
+///
+/// 可升级程序（BPFLoaderUpgradeable）的程序账户只保存 ProgramData 地址，
+/// ELF 位于 ProgramData 账户中，需要以该地址再次查询
+pub fn parse_account_info(address: &str, result: &Value) -> Result<ContractMeta> {
+    let account = &result["value"];
+    if account.is_null() {
+        return Err(anyhow!("Solana account not found: {}", address));
+    }
+
+    let data = account["data"]
+        .as_array()
+        .ok_or_else(|| anyhow!("Unexpected account data format for {}", address))?;
+    let (encoded, encoding) = match data.as_slice() {
+        [encoded, encoding] => (encoded.as_str(), encoding.as_str()),
+        _ => (None, None),
+    };
+    if encoding != Some("base64") {
+        return Err(anyhow!(
+            "Unsupported account data encoding for {}: {:?}",
+            address,
+            encoding
+        ));
     }
+    let bytecode = BASE64.decode(encoded.unwrap_or_default())?;
 
-    async fn get_transaction_receipt(&self, tx_hash: &str) -> Result<TransactionReceipt> {
-        // TODO: 实现 Solana 交易回执获取
-        todo!("Implement Solana transaction receipt")
+    if account["executable"].as_bool() != Some(true) {
+        warn!("Solana account {} is not executable", address);
     }
 
-    async fn get_balance(&self, address: &str) -> Result<u64> {
-        // TODO: 实现 Solana 账户余额查询
-        todo!("Implement Solana balance query")
+    Ok(ContractMeta {
+        address: address.to_string(),
+        chain_type: ChainType::Solana,
+        contract_type: ContractType::BPF,
+        bytecode,
+        abi: None, // Solana 程序没有链上 ABI
+        source_code: None,
+        compiler_version: None,
+        created_at: chrono::Utc::now().timestamp() as u64,
+        creator: None,
+        abi_source: None,
+        modules: vec![],
+    })
+}
+
+/// 解析 `getTransaction`（jsonParsed 编码）的结果
+pub fn parse_transaction(signature: &str, result: &Value) -> Result<TransactionReceipt> {
+    if result.is_null() {
+        return Err(anyhow!("Solana transaction not found: {}", signature));
     }
 
-    async fn get_nonce(&self, address: &str) -> Result<u64> {
-        // TODO: Solana 使用不同的 nonce 机制
-        todo!("Implement Solana nonce query")
+    let meta = &result["meta"];
+    let message = &result["transaction"]["message"];
+
+    let status = if meta["err"].is_null() {
+        TransactionStatus::Success
+    } else {
+        TransactionStatus::Failed
+    };
+
+    // jsonParsed 的 accountKeys 为对象数组，第一个签名者即手续费支付者
+    let from = message["accountKeys"]
+        .as_array()
+        .and_then(|keys| {
+            keys.iter()
+                .find(|key| key["signer"].as_bool() == Some(true))
+                .or_else(|| keys.first())
+        })
+        .and_then(|key| key["pubkey"].as_str())
+        .unwrap_or("")
+        .to_string();
+
+    let to = message["instructions"]
+        .as_array()
+        .and_then(|instructions| instructions.first())
+        .and_then(|instruction| instruction["programId"].as_str())
+        .map(|program_id| program_id.to_string());
+
+    // 旧节点不返回 computeUnitsConsumed 时以手续费代替
+    let gas_used = meta["computeUnitsConsumed"]
+        .as_u64()
+        .or_else(|| meta["fee"].as_u64())
+        .unwrap_or(0);
+
+    let logs = meta["logMessages"]
+        .as_array()
+        .map(|messages| {
+            parse_log_messages(messages.iter().filter_map(|message| message.as_str()))
+        })
+        .unwrap_or_default();
+
+    Ok(TransactionReceipt {
+        tx_hash: result["transaction"]["signatures"][0]
+            .as_str()
+            .unwrap_or(signature)
+            .to_string(),
+        block_hash: String::new(), // getTransaction 不返回区块哈希
+        block_number: result["slot"].as_u64().unwrap_or(0),
+        transaction_index: 0, // getTransaction 不返回交易在区块中的位置
+        from,
+        to,
+        gas_used,
+        status,
+        logs,
+        contract_address: None,
+    })
+}
+
+/// 解析 `getBlock`（signatures 详情）的结果
+pub fn parse_block(slot: u64, result: &Value) -> Result<BlockData> {
+    if result.is_null() {
+        return Err(anyhow!("Solana slot {} has no block", slot));
     }
+    Ok(BlockData {
+        number: slot,
+        hash: result["blockhash"].as_str().unwrap_or_default().to_string(),
+        parent_hash: result["previousBlockhash"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        timestamp: result["blockTime"].as_u64().unwrap_or(0),
+        transactions: result["signatures"]
+            .as_array()
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|sig| sig.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    })
+}
 
-    async fn get_block_number(&self) -> Result<u64> {
-        // TODO: 实现 Solana slot 高度查询
-        todo!("Implement Solana slot query")
+/// 解析 `getBlock`（full 详情，jsonParsed 编码）的结果
+///
+/// 区块中的交易不带 slot 与区块哈希，这里按区块补全，交易序号为其在区块中的位置
+pub fn parse_block_receipts(slot: u64, result: &Value) -> Result<BlockReceipts> {
+    if result.is_null() {
+        return Err(anyhow!("Solana slot {} has no block", slot));
     }
+    let block_hash = result["blockhash"].as_str().unwrap_or_default();
 
-    async fn subscribe_new_blocks(&self) -> Result<mpsc::Receiver<String>> {
-        // TODO: 实现 Solana 新 slot 订阅
-        todo!("Implement Solana new slot subscription")
+    let mut receipts = BlockReceipts {
+        block_number: slot,
+        ..Default::default()
+    };
+    for (index, transaction) in result["transactions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        let signature = transaction["transaction"]["signatures"][0]
+            .as_str()
+            .unwrap_or_default();
+        let mut entry = transaction.clone();
+        entry["slot"] = json!(slot);
+        match parse_transaction(signature, &entry) {
+            Ok(mut receipt) => {
+                receipt.block_hash = block_hash.to_string();
+                receipt.transaction_index = index as u32;
+                receipts.receipts.push(receipt);
+            }
+            Err(_) => receipts.missing.push(signature.to_string()),
+        }
     }
+    Ok(receipts)
+}
+
+/// 将程序日志转换为事件日志
+///
+/// `Program log:` 与 `Program data:` 行归属于当前正在执行（最内层 invoke）的程序，
+/// topic 分别为 `log` 和 `data`
+pub fn parse_log_messages<'a>(messages: impl Iterator<Item = &'a str>) -> Vec<EventLog> {
+    let mut call_stack: Vec<&str> = Vec::new();
+    let mut logs = Vec::new();
 
-    async fn subscribe_new_transactions(&self) -> Result<mpsc::Receiver<String>> {
-        // TODO: 实现 Solana 新交易订阅
-        todo!("Implement Solana new transaction subscription")
+    for message in messages {
+        if let Some(text) = message.strip_prefix("Program log: ") {
+            logs.push(EventLog {
+                address: call_stack.last().unwrap_or(&"").to_string(),
+                topics: vec!["log".to_string()],
+                data: text.to_string(),
+            });
+        } else if let Some(data) = message.strip_prefix("Program data: ") {
+            logs.push(EventLog {
+                address: call_stack.last().unwrap_or(&"").to_string(),
+                topics: vec!["data".to_string()],
+                data: data.to_string(),
+            });
+        } else if let Some(rest) = message.strip_prefix("Program ") {
+            let mut parts = rest.split_whitespace();
+            let program_id = parts.next().unwrap_or("");
+            match parts.next() {
+                Some("invoke") => call_stack.push(program_id),
+                Some("success") | Some("failed:") => {
+                    call_stack.pop();
+                }
+                _ => {}
+            }
+        }
     }
-} 
\ No newline at end of file
+
+    logs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCOUNT_INFO: &str = r#"{
+        "context": { "apiVersion": "1.18.22", "slot": 287654321 },
+        "value": {
+            "data": ["f0VMRgIBAQA=", "base64"],
+            "executable": true,
+            "lamports": 1141440,
+            "owner": "BPFLoader2111111111111111111111111111111111",
+            "rentEpoch": 18446744073709551615,
+            "space": 8
+        }
+    }"#;
+
+    const TRANSACTION: &str = r#"{
+        "blockTime": 1718000000,
+        "meta": {
+            "computeUnitsConsumed": 5321,
+            "err": null,
+            "fee": 5000,
+            "logMessages": [
+                "Program ComputeBudget111111111111111111111111111111 invoke [1]",
+                "Program ComputeBudget111111111111111111111111111111 success",
+                "Program Counter111111111111111111111111111111111 invoke [1]",
+                "Program log: Instruction: Increment",
+                "Program Tokenkeg11111111111111111111111111111111 invoke [2]",
+                "Program log: Instruction: Transfer",
+                "Program Tokenkeg11111111111111111111111111111111 consumed 4645 of 190000 compute units",
+                "Program Tokenkeg11111111111111111111111111111111 success",
+                "Program data: AQIDBA==",
+                "Program Counter111111111111111111111111111111111 consumed 5321 of 200000 compute units",
+                "Program Counter111111111111111111111111111111111 success"
+            ],
+            "postBalances": [999995000, 1141440],
+            "preBalances": [1000000000, 1141440],
+            "status": { "Ok": null }
+        },
+        "slot": 287654300,
+        "transaction": {
+            "message": {
+                "accountKeys": [
+                    { "pubkey": "Payer11111111111111111111111111111111111111", "signer": true, "source": "transaction", "writable": true },
+                    { "pubkey": "Counter111111111111111111111111111111111", "signer": false, "source": "transaction", "writable": false }
+                ],
+                "instructions": [
+                    { "accounts": ["Payer11111111111111111111111111111111111111"], "data": "3Bxs4Bc3VYuGVB19", "programId": "Counter111111111111111111111111111111111", "stackHeight": null }
+                ],
+                "recentBlockhash": "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N"
+            },
+            "signatures": ["5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"]
+        },
+        "version": 0
+    }"#;
+
+    #[test]
+    fn test_parse_account_info() {
+        let result: Value = serde_json::from_str(ACCOUNT_INFO).unwrap();
+        let meta = parse_account_info("Counter111111111111111111111111111111111", &result).unwrap();
+        assert_eq!(meta.bytecode, b"\x7fELF\x02\x01\x01\x00".to_vec());
+        assert!(matches!(meta.contract_type, ContractType::BPF));
+        assert_eq!(meta.chain_type, ChainType::Solana);
+
+        let missing = json!({ "context": { "slot": 1 }, "value": null });
+        assert!(parse_account_info("missing", &missing).is_err());
+    }
+
+    #[test]
+    fn test_parse_transaction() {
+        let result: Value = serde_json::from_str(TRANSACTION).unwrap();
+        let receipt = parse_transaction("sig", &result).unwrap();
+
+        assert!(receipt.tx_hash.starts_with("5VERv8"));
+        assert_eq!(receipt.block_number, 287654300);
+        assert_eq!(receipt.from, "Payer11111111111111111111111111111111111111");
+        assert_eq!(
+            receipt.to.as_deref(),
+            Some("Counter111111111111111111111111111111111")
+        );
+        assert_eq!(receipt.gas_used, 5321);
+        assert!(matches!(receipt.status, TransactionStatus::Success));
+
+        // 日志归属于最内层正在执行的程序
+        let owners: Vec<(&str, &str)> = receipt
+            .logs
+            .iter()
+            .map(|log| (log.address.as_str(), log.topics[0].as_str()))
+            .collect();
+        assert_eq!(
+            owners,
+            vec![
+                ("Counter111111111111111111111111111111111", "log"),
+                ("Tokenkeg11111111111111111111111111111111", "log"),
+                ("Counter111111111111111111111111111111111", "data"),
+            ]
+        );
+        assert_eq!(receipt.logs[2].data, "AQIDBA==");
+    }
+
+    #[test]
+    fn test_parse_block_receipts() {
+        let mut transaction: Value = serde_json::from_str(TRANSACTION).unwrap();
+        let entry = transaction.as_object_mut().unwrap();
+        entry.remove("slot");
+        entry.remove("blockTime");
+        let mut failed = transaction.clone();
+        failed["meta"]["err"] = json!({ "InstructionError": [0, { "Custom": 1 }] });
+        failed["transaction"]["signatures"] = json!(["SecondSig"]);
+
+        let block = json!({
+            "blockHeight": 265000000,
+            "blockTime": 1718000000,
+            "blockhash": "BlockHash1111111111111111111111111111111111",
+            "parentSlot": 287654299,
+            "previousBlockhash": "PrevHash11111111111111111111111111111111111",
+            "transactions": [transaction, failed],
+        });
+
+        let receipts = parse_block_receipts(287654300, &block).unwrap();
+        assert_eq!(receipts.block_number, 287654300);
+        assert!(receipts.missing.is_empty());
+        assert_eq!(receipts.receipts.len(), 2);
+        let first = &receipts.receipts[0];
+        assert!(first.tx_hash.starts_with("5VERv8"));
+        assert_eq!(first.block_number, 287654300);
+        assert_eq!(
+            first.block_hash,
+            "BlockHash1111111111111111111111111111111111"
+        );
+        assert_eq!(first.logs.len(), 3);
+        let second = &receipts.receipts[1];
+        assert_eq!(second.tx_hash, "SecondSig");
+        assert_eq!(second.transaction_index, 1);
+        assert!(matches!(second.status, TransactionStatus::Failed));
+
+        // 被跳过的 slot
+        assert!(parse_block_receipts(287654301, &Value::Null).is_err());
+    }
+
+    #[test]
+    fn test_parse_block() {
+        let block = json!({
+            "blockTime": 1718000000,
+            "blockhash": "BlockHash1111111111111111111111111111111111",
+            "previousBlockhash": "PrevHash11111111111111111111111111111111111",
+            "signatures": ["SigA", "SigB"],
+        });
+        let block = parse_block(287654300, &block).unwrap();
+        assert_eq!(block.number, 287654300);
+        assert_eq!(block.timestamp, 1_718_000_000);
+        assert_eq!(block.transactions, vec!["SigA", "SigB"]);
+    }
+
+    #[test]
+    fn test_parse_failed_transaction() {
+        let mut result: Value = serde_json::from_str(TRANSACTION).unwrap();
+        result["meta"]["err"] = json!({ "InstructionError": [0, { "Custom": 1 }] });
+        result["meta"]
+            .as_object_mut()
+            .unwrap()
+            .remove("computeUnitsConsumed");
+
+        let receipt = parse_transaction("sig", &result).unwrap();
+        assert!(matches!(receipt.status, TransactionStatus::Failed));
+        assert_eq!(receipt.gas_used, 5000);
+
+        assert!(parse_transaction("sig", &Value::Null).is_err());
+    }
+}
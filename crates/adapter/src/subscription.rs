@@ -0,0 +1,191 @@
+//! 适配器订阅监管
+//!
+//! 每个适配器的新区块 / 新交易订阅各由一个任务消费并转发到事件总线；
+//! 订阅流结束或建立失败时按指数退避重新订阅，直到收到停止信号
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+use tracing::{debug, warn};
+
+use crate::traits::ChainAdapter;
+use crate::types::{ChainEvent, ChainEventKind, ChainType};
+
+/// 重新订阅的退避参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriptionBackoff {
+    /// 首次重试前的等待（毫秒）
+    pub initial_ms: u64,
+    /// 等待时间上限（毫秒）
+    pub max_ms: u64,
+}
+
+impl Default for SubscriptionBackoff {
+    fn default() -> Self {
+        Self {
+            initial_ms: 500,
+            max_ms: 60_000,
+        }
+    }
+}
+
+impl SubscriptionBackoff {
+    pub(crate) fn initial(&self) -> Duration {
+        Duration::from_millis(self.initial_ms.max(1))
+    }
+
+    pub(crate) fn next(&self, current: Duration) -> Duration {
+        (current * 2).min(Duration::from_millis(self.max_ms.max(self.initial_ms)))
+    }
+}
+
+/// 消费一路订阅，流结束后退避重订阅；`shutdown` 置位或其发送端释放时返回
+pub(crate) async fn supervise(
+    adapter: Arc<dyn ChainAdapter + Send + Sync>,
+    chain_type: ChainType,
+    kind: ChainEventKind,
+    events: broadcast::Sender<ChainEvent>,
+    backoff: SubscriptionBackoff,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut delay = backoff.initial();
+
+    while !*shutdown.borrow() {
+        let subscription = match kind {
+            ChainEventKind::NewBlock => adapter.subscribe_new_blocks().await,
+            ChainEventKind::NewTransaction => adapter.subscribe_new_transactions().await,
+        };
+
+        match subscription {
+            Ok(mut stream) => loop {
+                tokio::select! {
+                    _ = shutdown.changed() => return,
+                    item = stream.recv() => match item {
+                        Some(payload) => {
+                            // 收到数据说明订阅恢复正常，重置退避
+                            delay = backoff.initial();
+                            // 没有订阅者时 send 返回错误，忽略即可
+                            let _ = events.send(ChainEvent {
+                                chain_type,
+                                kind,
+                                payload,
+                            });
+                        }
+                        None => {
+                            warn!(
+                                "⚠️ {:?} {:?} subscription ended, restarting in {:?}",
+                                chain_type, kind, delay
+                            );
+                            break;
+                        }
+                    },
+                }
+            },
+            Err(e) => warn!(
+                "⚠️ Failed to subscribe to {:?} {:?}: {}, retrying in {:?}",
+                chain_type, kind, e, delay
+            ),
+        }
+
+        tokio::select! {
+            _ = shutdown.changed() => return,
+            _ = tokio::time::sleep(delay) => {}
+        }
+        delay = backoff.next(delay);
+    }
+    debug!("{:?} {:?} subscription supervisor stopped", chain_type, kind);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{AdapterError, AdapterResult};
+    use crate::types::{ContractMeta, TransactionReceipt};
+    use crate::AdapterManager;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 每次订阅推送 3 个区块后结束，交易订阅不可用
+    #[derive(Default)]
+    struct FlakyAdapter {
+        subscriptions: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl ChainAdapter for FlakyAdapter {
+        async fn get_contract_meta(&self, _address: &str) -> AdapterResult<ContractMeta> {
+            unimplemented!()
+        }
+
+        async fn get_transaction_receipt(
+            &self,
+            _tx_hash: &str,
+        ) -> AdapterResult<TransactionReceipt> {
+            unimplemented!()
+        }
+
+        async fn get_balance(&self, _address: &str) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn get_nonce(&self, _address: &str) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn get_block_number(&self) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn subscribe_new_blocks(&self) -> AdapterResult<tokio::sync::mpsc::Receiver<String>> {
+            let round = self.subscriptions.fetch_add(1, Ordering::SeqCst);
+            let (tx, rx) = tokio::sync::mpsc::channel(3);
+            for i in 0..3 {
+                tx.send(format!("0x{}{}", round, i)).await.unwrap();
+            }
+            Ok(rx)
+        }
+
+        async fn subscribe_new_transactions(
+            &self,
+        ) -> AdapterResult<tokio::sync::mpsc::Receiver<String>> {
+            Err(AdapterError::Unsupported(
+                "subscribe_new_transactions".to_string(),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restarts_ended_subscription() {
+        let adapter = FlakyAdapter::default();
+        let subscriptions = adapter.subscriptions.clone();
+        let manager = AdapterManager::new().with_backoff(SubscriptionBackoff {
+            initial_ms: 5,
+            max_ms: 20,
+        });
+        manager
+            .register_adapter(ChainType::Sui, Box::new(adapter))
+            .await;
+
+        let mut events = manager.subscribe_events();
+        manager.start_background_tasks().await.unwrap();
+
+        let mut payloads = Vec::new();
+        while payloads.len() < 7 {
+            let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+                .await
+                .expect("subscription was not restarted")
+                .unwrap();
+            assert_eq!(event.chain_type, ChainType::Sui);
+            assert_eq!(event.kind, ChainEventKind::NewBlock);
+            payloads.push(event.payload);
+        }
+        assert_eq!(payloads[..4], ["0x00", "0x01", "0x02", "0x10"]);
+        assert!(subscriptions.load(Ordering::SeqCst) >= 3);
+
+        manager.stop().await;
+        let stopped_at = subscriptions.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(subscriptions.load(Ordering::SeqCst), stopped_at);
+    }
+}
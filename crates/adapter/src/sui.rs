@@ -4,32 +4,170 @@
 
 use anyhow::Result;
 use async_trait::async_trait;
+use base64::Engine;
 use reqwest::Client;
 use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, field, info, info_span, warn, Instrument};
 
+use dubhe_observability::TRACE_TARGET;
+
+use crate::cursor::CursorStore;
+use crate::endpoint::{post_json_rpc, EndpointPool, HealthChecker, JsonRpcProbe};
+use crate::error::{AdapterError, AdapterResult};
+use crate::signer::Signer;
+use crate::sui_tx::{self, *};
 use crate::sui_types::*;
 use crate::traits::ChainAdapter;
 use crate::types::*;
 
+/// 选择 gas 币时单页查询的数量
+const GAS_COIN_PAGE_SIZE: usize = 50;
+
+/// 检查点轮询间隔
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 恢复的游标落后链头超过该数量时发出缺口告警
+const DEFAULT_GAP_WARNING_THRESHOLD: u64 = 1_000;
+
+/// 回放历史检查点的默认速率（每秒）
+const DEFAULT_BACKFILL_RATE: u32 = 50;
+
+/// 检查点订阅与交易订阅各自的游标
+const CHECKPOINT_STREAM: &str = "checkpoints";
+const TRANSACTION_STREAM: &str = "transactions";
+const OBJECT_VERSION_STREAM: &str = "object_versions";
+
+/// `sui_multiGetTransactionBlocks` 单次请求的交易数上限
+const MULTI_GET_LIMIT: usize = 50;
+
 /// Sui 适配器
 pub struct SuiAdapter {
     config: SuiConfig,
     client: Client,
+    endpoints: Arc<EndpointPool>,
+    signer: Option<Arc<dyn Signer>>,
+    cursor_store: Option<Arc<dyn CursorStore>>,
+    poll_interval: Duration,
+    gap_warning_threshold: u64,
+    backfill_rate: u32,
 }
 
 impl SuiAdapter {
     pub async fn new(config: SuiConfig) -> Result<Self> {
-        let client = Client::new();
+        let client = Client::builder()
+            .timeout(config.health_check.request_timeout())
+            .build()?;
+        let endpoints = Arc::new(EndpointPool::new(
+            ChainType::Sui,
+            &config.rpc_url,
+            &config.rpc_urls,
+            config.health_check,
+        ));
 
         info!(
             "Sui adapter initialized for {} network: {}",
             format!("{:?}", config.network_type),
-            config.rpc_url
+            endpoints.urls().join(", ")
         );
+        // 签名密钥由节点从密钥库取出后通过 with_signer 注入
+        if config.signer.is_none() {
+            warn!("⚠️ No Sui signer configured, transactions can only be dry-run");
+        }
+
+        Ok(Self {
+            config,
+            client,
+            endpoints,
+            signer: None,
+            cursor_store: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            gap_warning_threshold: DEFAULT_GAP_WARNING_THRESHOLD,
+            backfill_rate: DEFAULT_BACKFILL_RATE,
+        })
+    }
+
+    /// 使用指定签名者
+    pub fn with_signer(mut self, signer: Arc<dyn Signer>) -> Self {
+        info!("🔑 Sui transactions signed by {}", signer.address());
+        self.signer = Some(signer);
+        self
+    }
+
+    pub fn signer(&self) -> Option<&Arc<dyn Signer>> {
+        self.signer.as_ref()
+    }
+
+    /// 持久化订阅游标；未设置时每次订阅都从当前链头开始
+    pub fn with_cursor_store(mut self, store: Arc<dyn CursorStore>) -> Self {
+        self.cursor_store = Some(store);
+        self
+    }
+
+    /// 设置检查点轮询间隔
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// 设置缺口告警阈值（检查点数）
+    pub fn with_gap_warning_threshold(mut self, threshold: u64) -> Self {
+        self.gap_warning_threshold = threshold;
+        self
+    }
 
-        Ok(Self { config, client })
+    /// 设置回放速率（每秒检查点数）
+    pub fn with_backfill_rate(mut self, per_second: u32) -> Self {
+        self.backfill_rate = per_second.max(1);
+        self
+    }
+
+    /// 按限定速率回放历史检查点 `[from, to]`，不移动订阅游标
+    pub async fn backfill(&self, from: u64, to: u64) -> Result<mpsc::Receiver<String>> {
+        if from > to {
+            return Err(anyhow::anyhow!("Invalid backfill range: {} > {}", from, to));
+        }
+        let tip = Self::get_latest_checkpoint(&self.client, &self.endpoints).await?;
+        if to > tip {
+            return Err(anyhow::anyhow!(
+                "Backfill end {} is beyond the latest checkpoint {}",
+                to,
+                tip
+            ));
+        }
+
+        info!("⏪ Backfilling Sui checkpoints {}..={}", from, to);
+        let (tx, rx) = mpsc::channel(1000);
+        let period = Duration::from_secs(1) / self.backfill_rate;
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            for checkpoint in from..=to {
+                interval.tick().await;
+                if tx.send(checkpoint.to_string()).await.is_err() {
+                    warn!("Sui backfill channel closed at checkpoint {}", checkpoint);
+                    return;
+                }
+            }
+            info!("✅ Sui backfill {}..={} finished", from, to);
+        });
+
+        Ok(rx)
+    }
+
+    fn checkpoint_follower(&self, stream: &str) -> CheckpointFollower {
+        CheckpointFollower {
+            client: self.client.clone(),
+            endpoints: self.endpoints.clone(),
+            network: format!("{:?}", self.config.network_type),
+            key: cursor_key(&self.config.network_type, stream),
+            store: self.cursor_store.clone(),
+            poll_interval: self.poll_interval,
+            gap_warning_threshold: self.gap_warning_threshold,
+        }
     }
 
     /// 获取网络的完整节点 URL
@@ -95,34 +233,43 @@ impl SuiAdapter {
 
     /// 调用 Sui JSON-RPC 方法
     async fn call_rpc(&self, method: &str, params: Value) -> Result<Value> {
-        let request = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": method,
-            "params": params
-        });
-
-        let response = self
-            .client
-            .post(&self.config.rpc_url)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
-
-        let response_json: Value = response.json().await?;
-
-        if let Some(error) = response_json.get("error") {
-            return Err(anyhow::anyhow!("Sui RPC error: {}", error));
-        }
+        Self::rpc(&self.client, &self.endpoints, method, params).await
+    }
 
-        Ok(response_json["result"].clone())
+    /// 经端点池发送请求，交易提交以外的方法在端点故障时转到下一个端点重试
+    ///
+    /// `rpc.endpoint` 记录最后一次尝试的端点，`rpc.latency_ms` 含故障转移的总耗时
+    async fn rpc(
+        client: &Client,
+        endpoints: &EndpointPool,
+        method: &str,
+        params: Value,
+    ) -> Result<Value> {
+        let idempotent = method != "sui_executeTransactionBlock";
+        let span = info_span!(
+            target: TRACE_TARGET,
+            "adapter.call_rpc",
+            chain = "sui",
+            rpc.method = method,
+            rpc.endpoint = field::Empty,
+            rpc.latency_ms = field::Empty,
+        );
+        let started = Instant::now();
+        let result = endpoints
+            .call(idempotent, |url| {
+                span.record("rpc.endpoint", url.as_str());
+                post_json_rpc(client, ChainType::Sui, url, method, &params)
+            })
+            .instrument(span.clone())
+            .await;
+        span.record("rpc.latency_ms", started.elapsed().as_millis() as u64);
+        Ok(result?)
     }
 }
 
 #[async_trait]
 impl ChainAdapter for SuiAdapter {
-    async fn get_contract_meta(&self, address: &str) -> Result<ContractMeta> {
+    async fn get_contract_meta(&self, address: &str) -> AdapterResult<ContractMeta> {
         info!("Getting Sui package metadata for: {}", address);
 
         // 获取 Sui 包信息
@@ -145,10 +292,38 @@ impl ChainAdapter for SuiAdapter {
             .await?;
 
         debug!("Sui package info: {}", package_info);
+        if package_info["data"].is_null() {
+            return Err(AdapterError::ContractNotFound {
+                chain: ChainType::Sui,
+                address: address.to_string(),
+            });
+        }
 
         // 解析包内容
         let content = package_info["data"]["content"].clone();
-        let bytecode = if let Some(bcs) = package_info["data"]["bcs"].as_str() {
+
+        // 规范化模块描述入口函数及参数类型，供编译器生成分派桩；取不到时退回包内容
+        let abi = match self
+            .call_rpc("sui_getNormalizedMoveModulesByPackage", json!([address]))
+            .await
+        {
+            Ok(modules) if modules.is_object() => modules,
+            Ok(_) => content,
+            Err(e) => {
+                warn!("Failed to get normalized modules for {}: {}", address, e);
+                content
+            }
+        };
+
+        // 包对象的 BCS 带有各模块字节码；旧格式为整体的十六进制字节
+        let bcs = &package_info["data"]["bcs"];
+        let modules = package_modules(bcs, &abi)?;
+        let bytecode = if !modules.is_empty() {
+            modules
+                .iter()
+                .flat_map(|module| module.bytecode.iter().copied())
+                .collect()
+        } else if let Some(bcs) = bcs.as_str() {
             hex::decode(bcs.strip_prefix("0x").unwrap_or(bcs)).unwrap_or_else(|_| vec![])
         } else {
             vec![]
@@ -167,15 +342,17 @@ impl ChainAdapter for SuiAdapter {
             chain_type: ChainType::Sui,
             contract_type: ContractType::Move,
             bytecode,
-            abi: Some(content.to_string()),
+            abi: Some(abi.to_string()),
             source_code: None,
             compiler_version: Some("move".to_string()),
             created_at,
             creator,
+            abi_source: Some(AbiSource::Verified),
+            modules,
         })
     }
 
-    async fn get_transaction_receipt(&self, tx_hash: &str) -> Result<TransactionReceipt> {
+    async fn get_transaction_receipt(&self, tx_hash: &str) -> AdapterResult<TransactionReceipt> {
         info!("Getting Sui transaction: {}", tx_hash);
 
         let tx_info = self
@@ -196,56 +373,39 @@ impl ChainAdapter for SuiAdapter {
             .await?;
 
         debug!("Sui transaction info: {}", tx_info);
+        Ok(parse_transaction_block(tx_hash, &tx_info))
+    }
 
-        // 解析交易信息
-        let digest = tx_info["digest"].as_str().unwrap_or(tx_hash).to_string();
-        let effects = &tx_info["effects"];
-
-        let status = if effects["status"]["status"].as_str() == Some("success") {
-            TransactionStatus::Success
-        } else {
-            TransactionStatus::Failed
+    async fn get_block(&self, block: BlockId) -> AdapterResult<BlockData> {
+        // sui_getCheckpoint 同时接受序号与检查点摘要
+        let id = match block {
+            BlockId::Number(number) => number.to_string(),
+            BlockId::Hash(digest) => digest,
         };
+        let checkpoint = self.call_rpc("sui_getCheckpoint", json!([id])).await?;
+        Ok(parse_checkpoint(&checkpoint)?)
+    }
 
-        // 获取 gas 使用量
-        let gas_used = effects["gasUsed"]["computationCost"].as_u64().unwrap_or(0);
-
-        // 获取发送者
-        let sender = tx_info["transaction"]["data"]["sender"]
-            .as_str()
-            .unwrap_or("")
-            .to_string();
-
-        // 解析事件日志
-        let mut logs = vec![];
-        if let Some(events) = tx_info["events"].as_array() {
-            for event in events {
-                logs.push(EventLog {
-                    address: event["packageId"].as_str().unwrap_or("").to_string(),
-                    topics: vec![event["type"].as_str().unwrap_or("").to_string()],
-                    data: event["parsedJson"].to_string(),
-                });
-            }
+    /// 取检查点的交易列表后按 `sui_multiGetTransactionBlocks` 的上限分批取回
+    async fn get_block_receipts(&self, number: u64) -> AdapterResult<BlockReceipts> {
+        let block = self.get_block(BlockId::Number(number)).await?;
+        let mut responses = Vec::with_capacity(block.transactions.len());
+        for chunk in block.transactions.chunks(MULTI_GET_LIMIT) {
+            let blocks = self
+                .call_rpc(
+                    "sui_multiGetTransactionBlocks",
+                    json!([
+                        chunk,
+                        { "showInput": true, "showEffects": true, "showEvents": true }
+                    ]),
+                )
+                .await?;
+            responses.extend(blocks.as_array().into_iter().flatten().cloned());
         }
-
-        Ok(TransactionReceipt {
-            tx_hash: digest,
-            block_hash: effects["transactionDigest"]
-                .as_str()
-                .unwrap_or("")
-                .to_string(),
-            block_number: effects["checkpoint"].as_u64().unwrap_or(0),
-            transaction_index: 0, // Sui 不使用传统的交易索引
-            from: sender,
-            to: None, // Sui 交易可能有多个接收者，这里简化处理
-            gas_used,
-            status,
-            logs,
-            contract_address: None,
-        })
+        Ok(checkpoint_receipts(&block, &responses))
     }
 
-    async fn get_balance(&self, address: &str) -> Result<u64> {
+    async fn get_balance(&self, address: &str) -> AdapterResult<u64> {
         info!("Getting Sui balance for: {}", address);
 
         let balance_info = self
@@ -261,7 +421,7 @@ impl ChainAdapter for SuiAdapter {
         Ok(balance)
     }
 
-    async fn get_nonce(&self, address: &str) -> Result<u64> {
+    async fn get_nonce(&self, address: &str) -> AdapterResult<u64> {
         // Sui 使用序列号概念，获取最新的序列号
         info!("Getting Sui sequence number for: {}", address);
 
@@ -297,7 +457,7 @@ impl ChainAdapter for SuiAdapter {
         Ok(sequence)
     }
 
-    async fn get_block_number(&self) -> Result<u64> {
+    async fn get_block_number(&self) -> AdapterResult<u64> {
         info!("Getting latest Sui checkpoint");
 
         let checkpoint_info = self
@@ -313,36 +473,36 @@ impl ChainAdapter for SuiAdapter {
         Ok(checkpoint)
     }
 
-    async fn subscribe_new_blocks(&self) -> Result<mpsc::Receiver<String>> {
+    async fn subscribe_new_blocks(&self) -> AdapterResult<mpsc::Receiver<String>> {
         info!("Starting Sui checkpoint subscription");
         let (tx, rx) = mpsc::channel(1000);
+        let follower = self.checkpoint_follower(CHECKPOINT_STREAM);
 
         // 启动轮询任务来模拟订阅
-        let config = self.config.clone();
-        let client = self.client.clone();
-
         tokio::spawn(async move {
-            let mut last_checkpoint = 0u64;
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+            let mut interval = tokio::time::interval(follower.poll_interval);
+            let mut cursor = None;
 
             loop {
                 interval.tick().await;
 
-                match Self::get_latest_checkpoint(&client, &config.rpc_url).await {
-                    Ok(current_checkpoint) => {
-                        if current_checkpoint > last_checkpoint {
-                            for checkpoint in (last_checkpoint + 1)..=current_checkpoint {
-                                if tx.send(checkpoint.to_string()).await.is_err() {
-                                    warn!("Sui checkpoint subscription channel closed");
-                                    return;
-                                }
-                            }
-                            last_checkpoint = current_checkpoint;
-                        }
-                    }
+                let current_checkpoint = match follower.latest().await {
+                    Ok(current_checkpoint) => current_checkpoint,
                     Err(e) => {
                         error!("Failed to get latest Sui checkpoint: {}", e);
+                        continue;
+                    }
+                };
+                let last_checkpoint =
+                    *cursor.get_or_insert_with(|| follower.resume(current_checkpoint));
+
+                for checkpoint in (last_checkpoint + 1)..=current_checkpoint {
+                    if tx.send(checkpoint.to_string()).await.is_err() {
+                        warn!("Sui checkpoint subscription channel closed");
+                        return;
                     }
+                    follower.commit(checkpoint);
+                    cursor = Some(checkpoint);
                 }
             }
         });
@@ -350,45 +510,74 @@ impl ChainAdapter for SuiAdapter {
         Ok(rx)
     }
 
-    async fn subscribe_new_transactions(&self) -> Result<mpsc::Receiver<String>> {
+    async fn get_transaction_packages(&self, tx_hash: &str) -> AdapterResult<Vec<String>> {
+        let tx_info = self
+            .call_rpc(
+                "sui_getTransactionBlock",
+                json!([tx_hash, { "showInput": true, "showObjectChanges": true }]),
+            )
+            .await?;
+        Ok(referenced_packages(&tx_info))
+    }
+
+    fn health_checker(&self) -> Option<HealthChecker> {
+        let probe = JsonRpcProbe::new(
+            self.client.clone(),
+            ChainType::Sui,
+            "sui_getLatestCheckpointSequenceNumber",
+        );
+        Some(HealthChecker::new(self.endpoints.clone(), Arc::new(probe)))
+    }
+
+    async fn subscribe_new_transactions(&self) -> AdapterResult<mpsc::Receiver<String>> {
         info!("Starting Sui transaction subscription");
         let (tx, rx) = mpsc::channel(1000);
+        let follower = self.checkpoint_follower(TRANSACTION_STREAM);
 
         // 启动轮询任务来获取新交易
-        let config = self.config.clone();
-        let client = self.client.clone();
-
         tokio::spawn(async move {
-            let mut last_checkpoint = 0u64;
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(1));
+            let mut interval = tokio::time::interval(follower.poll_interval);
+            let mut cursor = None;
 
             loop {
                 interval.tick().await;
 
-                match Self::get_latest_checkpoint(&client, &config.rpc_url).await {
-                    Ok(current_checkpoint) => {
-                        if current_checkpoint > last_checkpoint {
-                            // 获取新检查点中的交易
-                            if let Ok(transactions) = Self::get_checkpoint_transactions(
-                                &client,
-                                &config.rpc_url,
-                                current_checkpoint,
-                            )
-                            .await
-                            {
-                                for tx_hash in transactions {
-                                    if tx.send(tx_hash).await.is_err() {
-                                        warn!("Sui transaction subscription channel closed");
-                                        return;
-                                    }
-                                }
-                            }
-                            last_checkpoint = current_checkpoint;
-                        }
-                    }
+                let current_checkpoint = match follower.latest().await {
+                    Ok(current_checkpoint) => current_checkpoint,
                     Err(e) => {
                         error!("Failed to get Sui transactions: {}", e);
+                        continue;
                     }
+                };
+                let last_checkpoint =
+                    *cursor.get_or_insert_with(|| follower.resume(current_checkpoint));
+
+                // 逐个检查点取交易，失败时停在该检查点，下次轮询重试
+                for checkpoint in (last_checkpoint + 1)..=current_checkpoint {
+                    let transactions = match Self::get_checkpoint_transactions(
+                        &follower.client,
+                        &follower.endpoints,
+                        checkpoint,
+                    )
+                    .await
+                    {
+                        Ok(transactions) => transactions,
+                        Err(e) => {
+                            error!(
+                                "Failed to get Sui checkpoint {} transactions: {}",
+                                checkpoint, e
+                            );
+                            break;
+                        }
+                    };
+                    for tx_hash in transactions {
+                        if tx.send(tx_hash).await.is_err() {
+                            warn!("Sui transaction subscription channel closed");
+                            return;
+                        }
+                    }
+                    follower.commit(checkpoint);
+                    cursor = Some(checkpoint);
                 }
             }
         });
@@ -397,54 +586,266 @@ impl ChainAdapter for SuiAdapter {
     }
 }
 
-impl SuiAdapter {
-    /// 获取最新检查点号
-    async fn get_latest_checkpoint(client: &Client, rpc_url: &str) -> Result<u64> {
-        let request = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "sui_getLatestCheckpointSequenceNumber",
-            "params": []
-        });
+/// 交易效果中版本发生变化的对象（创建、修改、解包、删除与包装）
+pub fn object_versions(effects: &Value, checkpoint: u64) -> Vec<ObjectVersionUpdate> {
+    let version = |reference: &Value| {
+        let version = &reference["version"];
+        version.as_u64().or_else(|| version.as_str()?.parse().ok())
+    };
+    let owned = ["created", "mutated", "unwrapped"]
+        .into_iter()
+        .flat_map(|field| effects[field].as_array().into_iter().flatten())
+        .map(|owned| &owned["reference"]);
+    let removed = ["deleted", "wrapped", "unwrappedThenDeleted"]
+        .into_iter()
+        .flat_map(|field| effects[field].as_array().into_iter().flatten());
+    owned
+        .chain(removed)
+        .filter_map(|reference| {
+            Some(ObjectVersionUpdate {
+                object_id: reference["objectId"].as_str()?.to_string(),
+                version: version(reference)?,
+                checkpoint,
+            })
+        })
+        .collect()
+}
 
-        let response = client
-            .post(rpc_url)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
+/// 交易引用的包：Move 调用的目标包、新发布的包，以及变更对象类型（含类型参数）所属的包
+///
+/// 按首次出现的顺序去重，`tx_info` 需包含 `showInput` 与 `showObjectChanges` 的内容
+pub fn referenced_packages(tx_info: &Value) -> Vec<String> {
+    let mut packages: Vec<String> = Vec::new();
+    let mut add = |package: &str| {
+        if !packages.iter().any(|known| known == package) {
+            packages.push(package.to_string());
+        }
+    };
+
+    let commands = &tx_info["transaction"]["data"]["transaction"]["transactions"];
+    for command in commands.as_array().into_iter().flatten() {
+        if let Some(package) = command["MoveCall"]["package"].as_str() {
+            add(package);
+        }
+    }
+    for change in tx_info["objectChanges"].as_array().into_iter().flatten() {
+        if let Some(package) = change["packageId"].as_str() {
+            add(package);
+        }
+        // 类型形如 0xpkg::module::Type<0xother::coin::COIN>
+        let object_type = change["objectType"].as_str().unwrap_or_default();
+        for segment in object_type.split(['<', '>', ',', ' ']) {
+            if let Some((package, _)) = segment.split_once("::") {
+                if package.starts_with("0x") {
+                    add(package);
+                }
+            }
+        }
+    }
+    packages
+}
 
-        let response_json: Value = response.json().await?;
+/// 由 `sui_getTransactionBlock`（含 input、effects 与 events）的结果生成回执
+pub fn parse_transaction_block(tx_hash: &str, tx_info: &Value) -> TransactionReceipt {
+    let digest = tx_info["digest"].as_str().unwrap_or(tx_hash).to_string();
+    let effects = &tx_info["effects"];
+
+    let status = if effects["status"]["status"].as_str() == Some("success") {
+        TransactionStatus::Success
+    } else {
+        TransactionStatus::Failed
+    };
+
+    // 获取 gas 使用量
+    let gas_used = effects["gasUsed"]["computationCost"].as_u64().unwrap_or(0);
+
+    // 获取发送者
+    let sender = tx_info["transaction"]["data"]["sender"]
+        .as_str()
+        .unwrap_or("")
+        .to_string();
+
+    // 解析事件日志
+    let mut logs = vec![];
+    if let Some(events) = tx_info["events"].as_array() {
+        for event in events {
+            logs.push(EventLog {
+                address: event["packageId"].as_str().unwrap_or("").to_string(),
+                topics: vec![event["type"].as_str().unwrap_or("").to_string()],
+                data: event["parsedJson"].to_string(),
+            });
+        }
+    }
 
-        response_json["result"]
+    TransactionReceipt {
+        tx_hash: digest,
+        block_hash: effects["transactionDigest"]
             .as_str()
-            .and_then(|s| s.parse::<u64>().ok())
-            .ok_or_else(|| anyhow::anyhow!("Failed to parse checkpoint number"))
+            .unwrap_or("")
+            .to_string(),
+        block_number: effects["checkpoint"].as_u64().unwrap_or(0),
+        transaction_index: 0, // Sui 不使用传统的交易索引
+        from: sender,
+        to: None, // Sui 交易可能有多个接收者，这里简化处理
+        gas_used,
+        status,
+        logs,
+        contract_address: None,
+    }
+}
+
+/// 解析 `sui_getCheckpoint` 的结果
+pub fn parse_checkpoint(checkpoint: &Value) -> Result<BlockData> {
+    let number = json_u64(&checkpoint["sequenceNumber"])
+        .ok_or_else(|| anyhow::anyhow!("Invalid Sui checkpoint: {}", checkpoint))?;
+    Ok(BlockData {
+        number,
+        hash: checkpoint["digest"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        // 创世检查点没有前一个摘要
+        parent_hash: checkpoint["previousDigest"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        timestamp: json_u64(&checkpoint["timestampMs"]).unwrap_or(0) / 1000,
+        transactions: checkpoint["transactions"]
+            .as_array()
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|tx| tx.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    })
+}
+
+/// 按检查点中的交易顺序组装批量查询的结果
+///
+/// 回执的区块哈希与高度取自检查点，交易序号为交易在检查点中的位置；
+/// 节点未返回或只返回错误的交易记入 `missing`
+pub fn checkpoint_receipts(block: &BlockData, responses: &[Value]) -> BlockReceipts {
+    let by_digest: HashMap<&str, &Value> = responses
+        .iter()
+        .filter(|response| !response["effects"].is_null())
+        .filter_map(|response| response["digest"].as_str().map(|digest| (digest, response)))
+        .collect();
+
+    let mut receipts = BlockReceipts {
+        block_number: block.number,
+        ..Default::default()
+    };
+    for (index, digest) in block.transactions.iter().enumerate() {
+        match by_digest.get(digest.as_str()) {
+            Some(response) => {
+                let mut receipt = parse_transaction_block(digest, response);
+                receipt.block_hash = block.hash.clone();
+                receipt.block_number = block.number;
+                receipt.transaction_index = index as u32;
+                receipts.receipts.push(receipt);
+            }
+            None => receipts.missing.push(digest.clone()),
+        }
+    }
+    receipts
+}
+
+/// 游标键：链、网络与订阅流
+fn cursor_key(network_type: &SuiNetworkType, stream: &str) -> String {
+    format!("sui:{:?}:{}", network_type, stream)
+}
+
+/// 单路订阅的轮询状态，游标在每个检查点投递完成后持久化
+struct CheckpointFollower {
+    client: Client,
+    endpoints: Arc<EndpointPool>,
+    network: String,
+    key: String,
+    store: Option<Arc<dyn CursorStore>>,
+    poll_interval: Duration,
+    gap_warning_threshold: u64,
+}
+
+impl CheckpointFollower {
+    async fn latest(&self) -> Result<u64> {
+        SuiAdapter::get_latest_checkpoint(&self.client, &self.endpoints).await
+    }
+
+    /// 确定起始游标：有持久化游标时从其后继续，否则从当前链头开始
+    fn resume(&self, tip: u64) -> u64 {
+        let stored = match &self.store {
+            Some(store) => store.load(&self.key).unwrap_or_else(|e| {
+                error!("Failed to load Sui cursor {}: {}", self.key, e);
+                None
+            }),
+            None => None,
+        };
+
+        match stored {
+            Some(cursor) => {
+                let behind = tip.saturating_sub(cursor);
+                if behind > self.gap_warning_threshold {
+                    warn!(
+                        network = %self.network,
+                        stream = %self.key,
+                        cursor,
+                        tip,
+                        behind,
+                        "⚠️ Resumed Sui cursor is far behind the tip, catching up"
+                    );
+                } else {
+                    info!("🔁 Resuming Sui {} after checkpoint {}", self.key, cursor);
+                }
+                cursor
+            }
+            None => {
+                info!("▶️ Starting Sui {} at checkpoint {}", self.key, tip);
+                self.commit(tip);
+                tip
+            }
+        }
+    }
+
+    fn commit(&self, checkpoint: u64) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save(&self.key, checkpoint) {
+                error!("Failed to persist Sui cursor {}: {}", self.key, e);
+            }
+        }
+    }
+}
+
+impl SuiAdapter {
+    /// 获取最新检查点号
+    async fn get_latest_checkpoint(client: &Client, endpoints: &EndpointPool) -> Result<u64> {
+        Self::rpc(
+            client,
+            endpoints,
+            "sui_getLatestCheckpointSequenceNumber",
+            json!([]),
+        )
+        .await?
+        .as_str()
+        .and_then(|s| s.parse::<u64>().ok())
+        .ok_or_else(|| anyhow::anyhow!("Failed to parse checkpoint number"))
     }
 
     /// 获取检查点中的交易列表
     async fn get_checkpoint_transactions(
         client: &Client,
-        rpc_url: &str,
+        endpoints: &EndpointPool,
         checkpoint: u64,
     ) -> Result<Vec<String>> {
-        let request = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "sui_getCheckpoint",
-            "params": [checkpoint.to_string()]
-        });
-
-        let response = client
-            .post(rpc_url)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
-
-        let response_json: Value = response.json().await?;
-
-        let transactions = response_json["result"]["transactions"]
+        let checkpoint = Self::rpc(
+            client,
+            endpoints,
+            "sui_getCheckpoint",
+            json!([checkpoint.to_string()]),
+        )
+        .await?;
+
+        let transactions = checkpoint["transactions"]
             .as_array()
             .map(|arr| {
                 arr.iter()
@@ -456,6 +857,115 @@ impl SuiAdapter {
         Ok(transactions)
     }
 
+    /// 订阅对象版本变化：逐个检查点取交易效果，报告其中创建、修改、删除或包装的对象
+    ///
+    /// 从当前链头开始且不持久化游标，供只在内存中保存版本的订阅者使用
+    pub async fn subscribe_object_versions(&self) -> Result<mpsc::Receiver<ObjectVersionUpdate>> {
+        info!("Starting Sui object version subscription");
+        let (tx, rx) = mpsc::channel(1000);
+        let mut follower = self.checkpoint_follower(OBJECT_VERSION_STREAM);
+        follower.store = None;
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(follower.poll_interval);
+            let mut cursor = None;
+
+            loop {
+                interval.tick().await;
+
+                let current_checkpoint = match follower.latest().await {
+                    Ok(current_checkpoint) => current_checkpoint,
+                    Err(e) => {
+                        error!("Failed to get latest Sui checkpoint: {}", e);
+                        continue;
+                    }
+                };
+                let last_checkpoint =
+                    *cursor.get_or_insert_with(|| follower.resume(current_checkpoint));
+
+                // 失败时停在该检查点，下次轮询重试，不会跳过任何版本变化
+                for checkpoint in (last_checkpoint + 1)..=current_checkpoint {
+                    let updates = match Self::get_checkpoint_object_versions(
+                        &follower.client,
+                        &follower.endpoints,
+                        checkpoint,
+                    )
+                    .await
+                    {
+                        Ok(updates) => updates,
+                        Err(e) => {
+                            error!(
+                                "Failed to get Sui checkpoint {} object changes: {}",
+                                checkpoint, e
+                            );
+                            break;
+                        }
+                    };
+                    for update in updates {
+                        if tx.send(update).await.is_err() {
+                            warn!("Sui object version subscription channel closed");
+                            return;
+                        }
+                    }
+                    cursor = Some(checkpoint);
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    /// 检查点内所有交易效果中的对象新版本
+    async fn get_checkpoint_object_versions(
+        client: &Client,
+        endpoints: &EndpointPool,
+        checkpoint: u64,
+    ) -> Result<Vec<ObjectVersionUpdate>> {
+        let digests = Self::get_checkpoint_transactions(client, endpoints, checkpoint).await?;
+        let mut updates = Vec::new();
+        for chunk in digests.chunks(MULTI_GET_LIMIT) {
+            let blocks = Self::rpc(
+                client,
+                endpoints,
+                "sui_multiGetTransactionBlocks",
+                json!([chunk, { "showEffects": true }]),
+            )
+            .await?;
+            for block in blocks.as_array().into_iter().flatten() {
+                updates.extend(object_versions(&block["effects"], checkpoint));
+            }
+        }
+        Ok(updates)
+    }
+
+    /// 按摘要取回交易的执行效果（`effects` 字段）
+    pub async fn get_transaction_effects(&self, digest: &str) -> Result<Value> {
+        let result = self
+            .call_rpc(
+                "sui_getTransactionBlock",
+                json!([digest, { "showEffects": true }]),
+            )
+            .await?;
+        match result.get("effects") {
+            Some(effects) if !effects.is_null() => Ok(effects.clone()),
+            _ => Err(anyhow::anyhow!("Transaction {} has no effects", digest)),
+        }
+    }
+
+    /// 对象在指定历史版本的数据，节点已不保留该版本或对象已删除时返回 `None`
+    pub async fn get_past_object(&self, object_id: &str, version: u64) -> Result<Option<Value>> {
+        let result = self
+            .call_rpc(
+                "sui_tryGetPastObject",
+                json!([object_id, version, { "showContent": true }]),
+            )
+            .await?;
+        Ok(match result["status"].as_str() {
+            Some("VersionFound") => Some(result["details"].clone()),
+            _ => None,
+        })
+    }
+
     /// 获取对象的完整状态数据
     pub async fn get_object_data(&self, object_id: &str) -> Result<Value> {
         info!("Getting complete object data for: {}", object_id);
@@ -522,27 +1032,47 @@ impl SuiAdapter {
         }
     }
 
-    /// 执行 Move 函数调用 (干跑)
-    pub async fn dry_run_transaction(&self, tx_data: &Value) -> Result<Value> {
+    /// 干跑本地构建的交易
+    pub async fn dry_run_transaction(&self, tx_bytes: &[u8]) -> Result<Value> {
         info!("Performing dry run transaction");
 
+        let tx_bytes = base64::engine::general_purpose::STANDARD.encode(tx_bytes);
         let result = self
-            .call_rpc("sui_dryRunTransactionBlock", json!([tx_data]))
+            .call_rpc("sui_dryRunTransactionBlock", json!([tx_bytes]))
             .await?;
 
         debug!("Dry run result: {}", result);
         Ok(result)
     }
 
-    /// 执行实际的 Move 函数调用交易
-    pub async fn execute_transaction(&self, tx_data: &Value, signature: &str) -> Result<String> {
+    /// 提交已签名的交易，返回交易摘要
+    pub async fn execute_transaction(&self, tx_bytes: &[u8], signature: &str) -> Result<String> {
+        let result = self.execute_transaction_block(tx_bytes, signature).await?;
+        let tx_hash = result["digest"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("No transaction hash returned"))?
+            .to_string();
+
+        info!("Transaction executed successfully: {}", tx_hash);
+        Ok(tx_hash)
+    }
+
+    /// 提交已签名的交易，返回完整响应（含 `effects.status`）
+    ///
+    /// 交易被打包但执行失败（如 Move 中止）时 RPC 仍然成功返回，调用方需自行检查执行状态
+    pub async fn execute_transaction_block(
+        &self,
+        tx_bytes: &[u8],
+        signature: &str,
+    ) -> Result<Value> {
         info!("Executing Move transaction");
 
+        let tx_bytes = base64::engine::general_purpose::STANDARD.encode(tx_bytes);
         let result = self
             .call_rpc(
                 "sui_executeTransactionBlock",
                 json!([
-                    tx_data,
+                    tx_bytes,
                     [signature],
                     {
                         "showInput": true,
@@ -556,16 +1086,31 @@ impl SuiAdapter {
             )
             .await?;
 
-        let tx_hash = result["digest"]
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("No transaction hash returned"))?
-            .to_string();
+        debug!("Execution result: {}", result);
+        Ok(result)
+    }
 
-        info!("Transaction executed successfully: {}", tx_hash);
-        Ok(tx_hash)
+    /// 用配置的签名者签名并提交交易
+    pub async fn sign_and_execute_transaction(&self, tx_bytes: &[u8]) -> Result<String> {
+        let signature = self.sign(tx_bytes)?;
+        self.execute_transaction(tx_bytes, &signature).await
+    }
+
+    /// 用配置的签名者签名并提交交易，返回完整响应
+    pub async fn sign_and_execute_transaction_block(&self, tx_bytes: &[u8]) -> Result<Value> {
+        let signature = self.sign(tx_bytes)?;
+        self.execute_transaction_block(tx_bytes, &signature).await
+    }
+
+    fn sign(&self, tx_bytes: &[u8]) -> Result<String> {
+        let signer = self
+            .signer
+            .as_ref()
+            .ok_or(AdapterError::SignerUnavailable(ChainType::Sui))?;
+        signer.sign_transaction(tx_bytes)
     }
 
-    /// 构建 Move 函数调用交易
+    /// 构建单个 Move 调用的交易，返回 `TransactionData` 的 BCS 字节
     pub async fn build_move_call_transaction(
         &self,
         sender: &str,
@@ -575,30 +1120,523 @@ impl SuiAdapter {
         type_arguments: Vec<String>,
         arguments: Vec<Value>,
         gas_budget: u64,
-    ) -> Result<Value> {
+    ) -> Result<Vec<u8>> {
+        let call = MoveCall {
+            package: package_id.to_string(),
+            module: module.to_string(),
+            function: function.to_string(),
+            type_arguments,
+            arguments,
+        };
+        self.build_ptb(sender, &[call], gas_budget).await
+    }
+
+    /// 将多个 Move 调用构建为一个可编程交易块（PTB），所有调用原子执行。
+    /// 在本地按 BCS 编码，返回 `TransactionData` 字节
+    pub async fn build_ptb(
+        &self,
+        sender: &str,
+        calls: &[MoveCall],
+        gas_budget: u64,
+    ) -> Result<Vec<u8>> {
         info!(
-            "Building Move call: {}::{}::{} for sender {}",
-            package_id, module, function, sender
+            "Building PTB with {} Move calls for sender {}",
+            calls.len(),
+            sender
         );
 
-        // 构建 PTB (Programmable Transaction Block)
-        let result = self
+        let mut builder = PtbBuilder::default();
+        for call in calls {
+            let arguments = self.resolve_call_arguments(&mut builder, call).await?;
+            builder.move_call(ProgrammableMoveCall {
+                package: parse_address(&call.package)?,
+                module: call.module.clone(),
+                function: call.function.clone(),
+                type_arguments: call
+                    .type_arguments
+                    .iter()
+                    .map(|tag| tag.parse())
+                    .collect::<Result<_>>()?,
+                arguments,
+            });
+        }
+
+        let price = self.get_reference_gas_price().await?;
+        let payment = self.select_gas_coins(sender, gas_budget, &builder).await?;
+        let sender = parse_address(sender)?;
+        let tx_data = TransactionData {
+            kind: builder.finish(),
+            sender,
+            gas_data: GasData {
+                payment,
+                owner: sender,
+                price,
+                budget: gas_budget,
+            },
+            expiration: None,
+        };
+
+        let tx_bytes = tx_data.to_bcs();
+        debug!("Built PTB: {} bytes", tx_bytes.len());
+        Ok(tx_bytes)
+    }
+
+    /// 按函数签名把 JSON 参数解析为 PTB 输入：对象参数查询其引用，纯值参数编码为 BCS
+    async fn resolve_call_arguments(
+        &self,
+        builder: &mut PtbBuilder,
+        call: &MoveCall,
+    ) -> Result<Vec<Argument>> {
+        let function = self
             .call_rpc(
-                "unsafe_moveCall",
-                json!([
-                    sender,
-                    package_id,
-                    module,
-                    function,
-                    type_arguments,
-                    arguments,
-                    null, // gas_coin
-                    gas_budget.to_string()
-                ]),
+                "sui_getNormalizedMoveFunction",
+                json!([call.package, call.module, call.function]),
             )
             .await?;
+        let params: Vec<&Value> = function["parameters"]
+            .as_array()
+            .map(|params| params.iter().filter(|p| !sui_tx::is_tx_context(p)).collect())
+            .unwrap_or_default();
+        if params.len() != call.arguments.len() {
+            return Err(anyhow::anyhow!(
+                "{}::{} expects {} arguments, got {}",
+                call.module,
+                call.function,
+                params.len(),
+                call.arguments.len()
+            ));
+        }
 
-        debug!("Built transaction: {}", result);
-        Ok(result)
+        let mut arguments = Vec::with_capacity(params.len());
+        for (param, value) in params.into_iter().zip(&call.arguments) {
+            let argument = match sui_tx::object_param(param) {
+                Some(mutable) => {
+                    let object_id = value.as_str().ok_or_else(|| {
+                        anyhow::anyhow!("Object argument must be an object ID, got {}", value)
+                    })?;
+                    builder.object(self.resolve_object_arg(object_id, mutable).await?)
+                }
+                None => {
+                    let mut bytes = Vec::new();
+                    sui_tx::encode_pure(param, value, &mut bytes)?;
+                    builder.pure(bytes)
+                }
+            };
+            arguments.push(argument);
+        }
+        Ok(arguments)
+    }
+
+    /// 共享对象按初始共享版本引用，其余对象按当前版本与摘要引用
+    async fn resolve_object_arg(&self, object_id: &str, mutable: bool) -> Result<ObjectArg> {
+        let object = self.get_object_data(object_id).await?;
+        let data = &object["data"];
+        if data.is_null() {
+            return Err(AdapterError::ObjectNotFound(object_id.to_string()).into());
+        }
+
+        if let Some(version) = json_u64(&data["owner"]["Shared"]["initial_shared_version"]) {
+            return Ok(ObjectArg::Shared {
+                object_id: parse_address(object_id)?,
+                initial_shared_version: version,
+                mutable,
+            });
+        }
+        Ok(ObjectArg::ImmOrOwned(object_ref(data)?))
+    }
+
+    async fn get_reference_gas_price(&self) -> Result<u64> {
+        let price = self
+            .call_rpc("suix_getReferenceGasPrice", json!([]))
+            .await?;
+        json_u64(&price).ok_or_else(|| anyhow::anyhow!("Invalid reference gas price: {}", price))
+    }
+
+    /// 选择余额合计不少于预算的 SUI 币作为 gas，排除已作为输入的对象。
+    /// 发送方没有可用的币时返回空列表，此时交易只能干跑
+    async fn select_gas_coins(
+        &self,
+        owner: &str,
+        gas_budget: u64,
+        builder: &PtbBuilder,
+    ) -> Result<Vec<ObjectRef>> {
+        let coins = self
+            .call_rpc(
+                "suix_getCoins",
+                json!([owner, "0x2::sui::SUI", null, GAS_COIN_PAGE_SIZE]),
+            )
+            .await?;
+
+        let mut payment = Vec::new();
+        let mut total = 0u64;
+        for coin in coins["data"].as_array().into_iter().flatten() {
+            let object = ObjectRef {
+                object_id: parse_address(coin["coinObjectId"].as_str().unwrap_or_default())?,
+                version: json_u64(&coin["version"]).unwrap_or_default(),
+                digest: parse_digest(coin["digest"].as_str().unwrap_or_default())?,
+            };
+            if builder.contains_object(&object.object_id) {
+                continue;
+            }
+            payment.push(object);
+            total = total.saturating_add(json_u64(&coin["balance"]).unwrap_or_default());
+            if total >= gas_budget {
+                return Ok(payment);
+            }
+        }
+
+        if payment.is_empty() {
+            warn!("⚠️ {} owns no SUI coins, transaction can only be dry-run", owner);
+        } else {
+            warn!(
+                "⚠️ {} SUI coins of {} cover only {} of gas budget {}",
+                payment.len(),
+                owner,
+                total,
+                gas_budget
+            );
+        }
+        Ok(payment)
+    }
+}
+
+/// `sui_getObject` 返回的对象引用
+fn object_ref(data: &Value) -> Result<ObjectRef> {
+    Ok(ObjectRef {
+        object_id: parse_address(data["objectId"].as_str().unwrap_or_default())?,
+        version: json_u64(&data["version"])
+            .ok_or_else(|| anyhow::anyhow!("Object has no version: {}", data))?,
+        digest: parse_digest(data["digest"].as_str().unwrap_or_default())?,
+    })
+}
+
+/// Sui JSON-RPC 以字符串或数字表示 u64
+fn json_u64(value: &Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|text| text.parse().ok()))
+}
+
+/// 由包对象 BCS 的 `moduleMap`（模块名 → base64 字节码）与规范化模块生成各模块，按模块名排序
+fn package_modules(bcs: &Value, normalized: &Value) -> Result<Vec<ModuleArtifact>> {
+    let Some(module_map) = bcs["moduleMap"].as_object() else {
+        return Ok(vec![]);
+    };
+
+    let mut names: Vec<&String> = module_map.keys().collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| {
+            let encoded = module_map[name]
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("Module {} bytecode is not a string", name))?;
+            let bytecode = base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .map_err(|e| anyhow::anyhow!("Invalid bytecode for module {}: {}", name, e))?;
+            Ok(ModuleArtifact {
+                name: name.clone(),
+                bytecode,
+                abi: normalized.get(name).map(|module| module.to_string()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::MemoryCursorStore;
+    use crate::endpoint::{HealthCheckConfig, RpcEndpoint};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::time::timeout;
+
+    /// 只实现 `sui_getLatestCheckpointSequenceNumber` 的 JSON-RPC 服务，链头由测试推进
+    async fn serve_checkpoints(tip: Arc<AtomicU64>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let tip = tip.clone();
+                tokio::spawn(async move {
+                    // 读完请求头与请求体后再应答
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    loop {
+                        let n = socket.read(&mut buf).await.unwrap_or(0);
+                        if n == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&buf[..n]);
+                        let text = String::from_utf8_lossy(&request).to_lowercase();
+                        if let Some(end) = text.find("\r\n\r\n") {
+                            let length = text
+                                .lines()
+                                .find_map(|line| line.strip_prefix("content-length:"))
+                                .and_then(|value| value.trim().parse::<usize>().ok())
+                                .unwrap_or(0);
+                            if request.len() >= end + 4 + length {
+                                break;
+                            }
+                        }
+                    }
+                    let body = json!({
+                        "jsonrpc": "2.0",
+                        "id": 1,
+                        "result": tip.load(Ordering::SeqCst).to_string(),
+                    })
+                    .to_string();
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        url
+    }
+
+    async fn adapter(url: &str, store: Arc<MemoryCursorStore>) -> SuiAdapter {
+        SuiAdapter::new(SuiConfig {
+            enabled: true,
+            rpc_url: url.to_string(),
+            ws_url: None,
+            network_type: SuiNetworkType::Localnet,
+            package_ids: vec![],
+            signer: None,
+            rpc_urls: vec![],
+            health_check: HealthCheckConfig::default(),
+        })
+        .await
+        .unwrap()
+        .with_cursor_store(store)
+        .with_poll_interval(Duration::from_millis(10))
+        .with_backfill_rate(1_000)
+    }
+
+    async fn wait_for_cursor(store: &MemoryCursorStore, expected: u64) {
+        let key = cursor_key(&SuiNetworkType::Localnet, CHECKPOINT_STREAM);
+        timeout(Duration::from_secs(5), async {
+            while store.load(&key).unwrap() != Some(expected) {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("cursor was not persisted");
+    }
+
+    async fn take(rx: &mut mpsc::Receiver<String>, count: usize) -> Vec<u64> {
+        let mut delivered = Vec::new();
+        for _ in 0..count {
+            let checkpoint = timeout(Duration::from_secs(5), rx.recv())
+                .await
+                .expect("checkpoint not delivered")
+                .expect("subscription closed");
+            delivered.push(checkpoint.parse().unwrap());
+        }
+        delivered
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_subscription_resumes_after_restart() {
+        let tip = Arc::new(AtomicU64::new(5));
+        let url = serve_checkpoints(tip.clone()).await;
+        let store = Arc::new(MemoryCursorStore::default());
+
+        // 首次启动没有游标，从当前链头之后开始
+        let first = adapter(&url, store.clone()).await;
+        let mut rx = first.subscribe_new_blocks().await.unwrap();
+        wait_for_cursor(&store, 5).await;
+        tip.store(10, Ordering::SeqCst);
+        let mut delivered = take(&mut rx, 5).await;
+        drop(rx);
+        drop(first);
+
+        // 停机期间链头前进，重建适配器后从游标继续
+        tip.store(15, Ordering::SeqCst);
+        let second = adapter(&url, store.clone()).await;
+        let mut rx = second.subscribe_new_blocks().await.unwrap();
+        delivered.extend(take(&mut rx, 5).await);
+        wait_for_cursor(&store, 15).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(rx.try_recv().is_err());
+
+        assert_eq!(delivered, (6..=15).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_backfill_streams_range_without_moving_cursor() {
+        let tip = Arc::new(AtomicU64::new(20));
+        let url = serve_checkpoints(tip).await;
+        let store = Arc::new(MemoryCursorStore::default());
+        let sui = adapter(&url, store.clone()).await;
+
+        let mut rx = sui.backfill(3, 7).await.unwrap();
+        assert_eq!(take(&mut rx, 5).await, vec![3, 4, 5, 6, 7]);
+        assert!(timeout(Duration::from_secs(5), rx.recv()).await.unwrap().is_none());
+
+        assert!(sui.backfill(8, 21).await.is_err());
+        assert!(sui.backfill(9, 8).await.is_err());
+        let key = cursor_key(&SuiNetworkType::Localnet, CHECKPOINT_STREAM);
+        assert_eq!(store.load(&key).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_reads_fail_over_to_healthy_endpoint() {
+        let healthy = serve_checkpoints(Arc::new(AtomicU64::new(42))).await;
+        // 绑定后立即释放的端口，连接被拒绝
+        let dead = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let sui = SuiAdapter::new(SuiConfig {
+            enabled: true,
+            rpc_url: dead.clone(),
+            ws_url: None,
+            network_type: SuiNetworkType::Localnet,
+            package_ids: vec![],
+            signer: None,
+            rpc_urls: vec![RpcEndpoint {
+                url: healthy,
+                weight: 1,
+            }],
+            health_check: HealthCheckConfig {
+                failure_threshold: 2,
+                ..HealthCheckConfig::default()
+            },
+        })
+        .await
+        .unwrap();
+
+        for _ in 0..4 {
+            assert_eq!(sui.get_block_number().await.unwrap(), 42);
+        }
+        let health = sui.health_checker().unwrap().endpoints().health();
+        assert_eq!(health[0].url, dead);
+        assert!(!health[0].healthy);
+        assert_eq!(health[0].failures, 2);
+        assert!(health[1].healthy);
+        assert_eq!(health[1].requests, 4);
+    }
+
+    #[test]
+    fn test_object_versions_from_effects() {
+        let effects = json!({
+            "created": [{"owner": "Immutable", "reference": {"objectId": "0xc", "version": 9, "digest": "d1"}}],
+            "mutated": [{"owner": {"Shared": {"initial_shared_version": 1}}, "reference": {"objectId": "0xa", "version": "9", "digest": "d2"}}],
+            "deleted": [{"objectId": "0xb", "version": 9, "digest": "d3"}],
+        });
+        let updates = object_versions(&effects, 42);
+        let versions: Vec<_> = updates
+            .iter()
+            .map(|update| (update.object_id.as_str(), update.version, update.checkpoint))
+            .collect();
+        assert_eq!(
+            versions,
+            vec![("0xc", 9, 42), ("0xa", 9, 42), ("0xb", 9, 42)]
+        );
+        assert!(object_versions(&Value::Null, 42).is_empty());
+    }
+
+    #[test]
+    fn test_referenced_packages_from_transaction() {
+        let tx_info = json!({
+            "transaction": {"data": {"transaction": {"kind": "ProgrammableTransaction", "transactions": [
+                {"MoveCall": {"package": "0xa", "module": "pool", "function": "swap"}},
+                {"TransferObjects": [[{"Result": 0}], {"Input": 1}]},
+            ]}}},
+            "objectChanges": [
+                {"type": "published", "packageId": "0xc", "version": "1", "modules": ["m"]},
+                {"type": "mutated", "objectId": "0x1f", "objectType": "0xa::pool::Pool<0x2::sui::SUI, 0xb::usdc::USDC>"},
+                {"type": "created", "objectId": "0x20", "objectType": "0xa::pool::Receipt"},
+            ],
+        });
+        assert_eq!(
+            referenced_packages(&tx_info),
+            vec!["0xa", "0xc", "0x2", "0xb"]
+        );
+        assert!(referenced_packages(&Value::Null).is_empty());
+    }
+
+    #[test]
+    fn test_checkpoint_receipts_in_checkpoint_order() {
+        let checkpoint = parse_checkpoint(&json!({
+            "sequenceNumber": "1024",
+            "digest": "CkpDigest",
+            "previousDigest": "PrevDigest",
+            "timestampMs": "1700000000123",
+            "transactions": ["TxA", "TxB", "TxC"],
+        }))
+        .unwrap();
+        assert_eq!(checkpoint.number, 1024);
+        assert_eq!(checkpoint.timestamp, 1_700_000_000);
+        assert_eq!(checkpoint.parent_hash, "PrevDigest");
+
+        // 节点乱序返回；TxB 只有错误，TxC 未返回
+        let responses = vec![
+            json!({
+                "digest": "TxB",
+                "error": { "code": "notExists", "object_id": "TxB" },
+            }),
+            json!({
+                "digest": "TxA",
+                "transaction": { "data": { "sender": "0xabc" } },
+                "effects": {
+                    "status": { "status": "success" },
+                    "gasUsed": { "computationCost": 1000 },
+                },
+                "events": [{
+                    "packageId": "0x2",
+                    "type": "0x2::coin::Minted",
+                    "parsedJson": { "amount": "5" },
+                }],
+            }),
+        ];
+
+        let receipts = checkpoint_receipts(&checkpoint, &responses);
+        assert_eq!(receipts.block_number, 1024);
+        assert_eq!(receipts.receipts.len(), 1);
+        let receipt = &receipts.receipts[0];
+        assert_eq!(receipt.tx_hash, "TxA");
+        assert_eq!(receipt.block_hash, "CkpDigest");
+        assert_eq!(receipt.block_number, 1024);
+        assert_eq!(receipt.from, "0xabc");
+        assert_eq!(receipt.logs[0].topics, vec!["0x2::coin::Minted"]);
+        assert_eq!(receipts.missing, vec!["TxB", "TxC"]);
+
+        assert!(parse_checkpoint(&json!({ "digest": "x" })).is_err());
+    }
+
+    #[test]
+    fn test_package_modules_from_module_map() {
+        let encode = |bytes: &[u8]| base64::engine::general_purpose::STANDARD.encode(bytes);
+        let bcs = json!({
+            "dataType": "package",
+            "moduleMap": {
+                "pool": encode(&[0xa1, 0x1c, 2]),
+                "coin": encode(&[0xa1, 0x1c, 1]),
+            }
+        });
+        let normalized = json!({ "coin": { "exposedFunctions": {} } });
+
+        let modules = package_modules(&bcs, &normalized).unwrap();
+        let names: Vec<&str> = modules.iter().map(|module| module.name.as_str()).collect();
+        assert_eq!(names, vec!["coin", "pool"]);
+        assert_eq!(modules[0].bytecode, vec![0xa1, 0x1c, 1]);
+        assert_eq!(
+            modules[0].abi.as_deref(),
+            Some(r#"{"exposedFunctions":{}}"#)
+        );
+        assert!(modules[1].abi.is_none());
+
+        // 旧格式的十六进制 BCS 不含模块
+        assert!(package_modules(&json!("0xa11c"), &normalized)
+            .unwrap()
+            .is_empty());
+        assert!(package_modules(&json!({ "moduleMap": { "coin": 1 } }), &normalized).is_err());
     }
 }
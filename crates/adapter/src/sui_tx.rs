@@ -0,0 +1,661 @@
+//! Sui 交易本地构建
+//!
+//! 在进程内按 BCS 编码 `TransactionData`，替代全节点的 `unsafe_moveCall` /
+//! `unsafe_batchTransaction`（多数公共全节点已禁用）。布局与 sui-types 一致：
+//!
+//! ```text
+//! TransactionData::V1 { kind: ProgrammableTransaction { inputs, commands },
+//!                       sender, gas_data: { payment, owner, price, budget }, expiration }
+//! ```
+//!
+//! 目前只生成 `MoveCall` 命令；纯值参数按 `sui_getNormalizedMoveFunction`
+//! 返回的参数类型编码。
+
+use anyhow::{anyhow, bail, Result};
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Digest};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// TransactionData 签名意图前缀（scope = TransactionData, version = V0, app_id = Sui）
+pub const TRANSACTION_INTENT: [u8; 3] = [0, 0, 0];
+
+type Blake2b256 = Blake2b<U32>;
+
+/// 32 字节 Sui 地址 / 对象 ID，十六进制不足 64 位时左侧补零
+pub fn parse_address(text: &str) -> Result<[u8; 32]> {
+    let digits = text.strip_prefix("0x").unwrap_or(text);
+    if digits.is_empty() || digits.len() > 64 {
+        bail!("Invalid Sui address: {}", text);
+    }
+    let bytes = hex::decode(format!("{:0>64}", digits))
+        .map_err(|_| anyhow!("Invalid Sui address: {}", text))?;
+    Ok(bytes.try_into().expect("64 hex digits"))
+}
+
+/// Base58 编码的 32 字节摘要（对象摘要、交易摘要）
+pub fn parse_digest(text: &str) -> Result<[u8; 32]> {
+    bs58::decode(text)
+        .into_vec()
+        .map_err(|e| anyhow!("Invalid digest {}: {}", text, e))?
+        .try_into()
+        .map_err(|_| anyhow!("Digest {} is not 32 bytes", text))
+}
+
+fn write_uleb128(mut value: usize, out: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    write_uleb128(bytes.len(), out);
+    out.extend_from_slice(bytes);
+}
+
+/// 对象引用 `(ObjectID, SequenceNumber, ObjectDigest)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectRef {
+    pub object_id: [u8; 32],
+    pub version: u64,
+    pub digest: [u8; 32],
+}
+
+impl ObjectRef {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.object_id);
+        out.extend_from_slice(&self.version.to_le_bytes());
+        // ObjectDigest 按字节序列编码，带长度前缀
+        write_bytes(&self.digest, out);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjectArg {
+    ImmOrOwned(ObjectRef),
+    Shared {
+        object_id: [u8; 32],
+        initial_shared_version: u64,
+        mutable: bool,
+    },
+    Receiving(ObjectRef),
+}
+
+impl ObjectArg {
+    pub fn object_id(&self) -> [u8; 32] {
+        match self {
+            ObjectArg::ImmOrOwned(object) | ObjectArg::Receiving(object) => object.object_id,
+            ObjectArg::Shared { object_id, .. } => *object_id,
+        }
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            ObjectArg::ImmOrOwned(object) => {
+                out.push(0);
+                object.encode(out);
+            }
+            ObjectArg::Shared {
+                object_id,
+                initial_shared_version,
+                mutable,
+            } => {
+                out.push(1);
+                out.extend_from_slice(object_id);
+                out.extend_from_slice(&initial_shared_version.to_le_bytes());
+                out.push(*mutable as u8);
+            }
+            ObjectArg::Receiving(object) => {
+                out.push(2);
+                object.encode(out);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallArg {
+    /// BCS 编码后的纯值
+    Pure(Vec<u8>),
+    Object(ObjectArg),
+}
+
+impl CallArg {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            CallArg::Pure(bytes) => {
+                out.push(0);
+                write_bytes(bytes, out);
+            }
+            CallArg::Object(object) => {
+                out.push(1);
+                object.encode(out);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructTag {
+    pub address: [u8; 32],
+    pub module: String,
+    pub name: String,
+    pub type_params: Vec<TypeTag>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeTag {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Signer,
+    Vector(Box<TypeTag>),
+    Struct(Box<StructTag>),
+}
+
+impl TypeTag {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            TypeTag::Bool => out.push(0),
+            TypeTag::U8 => out.push(1),
+            TypeTag::U64 => out.push(2),
+            TypeTag::U128 => out.push(3),
+            TypeTag::Address => out.push(4),
+            TypeTag::Signer => out.push(5),
+            TypeTag::Vector(inner) => {
+                out.push(6);
+                inner.encode(out);
+            }
+            TypeTag::Struct(tag) => {
+                out.push(7);
+                out.extend_from_slice(&tag.address);
+                write_bytes(tag.module.as_bytes(), out);
+                write_bytes(tag.name.as_bytes(), out);
+                write_uleb128(tag.type_params.len(), out);
+                for param in &tag.type_params {
+                    param.encode(out);
+                }
+            }
+            TypeTag::U16 => out.push(8),
+            TypeTag::U32 => out.push(9),
+            TypeTag::U256 => out.push(10),
+        }
+    }
+}
+
+impl FromStr for TypeTag {
+    type Err = anyhow::Error;
+
+    /// 解析 `u64`、`vector<u8>`、`0x2::coin::Coin<0x2::sui::SUI>` 形式的类型
+    fn from_str(text: &str) -> Result<Self> {
+        let (tag, rest) = parse_type_tag(text)?;
+        if !rest.trim().is_empty() {
+            bail!("Unexpected trailing input in type {}", text);
+        }
+        Ok(tag)
+    }
+}
+
+fn parse_type_tag(text: &str) -> Result<(TypeTag, &str)> {
+    let text = text.trim_start();
+    let end = text
+        .find(|c| c == '<' || c == '>' || c == ',')
+        .unwrap_or(text.len());
+    let head = text[..end].trim();
+    let mut rest = &text[end..];
+
+    let mut params = Vec::new();
+    if let Some(inner) = rest.strip_prefix('<') {
+        rest = inner;
+        loop {
+            let (param, remaining) = parse_type_tag(rest)?;
+            params.push(param);
+            let remaining = remaining.trim_start();
+            if let Some(remaining) = remaining.strip_prefix(',') {
+                rest = remaining;
+            } else if let Some(remaining) = remaining.strip_prefix('>') {
+                rest = remaining;
+                break;
+            } else {
+                bail!("Unterminated type parameters in {}", text);
+            }
+        }
+    }
+
+    let tag = match (head, params.len()) {
+        ("bool", 0) => TypeTag::Bool,
+        ("u8", 0) => TypeTag::U8,
+        ("u16", 0) => TypeTag::U16,
+        ("u32", 0) => TypeTag::U32,
+        ("u64", 0) => TypeTag::U64,
+        ("u128", 0) => TypeTag::U128,
+        ("u256", 0) => TypeTag::U256,
+        ("address", 0) => TypeTag::Address,
+        ("signer", 0) => TypeTag::Signer,
+        ("vector", 1) => TypeTag::Vector(Box::new(params.remove(0))),
+        _ => match head.split("::").collect::<Vec<_>>()[..] {
+            [address, module, name] => TypeTag::Struct(Box::new(StructTag {
+                address: parse_address(address)?,
+                module: module.to_string(),
+                name: name.to_string(),
+                type_params: params,
+            })),
+            _ => bail!("Invalid type tag: {}", head),
+        },
+    };
+    Ok((tag, rest))
+}
+
+/// PTB 命令参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Argument {
+    GasCoin,
+    Input(u16),
+    Result(u16),
+    NestedResult(u16, u16),
+}
+
+impl Argument {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Argument::GasCoin => out.push(0),
+            Argument::Input(index) => {
+                out.push(1);
+                out.extend_from_slice(&index.to_le_bytes());
+            }
+            Argument::Result(index) => {
+                out.push(2);
+                out.extend_from_slice(&index.to_le_bytes());
+            }
+            Argument::NestedResult(index, nested) => {
+                out.push(3);
+                out.extend_from_slice(&index.to_le_bytes());
+                out.extend_from_slice(&nested.to_le_bytes());
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgrammableMoveCall {
+    pub package: [u8; 32],
+    pub module: String,
+    pub function: String,
+    pub type_arguments: Vec<TypeTag>,
+    pub arguments: Vec<Argument>,
+}
+
+/// 只含 MoveCall 命令的可编程交易
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgrammableTransaction {
+    pub inputs: Vec<CallArg>,
+    pub commands: Vec<ProgrammableMoveCall>,
+}
+
+impl ProgrammableTransaction {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_uleb128(self.inputs.len(), out);
+        for input in &self.inputs {
+            input.encode(out);
+        }
+        write_uleb128(self.commands.len(), out);
+        for call in &self.commands {
+            // Command::MoveCall
+            out.push(0);
+            out.extend_from_slice(&call.package);
+            write_bytes(call.module.as_bytes(), out);
+            write_bytes(call.function.as_bytes(), out);
+            write_uleb128(call.type_arguments.len(), out);
+            for tag in &call.type_arguments {
+                tag.encode(out);
+            }
+            write_uleb128(call.arguments.len(), out);
+            for argument in &call.arguments {
+                argument.encode(out);
+            }
+        }
+    }
+}
+
+/// 逐个追加输入与命令；同一对象只登记一次，任一次可变使用即按可变共享
+#[derive(Debug, Default)]
+pub struct PtbBuilder {
+    transaction: ProgrammableTransaction,
+    objects: HashMap<[u8; 32], u16>,
+}
+
+impl PtbBuilder {
+    pub fn pure(&mut self, bytes: Vec<u8>) -> Argument {
+        self.push_input(CallArg::Pure(bytes))
+    }
+
+    pub fn object(&mut self, object: ObjectArg) -> Argument {
+        let object_id = object.object_id();
+        if let Some(&index) = self.objects.get(&object_id) {
+            if let (
+                CallArg::Object(ObjectArg::Shared { mutable, .. }),
+                ObjectArg::Shared { mutable: true, .. },
+            ) = (&mut self.transaction.inputs[index as usize], &object)
+            {
+                *mutable = true;
+            }
+            return Argument::Input(index);
+        }
+        let argument = self.push_input(CallArg::Object(object));
+        if let Argument::Input(index) = argument {
+            self.objects.insert(object_id, index);
+        }
+        argument
+    }
+
+    pub fn move_call(&mut self, call: ProgrammableMoveCall) {
+        self.transaction.commands.push(call);
+    }
+
+    /// 已作为输入的对象，选择 gas 币时需排除
+    pub fn contains_object(&self, object_id: &[u8; 32]) -> bool {
+        self.objects.contains_key(object_id)
+    }
+
+    pub fn finish(self) -> ProgrammableTransaction {
+        self.transaction
+    }
+
+    fn push_input(&mut self, input: CallArg) -> Argument {
+        self.transaction.inputs.push(input);
+        Argument::Input((self.transaction.inputs.len() - 1) as u16)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasData {
+    pub payment: Vec<ObjectRef>,
+    pub owner: [u8; 32],
+    pub price: u64,
+    pub budget: u64,
+}
+
+/// `TransactionData::V1`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionData {
+    pub kind: ProgrammableTransaction,
+    pub sender: [u8; 32],
+    pub gas_data: GasData,
+    /// 过期纪元，`None` 表示不过期
+    pub expiration: Option<u64>,
+}
+
+impl TransactionData {
+    pub fn to_bcs(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        // TransactionData::V1, TransactionKind::ProgrammableTransaction
+        out.push(0);
+        out.push(0);
+        self.kind.encode(&mut out);
+        out.extend_from_slice(&self.sender);
+
+        write_uleb128(self.gas_data.payment.len(), &mut out);
+        for object in &self.gas_data.payment {
+            object.encode(&mut out);
+        }
+        out.extend_from_slice(&self.gas_data.owner);
+        out.extend_from_slice(&self.gas_data.price.to_le_bytes());
+        out.extend_from_slice(&self.gas_data.budget.to_le_bytes());
+
+        match self.expiration {
+            None => out.push(0),
+            Some(epoch) => {
+                out.push(1);
+                out.extend_from_slice(&epoch.to_le_bytes());
+            }
+        }
+        out
+    }
+}
+
+/// 签名摘要：`blake2b256(intent || tx_bytes)`
+pub fn signing_digest(tx_bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Blake2b256::new();
+    hasher.update(TRANSACTION_INTENT);
+    hasher.update(tx_bytes);
+    hasher.finalize().into()
+}
+
+/// 标准化参数类型中的结构体 `(地址, 模块, 名称)` 是否匹配
+fn is_struct(param: &Value, address: &str, module: &str, name: &str) -> bool {
+    let tag = &param["Struct"];
+    tag["module"] == module
+        && tag["name"] == name
+        && tag["address"]
+            .as_str()
+            .and_then(|a| parse_address(a).ok())
+            .zip(parse_address(address).ok())
+            .is_some_and(|(a, b)| a == b)
+}
+
+/// 可按纯值传递的标准库结构体
+fn is_pure_struct(param: &Value) -> bool {
+    is_struct(param, "0x1", "string", "String")
+        || is_struct(param, "0x1", "ascii", "String")
+        || is_struct(param, "0x2", "object", "ID")
+        || is_struct(param, "0x1", "option", "Option")
+}
+
+/// `&TxContext` / `&mut TxContext` 由运行时注入，不出现在调用参数中
+pub fn is_tx_context(param: &Value) -> bool {
+    ["Reference", "MutableReference"]
+        .iter()
+        .any(|kind| is_struct(&param[kind], "0x2", "tx_context", "TxContext"))
+}
+
+/// 对象参数返回 `Some(是否可变)`，纯值参数返回 `None`
+pub fn object_param(param: &Value) -> Option<bool> {
+    if param.get("Reference").is_some() {
+        Some(false)
+    } else if param.get("MutableReference").is_some() || param.get("TypeParameter").is_some() {
+        Some(true)
+    } else if param.get("Struct").is_some() {
+        (!is_pure_struct(param)).then_some(true)
+    } else {
+        None
+    }
+}
+
+/// 按标准化参数类型把 JSON 值编码为 BCS 纯值
+pub fn encode_pure(param: &Value, value: &Value, out: &mut Vec<u8>) -> Result<()> {
+    let mismatch = || anyhow!("Cannot encode {} as {}", value, param);
+    match param {
+        Value::String(kind) => match kind.as_str() {
+            "Bool" => out.push(value.as_bool().ok_or_else(mismatch)? as u8),
+            "U8" | "U16" | "U32" | "U64" | "U128" | "U256" => {
+                let bits: u32 = kind[1..].parse().expect("integer width");
+                let number = match value {
+                    Value::Number(number) => number.as_u64().map(u128::from),
+                    Value::String(text) => text.parse::<u128>().ok(),
+                    _ => None,
+                }
+                .ok_or_else(mismatch)?;
+                if bits < 128 && number >> bits != 0 {
+                    return Err(mismatch());
+                }
+                let le = number.to_le_bytes();
+                match bits {
+                    256 => {
+                        out.extend_from_slice(&le);
+                        out.extend_from_slice(&[0u8; 16]);
+                    }
+                    _ => out.extend_from_slice(&le[..bits as usize / 8]),
+                }
+            }
+            "Address" => out.extend(parse_address(value.as_str().ok_or_else(mismatch)?)?),
+            _ => return Err(mismatch()),
+        },
+        Value::Object(_) if param.get("Vector").is_some() => {
+            let inner = &param["Vector"];
+            match value {
+                // vector<u8> 可直接传十六进制或 UTF-8 字符串
+                Value::String(text) if inner == "U8" => {
+                    let bytes = match text.strip_prefix("0x") {
+                        Some(digits) => hex::decode(digits).map_err(|_| mismatch())?,
+                        None => text.as_bytes().to_vec(),
+                    };
+                    write_bytes(&bytes, out);
+                }
+                Value::Array(items) => {
+                    write_uleb128(items.len(), out);
+                    for item in items {
+                        encode_pure(inner, item, out)?;
+                    }
+                }
+                _ => return Err(mismatch()),
+            }
+        }
+        _ if is_struct(param, "0x1", "string", "String")
+            || is_struct(param, "0x1", "ascii", "String") =>
+        {
+            write_bytes(value.as_str().ok_or_else(mismatch)?.as_bytes(), out)
+        }
+        _ if is_struct(param, "0x2", "object", "ID") => {
+            out.extend(parse_address(value.as_str().ok_or_else(mismatch)?)?)
+        }
+        _ if is_struct(param, "0x1", "option", "Option") => match value {
+            Value::Null => out.push(0),
+            value => {
+                out.push(1);
+                encode_pure(&param["Struct"]["typeArguments"][0], value, out)?;
+            }
+        },
+        _ => return Err(mismatch()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_type_tags() {
+        assert_eq!("u64".parse::<TypeTag>().unwrap(), TypeTag::U64);
+        assert_eq!(
+            "vector<u8>".parse::<TypeTag>().unwrap(),
+            TypeTag::Vector(Box::new(TypeTag::U8))
+        );
+        let coin = "0x2::coin::Coin<0x2::sui::SUI>".parse::<TypeTag>().unwrap();
+        let TypeTag::Struct(tag) = coin else {
+            panic!("expected struct tag");
+        };
+        assert_eq!(tag.address, parse_address("0x2").unwrap());
+        assert_eq!((tag.module.as_str(), tag.name.as_str()), ("coin", "Coin"));
+        assert_eq!(tag.type_params.len(), 1);
+        assert!("vector<u8".parse::<TypeTag>().is_err());
+    }
+
+    #[test]
+    fn test_encode_pure_values() {
+        let mut out = Vec::new();
+        encode_pure(&json!("U64"), &json!("42"), &mut out).unwrap();
+        encode_pure(&json!({"Vector": "U8"}), &json!("0x0102"), &mut out).unwrap();
+        let string = json!({"Struct": {
+            "address": "0x1", "module": "string", "name": "String", "typeArguments": []
+        }});
+        encode_pure(&string, &json!("hi"), &mut out).unwrap();
+        assert_eq!(
+            out,
+            [&42u64.to_le_bytes()[..], &[2, 1, 2], &[2, b'h', b'i']].concat()
+        );
+        assert!(encode_pure(&json!("U8"), &json!(256), &mut out).is_err());
+
+        let counter = json!({"MutableReference": {"Struct": {
+            "address": "0xc0de", "module": "counter", "name": "Counter", "typeArguments": []
+        }}});
+        let ctx = json!({"MutableReference": {"Struct": {
+            "address": "0x2", "module": "tx_context", "name": "TxContext", "typeArguments": []
+        }}});
+        assert_eq!(object_param(&counter), Some(true));
+        assert_eq!(object_param(&string), None);
+        assert!(is_tx_context(&ctx) && !is_tx_context(&counter));
+    }
+
+    /// 包 0x2 上 `counter::set_value(&mut Counter, 42)`，计数器为共享对象
+    fn sample_transaction() -> TransactionData {
+        let mut builder = PtbBuilder::default();
+        let counter = builder.object(ObjectArg::Shared {
+            object_id: [0xaa; 32],
+            initial_shared_version: 7,
+            mutable: false,
+        });
+        let value = builder.pure(42u64.to_le_bytes().to_vec());
+        // 同一对象再次以可变方式使用时复用输入并升级为可变
+        let again = builder.object(ObjectArg::Shared {
+            object_id: [0xaa; 32],
+            initial_shared_version: 7,
+            mutable: true,
+        });
+        assert_eq!(counter, again);
+        builder.move_call(ProgrammableMoveCall {
+            package: parse_address("0x2").unwrap(),
+            module: "counter".to_string(),
+            function: "set_value".to_string(),
+            type_arguments: vec![],
+            arguments: vec![counter, value],
+        });
+
+        TransactionData {
+            kind: builder.finish(),
+            sender: [0x11; 32],
+            gas_data: GasData {
+                payment: vec![ObjectRef {
+                    object_id: [0x22; 32],
+                    version: 3,
+                    digest: [0x33; 32],
+                }],
+                owner: [0x11; 32],
+                price: 1000,
+                budget: 10_000_000,
+            },
+            expiration: None,
+        }
+    }
+
+    #[test]
+    fn test_transaction_data_bcs_vector() {
+        let bytes = sample_transaction().to_bcs();
+        let expected = concat!(
+            "0000",                                                             // V1, ProgrammableTransaction
+            "02",                                                               // 2 inputs
+            "01", "01", "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "0700000000000000", "01",                                          // shared, v7, mutable
+            "00", "08", "2a00000000000000",                                     // pure u64 42
+            "01", "00",                                                         // 1 command: MoveCall
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "07636f756e746572", "097365745f76616c7565", "00",                  // module, function, no types
+            "02", "010000", "010100",                                           // Input(0), Input(1)
+            "1111111111111111111111111111111111111111111111111111111111111111", // sender
+            "01", "2222222222222222222222222222222222222222222222222222222222222222",
+            "0300000000000000",
+            "20", "3333333333333333333333333333333333333333333333333333333333333333",
+            "1111111111111111111111111111111111111111111111111111111111111111", // gas owner
+            "e803000000000000", "8096980000000000",                            // price, budget
+            "00",                                                               // no expiration
+        );
+        assert_eq!(hex::encode(&bytes), expected);
+        assert_eq!(
+            hex::encode(signing_digest(&bytes)),
+            "594aa44312614bfd4533aea321235b131112affdd5e91aa80e9302eddddc6177"
+        );
+    }
+}
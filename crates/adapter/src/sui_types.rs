@@ -291,6 +291,14 @@ pub enum SuiObjectChange {
     },
 }
 
+/// 检查点中某个对象的新版本；删除或被包装的对象同样报告其新版本号
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectVersionUpdate {
+    pub object_id: String,
+    pub version: u64,
+    pub checkpoint: u64,
+}
+
 /// Sui 余额变化
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SuiBalanceChange {
@@ -1,30 +1,189 @@
 //! 适配器通用 trait
 
 use async_trait::async_trait;
-use anyhow::Result;
+use tracing::warn;
+use crate::endpoint::HealthChecker;
+use crate::error::{AdapterError, AdapterResult};
 use crate::types::*;
 
 /// 链适配器通用接口
 #[async_trait]
 pub trait ChainAdapter {
     /// 获取合约元数据 (bytecode + ABI)
-    async fn get_contract_meta(&self, address: &str) -> Result<ContractMeta>;
+    async fn get_contract_meta(&self, address: &str) -> AdapterResult<ContractMeta>;
     
     /// 获取交易回执
-    async fn get_transaction_receipt(&self, tx_hash: &str) -> Result<TransactionReceipt>;
+    async fn get_transaction_receipt(&self, tx_hash: &str) -> AdapterResult<TransactionReceipt>;
     
     /// 获取账户余额
-    async fn get_balance(&self, address: &str) -> Result<u64>;
+    async fn get_balance(&self, address: &str) -> AdapterResult<u64>;
     
     /// 获取账户 nonce
-    async fn get_nonce(&self, address: &str) -> Result<u64>;
+    async fn get_nonce(&self, address: &str) -> AdapterResult<u64>;
     
     /// 获取当前区块高度
-    async fn get_block_number(&self) -> Result<u64>;
+    async fn get_block_number(&self) -> AdapterResult<u64>;
     
     /// 监听新区块（返回区块哈希）
-    async fn subscribe_new_blocks(&self) -> Result<tokio::sync::mpsc::Receiver<String>>;
+    async fn subscribe_new_blocks(&self) -> AdapterResult<tokio::sync::mpsc::Receiver<String>>;
     
     /// 监听新交易（返回交易哈希）
-    async fn subscribe_new_transactions(&self) -> Result<tokio::sync::mpsc::Receiver<String>>;
-} 
\ No newline at end of file
+    async fn subscribe_new_transactions(
+        &self,
+    ) -> AdapterResult<tokio::sync::mpsc::Receiver<String>>;
+
+    /// 交易引用的合约包（调用、发布或其对象类型所属的包），不区分包的链返回空列表
+    async fn get_transaction_packages(&self, _tx_hash: &str) -> AdapterResult<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// 按高度或哈希获取区块头与交易列表；默认不支持
+    async fn get_block(&self, _block: BlockId) -> AdapterResult<BlockData> {
+        Err(AdapterError::Unsupported("get_block".to_string()))
+    }
+
+    /// 获取区块内全部交易的回执，用于批量回填
+    ///
+    /// 默认逐笔调用 [`Self::get_transaction_receipt`]，见 [`receipts_one_by_one`]；
+    /// 支持批量接口的链应覆盖此方法
+    async fn get_block_receipts(&self, number: u64) -> AdapterResult<BlockReceipts> {
+        let block = self.get_block(BlockId::Number(number)).await?;
+        receipts_one_by_one(self, &block).await
+    }
+
+    /// RPC 端点健康检查，未使用端点池的适配器返回 None
+    fn health_checker(&self) -> Option<HealthChecker> {
+        None
+    }
+}
+
+/// 逐笔获取区块内交易的回执
+///
+/// 端点不可用（超时、连接失败、限流）时整个区块失败，调用方稍后重试即可；
+/// 其余错误（如节点已裁剪该交易）只影响这一笔，记入 [`BlockReceipts::missing`]。
+/// 请求按顺序逐笔发出，不会超出端点的并发限制
+pub async fn receipts_one_by_one<A>(adapter: &A, block: &BlockData) -> AdapterResult<BlockReceipts>
+where
+    A: ChainAdapter + Sync + ?Sized,
+{
+    let mut receipts = BlockReceipts {
+        block_number: block.number,
+        ..Default::default()
+    };
+    for tx_hash in &block.transactions {
+        match adapter.get_transaction_receipt(tx_hash).await {
+            Ok(receipt) => receipts.receipts.push(receipt),
+            Err(e) if e.is_transport() => return Err(e),
+            Err(e) => {
+                warn!(
+                    "Receipt for {} in block {} unavailable: {}",
+                    tx_hash, block.number, e
+                );
+                receipts.missing.push(tx_hash.clone());
+            }
+        }
+    }
+    Ok(receipts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    /// 区块 7 含三笔交易：`0xa` 正常，`0xb` 已被节点裁剪，`0xc` 视 `offline` 而定
+    struct PartialNode {
+        offline: bool,
+    }
+
+    #[async_trait]
+    impl ChainAdapter for PartialNode {
+        async fn get_contract_meta(&self, address: &str) -> AdapterResult<ContractMeta> {
+            Err(AdapterError::ContractNotFound {
+                chain: ChainType::Ethereum,
+                address: address.to_string(),
+            })
+        }
+
+        async fn get_transaction_receipt(
+            &self,
+            tx_hash: &str,
+        ) -> AdapterResult<TransactionReceipt> {
+            match tx_hash {
+                "0xb" => Err(AdapterError::Rpc {
+                    chain: ChainType::Ethereum,
+                    message: "transaction pruned".to_string(),
+                }),
+                "0xc" if self.offline => {
+                    // 连接被拒绝的端口，得到真实的传输错误
+                    let source = reqwest::get("http://127.0.0.1:1").await.unwrap_err();
+                    Err(AdapterError::transport(ChainType::Ethereum, source))
+                }
+                _ => Ok(TransactionReceipt {
+                    tx_hash: tx_hash.to_string(),
+                    block_hash: "0x7".to_string(),
+                    block_number: 7,
+                    transaction_index: 0,
+                    from: "0xsender".to_string(),
+                    to: None,
+                    gas_used: 21_000,
+                    status: TransactionStatus::Success,
+                    logs: vec![],
+                    contract_address: None,
+                }),
+            }
+        }
+
+        async fn get_balance(&self, _address: &str) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn get_nonce(&self, _address: &str) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn get_block_number(&self) -> AdapterResult<u64> {
+            Ok(7)
+        }
+
+        async fn subscribe_new_blocks(&self) -> AdapterResult<mpsc::Receiver<String>> {
+            Ok(mpsc::channel(1).1)
+        }
+
+        async fn subscribe_new_transactions(&self) -> AdapterResult<mpsc::Receiver<String>> {
+            Ok(mpsc::channel(1).1)
+        }
+
+        async fn get_block(&self, block: BlockId) -> AdapterResult<BlockData> {
+            assert_eq!(block, BlockId::Number(7));
+            Ok(BlockData {
+                number: 7,
+                hash: "0x7".to_string(),
+                parent_hash: "0x6".to_string(),
+                timestamp: 1_700_000_000,
+                transactions: vec!["0xa".to_string(), "0xb".to_string(), "0xc".to_string()],
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_block_receipts_records_missing() {
+        let node = PartialNode { offline: false };
+        let receipts = node.get_block_receipts(7).await.unwrap();
+        let hashes: Vec<&str> = receipts
+            .receipts
+            .iter()
+            .map(|r| r.tx_hash.as_str())
+            .collect();
+        assert_eq!(receipts.block_number, 7);
+        assert_eq!(hashes, vec!["0xa", "0xc"]);
+        assert_eq!(receipts.missing, vec!["0xb".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_default_block_receipts_fails_on_transport_error() {
+        let node = PartialNode { offline: true };
+        let error = node.get_block_receipts(7).await.unwrap_err();
+        assert!(error.is_transport());
+    }
+}
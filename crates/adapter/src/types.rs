@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::endpoint::{HealthCheckConfig, RpcEndpoint};
+
 /// 支持的区块链类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ChainType {
@@ -13,12 +15,13 @@ pub enum ChainType {
 }
 
 /// 合约类型
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ContractType {
     EVM,    // Ethereum Virtual Machine
     Move,   // Aptos/Sui Move
     BPF,    // Solana Berkeley Packet Filter
     Script, // Bitcoin Script
+    WASM,   // WebAssembly (CosmWasm / NEAR)
 }
 
 /// 统一的合约元数据结构
@@ -33,6 +36,26 @@ pub struct ContractMeta {
     pub compiler_version: Option<String>,
     pub created_at: u64,         // 创建时间戳
     pub creator: Option<String>, // 创建者地址
+    #[serde(default)]
+    pub abi_source: Option<AbiSource>, // ABI 来源
+    /// 多模块 Move 包的各模块（按模块名排序）；`bytecode` 仍保留全部模块拼接后的字节
+    #[serde(default)]
+    pub modules: Vec<ModuleArtifact>,
+}
+
+/// Move 包中的单个模块
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleArtifact {
+    pub name: String,
+    pub bytecode: Vec<u8>,
+    pub abi: Option<String>, // 规范化模块的 JSON
+}
+
+/// ABI 来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AbiSource {
+    Verified,  // 区块浏览器上已验证的源码
+    Heuristic, // 从字节码的函数分发表推断（仅含选择器）
 }
 
 /// 交易回执
@@ -66,7 +89,38 @@ pub struct EventLog {
     pub data: String,
 }
 
+/// 区块标识
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlockId {
+    Number(u64), // 区块高度（Sui 为检查点序号，Solana 为 slot）
+    Hash(String),
+}
+
+/// 区块头字段与区块内的交易哈希（按区块内顺序）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockData {
+    pub number: u64,
+    pub hash: String,
+    pub parent_hash: String,
+    pub timestamp: u64, // 秒
+    pub transactions: Vec<String>,
+}
+
+/// 一个区块的交易回执
+///
+/// 链上查不到的单笔回执不会让整个区块失败，而是记入 `missing`，由调用方决定是否补取
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BlockReceipts {
+    pub block_number: u64,
+    /// 按区块内顺序排列
+    pub receipts: Vec<TransactionReceipt>,
+    /// 未取到回执的交易哈希
+    pub missing: Vec<String>,
+}
+
 /// 适配器配置
+///
+/// 每条链一个配置段，未配置或 `enabled = false` 的链不会初始化
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AdapterConfig {
     pub ethereum: Option<EthereumConfig>,
@@ -74,34 +128,79 @@ pub struct AdapterConfig {
     pub aptos: Option<AptosConfig>,
     pub sui: Option<SuiConfig>,
     pub bitcoin: Option<BitcoinConfig>,
+    /// 任一已启用链初始化失败时拒绝启动；默认降级该链并在后台重试
+    #[serde(default)]
+    pub strict_startup: bool,
+}
+
+fn default_enabled() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EthereumConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
     pub rpc_url: String,
     pub ws_url: Option<String>,
     pub chain_id: u64,
+    #[serde(default)]
+    pub explorer_api_url: Option<String>, // Etherscan 兼容的已验证源码 API
+    #[serde(default)]
+    pub explorer_api_key: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SolanaConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
     pub rpc_url: String,
     pub ws_url: Option<String>,
     pub commitment: String, // finalized, confirmed, processed
+    #[serde(default)]
+    pub rpc_urls: Vec<RpcEndpoint>, // 备用端点，与 rpc_url 一起按权重轮询
+    #[serde(default)]
+    pub health_check: HealthCheckConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AptosConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
     pub rpc_url: String,
     pub faucet_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SuiConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
     pub rpc_url: String,
     pub ws_url: Option<String>,
     pub network_type: SuiNetworkType,
     pub package_ids: Vec<String>, // 用户配置的包ID列表
+    #[serde(default)]
+    pub signer: Option<SignerConfig>, // 交易签名私钥，未配置时只能干跑
+    #[serde(default)]
+    pub rpc_urls: Vec<RpcEndpoint>, // 备用端点，与 rpc_url 一起按权重轮询
+    #[serde(default)]
+    pub health_check: HealthCheckConfig,
+}
+
+/// 签名密钥（节点加密密钥库 `security.keystore` 中的 Ed25519 密钥）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignerConfig {
+    pub key_name: String, // 密钥库中的密钥名称
+}
+
+/// PTB 中的一次 Move 调用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MoveCall {
+    pub package: String,
+    pub module: String,
+    pub function: String,
+    pub type_arguments: Vec<String>,
+    pub arguments: Vec<serde_json::Value>,
 }
 
 /// Sui 网络类型
@@ -115,7 +214,34 @@ pub enum SuiNetworkType {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BitcoinConfig {
-    pub rpc_url: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub rpc_url: String, // bitcoind RPC 地址，或 Esplora API 根地址
     pub rpc_user: String,
     pub rpc_password: String,
+    #[serde(default)]
+    pub backend: BitcoinBackend,
+}
+
+/// Bitcoin 数据源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BitcoinBackend {
+    #[default]
+    BitcoindRpc, // bitcoind JSON-RPC（需要钱包以跟踪观察地址）
+    Esplora,     // Esplora 兼容的 HTTP API
+}
+
+/// 链事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChainEventKind {
+    NewBlock,       // payload 为区块哈希
+    NewTransaction, // payload 为交易哈希
+}
+
+/// 适配器订阅汇总到事件总线的链事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainEvent {
+    pub chain_type: ChainType,
+    pub kind: ChainEventKind,
+    pub payload: String,
 }
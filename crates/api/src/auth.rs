@@ -0,0 +1,530 @@
+//! 请求认证与限流
+//!
+//! API Key 以 SHA-256 摘要形式配置，请求通过 `Authorization: Bearer <key>` 携带。
+//! 令牌桶按调用方（API Key 或未认证时的 IP）和方法类别（读 / 执行）计数，
+//! 同一个 Authenticator 由 HTTP 与 WebSocket 服务器共享，切换传输方式不会获得额外额度。
+//! 威胁检测命中时可在冷却期内封禁某个调用方，封禁期间其所有请求都被限流。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+use dubhe_security::{Permission, Principal, Throttle};
+
+/// 超出限流时的 JSON-RPC 错误码（EIP-1474 Limit exceeded）
+pub const RATE_LIMITED_CODE: i64 = -32005;
+
+/// 认证失败的 JSON-RPC 错误码
+pub const UNAUTHORIZED_CODE: i64 = -32010;
+
+/// 调用方没有执行特权方法所需权限的 JSON-RPC 错误码
+pub const FORBIDDEN_CODE: i64 = -32011;
+
+/// 限流表最多跟踪的调用方数，超过后清理已回满的桶
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+/// 建议重试间隔的上限，速率极低时不至于给出无意义的等待时长
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(3600);
+
+/// 认证配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthConfig {
+    /// 已签发的 API Key；为空时不做认证，所有调用方按 IP 限流
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfig>,
+    #[serde(default)]
+    pub rate_limits: RateLimitConfig,
+}
+
+/// 单个 API Key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyConfig {
+    pub name: String,
+    /// Key 的 SHA-256 十六进制摘要，见 [`hash_api_key`]
+    pub key_hash: String,
+}
+
+/// 各方法类别的令牌桶参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    pub read: BucketConfig,
+    pub execute: BucketConfig,
+}
+
+impl RateLimitConfig {
+    /// 补充速率必须是有限的正数，否则桶永远不会回满
+    pub fn validate(&self) -> Result<(), String> {
+        for (class, bucket) in [("read", &self.read), ("execute", &self.execute)] {
+            if !(bucket.per_second.is_finite() && bucket.per_second > 0.0) {
+                return Err(format!(
+                    "rate_limits.{}.per_second must be a positive number, got {}",
+                    class, bucket.per_second
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            read: BucketConfig {
+                burst: 100,
+                per_second: 50.0,
+            },
+            execute: BucketConfig {
+                burst: 10,
+                per_second: 2.0,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BucketConfig {
+    /// 桶容量（允许的突发请求数）
+    pub burst: u32,
+    /// 每秒补充的令牌数
+    pub per_second: f64,
+}
+
+/// 方法类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MethodClass {
+    Read,
+    Execute, // 触发执行、编译或提交交易的方法
+}
+
+impl MethodClass {
+    pub fn of(method: &str) -> Self {
+        match method {
+            "eth_call"
+            | "eth_estimateGas"
+            | "debug_traceCall"
+            | "eth_sendRawTransaction"
+            | "dubhe_loadContract"
+            | "dubhe_executeOffchain"
+            | "dubhe_replayTransaction"
+            | "dubhe_reloadAlertRules"
+            | "dubhe_reloadConfig"
+            | "dubhe_createSnapshot" => Self::Execute,
+            _ => Self::Read,
+        }
+    }
+}
+
+/// 特权方法在分发前需要的权限，由访问控制检查
+pub fn required_permission(method: &str) -> Option<Permission> {
+    match method {
+        "dubhe_reloadAlertRules" => Some(Permission::ReloadAlertRules),
+        "dubhe_reloadConfig" => Some(Permission::ReloadConfig),
+        "dubhe_createSnapshot" => Some(Permission::CreateSnapshot),
+        "dubhe_invalidateCache" => Some(Permission::InvalidateCache),
+        "dubhe_loadPlugin" => Some(Permission::LoadPlugin),
+        "dubhe_unloadPlugin" => Some(Permission::UnloadPlugin),
+        _ => None,
+    }
+}
+
+/// 调用方身份
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Caller {
+    ApiKey(String), // Key 名称
+    Ip(IpAddr),
+}
+
+impl Caller {
+    /// 访问控制中的主体，未认证的调用方为匿名主体
+    pub fn principal(&self) -> Principal {
+        match self {
+            Self::ApiKey(name) => Principal::ApiKey(name.clone()),
+            Self::Ip(_) => Principal::Anonymous,
+        }
+    }
+
+    /// 封禁时使用的标识：API Key 名称或 IP
+    pub fn subject(&self) -> String {
+        match self {
+            Self::ApiKey(name) => name.clone(),
+            Self::Ip(ip) => ip.to_string(),
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum AuthError {
+    #[error("Invalid API key")]
+    InvalidKey,
+
+    #[error("Method {0} requires an API key")]
+    KeyRequired(String),
+
+    #[error("Rate limit exceeded, retry after {}ms", retry_after.as_millis())]
+    RateLimited { retry_after: Duration },
+
+    #[error("{0}")]
+    Forbidden(String),
+}
+
+impl AuthError {
+    pub fn code(&self) -> i64 {
+        match self {
+            Self::RateLimited { .. } => RATE_LIMITED_CODE,
+            Self::Forbidden(_) => FORBIDDEN_CODE,
+            _ => UNAUTHORIZED_CODE,
+        }
+    }
+
+    /// JSON-RPC error 对象，限流时带 retryAfterMs
+    pub fn to_error_object(&self) -> Value {
+        match self {
+            Self::RateLimited { retry_after } => json!({
+                "code": self.code(),
+                "message": self.to_string(),
+                "data": { "retryAfterMs": retry_after.as_millis() as u64 },
+            }),
+            _ => json!({ "code": self.code(), "message": self.to_string() }),
+        }
+    }
+}
+
+/// API Key 的摘要，配置文件中只保存该值
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    last: Instant,
+}
+
+/// 按 (调用方, 方法类别) 计数的令牌桶限流器
+pub struct RateLimiter {
+    config: RwLock<RateLimitConfig>,
+    buckets: Mutex<HashMap<(Caller, MethodClass), TokenBucket>>,
+    // 调用方标识 → 封禁结束时间
+    blocked: Mutex<HashMap<String, Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            buckets: Mutex::new(HashMap::new()),
+            blocked: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 封禁调用方（API Key 名称或 IP）直到 `until`
+    pub fn block_until(&self, subject: &str, until: Instant) {
+        self.blocked
+            .lock()
+            .unwrap()
+            .insert(subject.to_string(), until);
+    }
+
+    /// 提前解除封禁
+    pub fn unblock(&self, subject: &str) {
+        self.blocked.lock().unwrap().remove(subject);
+    }
+
+    /// 替换令牌桶参数；已有的桶保留余量，按新速率与容量继续补充
+    pub fn update(&self, config: RateLimitConfig) {
+        *self.config.write().unwrap() = config;
+    }
+
+    /// 消耗一个令牌；不足时返回需要等待的时长
+    pub fn check(&self, caller: &Caller, class: MethodClass) -> Result<(), Duration> {
+        self.check_at(caller, class, Instant::now())
+    }
+
+    pub fn check_at(
+        &self,
+        caller: &Caller,
+        class: MethodClass,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        {
+            let mut blocked = self.blocked.lock().unwrap();
+            let subject = caller.subject();
+            match blocked.get(&subject) {
+                Some(until) if *until > now => return Err(until.saturating_duration_since(now)),
+                Some(_) => {
+                    blocked.remove(&subject);
+                }
+                None => {}
+            }
+        }
+
+        let config = self.config.read().unwrap().clone();
+        let limits = limits_of(&config, class);
+        let capacity = f64::from(limits.burst.max(1));
+        let rate = limits.per_second.max(f64::MIN_POSITIVE);
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_TRACKED_BUCKETS {
+            buckets.retain(|(_, class), bucket| {
+                let limits = limits_of(&config, *class);
+                let refilled = bucket.tokens
+                    + now.saturating_duration_since(bucket.last).as_secs_f64() * limits.per_second;
+                refilled < f64::from(limits.burst)
+            });
+        }
+
+        let bucket = buckets
+            .entry((caller.clone(), class))
+            .or_insert(TokenBucket {
+                tokens: capacity,
+                last: now,
+            });
+
+        let elapsed = now.saturating_duration_since(bucket.last).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.last = bucket.last.max(now);
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait = Duration::try_from_secs_f64((1.0 - bucket.tokens) / rate)
+                .unwrap_or(MAX_RETRY_AFTER);
+            Err(wait.min(MAX_RETRY_AFTER))
+        }
+    }
+}
+
+fn limits_of(config: &RateLimitConfig, class: MethodClass) -> &BucketConfig {
+    match class {
+        MethodClass::Read => &config.read,
+        MethodClass::Execute => &config.execute,
+    }
+}
+
+/// 认证与限流入口
+pub struct Authenticator {
+    // 摘要 -> Key 名称
+    keys: HashMap<String, String>,
+    limiter: RateLimiter,
+}
+
+impl Authenticator {
+    pub fn new(config: &AuthConfig) -> Self {
+        Self {
+            keys: config
+                .api_keys
+                .iter()
+                .map(|key| (key.key_hash.to_lowercase(), key.name.clone()))
+                .collect(),
+            limiter: RateLimiter::new(config.rate_limits.clone()),
+        }
+    }
+
+    /// 是否配置了 API Key
+    pub fn enabled(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    /// 根据 Authorization 头识别调用方，未携带时按 IP 识别
+    pub fn identify(&self, authorization: Option<&str>, ip: IpAddr) -> Result<Caller, AuthError> {
+        let Some(header) = authorization else {
+            return Ok(Caller::Ip(ip));
+        };
+
+        let key = header.strip_prefix("Bearer ").unwrap_or(header).trim();
+        self.keys
+            .get(&hash_api_key(key))
+            .map(|name| Caller::ApiKey(name.clone()))
+            .ok_or(AuthError::InvalidKey)
+    }
+
+    /// 热加载限流参数，HTTP 与 WebSocket 同时生效
+    pub fn update_rate_limits(&self, config: RateLimitConfig) {
+        self.limiter.update(config);
+    }
+
+    /// 检查调用方能否调用该方法，并消耗对应类别的令牌
+    pub fn authorize(&self, caller: &Caller, method: &str) -> Result<(), AuthError> {
+        let class = MethodClass::of(method);
+        // 配置了 Key 时，未认证的调用方只能使用只读方法
+        if self.enabled() && class == MethodClass::Execute && matches!(caller, Caller::Ip(_)) {
+            return Err(AuthError::KeyRequired(method.to_string()));
+        }
+
+        self.limiter
+            .check(caller, class)
+            .map_err(|retry_after| AuthError::RateLimited { retry_after })
+    }
+}
+
+/// 威胁检测的限流钩子：在冷却期内封禁命中规则的 API Key 或 IP
+impl Throttle for Authenticator {
+    fn throttle(&self, source: &str, cooldown: Duration) {
+        self.limiter.block_until(source, Instant::now() + cooldown);
+    }
+
+    fn release(&self, source: &str) {
+        self.limiter.unblock(source);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn limiter(burst: u32, per_second: f64) -> RateLimiter {
+        let bucket = BucketConfig { burst, per_second };
+        RateLimiter::new(RateLimitConfig {
+            read: bucket.clone(),
+            execute: bucket,
+        })
+    }
+
+    #[test]
+    fn test_concurrent_requests_share_one_bucket() {
+        let limiter = Arc::new(limiter(10, 5.0));
+        let caller = Caller::ApiKey("team".to_string());
+        let now = Instant::now();
+        let granted = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let (limiter, caller, granted) = (limiter.clone(), caller.clone(), granted.clone());
+                std::thread::spawn(move || {
+                    for _ in 0..10 {
+                        if limiter.check_at(&caller, MethodClass::Read, now).is_ok() {
+                            granted.fetch_add(1, Ordering::SeqCst);
+                        }
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(granted.load(Ordering::SeqCst), 10);
+
+        // 200ms 补充一个令牌
+        let retry = limiter
+            .check_at(&caller, MethodClass::Read, now)
+            .unwrap_err();
+        assert_eq!(retry, Duration::from_millis(200));
+        let later = now + Duration::from_millis(200);
+        assert!(limiter.check_at(&caller, MethodClass::Read, later).is_ok());
+        assert!(limiter.check_at(&caller, MethodClass::Read, later).is_err());
+
+        // 长时间空闲后最多回满到容量
+        let idle = now + Duration::from_secs(60);
+        let refilled = (0..20)
+            .filter(|_| limiter.check_at(&caller, MethodClass::Read, idle).is_ok())
+            .count();
+        assert_eq!(refilled, 10);
+    }
+
+    #[test]
+    fn test_non_positive_rate_does_not_panic() {
+        let now = Instant::now();
+        let caller = Caller::ApiKey("team".to_string());
+        for per_second in [0.0, -1.0, f64::NAN, 1e-300] {
+            let limiter = limiter(1, per_second);
+            assert!(limiter.check_at(&caller, MethodClass::Read, now).is_ok());
+            let retry = limiter
+                .check_at(&caller, MethodClass::Read, now)
+                .unwrap_err();
+            assert_eq!(retry, MAX_RETRY_AFTER);
+
+            let bucket = BucketConfig {
+                burst: 1,
+                per_second,
+            };
+            let config = RateLimitConfig {
+                read: bucket.clone(),
+                execute: bucket,
+            };
+            if per_second > 0.0 {
+                assert!(config.validate().is_ok());
+            } else {
+                assert!(config.validate().is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_classes_and_callers_are_independent() {
+        let limiter = limiter(1, 1.0);
+        let now = Instant::now();
+        let alice = Caller::ApiKey("alice".to_string());
+        let ip = Caller::Ip("10.0.0.1".parse().unwrap());
+
+        assert!(limiter.check_at(&alice, MethodClass::Read, now).is_ok());
+        assert!(limiter.check_at(&alice, MethodClass::Read, now).is_err());
+        assert!(limiter.check_at(&alice, MethodClass::Execute, now).is_ok());
+        assert!(limiter.check_at(&ip, MethodClass::Read, now).is_ok());
+    }
+
+    #[test]
+    fn test_blocked_caller_is_limited_until_released() {
+        let limiter = limiter(100, 100.0);
+        let now = Instant::now();
+        let spammer = Caller::ApiKey("spammer".to_string());
+        let ip = Caller::Ip("10.0.0.1".parse().unwrap());
+
+        limiter.block_until("spammer", now + Duration::from_secs(30));
+        limiter.block_until("10.0.0.1", now + Duration::from_secs(30));
+        assert_eq!(
+            limiter.check_at(&spammer, MethodClass::Read, now),
+            Err(Duration::from_secs(30))
+        );
+        assert!(limiter.check_at(&ip, MethodClass::Execute, now).is_err());
+        assert!(limiter
+            .check_at(&Caller::ApiKey("alice".to_string()), MethodClass::Read, now)
+            .is_ok());
+
+        // 到期自动解除，也可提前解除
+        let later = now + Duration::from_secs(30);
+        assert!(limiter.check_at(&spammer, MethodClass::Read, later).is_ok());
+        limiter.unblock("10.0.0.1");
+        assert!(limiter.check_at(&ip, MethodClass::Execute, now).is_ok());
+    }
+
+    #[test]
+    fn test_authenticator() {
+        let auth = Authenticator::new(&AuthConfig {
+            api_keys: vec![ApiKeyConfig {
+                name: "ops".to_string(),
+                key_hash: hash_api_key("secret"),
+            }],
+            rate_limits: RateLimitConfig::default(),
+        });
+        let ip: IpAddr = "127.0.0.1".parse().unwrap();
+
+        let caller = auth.identify(Some("Bearer secret"), ip).unwrap();
+        assert_eq!(caller, Caller::ApiKey("ops".to_string()));
+        assert!(auth.authorize(&caller, "dubhe_loadContract").is_ok());
+
+        assert_eq!(
+            auth.identify(Some("Bearer wrong"), ip),
+            Err(AuthError::InvalidKey)
+        );
+
+        let anonymous = auth.identify(None, ip).unwrap();
+        assert!(auth.authorize(&anonymous, "eth_blockNumber").is_ok());
+        let error = auth.authorize(&anonymous, "eth_call").unwrap_err();
+        assert_eq!(error.code(), UNAUTHORIZED_CODE);
+
+        let limited = AuthError::RateLimited {
+            retry_after: Duration::from_millis(1500),
+        };
+        let object = limited.to_error_object();
+        assert_eq!(object["code"], RATE_LIMITED_CODE);
+        assert_eq!(object["data"]["retryAfterMs"], 1500);
+    }
+}
@@ -1,7 +1,82 @@
-//! API 错误类型
+//! API 错误类型与 JSON-RPC 错误码映射
+//!
+//! 各 crate 的错误在产生处以类型化枚举构造，经 `anyhow` 错误链原样传到这里，
+//! 由 [`to_rpc_error`] 转换为稳定的错误码与机器可读的 `data`。适配器、VM 与调度器
+//! 把未分类的错误包在各自的 `Internal` 变体中，映射时沿被包装的错误链继续查找：
+//! `{"kind": <变体名>, ...驼峰命名的字段}`
+//!
+//! | 错误码 | 含义 |
+//! |---|---|
+//! | -32700 ~ -32600 | JSON-RPC 协议错误（解析失败、非法请求、方法不存在、参数错误、内部错误） |
+//! | 3 | 执行回滚，`data` 为回滚数据 |
+//! | -32000 | gas 耗尽 |
+//! | -32001 | 调用超时 |
+//! | -32002 / -32003 | 交易池已满 / 交易被拒绝 |
+//! | -32005 | 限流 |
+//! | -32010 / -32011 | 未认证 / 无权限 |
+//! | -32020 ~ -32029 | 链适配器 |
+//! | -32030 ~ -32039 | 合约加载与编译 |
+//! | -32040 ~ -32049 | VM 运行时 |
+//! | -32050 ~ -32059 | 调度器 |
+//! | -32060 ~ -32069 | 索引查询 |
 
+use jsonrpc_core::{Error as RpcError, ErrorCode};
+use serde_json::{json, Value};
 use thiserror::Error;
 
+use crate::ingress::{MEMPOOL_FULL_CODE, TRANSACTION_REJECTED_CODE};
+use dubhe_adapter::AdapterError;
+use dubhe_loader::LoaderError;
+use dubhe_scheduler::{MempoolError, SchedulerError};
+use dubhe_state::LogQueryError;
+use dubhe_vm_runtime::VmError;
+
+/// gas 耗尽（与 geth 一致）
+pub const OUT_OF_GAS_CODE: i64 = -32000;
+
+/// 没有对应链的适配器
+pub const ADAPTER_NOT_FOUND_CODE: i64 = -32020;
+/// 合约或对象在链上不存在
+pub const NOT_FOUND_CODE: i64 = -32021;
+/// 上游链 RPC 超时
+pub const UPSTREAM_TIMEOUT_CODE: i64 = -32022;
+/// 上游链 RPC 传输失败或返回错误
+pub const UPSTREAM_ERROR_CODE: i64 = -32023;
+/// 未配置签名者
+pub const SIGNER_UNAVAILABLE_CODE: i64 = -32024;
+
+/// 字节码不合法或合约类型不受支持
+pub const INVALID_CONTRACT_CODE: i64 = -32030;
+/// 编译或插件失败
+pub const COMPILATION_FAILED_CODE: i64 = -32031;
+/// 入口函数或参数不匹配
+pub const INVALID_ENTRY_CALL_CODE: i64 = -32032;
+/// 离线产物包被拒绝
+pub const ARTIFACT_REJECTED_CODE: i64 = -32033;
+/// 缓存、存储等加载器内部错误
+pub const LOADER_ERROR_CODE: i64 = -32039;
+
+/// 超出 VM 资源限制
+pub const RESOURCE_LIMIT_CODE: i64 = -32040;
+/// VM 执行失败
+pub const VM_EXECUTION_FAILED_CODE: i64 = -32041;
+/// VM 类型或能力不可用
+pub const VM_UNAVAILABLE_CODE: i64 = -32042;
+/// 代码加载、快照等 VM 内部错误
+pub const VM_ERROR_CODE: i64 = -32049;
+
+/// 交易执行超时
+pub const SCHEDULER_TIMEOUT_CODE: i64 = -32050;
+/// 调度器关闭或批次被取消
+pub const SCHEDULER_UNAVAILABLE_CODE: i64 = -32051;
+/// 调度器提交队列已满，稍后重试
+pub const SCHEDULER_QUEUE_FULL_CODE: i64 = -32052;
+/// 冲突检测、策略等调度器内部错误
+pub const SCHEDULER_ERROR_CODE: i64 = -32059;
+
+/// 日志查询超出区块范围或结果数限制
+pub const LOG_QUERY_LIMIT_CODE: i64 = -32060;
+
 #[derive(Error, Debug)]
 pub enum ApiError {
     #[error("Invalid request: {0}")]
@@ -25,3 +100,444 @@ pub enum ApiError {
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 }
+
+/// 可映射为 JSON-RPC 错误的类型化错误
+pub trait RpcErrorKind {
+    /// 稳定的错误码
+    fn rpc_code(&self) -> i64;
+
+    /// 机器可读的 `data`
+    fn rpc_data(&self) -> Value;
+
+    /// `Internal` 变体包装的未分类错误
+    fn internal(&self) -> Option<&anyhow::Error> {
+        None
+    }
+}
+
+impl RpcErrorKind for AdapterError {
+    fn rpc_code(&self) -> i64 {
+        match self {
+            AdapterError::AdapterNotFound(_) => ADAPTER_NOT_FOUND_CODE,
+            AdapterError::ContractNotFound { .. } | AdapterError::ObjectNotFound(_) => {
+                NOT_FOUND_CODE
+            }
+            AdapterError::Timeout { .. } => UPSTREAM_TIMEOUT_CODE,
+            AdapterError::Transport { .. }
+            | AdapterError::Rpc { .. }
+            | AdapterError::Unsupported(_) => UPSTREAM_ERROR_CODE,
+            AdapterError::SignerUnavailable(_) => SIGNER_UNAVAILABLE_CODE,
+            AdapterError::Internal(_) => ErrorCode::InternalError.code(),
+        }
+    }
+
+    fn rpc_data(&self) -> Value {
+        match self {
+            AdapterError::AdapterNotFound(chain) => {
+                json!({"kind": "AdapterNotFound", "chain": chain})
+            }
+            AdapterError::ContractNotFound { chain, address } => {
+                json!({"kind": "ContractNotFound", "chain": chain, "address": address})
+            }
+            AdapterError::ObjectNotFound(id) => json!({"kind": "ObjectNotFound", "objectId": id}),
+            AdapterError::Timeout { chain, .. } => {
+                json!({"kind": "UpstreamTimeout", "chain": chain})
+            }
+            AdapterError::Transport { chain, .. } => {
+                json!({"kind": "UpstreamTransport", "chain": chain})
+            }
+            AdapterError::Rpc { chain, message } => {
+                json!({"kind": "UpstreamRpc", "chain": chain, "message": message})
+            }
+            AdapterError::SignerUnavailable(chain) => {
+                json!({"kind": "SignerUnavailable", "chain": chain})
+            }
+            AdapterError::Unsupported(operation) => {
+                json!({"kind": "Unsupported", "operation": operation})
+            }
+            AdapterError::Internal(_) => json!({"kind": "Internal"}),
+        }
+    }
+
+    fn internal(&self) -> Option<&anyhow::Error> {
+        match self {
+            AdapterError::Internal(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl RpcErrorKind for LoaderError {
+    fn rpc_code(&self) -> i64 {
+        match self {
+            LoaderError::InvalidBytecode(_)
+            | LoaderError::DisallowedImport { .. }
+            | LoaderError::MemoryLimitExceeded { .. }
+            | LoaderError::UnsupportedContractType(_) => INVALID_CONTRACT_CODE,
+            LoaderError::CompilationFailed(_)
+            | LoaderError::CompileTimeout { .. }
+            | LoaderError::PluginError(_)
+            | LoaderError::PluginAbiMismatch { .. } => COMPILATION_FAILED_CODE,
+            LoaderError::UnknownEntryFunction(_)
+            | LoaderError::ArgumentCountMismatch { .. }
+            | LoaderError::ArgumentTypeMismatch { .. }
+            | LoaderError::ArgumentsTooLarge { .. } => INVALID_ENTRY_CALL_CODE,
+            LoaderError::ArtifactNotFound(_)
+            | LoaderError::InvalidArtifact(_)
+            | LoaderError::UntrustedArtifactSigner(_)
+            | LoaderError::ArtifactCompilerMismatch { .. } => ARTIFACT_REJECTED_CODE,
+            _ => LOADER_ERROR_CODE,
+        }
+    }
+
+    fn rpc_data(&self) -> Value {
+        match self {
+            LoaderError::CompilationFailed(_) => json!({"kind": "CompilationFailed"}),
+            LoaderError::CacheError(_) => json!({"kind": "CacheError"}),
+            LoaderError::PluginError(_) => json!({"kind": "PluginError"}),
+            LoaderError::CapabilityMismatch { .. } => json!({"kind": "CapabilityMismatch"}),
+            LoaderError::PluginAbiMismatch {
+                path,
+                found,
+                expected,
+            } => json!({
+                "kind": "PluginAbiMismatch",
+                "path": path,
+                "found": found,
+                "expected": expected,
+            }),
+            LoaderError::CompileTimeout {
+                compiler,
+                timeout_ms,
+            } => json!({
+                "kind": "CompileTimeout",
+                "compiler": compiler,
+                "timeoutMs": timeout_ms,
+            }),
+            LoaderError::UnsupportedContractType(contract_type) => {
+                json!({"kind": "UnsupportedContractType", "contractType": contract_type})
+            }
+            LoaderError::UnknownEntryFunction(function) => {
+                json!({"kind": "UnknownEntryFunction", "function": function})
+            }
+            LoaderError::ArgumentCountMismatch {
+                function,
+                expected,
+                found,
+            } => json!({
+                "kind": "ArgumentCountMismatch",
+                "function": function,
+                "expected": expected,
+                "found": found,
+            }),
+            LoaderError::ArgumentTypeMismatch {
+                function,
+                index,
+                expected,
+                found,
+            } => json!({
+                "kind": "ArgumentTypeMismatch",
+                "function": function,
+                "index": index,
+                "expected": expected,
+                "found": found,
+            }),
+            LoaderError::ArgumentsTooLarge {
+                function,
+                size,
+                limit,
+            } => json!({
+                "kind": "ArgumentsTooLarge",
+                "function": function,
+                "size": size,
+                "limit": limit,
+            }),
+            LoaderError::InvalidBytecode(_) => json!({"kind": "InvalidBytecode"}),
+            LoaderError::DisallowedImport { module, name, .. } => {
+                json!({"kind": "DisallowedImport", "module": module, "name": name})
+            }
+            LoaderError::MemoryLimitExceeded { limit, .. } => {
+                json!({"kind": "MemoryLimitExceeded", "limitPages": limit})
+            }
+            LoaderError::ArtifactNotFound(address) => {
+                json!({"kind": "ArtifactNotFound", "address": address})
+            }
+            LoaderError::InvalidArtifact(_) => json!({"kind": "InvalidArtifact"}),
+            LoaderError::UntrustedArtifactSigner(signer) => {
+                json!({"kind": "UntrustedArtifactSigner", "signer": signer})
+            }
+            LoaderError::ArtifactCompilerMismatch { found, expected } => {
+                json!({"kind": "ArtifactCompilerMismatch", "found": found, "expected": expected})
+            }
+            LoaderError::ConfigError(_) => json!({"kind": "ConfigError"}),
+            LoaderError::IoError(_) => json!({"kind": "IoError"}),
+            LoaderError::SerializationError(_) => json!({"kind": "SerializationError"}),
+            LoaderError::DatabaseError(_) => json!({"kind": "DatabaseError"}),
+        }
+    }
+}
+
+impl RpcErrorKind for VmError {
+    fn rpc_code(&self) -> i64 {
+        match self {
+            VmError::OutOfGas { .. } => OUT_OF_GAS_CODE,
+            VmError::ResourceLimitExceeded(_) => RESOURCE_LIMIT_CODE,
+            VmError::ExecutionFailed(_) => VM_EXECUTION_FAILED_CODE,
+            VmError::UnsupportedVm(_)
+            | VmError::HostFunctionsUnsupported(_)
+            | VmError::TracingUnsupported(_)
+            | VmError::ResetUnsupported(_)
+            | VmError::InitializationFailed(_) => VM_UNAVAILABLE_CODE,
+            VmError::CodeLoadingFailed(_) | VmError::SnapshotFailed(_) => VM_ERROR_CODE,
+            VmError::Internal(_) => ErrorCode::InternalError.code(),
+        }
+    }
+
+    fn rpc_data(&self) -> Value {
+        match self {
+            VmError::ExecutionFailed(_) => json!({"kind": "ExecutionFailed"}),
+            VmError::CodeLoadingFailed(_) => json!({"kind": "CodeLoadingFailed"}),
+            VmError::InitializationFailed(_) => json!({"kind": "InitializationFailed"}),
+            VmError::SnapshotFailed(_) => json!({"kind": "SnapshotFailed"}),
+            VmError::ResourceLimitExceeded(_) => json!({"kind": "ResourceLimitExceeded"}),
+            VmError::OutOfGas {
+                gas_limit,
+                gas_used,
+            } => out_of_gas_data(*gas_limit, *gas_used),
+            VmError::UnsupportedVm(vm_type) => json!({"kind": "UnsupportedVm", "vmType": vm_type}),
+            VmError::HostFunctionsUnsupported(vm_type) => {
+                json!({"kind": "HostFunctionsUnsupported", "vmType": vm_type})
+            }
+            VmError::TracingUnsupported(vm_type) => {
+                json!({"kind": "TracingUnsupported", "vmType": vm_type})
+            }
+            VmError::ResetUnsupported(vm_type) => {
+                json!({"kind": "ResetUnsupported", "vmType": vm_type})
+            }
+            VmError::Internal(_) => json!({"kind": "Internal"}),
+        }
+    }
+
+    fn internal(&self) -> Option<&anyhow::Error> {
+        match self {
+            VmError::Internal(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl RpcErrorKind for SchedulerError {
+    fn rpc_code(&self) -> i64 {
+        match self {
+            SchedulerError::TimedOut { .. } => SCHEDULER_TIMEOUT_CODE,
+            SchedulerError::ShuttingDown | SchedulerError::Cancelled => SCHEDULER_UNAVAILABLE_CODE,
+            SchedulerError::QueueFull { .. } => SCHEDULER_QUEUE_FULL_CODE,
+            SchedulerError::Internal(_) => ErrorCode::InternalError.code(),
+            _ => SCHEDULER_ERROR_CODE,
+        }
+    }
+
+    fn rpc_data(&self) -> Value {
+        match self {
+            SchedulerError::ExecutionFailed(_) => json!({"kind": "ExecutionFailed"}),
+            SchedulerError::ConflictDetectionFailed(_) => {
+                json!({"kind": "ConflictDetectionFailed"})
+            }
+            SchedulerError::StrategyError(_) => json!({"kind": "StrategyError"}),
+            SchedulerError::ConfigError(_) => json!({"kind": "ConfigError"}),
+            SchedulerError::ShuttingDown => json!({"kind": "ShuttingDown"}),
+            SchedulerError::QueueFull {
+                depth,
+                capacity,
+                estimated_drain_ms,
+            } => json!({
+                "kind": "QueueFull",
+                "depth": depth,
+                "capacity": capacity,
+                "estimatedDrainMs": estimated_drain_ms,
+            }),
+            SchedulerError::Cancelled => json!({"kind": "Cancelled"}),
+            SchedulerError::TimedOut {
+                tx_hash,
+                timeout_ms,
+            } => json!({"kind": "TimedOut", "txHash": tx_hash, "timeoutMs": timeout_ms}),
+            SchedulerError::UnsupportedStrategy(strategy) => {
+                json!({"kind": "UnsupportedStrategy", "strategy": strategy})
+            }
+            SchedulerError::UnsafePlan { issues } => {
+                json!({"kind": "UnsafePlan", "conflicts": issues.len()})
+            }
+            SchedulerError::WorkerFailed(_) => json!({"kind": "WorkerFailed"}),
+            SchedulerError::Internal(_) => json!({"kind": "Internal"}),
+        }
+    }
+
+    fn internal(&self) -> Option<&anyhow::Error> {
+        match self {
+            SchedulerError::Internal(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl RpcErrorKind for MempoolError {
+    fn rpc_code(&self) -> i64 {
+        match self {
+            MempoolError::Full { .. } => MEMPOOL_FULL_CODE,
+            _ => TRANSACTION_REJECTED_CODE,
+        }
+    }
+
+    fn rpc_data(&self) -> Value {
+        match self {
+            MempoolError::Full { capacity } => json!({"kind": "MempoolFull", "capacity": capacity}),
+            MempoolError::Underpriced { offered, required } => {
+                json!({"kind": "Underpriced", "offered": offered, "required": required})
+            }
+            MempoolError::NonceTooLow { expected, actual } => {
+                json!({"kind": "NonceTooLow", "expected": expected, "actual": actual})
+            }
+        }
+    }
+}
+
+impl RpcErrorKind for LogQueryError {
+    fn rpc_code(&self) -> i64 {
+        match self {
+            LogQueryError::InvalidRange { .. } => ErrorCode::InvalidParams.code(),
+            LogQueryError::BlockRangeTooLarge { .. } | LogQueryError::TooManyResults { .. } => {
+                LOG_QUERY_LIMIT_CODE
+            }
+        }
+    }
+
+    fn rpc_data(&self) -> Value {
+        match self {
+            LogQueryError::InvalidRange { from, to } => {
+                json!({"kind": "InvalidBlockRange", "fromBlock": from, "toBlock": to})
+            }
+            LogQueryError::BlockRangeTooLarge { from, to, max } => json!({
+                "kind": "BlockRangeTooLarge",
+                "fromBlock": from,
+                "toBlock": to,
+                "maxBlockRange": max,
+            }),
+            LogQueryError::TooManyResults { max } => {
+                json!({"kind": "TooManyResults", "maxResults": max})
+            }
+        }
+    }
+}
+
+/// gas 耗尽的 `data`，VM 报告与调用方 gas 上限检查共用
+pub fn out_of_gas_data(gas_limit: u64, gas_used: u64) -> Value {
+    json!({"kind": "OutOfGas", "gasLimit": gas_limit, "gasUsed": gas_used})
+}
+
+/// 沿错误链找到第一个类型化错误并映射；错误链上都是未分类错误时为 -32603
+pub fn to_rpc_error(error: &anyhow::Error) -> RpcError {
+    let message = format!("{:#}", error);
+    match typed_kind(error) {
+        Some(kind) => RpcError {
+            code: ErrorCode::ServerError(kind.rpc_code()),
+            message,
+            data: Some(kind.rpc_data()),
+        },
+        None => RpcError {
+            code: ErrorCode::InternalError,
+            message,
+            data: None,
+        },
+    }
+}
+
+/// 错误链上第一个类型化错误
+///
+/// `Internal` 变体的 `source` 越过了被包装错误本身，因此转而在被包装的错误链上查找
+fn typed_kind(error: &anyhow::Error) -> Option<&dyn RpcErrorKind> {
+    for cause in error.chain() {
+        let kind: Option<&dyn RpcErrorKind> = if let Some(e) = cause.downcast_ref::<VmError>() {
+            Some(e)
+        } else if let Some(e) = cause.downcast_ref::<AdapterError>() {
+            Some(e)
+        } else if let Some(e) = cause.downcast_ref::<LoaderError>() {
+            Some(e)
+        } else if let Some(e) = cause.downcast_ref::<SchedulerError>() {
+            Some(e)
+        } else if let Some(e) = cause.downcast_ref::<MempoolError>() {
+            Some(e)
+        } else if let Some(e) = cause.downcast_ref::<LogQueryError>() {
+            Some(e)
+        } else {
+            None
+        };
+        if let Some(kind) = kind {
+            return match kind.internal() {
+                Some(inner) => typed_kind(inner),
+                None => Some(kind),
+            };
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+    use dubhe_adapter::ChainType;
+
+    #[test]
+    fn test_chain_is_searched_for_typed_errors() {
+        let error = anyhow::Error::new(AdapterError::ContractNotFound {
+            chain: ChainType::Sui,
+            address: "0x2".to_string(),
+        })
+        .context("failed to resolve call target");
+        let rpc = to_rpc_error(&error);
+        assert_eq!(rpc.code, ErrorCode::ServerError(NOT_FOUND_CODE));
+        assert_eq!(rpc.data.as_ref().unwrap()["kind"], "ContractNotFound");
+        assert_eq!(rpc.data.as_ref().unwrap()["address"], "0x2");
+        assert!(rpc.message.starts_with("failed to resolve call target: "));
+
+        let error: anyhow::Result<()> = Err(VmError::OutOfGas {
+            gas_limit: 10,
+            gas_used: 12,
+        })
+        .context("execute");
+        let rpc = to_rpc_error(&error.unwrap_err());
+        assert_eq!(rpc.code, ErrorCode::ServerError(OUT_OF_GAS_CODE));
+        assert_eq!(rpc.data, Some(out_of_gas_data(10, 12)));
+
+        let rpc = to_rpc_error(&anyhow::anyhow!("disk on fire"));
+        assert_eq!(rpc.code, ErrorCode::InternalError);
+        assert_eq!(rpc.data, None);
+    }
+
+    #[test]
+    fn test_internal_variants_are_searched_for_typed_errors() {
+        let wrapped = AdapterError::from(
+            anyhow::Error::new(VmError::OutOfGas {
+                gas_limit: 10,
+                gas_used: 12,
+            })
+            .context("execute"),
+        );
+        assert!(matches!(wrapped, AdapterError::Internal(_)));
+        let rpc = to_rpc_error(&anyhow::Error::new(wrapped).context("call"));
+        assert_eq!(rpc.code, ErrorCode::ServerError(OUT_OF_GAS_CODE));
+        assert_eq!(rpc.data, Some(out_of_gas_data(10, 12)));
+        assert!(rpc.message.starts_with("call: execute: "));
+
+        // 已是类型化错误的不被包装
+        let error = VmError::from(anyhow::Error::new(VmError::ResetUnsupported(
+            dubhe_vm_runtime::VmType::CkbVM,
+        )));
+        assert!(matches!(error, VmError::ResetUnsupported(_)));
+
+        let error = SchedulerError::from(anyhow::anyhow!("disk on fire"));
+        let rpc = to_rpc_error(&anyhow::Error::new(error));
+        assert_eq!(rpc.code, ErrorCode::InternalError);
+        assert_eq!(rpc.data, None);
+    }
+}
@@ -0,0 +1,470 @@
+//! 只读执行后端
+//!
+//! 为 eth_call / eth_estimateGas / debug_traceCall 提供执行路径：
+//! AdapterManager 获取合约 → CodeLoader 编译 → VmManager 创建实例执行
+
+use jsonrpc_core::{Error as RpcError, ErrorCode};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::debug;
+
+use crate::error::{out_of_gas_data, to_rpc_error, OUT_OF_GAS_CODE};
+use dubhe_adapter::{AdapterManager, ChainType};
+use dubhe_loader::CodeLoader;
+use dubhe_vm_runtime::{
+    ExecutionResult, ExecutionTrace, TraceConfig, VmError, VmInstance, VmManager,
+};
+
+/// 未指定 gas 时的默认上限（与以太坊区块 gas 上限一致）
+pub const DEFAULT_CALL_GAS: u64 = 30_000_000;
+
+/// EIP-1474 执行回滚错误码
+pub const EXECUTION_REVERTED_CODE: i64 = 3;
+
+/// 标准调用对象
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CallRequest {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub gas: Option<String>,
+    #[serde(rename = "gasPrice")]
+    pub gas_price: Option<String>,
+    pub value: Option<String>,
+    #[serde(alias = "input")]
+    pub data: Option<String>,
+}
+
+/// 调用失败原因
+#[derive(Error, Debug)]
+pub enum CallError {
+    #[error("Invalid call params: {0}")]
+    InvalidParams(String),
+
+    #[error("execution reverted: {reason}")]
+    Reverted { reason: String, data: Vec<u8> },
+
+    #[error("out of gas: gas required exceeds allowance ({gas_limit})")]
+    OutOfGas { gas_limit: u64, gas_used: u64 },
+
+    #[error("Internal error: {0}")]
+    Internal(String),
+
+    /// 适配器、加载器或 VM 的错误，按错误链中的类型映射错误码
+    #[error("{0:#}")]
+    Upstream(#[from] anyhow::Error),
+}
+
+impl From<CallError> for RpcError {
+    fn from(e: CallError) -> Self {
+        match &e {
+            CallError::InvalidParams(msg) => RpcError::invalid_params(msg.clone()),
+            CallError::Reverted { data, .. } => RpcError {
+                code: ErrorCode::ServerError(EXECUTION_REVERTED_CODE),
+                message: e.to_string(),
+                data: Some(serde_json::json!(encode_hex(data))),
+            },
+            CallError::OutOfGas {
+                gas_limit,
+                gas_used,
+            } => RpcError {
+                code: ErrorCode::ServerError(OUT_OF_GAS_CODE),
+                message: e.to_string(),
+                data: Some(out_of_gas_data(*gas_limit, *gas_used)),
+            },
+            CallError::Internal(_) => RpcError {
+                code: ErrorCode::InternalError,
+                message: e.to_string(),
+                data: None,
+            },
+            CallError::Upstream(error) => to_rpc_error(error),
+        }
+    }
+}
+
+/// eth_call / eth_estimateGas 执行后端
+pub struct CallExecutor {
+    adapters: Arc<AdapterManager>,
+    loader: Arc<CodeLoader>,
+    vm_manager: Arc<VmManager>,
+    chain_type: ChainType,
+}
+
+impl CallExecutor {
+    pub fn new(
+        adapters: Arc<AdapterManager>,
+        loader: Arc<CodeLoader>,
+        vm_manager: Arc<VmManager>,
+    ) -> Self {
+        Self {
+            adapters,
+            loader,
+            vm_manager,
+            chain_type: ChainType::Ethereum,
+        }
+    }
+
+    /// 从哪条链解析合约（默认 Ethereum）
+    pub fn with_chain_type(mut self, chain_type: ChainType) -> Self {
+        self.chain_type = chain_type;
+        self
+    }
+
+    /// 执行只读调用，返回 VM 执行结果（不修改任何状态）
+    pub async fn call(&self, request: &CallRequest) -> Result<ExecutionResult, CallError> {
+        let (mut vm, input, gas_limit) = self.prepare(request).await?;
+        let result = vm
+            .execute(&input)
+            .await
+            .map_err(|e| execution_error(e, gas_limit))?;
+
+        debug!(
+            "eth_call {} -> success={}, gas_used={}",
+            request.to.as_deref().unwrap_or_default(),
+            result.success,
+            result.gas_used
+        );
+
+        if !result.success {
+            return Err(CallError::Reverted {
+                reason: result.error.clone().unwrap_or_default(),
+                data: result.output,
+            });
+        }
+        if result.gas_used > gas_limit {
+            return Err(CallError::OutOfGas {
+                gas_limit,
+                gas_used: result.gas_used,
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// 在一次性实例中以跟踪模式执行调用；回滚不视为错误，随跟踪一并返回
+    pub async fn trace_call(
+        &self,
+        request: &CallRequest,
+        config: &TraceConfig,
+    ) -> Result<TracedCall, CallError> {
+        let (mut vm, input, gas_limit) = self.prepare(request).await?;
+        let (result, trace) = vm
+            .execute_traced(&input, config)
+            .await
+            .map_err(|e| execution_error(e, gas_limit))?;
+
+        debug!(
+            "debug_traceCall {} -> success={}, steps={}",
+            request.to.as_deref().unwrap_or_default(),
+            result.success,
+            trace.total_steps
+        );
+
+        Ok(TracedCall {
+            success: result.success,
+            output: encode_hex(&result.output),
+            gas_used: result.gas_used,
+            error: result.error,
+            trace,
+        })
+    }
+
+    /// 解析调用、加载合约并创建已设好 gas 上限的实例
+    async fn prepare(
+        &self,
+        request: &CallRequest,
+    ) -> Result<(Box<dyn VmInstance + Send + Sync>, Vec<u8>, u64), CallError> {
+        let to = request
+            .to
+            .as_deref()
+            .ok_or_else(|| CallError::InvalidParams("missing 'to' address".to_string()))?;
+        let input = match &request.data {
+            Some(data) => decode_hex(data)?,
+            None => Vec::new(),
+        };
+        let gas_limit = match &request.gas {
+            Some(gas) => parse_quantity(gas)?,
+            None => DEFAULT_CALL_GAS,
+        };
+
+        let meta = self
+            .adapters
+            .get_contract_meta(self.chain_type, to)
+            .await
+            .map_err(anyhow::Error::from)?;
+        let compiled = self.loader.load_contract(&meta).await?;
+
+        let mut vm = self.vm_manager.create_instance(None)?;
+        if !vm.supports_artifact(compiled.artifact) {
+            return Err(CallError::Internal(format!(
+                "{:?} cannot execute {:?} artifacts",
+                vm.vm_type(),
+                compiled.artifact
+            )));
+        }
+        vm.set_limits(self.vm_manager.limits_for_gas(gas_limit));
+        vm.load_code(&compiled.risc_v_code)
+            .await
+            .map_err(anyhow::Error::from)?;
+        Ok((vm, input, gas_limit))
+    }
+}
+
+/// debug_traceCall 的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TracedCall {
+    pub success: bool,
+    /// 0x 前缀十六进制输出
+    pub output: String,
+    pub gas_used: u64,
+    pub error: Option<String>,
+    pub trace: ExecutionTrace,
+}
+
+/// cycle 超限即 gas 耗尽，其余错误按类型映射
+fn execution_error(e: VmError, gas_limit: u64) -> CallError {
+    match e {
+        VmError::OutOfGas { gas_used, .. } => CallError::OutOfGas {
+            gas_limit,
+            gas_used,
+        },
+        VmError::ResourceLimitExceeded(_) => CallError::OutOfGas {
+            gas_limit,
+            gas_used: gas_limit,
+        },
+        e => anyhow::Error::from(e).into(),
+    }
+}
+
+/// 0x 前缀十六进制字节串编码
+pub fn encode_hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(2 + bytes.len() * 2);
+    out.push_str("0x");
+    for byte in bytes {
+        out.push_str(&format!("{:02x}", byte));
+    }
+    out
+}
+
+/// 解析 0x 前缀十六进制字节串
+pub fn decode_hex(value: &str) -> Result<Vec<u8>, CallError> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    if digits.len() % 2 != 0 {
+        return Err(CallError::InvalidParams(format!(
+            "odd-length hex data: {}",
+            value
+        )));
+    }
+    hex::decode(digits)
+        .map_err(|_| CallError::InvalidParams(format!("invalid hex data: {}", value)))
+}
+
+/// 解析 EIP-1474 数量（0x 前缀十六进制整数）
+pub fn parse_quantity(value: &str) -> Result<u64, CallError> {
+    let digits = value
+        .strip_prefix("0x")
+        .ok_or_else(|| CallError::InvalidParams(format!("quantity must be 0x-prefixed: {}", value)))?;
+    // from_str_radix 接受前导的 '+'
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(CallError::InvalidParams(format!(
+            "invalid quantity: {}",
+            value
+        )));
+    }
+    u64::from_str_radix(digits, 16)
+        .map_err(|_| CallError::InvalidParams(format!("invalid quantity: {}", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use dubhe_adapter::{
+        AdapterError, AdapterResult, ChainAdapter, ContractMeta, ContractType, TransactionReceipt,
+    };
+    use dubhe_vm_runtime::VmType;
+    use tokio::sync::mpsc;
+
+    struct StaticAdapter;
+
+    #[async_trait]
+    impl ChainAdapter for StaticAdapter {
+        async fn get_contract_meta(&self, address: &str) -> AdapterResult<ContractMeta> {
+            Ok(ContractMeta {
+                address: address.to_string(),
+                chain_type: ChainType::Ethereum,
+                contract_type: ContractType::EVM,
+                bytecode: vec![0x60, 0x00],
+                abi: None,
+                source_code: None,
+                compiler_version: None,
+                created_at: 0,
+                creator: None,
+                abi_source: None,
+                modules: vec![],
+            })
+        }
+
+        async fn get_transaction_receipt(
+            &self,
+            _tx_hash: &str,
+        ) -> AdapterResult<TransactionReceipt> {
+            Err(AdapterError::Unsupported(
+                "get_transaction_receipt".to_string(),
+            ))
+        }
+
+        async fn get_balance(&self, _address: &str) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn get_nonce(&self, _address: &str) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn get_block_number(&self) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn subscribe_new_blocks(&self) -> AdapterResult<mpsc::Receiver<String>> {
+            Ok(mpsc::channel(1).1)
+        }
+
+        async fn subscribe_new_transactions(&self) -> AdapterResult<mpsc::Receiver<String>> {
+            Ok(mpsc::channel(1).1)
+        }
+    }
+
+    #[test]
+    fn test_hex_helpers() {
+        assert_eq!(decode_hex("0x0a0b").unwrap(), vec![0x0a, 0x0b]);
+        assert!(decode_hex("0x0").is_err());
+        // 多字节字符与 '+' 号不能被当作十六进制数字
+        assert!(decode_hex("0xaé0").is_err());
+        assert!(decode_hex("0x+1").is_err());
+        assert!(parse_quantity("0x+1").is_err());
+        assert_eq!(encode_hex(&[0xde, 0xad]), "0xdead");
+        assert_eq!(parse_quantity("0x5208").unwrap(), 21000);
+        assert!(parse_quantity("21000").is_err());
+    }
+
+    #[test]
+    fn test_revert_maps_to_rpc_error() {
+        let error: RpcError = CallError::Reverted {
+            reason: "insufficient balance".to_string(),
+            data: vec![0x08, 0xc3],
+        }
+        .into();
+        assert_eq!(error.code, ErrorCode::ServerError(EXECUTION_REVERTED_CODE));
+        assert_eq!(error.data, Some(serde_json::json!("0x08c3")));
+        assert!(error.message.starts_with("execution reverted"));
+    }
+
+    #[tokio::test]
+    async fn test_call_and_gas_limit() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let adapters = Arc::new(AdapterManager::new());
+        adapters
+            .register_adapter(ChainType::Ethereum, Box::new(StaticAdapter))
+            .await;
+        let executor = CallExecutor::new(
+            adapters,
+            Arc::new(CodeLoader::with_cache_dir(temp_dir.path()).unwrap()),
+            Arc::new(VmManager::new(VmType::CkbVM)),
+        );
+
+        let request = CallRequest {
+            to: Some("0x1234".to_string()),
+            data: Some("0x01020304".to_string()),
+            ..CallRequest::default()
+        };
+        let result = executor.call(&request).await.unwrap();
+        assert!(result.success);
+        assert!(result.gas_used > 0);
+
+        // gas 上限不足
+        let starved = CallRequest {
+            gas: Some("0x1".to_string()),
+            ..request.clone()
+        };
+        assert!(matches!(
+            executor.call(&starved).await,
+            Err(CallError::OutOfGas { gas_limit: 1, .. })
+        ));
+
+        // 缺少 to
+        assert!(matches!(
+            executor.call(&CallRequest::default()).await,
+            Err(CallError::InvalidParams(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_trace_call_returns_bounded_trace() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let adapters = Arc::new(AdapterManager::new());
+        adapters
+            .register_adapter(ChainType::Ethereum, Box::new(StaticAdapter))
+            .await;
+        let executor = CallExecutor::new(
+            adapters,
+            Arc::new(CodeLoader::with_cache_dir(temp_dir.path()).unwrap()),
+            Arc::new(VmManager::new(VmType::CkbVM)),
+        );
+        let request = CallRequest {
+            to: Some("0x1234".to_string()),
+            data: Some("0x01020304".to_string()),
+            ..CallRequest::default()
+        };
+
+        let call = executor.call(&request).await.unwrap();
+        let config = TraceConfig {
+            max_steps: 3,
+            ..TraceConfig::default()
+        };
+        let traced = executor.trace_call(&request, &config).await.unwrap();
+        assert!(traced.success);
+        assert_eq!(traced.output, encode_hex(&call.output));
+        assert_eq!(traced.gas_used, call.gas_used);
+        assert_eq!(traced.trace.steps.len(), 3);
+        assert!(traced.trace.truncated);
+        assert!(traced.trace.total_steps > 3);
+    }
+
+    #[tokio::test]
+    async fn test_out_of_gas_reaches_http_client() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let adapters = Arc::new(AdapterManager::new());
+        adapters
+            .register_adapter(ChainType::Ethereum, Box::new(StaticAdapter))
+            .await;
+        let executor = Arc::new(CallExecutor::new(
+            adapters,
+            Arc::new(CodeLoader::with_cache_dir(temp_dir.path()).unwrap()),
+            Arc::new(VmManager::new(VmType::CkbVM)),
+        ));
+        let server = crate::rpc::RpcServer::with_executor(executor);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { server.serve(listener).await });
+
+        let response: serde_json::Value = reqwest::Client::new()
+            .post(&url)
+            .json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "eth_call",
+                "params": [{"to": "0x1234", "data": "0x01020304", "gas": "0x1"}, "latest"],
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], OUT_OF_GAS_CODE);
+        assert_eq!(response["error"]["data"]["kind"], "OutOfGas");
+        assert_eq!(response["error"]["data"]["gasLimit"], 1);
+    }
+}
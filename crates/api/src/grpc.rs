@@ -1,73 +1,322 @@
 //! gRPC 服务器
-//! 
-//! 高性能内部微服务调用接口
+//!
+//! 高性能内部微服务调用接口：批次提交、调度器状态查询与执行统计订阅，
+//! 全部委托给构造时注入的 ParallelScheduler
 
 use anyhow::Result;
+use futures::Stream;
+use std::pin::Pin;
+use std::sync::Arc;
 use tokio::net::TcpListener;
-use tonic::{transport::Server, Request, Response, Status};
-use tracing::info;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{transport::Server, Request, Response, Status, Streaming};
+use tracing::{info, warn};
 
-// TODO: 生成 protobuf 定义后取消注释
-// use crate::proto::{
-//     execution_service_server::{ExecutionService, ExecutionServiceServer},
-//     ExecuteRequest, ExecuteResponse,
-// };
+use dubhe_scheduler::{ParallelScheduler, SchedulerError};
+
+use self::proto::scheduler_service_server::{SchedulerService, SchedulerServiceServer};
+
+/// 由 proto/dubhe.proto 生成
+pub mod proto {
+    tonic::include_proto!("dubhe.v1");
+}
 
 /// gRPC 服务器
 pub struct GrpcServer {
-    // service: ExecutionServiceImpl,
+    service: SchedulerServiceImpl,
 }
 
 impl GrpcServer {
-    pub fn new() -> Self {
+    pub fn new(scheduler: Arc<ParallelScheduler>) -> Self {
         Self {
-            // service: ExecutionServiceImpl::new(),
+            service: SchedulerServiceImpl { scheduler },
         }
     }
 
     pub async fn start(&self, bind_addr: &str) -> Result<()> {
         info!("gRPC server starting on {}", bind_addr);
-        
-        // TODO: 实现 gRPC 服务
-        // let addr = bind_addr.parse()?;
-        // Server::builder()
-        //     .add_service(ExecutionServiceServer::new(self.service.clone()))
-        //     .serve(addr)
-        //     .await?;
-        
-        // 暂时的占位实现
-        loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+        let listener = TcpListener::bind(bind_addr).await?;
+        self.serve(listener).await
+    }
+
+    /// 在已绑定的监听器上提供服务
+    pub async fn serve(&self, listener: TcpListener) -> Result<()> {
+        Server::builder()
+            .add_service(SchedulerServiceServer::new(self.service.clone()))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct SchedulerServiceImpl {
+    scheduler: Arc<ParallelScheduler>,
+}
+
+type ExecutionStatsStream =
+    Pin<Box<dyn Stream<Item = Result<proto::ExecutionStats, Status>> + Send + 'static>>;
+
+#[tonic::async_trait]
+impl SchedulerService for SchedulerServiceImpl {
+    async fn submit_batch(
+        &self,
+        request: Request<Streaming<proto::Transaction>>,
+    ) -> Result<Response<proto::BatchResult>, Status> {
+        let mut stream = request.into_inner();
+        let mut transactions = Vec::new();
+        while let Some(transaction) = stream.message().await? {
+            transactions.push(transaction.into());
+        }
+
+        let result = self
+            .scheduler
+            .submit_batch(transactions)
+            .await
+            .map_err(scheduler_status)?;
+        Ok(Response::new(result.into()))
+    }
+
+    async fn get_scheduler_status(
+        &self,
+        _request: Request<proto::GetSchedulerStatusRequest>,
+    ) -> Result<Response<proto::SchedulerStatus>, Status> {
+        Ok(Response::new(self.scheduler.get_status().await.into()))
+    }
+
+    type WatchExecutionStatsStream = ExecutionStatsStream;
+
+    async fn watch_execution_stats(
+        &self,
+        _request: Request<proto::WatchExecutionStatsRequest>,
+    ) -> Result<Response<Self::WatchExecutionStatsStream>, Status> {
+        let receiver = self.scheduler.subscribe_stats();
+        let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(stats) => return Some((Ok(stats.into()), receiver)),
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("gRPC stats watcher lagged, skipped {} batches", skipped);
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+fn scheduler_status(error: anyhow::Error) -> Status {
+    match error.downcast_ref::<SchedulerError>() {
+        Some(SchedulerError::ShuttingDown) => Status::unavailable(error.to_string()),
+        Some(SchedulerError::QueueFull { .. }) => Status::resource_exhausted(error.to_string()),
+        Some(SchedulerError::Cancelled) => Status::cancelled(error.to_string()),
+        _ => Status::internal(error.to_string()),
+    }
+}
+
+impl From<proto::Transaction> for dubhe_scheduler::Transaction {
+    fn from(tx: proto::Transaction) -> Self {
+        Self {
+            hash: tx.hash,
+            from: tx.from,
+            to: tx.to,
+            data: tx.data,
+            gas_limit: tx.gas_limit,
+            gas_price: tx.gas_price,
+            nonce: tx.nonce,
+            read_set: tx.read_set,
+            write_set: tx.write_set,
+            access_list: tx.access_list.map(|list| dubhe_scheduler::AccessList {
+                reads: list.reads,
+                writes: list.writes,
+            }),
+            max_priority_fee: tx.max_priority_fee,
+        }
+    }
+}
+
+impl From<dubhe_scheduler::Transaction> for proto::Transaction {
+    fn from(tx: dubhe_scheduler::Transaction) -> Self {
+        Self {
+            hash: tx.hash,
+            from: tx.from,
+            to: tx.to,
+            data: tx.data,
+            gas_limit: tx.gas_limit,
+            gas_price: tx.gas_price,
+            nonce: tx.nonce,
+            read_set: tx.read_set,
+            write_set: tx.write_set,
+            access_list: tx.access_list.map(|list| proto::AccessList {
+                reads: list.reads,
+                writes: list.writes,
+            }),
+            max_priority_fee: tx.max_priority_fee,
+        }
+    }
+}
+
+impl From<dubhe_scheduler::TransactionResult> for proto::TransactionResult {
+    fn from(result: dubhe_scheduler::TransactionResult) -> Self {
+        Self {
+            tx_hash: result.tx_hash,
+            success: result.success,
+            gas_used: result.gas_used,
+            output: result.output,
+            logs: result.logs,
+            error: result.error,
+        }
+    }
+}
+
+impl From<dubhe_scheduler::ExecutionStats> for proto::ExecutionStats {
+    fn from(stats: dubhe_scheduler::ExecutionStats) -> Self {
+        Self {
+            total_transactions: stats.total_transactions as u64,
+            successful_transactions: stats.successful_transactions as u64,
+            failed_transactions: stats.failed_transactions as u64,
+            total_gas_used: stats.total_gas_used,
+            execution_time_ms: stats.execution_time_ms,
+            parallel_efficiency: stats.parallel_efficiency,
+            conflicts_detected: stats.conflicts_detected as u64,
+            queue_time_ms: stats.queue_time_ms,
+            parallelism_bound: stats.parallelism_bound,
+            total_fees: stats.total_fees,
+            priority_fees: stats.priority_fees,
+        }
+    }
+}
+
+impl From<dubhe_scheduler::BatchResult> for proto::BatchResult {
+    fn from(batch: dubhe_scheduler::BatchResult) -> Self {
+        Self {
+            transaction_results: batch
+                .transaction_results
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            execution_stats: Some(batch.execution_stats.into()),
+        }
+    }
+}
+
+impl From<dubhe_scheduler::SchedulerStatus> for proto::SchedulerStatus {
+    fn from(status: dubhe_scheduler::SchedulerStatus) -> Self {
+        Self {
+            strategy_type: format!("{:?}", status.strategy_type),
+            worker_threads: status.worker_threads as u64,
+            queue_length: status.queue_length as u64,
+            total_processed: status.total_processed,
+            conflicts_detected: status.conflicts_detected,
+            parallel_efficiency: status.parallel_efficiency,
+            per_strategy: status
+                .per_strategy
+                .into_iter()
+                .map(|(strategy, counters)| {
+                    (
+                        format!("{:?}", strategy),
+                        proto::StrategyCounters {
+                            batches: counters.batches,
+                            transactions: counters.transactions,
+                            conflicts: counters.conflicts,
+                        },
+                    )
+                })
+                .collect(),
         }
     }
 }
 
-// TODO: 实现 gRPC 服务
-// #[derive(Debug, Clone)]
-// struct ExecutionServiceImpl {}
-// 
-// impl ExecutionServiceImpl {
-//     fn new() -> Self {
-//         Self {}
-//     }
-// }
-// 
-// #[tonic::async_trait]
-// impl ExecutionService for ExecutionServiceImpl {
-//     async fn execute_transaction(
-//         &self,
-//         request: Request<ExecuteRequest>,
-//     ) -> Result<Response<ExecuteResponse>, Status> {
-//         let req = request.into_inner();
-//         
-//         // TODO: 调用 scheduler 执行交易
-//         let response = ExecuteResponse {
-//             tx_hash: "0x0".to_string(),
-//             success: true,
-//             gas_used: 21000,
-//             output: vec![],
-//         };
-//         
-//         Ok(Response::new(response))
-//     }
-// } 
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::proto::scheduler_service_client::SchedulerServiceClient;
+    use super::*;
+    use dubhe_scheduler::{SchedulerConfig, StrategyType};
+
+    fn transaction(hash: &str, account: &str) -> proto::Transaction {
+        proto::Transaction {
+            hash: hash.to_string(),
+            from: "0xsender".to_string(),
+            to: Some(account.to_string()),
+            data: vec![0xde, 0xad],
+            gas_limit: u64::MAX,
+            gas_price: 7,
+            nonce: 3,
+            read_set: vec![account.to_string()],
+            write_set: vec![account.to_string(), "0xfee".to_string()],
+            access_list: Some(proto::AccessList {
+                reads: vec!["r".to_string()],
+                writes: vec![],
+            }),
+            max_priority_fee: Some(3),
+        }
+    }
+
+    #[test]
+    fn test_transaction_round_trip() {
+        let original = transaction("0x01", "0xaccount");
+        let scheduler_tx: dubhe_scheduler::Transaction = original.clone().into();
+        assert_eq!(scheduler_tx.gas_limit, u64::MAX);
+        assert_eq!(scheduler_tx.write_set, original.write_set);
+        assert_eq!(proto::Transaction::from(scheduler_tx), original);
+
+        let contract_creation = proto::Transaction {
+            to: None,
+            access_list: None,
+            max_priority_fee: None,
+            ..original
+        };
+        let scheduler_tx: dubhe_scheduler::Transaction = contract_creation.clone().into();
+        assert!(scheduler_tx.to.is_none() && scheduler_tx.access_list.is_none());
+        assert_eq!(proto::Transaction::from(scheduler_tx), contract_creation);
+    }
+
+    #[tokio::test]
+    async fn test_submit_batch_over_grpc() {
+        let scheduler = Arc::new(
+            ParallelScheduler::new(StrategyType::Sequential, SchedulerConfig::default()).unwrap(),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = GrpcServer::new(scheduler);
+        tokio::spawn(async move { server.serve(listener).await });
+
+        let mut client = SchedulerServiceClient::connect(format!("http://{}", addr))
+            .await
+            .unwrap();
+        let mut stats = client
+            .watch_execution_stats(proto::WatchExecutionStatsRequest {})
+            .await
+            .unwrap()
+            .into_inner();
+
+        let batch = vec![transaction("0x01", "0xa"), transaction("0x02", "0xb")];
+        let result = client
+            .submit_batch(tokio_stream::iter(batch))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let hashes: Vec<&str> = result
+            .transaction_results
+            .iter()
+            .map(|r| r.tx_hash.as_str())
+            .collect();
+        assert_eq!(hashes, vec!["0x01", "0x02"]);
+        assert!(result.transaction_results.iter().all(|r| r.success));
+        assert_eq!(result.execution_stats.unwrap().total_transactions, 2);
+
+        let streamed = stats.message().await.unwrap().unwrap();
+        assert_eq!(streamed.successful_transactions, 2);
+
+        let status = client
+            .get_scheduler_status(proto::GetSchedulerStatusRequest {})
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(status.strategy_type, "Sequential");
+        assert_eq!(status.total_processed, 2);
+    }
+}
@@ -0,0 +1,455 @@
+//! 交易入口
+//!
+//! eth_sendRawTransaction 的写路径：RLP 解码 legacy / EIP-2930 / EIP-1559 交易，
+//! 恢复 secp256k1 签名者，按适配器提供的链上状态校验 nonce 与 gas，
+//! 转换为调度器交易后放入交易池，由交易池按批次交给调度器执行
+
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::U256;
+use ethers::utils::{keccak256, rlp::Rlp};
+use jsonrpc_core::{Error as RpcError, ErrorCode};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{debug, info};
+
+use crate::error::RpcErrorKind;
+use crate::execution::{encode_hex, DEFAULT_CALL_GAS};
+use dubhe_adapter::{AdapterManager, ChainType};
+use dubhe_scheduler::{
+    AccessList, AccessSetEstimator, InsertOutcome, Mempool, MempoolError, Transaction,
+};
+
+/// Dubhe Channel 的 Chain ID（0x44554248，"DUBH"），签名须带 EIP-155 重放保护
+pub const DUBHE_CHAIN_ID: u64 = 0x4455_4248;
+
+/// 普通转账的固有 gas
+pub const INTRINSIC_GAS: u64 = 21_000;
+
+/// 交易被拒绝的 JSON-RPC 错误码（EIP-1474 Transaction rejected）
+pub const TRANSACTION_REJECTED_CODE: i64 = -32003;
+
+/// 交易池已满的 JSON-RPC 错误码（EIP-1474 Resource unavailable）
+pub const MEMPOOL_FULL_CODE: i64 = -32002;
+
+/// 提交失败原因
+#[derive(Error, Debug)]
+pub enum IngressError {
+    #[error("Invalid transaction encoding: {0}")]
+    InvalidEncoding(String),
+
+    #[error("Invalid transaction signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("Invalid chain id: expected {expected}, got {actual:?}")]
+    WrongChainId { expected: u64, actual: Option<u64> },
+
+    #[error("nonce too low: account nonce {expected}, transaction nonce {actual}")]
+    NonceTooLow { expected: u64, actual: u64 },
+
+    #[error("intrinsic gas too low: {gas}")]
+    IntrinsicGasTooLow { gas: u64 },
+
+    #[error("exceeds block gas limit: {gas} > {limit}")]
+    GasLimitExceeded { gas: u64, limit: u64 },
+
+    #[error(transparent)]
+    Mempool(#[from] MempoolError),
+
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+impl From<IngressError> for RpcError {
+    fn from(e: IngressError) -> Self {
+        let code = match &e {
+            IngressError::InvalidEncoding(_) => ErrorCode::InvalidParams,
+            IngressError::Mempool(mempool) => ErrorCode::ServerError(mempool.rpc_code()),
+            IngressError::Internal(_) => ErrorCode::InternalError,
+            _ => ErrorCode::ServerError(TRANSACTION_REJECTED_CODE),
+        };
+        let data = match &e {
+            IngressError::Mempool(mempool) => Some(mempool.rpc_data()),
+            _ => None,
+        };
+        RpcError {
+            code,
+            message: e.to_string(),
+            data,
+        }
+    }
+}
+
+/// 已解码、已验签的交易
+#[derive(Debug, Clone)]
+pub struct SignedTransaction {
+    pub hash: String,
+    pub from: String,
+    pub to: Option<String>,
+    pub data: Vec<u8>,
+    pub gas_limit: u64,
+    /// legacy 交易的 gas price，EIP-1559 交易的 max fee per gas
+    pub gas_price: u64,
+    pub nonce: u64,
+    /// EIP-1559 交易的 max priority fee per gas，其它类型为 `None`
+    pub max_priority_fee: Option<u64>,
+}
+
+impl SignedTransaction {
+    /// 解码已签名的原始交易并恢复发送方
+    pub fn decode(raw: &[u8], chain_id: u64) -> Result<Self, IngressError> {
+        let (tx, signature) = TypedTransaction::decode_signed(&Rlp::new(raw))
+            .map_err(|e| IngressError::InvalidEncoding(e.to_string()))?;
+
+        let actual = tx.chain_id().map(|id| id.as_u64());
+        if actual != Some(chain_id) {
+            return Err(IngressError::WrongChainId {
+                expected: chain_id,
+                actual,
+            });
+        }
+        let from = signature
+            .recover(tx.sighash())
+            .map_err(|e| IngressError::InvalidSignature(e.to_string()))?;
+
+        let quantity = |value: Option<U256>, field: &str| {
+            u64::try_from(value.unwrap_or_default())
+                .map_err(|_| IngressError::InvalidEncoding(format!("{} overflows u64", field)))
+        };
+        let max_priority_fee = match &tx {
+            TypedTransaction::Eip1559(request) => Some(quantity(
+                request.max_priority_fee_per_gas,
+                "maxPriorityFeePerGas",
+            )?),
+            _ => None,
+        };
+        Ok(Self {
+            hash: encode_hex(&keccak256(raw)),
+            from: format!("{:?}", from),
+            to: tx.to_addr().map(|to| format!("{:?}", to)),
+            data: tx.data().map(|data| data.to_vec()).unwrap_or_default(),
+            gas_limit: quantity(tx.gas().copied(), "gas")?,
+            gas_price: quantity(tx.gas_price(), "gasPrice")?,
+            nonce: quantity(tx.nonce().copied(), "nonce")?,
+            max_priority_fee,
+        })
+    }
+}
+
+/// eth_sendRawTransaction 后端
+pub struct TransactionIngress {
+    adapters: Arc<AdapterManager>,
+    mempool: Arc<Mempool>,
+    estimator: Option<Arc<AccessSetEstimator>>,
+    chain_type: ChainType,
+    chain_id: u64,
+    block_gas_limit: u64,
+}
+
+impl TransactionIngress {
+    pub fn new(adapters: Arc<AdapterManager>, mempool: Arc<Mempool>) -> Self {
+        Self {
+            adapters,
+            mempool,
+            estimator: None,
+            chain_type: ChainType::Ethereum,
+            chain_id: DUBHE_CHAIN_ID,
+            block_gas_limit: DEFAULT_CALL_GAS,
+        }
+    }
+
+    /// 接收交易的交易池
+    pub fn mempool(&self) -> &Arc<Mempool> {
+        &self.mempool
+    }
+
+    /// 从哪条链读取账户 nonce（默认 Ethereum）
+    pub fn with_chain_type(mut self, chain_type: ChainType) -> Self {
+        self.chain_type = chain_type;
+        self
+    }
+
+    /// 入池前估算目标合约的读写集合
+    pub fn with_access_estimator(mut self, estimator: Arc<AccessSetEstimator>) -> Self {
+        self.estimator = Some(estimator);
+        self
+    }
+
+    /// 校验原始交易并放入交易池，返回交易哈希；重复提交返回同一哈希
+    pub async fn submit_raw(&self, raw: &[u8]) -> Result<String, IngressError> {
+        let signed = SignedTransaction::decode(raw, self.chain_id)?;
+
+        if signed.gas_limit < INTRINSIC_GAS {
+            return Err(IngressError::IntrinsicGasTooLow {
+                gas: signed.gas_limit,
+            });
+        }
+        if signed.gas_limit > self.block_gas_limit {
+            return Err(IngressError::GasLimitExceeded {
+                gas: signed.gas_limit,
+                limit: self.block_gas_limit,
+            });
+        }
+        let account_nonce = self
+            .adapters
+            .get_nonce(self.chain_type, &signed.from)
+            .await
+            .map_err(|e| IngressError::Internal(e.to_string()))?;
+        if signed.nonce < account_nonce {
+            return Err(IngressError::NonceTooLow {
+                expected: account_nonce,
+                actual: signed.nonce,
+            });
+        }
+
+        let hash = signed.hash.clone();
+        let transaction = self.to_transaction(signed).await;
+        match self.mempool.insert(transaction, account_nonce)? {
+            InsertOutcome::Added => info!("📥 Accepted transaction {}", hash),
+            InsertOutcome::Replaced { previous } => {
+                info!("📥 Transaction {} replaced {}", hash, previous)
+            }
+            InsertOutcome::Known => debug!("Transaction {} already submitted", hash),
+        }
+        Ok(hash)
+    }
+
+    /// 发送方账户总是写入（同一发送方的交易按 nonce 串行），
+    /// 目标合约的读写集合由估算器给出，未配置估算器时视为写整个合约
+    async fn to_transaction(&self, signed: SignedTransaction) -> Transaction {
+        let mut transaction = Transaction {
+            hash: signed.hash,
+            from: signed.from,
+            to: signed.to,
+            data: signed.data,
+            gas_limit: signed.gas_limit,
+            gas_price: signed.gas_price,
+            nonce: signed.nonce,
+            read_set: vec![],
+            write_set: vec![],
+            access_list: None,
+            max_priority_fee: signed.max_priority_fee,
+        };
+
+        let access = match &self.estimator {
+            Some(estimator) => estimator.estimate(&transaction).await.ok(),
+            None => None,
+        };
+        let access = access.unwrap_or_else(|| AccessList {
+            reads: vec![],
+            writes: transaction.to.iter().cloned().collect(),
+        });
+        transaction.read_set = access.reads;
+        transaction.write_set = std::iter::once(transaction.from.clone())
+            .chain(access.writes)
+            .collect();
+        transaction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use dubhe_adapter::{
+        AdapterError, AdapterResult, ChainAdapter, ContractMeta, TransactionReceipt,
+    };
+    use dubhe_scheduler::{MempoolConfig, ParallelScheduler, SchedulerConfig, StrategyType};
+    use ethers::signers::{LocalWallet, Signer};
+    use ethers::types::{Address, Eip1559TransactionRequest, TransactionRequest};
+    use tokio::sync::mpsc;
+    use tokio_util::sync::CancellationToken;
+
+    /// 所有账户的 nonce 都是固定值
+    struct NonceAdapter(u64);
+
+    #[async_trait]
+    impl ChainAdapter for NonceAdapter {
+        async fn get_contract_meta(&self, _address: &str) -> AdapterResult<ContractMeta> {
+            Err(AdapterError::Unsupported("get_contract_meta".to_string()))
+        }
+
+        async fn get_transaction_receipt(
+            &self,
+            _tx_hash: &str,
+        ) -> AdapterResult<TransactionReceipt> {
+            Err(AdapterError::Unsupported(
+                "get_transaction_receipt".to_string(),
+            ))
+        }
+
+        async fn get_balance(&self, _address: &str) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn get_nonce(&self, _address: &str) -> AdapterResult<u64> {
+            Ok(self.0)
+        }
+
+        async fn get_block_number(&self) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn subscribe_new_blocks(&self) -> AdapterResult<mpsc::Receiver<String>> {
+            Ok(mpsc::channel(1).1)
+        }
+
+        async fn subscribe_new_transactions(&self) -> AdapterResult<mpsc::Receiver<String>> {
+            Ok(mpsc::channel(1).1)
+        }
+    }
+
+    fn wallet() -> LocalWallet {
+        LocalWallet::from_bytes(&[0x11; 32])
+            .unwrap()
+            .with_chain_id(DUBHE_CHAIN_ID)
+    }
+
+    /// 本地签名的交易夹具
+    fn sign(tx: impl Into<TypedTransaction>) -> Vec<u8> {
+        let tx = tx.into();
+        let signature = wallet().sign_transaction_sync(&tx).unwrap();
+        tx.rlp_signed(&signature).to_vec()
+    }
+
+    fn legacy(nonce: u64) -> Vec<u8> {
+        sign(
+            TransactionRequest::new()
+                .to(Address::repeat_byte(0x22))
+                .nonce(nonce)
+                .gas(50_000)
+                .gas_price(1_000_000_000u64)
+                .data(vec![0xab, 0xcd])
+                .chain_id(DUBHE_CHAIN_ID),
+        )
+    }
+
+    async fn ingress(account_nonce: u64, capacity: usize) -> (TransactionIngress, Arc<Mempool>) {
+        let adapters = Arc::new(AdapterManager::new());
+        adapters
+            .register_adapter(ChainType::Ethereum, Box::new(NonceAdapter(account_nonce)))
+            .await;
+        let mempool = Arc::new(Mempool::new(MempoolConfig {
+            capacity,
+            batch_interval_ms: 10,
+        }));
+        (TransactionIngress::new(adapters, mempool.clone()), mempool)
+    }
+
+    #[test]
+    fn test_decode_recovers_sender() {
+        let expected = format!("{:?}", wallet().address());
+        let signed = SignedTransaction::decode(&legacy(3), DUBHE_CHAIN_ID).unwrap();
+        assert_eq!(signed.from, expected);
+        assert_eq!(signed.nonce, 3);
+        assert_eq!(signed.data, vec![0xab, 0xcd]);
+        assert_eq!(signed.hash, encode_hex(&keccak256(legacy(3))));
+        assert_eq!(signed.max_priority_fee, None);
+
+        let eip1559 = sign(
+            Eip1559TransactionRequest::new()
+                .to(Address::repeat_byte(0x22))
+                .nonce(4)
+                .gas(50_000)
+                .max_fee_per_gas(2_000_000_000u64)
+                .max_priority_fee_per_gas(1_000_000_000u64)
+                .chain_id(DUBHE_CHAIN_ID),
+        );
+        let signed = SignedTransaction::decode(&eip1559, DUBHE_CHAIN_ID).unwrap();
+        assert_eq!(signed.from, expected);
+        assert_eq!(signed.gas_price, 2_000_000_000);
+        assert_eq!(signed.max_priority_fee, Some(1_000_000_000));
+
+        assert!(matches!(
+            SignedTransaction::decode(&legacy(3), 1),
+            Err(IngressError::WrongChainId { expected: 1, .. })
+        ));
+        assert!(matches!(
+            SignedTransaction::decode(&[0xc0, 0x01], DUBHE_CHAIN_ID),
+            Err(IngressError::InvalidEncoding(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_submitted_transaction_reaches_batch_result() {
+        let (ingress, mempool) = ingress(0, 16).await;
+        let scheduler = Arc::new(
+            ParallelScheduler::new(StrategyType::Sequential, SchedulerConfig::default()).unwrap(),
+        );
+        let mut results = mempool.subscribe_results();
+        let cancel = CancellationToken::new();
+        let batcher = mempool.spawn_batcher(scheduler, cancel.clone());
+
+        let raw = legacy(0);
+        let hash = ingress.submit_raw(&raw).await.unwrap();
+        // 重复提交返回同一哈希，不会再次执行
+        assert_eq!(ingress.submit_raw(&raw).await.unwrap(), hash);
+
+        let batch = results.recv().await.unwrap();
+        assert_eq!(batch.transaction_results.len(), 1);
+        assert_eq!(batch.transaction_results[0].tx_hash, hash);
+        assert!(batch.transaction_results[0].success);
+        assert_eq!(ingress.submit_raw(&raw).await.unwrap(), hash);
+        assert!(mempool.is_empty());
+
+        cancel.cancel();
+        batcher.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejections_map_to_error_codes() {
+        let (ingress, _mempool) = ingress(5, 1).await;
+
+        let error = ingress.submit_raw(&legacy(4)).await.unwrap_err();
+        assert!(matches!(
+            error,
+            IngressError::NonceTooLow {
+                expected: 5,
+                actual: 4
+            }
+        ));
+        assert_eq!(
+            RpcError::from(error).code,
+            ErrorCode::ServerError(TRANSACTION_REJECTED_CODE)
+        );
+
+        let starved = sign(
+            TransactionRequest::new()
+                .to(Address::repeat_byte(0x22))
+                .nonce(5)
+                .gas(20_000)
+                .gas_price(1u64)
+                .chain_id(DUBHE_CHAIN_ID),
+        );
+        assert!(matches!(
+            ingress.submit_raw(&starved).await,
+            Err(IngressError::IntrinsicGasTooLow { gas: 20_000 })
+        ));
+
+        // 交易池已满
+        ingress.submit_raw(&legacy(5)).await.unwrap();
+        let error = ingress.submit_raw(&legacy(6)).await.unwrap_err();
+        assert_eq!(
+            RpcError::from(error).code,
+            ErrorCode::ServerError(MEMPOOL_FULL_CODE)
+        );
+
+        // 同一 nonce 的替换交易出价不足
+        let underpriced = sign(
+            TransactionRequest::new()
+                .to(Address::repeat_byte(0x33))
+                .nonce(5)
+                .gas(50_000)
+                .gas_price(1_050_000_000u64)
+                .chain_id(DUBHE_CHAIN_ID),
+        );
+        let error = ingress.submit_raw(&underpriced).await.unwrap_err();
+        assert!(matches!(
+            error,
+            IngressError::Mempool(MempoolError::Underpriced { .. })
+        ));
+        assert_eq!(
+            RpcError::from(error).code,
+            ErrorCode::ServerError(TRANSACTION_REJECTED_CODE)
+        );
+    }
+}
@@ -5,17 +5,31 @@
 //! - gRPC (高性能内部微服务调用)
 //! - WebSocket PubSub (事件推送)
 
+pub mod auth;
 pub mod error;
+pub mod execution;
 pub mod grpc;
+pub mod ingress;
+pub mod offchain;
 pub mod rpc;
 pub mod types;
 pub mod ws;
 
-pub use error::ApiError;
+pub use auth::{AuthConfig, Authenticator};
+pub use error::{to_rpc_error, ApiError, RpcErrorKind};
+pub use execution::{CallError, CallExecutor, CallRequest};
 pub use grpc::GrpcServer;
-pub use rpc::RpcServer;
+pub use ingress::{IngressError, TransactionIngress};
+pub use offchain::{
+    ExecutionStatus, OffchainExecutionParams, OffchainHandler, OffchainRpcConfig, OffchainSessions,
+    SessionEvent, SessionSubscription,
+};
+pub use rpc::{
+    AdminHandler, ConfigReloadReport, PrefetchHandler, PrefetchReport, RpcLimits, RpcServer,
+    SnapshotReport,
+};
 pub use types::*;
-pub use ws::WsServer;
+pub use ws::{ChainEvent, SubscriptionRegistry, WsServer};
 
 use anyhow::Result;
 use tokio::net::TcpListener;
@@ -28,7 +42,49 @@ pub struct ApiConfig {
     pub grpc_bind: String,
     pub ws_bind: String,
     pub max_connections: usize,
+    /// 单个调用的超时，批量请求中逐个计算
     pub request_timeout_ms: u64,
+    /// 单个批量请求最多包含的调用数，超过返回 -32600
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: usize,
+    /// 批量请求内同时执行的调用数
+    #[serde(default = "default_batch_concurrency")]
+    pub batch_concurrency: usize,
+    /// 每个 WebSocket 客户端最多积压的订阅通知数，超过即断开
+    #[serde(default = "default_ws_max_pending_messages")]
+    pub ws_max_pending_messages: usize,
+    /// eth_getLogs 单次查询最多跨越的区块数
+    #[serde(default = "default_max_logs_block_range")]
+    pub max_logs_block_range: u64,
+    /// eth_getLogs 单次查询最多返回的日志数
+    #[serde(default = "default_max_logs_results")]
+    pub max_logs_results: usize,
+    /// API Key 与限流配置
+    #[serde(default)]
+    pub auth: AuthConfig,
+    /// dubhe_executeOffchain 的请求限制
+    #[serde(default)]
+    pub offchain: OffchainRpcConfig,
+}
+
+fn default_ws_max_pending_messages() -> usize {
+    ws::DEFAULT_MAX_PENDING_MESSAGES
+}
+
+fn default_max_logs_block_range() -> u64 {
+    rpc::DEFAULT_MAX_LOGS_BLOCK_RANGE
+}
+
+fn default_max_logs_results() -> usize {
+    rpc::DEFAULT_MAX_LOGS_RESULTS
+}
+
+fn default_max_batch_size() -> usize {
+    rpc::DEFAULT_MAX_BATCH_SIZE
+}
+
+fn default_batch_concurrency() -> usize {
+    rpc::DEFAULT_BATCH_CONCURRENCY
 }
 
 impl Default for ApiConfig {
@@ -39,6 +95,13 @@ impl Default for ApiConfig {
             ws_bind: "127.0.0.1:8546".to_string(),
             max_connections: 1000,
             request_timeout_ms: 30000,
+            max_batch_size: default_max_batch_size(),
+            batch_concurrency: default_batch_concurrency(),
+            ws_max_pending_messages: default_ws_max_pending_messages(),
+            max_logs_block_range: default_max_logs_block_range(),
+            max_logs_results: default_max_logs_results(),
+            auth: AuthConfig::default(),
+            offchain: OffchainRpcConfig::default(),
         }
     }
 }
@@ -47,20 +110,121 @@ impl Default for ApiConfig {
 pub struct ApiServer {
     config: ApiConfig,
     rpc_server: RpcServer,
-    grpc_server: GrpcServer,
+    grpc_server: Option<GrpcServer>,
     ws_server: WsServer,
+    auth: std::sync::Arc<Authenticator>,
 }
 
 impl ApiServer {
     pub fn new(config: ApiConfig) -> Self {
+        Self::build(config, RpcServer::new())
+    }
+
+    /// 创建带只读执行后端的 API 服务器（启用 eth_call / eth_estimateGas）
+    pub fn with_executor(config: ApiConfig, executor: std::sync::Arc<CallExecutor>) -> Self {
+        Self::build(config, RpcServer::with_executor(executor))
+    }
+
+    fn build(config: ApiConfig, rpc_server: RpcServer) -> Self {
+        // HTTP 与 WebSocket 共享同一个限流器
+        let auth = std::sync::Arc::new(Authenticator::new(&config.auth));
+        let limits = RpcLimits {
+            max_batch_size: config.max_batch_size,
+            batch_concurrency: config.batch_concurrency,
+            request_timeout: std::time::Duration::from_millis(config.request_timeout_ms),
+            max_logs_block_range: config.max_logs_block_range,
+            max_logs_results: config.max_logs_results,
+        };
         Self {
-            rpc_server: RpcServer::new(),
-            grpc_server: GrpcServer::new(),
-            ws_server: WsServer::new(),
+            rpc_server: rpc_server.with_auth(auth.clone()).with_limits(limits),
+            grpc_server: None,
+            ws_server: WsServer::with_max_pending(config.ws_max_pending_messages)
+                .with_auth(auth.clone()),
+            auth,
             config,
         }
     }
 
+    /// 启用 gRPC 服务，批次提交与状态查询委托给调度器；同时启用 dubhe_feeHistory
+    pub fn with_scheduler(
+        mut self,
+        scheduler: std::sync::Arc<dubhe_scheduler::ParallelScheduler>,
+    ) -> Self {
+        self.rpc_server = self.rpc_server.with_scheduler(scheduler.clone());
+        self.grpc_server = Some(GrpcServer::new(scheduler));
+        self
+    }
+
+    /// 启用索引查询方法（eth_getLogs / dubhe_queryEvents）
+    pub fn with_indexer(mut self, indexer: std::sync::Arc<dubhe_state::Indexer>) -> Self {
+        self.rpc_server = self.rpc_server.with_indexer(indexer);
+        self
+    }
+
+    /// 特权方法按调用方角色做访问控制
+    pub fn with_access_control(
+        mut self,
+        access: std::sync::Arc<dubhe_security::AccessControl>,
+    ) -> Self {
+        self.rpc_server = self.rpc_server.with_access_control(access);
+        self
+    }
+
+    /// 启用节点管理方法
+    pub fn with_admin(mut self, admin: std::sync::Arc<dyn AdminHandler>) -> Self {
+        self.rpc_server = self.rpc_server.with_admin(admin);
+        self
+    }
+
+    /// 启用 dubhe_executeOffchain / dubhe_getExecutionStatus，以及 WebSocket 上的 dubhe_subscribeExecution
+    pub fn with_offchain(mut self, handler: std::sync::Arc<dyn OffchainHandler>) -> Self {
+        let sessions =
+            std::sync::Arc::new(OffchainSessions::new(handler, self.config.offchain.clone()));
+        self.rpc_server = self.rpc_server.with_offchain(sessions.clone());
+        self.ws_server = self.ws_server.with_offchain(sessions);
+        self
+    }
+
+    /// 启用 dubhe_prefetchPackage
+    pub fn with_prefetch(mut self, prefetcher: std::sync::Arc<dyn PrefetchHandler>) -> Self {
+        self.rpc_server = self.rpc_server.with_prefetch(prefetcher);
+        self
+    }
+
+    /// 启用 eth_sendRawTransaction
+    pub fn with_ingress(mut self, ingress: std::sync::Arc<TransactionIngress>) -> Self {
+        self.rpc_server = self.rpc_server.with_ingress(ingress);
+        self
+    }
+
+    /// dubhe_getChannelStatus 报告各链适配器状态
+    pub fn with_adapters(
+        mut self,
+        adapters: std::sync::Arc<dubhe_adapter::AdapterManager>,
+    ) -> Self {
+        self.rpc_server = self.rpc_server.with_adapters(adapters);
+        self
+    }
+
+    /// 启用 dubhe_verifyAttestation
+    pub fn with_attestation(
+        mut self,
+        provider: std::sync::Arc<dyn dubhe_security::AttestationProvider>,
+    ) -> Self {
+        self.rpc_server = self.rpc_server.with_attestation(provider);
+        self
+    }
+
+    /// HTTP 与 WebSocket 共享的认证与限流器，可热加载限流参数
+    pub fn auth(&self) -> std::sync::Arc<Authenticator> {
+        self.auth.clone()
+    }
+
+    /// WebSocket 订阅服务，用于接入适配器事件流
+    pub fn ws(&self) -> &WsServer {
+        &self.ws_server
+    }
+
     /// 启动所有 API 服务
     pub async fn start(&self) -> Result<()> {
         info!("Starting Dubhe Channel API servers...");
@@ -75,14 +239,36 @@ impl ApiServer {
         Ok(())
     }
 
+    /// 在已绑定的监听器上提供所有 API 服务，忽略配置中的监听地址
+    ///
+    /// 监听器可绑定在端口 0 上，由调用方读取实际端口（集成测试使用）
+    pub async fn serve(&self, listeners: ApiListeners) -> Result<()> {
+        let grpc_task = async {
+            match &self.grpc_server {
+                Some(grpc_server) => grpc_server.serve(listeners.grpc).await,
+                None => Ok(()),
+            }
+        };
+        tokio::try_join!(
+            self.rpc_server.serve(listeners.rpc),
+            grpc_task,
+            self.ws_server.serve(listeners.ws)
+        )?;
+        Ok(())
+    }
+
     async fn start_rpc(&self) -> Result<()> {
         info!("Starting JSON-RPC server on {}", self.config.rpc_bind);
         self.rpc_server.start(&self.config.rpc_bind).await
     }
 
     async fn start_grpc(&self) -> Result<()> {
+        let Some(grpc_server) = &self.grpc_server else {
+            info!("gRPC server disabled: no scheduler attached");
+            return Ok(());
+        };
         info!("Starting gRPC server on {}", self.config.grpc_bind);
-        self.grpc_server.start(&self.config.grpc_bind).await
+        grpc_server.start(&self.config.grpc_bind).await
     }
 
     async fn start_ws(&self) -> Result<()> {
@@ -90,3 +276,10 @@ impl ApiServer {
         self.ws_server.start(&self.config.ws_bind).await
     }
 }
+
+/// [`ApiServer::serve`] 使用的已绑定监听器
+pub struct ApiListeners {
+    pub rpc: TcpListener,
+    pub grpc: TcpListener,
+    pub ws: TcpListener,
+}
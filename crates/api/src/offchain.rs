@@ -0,0 +1,408 @@
+//! 链下执行入口
+//!
+//! dubhe_executeOffchain 校验请求、在服务端生成会话 ID 后交给节点的 [`OffchainHandler`]；
+//! 异步模式立即返回会话 ID，客户端通过 dubhe_getExecutionStatus 轮询结果，
+//! 或经 WebSocket 的 dubhe_subscribeExecution 接收进度与最终结果
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::stream::{BoxStream, StreamExt};
+use jsonrpc_core::Error as RpcError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use crate::error::to_rpc_error;
+
+/// dubhe_executeOffchain 的限制
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OffchainRpcConfig {
+    /// 单次调用最多的参数个数
+    pub max_arguments: usize,
+    /// 单次调用最多引用的共享对象数
+    pub max_shared_objects: usize,
+    /// gas 预算上限
+    pub max_gas_budget: u64,
+    /// 已结束会话的状态保留时间（秒）
+    pub status_retention_secs: u64,
+}
+
+impl Default for OffchainRpcConfig {
+    fn default() -> Self {
+        Self {
+            max_arguments: 32,
+            max_shared_objects: 16,
+            max_gas_budget: 50_000_000_000, // 50 SUI
+            status_retention_secs: 600,
+        }
+    }
+}
+
+/// dubhe_executeOffchain 参数，会话 ID 由服务端生成
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct OffchainExecutionParams {
+    pub package_id: String,
+    pub function_name: String,
+    #[serde(default)]
+    pub arguments: Vec<Value>,
+    #[serde(default)]
+    pub shared_objects: Vec<String>,
+    #[serde(with = "crate::types::quantity")]
+    pub gas_budget: u64,
+    /// 立即返回会话 ID，不等待执行结束
+    #[serde(default, rename = "async")]
+    pub async_mode: bool,
+    /// 只读查询，输入对象版本未变时可复用缓存的结果
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+impl OffchainExecutionParams {
+    /// 按配置校验请求
+    pub fn validate(&self, config: &OffchainRpcConfig) -> Result<(), RpcError> {
+        if self.package_id.trim().is_empty() {
+            return Err(RpcError::invalid_params("packageId must not be empty"));
+        }
+        if self.function_name.trim().is_empty() {
+            return Err(RpcError::invalid_params("functionName must not be empty"));
+        }
+        if self.arguments.len() > config.max_arguments {
+            return Err(RpcError::invalid_params(format!(
+                "Too many arguments: {} > {}",
+                self.arguments.len(),
+                config.max_arguments
+            )));
+        }
+        if self.shared_objects.len() > config.max_shared_objects {
+            return Err(RpcError::invalid_params(format!(
+                "Too many shared objects: {} > {}",
+                self.shared_objects.len(),
+                config.max_shared_objects
+            )));
+        }
+        if self.gas_budget == 0 || self.gas_budget > config.max_gas_budget {
+            return Err(RpcError::invalid_params(format!(
+                "gasBudget must be between 1 and {}",
+                config.max_gas_budget
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// 链下执行后端，由节点实现
+#[async_trait]
+pub trait OffchainHandler: Send + Sync {
+    /// 以给定会话 ID 执行，返回序列化后的执行结果
+    async fn execute(&self, session_id: String, params: OffchainExecutionParams) -> Result<Value>;
+
+    /// 按录制重放会话，返回原始结果、重放结果与差异；未开启录制的后端不支持
+    async fn replay(&self, session_id: String) -> Result<Value> {
+        anyhow::bail!("Replay is not supported for session {}", session_id)
+    }
+
+    /// 按会话记录的回写交易从主网核对执行结果，返回核对报告；不存档结果的后端不支持
+    async fn verify(&self, session_id: String) -> Result<Value> {
+        anyhow::bail!("Verification is not supported for session {}", session_id)
+    }
+
+    /// 只读查询缓存的统计；未启用缓存的后端不支持
+    async fn query_cache_stats(&self) -> Result<Value> {
+        anyhow::bail!("Query cache is not supported")
+    }
+
+    /// 会话的执行进度，在 [`Self::execute`] 之前调用；流必须在会话结束时结束。
+    /// 不上报进度的后端返回 `None`，订阅方只收到最终事件
+    fn subscribe_progress(&self, _session_id: &str) -> Option<BoxStream<'static, Value>> {
+        None
+    }
+}
+
+/// 会话状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub enum ExecutionStatus {
+    Pending,
+    Completed { result: Value },
+    Failed { error: String },
+}
+
+impl ExecutionStatus {
+    fn finished(&self) -> bool {
+        !matches!(self, Self::Pending)
+    }
+
+    /// 已结束会话的最终事件
+    fn final_event(&self) -> Option<SessionEvent> {
+        match self {
+            Self::Pending => None,
+            Self::Completed { result } => Some(SessionEvent::Completed {
+                result: result.clone(),
+            }),
+            Self::Failed { error } => Some(SessionEvent::Failed {
+                error: error.clone(),
+            }),
+        }
+    }
+}
+
+/// 每个会话最多缓冲的事件数
+const SESSION_EVENT_CAPACITY: usize = 64;
+
+/// 推送给 dubhe_subscribeExecution 订阅方的会话事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum SessionEvent {
+    /// 节点上报的执行进度
+    Progress { progress: Value },
+    /// 最终事件，携带执行结果
+    Completed { result: Value },
+    /// 最终事件
+    Failed { error: String },
+}
+
+impl SessionEvent {
+    pub fn is_final(&self) -> bool {
+        !matches!(self, Self::Progress { .. })
+    }
+}
+
+/// 会话事件订阅
+pub enum SessionSubscription {
+    /// 会话执行中，事件以最终事件结束
+    Live(broadcast::Receiver<SessionEvent>),
+    /// 会话已结束，只有最终事件
+    Finished(SessionEvent),
+}
+
+struct TrackedSession {
+    status: ExecutionStatus,
+    updated_at: Instant,
+    /// 执行中会话的事件通道，结束后移除
+    events: Option<broadcast::Sender<SessionEvent>>,
+}
+
+/// 经 RPC 发起的会话状态，已结束的会话保留一段时间后清理
+pub struct OffchainSessions {
+    handler: Arc<dyn OffchainHandler>,
+    config: OffchainRpcConfig,
+    sessions: Mutex<HashMap<String, TrackedSession>>,
+}
+
+impl OffchainSessions {
+    pub fn new(handler: Arc<dyn OffchainHandler>, config: OffchainRpcConfig) -> Self {
+        Self {
+            handler,
+            config,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn set_status(&self, session_id: &str, status: ExecutionStatus) {
+        let now = Instant::now();
+        let retention = Duration::from_secs(self.config.status_retention_secs);
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, session| {
+            !session.status.finished() || now.duration_since(session.updated_at) < retention
+        });
+
+        let previous = sessions
+            .remove(session_id)
+            .and_then(|session| session.events);
+        let events = match status.final_event() {
+            Some(event) => {
+                if let Some(events) = previous {
+                    // 没有订阅者时发送失败，忽略即可
+                    let _ = events.send(event);
+                }
+                None
+            }
+            None => Some(previous.unwrap_or_else(|| broadcast::channel(SESSION_EVENT_CAPACITY).0)),
+        };
+        sessions.insert(
+            session_id.to_string(),
+            TrackedSession {
+                status,
+                updated_at: now,
+                events,
+            },
+        );
+    }
+
+    fn publish(&self, session_id: &str, event: SessionEvent) {
+        if let Some(events) = self
+            .sessions
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .and_then(|session| session.events.as_ref())
+        {
+            let _ = events.send(event);
+        }
+    }
+
+    /// 订阅会话事件；已结束的会话直接给出最终事件，未知会话返回 `None`
+    pub fn subscribe(&self, session_id: &str) -> Option<SessionSubscription> {
+        let sessions = self.sessions.lock().unwrap();
+        let session = sessions.get(session_id)?;
+        match session.status.final_event() {
+            Some(event) => Some(SessionSubscription::Finished(event)),
+            None => Some(SessionSubscription::Live(
+                session.events.as_ref()?.subscribe(),
+            )),
+        }
+    }
+
+    /// 会话当前状态
+    pub fn status(&self, session_id: &str) -> Option<ExecutionStatus> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .map(|session| session.status.clone())
+    }
+
+    async fn run(&self, session_id: String, params: OffchainExecutionParams) -> ExecutionStatus {
+        // 先订阅进度再开始执行，执行期间逐条转发给会话订阅方
+        let progress = self.handler.subscribe_progress(&session_id);
+        let forward = async {
+            if let Some(mut progress) = progress {
+                while let Some(progress) = progress.next().await {
+                    self.publish(&session_id, SessionEvent::Progress { progress });
+                }
+            }
+        };
+        let (outcome, ()) = tokio::join!(self.handler.execute(session_id.clone(), params), forward);
+
+        let status = match outcome {
+            Ok(result) => ExecutionStatus::Completed { result },
+            Err(e) => {
+                warn!("Offchain session {} failed: {}", session_id, e);
+                ExecutionStatus::Failed {
+                    error: e.to_string(),
+                }
+            }
+        };
+        self.set_status(&session_id, status.clone());
+        status
+    }
+
+    /// dubhe_executeOffchain
+    pub async fn execute(
+        self: &Arc<Self>,
+        params: OffchainExecutionParams,
+    ) -> Result<Value, RpcError> {
+        params.validate(&self.config)?;
+        let session_id = format!("rpc-{}", uuid::Uuid::new_v4());
+        info!(
+            "🎯 Offchain execution requested over RPC: {} ({}::{})",
+            session_id, params.package_id, params.function_name
+        );
+        self.set_status(&session_id, ExecutionStatus::Pending);
+
+        if params.async_mode {
+            let sessions = self.clone();
+            let id = session_id.clone();
+            tokio::spawn(async move { sessions.run(id, params).await });
+            return Ok(serde_json::json!({
+                "sessionId": session_id,
+                "status": "pending",
+            }));
+        }
+
+        match self.run(session_id, params).await {
+            ExecutionStatus::Completed { result } => Ok(result),
+            ExecutionStatus::Failed { error } => Err(RpcError {
+                code: jsonrpc_core::ErrorCode::InternalError,
+                message: error,
+                data: None,
+            }),
+            ExecutionStatus::Pending => unreachable!("session finished"),
+        }
+    }
+
+    /// dubhe_getExecutionStatus
+    pub fn get_status(&self, session_id: &str) -> Result<Value, RpcError> {
+        let status = self
+            .status(session_id)
+            .ok_or_else(|| RpcError::invalid_params(format!("Unknown session {}", session_id)))?;
+        let mut value = serde_json::to_value(status).map_err(|e| RpcError {
+            code: jsonrpc_core::ErrorCode::InternalError,
+            message: e.to_string(),
+            data: None,
+        })?;
+        value["sessionId"] = Value::String(session_id.to_string());
+        Ok(value)
+    }
+
+    /// dubhe_replayTransaction
+    pub async fn replay(&self, session_id: String) -> Result<Value, RpcError> {
+        info!("🔁 Replay requested over RPC: {}", session_id);
+        self.handler
+            .replay(session_id)
+            .await
+            .map_err(|e| to_rpc_error(&e))
+    }
+
+    /// dubhe_verifyExecution
+    pub async fn verify(&self, session_id: String) -> Result<Value, RpcError> {
+        info!("🔎 Verification requested over RPC: {}", session_id);
+        self.handler
+            .verify(session_id)
+            .await
+            .map_err(|e| to_rpc_error(&e))
+    }
+
+    /// dubhe_queryCacheStats
+    pub async fn query_cache_stats(&self) -> Result<Value, RpcError> {
+        self.handler
+            .query_cache_stats()
+            .await
+            .map_err(|e| to_rpc_error(&e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> OffchainExecutionParams {
+        OffchainExecutionParams {
+            package_id: "0xpkg".to_string(),
+            function_name: "counter::increment".to_string(),
+            arguments: vec![],
+            shared_objects: vec!["0xa".to_string()],
+            gas_budget: 1_000,
+            async_mode: false,
+            read_only: false,
+        }
+    }
+
+    #[test]
+    fn test_validate_limits() {
+        let config = OffchainRpcConfig {
+            max_arguments: 1,
+            max_gas_budget: 10_000,
+            ..Default::default()
+        };
+        assert!(params().validate(&config).is_ok());
+
+        let mut request = params();
+        request.package_id = " ".to_string();
+        assert!(request.validate(&config).is_err());
+
+        let mut request = params();
+        request.arguments = vec![Value::from(1), Value::from(2)];
+        assert!(request.validate(&config).is_err());
+
+        let mut request = params();
+        request.gas_budget = 10_001;
+        assert!(request.validate(&config).is_err());
+    }
+}
@@ -1,26 +1,323 @@
 //! JSON-RPC 服务器
 //!
-//! 兼容 EIP-1474 标准，支持 Metamask 等钱包直接连接
+//! 兼容 EIP-1474 标准，支持 Metamask 等钱包直接连接。
+//! 按 JSON-RPC 2.0 接受批量请求：批内调用并发执行，逐个认证、限流和计时，
+//! 通知（不带 id 的请求）执行后不产生响应
 
 use anyhow::Result;
-use axum::{extract::State, http::StatusCode, response::Json, routing::post, Router};
-use jsonrpc_core::{IoHandler, Params, Value};
+use async_trait::async_trait;
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::post,
+    Router,
+};
+use futures::stream::{self, StreamExt};
+use jsonrpc_core::{ErrorCode, IoHandler, Params, Value};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tower_http::cors::CorsLayer;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use crate::error::ApiError;
+use crate::auth::{required_permission, AuthError, Authenticator, Caller};
+use crate::error::{to_rpc_error, ApiError};
+use crate::execution::{
+    decode_hex, encode_hex, parse_quantity, CallError, CallExecutor, CallRequest,
+};
+use crate::ingress::{TransactionIngress, DUBHE_CHAIN_ID};
+use crate::offchain::{OffchainExecutionParams, OffchainSessions};
 use crate::types::*;
+use dubhe_adapter::AdapterManager;
+use dubhe_scheduler::ParallelScheduler;
+use dubhe_security::{
+    AccessControl, AttestationProvider, AttestationReport, Permission, Principal,
+};
+use dubhe_state::{EventQuery, Indexer, LogFilter};
+use dubhe_vm_runtime::TraceConfig;
+
+/// dubhe_queryEvents 默认每页条数
+const DEFAULT_QUERY_LIMIT: usize = 100;
+
+/// debug_traceCall 最多保留的跟踪步数，请求中更大的 maxSteps 按此截断
+pub const MAX_TRACE_STEPS: usize = 100_000;
+
+/// 默认单个批量请求最多包含的调用数
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 100;
+
+/// 默认批量请求内同时执行的调用数
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 16;
+
+/// 默认 eth_getLogs 单次查询最多跨越的区块数
+pub const DEFAULT_MAX_LOGS_BLOCK_RANGE: u64 = 10_000;
+
+/// 默认 eth_getLogs 单次查询最多返回的日志数
+pub const DEFAULT_MAX_LOGS_RESULTS: usize = 10_000;
+
+/// 有副作用、开始后不应中途取消的方法：超时只是不再等待，执行在后台完成
+///
+/// 其余方法超时即取消，处理耗时受 `request_timeout` 约束
+fn runs_to_completion(method: &str) -> bool {
+    matches!(
+        method,
+        "dubhe_executeOffchain" | "dubhe_createSnapshot" | "eth_sendRawTransaction"
+    )
+}
+
+/// 请求对象无效或批量请求超限的 JSON-RPC 错误码
+pub const INVALID_REQUEST_CODE: i64 = -32600;
+
+/// 单个调用超时的 JSON-RPC 错误码（实现自定义的服务端错误区间）
+pub const REQUEST_TIMEOUT_CODE: i64 = -32001;
+
+/// 请求处理限制
+#[derive(Debug, Clone)]
+pub struct RpcLimits {
+    pub max_batch_size: usize,
+    pub batch_concurrency: usize,
+    /// 单个调用的超时，批量请求中逐个计时
+    pub request_timeout: Duration,
+    /// eth_getLogs 单次查询最多跨越的区块数
+    pub max_logs_block_range: u64,
+    /// eth_getLogs 单次查询最多返回的日志数
+    pub max_logs_results: usize,
+}
+
+impl Default for RpcLimits {
+    fn default() -> Self {
+        Self {
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
+            request_timeout: Duration::from_secs(30),
+            max_logs_block_range: DEFAULT_MAX_LOGS_BLOCK_RANGE,
+            max_logs_results: DEFAULT_MAX_LOGS_RESULTS,
+        }
+    }
+}
+
+/// 配置热加载结果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigReloadReport {
+    /// 已在运行时生效的字段
+    pub applied: Vec<String>,
+    /// 发生变化但需要重启才能生效、因而被拒绝的字段
+    pub rejected: Vec<String>,
+}
+
+/// 节点状态快照结果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotReport {
+    pub id: String,
+    /// 快照目录
+    pub path: String,
+    /// 增量快照的基准快照 ID
+    pub base: Option<String>,
+    /// 清单中的文件数
+    pub files: usize,
+    /// 实际写入快照目录（不与基准共享）的文件数
+    pub written_files: usize,
+    pub written_bytes: u64,
+}
+
+/// 节点管理操作，由节点实现并通过 `dubhe_` 管理方法暴露
+#[async_trait]
+pub trait AdminHandler: Send + Sync {
+    /// 从配置文件重新加载告警规则，返回生效的规则数
+    async fn reload_alert_rules(&self) -> Result<usize>;
+
+    /// 重新读取配置文件，应用可在运行时调整的配置段
+    async fn reload_config(&self) -> Result<ConfigReloadReport>;
+
+    /// 在节点运行时于 `out` 创建状态快照；给出 `base` 时只写入相对该快照变化的文件
+    async fn create_snapshot(&self, out: String, _base: Option<String>) -> Result<SnapshotReport> {
+        anyhow::bail!(
+            "Snapshots are not supported by this node, cannot write {}",
+            out
+        )
+    }
+}
+
+/// dubhe_prefetchPackage 结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefetchReport {
+    pub package_id: String,
+    /// 编译产物大小（字节）
+    pub code_size: usize,
+    pub entry_points: Vec<String>,
+}
+
+/// 合约包预取，由节点的发现管线实现
+#[async_trait]
+pub trait PrefetchHandler: Send + Sync {
+    /// 立即拉取并编译 `package_id`，不经过发现管线的名单与去重过滤
+    async fn prefetch_package(&self, package_id: String) -> Result<PrefetchReport>;
+}
 
 /// JSON-RPC 服务器
 pub struct RpcServer {
     handler: IoHandler,
+    auth: Option<Arc<Authenticator>>,
+    access: Option<Arc<AccessControl>>,
+    limits: RpcLimits,
+}
+
+struct RpcState {
+    handler: IoHandler,
+    auth: Option<Arc<Authenticator>>,
+    access: Option<Arc<AccessControl>>,
+    limits: RpcLimits,
+}
+
+/// 单个调用的响应，通知没有响应
+struct CallResponse {
+    body: Value,
+    /// 被限流时建议的重试间隔
+    retry_after: Option<Duration>,
+}
+
+impl RpcState {
+    /// 识别调用方、限流，特权方法再经访问控制检查
+    fn authorize(
+        &self,
+        authorization: Option<&str>,
+        ip: IpAddr,
+        method: &str,
+    ) -> Result<(), AuthError> {
+        let caller = match &self.auth {
+            Some(auth) => {
+                let caller = auth.identify(authorization, ip)?;
+                auth.authorize(&caller, method)?;
+                caller
+            }
+            None => Caller::Ip(ip),
+        };
+
+        let Some(permission) = required_permission(method) else {
+            return Ok(());
+        };
+        // 快照写入节点文件系统，即使未启用访问控制也不接受匿名调用方
+        if permission == Permission::CreateSnapshot && caller.principal() == Principal::Anonymous {
+            return Err(AuthError::Forbidden(format!(
+                "{} requires an API key",
+                method
+            )));
+        }
+        if let Some(access) = &self.access {
+            access
+                .check(&caller.principal(), permission)
+                .map_err(|denied| AuthError::Forbidden(denied.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// 认证并执行单个调用，超时按调用计算
+    async fn call(
+        &self,
+        authorization: Option<&str>,
+        ip: IpAddr,
+        request: Value,
+    ) -> Option<CallResponse> {
+        let id = match &request {
+            Value::Object(object) => object.get("id").cloned(),
+            _ => Some(Value::Null),
+        };
+        let method = request
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+
+        if let Err(e) = self.authorize(authorization, ip, method) {
+            let retry_after = match e {
+                AuthError::RateLimited { retry_after } => Some(retry_after),
+                _ => None,
+            };
+            return id.map(|id| CallResponse {
+                body: error_response(id, e.to_error_object()),
+                retry_after,
+            });
+        }
+
+        let timeout = self.limits.request_timeout;
+        let request_str = request.to_string();
+        let outcome = if runs_to_completion(method) {
+            let handler = self.handler.clone();
+            let task = tokio::spawn(async move { handler.handle_request(&request_str).await });
+            tokio::time::timeout(timeout, task).await
+        } else {
+            let task = async {
+                Ok::<_, tokio::task::JoinError>(self.handler.handle_request(&request_str).await)
+            };
+            tokio::time::timeout(timeout, task).await
+        };
+        let body = match outcome {
+            Ok(Ok(response)) => serde_json::from_str(&response?)
+                .map_err(|e| error!("Failed to parse RPC response: {}", e))
+                .ok()?,
+            Ok(Err(e)) => {
+                error!("RPC method {} panicked: {}", method, e);
+                let error = internal_error(format!("RPC method {} failed", method));
+                error_response(id?, json!(error))
+            }
+            Err(_) => {
+                warn!("⏱️ RPC method {} timed out after {:?}", method, timeout);
+                let message = format!("Request timed out after {}ms", timeout.as_millis());
+                error_response(
+                    id?,
+                    json!({ "code": REQUEST_TIMEOUT_CODE, "message": message }),
+                )
+            }
+        };
+        Some(CallResponse {
+            body,
+            retry_after: None,
+        })
+    }
+
+    /// 并发执行批量请求，响应按请求顺序排列、省略通知
+    async fn call_batch(
+        &self,
+        authorization: Option<&str>,
+        ip: IpAddr,
+        requests: Vec<Value>,
+    ) -> Vec<CallResponse> {
+        let requests = stream::iter(requests.into_iter().enumerate());
+        let mut responses: Vec<(usize, CallResponse)> = requests
+            .map(|(index, request)| async move {
+                self.call(authorization, ip, request)
+                    .await
+                    .map(|response| (index, response))
+            })
+            .buffer_unordered(self.limits.batch_concurrency.max(1))
+            .filter_map(|response| async move { response })
+            .collect()
+            .await;
+        responses.sort_unstable_by_key(|(index, _)| *index);
+        responses
+            .into_iter()
+            .map(|(_, response)| response)
+            .collect()
+    }
 }
 
 impl RpcServer {
     pub fn new() -> Self {
+        Self::build(None)
+    }
+
+    /// 启用 eth_call / eth_estimateGas / debug_traceCall 执行路径
+    pub fn with_executor(executor: Arc<CallExecutor>) -> Self {
+        Self::build(Some(executor))
+    }
+
+    fn build(executor: Option<Arc<CallExecutor>>) -> Self {
         let mut handler = IoHandler::new();
 
         // EIP-1474 标准方法
@@ -28,38 +325,256 @@ impl RpcServer {
         handler.add_method("eth_blockNumber", Self::eth_block_number);
         handler.add_method("eth_getBalance", Self::eth_get_balance);
         handler.add_method("eth_getTransactionCount", Self::eth_get_transaction_count);
-        handler.add_method("eth_sendRawTransaction", Self::eth_send_raw_transaction);
-        handler.add_method("eth_call", Self::eth_call);
-        handler.add_method("eth_estimateGas", Self::eth_estimate_gas);
         handler.add_method(
             "eth_getTransactionReceipt",
             Self::eth_get_transaction_receipt,
         );
-        handler.add_method("eth_getLogs", Self::eth_get_logs);
 
         // 自定义扩展方法
         handler.add_method("dubhe_getChannelStatus", Self::dubhe_get_channel_status);
         handler.add_method("dubhe_loadContract", Self::dubhe_load_contract);
         handler.add_method("dubhe_getParallelStats", Self::dubhe_get_parallel_stats);
 
-        // Phase 1 链下执行方法
-        handler.add_method("dubhe_executeOffchain", Self::dubhe_execute_offchain);
+        // Phase 1 链下执行方法（dubhe_executeOffchain 需配置执行后端，见 with_offchain）
         handler.add_method("dubhe_getOffchainStats", Self::dubhe_get_offchain_stats);
 
-        Self { handler }
+        // 只读执行方法：未配置执行后端时不注册，请求返回 method not found
+        if let Some(executor) = executor {
+            let call_executor = executor.clone();
+            handler.add_method("eth_call", move |params| {
+                Self::eth_call(call_executor.clone(), params)
+            });
+            let estimate_executor = executor.clone();
+            handler.add_method("eth_estimateGas", move |params| {
+                Self::eth_estimate_gas(estimate_executor.clone(), params)
+            });
+            handler.add_method("debug_traceCall", move |params| {
+                Self::debug_trace_call(executor.clone(), params)
+            });
+        }
+
+        Self {
+            handler,
+            auth: None,
+            access: None,
+            limits: RpcLimits::default(),
+        }
+    }
+
+    /// 启用基于二级索引的查询方法（eth_getLogs / dubhe_queryEvents）
+    ///
+    /// eth_getLogs 的区块范围与结果数限制取自此前 [`with_limits`](Self::with_limits) 的设置
+    pub fn with_indexer(mut self, indexer: Arc<Indexer>) -> Self {
+        let logs_indexer = indexer.clone();
+        let limits = self.limits.clone();
+        self.handler.add_method("eth_getLogs", move |params| {
+            Self::eth_get_logs(logs_indexer.clone(), limits.clone(), params)
+        });
+        self.handler.add_method("dubhe_queryEvents", move |params| {
+            Self::dubhe_query_events(indexer.clone(), params)
+        });
+        self
+    }
+
+    /// 启用管理方法（dubhe_reloadAlertRules / dubhe_reloadConfig / dubhe_createSnapshot）
+    pub fn with_admin(mut self, admin: Arc<dyn AdminHandler>) -> Self {
+        let rules_admin = admin.clone();
+        self.handler.add_method("dubhe_reloadAlertRules", move |_params| {
+            let admin = rules_admin.clone();
+            async move {
+                let rules = admin
+                    .reload_alert_rules()
+                    .await
+                    .map_err(|e| to_rpc_error(&e))?;
+                Ok(json!({ "rules": rules }))
+            }
+        });
+        let config_admin = admin.clone();
+        self.handler.add_method("dubhe_reloadConfig", move |_params| {
+            let admin = config_admin.clone();
+            async move {
+                let report = admin
+                    .reload_config()
+                    .await
+                    .map_err(|e| to_rpc_error(&e))?;
+                Ok(json!(report))
+            }
+        });
+        self.handler.add_method("dubhe_createSnapshot", move |params: Params| {
+            let admin = admin.clone();
+            async move {
+                let request: CreateSnapshotParams =
+                    parse_request("dubhe_createSnapshot", params)?;
+                let report = admin
+                    .create_snapshot(request.out, request.base)
+                    .await
+                    .map_err(|e| to_rpc_error(&e))?;
+                Ok(json!(report))
+            }
+        });
+        self
+    }
+
+    /// 启用链下执行方法（dubhe_executeOffchain / dubhe_getExecutionStatus / dubhe_replayTransaction /
+    /// dubhe_verifyExecution / dubhe_queryCacheStats）
+    pub fn with_offchain(mut self, sessions: Arc<OffchainSessions>) -> Self {
+        let execute_sessions = sessions.clone();
+        self.handler.add_method("dubhe_executeOffchain", move |params: Params| {
+            let sessions = execute_sessions.clone();
+            async move {
+                let request: OffchainExecutionParams =
+                    parse_request("dubhe_executeOffchain", params)?;
+                sessions.execute(request).await
+            }
+        });
+        let status_sessions = sessions.clone();
+        self.handler.add_method("dubhe_getExecutionStatus", move |params: Params| {
+            let sessions = status_sessions.clone();
+            async move {
+                let (session_id,): (String,) = params.parse()?;
+                sessions.get_status(&session_id)
+            }
+        });
+        let verify_sessions = sessions.clone();
+        self.handler.add_method("dubhe_verifyExecution", move |params: Params| {
+            let sessions = verify_sessions.clone();
+            async move {
+                let (session_id,): (String,) = params.parse()?;
+                sessions.verify(session_id).await
+            }
+        });
+        let stats_sessions = sessions.clone();
+        self.handler.add_method("dubhe_queryCacheStats", move |_params: Params| {
+            let sessions = stats_sessions.clone();
+            async move { sessions.query_cache_stats().await }
+        });
+        self.handler.add_method("dubhe_replayTransaction", move |params: Params| {
+            let sessions = sessions.clone();
+            async move {
+                let (session_id,): (String,) = params.parse()?;
+                sessions.replay(session_id).await
+            }
+        });
+        self
+    }
+
+    /// 启用合约包预取（dubhe_prefetchPackage）
+    pub fn with_prefetch(mut self, prefetcher: Arc<dyn PrefetchHandler>) -> Self {
+        self.handler.add_method("dubhe_prefetchPackage", move |params: Params| {
+            let prefetcher = prefetcher.clone();
+            async move {
+                let (package_id,): (String,) = params.parse()?;
+                let report = prefetcher
+                    .prefetch_package(package_id)
+                    .await
+                    .map_err(|e| to_rpc_error(&e))?;
+                Ok(json!(report))
+            }
+        });
+        self
+    }
+
+    /// 启用交易提交（eth_sendRawTransaction），交易经校验后放入交易池；
+    /// 同时提供 mempool_status / mempool_content 调试方法
+    pub fn with_ingress(mut self, ingress: Arc<TransactionIngress>) -> Self {
+        let mempool = ingress.mempool().clone();
+        self.handler.add_method("mempool_status", move |_params: Params| {
+            let mempool = mempool.clone();
+            async move { Ok(json!(mempool.status())) }
+        });
+        let mempool = ingress.mempool().clone();
+        self.handler.add_method("mempool_content", move |_params: Params| {
+            let mempool = mempool.clone();
+            async move { Ok(json!(mempool.content())) }
+        });
+
+        self.handler.add_method("eth_sendRawTransaction", move |params: Params| {
+            let ingress = ingress.clone();
+            async move {
+                let (raw,): (String,) = params.parse()?;
+                let raw = decode_hex(&raw)?;
+                let hash = ingress.submit_raw(&raw).await?;
+                Ok(json!(hash))
+            }
+        });
+        self
+    }
+
+    /// 启用调度器查询方法（dubhe_feeHistory）
+    ///
+    /// 参数为 `[批次数, 分位数列表]`，分位数可省略；返回最近批次按 gas 加权的优先费分位数
+    pub fn with_scheduler(mut self, scheduler: Arc<ParallelScheduler>) -> Self {
+        self.handler.add_method("dubhe_feeHistory", move |params| {
+            Self::dubhe_fee_history(scheduler.clone(), params)
+        });
+        self
+    }
+
+    /// dubhe_getChannelStatus 附带各链适配器状态（active / degraded / disabled）
+    pub fn with_adapters(mut self, adapters: Arc<AdapterManager>) -> Self {
+        self.handler.add_method("dubhe_getChannelStatus", move |_params: Params| {
+            let adapters = adapters.clone();
+            async move {
+                Ok(json!(ChannelStatus {
+                    chains: Some(adapters.chain_statuses().await),
+                    ..channel_status()
+                }))
+            }
+        });
+        self
+    }
+
+    /// 启用证明报告验证（dubhe_verifyAttestation）
+    pub fn with_attestation(mut self, provider: Arc<dyn AttestationProvider>) -> Self {
+        self.handler.add_method("dubhe_verifyAttestation", move |params: Params| {
+            let provider = provider.clone();
+            async move {
+                let (report,): (AttestationReport,) = params.parse()?;
+                Ok(json!(provider.verify_report(&report)))
+            }
+        });
+        self
+    }
+
+    /// 启用认证与限流（与 WebSocket 服务器共享同一个 Authenticator）
+    pub fn with_auth(mut self, auth: Arc<Authenticator>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// 特权方法（管理、插件、缓存）在分发前检查调用方角色
+    pub fn with_access_control(mut self, access: Arc<AccessControl>) -> Self {
+        self.access = Some(access);
+        self
+    }
+
+    /// 批量大小、批内并发与单个调用超时
+    pub fn with_limits(mut self, limits: RpcLimits) -> Self {
+        self.limits = limits;
+        self
     }
 
     pub async fn start(&self, bind_addr: &str) -> Result<()> {
+        let listener = TcpListener::bind(bind_addr).await?;
+        info!("JSON-RPC server listening on {}", bind_addr);
+        self.serve(listener).await
+    }
+
+    /// 在已绑定的监听器上提供服务
+    pub async fn serve(&self, listener: TcpListener) -> Result<()> {
+        let state = Arc::new(RpcState {
+            handler: self.handler.clone(),
+            auth: self.auth.clone(),
+            access: self.access.clone(),
+            limits: self.limits.clone(),
+        });
         let app = Router::new()
             .route("/", post(Self::handle_request))
             .layer(CorsLayer::permissive())
-            .with_state(Arc::new(self.handler.clone()));
-
-        let listener = TcpListener::bind(bind_addr).await?;
-        info!("JSON-RPC server listening on {}", bind_addr);
+            .with_state(state);
 
         // 使用 hyper 直接服务，避免版本兼容性问题
-        let make_service = app.into_make_service();
+        let make_service = app.into_make_service_with_connect_info::<SocketAddr>();
         let server = hyper::Server::from_tcp(listener.into_std()?)?.serve(make_service);
 
         server.await?;
@@ -67,28 +582,58 @@ impl RpcServer {
     }
 
     async fn handle_request(
-        State(handler): State<Arc<IoHandler>>,
-        Json(request): Json<JsonRpcRequest>,
-    ) -> Result<Json<JsonRpcResponse>, StatusCode> {
-        let request_str = serde_json::to_string(&request).map_err(|_| StatusCode::BAD_REQUEST)?;
-        let response = match handler.handle_request(&request_str).await {
-            Some(resp) => resp,
-            None => {
-                error!("Failed to handle RPC request: {:?}", request);
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        State(state): State<Arc<RpcState>>,
+        ConnectInfo(peer): ConnectInfo<SocketAddr>,
+        headers: HeaderMap,
+        Json(request): Json<Value>,
+    ) -> Response {
+        let authorization = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok());
+
+        let (retry_after, body) = match request {
+            Value::Array(requests) => {
+                if requests.is_empty() || requests.len() > state.limits.max_batch_size {
+                    let message = if requests.is_empty() {
+                        "Empty batch".to_string()
+                    } else {
+                        format!(
+                            "Batch of {} requests exceeds the limit of {}",
+                            requests.len(),
+                            state.limits.max_batch_size
+                        )
+                    };
+                    let error = json!({ "code": INVALID_REQUEST_CODE, "message": message });
+                    return Json(error_response(Value::Null, error)).into_response();
+                }
+                let responses = state.call_batch(authorization, peer.ip(), requests).await;
+                if responses.is_empty() {
+                    return StatusCode::NO_CONTENT.into_response();
+                }
+                let retry_after = responses.iter().filter_map(|r| r.retry_after).max();
+                let body = Value::Array(responses.into_iter().map(|r| r.body).collect());
+                (retry_after, body)
             }
+            request => match state.call(authorization, peer.ip(), request).await {
+                Some(response) => (response.retry_after, response.body),
+                None => return StatusCode::NO_CONTENT.into_response(),
+            },
         };
 
-        let parsed_response: JsonRpcResponse =
-            serde_json::from_str(&response).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-        Ok(Json(parsed_response))
+        // 有调用被限流时附带 Retry-After 头（秒，向上取整）
+        match retry_after {
+            Some(retry_after) => {
+                let seconds = retry_after.as_millis().div_ceil(1000).max(1).to_string();
+                ([(header::RETRY_AFTER, seconds)], Json(body)).into_response()
+            }
+            None => Json(body).into_response(),
+        }
     }
 
     // EIP-1474 标准方法实现
     async fn eth_chain_id(_params: Params) -> Result<Value, jsonrpc_core::Error> {
         // Dubhe Channel 使用自定义 Chain ID: 0x44554248 (DUBH)
-        Ok(json!("0x44554248"))
+        Ok(json!(format!("0x{:x}", DUBHE_CHAIN_ID)))
     }
 
     async fn eth_block_number(_params: Params) -> Result<Value, jsonrpc_core::Error> {
@@ -106,19 +651,55 @@ impl RpcServer {
         Ok(json!("0x0"))
     }
 
-    async fn eth_send_raw_transaction(_params: Params) -> Result<Value, jsonrpc_core::Error> {
-        // TODO: 将原始交易提交到 scheduler 模块
-        Ok(json!("0x0"))
+    async fn eth_call(
+        executor: Arc<CallExecutor>,
+        params: Params,
+    ) -> Result<Value, jsonrpc_core::Error> {
+        let request = Self::parse_call_request(params)?;
+        let result = executor.call(&request).await?;
+        Ok(json!(encode_hex(&result.output)))
     }
 
-    async fn eth_call(_params: Params) -> Result<Value, jsonrpc_core::Error> {
-        // TODO: 执行只读合约调用
-        Ok(json!("0x"))
+    async fn eth_estimate_gas(
+        executor: Arc<CallExecutor>,
+        params: Params,
+    ) -> Result<Value, jsonrpc_core::Error> {
+        let request = Self::parse_call_request(params)?;
+        let result = executor.call(&request).await?;
+        Ok(json!(format!("0x{:x}", result.gas_used)))
     }
 
-    async fn eth_estimate_gas(_params: Params) -> Result<Value, jsonrpc_core::Error> {
-        // TODO: 估算 gas 消耗
-        Ok(json!("0x5208"))
+    /// `[callObject, blockTag?, traceConfig?]`，在一次性实例中执行并返回指令级跟踪
+    async fn debug_trace_call(
+        executor: Arc<CallExecutor>,
+        params: Params,
+    ) -> Result<Value, jsonrpc_core::Error> {
+        let values: Vec<Value> = params.parse()?;
+        let request = Self::parse_call_request(Params::Array(values.clone()))?;
+        let config = Self::parse_trace_config(values.get(2))?;
+        let traced = executor.trace_call(&request, &config).await?;
+        Ok(json!(traced))
+    }
+
+    /// 跟踪缓冲区按 maxSteps 分配，上限由服务端决定
+    fn parse_trace_config(value: Option<&Value>) -> Result<TraceConfig, jsonrpc_core::Error> {
+        let mut config: TraceConfig = match value {
+            Some(config) => serde_json::from_value(config.clone())
+                .map_err(|e| CallError::InvalidParams(e.to_string()))?,
+            None => TraceConfig::default(),
+        };
+        config.max_steps = config.max_steps.min(MAX_TRACE_STEPS);
+        Ok(config)
+    }
+
+    /// 解析 `[callObject, blockTag?]`，块标签暂不支持历史状态，忽略
+    fn parse_call_request(params: Params) -> Result<CallRequest, jsonrpc_core::Error> {
+        let mut values: Vec<Value> = params.parse()?;
+        if values.is_empty() {
+            return Err(CallError::InvalidParams("missing call object".to_string()).into());
+        }
+        serde_json::from_value(values.swap_remove(0))
+            .map_err(|e| CallError::InvalidParams(e.to_string()).into())
     }
 
     async fn eth_get_transaction_receipt(_params: Params) -> Result<Value, jsonrpc_core::Error> {
@@ -126,60 +707,777 @@ impl RpcServer {
         Ok(Value::Null)
     }
 
-    async fn eth_get_logs(_params: Params) -> Result<Value, jsonrpc_core::Error> {
-        // TODO: 从 state 模块查询事件日志
-        Ok(json!([]))
+    /// `[{fromBlock?, toBlock?, address?, topics?}]`
+    ///
+    /// 区块为十六进制数量或 `earliest` / `latest`（`pending`、`safe`、`finalized` 同 `latest`），
+    /// 省略时为 `latest`，即已索引的最高区块；`topics` 中的 `null` 为通配，数组为“或”
+    async fn eth_get_logs(
+        indexer: Arc<Indexer>,
+        limits: RpcLimits,
+        params: Params,
+    ) -> Result<Value, jsonrpc_core::Error> {
+        let request: GetLogsParams = parse_request("eth_getLogs", params)?;
+        if request.block_hash.is_some() {
+            return Err(jsonrpc_core::Error::invalid_params(
+                "blockHash filters are not supported, use fromBlock/toBlock",
+            ));
+        }
+
+        // RocksDB 读取是阻塞调用
+        let logs = tokio::task::spawn_blocking(move || -> Result<Vec<EthLog>> {
+            let latest = indexer.indexed_block()?.unwrap_or(0);
+            let filter = LogFilter {
+                from_block: resolve_block_tag(request.from_block.as_deref(), latest)?,
+                to_block: resolve_block_tag(request.to_block.as_deref(), latest)?,
+                addresses: request.address.map(OneOrMany::into_vec).unwrap_or_default(),
+                topics: request
+                    .topics
+                    .into_iter()
+                    .map(|topic| topic.map(OneOrMany::into_vec))
+                    .collect(),
+            };
+            let events = indexer.query_logs(
+                &filter,
+                limits.max_logs_block_range,
+                limits.max_logs_results,
+            )?;
+
+            // logIndex 是区块内的序号，按交易换算一次
+            let mut offsets = std::collections::HashMap::new();
+            let mut logs = Vec::with_capacity(events.len());
+            for event in events {
+                let key = (event.block_number, event.transaction_index);
+                let offset = match offsets.get(&key) {
+                    Some(offset) => *offset,
+                    None => {
+                        let offset = indexer.logs_before_transaction(key.0, key.1)?;
+                        offsets.insert(key, offset);
+                        offset
+                    }
+                };
+                let log_index = offset + event.log_index;
+                logs.push(EthLog::new(event, log_index));
+            }
+            Ok(logs)
+        })
+        .await
+        .map_err(|e| internal_error(e.to_string()))?
+        .map_err(|e| match e.downcast_ref::<CallError>() {
+            Some(CallError::InvalidParams(message)) => {
+                jsonrpc_core::Error::invalid_params(message.clone())
+            }
+            _ => to_rpc_error(&e),
+        })?;
+
+        Ok(json!(logs))
     }
 
     // Dubhe 自定义方法
     async fn dubhe_get_channel_status(_params: Params) -> Result<Value, jsonrpc_core::Error> {
         // TODO: 返回 Channel 运行状态
+        Ok(json!(channel_status()))
+    }
+
+    /// `[{package, eventType?, fromBlock?, toBlock?, cursor?, limit?}]`
+    async fn dubhe_query_events(
+        indexer: Arc<Indexer>,
+        params: Params,
+    ) -> Result<Value, jsonrpc_core::Error> {
+        let request: QueryEventsParams = parse_request("dubhe_queryEvents", params)?;
+        let query = EventQuery {
+            package: request.package,
+            event_type: request.event_type,
+            from_block: request.from_block,
+            to_block: request.to_block,
+        };
+        let limit = request.limit.unwrap_or(DEFAULT_QUERY_LIMIT);
+
+        // RocksDB 读取是阻塞调用
+        let page = tokio::task::spawn_blocking(move || {
+            indexer.query_events(&query, request.cursor.as_deref(), limit)
+        })
+        .await
+        .map_err(|e| internal_error(e.to_string()))?
+        .map_err(|e| jsonrpc_core::Error::invalid_params(e.to_string()))?;
+
         Ok(json!({
-            "status": "running",
-            "parallel_workers": 8,
-            "loaded_contracts": 0,
-            "tps": 0
+            "events": page.items,
+            "nextCursor": page.next_cursor,
         }))
     }
 
     async fn dubhe_load_contract(_params: Params) -> Result<Value, jsonrpc_core::Error> {
         // TODO: 动态加载合约
-        Ok(json!({
-            "success": true,
-            "contract_id": "0x0",
-            "loaded_at": 0
+        Ok(json!(LoadContractResult {
+            success: true,
+            contract_id: "0x0".to_string(),
+            loaded_at: 0,
         }))
     }
 
     async fn dubhe_get_parallel_stats(_params: Params) -> Result<Value, jsonrpc_core::Error> {
         // TODO: 返回并行执行统计
-        Ok(json!({
-            "parallel_efficiency": 0.95,
-            "conflict_rate": 0.05,
-            "avg_execution_time_ms": 10
+        Ok(json!(ParallelStats {
+            parallel_efficiency: 0.95,
+            conflict_rate: 0.05,
+            avg_execution_time_ms: 10,
         }))
     }
 
-    // Phase 1 链下执行方法
-    async fn dubhe_execute_offchain(_params: Params) -> Result<Value, jsonrpc_core::Error> {
-        // TODO: 执行链下交易
-        Ok(json!({
-            "session_id": "session_123",
-            "success": true,
-            "gas_used": 5000,
-            "execution_time_ms": 50,
-            "modified_objects": [],
-            "new_objects": []
-        }))
+    async fn dubhe_fee_history(
+        scheduler: Arc<ParallelScheduler>,
+        params: Params,
+    ) -> Result<Value, jsonrpc_core::Error> {
+        let (batches, percentiles): (usize, Vec<f64>) = match &params {
+            Params::Array(values) if values.len() == 1 => {
+                let (batches,): (usize,) = params.parse()?;
+                (batches, vec![])
+            }
+            _ => params.parse()?,
+        };
+        let report = scheduler
+            .fee_history(batches, &percentiles)
+            .map_err(|e| jsonrpc_core::Error::invalid_params(e.to_string()))?;
+        Ok(json!(report))
     }
 
+    // Phase 1 链下执行方法
     async fn dubhe_get_offchain_stats(_params: Params) -> Result<Value, jsonrpc_core::Error> {
         // TODO: 返回链下执行统计
-        Ok(json!({
-            "active_sessions": 0,
-            "locked_objects": 0,
-            "pending_executions": 0,
-            "total_gas_saved": 0
+        Ok(json!(OffchainStats {
+            active_sessions: 0,
+            locked_objects: 0,
+            pending_executions: 0,
+            total_gas_saved: 0,
+        }))
+    }
+}
+
+fn error_response(id: Value, error: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": error,
+    })
+}
+
+/// 占位的 Channel 运行状态
+fn channel_status() -> ChannelStatus {
+    ChannelStatus {
+        status: "running".to_string(),
+        parallel_workers: 8,
+        loaded_contracts: 0,
+        tps: 0,
+        chains: None,
+    }
+}
+
+/// 解析 `[params]` 形式的请求对象，先把上一版本的 snake_case 字段改写为 camelCase
+fn parse_request<T: DeserializeOwned>(
+    method: &str,
+    params: Params,
+) -> Result<T, jsonrpc_core::Error> {
+    let (mut request,): (Value,) = params.parse()?;
+    migrate_legacy_fields(method, &mut request);
+    serde_json::from_value(request)
+        .map_err(|e| jsonrpc_core::Error::invalid_params(format!("Invalid params: {}", e)))
+}
+
+/// 解析 eth_getLogs 的区块参数
+fn resolve_block_tag(tag: Option<&str>, latest: u64) -> Result<u64, CallError> {
+    match tag {
+        None | Some("latest" | "pending" | "safe" | "finalized") => Ok(latest),
+        Some("earliest") => Ok(0),
+        Some(quantity) => parse_quantity(quantity),
+    }
+}
+
+fn internal_error(message: String) -> jsonrpc_core::Error {
+    jsonrpc_core::Error {
+        code: ErrorCode::InternalError,
+        message,
+        data: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dubhe_adapter::{EventLog, TransactionReceipt, TransactionStatus};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_query_events() {
+        let dir = tempdir().unwrap();
+        let indexer = Arc::new(Indexer::open(dir.path()).unwrap());
+        for block in 0..5 {
+            indexer
+                .index_receipt(&TransactionReceipt {
+                    tx_hash: format!("0x{}", block),
+                    block_hash: format!("0xb{}", block),
+                    block_number: block,
+                    transaction_index: 0,
+                    from: "0xsender".to_string(),
+                    to: None,
+                    gas_used: 0,
+                    status: TransactionStatus::Success,
+                    logs: vec![EventLog {
+                        address: "0xpkg".to_string(),
+                        topics: vec!["Transfer".to_string()],
+                        data: "0x".to_string(),
+                    }],
+                    contract_address: None,
+                })
+                .unwrap();
+        }
+
+        let server = RpcServer::new().with_indexer(indexer);
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "dubhe_queryEvents",
+            "params": [{"package": "0xpkg", "eventType": "Transfer", "fromBlock": 1, "toBlock": 3, "limit": 2}],
+        });
+        let response = server
+            .handler
+            .handle_request(&request.to_string())
+            .await
+            .unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        let result = &response["result"];
+        assert_eq!(result["events"].as_array().unwrap().len(), 2);
+        assert_eq!(result["events"][0]["block_number"], 1);
+        assert!(result["nextCursor"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_eth_get_logs() {
+        let dir = tempdir().unwrap();
+        let indexer = Arc::new(Indexer::open(dir.path()).unwrap());
+        let token = "0x00000000000000000000000000000000000000aa";
+        let transfer = format!("0x{:064x}", 1);
+        for block in 0..20u64 {
+            let log = |topics: Vec<String>| EventLog {
+                address: token.to_string(),
+                topics,
+                data: "0x".to_string(),
+            };
+            indexer
+                .index_receipt(&TransactionReceipt {
+                    tx_hash: format!("0x{:064x}", block),
+                    block_hash: format!("0x{:064x}", block + 1000),
+                    block_number: block,
+                    transaction_index: 1,
+                    from: "0xsender".to_string(),
+                    to: None,
+                    gas_used: 0,
+                    status: TransactionStatus::Success,
+                    logs: vec![
+                        log(vec![format!("0x{:064x}", 2)]),
+                        log(vec![transfer.clone()]),
+                    ],
+                    contract_address: None,
+                })
+                .unwrap();
+        }
+
+        let server = RpcServer::new()
+            .with_limits(RpcLimits {
+                max_logs_block_range: 10,
+                ..RpcLimits::default()
+            })
+            .with_indexer(indexer);
+        let call = |filter: Value| {
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "eth_getLogs",
+                "params": [filter],
+            });
+            let handler = server.handler.clone();
+            async move {
+                let response = handler.handle_request(&request.to_string()).await.unwrap();
+                serde_json::from_str::<Value>(&response).unwrap()
+            }
+        };
+
+        let response = call(json!({
+            "fromBlock": "0x5",
+            "toBlock": "0x6",
+            "address": token.to_uppercase().replacen("0X", "0x", 1),
+            "topics": [[transfer.clone(), format!("0x{:064x}", 3)]],
         }))
+        .await;
+        let logs = response["result"].as_array().unwrap();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0]["address"], token);
+        assert_eq!(logs[0]["topics"][0], transfer);
+        assert_eq!(logs[0]["blockNumber"], "0x5");
+        assert_eq!(logs[0]["blockHash"], format!("0x{:064x}", 1005));
+        assert_eq!(logs[0]["transactionHash"], format!("0x{:064x}", 5));
+        assert_eq!(logs[0]["transactionIndex"], "0x1");
+        assert_eq!(logs[0]["logIndex"], "0x1");
+        assert_eq!(logs[0]["removed"], false);
+
+        // 省略区块为已索引的最高区块
+        let response = call(json!({"topics": [null, null]})).await;
+        assert!(response["result"].as_array().unwrap().is_empty());
+        let response = call(json!({"fromBlock": "0x12", "topics": [null]})).await;
+        assert_eq!(response["result"].as_array().unwrap().len(), 4);
+
+        let response = call(json!({"fromBlock": "earliest", "toBlock": "latest"})).await;
+        assert_eq!(
+            response["error"]["code"],
+            crate::error::LOG_QUERY_LIMIT_CODE
+        );
+        assert_eq!(response["error"]["data"]["kind"], "BlockRangeTooLarge");
+        assert_eq!(response["error"]["data"]["maxBlockRange"], 10);
+
+        let response = call(json!({"fromBlock": "5"})).await;
+        assert_eq!(response["error"]["code"], ErrorCode::InvalidParams.code());
+    }
+
+    struct CountingAdmin(std::sync::atomic::AtomicUsize);
+
+    #[async_trait]
+    impl AdminHandler for CountingAdmin {
+        async fn reload_alert_rules(&self) -> Result<usize> {
+            Ok(self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1)
+        }
+
+        async fn reload_config(&self) -> Result<ConfigReloadReport> {
+            Ok(ConfigReloadReport {
+                applied: vec!["scheduler.batch_size".to_string()],
+                rejected: vec!["api.rpc_bind".to_string()],
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reload_alert_rules() {
+        let admin = Arc::new(CountingAdmin(Default::default()));
+        let server = RpcServer::new().with_admin(admin);
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "dubhe_reloadAlertRules",
+            "params": [],
+        });
+        let response = server
+            .handler
+            .handle_request(&request.to_string())
+            .await
+            .unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["result"]["rules"], 1);
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "dubhe_reloadConfig",
+            "params": [],
+        });
+        let response = server
+            .handler
+            .handle_request(&request.to_string())
+            .await
+            .unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["result"]["applied"][0], "scheduler.batch_size");
+        assert_eq!(response["result"]["rejected"][0], "api.rpc_bind");
+    }
+
+    #[test]
+    fn test_privileged_methods_require_role() {
+        use crate::auth::{hash_api_key, ApiKeyConfig, AuthConfig, FORBIDDEN_CODE};
+        use dubhe_security::{
+            AccessControlConfig, MemoryAuditLog, Permission, Principal, PrincipalConfig, Role,
+        };
+
+        let audit = Arc::new(MemoryAuditLog::default());
+        let state = RpcState {
+            handler: IoHandler::new(),
+            auth: Some(Arc::new(Authenticator::new(&AuthConfig {
+                api_keys: vec![ApiKeyConfig {
+                    name: "ops".to_string(),
+                    key_hash: hash_api_key("ops-secret"),
+                }],
+                ..AuthConfig::default()
+            }))),
+            access: Some(Arc::new(
+                AccessControl::new(&AccessControlConfig {
+                    principals: vec![PrincipalConfig {
+                        api_key: "ops".to_string(),
+                        role: Role::Operator,
+                    }],
+                })
+                .with_audit_hook(audit.clone()),
+            )),
+            limits: RpcLimits::default(),
+        };
+        let ip: IpAddr = "127.0.0.1".parse().unwrap();
+        let operator = Some("Bearer ops-secret");
+
+        assert!(state
+            .authorize(operator, ip, "dubhe_reloadAlertRules")
+            .is_ok());
+        let denied = state
+            .authorize(operator, ip, "dubhe_loadPlugin")
+            .unwrap_err();
+        assert_eq!(denied.code(), FORBIDDEN_CODE);
+        // 非特权方法不经过访问控制
+        assert!(state.authorize(None, ip, "eth_chainId").is_ok());
+
+        let denials = audit.denials();
+        assert_eq!(denials.len(), 1);
+        assert_eq!(denials[0].principal, Principal::ApiKey("ops".to_string()));
+        assert_eq!(denials[0].permission, Permission::LoadPlugin);
+        assert_eq!(audit.decisions().len(), 2);
+    }
+
+    #[test]
+    fn test_snapshot_requires_api_key_without_access_control() {
+        use crate::auth::{hash_api_key, ApiKeyConfig, AuthConfig, FORBIDDEN_CODE};
+
+        let state = |auth: Option<Arc<Authenticator>>| RpcState {
+            handler: IoHandler::new(),
+            auth,
+            access: Some(Arc::new(AccessControl::disabled())),
+            limits: RpcLimits::default(),
+        };
+        let ip: IpAddr = "127.0.0.1".parse().unwrap();
+
+        // 默认配置：未配置 API Key、未启用访问控制
+        let open = state(None);
+        let denied = open
+            .authorize(None, ip, "dubhe_createSnapshot")
+            .unwrap_err();
+        assert_eq!(denied.code(), FORBIDDEN_CODE);
+        // 其余管理方法不受影响
+        assert!(open.authorize(None, ip, "dubhe_reloadConfig").is_ok());
+
+        let keyed = state(Some(Arc::new(Authenticator::new(&AuthConfig {
+            api_keys: vec![ApiKeyConfig {
+                name: "ops".to_string(),
+                key_hash: hash_api_key("ops-secret"),
+            }],
+            ..AuthConfig::default()
+        }))));
+        assert!(keyed
+            .authorize(Some("Bearer ops-secret"), ip, "dubhe_createSnapshot")
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_verify_attestation() {
+        use dubhe_security::SimulatedProvider;
+
+        let provider = Arc::new(SimulatedProvider::from_seed(
+            [5u8; 32],
+            SimulatedProvider::default_measurement(),
+        ));
+        let report = provider.generate_report(&[0xab; 32]).unwrap();
+        let server = RpcServer::new().with_attestation(provider);
+
+        let verify = |report: &AttestationReport| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "dubhe_verifyAttestation",
+                "params": [report],
+            })
+            .to_string()
+        };
+        let response = server
+            .handler
+            .handle_request(&verify(&report))
+            .await
+            .unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["result"]["valid"], true);
+        assert_eq!(
+            response["result"]["measurement"],
+            hex::encode(SimulatedProvider::default_measurement())
+        );
+
+        let mut tampered = report;
+        tampered.user_data = hex::encode([0xcd; 32]);
+        let response = server
+            .handler
+            .handle_request(&verify(&tampered))
+            .await
+            .unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["result"]["valid"], false);
+        assert!(response["result"]["reason"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_fee_history() {
+        use dubhe_scheduler::{SchedulerConfig, StrategyType, Transaction};
+
+        let scheduler = Arc::new(
+            ParallelScheduler::new(StrategyType::Sequential, SchedulerConfig::default()).unwrap(),
+        );
+        let server = RpcServer::new().with_scheduler(scheduler.clone());
+        let tx = |hash: &str, gas_price| Transaction {
+            hash: hash.to_string(),
+            from: hash.to_string(),
+            to: None,
+            data: vec![],
+            gas_limit: 21000,
+            gas_price,
+            nonce: 0,
+            read_set: vec![],
+            write_set: vec![],
+            access_list: None,
+            max_priority_fee: None,
+        };
+        scheduler
+            .submit_batch(vec![tx("0x1", 3), tx("0x2", 9)])
+            .await
+            .unwrap();
+
+        let server = &server;
+        let call = |params: Value| async move {
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "dubhe_feeHistory",
+                "params": params,
+            });
+            let response = server
+                .handler
+                .handle_request(&request.to_string())
+                .await
+                .unwrap();
+            serde_json::from_str::<Value>(&response).unwrap()
+        };
+        // 占位执行器不消耗 gas，按交易数计分位数
+        let response = call(json!([4, [0, 100]])).await;
+        assert_eq!(response["result"]["oldestBatch"], 0);
+        assert_eq!(response["result"]["reward"], json!([[3, 9]]));
+
+        let response = call(json!([4])).await;
+        assert_eq!(response["result"]["reward"], json!([[]]));
+
+        let response = call(json!([4, [90, 10]])).await;
+        assert_eq!(response["error"]["code"], -32602);
+    }
+
+    #[test]
+    fn test_trace_steps_are_capped() {
+        let config =
+            RpcServer::parse_trace_config(Some(&json!({ "maxSteps": usize::MAX }))).unwrap();
+        assert_eq!(config.max_steps, MAX_TRACE_STEPS);
+
+        let config = RpcServer::parse_trace_config(Some(&json!({ "maxSteps": 16 }))).unwrap();
+        assert_eq!(config.max_steps, 16);
+
+        let config = RpcServer::parse_trace_config(None).unwrap();
+        assert_eq!(config.max_steps, TraceConfig::default().max_steps);
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_call_keeps_running() {
+        let mut server = RpcServer::new().with_limits(RpcLimits {
+            request_timeout: Duration::from_millis(50),
+            ..RpcLimits::default()
+        });
+        let offchain = Arc::new(AtomicBool::new(false));
+        let query = Arc::new(AtomicBool::new(false));
+        for (method, flag) in [
+            ("dubhe_executeOffchain", offchain.clone()),
+            ("test_slow", query.clone()),
+        ] {
+            server.handler.add_method(method, move |_params: Params| {
+                let flag = flag.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    flag.store(true, Ordering::SeqCst);
+                    Ok::<_, jsonrpc_core::Error>(Value::Null)
+                }
+            });
+        }
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { server.serve(listener).await });
+
+        let client = reqwest::Client::new();
+        for method in ["dubhe_executeOffchain", "test_slow"] {
+            let response: Value = client
+                .post(&url)
+                .json(&json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": []}))
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            assert_eq!(response["error"]["code"], REQUEST_TIMEOUT_CODE);
+        }
+        assert!(!offchain.load(Ordering::SeqCst));
+
+        // 有副作用的调用超时后在后台继续执行完毕，其余调用超时即取消
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert!(offchain.load(Ordering::SeqCst));
+        assert!(!query.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_batch_requests() {
+        let mut server = RpcServer::new().with_limits(RpcLimits {
+            max_batch_size: 4,
+            batch_concurrency: 4,
+            request_timeout: Duration::from_millis(100),
+            ..RpcLimits::default()
+        });
+        server.handler.add_method("test_sleep", |_params: Params| async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok::<_, jsonrpc_core::Error>(Value::Null)
+        });
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { server.serve(listener).await });
+
+        let client = reqwest::Client::new();
+        let post = |body: Value| {
+            let request = client.post(&url).json(&body);
+            async move { request.send().await.unwrap() }
+        };
+
+        // 有效调用、未知方法、通知与超时调用混在同一批
+        let response = post(json!([
+            {"jsonrpc": "2.0", "id": 1, "method": "eth_chainId", "params": []},
+            {"jsonrpc": "2.0", "id": "two", "method": "eth_unknown", "params": []},
+            {"jsonrpc": "2.0", "method": "eth_blockNumber", "params": []},
+            {"jsonrpc": "2.0", "id": 3, "method": "test_sleep", "params": []},
+        ]))
+        .await;
+        let responses: Vec<Value> = response.json().await.unwrap();
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[0]["result"], "0x44554248");
+        assert_eq!(responses[1]["id"], "two");
+        assert_eq!(responses[1]["error"]["code"], -32601);
+        // 超时只影响该调用本身
+        assert_eq!(responses[2]["id"], 3);
+        assert_eq!(responses[2]["error"]["code"], REQUEST_TIMEOUT_CODE);
+
+        // 只含通知的批量请求没有响应体
+        let response = post(json!([
+            {"jsonrpc": "2.0", "method": "eth_chainId", "params": []},
+        ]))
+        .await;
+        assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT);
+
+        let call = json!({"jsonrpc": "2.0", "id": 1, "method": "eth_chainId", "params": []});
+        for batch in [vec![call; 5], vec![]] {
+            let response: Value = post(Value::Array(batch)).await.json().await.unwrap();
+            assert_eq!(response["error"]["code"], INVALID_REQUEST_CODE);
+            assert_eq!(response["id"], Value::Null);
+        }
+
+        // 单个请求不受影响
+        let response: Value = post(json!({"jsonrpc": "2.0", "id": 7, "method": "eth_chainId"}))
+            .await
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["result"], "0x44554248");
+    }
+
+    /// 模拟的链下执行后端：`fail` 函数返回错误，其余立即成功
+    struct MockOffchain;
+
+    #[async_trait]
+    impl crate::offchain::OffchainHandler for MockOffchain {
+        async fn execute(
+            &self,
+            session_id: String,
+            params: OffchainExecutionParams,
+        ) -> Result<Value> {
+            if params.function_name == "fail" {
+                anyhow::bail!("object 0xa is locked by another session");
+            }
+            Ok(json!({
+                "sessionId": session_id,
+                "success": true,
+                "gasUsed": params.gas_budget / 2,
+                "modifiedObjects": [],
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_offchain_over_http() {
+        let sessions = Arc::new(OffchainSessions::new(
+            Arc::new(MockOffchain),
+            crate::offchain::OffchainRpcConfig::default(),
+        ));
+        let server = RpcServer::new().with_offchain(sessions);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { server.serve(listener).await });
+
+        let client = reqwest::Client::new();
+        let call = |method: &'static str, params: Value| {
+            let request = client.post(&url).json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }));
+            async move { request.send().await.unwrap().json::<Value>().await.unwrap() }
+        };
+        let request = |function: &str, gas_budget: u64, is_async: bool| {
+            json!([{
+                "packageId": "0xpkg",
+                "functionName": function,
+                "arguments": [1],
+                "sharedObjects": ["0xa"],
+                "gasBudget": gas_budget,
+                "async": is_async,
+            }])
+        };
+
+        // 同步模式直接返回结果，会话 ID 由服务端生成
+        let response = call("dubhe_executeOffchain", request("increment", 1_000, false)).await;
+        let session_id = response["result"]["sessionId"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(session_id.starts_with("rpc-"));
+        assert_eq!(response["result"]["gasUsed"], 500);
+        let response = call("dubhe_getExecutionStatus", json!([session_id])).await;
+        assert_eq!(response["result"]["status"], "completed");
+
+        // 超出 gas 上限的请求被拒绝
+        let response = call("dubhe_executeOffchain", request("increment", 0, false)).await;
+        assert_eq!(response["error"]["code"], -32602);
+
+        // 异步模式先返回会话 ID，再轮询
+        for (function, expected) in [("increment", "completed"), ("fail", "failed")] {
+            let response = call("dubhe_executeOffchain", request(function, 1_000, true)).await;
+            assert_eq!(response["result"]["status"], "pending");
+            let session_id = response["result"]["sessionId"].clone();
+
+            let mut status = Value::Null;
+            for _ in 0..50 {
+                status =
+                    call("dubhe_getExecutionStatus", json!([session_id])).await["result"].clone();
+                if status["status"] != "pending" {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+            assert_eq!(status["status"], expected);
+            assert_eq!(status["sessionId"], session_id);
+        }
+
+        let response = call("dubhe_getExecutionStatus", json!(["rpc-unknown"])).await;
+        assert!(response["error"].is_object());
     }
 }
@@ -1,13 +1,29 @@
 //! API 类型定义
+//!
+//! 对外的 JSON 结构固定为 camelCase 字段名；gas、余额与区块号等数量按 EIP-1474 编码为
+//! 0x 前缀十六进制字符串，避免 JavaScript 客户端丢失精度。请求类型拒绝未知字段，
+//! 上一版本的 snake_case 字段由 [`migrate_legacy_fields`] 改写后继续接受。
+//! 各响应类型的 JSON 形状由 `snapshots/v1` 下的快照固定
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+use tracing::warn;
+
+use dubhe_adapter::ChainStatus;
+use dubhe_state::IndexedEvent;
 
 /// JSON-RPC 请求
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct JsonRpcRequest {
     pub jsonrpc: String,
     pub method: String,
+    #[serde(default)]
     pub params: serde_json::Value,
+    /// 通知没有 id
+    #[serde(default)]
     pub id: serde_json::Value,
 }
 
@@ -37,12 +53,420 @@ pub type BlockHash = String;
 /// 地址
 pub type Address = String;
 
+/// EIP-1474 数量（QUANTITY）：序列化为 0x 前缀十六进制字符串
+///
+/// 反序列化同时接受十六进制字符串与 JSON 数字，兼容仍发送数字的旧客户端
+pub mod quantity {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(u64),
+        Hex(String),
+    }
+
+    impl Raw {
+        fn into_u64<E: Error>(self) -> Result<u64, E> {
+            match self {
+                Raw::Number(value) => Ok(value),
+                Raw::Hex(value) => crate::execution::parse_quantity(&value).map_err(E::custom),
+            }
+        }
+    }
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{:x}", value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        Raw::deserialize(deserializer)?.into_u64()
+    }
+
+    /// 可选数量，`None` 序列化为 `null`
+    pub mod option {
+        use super::Raw;
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            value: &Option<u64>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match value {
+                Some(value) => super::serialize(value, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<u64>, D::Error> {
+            Option::<Raw>::deserialize(deserializer)?
+                .map(Raw::into_u64)
+                .transpose()
+        }
+    }
+}
+
+/// 把请求对象顶层的 snake_case 字段改写为 camelCase，并对每个方法的每个旧字段记录一次弃用警告
+///
+/// 请求类型拒绝未知字段，改写后上一版本的客户端仍能调用；同时出现新旧两种写法时保留原样，
+/// 由反序列化报错。兼容只保留一个版本，之后移除
+pub fn migrate_legacy_fields(method: &str, request: &mut Value) {
+    static WARNED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+    let Some(object) = request.as_object_mut() else {
+        return;
+    };
+    let legacy: Vec<String> = object
+        .keys()
+        .filter(|key| key.contains('_'))
+        .cloned()
+        .collect();
+    for key in legacy {
+        let camel = to_camel_case(&key);
+        if object.contains_key(&camel) {
+            continue;
+        }
+        if let Some(value) = object.remove(&key) {
+            object.insert(camel.clone(), value);
+        }
+        let first = WARNED
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap()
+            .insert(format!("{}.{}", method, key));
+        if first {
+            warn!(
+                "⚠️ {} received deprecated field {}, use {} instead; snake_case fields will be rejected in the next release",
+                method, key, camel
+            );
+        }
+    }
+}
+
+fn to_camel_case(key: &str) -> String {
+    let mut camel = String::with_capacity(key.len());
+    let mut upper = false;
+    for c in key.chars() {
+        if c == '_' {
+            upper = !camel.is_empty();
+        } else if upper {
+            camel.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
+}
+
+/// dubhe_queryEvents 参数
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct QueryEventsParams {
+    pub package: String,
+    #[serde(default)]
+    pub event_type: Option<String>,
+    #[serde(default, with = "quantity::option")]
+    pub from_block: Option<u64>,
+    #[serde(default, with = "quantity::option")]
+    pub to_block: Option<u64>,
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// eth_getLogs 过滤对象
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GetLogsParams {
+    /// 十六进制数量或区块标签（`latest`、`earliest` 等）
+    #[serde(default)]
+    pub from_block: Option<String>,
+    #[serde(default)]
+    pub to_block: Option<String>,
+    #[serde(default)]
+    pub address: Option<OneOrMany>,
+    #[serde(default)]
+    pub topics: Vec<Option<OneOrMany>>,
+    #[serde(default)]
+    pub block_hash: Option<String>,
+}
+
+/// 单个值或候选列表
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    pub fn into_vec(self) -> Vec<String> {
+        match self {
+            OneOrMany::One(value) => vec![value],
+            OneOrMany::Many(values) => values,
+        }
+    }
+}
+
+/// dubhe_createSnapshot 参数
+///
+/// `out` / `base` 是节点快照根目录（`node.snapshot_dir`）下的相对路径
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CreateSnapshotParams {
+    pub out: String,
+    #[serde(default)]
+    pub base: Option<String>,
+}
+
+/// eth_getLogs 返回的日志，数量字段为 0x 前缀十六进制
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EthLog {
+    pub address: Address,
+    pub topics: Vec<String>,
+    pub data: String,
+    pub block_number: String,
+    pub block_hash: BlockHash,
+    pub transaction_hash: TxHash,
+    pub transaction_index: String,
+    /// 在区块内的序号
+    pub log_index: String,
+    /// 索引只记录已确认的交易，总为 `false`
+    pub removed: bool,
+}
+
+impl EthLog {
+    /// `block_log_index` 为日志在整个区块内的序号
+    pub fn new(event: IndexedEvent, block_log_index: u32) -> Self {
+        Self {
+            address: event.package,
+            topics: event.topics,
+            data: event.data,
+            block_number: format!("0x{:x}", event.block_number),
+            block_hash: event.block_hash,
+            transaction_hash: event.tx_hash,
+            transaction_index: format!("0x{:x}", event.transaction_index),
+            log_index: format!("0x{:x}", block_log_index),
+            removed: false,
+        }
+    }
+}
+
+/// dubhe_getChannelStatus 结果
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelStatus {
+    pub status: String,
+    pub parallel_workers: usize,
+    pub loaded_contracts: usize,
+    pub tps: u64,
+    /// 各链适配器状态，未接入适配器管理器时省略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chains: Option<Vec<ChainStatus>>,
+}
+
+/// dubhe_loadContract 结果
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadContractResult {
+    pub success: bool,
+    pub contract_id: String,
+    pub loaded_at: u64,
+}
+
+/// dubhe_getParallelStats 结果
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParallelStats {
+    pub parallel_efficiency: f64,
+    pub conflict_rate: f64,
+    pub avg_execution_time_ms: u64,
+}
+
+/// dubhe_getOffchainStats 结果
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OffchainStats {
+    pub active_sessions: usize,
+    pub locked_objects: usize,
+    pub pending_executions: usize,
+    #[serde(with = "quantity")]
+    pub total_gas_saved: u64,
+}
+
 /// WebSocket 事件类型
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type")]
 pub enum WsEvent {
+    #[serde(rename_all = "camelCase")]
     NewTransaction { tx_hash: TxHash },
-    NewBlock { block_hash: BlockHash, number: u64 },
+    #[serde(rename_all = "camelCase")]
+    NewBlock {
+        block_hash: BlockHash,
+        #[serde(with = "quantity")]
+        number: u64,
+    },
+    #[serde(rename_all = "camelCase")]
     ContractLoaded { address: Address, name: String },
+    #[serde(rename_all = "camelCase")]
     ParallelStats { efficiency: f64, conflicts: u64 },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::{ConfigReloadReport, PrefetchReport, SnapshotReport};
+    use dubhe_adapter::{AdapterState, ChainType};
+    use serde_json::json;
+    use std::path::Path;
+
+    /// 各公开响应类型的样例，键为类型名
+    fn response_samples() -> Value {
+        json!({
+            "EthLog": EthLog {
+                address: "0xpkg".to_string(),
+                topics: vec!["Transfer".to_string()],
+                data: "0x".to_string(),
+                block_number: "0x10".to_string(),
+                block_hash: "0xblock".to_string(),
+                transaction_hash: "0xtx".to_string(),
+                transaction_index: "0x1".to_string(),
+                log_index: "0x2".to_string(),
+                removed: false,
+            },
+            "ChannelStatus": ChannelStatus {
+                status: "running".to_string(),
+                parallel_workers: 8,
+                loaded_contracts: 3,
+                tps: 120,
+                chains: Some(vec![ChainStatus {
+                    chain_type: ChainType::Sui,
+                    state: AdapterState::Degraded,
+                    error: Some("endpoint is unreachable".to_string()),
+                    attempts: 2,
+                }]),
+            },
+            "LoadContractResult": LoadContractResult {
+                success: true,
+                contract_id: "0x0".to_string(),
+                loaded_at: 1_700_000_000,
+            },
+            "ParallelStats": ParallelStats {
+                parallel_efficiency: 0.95,
+                conflict_rate: 0.05,
+                avg_execution_time_ms: 10,
+            },
+            "OffchainStats": OffchainStats {
+                active_sessions: 1,
+                locked_objects: 2,
+                pending_executions: 3,
+                total_gas_saved: 9_007_199_254_740_993,
+            },
+            "WsEvent": [
+                WsEvent::NewTransaction { tx_hash: "0xtx".to_string() },
+                WsEvent::NewBlock { block_hash: "0xblock".to_string(), number: 255 },
+                WsEvent::ContractLoaded { address: "0xpkg".to_string(), name: "counter".to_string() },
+                WsEvent::ParallelStats { efficiency: 0.5, conflicts: 4 },
+            ],
+            "PrefetchReport": PrefetchReport {
+                package_id: "0xpkg".to_string(),
+                code_size: 128,
+                entry_points: vec!["increment".to_string()],
+            },
+            "SnapshotReport": SnapshotReport {
+                id: "snap-1".to_string(),
+                path: "/data/snapshots/snap-1".to_string(),
+                base: None,
+                files: 4,
+                written_files: 2,
+                written_bytes: 4096,
+            },
+            "ConfigReloadReport": ConfigReloadReport {
+                applied: vec!["scheduler.batch_size".to_string()],
+                rejected: vec!["api.rpc_bind".to_string()],
+            },
+        })
+    }
+
+    /// 响应类型的 JSON 形状变化即为破坏性修改：确属有意时新建快照版本目录，
+    /// 或以 `UPDATE_SNAPSHOTS=1` 运行测试重写当前快照
+    #[test]
+    fn test_response_schema_snapshot() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("snapshots/v1/responses.json");
+        let actual = response_samples();
+        let pretty = serde_json::to_string_pretty(&actual).unwrap();
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(&path, format!("{}\n", pretty)).unwrap();
+        }
+        let expected: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            actual,
+            expected,
+            "API response schema differs from {}:\n{}",
+            path.display(),
+            pretty
+        );
+    }
+
+    #[test]
+    fn test_quantities_round_trip() {
+        let stats: OffchainStats = serde_json::from_value(json!({
+            "activeSessions": 0,
+            "lockedObjects": 0,
+            "pendingExecutions": 0,
+            "totalGasSaved": "0x20000000000001",
+        }))
+        .unwrap();
+        assert_eq!(stats.total_gas_saved, 9_007_199_254_740_993);
+
+        // 区块号既可以是十六进制数量，也可以是旧客户端发送的数字
+        let params: QueryEventsParams = serde_json::from_value(json!({
+            "package": "0xpkg",
+            "fromBlock": "0x10",
+            "toBlock": 32,
+        }))
+        .unwrap();
+        assert_eq!((params.from_block, params.to_block), (Some(16), Some(32)));
+        assert!(serde_json::from_value::<QueryEventsParams>(json!({
+            "package": "0xpkg",
+            "fromBlock": "16",
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_request_types_reject_unknown_fields() {
+        let unknown = json!({ "package": "0xpkg", "evenType": "Transfer" });
+        assert!(serde_json::from_value::<QueryEventsParams>(unknown).is_err());
+        let unknown = json!({ "fromBlock": "latest", "limit": 10 });
+        assert!(serde_json::from_value::<GetLogsParams>(unknown).is_err());
+        let unknown = json!({ "out": "/tmp/snap", "incremental": true });
+        assert!(serde_json::from_value::<CreateSnapshotParams>(unknown).is_err());
+    }
+
+    #[test]
+    fn test_legacy_snake_case_fields_are_migrated() {
+        let mut request = json!({
+            "package": "0xpkg",
+            "event_type": "Transfer",
+            "from_block": 1,
+            "toBlock": 3,
+        });
+        migrate_legacy_fields("dubhe_queryEvents", &mut request);
+        let params: QueryEventsParams = serde_json::from_value(request).unwrap();
+        assert_eq!(params.event_type.as_deref(), Some("Transfer"));
+        assert_eq!((params.from_block, params.to_block), (Some(1), Some(3)));
+
+        // 新旧写法同时出现时不改写，由反序列化拒绝
+        let mut request = json!({ "package": "0xpkg", "to_block": 1, "toBlock": 3 });
+        migrate_legacy_fields("dubhe_queryEvents", &mut request);
+        assert!(serde_json::from_value::<QueryEventsParams>(request).is_err());
+    }
+}
@@ -1,154 +1,973 @@
 //! WebSocket 服务器
-//! 
-//! 事件推送服务，利用 tokio-broadcast 多订阅者模型
+//!
+//! eth_subscribe / eth_unsubscribe 订阅服务：
+//! 适配器的新区块 / 新交易流经 tokio-broadcast 汇总，由订阅表分发给各客户端。
+//! 每个客户端的待发送队列有上限，慢客户端落后超过上限时取消其订阅并断开连接。
+//! dubhe_subscribeExecution 推送单个链下执行会话的进度，以携带结果的最终事件结束。
 
 use anyhow::Result;
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, State,
+    },
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
 use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{broadcast, RwLock};
-use tokio_util::codec::{FramedRead, FramedWrite, LinesCodec};
-use tracing::{error, info, warn};
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+use dubhe_adapter::{AdapterManager, ChainEventKind, ChainType, EventLog};
+
+use crate::auth::{Authenticator, Caller};
+use crate::offchain::{OffchainSessions, SessionEvent, SessionSubscription};
 use crate::types::WsEvent;
 
+/// 默认每个客户端最多积压的通知数
+pub const DEFAULT_MAX_PENDING_MESSAGES: usize = 256;
+
+/// 推送给订阅者的链上事件
+#[derive(Debug, Clone)]
+pub enum ChainEvent {
+    NewHead {
+        hash: String,
+        number: u64,
+    },
+    Logs {
+        tx_hash: String,
+        block_hash: String,
+        block_number: u64,
+        logs: Vec<EventLog>,
+    },
+    NewPendingTransaction {
+        hash: String,
+    },
+    Dubhe(WsEvent),
+}
+
+/// 日志过滤条件（EIP-1474 filter object 的 address / topics 部分）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogFilter {
+    pub addresses: Vec<String>,
+    /// 按位置匹配，`None` 表示通配，多个候选为“或”
+    pub topics: Vec<Option<Vec<String>>>,
+}
+
+impl LogFilter {
+    fn from_params(value: Option<&Value>) -> Result<Self, String> {
+        let Some(value) = value else {
+            return Ok(Self::default());
+        };
+
+        let addresses = match value.get("address") {
+            None | Some(Value::Null) => vec![],
+            Some(Value::String(address)) => vec![address.to_lowercase()],
+            Some(Value::Array(items)) => items
+                .iter()
+                .map(|v| v.as_str().map(str::to_lowercase))
+                .collect::<Option<Vec<_>>>()
+                .ok_or("address must be a string or array of strings")?,
+            Some(_) => return Err("address must be a string or array of strings".to_string()),
+        };
+
+        let topics = match value.get("topics") {
+            None | Some(Value::Null) => vec![],
+            Some(Value::Array(items)) => items
+                .iter()
+                .map(|topic| match topic {
+                    Value::Null => Ok(None),
+                    Value::String(t) => Ok(Some(vec![t.to_lowercase()])),
+                    Value::Array(options) => options
+                        .iter()
+                        .map(|v| v.as_str().map(str::to_lowercase))
+                        .collect::<Option<Vec<_>>>()
+                        .map(Some)
+                        .ok_or_else(|| "invalid topic filter".to_string()),
+                    _ => Err("invalid topic filter".to_string()),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            Some(_) => return Err("topics must be an array".to_string()),
+        };
+
+        Ok(Self { addresses, topics })
+    }
+
+    pub fn matches(&self, log: &EventLog) -> bool {
+        if !self.addresses.is_empty() && !self.addresses.contains(&log.address.to_lowercase()) {
+            return false;
+        }
+
+        self.topics.iter().enumerate().all(|(i, expected)| match expected {
+            None => true,
+            Some(options) => log
+                .topics
+                .get(i)
+                .map(|topic| options.contains(&topic.to_lowercase()))
+                .unwrap_or(false),
+        })
+    }
+}
+
+/// 订阅类型
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionKind {
+    NewHeads,
+    Logs(LogFilter),
+    NewPendingTransactions,
+    DubheEvents,
+    /// 单个链下执行会话的事件，由 dubhe_subscribeExecution 创建
+    Execution(String),
+}
+
+impl SubscriptionKind {
+    fn from_params(params: &[Value]) -> Result<Self, String> {
+        match params.first().and_then(Value::as_str) {
+            Some("newHeads") => Ok(Self::NewHeads),
+            Some("logs") => Ok(Self::Logs(LogFilter::from_params(params.get(1))?)),
+            Some("newPendingTransactions") => Ok(Self::NewPendingTransactions),
+            Some("dubheEvents") => Ok(Self::DubheEvents),
+            Some(other) => Err(format!("unsupported subscription type: {}", other)),
+            None => Err("missing subscription type".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Subscription {
+    client: Uuid,
+    kind: SubscriptionKind,
+}
+
+/// EIP-1474 订阅通知
+#[derive(Serialize)]
+struct Notification<'a> {
+    jsonrpc: &'static str,
+    method: &'static str,
+    params: NotificationParams<'a>,
+}
+
+#[derive(Serialize)]
+struct NotificationParams<'a> {
+    subscription: &'a str,
+    result: Value,
+}
+
+/// 订阅表
+///
+/// 每个客户端持有一个有界发送队列；队列满即视为慢客户端并被移除
+pub struct SubscriptionRegistry {
+    max_pending: usize,
+    clients: RwLock<HashMap<Uuid, mpsc::Sender<String>>>,
+    subscriptions: RwLock<HashMap<String, Subscription>>,
+}
+
+impl SubscriptionRegistry {
+    pub fn new(max_pending: usize) -> Self {
+        Self {
+            max_pending: max_pending.max(1),
+            clients: RwLock::new(HashMap::new()),
+            subscriptions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 注册客户端，返回其发送队列的接收端
+    pub fn register_client(&self, client: Uuid) -> mpsc::Receiver<String> {
+        let (tx, rx) = mpsc::channel(self.max_pending);
+        self.clients.write().unwrap().insert(client, tx);
+        rx
+    }
+
+    /// 移除客户端及其全部订阅；发送端被丢弃后连接随之关闭
+    pub fn remove_client(&self, client: &Uuid) {
+        self.clients.write().unwrap().remove(client);
+        self.subscriptions
+            .write()
+            .unwrap()
+            .retain(|_, sub| sub.client != *client);
+    }
+
+    pub fn subscribe(&self, client: Uuid, kind: SubscriptionKind) -> String {
+        let id = format!("0x{}", &Uuid::new_v4().simple().to_string()[..16]);
+        self.subscriptions
+            .write()
+            .unwrap()
+            .insert(id.clone(), Subscription { client, kind });
+        id
+    }
+
+    /// 只能取消自己的订阅
+    pub fn unsubscribe(&self, client: &Uuid, id: &str) -> bool {
+        let mut subscriptions = self.subscriptions.write().unwrap();
+        match subscriptions.get(id) {
+            Some(sub) if sub.client == *client => {
+                subscriptions.remove(id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn subscription_count(&self) -> usize {
+        self.subscriptions.read().unwrap().len()
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.clients.read().unwrap().len()
+    }
+
+    /// 向客户端发送一条直接响应（非订阅通知）
+    pub fn send_to(&self, client: &Uuid, message: String) -> bool {
+        let sender = self.clients.read().unwrap().get(client).cloned();
+        match sender {
+            Some(sender) => self.deliver(client, &sender, message),
+            None => false,
+        }
+    }
+
+    /// 分发事件到所有匹配的订阅
+    pub fn publish(&self, event: &ChainEvent) {
+        let targets: Vec<(String, Uuid, Value)> = self
+            .subscriptions
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|(id, sub)| {
+                notification_payload(&sub.kind, event).map(|result| (id.clone(), sub.client, result))
+            })
+            .collect();
+
+        for (id, client, result) in targets {
+            self.notify_client(&client, &id, result);
+        }
+    }
+
+    /// 向单个订阅发送通知，订阅已取消或客户端已断开时返回 false
+    pub fn notify(&self, id: &str, result: Value) -> bool {
+        let client = self
+            .subscriptions
+            .read()
+            .unwrap()
+            .get(id)
+            .map(|sub| sub.client);
+        match client {
+            Some(client) => self.notify_client(&client, id, result),
+            None => false,
+        }
+    }
+
+    fn notify_client(&self, client: &Uuid, id: &str, result: Value) -> bool {
+        let sender = self.clients.read().unwrap().get(client).cloned();
+        let Some(sender) = sender else { return false };
+
+        let frame = Notification {
+            jsonrpc: "2.0",
+            method: "eth_subscription",
+            params: NotificationParams {
+                subscription: id,
+                result,
+            },
+        };
+        match serde_json::to_string(&frame) {
+            Ok(message) => self.deliver(client, &sender, message),
+            Err(e) => {
+                error!("Failed to serialize subscription notification: {}", e);
+                false
+            }
+        }
+    }
+
+    fn deliver(&self, client: &Uuid, sender: &mpsc::Sender<String>, message: String) -> bool {
+        match sender.try_send(message) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                warn!(
+                    "WebSocket client {} fell more than {} messages behind, dropping it",
+                    client, self.max_pending
+                );
+                self.remove_client(client);
+                false
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                self.remove_client(client);
+                false
+            }
+        }
+    }
+}
+
+/// 订阅与事件匹配时生成通知内容
+fn notification_payload(kind: &SubscriptionKind, event: &ChainEvent) -> Option<Value> {
+    match (kind, event) {
+        (SubscriptionKind::NewHeads, ChainEvent::NewHead { hash, number }) => Some(json!({
+            "hash": hash,
+            "number": format!("0x{:x}", number),
+        })),
+        (
+            SubscriptionKind::Logs(filter),
+            ChainEvent::Logs {
+                tx_hash,
+                block_hash,
+                block_number,
+                logs,
+            },
+        ) => {
+            let matched: Vec<Value> = logs
+                .iter()
+                .enumerate()
+                .filter(|(_, log)| filter.matches(log))
+                .map(|(index, log)| {
+                    json!({
+                        "address": log.address,
+                        "topics": log.topics,
+                        "data": log.data,
+                        "transactionHash": tx_hash,
+                        "blockHash": block_hash,
+                        "blockNumber": format!("0x{:x}", block_number),
+                        "logIndex": format!("0x{:x}", index),
+                        "removed": false,
+                    })
+                })
+                .collect();
+            (!matched.is_empty()).then(|| Value::Array(matched))
+        }
+        (SubscriptionKind::NewPendingTransactions, ChainEvent::NewPendingTransaction { hash }) => {
+            Some(json!(hash))
+        }
+        (SubscriptionKind::DubheEvents, ChainEvent::Dubhe(event)) => serde_json::to_value(event).ok(),
+        _ => None,
+    }
+}
+
+/// 执行订阅：响应发出后开始推送的会话事件
+struct ExecutionFeed {
+    subscription: String,
+    events: SessionSubscription,
+}
+
+/// 把会话事件推送给订阅方，最终事件送达后结束订阅
+async fn forward_session_events(
+    registry: Arc<SubscriptionRegistry>,
+    client: Uuid,
+    feed: ExecutionFeed,
+) {
+    let notify = |event: &SessionEvent| match serde_json::to_value(event) {
+        Ok(result) => registry.notify(&feed.subscription, result),
+        Err(e) => {
+            error!("Failed to serialize session event: {}", e);
+            false
+        }
+    };
+
+    match feed.events {
+        SessionSubscription::Finished(event) => {
+            notify(&event);
+        }
+        SessionSubscription::Live(mut events) => loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(
+                        "Execution subscription {} lagged, skipped {} events",
+                        feed.subscription, skipped
+                    );
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            if !notify(&event) || event.is_final() {
+                break;
+            }
+        },
+    }
+    registry.unsubscribe(&client, &feed.subscription);
+}
+
 /// WebSocket 服务器
 pub struct WsServer {
-    connections: Arc<RwLock<HashMap<Uuid, broadcast::Sender<String>>>>,
-    event_sender: broadcast::Sender<WsEvent>,
+    registry: Arc<SubscriptionRegistry>,
+    event_sender: broadcast::Sender<ChainEvent>,
+    auth: Option<Arc<Authenticator>>,
+    offchain: Option<Arc<OffchainSessions>>,
+}
+
+#[derive(Clone)]
+struct WsState {
+    registry: Arc<SubscriptionRegistry>,
+    auth: Option<Arc<Authenticator>>,
+    offchain: Option<Arc<OffchainSessions>>,
 }
 
 impl WsServer {
     pub fn new() -> Self {
+        Self::with_max_pending(DEFAULT_MAX_PENDING_MESSAGES)
+    }
+
+    /// 指定每个客户端最多积压的通知数
+    pub fn with_max_pending(max_pending: usize) -> Self {
         let (event_sender, _) = broadcast::channel(1000);
-        
+
         Self {
-            connections: Arc::new(RwLock::new(HashMap::new())),
+            registry: Arc::new(SubscriptionRegistry::new(max_pending)),
             event_sender,
+            auth: None,
+            offchain: None,
         }
     }
 
+    /// 启用认证与限流（与 JSON-RPC 服务器共享同一个 Authenticator）
+    pub fn with_auth(mut self, auth: Arc<Authenticator>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// 启用 dubhe_subscribeExecution（与 JSON-RPC 服务器共享会话表）
+    pub fn with_offchain(mut self, sessions: Arc<OffchainSessions>) -> Self {
+        self.offchain = Some(sessions);
+        self
+    }
+
+    pub fn registry(&self) -> Arc<SubscriptionRegistry> {
+        self.registry.clone()
+    }
+
     pub async fn start(&self, bind_addr: &str) -> Result<()> {
         let listener = TcpListener::bind(bind_addr).await?;
         info!("WebSocket server listening on {}", bind_addr);
+        self.serve(listener).await
+    }
 
-        // 启动事件广播任务
-        self.start_event_broadcaster().await;
-
-        // 处理连接
-        while let Ok((stream, addr)) = listener.accept().await {
-            info!("New WebSocket connection from {}", addr);
-            
-            let connections = self.connections.clone();
-            let event_receiver = self.event_sender.subscribe();
-            
-            tokio::spawn(async move {
-                if let Err(e) = Self::handle_connection(stream, connections, event_receiver).await {
-                    error!("WebSocket connection error: {}", e);
-                }
+    /// 在已绑定的监听器上提供服务
+    pub async fn serve(&self, listener: TcpListener) -> Result<()> {
+        // 启动事件分发任务
+        self.start_event_dispatcher();
+
+        let app = Router::new()
+            .route("/", get(Self::handle_upgrade))
+            .with_state(WsState {
+                registry: self.registry.clone(),
+                auth: self.auth.clone(),
+                offchain: self.offchain.clone(),
             });
-        }
 
+        let server = hyper::Server::from_tcp(listener.into_std()?)?
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>());
+        server.await?;
         Ok(())
     }
 
-    async fn start_event_broadcaster(&self) {
-        let event_sender = self.event_sender.clone();
-        
-        // 模拟事件发送（实际应该从其他模块接收事件）
+    /// 将适配器管理器事件总线上某条链的新区块 / 新交易接入订阅
+    pub fn bridge_chain_events(
+        &self,
+        adapters: Arc<AdapterManager>,
+        chain_type: ChainType,
+    ) -> JoinHandle<()> {
+        let mut events = adapters.subscribe_events();
+        let sender = self.event_sender.clone();
+
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
-            
             loop {
-                interval.tick().await;
-                
-                // 发送示例事件
-                let event = WsEvent::ParallelStats {
-                    efficiency: 0.95,
-                    conflicts: 12,
+                let event = match events.recv().await {
+                    Ok(event) if event.chain_type == chain_type => event,
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Chain event bridge lagged, skipped {} events", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
                 };
-                
-                if let Err(e) = event_sender.send(event) {
-                    warn!("Failed to send event: {}", e);
+
+                match event.kind {
+                    ChainEventKind::NewBlock => {
+                        let number = adapters
+                            .get_block_number(chain_type)
+                            .await
+                            .unwrap_or_default();
+                        // 没有订阅者时 send 返回错误，忽略即可
+                        let _ = sender.send(ChainEvent::NewHead {
+                            hash: event.payload,
+                            number,
+                        });
+                    }
+                    ChainEventKind::NewTransaction => {
+                        let hash = event.payload;
+                        let _ = sender.send(ChainEvent::NewPendingTransaction {
+                            hash: hash.clone(),
+                        });
+
+                        match adapters.get_transaction_receipt(chain_type, &hash).await {
+                            Ok(receipt) if !receipt.logs.is_empty() => {
+                                let _ = sender.send(ChainEvent::Logs {
+                                    tx_hash: receipt.tx_hash,
+                                    block_hash: receipt.block_hash,
+                                    block_number: receipt.block_number,
+                                    logs: receipt.logs,
+                                });
+                            }
+                            Ok(_) => {}
+                            Err(e) => debug!("No receipt for {} yet: {}", hash, e),
+                        }
+                    }
+                }
+            }
+            debug!("Chain event bus closed, {:?} bridge stopped", chain_type);
+        })
+    }
+
+    /// 发送事件到所有订阅了 dubheEvents 的客户端
+    pub async fn broadcast_event(&self, event: WsEvent) -> Result<()> {
+        self.event_sender.send(ChainEvent::Dubhe(event))?;
+        Ok(())
+    }
+
+    fn start_event_dispatcher(&self) {
+        let registry = self.registry.clone();
+        let mut events = self.event_sender.subscribe();
+
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event) => registry.publish(&event),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Event dispatcher lagged, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
                 }
             }
         });
     }
 
+    async fn handle_upgrade(
+        ws: WebSocketUpgrade,
+        State(state): State<WsState>,
+        ConnectInfo(peer): ConnectInfo<SocketAddr>,
+        headers: HeaderMap,
+    ) -> Response {
+        // 调用方身份在握手时确定，之后每条请求按该身份限流
+        let access = match &state.auth {
+            Some(auth) => {
+                let authorization = headers
+                    .get(header::AUTHORIZATION)
+                    .and_then(|value| value.to_str().ok());
+                match auth.identify(authorization, peer.ip()) {
+                    Ok(caller) => Some((auth.clone(), caller)),
+                    Err(e) => return (StatusCode::UNAUTHORIZED, e.to_string()).into_response(),
+                }
+            }
+            None => None,
+        };
+
+        let registry = state.registry;
+        let offchain = state.offchain;
+        ws.on_upgrade(move |socket| async move {
+            Self::handle_connection(socket, registry, access, offchain).await;
+        })
+        .into_response()
+    }
+
     async fn handle_connection(
-        stream: TcpStream,
-        connections: Arc<RwLock<HashMap<Uuid, broadcast::Sender<String>>>>,
-        mut event_receiver: broadcast::Receiver<WsEvent>,
-    ) -> Result<()> {
-        let connection_id = Uuid::new_v4();
-        let (reader, writer) = stream.into_split();
-        
-        let mut lines = FramedRead::new(reader, LinesCodec::new());
-        let mut sink = FramedWrite::new(writer, LinesCodec::new());
-        
-        // 创建连接专用的广播通道
-        let (tx, mut rx) = broadcast::channel(100);
-        connections.write().await.insert(connection_id, tx);
-
-        // 处理输出消息
-        let output_task = tokio::spawn(async move {
-            while let Ok(message) = rx.recv().await {
-                if let Err(e) = sink.send(message).await {
-                    error!("Failed to send message: {}", e);
-                    break;
+        socket: WebSocket,
+        registry: Arc<SubscriptionRegistry>,
+        access: Option<(Arc<Authenticator>, Caller)>,
+        offchain: Option<Arc<OffchainSessions>>,
+    ) {
+        let client = Uuid::new_v4();
+        let (mut sink, mut stream) = socket.split();
+        let mut outbound = registry.register_client(client);
+        info!("WebSocket client {} connected", client);
+
+        // 发送队列被移除（慢客户端）时结束并关闭连接
+        let mut output_task = tokio::spawn(async move {
+            while let Some(message) = outbound.recv().await {
+                if sink.send(Message::Text(message)).await.is_err() {
+                    return;
                 }
             }
+            let _ = sink.send(Message::Close(None)).await;
         });
 
-        // 处理事件广播
-        let connections_clone = connections.clone();
-        let event_task = tokio::spawn(async move {
-            while let Ok(event) = event_receiver.recv().await {
-                let message = match serde_json::to_string(&event) {
-                    Ok(msg) => msg,
-                    Err(e) => {
-                        error!("Failed to serialize event: {}", e);
-                        continue;
+        loop {
+            tokio::select! {
+                incoming = stream.next() => match incoming {
+                    Some(Ok(Message::Text(text))) => {
+                        let access = access.as_ref().map(|(auth, caller)| (auth.as_ref(), caller));
+                        let (response, feed) = Self::handle_request(
+                            &registry,
+                            client,
+                            &text,
+                            access,
+                            offchain.as_deref(),
+                        );
+                        if !registry.send_to(&client, response) {
+                            break;
+                        }
+                        // 先送达订阅 ID，再推送会话事件
+                        if let Some(feed) = feed {
+                            tokio::spawn(forward_session_events(registry.clone(), client, feed));
+                        }
                     }
-                };
-
-                let connections = connections_clone.read().await;
-                for (_, sender) in connections.iter() {
-                    if let Err(e) = sender.send(message.clone()) {
-                        warn!("Failed to broadcast message: {}", e);
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
+                        error!("WebSocket read error: {}", e);
+                        break;
                     }
-                }
+                },
+                _ = &mut output_task => break,
             }
-        });
+        }
+
+        registry.remove_client(&client);
+        output_task.abort();
+        info!("WebSocket client {} disconnected", client);
+    }
 
-        // 处理输入消息（目前只是回显）
-        while let Some(line) = lines.next().await {
-            match line {
-                Ok(msg) => {
-                    info!("Received message: {}", msg);
-                    // TODO: 处理客户端消息
+    /// 处理 eth_subscribe / eth_unsubscribe / dubhe_subscribeExecution 请求，返回响应帧；
+    /// 执行订阅另外返回待推送的会话事件
+    fn handle_request(
+        registry: &SubscriptionRegistry,
+        client: Uuid,
+        text: &str,
+        access: Option<(&Authenticator, &Caller)>,
+        offchain: Option<&OffchainSessions>,
+    ) -> (String, Option<ExecutionFeed>) {
+        let request: Value = match serde_json::from_str(text) {
+            Ok(request) => request,
+            Err(_) => return (error_frame(Value::Null, -32700, "Parse error"), None),
+        };
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+
+        if let Some((auth, caller)) = access {
+            let method = request.get("method").and_then(Value::as_str).unwrap_or_default();
+            if let Err(e) = auth.authorize(caller, method) {
+                let response =
+                    json!({ "jsonrpc": "2.0", "id": id, "error": e.to_error_object() }).to_string();
+                return (response, None);
+            }
+        }
+        let params = request
+            .get("params")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+
+        let response = match request.get("method").and_then(Value::as_str) {
+            Some("dubhe_subscribeExecution") => {
+                let Some(sessions) = offchain else {
+                    return (
+                        error_frame(id, -32601, "Offchain execution is not enabled"),
+                        None,
+                    );
+                };
+                let Some(session_id) = params.first().and_then(Value::as_str) else {
+                    return (error_frame(id, -32602, "missing session id"), None);
+                };
+                let Some(events) = sessions.subscribe(session_id) else {
+                    return (
+                        error_frame(id, -32602, &format!("Unknown session {}", session_id)),
+                        None,
+                    );
+                };
+                let subscription =
+                    registry.subscribe(client, SubscriptionKind::Execution(session_id.to_string()));
+                let response =
+                    json!({ "jsonrpc": "2.0", "id": id, "result": subscription }).to_string();
+                return (
+                    response,
+                    Some(ExecutionFeed {
+                        subscription,
+                        events,
+                    }),
+                );
+            }
+            Some("eth_subscribe") => match SubscriptionKind::from_params(&params) {
+                Ok(kind) => {
+                    let subscription = registry.subscribe(client, kind);
+                    json!({ "jsonrpc": "2.0", "id": id, "result": subscription }).to_string()
+                }
+                Err(e) => error_frame(id, -32602, &e),
+            },
+            Some("eth_unsubscribe") => match params.first().and_then(Value::as_str) {
+                Some(subscription) => {
+                    let removed = registry.unsubscribe(&client, subscription);
+                    json!({ "jsonrpc": "2.0", "id": id, "result": removed }).to_string()
                 }
-                Err(e) => {
-                    error!("Error reading line: {}", e);
-                    break;
+                None => error_frame(id, -32602, "missing subscription id"),
+            },
+            Some(method) => error_frame(id, -32601, &format!("Method not found: {}", method)),
+            None => error_frame(id, -32600, "Invalid request"),
+        };
+        (response, None)
+    }
+}
+
+fn error_frame(id: Value, code: i64, message: &str) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+    fn log(address: &str, topics: &[&str]) -> EventLog {
+        EventLog {
+            address: address.to_string(),
+            topics: topics.iter().map(|t| t.to_string()).collect(),
+            data: "0x".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_and_notify() {
+        let registry = SubscriptionRegistry::new(8);
+        let client = Uuid::new_v4();
+        let mut rx = registry.register_client(client);
+
+        let (response, _) = WsServer::handle_request(
+            &registry,
+            client,
+            r#"{"jsonrpc":"2.0","id":1,"method":"eth_subscribe","params":["newHeads"]}"#,
+            None,
+            None,
+        );
+        let subscription = serde_json::from_str::<Value>(&response).unwrap()["result"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        registry.publish(&ChainEvent::NewHead {
+            hash: "0xabc".to_string(),
+            number: 16,
+        });
+        let frame: Value = serde_json::from_str(&rx.recv().await.unwrap()).unwrap();
+        assert_eq!(frame["method"], "eth_subscription");
+        assert_eq!(frame["params"]["subscription"], subscription.as_str());
+        assert_eq!(frame["params"]["result"]["number"], "0x10");
+
+        // 取消后不再收到通知
+        assert!(registry.unsubscribe(&client, &subscription));
+        registry.publish(&ChainEvent::NewHead {
+            hash: "0xdef".to_string(),
+            number: 17,
+        });
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_log_filter() {
+        let filter = LogFilter::from_params(Some(&json!({
+            "address": "0xAA",
+            "topics": [null, ["0x01", "0x02"]],
+        })))
+        .unwrap();
+
+        assert!(filter.matches(&log("0xaa", &["0xff", "0x02"])));
+        assert!(!filter.matches(&log("0xaa", &["0xff", "0x03"])));
+        assert!(!filter.matches(&log("0xbb", &["0xff", "0x01"])));
+        assert!(!filter.matches(&log("0xaa", &["0xff"])));
+    }
+
+    #[test]
+    fn test_rate_limited_request_returns_retry_hint() {
+        use crate::auth::{AuthConfig, BucketConfig, RateLimitConfig};
+
+        let bucket = BucketConfig {
+            burst: 1,
+            per_second: 0.5,
+        };
+        let auth = Authenticator::new(&AuthConfig {
+            api_keys: vec![],
+            rate_limits: RateLimitConfig {
+                read: bucket.clone(),
+                execute: bucket,
+            },
+        });
+        let caller = Caller::Ip("10.0.0.7".parse().unwrap());
+        let registry = SubscriptionRegistry::new(8);
+        let client = Uuid::new_v4();
+        let request = r#"{"jsonrpc":"2.0","id":7,"method":"eth_subscribe","params":["newHeads"]}"#;
+
+        let (first, _) =
+            WsServer::handle_request(&registry, client, request, Some((&auth, &caller)), None);
+        assert!(serde_json::from_str::<Value>(&first).unwrap()["result"].is_string());
+
+        let second: Value = serde_json::from_str(
+            &WsServer::handle_request(&registry, client, request, Some((&auth, &caller)), None).0,
+        )
+        .unwrap();
+        assert_eq!(second["id"], 7);
+        assert_eq!(second["error"]["code"], -32005);
+        assert!(second["error"]["data"]["retryAfterMs"].as_u64().unwrap() > 1000);
+        assert_eq!(registry.subscription_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_slow_client_is_dropped() {
+        let registry = SubscriptionRegistry::new(2);
+        let slow = Uuid::new_v4();
+        let fast = Uuid::new_v4();
+        let mut slow_rx = registry.register_client(slow);
+        let mut fast_rx = registry.register_client(fast);
+        registry.subscribe(slow, SubscriptionKind::NewPendingTransactions);
+        registry.subscribe(fast, SubscriptionKind::NewPendingTransactions);
+
+        for i in 0..3 {
+            registry.publish(&ChainEvent::NewPendingTransaction {
+                hash: format!("0x{}", i),
+            });
+            // 快客户端及时消费
+            assert!(fast_rx.recv().await.is_some());
+        }
+
+        assert_eq!(registry.client_count(), 1);
+        assert_eq!(registry.subscription_count(), 1);
+
+        // 慢客户端收完已缓冲的消息后通道关闭
+        assert!(slow_rx.recv().await.is_some());
+        assert!(slow_rx.recv().await.is_some());
+        assert!(slow_rx.recv().await.is_none());
+    }
+
+    /// 分阶段上报进度的慢执行后端，收到开始信号后才执行
+    #[derive(Default)]
+    struct SlowOffchain {
+        start: tokio::sync::Notify,
+        progress: std::sync::Mutex<HashMap<String, mpsc::UnboundedSender<Value>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::offchain::OffchainHandler for SlowOffchain {
+        async fn execute(
+            &self,
+            session_id: String,
+            _params: crate::offchain::OffchainExecutionParams,
+        ) -> Result<Value> {
+            self.start.notified().await;
+            let progress = self.progress.lock().unwrap().remove(&session_id);
+            for phase in ["locking", "executing", "syncing_results"] {
+                if let Some(progress) = &progress {
+                    progress.send(json!({ "phase": phase })).unwrap();
                 }
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
             }
+            Ok(json!({ "sessionId": session_id, "success": true }))
         }
 
-        // 清理连接
-        connections.write().await.remove(&connection_id);
-        output_task.abort();
-        event_task.abort();
-        
-        info!("WebSocket connection {} closed", connection_id);
-        Ok(())
+        fn subscribe_progress(
+            &self,
+            session_id: &str,
+        ) -> Option<futures::stream::BoxStream<'static, Value>> {
+            let (tx, rx) = mpsc::unbounded_channel();
+            self.progress
+                .lock()
+                .unwrap()
+                .insert(session_id.to_string(), tx);
+            Some(tokio_stream::wrappers::UnboundedReceiverStream::new(rx).boxed())
+        }
     }
 
-    /// 发送事件到所有连接的客户端
-    pub async fn broadcast_event(&self, event: WsEvent) -> Result<()> {
-        self.event_sender.send(event)?;
-        Ok(())
+    fn subscribe_execution(id: u64, session_id: &Value) -> WsMessage {
+        WsMessage::Text(
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "dubhe_subscribeExecution",
+                "params": [session_id],
+            })
+            .to_string(),
+        )
     }
-} 
\ No newline at end of file
+
+    async fn next_json<S>(socket: &mut S) -> Value
+    where
+        S: futures::Stream<Item = Result<WsMessage, tokio_tungstenite::tungstenite::Error>> + Unpin,
+    {
+        let message = socket.next().await.unwrap().unwrap();
+        serde_json::from_str(message.to_text().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_execution_progress_over_socket() {
+        let handler = Arc::new(SlowOffchain::default());
+        let sessions = Arc::new(OffchainSessions::new(
+            handler.clone(),
+            crate::offchain::OffchainRpcConfig::default(),
+        ));
+        let server = WsServer::new().with_offchain(sessions.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { server.serve(listener).await });
+
+        let params = serde_json::from_value(json!({
+            "packageId": "0xpkg",
+            "functionName": "increment",
+            "gasBudget": 1_000,
+            "async": true,
+        }))
+        .unwrap();
+        let session_id = sessions.execute(params).await.unwrap()["sessionId"].clone();
+
+        // 订阅完成后才开始执行，进度从第一个阶段开始
+        let (mut socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        socket
+            .send(subscribe_execution(1, &session_id))
+            .await
+            .unwrap();
+        let subscription = next_json(&mut socket).await["result"].clone();
+        assert!(subscription.is_string());
+        handler.start.notify_one();
+
+        let mut events = Vec::new();
+        loop {
+            let frame = next_json(&mut socket).await;
+            assert_eq!(frame["params"]["subscription"], subscription);
+            let event = frame["params"]["result"].clone();
+            let last = event["type"] != "progress";
+            events.push(event);
+            if last {
+                break;
+            }
+        }
+        assert_eq!(events.len(), 4);
+        let phases: Vec<&str> = events[..3]
+            .iter()
+            .map(|event| event["progress"]["phase"].as_str().unwrap())
+            .collect();
+        assert_eq!(phases, ["locking", "executing", "syncing_results"]);
+        assert_eq!(events[3]["type"], "completed");
+        assert_eq!(events[3]["result"]["sessionId"], session_id);
+
+        // 已结束的会话直接重放最终事件
+        socket
+            .send(subscribe_execution(2, &session_id))
+            .await
+            .unwrap();
+        let response = next_json(&mut socket).await;
+        assert_eq!(response["id"], 2);
+        let frame = next_json(&mut socket).await;
+        assert_eq!(frame["params"]["subscription"], response["result"]);
+        assert_eq!(frame["params"]["result"]["type"], "completed");
+
+        socket
+            .send(subscribe_execution(3, &json!("rpc-unknown")))
+            .await
+            .unwrap();
+        assert_eq!(next_json(&mut socket).await["error"]["code"], -32602);
+    }
+}
@@ -0,0 +1,105 @@
+//! 交易分发器 criterion 基准
+//!
+//! `cargo bench -p dubhe-bench --bench dispatcher`：在低冲突的 10 万笔交易负载上，
+//! 对比所有 worker 争用同一接收端的共享队列与按写键分区、可窃取的本地队列
+
+use criterion::{BenchmarkId, Criterion, Throughput};
+use dubhe_bench::workload::{WorkloadConfig, WorkloadGenerator};
+use dubhe_bench::BenchConfig;
+use dubhe_scheduler::{
+    ExecutionPlan, NoopExecutor, Transaction, TransactionDispatcher, TransactionExecutor,
+    TransactionResult,
+};
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tokio::sync::{mpsc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+const TRANSACTIONS: usize = 100_000;
+
+/// 共享队列基线：全部 worker 从同一个接收端取交易
+async fn shared_queue(
+    workers: usize,
+    transactions: Arc<Vec<Transaction>>,
+) -> Vec<TransactionResult> {
+    let (queue_tx, queue_rx) = mpsc::unbounded_channel();
+    for index in 0..transactions.len() {
+        queue_tx.send(index).expect("queue open");
+    }
+    drop(queue_tx);
+    let queue = Arc::new(Mutex::new(queue_rx));
+
+    let mut handles = Vec::with_capacity(workers);
+    for _ in 0..workers {
+        let queue = queue.clone();
+        let transactions = transactions.clone();
+        handles.push(tokio::spawn(async move {
+            let mut completed = Vec::new();
+            loop {
+                let Some(index) = queue.lock().await.recv().await else {
+                    return completed;
+                };
+                let result = NoopExecutor
+                    .execute(&transactions[index])
+                    .await
+                    .expect("noop execution");
+                completed.push((index, result));
+            }
+        }));
+    }
+
+    let mut results = vec![None; transactions.len()];
+    for handle in handles {
+        for (index, result) in handle.await.expect("worker") {
+            results[index] = Some(result);
+        }
+    }
+    results.into_iter().flatten().collect()
+}
+
+fn bench_dispatcher(c: &mut Criterion, config: &BenchConfig) {
+    let runtime = Runtime::new().expect("tokio runtime");
+    let workers = num_cpus::get();
+    let transactions = Arc::new(
+        WorkloadGenerator::new(WorkloadConfig {
+            batch_size: TRANSACTIONS,
+            conflict_density: 0.01,
+            hot_keys: 1024,
+            ..Default::default()
+        })
+        .next_batch(),
+    );
+    // 低冲突负载整体作为一个并行组，只测分发开销
+    let plan = ExecutionPlan {
+        parallel_groups: vec![(0..TRANSACTIONS).collect()],
+        dependency_order: (0..TRANSACTIONS).collect(),
+        unordered: vec![],
+    };
+    let dispatcher = TransactionDispatcher::new(workers).expect("dispatcher");
+    let cancel = CancellationToken::new();
+
+    let mut group = c.benchmark_group("dispatcher");
+    group
+        .sample_size(config.iterations.max(10))
+        .throughput(Throughput::Elements(TRANSACTIONS as u64));
+    group.bench_function(BenchmarkId::new("shared_queue", workers), |b| {
+        b.to_async(&runtime)
+            .iter(|| shared_queue(workers, transactions.clone()))
+    });
+    group.bench_function(BenchmarkId::new("work_stealing", workers), |b| {
+        b.to_async(&runtime).iter(|| async {
+            dispatcher
+                .execute_parallel(plan.clone(), &transactions, &cancel)
+                .await
+                .expect("dispatch")
+        })
+    });
+    group.finish();
+}
+
+fn main() {
+    let config = BenchConfig::from_env().expect("bench config");
+    let mut criterion = Criterion::default().configure_from_args();
+    bench_dispatcher(&mut criterion, &config);
+    criterion.final_summary();
+}
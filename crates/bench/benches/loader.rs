@@ -0,0 +1,89 @@
+//! Loader criterion 基准
+//!
+//! `cargo bench -p dubhe-bench --bench loader`：criterion 的 HTML 报告之外，
+//! 在 `BenchConfig::output_dir` 下写出 JSON 汇总
+
+use criterion::{BenchmarkId, Criterion};
+use dubhe_bench::loader_bench::{
+    cache_hit, fixture_corpus, fixture_label, load_cold, load_warm, run_summary, LoaderSummary,
+};
+use dubhe_bench::BenchConfig;
+use dubhe_loader::CodeLoader;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+fn bench_loader(c: &mut Criterion, config: &BenchConfig) {
+    let runtime = Runtime::new().expect("tokio runtime");
+    let cache_dir = tempfile::tempdir().expect("cache dir");
+    let loader = CodeLoader::with_cache_dir(cache_dir.path()).expect("code loader");
+    let loader = &loader;
+    let corpus = fixture_corpus(config);
+
+    let mut cold = c.benchmark_group("loader_cold");
+    for meta in &corpus {
+        cold.bench_with_input(
+            BenchmarkId::from_parameter(fixture_label(meta)),
+            meta,
+            |b, meta| {
+                b.to_async(&runtime).iter_custom(|iters| async move {
+                    let mut total = Duration::ZERO;
+                    for _ in 0..iters {
+                        total += load_cold(loader, meta).await.expect("cold load");
+                    }
+                    total
+                })
+            },
+        );
+    }
+    cold.finish();
+
+    let mut warm = c.benchmark_group("loader_warm");
+    for meta in &corpus {
+        runtime
+            .block_on(loader.load_contract(meta))
+            .expect("warm up");
+        warm.bench_with_input(
+            BenchmarkId::from_parameter(fixture_label(meta)),
+            meta,
+            |b, meta| {
+                b.to_async(&runtime)
+                    .iter(|| async { load_warm(loader, meta).await.expect("warm load") })
+            },
+        );
+    }
+    warm.finish();
+
+    let mut hits = c.benchmark_group("cache_hit");
+    for meta in &corpus {
+        let key = loader.generate_cache_key(meta);
+        runtime
+            .block_on(loader.load_contract(meta))
+            .expect("warm up");
+        hits.bench_with_input(
+            BenchmarkId::from_parameter(fixture_label(meta)),
+            &key,
+            |b, key| {
+                b.to_async(&runtime)
+                    .iter(|| async { cache_hit(loader, key).await.expect("cache hit") })
+            },
+        );
+    }
+    hits.finish();
+}
+
+fn main() {
+    let config = BenchConfig::from_env().expect("bench config");
+    let mut criterion = Criterion::default().configure_from_args();
+    bench_loader(&mut criterion, &config);
+    criterion.final_summary();
+
+    let summary = Runtime::new()
+        .expect("tokio runtime")
+        .block_on(run_summary(&config))
+        .expect("loader summary");
+    println!(
+        "Loader summary ({} fixtures) written to {}",
+        summary.samples.len(),
+        LoaderSummary::path(&config).display()
+    );
+}
@@ -0,0 +1,73 @@
+//! VM 实例池 criterion 基准
+//!
+//! `cargo bench -p dubhe-bench --bench vm_pool`：对同一个包反复发起短小调用，
+//! 对比每次新建实例并加载代码，与从实例池签出预热实例、用完归还
+
+use criterion::{BenchmarkId, Criterion, Throughput};
+use dubhe_bench::BenchConfig;
+use dubhe_loader::riscv;
+use dubhe_vm_runtime::{VmManager, VmPool, VmPoolConfig, VmType};
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+const INPUT: &[u8] = b"balance_of(0xa)";
+
+fn bench_vm_pool(c: &mut Criterion, config: &BenchConfig) {
+    let runtime = Runtime::new().expect("tokio runtime");
+    let manager = Arc::new(VmManager::new(VmType::CkbVM));
+    // 发布构建默认不做重置校验，显式关闭以免调试构建下测到校验开销
+    let pool = VmPool::new(
+        manager.clone(),
+        VmPoolConfig {
+            verify_reset: false,
+            ..Default::default()
+        },
+    );
+    let (manager, pool) = (&manager, &pool);
+
+    let mut group = c.benchmark_group("vm_pool");
+    group
+        .sample_size(config.iterations.max(10))
+        .throughput(Throughput::Elements(1));
+    for size in &config.bytecode_sizes {
+        // echo 程序后补齐到语料大小，代码加载开销随包大小增长
+        let mut code = riscv::assemble(&riscv::echo_program());
+        code.resize(code.len().max(*size), 0);
+        let code = &code;
+
+        group.bench_with_input(BenchmarkId::new("fresh", size), code, |b, code| {
+            b.to_async(&runtime).iter(|| async {
+                let mut vm = manager.create_instance(None).expect("instance");
+                vm.load_code(code).await.expect("load code");
+                vm.execute(INPUT).await.expect("execute")
+            })
+        });
+
+        runtime.block_on(async {
+            let vm = pool.checkout(None, code, None).await.expect("warm up");
+            pool.checkin(vm).await;
+        });
+        group.bench_with_input(BenchmarkId::new("pooled", size), code, |b, code| {
+            b.to_async(&runtime).iter(|| async {
+                let mut vm = pool.checkout(None, code, None).await.expect("checkout");
+                let result = vm.execute(INPUT).await.expect("execute");
+                pool.checkin(vm).await;
+                result
+            })
+        });
+    }
+    group.finish();
+
+    let stats = pool.stats();
+    println!(
+        "vm_pool: {} hits, {} misses, {} idle instances",
+        stats.hits, stats.misses, stats.idle_instances
+    );
+}
+
+fn main() {
+    let config = BenchConfig::from_env().expect("bench config");
+    let mut criterion = Criterion::default().configure_from_args();
+    bench_vm_pool(&mut criterion, &config);
+    criterion.final_summary();
+}
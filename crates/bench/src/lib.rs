@@ -2,17 +2,33 @@
 //!
 //! TPS / Loader / Scheduler 压测工具
 
+// 压测模块同时作为独立二进制编译，统一通过 crate 名引用公共配置
+extern crate self as dubhe_bench;
+
 pub mod loader_bench;
 pub mod scheduler_bench;
+pub mod workload;
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// 基准测试配置
-#[derive(Debug, Clone)]
+///
+/// 语料规模可通过环境变量覆盖，见 [`BenchConfig::from_env`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BenchConfig {
     pub duration_secs: u64,
     pub concurrent_requests: usize,
     pub warmup_secs: u64,
+    /// Loader 语料：每种合约类型生成的字节码大小（字节）
+    pub bytecode_sizes: Vec<usize>,
+    /// Scheduler 语料：每轮负载的交易数
+    pub transaction_counts: Vec<usize>,
+    /// 汇总统计的每组采样次数
+    pub iterations: usize,
+    /// JSON 汇总的输出目录
+    pub output_dir: PathBuf,
 }
 
 impl Default for BenchConfig {
@@ -21,6 +37,55 @@ impl Default for BenchConfig {
             duration_secs: 60,
             concurrent_requests: 100,
             warmup_secs: 10,
+            bytecode_sizes: vec![1024, 32 * 1024, 256 * 1024],
+            transaction_counts: vec![1000],
+            iterations: 20,
+            output_dir: PathBuf::from("target/criterion"),
         }
     }
 }
+
+impl BenchConfig {
+    /// 默认配置，按以下环境变量覆盖：
+    ///
+    /// - `DUBHE_BENCH_DURATION_SECS` / `DUBHE_BENCH_WARMUP_SECS`：测量与预热时长
+    /// - `DUBHE_BENCH_CONCURRENCY`：并发提交数
+    /// - `DUBHE_BENCH_BYTECODE_SIZES`：逗号分隔的字节码大小
+    /// - `DUBHE_BENCH_TRANSACTION_COUNTS`：逗号分隔的交易数
+    /// - `DUBHE_BENCH_ITERATIONS`：每组采样次数
+    /// - `DUBHE_BENCH_OUTPUT_DIR`：JSON 汇总目录
+    pub fn from_env() -> Result<Self> {
+        let mut config = Self::default();
+        if let Ok(secs) = std::env::var("DUBHE_BENCH_DURATION_SECS") {
+            config.duration_secs = secs.trim().parse()?;
+        }
+        if let Ok(secs) = std::env::var("DUBHE_BENCH_WARMUP_SECS") {
+            config.warmup_secs = secs.trim().parse()?;
+        }
+        if let Ok(concurrency) = std::env::var("DUBHE_BENCH_CONCURRENCY") {
+            config.concurrent_requests = concurrency.trim().parse()?;
+        }
+        if let Ok(sizes) = std::env::var("DUBHE_BENCH_BYTECODE_SIZES") {
+            config.bytecode_sizes = parse_list(&sizes)?;
+        }
+        if let Ok(counts) = std::env::var("DUBHE_BENCH_TRANSACTION_COUNTS") {
+            config.transaction_counts = parse_list(&counts)?;
+        }
+        if let Ok(iterations) = std::env::var("DUBHE_BENCH_ITERATIONS") {
+            config.iterations = iterations.trim().parse()?;
+        }
+        if let Ok(dir) = std::env::var("DUBHE_BENCH_OUTPUT_DIR") {
+            config.output_dir = PathBuf::from(dir);
+        }
+        Ok(config)
+    }
+}
+
+fn parse_list(value: &str) -> Result<Vec<usize>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| Ok(item.parse()?))
+        .collect()
+}
@@ -1,8 +1,262 @@
 //! Loader 基准测试
+//!
+//! 为每种合约类型生成不同大小的合成 ContractMeta，分别测量：
+//! - 冷加载：清空编译缓存后的 `CodeLoader::load_contract`（含编译与落盘）
+//! - 热加载：缓存已就绪时的 `CodeLoader::load_contract`
+//! - 缓存命中：仅 `CompilationCache::get` 的耗时
+//!
+//! 结果以 JSON 写入 `BenchConfig::output_dir`，供 CI 跟踪回归
 
 use anyhow::Result;
+use dubhe_adapter::{ChainType, ContractMeta, ContractType};
+use dubhe_bench::BenchConfig;
+use dubhe_loader::CodeLoader;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// JSON 汇总的文件名
+pub const SUMMARY_FILE: &str = "loader-summary.json";
+
+/// 参与测量的合约类型
+pub const CONTRACT_TYPES: [ContractType; 3] =
+    [ContractType::Move, ContractType::EVM, ContractType::BPF];
+
+/// 确定性伪随机字节，避免不同运行间字节码差异
+fn synthetic_bytes(size: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    (0..size)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+/// Move 包的规范化 ABI：每 KB 字节码一个入口函数
+fn move_abi(size: usize) -> String {
+    let functions: serde_json::Map<String, serde_json::Value> = (0..(size / 1024).max(1))
+        .map(|i| {
+            (
+                format!("entry_{}", i),
+                serde_json::json!({
+                    "isEntry": true,
+                    "visibility": "Public",
+                    "parameters": ["U64", "Address", "Bool"],
+                }),
+            )
+        })
+        .collect();
+    serde_json::json!({ "bench": { "exposedFunctions": functions } }).to_string()
+}
+
+/// 生成合成合约
+pub fn contract_fixture(contract_type: ContractType, size: usize) -> ContractMeta {
+    let (chain_type, abi, seed) = match contract_type {
+        ContractType::Move => (ChainType::Sui, Some(move_abi(size)), 1),
+        ContractType::EVM => (ChainType::Ethereum, None, 2),
+        ContractType::BPF => (ChainType::Solana, None, 3),
+        ContractType::Script => (ChainType::Bitcoin, None, 4),
+        ContractType::WASM => (ChainType::Ethereum, None, 5),
+    };
+    ContractMeta {
+        address: format!("0xbench_{:?}_{}", contract_type, size).to_lowercase(),
+        chain_type,
+        contract_type,
+        bytecode: synthetic_bytes(size, seed ^ size as u64),
+        abi,
+        source_code: None,
+        compiler_version: None,
+        created_at: 0,
+        creator: None,
+        abi_source: None,
+        modules: vec![],
+    }
+}
+
+/// 按配置生成全部语料
+pub fn fixture_corpus(config: &BenchConfig) -> Vec<ContractMeta> {
+    CONTRACT_TYPES
+        .iter()
+        .flat_map(|contract_type| {
+            config
+                .bytecode_sizes
+                .iter()
+                .map(|&size| contract_fixture(contract_type.clone(), size))
+        })
+        .collect()
+}
+
+/// 语料在报告中的标识，例如 `Move/32768`
+pub fn fixture_label(meta: &ContractMeta) -> String {
+    format!("{:?}/{}", meta.contract_type, meta.bytecode.len())
+}
+
+/// 一次冷加载：清空缓存后加载
+pub async fn load_cold(loader: &CodeLoader, meta: &ContractMeta) -> Result<Duration> {
+    loader.cache().clear().await?;
+    let started = Instant::now();
+    loader.load_contract(meta).await?;
+    Ok(started.elapsed())
+}
+
+/// 一次热加载，调用方需保证已加载过
+pub async fn load_warm(loader: &CodeLoader, meta: &ContractMeta) -> Result<Duration> {
+    let started = Instant::now();
+    loader.load_contract(meta).await?;
+    Ok(started.elapsed())
+}
+
+/// 一次缓存读取，调用方需保证已加载过
+pub async fn cache_hit(loader: &CodeLoader, key: &str) -> Result<Duration> {
+    let started = Instant::now();
+    let hit = loader.cache().get(key).await?;
+    let elapsed = started.elapsed();
+    anyhow::ensure!(hit.is_some(), "expected a cache hit for {}", key);
+    Ok(elapsed)
+}
+
+/// 一组采样的延迟统计（微秒）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub samples: usize,
+    pub mean_us: f64,
+    pub median_us: f64,
+    pub p95_us: f64,
+    pub max_us: f64,
+}
+
+impl LatencyStats {
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        let mut micros: Vec<f64> = samples
+            .iter()
+            .map(|sample| sample.as_secs_f64() * 1e6)
+            .collect();
+        micros.sort_by(|a, b| a.total_cmp(b));
+        let percentile = |p: f64| {
+            if micros.is_empty() {
+                return 0.0;
+            }
+            let rank = ((micros.len() - 1) as f64 * p).round() as usize;
+            micros[rank]
+        };
+        Self {
+            samples: micros.len(),
+            mean_us: micros.iter().sum::<f64>() / micros.len().max(1) as f64,
+            median_us: percentile(0.5),
+            p95_us: percentile(0.95),
+            max_us: micros.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+/// 单个语料的测量结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoaderSample {
+    pub contract_type: String,
+    pub bytecode_size: usize,
+    pub cold: LatencyStats,
+    pub warm: LatencyStats,
+    pub cache_hit: LatencyStats,
+}
+
+/// 机器可读的汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoaderSummary {
+    pub generated_at: u64,
+    pub config: BenchConfig,
+    pub samples: Vec<LoaderSample>,
+}
+
+impl LoaderSummary {
+    pub fn path(config: &BenchConfig) -> PathBuf {
+        config.output_dir.join(SUMMARY_FILE)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+}
+
+/// 对全部语料各采样 `config.iterations` 次
+pub async fn measure(loader: &CodeLoader, config: &BenchConfig) -> Result<LoaderSummary> {
+    let iterations = config.iterations.max(1);
+    let mut samples = Vec::new();
+
+    for meta in fixture_corpus(config) {
+        let mut cold = Vec::with_capacity(iterations);
+        for _ in 0..iterations {
+            cold.push(load_cold(loader, &meta).await?);
+        }
+
+        let key = loader.generate_cache_key(&meta);
+        let mut warm = Vec::with_capacity(iterations);
+        let mut hits = Vec::with_capacity(iterations);
+        for _ in 0..iterations {
+            warm.push(load_warm(loader, &meta).await?);
+            hits.push(cache_hit(loader, &key).await?);
+        }
+
+        samples.push(LoaderSample {
+            contract_type: format!("{:?}", meta.contract_type),
+            bytecode_size: meta.bytecode.len(),
+            cold: LatencyStats::from_samples(&cold),
+            warm: LatencyStats::from_samples(&warm),
+            cache_hit: LatencyStats::from_samples(&hits),
+        });
+    }
+
+    Ok(LoaderSummary {
+        generated_at: chrono::Utc::now().timestamp() as u64,
+        config: config.clone(),
+        samples,
+    })
+}
+
+/// 使用临时缓存目录测量并写出汇总
+pub async fn run_summary(config: &BenchConfig) -> Result<LoaderSummary> {
+    let cache_dir = tempfile::tempdir()?;
+    let loader = CodeLoader::with_cache_dir(cache_dir.path())?;
+    let summary = measure(&loader, config).await?;
+    summary.write(&LoaderSummary::path(config))?;
+    Ok(summary)
+}
+
+async fn run() -> Result<()> {
+    let config = BenchConfig::from_env()?;
+    let summary = run_summary(&config).await?;
+
+    println!(
+        "{:<14} {:>12} {:>12} {:>12}",
+        "contract", "cold p50", "warm p50", "hit p50"
+    );
+    for sample in &summary.samples {
+        println!(
+            "{:<14} {:>10.1}us {:>10.1}us {:>10.1}us",
+            format!("{}/{}", sample.contract_type, sample.bytecode_size),
+            sample.cold.median_us,
+            sample.warm.median_us,
+            sample.cache_hit.median_us
+        );
+    }
+    println!(
+        "Summary written to {}",
+        LoaderSummary::path(&config).display()
+    );
 
-fn main() -> Result<()> {
-    println!("Loader benchmark - TODO: implement");
     Ok(())
 }
+
+fn main() -> Result<()> {
+    tokio::runtime::Runtime::new()?.block_on(run())
+}
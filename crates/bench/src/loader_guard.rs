@@ -0,0 +1,78 @@
+//! Loader 热路径回归守卫
+//!
+//! 读取 JSON 汇总（或当场测量），任一语料的热加载 p95 超过预算时以非零状态退出：
+//!
+//! ```text
+//! loader-guard [--summary <path>] [--budget-us <micros>]
+//! ```
+//!
+//! 预算默认取 `DUBHE_BENCH_WARM_BUDGET_US`，未设置时为 [`DEFAULT_WARM_BUDGET_US`]
+
+use anyhow::{bail, Context, Result};
+use dubhe_bench::loader_bench::{run_summary, LoaderSummary};
+use dubhe_bench::BenchConfig;
+use std::path::PathBuf;
+
+/// 热加载 p95 的默认预算（微秒）
+const DEFAULT_WARM_BUDGET_US: f64 = 1_000.0;
+
+async fn run() -> Result<()> {
+    let mut summary_path: Option<PathBuf> = None;
+    let mut budget_us = match std::env::var("DUBHE_BENCH_WARM_BUDGET_US") {
+        Ok(value) => value.trim().parse()?,
+        Err(_) => DEFAULT_WARM_BUDGET_US,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--summary" => {
+                summary_path = Some(args.next().context("--summary needs a path")?.into())
+            }
+            "--budget-us" => {
+                budget_us = args.next().context("--budget-us needs a value")?.parse()?
+            }
+            other => bail!("Unknown argument: {}", other),
+        }
+    }
+
+    let summary = match summary_path {
+        Some(path) => LoaderSummary::read(&path)
+            .with_context(|| format!("Failed to read summary {}", path.display()))?,
+        None => run_summary(&BenchConfig::from_env()?).await?,
+    };
+
+    let over_budget: Vec<_> = summary
+        .samples
+        .iter()
+        .filter(|sample| sample.warm.p95_us > budget_us)
+        .collect();
+    for sample in &summary.samples {
+        println!(
+            "{:<5} {}/{}: warm p95 {:.1}us (budget {:.1}us)",
+            if sample.warm.p95_us > budget_us {
+                "FAIL"
+            } else {
+                "ok"
+            },
+            sample.contract_type,
+            sample.bytecode_size,
+            sample.warm.p95_us,
+            budget_us
+        );
+    }
+
+    if !over_budget.is_empty() {
+        bail!(
+            "{} of {} fixtures exceeded the warm-path budget of {:.1}us",
+            over_budget.len(),
+            summary.samples.len(),
+            budget_us
+        );
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    tokio::runtime::Runtime::new()?.block_on(run())
+}
@@ -1,8 +1,488 @@
 //! Scheduler 基准测试
+//!
+//! 高冲突负载下对比 Block-STM（AptosStrategy）与账号读写集合（SolanaStrategy），
+//! 并用 [`WorkloadGenerator`] 生成的同一负载驱动 `ParallelScheduler` 的各个策略，
+//! 输出 TPS、批次延迟、中止 / 重试次数与并行效率报告
 
 use anyhow::Result;
+use async_trait::async_trait;
+use dubhe_bench::workload::{measured_conflict_density, WorkloadConfig, WorkloadGenerator};
+use dubhe_bench::BenchConfig;
+use dubhe_scheduler::aptos_strategy::AptosStrategy;
+use dubhe_scheduler::solana_strategy::SolanaStrategy;
+use dubhe_scheduler::{
+    ConflictAnalyzer, ExecutionStats, ExecutionStrategy, NoopExecutor, ParallelScheduler,
+    SchedulerConfig, SchedulerResult, StateView, StrategyType, Transaction, TransactionDispatcher,
+    TransactionExecutor, TransactionResult, VersionedExecutor, VersionedOutput,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// 单笔交易模拟的执行耗时
+const EXECUTION_COST: Duration = Duration::from_micros(200);
+
+/// 高冲突负载：`count` 笔交易轮流写 `hot_accounts` 个热点账户
+pub fn high_conflict_workload(count: usize, hot_accounts: usize) -> Vec<Transaction> {
+    (0..count)
+        .map(|i| {
+            let account = format!("0xhot{}", i % hot_accounts.max(1));
+            Transaction {
+                hash: format!("0x{:064x}", i),
+                from: format!("0xsender{}", i),
+                to: Some(account.clone()),
+                data: vec![],
+                gas_limit: 21000,
+                gas_price: 1,
+                nonce: 0,
+                read_set: vec![account.clone()],
+                write_set: vec![account],
+                access_list: None,
+                max_priority_fee: None,
+            }
+        })
+        .collect()
+}
+
+/// 策略报告的文件名
+pub const REPORT_FILE: &str = "scheduler-report.json";
+
+/// 参与对比的策略
+pub const STRATEGIES: [StrategyType; 4] = [
+    StrategyType::Sequential,
+    StrategyType::SolanaParallel,
+    StrategyType::AptosSTM,
+    StrategyType::SuiObject,
+];
+
+/// 预生成并循环提交的批次数
+const WORKLOAD_BATCHES: usize = 16;
+
+/// 单次运行的结果
+#[derive(Debug, Clone)]
+pub struct StrategyRun {
+    pub strategy: &'static str,
+    pub elapsed: Duration,
+    pub conflicts: usize,
+    pub executions: usize,
+}
+
+impl StrategyRun {
+    pub fn tps(&self, transactions: usize) -> f64 {
+        transactions as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// 热点账户计数器，同时作为两种执行路径的执行器
+#[derive(Default)]
+struct CounterExecutor {
+    storage: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+fn decode(bytes: &[u8]) -> u64 {
+    bytes.try_into().map(u64::from_le_bytes).unwrap_or(0)
+}
+
+#[async_trait]
+impl VersionedExecutor for CounterExecutor {
+    fn storage_read(&self, location: &str) -> Option<Vec<u8>> {
+        self.storage.lock().unwrap().get(location).cloned()
+    }
+
+    async fn execute(
+        &self,
+        transaction: &Transaction,
+        view: &dyn StateView,
+    ) -> SchedulerResult<VersionedOutput> {
+        let account = transaction.to.clone().unwrap_or_default();
+        let current = view.read(&account).map(|bytes| decode(&bytes)).unwrap_or(0);
+        tokio::time::sleep(EXECUTION_COST).await;
+
+        let next = (current + 1).to_le_bytes().to_vec();
+        Ok(VersionedOutput {
+            result: TransactionResult {
+                tx_hash: transaction.hash.clone(),
+                success: true,
+                gas_used: 21000,
+                output: next.clone(),
+                logs: vec![],
+                error: None,
+            },
+            writes: vec![(account, next)],
+        })
+    }
+
+    async fn commit(&self, writes: BTreeMap<String, Vec<u8>>) -> SchedulerResult<()> {
+        self.storage.lock().unwrap().extend(writes);
+        Ok(())
+    }
+}
+
+/// 调度计划执行路径下的执行器：按声明的读写集合保证互斥，只模拟耗时
+struct SleepExecutor;
+
+#[async_trait]
+impl TransactionExecutor for SleepExecutor {
+    async fn execute(&self, transaction: &Transaction) -> SchedulerResult<TransactionResult> {
+        tokio::time::sleep(EXECUTION_COST).await;
+        NoopExecutor.execute(transaction).await
+    }
+}
+
+/// Block-STM 乐观执行
+pub async fn run_block_stm(transactions: &[Transaction], workers: usize) -> Result<StrategyRun> {
+    let executor = Arc::new(CounterExecutor::default());
+    let started = Instant::now();
+    let outcome = AptosStrategy::new()
+        .with_workers(workers)
+        .execute_block(transactions, executor, &CancellationToken::new())
+        .await?;
+
+    Ok(StrategyRun {
+        strategy: "aptos_block_stm",
+        elapsed: started.elapsed(),
+        conflicts: outcome.aborts,
+        executions: outcome.executions,
+    })
+}
+
+/// 冲突分析 + Solana 分层计划 + 分发器执行
+pub async fn run_solana(transactions: &[Transaction], workers: usize) -> Result<StrategyRun> {
+    let dispatcher = TransactionDispatcher::new(workers)?.with_executor(Arc::new(SleepExecutor));
+    let started = Instant::now();
+    let graph = ConflictAnalyzer::new().analyze(transactions).await?;
+    let plan = SolanaStrategy::new().plan_execution(transactions, &graph).await?;
+    dispatcher
+        .execute_parallel(plan, transactions, &CancellationToken::new())
+        .await?;
+
+    Ok(StrategyRun {
+        strategy: "solana_parallel",
+        elapsed: started.elapsed(),
+        conflicts: graph.edges.len(),
+        executions: transactions.len(),
+    })
+}
+
+/// 按读写集合访问键的版本化执行器，统计执行次数（含重试）
+#[derive(Default)]
+struct KeyedExecutor {
+    storage: Mutex<BTreeMap<String, Vec<u8>>>,
+    executions: AtomicUsize,
+}
+
+#[async_trait]
+impl VersionedExecutor for KeyedExecutor {
+    fn storage_read(&self, location: &str) -> Option<Vec<u8>> {
+        self.storage.lock().unwrap().get(location).cloned()
+    }
+
+    async fn execute(
+        &self,
+        transaction: &Transaction,
+        view: &dyn StateView,
+    ) -> SchedulerResult<VersionedOutput> {
+        self.executions.fetch_add(1, Ordering::Relaxed);
+        for key in &transaction.read_set {
+            view.read(key);
+        }
+        let writes: Vec<(String, Vec<u8>)> = transaction
+            .write_set
+            .iter()
+            .map(|key| {
+                let current = view.read(key).map(|bytes| decode(&bytes)).unwrap_or(0);
+                (key.clone(), (current + 1).to_le_bytes().to_vec())
+            })
+            .collect();
+        tokio::time::sleep(EXECUTION_COST).await;
+
+        Ok(VersionedOutput {
+            result: TransactionResult {
+                tx_hash: transaction.hash.clone(),
+                success: true,
+                gas_used: 21000,
+                output: vec![],
+                logs: vec![],
+                error: None,
+            },
+            writes,
+        })
+    }
+
+    async fn commit(&self, writes: BTreeMap<String, Vec<u8>>) -> SchedulerResult<()> {
+        self.storage.lock().unwrap().extend(writes);
+        Ok(())
+    }
+}
+
+/// 单个策略的测量结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyReport {
+    pub strategy: StrategyType,
+    pub batches: usize,
+    pub transactions: usize,
+    pub elapsed_ms: u64,
+    pub tps: f64,
+    pub p50_latency_ms: f64,
+    pub p99_latency_ms: f64,
+    /// 乐观执行的中止次数；按计划执行的策略为冲突边数
+    pub aborts: usize,
+    /// 超出交易数的重复执行次数
+    pub retries: usize,
+    /// 各批次并行效率的均值
+    pub parallel_efficiency: f64,
+}
+
+/// 一组负载下所有策略的对比报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerReport {
+    pub generated_at: u64,
+    pub config: BenchConfig,
+    pub workload: WorkloadConfig,
+    /// 生成批次的实际冲突密度
+    pub conflict_density: f64,
+    pub strategies: Vec<StrategyReport>,
+}
+
+impl SchedulerReport {
+    pub fn path(config: &BenchConfig, batch_size: usize) -> PathBuf {
+        config
+            .output_dir
+            .join(format!("{}-{}", batch_size, REPORT_FILE))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for SchedulerReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "batch_size={} conflict_density={:.3} (configured {:.2}) zipf={:.2}",
+            self.workload.batch_size,
+            self.conflict_density,
+            self.workload.conflict_density,
+            self.workload.zipf_exponent
+        )?;
+        writeln!(
+            f,
+            "  {:<16} {:>10} {:>10} {:>10} {:>8} {:>8} {:>10}",
+            "strategy", "tps", "p50 ms", "p99 ms", "aborts", "retries", "efficiency"
+        )?;
+        for report in &self.strategies {
+            writeln!(
+                f,
+                "  {:<16} {:>10.0} {:>10.2} {:>10.2} {:>8} {:>8} {:>10.2}",
+                format!("{:?}", report.strategy),
+                report.tps,
+                report.p50_latency_ms,
+                report.p99_latency_ms,
+                report.aborts,
+                report.retries,
+                report.parallel_efficiency
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 单个批次的提交延迟与统计
+struct BatchRun {
+    latency: Duration,
+    stats: ExecutionStats,
+}
+
+/// `concurrency` 个提交者循环提交批次，直到 `duration` 结束
+async fn drive(
+    scheduler: Arc<ParallelScheduler>,
+    batches: Arc<Vec<Vec<Transaction>>>,
+    concurrency: usize,
+    duration: Duration,
+) -> Result<Vec<BatchRun>> {
+    let deadline = Instant::now() + duration;
+    let next = Arc::new(AtomicUsize::new(0));
+    let mut submitters = tokio::task::JoinSet::new();
+    for _ in 0..concurrency.max(1) {
+        let scheduler = scheduler.clone();
+        let batches = batches.clone();
+        let next = next.clone();
+        submitters.spawn(async move {
+            let mut runs = Vec::new();
+            while Instant::now() < deadline {
+                let batch = &batches[next.fetch_add(1, Ordering::Relaxed) % batches.len()];
+                let started = Instant::now();
+                let result = scheduler.submit_batch(batch.clone()).await?;
+                runs.push(BatchRun {
+                    latency: started.elapsed(),
+                    stats: result.execution_stats,
+                });
+            }
+            Ok::<_, anyhow::Error>(runs)
+        });
+    }
+
+    let mut runs = Vec::new();
+    while let Some(joined) = submitters.join_next().await {
+        runs.extend(joined??);
+    }
+    Ok(runs)
+}
+
+fn percentile_ms(sorted: &[Duration], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[rank].as_secs_f64() * 1e3
+}
+
+/// 在同一组批次上运行一个策略：先预热，再按配置时长测量
+pub async fn run_strategy(
+    strategy: StrategyType,
+    batches: Arc<Vec<Vec<Transaction>>>,
+    config: &BenchConfig,
+) -> Result<StrategyReport> {
+    let executor = Arc::new(KeyedExecutor::default());
+    let mut scheduler = ParallelScheduler::new(strategy, SchedulerConfig::default())?
+        .with_executor(Arc::new(SleepExecutor));
+    if strategy == StrategyType::AptosSTM {
+        scheduler = scheduler.with_versioned_executor(executor.clone());
+    }
+    let scheduler = Arc::new(scheduler);
+
+    drive(
+        scheduler.clone(),
+        batches.clone(),
+        config.concurrent_requests,
+        Duration::from_secs(config.warmup_secs),
+    )
+    .await?;
+    executor.executions.store(0, Ordering::Relaxed);
+
+    let started = Instant::now();
+    let runs = drive(
+        scheduler,
+        batches,
+        config.concurrent_requests,
+        Duration::from_secs(config.duration_secs),
+    )
+    .await?;
+    let elapsed = started.elapsed();
+
+    let transactions: usize = runs.iter().map(|run| run.stats.total_transactions).sum();
+    let mut latencies: Vec<Duration> = runs.iter().map(|run| run.latency).collect();
+    latencies.sort();
+    let executions = executor.executions.load(Ordering::Relaxed);
+
+    Ok(StrategyReport {
+        strategy,
+        batches: runs.len(),
+        transactions,
+        elapsed_ms: elapsed.as_millis() as u64,
+        tps: transactions as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        p50_latency_ms: percentile_ms(&latencies, 0.5),
+        p99_latency_ms: percentile_ms(&latencies, 0.99),
+        aborts: runs.iter().map(|run| run.stats.conflicts_detected).sum(),
+        retries: executions.saturating_sub(transactions),
+        parallel_efficiency: runs
+            .iter()
+            .map(|run| run.stats.parallel_efficiency)
+            .sum::<f64>()
+            / runs.len().max(1) as f64,
+    })
+}
+
+/// 同一负载依次交给各个策略
+pub async fn compare_strategies(
+    workload: WorkloadConfig,
+    config: &BenchConfig,
+) -> Result<SchedulerReport> {
+    let batches = WorkloadGenerator::new(workload.clone()).batches(WORKLOAD_BATCHES);
+    let conflict_density = batches
+        .iter()
+        .map(|batch| measured_conflict_density(batch))
+        .sum::<f64>()
+        / batches.len() as f64;
+    let batches = Arc::new(batches);
+
+    let mut strategies = Vec::new();
+    for strategy in STRATEGIES {
+        strategies.push(run_strategy(strategy, batches.clone(), config).await?);
+    }
+
+    Ok(SchedulerReport {
+        generated_at: chrono::Utc::now().timestamp() as u64,
+        config: config.clone(),
+        workload,
+        conflict_density,
+        strategies,
+    })
+}
+
+async fn run() -> Result<()> {
+    let config = BenchConfig::from_env()?;
+    let mut workload = WorkloadConfig::default();
+    if let Ok(density) = std::env::var("DUBHE_BENCH_CONFLICT_DENSITY") {
+        workload.conflict_density = density.trim().parse()?;
+    }
+    if let Ok(exponent) = std::env::var("DUBHE_BENCH_ZIPF_EXPONENT") {
+        workload.zipf_exponent = exponent.trim().parse()?;
+    }
+
+    let workers = num_cpus::get();
+    for (count, hot_accounts) in config
+        .transaction_counts
+        .iter()
+        .flat_map(|&count| [1, 4, 16].map(|hot_accounts| (count, hot_accounts)))
+    {
+        let transactions = high_conflict_workload(count, hot_accounts);
+        println!("{} transactions over {} hot accounts:", count, hot_accounts);
+
+        for run in [
+            run_block_stm(&transactions, workers).await?,
+            run_solana(&transactions, workers).await?,
+        ] {
+            println!(
+                "  {:<16} {:>8.2?} {:>10.0} tps  conflicts={:<6} executions={}",
+                run.strategy,
+                run.elapsed,
+                run.tps(count),
+                run.conflicts,
+                run.executions
+            );
+        }
+    }
+
+    for &batch_size in &config.transaction_counts {
+        let report = compare_strategies(
+            WorkloadConfig {
+                batch_size,
+                ..workload.clone()
+            },
+            &config,
+        )
+        .await?;
+        print!("{}", report);
+
+        let path = SchedulerReport::path(&config, batch_size);
+        report.write(&path)?;
+        println!("Report written to {}", path.display());
+    }
 
-fn main() -> Result<()> {
-    println!("Scheduler benchmark - TODO: implement");
     Ok(())
 }
+
+fn main() -> Result<()> {
+    tokio::runtime::Runtime::new()?.block_on(run())
+}
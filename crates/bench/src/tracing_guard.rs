@@ -0,0 +1,123 @@
+//! 追踪开销回归守卫
+//!
+//! 用同一负载交替测量两种配置下 `ParallelScheduler::submit_batch` 的耗时：
+//! 不安装 subscriber，以及安装节点默认的 subscriber（日志层 + 未配置 OTLP 端点）。
+//! 每轮取两者之比，中位数超过预算时以非零状态退出：
+//!
+//! ```text
+//! tracing-guard [--rounds <n>] [--budget-pct <percent>]
+//! ```
+//!
+//! subscriber 以线程局部默认值安装，因此在单线程运行时上执行，保证 worker 任务也在同一线程
+
+use anyhow::{bail, Context, Result};
+use dubhe_bench::workload::{WorkloadConfig, WorkloadGenerator};
+use dubhe_observability::{build_subscriber, TracingConfig};
+use dubhe_scheduler::{
+    NoopExecutor, ParallelScheduler, SchedulerConfig, StrategyType, Transaction,
+};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 默认允许的回归比例（百分比）
+const DEFAULT_BUDGET_PCT: f64 = 1.0;
+
+/// 默认测量轮数
+const DEFAULT_ROUNDS: usize = 21;
+
+/// 每轮每种配置提交的批次数
+const BATCHES_PER_ROUND: usize = 8;
+
+async fn measure(scheduler: &ParallelScheduler, batches: &[Vec<Transaction>]) -> Result<Duration> {
+    let start = Instant::now();
+    for batch in batches {
+        scheduler.submit_batch(batch.clone()).await?;
+    }
+    Ok(start.elapsed())
+}
+
+async fn measure_traced(
+    scheduler: &ParallelScheduler,
+    batches: &[Vec<Transaction>],
+) -> Result<Duration> {
+    let (subscriber, _guard) = build_subscriber("info", &TracingConfig::default(), std::io::sink)?;
+    let _default = tracing::subscriber::set_default(subscriber);
+    measure(scheduler, batches).await
+}
+
+async fn run() -> Result<()> {
+    let mut rounds = DEFAULT_ROUNDS;
+    let mut budget_pct = DEFAULT_BUDGET_PCT;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--rounds" => rounds = args.next().context("--rounds needs a value")?.parse()?,
+            "--budget-pct" => {
+                budget_pct = args.next().context("--budget-pct needs a value")?.parse()?
+            }
+            other => bail!("Unknown argument: {}", other),
+        }
+    }
+    if rounds == 0 {
+        bail!("--rounds must be at least 1");
+    }
+
+    let workload = WorkloadConfig {
+        batch_size: 1_000,
+        ..WorkloadConfig::default()
+    };
+    let config = SchedulerConfig {
+        batch_size: workload.batch_size,
+        ..SchedulerConfig::default()
+    };
+    let batches = WorkloadGenerator::new(workload).batches(BATCHES_PER_ROUND);
+    let scheduler = ParallelScheduler::new(StrategyType::SolanaParallel, config)?
+        .with_executor(Arc::new(NoopExecutor));
+
+    // 预热
+    measure(&scheduler, &batches).await?;
+    measure_traced(&scheduler, &batches).await?;
+
+    let mut ratios = Vec::with_capacity(rounds);
+    for round in 0..rounds {
+        // 交替先后顺序，抵消缓存与频率调节的影响
+        let (plain, traced) = if round % 2 == 0 {
+            let plain = measure(&scheduler, &batches).await?;
+            (plain, measure_traced(&scheduler, &batches).await?)
+        } else {
+            let traced = measure_traced(&scheduler, &batches).await?;
+            (measure(&scheduler, &batches).await?, traced)
+        };
+        ratios.push(traced.as_secs_f64() / plain.as_secs_f64().max(f64::EPSILON));
+    }
+    ratios.sort_by(f64::total_cmp);
+    let regression_pct = (ratios[ratios.len() / 2] - 1.0) * 100.0;
+
+    println!(
+        "{:<5} tracing overhead {:+.2}% over {} rounds (budget {:.2}%)",
+        if regression_pct > budget_pct {
+            "FAIL"
+        } else {
+            "ok"
+        },
+        regression_pct,
+        rounds,
+        budget_pct
+    );
+    if regression_pct > budget_pct {
+        bail!(
+            "Tracing instrumentation slowed the scheduler by {:.2}%, budget is {:.2}%",
+            regression_pct,
+            budget_pct
+        );
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(run())
+}
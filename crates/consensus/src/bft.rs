@@ -1,9 +1,896 @@
 //! BFT 共识
+//!
+//! 最小化的 leader 轮换 BFT（HotStuff / Tendermint 风格的三阶段）：
+//! 1. 视图 v 的 leader（v mod n）从交易池取一批交易提议区块
+//! 2. 验证者对合法提议广播 prevote；收到 2f+1 个 prevote 后锁定该区块并广播 precommit
+//! 3. 收到 2f+1 个 precommit 后提交区块，交给提交回调（例如 ParallelScheduler），进入下一高度
+//!
+//! leader 超时未能推进时，验证者广播 view-change 并附上自己锁定的区块与 prevote 证书；
+//! 新 leader 收集 2f+1 条 view-change 后重新提议其中视图最高的锁定区块，
+//! 已锁定的验证者只接受与锁相同的区块或附带更高视图证书的区块，从而保证不会提交冲突区块。
 
-pub struct BftConsensus;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::futures::Notified;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{debug, error, info, warn};
+
+use dubhe_scheduler::{ParallelScheduler, SubmissionLane, Transaction};
+
+use crate::crypto::{VoteSigner, VoteVerifier};
+use crate::network::Network;
+use crate::types::*;
+
+/// 第一个区块的父哈希
+pub const GENESIS_PARENT: &str = "genesis";
+
+/// 等待进入当前高度 / 视图的消息最多缓存条数
+const MAX_FUTURE_MESSAGES: usize = 10_000;
+
+/// BFT 参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BftConfig {
+    /// 视图内未能提交时发起 view-change 的超时
+    pub view_timeout_ms: u64,
+    /// 每个区块最多包含的交易数
+    pub max_batch_size: usize,
+}
+
+impl Default for BftConfig {
+    fn default() -> Self {
+        Self {
+            view_timeout_ms: 1_000,
+            max_batch_size: 1_000,
+        }
+    }
+}
+
+/// 区块提交回调
+#[async_trait]
+pub trait CommitHandler: Send + Sync {
+    async fn commit(&self, block: &Block) -> Result<()>;
+}
+
+/// 将提交的批次按共识顺序交给并行调度器执行
+pub struct SchedulerCommitHandler {
+    scheduler: Arc<ParallelScheduler>,
+}
+
+impl SchedulerCommitHandler {
+    pub fn new(scheduler: Arc<ParallelScheduler>) -> Self {
+        Self { scheduler }
+    }
+}
+
+#[async_trait]
+impl CommitHandler for SchedulerCommitHandler {
+    async fn commit(&self, block: &Block) -> Result<()> {
+        let result = self
+            .scheduler
+            .submit_to_lane(block.transactions.clone(), SubmissionLane::Consensus)
+            .await?;
+        debug!(
+            "Block {} executed: {} succeeded, {} failed",
+            block.height,
+            result.execution_stats.successful_transactions,
+            result.execution_stats.failed_transactions
+        );
+        Ok(())
+    }
+}
+
+/// 待排序的交易；提议时只读取，区块提交后才移除
+#[derive(Default)]
+pub struct Mempool {
+    queue: Mutex<VecDeque<Transaction>>,
+    notify: Notify,
+}
+
+impl Mempool {
+    pub fn submit(&self, transaction: Transaction) {
+        self.queue.lock().unwrap().push_back(transaction);
+        self.notify.notify_waiters();
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn notified(&self) -> Notified<'_> {
+        self.notify.notified()
+    }
+
+    /// 取出最早提交的至多 `max` 笔交易
+    pub(crate) fn take(&self, max: usize) -> Vec<Transaction> {
+        let mut queue = self.queue.lock().unwrap();
+        let count = queue.len().min(max);
+        queue.drain(..count).collect()
+    }
+
+    fn peek(&self, max: usize) -> Vec<Transaction> {
+        self.queue
+            .lock()
+            .unwrap()
+            .iter()
+            .take(max)
+            .cloned()
+            .collect()
+    }
+
+    fn remove_committed(&self, block: &Block) {
+        let committed: HashSet<&str> = block
+            .transactions
+            .iter()
+            .map(|tx| tx.hash.as_str())
+            .collect();
+        self.queue
+            .lock()
+            .unwrap()
+            .retain(|tx| !committed.contains(tx.hash.as_str()));
+    }
+}
+
+/// 锁定的区块及其 prevote 证书
+type Lock = (QuorumCertificate, Block);
+
+/// 当前高度的共识状态，提交后整体重置（视图按证书推进）
+struct Round {
+    height: u64,
+    parent: String,
+    view: u64,
+    deadline: Instant,
+    /// 本视图已接受的提议
+    proposal: Option<Block>,
+    proposed: bool,
+    prevoted: bool,
+    precommitted: bool,
+    lock: Option<Lock>,
+    /// 作为新 leader 需要重新提议的锁定区块
+    reproposal: Option<Lock>,
+    /// 本高度见过的区块，按哈希索引
+    blocks: HashMap<String, Block>,
+    votes: HashMap<Vote, BTreeMap<ValidatorId, Vec<u8>>>,
+    view_changes: BTreeMap<u64, HashMap<ValidatorId, Option<Lock>>>,
+    requested_view: u64,
+}
+
+/// 单个验证者的 BFT 状态机
+pub struct BftConsensus {
+    id: ValidatorId,
+    validators: usize,
+    config: BftConfig,
+    signer: Arc<dyn VoteSigner>,
+    verifier: Arc<dyn VoteVerifier>,
+    network: Arc<dyn Network>,
+    commit_handler: Arc<dyn CommitHandler>,
+    mempool: Arc<Mempool>,
+    round: Round,
+    /// 属于更高高度或更高视图的消息，进入对应高度 / 视图后重放
+    future: Vec<SignedMessage>,
+    replay: VecDeque<SignedMessage>,
+}
 
 impl BftConsensus {
-    pub fn new() -> Self {
-        Self
+    pub fn new(
+        id: ValidatorId,
+        signer: Arc<dyn VoteSigner>,
+        verifier: Arc<dyn VoteVerifier>,
+        network: Arc<dyn Network>,
+        commit_handler: Arc<dyn CommitHandler>,
+    ) -> Self {
+        let config = BftConfig::default();
+        Self {
+            id,
+            validators: verifier.validator_count(),
+            round: Round::new(0, GENESIS_PARENT.to_string(), 0, timeout_of(&config)),
+            config,
+            signer,
+            verifier,
+            network,
+            commit_handler,
+            mempool: Arc::new(Mempool::default()),
+            future: Vec::new(),
+            replay: VecDeque::new(),
+        }
+    }
+
+    pub fn with_config(mut self, config: BftConfig) -> Self {
+        self.round.deadline = Instant::now() + timeout_of(&config);
+        self.config = config;
+        self
+    }
+
+    /// 本验证者的交易池，提交给它的交易在其担任 leader 时被提议
+    pub fn mempool(&self) -> Arc<Mempool> {
+        self.mempool.clone()
+    }
+
+    /// 可容忍的拜占庭验证者数 f（n >= 3f + 1）
+    pub fn fault_tolerance(&self) -> usize {
+        self.validators.saturating_sub(1) / 3
+    }
+
+    /// 法定人数 n - f（n = 3f + 1 时为 2f + 1）
+    pub fn quorum(&self) -> usize {
+        self.validators - self.fault_tolerance()
+    }
+
+    pub fn leader(&self, view: u64) -> ValidatorId {
+        (view % self.validators.max(1) as u64) as ValidatorId
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+
+    /// 运行共识直到网络关闭
+    pub async fn run(mut self) {
+        info!(
+            "🗳️ Validator {} joined BFT consensus ({} validators, quorum {})",
+            self.id,
+            self.validators,
+            self.quorum()
+        );
+        self.round.deadline = Instant::now() + self.view_timeout();
+        let network = self.network.clone();
+        let mempool = self.mempool.clone();
+
+        loop {
+            while let Some(message) = self.replay.pop_front() {
+                self.handle(message).await;
+            }
+            let new_transaction = mempool.notified();
+            self.try_propose().await;
+
+            tokio::select! {
+                message = network.receive() => match message {
+                    Some(message) => self.handle(message).await,
+                    None => break,
+                },
+                _ = tokio::time::sleep_until(self.round.deadline) => self.on_timeout().await,
+                _ = new_transaction => {}
+            }
+        }
+        info!("Validator {} left BFT consensus", self.id);
+    }
+
+    async fn handle(&mut self, signed: SignedMessage) {
+        let valid = signed
+            .message
+            .signing_bytes()
+            .is_ok_and(|bytes| self.verifier.verify(signed.from, &bytes, &signed.signature));
+        if !valid {
+            warn!(
+                "Validator {} dropped message with invalid signature from {}",
+                self.id, signed.from
+            );
+            return;
+        }
+
+        let height = signed.message.height();
+        if height < self.round.height {
+            return;
+        }
+        let future_view = matches!(
+            &signed.message,
+            ConsensusMessage::Proposal { block, .. } if block.view > self.round.view
+        );
+        if height > self.round.height || future_view {
+            if self.future.len() < MAX_FUTURE_MESSAGES {
+                self.future.push(signed);
+            }
+            return;
+        }
+
+        let from = signed.from;
+        match signed.message {
+            ConsensusMessage::Proposal { block, justify } => {
+                self.on_proposal(from, block, justify).await
+            }
+            ConsensusMessage::Vote(vote) => {
+                self.round
+                    .votes
+                    .entry(vote)
+                    .or_default()
+                    .insert(from, signed.signature);
+            }
+            ConsensusMessage::ViewChange {
+                view,
+                high_qc,
+                locked_block,
+                ..
+            } => self.on_view_change(from, view, high_qc, locked_block).await,
+            ConsensusMessage::Commit { block, certificate } => {
+                if self.valid_certificate(&certificate, Phase::Precommit, &block) {
+                    self.commit(block, certificate).await;
+                }
+                return;
+            }
+        }
+
+        self.advance().await;
+    }
+
+    async fn on_proposal(
+        &mut self,
+        from: ValidatorId,
+        block: Block,
+        justify: Option<QuorumCertificate>,
+    ) {
+        if block.view != self.round.view
+            || from != self.leader(block.view)
+            || block.proposer != from
+            || block.parent != self.round.parent
+            || !block.is_well_formed()
+            || self.round.proposal.is_some()
+        {
+            return;
+        }
+
+        // 锁定规则：只接受锁定的区块，或附带更高视图 prevote 证书的区块
+        if let Some((lock_qc, locked)) = &self.round.lock {
+            let unlocked = justify.as_ref().is_some_and(|qc| {
+                qc.vote.view > lock_qc.vote.view
+                    && self.valid_certificate(qc, Phase::Prevote, &block)
+            });
+            if locked.hash != block.hash && !unlocked {
+                warn!(
+                    "Validator {} rejected proposal {} conflicting with its lock on {}",
+                    self.id, block.hash, locked.hash
+                );
+                return;
+            }
+        }
+
+        debug!(
+            "Validator {} accepted proposal at height {} view {}",
+            self.id, block.height, block.view
+        );
+        self.round.blocks.insert(block.hash.clone(), block.clone());
+        let digest = block.hash.clone();
+        self.round.proposal = Some(block);
+        if !self.round.prevoted {
+            self.round.prevoted = true;
+            self.vote(Phase::Prevote, digest).await;
+        }
+    }
+
+    /// 根据已收集的投票推进：precommit 证书提交区块，本视图的 prevote 证书锁定区块
+    async fn advance(&mut self) {
+        if let Some((certificate, block)) = self.find_certificate(Phase::Precommit, None) {
+            self.commit(block, certificate).await;
+            return;
+        }
+
+        if !self.round.precommitted {
+            if let Some(lock) = self.find_certificate(Phase::Prevote, Some(self.round.view)) {
+                let digest = lock.1.hash.clone();
+                self.round.lock = Some(lock);
+                self.round.precommitted = true;
+                self.vote(Phase::Precommit, digest).await;
+            }
+        }
+    }
+
+    fn find_certificate(&self, phase: Phase, view: Option<u64>) -> Option<Lock> {
+        let quorum = self.quorum();
+        self.round.votes.iter().find_map(|(vote, voters)| {
+            if vote.phase != phase || voters.len() < quorum || view.is_some_and(|v| v != vote.view)
+            {
+                return None;
+            }
+            let block = self.round.blocks.get(&vote.digest)?;
+            let certificate = QuorumCertificate {
+                vote: vote.clone(),
+                signatures: voters
+                    .iter()
+                    .map(|(validator, signature)| (*validator, signature.clone()))
+                    .collect(),
+            };
+            Some((certificate, block.clone()))
+        })
+    }
+
+    fn valid_certificate(
+        &self,
+        certificate: &QuorumCertificate,
+        phase: Phase,
+        block: &Block,
+    ) -> bool {
+        certificate.vote.phase == phase
+            && certificate.vote.height == self.round.height
+            && certificate.vote.digest == block.hash
+            && block.height == self.round.height
+            && block.parent == self.round.parent
+            && block.is_well_formed()
+            && certificate.verify(self.verifier.as_ref(), self.quorum())
+    }
+
+    async fn commit(&mut self, block: Block, certificate: QuorumCertificate) {
+        info!(
+            "✅ Validator {} committed block {} at height {} ({} transactions, view {})",
+            self.id,
+            &block.hash[..16],
+            block.height,
+            block.transactions.len(),
+            certificate.vote.view
+        );
+        self.mempool.remove_committed(&block);
+        if let Err(e) = self.commit_handler.commit(&block).await {
+            error!(
+                "❌ Commit handler failed for block at height {}: {}",
+                block.height, e
+            );
+        }
+
+        // 转发证书，帮助错过投票的验证者提交同一区块
+        self.broadcast(ConsensusMessage::Commit {
+            block: block.clone(),
+            certificate: certificate.clone(),
+        })
+        .await;
+
+        // 所有诚实验证者按证书所在视图推进，保持视图一致
+        self.round = Round::new(
+            block.height + 1,
+            block.hash,
+            certificate.vote.view + 1,
+            self.view_timeout(),
+        );
+        self.replay_future();
+    }
+
+    async fn on_timeout(&mut self) {
+        self.round.deadline = Instant::now() + self.view_timeout();
+        let busy =
+            !self.mempool.is_empty() || self.round.proposal.is_some() || self.round.lock.is_some();
+        if !busy {
+            return;
+        }
+
+        let view = self.round.view + 1;
+        warn!(
+            "⏰ Validator {} timed out in view {} (leader {}), requesting view {}",
+            self.id,
+            self.round.view,
+            self.leader(self.round.view),
+            view
+        );
+        self.request_view(view).await;
+    }
+
+    async fn request_view(&mut self, view: u64) {
+        self.round.requested_view = self.round.requested_view.max(view);
+        let (high_qc, locked_block) = match self.round.lock.clone() {
+            Some((qc, block)) => (Some(qc), Some(block)),
+            None => (None, None),
+        };
+        self.broadcast(ConsensusMessage::ViewChange {
+            view,
+            height: self.round.height,
+            high_qc,
+            locked_block,
+        })
+        .await;
+    }
+
+    async fn on_view_change(
+        &mut self,
+        from: ValidatorId,
+        view: u64,
+        high_qc: Option<QuorumCertificate>,
+        locked_block: Option<Block>,
+    ) {
+        if view <= self.round.view {
+            return;
+        }
+        let lock = match (high_qc, locked_block) {
+            (Some(qc), Some(block)) if self.valid_certificate(&qc, Phase::Prevote, &block) => {
+                Some((qc, block))
+            }
+            _ => None,
+        };
+        let requests = self.round.view_changes.entry(view).or_default();
+        requests.insert(from, lock);
+        let count = requests.len();
+
+        // f+1 个验证者请求时至少有一个诚实验证者超时，跟随请求
+        if count > self.fault_tolerance() && self.round.requested_view < view {
+            self.request_view(view).await;
+        }
+        if count >= self.quorum() {
+            self.enter_view(view);
+        }
+    }
+
+    fn enter_view(&mut self, view: u64) {
+        let requests = self.round.view_changes.remove(&view).unwrap_or_default();
+        let reproposal = requests
+            .into_values()
+            .flatten()
+            .chain(self.round.lock.clone())
+            .max_by_key(|(qc, _)| qc.vote.view);
+        if let Some((_, block)) = &reproposal {
+            self.round.blocks.insert(block.hash.clone(), block.clone());
+        }
+
+        info!(
+            "🔄 Validator {} moved to view {} at height {} (leader {})",
+            self.id,
+            view,
+            self.round.height,
+            self.leader(view)
+        );
+        self.round.view = view;
+        self.round.deadline = Instant::now() + self.view_timeout();
+        self.round.proposal = None;
+        self.round.proposed = false;
+        self.round.prevoted = false;
+        self.round.precommitted = false;
+        self.round.reproposal = reproposal;
+        self.round.view_changes.retain(|pending, _| *pending > view);
+        self.replay_future();
+    }
+
+    async fn try_propose(&mut self) {
+        let view = self.round.view;
+        if self.round.proposed || self.leader(view) != self.id {
+            return;
+        }
+
+        let (block, justify) = match self.round.reproposal.take() {
+            Some((qc, block)) => (
+                Block {
+                    proposer: self.id,
+                    view,
+                    ..block
+                },
+                Some(qc),
+            ),
+            None if !self.mempool.is_empty() => {
+                let block = Block::new(
+                    self.round.height,
+                    self.round.parent.clone(),
+                    self.id,
+                    view,
+                    self.mempool.peek(self.config.max_batch_size),
+                );
+                match block {
+                    Ok(block) => (block, None),
+                    Err(e) => {
+                        error!("❌ Validator {} failed to build a block: {}", self.id, e);
+                        return;
+                    }
+                }
+            }
+            None => return,
+        };
+
+        info!(
+            "📣 Validator {} proposing {} transactions at height {} view {}",
+            self.id,
+            block.transactions.len(),
+            block.height,
+            view
+        );
+        self.round.proposed = true;
+        self.broadcast(ConsensusMessage::Proposal { block, justify })
+            .await;
+    }
+
+    async fn vote(&mut self, phase: Phase, digest: String) {
+        self.broadcast(ConsensusMessage::Vote(Vote {
+            phase,
+            view: self.round.view,
+            height: self.round.height,
+            digest,
+        }))
+        .await;
+    }
+
+    async fn broadcast(&self, message: ConsensusMessage) {
+        let bytes = match message.signing_bytes() {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                error!("❌ Validator {} failed to encode message: {}", self.id, e);
+                return;
+            }
+        };
+        let signature = self.signer.sign(&bytes);
+        let signed = SignedMessage {
+            from: self.id,
+            message,
+            signature,
+        };
+        if let Err(e) = self.network.broadcast(signed).await {
+            warn!("Validator {} failed to broadcast: {}", self.id, e);
+        }
+    }
+
+    fn replay_future(&mut self) {
+        self.replay.extend(self.future.drain(..));
+    }
+
+    fn view_timeout(&self) -> Duration {
+        timeout_of(&self.config)
+    }
+}
+
+impl Round {
+    fn new(height: u64, parent: String, view: u64, timeout: Duration) -> Self {
+        Self {
+            height,
+            parent,
+            view,
+            deadline: Instant::now() + timeout,
+            proposal: None,
+            proposed: false,
+            prevoted: false,
+            precommitted: false,
+            lock: None,
+            reproposal: None,
+            blocks: HashMap::new(),
+            votes: HashMap::new(),
+            view_changes: BTreeMap::new(),
+            requested_view: view,
+        }
+    }
+}
+
+fn timeout_of(config: &BftConfig) -> Duration {
+    Duration::from_millis(config.view_timeout_ms.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{Ed25519VoteSigner, Ed25519VoteVerifier};
+    use crate::network::{InMemoryNetwork, NetworkConditions};
+    use dubhe_scheduler::{SchedulerConfig, StrategyType};
+    use ed25519_dalek::SigningKey;
+
+    /// 记录提交顺序
+    #[derive(Default)]
+    struct Recorder {
+        blocks: Mutex<Vec<Block>>,
+    }
+
+    #[async_trait]
+    impl CommitHandler for Recorder {
+        async fn commit(&self, block: &Block) -> Result<()> {
+            self.blocks.lock().unwrap().push(block.clone());
+            Ok(())
+        }
+    }
+
+    impl Recorder {
+        fn committed(&self) -> Vec<Block> {
+            self.blocks.lock().unwrap().clone()
+        }
+    }
+
+    fn transaction(index: usize) -> Transaction {
+        Transaction {
+            hash: format!("0x{:02x}", index),
+            from: format!("0xsender{}", index),
+            to: Some("0xcontract".to_string()),
+            data: vec![index as u8],
+            gas_limit: 21_000,
+            gas_price: 1,
+            nonce: 0,
+            read_set: vec![],
+            write_set: vec![format!("0xslot{}", index)],
+            access_list: None,
+            max_priority_fee: None,
+        }
+    }
+
+    /// 启动 `validators` 个验证者，`crashed` 中的验证者从不运行
+    fn start(
+        validators: usize,
+        crashed: &[ValidatorId],
+        transactions: usize,
+    ) -> (Vec<Option<Arc<Recorder>>>, Vec<JoinHandle<()>>) {
+        let keys: Vec<SigningKey> = (0..validators)
+            .map(|i| SigningKey::from_bytes(&[i as u8 + 1; 32]))
+            .collect();
+        let verifier = Arc::new(Ed25519VoteVerifier::new(
+            keys.iter().map(SigningKey::verifying_key).collect(),
+        ));
+        let endpoints = InMemoryNetwork::build::<SignedMessage>(
+            validators,
+            NetworkConditions {
+                latency: Duration::from_millis(2),
+                ..Default::default()
+            },
+        );
+
+        let mut recorders = Vec::new();
+        let mut handles = Vec::new();
+        for (id, (key, endpoint)) in keys.into_iter().zip(endpoints).enumerate() {
+            if crashed.contains(&id) {
+                recorders.push(None);
+                continue;
+            }
+            let recorder = Arc::new(Recorder::default());
+            let consensus = BftConsensus::new(
+                id,
+                Arc::new(Ed25519VoteSigner::new(key)),
+                verifier.clone(),
+                Arc::new(endpoint),
+                recorder.clone(),
+            )
+            .with_config(BftConfig {
+                view_timeout_ms: 150,
+                max_batch_size: 2,
+            });
+            let mempool = consensus.mempool();
+            for index in 0..transactions {
+                mempool.submit(transaction(index));
+            }
+            recorders.push(Some(recorder));
+            handles.push(consensus.spawn());
+        }
+        (recorders, handles)
+    }
+
+    async fn wait_for_blocks(
+        recorders: &[Option<Arc<Recorder>>],
+        blocks: usize,
+    ) -> Vec<Vec<Block>> {
+        tokio::time::timeout(Duration::from_secs(20), async {
+            loop {
+                let committed: Vec<Vec<Block>> = recorders
+                    .iter()
+                    .flatten()
+                    .map(|recorder| recorder.committed())
+                    .collect();
+                if committed.iter().all(|blocks_of| blocks_of.len() >= blocks) {
+                    return committed;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("validators did not commit in time")
+    }
+
+    fn assert_same_chain(committed: &[Vec<Block>], blocks: usize) {
+        let reference: Vec<&str> = committed[0][..blocks]
+            .iter()
+            .map(|b| b.hash.as_str())
+            .collect();
+        for chain in committed {
+            let hashes: Vec<&str> = chain[..blocks].iter().map(|b| b.hash.as_str()).collect();
+            assert_eq!(hashes, reference);
+        }
+        for (height, block) in committed[0][..blocks].iter().enumerate() {
+            assert_eq!(block.height, height as u64);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commits_with_one_crashed_validator() {
+        // 验证者 3 宕机：轮到它担任 leader 的视图需要 view-change
+        let (recorders, handles) = start(4, &[3], 10);
+        let committed = wait_for_blocks(&recorders, 5).await;
+        assert_same_chain(&committed, 5);
+
+        let transactions: Vec<String> = committed[0][..5]
+            .iter()
+            .flat_map(|block| block.transactions.iter().map(|tx| tx.hash.clone()))
+            .collect();
+        assert_eq!(
+            transactions,
+            (0..10).map(|i| format!("0x{:02x}", i)).collect::<Vec<_>>()
+        );
+        assert!(committed[0].iter().all(|block| block.proposer != 3));
+
+        handles.iter().for_each(JoinHandle::abort);
+    }
+
+    #[tokio::test]
+    async fn test_view_change_after_leader_failure() {
+        // 视图 0 的 leader 宕机，其余验证者超时后切换到视图 1 继续提交
+        let (recorders, handles) = start(4, &[0], 2);
+        let committed = wait_for_blocks(&recorders, 1).await;
+        assert_same_chain(&committed, 1);
+
+        let block = &committed[0][0];
+        assert!(block.view >= 1);
+        assert_ne!(block.proposer, 0);
+        assert_eq!(block.transactions.len(), 2);
+
+        handles.iter().for_each(JoinHandle::abort);
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_commit_handler_executes_batch() -> Result<()> {
+        let scheduler = Arc::new(ParallelScheduler::new(
+            StrategyType::Sequential,
+            SchedulerConfig::default(),
+        )?);
+        let handler = SchedulerCommitHandler::new(scheduler.clone());
+        let block = Block::new(
+            0,
+            GENESIS_PARENT.to_string(),
+            0,
+            0,
+            (0..3).map(transaction).collect(),
+        )?;
+        assert!(block.is_well_formed());
+
+        handler.commit(&block).await?;
+        assert_eq!(scheduler.get_status().await.total_processed, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_hash_covers_contents() -> Result<()> {
+        let block = Block::new(0, GENESIS_PARENT.to_string(), 0, 0, vec![transaction(0)])?;
+        assert!(block.hash.starts_with("v1:0x"));
+
+        // 提议者与视图不计入哈希，高度与交易计入
+        let reproposed = Block {
+            proposer: 1,
+            view: 1,
+            ..block.clone()
+        };
+        assert!(reproposed.is_well_formed());
+        let tampered = Block {
+            transactions: vec![transaction(1)],
+            ..block.clone()
+        };
+        assert!(!tampered.is_well_formed());
+
+        // 不同阶段的投票签名字节不同
+        let vote = |phase| {
+            ConsensusMessage::Vote(Vote {
+                phase,
+                view: 0,
+                height: 0,
+                digest: block.hash.clone(),
+            })
+        };
+        assert_ne!(
+            vote(Phase::Prevote).signing_bytes()?,
+            vote(Phase::Precommit).signing_bytes()?
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_network_drops_messages() {
+        let endpoints = InMemoryNetwork::build(
+            2,
+            NetworkConditions {
+                drop_rate: 1.0,
+                ..Default::default()
+            },
+        );
+        let message = SignedMessage {
+            from: 0,
+            message: ConsensusMessage::Vote(Vote {
+                phase: Phase::Prevote,
+                view: 0,
+                height: 0,
+                digest: "digest".to_string(),
+            }),
+            signature: vec![],
+        };
+        endpoints[0].send(1, message).await.unwrap();
+        let received =
+            tokio::time::timeout(Duration::from_millis(50), endpoints[1].receive()).await;
+        assert!(received.is_err());
     }
 }
@@ -0,0 +1,67 @@
+//! 投票签名
+//!
+//! 签名方案可插拔，默认实现为 Ed25519
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+use crate::types::ValidatorId;
+
+/// 本验证者的签名者
+pub trait VoteSigner: Send + Sync {
+    fn sign(&self, bytes: &[u8]) -> Vec<u8>;
+}
+
+/// 验证者集合的公钥，按 ValidatorId 索引
+pub trait VoteVerifier: Send + Sync {
+    fn verify(&self, validator: ValidatorId, bytes: &[u8], signature: &[u8]) -> bool;
+
+    /// 验证者总数
+    fn validator_count(&self) -> usize;
+}
+
+/// Ed25519 签名者
+pub struct Ed25519VoteSigner {
+    key: SigningKey,
+}
+
+impl Ed25519VoteSigner {
+    pub fn new(key: SigningKey) -> Self {
+        Self { key }
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.key.verifying_key()
+    }
+}
+
+impl VoteSigner for Ed25519VoteSigner {
+    fn sign(&self, bytes: &[u8]) -> Vec<u8> {
+        self.key.sign(bytes).to_bytes().to_vec()
+    }
+}
+
+/// Ed25519 验证者集合
+pub struct Ed25519VoteVerifier {
+    keys: Vec<VerifyingKey>,
+}
+
+impl Ed25519VoteVerifier {
+    pub fn new(keys: Vec<VerifyingKey>) -> Self {
+        Self { keys }
+    }
+}
+
+impl VoteVerifier for Ed25519VoteVerifier {
+    fn verify(&self, validator: ValidatorId, bytes: &[u8], signature: &[u8]) -> bool {
+        let (Some(key), Ok(signature)) =
+            (self.keys.get(validator), Signature::from_slice(signature))
+        else {
+            return false;
+        };
+        key.verify(bytes, &signature).is_ok()
+    }
+
+    fn validator_count(&self) -> usize {
+        self.keys.len()
+    }
+}
@@ -1,9 +1,733 @@
 //! DAG 共识
+//!
+//! Narwhal 风格的批次可用性层加确定性排序：
+//! 1. worker 把交易池中的交易打包成批次并广播给所有验证者保存
+//! 2. primary 每轮提出一个头，引用本地批次的摘要与上一轮至少 2f+1 个证书
+//! 3. 验证者保存了头引用的全部批次与父证书后对头投票，作者收集 2f+1 票组成可用性证书
+//! 4. 偶数轮按轮转选出 leader，下一轮有 f+1 个证书引用 leader 时提交；
+//!    提交时先补提交可达的更早 leader，再把各 leader 的因果历史按 (轮次, 作者) 展平输出
 
-pub struct DagConsensus;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+use dubhe_scheduler::Transaction;
+
+use crate::bft::Mempool;
+use crate::crypto::{VoteSigner, VoteVerifier};
+use crate::network::Network;
+use crate::types::*;
+
+/// 等待批次或父证书的头 / 证书最多缓存条数
+const MAX_PENDING: usize = 10_000;
+
+/// DAG 参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagConfig {
+    /// 每个批次最多包含的交易数
+    pub batch_size: usize,
+    /// 打包未满批次与提出空头的间隔
+    pub header_delay_ms: u64,
+}
+
+impl Default for DagConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 500,
+            header_delay_ms: 200,
+        }
+    }
+}
+
+/// 单个验证者的 DAG 状态机（worker 与 primary 在同一个任务中运行）
+pub struct DagConsensus {
+    id: ValidatorId,
+    validators: usize,
+    config: DagConfig,
+    signer: Arc<dyn VoteSigner>,
+    verifier: Arc<dyn VoteVerifier>,
+    network: Arc<dyn Network<SignedDagMessage>>,
+    mempool: Arc<Mempool>,
+    output: mpsc::UnboundedSender<OrderedBatch>,
+    /// 已保存的批次
+    batches: HashMap<BatchDigest, Vec<Transaction>>,
+    /// 尚未被本验证者的证书包含的本地批次
+    own_batches: Vec<BatchDigest>,
+    /// 下一个提出的轮次
+    round: Round,
+    header_due: bool,
+    /// 正在收集投票的头
+    header: Option<Header>,
+    votes: BTreeMap<ValidatorId, Vec<u8>>,
+    voted: HashSet<(ValidatorId, Round)>,
+    pending_headers: Vec<Header>,
+    pending_certificates: Vec<Certificate>,
+    certificates: HashMap<String, Certificate>,
+    dag: BTreeMap<Round, BTreeMap<ValidatorId, String>>,
+    last_committed: Option<Round>,
+    ordered: HashSet<String>,
+    delivered: HashSet<BatchDigest>,
+}
 
 impl DagConsensus {
-    pub fn new() -> Self {
-        Self
+    pub fn new(
+        id: ValidatorId,
+        signer: Arc<dyn VoteSigner>,
+        verifier: Arc<dyn VoteVerifier>,
+        network: Arc<dyn Network<SignedDagMessage>>,
+        output: mpsc::UnboundedSender<OrderedBatch>,
+    ) -> Self {
+        Self {
+            id,
+            validators: verifier.validator_count(),
+            config: DagConfig::default(),
+            signer,
+            verifier,
+            network,
+            mempool: Arc::new(Mempool::default()),
+            output,
+            batches: HashMap::new(),
+            own_batches: Vec::new(),
+            round: 0,
+            header_due: false,
+            header: None,
+            votes: BTreeMap::new(),
+            voted: HashSet::new(),
+            pending_headers: Vec::new(),
+            pending_certificates: Vec::new(),
+            certificates: HashMap::new(),
+            dag: BTreeMap::new(),
+            last_committed: None,
+            ordered: HashSet::new(),
+            delivered: HashSet::new(),
+        }
+    }
+
+    pub fn with_config(mut self, config: DagConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// 本验证者 worker 的交易池
+    pub fn mempool(&self) -> Arc<Mempool> {
+        self.mempool.clone()
+    }
+
+    /// 可容忍的拜占庭验证者数 f（n >= 3f + 1）
+    pub fn fault_tolerance(&self) -> usize {
+        self.validators.saturating_sub(1) / 3
+    }
+
+    /// 法定人数 n - f（n = 3f + 1 时为 2f + 1）
+    pub fn quorum(&self) -> usize {
+        self.validators - self.fault_tolerance()
+    }
+
+    /// 偶数轮的 leader，奇数轮没有 leader
+    pub fn leader(&self, round: Round) -> Option<ValidatorId> {
+        (round % 2 == 0).then(|| ((round / 2) % self.validators.max(1) as u64) as ValidatorId)
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+
+    /// 运行直到网络关闭
+    pub async fn run(mut self) {
+        info!(
+            "🕸️ Validator {} joined DAG consensus ({} validators, quorum {})",
+            self.id,
+            self.validators,
+            self.quorum()
+        );
+        let network = self.network.clone();
+        let mempool = self.mempool.clone();
+        let mut ticker =
+            tokio::time::interval(Duration::from_millis(self.config.header_delay_ms.max(1)));
+
+        loop {
+            let new_transaction = mempool.notified();
+            if mempool.len() >= self.config.batch_size {
+                self.seal_batch().await;
+            }
+            self.try_propose().await;
+
+            tokio::select! {
+                message = network.receive() => match message {
+                    Some(message) => self.handle(message).await,
+                    None => break,
+                },
+                _ = ticker.tick() => {
+                    self.seal_batch().await;
+                    self.header_due = true;
+                }
+                _ = new_transaction => {}
+            }
+        }
+        info!("Validator {} left DAG consensus", self.id);
+    }
+
+    /// worker：打包交易池中的交易并广播
+    async fn seal_batch(&mut self) {
+        let transactions = self.mempool.take(self.config.batch_size);
+        if transactions.is_empty() {
+            return;
+        }
+        let digest = match BatchDigest::of(&transactions) {
+            Ok(digest) => digest,
+            Err(e) => {
+                error!("❌ Validator {} failed to seal a batch: {}", self.id, e);
+                return;
+            }
+        };
+        debug!(
+            "Validator {} sealed batch {} with {} transactions",
+            self.id,
+            digest,
+            transactions.len()
+        );
+        self.batches.insert(digest.clone(), transactions.clone());
+        self.own_batches.push(digest);
+        self.broadcast(DagMessage::Batch(transactions)).await;
+    }
+
+    /// primary：上一轮凑齐 2f+1 个证书后提出新头
+    async fn try_propose(&mut self) {
+        let latest = self
+            .dag
+            .iter()
+            .rev()
+            .find(|(_, certificates)| certificates.len() >= self.quorum())
+            .map(|(round, _)| round + 1)
+            .unwrap_or(0);
+        let round = self.round.max(latest);
+        let parents: Vec<String> = match round.checked_sub(1) {
+            None => Vec::new(),
+            Some(previous) => match self.dag.get(&previous) {
+                Some(certificates) if certificates.len() >= self.quorum() => {
+                    certificates.values().cloned().collect()
+                }
+                _ => return,
+            },
+        };
+        if self.own_batches.is_empty() && !self.header_due {
+            return;
+        }
+
+        // 上一个头未能成证时，其批次放进新头重新提出
+        if let Some(previous) = self.header.take() {
+            let mut batches = previous.batches;
+            batches.append(&mut self.own_batches);
+            self.own_batches = batches;
+        }
+        let header = Header {
+            author: self.id,
+            round,
+            batches: std::mem::take(&mut self.own_batches),
+            parents,
+        };
+        debug!(
+            "Validator {} proposing header at round {} with {} batches",
+            self.id,
+            round,
+            header.batches.len()
+        );
+        self.header = Some(header.clone());
+        self.votes.clear();
+        self.header_due = false;
+        self.round = round + 1;
+        self.broadcast(DagMessage::Header(header)).await;
+    }
+
+    async fn handle(&mut self, signed: SignedDagMessage) {
+        let valid = signed
+            .message
+            .signing_bytes()
+            .is_ok_and(|bytes| self.verifier.verify(signed.from, &bytes, &signed.signature));
+        if !valid {
+            warn!(
+                "Validator {} dropped DAG message with invalid signature from {}",
+                self.id, signed.from
+            );
+            return;
+        }
+
+        match signed.message {
+            DagMessage::Batch(transactions) => {
+                let Ok(digest) = BatchDigest::of(&transactions) else {
+                    return;
+                };
+                self.batches.insert(digest, transactions);
+                self.retry_pending_headers().await;
+                self.try_order();
+            }
+            DagMessage::Header(header) => {
+                if header.author == signed.from {
+                    self.on_header(header).await;
+                }
+            }
+            DagMessage::Vote(vote) => self.on_vote(signed.from, vote, signed.signature).await,
+            DagMessage::Certificate(certificate) => {
+                if certificate.verify(self.verifier.as_ref(), self.quorum()) {
+                    self.on_certificate(certificate).await;
+                }
+            }
+        }
+    }
+
+    async fn on_header(&mut self, header: Header) {
+        if self.voted.contains(&(header.author, header.round)) {
+            return;
+        }
+        if !self.can_vote(&header) {
+            if self.pending_headers.len() < MAX_PENDING {
+                self.pending_headers.push(header);
+            }
+            return;
+        }
+
+        let Ok(vote) = header.vote() else {
+            return;
+        };
+        self.voted.insert((header.author, header.round));
+        let Some(signed) = self.sign(DagMessage::Vote(vote)) else {
+            return;
+        };
+        if let Err(e) = self.network.send(header.author, signed).await {
+            warn!("Validator {} failed to send vote: {}", self.id, e);
+        }
+    }
+
+    /// 已保存头引用的全部批次，且父证书是上一轮至少 2f+1 个不同作者的证书
+    fn can_vote(&self, header: &Header) -> bool {
+        if !header
+            .batches
+            .iter()
+            .all(|digest| self.batches.contains_key(digest))
+        {
+            return false;
+        }
+        self.valid_parents(header)
+    }
+
+    fn valid_parents(&self, header: &Header) -> bool {
+        let Some(previous) = header.round.checked_sub(1) else {
+            return header.parents.is_empty();
+        };
+        let mut authors = HashSet::new();
+        for parent in &header.parents {
+            match self.certificates.get(parent) {
+                Some(certificate) if certificate.round() == previous => {
+                    authors.insert(certificate.author());
+                }
+                _ => return false,
+            }
+        }
+        authors.len() >= self.quorum()
+    }
+
+    async fn retry_pending_headers(&mut self) {
+        for header in std::mem::take(&mut self.pending_headers) {
+            self.on_header(header).await;
+        }
+    }
+
+    async fn on_vote(&mut self, from: ValidatorId, vote: HeaderVote, signature: Vec<u8>) {
+        let Some(header) = &self.header else {
+            return;
+        };
+        if !header.vote().is_ok_and(|expected| expected == vote) {
+            return;
+        }
+        self.votes.insert(from, signature);
+        if self.votes.len() < self.quorum() {
+            return;
+        }
+
+        let certificate = Certificate {
+            header: header.clone(),
+            signatures: std::mem::take(&mut self.votes).into_iter().collect(),
+        };
+        debug!(
+            "Validator {} formed certificate for round {}",
+            self.id,
+            certificate.round()
+        );
+        self.header = None;
+        self.broadcast(DagMessage::Certificate(certificate)).await;
+    }
+
+    async fn on_certificate(&mut self, certificate: Certificate) {
+        if !self.insert_certificate(certificate) {
+            return;
+        }
+        // 新证书可能补齐了缓存证书的父证书
+        loop {
+            let mut progressed = false;
+            for certificate in std::mem::take(&mut self.pending_certificates) {
+                progressed |= self.insert_certificate(certificate);
+            }
+            if !progressed {
+                break;
+            }
+        }
+        self.retry_pending_headers().await;
+        self.try_order();
+    }
+
+    /// 父证书齐全时加入 DAG，否则缓存；返回是否加入
+    fn insert_certificate(&mut self, certificate: Certificate) -> bool {
+        let Ok(digest) = certificate.digest() else {
+            return false;
+        };
+        if self.certificates.contains_key(&digest) {
+            return false;
+        }
+        if !self.valid_parents(&certificate.header) {
+            if self.pending_certificates.len() < MAX_PENDING {
+                self.pending_certificates.push(certificate);
+            }
+            return false;
+        }
+
+        let slot = self
+            .dag
+            .entry(certificate.round())
+            .or_default()
+            .entry(certificate.author());
+        if let std::collections::btree_map::Entry::Vacant(slot) = slot {
+            slot.insert(digest.clone());
+            self.certificates.insert(digest, certificate);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn leader_certificate(&self, round: Round) -> Option<String> {
+        let leader = self.leader(round)?;
+        self.dag.get(&round)?.get(&leader).cloned()
+    }
+
+    /// 从上次提交之后的 leader 轮开始，提交下一轮有 f+1 个证书支持的 leader
+    fn try_order(&mut self) {
+        let mut round = self.last_committed.map_or(0, |committed| committed + 2);
+        while let Some(next) = self.dag.get(&(round + 1)) {
+            if let Some(leader) = self.leader_certificate(round) {
+                let support = next
+                    .values()
+                    .filter(|digest| self.certificates[*digest].header.parents.contains(&leader))
+                    .count();
+                if support > self.fault_tolerance() && !self.commit_leader(round, leader) {
+                    // 缺少批次内容，等批次到达后重试
+                    return;
+                }
+            }
+            round += 2;
+        }
+    }
+
+    fn commit_leader(&mut self, round: Round, leader: String) -> bool {
+        // 补提交与当前 leader 有路径相连的更早 leader
+        let floor = self.last_committed.map_or(0, |committed| committed + 2);
+        let mut chain = vec![(round, leader.clone())];
+        let mut current = leader;
+        let earlier: Vec<Round> = (floor..round).step_by(2).collect();
+        for previous in earlier.into_iter().rev() {
+            if let Some(candidate) = self.leader_certificate(previous) {
+                if self.linked(&current, &candidate) {
+                    chain.push((previous, candidate.clone()));
+                    current = candidate;
+                }
+            }
+        }
+        chain.reverse();
+
+        let mut seen = self.ordered.clone();
+        let mut sub_dags = Vec::new();
+        for (leader_round, leader) in chain {
+            let history = self.causal_history(&leader, &mut seen);
+            let available = history.iter().all(|digest| {
+                self.certificates[digest]
+                    .header
+                    .batches
+                    .iter()
+                    .all(|batch| self.batches.contains_key(batch))
+            });
+            if !available {
+                return false;
+            }
+            sub_dags.push((leader_round, history));
+        }
+
+        for (leader_round, history) in sub_dags {
+            info!(
+                "📦 Validator {} committed leader of round {} ({} certificates)",
+                self.id,
+                leader_round,
+                history.len()
+            );
+            for digest in history {
+                let header = &self.certificates[&digest].header;
+                for batch in &header.batches {
+                    if !self.delivered.insert(batch.clone()) {
+                        continue;
+                    }
+                    let _ = self.output.send(OrderedBatch {
+                        leader_round,
+                        author: header.author,
+                        digest: batch.clone(),
+                        transactions: self.batches[batch].clone(),
+                    });
+                }
+                self.ordered.insert(digest);
+            }
+            self.last_committed = Some(leader_round);
+        }
+        true
+    }
+
+    /// `from` 是否经由父证书到达 `to`
+    fn linked(&self, from: &str, to: &str) -> bool {
+        let target_round = self.certificates[to].round();
+        let mut stack = vec![from.to_string()];
+        let mut visited = HashSet::new();
+        while let Some(digest) = stack.pop() {
+            if digest == to {
+                return true;
+            }
+            let certificate = &self.certificates[&digest];
+            if certificate.round() <= target_round || !visited.insert(digest.clone()) {
+                continue;
+            }
+            stack.extend(certificate.header.parents.iter().cloned());
+        }
+        false
+    }
+
+    /// leader 尚未排序的因果历史，按 (轮次, 作者) 排序
+    fn causal_history(&self, leader: &str, seen: &mut HashSet<String>) -> Vec<String> {
+        let mut history = Vec::new();
+        let mut stack = vec![leader.to_string()];
+        while let Some(digest) = stack.pop() {
+            if !seen.insert(digest.clone()) {
+                continue;
+            }
+            stack.extend(self.certificates[&digest].header.parents.iter().cloned());
+            history.push(digest);
+        }
+        history.sort_by_key(|digest| {
+            let certificate = &self.certificates[digest];
+            (certificate.round(), certificate.author())
+        });
+        history
+    }
+
+    fn sign(&self, message: DagMessage) -> Option<SignedDagMessage> {
+        let bytes = match message.signing_bytes() {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                error!(
+                    "❌ Validator {} failed to encode DAG message: {}",
+                    self.id, e
+                );
+                return None;
+            }
+        };
+        Some(SignedDagMessage {
+            from: self.id,
+            signature: self.signer.sign(&bytes),
+            message,
+        })
+    }
+
+    async fn broadcast(&self, message: DagMessage) {
+        let Some(signed) = self.sign(message) else {
+            return;
+        };
+        if let Err(e) = self.network.broadcast(signed).await {
+            warn!("Validator {} failed to broadcast: {}", self.id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{Ed25519VoteSigner, Ed25519VoteVerifier};
+    use crate::network::{InMemoryNetwork, NetworkConditions};
+    use crate::ConsensusManager;
+    use dubhe_scheduler::{ParallelScheduler, SchedulerConfig, StrategyType};
+    use ed25519_dalek::SigningKey;
+
+    fn transaction(index: usize) -> Transaction {
+        Transaction {
+            hash: format!("0x{:02x}", index),
+            from: format!("0xsender{}", index),
+            to: Some("0xcontract".to_string()),
+            data: vec![index as u8],
+            gas_limit: 21_000,
+            gas_price: 1,
+            nonce: 0,
+            read_set: vec![],
+            write_set: vec![format!("0xslot{}", index)],
+            access_list: None,
+            max_priority_fee: None,
+        }
+    }
+
+    /// 启动 4 个验证者，`crashed` 中的验证者从不运行；交易轮流提交给在线验证者
+    fn start(
+        crashed: &[ValidatorId],
+        transactions: usize,
+    ) -> (
+        Vec<mpsc::UnboundedReceiver<OrderedBatch>>,
+        Vec<JoinHandle<()>>,
+    ) {
+        let keys: Vec<SigningKey> = (0..4u8)
+            .map(|i| SigningKey::from_bytes(&[i + 1; 32]))
+            .collect();
+        let verifier = Arc::new(Ed25519VoteVerifier::new(
+            keys.iter().map(SigningKey::verifying_key).collect(),
+        ));
+        let endpoints = InMemoryNetwork::build::<SignedDagMessage>(
+            4,
+            NetworkConditions {
+                latency: Duration::from_millis(2),
+                ..Default::default()
+            },
+        );
+
+        let mut outputs = Vec::new();
+        let mut mempools = Vec::new();
+        let mut handles = Vec::new();
+        for (id, (key, endpoint)) in keys.into_iter().zip(endpoints).enumerate() {
+            if crashed.contains(&id) {
+                continue;
+            }
+            let (sender, receiver) = mpsc::unbounded_channel();
+            let consensus = DagConsensus::new(
+                id,
+                Arc::new(Ed25519VoteSigner::new(key)),
+                verifier.clone(),
+                Arc::new(endpoint),
+                sender,
+            )
+            .with_config(DagConfig {
+                batch_size: 3,
+                header_delay_ms: 20,
+            });
+            mempools.push(consensus.mempool());
+            outputs.push(receiver);
+            handles.push(consensus.spawn());
+        }
+        for index in 0..transactions {
+            mempools[index % mempools.len()].submit(transaction(index));
+        }
+        (outputs, handles)
+    }
+
+    /// 收集每个验证者输出的交易序列，直到都包含 `transactions` 笔交易
+    async fn collect(
+        outputs: &mut [mpsc::UnboundedReceiver<OrderedBatch>],
+        transactions: usize,
+    ) -> Vec<Vec<String>> {
+        let mut sequences = Vec::new();
+        for output in outputs.iter_mut() {
+            let mut sequence = Vec::new();
+            while sequence.len() < transactions {
+                let batch = tokio::time::timeout(Duration::from_secs(20), output.recv())
+                    .await
+                    .expect("validator did not order all transactions in time")
+                    .expect("validator stopped");
+                assert_eq!(batch.digest, BatchDigest::of(&batch.transactions).unwrap());
+                sequence.extend(batch.transactions.into_iter().map(|tx| tx.hash));
+            }
+            sequences.push(sequence);
+        }
+        sequences
+    }
+
+    #[tokio::test]
+    async fn test_honest_nodes_output_same_sequence() {
+        let (mut outputs, handles) = start(&[], 20);
+        let sequences = collect(&mut outputs, 20).await;
+
+        for sequence in &sequences {
+            assert_eq!(sequence, &sequences[0]);
+        }
+        let mut hashes = sequences[0].clone();
+        hashes.sort();
+        hashes.dedup();
+        assert_eq!(hashes.len(), 20);
+
+        handles.iter().for_each(JoinHandle::abort);
+    }
+
+    #[tokio::test]
+    async fn test_orders_with_one_crashed_authority() {
+        // 验证者 1 宕机：它担任 leader 的轮次没有 leader 证书，由后续 leader 补提交
+        let (mut outputs, handles) = start(&[1], 12);
+        let sequences = collect(&mut outputs, 12).await;
+
+        assert_eq!(sequences.len(), 3);
+        for sequence in &sequences {
+            assert_eq!(sequence, &sequences[0]);
+        }
+
+        handles.iter().for_each(JoinHandle::abort);
+    }
+
+    #[tokio::test]
+    async fn test_consensus_manager_forwards_to_scheduler() -> Result<()> {
+        let scheduler = Arc::new(ParallelScheduler::new(
+            StrategyType::Sequential,
+            SchedulerConfig::default(),
+        )?);
+        let manager = Arc::new(ConsensusManager::new()?.with_scheduler(scheduler.clone()));
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let forwarder = manager.clone().forward(receiver);
+
+        let transactions: Vec<Transaction> = (0..4).map(transaction).collect();
+        sender.send(OrderedBatch {
+            leader_round: 0,
+            author: 0,
+            digest: BatchDigest::of(&transactions)?,
+            transactions,
+        })?;
+        drop(sender);
+        forwarder.await?;
+
+        assert_eq!(manager.delivered_batches(), 1);
+        assert_eq!(scheduler.get_status().await.total_processed, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_header_digest_covers_batches() -> Result<()> {
+        let transactions: Vec<Transaction> = (0..2).map(transaction).collect();
+        let batch = BatchDigest::of(&transactions)?;
+        assert_eq!(batch, BatchDigest::of(&transactions)?);
+
+        let header = Header {
+            author: 0,
+            round: 0,
+            batches: vec![batch.clone()],
+            parents: vec![],
+        };
+        assert_ne!(header.digest()?, batch.0);
+
+        let other = Header {
+            batches: vec![BatchDigest::of(&transactions[..1])?],
+            ..header.clone()
+        };
+        assert_ne!(other.digest()?, header.digest()?);
+        assert_eq!(header.vote()?.digest, header.digest()?);
+        Ok(())
     }
 }
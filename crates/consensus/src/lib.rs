@@ -3,20 +3,79 @@
 //! 内部轻量 BFT / DAG 共识 (可选)
 
 pub mod bft;
+pub mod crypto;
 pub mod dag;
+pub mod network;
 pub mod types;
 
+pub use bft::*;
+pub use crypto::*;
+pub use dag::*;
+pub use network::*;
 pub use types::*;
 
 use anyhow::Result;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{error, warn};
 
-/// 共识管理器
+use dubhe_scheduler::{ParallelScheduler, SubmissionLane};
+
+/// 共识管理器：把共识排序后的批次按顺序经共识通道交给调度器执行
 pub struct ConsensusManager {
-    // TODO: 实现共识机制
+    scheduler: Option<Arc<ParallelScheduler>>,
+    delivered: AtomicU64,
 }
 
 impl ConsensusManager {
     pub fn new() -> Result<Self> {
-        Ok(Self {})
+        Ok(Self {
+            scheduler: None,
+            delivered: AtomicU64::new(0),
+        })
+    }
+
+    pub fn with_scheduler(mut self, scheduler: Arc<ParallelScheduler>) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    /// 已交付的批次数
+    pub fn delivered_batches(&self) -> u64 {
+        self.delivered.load(Ordering::Relaxed)
+    }
+
+    /// 交付一个已排序的批次
+    pub async fn deliver(&self, batch: OrderedBatch) -> Result<()> {
+        self.delivered.fetch_add(1, Ordering::Relaxed);
+        match &self.scheduler {
+            Some(scheduler) => {
+                scheduler
+                    .submit_to_lane(batch.transactions, SubmissionLane::Consensus)
+                    .await?;
+            }
+            None => warn!(
+                "No scheduler attached, dropping ordered batch {}",
+                batch.digest
+            ),
+        }
+        Ok(())
+    }
+
+    /// 持续交付排序输出，直到发送端关闭
+    pub fn forward(
+        self: Arc<Self>,
+        mut batches: mpsc::UnboundedReceiver<OrderedBatch>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            while let Some(batch) = batches.recv().await {
+                let digest = batch.digest.clone();
+                if let Err(e) = self.deliver(batch).await {
+                    error!("❌ Failed to execute ordered batch {}: {}", digest, e);
+                }
+            }
+        })
     }
 }
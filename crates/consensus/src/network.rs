@@ -0,0 +1,142 @@
+//! 共识网络抽象
+//!
+//! 共识只依赖 send / broadcast / receive 三个操作；进程内实现可注入延迟与丢包，
+//! 用于在单进程中驱动多验证者测试
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+
+use crate::types::{SignedMessage, ValidatorId};
+
+/// 验证者之间的消息通道，消息类型默认为 BFT 消息
+#[async_trait]
+pub trait Network<M = SignedMessage>: Send + Sync
+where
+    M: Send + 'static,
+{
+    /// 发送给单个验证者
+    async fn send(&self, to: ValidatorId, message: M) -> Result<()>;
+
+    /// 广播给所有验证者（包括自己）
+    async fn broadcast(&self, message: M) -> Result<()>;
+
+    /// 接收下一条消息，网络关闭时返回 None
+    async fn receive(&self) -> Option<M>;
+}
+
+/// 进程内网络的传输条件
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NetworkConditions {
+    /// 每条消息的投递延迟
+    pub latency: Duration,
+    /// 丢包概率（0.0 ~ 1.0）
+    pub drop_rate: f64,
+    /// 丢包随机数种子，相同种子得到相同的丢包序列
+    pub seed: u64,
+}
+
+/// 进程内网络：每个验证者一个收件箱
+pub struct InMemoryNetwork;
+
+impl InMemoryNetwork {
+    /// 为 `validators` 个验证者创建互相连通的端点
+    pub fn build<M: Send>(
+        validators: usize,
+        conditions: NetworkConditions,
+    ) -> Vec<InMemoryEndpoint<M>> {
+        let (senders, receivers): (Vec<_>, Vec<_>) =
+            (0..validators).map(|_| mpsc::unbounded_channel()).unzip();
+        let peers = Arc::new(senders);
+        // xorshift 状态不能为 0
+        let rng = Arc::new(AtomicU64::new(conditions.seed | 1));
+
+        receivers
+            .into_iter()
+            .enumerate()
+            .map(|(id, inbox)| InMemoryEndpoint {
+                id,
+                peers: peers.clone(),
+                inbox: Mutex::new(inbox),
+                conditions,
+                rng: rng.clone(),
+            })
+            .collect()
+    }
+}
+
+/// 单个验证者的网络端点
+pub struct InMemoryEndpoint<M = SignedMessage> {
+    id: ValidatorId,
+    peers: Arc<Vec<mpsc::UnboundedSender<M>>>,
+    inbox: Mutex<mpsc::UnboundedReceiver<M>>,
+    conditions: NetworkConditions,
+    rng: Arc<AtomicU64>,
+}
+
+impl<M: Send + 'static> InMemoryEndpoint<M> {
+    pub fn id(&self) -> ValidatorId {
+        self.id
+    }
+
+    fn dropped(&self) -> bool {
+        if self.conditions.drop_rate <= 0.0 {
+            return false;
+        }
+        let previous = self
+            .rng
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |mut x| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                Some(x)
+            })
+            .unwrap_or_default();
+        let sample = (previous >> 11) as f64 / (1u64 << 53) as f64;
+        sample < self.conditions.drop_rate
+    }
+
+    fn deliver(&self, to: ValidatorId, message: M) -> Result<()> {
+        let peer = self
+            .peers
+            .get(to)
+            .ok_or_else(|| anyhow::anyhow!("Unknown validator {}", to))?
+            .clone();
+        if self.dropped() {
+            return Ok(());
+        }
+
+        let latency = self.conditions.latency;
+        if latency.is_zero() {
+            // 接收方已停止时消息直接丢弃，与真实网络一致
+            let _ = peer.send(message);
+        } else {
+            tokio::spawn(async move {
+                tokio::time::sleep(latency).await;
+                let _ = peer.send(message);
+            });
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<M: Clone + Send + 'static> Network<M> for InMemoryEndpoint<M> {
+    async fn send(&self, to: ValidatorId, message: M) -> Result<()> {
+        self.deliver(to, message)
+    }
+
+    async fn broadcast(&self, message: M) -> Result<()> {
+        for to in 0..self.peers.len() {
+            self.deliver(to, message.clone())?;
+        }
+        Ok(())
+    }
+
+    async fn receive(&self) -> Option<M> {
+        self.inbox.lock().await.recv().await
+    }
+}
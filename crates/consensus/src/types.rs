@@ -1,7 +1,299 @@
 //! 共识类型定义
+//!
+//! 区块哈希与消息签名都经由 `dubhe_security` 的规范化摘要计算，各自使用独立的摘要域
 
-#[derive(Debug, Clone)]
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use dubhe_scheduler::Transaction;
+use dubhe_security::{canonical_digest, canonical_payload, CanonicalPayload};
+
+use crate::crypto::VoteVerifier;
+
+/// 验证者在验证者集合中的序号
+pub type ValidatorId = usize;
+
+/// 共识提交的区块：一批排好序的交易
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Block {
     pub hash: String,
     pub height: u64,
+    pub parent: String,
+    /// 提议者与提议所在视图不计入哈希，换视图重新提议时哈希不变
+    pub proposer: ValidatorId,
+    pub view: u64,
+    pub transactions: Vec<Transaction>,
+}
+
+impl Block {
+    pub fn new(
+        height: u64,
+        parent: String,
+        proposer: ValidatorId,
+        view: u64,
+        transactions: Vec<Transaction>,
+    ) -> Result<Self> {
+        Ok(Self {
+            hash: Self::compute_hash(height, &parent, &transactions)?,
+            height,
+            parent,
+            proposer,
+            view,
+            transactions,
+        })
+    }
+
+    pub fn compute_hash(height: u64, parent: &str, transactions: &[Transaction]) -> Result<String> {
+        let contents = BlockContents {
+            height,
+            parent,
+            transactions,
+        };
+        Ok(contents.canonical_digest()?.to_string())
+    }
+
+    /// 哈希与内容一致
+    pub fn is_well_formed(&self) -> bool {
+        Self::compute_hash(self.height, &self.parent, &self.transactions)
+            .is_ok_and(|hash| hash == self.hash)
+    }
+}
+
+/// 参与区块哈希的内容
+#[derive(Serialize)]
+struct BlockContents<'a> {
+    height: u64,
+    parent: &'a str,
+    transactions: &'a [Transaction],
+}
+
+canonical_payload!(BlockContents<'_>, "dubhe.consensus.block");
+
+/// 投票阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Phase {
+    Prevote,
+    Precommit,
+}
+
+/// 对某视图、某高度区块的投票
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Vote {
+    pub phase: Phase,
+    pub view: u64,
+    pub height: u64,
+    pub digest: String,
+}
+
+/// 法定人数证书：至少 2f+1 个验证者对同一投票的签名
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuorumCertificate {
+    pub vote: Vote,
+    pub signatures: Vec<(ValidatorId, Vec<u8>)>,
+}
+
+impl QuorumCertificate {
+    /// 签名全部有效且来自至少 `quorum` 个不同验证者
+    pub fn verify(&self, verifier: &dyn VoteVerifier, quorum: usize) -> bool {
+        let Ok(bytes) = ConsensusMessage::Vote(self.vote.clone()).signing_bytes() else {
+            return false;
+        };
+        signed_by_quorum(verifier, &bytes, &self.signatures, quorum)
+    }
+}
+
+fn signed_by_quorum(
+    verifier: &dyn VoteVerifier,
+    bytes: &[u8],
+    signatures: &[(ValidatorId, Vec<u8>)],
+    quorum: usize,
+) -> bool {
+    let mut signers = HashSet::new();
+    for (validator, signature) in signatures {
+        if !verifier.verify(*validator, bytes, signature) {
+            return false;
+        }
+        signers.insert(*validator);
+    }
+    signers.len() >= quorum
+}
+
+/// 共识消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ConsensusMessage {
+    /// leader 的提议；重新提议已锁定区块时附带其 prevote 证书
+    Proposal {
+        block: Block,
+        justify: Option<QuorumCertificate>,
+    },
+    Vote(Vote),
+    /// 请求切换到 `view`，附带发送方锁定的区块及其 prevote 证书
+    ViewChange {
+        view: u64,
+        height: u64,
+        high_qc: Option<QuorumCertificate>,
+        locked_block: Option<Block>,
+    },
+    /// 已提交区块及其 precommit 证书，供落后的验证者追赶
+    Commit {
+        block: Block,
+        certificate: QuorumCertificate,
+    },
+}
+
+canonical_payload!(ConsensusMessage, "dubhe.consensus.bft_message");
+
+impl ConsensusMessage {
+    /// 被签名的字节：消息的规范化摘要
+    pub fn signing_bytes(&self) -> Result<Vec<u8>> {
+        Ok(self.canonical_digest()?.to_string().into_bytes())
+    }
+
+    pub fn height(&self) -> u64 {
+        match self {
+            Self::Proposal { block, .. } | Self::Commit { block, .. } => block.height,
+            Self::Vote(vote) => vote.height,
+            Self::ViewChange { height, .. } => *height,
+        }
+    }
+}
+
+/// 带发送方签名的消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedMessage {
+    pub from: ValidatorId,
+    pub message: ConsensusMessage,
+    pub signature: Vec<u8>,
+}
+
+/// DAG 轮次
+pub type Round = u64;
+
+/// 交易批次摘要域
+pub const BATCH_DOMAIN: &str = "dubhe.consensus.batch";
+
+/// 交易批次摘要
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BatchDigest(pub String);
+
+impl BatchDigest {
+    pub fn of(transactions: &[Transaction]) -> Result<Self> {
+        Ok(Self(
+            canonical_digest(BATCH_DOMAIN, transactions)?.to_string(),
+        ))
+    }
+}
+
+impl std::fmt::Display for BatchDigest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// DAG 顶点头：作者在某一轮引用的批次与上一轮的证书
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Header {
+    pub author: ValidatorId,
+    pub round: Round,
+    pub batches: Vec<BatchDigest>,
+    /// 上一轮证书的摘要，第 0 轮为空
+    pub parents: Vec<String>,
+}
+
+canonical_payload!(Header, "dubhe.consensus.dag_header");
+
+impl Header {
+    pub fn digest(&self) -> Result<String> {
+        Ok(self.canonical_digest()?.to_string())
+    }
+
+    /// 对本头的投票
+    pub fn vote(&self) -> Result<HeaderVote> {
+        Ok(HeaderVote {
+            digest: self.digest()?,
+            round: self.round,
+            author: self.author,
+        })
+    }
+}
+
+/// 对头的投票：投票者已保存头引用的全部批次与父证书
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct HeaderVote {
+    pub digest: String,
+    pub round: Round,
+    pub author: ValidatorId,
+}
+
+/// 可用性证书：2f+1 个验证者对同一个头的投票，保证其批次至少由 f+1 个诚实验证者保存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Certificate {
+    pub header: Header,
+    pub signatures: Vec<(ValidatorId, Vec<u8>)>,
+}
+
+impl Certificate {
+    pub fn digest(&self) -> Result<String> {
+        self.header.digest()
+    }
+
+    pub fn round(&self) -> Round {
+        self.header.round
+    }
+
+    pub fn author(&self) -> ValidatorId {
+        self.header.author
+    }
+
+    /// 签名全部有效且来自至少 `quorum` 个不同验证者
+    pub fn verify(&self, verifier: &dyn VoteVerifier, quorum: usize) -> bool {
+        let bytes = self
+            .header
+            .vote()
+            .and_then(|vote| DagMessage::Vote(vote).signing_bytes());
+        let Ok(bytes) = bytes else {
+            return false;
+        };
+        signed_by_quorum(verifier, &bytes, &self.signatures, quorum)
+    }
+}
+
+/// DAG 共识消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DagMessage {
+    /// worker 打包的交易批次
+    Batch(Vec<Transaction>),
+    Header(Header),
+    /// 发给头作者的投票
+    Vote(HeaderVote),
+    Certificate(Certificate),
+}
+
+canonical_payload!(DagMessage, "dubhe.consensus.dag_message");
+
+impl DagMessage {
+    /// 被签名的字节：消息的规范化摘要
+    pub fn signing_bytes(&self) -> Result<Vec<u8>> {
+        Ok(self.canonical_digest()?.to_string().into_bytes())
+    }
+}
+
+/// 带发送方签名的 DAG 消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedDagMessage {
+    pub from: ValidatorId,
+    pub message: DagMessage,
+    pub signature: Vec<u8>,
+}
+
+/// DAG 排序输出的批次
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderedBatch {
+    /// 提交该批次的 leader 所在轮次
+    pub leader_round: Round,
+    /// 批次所属顶点的作者
+    pub author: ValidatorId,
+    pub digest: BatchDigest,
+    pub transactions: Vec<Transaction>,
 }
@@ -0,0 +1,104 @@
+//! Guest ABI
+//!
+//! 编译产物与 VM 运行时之间的系统调用约定：
+//! `a7` 为调用号，`a0`-`a5` 为参数，返回值写回 `a0`
+//!
+//! 编译器按此约定生成代码，vm-runtime 负责实现
+
+/// 编译产物所依赖的 ABI 版本，参与编译缓存键计算
+pub const GUEST_ABI_VERSION: u32 = 2;
+
+/// 退出：`a0` 为退出码，0 表示成功
+pub const SYS_EXIT: u64 = 93;
+
+/// 返回调用输入的字节数
+pub const SYS_INPUT_LENGTH: u64 = 1000;
+
+/// `(buf, len, offset)`：从输入 `offset` 处复制至多 `len` 字节到 `buf`，返回复制的字节数
+pub const SYS_LOAD_INPUT: u64 = 1001;
+
+/// `(buf, len)`：将 `buf` 处 `len` 字节追加到执行输出，返回 0
+pub const SYS_WRITE_OUTPUT: u64 = 1002;
+
+/// `(key, key_len, buf, buf_len, offset)`：读取状态区域
+///
+/// 从区域 `offset` 处复制至多 `buf_len` 字节到 `buf`，返回区域总长度；
+/// 区域不存在时返回 `STATE_NOT_FOUND`
+pub const SYS_STATE_READ: u64 = 1010;
+
+/// `(key, key_len, data, data_len)`：覆盖可写状态区域的内容，返回 0
+pub const SYS_STATE_WRITE: u64 = 1011;
+
+/// 状态区域不存在
+pub const STATE_NOT_FOUND: u64 = u64::MAX;
+
+/// `(key, key_len, buf, buf_len, offset)`：读取宿主存储
+///
+/// 语义与 `SYS_STATE_READ` 相同，键不存在时返回 `STATE_NOT_FOUND`
+pub const SYS_STORAGE_READ: u64 = 1020;
+
+/// `(key, key_len, data, data_len)`：写入宿主存储，返回 0
+pub const SYS_STORAGE_WRITE: u64 = 1021;
+
+/// `(topic, topic_len, data, data_len)`：发出事件，返回 0
+pub const SYS_EMIT_EVENT: u64 = 1030;
+
+/// `(data, len, out)`：将 `data` 的 Keccak-256 摘要写入 `out`（32 字节），返回 0
+pub const SYS_KECCAK256: u64 = 1040;
+
+/// `(data, len, out)`：将 `data` 的 Blake2b-256 摘要写入 `out`（32 字节），返回 0
+pub const SYS_BLAKE2B256: u64 = 1041;
+
+/// `(code, code_len)`：以调用输入为 calldata，用宿主的 EVM 解释器执行 `code`
+///
+/// 返回数据追加到执行输出；正常返回 0，`REVERT` 返回 1。
+/// 存储读写经由宿主函数，未注入时仅在本次执行内可见
+pub const SYS_EVM_INTERPRET: u64 = 1050;
+
+/// `(code, code_len)`：以调用输入为入口调用，用宿主的 WASM 解释器执行 `code`
+///
+/// 序号对应按名称排序的第几个导出函数，参数为各 `i32`/`i64` 的小端编码；
+/// 返回值按同样编码追加到执行输出，正常返回 0，guest 陷入时执行以错误结束
+pub const SYS_WASM_INTERPRET: u64 = 1051;
+
+/// WASM 模块可导入的宿主函数所在模块名
+pub const WASM_HOST_MODULE: &str = "env";
+
+/// WASM 模块可导入的宿主函数：
+///
+/// - `storage_read(key, key_len, buf, buf_len) -> i64`：返回值长度，键不存在时返回 -1
+/// - `storage_write(key, key_len, data, data_len) -> i32`：返回 0
+/// - `emit_event(topic, topic_len, data, data_len) -> i32`：返回 0
+///
+/// 指针均为模块导出的 `memory` 中的偏移
+pub const WASM_HOST_IMPORTS: &[&str] = &["storage_read", "storage_write", "emit_event"];
+
+/// 入口调用约定：调用输入的前 4 字节为入口函数序号（小端 u32），
+/// 其后按参数顺序拼接各参数的 BCS 编码
+///
+/// 分派桩把输入复制到 `sp + ENTRY_INPUT_OFFSET`，参数从
+/// `sp + ENTRY_INPUT_OFFSET + ENTRY_INDEX_SIZE` 开始
+pub const ENTRY_INDEX_SIZE: usize = 4;
+
+/// 分派桩中输入缓冲区相对栈顶的偏移
+pub const ENTRY_INPUT_OFFSET: i32 = 16;
+
+/// 入口调用输入（序号 + 参数）的最大字节数
+pub const MAX_ENTRY_INPUT: usize = 1024;
+
+/// 序号不存在或输入不足 4 字节时分派桩的退出码
+pub const EXIT_UNKNOWN_ENTRY: u64 = 2;
+
+/// 以导入函数调用宿主的后端（PolkaVM）使用的导入名与调用号
+pub const HOST_IMPORTS: &[(&str, u64)] = &[
+    ("input_length", SYS_INPUT_LENGTH),
+    ("load_input", SYS_LOAD_INPUT),
+    ("write_output", SYS_WRITE_OUTPUT),
+    ("state_read", SYS_STATE_READ),
+    ("state_write", SYS_STATE_WRITE),
+    ("storage_read", SYS_STORAGE_READ),
+    ("storage_write", SYS_STORAGE_WRITE),
+    ("emit_event", SYS_EMIT_EVENT),
+    ("keccak256", SYS_KECCAK256),
+    ("blake2b256", SYS_BLAKE2B256),
+];
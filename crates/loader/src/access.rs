@@ -0,0 +1,104 @@
+//! 存储访问分析
+//!
+//! 从源字节码中提取合约可能读写的存储位置，供调度器估算交易读写集合
+
+use serde::{Deserialize, Serialize};
+
+/// 键在运行时计算（如 mapping 元素），无法静态确定
+pub const DYNAMIC_SLOT: &str = "*";
+
+const OP_SLOAD: u8 = 0x54;
+const OP_SSTORE: u8 = 0x55;
+const OP_PUSH0: u8 = 0x5f;
+const OP_PUSH1: u8 = 0x60;
+const OP_PUSH32: u8 = 0x7f;
+
+/// 合约的存储访问摘要（合约整体，不区分入口）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageAccess {
+    pub reads: Vec<String>,
+    pub writes: Vec<String>,
+}
+
+impl StorageAccess {
+    /// 是否包含无法静态确定的存储键
+    pub fn has_dynamic(&self) -> bool {
+        self.reads
+            .iter()
+            .chain(&self.writes)
+            .any(|slot| slot == DYNAMIC_SLOT)
+    }
+}
+
+/// 扫描 EVM 字节码中的 SLOAD/SSTORE
+///
+/// 紧跟在 PUSH 之后的访问视为常量槽位，其余记为 `DYNAMIC_SLOT`
+pub fn evm_storage_access(bytecode: &[u8]) -> StorageAccess {
+    let mut access = StorageAccess::default();
+    let mut last_push: Option<&[u8]> = None;
+    let mut pc = 0;
+
+    while pc < bytecode.len() {
+        let op = bytecode[pc];
+        pc += 1;
+
+        match op {
+            OP_PUSH0 => {
+                last_push = Some(&[]);
+                continue;
+            }
+            OP_PUSH1..=OP_PUSH32 => {
+                let end = (pc + (op - OP_PUSH1 + 1) as usize).min(bytecode.len());
+                last_push = Some(&bytecode[pc..end]);
+                pc = end;
+                continue;
+            }
+            OP_SLOAD | OP_SSTORE => {
+                let slot = last_push.map(slot_key).unwrap_or_else(|| DYNAMIC_SLOT.to_string());
+                let set = if op == OP_SLOAD {
+                    &mut access.reads
+                } else {
+                    &mut access.writes
+                };
+                if !set.contains(&slot) {
+                    set.push(slot);
+                }
+            }
+            _ => {}
+        }
+        last_push = None;
+    }
+
+    access.reads.sort();
+    access.writes.sort();
+    access
+}
+
+/// 槽位的规范化十六进制表示（去掉前导零）
+fn slot_key(bytes: &[u8]) -> String {
+    let digits = hex::encode(bytes);
+    let trimmed = digits.trim_start_matches('0');
+    format!("0x{}", if trimmed.is_empty() { "0" } else { trimmed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evm_storage_access() {
+        let bytecode = [
+            0x60, 0x00, 0x54, // PUSH1 0 SLOAD
+            0x60, 0x01, 0x01, // PUSH1 1 ADD
+            0x60, 0x00, 0x55, // PUSH1 0 SSTORE
+            0x61, 0x00, 0x05, 0x54, // PUSH2 0x0005 SLOAD
+            0x60, 0x00, 0x20, 0x55, // PUSH1 0 SHA3 SSTORE（mapping 写入）
+            0x7f, 0x54, // 截断的 PUSH32，立即数中的 SLOAD 不计入
+        ];
+
+        let access = evm_storage_access(&bytecode);
+        assert_eq!(access.reads, vec!["0x0", "0x5"]);
+        assert_eq!(access.writes, vec!["*", "0x0"]);
+        assert!(access.has_dynamic());
+    }
+}
@@ -0,0 +1,99 @@
+//! 离线产物包
+//!
+//! 无法访问链 RPC、也不在本地编译的节点（如隔离网络中的验证者）通过签名的产物包
+//! 获取编译结果：联网节点用密钥库中的密钥导出 [`ArtifactBundle`]，离线节点用
+//! 受信任的公钥验证签名与代码哈希后写入编译缓存
+//!
+//! 签名覆盖清单的序列化字节本身，验证时不依赖重新序列化的结果
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+use crate::error::LoaderError;
+use crate::types::{ArtifactOrigin, CompiledContract};
+use dubhe_security::{verify_signature, KeyHandle};
+
+/// 产物包文件格式版本
+pub const ARTIFACT_BUNDLE_VERSION: u32 = 1;
+
+/// 产物包清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactManifest {
+    pub address: String,
+    /// 源字节码哈希与编译器标识
+    pub origin: ArtifactOrigin,
+    pub compiler_version: String,
+    /// 影响编译产物的编译器版本与配置
+    pub compiler_fingerprint: String,
+    /// 产物代码的 SHA-256
+    pub code_hash: String,
+    /// 产物本身，含入口函数与导出签名
+    pub contract: CompiledContract,
+    pub exported_at: u64,
+}
+
+/// 签名的产物包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactBundle {
+    pub format_version: u32,
+    /// bincode 编码的 [`ArtifactManifest`]
+    pub manifest: Vec<u8>,
+    /// 签名者的 Ed25519 公钥
+    pub signer: [u8; 32],
+    pub signature: Vec<u8>,
+}
+
+impl ArtifactBundle {
+    /// 用密钥库中的密钥签名清单
+    pub fn sign(manifest: &ArtifactManifest, key: &KeyHandle) -> Result<Self> {
+        let manifest = bincode::serialize(manifest)?;
+        Ok(Self {
+            format_version: ARTIFACT_BUNDLE_VERSION,
+            signature: key.sign(&manifest)?.to_vec(),
+            signer: key.public_key()?,
+            manifest,
+        })
+    }
+
+    /// 验证签名者、签名与代码哈希，返回清单
+    pub fn verify(&self, trusted_signers: &[[u8; 32]]) -> Result<ArtifactManifest, LoaderError> {
+        if self.format_version != ARTIFACT_BUNDLE_VERSION {
+            return Err(LoaderError::InvalidArtifact(format!(
+                "format version {} does not match {}",
+                self.format_version, ARTIFACT_BUNDLE_VERSION
+            )));
+        }
+        if !trusted_signers.contains(&self.signer) {
+            return Err(LoaderError::UntrustedArtifactSigner(hex::encode(
+                self.signer,
+            )));
+        }
+        if !verify_signature(&self.signer, &self.manifest, &self.signature) {
+            return Err(LoaderError::InvalidArtifact(
+                "signature verification failed".to_string(),
+            ));
+        }
+
+        let manifest: ArtifactManifest = bincode::deserialize(&self.manifest)?;
+        let code_hash = hex::encode(Sha256::digest(&manifest.contract.risc_v_code));
+        if code_hash != manifest.code_hash {
+            return Err(LoaderError::InvalidArtifact(format!(
+                "code hash {} does not match manifest {}",
+                code_hash, manifest.code_hash
+            )));
+        }
+        Ok(manifest)
+    }
+
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, bincode::serialize(self)?)?;
+        Ok(())
+    }
+
+    pub fn read_from<P: AsRef<Path>>(path: P) -> Result<Self, LoaderError> {
+        let data = std::fs::read(path)?;
+        Ok(bincode::deserialize(&data)?)
+    }
+}
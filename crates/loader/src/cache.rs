@@ -1,87 +1,352 @@
 //! 编译缓存模块
 //!
 //! LRU + 持久层，首编译后落盘
-
-use anyhow::Result;
-use rocksdb::{Options, DB};
+//!
+//! 所有条目（内存与磁盘）共用一个 LRU 索引，超过条目数或总字节数上限时
+//! 淘汰最久未使用的条目，同时从内存和持久层删除
+//!
+//! 持久层按内容寻址：产物以 `blob:<序列化产物的 SHA-256>` 为键保存，缓存键只记录内容 ID；
+//! 产物前带有条目头（格式版本、源字节码哈希、编译器标识、校验和），读取时校验，
+//! 不通过的条目视为未命中并从持久层删除
+//!
+//! 各缓存键的使用统计以 `usage:<缓存键>` 保存，随缓存条目一起删除，
+//! 供重启后的后台重编译排序
+
+use anyhow::{anyhow, bail, Result};
+use rocksdb::checkpoint::Checkpoint;
+use rocksdb::{IteratorMode, Options, WriteBatch, DB};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::num::NonZeroUsize;
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::Mutex;
 use tracing::{debug, info, warn};
 
-use crate::types::CompiledContract;
+use crate::types::{ArtifactOrigin, ArtifactVersion, CompiledContract, ARTIFACT_FORMAT_VERSION};
+
+/// 保存编译版本的保留键
+const ARTIFACT_VERSION_KEY: &[u8] = b"__dubhe_artifact_version__";
+
+/// 内容寻址产物的键前缀
+const BLOB_PREFIX: &str = "blob:";
+
+/// 使用统计的键前缀
+const USAGE_PREFIX: &str = "usage:";
+
+/// 缓存配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// 最多缓存的合约数
+    pub max_entries: usize,
+    /// 所有缓存产物序列化后的总字节上限
+    pub max_total_bytes: u64,
+    /// 内存层最多保留的条目数
+    pub memory_entries: usize,
+    /// 持久层完整性扫描间隔（秒），见 [`CompilationCache::spawn_scrubber`]
+    #[serde(default)]
+    pub scrub_interval_secs: Option<u64>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 10_000,
+            max_total_bytes: 1024 * 1024 * 1024, // 1GB
+            memory_entries: 1000,
+            scrub_interval_secs: None,
+        }
+    }
+}
+
+/// 持久化条目头
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct EntryHeader {
+    format_version: u32,
+    origin: ArtifactOrigin,
+    /// 产物字节的 SHA-256，同时是内容 ID
+    checksum: String,
+}
+
+/// LRU 索引与内存层，同一把锁保护，保证并发 get/put 下顺序一致
+struct CacheState {
+    /// 缓存键 → 持久化字节数，按最近使用排序
+    index: lru::LruCache<String, u64>,
+    memory: lru::LruCache<String, CompiledContract>,
+    /// 缓存键 → 内容 ID
+    contents: HashMap<String, String>,
+    /// 内容 ID → 引用它的缓存键数
+    refs: HashMap<String, usize>,
+    total_bytes: u64,
+}
 
 /// 编译缓存
 pub struct CompilationCache {
+    config: CacheConfig,
     disk_cache: Arc<DB>,
-    memory_cache: Arc<RwLock<lru::LruCache<String, CompiledContract>>>,
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    rejected: AtomicU64,
+    corrupted: AtomicU64,
+}
+
+/// 一次完整性扫描的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScrubReport {
+    pub scanned: usize,
+    /// 校验失败并被删除的内容 ID
+    pub corrupt: Vec<String>,
+    /// 随之移除的缓存键数
+    pub removed_keys: usize,
 }
 
 impl CompilationCache {
-    pub fn new<P: AsRef<Path>>(cache_dir: P) -> Result<Self> {
+    pub fn new<P: AsRef<Path>>(cache_dir: P, config: CacheConfig) -> Result<Self> {
         let mut opts = Options::default();
         opts.create_if_missing(true);
         opts.set_compression_type(rocksdb::DBCompressionType::Lz4);
 
         let disk_cache = Arc::new(DB::open(&opts, cache_dir)?);
-        let memory_cache = Arc::new(RwLock::new(lru::LruCache::new(
-            NonZeroUsize::new(1000).unwrap(),
-        ))); // 1000 entries
 
-        info!("Compilation cache initialized");
+        // 从持久层重建索引
+        let mut blobs = HashMap::new();
+        let mut entries = Vec::new();
+        let mut usage = Vec::new();
+        for item in disk_cache.iterator(IteratorMode::Start) {
+            let (key, value) = item?;
+            if key.as_ref() == ARTIFACT_VERSION_KEY {
+                continue;
+            }
+            let key = String::from_utf8_lossy(&key).into_owned();
+            if let Some(content) = key.strip_prefix(BLOB_PREFIX) {
+                blobs.insert(content.to_string(), value.len() as u64);
+            } else if let Some(entry) = key.strip_prefix(USAGE_PREFIX) {
+                usage.push(entry.to_string());
+            } else {
+                entries.push((key, String::from_utf8_lossy(&value).into_owned()));
+            }
+        }
+
+        let mut index = lru::LruCache::unbounded();
+        let mut contents = HashMap::new();
+        let mut refs: HashMap<String, usize> = HashMap::new();
+        let mut total_bytes = 0;
+        let mut stale = WriteBatch::default();
+        for (key, content) in entries {
+            // 指向不存在产物的键（旧格式条目或写入中断）直接清理
+            let Some(&size) = blobs.get(&content) else {
+                stale.delete(key.as_bytes());
+                continue;
+            };
+            total_bytes += size;
+            index.put(key.clone(), size);
+            *refs.entry(content.clone()).or_default() += 1;
+            contents.insert(key, content);
+        }
+        for content in blobs.keys().filter(|content| !refs.contains_key(*content)) {
+            stale.delete(blob_key(content));
+        }
+        for key in usage.iter().filter(|key| !contents.contains_key(*key)) {
+            stale.delete(usage_key(key));
+        }
+        if !stale.is_empty() {
+            debug!("Removing {} stale cache records", stale.len());
+            disk_cache.write(stale)?;
+        }
+
+        let memory = lru::LruCache::new(
+            NonZeroUsize::new(config.memory_entries.max(1)).unwrap(),
+        );
+
+        info!(
+            "Compilation cache initialized ({} entries, {} bytes on disk)",
+            index.len(),
+            total_bytes
+        );
 
-        Ok(Self {
+        let cache = Self {
+            config,
             disk_cache,
-            memory_cache,
-        })
+            state: Mutex::new(CacheState {
+                index,
+                memory,
+                contents,
+                refs,
+                total_bytes,
+            }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            corrupted: AtomicU64::new(0),
+        };
+
+        // 限制调小后，启动时立即收敛
+        {
+            let mut state = cache.state.try_lock().expect("cache state is not shared yet");
+            cache.evict_over_limit(&mut state)?;
+        }
+
+        Ok(cache)
+    }
+
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
     }
 
     /// 从缓存获取编译结果
     pub async fn get(&self, key: &str) -> Result<Option<CompiledContract>> {
-        // 首先检查内存缓存
-        {
-            let mut cache = self.memory_cache.write().await;
-            if let Some(contract) = cache.get(key) {
+        // 首先检查内存缓存，同时刷新 LRU 顺序
+        let content = {
+            let mut state = self.state.lock().await;
+            if state.index.get(key).is_none() {
+                debug!("Cache miss: {}", key);
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                return Ok(None);
+            }
+            if let Some(contract) = state.memory.get(key) {
                 debug!("Cache hit (memory): {}", key);
+                self.hits.fetch_add(1, Ordering::Relaxed);
                 return Ok(Some(contract.clone()));
             }
-        }
+            state.contents.get(key).cloned()
+        };
+
+        // 内存缓存未命中，检查磁盘缓存并校验
+        let verified = match &content {
+            Some(content) => match self.disk_cache.get(blob_key(content))? {
+                Some(data) => decode_entry(content, &data),
+                None => Err(anyhow!("artifact {} is missing", content)),
+            },
+            None => Err(anyhow!("no content recorded")),
+        };
 
-        // 内存缓存未命中，检查磁盘缓存
-        match self.disk_cache.get(key.as_bytes())? {
-            Some(data) => {
+        match verified {
+            Ok(contract) => {
                 debug!("Cache hit (disk): {}", key);
-                let contract: CompiledContract = bincode::deserialize(&data)?;
 
-                // 将结果放入内存缓存
+                // 读盘期间可能已被淘汰，只有仍在索引中才放入内存
                 {
-                    let mut cache = self.memory_cache.write().await;
-                    cache.put(key.to_string(), contract.clone());
+                    let mut state = self.state.lock().await;
+                    if state.index.contains(key) {
+                        state.memory.put(key.to_string(), contract.clone());
+                    }
                 }
 
+                self.hits.fetch_add(1, Ordering::Relaxed);
                 Ok(Some(contract))
             }
-            None => {
-                debug!("Cache miss: {}", key);
+            Err(e) => {
+                warn!("Discarding corrupt cache entry {}: {}", key, e);
+                self.corrupted.fetch_add(1, Ordering::Relaxed);
+                self.misses.fetch_add(1, Ordering::Relaxed);
+
+                let mut state = self.state.lock().await;
+                match content {
+                    Some(content) => {
+                        self.discard_content(&mut state, &content)?;
+                    }
+                    None => {
+                        self.drop_entry(&mut state, key)?;
+                    }
+                }
                 Ok(None)
             }
         }
     }
 
-    /// 将编译结果存入缓存
+    /// 从持久层读取产物及其来源（校验后返回，不影响 LRU 顺序与命中统计）
+    pub async fn get_with_origin(
+        &self,
+        key: &str,
+    ) -> Result<Option<(CompiledContract, ArtifactOrigin)>> {
+        let content = self.state.lock().await.contents.get(key).cloned();
+        let Some(content) = content else {
+            return Ok(None);
+        };
+        match self.disk_cache.get(blob_key(&content))? {
+            Some(data) => decode_entry_with_origin(&content, &data).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// 缓存中是否有该键（不影响 LRU 顺序与命中统计）
+    pub async fn contains(&self, key: &str) -> bool {
+        self.state.lock().await.index.contains(key)
+    }
+
+    /// 指定前缀下最近使用的缓存键
+    pub async fn latest_with_prefix(&self, prefix: &str) -> Option<String> {
+        let state = self.state.lock().await;
+        state
+            .index
+            .iter()
+            .map(|(key, _)| key)
+            .find(|key| key.starts_with(prefix))
+            .cloned()
+    }
+
+    /// 将编译结果存入缓存（不记录产物来源）
     pub async fn put(&self, key: &str, contract: &CompiledContract) -> Result<()> {
-        // 序列化合约
-        let data = bincode::serialize(contract)?;
+        self.put_with_origin(key, contract, &ArtifactOrigin::default())
+            .await
+    }
 
-        // 存储到磁盘
-        self.disk_cache.put(key.as_bytes(), &data)?;
+    /// 将编译结果连同来源信息存入缓存
+    ///
+    /// 单个产物超过总字节上限时不缓存，直接返回
+    pub async fn put_with_origin(
+        &self,
+        key: &str,
+        contract: &CompiledContract,
+        origin: &ArtifactOrigin,
+    ) -> Result<()> {
+        let (content, data) = encode_entry(contract, origin)?;
+        let size = data.len() as u64;
+
+        if size > self.config.max_total_bytes {
+            warn!(
+                "Compiled artifact {} ({} bytes) exceeds cache limit of {} bytes, not caching",
+                key, size, self.config.max_total_bytes
+            );
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        }
 
-        // 存储到内存缓存
-        {
-            let mut cache = self.memory_cache.write().await;
-            cache.put(key.to_string(), contract.clone());
+        let mut state = self.state.lock().await;
+
+        // 同一键改指向新内容时先释放旧内容
+        if state.contents.get(key).is_some_and(|old| *old != content) {
+            self.drop_entry(&mut state, key)?;
+        }
+        let is_new_ref = !state.contents.contains_key(key);
+
+        // 产物与键在同一批次写入
+        let mut batch = WriteBatch::default();
+        if !state.refs.contains_key(&content) {
+            batch.put(blob_key(&content), &data);
+        }
+        batch.put(key.as_bytes(), content.as_bytes());
+        self.disk_cache.write(batch)?;
+
+        // 更新索引与内存缓存
+        if let Some(old_size) = state.index.put(key.to_string(), size) {
+            state.total_bytes -= old_size;
+        }
+        state.total_bytes += size;
+        if is_new_ref {
+            *state.refs.entry(content.clone()).or_default() += 1;
+            state.contents.insert(key.to_string(), content);
         }
+        state.memory.put(key.to_string(), contract.clone());
+
+        self.evict_over_limit(&mut state)?;
 
         debug!("Cache stored: {}", key);
         Ok(())
@@ -89,48 +354,130 @@ impl CompilationCache {
 
     /// 清除缓存中的特定项
     pub async fn remove(&self, key: &str) -> Result<()> {
-        // 从磁盘删除
-        self.disk_cache.delete(key.as_bytes())?;
-
-        // 从内存删除
-        {
-            let mut cache = self.memory_cache.write().await;
-            cache.pop(key);
-        }
+        let mut state = self.state.lock().await;
+        self.drop_entry(&mut state, key)?;
 
         debug!("Cache removed: {}", key);
         Ok(())
     }
 
+    /// 删除指定前缀的所有缓存项，返回删除数量
+    pub async fn remove_prefix(&self, prefix: &str) -> Result<usize> {
+        let mut state = self.state.lock().await;
+        let keys: Vec<String> = state
+            .index
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in &keys {
+            self.drop_entry(&mut state, key)?;
+        }
+
+        debug!("Cache removed {} entries with prefix {}", keys.len(), prefix);
+        Ok(keys.len())
+    }
+
     /// 清空所有缓存
     pub async fn clear(&self) -> Result<()> {
-        // 清空内存缓存
-        {
-            let mut cache = self.memory_cache.write().await;
-            cache.clear();
+        warn!("Clearing all cache data");
+
+        let mut state = self.state.lock().await;
+        while let Some((key, _)) = state.index.peek_lru().map(|(k, v)| (k.clone(), *v)) {
+            self.drop_entry(&mut state, &key)?;
         }
+        state.memory.clear();
+        state.total_bytes = 0;
 
-        // 清空磁盘缓存（重新创建数据库）
-        // 注意：这是一个简化的实现，生产环境可能需要更精细的控制
-        warn!("Clearing all cache data");
+        Ok(())
+    }
+
+    /// 将持久层缓冲写入磁盘
+    pub fn flush(&self) -> Result<()> {
+        self.disk_cache.flush()?;
+        debug!("Cache flushed to disk");
+        Ok(())
+    }
 
+    /// 在 `path`（不能已存在）创建持久层的一致性检查点
+    pub fn checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        Checkpoint::new(&self.disk_cache)?.create_checkpoint(path)?;
+        Ok(())
+    }
+
+    /// 读取缓存产物对应的编译版本，旧格式的记录视为未知版本
+    pub fn artifact_version(&self) -> Result<Option<ArtifactVersion>> {
+        match self.disk_cache.get(ARTIFACT_VERSION_KEY)? {
+            Some(data) => match bincode::deserialize(&data) {
+                Ok(version) => Ok(Some(version)),
+                Err(e) => {
+                    warn!("Ignoring unreadable artifact version record: {}", e);
+                    Ok(None)
+                }
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// 记录缓存产物对应的编译版本
+    pub fn set_artifact_version(&self, version: &ArtifactVersion) -> Result<()> {
+        self.disk_cache
+            .put(ARTIFACT_VERSION_KEY, bincode::serialize(version)?)?;
+        Ok(())
+    }
+
+    /// 读取持久化的使用统计，无法解码的记录跳过
+    pub(crate) fn usage_records<T: DeserializeOwned>(&self) -> Result<Vec<(String, T)>> {
+        let mut records = Vec::new();
+        for item in self.disk_cache.prefix_iterator(USAGE_PREFIX.as_bytes()) {
+            let (key, value) = item?;
+            let Some(key) = std::str::from_utf8(&key)
+                .ok()
+                .and_then(|key| key.strip_prefix(USAGE_PREFIX))
+            else {
+                break;
+            };
+            match bincode::deserialize(&value) {
+                Ok(record) => records.push((key.to_string(), record)),
+                Err(e) => warn!("Skipping unreadable usage record for {}: {}", key, e),
+            }
+        }
+        Ok(records)
+    }
+
+    /// 批量写入使用统计
+    pub(crate) fn put_usage_records<T: Serialize>(&self, records: &[(String, T)]) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        for (key, record) in records {
+            batch.put(usage_key(key), bincode::serialize(record)?);
+        }
+        self.disk_cache.write(batch)?;
         Ok(())
     }
 
     /// 获取缓存统计信息
     pub async fn stats(&self) -> CacheStats {
-        let memory_cache = self.memory_cache.read().await;
-        let memory_size = memory_cache.len();
-        let memory_capacity = memory_cache.cap().get();
-
-        // 估算磁盘缓存大小（这里简化处理）
-        let disk_size = 0; // TODO: 实现磁盘缓存大小统计
+        let state = self.state.lock().await;
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
 
         CacheStats {
-            memory_entries: memory_size,
-            memory_capacity,
-            disk_entries: disk_size,
-            hit_rate: 0.0, // TODO: 实现命中率统计
+            memory_entries: state.memory.len(),
+            memory_capacity: state.memory.cap().get(),
+            disk_entries: state.index.len() as u64,
+            total_bytes: state.total_bytes,
+            hits,
+            misses,
+            evictions: self.evictions.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            corrupted: self.corrupted.load(Ordering::Relaxed),
+            hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                hits as f64 / lookups as f64
+            },
         }
     }
 
@@ -139,7 +486,7 @@ impl CompilationCache {
         info!("Warming up cache with {} keys", keys.len());
 
         for key in keys {
-            if let Ok(Some(contract)) = self.get(&key).await {
+            if let Ok(Some(_)) = self.get(&key).await {
                 // get 方法已经会将数据加载到内存缓存
                 debug!("Warmed up: {}", key);
             }
@@ -147,6 +494,187 @@ impl CompilationCache {
 
         Ok(())
     }
+
+    /// 校验持久层中的全部产物，删除校验失败的产物及引用它们的缓存键
+    pub async fn scrub(&self) -> Result<ScrubReport> {
+        let disk_cache = self.disk_cache.clone();
+        let (scanned, corrupt) = tokio::task::spawn_blocking(move || -> Result<_> {
+            let mut scanned = 0;
+            let mut corrupt = Vec::new();
+            for item in disk_cache.prefix_iterator(BLOB_PREFIX.as_bytes()) {
+                let (key, value) = item?;
+                let Some(content) = std::str::from_utf8(&key)
+                    .ok()
+                    .and_then(|key| key.strip_prefix(BLOB_PREFIX))
+                else {
+                    break;
+                };
+                scanned += 1;
+                if let Err(e) = decode_entry(content, &value) {
+                    warn!("Cache scrub found corrupt artifact {}: {}", content, e);
+                    corrupt.push(content.to_string());
+                }
+            }
+            Ok((scanned, corrupt))
+        })
+        .await??;
+
+        let mut removed_keys = 0;
+        if !corrupt.is_empty() {
+            let mut state = self.state.lock().await;
+            for content in &corrupt {
+                removed_keys += self.discard_content(&mut state, content)?;
+            }
+            self.corrupted
+                .fetch_add(corrupt.len() as u64, Ordering::Relaxed);
+        }
+
+        Ok(ScrubReport {
+            scanned,
+            corrupt,
+            removed_keys,
+        })
+    }
+
+    /// 启动周期性完整性扫描（可选）
+    pub fn spawn_scrubber(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let cache = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                match cache.scrub().await {
+                    Ok(report) if report.corrupt.is_empty() => {
+                        debug!("Cache scrub checked {} artifacts", report.scanned);
+                    }
+                    Ok(report) => warn!(
+                        "Cache scrub removed {} corrupt artifacts ({} keys) out of {}",
+                        report.corrupt.len(),
+                        report.removed_keys,
+                        report.scanned
+                    ),
+                    Err(e) => warn!("Cache scrub failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// 从索引、内存和持久层移除一个键；内容不再被引用时一并删除产物
+    fn drop_entry(&self, state: &mut CacheState, key: &str) -> Result<Option<u64>> {
+        let mut batch = WriteBatch::default();
+        batch.delete(key.as_bytes());
+        batch.delete(usage_key(key));
+
+        if let Some(content) = state.contents.remove(key) {
+            let remaining = state.refs.get_mut(&content).map(|count| {
+                *count -= 1;
+                *count
+            });
+            if remaining == Some(0) {
+                state.refs.remove(&content);
+                batch.delete(blob_key(&content));
+            }
+        }
+        self.disk_cache.write(batch)?;
+
+        state.memory.pop(key);
+        let size = state.index.pop(key);
+        if let Some(size) = size {
+            state.total_bytes -= size;
+        }
+        Ok(size)
+    }
+
+    /// 删除损坏的产物及所有引用它的键，返回移除的键数
+    fn discard_content(&self, state: &mut CacheState, content: &str) -> Result<usize> {
+        let keys: Vec<String> = state
+            .contents
+            .iter()
+            .filter(|(_, c)| c.as_str() == content)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &keys {
+            self.drop_entry(state, key)?;
+        }
+        // 没有键引用时产物可能仍残留在磁盘上
+        self.disk_cache.delete(blob_key(content))?;
+        Ok(keys.len())
+    }
+
+    /// 淘汰最久未使用的条目直到满足上限
+    fn evict_over_limit(&self, state: &mut CacheState) -> Result<()> {
+        while state.index.len() > self.config.max_entries
+            || state.total_bytes > self.config.max_total_bytes
+        {
+            let Some(key) = state.index.peek_lru().map(|(key, _)| key.clone()) else {
+                break;
+            };
+            let size = self.drop_entry(state, &key)?.unwrap_or_default();
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+            debug!("Cache evicted: {} ({} bytes)", key, size);
+        }
+        Ok(())
+    }
+}
+
+fn blob_key(content: &str) -> Vec<u8> {
+    format!("{}{}", BLOB_PREFIX, content).into_bytes()
+}
+
+fn usage_key(key: &str) -> Vec<u8> {
+    format!("{}{}", USAGE_PREFIX, key).into_bytes()
+}
+
+/// 持久化格式：`[u32 LE 头长度][bincode 条目头][bincode 产物]`，返回 (内容 ID, 字节)
+fn encode_entry(contract: &CompiledContract, origin: &ArtifactOrigin) -> Result<(String, Vec<u8>)> {
+    let payload = bincode::serialize(contract)?;
+    let checksum = hex::encode(Sha256::digest(&payload));
+    let header = bincode::serialize(&EntryHeader {
+        format_version: ARTIFACT_FORMAT_VERSION,
+        origin: origin.clone(),
+        checksum: checksum.clone(),
+    })?;
+
+    let mut data = Vec::with_capacity(4 + header.len() + payload.len());
+    data.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    data.extend_from_slice(&header);
+    data.extend_from_slice(&payload);
+    Ok((checksum, data))
+}
+
+/// 校验条目头与校验和后解出产物
+fn decode_entry(content: &str, data: &[u8]) -> Result<CompiledContract> {
+    decode_entry_with_origin(content, data).map(|(contract, _)| contract)
+}
+
+/// 同 [`decode_entry`]，并返回条目头中记录的来源
+fn decode_entry_with_origin(
+    content: &str,
+    data: &[u8],
+) -> Result<(CompiledContract, ArtifactOrigin)> {
+    let Some((len, rest)) = data.split_first_chunk::<4>() else {
+        bail!("truncated header");
+    };
+    let len = u32::from_le_bytes(*len) as usize;
+    if rest.len() < len {
+        bail!("truncated header");
+    }
+    let (header, payload) = rest.split_at(len);
+    let header: EntryHeader = bincode::deserialize(header)?;
+
+    if header.format_version != ARTIFACT_FORMAT_VERSION {
+        bail!(
+            "format version {} does not match {}",
+            header.format_version,
+            ARTIFACT_FORMAT_VERSION
+        );
+    }
+    let checksum = hex::encode(Sha256::digest(payload));
+    if checksum != header.checksum || checksum != content {
+        bail!("checksum mismatch");
+    }
+    Ok((bincode::deserialize(payload)?, header.origin))
 }
 
 /// 缓存统计信息
@@ -155,6 +683,14 @@ pub struct CacheStats {
     pub memory_entries: usize,
     pub memory_capacity: usize,
     pub disk_entries: u64,
+    pub total_bytes: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    /// 因超过总字节上限而未缓存的产物数
+    pub rejected: u64,
+    /// 校验失败而被丢弃的产物数
+    pub corrupted: u64,
     pub hit_rate: f64,
 }
 
@@ -163,15 +699,35 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    fn contract(address: &str, code_len: usize) -> CompiledContract {
+        CompiledContract {
+            original_address: address.to_string(),
+            source_type: dubhe_adapter::ContractType::EVM,
+            risc_v_code: vec![1; code_len],
+            artifact: crate::types::ArtifactKind::NativeRiscV,
+            entry_points: vec!["main".to_string()],
+            metadata: crate::types::ContractMetadata {
+                gas_metering: true,
+                memory_limit: 1024,
+                stack_limit: 512,
+                call_depth_limit: 64,
+                exports: std::collections::HashMap::new(),
+                storage_access: None,
+            },
+            compiled_at: 1234567890,
+        }
+    }
+
     #[tokio::test]
     async fn test_cache_operations() -> Result<()> {
         let temp_dir = tempdir()?;
-        let cache = CompilationCache::new(temp_dir.path())?;
+        let cache = CompilationCache::new(temp_dir.path(), CacheConfig::default())?;
 
         let contract = CompiledContract {
             original_address: "0x123".to_string(),
             source_type: dubhe_adapter::ContractType::EVM,
             risc_v_code: vec![1, 2, 3, 4],
+            artifact: crate::types::ArtifactKind::NativeRiscV,
             entry_points: vec!["main".to_string()],
             metadata: crate::types::ContractMetadata {
                 gas_metering: true,
@@ -179,6 +735,7 @@ mod tests {
                 stack_limit: 512,
                 call_depth_limit: 64,
                 exports: std::collections::HashMap::new(),
+                storage_access: None,
             },
             compiled_at: 1234567890,
         };
@@ -200,4 +757,211 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_lru_eviction_by_entries_and_bytes() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let entry_size = bincode::serialize(&contract("0x0", 100))?.len() as u64;
+        let cache = CompilationCache::new(
+            temp_dir.path(),
+            CacheConfig {
+                max_entries: 3,
+                max_total_bytes: entry_size * 10,
+                memory_entries: 2,
+                scrub_interval_secs: None,
+            },
+        )?;
+
+        for i in 0..3 {
+            cache.put(&format!("k{}", i), &contract("0x0", 100)).await?;
+        }
+        // 访问 k0，使 k1 成为最久未使用
+        assert!(cache.get("k0").await?.is_some());
+        cache.put("k3", &contract("0x0", 100)).await?;
+
+        assert!(cache.get("k1").await?.is_none());
+        assert!(cache.get("k0").await?.is_some());
+        // 已从持久层删除
+        assert!(cache.disk_cache.get(b"k1")?.is_none());
+
+        // 按字节淘汰：一个大产物挤出多个小产物
+        cache.put("big", &contract("0x0", entry_size as usize * 8)).await?;
+        let stats = cache.stats().await;
+        assert!(stats.total_bytes <= entry_size * 10);
+        assert!(stats.evictions >= 2);
+        assert!(stats.hits >= 2);
+        assert!(stats.misses >= 1);
+
+        // 超过总上限的产物不缓存
+        cache.put("huge", &contract("0x0", entry_size as usize * 20)).await?;
+        assert!(cache.get("huge").await?.is_none());
+        assert_eq!(cache.stats().await.rejected, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_index_rebuilt_from_disk() -> Result<()> {
+        let temp_dir = tempdir()?;
+        {
+            let cache = CompilationCache::new(temp_dir.path(), CacheConfig::default())?;
+            for i in 0..4 {
+                cache.put(&format!("k{}", i), &contract("0x0", 10)).await?;
+            }
+            cache.set_artifact_version(&ArtifactVersion::default())?;
+        }
+
+        // 以更小的上限重新打开，启动时即淘汰到上限内
+        let cache = CompilationCache::new(
+            temp_dir.path(),
+            CacheConfig {
+                max_entries: 2,
+                ..CacheConfig::default()
+            },
+        )?;
+        let stats = cache.stats().await;
+        assert_eq!(stats.disk_entries, 2);
+        assert_eq!(stats.evictions, 2);
+        assert!(cache.artifact_version()?.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_get_put() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let cache = Arc::new(CompilationCache::new(
+            temp_dir.path(),
+            CacheConfig {
+                max_entries: 8,
+                ..CacheConfig::default()
+            },
+        )?);
+
+        let mut handles = Vec::new();
+        for task in 0..8 {
+            let cache = cache.clone();
+            handles.push(tokio::spawn(async move {
+                for i in 0..20 {
+                    let key = format!("k{}", (task * 7 + i) % 16);
+                    cache.put(&key, &contract(&key, 16)).await?;
+                    cache.get(&key).await?;
+                }
+                Ok::<_, anyhow::Error>(())
+            }));
+        }
+        for handle in handles {
+            handle.await??;
+        }
+
+        let stats = cache.stats().await;
+        assert_eq!(stats.disk_entries, 8);
+        assert!(stats.memory_entries <= 8);
+
+        // 索引与持久层保持一致：8 个键各指向一个产物
+        let keys: Vec<Vec<u8>> = cache
+            .disk_cache
+            .iterator(IteratorMode::Start)
+            .filter_map(|item| item.ok())
+            .map(|(key, _)| key.to_vec())
+            .collect();
+        let blobs = keys
+            .iter()
+            .filter(|key| key.starts_with(BLOB_PREFIX.as_bytes()))
+            .count();
+        assert_eq!(keys.len() - blobs, 8);
+        assert_eq!(blobs, 8);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_entry_is_recompiled() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let meta = dubhe_adapter::ContractMeta {
+            address: "0xpkg".to_string(),
+            chain_type: dubhe_adapter::ChainType::Sui,
+            contract_type: dubhe_adapter::ContractType::Move,
+            bytecode: vec![1, 2, 3, 4],
+            abi: None,
+            source_code: None,
+            compiler_version: None,
+            created_at: 0,
+            creator: None,
+            abi_source: None,
+            modules: vec![],
+        };
+
+        let original = {
+            let loader = crate::CodeLoader::with_cache_dir(temp_dir.path())?;
+            loader.load_contract(&meta).await?
+        };
+
+        // 篡改持久层中的产物：条目头完好，产物字节被破坏
+        {
+            let db = DB::open_default(temp_dir.path())?;
+            let (key, value) = db
+                .prefix_iterator(BLOB_PREFIX.as_bytes())
+                .next()
+                .expect("artifact persisted")?;
+            let mut value = value.to_vec();
+            let last = value.len() - 1;
+            value[last] ^= 0xff;
+            db.put(&key, &value)?;
+        }
+
+        let loader = crate::CodeLoader::with_cache_dir(temp_dir.path())?;
+        let recompiled = loader.load_contract(&meta).await?;
+        assert_eq!(recompiled.risc_v_code, original.risc_v_code);
+
+        let cache = loader.cache();
+        let stats = cache.stats().await;
+        assert_eq!(stats.corrupted, 1);
+        assert_eq!(stats.hits, 0);
+        assert_eq!(stats.disk_entries, 1);
+
+        // 损坏的产物已被替换，新写入的产物能通过校验
+        let blobs: Vec<(Box<[u8]>, Box<[u8]>)> = cache
+            .disk_cache
+            .prefix_iterator(BLOB_PREFIX.as_bytes())
+            .filter_map(|item| item.ok())
+            .filter(|(key, _)| key.starts_with(BLOB_PREFIX.as_bytes()))
+            .collect();
+        assert_eq!(blobs.len(), 1);
+        let (key, value) = &blobs[0];
+        let content = std::str::from_utf8(key)?.strip_prefix(BLOB_PREFIX).unwrap();
+        assert!(decode_entry(content, value).is_ok());
+
+        loader.load_contract(&meta).await?;
+        assert_eq!(cache.stats().await.hits, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scrub_removes_corrupt_artifacts() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let cache = CompilationCache::new(temp_dir.path(), CacheConfig::default())?;
+
+        // 两个键共享同一产物
+        cache.put("a", &contract("0x1", 32)).await?;
+        cache.put("b", &contract("0x1", 32)).await?;
+        cache.put("c", &contract("0x2", 32)).await?;
+
+        let content = String::from_utf8(cache.disk_cache.get(b"a")?.unwrap())?;
+        cache.disk_cache.put(blob_key(&content), b"garbage")?;
+
+        let report = cache.scrub().await?;
+        assert_eq!(report.scanned, 2);
+        assert_eq!(report.corrupt, vec![content.clone()]);
+        assert_eq!(report.removed_keys, 2);
+
+        assert!(cache.get("a").await?.is_none());
+        assert!(cache.get("b").await?.is_none());
+        assert!(cache.get("c").await?.is_some());
+        assert!(cache.disk_cache.get(blob_key(&content))?.is_none());
+        assert_eq!(cache.stats().await.disk_entries, 1);
+
+        Ok(())
+    }
 }
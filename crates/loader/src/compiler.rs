@@ -6,7 +6,11 @@ use async_trait::async_trait;
 use anyhow::Result;
 use tracing::{info, warn};
 
+use crate::abi::{SYS_EVM_INTERPRET, SYS_EXIT};
+use crate::access;
+use crate::riscv;
 use crate::types::*;
+use crate::wasm_compiler::WasmCompiler;
 use dubhe_adapter::{ContractMeta, ContractType};
 
 /// 编译器 trait
@@ -30,6 +34,10 @@ impl DefaultCompiler {
     pub fn with_config(config: CompilationConfig) -> Self {
         Self { config }
     }
+
+    pub fn config(&self) -> &CompilationConfig {
+        &self.config
+    }
 }
 
 #[async_trait]
@@ -37,11 +45,25 @@ impl Compiler for DefaultCompiler {
     async fn compile(&self, meta: &ContractMeta) -> Result<CompiledContract> {
         info!("Compiling contract {} ({:?})", meta.address, meta.contract_type);
         
-        let risc_v_code = match meta.contract_type {
-            ContractType::EVM => self.compile_evm(&meta.bytecode).await?,
-            ContractType::Move => self.compile_move(&meta.bytecode).await?,
-            ContractType::BPF => self.compile_bpf(&meta.bytecode).await?,
-            ContractType::Script => self.compile_script(&meta.bytecode).await?,
+        let (risc_v_code, artifact) = match meta.contract_type {
+            ContractType::EVM => (
+                self.compile_evm(&meta.bytecode).await?,
+                ArtifactKind::InterpreterBundle,
+            ),
+            ContractType::Move => (
+                self.compile_move(&meta.bytecode).await?,
+                ArtifactKind::NativeRiscV,
+            ),
+            ContractType::BPF => (
+                self.compile_bpf(&meta.bytecode).await?,
+                ArtifactKind::NativeRiscV,
+            ),
+            ContractType::Script => (
+                self.compile_script(&meta.bytecode).await?,
+                ArtifactKind::NativeRiscV,
+            ),
+            // 需要校验与导出签名，交给专用编译器
+            ContractType::WASM => return WasmCompiler::default().compile(meta),
         };
 
         let metadata = ContractMetadata {
@@ -50,12 +72,17 @@ impl Compiler for DefaultCompiler {
             stack_limit: 1 * 1024 * 1024,   // 1MB  
             call_depth_limit: 1024,
             exports: std::collections::HashMap::new(), // TODO: 从 ABI 解析
+            storage_access: match meta.contract_type {
+                ContractType::EVM => Some(access::evm_storage_access(&meta.bytecode)),
+                _ => None,
+            },
         };
 
         Ok(CompiledContract {
             original_address: meta.address.clone(),
             source_type: meta.contract_type.clone(),
             risc_v_code,
+            artifact,
             entry_points: vec!["main".to_string()], // TODO: 从编译结果提取
             metadata,
             compiled_at: chrono::Utc::now().timestamp() as u64,
@@ -65,16 +92,15 @@ impl Compiler for DefaultCompiler {
 
 impl DefaultCompiler {
     /// 编译 EVM 字节码到 RISC-V
+    ///
+    /// 在真正的转译实现之前，产出解释器包：引导代码把紧随其后的原始字节码
+    /// 交给宿主的 EVM 解释器执行
     async fn compile_evm(&self, bytecode: &[u8]) -> Result<Vec<u8>> {
-        info!("Compiling EVM bytecode to RISC-V");
-        
-        // TODO: 实现 EVM → RISC-V 编译
-        // 可以基于 LLVM 管道或现有的转换工具
-        
-        warn!("EVM compilation not yet implemented, returning placeholder");
-        
-        // 返回一个简单的 RISC-V 程序作为占位符
-        Ok(self.generate_placeholder_riscv())
+        info!(
+            "Bundling {} bytes of EVM bytecode with interpreter bootstrap",
+            bytecode.len()
+        );
+        Ok(interpreter_bundle(bytecode))
     }
 
     /// 编译 Move 字节码到 RISC-V  
@@ -108,15 +134,35 @@ impl DefaultCompiler {
     }
 
     /// 生成占位符 RISC-V 代码
+    ///
+    /// 回显程序：调用输入原样作为输出返回
     fn generate_placeholder_riscv(&self) -> Vec<u8> {
-        // 一个简单的 RISC-V 程序：返回 0
-        vec![
-            0x93, 0x02, 0x00, 0x00, // addi t0, zero, 0
-            0x73, 0x00, 0x10, 0x00, // ebreak (exit)
-        ]
+        riscv::assemble(&riscv::echo_program())
     }
 }
 
+/// EVM 解释器包，见 [`bundle_for`]
+pub fn interpreter_bundle(bytecode: &[u8]) -> Vec<u8> {
+    bundle_for(SYS_EVM_INTERPRET, bytecode)
+}
+
+/// 解释器包：`auipc` 定位紧随引导代码的字节码，调用 `interpret` 后以其返回值退出
+pub fn bundle_for(interpret: u64, bytecode: &[u8]) -> Vec<u8> {
+    use riscv::*;
+
+    let mut tail = li(A1, bytecode.len() as i32);
+    tail.extend(syscall(interpret));
+    tail.extend(syscall(SYS_EXIT));
+    // auipc 与 addi 之后即为 tail，字节码紧随其后
+    let offset = ((2 + tail.len()) * 4) as i32;
+
+    let mut words = vec![auipc(A0, 0), addi(A0, A0, offset)];
+    words.extend(tail);
+    let mut image = assemble(&words);
+    image.extend_from_slice(bytecode);
+    image
+}
+
 /// EVM 特定编译器（可选的专用实现）
 pub struct EvmCompiler {
     // TODO: 添加 EVM 编译相关的配置和状态
@@ -165,4 +211,12 @@ mod tests {
         let riscv_code = compiler.generate_placeholder_riscv();
         assert!(!riscv_code.is_empty());
     }
+
+    #[test]
+    fn test_interpreter_bundle_layout() {
+        let bytecode = [0x60, 0x01, 0x00];
+        let bundle = interpreter_bundle(&bytecode);
+        assert!(bundle.ends_with(&bytecode));
+        assert_eq!((bundle.len() - bytecode.len()) % 4, 0);
+    }
 } 
\ No newline at end of file
@@ -1,25 +1,50 @@
 //! 动态库加载模块
 //!
 //! Rust libloading 插件安全封装
+//!
+//! 加载前校验插件导出的 ABI 版本，所有 `compile` 调用都在 `catch_unwind` 中执行，
+//! 插件 panic 只会让本次编译失败，不会展开穿过 FFI 边界
+//!
+//! 异步编译经 [`PluginManager::compile_async`] 轮询插件的编译任务，按
+//! [`CompilationConfig::compile_timeout`] 超时取消；插件可用 [`CompileJobs`] 在后台线程上实现任务接口
 
 use anyhow::Result;
+use dubhe_adapter::ContractType;
 use libloading::{Library, Symbol};
+use std::any::Any;
 use std::collections::HashMap;
-use std::sync::Arc;
-use tracing::{error, info, warn};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+use dubhe_security::{AuditEvent, AuditTrail};
+
+use crate::error::LoaderError;
+use crate::types::{
+    CompilationConfig, CompileJob, CompilePoll, Plugin, PluginCapabilities, PluginHandle,
+    PLUGIN_ABI_VERSION,
+};
+
+/// 轮询插件编译任务的间隔
+pub const COMPILE_POLL_INTERVAL: Duration = Duration::from_millis(10);
 
-use crate::types::{CompilationConfig, Plugin, PluginHandle};
+/// 插件导出的 ABI 版本符号
+pub const PLUGIN_ABI_SYMBOL: &[u8] = b"DUBHE_PLUGIN_ABI";
 
 /// 插件管理器
 pub struct PluginManager {
     plugins: HashMap<PluginHandle, LoadedPlugin>,
     next_handle: u64,
+    audit: Option<Arc<AuditTrail>>,
 }
 
 /// 已加载的插件
 struct LoadedPlugin {
+    // 进程内注册的插件没有动态库
     #[allow(dead_code)]
-    library: Library,
+    library: Option<Library>,
     plugin: Box<dyn Plugin>,
     path: String,
 }
@@ -29,9 +54,15 @@ impl PluginManager {
         Self {
             plugins: HashMap::new(),
             next_handle: 1,
+            audit: None,
         }
     }
 
+    /// 插件加载与卸载写入审计日志
+    pub fn set_audit_trail(&mut self, audit: Arc<AuditTrail>) {
+        self.audit = Some(audit);
+    }
+
     /// 加载插件
     pub fn load_plugin(&mut self, path: &str) -> Result<PluginHandle> {
         info!("Loading plugin from: {}", path);
@@ -44,6 +75,25 @@ impl PluginManager {
         // 加载动态库
         let library = unsafe { Library::new(path)? };
 
+        // 先校验 ABI 版本，旧版插件的 trait 对象布局可能不同
+        let abi_version = unsafe {
+            let symbol: Symbol<*const u32> = library.get(PLUGIN_ABI_SYMBOL).map_err(|_| {
+                LoaderError::PluginError(format!(
+                    "{} does not export DUBHE_PLUGIN_ABI, rebuild it against this loader",
+                    path
+                ))
+            })?;
+            **symbol
+        };
+        if abi_version != PLUGIN_ABI_VERSION {
+            return Err(LoaderError::PluginAbiMismatch {
+                path: path.to_string(),
+                found: abi_version,
+                expected: PLUGIN_ABI_VERSION,
+            }
+            .into());
+        }
+
         // 获取插件创建函数
         let create_plugin: Symbol<unsafe extern "C" fn() -> *mut dyn Plugin> =
             unsafe { library.get(b"create_plugin")? };
@@ -56,21 +106,46 @@ impl PluginManager {
 
         let plugin = unsafe { Box::from_raw(plugin_ptr) };
 
+        let handle = self.insert(Some(library), plugin, path)?;
+        info!("Plugin loaded successfully: {}", path);
+        Ok(handle)
+    }
+
+    /// 注册进程内（静态链接）的插件
+    pub fn register_plugin(&mut self, plugin: Box<dyn Plugin>) -> Result<PluginHandle> {
+        let path = format!("builtin:{}", plugin.name());
+        let handle = self.insert(None, plugin, &path)?;
+        info!("Plugin registered: {}", path);
+        Ok(handle)
+    }
+
+    fn insert(
+        &mut self,
+        library: Option<Library>,
+        plugin: Box<dyn Plugin>,
+        path: &str,
+    ) -> Result<PluginHandle> {
         // 验证插件
         self.validate_plugin(&*plugin)?;
 
         let handle = PluginHandle(self.next_handle);
         self.next_handle += 1;
 
-        let loaded_plugin = LoadedPlugin {
-            library,
-            plugin,
-            path: path.to_string(),
-        };
-
-        self.plugins.insert(handle, loaded_plugin);
-
-        info!("Plugin loaded successfully: {}", path);
+        if let Some(audit) = &self.audit {
+            audit.record_event(AuditEvent::PluginLoaded {
+                name: plugin.name().to_string(),
+                version: plugin.version().to_string(),
+                path: path.to_string(),
+            });
+        }
+        self.plugins.insert(
+            handle,
+            LoadedPlugin {
+                library,
+                plugin,
+                path: path.to_string(),
+            },
+        );
         Ok(handle)
     }
 
@@ -79,6 +154,12 @@ impl PluginManager {
         match self.plugins.remove(&handle) {
             Some(plugin) => {
                 info!("Unloading plugin: {}", plugin.path);
+                if let Some(audit) = &self.audit {
+                    audit.record_event(AuditEvent::PluginUnloaded {
+                        name: plugin.plugin.name().to_string(),
+                        path: plugin.path.clone(),
+                    });
+                }
                 // 库会在 drop 时自动卸载
                 Ok(())
             }
@@ -91,6 +172,104 @@ impl PluginManager {
         self.plugins.get(&handle).map(|p| &*p.plugin)
     }
 
+    /// 查找支持该合约类型的插件，多个插件都支持时取最先加载的
+    pub fn find_plugin_for(&self, contract_type: &ContractType) -> Option<PluginHandle> {
+        self.plugins
+            .iter()
+            .filter(|(_, loaded)| loaded.plugin.capabilities().supports(contract_type))
+            .map(|(handle, _)| *handle)
+            .min_by_key(|handle| handle.0)
+    }
+
+    /// 用指定插件编译，插件 panic 时返回编译错误
+    pub fn compile(
+        &self,
+        handle: PluginHandle,
+        bytecode: &[u8],
+        config: &CompilationConfig,
+    ) -> Result<Vec<u8>, LoaderError> {
+        let loaded = self
+            .plugins
+            .get(&handle)
+            .ok_or_else(|| LoaderError::PluginError(format!("Plugin handle not found: {:?}", handle)))?;
+        compile_isolated(&*loaded.plugin, bytecode, config)
+    }
+
+    /// 用指定插件异步编译，`on_progress` 在进度变化时收到完成百分比
+    ///
+    /// 超过 `config.compile_timeout` 时取消插件任务并返回 [`LoaderError::CompileTimeout`]；
+    /// Future 被提前丢弃时同样取消任务。不支持异步编译的插件退回同步 `compile`
+    pub async fn compile_async<F>(
+        &self,
+        handle: PluginHandle,
+        bytecode: &[u8],
+        config: &CompilationConfig,
+        mut on_progress: F,
+    ) -> Result<Vec<u8>, LoaderError>
+    where
+        F: FnMut(u8) + Send,
+    {
+        let loaded = self.plugins.get(&handle).ok_or_else(|| {
+            LoaderError::PluginError(format!("Plugin handle not found: {:?}", handle))
+        })?;
+        let plugin = &*loaded.plugin;
+
+        let job = match catch_unwind(AssertUnwindSafe(|| plugin.start_compile(bytecode, config))) {
+            Ok(Ok(Some(job))) => job,
+            Ok(Ok(None)) => return compile_isolated(plugin, bytecode, config),
+            Ok(Err(e)) => return Err(plugin_failed(plugin, e)),
+            Err(payload) => return Err(plugin_panicked(plugin, payload)),
+        };
+        let mut job = RunningJob {
+            plugin,
+            job,
+            finished: false,
+        };
+        debug!("Plugin {} started compile job {:?}", plugin.name(), job.job);
+
+        let started = Instant::now();
+        let mut reported = None;
+        loop {
+            let poll = catch_unwind(AssertUnwindSafe(|| plugin.poll_compile(job.job)))
+                .map_err(|payload| plugin_panicked(plugin, payload))?;
+            match poll {
+                CompilePoll::Pending => {}
+                CompilePoll::Progress(percent) => {
+                    let percent = percent.min(100);
+                    if reported != Some(percent) {
+                        reported = Some(percent);
+                        on_progress(percent);
+                    }
+                }
+                CompilePoll::Done(output) => {
+                    job.finished = true;
+                    return Ok(output);
+                }
+                CompilePoll::Error(message) => {
+                    job.finished = true;
+                    return Err(plugin_failed(plugin, message));
+                }
+            }
+
+            if let Some(timeout) = config.compile_timeout {
+                if started.elapsed() >= timeout {
+                    warn!(
+                        "Plugin {} compile job {:?} exceeded {:?}, cancelling",
+                        plugin.name(),
+                        job.job,
+                        timeout
+                    );
+                    // 由 RunningJob 的 drop 取消任务
+                    return Err(LoaderError::CompileTimeout {
+                        compiler: plugin.name().to_string(),
+                        timeout_ms: timeout.as_millis() as u64,
+                    });
+                }
+            }
+            tokio::time::sleep(COMPILE_POLL_INTERVAL).await;
+        }
+    }
+
     /// 列出所有已加载的插件
     pub fn list_plugins(&self) -> Vec<(PluginHandle, &str, &str, &str)> {
         self.plugins
@@ -119,7 +298,7 @@ impl PluginManager {
 
         // 测试编译功能（使用空字节码）
         let test_config = CompilationConfig::default();
-        match plugin.compile(&[], &test_config) {
+        match compile_isolated(plugin, &[], &test_config) {
             Ok(_) => {
                 info!("Plugin validation passed: {}", plugin.name());
                 Ok(())
@@ -153,8 +332,172 @@ impl PluginManager {
     }
 }
 
+/// 调用插件编译，捕获 panic 转换为 `CompilationFailed`
+pub fn compile_isolated(
+    plugin: &dyn Plugin,
+    bytecode: &[u8],
+    config: &CompilationConfig,
+) -> Result<Vec<u8>, LoaderError> {
+    match catch_unwind(AssertUnwindSafe(|| plugin.compile(bytecode, config))) {
+        Ok(Ok(output)) => Ok(output),
+        Ok(Err(e)) => Err(plugin_failed(plugin, e)),
+        Err(payload) => Err(plugin_panicked(plugin, payload)),
+    }
+}
+
+fn plugin_failed(plugin: &dyn Plugin, e: impl std::fmt::Display) -> LoaderError {
+    LoaderError::CompilationFailed(format!("plugin {} failed: {}", plugin.name(), e))
+}
+
+fn plugin_panicked(plugin: &dyn Plugin, payload: Box<dyn Any + Send>) -> LoaderError {
+    let message = panic_message(payload);
+    error!(
+        "Plugin {} panicked during compile: {}",
+        plugin.name(),
+        message
+    );
+    LoaderError::CompilationFailed(format!("plugin {} panicked: {}", plugin.name(), message))
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// 进行中的插件编译任务，未完成就被丢弃时（超时或调用方放弃）取消任务
+struct RunningJob<'a> {
+    plugin: &'a dyn Plugin,
+    job: CompileJob,
+    finished: bool,
+}
+
+impl Drop for RunningJob<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let (plugin, job) = (self.plugin, self.job);
+            if catch_unwind(AssertUnwindSafe(|| plugin.cancel_compile(job))).is_err() {
+                error!(
+                    "Plugin {} panicked while cancelling {:?}",
+                    plugin.name(),
+                    job
+                );
+            }
+        }
+    }
+}
+
+/// 后台编译任务的进度与取消标志，交给编译函数
+#[derive(Debug, Default)]
+pub struct JobProgress {
+    percent: AtomicU8,
+    cancelled: AtomicBool,
+}
+
+impl JobProgress {
+    /// 报告完成百分比（超过 100 按 100 计）
+    pub fn report(&self, percent: u8) {
+        self.percent.store(percent.min(100), Ordering::Relaxed);
+    }
+
+    /// 宿主是否已取消任务；编译函数应定期检查并尽快返回
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Default)]
+struct JobState {
+    progress: JobProgress,
+    outcome: Mutex<Option<Result<Vec<u8>, String>>>,
+}
+
+/// 插件侧的编译任务表：每个任务在独立线程上运行，实现 `start/poll/cancel_compile` 三个接口
+#[derive(Default)]
+pub struct CompileJobs {
+    next_id: AtomicU64,
+    jobs: Mutex<HashMap<CompileJob, Arc<JobState>>>,
+}
+
+impl CompileJobs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 在后台线程上运行编译函数，立即返回任务句柄
+    pub fn spawn<F>(&self, name: &str, compile: F) -> Result<CompileJob>
+    where
+        F: FnOnce(&JobProgress) -> Result<Vec<u8>> + Send + 'static,
+    {
+        let job = CompileJob(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let state = Arc::new(JobState::default());
+        self.jobs.lock().unwrap().insert(job, state.clone());
+
+        let spawned = std::thread::Builder::new()
+            .name(format!("{}-compile-{}", name, job.0))
+            .spawn(move || {
+                let outcome = match catch_unwind(AssertUnwindSafe(|| compile(&state.progress))) {
+                    Ok(Ok(output)) => Ok(output),
+                    Ok(Err(e)) => Err(e.to_string()),
+                    Err(payload) => Err(format!("panicked: {}", panic_message(payload))),
+                };
+                *state.outcome.lock().unwrap() = Some(outcome);
+            });
+        if let Err(e) = spawned {
+            self.jobs.lock().unwrap().remove(&job);
+            return Err(e.into());
+        }
+        Ok(job)
+    }
+
+    /// 查询任务状态；完成或失败的任务随之移除
+    pub fn poll(&self, job: CompileJob) -> CompilePoll {
+        let mut jobs = self.jobs.lock().unwrap();
+        let Some(state) = jobs.get(&job).cloned() else {
+            return CompilePoll::Error(format!("Unknown compile job {:?}", job));
+        };
+        let outcome = state.outcome.lock().unwrap().take();
+        match outcome {
+            Some(outcome) => {
+                jobs.remove(&job);
+                match outcome {
+                    Ok(output) => CompilePoll::Done(output),
+                    Err(message) => CompilePoll::Error(message),
+                }
+            }
+            None => match state.progress.percent.load(Ordering::Relaxed) {
+                0 => CompilePoll::Pending,
+                percent => CompilePoll::Progress(percent),
+            },
+        }
+    }
+
+    /// 标记取消并移除任务，线程在编译函数返回后结束，结果被丢弃
+    pub fn cancel(&self, job: CompileJob) {
+        if let Some(state) = self.jobs.lock().unwrap().remove(&job) {
+            state.progress.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// 尚未被取走结果或取消的任务数
+    pub fn active(&self) -> usize {
+        self.jobs.lock().unwrap().len()
+    }
+}
+
 /// 示例插件实现
-pub struct ExamplePlugin;
+#[derive(Default)]
+pub struct ExamplePlugin {
+    jobs: CompileJobs,
+}
+
+impl ExamplePlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
 
 impl Plugin for ExamplePlugin {
     fn name(&self) -> &str {
@@ -165,16 +508,44 @@ impl Plugin for ExamplePlugin {
         "0.1.0"
     }
 
+    fn capabilities(&self) -> PluginCapabilities {
+        PluginCapabilities::default()
+    }
+
     fn compile(&self, bytecode: &[u8], _config: &CompilationConfig) -> anyhow::Result<Vec<u8>> {
         // 示例编译：直接返回输入（用于测试）
         Ok(bytecode.to_vec())
     }
+
+    fn start_compile(
+        &self,
+        bytecode: &[u8],
+        _config: &CompilationConfig,
+    ) -> anyhow::Result<Option<CompileJob>> {
+        let bytecode = bytecode.to_vec();
+        let job = self.jobs.spawn(self.name(), move |progress| {
+            progress.report(50);
+            Ok(bytecode)
+        })?;
+        Ok(Some(job))
+    }
+
+    fn poll_compile(&self, job: CompileJob) -> CompilePoll {
+        self.jobs.poll(job)
+    }
+
+    fn cancel_compile(&self, job: CompileJob) {
+        self.jobs.cancel(job)
+    }
 }
 
-// 导出函数（用于动态加载）
+// 导出符号（用于动态加载）
+#[no_mangle]
+pub static DUBHE_PLUGIN_ABI: u32 = PLUGIN_ABI_VERSION;
+
 #[no_mangle]
 pub extern "C" fn create_plugin() -> *mut dyn Plugin {
-    Box::into_raw(Box::new(ExamplePlugin))
+    Box::into_raw(Box::new(ExamplePlugin::new()))
 }
 
 #[cfg(test)]
@@ -196,7 +567,7 @@ mod tests {
 
     #[test]
     fn test_example_plugin() {
-        let plugin = ExamplePlugin;
+        let plugin = ExamplePlugin::new();
         assert_eq!(plugin.name(), "example-compiler");
         assert_eq!(plugin.version(), "0.1.0");
 
@@ -204,4 +575,197 @@ mod tests {
         let result = plugin.compile(&[1, 2, 3], &config).unwrap();
         assert_eq!(result, vec![1, 2, 3]);
     }
+
+    struct PanickingPlugin;
+
+    impl Plugin for PanickingPlugin {
+        fn name(&self) -> &str {
+            "panicking"
+        }
+
+        fn version(&self) -> &str {
+            "0.0.1"
+        }
+
+        fn capabilities(&self) -> PluginCapabilities {
+            PluginCapabilities {
+                contract_types: vec![ContractType::EVM],
+            }
+        }
+
+        fn compile(&self, bytecode: &[u8], _config: &CompilationConfig) -> Result<Vec<u8>> {
+            if bytecode.is_empty() {
+                return Ok(vec![]);
+            }
+            panic!("bad opcode {:#x}", bytecode[0]);
+        }
+    }
+
+    #[test]
+    fn test_panicking_plugin_returns_compilation_error() {
+        let mut manager = PluginManager::new();
+        let handle = manager.register_plugin(Box::new(PanickingPlugin)).unwrap();
+
+        let error = manager
+            .compile(handle, &[0xfe], &CompilationConfig::default())
+            .unwrap_err();
+        match error {
+            LoaderError::CompilationFailed(message) => {
+                assert!(message.contains("panicked: bad opcode 0xfe"), "{}", message)
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        // 插件仍可继续使用
+        assert_eq!(manager.find_plugin_for(&ContractType::EVM), Some(handle));
+        assert!(manager.find_plugin_for(&ContractType::Move).is_none());
+        assert!(manager
+            .compile(handle, &[], &CompilationConfig::default())
+            .is_ok());
+    }
+
+    /// 分若干步编译的慢插件，每步报告进度并检查取消
+    struct SlowPlugin {
+        jobs: CompileJobs,
+        steps: u8,
+        step: Duration,
+        cancelled: Arc<AtomicBool>,
+    }
+
+    impl SlowPlugin {
+        fn new(steps: u8, step: Duration) -> Self {
+            Self {
+                jobs: CompileJobs::new(),
+                steps,
+                step,
+                cancelled: Arc::new(AtomicBool::new(false)),
+            }
+        }
+    }
+
+    impl Plugin for SlowPlugin {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn version(&self) -> &str {
+            "0.0.1"
+        }
+
+        fn capabilities(&self) -> PluginCapabilities {
+            PluginCapabilities {
+                contract_types: vec![ContractType::Move],
+            }
+        }
+
+        fn compile(&self, bytecode: &[u8], _config: &CompilationConfig) -> Result<Vec<u8>> {
+            Ok(bytecode.to_vec())
+        }
+
+        fn start_compile(
+            &self,
+            bytecode: &[u8],
+            _config: &CompilationConfig,
+        ) -> Result<Option<CompileJob>> {
+            let (bytecode, steps, step) = (bytecode.to_vec(), self.steps, self.step);
+            let cancelled = self.cancelled.clone();
+            let job = self.jobs.spawn(self.name(), move |progress| {
+                for i in 1..=steps {
+                    std::thread::sleep(step);
+                    if progress.is_cancelled() {
+                        cancelled.store(true, Ordering::SeqCst);
+                        anyhow::bail!("cancelled");
+                    }
+                    progress.report((i as u32 * 100 / steps as u32) as u8);
+                }
+                Ok(bytecode)
+            })?;
+            Ok(Some(job))
+        }
+
+        fn poll_compile(&self, job: CompileJob) -> CompilePoll {
+            self.jobs.poll(job)
+        }
+
+        fn cancel_compile(&self, job: CompileJob) {
+            self.jobs.cancel(job)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_compile_reports_progress() {
+        let mut manager = PluginManager::new();
+        let handle = manager
+            .register_plugin(Box::new(SlowPlugin::new(4, Duration::from_millis(30))))
+            .unwrap();
+
+        let mut progress = Vec::new();
+        let output = manager
+            .compile_async(
+                handle,
+                &[7, 8, 9],
+                &CompilationConfig::default(),
+                |percent| progress.push(percent),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(output, vec![7, 8, 9]);
+        assert!(!progress.is_empty());
+        assert!(progress.windows(2).all(|w| w[0] < w[1]), "{:?}", progress);
+        assert!(progress.iter().all(|p| [25, 50, 75, 100].contains(p)));
+    }
+
+    #[tokio::test]
+    async fn test_async_compile_timeout_cancels_job() {
+        let plugin = SlowPlugin::new(100, Duration::from_millis(20));
+        let cancelled = plugin.cancelled.clone();
+        let mut manager = PluginManager::new();
+        let handle = manager.register_plugin(Box::new(plugin)).unwrap();
+
+        let config = CompilationConfig {
+            compile_timeout: Some(Duration::from_millis(100)),
+            ..CompilationConfig::default()
+        };
+        let started = Instant::now();
+        let error = manager
+            .compile_async(handle, &[1], &config, |_| {})
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            LoaderError::CompileTimeout {
+                timeout_ms: 100,
+                ..
+            }
+        ));
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        // 插件线程在下一步检查到取消后退出
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(cancelled.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_async_compile_falls_back_to_sync_plugins() {
+        let mut manager = PluginManager::new();
+        let example = manager
+            .register_plugin(Box::new(ExamplePlugin::new()))
+            .unwrap();
+        let panicking = manager.register_plugin(Box::new(PanickingPlugin)).unwrap();
+        let config = CompilationConfig::default();
+
+        let output = manager
+            .compile_async(example, &[1, 2], &config, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(output, vec![1, 2]);
+
+        // 不支持异步编译的插件走同步路径，panic 仍被隔离
+        let error = manager
+            .compile_async(panicking, &[0xfe], &config, |_| {})
+            .await
+            .unwrap_err();
+        assert!(matches!(error, LoaderError::CompilationFailed(_)));
+    }
 }
@@ -0,0 +1,258 @@
+//! 入口函数调用编码
+//!
+//! 按 [`crate::abi`] 中的入口调用约定，将 JSON 参数按编译产物记录的参数类型
+//! 编码为 BCS，参数个数或类型不符时在进入 VM 之前报错
+
+use serde_json::Value;
+
+use crate::abi::{ENTRY_INDEX_SIZE, MAX_ENTRY_INPUT};
+use crate::error::LoaderError;
+use crate::types::{CompiledContract, FunctionSignature, ParamType};
+
+impl CompiledContract {
+    /// 按名称查找入口函数，返回分派序号与签名
+    ///
+    /// 名称可以是 `module::function`，也可以是在包内唯一的函数名
+    pub fn entry_function(&self, name: &str) -> Option<(u32, &FunctionSignature)> {
+        let index = self
+            .entry_points
+            .iter()
+            .position(|entry| entry == name)
+            .or_else(|| {
+                let suffix = format!("::{}", name);
+                let mut matches = self
+                    .entry_points
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, entry)| entry.ends_with(&suffix));
+                match (matches.next(), matches.next()) {
+                    (Some((index, _)), None) => Some(index),
+                    _ => None,
+                }
+            })?;
+        let signature = self.metadata.exports.get(&self.entry_points[index])?;
+        Some((index as u32, signature))
+    }
+
+    /// 编码一次入口函数调用：序号 + BCS 参数
+    pub fn encode_entry_call(
+        &self,
+        function: &str,
+        arguments: &[Value],
+    ) -> Result<Vec<u8>, LoaderError> {
+        let (index, signature) = self
+            .entry_function(function)
+            .ok_or_else(|| LoaderError::UnknownEntryFunction(function.to_string()))?;
+
+        if arguments.len() != signature.inputs.len() {
+            return Err(LoaderError::ArgumentCountMismatch {
+                function: signature.name.clone(),
+                expected: signature.inputs.len(),
+                found: arguments.len(),
+            });
+        }
+
+        let mut input = index.to_le_bytes().to_vec();
+        for (position, (param, value)) in signature.inputs.iter().zip(arguments).enumerate() {
+            encode_value(param, value, &mut input).map_err(|expected| {
+                LoaderError::ArgumentTypeMismatch {
+                    function: signature.name.clone(),
+                    index: position,
+                    expected,
+                    found: value.to_string(),
+                }
+            })?;
+        }
+
+        if input.len() > MAX_ENTRY_INPUT {
+            return Err(LoaderError::ArgumentsTooLarge {
+                function: signature.name.clone(),
+                size: input.len() - ENTRY_INDEX_SIZE,
+                limit: MAX_ENTRY_INPUT - ENTRY_INDEX_SIZE,
+            });
+        }
+        Ok(input)
+    }
+}
+
+/// 按参数类型追加 BCS 编码，失败时返回期望类型的描述
+fn encode_value(param: &ParamType, value: &Value, out: &mut Vec<u8>) -> Result<(), String> {
+    let expected = || format!("{:?}", param);
+    match param {
+        ParamType::Bool => out.push(value.as_bool().ok_or_else(expected)? as u8),
+        ParamType::Uint(bits) => {
+            // Sui JSON-RPC 中 u64 及以上常以字符串传递
+            let number = match value {
+                Value::Number(number) => number.as_u64().map(u128::from),
+                Value::String(text) => text.parse::<u128>().ok(),
+                _ => None,
+            }
+            .ok_or_else(expected)?;
+            let bytes = bits / 8;
+            if *bits < 128 && number >> bits != 0 {
+                return Err(expected());
+            }
+            let le = number.to_le_bytes();
+            match bytes {
+                1 | 2 | 4 | 8 | 16 => out.extend_from_slice(&le[..bytes]),
+                32 => {
+                    out.extend_from_slice(&le);
+                    out.extend_from_slice(&[0u8; 16]);
+                }
+                _ => return Err(expected()),
+            }
+        }
+        ParamType::Address => {
+            let text = value.as_str().ok_or_else(expected)?;
+            let digits = text.strip_prefix("0x").unwrap_or(text);
+            if digits.is_empty() || digits.len() > 64 {
+                return Err(expected());
+            }
+            let padded = format!("{:0>64}", digits);
+            out.extend(hex::decode(padded).map_err(|_| expected())?);
+        }
+        ParamType::String => {
+            let text = value.as_str().ok_or_else(expected)?;
+            write_uleb128(text.len(), out);
+            out.extend_from_slice(text.as_bytes());
+        }
+        ParamType::Bytes => {
+            let bytes = match value {
+                Value::String(text) => {
+                    hex::decode(text.strip_prefix("0x").unwrap_or(text)).map_err(|_| expected())?
+                }
+                Value::Array(items) => items
+                    .iter()
+                    .map(|item| item.as_u64().filter(|b| *b <= 0xff).map(|b| b as u8))
+                    .collect::<Option<Vec<u8>>>()
+                    .ok_or_else(expected)?,
+                _ => return Err(expected()),
+            };
+            write_uleb128(bytes.len(), out);
+            out.extend(bytes);
+        }
+        ParamType::Array(inner) => {
+            let items = value.as_array().ok_or_else(expected)?;
+            write_uleb128(items.len(), out);
+            for item in items {
+                encode_value(inner, item, out)?;
+            }
+        }
+        // WASM 导出函数的 i32/i64 参数，按补码小端编码
+        ParamType::Int(bits @ (8 | 16 | 32 | 64)) => {
+            let number = match value {
+                Value::Number(number) => number.as_i64(),
+                Value::String(text) => text.parse::<i64>().ok(),
+                _ => None,
+            }
+            .ok_or_else(expected)?;
+            let bytes = bits / 8;
+            let min = i64::MIN >> (64 - bits);
+            if number < min || number > !min {
+                return Err(expected());
+            }
+            out.extend_from_slice(&number.to_le_bytes()[..bytes]);
+        }
+        // 入口函数没有更宽的有符号整数与元组参数
+        ParamType::Int(_) | ParamType::Tuple(_) => return Err(expected()),
+    }
+    Ok(())
+}
+
+/// BCS 长度前缀
+fn write_uleb128(mut value: usize, out: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ArtifactKind, ContractMetadata, Mutability};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn counter_contract() -> CompiledContract {
+        let signature = |name: &str, inputs| FunctionSignature {
+            name: name.to_string(),
+            inputs,
+            outputs: vec![],
+            mutability: Mutability::NonPayable,
+        };
+        let entries = vec![
+            signature("counter::increment", vec![ParamType::Address]),
+            signature(
+                "counter::set_value",
+                vec![ParamType::Address, ParamType::Uint(64)],
+            ),
+        ];
+
+        CompiledContract {
+            original_address: "0xc0de".to_string(),
+            source_type: dubhe_adapter::ContractType::Move,
+            risc_v_code: vec![],
+            artifact: ArtifactKind::NativeRiscV,
+            entry_points: entries.iter().map(|e| e.name.clone()).collect(),
+            metadata: ContractMetadata {
+                gas_metering: true,
+                memory_limit: 0,
+                stack_limit: 0,
+                call_depth_limit: 0,
+                exports: entries
+                    .into_iter()
+                    .map(|e| (e.name.clone(), e))
+                    .collect::<HashMap<_, _>>(),
+                storage_access: None,
+            },
+            compiled_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_encode_entry_call() {
+        let contract = counter_contract();
+        let input = contract
+            .encode_entry_call("set_value", &[json!("0x1"), json!("42")])
+            .unwrap();
+
+        let mut expected = 1u32.to_le_bytes().to_vec();
+        expected.extend([0u8; 31]);
+        expected.push(1);
+        expected.extend(42u64.to_le_bytes());
+        assert_eq!(input, expected);
+
+        let input = contract
+            .encode_entry_call("counter::increment", &[json!("0x1")])
+            .unwrap();
+        assert_eq!(input[..4], 0u32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_rejects_mismatched_arguments() {
+        let contract = counter_contract();
+
+        assert!(matches!(
+            contract.encode_entry_call("set_value", &[json!("0x1")]),
+            Err(LoaderError::ArgumentCountMismatch {
+                expected: 2,
+                found: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            contract.encode_entry_call("set_value", &[json!("0x1"), json!("forty-two")]),
+            Err(LoaderError::ArgumentTypeMismatch { index: 1, .. })
+        ));
+        assert!(matches!(
+            contract.encode_entry_call("decrement", &[]),
+            Err(LoaderError::UnknownEntryFunction(_))
+        ));
+    }
+}
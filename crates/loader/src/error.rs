@@ -13,12 +13,75 @@ pub enum LoaderError {
     #[error("Plugin error: {0}")]
     PluginError(String),
 
+    #[error("Capability for {granted:?} cannot be used to {required:?}")]
+    CapabilityMismatch {
+        granted: dubhe_security::Permission,
+        required: dubhe_security::Permission,
+    },
+
+    #[error("Plugin {path} was built for ABI version {found}, expected {expected}")]
+    PluginAbiMismatch {
+        path: String,
+        found: u32,
+        expected: u32,
+    },
+
+    #[error("Compiler {compiler} exceeded its {timeout_ms}ms compile timeout, job cancelled")]
+    CompileTimeout { compiler: String, timeout_ms: u64 },
+
     #[error("Unsupported contract type: {0:?}")]
     UnsupportedContractType(dubhe_adapter::ContractType),
 
+    #[error("Unknown entry function: {0}")]
+    UnknownEntryFunction(String),
+
+    #[error("{function} expects {expected} arguments, got {found}")]
+    ArgumentCountMismatch {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("Argument {index} of {function} must be {expected}, got {found}")]
+    ArgumentTypeMismatch {
+        function: String,
+        index: usize,
+        expected: String,
+        found: String,
+    },
+
+    #[error("Arguments of {function} take {size} bytes, limit is {limit}")]
+    ArgumentsTooLarge {
+        function: String,
+        size: usize,
+        limit: usize,
+    },
+
     #[error("Invalid bytecode: {0}")]
     InvalidBytecode(String),
 
+    #[error("WASM import {module}::{name} is not allowed: {reason}")]
+    DisallowedImport {
+        module: String,
+        name: String,
+        reason: String,
+    },
+
+    #[error("WASM memory must declare a maximum of at most {limit} pages, found {declared}")]
+    MemoryLimitExceeded { declared: String, limit: u64 },
+
+    #[error("No cached artifact for {0}")]
+    ArtifactNotFound(String),
+
+    #[error("Invalid artifact bundle: {0}")]
+    InvalidArtifact(String),
+
+    #[error("Artifact is signed by untrusted key {0}")]
+    UntrustedArtifactSigner(String),
+
+    #[error("Artifact was built by compiler {found}, this node runs {expected}")]
+    ArtifactCompilerMismatch { found: String, expected: String },
+
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
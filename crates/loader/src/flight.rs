@@ -0,0 +1,81 @@
+//! 编译的单飞（single-flight）控制
+//!
+//! 同一缓存键同时只有一个加载者（leader）查询缓存并编译，其余并发请求等待它的结果，
+//! 不会重复编译。leader 在写入缓存后才结束，之后到达的请求直接命中缓存；
+//! leader 被取消时等待者重新竞争，由其中一个接替编译
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+use crate::types::CompiledContract;
+
+/// 等待者看到的编译结果，错误只保留描述
+type FlightResult = Result<CompiledContract, String>;
+
+/// 进行中的编译
+#[derive(Default)]
+pub(crate) struct CompileFlights {
+    flights: Mutex<HashMap<String, watch::Receiver<Option<FlightResult>>>>,
+}
+
+/// 加入某个缓存键的编译
+pub(crate) enum Flight<'a> {
+    /// 没有进行中的编译，由调用方负责
+    Leader(FlightGuard<'a>),
+    /// 等待进行中的编译
+    Follower(watch::Receiver<Option<FlightResult>>),
+}
+
+impl CompileFlights {
+    pub(crate) fn join(&self, key: &str) -> Flight<'_> {
+        let mut flights = self.flights.lock().unwrap();
+        if let Some(receiver) = flights.get(key) {
+            return Flight::Follower(receiver.clone());
+        }
+        let (sender, receiver) = watch::channel(None);
+        flights.insert(key.to_string(), receiver);
+        Flight::Leader(FlightGuard {
+            flights: self,
+            key: key.to_string(),
+            sender,
+        })
+    }
+}
+
+/// 等待 leader 的结果；leader 未完成就被取消时返回 `None`
+pub(crate) async fn wait(
+    mut receiver: watch::Receiver<Option<FlightResult>>,
+) -> Option<Result<CompiledContract>> {
+    let result = receiver.wait_for(Option::is_some).await.ok()?;
+    Some(
+        result
+            .as_ref()
+            .expect("waited for a result")
+            .clone()
+            .map_err(anyhow::Error::msg),
+    )
+}
+
+/// leader 持有的编译权，drop 时结束该次编译
+pub(crate) struct FlightGuard<'a> {
+    flights: &'a CompileFlights,
+    key: String,
+    sender: watch::Sender<Option<FlightResult>>,
+}
+
+impl FlightGuard<'_> {
+    /// 把结果交给等待者
+    pub(crate) fn complete(self, result: &Result<CompiledContract>) {
+        self.sender.send_replace(Some(
+            result.as_ref().cloned().map_err(|e| format!("{:#}", e)),
+        ));
+    }
+}
+
+impl Drop for FlightGuard<'_> {
+    fn drop(&mut self) {
+        self.flights.flights.lock().unwrap().remove(&self.key);
+    }
+}
@@ -4,39 +4,92 @@
 //!
 //! 核心功能：
 //! 1. EVM → RISC-V 编译
-//! 2. Move/BPF/WASM → RISC-V 编译  
+//! 2. Move/BPF → RISC-V 编译，WASM 解释器包  
 //! 3. LRU + 持久层编译缓存
 //! 4. 动态 .so 插件安全加载
+//! 5. 版本升级后的空闲期后台重编译
+//! 6. 签名产物包的导出与导入（离线部署）
+//! 7. 批量加载：按缓存键去重、并行编译，同一缓存键的并发加载只编译一次
 
+pub mod abi;
+pub mod access;
+pub mod artifact;
 pub mod cache;
 pub mod compiler;
 pub mod dyn_lib;
+pub mod entry;
 pub mod error;
+mod flight;
 pub mod move_compiler;
+pub mod recompile;
+pub mod riscv;
 pub mod types;
+pub mod wasm_compiler;
 
+pub use access::StorageAccess;
+pub use artifact::*;
 pub use cache::*;
 pub use compiler::*;
 pub use dyn_lib::*;
 pub use error::*;
 pub use move_compiler::*;
+pub use recompile::*;
 pub use types::*;
+pub use wasm_compiler::*;
 
 use anyhow::Result;
+use async_trait::async_trait;
+use dubhe_observability::{NodeMetrics, TRACE_TARGET};
+use dubhe_security::{AuditTrail, Capability, KeyHandle, Permission};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
-use tracing::info;
+use tokio::sync::Semaphore;
+use tracing::{debug, field, info, info_span, warn, Instrument, Span};
+
+use crate::flight::{CompileFlights, Flight};
+
+/// 默认的并行编译数：可用 CPU 核数
+pub fn default_compile_parallelism() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+}
 
 /// 代码加载器主管理器
 pub struct CodeLoader {
-    compiler: DefaultCompiler,
-    move_compiler: MoveToRiscVCompiler,
+    // 内置编译器只持有配置，编译在独立任务中进行
+    compiler: Arc<DefaultCompiler>,
+    move_compiler: Arc<MoveToRiscVCompiler>,
+    wasm_compiler: Arc<WasmCompiler>,
     cache: Arc<CompilationCache>,
+    /// 同一缓存键的并发加载共享一次编译
+    flights: CompileFlights,
+    /// 同时进行的内置编译数上限
+    compile_slots: Arc<Semaphore>,
     plugin_manager: PluginManager,
+    /// 影响编译产物的编译器版本与配置，参与缓存键计算
+    compiler_fingerprint: String,
+    usage: Arc<UsageTracker>,
+    activity: Arc<LoadActivity>,
+    metrics: Option<Arc<NodeMetrics>>,
 }
 
 impl CodeLoader {
     pub fn new() -> Result<Self> {
-        let cache = Arc::new(CompilationCache::new("./cache")?);
+        Self::with_cache_dir("./cache")
+    }
+
+    /// 使用指定缓存目录创建加载器
+    pub fn with_cache_dir<P: AsRef<Path>>(cache_dir: P) -> Result<Self> {
+        Self::with_cache_config(cache_dir, CacheConfig::default())
+    }
+
+    /// 使用指定缓存目录和淘汰策略创建加载器
+    pub fn with_cache_config<P: AsRef<Path>>(cache_dir: P, cache_config: CacheConfig) -> Result<Self> {
+        let cache = Arc::new(CompilationCache::new(cache_dir, cache_config)?);
+        let usage = Arc::new(UsageTracker::restore(&cache)?);
         let compiler = DefaultCompiler::new();
         let move_compiler = MoveToRiscVCompiler::new(move_compiler::MoveCompilerConfig {
             target_arch: move_compiler::RiscVTarget::RV64IMC,
@@ -45,27 +98,157 @@ impl CodeLoader {
             enable_debug_info: false,
             stackless_bytecode: true,
         })?;
+        let wasm_compiler = WasmCompiler::default();
         let plugin_manager = PluginManager::new();
+        let compiler_fingerprint = format!(
+            "{}|abi{}|fmt{}|{:?}|{:?}|{:?}",
+            ArtifactVersion::default().compiler_version,
+            abi::GUEST_ABI_VERSION,
+            ARTIFACT_FORMAT_VERSION,
+            // 编译时限不影响产物，不参与缓存键
+            CompilationConfig {
+                compile_timeout: None,
+                ..compiler.config().clone()
+            },
+            move_compiler.config(),
+            wasm_compiler.config()
+        );
 
         info!("Code loader initialized with Move compiler");
 
         Ok(Self {
-            compiler,
-            move_compiler,
+            compiler: Arc::new(compiler),
+            move_compiler: Arc::new(move_compiler),
+            wasm_compiler: Arc::new(wasm_compiler),
             cache,
+            flights: CompileFlights::default(),
+            compile_slots: Arc::new(Semaphore::new(default_compile_parallelism())),
             plugin_manager,
+            compiler_fingerprint,
+            usage,
+            activity: Arc::new(LoadActivity::new()),
+            metrics: None,
         })
     }
 
+    /// 将编译缓存命中率记录到共享的 Prometheus 指标
+    pub fn with_metrics(mut self, metrics: Arc<NodeMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// 设置同时进行的内置编译数（至少为 1）
+    pub fn with_compile_parallelism(mut self, parallelism: usize) -> Self {
+        self.compile_slots = Arc::new(Semaphore::new(parallelism.max(1)));
+        self
+    }
+
+    /// 设置单次编译（插件与内置编译器）的时限，`None` 表示不限时
+    pub fn with_compile_timeout(mut self, timeout: Option<std::time::Duration>) -> Self {
+        let config = CompilationConfig {
+            compile_timeout: timeout,
+            ..self.compiler.config().clone()
+        };
+        self.compiler = Arc::new(DefaultCompiler::with_config(config));
+        self
+    }
+
+    /// 插件加载与卸载写入审计日志
+    pub fn with_audit_trail(mut self, audit: Arc<AuditTrail>) -> Self {
+        self.plugin_manager.set_audit_trail(audit);
+        self
+    }
+
     /// 加载合约代码（优先从缓存读取）
     pub async fn load_contract(
         &self,
         meta: &dubhe_adapter::ContractMeta,
     ) -> Result<CompiledContract> {
+        let span = info_span!(
+            target: TRACE_TARGET,
+            "loader.load_contract",
+            contract.address = %meta.address,
+            contract.type = ?meta.contract_type,
+            cache.hit = field::Empty,
+        );
+        self.load_contract_inner(meta).instrument(span).await
+    }
+
+    async fn load_contract_inner(
+        &self,
+        meta: &dubhe_adapter::ContractMeta,
+    ) -> Result<CompiledContract> {
+        let _active = self.activity.enter();
         let cache_key = self.generate_cache_key(meta);
+        self.usage.record(&cache_key, meta);
 
+        loop {
+            match self.flights.join(&cache_key) {
+                Flight::Leader(flight) => {
+                    let result = self.load_or_compile(meta, &cache_key).await;
+                    flight.complete(&result);
+                    return result;
+                }
+                Flight::Follower(receiver) => {
+                    if let Some(result) = flight::wait(receiver).await {
+                        return result;
+                    }
+                    // leader 被取消，重新竞争
+                }
+            }
+        }
+    }
+
+    /// 批量加载合约，按缓存键去重后并行编译
+    ///
+    /// 返回值与 `metas` 一一对应，单个合约失败不影响其他合约
+    pub async fn load_contracts_batch(
+        &self,
+        metas: &[dubhe_adapter::ContractMeta],
+    ) -> Vec<Result<CompiledContract>> {
+        let keys: Vec<String> = metas
+            .iter()
+            .map(|meta| self.generate_cache_key(meta))
+            .collect();
+        let mut unique: HashMap<&str, usize> = HashMap::new();
+        for (index, key) in keys.iter().enumerate() {
+            unique.entry(key.as_str()).or_insert(index);
+        }
+        info!(
+            "Loading {} contracts ({} unique)",
+            metas.len(),
+            unique.len()
+        );
+
+        let loads = unique
+            .iter()
+            .map(|(key, index)| async move { (*key, self.load_contract(&metas[*index]).await) });
+        let loaded: HashMap<&str, Result<CompiledContract>> =
+            futures::future::join_all(loads).await.into_iter().collect();
+
+        keys.iter()
+            .map(|key| match &loaded[key.as_str()] {
+                Ok(compiled) => Ok(compiled.clone()),
+                Err(e) => Err(anyhow::anyhow!("{:#}", e)),
+            })
+            .collect()
+    }
+
+    /// 由 leader 调用：查询缓存，未命中时编译并写入缓存
+    async fn load_or_compile(
+        &self,
+        meta: &dubhe_adapter::ContractMeta,
+        cache_key: &str,
+    ) -> Result<CompiledContract> {
         // 尝试从缓存加载
-        if let Some(cached) = self.cache.get(&cache_key).await? {
+        let cached = self.cache.get(cache_key).await?;
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .cache_hit_ratio
+                .set(self.cache.stats().await.hit_rate);
+        }
+        Span::current().record("cache.hit", cached.is_some());
+        if let Some(cached) = cached {
             info!("Contract loaded from cache: {}", meta.address);
             return Ok(cached);
         }
@@ -73,26 +256,210 @@ impl CodeLoader {
         // 缓存未命中，进行编译
         info!("Compiling contract: {}", meta.address);
 
-        let compiled = match meta.contract_type {
-            dubhe_adapter::ContractType::Move => {
-                // 使用专门的 Move 编译器
-                info!("Using Move → RISC-V compiler for {}", meta.address);
-                self.move_compiler.compile_sui_package(meta).await?
+        let compiled = self.compile(meta).await?;
+
+        // 存入缓存
+        self.cache
+            .put_with_origin(cache_key, &compiled, &ArtifactOrigin::new(meta))
+            .await?;
+
+        Ok(compiled)
+    }
+
+    /// 在独立任务中用内置编译器编译，受并行编译数限制
+    async fn compile_builtin(
+        &self,
+        meta: &dubhe_adapter::ContractMeta,
+    ) -> Result<CompiledContract> {
+        let permit = self.compile_slots.clone().acquire_owned().await?;
+        let (address, contract_type) = (meta.address.clone(), meta.contract_type.clone());
+        let meta = meta.clone();
+        let compiler = self.compiler.clone();
+        let move_compiler = self.move_compiler.clone();
+        let wasm_compiler = self.wasm_compiler.clone();
+        let cache = self.cache.clone();
+        let fingerprint = self.compiler_fingerprint.clone();
+
+        let task = async move {
+            let _permit = permit;
+            match &meta.contract_type {
+                dubhe_adapter::ContractType::Move => {
+                    // 使用专门的 Move 编译器，模块产物单独缓存
+                    info!("Using Move → RISC-V compiler for {}", meta.address);
+                    move_compiler
+                        .compile_sui_package_cached(&meta, &cache, &fingerprint)
+                        .await
+                }
+                dubhe_adapter::ContractType::WASM => {
+                    info!("Using WASM interpreter bundle for {}", meta.address);
+                    wasm_compiler.compile(&meta)
+                }
+                _ => {
+                    // 使用通用编译器
+                    info!(
+                        "Using default compiler for {:?} contract {}",
+                        meta.contract_type, meta.address
+                    );
+                    compiler.compile(&meta).await
+                }
             }
-            _ => {
-                // 使用通用编译器
-                info!(
-                    "Using default compiler for {:?} contract {}",
-                    meta.contract_type, meta.address
+        };
+        let mut compile = tokio::spawn(task.instrument(Span::current()));
+        let Some(timeout) = self.compiler.config().compile_timeout else {
+            return compile.await?;
+        };
+        match tokio::time::timeout(timeout, &mut compile).await {
+            Ok(result) => result?,
+            Err(_) => {
+                // 放弃编译任务，释放编译槽位
+                compile.abort();
+                warn!(
+                    "Built-in compile of {} exceeded {:?}, aborted",
+                    address, timeout
                 );
-                self.compiler.compile(meta).await?
+                Err(LoaderError::CompileTimeout {
+                    compiler: format!("builtin:{:?}", contract_type),
+                    timeout_ms: timeout.as_millis() as u64,
+                }
+                .into())
             }
+        }
+    }
+
+    /// 失效某个地址下的所有编译产物（例如观察到包升级时）
+    ///
+    /// 返回被删除的缓存条目数
+    pub async fn invalidate(&self, address: &str) -> Result<usize> {
+        let prefix = format!("{}-", address);
+        self.usage.forget_prefix(&prefix);
+        let removed = self.cache.remove_prefix(&prefix).await?;
+        info!("Invalidated {} cached artifacts for {}", removed, address);
+        Ok(removed)
+    }
+
+    /// 导出某地址最近使用的编译产物，用密钥库中的密钥签名
+    pub async fn export_artifact(&self, address: &str, key: &KeyHandle) -> Result<ArtifactBundle> {
+        let not_found = || LoaderError::ArtifactNotFound(address.to_string());
+        let cache_key = self
+            .cache
+            .latest_with_prefix(&format!("{}-", address))
+            .await
+            .ok_or_else(not_found)?;
+        let (contract, origin) = self
+            .cache
+            .get_with_origin(&cache_key)
+            .await?
+            .ok_or_else(not_found)?;
+        if origin.bytecode_hash.is_empty() {
+            return Err(LoaderError::InvalidArtifact(format!(
+                "cached artifact {} has no recorded origin",
+                cache_key
+            ))
+            .into());
+        }
+
+        let manifest = ArtifactManifest {
+            address: address.to_string(),
+            origin,
+            compiler_version: ArtifactVersion::default().compiler_version,
+            compiler_fingerprint: self.compiler_fingerprint.clone(),
+            code_hash: hex::encode(Sha256::digest(&contract.risc_v_code)),
+            contract,
+            exported_at: chrono::Utc::now().timestamp() as u64,
         };
+        let bundle = ArtifactBundle::sign(&manifest, key)?;
+        info!(
+            "📦 Exported artifact {} signed by {}",
+            cache_key,
+            key.name()
+        );
+        Ok(bundle)
+    }
 
-        // 存入缓存
-        self.cache.put(&cache_key, &compiled).await?;
+    /// 验证产物包后写入编译缓存，返回缓存键
+    ///
+    /// 编译器版本或配置与本节点不同的产物默认拒绝，`force` 时只告警
+    pub async fn import_artifact<P: AsRef<Path>>(
+        &self,
+        path: P,
+        trusted_signers: &[[u8; 32]],
+        force: bool,
+    ) -> Result<String> {
+        let manifest = ArtifactBundle::read_from(path)?.verify(trusted_signers)?;
 
-        Ok(compiled)
+        if manifest.compiler_fingerprint != self.compiler_fingerprint {
+            let expected = ArtifactVersion::default().compiler_version;
+            warn!(
+                "⚠️ Artifact for {} was built by compiler {} ({}), this node runs {} ({})",
+                manifest.address,
+                manifest.compiler_version,
+                manifest.compiler_fingerprint,
+                expected,
+                self.compiler_fingerprint
+            );
+            if !force {
+                return Err(LoaderError::ArtifactCompilerMismatch {
+                    found: manifest.compiler_version,
+                    expected,
+                }
+                .into());
+            }
+        }
+
+        // 按本节点的编译器标识计算缓存键，加载同一字节码时直接命中
+        let cache_key = self.cache_key(
+            &manifest.address,
+            &manifest.contract.source_type,
+            &manifest.origin.bytecode_hash,
+        );
+        self.cache
+            .put_with_origin(&cache_key, &manifest.contract, &manifest.origin)
+            .await?;
+        info!(
+            "📦 Imported artifact for {} as {}",
+            manifest.address, cache_key
+        );
+        Ok(cache_key)
+    }
+
+    /// 编译缓存
+    pub fn cache(&self) -> Arc<CompilationCache> {
+        self.cache.clone()
+    }
+
+    /// 合约使用频率统计
+    pub fn usage(&self) -> Arc<UsageTracker> {
+        self.usage.clone()
+    }
+
+    /// 进行中的加载，可作为后台重编译的负载门控
+    pub fn activity(&self) -> Arc<LoadActivity> {
+        self.activity.clone()
+    }
+
+    /// 将使用统计写入编译缓存，返回写入条数
+    pub async fn persist_usage(&self) -> Result<usize> {
+        self.usage.persist(&self.cache).await
+    }
+
+    /// 启动周期性使用统计持久化
+    pub fn spawn_usage_persister(
+        self: &Arc<Self>,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let loader = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                match loader.persist_usage().await {
+                    Ok(0) => {}
+                    Ok(written) => debug!("Persisted {} usage records", written),
+                    Err(e) => warn!("Failed to persist usage statistics: {}", e),
+                }
+            }
+        })
     }
 
     /// 加载动态插件
@@ -100,18 +467,439 @@ impl CodeLoader {
         self.plugin_manager.load_plugin(path)
     }
 
+    /// 经 API 触发的插件加载，需要访问控制签发的 LoadPlugin 凭证
+    pub fn load_plugin_authorized(
+        &mut self,
+        path: &str,
+        capability: &Capability,
+    ) -> Result<PluginHandle> {
+        require_capability(capability, Permission::LoadPlugin)?;
+        info!("🔌 Loading plugin {} for {}", path, capability.principal());
+        self.load_plugin(path)
+    }
+
+    /// 经 API 触发的插件卸载，需要访问控制签发的 UnloadPlugin 凭证
+    pub fn unload_plugin_authorized(
+        &mut self,
+        handle: PluginHandle,
+        capability: &Capability,
+    ) -> Result<()> {
+        require_capability(capability, Permission::UnloadPlugin)?;
+        info!("🔌 Unloading plugin {:?} for {}", handle, capability.principal());
+        self.unload_plugin(handle)
+    }
+
+    /// 注册进程内插件
+    pub fn register_plugin(&mut self, plugin: Box<dyn Plugin>) -> Result<PluginHandle> {
+        self.plugin_manager.register_plugin(plugin)
+    }
+
     /// 卸载插件
     pub fn unload_plugin(&mut self, handle: PluginHandle) -> Result<()> {
         self.plugin_manager.unload_plugin(handle)
     }
 
-    fn generate_cache_key(&self, meta: &dubhe_adapter::ContractMeta) -> String {
-        // 简化实现，避免依赖问题
+    /// 插件只产出 RISC-V 代码，其余元数据与内置编译器保持一致
+    async fn compile_with_plugin(
+        &self,
+        handle: PluginHandle,
+        meta: &dubhe_adapter::ContractMeta,
+    ) -> Result<CompiledContract> {
+        let config = self.compiler.config();
+        let risc_v_code = self
+            .plugin_manager
+            .compile_async(handle, &meta.bytecode, config, |percent| {
+                debug!("Plugin compile of {}: {}%", meta.address, percent)
+            })
+            .await?;
+
+        Ok(CompiledContract {
+            original_address: meta.address.clone(),
+            source_type: meta.contract_type.clone(),
+            risc_v_code,
+            artifact: ArtifactKind::NativeRiscV,
+            entry_points: vec!["main".to_string()],
+            metadata: ContractMetadata {
+                gas_metering: config.enable_gas_metering,
+                memory_limit: 64 * 1024 * 1024,
+                stack_limit: 1024 * 1024,
+                call_depth_limit: 1024,
+                exports: std::collections::HashMap::new(),
+                storage_access: None,
+            },
+            compiled_at: chrono::Utc::now().timestamp() as u64,
+        })
+    }
+
+    /// 缓存键：地址 + 合约类型 + 字节码哈希与编译配置的 SHA-256
+    ///
+    /// 同一地址重新发布（即使字节码长度相同）也会得到新的键
+    pub fn generate_cache_key(&self, meta: &dubhe_adapter::ContractMeta) -> String {
+        self.cache_key(
+            &meta.address,
+            &meta.contract_type,
+            &ArtifactOrigin::new(meta).bytecode_hash,
+        )
+    }
+
+    /// 只依赖源字节码哈希，导入的产物无需原始字节码即可算出缓存键
+    fn cache_key(
+        &self,
+        address: &str,
+        contract_type: &dubhe_adapter::ContractType,
+        bytecode_hash: &str,
+    ) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(bytecode_hash.as_bytes());
+        hasher.update(self.compiler_fingerprint.as_bytes());
         format!(
-            "{}-{}-{:?}",
-            meta.address,
-            meta.bytecode.len(),
-            meta.contract_type
+            "{}-{:?}-{}",
+            address,
+            contract_type,
+            hex::encode(hasher.finalize())
         )
     }
 }
+
+/// 不经缓存直接编译：已加载的插件优先于内置编译器，供后台重编译使用
+#[async_trait]
+impl Compiler for CodeLoader {
+    async fn compile(&self, meta: &dubhe_adapter::ContractMeta) -> Result<CompiledContract> {
+        match self.plugin_manager.find_plugin_for(&meta.contract_type) {
+            Some(handle) => {
+                info!(
+                    "Using plugin {:?} for {:?} contract {}",
+                    handle, meta.contract_type, meta.address
+                );
+                self.compile_with_plugin(handle, meta).await
+            }
+            None => self.compile_builtin(meta).await,
+        }
+    }
+}
+
+impl CacheKeySource for CodeLoader {
+    fn cache_key(&self, meta: &dubhe_adapter::ContractMeta) -> String {
+        self.generate_cache_key(meta)
+    }
+}
+
+/// 凭证必须正是该操作所需的权限
+fn require_capability(capability: &Capability, required: Permission) -> Result<()> {
+    if capability.permission() != required {
+        return Err(LoaderError::CapabilityMismatch {
+            granted: capability.permission(),
+            required,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dubhe_adapter::{ChainType, ContractMeta, ContractType};
+    use tempfile::tempdir;
+
+    fn package(bytecode: Vec<u8>) -> ContractMeta {
+        ContractMeta {
+            address: "0xpkg".to_string(),
+            chain_type: ChainType::Sui,
+            contract_type: ContractType::Move,
+            bytecode,
+            abi: None,
+            source_code: None,
+            compiler_version: None,
+            created_at: 0,
+            creator: None,
+            abi_source: None,
+            modules: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_republished_bytecode_is_not_a_cache_hit() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let loader = CodeLoader::with_cache_dir(temp_dir.path())?;
+
+        // 同地址、同长度、不同内容
+        let v1 = package(vec![1, 2, 3, 4]);
+        let v2 = package(vec![4, 3, 2, 1]);
+        assert_ne!(loader.generate_cache_key(&v1), loader.generate_cache_key(&v2));
+
+        loader.load_contract(&v1).await?;
+        loader.load_contract(&v2).await?;
+        let stats = loader.cache().stats().await;
+        assert_eq!(stats.hits, 0);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.disk_entries, 2);
+
+        loader.load_contract(&v1).await?;
+        assert_eq!(loader.cache().stats().await.hits, 1);
+
+        // 包升级后按地址失效
+        assert_eq!(loader.invalidate("0xpkg").await?, 2);
+        assert_eq!(loader.cache().stats().await.disk_entries, 0);
+        assert_eq!(loader.usage().ranked().len(), 0);
+
+        Ok(())
+    }
+
+    struct MovePlugin;
+
+    impl Plugin for MovePlugin {
+        fn name(&self) -> &str {
+            "move-riscv"
+        }
+
+        fn version(&self) -> &str {
+            "1.0.0"
+        }
+
+        fn capabilities(&self) -> PluginCapabilities {
+            PluginCapabilities {
+                contract_types: vec![ContractType::Move],
+            }
+        }
+
+        fn compile(&self, bytecode: &[u8], _config: &CompilationConfig) -> Result<Vec<u8>> {
+            Ok(bytecode.iter().rev().copied().collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plugin_selected_for_supported_contract_type() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let mut loader = CodeLoader::with_cache_dir(temp_dir.path())?;
+        let handle = loader.register_plugin(Box::new(MovePlugin))?;
+        assert_eq!(
+            loader.plugin_manager.find_plugin_for(&ContractType::Move),
+            Some(handle)
+        );
+        assert!(loader.plugin_manager.find_plugin_for(&ContractType::EVM).is_none());
+
+        let compiled = loader.load_contract(&package(vec![1, 2, 3])).await?;
+        assert_eq!(compiled.risc_v_code, vec![3, 2, 1]);
+
+        Ok(())
+    }
+
+    /// 统计编译次数的插件，空字节码编译失败
+    struct CountingPlugin {
+        compiles: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl Plugin for CountingPlugin {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn version(&self) -> &str {
+            "1.0.0"
+        }
+
+        fn capabilities(&self) -> PluginCapabilities {
+            PluginCapabilities {
+                contract_types: vec![ContractType::Move],
+            }
+        }
+
+        fn compile(&self, bytecode: &[u8], _config: &CompilationConfig) -> Result<Vec<u8>> {
+            self.compiles
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            // 拉长编译时间，让并发加载在编译期间到达
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            anyhow::ensure!(!bytecode.is_empty(), "empty package");
+            Ok(bytecode.to_vec())
+        }
+    }
+
+    fn counting_loader(dir: &Path) -> Result<(CodeLoader, Arc<std::sync::atomic::AtomicUsize>)> {
+        let compiles = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut loader = CodeLoader::with_cache_dir(dir)?;
+        loader.register_plugin(Box::new(CountingPlugin {
+            compiles: compiles.clone(),
+        }))?;
+        Ok((loader, compiles))
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_loads_compile_once() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let (loader, compiles) = counting_loader(temp_dir.path())?;
+        let loader = Arc::new(loader);
+        let meta = package(vec![1, 2, 3]);
+
+        let loads: Vec<_> = (0..50)
+            .map(|_| {
+                let loader = loader.clone();
+                let meta = meta.clone();
+                tokio::spawn(async move { loader.load_contract(&meta).await })
+            })
+            .collect();
+        for load in loads {
+            assert_eq!(load.await??.risc_v_code, vec![1, 2, 3]);
+        }
+
+        assert_eq!(compiles.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(loader.cache().stats().await.disk_entries, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_batch_load_deduplicates_and_isolates_failures() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let (loader, compiles) = counting_loader(temp_dir.path())?;
+        let mut other = package(vec![4, 5]);
+        other.address = "0xother".to_string();
+        let mut broken = package(vec![]);
+        broken.address = "0xbroken".to_string();
+        let metas = vec![
+            package(vec![1, 2, 3]),
+            broken,
+            other,
+            package(vec![1, 2, 3]),
+        ];
+
+        let results = loader.load_contracts_batch(&metas).await;
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].as_ref().unwrap().risc_v_code, vec![1, 2, 3]);
+        assert!(format!("{:#}", results[1].as_ref().unwrap_err()).contains("empty package"));
+        assert_eq!(results[2].as_ref().unwrap().risc_v_code, vec![4, 5]);
+        assert_eq!(results[3].as_ref().unwrap().risc_v_code, vec![1, 2, 3]);
+        assert_eq!(compiles.load(std::sync::atomic::Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    fn artifact_key(dir: &Path) -> Result<KeyHandle> {
+        use dubhe_security::{Keystore, Passphrase};
+
+        let keystore =
+            Keystore::open(dir, Passphrase::new("artifact test"))?.with_kdf_iterations(1_000);
+        keystore.generate("artifact")?;
+        Ok(Arc::new(keystore).handle("artifact")?)
+    }
+
+    #[tokio::test]
+    async fn test_artifact_export_import_round_trip() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let key = artifact_key(&temp_dir.path().join("keys"))?;
+        let trusted = [key.public_key()?];
+
+        let mut online = CodeLoader::with_cache_dir(temp_dir.path().join("online"))?;
+        online.register_plugin(Box::new(MovePlugin))?;
+        let meta = package(vec![1, 2, 3]);
+        online.load_contract(&meta).await?;
+        let path = temp_dir.path().join("0xpkg.artifact");
+        online
+            .export_artifact("0xpkg", &key)
+            .await?
+            .write_to(&path)?;
+        assert!(matches!(
+            online
+                .export_artifact("0xother", &key)
+                .await
+                .unwrap_err()
+                .downcast_ref::<LoaderError>(),
+            Some(LoaderError::ArtifactNotFound(_))
+        ));
+
+        // 离线节点没有插件，导入后加载直接命中缓存
+        let offline = CodeLoader::with_cache_dir(temp_dir.path().join("offline"))?;
+        let cache_key = offline.import_artifact(&path, &trusted, false).await?;
+        assert_eq!(cache_key, offline.generate_cache_key(&meta));
+        let compiled = offline.load_contract(&meta).await?;
+        assert_eq!(compiled.risc_v_code, vec![3, 2, 1]);
+        assert_eq!(offline.cache().stats().await.hits, 1);
+
+        // 未受信任的签名者
+        let err = offline
+            .import_artifact(&path, &[[7; 32]], false)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<LoaderError>(),
+            Some(LoaderError::UntrustedArtifactSigner(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tampered_artifact_is_rejected() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let key = artifact_key(&temp_dir.path().join("keys"))?;
+        let trusted = [key.public_key()?];
+        let mut online = CodeLoader::with_cache_dir(temp_dir.path().join("online"))?;
+        online.register_plugin(Box::new(MovePlugin))?;
+        online.load_contract(&package(vec![1, 2, 3])).await?;
+        let bundle = online.export_artifact("0xpkg", &key).await?;
+        let offline = CodeLoader::with_cache_dir(temp_dir.path().join("offline"))?;
+
+        // 改动产物代码后签名不再匹配
+        let path = temp_dir.path().join("tampered.artifact");
+        let mut tampered = bundle.clone();
+        let last = tampered.manifest.len() - 1;
+        tampered.manifest[last] ^= 0xff;
+        tampered.write_to(&path)?;
+        let err = offline
+            .import_artifact(&path, &trusted, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<LoaderError>(),
+            Some(LoaderError::InvalidArtifact(_))
+        ));
+        assert_eq!(offline.cache().stats().await.disk_entries, 0);
+
+        // 截断的文件
+        std::fs::write(&path, b"not an artifact")?;
+        assert!(offline
+            .import_artifact(&path, &trusted, false)
+            .await
+            .is_err());
+
+        // 其他编译器产出的包需要 force
+        let mut manifest = bundle.verify(&trusted)?;
+        manifest.compiler_version = "0.0.1".to_string();
+        manifest.compiler_fingerprint = "0.0.1|abi0".to_string();
+        ArtifactBundle::sign(&manifest, &key)?.write_to(&path)?;
+        let err = offline
+            .import_artifact(&path, &trusted, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<LoaderError>(),
+            Some(LoaderError::ArtifactCompilerMismatch { found, .. }) if found == "0.0.1"
+        ));
+        offline.import_artifact(&path, &trusted, true).await?;
+        assert_eq!(offline.cache().stats().await.disk_entries, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_plugin_loading_requires_load_capability() -> Result<()> {
+        use dubhe_security::{AccessControl, AccessControlConfig, Principal, PrincipalConfig, Role};
+
+        let temp_dir = tempdir()?;
+        let mut loader = CodeLoader::with_cache_dir(temp_dir.path())?;
+        let access = AccessControl::new(&AccessControlConfig {
+            principals: vec![PrincipalConfig {
+                api_key: "ops".to_string(),
+                role: Role::Operator,
+            }],
+        });
+        let operator = Principal::ApiKey("ops".to_string());
+
+        assert!(access.authorize(&operator, Permission::LoadPlugin).is_err());
+        let capability = access.authorize(&operator, Permission::InvalidateCache)?;
+        let err = loader
+            .load_plugin_authorized("/nonexistent/plugin.so", &capability)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<LoaderError>(),
+            Some(LoaderError::CapabilityMismatch { .. })
+        ));
+        Ok(())
+    }
+}
@@ -4,17 +4,25 @@
 //!
 //! 特性：
 //! - 基于 LLVM 后端的编译管道
-//! - 支持 Sui Move 包和模块
+//! - 支持 Sui 与 Aptos Move 包和模块
 //! - 直接生成 RISC-V 机器码
 //! - 集成 gas 计量和内存管理
 
 use anyhow::Result;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::path::Path;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
-use crate::types::{CompiledContract, ContractMetadata};
-use dubhe_adapter::{ContractMeta, ContractType};
+use crate::abi::*;
+use crate::cache::CompilationCache;
+use crate::error::LoaderError;
+use crate::riscv;
+use crate::types::{
+    ArtifactKind, CompiledContract, ContractMetadata, FunctionSignature, Mutability, ParamType,
+};
+use dubhe_adapter::{ContractMeta, ContractType, ModuleArtifact};
 
 /// Move 到 RISC-V 编译器
 ///
@@ -60,55 +68,207 @@ impl MoveToRiscVCompiler {
         Ok(Self { config })
     }
 
+    pub fn config(&self) -> &MoveCompilerConfig {
+        &self.config
+    }
+
     /// 编译 Sui Move 包到 RISC-V
     pub async fn compile_sui_package(
         &self,
         package_meta: &ContractMeta,
     ) -> Result<CompiledContract> {
-        info!("Compiling Sui Move package: {}", package_meta.address);
+        self.compile_package(package_meta, None).await
+    }
+
+    /// 编译 Sui Move 包，各模块的编译产物缓存在 `cache` 中
+    ///
+    /// 模块产物按模块字节码、ABI 与编译器标识寻址，重新发布时只重编译有变化的模块
+    pub async fn compile_sui_package_cached(
+        &self,
+        package_meta: &ContractMeta,
+        cache: &CompilationCache,
+        fingerprint: &str,
+    ) -> Result<CompiledContract> {
+        self.compile_package(package_meta, Some((cache, fingerprint)))
+            .await
+    }
+
+    async fn compile_package(
+        &self,
+        package_meta: &ContractMeta,
+        cache: Option<(&CompilationCache, &str)>,
+    ) -> Result<CompiledContract> {
+        info!(
+            "Compiling {:?} Move package: {}",
+            package_meta.chain_type, package_meta.address
+        );
 
         // 1. 解析 Move 包结构
         let package_info = self.parse_move_package(package_meta)?;
 
-        // 2. 编译到 stackless bytecode
-        let stackless_bytecode = self.compile_to_stackless_bytecode(&package_info)?;
+        // 没有入口函数时编译占位程序
+        if package_info.entry_count() == 0 {
+            let stackless_bytecode = self.compile_to_stackless_bytecode(&package_info)?;
+            let riscv_code = self.compile_to_riscv(&stackless_bytecode).await?;
+            let metadata = self.generate_metadata(&riscv_code)?;
+            return Ok(CompiledContract {
+                original_address: package_meta.address.clone(),
+                source_type: ContractType::Move,
+                risc_v_code: riscv_code,
+                artifact: ArtifactKind::NativeRiscV,
+                entry_points: vec!["main".to_string()],
+                metadata,
+                compiled_at: chrono::Utc::now().timestamp() as u64,
+            });
+        }
 
-        // 3. 编译到 RISC-V
-        let riscv_code = self.compile_to_riscv(&stackless_bytecode).await?;
+        // 2. 逐模块编译入口函数体
+        let mut compiled_modules = Vec::with_capacity(package_info.modules.len());
+        for module in &package_info.modules {
+            compiled_modules.push(
+                self.compile_module(&package_info.package_id, module, cache)
+                    .await?,
+            );
+        }
+
+        // 3. 链接分派桩与各模块函数体
+        let riscv_code = self.link(&compiled_modules)?;
 
         // 4. 生成元数据
-        let metadata = self.generate_metadata(&riscv_code)?;
+        let mut metadata = self.generate_metadata(&riscv_code)?;
+        let entry_points = compiled_modules
+            .iter()
+            .flat_map(|module| module.entry_points.iter().cloned())
+            .collect();
+        metadata.exports = compiled_modules
+            .into_iter()
+            .flat_map(|module| module.metadata.exports)
+            .collect();
 
         Ok(CompiledContract {
             original_address: package_meta.address.clone(),
             source_type: ContractType::Move,
             risc_v_code: riscv_code,
-            entry_points: vec!["main".to_string()],
+            artifact: ArtifactKind::NativeRiscV,
+            entry_points,
             metadata,
             compiled_at: chrono::Utc::now().timestamp() as u64,
         })
     }
 
     fn parse_move_package(&self, meta: &ContractMeta) -> Result<MovePackageInfo> {
-        // 从 ABI 解析包信息
-        let _abi_data = match &meta.abi {
-            Some(abi) => {
-                info!("Parsing Move package from ABI ({} bytes)", abi.len());
-                // TODO: 实际解析 ABI
-                serde_json::Value::Null
+        let mut modules = Vec::new();
+        if meta.modules.is_empty() {
+            // ABI 为模块名 → 规范化模块（Sui）或模块 ABI（Aptos）
+            let abi_data = match &meta.abi {
+                Some(abi) => {
+                    info!("Parsing Move package from ABI ({} bytes)", abi.len());
+                    serde_json::from_str(abi).unwrap_or(Value::Null)
+                }
+                None => {
+                    warn!("No ABI provided, using placeholder");
+                    Value::Null
+                }
+            };
+
+            if let Some(abi_modules) = abi_data.as_object() {
+                let mut names: Vec<&String> = abi_modules.keys().collect();
+                names.sort();
+                for name in names {
+                    modules.extend(parse_move_module(name, &[], &abi_modules[name])?);
+                }
             }
-            None => {
-                warn!("No ABI provided, using placeholder");
-                serde_json::Value::Null
+        } else {
+            info!("Parsing Move package with {} modules", meta.modules.len());
+            let mut artifacts: Vec<&ModuleArtifact> = meta.modules.iter().collect();
+            artifacts.sort_by(|a, b| a.name.cmp(&b.name));
+            for artifact in artifacts {
+                let abi = artifact
+                    .abi
+                    .as_deref()
+                    .and_then(|abi| serde_json::from_str(abi).ok())
+                    .unwrap_or(Value::Null);
+                modules.extend(parse_move_module(&artifact.name, &artifact.bytecode, &abi)?);
             }
-        };
+        }
 
         Ok(MovePackageInfo {
             package_id: meta.address.clone(),
-            modules: vec!["main".to_string()],
+            modules,
         })
     }
 
+    /// 编译单个模块的入口函数体
+    ///
+    /// 函数体与所在位置及分派序号无关，缓存的模块产物可原样链接进任意包
+    async fn compile_module(
+        &self,
+        package_id: &str,
+        module: &MoveModuleInfo,
+        cache: Option<(&CompilationCache, &str)>,
+    ) -> Result<CompiledContract> {
+        let cached =
+            cache.map(|(cache, fingerprint)| (cache, self.module_cache_key(module, fingerprint)));
+        if let Some((cache, key)) = &cached {
+            if let Some(compiled) = cache.get(key).await? {
+                debug!("Move module {} loaded from cache", module.name);
+                return Ok(compiled);
+            }
+        }
+
+        info!(
+            "Compiling Move module {}::{} ({} entry functions)",
+            package_id,
+            module.name,
+            module.entry_functions.len()
+        );
+        let mut words = Vec::new();
+        for entry in &module.entry_functions {
+            words.extend(self.compile_entry_body(entry));
+        }
+        let riscv_code = riscv::assemble(&words);
+        let mut metadata = self.generate_metadata(&riscv_code)?;
+        metadata.exports = module
+            .entry_functions
+            .iter()
+            .map(|entry| (entry.name.clone(), entry.clone()))
+            .collect();
+
+        let compiled = CompiledContract {
+            original_address: package_id.to_string(),
+            source_type: ContractType::Move,
+            risc_v_code: riscv_code,
+            artifact: ArtifactKind::NativeRiscV,
+            entry_points: module
+                .entry_functions
+                .iter()
+                .map(|entry| entry.name.clone())
+                .collect(),
+            metadata,
+            compiled_at: chrono::Utc::now().timestamp() as u64,
+        };
+        if let Some((cache, key)) = &cached {
+            cache.put(key, &compiled).await?;
+        }
+        Ok(compiled)
+    }
+
+    /// 模块产物的缓存键：模块名 + 模块字节码、ABI、编译配置与编译器标识的 SHA-256
+    ///
+    /// 不含包地址，升级后地址变化的包也能复用未改动的模块
+    fn module_cache_key(&self, module: &MoveModuleInfo, fingerprint: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(&module.bytecode);
+        hasher.update(module.abi.as_bytes());
+        hasher.update(format!("{:?}", self.config).as_bytes());
+        hasher.update(fingerprint.as_bytes());
+        format!(
+            "move-module-{}-{}",
+            module.name,
+            hex::encode(hasher.finalize())
+        )
+    }
+
     fn compile_to_stackless_bytecode(
         &self,
         package: &MovePackageInfo,
@@ -161,6 +321,113 @@ impl MoveToRiscVCompiler {
         Ok(riscv_code)
     }
 
+    /// 链接入口分派桩与各模块函数体
+    ///
+    /// 分派桩把调用输入复制到 `sp + ENTRY_INPUT_OFFSET`，读取序号后跳转到对应函数体；
+    /// 序号不存在或输入不足时以 `EXIT_UNKNOWN_ENTRY` 退出。序号按模块顺序连续编号，
+    /// 同一模块内的函数体等长
+    fn link(&self, modules: &[CompiledContract]) -> Result<Vec<u8>> {
+        use riscv::*;
+
+        // 各函数体相对函数体区起点的字节偏移
+        let mut bodies = Vec::new();
+        let mut offset = 0;
+        for module in modules {
+            let count = module.entry_points.len();
+            if count == 0 {
+                continue;
+            }
+            let code_len = module.risc_v_code.len();
+            if code_len % (count * 4) != 0 {
+                return Err(LoaderError::CompilationFailed(format!(
+                    "Module artifact has {} bytes for {} entry functions",
+                    code_len, count
+                ))
+                .into());
+            }
+            let body_len = code_len / count;
+            bodies.extend((0..count).map(|index| offset + index * body_len));
+            offset += code_len;
+        }
+
+        let buffer = ENTRY_INPUT_OFFSET;
+        let mut words = vec![addi(SP, SP, -(buffer + MAX_ENTRY_INPUT as i32))];
+        words.extend([addi(A0, SP, buffer), addi(A2, ZERO, 0)]);
+        words.extend(li(A1, MAX_ENTRY_INPUT as i32));
+        words.extend(syscall(SYS_LOAD_INPUT));
+        words.push(addi(S1, A0, 0));
+
+        // 输入不足一个序号时退出
+        words.push(addi(T1, ZERO, ENTRY_INDEX_SIZE as i32));
+        words.push(bltu(S1, T1, 8));
+        let skip_unknown = words.len();
+        words.push(0);
+        let unknown = words.len();
+        words.extend(li(A0, EXIT_UNKNOWN_ENTRY as i32));
+        words.extend(syscall(SYS_EXIT));
+        words[skip_unknown] = jal(ZERO, ((words.len() - skip_unknown) * 4) as i32);
+
+        // 小端 u32 序号，函数体从 t0 读取
+        words.push(lbu(T0, SP, buffer));
+        for byte in 1..ENTRY_INDEX_SIZE as i32 {
+            words.push(lbu(T1, SP, buffer + byte));
+            words.push(slli(T1, T1, 8 * byte as u32));
+            words.push(add(T0, T0, T1));
+        }
+
+        // 分派表：jal 的跳转范围足以覆盖任意数量的函数体
+        let mut jumps = Vec::with_capacity(bodies.len());
+        for index in 0..bodies.len() {
+            words.extend(li(T1, index as i32));
+            words.push(bne(T0, T1, 8));
+            jumps.push(words.len());
+            words.push(0);
+        }
+        words.push(jal(ZERO, -(((words.len() - unknown) * 4) as i32)));
+
+        let base = words.len();
+        for (jump, body) in jumps.into_iter().zip(&bodies) {
+            words[jump] = jal(ZERO, ((base - jump) * 4 + body) as i32);
+        }
+
+        let mut code = riscv::assemble(&words);
+        for module in modules {
+            code.extend_from_slice(&module.risc_v_code);
+        }
+        info!(
+            "Linked {} modules with {} entry functions ({} bytes)",
+            modules.len(),
+            bodies.len(),
+            code.len()
+        );
+        Ok(code)
+    }
+
+    /// 入口函数体
+    ///
+    /// 在 Move 字节码翻译实现之前，函数体输出分派序号（1 字节，取自 t0）与收到的 BCS 参数，
+    /// 调用方据此确认分派与参数编码
+    fn compile_entry_body(&self, entry: &FunctionSignature) -> Vec<u32> {
+        use riscv::*;
+
+        let args = ENTRY_INPUT_OFFSET + ENTRY_INDEX_SIZE as i32;
+        let mut words = Vec::new();
+        if self.config.enable_gas_metering {
+            // gas 检查占位（nop）
+            words.push(addi(ZERO, ZERO, 0));
+        }
+        words.push(sb(T0, SP, 0));
+        words.extend([addi(A0, SP, 0), addi(A1, ZERO, 1)]);
+        words.extend(syscall(SYS_WRITE_OUTPUT));
+        words.extend([addi(A0, SP, args), addi(A1, S1, -(ENTRY_INDEX_SIZE as i32))]);
+        words.extend(syscall(SYS_WRITE_OUTPUT));
+        words.push(addi(A0, ZERO, 0));
+        words.extend(syscall(SYS_EXIT));
+
+        debug!("Compiled entry {}", entry.name);
+        words
+    }
+
     fn compile_instruction(&self, instruction: &StacklessInstruction) -> Result<Vec<u8>> {
         match instruction {
             StacklessInstruction::LoadConst(value) => {
@@ -172,8 +439,8 @@ impl MoveToRiscVCompiler {
                 Ok(vec![0x13, 0x00, 0x00, 0x00])
             }
             StacklessInstruction::Return => {
-                // RISC-V: ebreak (simplified return)
-                Ok(vec![0x73, 0x00, 0x10, 0x00])
+                // 简化的返回：将调用输入作为返回值写出并退出
+                Ok(riscv::assemble(&riscv::echo_program()))
             }
         }
     }
@@ -199,6 +466,7 @@ impl MoveToRiscVCompiler {
             stack_limit: 1 * 1024 * 1024,   // 1MB
             call_depth_limit: 1024,
             exports: HashMap::new(),
+            storage_access: None,
         })
     }
 }
@@ -207,7 +475,265 @@ impl MoveToRiscVCompiler {
 #[derive(Debug)]
 struct MovePackageInfo {
     package_id: String,
-    modules: Vec<String>,
+    /// 按模块名排序，入口函数在包内的位置即分派序号
+    modules: Vec<MoveModuleInfo>,
+}
+
+impl MovePackageInfo {
+    fn entry_count(&self) -> usize {
+        self.modules
+            .iter()
+            .map(|module| module.entry_functions.len())
+            .sum()
+    }
+}
+
+/// Move 模块信息
+#[derive(Debug)]
+struct MoveModuleInfo {
+    name: String,
+    bytecode: Vec<u8>,
+    /// 规范化模块的 JSON，参与缓存键计算
+    abi: String,
+    /// 按函数名排序的入口函数
+    entry_functions: Vec<FunctionSignature>,
+}
+
+/// 由规范化模块解析入口函数；没有 `exposedFunctions` 的模块返回 None
+///
+/// Aptos 模块 ABI（`exposed_functions` 数组）交给 [`parse_aptos_module`]
+fn parse_move_module(
+    name: &str,
+    bytecode: &[u8],
+    normalized: &Value,
+) -> std::result::Result<Option<MoveModuleInfo>, LoaderError> {
+    if let Some(functions) = normalized["exposed_functions"].as_array() {
+        return parse_aptos_module(name, bytecode, normalized, functions).map(Some);
+    }
+    let Some(functions) = normalized["exposedFunctions"].as_object() else {
+        return Ok(None);
+    };
+
+    let mut function_names: Vec<&String> = functions.keys().collect();
+    function_names.sort();
+    let mut entry_functions = Vec::new();
+    for function in function_names {
+        let definition = &functions[function];
+        let callable = definition["isEntry"].as_bool().unwrap_or(false)
+            || definition["visibility"] == "Public";
+        if callable {
+            entry_functions.push(parse_entry_function(name, function, definition)?);
+        }
+    }
+
+    Ok(Some(MoveModuleInfo {
+        name: name.to_string(),
+        bytecode: bytecode.to_vec(),
+        abi: normalized.to_string(),
+        entry_functions,
+    }))
+}
+
+/// 由规范化函数定义生成入口签名，`&mut TxContext` 由运行时提供，不计入参数
+fn parse_entry_function(
+    module: &str,
+    function: &str,
+    definition: &Value,
+) -> std::result::Result<FunctionSignature, LoaderError> {
+    let name = format!("{}::{}", module, function);
+    let mut inputs = Vec::new();
+    for param in definition["parameters"].as_array().into_iter().flatten() {
+        match move_param_type(param) {
+            Some(Some(param_type)) => inputs.push(param_type),
+            Some(None) => {}
+            None => {
+                return Err(LoaderError::CompilationFailed(format!(
+                    "Unsupported parameter type in {}: {}",
+                    name, param
+                )))
+            }
+        }
+    }
+
+    Ok(FunctionSignature {
+        name,
+        inputs,
+        outputs: vec![],
+        mutability: Mutability::NonPayable,
+    })
+}
+
+/// 由 Aptos 模块 ABI 解析入口函数，参数为 `u64`、`vector<u8>`、`0x1::string::String` 形式的类型字符串
+fn parse_aptos_module(
+    name: &str,
+    bytecode: &[u8],
+    abi: &Value,
+    functions: &[Value],
+) -> std::result::Result<MoveModuleInfo, LoaderError> {
+    let mut callable: Vec<&Value> = functions
+        .iter()
+        .filter(|function| {
+            function["is_entry"].as_bool().unwrap_or(false) || function["visibility"] == "public"
+        })
+        .collect();
+    callable.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+
+    let mut entry_functions = Vec::with_capacity(callable.len());
+    for definition in callable {
+        let function = definition["name"].as_str().ok_or_else(|| {
+            LoaderError::CompilationFailed(format!("Function without name in module {}", name))
+        })?;
+        let name = format!("{}::{}", name, function);
+        let mut inputs = Vec::new();
+        for param in definition["params"].as_array().into_iter().flatten() {
+            match param.as_str().and_then(aptos_param_type) {
+                Some(Some(param_type)) => inputs.push(param_type),
+                Some(None) => {}
+                None => {
+                    return Err(LoaderError::CompilationFailed(format!(
+                        "Unsupported parameter type in {}: {}",
+                        name, param
+                    )))
+                }
+            }
+        }
+        entry_functions.push(FunctionSignature {
+            name,
+            inputs,
+            outputs: vec![],
+            mutability: Mutability::NonPayable,
+        });
+    }
+
+    Ok(MoveModuleInfo {
+        name: name.to_string(),
+        bytecode: bytecode.to_vec(),
+        abi: abi.to_string(),
+        entry_functions,
+    })
+}
+
+/// Aptos 类型字符串到参数类型；`&signer` 由运行时以交易发送方提供
+fn aptos_param_type(param: &str) -> Option<Option<ParamType>> {
+    let param = param.trim();
+    if param == "&signer" || param == "signer" {
+        return Some(None);
+    }
+    if let Some(inner) = param
+        .strip_prefix("&mut ")
+        .or_else(|| param.strip_prefix('&'))
+    {
+        // 对象引用以对象 ID 传入
+        return struct_tag(inner).map(|_| Some(ParamType::Address));
+    }
+
+    let param_type = match param {
+        "bool" => ParamType::Bool,
+        "u8" => ParamType::Uint(8),
+        "u16" => ParamType::Uint(16),
+        "u32" => ParamType::Uint(32),
+        "u64" => ParamType::Uint(64),
+        "u128" => ParamType::Uint(128),
+        "u256" => ParamType::Uint(256),
+        "address" => ParamType::Address,
+        _ => {
+            if let Some(inner) = param
+                .strip_prefix("vector<")
+                .and_then(|rest| rest.strip_suffix('>'))
+            {
+                return match aptos_param_type(inner)? {
+                    Some(ParamType::Uint(8)) => Some(Some(ParamType::Bytes)),
+                    Some(element) => Some(Some(ParamType::Array(Box::new(element)))),
+                    None => None,
+                };
+            }
+            let (address, module, name) = struct_tag(param)?;
+            if short_address(address) == "0x1"
+                && (module == "string" || module == "ascii")
+                && name == "String"
+            {
+                return Some(Some(ParamType::String));
+            }
+            ParamType::Address
+        }
+    };
+    Some(Some(param_type))
+}
+
+/// 拆分 `address::module::Name<...>`，泛型参数（如 `T0`）等非结构体类型返回 None
+fn struct_tag(tag: &str) -> Option<(&str, &str, &str)> {
+    let base = tag.split_once('<').map_or(tag, |(base, _)| base);
+    let mut parts = base.splitn(3, "::");
+    let (address, module, name) = (parts.next()?, parts.next()?, parts.next()?);
+    let valid = address.starts_with("0x")
+        && address.len() > 2
+        && address[2..].chars().all(|c| c.is_ascii_hexdigit())
+        && !module.is_empty()
+        && !name.is_empty();
+    valid.then_some((address, module, name))
+}
+
+/// 去掉前导零的地址：Sui 使用完整的 32 字节地址，Aptos 常用 `0x1` 等短地址
+fn short_address(address: &str) -> String {
+    let digits = address.trim_start_matches("0x").trim_start_matches('0');
+    format!("0x{}", digits)
+}
+
+/// 规范化 Move 类型到参数类型；`Some(None)` 表示由运行时提供的参数，`None` 表示不支持
+fn move_param_type(param: &Value) -> Option<Option<ParamType>> {
+    if let Some(primitive) = param.as_str() {
+        let param_type = match primitive {
+            "Bool" => ParamType::Bool,
+            "U8" => ParamType::Uint(8),
+            "U16" => ParamType::Uint(16),
+            "U32" => ParamType::Uint(32),
+            "U64" => ParamType::Uint(64),
+            "U128" => ParamType::Uint(128),
+            "U256" => ParamType::Uint(256),
+            "Address" | "Signer" => ParamType::Address,
+            _ => return None,
+        };
+        return Some(Some(param_type));
+    }
+
+    if let Some(inner) = param.get("Vector") {
+        return match move_param_type(inner)? {
+            Some(ParamType::Uint(8)) => Some(Some(ParamType::Bytes)),
+            Some(element) => Some(Some(ParamType::Array(Box::new(element)))),
+            None => None,
+        };
+    }
+
+    if let Some(inner) = param
+        .get("Reference")
+        .or_else(|| param.get("MutableReference"))
+    {
+        if is_struct(inner, "0x2", "tx_context", "TxContext") {
+            return Some(None);
+        }
+        // 对象引用以对象 ID 传入
+        return inner.get("Struct").map(|_| Some(ParamType::Address));
+    }
+
+    if param.get("Struct").is_some() {
+        if is_struct(param, "0x1", "string", "String") || is_struct(param, "0x1", "ascii", "String")
+        {
+            return Some(Some(ParamType::String));
+        }
+        return Some(Some(ParamType::Address));
+    }
+
+    None
+}
+
+fn is_struct(param: &Value, address: &str, module: &str, name: &str) -> bool {
+    let value = &param["Struct"];
+    value["address"]
+        .as_str()
+        .map(|addr| short_address(addr) == address)
+        .unwrap_or(false)
+        && value["module"] == module
+        && value["name"] == name
 }
 
 /// 无栈字节码
@@ -282,6 +808,8 @@ mod tests {
             compiler_version: Some("move".to_string()),
             created_at: 1234567890,
             creator: None,
+            abi_source: None,
+            modules: vec![],
         };
 
         let result = compiler.compile_sui_package(&mock_meta).await;
@@ -292,4 +820,198 @@ mod tests {
         assert!(!compiled.risc_v_code.is_empty());
         assert!(compiled.metadata.gas_metering);
     }
+
+    fn module(name: &str, bytecode: Vec<u8>, functions: &[&str]) -> ModuleArtifact {
+        let exposed: serde_json::Map<String, Value> = functions
+            .iter()
+            .map(|function| {
+                (
+                    function.to_string(),
+                    serde_json::json!({
+                        "isEntry": true,
+                        "visibility": "Public",
+                        "parameters": ["U64"],
+                    }),
+                )
+            })
+            .collect();
+        ModuleArtifact {
+            name: name.to_string(),
+            bytecode,
+            abi: Some(serde_json::json!({ "exposedFunctions": exposed }).to_string()),
+        }
+    }
+
+    fn two_module_package(modules: Vec<ModuleArtifact>) -> ContractMeta {
+        ContractMeta {
+            address: "0xpkg".to_string(),
+            chain_type: dubhe_adapter::ChainType::Sui,
+            contract_type: ContractType::Move,
+            bytecode: modules
+                .iter()
+                .flat_map(|module| module.bytecode.clone())
+                .collect(),
+            abi: None,
+            source_code: None,
+            compiler_version: Some("move".to_string()),
+            created_at: 0,
+            creator: None,
+            abi_source: None,
+            modules,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multi_module_package_recompiles_only_changed_module() -> Result<()> {
+        let compiler = MoveToRiscVCompiler::new(MoveCompilerConfig {
+            target_arch: RiscVTarget::RV64IMC,
+            optimization_level: OptimizationLevel::Speed,
+            enable_gas_metering: true,
+            enable_debug_info: false,
+            stackless_bytecode: true,
+        })?;
+        let temp_dir = tempfile::tempdir()?;
+        let cache = CompilationCache::new(temp_dir.path(), Default::default())?;
+
+        // 模块按名称排序，序号跨模块连续
+        let v1 = two_module_package(vec![
+            module("pool", vec![2], &["swap"]),
+            module("coin", vec![1], &["mint", "burn"]),
+        ]);
+        let compiled = compiler
+            .compile_sui_package_cached(&v1, &cache, "fp")
+            .await?;
+        assert_eq!(
+            compiled.entry_points,
+            vec!["coin::burn", "coin::mint", "pool::swap"]
+        );
+        assert_eq!(compiled.metadata.exports.len(), 3);
+        assert_eq!(cache.stats().await.misses, 2);
+
+        // 缓存链接的产物与直接编译一致
+        let uncached = compiler.compile_sui_package(&v1).await?;
+        assert_eq!(compiled.risc_v_code, uncached.risc_v_code);
+
+        // 只重新发布 pool 模块
+        let v2 = two_module_package(vec![
+            module("coin", vec![1], &["mint", "burn"]),
+            module("pool", vec![3], &["swap", "quote"]),
+        ]);
+        let compiled = compiler
+            .compile_sui_package_cached(&v2, &cache, "fp")
+            .await?;
+        let stats = cache.stats().await;
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 3);
+        assert_eq!(
+            compiled.entry_points,
+            vec!["coin::burn", "coin::mint", "pool::quote", "pool::swap"]
+        );
+        assert_eq!(
+            compiled.risc_v_code,
+            compiler.compile_sui_package(&v2).await?.risc_v_code
+        );
+
+        Ok(())
+    }
+
+    /// `/accounts/{address}/modules` 中单个模块的 ABI
+    const APTOS_MESSAGE_ABI: &str = r#"{
+        "address": "0x00000000000000000000000000000000000000000000000000000000000000a1",
+        "name": "message",
+        "friends": [],
+        "exposed_functions": [
+            {
+                "name": "set_message",
+                "visibility": "public",
+                "is_entry": true,
+                "is_view": false,
+                "generic_type_params": [],
+                "params": ["&signer", "0x1::string::String", "vector<u8>"],
+                "return": []
+            },
+            {
+                "name": "get_message",
+                "visibility": "public",
+                "is_entry": false,
+                "is_view": true,
+                "generic_type_params": [],
+                "params": ["address"],
+                "return": ["0x1::string::String"]
+            },
+            {
+                "name": "bump",
+                "visibility": "private",
+                "is_entry": false,
+                "is_view": false,
+                "generic_type_params": [],
+                "params": ["u64"],
+                "return": []
+            },
+            {
+                "name": "attach",
+                "visibility": "friend",
+                "is_entry": true,
+                "is_view": false,
+                "generic_type_params": [],
+                "params": ["&signer", "0x1::object::Object<0x1::object::ObjectCore>", "vector<u64>"],
+                "return": []
+            }
+        ],
+        "structs": []
+    }"#;
+
+    #[tokio::test]
+    async fn test_aptos_module_abi() -> Result<()> {
+        let compiler = MoveToRiscVCompiler::new(MoveCompilerConfig {
+            target_arch: RiscVTarget::RV64IMC,
+            optimization_level: OptimizationLevel::Speed,
+            enable_gas_metering: true,
+            enable_debug_info: false,
+            stackless_bytecode: true,
+        })?;
+        let meta = ContractMeta {
+            address: "0xa1".to_string(),
+            chain_type: dubhe_adapter::ChainType::Aptos,
+            ..two_module_package(vec![ModuleArtifact {
+                name: "message".to_string(),
+                bytecode: vec![0xa1, 0x1c, 0xeb, 0x0b],
+                abi: Some(APTOS_MESSAGE_ABI.to_string()),
+            }])
+        };
+
+        let compiled = compiler.compile_sui_package(&meta).await?;
+        assert_eq!(
+            compiled.entry_points,
+            vec![
+                "message::attach",
+                "message::get_message",
+                "message::set_message"
+            ]
+        );
+        let exports = &compiled.metadata.exports;
+        assert!(matches!(
+            exports["message::set_message"].inputs.as_slice(),
+            [ParamType::String, ParamType::Bytes]
+        ));
+        assert!(matches!(
+            exports["message::attach"].inputs.as_slice(),
+            [ParamType::Address, ParamType::Array(element)] if matches!(**element, ParamType::Uint(64))
+        ));
+
+        // 泛型参数与畸形类型返回错误而不是崩溃
+        for param in ["T0", "0x::m::S", "vector<", "&0x1::", "vector<T0>"] {
+            assert!(aptos_param_type(param).is_none(), "{}", param);
+        }
+        let generic = serde_json::json!({
+            "exposed_functions": [{
+                "name": "transfer",
+                "visibility": "public",
+                "is_entry": true,
+                "params": ["&signer", "address", "T0"],
+            }],
+        });
+        assert!(parse_move_module("coin", &[], &generic).is_err());
+        Ok(())
+    }
 }
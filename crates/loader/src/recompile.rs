@@ -0,0 +1,655 @@
+//! 后台重编译模块
+//!
+//! gas 计价表 / 编译器版本升级会让所有缓存产物同时失效。为避免部署后第一个
+//! 高峰期出现编译风暴，检测到版本变化后按历史使用频率从高到低排队，
+//! 仅在空闲时低优先级重编译，并逐条原子替换缓存项，进行中的加载始终读到完整产物。
+//!
+//! 使用统计随编译缓存持久化，节点重启后仍能按历史频率排队。
+
+use anyhow::Result;
+use dubhe_adapter::ContractMeta;
+use dubhe_observability::NodeMetrics;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use crate::cache::CompilationCache;
+use crate::compiler::Compiler;
+use crate::types::{ArtifactOrigin, ArtifactVersion};
+
+/// 合约使用频率统计
+///
+/// 记录每个缓存键的加载次数和原始元数据，重编译按次数排序
+#[derive(Debug, Default)]
+pub struct UsageTracker {
+    entries: std::sync::RwLock<HashMap<String, UsageEntry>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UsageEntry {
+    meta: ContractMeta,
+    hits: u64,
+    last_used: u64,
+    /// 上次持久化后是否有变化
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl UsageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从编译缓存恢复上次运行持久化的使用统计
+    pub fn restore(cache: &CompilationCache) -> Result<Self> {
+        let entries: HashMap<String, UsageEntry> = cache.usage_records()?.into_iter().collect();
+        if !entries.is_empty() {
+            info!(
+                "Restored usage statistics for {} cached artifacts",
+                entries.len()
+            );
+        }
+        Ok(Self {
+            entries: std::sync::RwLock::new(entries),
+        })
+    }
+
+    /// 将上次持久化后有变化、且仍在缓存中的统计写入编译缓存，返回写入条数
+    pub async fn persist(&self, cache: &CompilationCache) -> Result<usize> {
+        let dirty: Vec<(String, UsageEntry)> = self
+            .entries
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, entry)| entry.dirty)
+            .map(|(key, entry)| (key.clone(), entry.clone()))
+            .collect();
+
+        // 尚未写入缓存（编译中）或已被淘汰的键不持久化，保持 dirty 留待下次
+        let mut records = Vec::with_capacity(dirty.len());
+        for (key, entry) in dirty {
+            if cache.contains(&key).await {
+                records.push((key, entry));
+            }
+        }
+        if records.is_empty() {
+            return Ok(0);
+        }
+        cache.put_usage_records(&records)?;
+
+        // 写入期间又有加载的条目保持 dirty
+        let mut entries = self.entries.write().unwrap();
+        for (key, written) in &records {
+            if let Some(entry) = entries.get_mut(key) {
+                if entry.hits == written.hits {
+                    entry.dirty = false;
+                }
+            }
+        }
+        Ok(records.len())
+    }
+
+    /// 记录一次加载
+    pub fn record(&self, key: &str, meta: &ContractMeta) {
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let mut entries = self.entries.write().unwrap();
+        let entry = entries.entry(key.to_string()).or_insert_with(|| UsageEntry {
+            meta: meta.clone(),
+            hits: 0,
+            last_used: now,
+            dirty: true,
+        });
+        entry.hits += 1;
+        entry.last_used = now;
+        entry.dirty = true;
+    }
+
+    /// 缓存键改变后（编译器配置升级）将统计迁移到新键，与新键已有的统计合并
+    pub fn rebind(&self, old: &str, new: &str) {
+        let mut entries = self.entries.write().unwrap();
+        let Some(moved) = entries.remove(old) else {
+            return;
+        };
+        let entry = entries.entry(new.to_string()).or_insert_with(|| UsageEntry {
+            hits: 0,
+            ..moved.clone()
+        });
+        entry.hits += moved.hits;
+        entry.last_used = entry.last_used.max(moved.last_used);
+        entry.dirty = true;
+    }
+
+    /// 删除指定前缀的缓存键
+    pub fn forget_prefix(&self, prefix: &str) {
+        self.entries
+            .write()
+            .unwrap()
+            .retain(|key, _| !key.starts_with(prefix));
+    }
+
+    /// 某个缓存键的加载次数
+    pub fn hits(&self, key: &str) -> u64 {
+        self.entries
+            .read()
+            .unwrap()
+            .get(key)
+            .map(|e| e.hits)
+            .unwrap_or(0)
+    }
+
+    /// 按使用频率降序列出缓存键（次数相同时最近使用的优先）
+    pub fn ranked(&self) -> Vec<(String, ContractMeta)> {
+        let entries = self.entries.read().unwrap();
+        let mut ranked: Vec<_> = entries.iter().collect();
+        ranked.sort_by(|a, b| {
+            b.1.hits
+                .cmp(&a.1.hits)
+                .then(b.1.last_used.cmp(&a.1.last_used))
+        });
+        ranked
+            .into_iter()
+            .map(|(key, entry)| (key.clone(), entry.meta.clone()))
+            .collect()
+    }
+}
+
+/// 当前编译器配置下合约的缓存键
+///
+/// 缓存键包含编译器指纹，升级后重编译的产物必须写到新键下，前台加载才能命中
+pub trait CacheKeySource: Send + Sync {
+    fn cache_key(&self, meta: &ContractMeta) -> String;
+}
+
+/// 负载门控：只有空闲时才允许后台重编译
+pub trait LoadGate: Send + Sync {
+    fn is_idle(&self) -> bool;
+}
+
+/// 进行中的合约加载计数
+#[derive(Debug, Default)]
+pub struct LoadActivity {
+    in_flight: AtomicUsize,
+}
+
+impl LoadActivity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 标记一次加载开始，guard 释放时结束
+    pub fn enter(&self) -> LoadActivityGuard<'_> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        LoadActivityGuard { activity: self }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+}
+
+impl LoadGate for LoadActivity {
+    fn is_idle(&self) -> bool {
+        self.in_flight() == 0
+    }
+}
+
+pub struct LoadActivityGuard<'a> {
+    activity: &'a LoadActivity,
+}
+
+impl Drop for LoadActivityGuard<'_> {
+    fn drop(&mut self) {
+        self.activity.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// 重编译配置
+#[derive(Debug, Clone)]
+pub struct RecompileConfig {
+    /// 非空闲时的轮询间隔
+    pub idle_poll_interval: Duration,
+    /// 每编译完一项后让出的时间
+    pub yield_interval: Duration,
+}
+
+impl Default for RecompileConfig {
+    fn default() -> Self {
+        Self {
+            idle_poll_interval: Duration::from_millis(50),
+            yield_interval: Duration::from_millis(1),
+        }
+    }
+}
+
+/// 重编译进度
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecompileProgress {
+    pub target_version: Option<ArtifactVersion>,
+    pub total: u64,
+    pub completed: u64,
+    pub failed: u64,
+    pub remaining: u64,
+}
+
+/// 后台重编译服务
+pub struct RecompilationService {
+    cache: Arc<CompilationCache>,
+    usage: Arc<UsageTracker>,
+    compiler: Arc<dyn Compiler + Send + Sync>,
+    keys: Arc<dyn CacheKeySource>,
+    gate: Arc<dyn LoadGate>,
+    config: RecompileConfig,
+    queue: Mutex<VecDeque<(String, ContractMeta)>>,
+    target_version: std::sync::RwLock<Option<ArtifactVersion>>,
+    total: AtomicU64,
+    completed: AtomicU64,
+    failed: AtomicU64,
+    running: AtomicBool,
+    metrics: Option<Arc<NodeMetrics>>,
+}
+
+impl RecompilationService {
+    pub fn new(
+        cache: Arc<CompilationCache>,
+        usage: Arc<UsageTracker>,
+        compiler: Arc<dyn Compiler + Send + Sync>,
+        keys: Arc<dyn CacheKeySource>,
+        gate: Arc<dyn LoadGate>,
+        config: RecompileConfig,
+    ) -> Self {
+        Self {
+            cache,
+            usage,
+            compiler,
+            keys,
+            gate,
+            config,
+            queue: Mutex::new(VecDeque::new()),
+            target_version: std::sync::RwLock::new(None),
+            total: AtomicU64::new(0),
+            completed: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            running: AtomicBool::new(false),
+            metrics: None,
+        }
+    }
+
+    /// 将重编译进度记录到共享的 Prometheus 指标
+    pub fn with_metrics(mut self, metrics: Arc<NodeMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// 对比缓存记录的版本，发生变化时按使用频率排队重编译
+    ///
+    /// 返回排队的条目数；版本未变化时返回 0
+    pub async fn detect_version_change(&self, current: &ArtifactVersion) -> Result<usize> {
+        let cached = self.cache.artifact_version()?;
+        if cached.as_ref() == Some(current) {
+            return Ok(0);
+        }
+
+        info!(
+            "Artifact version changed ({:?} -> {:?}), scheduling background recompilation",
+            cached, current
+        );
+
+        // 已被淘汰的条目无需重编译
+        let mut entries = Vec::new();
+        for (key, meta) in self.usage.ranked() {
+            if self.cache.contains(&key).await {
+                entries.push((key, meta));
+            }
+        }
+        let scheduled = entries.len();
+        {
+            let mut queue = self.queue.lock().await;
+            queue.clear();
+            queue.extend(entries);
+        }
+
+        *self.target_version.write().unwrap() = Some(current.clone());
+        self.total.store(scheduled as u64, Ordering::SeqCst);
+        self.completed.store(0, Ordering::SeqCst);
+        self.failed.store(0, Ordering::SeqCst);
+        self.record_metrics(scheduled);
+
+        // 队列为空时直接记录新版本
+        if scheduled == 0 {
+            self.cache.set_artifact_version(current)?;
+        }
+
+        Ok(scheduled)
+    }
+
+    /// 处理排队的重编译任务，直到队列为空
+    pub async fn run_pending(&self) -> Result<()> {
+        if self.running.swap(true, Ordering::SeqCst) {
+            debug!("Recompilation already running");
+            return Ok(());
+        }
+
+        let result = self.drain_queue().await;
+        self.running.store(false, Ordering::SeqCst);
+        result
+    }
+
+    /// 在后台运行重编译
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<Result<()>> {
+        tokio::spawn(async move { self.run_pending().await })
+    }
+
+    /// 当前进度
+    pub async fn progress(&self) -> RecompileProgress {
+        RecompileProgress {
+            target_version: self.target_version.read().unwrap().clone(),
+            total: self.total.load(Ordering::SeqCst),
+            completed: self.completed.load(Ordering::SeqCst),
+            failed: self.failed.load(Ordering::SeqCst),
+            remaining: self.queue.lock().await.len() as u64,
+        }
+    }
+
+    async fn drain_queue(&self) -> Result<()> {
+        loop {
+            // 等待空闲再取任务，负载高时不与前台加载争抢
+            while !self.gate.is_idle() {
+                tokio::time::sleep(self.config.idle_poll_interval).await;
+            }
+
+            let next = {
+                let mut queue = self.queue.lock().await;
+                queue.pop_front().map(|entry| (entry, queue.len()))
+            };
+            let ((key, meta), remaining) = match next {
+                Some(next) => next,
+                None => break,
+            };
+
+            // 编译期间不持有任何锁，缓存中仍是旧产物
+            match self.compiler.compile(&meta).await {
+                Ok(compiled) => {
+                    // 写到当前编译器指纹下的键，旧键及其使用统计随之删除
+                    let current = self.keys.cache_key(&meta);
+                    self.cache
+                        .put_with_origin(&current, &compiled, &ArtifactOrigin::new(&meta))
+                        .await?;
+                    if current != key {
+                        self.cache.remove(&key).await?;
+                        self.usage.rebind(&key, &current);
+                    }
+                    self.completed.fetch_add(1, Ordering::SeqCst);
+                    debug!("Recompiled cached artifact: {} -> {}", key, current);
+                }
+                Err(e) => {
+                    // 失败的条目保留旧产物，下次加载时按需编译
+                    warn!("Background recompilation failed for {}: {}", key, e);
+                    self.failed.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+            self.record_metrics(remaining);
+
+            tokio::time::sleep(self.config.yield_interval).await;
+        }
+
+        let target = self.target_version.read().unwrap().clone();
+        if let Some(version) = target {
+            self.cache.set_artifact_version(&version)?;
+            info!(
+                "Background recompilation finished: {} recompiled, {} failed",
+                self.completed.load(Ordering::SeqCst),
+                self.failed.load(Ordering::SeqCst)
+            );
+        }
+
+        Ok(())
+    }
+
+    fn record_metrics(&self, remaining: usize) {
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .recompile_completed
+                .set(self.completed.load(Ordering::SeqCst) as i64);
+            metrics
+                .recompile_failed
+                .set(self.failed.load(Ordering::SeqCst) as i64);
+            metrics.recompile_remaining.set(remaining as i64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ArtifactKind, CompiledContract, ContractMetadata, DEFAULT_CYCLES_PER_GAS};
+    use crate::CodeLoader;
+    use async_trait::async_trait;
+    use dubhe_adapter::{ChainType, ContractType};
+    use tempfile::tempdir;
+    use tokio::sync::Notify;
+
+    /// 记录编译顺序，首个编译阻塞直到放行
+    struct RecordingCompiler {
+        order: std::sync::Mutex<Vec<String>>,
+        started: Notify,
+        release: Notify,
+    }
+
+    #[async_trait]
+    impl Compiler for RecordingCompiler {
+        async fn compile(&self, meta: &ContractMeta) -> Result<CompiledContract> {
+            let first = {
+                let mut order = self.order.lock().unwrap();
+                order.push(meta.address.clone());
+                order.len() == 1
+            };
+            if first {
+                self.started.notify_one();
+                self.release.notified().await;
+            }
+
+            Ok(CompiledContract {
+                original_address: meta.address.clone(),
+                source_type: meta.contract_type.clone(),
+                risc_v_code: vec![2],
+                artifact: ArtifactKind::NativeRiscV,
+                entry_points: vec!["main".to_string()],
+                metadata: ContractMetadata {
+                    gas_metering: true,
+                    memory_limit: 1024,
+                    stack_limit: 512,
+                    call_depth_limit: 64,
+                    exports: HashMap::new(),
+                    storage_access: None,
+                },
+                compiled_at: 2,
+            })
+        }
+    }
+
+    fn contract_meta(address: &str) -> ContractMeta {
+        ContractMeta {
+            address: address.to_string(),
+            chain_type: ChainType::Ethereum,
+            contract_type: ContractType::EVM,
+            bytecode: vec![0x60, 0x00],
+            abi: None,
+            source_code: None,
+            compiler_version: None,
+            created_at: 0,
+            creator: None,
+            abi_source: None,
+            modules: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_version_bump_recompiles_in_usage_order() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let loader = Arc::new(CodeLoader::with_cache_dir(temp_dir.path())?);
+
+        // 五个合约，使用次数 1..=5
+        let metas: Vec<_> = (0..5).map(|i| contract_meta(&format!("0x{}", i))).collect();
+        for (i, meta) in metas.iter().enumerate() {
+            for _ in 0..=i {
+                loader.load_contract(meta).await?;
+            }
+        }
+        loader.cache().set_artifact_version(&ArtifactVersion::default())?;
+
+        let compiler = Arc::new(RecordingCompiler {
+            order: std::sync::Mutex::new(Vec::new()),
+            started: Notify::new(),
+            release: Notify::new(),
+        });
+        let service = Arc::new(RecompilationService::new(
+            loader.cache(),
+            loader.usage(),
+            compiler.clone(),
+            loader.clone(),
+            loader.activity(),
+            RecompileConfig {
+                idle_poll_interval: Duration::from_millis(5),
+                yield_interval: Duration::from_millis(1),
+            },
+        ));
+
+        // 版本未变化时不排队
+        assert_eq!(service.detect_version_change(&ArtifactVersion::default()).await?, 0);
+
+        let bumped = ArtifactVersion::new(DEFAULT_CYCLES_PER_GAS + 1);
+        assert_eq!(service.detect_version_change(&bumped).await?, 5);
+
+        let handle = service.clone().spawn();
+        compiler.started.notified().await;
+
+        // 重编译进行中，前台加载不被阻塞且读到旧产物
+        let loaded = tokio::time::timeout(
+            Duration::from_millis(500),
+            loader.load_contract(&metas[0]),
+        )
+        .await??;
+        assert_ne!(loaded.risc_v_code, vec![2]);
+
+        let progress = service.progress().await;
+        assert_eq!(progress.total, 5);
+        assert_eq!(progress.completed, 0);
+        assert_eq!(progress.remaining, 4);
+
+        compiler.release.notify_one();
+        handle.await??;
+
+        let order = compiler.order.lock().unwrap().clone();
+        assert_eq!(order, vec!["0x4", "0x3", "0x2", "0x1", "0x0"]);
+
+        let progress = service.progress().await;
+        assert_eq!(progress.completed, 5);
+        assert_eq!(progress.remaining, 0);
+        assert_eq!(loader.cache().artifact_version()?, Some(bumped));
+        assert_eq!(loader.load_contract(&metas[4]).await?.risc_v_code, vec![2]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_usage_survives_restart() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let metas: Vec<_> = (0..3).map(|i| contract_meta(&format!("0x{}", i))).collect();
+        {
+            let loader = CodeLoader::with_cache_dir(temp_dir.path())?;
+            for (i, meta) in metas.iter().enumerate() {
+                for _ in 0..=i {
+                    loader.load_contract(meta).await?;
+                }
+            }
+            assert_eq!(loader.persist_usage().await?, 3);
+            // 没有新的加载时不重复写入
+            assert_eq!(loader.persist_usage().await?, 0);
+
+            // 失效的条目连同使用统计一起删除
+            loader.invalidate("0x0").await?;
+            loader
+                .cache()
+                .set_artifact_version(&ArtifactVersion::default())?;
+        }
+
+        let loader = Arc::new(CodeLoader::with_cache_dir(temp_dir.path())?);
+        assert_eq!(loader.usage().hits(&loader.generate_cache_key(&metas[2])), 3);
+        assert_eq!(loader.usage().ranked().len(), 2);
+
+        let metrics = Arc::new(NodeMetrics::new()?);
+        let service = RecompilationService::new(
+            loader.cache(),
+            loader.usage(),
+            loader.clone(),
+            loader.clone(),
+            loader.activity(),
+            RecompileConfig::default(),
+        )
+        .with_metrics(metrics.clone());
+        let bumped = ArtifactVersion::new(DEFAULT_CYCLES_PER_GAS + 1);
+        assert_eq!(service.detect_version_change(&bumped).await?, 2);
+        assert_eq!(metrics.recompile_remaining.get(), 2);
+
+        service.run_pending().await?;
+        assert_eq!(metrics.recompile_completed.get(), 2);
+        assert_eq!(metrics.recompile_failed.get(), 0);
+        assert_eq!(metrics.recompile_remaining.get(), 0);
+        assert_eq!(loader.cache().artifact_version()?, Some(bumped));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recompiled_artifacts_follow_compiler_fingerprint() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let meta = contract_meta("0x1");
+        let mut loader = CodeLoader::with_cache_dir(temp_dir.path())?;
+        loader.load_contract(&meta).await?;
+        loader.load_contract(&meta).await?;
+        loader
+            .cache()
+            .set_artifact_version(&ArtifactVersion::default())?;
+        let old_key = loader.generate_cache_key(&meta);
+
+        // 升级后编译器指纹变化，缓存键随之变化
+        loader.compiler_fingerprint = format!("{}|upgraded", loader.compiler_fingerprint);
+        let loader = Arc::new(loader);
+        let new_key = loader.generate_cache_key(&meta);
+        assert_ne!(old_key, new_key);
+
+        let compiler = Arc::new(RecordingCompiler {
+            order: std::sync::Mutex::new(Vec::new()),
+            started: Notify::new(),
+            release: Notify::new(),
+        });
+        compiler.release.notify_one();
+        let service = RecompilationService::new(
+            loader.cache(),
+            loader.usage(),
+            compiler.clone(),
+            loader.clone(),
+            loader.activity(),
+            RecompileConfig::default(),
+        );
+        let bumped = ArtifactVersion::new(DEFAULT_CYCLES_PER_GAS + 1);
+        assert_eq!(service.detect_version_change(&bumped).await?, 1);
+        service.run_pending().await?;
+
+        // 旧键连同使用统计一并删除，统计迁移到新键
+        assert!(!loader.cache().contains(&old_key).await);
+        assert!(loader.cache().contains(&new_key).await);
+        assert_eq!(loader.usage().hits(&old_key), 0);
+        assert_eq!(loader.usage().hits(&new_key), 2);
+
+        // 前台加载直接命中重编译产物，不再重新编译
+        assert_eq!(loader.load_contract(&meta).await?.risc_v_code, vec![2]);
+        assert_eq!(compiler.order.lock().unwrap().len(), 1);
+
+        Ok(())
+    }
+}
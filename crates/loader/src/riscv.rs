@@ -0,0 +1,202 @@
+//! 最小 RV64I 指令编码器
+//!
+//! 供编译器生成占位程序与运行时桩代码，只覆盖用到的指令
+
+use crate::abi::{SYS_EXIT, SYS_INPUT_LENGTH, SYS_LOAD_INPUT, SYS_WRITE_OUTPUT};
+
+pub const ZERO: u32 = 0;
+pub const RA: u32 = 1;
+pub const SP: u32 = 2;
+pub const T0: u32 = 5;
+pub const T1: u32 = 6;
+pub const T2: u32 = 7;
+pub const S0: u32 = 8;
+pub const S1: u32 = 9;
+pub const A0: u32 = 10;
+pub const A1: u32 = 11;
+pub const A2: u32 = 12;
+pub const A3: u32 = 13;
+pub const A4: u32 = 14;
+pub const A5: u32 = 15;
+pub const A7: u32 = 17;
+pub const T3: u32 = 28;
+pub const T4: u32 = 29;
+pub const T5: u32 = 30;
+pub const T6: u32 = 31;
+
+fn i_type(opcode: u32, funct3: u32, rd: u32, rs1: u32, imm: i32) -> u32 {
+    ((imm as u32 & 0xfff) << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+}
+
+fn r_type(funct7: u32, funct3: u32, rd: u32, rs1: u32, rs2: u32) -> u32 {
+    (funct7 << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | 0x33
+}
+
+fn s_type(funct3: u32, rs1: u32, rs2: u32, imm: i32) -> u32 {
+    let imm = imm as u32;
+    (((imm >> 5) & 0x7f) << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12) | ((imm & 0x1f) << 7) | 0x23
+}
+
+fn b_type(funct3: u32, rs1: u32, rs2: u32, offset: i32) -> u32 {
+    let imm = offset as u32;
+    (((imm >> 12) & 1) << 31)
+        | (((imm >> 5) & 0x3f) << 25)
+        | (rs2 << 20)
+        | (rs1 << 15)
+        | (funct3 << 12)
+        | (((imm >> 1) & 0xf) << 8)
+        | (((imm >> 11) & 1) << 7)
+        | 0x63
+}
+
+pub fn lui(rd: u32, imm20: u32) -> u32 {
+    ((imm20 & 0xf_ffff) << 12) | (rd << 7) | 0x37
+}
+
+/// `rd = pc + (imm20 << 12)`
+pub fn auipc(rd: u32, imm20: u32) -> u32 {
+    ((imm20 & 0xf_ffff) << 12) | (rd << 7) | 0x17
+}
+
+pub fn addi(rd: u32, rs1: u32, imm: i32) -> u32 {
+    i_type(0x13, 0, rd, rs1, imm)
+}
+
+pub fn andi(rd: u32, rs1: u32, imm: i32) -> u32 {
+    i_type(0x13, 7, rd, rs1, imm)
+}
+
+pub fn slli(rd: u32, rs1: u32, shamt: u32) -> u32 {
+    i_type(0x13, 1, rd, rs1, (shamt & 0x3f) as i32)
+}
+
+pub fn srli(rd: u32, rs1: u32, shamt: u32) -> u32 {
+    i_type(0x13, 5, rd, rs1, (shamt & 0x3f) as i32)
+}
+
+pub fn add(rd: u32, rs1: u32, rs2: u32) -> u32 {
+    r_type(0, 0, rd, rs1, rs2)
+}
+
+pub fn sub(rd: u32, rs1: u32, rs2: u32) -> u32 {
+    r_type(0x20, 0, rd, rs1, rs2)
+}
+
+pub fn mul(rd: u32, rs1: u32, rs2: u32) -> u32 {
+    r_type(1, 0, rd, rs1, rs2)
+}
+
+pub fn ld(rd: u32, rs1: u32, offset: i32) -> u32 {
+    i_type(0x03, 3, rd, rs1, offset)
+}
+
+pub fn lbu(rd: u32, rs1: u32, offset: i32) -> u32 {
+    i_type(0x03, 4, rd, rs1, offset)
+}
+
+pub fn sd(rs2: u32, rs1: u32, offset: i32) -> u32 {
+    s_type(3, rs1, rs2, offset)
+}
+
+pub fn sb(rs2: u32, rs1: u32, offset: i32) -> u32 {
+    s_type(0, rs1, rs2, offset)
+}
+
+/// 偏移量以字节计，相对当前指令
+pub fn beq(rs1: u32, rs2: u32, offset: i32) -> u32 {
+    b_type(0, rs1, rs2, offset)
+}
+
+pub fn bne(rs1: u32, rs2: u32, offset: i32) -> u32 {
+    b_type(1, rs1, rs2, offset)
+}
+
+pub fn bltu(rs1: u32, rs2: u32, offset: i32) -> u32 {
+    b_type(6, rs1, rs2, offset)
+}
+
+pub fn jal(rd: u32, offset: i32) -> u32 {
+    let imm = offset as u32;
+    (((imm >> 20) & 1) << 31)
+        | (((imm >> 1) & 0x3ff) << 21)
+        | (((imm >> 11) & 1) << 20)
+        | (((imm >> 12) & 0xff) << 12)
+        | (rd << 7)
+        | 0x6f
+}
+
+pub fn ecall() -> u32 {
+    0x0000_0073
+}
+
+pub fn ebreak() -> u32 {
+    0x0010_0073
+}
+
+/// 加载 32 位有符号立即数（必要时拆为 lui + addi）
+pub fn li(rd: u32, value: i32) -> Vec<u32> {
+    if (-2048..2048).contains(&value) {
+        return vec![addi(rd, ZERO, value)];
+    }
+    // addi 的立即数符号扩展，高位需要补偿
+    let upper = ((value as i64 + 0x800) >> 12) as u32;
+    let lower = value.wrapping_sub((upper << 12) as i32);
+    vec![lui(rd, upper), addi(rd, rd, lower)]
+}
+
+/// 系统调用：`a7 = number; ecall`
+pub fn syscall(number: u64) -> Vec<u32> {
+    let mut words = li(A7, number as i32);
+    words.push(ecall());
+    words
+}
+
+/// 指令字转为小端字节序列
+pub fn assemble(words: &[u32]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+/// 回显程序：把调用输入原样写到输出后以 0 退出
+///
+/// 在真正的翻译实现之前，占位产物用它保持“输入即输出”的直通语义
+pub fn echo_program() -> Vec<u32> {
+    let mut words = Vec::new();
+    words.extend(syscall(SYS_INPUT_LENGTH));
+    words.push(addi(S0, A0, 0));
+    // 在栈上分配 16 字节对齐的缓冲区
+    words.push(sub(SP, SP, S0));
+    words.push(andi(SP, SP, -16));
+    words.push(addi(A0, SP, 0));
+    words.push(addi(A1, S0, 0));
+    words.push(addi(A2, ZERO, 0));
+    words.extend(syscall(SYS_LOAD_INPUT));
+    words.push(addi(A0, SP, 0));
+    words.push(addi(A1, S0, 0));
+    words.extend(syscall(SYS_WRITE_OUTPUT));
+    words.push(addi(A0, ZERO, 0));
+    words.extend(syscall(SYS_EXIT));
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encodings() {
+        assert_eq!(addi(SP, SP, -16), 0xff01_0113);
+        assert_eq!(addi(T0, ZERO, 5), 0x0050_0293);
+        assert_eq!(add(A0, A0, A1), 0x00b5_0533);
+        assert_eq!(sd(RA, SP, 8), 0x0011_3423);
+        assert_eq!(ld(RA, SP, 8), 0x0081_3083);
+        assert_eq!(jal(ZERO, -8), 0xff9f_f06f);
+        assert_eq!(beq(A0, ZERO, 8), 0x0005_0463);
+    }
+
+    #[test]
+    fn test_li_splits_large_values() {
+        assert_eq!(li(A7, 93), vec![addi(A7, ZERO, 93)]);
+        // 0x12345fff：低 12 位为负，需要高位进一
+        assert_eq!(li(T0, 0x1234_5fff), vec![lui(T0, 0x12346), addi(T0, T0, -1)]);
+    }
+}
@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 
 /// 编译后的合约
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,11 +10,21 @@ pub struct CompiledContract {
     pub original_address: String,
     pub source_type: dubhe_adapter::ContractType,
     pub risc_v_code: Vec<u8>,
+    pub artifact: ArtifactKind,
     pub entry_points: Vec<String>,
     pub metadata: ContractMetadata,
     pub compiled_at: u64,
 }
 
+/// 编译产物类型，VM 运行时据此判断能否执行
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArtifactKind {
+    /// 直接执行的 RISC-V 机器码
+    NativeRiscV,
+    /// 引导代码 + 原始字节码，由宿主提供的解释器执行（见 `abi::SYS_EVM_INTERPRET`）
+    InterpreterBundle,
+}
+
 /// 合约元数据
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContractMetadata {
@@ -22,6 +33,8 @@ pub struct ContractMetadata {
     pub stack_limit: u64,
     pub call_depth_limit: u32,
     pub exports: HashMap<String, FunctionSignature>,
+    /// 源字节码的存储访问摘要（目前仅 EVM）
+    pub storage_access: Option<crate::access::StorageAccess>,
 }
 
 /// 函数签名
@@ -62,6 +75,8 @@ pub struct CompilationConfig {
     pub target_arch: TargetArch,
     pub enable_gas_metering: bool,
     pub enable_debug_info: bool,
+    /// 单次编译的时限，超时后取消编译任务并放弃其结果；`None` 表示不限时
+    pub compile_timeout: Option<Duration>,
 }
 
 /// 优化级别
@@ -87,6 +102,66 @@ impl Default for CompilationConfig {
             target_arch: TargetArch::RiscV64,
             enable_gas_metering: true,
             enable_debug_info: false,
+            compile_timeout: Some(DEFAULT_COMPILE_TIMEOUT),
+        }
+    }
+}
+
+/// 默认编译时限，足够编译大型 Move 包
+pub const DEFAULT_COMPILE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// `CompiledContract` 的序列化格式版本，字段变化时递增（参与缓存键计算）
+pub const ARTIFACT_FORMAT_VERSION: u32 = 3;
+
+/// 与 VM 默认 gas 计价表一致的每单位 gas 周期数
+pub const DEFAULT_CYCLES_PER_GAS: u64 = 2;
+
+/// 编译产物版本
+///
+/// gas 计价表或编译器升级后，已缓存的产物需要重新编译
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactVersion {
+    /// 节点配置的 gas 计价表（每单位 gas 对应的 VM 周期数）
+    pub cycles_per_gas: u64,
+    pub compiler_version: String,
+}
+
+impl ArtifactVersion {
+    /// 当前编译器与给定 gas 计价表下的产物版本
+    pub fn new(cycles_per_gas: u64) -> Self {
+        Self {
+            cycles_per_gas,
+            compiler_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+impl Default for ArtifactVersion {
+    fn default() -> Self {
+        Self::new(DEFAULT_CYCLES_PER_GAS)
+    }
+}
+
+/// 缓存条目头中记录的产物来源
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactOrigin {
+    /// 源字节码的 SHA-256
+    pub bytecode_hash: String,
+    /// 编译器标识：合约类型 + 编译器版本
+    pub compiler_id: String,
+}
+
+impl ArtifactOrigin {
+    pub fn new(meta: &dubhe_adapter::ContractMeta) -> Self {
+        use sha2::{Digest, Sha256};
+
+        Self {
+            bytecode_hash: hex::encode(Sha256::digest(&meta.bytecode)),
+            compiler_id: format!(
+                "{:?}@{}",
+                meta.contract_type,
+                ArtifactVersion::default().compiler_version
+            ),
         }
     }
 }
@@ -95,9 +170,70 @@ impl Default for CompilationConfig {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct PluginHandle(pub u64);
 
+/// 插件 ABI 版本
+///
+/// `Plugin` trait 或其参数类型的布局发生变化时递增；插件需导出同值的
+/// `DUBHE_PLUGIN_ABI` 符号，加载时不一致即拒绝
+///
+/// v3 新增 `start_compile` / `poll_compile` / `cancel_compile` 轮询式异步编译
+pub const PLUGIN_ABI_VERSION: u32 = 3;
+
+/// 插件支持的输入
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PluginCapabilities {
+    pub contract_types: Vec<dubhe_adapter::ContractType>,
+}
+
+impl PluginCapabilities {
+    pub fn supports(&self, contract_type: &dubhe_adapter::ContractType) -> bool {
+        self.contract_types.contains(contract_type)
+    }
+}
+
+/// 插件编译任务句柄，由插件分配
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompileJob(pub u64);
+
+/// 轮询编译任务的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompilePoll {
+    /// 仍在编译，尚无进度信息
+    Pending,
+    /// 仍在编译，已完成的百分比（0-100）
+    Progress(u8),
+    /// 编译完成，任务句柄随之失效
+    Done(Vec<u8>),
+    /// 编译失败或句柄未知，任务句柄随之失效
+    Error(String),
+}
+
 /// 插件接口
-pub trait Plugin {
+///
+/// 异步编译采用轮询式接口：`start_compile` 立即返回任务句柄，宿主定期 `poll_compile`，
+/// 超时或放弃时调用 `cancel_compile`。Future、waker 与 tokio 运行时都不跨越插件边界，
+/// 插件无需依赖任何异步运行时
+pub trait Plugin: Send + Sync {
     fn name(&self) -> &str;
     fn version(&self) -> &str;
+    /// 插件能编译的合约类型
+    fn capabilities(&self) -> PluginCapabilities;
     fn compile(&self, bytecode: &[u8], config: &CompilationConfig) -> anyhow::Result<Vec<u8>>;
+
+    /// 启动后台编译并返回任务句柄；返回 `None` 表示插件不支持异步编译，宿主改用 `compile`
+    fn start_compile(
+        &self,
+        _bytecode: &[u8],
+        _config: &CompilationConfig,
+    ) -> anyhow::Result<Option<CompileJob>> {
+        Ok(None)
+    }
+
+    /// 查询编译任务状态，不得阻塞
+    fn poll_compile(&self, job: CompileJob) -> CompilePoll {
+        CompilePoll::Error(format!("Unknown compile job {:?}", job))
+    }
+
+    /// 取消编译任务；插件应尽快停止工作，之后句柄失效
+    fn cancel_compile(&self, _job: CompileJob) {}
 }
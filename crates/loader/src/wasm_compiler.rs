@@ -0,0 +1,288 @@
+//! WASM 编译器
+//!
+//! 第一阶段不做 wasm32 → RISC-V 转译：校验模块后产出解释器包，由宿主内嵌的
+//! wasmi 以确定性的燃料计量执行（见 [`crate::abi::SYS_WASM_INTERPRET`]）
+//!
+//! 加载时拒绝：
+//! - 浮点类型与指令（不同平台的 NaN 位模式不确定）
+//! - `env` 宿主函数以外的导入，包括 WASI
+//! - 未声明上限或上限超过配置的线性内存
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+use tracing::info;
+use wasmparser::{ExternalKind, Parser, Payload, Type, TypeRef, ValType, Validator, WasmFeatures};
+
+use crate::abi::{SYS_WASM_INTERPRET, WASM_HOST_IMPORTS, WASM_HOST_MODULE};
+use crate::compiler::bundle_for;
+use crate::error::LoaderError;
+use crate::types::{
+    ArtifactKind, CompiledContract, ContractMetadata, FunctionSignature, Mutability, ParamType,
+};
+use dubhe_adapter::{ContractMeta, ContractType};
+
+/// WASM 线性内存页大小
+pub const WASM_PAGE_SIZE: u64 = 64 * 1024;
+
+/// WASM 编译器配置
+#[derive(Debug, Clone)]
+pub struct WasmCompilerConfig {
+    /// 线性内存上限（页）
+    pub max_memory_pages: u64,
+    pub enable_gas_metering: bool,
+}
+
+impl Default for WasmCompilerConfig {
+    fn default() -> Self {
+        Self {
+            max_memory_pages: 256, // 16MB
+            enable_gas_metering: true,
+        }
+    }
+}
+
+/// WASM 到解释器包的编译器
+pub struct WasmCompiler {
+    config: WasmCompilerConfig,
+}
+
+impl Default for WasmCompiler {
+    fn default() -> Self {
+        Self::new(WasmCompilerConfig::default())
+    }
+}
+
+impl WasmCompiler {
+    pub fn new(config: WasmCompilerConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &WasmCompilerConfig {
+        &self.config
+    }
+
+    /// 校验模块并产出解释器包，导出函数按名称排序作为入口
+    pub fn compile(&self, meta: &ContractMeta) -> Result<CompiledContract> {
+        info!(
+            "Compiling WASM contract {} ({} bytes)",
+            meta.address,
+            meta.bytecode.len()
+        );
+        let exports = self.validate(&meta.bytecode)?;
+
+        Ok(CompiledContract {
+            original_address: meta.address.clone(),
+            source_type: ContractType::WASM,
+            risc_v_code: bundle_for(SYS_WASM_INTERPRET, &meta.bytecode),
+            artifact: ArtifactKind::InterpreterBundle,
+            entry_points: exports.keys().cloned().collect(),
+            metadata: ContractMetadata {
+                gas_metering: self.config.enable_gas_metering,
+                memory_limit: self.config.max_memory_pages * WASM_PAGE_SIZE,
+                stack_limit: 1024 * 1024,
+                call_depth_limit: 1024,
+                exports: exports.into_iter().collect(),
+                storage_access: None,
+            },
+            compiled_at: chrono::Utc::now().timestamp() as u64,
+        })
+    }
+
+    /// 校验模块，返回按名称排序的导出函数签名
+    pub fn validate(
+        &self,
+        bytecode: &[u8],
+    ) -> std::result::Result<BTreeMap<String, FunctionSignature>, LoaderError> {
+        let invalid = |e: wasmparser::BinaryReaderError| {
+            LoaderError::InvalidBytecode(format!("WASM validation failed: {}", e))
+        };
+
+        let features = WasmFeatures {
+            floats: false,
+            ..WasmFeatures::default()
+        };
+        Validator::new_with_features(features)
+            .validate_all(bytecode)
+            .map_err(invalid)?;
+
+        let mut types = Vec::new();
+        // 函数索引空间：导入函数在前，随后是模块内定义的函数
+        let mut functions = Vec::new();
+        let mut exports = BTreeMap::new();
+        for payload in Parser::new(0).parse_all(bytecode) {
+            match payload.map_err(invalid)? {
+                Payload::TypeSection(reader) => {
+                    for ty in reader {
+                        let Type::Func(func) = ty.map_err(invalid)?;
+                        types.push(func);
+                    }
+                }
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        let import = import.map_err(invalid)?;
+                        let TypeRef::Func(type_index) = import.ty else {
+                            return Err(disallowed(
+                                import.module,
+                                import.name,
+                                "only host functions can be imported",
+                            ));
+                        };
+                        if import.module != WASM_HOST_MODULE {
+                            let reason = if import.module.starts_with("wasi") {
+                                "WASI is not available off-chain"
+                            } else {
+                                "unknown host module"
+                            };
+                            return Err(disallowed(import.module, import.name, reason));
+                        }
+                        if !WASM_HOST_IMPORTS.contains(&import.name) {
+                            return Err(disallowed(
+                                import.module,
+                                import.name,
+                                "unknown host function",
+                            ));
+                        }
+                        functions.push(type_index);
+                    }
+                }
+                Payload::FunctionSection(reader) => {
+                    for type_index in reader {
+                        functions.push(type_index.map_err(invalid)?);
+                    }
+                }
+                Payload::MemorySection(reader) => {
+                    for memory in reader {
+                        let memory = memory.map_err(invalid)?;
+                        let limit = self.config.max_memory_pages;
+                        match memory.maximum {
+                            Some(maximum) if maximum <= limit => {}
+                            declared => {
+                                return Err(LoaderError::MemoryLimitExceeded {
+                                    declared: declared
+                                        .map(|pages| format!("{} pages", pages))
+                                        .unwrap_or_else(|| "no maximum".to_string()),
+                                    limit,
+                                })
+                            }
+                        }
+                    }
+                }
+                Payload::ExportSection(reader) => {
+                    for export in reader {
+                        let export = export.map_err(invalid)?;
+                        if export.kind == ExternalKind::Func {
+                            exports.insert(export.name.to_string(), export.index);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        exports
+            .into_iter()
+            .map(|(name, function_index)| {
+                let func = functions
+                    .get(function_index as usize)
+                    .and_then(|&type_index| types.get(type_index as usize))
+                    .ok_or_else(|| {
+                        LoaderError::InvalidBytecode(format!("Export {} has no type", name))
+                    })?;
+                let convert = |types: &[ValType]| {
+                    types
+                        .iter()
+                        .map(|ty| wasm_param_type(&name, *ty))
+                        .collect::<std::result::Result<Vec<_>, _>>()
+                };
+                let signature = FunctionSignature {
+                    name: name.clone(),
+                    inputs: convert(func.params())?,
+                    outputs: convert(func.results())?,
+                    mutability: Mutability::NonPayable,
+                };
+                Ok((name, signature))
+            })
+            .collect()
+    }
+}
+
+fn disallowed(module: &str, name: &str, reason: &str) -> LoaderError {
+    LoaderError::DisallowedImport {
+        module: module.to_string(),
+        name: name.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// 入口函数只接受整数参数与返回值
+fn wasm_param_type(function: &str, ty: ValType) -> std::result::Result<ParamType, LoaderError> {
+    match ty {
+        ValType::I32 => Ok(ParamType::Int(32)),
+        ValType::I64 => Ok(ParamType::Int(64)),
+        other => Err(LoaderError::InvalidBytecode(format!(
+            "Export {} uses unsupported value type {:?}",
+            function, other
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(wat: &str) -> std::result::Result<BTreeMap<String, FunctionSignature>, LoaderError> {
+        WasmCompiler::default().validate(&wat::parse_str(wat).unwrap())
+    }
+
+    #[test]
+    fn test_counter_exports() {
+        let wasm = wat::parse_str(include_str!("../../../tests/fixtures/counter.wat")).unwrap();
+        let meta = ContractMeta {
+            address: "counter.wasm".to_string(),
+            chain_type: dubhe_adapter::ChainType::Ethereum,
+            contract_type: ContractType::WASM,
+            bytecode: wasm.clone(),
+            abi: None,
+            source_code: None,
+            compiler_version: None,
+            created_at: 0,
+            creator: None,
+            abi_source: None,
+            modules: vec![],
+        };
+
+        let compiled = WasmCompiler::default().compile(&meta).unwrap();
+        assert_eq!(compiled.artifact, ArtifactKind::InterpreterBundle);
+        assert!(compiled.risc_v_code.ends_with(&wasm));
+        assert_eq!(compiled.entry_points, vec!["get", "increment"]);
+        let increment = &compiled.metadata.exports["increment"];
+        assert!(matches!(increment.inputs[..], [ParamType::Int(64)]));
+        assert!(matches!(increment.outputs[..], [ParamType::Int(64)]));
+    }
+
+    #[test]
+    fn test_rejects_nondeterministic_and_unbounded_modules() {
+        let err = compile(
+            r#"(module (import "wasi_snapshot_preview1" "fd_write"
+                 (func (param i32 i32 i32 i32) (result i32))))"#,
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, LoaderError::DisallowedImport { ref reason, .. } if reason.contains("WASI"))
+        );
+
+        let err = compile(r#"(module (import "env" "abort" (func)))"#).unwrap_err();
+        assert!(matches!(err, LoaderError::DisallowedImport { .. }));
+
+        let err =
+            compile(r#"(module (func (export "half") (param f64) (result f64) local.get 0))"#)
+                .unwrap_err();
+        assert!(matches!(err, LoaderError::InvalidBytecode(_)));
+
+        let err = compile(r#"(module (memory 1))"#).unwrap_err();
+        assert!(matches!(err, LoaderError::MemoryLimitExceeded { .. }));
+        let err = compile(r#"(module (memory 1 1024))"#).unwrap_err();
+        assert!(err.to_string().contains("1024 pages"));
+        assert!(compile(r#"(module (memory 1 16))"#).is_ok());
+    }
+}
@@ -36,21 +36,33 @@ fn main() -> Result<()> {
             info!("  - VM max instances: {}", config.vm.max_instances);
 
             // 验证适配器配置
-            if config.adapters.ethereum.is_some() {
-                info!("  - Ethereum adapter: enabled");
-            }
-            if config.adapters.sui.is_some() {
-                info!("  - Sui adapter: enabled");
-            }
-            if config.adapters.solana.is_some() {
-                info!("  - Solana adapter: enabled");
-            }
-            if config.adapters.aptos.is_some() {
-                info!("  - Aptos adapter: enabled");
-            }
-            if config.adapters.bitcoin.is_some() {
-                info!("  - Bitcoin adapter: enabled");
-            }
+            let enabled = |enabled: Option<bool>| match enabled {
+                Some(true) => "enabled",
+                Some(false) => "disabled",
+                None => "not configured",
+            };
+            let adapters = &config.adapters;
+            info!(
+                "  - Ethereum adapter: {}",
+                enabled(adapters.ethereum.as_ref().map(|c| c.enabled))
+            );
+            info!(
+                "  - Sui adapter: {}",
+                enabled(adapters.sui.as_ref().map(|c| c.enabled))
+            );
+            info!(
+                "  - Solana adapter: {}",
+                enabled(adapters.solana.as_ref().map(|c| c.enabled))
+            );
+            info!(
+                "  - Aptos adapter: {}",
+                enabled(adapters.aptos.as_ref().map(|c| c.enabled))
+            );
+            info!(
+                "  - Bitcoin adapter: {}",
+                enabled(adapters.bitcoin.as_ref().map(|c| c.enabled))
+            );
+            info!("  - Strict adapter startup: {}", adapters.strict_startup);
 
             // 验证可观测性配置
             if config.observability.enable_prometheus {
@@ -0,0 +1,83 @@
+//! 密钥库管理工具
+//!
+//! 在 `security.keystore` 配置的目录中生成、导入、轮换加密签名密钥：
+//!
+//! ```text
+//! dubhe-keystore <config.toml> list
+//! dubhe-keystore <config.toml> generate <name>
+//! dubhe-keystore <config.toml> import-sui <name> <sui.keystore>
+//! dubhe-keystore <config.toml> rotate <name>
+//! ```
+
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use std::env;
+use std::sync::Arc;
+use tracing::{info, Level};
+use zeroize::Zeroizing;
+
+use dubhe_adapter::{KeystoreSigner, Signer};
+use dubhe_node::config::NodeConfig;
+use dubhe_security::{Keystore, Passphrase};
+
+const USAGE: &str = "usage: dubhe-keystore <config.toml> <list | generate <name> | import-sui <name> <sui.keystore> | rotate <name>>";
+
+fn main() -> Result<()> {
+    tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (config_path, command) = match args.as_slice() {
+        [config_path, command, ..] => (config_path, command.as_str()),
+        _ => return Err(anyhow!(USAGE)),
+    };
+    let config = NodeConfig::load(config_path)?;
+    let keystore = Arc::new(Keystore::open(
+        &config.security.keystore.dir,
+        Passphrase::from_env_or_prompt(&config.security.keystore.passphrase_env)?,
+    )?);
+    if command == "list" {
+        for name in keystore.names() {
+            print_key(&keystore, &name)?;
+        }
+        return Ok(());
+    }
+
+    let name = args.get(2).ok_or_else(|| anyhow!(USAGE))?;
+    match command {
+        "generate" => keystore.generate(name)?,
+        "import-sui" => {
+            let path = args.get(3).ok_or_else(|| anyhow!(USAGE))?;
+            keystore.import(name, read_sui_key(path)?)?
+        }
+        "rotate" => keystore.rotate(name)?,
+        _ => return Err(anyhow!(USAGE)),
+    };
+    print_key(&keystore, name)
+}
+
+fn print_key(keystore: &Arc<Keystore>, name: &str) -> Result<()> {
+    let signer = KeystoreSigner::new(keystore.handle(name)?)?;
+    info!("🔑 {}: {}", name, signer.address());
+    Ok(())
+}
+
+/// 读取 Sui keystore 的第一个 Ed25519 条目：base64 编码的 `[flag][32 字节私钥]`
+fn read_sui_key(path: &str) -> Result<Zeroizing<[u8; 32]>> {
+    let content = Zeroizing::new(std::fs::read_to_string(path)?);
+    let entries: Vec<Zeroizing<String>> = serde_json::from_str::<Vec<String>>(&content)
+        .map(|entries| entries.into_iter().map(Zeroizing::new).collect())
+        .unwrap_or_else(|_| vec![Zeroizing::new(content.trim().to_string())]);
+    let entry = entries
+        .first()
+        .ok_or_else(|| anyhow!("Keystore contains no keys"))?;
+
+    let bytes = Zeroizing::new(base64::engine::general_purpose::STANDARD.decode(entry.trim())?);
+    match bytes.split_first() {
+        Some((&dubhe_adapter::signer::ED25519_FLAG, secret)) => Ok(Zeroizing::new(
+            secret
+                .try_into()
+                .map_err(|_| anyhow!("Ed25519 key must be 32 bytes"))?,
+        )),
+        _ => Err(anyhow!("Only Ed25519 signer keys are supported")),
+    }
+}
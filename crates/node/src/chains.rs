@@ -0,0 +1,269 @@
+//! 按配置初始化各链适配器
+//!
+//! 未配置或 `enabled = false` 的链记为 disabled；已启用的链初始化失败时，
+//! 非严格模式下降级并在后台重试，`adapters.strict_startup = true` 时节点拒绝启动
+
+use anyhow::Result;
+use std::future::Future;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use dubhe_adapter::aptos::AptosAdapter;
+use dubhe_adapter::btc::BitcoinAdapter;
+use dubhe_adapter::eth::EthereumAdapter;
+use dubhe_adapter::solana::SolanaAdapter;
+use dubhe_adapter::sui::SuiAdapter;
+use dubhe_adapter::{
+    AdapterConfig, AdapterFactory, AdapterManager, AdapterState, ChainAdapter, ChainType,
+    CursorStore, Signer,
+};
+
+/// Sui 适配器的附加依赖
+#[derive(Clone, Default)]
+pub struct SuiDependencies {
+    pub cursor_store: Option<Arc<dyn CursorStore>>,
+    pub signer: Option<Arc<dyn Signer>>,
+}
+
+/// 由构造函数生成适配器工厂
+fn factory<C, A, F, Fut>(config: &C, build: F) -> AdapterFactory
+where
+    C: Clone + Send + Sync + 'static,
+    A: ChainAdapter + Send + Sync + 'static,
+    F: Fn(C) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<A>> + Send + 'static,
+{
+    let config = config.clone();
+    Arc::new(move || {
+        let adapter = build(config.clone());
+        Box::pin(async move { Ok(Box::new(adapter.await?) as Box<dyn ChainAdapter + Send + Sync>) })
+    })
+}
+
+/// 初始化配置中启用的链
+pub async fn init_adapters(
+    manager: &Arc<AdapterManager>,
+    config: &AdapterConfig,
+    sui: SuiDependencies,
+) -> Result<()> {
+    let strict = config.strict_startup;
+    let chains: [(ChainType, Option<AdapterFactory>); 5] = [
+        (
+            ChainType::Ethereum,
+            config
+                .ethereum
+                .as_ref()
+                .filter(|eth| eth.enabled)
+                .map(|eth| factory(eth, EthereumAdapter::new)),
+        ),
+        (
+            ChainType::Sui,
+            config
+                .sui
+                .as_ref()
+                .filter(|sui| sui.enabled)
+                .map(|sui_config| {
+                    factory(sui_config, move |sui_config| {
+                        let sui = sui.clone();
+                        async move {
+                            let mut adapter = SuiAdapter::new(sui_config).await?;
+                            if let Some(store) = sui.cursor_store {
+                                adapter = adapter.with_cursor_store(store);
+                            }
+                            if let Some(signer) = sui.signer {
+                                adapter = adapter.with_signer(signer);
+                            }
+                            Ok(adapter)
+                        }
+                    })
+                }),
+        ),
+        (
+            ChainType::Solana,
+            config
+                .solana
+                .as_ref()
+                .filter(|solana| solana.enabled)
+                .map(|solana| factory(solana, SolanaAdapter::new)),
+        ),
+        (
+            ChainType::Aptos,
+            config
+                .aptos
+                .as_ref()
+                .filter(|aptos| aptos.enabled)
+                .map(|aptos| factory(aptos, AptosAdapter::new)),
+        ),
+        (
+            ChainType::Bitcoin,
+            config
+                .bitcoin
+                .as_ref()
+                .filter(|bitcoin| bitcoin.enabled)
+                .map(|bitcoin| factory(bitcoin, BitcoinAdapter::new)),
+        ),
+    ];
+
+    for (chain_type, adapter_factory) in chains {
+        match adapter_factory {
+            Some(adapter_factory) => manager
+                .initialize_adapter(chain_type, adapter_factory, strict)
+                .await
+                .map_err(|e| {
+                    e.context(format!(
+                        "{:?} adapter failed to initialize (adapters.strict_startup = true)",
+                        chain_type
+                    ))
+                })?,
+            None => manager.disable_chain(chain_type).await,
+        }
+    }
+
+    log_chain_summary(manager).await;
+    Ok(())
+}
+
+/// 输出各链状态汇总
+pub async fn log_chain_summary(manager: &AdapterManager) {
+    let statuses = manager.chain_statuses().await;
+    let names = |state: AdapterState| {
+        let names: Vec<String> = statuses
+            .iter()
+            .filter(|status| status.state == state)
+            .map(|status| format!("{:?}", status.chain_type))
+            .collect();
+        if names.is_empty() {
+            "-".to_string()
+        } else {
+            names.join(", ")
+        }
+    };
+    info!(
+        "🔗 Chains active: [{}], degraded: [{}], disabled: [{}]",
+        names(AdapterState::Active),
+        names(AdapterState::Degraded),
+        names(AdapterState::Disabled)
+    );
+    for status in statuses
+        .iter()
+        .filter(|status| status.state == AdapterState::Degraded)
+    {
+        warn!(
+            "⚠️ {:?} is degraded: {}",
+            status.chain_type,
+            status.error.as_deref().unwrap_or("unknown error")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// 对任何请求都返回检查点 7 的 JSON-RPC 服务
+    async fn serve_sui() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    loop {
+                        let n = socket.read(&mut buf).await.unwrap_or(0);
+                        if n == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&buf[..n]);
+                        let text = String::from_utf8_lossy(&request).to_lowercase();
+                        if let Some(end) = text.find("\r\n\r\n") {
+                            let length = text
+                                .lines()
+                                .find_map(|line| line.strip_prefix("content-length:"))
+                                .and_then(|value| value.trim().parse::<usize>().ok())
+                                .unwrap_or(0);
+                            if request.len() >= end + 4 + length {
+                                break;
+                            }
+                        }
+                    }
+                    let body = r#"{"jsonrpc":"2.0","id":1,"result":"7"}"#;
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        url
+    }
+
+    fn adapter_config(sui_url: &str, strict: bool) -> AdapterConfig {
+        toml::from_str(&format!(
+            r#"
+            strict_startup = {strict}
+
+            [sui]
+            rpc_url = "{sui_url}"
+            network_type = "Localnet"
+            package_ids = []
+
+            [ethereum]
+            rpc_url = "http://127.0.0.1:1"
+            chain_id = 1
+
+            [aptos]
+            enabled = false
+            rpc_url = "http://127.0.0.1:1"
+            "#
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_dead_chain_is_degraded_in_non_strict_mode() {
+        let sui_url = serve_sui().await;
+
+        let manager = Arc::new(AdapterManager::new());
+        init_adapters(
+            &manager,
+            &adapter_config(&sui_url, false),
+            SuiDependencies::default(),
+        )
+        .await
+        .unwrap();
+
+        let states: Vec<(ChainType, AdapterState)> = manager
+            .chain_statuses()
+            .await
+            .into_iter()
+            .map(|status| (status.chain_type, status.state))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                (ChainType::Aptos, AdapterState::Disabled),
+                (ChainType::Bitcoin, AdapterState::Disabled),
+                (ChainType::Ethereum, AdapterState::Degraded),
+                (ChainType::Solana, AdapterState::Disabled),
+                (ChainType::Sui, AdapterState::Active),
+            ]
+        );
+        assert_eq!(manager.chain_types().await, vec![ChainType::Sui]);
+        manager.stop().await;
+
+        let strict = Arc::new(AdapterManager::new());
+        let error = init_adapters(
+            &strict,
+            &adapter_config(&sui_url, true),
+            SuiDependencies::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(format!("{:#}", error).contains("Ethereum adapter failed to initialize"));
+    }
+}
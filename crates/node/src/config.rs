@@ -1,13 +1,29 @@
 //! 节点配置模块
+//!
+//! 配置文件中的未知字段会被拒绝（通常是拼写错误），并指出所在行与最接近的字段名；
+//! 任意字段都可以通过 `DUBHE__` 前缀的环境变量覆盖，路径段以 `__` 分隔，
+//! 例如 `DUBHE__ADAPTERS__BITCOIN__RPC_PASSWORD`。
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::info;
 
 use dubhe_adapter::AdapterConfig;
 use dubhe_api::ApiConfig;
-use dubhe_scheduler::{SchedulerConfig, StrategyType};
-use dubhe_vm_runtime::VmType;
+use dubhe_observability::{AlertRule, TracingConfig};
+use dubhe_scheduler::{MempoolConfig, SchedulerConfig, StrategyType};
+use dubhe_security::{AccessControlConfig, KeystoreConfig, ThreatDetectionConfig};
+use dubhe_vm_runtime::{GasSchedule, VmPoolConfig, VmType};
+
+use crate::discovery::DiscoveryConfig;
+use crate::hotspot::HotspotConfig;
+use crate::locking::ObjectLockConfig;
+use crate::offchain_execution::SessionConfig;
+use crate::query_cache::QueryCacheConfig;
+use crate::replay::ReplayConfig;
+use crate::sync::SyncConfig;
 
 /// 节点完整配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +31,8 @@ pub struct NodeConfig {
     pub api: ApiConfig,
     pub adapters: AdapterConfig,
     pub scheduler: SchedulerConfig,
+    #[serde(default)]
+    pub mempool: MempoolConfig,
     pub vm: VmConfig,
     pub node: NodeSettings,
     #[serde(default)]
@@ -33,6 +51,20 @@ pub struct NodeConfig {
     pub performance: PerformanceConfig,
     #[serde(default)]
     pub alerting: AlertingConfig,
+    #[serde(default)]
+    pub hotspot: HotspotConfig,
+    #[serde(default)]
+    pub locking: ObjectLockConfig,
+    #[serde(default)]
+    pub sessions: SessionConfig,
+    #[serde(default)]
+    pub sync: SyncConfig,
+    #[serde(default)]
+    pub replay: ReplayConfig,
+    #[serde(default)]
+    pub query_cache: QueryCacheConfig,
+    #[serde(default)]
+    pub discovery: DiscoveryConfig,
 }
 
 /// VM 配置
@@ -42,6 +74,12 @@ pub struct VmConfig {
     pub max_instances: usize,
     #[serde(default)]
     pub move_compiler: MoveCompilerSettings,
+    /// 周期与 gas 的换算比例，CKB-VM 与 PolkaVM 共用
+    #[serde(default)]
+    pub gas_schedule: GasSchedule,
+    /// 链下执行复用的预热实例池
+    #[serde(default)]
+    pub pool: VmPoolConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,6 +111,26 @@ pub struct NodeSettings {
     pub data_dir: String,
     pub strategy: StrategyType,
     pub enable_metrics: bool,
+    /// 停机时等待在途批次完成的最长时间
+    #[serde(default = "default_shutdown_timeout_ms")]
+    pub shutdown_timeout_ms: u64,
+    /// dubhe_createSnapshot 只能写入此目录下，默认为 `<data_dir>/snapshots`
+    #[serde(default)]
+    pub snapshot_dir: Option<String>,
+}
+
+impl NodeSettings {
+    /// API 创建快照的根目录
+    pub fn snapshot_root(&self) -> PathBuf {
+        match &self.snapshot_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => Path::new(&self.data_dir).join("snapshots"),
+        }
+    }
+}
+
+fn default_shutdown_timeout_ms() -> u64 {
+    30_000
 }
 
 /// 安全配置
@@ -82,6 +140,15 @@ pub struct SecurityConfig {
     pub enable_sgx: bool,
     pub enable_access_control: bool,
     pub audit_level: String,
+    /// API Key 的角色，enable_access_control 为 true 时生效
+    #[serde(default)]
+    pub access_control: AccessControlConfig,
+    /// 加密密钥库，签名密钥在适配器配置中按名称引用
+    #[serde(default)]
+    pub keystore: KeystoreConfig,
+    /// 交易流异常检测规则
+    #[serde(default)]
+    pub threat_detection: ThreatDetectionConfig,
 }
 
 impl Default for SecurityConfig {
@@ -91,6 +158,9 @@ impl Default for SecurityConfig {
             enable_sgx: false,
             enable_access_control: false,
             audit_level: "Basic".to_string(),
+            access_control: AccessControlConfig::default(),
+            keystore: KeystoreConfig::default(),
+            threat_detection: ThreatDetectionConfig::default(),
         }
     }
 }
@@ -100,10 +170,36 @@ impl Default for SecurityConfig {
 pub struct ObservabilityConfig {
     pub enable_prometheus: bool,
     pub prometheus_port: u16,
+    /// /metrics 端点监听的地址（端口取 prometheus_port）
+    #[serde(default = "default_prometheus_host")]
+    pub prometheus_host: String,
     pub enable_tracing: bool,
     pub jaeger_endpoint: String,
     pub log_level: String,
     pub structured_logging: bool,
+    /// OpenTelemetry trace 导出（`[observability.tracing]`），需重启生效
+    #[serde(default)]
+    pub tracing: TracingConfig,
+}
+
+fn default_prometheus_host() -> String {
+    "0.0.0.0".to_string()
+}
+
+impl ObservabilityConfig {
+    /// Prometheus 导出端的监听地址
+    pub fn prometheus_bind(&self) -> String {
+        format!("{}:{}", self.prometheus_host, self.prometheus_port)
+    }
+
+    /// 生效的追踪配置：`enable_tracing = false` 时不导出
+    pub fn tracing_config(&self) -> TracingConfig {
+        if self.enable_tracing {
+            self.tracing.clone()
+        } else {
+            TracingConfig::default()
+        }
+    }
 }
 
 impl Default for ObservabilityConfig {
@@ -111,10 +207,12 @@ impl Default for ObservabilityConfig {
         Self {
             enable_prometheus: true,
             prometheus_port: 9100,
+            prometheus_host: default_prometheus_host(),
             enable_tracing: true,
             jaeger_endpoint: "http://localhost:14268/api/traces".to_string(),
             log_level: "info".to_string(),
             structured_logging: true,
+            tracing: TracingConfig::default(),
         }
     }
 }
@@ -126,6 +224,54 @@ pub struct CacheConfig {
     pub memory_cache_size: usize,
     pub enable_compression: bool,
     pub cleanup_interval_hours: u64,
+    /// 编译缓存最多保留的合约数，超过后按 LRU 淘汰
+    #[serde(default = "default_cache_max_entries")]
+    pub max_entries: usize,
+    /// 编译缓存总字节上限
+    #[serde(default = "default_cache_max_total_bytes")]
+    pub max_total_bytes: u64,
+    /// 编译缓存完整性扫描间隔（秒），不配置则不扫描
+    #[serde(default)]
+    pub scrub_interval_secs: Option<u64>,
+    /// 可导入其签名产物包的 Ed25519 公钥（十六进制）
+    #[serde(default)]
+    pub trusted_artifact_signers: Vec<String>,
+    /// 同时进行的合约编译数，默认为 CPU 核数
+    #[serde(default = "dubhe_loader::default_compile_parallelism")]
+    pub compile_parallelism: usize,
+}
+
+fn default_cache_max_entries() -> usize {
+    dubhe_loader::CacheConfig::default().max_entries
+}
+
+fn default_cache_max_total_bytes() -> u64 {
+    dubhe_loader::CacheConfig::default().max_total_bytes
+}
+
+impl CacheConfig {
+    /// 转换为编译缓存的淘汰策略
+    pub fn loader_cache_config(&self) -> dubhe_loader::CacheConfig {
+        dubhe_loader::CacheConfig {
+            max_entries: self.max_entries,
+            max_total_bytes: self.max_total_bytes,
+            memory_entries: self.memory_cache_size,
+            scrub_interval_secs: self.scrub_interval_secs,
+        }
+    }
+
+    /// 解析受信任的产物包签名公钥
+    pub fn trusted_artifact_signers(&self) -> Result<Vec<[u8; 32]>> {
+        self.trusted_artifact_signers
+            .iter()
+            .map(|key| {
+                hex::decode(key.trim_start_matches("0x"))
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| anyhow::anyhow!("Invalid artifact signer key: {}", key))
+            })
+            .collect()
+    }
 }
 
 impl Default for CacheConfig {
@@ -135,6 +281,11 @@ impl Default for CacheConfig {
             memory_cache_size: 1000,
             enable_compression: true,
             cleanup_interval_hours: 24,
+            max_entries: default_cache_max_entries(),
+            max_total_bytes: default_cache_max_total_bytes(),
+            scrub_interval_secs: None,
+            trusted_artifact_signers: Vec::new(),
+            compile_parallelism: dubhe_loader::default_compile_parallelism(),
         }
     }
 }
@@ -230,6 +381,18 @@ pub struct AlertingConfig {
     pub email: EmailConfig,
     pub slack: SlackConfig,
     pub thresholds: AlertThresholds,
+    /// 告警规则，可通过 dubhe_reloadAlertRules 热加载
+    #[serde(default)]
+    pub rules: Vec<AlertRule>,
+    /// 通用 webhook 地址，状态变更以 JSON POST 推送
+    #[serde(default)]
+    pub webhook_urls: Vec<String>,
+    #[serde(default = "default_evaluation_interval_secs")]
+    pub evaluation_interval_secs: u64,
+}
+
+fn default_evaluation_interval_secs() -> u64 {
+    15
 }
 
 impl Default for AlertingConfig {
@@ -239,6 +402,9 @@ impl Default for AlertingConfig {
             email: EmailConfig::default(),
             slack: SlackConfig::default(),
             thresholds: AlertThresholds::default(),
+            rules: Vec::new(),
+            webhook_urls: Vec::new(),
+            evaluation_interval_secs: default_evaluation_interval_secs(),
         }
     }
 }
@@ -309,41 +475,60 @@ impl Default for NodeConfig {
             api: ApiConfig::default(),
             adapters: AdapterConfig {
                 ethereum: Some(dubhe_adapter::EthereumConfig {
+                    enabled: true,
                     rpc_url: "https://eth-mainnet.g.alchemy.com/v2/YOUR-API-KEY".to_string(),
                     ws_url: Some("wss://eth-mainnet.g.alchemy.com/v2/YOUR-API-KEY".to_string()),
                     chain_id: 1,
+                    explorer_api_url: Some("https://api.etherscan.io/api".to_string()),
+                    explorer_api_key: None,
                 }),
                 solana: Some(dubhe_adapter::SolanaConfig {
+                    enabled: true,
                     rpc_url: "https://api.mainnet-beta.solana.com".to_string(),
                     ws_url: Some("wss://api.mainnet-beta.solana.com".to_string()),
                     commitment: "finalized".to_string(),
+                    rpc_urls: vec![],
+                    health_check: dubhe_adapter::HealthCheckConfig::default(),
                 }),
                 aptos: Some(dubhe_adapter::AptosConfig {
+                    enabled: true,
                     rpc_url: "https://fullnode.mainnet.aptoslabs.com/v1".to_string(),
                     faucet_url: None,
                 }),
                 sui: Some(dubhe_adapter::SuiConfig {
+                    enabled: true,
                     rpc_url: "https://fullnode.testnet.sui.io".to_string(),
                     ws_url: None,
                     network_type: dubhe_adapter::SuiNetworkType::Testnet,
                     package_ids: vec!["0x1".to_string()],
+                    signer: None,
+                    rpc_urls: vec![],
+                    health_check: dubhe_adapter::HealthCheckConfig::default(),
                 }),
                 bitcoin: Some(dubhe_adapter::BitcoinConfig {
+                    enabled: false,
                     rpc_url: "http://127.0.0.1:8332".to_string(),
                     rpc_user: "bitcoin".to_string(),
                     rpc_password: "password".to_string(),
+                    backend: dubhe_adapter::BitcoinBackend::BitcoindRpc,
                 }),
+                strict_startup: false,
             },
             scheduler: SchedulerConfig::default(),
+            mempool: MempoolConfig::default(),
             vm: VmConfig {
                 default_vm: VmType::CkbVM,
                 max_instances: 100,
                 move_compiler: MoveCompilerSettings::default(),
+                gas_schedule: GasSchedule::default(),
+                pool: VmPoolConfig::default(),
             },
             node: NodeSettings {
                 data_dir: "./data".to_string(),
                 strategy: StrategyType::SolanaParallel,
                 enable_metrics: true,
+                shutdown_timeout_ms: default_shutdown_timeout_ms(),
+                snapshot_dir: None,
             },
             security: SecurityConfig::default(),
             observability: ObservabilityConfig::default(),
@@ -353,23 +538,187 @@ impl Default for NodeConfig {
             testing: TestingConfig::default(),
             performance: PerformanceConfig::default(),
             alerting: AlertingConfig::default(),
+            hotspot: HotspotConfig::default(),
+            locking: ObjectLockConfig::default(),
+            sessions: SessionConfig::default(),
+            sync: SyncConfig::default(),
+            replay: ReplayConfig::default(),
+            query_cache: QueryCacheConfig::default(),
+            discovery: DiscoveryConfig::default(),
         }
     }
 }
 
+/// 环境变量覆盖的前缀
+pub const ENV_PREFIX: &str = "DUBHE__";
+
+/// 配置校验错误
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// 语法或类型错误，消息中包含行列号
+    #[error("{origin}: {message}")]
+    Invalid { origin: String, message: String },
+
+    #[error("{location}: unknown field `{field}`{hint}")]
+    UnknownField {
+        location: String,
+        field: String,
+        hint: String,
+    },
+
+    #[error("environment variable {var}: {message}")]
+    Environment { var: String, message: String },
+}
+
 impl NodeConfig {
-    /// 从文件加载配置
+    /// 从文件加载配置，并叠加环境变量覆盖
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
-        if path.as_ref().exists() {
+        let path = path.as_ref();
+        if path.exists() {
             let content = std::fs::read_to_string(path)?;
-            let config: NodeConfig = toml::from_str(&content)?;
-            Ok(config)
+            Ok(Self::parse(
+                &content,
+                &path.display().to_string(),
+                process_env(),
+            )?)
         } else {
             // 如果配置文件不存在，创建默认配置
             let config = Self::default();
             config.save(path)?;
-            Ok(config)
+            Ok(config.with_env_overrides(process_env())?)
+        }
+    }
+
+    /// 解析配置文本：拒绝未知字段，再叠加 `env` 中 `DUBHE__` 前缀的覆盖项
+    pub fn parse(
+        content: &str,
+        origin: &str,
+        env: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ConfigError> {
+        let invalid = |message: String| ConfigError::Invalid {
+            origin: origin.to_string(),
+            message,
+        };
+        let config: NodeConfig = toml::from_str(content).map_err(|e| invalid(e.to_string()))?;
+
+        // 反序列化会静默丢弃不认识的键：与重新序列化的结果比对找出它们
+        let raw = toml::Value::Table(toml::from_str(content).map_err(|e| invalid(e.to_string()))?);
+        let known = toml::Value::try_from(&config).map_err(|e| invalid(e.to_string()))?;
+        let mut path = Vec::new();
+        if let Some(candidates) = find_unknown(&raw, &known, &mut path) {
+            let field = display_path(&path);
+            let location = match locate(content, &path) {
+                Some(line) => format!("{}:{}", origin, line),
+                None => origin.to_string(),
+            };
+            let hint = match closest(
+                path.last().map(String::as_str).unwrap_or_default(),
+                &candidates,
+            ) {
+                Some(candidate) => format!(", did you mean `{}`?", candidate),
+                None => String::new(),
+            };
+            return Err(ConfigError::UnknownField {
+                location,
+                field,
+                hint,
+            });
+        }
+
+        let config = config.with_env_overrides(env)?;
+        config
+            .api
+            .auth
+            .rate_limits
+            .validate()
+            .map_err(|message| invalid(format!("api.auth.{}", message)))?;
+        Ok(config)
+    }
+
+    /// 叠加环境变量覆盖：`DUBHE__SCHEDULER__BATCH_SIZE=500` 覆盖 `scheduler.batch_size`。
+    /// 原值为字符串时按原文写入，否则按 TOML 字面量解析（数组写作 `["a", "b"]`）
+    pub fn with_env_overrides(
+        self,
+        env: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ConfigError> {
+        let mut overrides: Vec<(String, Vec<String>, String)> = env
+            .into_iter()
+            .filter_map(|(var, value)| {
+                let path = var
+                    .strip_prefix(ENV_PREFIX)?
+                    .split("__")
+                    .map(str::to_lowercase)
+                    .collect();
+                Some((var, path, value))
+            })
+            .collect();
+        if overrides.is_empty() {
+            return Ok(self);
+        }
+        overrides.sort();
+
+        let env_error = |var: &str, message: String| ConfigError::Environment {
+            var: var.to_string(),
+            message,
+        };
+        let mut value =
+            toml::Value::try_from(&self).map_err(|e| env_error(&overrides[0].0, e.to_string()))?;
+        for (var, path, raw) in &overrides {
+            set_path(&mut value, path, raw).map_err(|message| env_error(var, message))?;
         }
+        let vars: Vec<&str> = overrides.iter().map(|(var, _, _)| var.as_str()).collect();
+        let config: NodeConfig = value
+            .try_into()
+            .map_err(|e| env_error(&vars.join(", "), e.to_string()))?;
+
+        // 覆盖的路径必须对应真实字段，避免拼写错误的变量被静默忽略
+        let known = toml::Value::try_from(&config)
+            .map_err(|e| env_error(&vars.join(", "), e.to_string()))?;
+        for (var, path, _) in &overrides {
+            if lookup(&known, path).is_none() {
+                return Err(env_error(
+                    var,
+                    format!("`{}` is not a config field", path.join(".")),
+                ));
+            }
+        }
+
+        info!(
+            "🔧 Applied {} config overrides from the environment",
+            overrides.len()
+        );
+        Ok(config)
+    }
+
+    /// 与 `other` 相比发生变化、但只能在重启后生效的字段
+    pub fn immutable_changes(&self, other: &NodeConfig) -> Vec<String> {
+        let fields = [
+            ("api.rpc_bind", self.api.rpc_bind == other.api.rpc_bind),
+            ("api.grpc_bind", self.api.grpc_bind == other.api.grpc_bind),
+            ("api.ws_bind", self.api.ws_bind == other.api.ws_bind),
+            (
+                "observability.prometheus_host",
+                self.observability.prometheus_host == other.observability.prometheus_host,
+            ),
+            (
+                "observability.prometheus_port",
+                self.observability.prometheus_port == other.observability.prometheus_port,
+            ),
+            (
+                "observability.tracing",
+                self.observability.tracing_config() == other.observability.tracing_config(),
+            ),
+            ("node.data_dir", self.node.data_dir == other.node.data_dir),
+            (
+                "node.snapshot_dir",
+                self.node.snapshot_dir == other.node.snapshot_dir,
+            ),
+        ];
+        fields
+            .into_iter()
+            .filter(|(_, unchanged)| !unchanged)
+            .map(|(field, _)| field.to_string())
+            .collect()
     }
 
     /// 保存配置到文件
@@ -379,3 +728,267 @@ impl NodeConfig {
         Ok(())
     }
 }
+
+/// 进程环境变量，跳过非 UTF-8 的条目
+fn process_env() -> impl Iterator<Item = (String, String)> {
+    std::env::vars_os()
+        .filter_map(|(var, value)| Some((var.into_string().ok()?, value.into_string().ok()?)))
+}
+
+/// 找出 `raw` 中在 `known` 里不存在的第一个键，返回同一层的合法键名
+fn find_unknown(
+    raw: &toml::Value,
+    known: &toml::Value,
+    path: &mut Vec<String>,
+) -> Option<Vec<String>> {
+    match (raw, known) {
+        (toml::Value::Table(raw), toml::Value::Table(known)) => {
+            for (key, value) in raw {
+                path.push(key.clone());
+                let unknown = match known.get(key) {
+                    Some(known_value) => find_unknown(value, known_value, path),
+                    None => Some(known.keys().cloned().collect()),
+                };
+                if unknown.is_some() {
+                    return unknown;
+                }
+                path.pop();
+            }
+            None
+        }
+        (toml::Value::Array(raw), toml::Value::Array(known)) => {
+            for (index, (value, known_value)) in raw.iter().zip(known).enumerate() {
+                path.push(format!("[{}]", index));
+                let unknown = find_unknown(value, known_value, path);
+                if unknown.is_some() {
+                    return unknown;
+                }
+                path.pop();
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// `alerting.rules[0].name` 形式的路径
+fn display_path(path: &[String]) -> String {
+    path.iter().fold(String::new(), |mut text, segment| {
+        if !text.is_empty() && !segment.starts_with('[') {
+            text.push('.');
+        }
+        text.push_str(segment);
+        text
+    })
+}
+
+/// 字段所在行（从 1 开始）：键所在的赋值行，或以该字段为名的表头
+fn locate(content: &str, path: &[String]) -> Option<usize> {
+    let keys: Vec<&str> = path
+        .iter()
+        .filter(|segment| !segment.starts_with('['))
+        .map(String::as_str)
+        .collect();
+    let (key, parent) = keys.split_last()?;
+    let (full, parent) = (keys.join("."), parent.join("."));
+
+    let mut table = String::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.trim_start_matches('[').split(']').next()?.trim();
+            if header == full {
+                return Some(number + 1);
+            }
+            table = header.to_string();
+        } else if table == parent
+            && line
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+        {
+            return Some(number + 1);
+        }
+    }
+    None
+}
+
+/// 编辑距离足够近的候选字段名
+fn closest<'a>(field: &str, candidates: &'a [String]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(field, candidate), candidate))
+        .filter(|(distance, _)| *distance <= (field.len() / 3).max(2))
+        .min()
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn set_path(root: &mut toml::Value, path: &[String], raw: &str) -> Result<(), String> {
+    if path.iter().any(String::is_empty) {
+        return Err("empty path segment".to_string());
+    }
+    let (key, parents) = path
+        .split_last()
+        .ok_or_else(|| "missing field path".to_string())?;
+
+    let mut table = root
+        .as_table_mut()
+        .ok_or_else(|| "config root is not a table".to_string())?;
+    for segment in parents {
+        if !table.contains_key(segment) {
+            table.insert(segment.clone(), toml::Value::Table(toml::Table::new()));
+        }
+        table = table
+            .get_mut(segment)
+            .and_then(toml::Value::as_table_mut)
+            .ok_or_else(|| format!("`{}` is not a table", segment))?;
+    }
+
+    let value = match table.get(key) {
+        Some(toml::Value::String(_)) => toml::Value::String(raw.to_string()),
+        _ => parse_literal(raw),
+    };
+    table.insert(key.clone(), value);
+    Ok(())
+}
+
+/// 按 TOML 字面量解析，失败时视为字符串
+fn parse_literal(raw: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+fn lookup<'a>(value: &'a toml::Value, path: &[String]) -> Option<&'a toml::Value> {
+    path.iter()
+        .try_fold(value, |value, segment| value.as_table()?.get(segment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_toml() -> String {
+        toml::to_string_pretty(&NodeConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_unknown_field_is_rejected_with_location() {
+        let content = default_toml().replace("\nworker_threads =", "\nworker_thread =");
+        let line = content
+            .lines()
+            .position(|line| line.starts_with("worker_thread ="))
+            .unwrap()
+            + 1;
+
+        let error = NodeConfig::parse(&content, "node.toml", Vec::new()).unwrap_err();
+        match &error {
+            ConfigError::UnknownField {
+                location,
+                field,
+                hint,
+            } => {
+                assert_eq!(location, &format!("node.toml:{}", line));
+                assert_eq!(field, "scheduler.worker_thread");
+                assert!(hint.contains("worker_threads"));
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+
+        // 未知的整张表同样被拒绝
+        let content = format!("{}\n[scheduler.turbo]\nenabled = true\n", default_toml());
+        let error = NodeConfig::parse(&content, "node.toml", Vec::new()).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("unknown field `scheduler.turbo`"));
+
+        assert!(NodeConfig::parse(&default_toml(), "node.toml", Vec::new()).is_ok());
+    }
+
+    #[test]
+    fn test_environment_overrides() {
+        let env = vec![
+            (
+                "DUBHE__SCHEDULER__BATCH_SIZE".to_string(),
+                "512".to_string(),
+            ),
+            (
+                "DUBHE__ADAPTERS__BITCOIN__RPC_PASSWORD".to_string(),
+                "12345".to_string(),
+            ),
+            (
+                "DUBHE__ALERTING__WEBHOOK_URLS".to_string(),
+                r#"["https://hooks.example.com/a"]"#.to_string(),
+            ),
+            ("PATH".to_string(), "/usr/bin".to_string()),
+        ];
+        let config = NodeConfig::parse(&default_toml(), "node.toml", env).unwrap();
+        assert_eq!(config.scheduler.batch_size, 512);
+        assert_eq!(config.adapters.bitcoin.unwrap().rpc_password, "12345");
+        assert_eq!(
+            config.alerting.webhook_urls,
+            vec!["https://hooks.example.com/a"]
+        );
+
+        let typo = vec![("DUBHE__SCHEDULER__BATCH".to_string(), "1".to_string())];
+        let error = NodeConfig::parse(&default_toml(), "node.toml", typo).unwrap_err();
+        assert!(
+            matches!(error, ConfigError::Environment { ref var, .. } if var == "DUBHE__SCHEDULER__BATCH")
+        );
+
+        let wrong_type = vec![(
+            "DUBHE__SCHEDULER__BATCH_SIZE".to_string(),
+            "many".to_string(),
+        )];
+        assert!(NodeConfig::parse(&default_toml(), "node.toml", wrong_type).is_err());
+    }
+
+    #[test]
+    fn test_non_positive_rate_limit_is_rejected() {
+        let env = vec![(
+            "DUBHE__API__AUTH__RATE_LIMITS__EXECUTE__PER_SECOND".to_string(),
+            "0.0".to_string(),
+        )];
+        let error = NodeConfig::parse(&default_toml(), "node.toml", env).unwrap_err();
+        assert!(matches!(error, ConfigError::Invalid { .. }));
+        assert!(error
+            .to_string()
+            .contains("api.auth.rate_limits.execute.per_second"));
+    }
+
+    /// 仓库根目录随附的节点配置（`config*.toml`）必须能被解析
+    #[test]
+    fn test_shipped_configs_parse() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let mut shipped = Vec::new();
+        for entry in std::fs::read_dir(&root).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            if name.starts_with("config") && name.ends_with(".toml") {
+                shipped.push(path);
+            }
+        }
+        assert!(!shipped.is_empty());
+
+        for path in shipped {
+            let content = std::fs::read_to_string(&path).unwrap();
+            if let Err(e) = NodeConfig::parse(&content, &path.display().to_string(), Vec::new()) {
+                panic!("{}", e);
+            }
+        }
+    }
+}
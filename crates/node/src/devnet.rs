@@ -0,0 +1,219 @@
+//! 本地 mock 开发链
+//!
+//! 内存中的包 / 对象存储，实现 [`ChainAdapter`]，用于示例和集成测试，
+//! 不依赖任何外部 RPC 节点
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::{mpsc, RwLock};
+use tracing::info;
+
+use dubhe_adapter::{
+    AbiSource, AdapterError, AdapterResult, ChainAdapter, ChainType, ContractMeta, ContractType,
+    TransactionReceipt, TransactionStatus,
+};
+use dubhe_security::canonical_digest;
+
+/// 回写交易的摘要域
+const DEVNET_TRANSACTION_DOMAIN: &str = "dubhe.node.devnet_transaction";
+
+/// 链上对象
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainObject {
+    pub object_id: String,
+    pub version: u64,
+    pub content: serde_json::Value,
+}
+
+/// mock 开发链
+#[derive(Debug, Default)]
+pub struct DevChain {
+    packages: RwLock<HashMap<String, ContractMeta>>,
+    objects: RwLock<BTreeMap<String, ChainObject>>,
+    receipts: RwLock<Vec<TransactionReceipt>>,
+}
+
+impl DevChain {
+    pub fn new() -> Self {
+        info!("🧪 Booting mock dev chain");
+        Self::default()
+    }
+
+    /// 部署包，返回包 ID
+    pub async fn deploy_package(&self, meta: ContractMeta) -> String {
+        let package_id = meta.address.clone();
+        info!("📦 Deployed package {} on dev chain", package_id);
+        self.packages.write().await.insert(package_id.clone(), meta);
+        package_id
+    }
+
+    /// 创建共享对象
+    pub async fn create_object(&self, object_id: &str, content: serde_json::Value) -> ChainObject {
+        let object = ChainObject {
+            object_id: object_id.to_string(),
+            version: 1,
+            content,
+        };
+        self.objects
+            .write()
+            .await
+            .insert(object_id.to_string(), object.clone());
+        object
+    }
+
+    /// 读取对象
+    pub async fn get_object(&self, object_id: &str) -> Option<ChainObject> {
+        self.objects.read().await.get(object_id).cloned()
+    }
+
+    /// 提交一批对象更新，返回交易摘要
+    ///
+    /// 每个对象的版本号加一，乐观检查调用方持有的版本与链上一致
+    pub async fn apply_updates(&self, updates: Vec<ChainObject>) -> Result<String> {
+        let mut objects = self.objects.write().await;
+
+        for update in &updates {
+            let current = objects
+                .get(&update.object_id)
+                .ok_or_else(|| anyhow!("Object {} not found on dev chain", update.object_id))?;
+            if current.version != update.version {
+                return Err(anyhow!(
+                    "Version mismatch for {}: chain has {}, update based on {}",
+                    update.object_id,
+                    current.version,
+                    update.version
+                ));
+            }
+        }
+
+        let tx_hash = canonical_digest(DEVNET_TRANSACTION_DOMAIN, &updates)?.digest;
+        for update in &updates {
+            objects.insert(
+                update.object_id.clone(),
+                ChainObject {
+                    version: update.version + 1,
+                    ..update.clone()
+                },
+            );
+        }
+        drop(objects);
+
+        let mut receipts = self.receipts.write().await;
+        let block_number = receipts.len() as u64 + 1;
+        receipts.push(TransactionReceipt {
+            tx_hash: tx_hash.clone(),
+            block_hash: tx_hash.clone(),
+            block_number,
+            transaction_index: 0,
+            from: "dubhe-channel".to_string(),
+            to: None,
+            gas_used: 0,
+            status: TransactionStatus::Success,
+            logs: vec![],
+            contract_address: None,
+        });
+
+        Ok(tx_hash)
+    }
+}
+
+/// counter 示例包的元数据
+pub fn counter_package_meta(package_id: &str) -> ContractMeta {
+    ContractMeta {
+        address: package_id.to_string(),
+        chain_type: ChainType::Sui,
+        contract_type: ContractType::Move,
+        bytecode: b"module counter::counter".to_vec(),
+        abi: Some(
+            serde_json::json!({
+                "module": "counter",
+                "functions": ["increment", "set_value", "reset", "value"]
+            })
+            .to_string(),
+        ),
+        source_code: None,
+        compiler_version: None,
+        created_at: chrono::Utc::now().timestamp() as u64,
+        creator: None,
+        abi_source: Some(AbiSource::Verified),
+        modules: vec![],
+    }
+}
+
+#[async_trait]
+impl ChainAdapter for DevChain {
+    async fn get_contract_meta(&self, address: &str) -> AdapterResult<ContractMeta> {
+        self.packages
+            .read()
+            .await
+            .get(address)
+            .cloned()
+            .ok_or_else(|| AdapterError::ContractNotFound {
+                chain: ChainType::Sui,
+                address: address.to_string(),
+            })
+    }
+
+    async fn get_transaction_receipt(&self, tx_hash: &str) -> AdapterResult<TransactionReceipt> {
+        self.receipts
+            .read()
+            .await
+            .iter()
+            .find(|r| r.tx_hash == tx_hash)
+            .cloned()
+            .ok_or_else(|| anyhow!("Transaction {} not found on dev chain", tx_hash).into())
+    }
+
+    async fn get_balance(&self, _address: &str) -> AdapterResult<u64> {
+        Ok(0)
+    }
+
+    async fn get_nonce(&self, _address: &str) -> AdapterResult<u64> {
+        Ok(0)
+    }
+
+    async fn get_block_number(&self) -> AdapterResult<u64> {
+        Ok(self.receipts.read().await.len() as u64)
+    }
+
+    async fn subscribe_new_blocks(&self) -> AdapterResult<mpsc::Receiver<String>> {
+        let (_tx, rx) = mpsc::channel(1);
+        Ok(rx)
+    }
+
+    async fn subscribe_new_transactions(&self) -> AdapterResult<mpsc::Receiver<String>> {
+        let (_tx, rx) = mpsc::channel(1);
+        Ok(rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_apply_updates_bumps_version() {
+        let chain = DevChain::new();
+        let object = chain
+            .create_object("0xc0", serde_json::json!({ "value": 0 }))
+            .await;
+
+        let tx_hash = chain
+            .apply_updates(vec![ChainObject {
+                content: serde_json::json!({ "value": 3 }),
+                ..object.clone()
+            }])
+            .await
+            .unwrap();
+        assert!(chain.get_transaction_receipt(&tx_hash).await.is_ok());
+
+        let updated = chain.get_object("0xc0").await.unwrap();
+        assert_eq!(updated.version, 2);
+        assert_eq!(updated.content["value"], 3);
+
+        // 基于旧版本的更新被拒绝
+        assert!(chain.apply_updates(vec![object]).await.is_err());
+    }
+}
@@ -0,0 +1,494 @@
+//! 合约包发现与预取
+//!
+//! 合约默认在首次调用时才编译，首个请求要承担全部编译延迟。本模块订阅适配器事件总线上的
+//! Sui 新交易，取回每笔交易引用的包（Move 调用目标、新发布的包与变更对象类型所属的包），
+//! 经允许 / 拒绝名单与“已见”布隆过滤器筛选后，在后台经 `AdapterManager::get_contract_meta`
+//! 拉取元数据并交给 `CodeLoader::load_contracts_batch` 编译。同时处理的交易数与每秒 RPC
+//! 请求数都有上限，避免压垮链上节点。
+//!
+//! 布隆过滤器可能误判，极少数新包会被当作已见而跳过，拉取或编译失败的包也不会自动重试；
+//! 这些包仍会在首次调用时照常编译
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::f64::consts::LN_2;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tokio::time::{Interval, MissedTickBehavior};
+use tracing::{debug, info, warn};
+
+use dubhe_adapter::{AdapterManager, ChainEventKind, ChainType, ContractMeta};
+use dubhe_api::{PrefetchHandler, PrefetchReport};
+use dubhe_loader::{CodeLoader, CompiledContract};
+use dubhe_observability::MetricsCollector;
+
+/// 通过名单与去重、进入预取的新包数
+pub const DISCOVERY_DISCOVERED_COUNTER: &str = "dubhe_discovery_packages_discovered_total";
+/// 预取编译成功的包数
+pub const DISCOVERY_COMPILED_COUNTER: &str = "dubhe_discovery_packages_compiled_total";
+/// 因名单或已见过而跳过的包数
+pub const DISCOVERY_SKIPPED_COUNTER: &str = "dubhe_discovery_packages_skipped_total";
+/// 拉取元数据或编译失败的包数
+pub const DISCOVERY_FAILED_COUNTER: &str = "dubhe_discovery_packages_failed_total";
+
+/// 包发现配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiscoveryConfig {
+    /// 是否跟随新交易自动预取；关闭时 dubhe_prefetchPackage 仍可用
+    pub enabled: bool,
+    /// 非空时只预取其中的包
+    pub allowlist: Vec<String>,
+    /// 从不自动预取的包，默认为 Move 标准库、Sui 框架与系统包
+    pub denylist: Vec<String>,
+    /// 同时处理的交易数
+    pub max_concurrency: usize,
+    /// 每秒发往链上节点的 RPC 请求数上限
+    pub max_requests_per_sec: u32,
+    /// 布隆过滤器按该数量的包设计容量
+    pub seen_capacity: usize,
+    /// 达到设计容量时的误判率
+    pub false_positive_rate: f64,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allowlist: Vec::new(),
+            denylist: vec!["0x1".to_string(), "0x2".to_string(), "0x3".to_string()],
+            max_concurrency: 4,
+            max_requests_per_sec: 20,
+            seen_capacity: 100_000,
+            false_positive_rate: 0.01,
+        }
+    }
+}
+
+/// 规范化包 ID：小写并去掉前导零，`0x0002` 与 `0x2` 视为同一个包
+fn normalize(package_id: &str) -> String {
+    let package_id = package_id.trim().to_ascii_lowercase();
+    let digits = package_id
+        .strip_prefix("0x")
+        .unwrap_or(&package_id)
+        .trim_start_matches('0');
+    if digits.is_empty() {
+        "0x0".to_string()
+    } else {
+        format!("0x{}", digits)
+    }
+}
+
+/// 已见包的布隆过滤器
+struct SeenFilter {
+    bits: Vec<u64>,
+    len: u64,
+    hashes: u64,
+}
+
+impl SeenFilter {
+    fn new(capacity: usize, false_positive_rate: f64) -> Self {
+        let capacity = capacity.max(1) as f64;
+        let rate = false_positive_rate.clamp(1e-9, 0.5);
+        let len = (-capacity * rate.ln() / (LN_2 * LN_2)).ceil().max(64.0) as u64;
+        let hashes = (len as f64 / capacity * LN_2).round().clamp(1.0, 16.0) as u64;
+        Self {
+            bits: vec![0; ((len + 63) / 64) as usize],
+            len,
+            hashes,
+        }
+    }
+
+    /// 加入 `value`；可能已经存在时返回 `false`
+    fn insert(&mut self, value: &str) -> bool {
+        let digest = Sha256::digest(value.as_bytes());
+        let h1 = u64::from_le_bytes(digest[..8].try_into().unwrap());
+        let h2 = u64::from_le_bytes(digest[8..16].try_into().unwrap());
+        let mut inserted = false;
+        for i in 0..self.hashes {
+            let position = h1.wrapping_add(i.wrapping_mul(h2)) % self.len;
+            let (word, bit) = ((position / 64) as usize, 1u64 << (position % 64));
+            if self.bits[word] & bit == 0 {
+                self.bits[word] |= bit;
+                inserted = true;
+            }
+        }
+        inserted
+    }
+}
+
+/// 按固定间隔放行 RPC 请求
+struct RateLimiter {
+    interval: tokio::sync::Mutex<Interval>,
+}
+
+impl RateLimiter {
+    fn new(per_sec: u32) -> Self {
+        let mut interval = tokio::time::interval(Duration::from_secs(1) / per_sec.max(1));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self {
+            interval: tokio::sync::Mutex::new(interval),
+        }
+    }
+
+    async fn acquire(&self) {
+        self.interval.lock().await.tick().await;
+    }
+}
+
+/// 从 Sui 新交易中发现并预编译合约包
+pub struct PackageDiscovery {
+    adapters: Arc<AdapterManager>,
+    loader: Arc<CodeLoader>,
+    allowlist: HashSet<String>,
+    denylist: HashSet<String>,
+    seen: Mutex<SeenFilter>,
+    limiter: RateLimiter,
+    slots: Arc<Semaphore>,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl PackageDiscovery {
+    pub fn new(
+        adapters: Arc<AdapterManager>,
+        loader: Arc<CodeLoader>,
+        config: &DiscoveryConfig,
+    ) -> Self {
+        let normalize_all = |ids: &[String]| ids.iter().map(|id| normalize(id)).collect();
+        Self {
+            adapters,
+            loader,
+            allowlist: normalize_all(&config.allowlist),
+            denylist: normalize_all(&config.denylist),
+            seen: Mutex::new(SeenFilter::new(
+                config.seen_capacity,
+                config.false_positive_rate,
+            )),
+            limiter: RateLimiter::new(config.max_requests_per_sec),
+            slots: Arc::new(Semaphore::new(config.max_concurrency.max(1))),
+            metrics: Arc::new(MetricsCollector::new()),
+        }
+    }
+
+    /// 计数写入共享的指标收集器
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// 跟随事件总线上的 Sui 新交易预取新包
+    ///
+    /// 需在适配器后台任务启动前调用，避免丢失最早的事件
+    pub fn spawn(self: &Arc<Self>) -> JoinHandle<()> {
+        let mut events = self.adapters.subscribe_events();
+        let discovery = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(event)
+                        if event.chain_type == ChainType::Sui
+                            && event.kind == ChainEventKind::NewTransaction =>
+                    {
+                        // 处理中的交易达到上限时停止读取事件，落后过多的事件由总线丢弃
+                        let permit = discovery
+                            .slots
+                            .clone()
+                            .acquire_owned()
+                            .await
+                            .expect("discovery semaphore is never closed");
+                        let discovery = discovery.clone();
+                        tokio::spawn(async move {
+                            discovery.process_transaction(&event.payload).await;
+                            drop(permit);
+                        });
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Package discovery lagged, skipped {} chain events", skipped)
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// 预取一笔交易引用的新包
+    async fn process_transaction(&self, digest: &str) {
+        self.limiter.acquire().await;
+        let packages = match self
+            .adapters
+            .get_transaction_packages(ChainType::Sui, digest)
+            .await
+        {
+            Ok(packages) => packages,
+            Err(e) => {
+                debug!("Failed to fetch packages referenced by {}: {}", digest, e);
+                return;
+            }
+        };
+        let fresh = self.filter(packages);
+        if !fresh.is_empty() {
+            debug!(
+                "📦 Transaction {} references new packages {:?}",
+                digest, fresh
+            );
+            self.prefetch(fresh).await;
+        }
+    }
+
+    /// 按名单与已见过滤，返回首次出现的包
+    fn filter(&self, packages: Vec<String>) -> Vec<String> {
+        let mut seen = self.seen.lock().unwrap();
+        let mut fresh = Vec::new();
+        let mut skipped = 0;
+        for package in packages {
+            let id = normalize(&package);
+            let listed = self.denylist.contains(&id)
+                || (!self.allowlist.is_empty() && !self.allowlist.contains(&id));
+            if listed || !seen.insert(&id) {
+                skipped += 1;
+            } else {
+                fresh.push(package);
+            }
+        }
+        self.metrics
+            .inc_counter(DISCOVERY_DISCOVERED_COUNTER, fresh.len() as u64);
+        self.metrics.inc_counter(DISCOVERY_SKIPPED_COUNTER, skipped);
+        fresh
+    }
+
+    /// 拉取元数据并批量编译，结果与 `packages` 一一对应
+    async fn prefetch(&self, packages: Vec<String>) -> Vec<Result<CompiledContract>> {
+        let mut metas: Vec<ContractMeta> = Vec::new();
+        let mut results: Vec<Option<Result<CompiledContract>>> = Vec::new();
+        for package in &packages {
+            self.limiter.acquire().await;
+            match self
+                .adapters
+                .get_contract_meta(ChainType::Sui, package)
+                .await
+                .with_context(|| format!("Failed to fetch package {}", package))
+            {
+                Ok(meta) => {
+                    metas.push(meta);
+                    results.push(None);
+                }
+                Err(e) => results.push(Some(Err(e))),
+            }
+        }
+
+        let mut compiled = self.loader.load_contracts_batch(&metas).await.into_iter();
+        let results: Vec<Result<CompiledContract>> = results
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|| compiled.next().expect("one result per meta")))
+            .collect();
+
+        for (package, result) in packages.iter().zip(&results) {
+            match result {
+                Ok(_) => {
+                    self.metrics.inc_counter(DISCOVERY_COMPILED_COUNTER, 1);
+                    info!("📦 Prefetched package {}", package);
+                }
+                Err(e) => {
+                    self.metrics.inc_counter(DISCOVERY_FAILED_COUNTER, 1);
+                    warn!("⚠️ Failed to prefetch package {}: {:#}", package, e);
+                }
+            }
+        }
+        results
+    }
+}
+
+#[async_trait]
+impl PrefetchHandler for PackageDiscovery {
+    async fn prefetch_package(&self, package_id: String) -> Result<PrefetchReport> {
+        info!("📦 Prefetch requested for package {}", package_id);
+        self.seen.lock().unwrap().insert(&normalize(&package_id));
+        self.metrics.inc_counter(DISCOVERY_DISCOVERED_COUNTER, 1);
+        let compiled = self
+            .prefetch(vec![package_id.clone()])
+            .await
+            .pop()
+            .expect("one result per package")?;
+        Ok(PrefetchReport {
+            package_id,
+            code_size: compiled.risc_v_code.len(),
+            entry_points: compiled.entry_points,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dubhe_adapter::{
+        AdapterError, AdapterResult, ChainAdapter, ContractType, TransactionReceipt,
+    };
+    use tempfile::tempdir;
+    use tokio::sync::mpsc;
+
+    /// 推送固定交易、按交易报告引用包的适配器
+    struct TransactionFeed {
+        transactions: Vec<(&'static str, Vec<&'static str>)>,
+        fetched: Arc<Mutex<Vec<String>>>,
+        /// 持有订阅的发送端，订阅不会结束
+        senders: Mutex<Vec<mpsc::Sender<String>>>,
+    }
+
+    impl TransactionFeed {
+        fn new(transactions: Vec<(&'static str, Vec<&'static str>)>) -> Self {
+            Self {
+                transactions,
+                fetched: Arc::new(Mutex::new(Vec::new())),
+                senders: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ChainAdapter for TransactionFeed {
+        async fn get_contract_meta(&self, address: &str) -> AdapterResult<ContractMeta> {
+            self.fetched.lock().unwrap().push(address.to_string());
+            Ok(ContractMeta {
+                address: address.to_string(),
+                chain_type: ChainType::Sui,
+                contract_type: ContractType::Move,
+                bytecode: address.as_bytes().to_vec(),
+                abi: None,
+                source_code: None,
+                compiler_version: None,
+                created_at: 0,
+                creator: None,
+                abi_source: None,
+                modules: vec![],
+            })
+        }
+
+        async fn get_transaction_receipt(
+            &self,
+            _tx_hash: &str,
+        ) -> AdapterResult<TransactionReceipt> {
+            Err(AdapterError::Unsupported(
+                "get_transaction_receipt".to_string(),
+            ))
+        }
+
+        async fn get_balance(&self, _address: &str) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn get_nonce(&self, _address: &str) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn get_block_number(&self) -> AdapterResult<u64> {
+            Ok(1)
+        }
+
+        async fn subscribe_new_blocks(&self) -> AdapterResult<mpsc::Receiver<String>> {
+            let (tx, rx) = mpsc::channel(1);
+            self.senders.lock().unwrap().push(tx);
+            Ok(rx)
+        }
+
+        async fn subscribe_new_transactions(&self) -> AdapterResult<mpsc::Receiver<String>> {
+            let (tx, rx) = mpsc::channel(self.transactions.len().max(1));
+            for (digest, _) in &self.transactions {
+                tx.send(digest.to_string()).await.unwrap();
+            }
+            self.senders.lock().unwrap().push(tx);
+            Ok(rx)
+        }
+
+        async fn get_transaction_packages(&self, tx_hash: &str) -> AdapterResult<Vec<String>> {
+            let (_, packages) = self
+                .transactions
+                .iter()
+                .find(|(digest, _)| *digest == tx_hash)
+                .ok_or_else(|| anyhow::anyhow!("unknown transaction {}", tx_hash))?;
+            Ok(packages.iter().map(|package| package.to_string()).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_new_transactions_prefetch_referenced_packages() -> Result<()> {
+        let feed = TransactionFeed::new(vec![
+            ("tx1", vec!["0xa", "0x2"]),
+            ("tx2", vec!["0x000A", "0xb"]),
+        ]);
+        let fetched = feed.fetched.clone();
+        let adapters = Arc::new(AdapterManager::new());
+        adapters
+            .register_adapter(ChainType::Sui, Box::new(feed))
+            .await;
+        let temp_dir = tempdir()?;
+        let loader = Arc::new(CodeLoader::with_cache_dir(temp_dir.path())?);
+        let metrics = Arc::new(MetricsCollector::new());
+        let discovery = Arc::new(
+            PackageDiscovery::new(
+                adapters.clone(),
+                loader,
+                &DiscoveryConfig {
+                    max_concurrency: 1,
+                    max_requests_per_sec: 1_000,
+                    ..Default::default()
+                },
+            )
+            .with_metrics(metrics.clone()),
+        );
+
+        let task = discovery.spawn();
+        adapters.start_background_tasks().await?;
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while metrics.counter(DISCOVERY_COMPILED_COUNTER) < 2 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("packages were not prefetched");
+
+        // 0x2 在默认拒绝名单中，0x000A 与 0xa 是同一个包
+        assert_eq!(*fetched.lock().unwrap(), vec!["0xa", "0xb"]);
+        assert_eq!(metrics.counter(DISCOVERY_DISCOVERED_COUNTER), 2);
+        assert_eq!(metrics.counter(DISCOVERY_SKIPPED_COUNTER), 2);
+        assert_eq!(metrics.counter(DISCOVERY_FAILED_COUNTER), 0);
+
+        // 强制预取绕过去重
+        let report = discovery.prefetch_package("0xa".to_string()).await?;
+        assert_eq!(report.package_id, "0xa");
+        assert!(report.code_size > 0);
+        assert_eq!(fetched.lock().unwrap().len(), 3);
+        assert_eq!(metrics.counter(DISCOVERY_COMPILED_COUNTER), 3);
+
+        task.abort();
+        adapters.stop().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_and_seen_filter() {
+        let temp_dir = tempdir().unwrap();
+        let discovery = PackageDiscovery::new(
+            Arc::new(AdapterManager::new()),
+            Arc::new(CodeLoader::with_cache_dir(temp_dir.path()).unwrap()),
+            &DiscoveryConfig {
+                allowlist: vec!["0xA".to_string(), "0xb".to_string()],
+                ..Default::default()
+            },
+        );
+        let packages = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect();
+
+        assert_eq!(discovery.filter(packages(&["0xa", "0xc"])), vec!["0xa"]);
+        assert_eq!(
+            discovery.filter(packages(&["0x0a", "0xb", "0xb"])),
+            vec!["0xb"]
+        );
+        assert_eq!(discovery.metrics.counter(DISCOVERY_SKIPPED_COUNTER), 3);
+    }
+}
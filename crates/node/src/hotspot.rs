@@ -0,0 +1,633 @@
+//! 共享对象热点检测与建议合批
+//!
+//! 统计每个共享对象在时间窗口内的访问次数与串行（持锁）时间，
+//! 超过阈值的对象被标记为热点；热点对象上的待执行会话会被合并到
+//! 同一次加锁中依次执行，每个请求仍然单独计费、单独返回结果。
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use dubhe_observability::{AlertRule, AlertSeverity, Comparison, MetricsCollector};
+
+use crate::offchain_execution::{ExecutionRequest, OffchainExecutionResult};
+
+/// 当前热点对象数量
+pub const HOTSPOT_OBJECTS_GAUGE: &str = "dubhe_hotspot_objects";
+/// 被合批执行的请求总数
+pub const HOTSPOT_COALESCED_COUNTER: &str = "dubhe_hotspot_coalesced_requests_total";
+/// 合批加锁次数
+pub const HOTSPOT_BATCH_LOCKS_COUNTER: &str = "dubhe_hotspot_batch_locks_total";
+
+/// 热点检测配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotspotConfig {
+    /// 统计窗口（毫秒）
+    pub window_ms: u64,
+    /// 窗口内访问次数达到该值即视为热点
+    pub access_threshold: usize,
+    /// 窗口内累计持锁时间达到该值即视为热点（毫秒）
+    pub serialization_threshold_ms: u64,
+    /// 是否对热点对象启用建议合批
+    pub enable_coalescing: bool,
+    /// 合批等待窗口（毫秒），leader 在加锁前等待其它请求加入
+    pub coalesce_window_ms: u64,
+    /// 单次持锁最多执行的请求数
+    pub max_batch_size: usize,
+}
+
+impl Default for HotspotConfig {
+    fn default() -> Self {
+        Self {
+            window_ms: 10_000,
+            access_threshold: 50,
+            serialization_threshold_ms: 2_000,
+            enable_coalescing: true,
+            coalesce_window_ms: 5,
+            max_batch_size: 64,
+        }
+    }
+}
+
+/// 单个对象的争用统计
+#[derive(Debug, Default)]
+struct ObjectContention {
+    accesses: VecDeque<Instant>,
+    serialization: VecDeque<(Instant, Duration)>,
+    total_accesses: u64,
+}
+
+impl ObjectContention {
+    fn prune(&mut self, now: Instant, window: Duration) {
+        while matches!(self.accesses.front(), Some(t) if now.duration_since(*t) > window) {
+            self.accesses.pop_front();
+        }
+        while matches!(self.serialization.front(), Some((t, _)) if now.duration_since(*t) > window)
+        {
+            self.serialization.pop_front();
+        }
+    }
+
+    fn serialization_in_window(&self) -> Duration {
+        self.serialization.iter().map(|(_, held)| *held).sum()
+    }
+}
+
+/// 热点对象报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotspotReport {
+    pub object_id: String,
+    pub accesses_in_window: usize,
+    pub serialization_ms_in_window: u64,
+    pub total_accesses: u64,
+}
+
+/// 共享对象争用跟踪器
+pub struct HotspotTracker {
+    config: HotspotConfig,
+    objects: Mutex<HashMap<String, ObjectContention>>,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl HotspotTracker {
+    pub fn new(config: HotspotConfig, metrics: Arc<MetricsCollector>) -> Self {
+        Self {
+            config,
+            objects: Mutex::new(HashMap::new()),
+            metrics,
+        }
+    }
+
+    pub fn config(&self) -> &HotspotConfig {
+        &self.config
+    }
+
+    /// 记录一次对象访问
+    pub fn record_access(&self, object_id: &str) {
+        let now = Instant::now();
+        let mut objects = self.objects.lock().unwrap();
+        let entry = objects.entry(object_id.to_string()).or_default();
+        entry.prune(now, self.window());
+        entry.accesses.push_back(now);
+        entry.total_accesses += 1;
+        self.publish(&objects, now);
+    }
+
+    /// 记录一次持锁（串行执行）耗时
+    pub fn record_serialization(&self, object_id: &str, held: Duration) {
+        let now = Instant::now();
+        let mut objects = self.objects.lock().unwrap();
+        let entry = objects.entry(object_id.to_string()).or_default();
+        entry.prune(now, self.window());
+        entry.serialization.push_back((now, held));
+        self.publish(&objects, now);
+    }
+
+    /// 对象当前是否为热点
+    pub fn is_hot(&self, object_id: &str) -> bool {
+        let now = Instant::now();
+        let mut objects = self.objects.lock().unwrap();
+        match objects.get_mut(object_id) {
+            Some(entry) => {
+                entry.prune(now, self.window());
+                self.exceeds_thresholds(entry)
+            }
+            None => false,
+        }
+    }
+
+    /// 列出当前所有热点对象，按窗口内访问次数降序
+    pub fn hot_objects(&self) -> Vec<HotspotReport> {
+        let now = Instant::now();
+        let mut objects = self.objects.lock().unwrap();
+        let mut reports: Vec<HotspotReport> = objects
+            .iter_mut()
+            .filter_map(|(object_id, entry)| {
+                entry.prune(now, self.window());
+                self.exceeds_thresholds(entry).then(|| HotspotReport {
+                    object_id: object_id.clone(),
+                    accesses_in_window: entry.accesses.len(),
+                    serialization_ms_in_window: entry.serialization_in_window().as_millis()
+                        as u64,
+                    total_accesses: entry.total_accesses,
+                })
+            })
+            .collect();
+        reports.sort_by(|a, b| b.accesses_in_window.cmp(&a.accesses_in_window));
+        reports
+    }
+
+    fn window(&self) -> Duration {
+        Duration::from_millis(self.config.window_ms)
+    }
+
+    fn exceeds_thresholds(&self, entry: &ObjectContention) -> bool {
+        entry.accesses.len() >= self.config.access_threshold
+            || entry.serialization_in_window()
+                >= Duration::from_millis(self.config.serialization_threshold_ms)
+    }
+
+    fn publish(&self, objects: &HashMap<String, ObjectContention>, now: Instant) {
+        let window = self.window();
+        let hot = objects
+            .values()
+            .filter(|entry| {
+                let accesses = entry
+                    .accesses
+                    .iter()
+                    .filter(|t| now.duration_since(**t) <= window)
+                    .count();
+                let held: Duration = entry
+                    .serialization
+                    .iter()
+                    .filter(|(t, _)| now.duration_since(*t) <= window)
+                    .map(|(_, held)| *held)
+                    .sum();
+                accesses >= self.config.access_threshold
+                    || held >= Duration::from_millis(self.config.serialization_threshold_ms)
+            })
+            .count();
+        self.metrics.set_gauge(HOTSPOT_OBJECTS_GAUGE, hot as f64);
+    }
+}
+
+/// 热点告警规则：出现任意热点对象即告警
+pub fn hotspot_alert_rule() -> AlertRule {
+    AlertRule {
+        name: "SharedObjectHotspot".to_string(),
+        metric: HOTSPOT_OBJECTS_GAUGE.to_string(),
+        comparison: Comparison::GreaterThan,
+        threshold: 0.0,
+        duration_secs: 0,
+        severity: AlertSeverity::Warning,
+        description: "Shared object contention exceeds hot-spot thresholds".to_string(),
+    }
+}
+
+/// 合批执行所需的执行端
+///
+/// 对象锁在整个批次内只获取一次，`execute_locked` 在持锁状态下逐个执行请求
+#[async_trait]
+pub trait CoalescedExecutor: Send + Sync {
+    async fn acquire_object_lock(&self, object_id: &str) -> Result<()>;
+
+    async fn execute_locked(&self, request: &ExecutionRequest) -> Result<OffchainExecutionResult>;
+
+    async fn release_object_lock(&self, object_id: &str) -> Result<()>;
+}
+
+type Waiter = oneshot::Sender<Result<OffchainExecutionResult>>;
+type Queues = Mutex<HashMap<String, VecDeque<(ExecutionRequest, Waiter)>>>;
+
+/// 热点对象会话合并器
+pub struct SessionCoalescer {
+    tracker: Arc<HotspotTracker>,
+    metrics: Arc<MetricsCollector>,
+    queues: Queues,
+}
+
+impl SessionCoalescer {
+    pub fn new(tracker: Arc<HotspotTracker>, metrics: Arc<MetricsCollector>) -> Self {
+        Self {
+            tracker,
+            metrics,
+            queues: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 提交一个针对热点对象的请求
+    ///
+    /// 第一个到达的请求成为 leader，负责加锁并依次执行队列中的所有请求；
+    /// 其余请求只等待自己的结果。
+    pub async fn submit<E: CoalescedExecutor + ?Sized>(
+        &self,
+        object_id: &str,
+        request: ExecutionRequest,
+        executor: &E,
+    ) -> Result<OffchainExecutionResult> {
+        let (tx, rx) = oneshot::channel();
+
+        let is_leader = {
+            let mut queues = self.queues.lock().unwrap();
+            match queues.get_mut(object_id) {
+                Some(queue) => {
+                    queue.push_back((request, tx));
+                    false
+                }
+                None => {
+                    queues.insert(object_id.to_string(), VecDeque::from(vec![(request, tx)]));
+                    true
+                }
+            }
+        };
+
+        if is_leader {
+            self.lead(object_id, executor).await;
+        }
+
+        rx.await
+            .map_err(|_| anyhow::anyhow!("Coalesced batch on {} dropped the request", object_id))?
+    }
+
+    /// leader 循环：加锁 → 执行一批 → 解锁，直到队列为空
+    async fn lead<E: CoalescedExecutor + ?Sized>(&self, object_id: &str, executor: &E) {
+        let mut guard = LeaderGuard {
+            queues: &self.queues,
+            object_id,
+            armed: true,
+        };
+        let config = self.tracker.config().clone();
+        tokio::time::sleep(Duration::from_millis(config.coalesce_window_ms)).await;
+
+        loop {
+            if let Err(e) = executor.acquire_object_lock(object_id).await {
+                let message = format!("Failed to lock hot object {}: {}", object_id, e);
+                warn!("❌ {}", message);
+                let pending = {
+                    let mut queues = self.queues.lock().unwrap();
+                    guard.armed = false;
+                    queues.remove(object_id).unwrap_or_default()
+                };
+                for (_, waiter) in pending {
+                    let _ = waiter.send(Err(anyhow::anyhow!(message.clone())));
+                }
+                return;
+            }
+
+            let locked_at = Instant::now();
+            self.metrics.inc_counter(HOTSPOT_BATCH_LOCKS_COUNTER, 1);
+
+            let batch: Vec<(ExecutionRequest, Waiter)> = {
+                let mut queues = self.queues.lock().unwrap();
+                let queue = queues.entry(object_id.to_string()).or_default();
+                let take = queue.len().min(config.max_batch_size.max(1));
+                queue.drain(..take).collect()
+            };
+
+            info!(
+                "🔥 Executing {} coalesced requests on hot object {}",
+                batch.len(),
+                object_id
+            );
+            self.metrics
+                .inc_counter(HOTSPOT_COALESCED_COUNTER, batch.len() as u64);
+
+            for (request, waiter) in batch {
+                // 单个请求失败不影响同批次的其它请求
+                let result = match executor.execute_locked(&request).await {
+                    Ok(result) => result,
+                    Err(e) => failed_result(&request, e.to_string()),
+                };
+                let _ = waiter.send(Ok(result));
+            }
+
+            if let Err(e) = executor.release_object_lock(object_id).await {
+                warn!("Failed to release hot object {}: {}", object_id, e);
+            }
+            self.tracker
+                .record_serialization(object_id, locked_at.elapsed());
+
+            let mut queues = self.queues.lock().unwrap();
+            if queues.get(object_id).is_none_or(|queue| queue.is_empty()) {
+                guard.armed = false;
+                queues.remove(object_id);
+                return;
+            }
+        }
+    }
+}
+
+/// leader 的 future 在循环结束前被丢弃（如调用方超时）时移除队列并让排队的请求失败，
+/// 否则之后的请求会加入没有 leader 的队列而永远等待；已取出的批次随 future 一起丢弃，
+/// 其等待者同样收到失败
+struct LeaderGuard<'a> {
+    queues: &'a Queues,
+    object_id: &'a str,
+    /// leader 正常结束时在持有队列锁的情况下解除，避免误删新 leader 的队列
+    armed: bool,
+}
+
+impl Drop for LeaderGuard<'_> {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        warn!("Leader for hot object {} was cancelled", self.object_id);
+        let pending = self
+            .queues
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(self.object_id)
+            .unwrap_or_default();
+        for (_, waiter) in pending {
+            let _ = waiter.send(Err(anyhow::anyhow!(
+                "Coalesced batch on {} was cancelled",
+                self.object_id
+            )));
+        }
+    }
+}
+
+fn failed_result(request: &ExecutionRequest, error: String) -> OffchainExecutionResult {
+    OffchainExecutionResult {
+        session_id: request.session_id.clone(),
+        success: false,
+        gas_used: 0,
+        modified_objects: vec![],
+        new_objects: vec![],
+        deleted_objects: vec![],
+        commits: vec![],
+        error: Some(error),
+        execution_time_ms: 0,
+        attestation: None,
+        inputs_hash: String::new(),
+        effects_digest: None,
+        sync_transactions: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offchain_execution::{ModifiedObject, ObjectChanges};
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 模拟计数器对象的执行端
+    #[derive(Default)]
+    struct CounterExecutor {
+        lock_acquisitions: AtomicUsize,
+        value: tokio::sync::Mutex<u64>,
+    }
+
+    #[async_trait]
+    impl CoalescedExecutor for CounterExecutor {
+        async fn acquire_object_lock(&self, _object_id: &str) -> Result<()> {
+            self.lock_acquisitions.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn execute_locked(
+            &self,
+            request: &ExecutionRequest,
+        ) -> Result<OffchainExecutionResult> {
+            if request.function_name == "abort" {
+                return Err(anyhow::anyhow!("aborted by guest"));
+            }
+
+            let mut value = self.value.lock().await;
+            *value += 1;
+
+            Ok(OffchainExecutionResult {
+                session_id: request.session_id.clone(),
+                success: true,
+                gas_used: request.gas_budget / 10,
+                modified_objects: vec![ModifiedObject {
+                    object_id: request.shared_objects[0].clone(),
+                    old_version: *value - 1,
+                    new_content: serde_json::json!({ "value": *value }),
+                    changes: ObjectChanges {
+                        fields_modified: vec!["value".to_string()],
+                        ..Default::default()
+                    },
+                }],
+                new_objects: vec![],
+                deleted_objects: vec![],
+                commits: vec![],
+                error: None,
+                execution_time_ms: 0,
+                attestation: None,
+                inputs_hash: String::new(),
+                effects_digest: None,
+                sync_transactions: vec![],
+            })
+        }
+
+        async fn release_object_lock(&self, _object_id: &str) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn increment(i: usize, function_name: &str) -> ExecutionRequest {
+        ExecutionRequest {
+            session_id: format!("increment_{}", i),
+            package_id: "0xcounter".to_string(),
+            function_name: function_name.to_string(),
+            arguments: vec![],
+            shared_objects: vec!["0xpool".to_string()],
+            gas_budget: 1000 * (i as u64 + 1),
+            read_only: false,
+        }
+    }
+
+    fn coalescer() -> (Arc<SessionCoalescer>, Arc<HotspotTracker>, Arc<MetricsCollector>) {
+        let metrics = Arc::new(MetricsCollector::new());
+        let config = HotspotConfig {
+            access_threshold: 1,
+            coalesce_window_ms: 50,
+            ..HotspotConfig::default()
+        };
+        let tracker = Arc::new(HotspotTracker::new(config, metrics.clone()));
+        let coalescer = Arc::new(SessionCoalescer::new(tracker.clone(), metrics.clone()));
+        (coalescer, tracker, metrics)
+    }
+
+    #[test]
+    fn test_hotspot_detection() {
+        let metrics = Arc::new(MetricsCollector::new());
+        let config = HotspotConfig {
+            access_threshold: 3,
+            ..HotspotConfig::default()
+        };
+        let tracker = HotspotTracker::new(config, metrics.clone());
+
+        tracker.record_access("0xpool");
+        tracker.record_access("0xpool");
+        tracker.record_access("0xcold");
+        assert!(!tracker.is_hot("0xpool"));
+
+        tracker.record_access("0xpool");
+        assert!(tracker.is_hot("0xpool"));
+        assert!(!tracker.is_hot("0xcold"));
+
+        let hot = tracker.hot_objects();
+        assert_eq!(hot.len(), 1);
+        assert_eq!(hot[0].object_id, "0xpool");
+        assert_eq!(hot[0].accesses_in_window, 3);
+        assert_eq!(metrics.gauge(HOTSPOT_OBJECTS_GAUGE), Some(1.0));
+
+        let mut alerts = dubhe_observability::AlertManager::new();
+        alerts.add_rule(hotspot_alert_rule());
+        assert_eq!(alerts.evaluate(&metrics).len(), 1);
+    }
+
+    #[test]
+    fn test_serialization_threshold() {
+        let metrics = Arc::new(MetricsCollector::new());
+        let config = HotspotConfig {
+            serialization_threshold_ms: 100,
+            ..HotspotConfig::default()
+        };
+        let tracker = HotspotTracker::new(config, metrics);
+
+        tracker.record_serialization("0xpool", Duration::from_millis(60));
+        assert!(!tracker.is_hot("0xpool"));
+        tracker.record_serialization("0xpool", Duration::from_millis(60));
+        assert!(tracker.is_hot("0xpool"));
+    }
+
+    #[tokio::test]
+    async fn test_ten_concurrent_increments_share_one_lock() {
+        let (coalescer, tracker, metrics) = coalescer();
+        let executor = Arc::new(CounterExecutor::default());
+
+        let mut handles = Vec::new();
+        for i in 0..10 {
+            let coalescer = coalescer.clone();
+            let executor = executor.clone();
+            tracker.record_access("0xpool");
+            handles.push(tokio::spawn(async move {
+                coalescer
+                    .submit("0xpool", increment(i, "increment"), executor.as_ref())
+                    .await
+            }));
+        }
+
+        let mut values = HashSet::new();
+        for (i, handle) in handles.into_iter().enumerate() {
+            let result = handle.await.unwrap().unwrap();
+            assert!(result.success);
+            assert_eq!(result.session_id, format!("increment_{}", i));
+            // 每个请求单独计费
+            assert_eq!(result.gas_used, 100 * (i as u64 + 1));
+            values.insert(result.modified_objects[0].new_content["value"].as_u64().unwrap());
+        }
+
+        assert_eq!(executor.lock_acquisitions.load(Ordering::SeqCst), 1);
+        assert_eq!(values, (1..=10).collect::<HashSet<u64>>());
+        assert_eq!(*executor.value.lock().await, 10);
+        assert_eq!(metrics.counter(HOTSPOT_COALESCED_COUNTER), 10);
+        assert_eq!(metrics.counter(HOTSPOT_BATCH_LOCKS_COUNTER), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failure_isolation() {
+        let (coalescer, _tracker, _metrics) = coalescer();
+        let executor = Arc::new(CounterExecutor::default());
+
+        let mut handles = Vec::new();
+        for i in 0..3 {
+            let coalescer = coalescer.clone();
+            let executor = executor.clone();
+            let function = if i == 1 { "abort" } else { "increment" };
+            handles.push(tokio::spawn(async move {
+                coalescer
+                    .submit("0xpool", increment(i, function), executor.as_ref())
+                    .await
+            }));
+        }
+
+        let results: Vec<OffchainExecutionResult> = join_results(handles).await;
+        assert!(results[0].success);
+        assert!(!results[1].success);
+        assert_eq!(results[1].error.as_deref(), Some("aborted by guest"));
+        assert!(results[2].success);
+        assert_eq!(*executor.value.lock().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_leader_releases_queue() {
+        let (coalescer, _tracker, _metrics) = coalescer();
+        let executor = Arc::new(CounterExecutor::default());
+
+        // leader 在合批等待窗口内被调用方丢弃
+        let mut leader =
+            Box::pin(coalescer.submit("0xpool", increment(0, "increment"), executor.as_ref()));
+        assert!(tokio::time::timeout(Duration::from_millis(10), &mut leader)
+            .await
+            .is_err());
+        let follower = {
+            let coalescer = coalescer.clone();
+            let executor = executor.clone();
+            tokio::spawn(async move {
+                coalescer
+                    .submit("0xpool", increment(1, "increment"), executor.as_ref())
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(leader);
+
+        let follower = tokio::time::timeout(Duration::from_secs(1), follower)
+            .await
+            .expect("follower must not hang")
+            .unwrap();
+        assert!(follower.is_err());
+
+        // 之后的请求成为新的 leader 并正常执行
+        let result = tokio::time::timeout(
+            Duration::from_secs(1),
+            coalescer.submit("0xpool", increment(2, "increment"), executor.as_ref()),
+        )
+        .await
+        .expect("later submit must not hang")
+        .unwrap();
+        assert!(result.success);
+        assert_eq!(*executor.value.lock().await, 1);
+    }
+
+    async fn join_results(
+        handles: Vec<tokio::task::JoinHandle<Result<OffchainExecutionResult>>>,
+    ) -> Vec<OffchainExecutionResult> {
+        let mut results = Vec::new();
+        for handle in handles {
+            results.push(handle.await.unwrap().unwrap());
+        }
+        results
+    }
+}
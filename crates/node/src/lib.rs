@@ -2,10 +2,28 @@
 //!
 //! 完整节点二进制：组合以上模块启动完整节点
 
+pub mod chains;
 pub mod config;
+pub mod devnet;
+pub mod discovery;
+pub mod hotspot;
+pub mod locking;
 pub mod node;
+pub mod object_diff;
+pub mod object_host;
 pub mod offchain_execution;
+pub mod progress;
+pub mod query_cache;
+pub mod reload;
+pub mod replay;
+pub mod rollup;
+pub mod snapshot;
+pub mod status;
+pub mod sync;
+pub mod threats;
+pub mod verify;
 
 pub use config::*;
+pub use hotspot::*;
 pub use node::*;
 pub use offchain_execution::*;
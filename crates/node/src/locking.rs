@@ -0,0 +1,446 @@
+//! 主网共享对象租约锁
+//!
+//! 链下执行前在主网锁注册表包（`lock_registry` 模块）中为共享对象登记带过期时间的租约，
+//! 同一对象同时只能被一个节点持有；执行结束后提交释放调用。
+//! 租约已过期时释放调用会被合约拒绝，此时视为已释放。
+//!
+//! 锁注册表合约接口：
+//! - `acquire(registry, object_id, expires_at_ms, clock)`，对象已有未过期租约时以
+//!   [`ABORT_LEASE_HELD`] 中止
+//! - `release(registry, object_id, clock)`，租约已过期或不存在时以 [`ABORT_LEASE_EXPIRED`] 中止
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use dubhe_adapter::sui::SuiAdapter;
+
+/// 锁注册表模块名
+pub const LOCK_REGISTRY_MODULE: &str = "lock_registry";
+/// 对象已有未过期租约
+pub const ABORT_LEASE_HELD: u64 = 1;
+/// 租约已过期或不存在
+pub const ABORT_LEASE_EXPIRED: u64 = 2;
+
+/// Sui 系统时钟对象
+const SUI_CLOCK_OBJECT: &str = "0x6";
+
+/// 对象锁配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectLockConfig {
+    /// 锁注册表 Move 包 ID，未配置时链下执行拒绝加锁
+    pub lock_package_id: Option<String>,
+    /// 锁注册表共享对象 ID
+    pub registry_object_id: Option<String>,
+    /// 租约时长（秒）
+    pub lease_secs: u64,
+    /// 加锁 / 释放交易的 gas 预算
+    pub gas_budget: u64,
+}
+
+impl Default for ObjectLockConfig {
+    fn default() -> Self {
+        Self {
+            lock_package_id: None,
+            registry_object_id: None,
+            lease_secs: 60,
+            gas_budget: 10_000_000,
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LockError {
+    #[error("Object {0} already carries an unexpired lease")]
+    AlreadyLeased(String),
+
+    #[error("Lock transaction for object {object_id} failed: {reason}")]
+    TransactionFailed { object_id: String, reason: String },
+
+    #[error("Object locking is not configured (missing {0})")]
+    NotConfigured(&'static str),
+}
+
+/// 主网上持有的对象租约
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectLease {
+    pub object_id: String,
+    /// 加锁时的对象版本
+    pub version: u64,
+    /// 加锁交易摘要
+    pub digest: String,
+    /// 租约过期时间（Unix 毫秒）
+    pub expires_at_ms: u64,
+}
+
+impl ObjectLease {
+    pub fn is_expired_at(&self, now_ms: u64) -> bool {
+        now_ms >= self.expires_at_ms
+    }
+}
+
+/// 释放结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReleaseOutcome {
+    /// 释放交易摘要
+    Released(String),
+    /// 租约已过期，无需释放
+    Expired,
+}
+
+/// 对象租约的获取与释放
+#[async_trait]
+pub trait ObjectLocker: Send + Sync {
+    async fn acquire(&self, object_id: &str, lease: Duration) -> Result<ObjectLease>;
+
+    async fn release(&self, lease: &ObjectLease) -> Result<ReleaseOutcome>;
+}
+
+/// 依次获取所有对象的租约；任一失败时释放已获取的租约并返回该错误
+pub async fn acquire_all(
+    locker: &dyn ObjectLocker,
+    object_ids: &[String],
+    lease: Duration,
+) -> Result<Vec<ObjectLease>> {
+    let mut acquired = Vec::with_capacity(object_ids.len());
+    for object_id in object_ids {
+        match locker.acquire(object_id, lease).await {
+            Ok(object_lease) => acquired.push(object_lease),
+            Err(e) => {
+                warn!(
+                    "🔒 Failed to lock {}, releasing {} acquired leases: {}",
+                    object_id,
+                    acquired.len(),
+                    e
+                );
+                release_all(locker, &acquired).await;
+                return Err(e);
+            }
+        }
+    }
+    Ok(acquired)
+}
+
+/// 释放一组租约，单个失败只记录日志，返回释放失败的数量
+pub async fn release_all(locker: &dyn ObjectLocker, leases: &[ObjectLease]) -> usize {
+    let mut failed = 0;
+    for lease in leases {
+        match locker.release(lease).await {
+            Ok(ReleaseOutcome::Released(digest)) => {
+                info!("🔓 Released lease on {} ({})", lease.object_id, digest)
+            }
+            Ok(ReleaseOutcome::Expired) => {
+                info!("🔓 Lease on {} already expired", lease.object_id)
+            }
+            Err(e) => {
+                failed += 1;
+                warn!("❌ Failed to release lease on {}: {}", lease.object_id, e);
+            }
+        }
+    }
+    failed
+}
+
+/// 基于锁注册表合约的租约实现
+pub struct SuiObjectLocker {
+    adapter: Arc<SuiAdapter>,
+    package_id: String,
+    registry_id: String,
+    gas_budget: u64,
+}
+
+impl SuiObjectLocker {
+    pub fn from_config(adapter: Arc<SuiAdapter>, config: &ObjectLockConfig) -> Result<Self> {
+        let package_id = config
+            .lock_package_id
+            .clone()
+            .ok_or(LockError::NotConfigured("lock_package_id"))?;
+        let registry_id = config
+            .registry_object_id
+            .clone()
+            .ok_or(LockError::NotConfigured("registry_object_id"))?;
+        if adapter.signer().is_none() {
+            warn!(
+                "⚠️ No lock signer configured, lock transactions are dry-run only and not enforced on chain"
+            );
+        }
+
+        Ok(Self {
+            adapter,
+            package_id,
+            registry_id,
+            gas_budget: config.gas_budget,
+        })
+    }
+
+    fn sender(&self) -> String {
+        match self.adapter.signer() {
+            Some(signer) => signer.address(),
+            None => "0x0".to_string(),
+        }
+    }
+
+    /// 干跑并在配置了签名者时提交，返回交易摘要与干跑结果中的中止码
+    async fn submit(
+        &self,
+        object_id: &str,
+        function: &str,
+        arguments: Vec<Value>,
+    ) -> Result<std::result::Result<String, Option<u64>>> {
+        let tx_bytes = self
+            .adapter
+            .build_move_call_transaction(
+                &self.sender(),
+                &self.package_id,
+                LOCK_REGISTRY_MODULE,
+                function,
+                vec![],
+                arguments,
+                self.gas_budget,
+            )
+            .await?;
+
+        let dry_run = self.adapter.dry_run_transaction(&tx_bytes).await?;
+        if let Err(code) = check_effects(object_id, &dry_run)? {
+            return Ok(Err(code));
+        }
+
+        match self.adapter.signer() {
+            // 干跑与提交之间租约可能被其他节点取得，以链上实际执行的结果为准
+            Some(_) => {
+                let response = self
+                    .adapter
+                    .sign_and_execute_transaction_block(&tx_bytes)
+                    .await?;
+                if let Err(code) = check_effects(object_id, &response)? {
+                    return Ok(Err(code));
+                }
+                Ok(Ok(response["digest"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string()))
+            }
+            None => Ok(Ok(dry_run["effects"]["transactionDigest"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string())),
+        }
+    }
+}
+
+/// 检查干跑或执行响应中的 `effects.status`
+///
+/// Move 中止返回 `Ok(Err(中止码))`，其它失败（包括缺少执行状态）返回错误
+fn check_effects(
+    object_id: &str,
+    response: &Value,
+) -> Result<std::result::Result<(), Option<u64>>> {
+    let status = &response["effects"]["status"];
+    if status["status"] == "success" {
+        return Ok(Ok(()));
+    }
+    let error = status["error"]
+        .as_str()
+        .unwrap_or("transaction effects have no success status");
+    match abort_code(error) {
+        Some(code) => Ok(Err(Some(code))),
+        None => Err(LockError::TransactionFailed {
+            object_id: object_id.to_string(),
+            reason: error.to_string(),
+        }
+        .into()),
+    }
+}
+
+#[async_trait]
+impl ObjectLocker for SuiObjectLocker {
+    async fn acquire(&self, object_id: &str, lease: Duration) -> Result<ObjectLease> {
+        let object = self.adapter.get_object_data(object_id).await?;
+        let version = object["data"]["version"]
+            .as_str()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+        let expires_at_ms = now_ms() + lease.as_millis() as u64;
+
+        let arguments = vec![
+            json!(self.registry_id),
+            json!(object_id),
+            json!(expires_at_ms.to_string()),
+            json!(SUI_CLOCK_OBJECT),
+        ];
+        match self.submit(object_id, "acquire", arguments).await? {
+            Ok(digest) => Ok(ObjectLease {
+                object_id: object_id.to_string(),
+                version,
+                digest,
+                expires_at_ms,
+            }),
+            Err(Some(ABORT_LEASE_HELD)) => {
+                Err(LockError::AlreadyLeased(object_id.to_string()).into())
+            }
+            Err(code) => Err(LockError::TransactionFailed {
+                object_id: object_id.to_string(),
+                reason: format!("acquire aborted with code {:?}", code),
+            }
+            .into()),
+        }
+    }
+
+    async fn release(&self, lease: &ObjectLease) -> Result<ReleaseOutcome> {
+        if lease.is_expired_at(now_ms()) {
+            return Ok(ReleaseOutcome::Expired);
+        }
+
+        let arguments = vec![
+            json!(self.registry_id),
+            json!(lease.object_id),
+            json!(SUI_CLOCK_OBJECT),
+        ];
+        match self.submit(&lease.object_id, "release", arguments).await? {
+            Ok(digest) => Ok(ReleaseOutcome::Released(digest)),
+            // 提交前租约恰好过期
+            Err(Some(ABORT_LEASE_EXPIRED)) => Ok(ReleaseOutcome::Expired),
+            Err(code) => Err(LockError::TransactionFailed {
+                object_id: lease.object_id.clone(),
+                reason: format!("release aborted with code {:?}", code),
+            }
+            .into()),
+        }
+    }
+}
+
+/// 从 `MoveAbort(..., <code>) in command 0` 形式的错误中提取中止码
+fn abort_code(error: &str) -> Option<u64> {
+    let rest = &error[error.find("MoveAbort")?..];
+    let end = rest.rfind(')')?;
+    rest[..end].rsplit(',').next()?.trim().parse().ok()
+}
+
+fn now_ms() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+
+    /// 记录加锁 / 释放调用，对指定对象加锁失败
+    #[derive(Default)]
+    struct MockLocker {
+        failing: HashSet<String>,
+        held: Mutex<HashSet<String>>,
+        released: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ObjectLocker for MockLocker {
+        async fn acquire(&self, object_id: &str, lease: Duration) -> Result<ObjectLease> {
+            if self.failing.contains(object_id) {
+                return Err(LockError::TransactionFailed {
+                    object_id: object_id.to_string(),
+                    reason: "insufficient gas".to_string(),
+                }
+                .into());
+            }
+            if !self.held.lock().unwrap().insert(object_id.to_string()) {
+                return Err(LockError::AlreadyLeased(object_id.to_string()).into());
+            }
+            Ok(ObjectLease {
+                object_id: object_id.to_string(),
+                version: 1,
+                digest: format!("digest-{}", object_id),
+                expires_at_ms: now_ms() + lease.as_millis() as u64,
+            })
+        }
+
+        async fn release(&self, lease: &ObjectLease) -> Result<ReleaseOutcome> {
+            self.held.lock().unwrap().remove(&lease.object_id);
+            self.released.lock().unwrap().push(lease.object_id.clone());
+            Ok(ReleaseOutcome::Released(format!("release-{}", lease.object_id)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_partial_lock_failure_releases_acquired() {
+        let locker = MockLocker {
+            failing: HashSet::from(["0xc".to_string()]),
+            ..Default::default()
+        };
+        let objects: Vec<String> = ["0xa", "0xb", "0xc"].iter().map(|s| s.to_string()).collect();
+
+        let err = acquire_all(&locker, &objects, Duration::from_secs(60))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<LockError>(),
+            Some(LockError::TransactionFailed { object_id, .. }) if object_id == "0xc"
+        ));
+        assert_eq!(*locker.released.lock().unwrap(), vec!["0xa", "0xb"]);
+        assert!(locker.held.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_already_leased_object_is_refused() {
+        let locker = MockLocker::default();
+        let first = acquire_all(&locker, &["0xa".to_string()], Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(first[0].digest, "digest-0xa");
+
+        let err = acquire_all(
+            &locker,
+            &["0xb".to_string(), "0xa".to_string()],
+            Duration::from_secs(60),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<LockError>(),
+            Some(&LockError::AlreadyLeased("0xa".to_string()))
+        );
+        assert_eq!(*locker.released.lock().unwrap(), vec!["0xb"]);
+    }
+
+    #[test]
+    fn test_execution_effects_are_checked() {
+        let success = json!({ "effects": { "status": { "status": "success" } } });
+        assert_eq!(check_effects("0xa", &success).unwrap(), Ok(()));
+
+        // 执行阶段的 Move 中止与干跑一样给出中止码
+        let aborted = json!({ "effects": { "status": {
+            "status": "failure",
+            "error": "MoveAbort(MoveLocation { module: ModuleId { address: 0x1, name: Identifier(\"lock_registry\") }, function: 0, instruction: 12, function_name: Some(\"acquire\") }, 1) in command 0"
+        } } });
+        assert_eq!(
+            check_effects("0xa", &aborted).unwrap(),
+            Err(Some(ABORT_LEASE_HELD))
+        );
+
+        let out_of_gas = json!({ "effects": { "status": {
+            "status": "failure",
+            "error": "InsufficientGas"
+        } } });
+        let err = check_effects("0xa", &out_of_gas).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<LockError>(),
+            Some(LockError::TransactionFailed { reason, .. }) if reason == "InsufficientGas"
+        ));
+
+        // 未请求 showEffects 的响应不能当作成功
+        assert!(check_effects("0xa", &json!({ "digest": "abc" })).is_err());
+    }
+
+    #[test]
+    fn test_abort_code_parsing() {
+        let error = "MoveAbort(MoveLocation { module: ModuleId { address: 0x1, name: Identifier(\"lock_registry\") }, function: 0, instruction: 12, function_name: Some(\"acquire\") }, 1) in command 0";
+        assert_eq!(abort_code(error), Some(ABORT_LEASE_HELD));
+        assert_eq!(abort_code("InsufficientGas"), None);
+    }
+}
@@ -1,23 +1,37 @@
 //! Dubhe Channel Node
 //!
 //! 完整节点二进制：组合以上模块启动完整节点
+//!
+//! 离线部署的产物包工具（需在节点停止时运行，编译缓存只能被一个进程打开）：
+//!
+//! ```text
+//! dubhe-node -c config.toml artifact export <address> --out <file> [--key <name>]
+//! dubhe-node -c config.toml artifact import <file> [--force]
+//! ```
+//!
+//! 状态快照（运行中的节点改用 `dubhe_createSnapshot`）：
+//!
+//! ```text
+//! dubhe-node -c config.toml snapshot create --out <dir> [--base <dir>]
+//! dubhe-node -c config.toml snapshot restore <dir> [--base <dir>] [--force]
+//! ```
 
-use anyhow::Result;
-use clap::{Arg, Command};
+use anyhow::{anyhow, Result};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::path::Path;
+use std::sync::Arc;
 use tracing::{error, info};
-use tracing_subscriber;
 
-mod config;
-mod node;
-
-use config::NodeConfig;
-use node::DubheNode;
+use dubhe_loader::CodeLoader;
+use dubhe_node::config::NodeConfig;
+use dubhe_node::snapshot::{restore_snapshot, Snapshotter};
+use dubhe_node::DubheNode;
+use dubhe_observability::{ObservabilityManager, TracingConfig};
+use dubhe_security::{Keystore, Passphrase};
+use dubhe_state::StateManager;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // 初始化日志
-    tracing_subscriber::fmt::init();
-
     // 解析命令行参数
     let matches = Command::new("dubhe-node")
         .version("0.1.0")
@@ -38,19 +52,107 @@ async fn main() -> Result<()> {
                 .help("Sets the log level")
                 .default_value("info"),
         )
+        .subcommand(
+            Command::new("artifact")
+                .about("Export or import signed compiled artifacts for air-gapped nodes")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("export")
+                        .about("Sign the cached artifact of a contract into a bundle file")
+                        .arg(Arg::new("address").required(true))
+                        .arg(
+                            Arg::new("out")
+                                .short('o')
+                                .long("out")
+                                .value_name("FILE")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("key")
+                                .long("key")
+                                .value_name("NAME")
+                                .help("Keystore key used to sign the bundle")
+                                .default_value("artifact"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("import")
+                        .about("Verify a bundle file and insert it into the compilation cache")
+                        .arg(Arg::new("file").required(true))
+                        .arg(
+                            Arg::new("force")
+                                .long("force")
+                                .action(ArgAction::SetTrue)
+                                .help("Import even if the artifact was built by another compiler version"),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("snapshot")
+                .about("Create or restore a snapshot of the node state for bootstrapping replicas")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("create")
+                        .about("Write a snapshot of the state store, indexes and compilation cache")
+                        .arg(
+                            Arg::new("out")
+                                .short('o')
+                                .long("out")
+                                .value_name("DIR")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("base")
+                                .long("base")
+                                .value_name("DIR")
+                                .help("Only write files that changed since this full snapshot"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("restore")
+                        .about("Verify a snapshot and restore it into the configured data directories")
+                        .arg(Arg::new("dir").required(true))
+                        .arg(
+                            Arg::new("base")
+                                .long("base")
+                                .value_name("DIR")
+                                .help("Base snapshot of an incremental snapshot"),
+                        )
+                        .arg(
+                            Arg::new("force")
+                                .long("force")
+                                .action(ArgAction::SetTrue)
+                                .help("Replace existing state and cache directories"),
+                        ),
+                ),
+        )
         .get_matches();
 
     let config_path = matches.get_one::<String>("config").unwrap();
+    let log_level = matches.get_one::<String>("log-level").unwrap();
+
+    // 初始化日志与追踪导出；离线工具子命令不导出 span
+    let tracing_config = if matches.subcommand().is_none() {
+        NodeConfig::load(config_path)?
+            .observability
+            .tracing_config()
+    } else {
+        TracingConfig::default()
+    };
+    let _observability = ObservabilityManager::init(log_level, &tracing_config)?;
+
+    if let Some(artifact) = matches.subcommand_matches("artifact") {
+        return run_artifact(config_path, artifact).await;
+    }
+    if let Some(snapshot) = matches.subcommand_matches("snapshot") {
+        return run_snapshot(config_path, snapshot);
+    }
 
     info!("🚀 Starting Dubhe Channel Node...");
     info!("📄 Loading configuration from: {}", config_path);
 
-    // 加载配置
-    let config = NodeConfig::load(config_path)?;
-    info!("✅ Configuration loaded successfully");
-
-    // 创建并启动节点
-    let mut node = DubheNode::new(config).await?;
+    // 加载配置并创建节点（告警规则可从同一文件热加载）
+    let mut node = DubheNode::from_file(config_path).await?;
     info!("🏗️  Node initialized successfully");
 
     // 启动所有服务
@@ -61,9 +163,35 @@ async fn main() -> Result<()> {
             info!("🚀 gRPC: http://127.0.0.1:9090");
             info!("💬 WebSocket: ws://127.0.0.1:8546");
 
-            // 等待中断信号
-            tokio::signal::ctrl_c().await?;
+            // SIGHUP 热加载配置，Ctrl-C 停机
+            #[cfg(unix)]
+            let mut hangup =
+                tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+            loop {
+                #[cfg(unix)]
+                let reload = hangup.recv();
+                #[cfg(not(unix))]
+                let reload = std::future::pending::<Option<()>>();
+
+                tokio::select! {
+                    signal = tokio::signal::ctrl_c() => {
+                        signal?;
+                        break;
+                    }
+                    _ = reload => match node.reload_config().await {
+                        Ok(report) => info!(
+                            "🔄 Configuration reloaded: applied {:?}, rejected {:?}",
+                            report.applied, report.rejected
+                        ),
+                        Err(e) => error!("❌ Failed to reload configuration: {}", e),
+                    },
+                }
+            }
             info!("👋 Received shutdown signal, stopping node...");
+
+            if let Err(e) = node.shutdown().await {
+                error!("❌ Graceful shutdown failed: {}", e);
+            }
         }
         Err(e) => {
             error!("❌ Failed to start node: {}", e);
@@ -74,3 +202,79 @@ async fn main() -> Result<()> {
     info!("🛑 Dubhe Channel Node stopped");
     Ok(())
 }
+
+/// 产物包的导出与导入
+async fn run_artifact(config_path: &str, matches: &ArgMatches) -> Result<()> {
+    let config = NodeConfig::load(config_path)?;
+    let loader =
+        CodeLoader::with_cache_config(&config.cache.cache_dir, config.cache.loader_cache_config())?;
+
+    match matches.subcommand() {
+        Some(("export", export)) => {
+            let address = export.get_one::<String>("address").unwrap();
+            let out = export.get_one::<String>("out").unwrap();
+            let keystore = Arc::new(Keystore::open(
+                &config.security.keystore.dir,
+                Passphrase::from_env_or_prompt(&config.security.keystore.passphrase_env)?,
+            )?);
+            let key = keystore.handle(export.get_one::<String>("key").unwrap())?;
+            loader.export_artifact(address, &key).await?.write_to(out)?;
+            info!(
+                "📦 Artifact for {} written to {} (signer {})",
+                address,
+                out,
+                hex::encode(key.public_key()?)
+            );
+        }
+        Some(("import", import)) => {
+            let file = import.get_one::<String>("file").unwrap();
+            let trusted = config.cache.trusted_artifact_signers()?;
+            if trusted.is_empty() {
+                return Err(anyhow!(
+                    "No cache.trusted_artifact_signers configured, refusing to import {}",
+                    file
+                ));
+            }
+            let cache_key = loader
+                .import_artifact(file, &trusted, import.get_flag("force"))
+                .await?;
+            loader.cache().flush()?;
+            info!("📦 Imported {} as {}", file, cache_key);
+        }
+        _ => unreachable!("artifact subcommand is required"),
+    }
+    Ok(())
+}
+
+/// 状态快照的创建与恢复
+fn run_snapshot(config_path: &str, matches: &ArgMatches) -> Result<()> {
+    let config = NodeConfig::load(config_path)?;
+    let state_dir = Path::new(&config.node.data_dir).join("state");
+    let cache_dir = Path::new(&config.cache.cache_dir);
+
+    match matches.subcommand() {
+        Some(("create", create)) => {
+            let out = create.get_one::<String>("out").unwrap();
+            let base = create.get_one::<String>("base").map(Path::new);
+            let state = Arc::new(StateManager::new(&state_dir)?);
+            let loader =
+                CodeLoader::with_cache_config(cache_dir, config.cache.loader_cache_config())?;
+            let report = Snapshotter::new(state, loader.cache()).create(Path::new(out), base)?;
+            info!("📸 Snapshot {} written to {}", report.id, out);
+        }
+        Some(("restore", restore)) => {
+            let dir = restore.get_one::<String>("dir").unwrap();
+            let base = restore.get_one::<String>("base").map(Path::new);
+            let manifest = restore_snapshot(
+                Path::new(dir),
+                base,
+                &state_dir,
+                cache_dir,
+                restore.get_flag("force"),
+            )?;
+            info!("📸 Restored snapshot {} from {}", manifest.id, dir);
+        }
+        _ => unreachable!("snapshot subcommand is required"),
+    }
+    Ok(())
+}
@@ -1,25 +1,72 @@
 //! Dubhe 节点核心实现
 
 use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tracing::{error, info};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
 
-use dubhe_adapter::AdapterManager;
-use dubhe_api::ApiServer;
-use dubhe_loader::CodeLoader;
-use dubhe_scheduler::ParallelScheduler;
+use dubhe_adapter::sui::SuiAdapter;
+use dubhe_adapter::{
+    AdapterManager, ChainStatus, ChainType, EndpointHealth, KeystoreSigner, Signer, SuiConfig,
+};
+use dubhe_api::{AdminHandler, ApiServer, CallExecutor, ConfigReloadReport, TransactionIngress};
+use dubhe_loader::{ArtifactVersion, CodeLoader, RecompilationService, RecompileConfig};
+use dubhe_observability::{
+    AlertManager, Dashboard, LogNotifier, MetricSource, MetricsExporter, NodeMetrics,
+    WebhookNotifier,
+};
+use dubhe_scheduler::{DrainOutcome, Mempool, ParallelScheduler};
+use dubhe_security::{
+    default_attestation_provider, AuditTrail, Keystore, Passphrase, SecurityManager, ThreatDetector,
+};
+use dubhe_state::StateManager;
 use dubhe_vm_runtime::VmManager;
 
+use crate::chains::{init_adapters, log_chain_summary, SuiDependencies};
 use crate::config::NodeConfig;
+use crate::discovery::PackageDiscovery;
+use crate::locking::SuiObjectLocker;
+use crate::reload::ConfigReloader;
+use crate::snapshot::Snapshotter;
+use crate::status::{ApiKeyStatusAuth, NodeStatusProvider};
+use crate::sync::SuiPtbSubmitter;
+use crate::threats::{spawn_threat_monitor, AlertThreatSink};
 
 pub use crate::offchain_execution::{
     ExecutionRequest, ExecutionStats, OffchainExecutionManager, OffchainExecutionResult,
 };
 
+/// 使用统计的持久化间隔
+const USAGE_PERSIST_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Dubhe Channel 节点
 pub struct DubheNode {
     config: NodeConfig,
-    api_server: ApiServer,
+    api_server: Arc<ApiServer>,
+    api_task: Option<JoinHandle<()>>,
+    scrub_task: Option<JoinHandle<()>>,
+    usage_task: Option<JoinHandle<()>>,
+    recompile_task: Option<JoinHandle<()>>,
+    index_task: Option<JoinHandle<()>>,
+    bridge_task: Option<JoinHandle<()>>,
+    state_manager: Arc<StateManager>,
+    metrics: Arc<NodeMetrics>,
+    metrics_task: Option<JoinHandle<()>>,
+    alert_manager: Arc<Mutex<AlertManager>>,
+    reloader: Arc<ConfigReloader>,
+    alert_task: Option<JoinHandle<()>>,
+    session_task: Option<JoinHandle<()>>,
+    query_cache_task: Option<JoinHandle<()>>,
+    threat_detector: Option<Arc<ThreatDetector>>,
+    threat_task: Option<JoinHandle<()>>,
+    discovery: Arc<PackageDiscovery>,
+    discovery_task: Option<JoinHandle<()>>,
+    mempool: Arc<Mempool>,
+    mempool_task: Option<JoinHandle<()>>,
     adapter_manager: Arc<AdapterManager>,
     code_loader: Arc<CodeLoader>,
     scheduler: Arc<ParallelScheduler>,
@@ -30,49 +77,207 @@ pub struct DubheNode {
 impl DubheNode {
     /// 创建新的节点实例
     pub async fn new(config: NodeConfig) -> Result<Self> {
+        Self::build(config, None).await
+    }
+
+    /// 从配置文件创建节点，可调参数可从该文件热加载
+    pub async fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        Self::build(NodeConfig::load(&path)?, Some(path)).await
+    }
+
+    async fn build(config: NodeConfig, config_path: Option<PathBuf>) -> Result<Self> {
         info!("🔧 Initializing Dubhe Channel components...");
 
-        // 初始化各个组件
-        let api_server = ApiServer::new(config.api.clone());
+        // 初始化各个组件（共享同一组 Prometheus 指标）
+        let metrics = Arc::new(NodeMetrics::new()?);
         let adapter_manager = Arc::new(AdapterManager::new());
-        let code_loader = Arc::new(CodeLoader::new()?);
-        let scheduler = Arc::new(ParallelScheduler::new(
-            config.node.strategy,
-            config.scheduler.clone(),
+
+        // 插件、对象锁与特权 RPC 的哈希链审计日志
+        let audit_trail = Arc::new(AuditTrail::open(
+            Path::new(&config.node.data_dir).join("audit.log"),
         )?);
-        let vm_manager = Arc::new(VmManager::new(config.vm.default_vm));
+        let security = SecurityManager::new(
+            config.security.enable_access_control,
+            &config.security.access_control,
+            Some(audit_trail.clone()),
+        )?;
+
+        // Sui 交易签名密钥从加密密钥库按名称取出
+        let sui_signer = match enabled_sui(&config).and_then(|sui| sui.signer.as_ref()) {
+            Some(signer) => {
+                let keystore = Arc::new(Keystore::open(
+                    &config.security.keystore.dir,
+                    Passphrase::from_env_or_prompt(&config.security.keystore.passphrase_env)?,
+                )?);
+                let handle = keystore.handle(&signer.key_name)?;
+                Some(Arc::new(KeystoreSigner::new(handle)?) as Arc<dyn Signer>)
+            }
+            None => None,
+        };
+
+        let code_loader = Arc::new(
+            CodeLoader::with_cache_config(
+                &config.cache.cache_dir,
+                config.cache.loader_cache_config(),
+            )?
+            .with_metrics(metrics.clone())
+            .with_audit_trail(audit_trail.clone())
+            .with_compile_parallelism(config.cache.compile_parallelism),
+        );
+        let scheduler = Arc::new(
+            ParallelScheduler::new(config.node.strategy, config.scheduler.clone())?
+                .with_node_metrics(metrics.clone()),
+        );
 
-        // 注册适配器
-        if let Some(eth_config) = &config.adapters.ethereum {
-            let eth_adapter = dubhe_adapter::eth::EthereumAdapter::new(eth_config.clone()).await?;
-            adapter_manager
-                .register_adapter(dubhe_adapter::ChainType::Ethereum, Box::new(eth_adapter))
-                .await;
-            info!("✅ Ethereum adapter registered");
+        // eth_sendRawTransaction 提交的交易经交易池分批交给调度器
+        let mempool = Arc::new(Mempool::new(config.mempool.clone()).with_metrics(metrics.clone()));
+
+        // 链下状态暂存与索引
+        let state_dir = Path::new(&config.node.data_dir).join("state");
+        std::fs::create_dir_all(&state_dir)?;
+        let state_manager = Arc::new(StateManager::new(state_dir)?);
+
+        let vm_manager = Arc::new(
+            VmManager::new(config.vm.default_vm)
+                .with_gas_schedule(config.vm.gas_schedule)
+                .with_metrics(metrics.clone()),
+        );
+
+        // 告警规则与通知后端
+        let mut alerts = AlertManager::new();
+        alerts.replace_rules(config.alerting.rules.clone());
+        alerts.add_notifier(Arc::new(LogNotifier));
+        for url in &config.alerting.webhook_urls {
+            alerts.add_notifier(Arc::new(WebhookNotifier::new(url.clone())));
         }
+        let alert_manager = Arc::new(Mutex::new(alerts));
+
+        // 链下执行会话的远程证明
+        let attestation = config
+            .security
+            .enable_tee
+            .then(default_attestation_provider);
 
-        if let Some(sui_config) = &config.adapters.sui {
-            let sui_adapter = dubhe_adapter::sui::SuiAdapter::new(sui_config.clone()).await?;
-            adapter_manager
-                .register_adapter(dubhe_adapter::ChainType::Sui, Box::new(sui_adapter))
-                .await;
-            info!("✅ Sui adapter registered");
+        let mut api_server = ApiServer::with_executor(
+            config.api.clone(),
+            Arc::new(CallExecutor::new(
+                adapter_manager.clone(),
+                code_loader.clone(),
+                vm_manager.clone(),
+            )),
+        )
+        .with_scheduler(scheduler.clone())
+        .with_ingress(Arc::new(TransactionIngress::new(
+            adapter_manager.clone(),
+            mempool.clone(),
+        )))
+        .with_indexer(state_manager.indexer())
+        .with_adapters(adapter_manager.clone())
+        .with_access_control(security.access_control());
+        if let Some(provider) = &attestation {
+            api_server = api_server.with_attestation(provider.clone());
         }
 
-        // TODO: 注册其他链的适配器（Solana, Aptos, Bitcoin）
+        // 交易流异常检测：命中时审计、告警，并按配置限流对应的 API 调用方
+        let threat_detector = config.security.threat_detection.enabled.then(|| {
+            let mut detector = ThreatDetector::new(config.security.threat_detection.clone())
+                .with_audit_trail(audit_trail.clone())
+                .with_sink(Arc::new(AlertThreatSink::new(alert_manager.clone())));
+            if config.security.threat_detection.throttle {
+                detector = detector.with_throttle(api_server.auth());
+            }
+            Arc::new(detector)
+        });
+
+        // 调度参数、限流与告警规则可热加载，管理接口同时提供状态快照
+        let reloader = Arc::new(
+            ConfigReloader::new(
+                config.clone(),
+                config_path,
+                scheduler.clone(),
+                api_server.auth(),
+                alert_manager.clone(),
+            )
+            .with_snapshotter(Arc::new(Snapshotter::new(
+                state_manager.clone(),
+                code_loader.cache(),
+            ))),
+        );
+        let api_server = api_server.with_admin(reloader.clone());
+
+        // 只初始化启用的链；非严格模式下初始化失败的链降级并在后台重试
+        init_adapters(
+            &adapter_manager,
+            &config.adapters,
+            SuiDependencies {
+                cursor_store: Some(Arc::new(state_manager.cursor_store())),
+                signer: sui_signer.clone(),
+            },
+        )
+        .await?;
 
         // 初始化链下执行管理器
-        let sui_adapter = if let Some(sui_config) = &config.adapters.sui {
-            Arc::new(dubhe_adapter::sui::SuiAdapter::new(sui_config.clone()).await?)
+        let sui_adapter = if let Some(sui_config) = enabled_sui(&config) {
+            let mut sui_adapter = SuiAdapter::new(sui_config.clone()).await?;
+            if let Some(signer) = &sui_signer {
+                sui_adapter = sui_adapter.with_signer(signer.clone());
+            }
+            Arc::new(sui_adapter)
         } else {
             return Err(anyhow::anyhow!(
-                "Sui adapter is required for offchain execution"
+                "Sui adapter must be enabled: offchain execution depends on it"
             ));
         };
 
-        let offchain_manager = Arc::new(
-            OffchainExecutionManager::new(sui_adapter, vm_manager.clone(), code_loader.clone())
-                .await?,
+        let mut offchain_manager = OffchainExecutionManager::new(
+            sui_adapter.clone(),
+            vm_manager.clone(),
+            code_loader.clone(),
+        )
+        .await?
+        .with_hotspot_config(config.hotspot.clone())
+        .with_session_config(config.sessions.clone())
+        .with_replay_config(config.replay.clone())
+        .with_query_cache(config.query_cache.clone())
+        .with_vm_pool(config.vm.pool.clone())
+        .with_state_manager(state_manager.clone())
+        .with_audit_trail(audit_trail);
+        if let Some(provider) = attestation {
+            offchain_manager = offchain_manager.with_attestation(provider);
+        }
+        if config.locking.lock_package_id.is_some() {
+            let locker = SuiObjectLocker::from_config(sui_adapter.clone(), &config.locking)?;
+            offchain_manager = offchain_manager.with_object_locker(
+                Arc::new(locker),
+                Duration::from_secs(config.locking.lease_secs),
+            );
+            info!("🔒 Shared objects are leased through the on-chain lock registry");
+        } else {
+            warn!("⚠️ No lock registry configured, offchain execution will refuse to lock objects");
+        }
+        let offchain_manager = Arc::new(offchain_manager.with_ptb_submitter(
+            Arc::new(SuiPtbSubmitter::new(
+                sui_adapter.clone(),
+                config.sync.gas_budget,
+            )),
+            config.sync.clone(),
+        ));
+
+        // 新交易引用的包在后台预编译，dubhe_prefetchPackage 可强制预取指定包
+        let discovery = Arc::new(
+            PackageDiscovery::new(
+                adapter_manager.clone(),
+                code_loader.clone(),
+                &config.discovery,
+            )
+            .with_metrics(offchain_manager.metrics()),
+        );
+
+        let api_server = Arc::new(
+            api_server
+                .with_offchain(offchain_manager.clone())
+                .with_prefetch(discovery.clone()),
         );
 
         info!("✅ All components initialized successfully");
@@ -80,6 +285,26 @@ impl DubheNode {
         Ok(Self {
             config,
             api_server,
+            api_task: None,
+            scrub_task: None,
+            usage_task: None,
+            recompile_task: None,
+            index_task: None,
+            bridge_task: None,
+            state_manager,
+            metrics,
+            metrics_task: None,
+            alert_manager,
+            reloader,
+            alert_task: None,
+            session_task: None,
+            query_cache_task: None,
+            threat_detector,
+            threat_task: None,
+            discovery,
+            discovery_task: None,
+            mempool,
+            mempool_task: None,
             adapter_manager,
             code_loader,
             scheduler,
@@ -96,29 +321,236 @@ impl DubheNode {
         std::fs::create_dir_all(&self.config.node.data_dir)?;
         info!("📁 Data directory: {}", self.config.node.data_dir);
 
+        // 可选：周期性校验编译缓存
+        if let Some(secs) = self.config.cache.scrub_interval_secs {
+            self.scrub_task = Some(
+                self.code_loader
+                    .cache()
+                    .spawn_scrubber(Duration::from_secs(secs.max(1))),
+            );
+            info!("🧹 Compilation cache scrubber started (every {}s)", secs);
+        }
+
+        // 编译器或 gas 计价表升级后，按持久化的使用频率在空闲时重编译缓存产物
+        self.usage_task = Some(
+            self.code_loader
+                .spawn_usage_persister(USAGE_PERSIST_INTERVAL),
+        );
+        let recompiler = Arc::new(
+            RecompilationService::new(
+                self.code_loader.cache(),
+                self.code_loader.usage(),
+                self.code_loader.clone(),
+                self.code_loader.clone(),
+                self.code_loader.activity(),
+                RecompileConfig::default(),
+            )
+            .with_metrics(self.metrics.clone()),
+        );
+        let version = ArtifactVersion::new(self.config.vm.gas_schedule.cycles_per_gas);
+        match recompiler.detect_version_change(&version).await {
+            Ok(0) => {}
+            Ok(scheduled) => {
+                self.recompile_task = Some(tokio::spawn(async move {
+                    if let Err(e) = recompiler.run_pending().await {
+                        error!("❌ Background recompilation failed: {}", e);
+                    }
+                }));
+                info!(
+                    "🔁 Recompiling {} cached artifacts in the background",
+                    scheduled
+                );
+            }
+            Err(e) => error!("❌ Failed to check compiled artifact version: {}", e),
+        }
+
+        // 将 Sui 新区块 / 新交易接入 WebSocket 订阅与二级索引；
+        // 先订阅事件总线再启动适配器任务，避免丢失最早的事件
+        self.bridge_task = Some(
+            self.api_server
+                .ws()
+                .bridge_chain_events(self.adapter_manager.clone(), ChainType::Sui),
+        );
+        info!("📡 Sui event stream bridged to WebSocket subscriptions");
+        self.index_task = Some(
+            self.state_manager
+                .indexer()
+                .follow_chain_events(self.adapter_manager.clone(), ChainType::Sui),
+        );
+        info!("🗂️ Sui transactions feeding the state indexer");
+        if let Some(detector) = &self.threat_detector {
+            self.threat_task = Some(spawn_threat_monitor(
+                detector.clone(),
+                &self.scheduler,
+                self.adapter_manager.clone(),
+                ChainType::Sui,
+            ));
+            info!("🛡️ Threat detection watching scheduler results and Sui transactions");
+        }
+        if self.config.discovery.enabled {
+            self.discovery_task = Some(self.discovery.spawn());
+            info!("📦 Prefetching packages referenced by new Sui transactions");
+        }
+
+        // 交易池按批次大小或批次间隔出批
+        self.mempool_task = Some(
+            self.mempool
+                .spawn_batcher(self.scheduler.clone(), self.scheduler.cancel_handle()),
+        );
+
         // 启动适配器后台任务
         self.adapter_manager.start_background_tasks().await?;
         info!("🔗 Adapter background tasks started");
+        log_chain_summary(&self.adapter_manager).await;
+
+        // 启动 Prometheus 导出端
+        if self.config.observability.enable_prometheus {
+            let bind = self.config.observability.prometheus_bind();
+            let dashboard = Dashboard::new(self.metrics.clone())
+                .with_alerts(self.alert_manager.clone())
+                .with_provider(Arc::new(NodeStatusProvider::new(
+                    self.scheduler.clone(),
+                    self.adapter_manager.clone(),
+                    self.code_loader.clone(),
+                    self.offchain_manager.clone(),
+                )))
+                .with_auth(Arc::new(ApiKeyStatusAuth::new(self.api_server.auth())));
+            let exporter =
+                MetricsExporter::new(self.metrics.clone()).with_dashboard(Arc::new(dashboard));
+            self.metrics_task = Some(tokio::spawn(async move {
+                if let Err(e) = exporter.start(&bind).await {
+                    error!("❌ Prometheus exporter failed: {}", e);
+                }
+            }));
+            info!("📈 Prometheus metrics exported on /metrics, status page on /status");
+        }
+
+        // 完成上次运行中断的结果回写，再释放其对象租约
+        match self.offchain_manager.resume_pending_syncs().await {
+            Ok(0) => {}
+            Ok(resumed) => info!("♻️ Resumed {} interrupted result syncs", resumed),
+            Err(e) => error!("❌ Failed to resume interrupted result syncs: {}", e),
+        }
+
+        // 定期清理已结束的执行会话
+        self.session_task = Some(self.offchain_manager.spawn_session_cleanup());
+        info!(
+            "🗑️ Execution sessions retained for {}s after completion",
+            self.config.sessions.retention_secs
+        );
+
+        // 只读查询缓存按 Sui 检查点中的对象版本失效；订阅失败时缓存不提供结果
+        match self.offchain_manager.follow_object_versions().await {
+            Ok(task) => self.query_cache_task = task,
+            Err(e) => warn!(
+                "⚠️ Query cache disabled, object version subscription failed: {}",
+                e
+            ),
+        }
+
+        // 启动告警评估
+        if self.config.alerting.enable_alerts {
+            let sources: Vec<Arc<dyn MetricSource>> =
+                vec![self.metrics.clone(), self.offchain_manager.metrics()];
+            let interval = self.config.alerting.evaluation_interval_secs.max(1);
+            self.alert_task = Some(AlertManager::spawn_evaluator(
+                self.alert_manager.clone(),
+                sources,
+                Duration::from_secs(interval),
+            ));
+            info!("🚨 Alert rules evaluated every {}s", interval);
+        }
 
         // 启动 API 服务器
-        let api_config = self.config.api.clone();
-        tokio::spawn(async move {
-            let api_server = ApiServer::new(api_config);
+        let api_server = self.api_server.clone();
+        self.api_task = Some(tokio::spawn(async move {
             if let Err(e) = api_server.start().await {
                 error!("❌ API server failed: {}", e);
             }
-        });
+        }));
 
         info!("🌐 API servers started");
         Ok(())
     }
 
+    /// 优雅停机
+    ///
+    /// 1. 停止接收新的 API 请求
+    /// 2. 等待调度器在途批次完成（超时后强制取消）
+    /// 3. 释放主网上仍被锁定的对象
+    /// 4. 刷写编译缓存持久层
+    pub async fn shutdown(&mut self) -> Result<()> {
+        info!("🛑 Shutting down Dubhe Channel Node...");
+
+        // Phase 1: 停止 API 服务
+        if let Some(task) = self.api_task.take() {
+            task.abort();
+            let _ = task.await;
+        }
+        self.adapter_manager.stop().await;
+        for task in [
+            self.bridge_task.take(),
+            self.index_task.take(),
+            self.metrics_task.take(),
+            self.alert_task.take(),
+            self.session_task.take(),
+            self.query_cache_task.take(),
+            self.threat_task.take(),
+            self.discovery_task.take(),
+            self.mempool_task.take(),
+        ]
+            .into_iter()
+            .flatten()
+        {
+            task.abort();
+        }
+        info!("🌐 [1/4] API servers stopped, no longer accepting requests");
+
+        // Phase 2: 排空调度器
+        let timeout = Duration::from_millis(self.config.node.shutdown_timeout_ms);
+        match self.scheduler.drain(timeout).await {
+            DrainOutcome::Drained => info!("📦 [2/4] Scheduler drained"),
+            DrainOutcome::Cancelled { remaining } => warn!(
+                "📦 [2/4] Scheduler drain timed out after {:?}, cancelled {} batches",
+                timeout, remaining
+            ),
+        }
+
+        // Phase 3: 释放对象锁
+        match self.offchain_manager.release_all_locks().await {
+            Ok(released) => info!("🔓 [3/4] Released {} locked objects", released),
+            Err(e) => error!("❌ [3/4] Failed to release locked objects: {}", e),
+        }
+
+        // Phase 4: 刷写编译缓存
+        for task in [
+            self.scrub_task.take(),
+            self.usage_task.take(),
+            self.recompile_task.take(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            task.abort();
+        }
+        if let Err(e) = self.code_loader.persist_usage().await {
+            warn!("⚠️ Failed to persist compilation usage statistics: {}", e);
+        }
+        self.code_loader.cache().flush()?;
+        info!("💾 [4/4] Compilation cache flushed");
+
+        info!("👋 Dubhe Channel Node shut down cleanly");
+        Ok(())
+    }
+
     /// 获取节点状态
     pub async fn get_status(&self) -> NodeStatus {
         NodeStatus {
             running: true,
             scheduler_status: self.scheduler.get_status().await,
-            adapter_count: 1,    // TODO: 从 adapter_manager 获取实际数量
+            adapter_count: self.adapter_manager.chain_types().await.len(),
+            chains: self.adapter_manager.chain_statuses().await,
+            adapter_health: self.adapter_manager.get_adapter_health().await,
             loaded_contracts: 0, // TODO: 从 code_loader 获取实际数量
         }
     }
@@ -139,6 +571,16 @@ impl DubheNode {
     pub async fn get_offchain_stats(&self) -> ExecutionStats {
         self.offchain_manager.get_execution_stats().await
     }
+
+    /// 重新读取配置文件并应用可在运行时生效的配置段（SIGHUP 触发）
+    pub async fn reload_config(&self) -> Result<ConfigReloadReport> {
+        self.reloader.reload_config().await
+    }
+}
+
+/// 配置中启用的 Sui 适配器
+fn enabled_sui(config: &NodeConfig) -> Option<&SuiConfig> {
+    config.adapters.sui.as_ref().filter(|sui| sui.enabled)
 }
 
 /// 节点状态信息
@@ -147,5 +589,9 @@ pub struct NodeStatus {
     pub running: bool,
     pub scheduler_status: dubhe_scheduler::SchedulerStatus,
     pub adapter_count: usize,
+    /// 各链的启用与初始化状态（active / degraded / disabled）
+    pub chains: Vec<ChainStatus>,
+    /// 各链 RPC 端点的健康状态
+    pub adapter_health: HashMap<ChainType, Vec<EndpointHealth>>,
     pub loaded_contracts: usize,
 }
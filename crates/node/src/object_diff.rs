@@ -0,0 +1,235 @@
+//! 对象字段级差异
+//!
+//! 比较执行前的对象内容（`getObject` 返回的 JSON）与效果解析出的新内容，按路径列出新增、删除与修改的字段：
+//! 嵌套结构体以点号分隔（`config.fee.rate`），向量元素以下标表示（`items[3].amount`）。
+//! Sui JSON-RPC 把嵌套结构体渲染为 `{"type": ..., "fields": {...}}`，路径中省略这一层包装。
+//!
+//! 值类型改变（如数字变为结构体、结构体类型不同）时只在该路径记录一次，不再向下比较。
+//! 向量长度改变时记录新旧长度，不逐一列出增删的下标；公共部分修改的下标最多列出
+//! [`MAX_VECTOR_INDICES`] 个，其余只计数。
+
+use serde_json::{Map, Value};
+
+use crate::offchain_execution::{ObjectChanges, VectorChange};
+
+/// 每个向量最多逐一列出的修改下标数
+pub const MAX_VECTOR_INDICES: usize = 16;
+
+/// 计算两个对象内容之间的字段级差异；内容本身不是结构体时以空路径表示整个对象
+pub fn diff_objects(old: &Value, new: &Value) -> ObjectChanges {
+    let mut changes = ObjectChanges::default();
+    diff_value("", old, new, &mut changes);
+    changes
+}
+
+fn diff_value(path: &str, old: &Value, new: &Value, changes: &mut ObjectChanges) {
+    if old == new {
+        return;
+    }
+    if value_type(old) != value_type(new) {
+        changes.fields_modified.push(path.to_string());
+        changes.fields_retyped.push(path.to_string());
+        return;
+    }
+    match (old, new) {
+        (Value::Array(old), Value::Array(new)) => diff_vector(path, old, new, changes),
+        (Value::Object(_), Value::Object(_)) => {
+            let empty = Map::new();
+            let old = struct_fields(old).unwrap_or(&empty);
+            let new = struct_fields(new).unwrap_or(&empty);
+            diff_struct(path, old, new, changes);
+        }
+        _ => changes.fields_modified.push(path.to_string()),
+    }
+}
+
+fn diff_struct(
+    path: &str,
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+    changes: &mut ObjectChanges,
+) {
+    for (key, old_value) in old {
+        let field = field_path(path, key);
+        match new.get(key) {
+            Some(new_value) => diff_value(&field, old_value, new_value, changes),
+            None => changes.fields_removed.push(field),
+        }
+    }
+    for key in new.keys().filter(|key| !old.contains_key(*key)) {
+        changes.fields_added.push(field_path(path, key));
+    }
+}
+
+fn diff_vector(path: &str, old: &[Value], new: &[Value], changes: &mut ObjectChanges) {
+    let mut reported = 0;
+    let mut omitted = 0;
+    for (index, (old_item, new_item)) in old.iter().zip(new).enumerate() {
+        if old_item == new_item {
+            continue;
+        }
+        if reported < MAX_VECTOR_INDICES {
+            diff_value(&format!("{}[{}]", path, index), old_item, new_item, changes);
+            reported += 1;
+        } else {
+            omitted += 1;
+        }
+    }
+
+    if old.len() != new.len() || omitted > 0 {
+        if old.len() != new.len() {
+            changes.fields_modified.push(path.to_string());
+        }
+        changes.vectors.push(VectorChange {
+            path: path.to_string(),
+            old_len: old.len(),
+            new_len: new.len(),
+            omitted_indices: omitted,
+        });
+    }
+}
+
+/// 结构体的字段：Sui 的 `{"type", "fields"}` 包装取 `fields`，否则取对象本身
+fn struct_fields(value: &Value) -> Option<&Map<String, Value>> {
+    let object = value.as_object()?;
+    match (object.get("type"), object.get("fields")) {
+        (Some(Value::String(_)), Some(Value::Object(fields))) => Some(fields),
+        _ => Some(object),
+    }
+}
+
+/// 值的类型；Sui 结构体带上 Move 类型，类型不同的结构体视为类型改变
+fn value_type(value: &Value) -> &str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "vector",
+        Value::Object(object) => match (object.get("type"), object.get("fields")) {
+            (Some(Value::String(move_type)), Some(Value::Object(_))) => move_type,
+            _ => "struct",
+        },
+    }
+}
+
+fn field_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fixture() -> Value {
+        json!({
+            "id": "0xa",
+            "balance": "100",
+            "config": {
+                "type": "0xpkg::pool::Config",
+                "fields": {
+                    "fee": { "type": "0xpkg::pool::Fee", "fields": { "rate": 30, "cap": 1000 } },
+                    "paused": false,
+                },
+            },
+            "positions": [
+                { "owner": "0x1", "amount": 10 },
+                { "owner": "0x2", "amount": 20 },
+            ],
+            "limit": 5,
+        })
+    }
+
+    #[test]
+    fn test_nested_struct_paths() {
+        let mut new = fixture();
+        new["config"]["fields"]["fee"]["fields"]["rate"] = json!(25);
+        new["config"]["fields"]["fee"]["fields"]
+            .as_object_mut()
+            .unwrap()
+            .remove("cap");
+        new["config"]["fields"]["admin"] = json!("0x9");
+        new["balance"] = json!("90");
+
+        let changes = diff_objects(&fixture(), &new);
+        assert_eq!(changes.fields_modified, vec!["balance", "config.fee.rate"]);
+        assert_eq!(changes.fields_removed, vec!["config.fee.cap"]);
+        assert_eq!(changes.fields_added, vec!["config.admin"]);
+        assert!(changes.fields_retyped.is_empty());
+        assert!(changes.vectors.is_empty());
+
+        assert_eq!(
+            diff_objects(&fixture(), &fixture()),
+            ObjectChanges::default()
+        );
+    }
+
+    #[test]
+    fn test_type_changes() {
+        let mut new = fixture();
+        new["limit"] = json!({ "type": "0xpkg::pool::Limit", "fields": { "max": 5 } });
+        new["config"]["fields"]["fee"]["type"] = json!("0xpkg::pool::FeeV2");
+
+        let changes = diff_objects(&fixture(), &new);
+        assert_eq!(changes.fields_retyped, vec!["config.fee", "limit"]);
+        assert_eq!(changes.fields_modified, vec!["config.fee", "limit"]);
+        // 类型改变后不再向下比较
+        assert!(changes.fields_added.is_empty());
+    }
+
+    #[test]
+    fn test_vector_elements_and_length() {
+        let mut new = fixture();
+        new["positions"][1]["amount"] = json!(25);
+        new["positions"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({ "owner": "0x3", "amount": 30 }));
+
+        let changes = diff_objects(&fixture(), &new);
+        assert_eq!(
+            changes.fields_modified,
+            vec!["positions[1].amount", "positions"]
+        );
+        assert_eq!(
+            changes.vectors,
+            vec![VectorChange {
+                path: "positions".to_string(),
+                old_len: 2,
+                new_len: 3,
+                omitted_indices: 0,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_large_vector_caps_reported_indices() {
+        let old = json!({ "values": (0..10_000).collect::<Vec<u64>>() });
+        let new = json!({ "values": (0..10_000).map(|i| i * 2).collect::<Vec<u64>>() });
+
+        let changes = diff_objects(&old, &new);
+        // 下标 0 未变
+        assert_eq!(changes.fields_modified.len(), MAX_VECTOR_INDICES);
+        assert_eq!(changes.fields_modified[0], "values[1]");
+        assert_eq!(
+            changes.vectors,
+            vec![VectorChange {
+                path: "values".to_string(),
+                old_len: 10_000,
+                new_len: 10_000,
+                omitted_indices: 9_999 - MAX_VECTOR_INDICES,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_non_struct_content() {
+        let changes = diff_objects(&json!("0x01"), &json!("0x02"));
+        assert_eq!(changes.fields_modified, vec![""]);
+    }
+}
@@ -0,0 +1,104 @@
+//! 会话对象状态的宿主函数
+//!
+//! guest 通过存储调用读取同步到链下的对象：键为对象 ID 时返回对象的 BCS 数据，
+//! 键为 `对象 ID/字段名` 时返回该字段的 JSON 编码。写入只进入会话内的覆盖层，
+//! 由执行效果回写主网
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use dubhe_vm_runtime::{HostFunctions, VmError, VmEvent, VmResult};
+
+/// 以会话同步的对象状态为后端的宿主函数
+#[derive(Debug, Default)]
+pub struct ObjectStateHost {
+    objects: Mutex<HashMap<String, Vec<u8>>>,
+    writes: Mutex<HashMap<String, Vec<u8>>>,
+    events: Mutex<Vec<VmEvent>>,
+}
+
+impl ObjectStateHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 加载对象的 BCS 数据与 `getObject` 返回的字段
+    pub fn load_object(&self, object_id: &str, bcs_data: &[u8], object_data: &serde_json::Value) {
+        let mut objects = self.objects.lock().expect("object host poisoned");
+        objects.insert(object_id.to_string(), bcs_data.to_vec());
+
+        if let Some(fields) = object_data["data"]["content"]["fields"].as_object() {
+            for (name, value) in fields {
+                objects.insert(
+                    format!("{}/{}", object_id, name),
+                    value.to_string().into_bytes(),
+                );
+            }
+        }
+    }
+
+    /// 本次会话中 guest 写入的键值
+    pub fn writes(&self) -> HashMap<String, Vec<u8>> {
+        self.writes.lock().expect("object host poisoned").clone()
+    }
+
+    pub fn events(&self) -> Vec<VmEvent> {
+        self.events.lock().expect("object host poisoned").clone()
+    }
+}
+
+impl HostFunctions for ObjectStateHost {
+    fn storage_read(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let key = std::str::from_utf8(key).ok()?;
+        if let Some(value) = self.writes.lock().expect("object host poisoned").get(key) {
+            return Some(value.clone());
+        }
+        self.objects
+            .lock()
+            .expect("object host poisoned")
+            .get(key)
+            .cloned()
+    }
+
+    fn storage_write(&self, key: &[u8], value: &[u8]) -> VmResult<()> {
+        let key = std::str::from_utf8(key)
+            .map_err(|_| VmError::ExecutionFailed("Storage key is not valid UTF-8".to_string()))?;
+        self.writes
+            .lock()
+            .expect("object host poisoned")
+            .insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    fn emit_event(&self, topic: &[u8], data: &[u8]) -> VmResult<()> {
+        self.events.lock().expect("object host poisoned").push(VmEvent {
+            topic: topic.to_vec(),
+            data: data.to_vec(),
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_reads_object_fields_and_overlay() {
+        let host = ObjectStateHost::new();
+        host.load_object(
+            "0xc0ffee",
+            &[1, 2, 3],
+            &json!({"data": {"content": {"fields": {"value": "42", "owner": "0xa"}}}}),
+        );
+
+        assert_eq!(host.storage_read(b"0xc0ffee"), Some(vec![1, 2, 3]));
+        assert_eq!(host.storage_read(b"0xc0ffee/value"), Some(b"\"42\"".to_vec()));
+        assert_eq!(host.storage_read(b"0xc0ffee/missing"), None);
+
+        host.storage_write(b"0xc0ffee/value", b"\"43\"").unwrap();
+        assert_eq!(host.storage_read(b"0xc0ffee/value"), Some(b"\"43\"".to_vec()));
+        assert_eq!(host.writes().len(), 1);
+    }
+}
@@ -9,29 +9,98 @@
 //! 4. 将结果同步回主网/测试网
 
 use anyhow::Result;
+use async_trait::async_trait;
+use futures::stream::{BoxStream, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::hash::{Hash, Hasher};
 use std::sync::Arc;
-use tokio::sync::{Mutex, RwLock};
-use tracing::{error, info, warn};
-
-use dubhe_adapter::{sui::SuiAdapter, ChainAdapter, ContractMeta};
-use dubhe_loader::CodeLoader;
-use dubhe_vm_runtime::{ExecutionResult, VmInstance, VmManager, VmType};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::{broadcast, Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+use dubhe_adapter::{sui::SuiAdapter, ChainAdapter, ContractMeta, MoveCall};
+use dubhe_api::{OffchainExecutionParams, OffchainHandler};
+use dubhe_loader::{CodeLoader, CompiledContract};
+use dubhe_observability::{Alert, AlertManager, MetricsCollector};
+use dubhe_security::{
+    canonical_digest, AttestationProvider, AttestationReport, AuditEvent, AuditTrail,
+    VersionedDigest,
+};
+use dubhe_state::{
+    ExecutionRecording, JournalLease, RecordedObject, StateChange, StateManager, SyncJournalRecord,
+};
+use dubhe_vm_runtime::{
+    ExecutionResult, PooledVm, StateRegion, VmInstance, VmManager, VmPool, VmPoolConfig,
+    VmPoolStats, VmType,
+};
+
+use crate::hotspot::{
+    hotspot_alert_rule, CoalescedExecutor, HotspotConfig, HotspotReport, HotspotTracker,
+    SessionCoalescer,
+};
+use crate::locking::{acquire_all, release_all, LockError, ObjectLease, ObjectLocker};
+use crate::object_diff::diff_objects;
+use crate::object_host::ObjectStateHost;
+use crate::progress::{ExecutionPhase, ExecutionProgress, ProgressChannels, SessionProgress};
+use crate::query_cache::{QueryCache, QueryCacheConfig, QueryCacheStats, QueryKey};
+use crate::replay::{code_hash, replay_recording, ReplayConfig, ReplayReport};
+use crate::sync::{
+    split_calls, submit_journaled, PtbSubmitter, SyncCommit, SyncConfig, SyncError,
+};
+use crate::verify::{
+    effects_digest, inputs_hash, load_result, save_result, verify_result, SyncTxRecord,
+    VerificationReport,
+};
 
 /// 链下执行管理器
 pub struct OffchainExecutionManager {
     sui_adapter: Arc<SuiAdapter>,
     vm_manager: Arc<VmManager>,
+    vm_pool: Arc<VmPool>,
     code_loader: Arc<CodeLoader>,
 
     // 状态管理
     locked_objects: Arc<RwLock<HashMap<String, LockedObject>>>,
-    execution_sessions: Arc<RwLock<HashMap<String, ExecutionSession>>>,
+    execution_sessions: Arc<RwLock<HashMap<String, SessionHandle>>>,
+    session_config: SessionConfig,
 
     // 执行队列
     pending_executions: Arc<Mutex<Vec<ExecutionRequest>>>,
+
+    // 热点检测与合批
+    hotspot_config: HotspotConfig,
+    hotspots: Arc<HotspotTracker>,
+    coalescer: SessionCoalescer,
+    metrics: Arc<MetricsCollector>,
+    alerts: AlertManager,
+
+    // 链下暂存的对象状态（可选）
+    state: Option<Arc<StateManager>>,
+
+    // 主网对象租约，未配置时拒绝加锁
+    locker: Option<Arc<dyn ObjectLocker>>,
+    lease_duration: Duration,
+
+    // 结果回写
+    submitter: Option<Arc<dyn PtbSubmitter>>,
+    sync_config: SyncConfig,
+
+    // 对象加锁 / 解锁的审计日志
+    audit: Option<Arc<AuditTrail>>,
+
+    // 执行会话的远程证明（可选）
+    attestation: Option<Arc<dyn AttestationProvider>>,
+
+    // 执行录制，需配合状态存储
+    replay_config: ReplayConfig,
+
+    // 只读查询结果缓存（可选）
+    query_cache: Option<Arc<QueryCache>>,
+
+    // 各会话的执行进度
+    progress: ProgressChannels,
 }
 
 /// 锁定的共享对象
@@ -43,17 +112,86 @@ pub struct LockedObject {
     pub owner: String,
     pub content: serde_json::Value,
     pub locked_at: u64,
-    pub lock_hash: String, // 主网锁定凭证
+    pub lock_hash: String, // 主网加锁交易摘要
+    pub lease_expires_at_ms: u64,
+}
+
+impl LockedObject {
+    fn lease(&self) -> ObjectLease {
+        ObjectLease {
+            object_id: self.object_id.clone(),
+            version: self.version,
+            digest: self.lock_hash.clone(),
+            expires_at_ms: self.lease_expires_at_ms,
+        }
+    }
+}
+
+/// 会话保留配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionConfig {
+    /// 未结束会话的最长存活时间（秒），超时视为卡死并清理
+    pub ttl_secs: u64,
+    /// Completed / Failed 会话的保留时间（秒）
+    pub retention_secs: u64,
+    /// 清理任务运行间隔（秒）
+    pub cleanup_interval_secs: u64,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: 600,
+            retention_secs: 60,
+            cleanup_interval_secs: 10,
+        }
+    }
 }
 
+/// 会话表中的共享会话，各执行步骤操作同一个实例
+pub type SessionHandle = Arc<Mutex<ExecutionSession>>;
+
 /// 执行会话
 pub struct ExecutionSession {
     pub session_id: String,
     pub package_id: String,
     pub locked_objects: Vec<String>,
-    pub vm_instance: Box<dyn VmInstance + Send + Sync>,
+    /// 执行结束后归还实例池，之后为 `None`
+    pub vm_instance: Option<PooledVm>,
+    /// guest 存储调用读取的对象状态
+    pub host: Arc<ObjectStateHost>,
+    /// 已加载包的编译产物，入口函数元数据用于编码调用参数
+    pub contract: Arc<CompiledContract>,
     pub created_at: u64,
     pub status: SessionStatus,
+    /// 最近一次状态变更时间，用于 TTL 与保留期判断
+    pub updated_at: Instant,
+    /// 录制模式下逐步填充的执行录制，执行结束后写入状态存储
+    pub recording: Option<ExecutionRecording>,
+}
+
+impl ExecutionSession {
+    pub fn set_status(&mut self, status: SessionStatus) {
+        self.status = status;
+        self.updated_at = Instant::now();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            SessionStatus::Completed | SessionStatus::Failed(_)
+        )
+    }
+
+    /// 已结束会话超过保留期，或未结束会话超过 TTL
+    fn is_expired(&self, config: &SessionConfig, now: Instant) -> bool {
+        let limit = if self.is_finished() {
+            config.retention_secs
+        } else {
+            config.ttl_secs
+        };
+        now.saturating_duration_since(self.updated_at) >= Duration::from_secs(limit)
+    }
 }
 
 impl std::fmt::Debug for ExecutionSession {
@@ -88,22 +226,44 @@ pub struct ExecutionRequest {
     pub arguments: Vec<serde_json::Value>,
     pub shared_objects: Vec<String>,
     pub gas_budget: u64,
+    /// 只读查询：输入对象版本未变时可直接返回缓存的结果，见 [`QueryCache`]
+    #[serde(default)]
+    pub read_only: bool,
 }
 
 /// 执行结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct OffchainExecutionResult {
     pub session_id: String,
     pub success: bool,
     pub gas_used: u64,
     pub modified_objects: Vec<ModifiedObject>,
     pub new_objects: Vec<CreatedObject>,
+    #[serde(default)]
+    pub deleted_objects: Vec<DeletedObject>,
+    /// 回写主网的 PTB 摘要及各自包含的变更
+    #[serde(default)]
+    pub commits: Vec<SyncCommit>,
     pub error: Option<String>,
     pub execution_time_ms: u64,
+    /// 绑定会话输入输出的证明报告，见 [`attestation_user_data`]
+    #[serde(default)]
+    pub attestation: Option<AttestationReport>,
+    /// 会话输入摘要，见 [`inputs_hash`]
+    #[serde(default)]
+    pub inputs_hash: String,
+    /// 可在链上核对的效果摘要，见 [`crate::verify`]
+    #[serde(default)]
+    pub effects_digest: Option<VersionedDigest>,
+    /// 回写主网的交易
+    #[serde(default)]
+    pub sync_transactions: Vec<SyncTxRecord>,
 }
 
 /// 修改的对象
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ModifiedObject {
     pub object_id: String,
     pub old_version: u64,
@@ -113,18 +273,255 @@ pub struct ModifiedObject {
 
 /// 创建的对象
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CreatedObject {
     pub object_type: String,
     pub content: serde_json::Value,
     pub owner: String,
 }
 
-/// 对象变更
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 删除的对象
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedObject {
+    pub object_id: String,
+    pub old_version: u64,
+}
+
+/// 对象变更，字段以路径表示，见 [`crate::object_diff`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ObjectChanges {
     pub fields_modified: Vec<String>,
     pub fields_added: Vec<String>,
     pub fields_removed: Vec<String>,
+    /// 值类型改变的字段，同时列在 `fields_modified` 中
+    #[serde(default)]
+    pub fields_retyped: Vec<String>,
+    /// 长度改变或修改下标超出上限的向量
+    #[serde(default)]
+    pub vectors: Vec<VectorChange>,
+}
+
+/// 向量变更
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorChange {
+    pub path: String,
+    pub old_len: usize,
+    pub new_len: usize,
+    /// 修改但未逐一列出的下标数
+    pub omitted_indices: usize,
+}
+
+/// 执行效果输出的魔数
+///
+/// guest 在输出开头写入效果信封：`[b"DBFX"][u32 LE 格式版本][u32 LE 长度][JSON]`，
+/// JSON 为 `{"effects": [ObjectEffect, ...]}`；不以魔数开头的输出视为普通返回值，没有状态变更
+pub const EFFECTS_MAGIC: &[u8; 4] = b"DBFX";
+
+/// 当前效果格式版本
+pub const EFFECTS_FORMAT_VERSION: u32 = 1;
+
+/// 对象效果类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EffectKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+/// guest 上报的单个对象效果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectEffect {
+    pub kind: EffectKind,
+    /// modified / deleted 必填，为会话输入对象之一
+    #[serde(default)]
+    pub object_id: Option<String>,
+    /// modified / deleted 必填，须等于输入时给出的版本
+    #[serde(default)]
+    pub old_version: Option<u64>,
+    /// created 必填
+    #[serde(default)]
+    pub object_type: Option<String>,
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// 新内容字节（hex），deleted 时为空
+    #[serde(default)]
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EffectsEnvelope {
+    effects: Vec<ObjectEffect>,
+}
+
+/// 效果解析错误，任一错误都使本次执行失败
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EffectsError {
+    #[error("Malformed effects envelope: {0}")]
+    Malformed(String),
+
+    #[error("Unsupported effects format version {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("{kind:?} effect is missing field {field}")]
+    MissingField { kind: EffectKind, field: &'static str },
+
+    #[error("Effect references object {0} that was not an input of the session")]
+    UnknownObject(String),
+
+    #[error("Effect for {object_id} reports version {reported}, older than input version {given}")]
+    VersionRegression {
+        object_id: String,
+        given: u64,
+        reported: u64,
+    },
+
+    #[error("Effect for {object_id} reports version {reported}, but input version was {given}")]
+    VersionMismatch {
+        object_id: String,
+        given: u64,
+        reported: u64,
+    },
+
+    #[error("Duplicate effect for object {0}")]
+    DuplicateEntry(String),
+}
+
+/// 解析后的执行效果
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionEffects {
+    pub modified: Vec<ModifiedObject>,
+    pub created: Vec<CreatedObject>,
+    pub deleted: Vec<DeletedObject>,
+}
+
+/// 解析 VM 输出中的效果信封，并按会话输入对象严格校验
+pub fn parse_effects(
+    output: &[u8],
+    inputs: &[LockedObject],
+) -> std::result::Result<ExecutionEffects, EffectsError> {
+    let Some(rest) = output.strip_prefix(EFFECTS_MAGIC.as_slice()) else {
+        return Ok(ExecutionEffects::default());
+    };
+    let header = |bytes: &[u8]| -> std::result::Result<u32, EffectsError> {
+        Ok(u32::from_le_bytes(bytes.try_into().map_err(|_| {
+            EffectsError::Malformed("truncated header".to_string())
+        })?))
+    };
+    let version = header(rest.get(..4).unwrap_or_default())?;
+    if version != EFFECTS_FORMAT_VERSION {
+        return Err(EffectsError::UnsupportedVersion(version));
+    }
+    let len = header(rest.get(4..8).unwrap_or_default())? as usize;
+    let body = &rest[8..];
+    if body.len() != len {
+        return Err(EffectsError::Malformed(format!(
+            "declared {} bytes, found {}",
+            len,
+            body.len()
+        )));
+    }
+    let envelope: EffectsEnvelope =
+        serde_json::from_slice(body).map_err(|e| EffectsError::Malformed(e.to_string()))?;
+
+    let inputs: HashMap<&str, &LockedObject> = inputs
+        .iter()
+        .map(|object| (object.object_id.as_str(), object))
+        .collect();
+    let mut seen = std::collections::HashSet::new();
+    let mut effects = ExecutionEffects::default();
+
+    for effect in envelope.effects {
+        let missing = |field| EffectsError::MissingField {
+            kind: effect.kind,
+            field,
+        };
+        match effect.kind {
+            EffectKind::Created => {
+                if let Some(object_id) = &effect.object_id {
+                    if !seen.insert(object_id.clone()) {
+                        return Err(EffectsError::DuplicateEntry(object_id.clone()));
+                    }
+                }
+                effects.created.push(CreatedObject {
+                    object_type: effect.object_type.clone().ok_or_else(|| missing("object_type"))?,
+                    content: decode_content(&effect.content)?,
+                    owner: effect.owner.clone().unwrap_or_else(|| "shared".to_string()),
+                });
+            }
+            EffectKind::Modified | EffectKind::Deleted => {
+                let object_id = effect.object_id.clone().ok_or_else(|| missing("object_id"))?;
+                let reported = effect.old_version.ok_or_else(|| missing("old_version"))?;
+                let input = inputs
+                    .get(object_id.as_str())
+                    .ok_or_else(|| EffectsError::UnknownObject(object_id.clone()))?;
+                if reported < input.version {
+                    return Err(EffectsError::VersionRegression {
+                        object_id,
+                        given: input.version,
+                        reported,
+                    });
+                }
+                if reported != input.version {
+                    return Err(EffectsError::VersionMismatch {
+                        object_id,
+                        given: input.version,
+                        reported,
+                    });
+                }
+                if !seen.insert(object_id.clone()) {
+                    return Err(EffectsError::DuplicateEntry(object_id));
+                }
+
+                if effect.kind == EffectKind::Deleted {
+                    effects.deleted.push(DeletedObject {
+                        object_id,
+                        old_version: reported,
+                    });
+                } else {
+                    let new_content = decode_content(&effect.content)?;
+                    effects.modified.push(ModifiedObject {
+                        changes: diff_objects(&input.content, &new_content),
+                        object_id,
+                        old_version: reported,
+                        new_content,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(effects)
+}
+
+/// 内容为 hex 编码的字节；是 JSON 时按 JSON 解析，否则保留 hex 字符串
+fn decode_content(content: &str) -> std::result::Result<serde_json::Value, EffectsError> {
+    let hex_str = content.strip_prefix("0x").unwrap_or(content);
+    let bytes = hex::decode(hex_str).map_err(|e| EffectsError::Malformed(e.to_string()))?;
+    Ok(serde_json::from_slice(&bytes)
+        .unwrap_or_else(|_| serde_json::Value::String(format!("0x{}", hex_str))))
+}
+
+/// 会话证明的摘要域
+pub const ATTESTATION_DOMAIN: &str = "dubhe.offchain.attestation";
+
+/// 证明报告中的用户数据：会话 ID、输入、输出与修改对象的规范摘要（32 字节）
+pub fn attestation_user_data(
+    request: &ExecutionRequest,
+    output: &[u8],
+    modified_objects: &[ModifiedObject],
+) -> Result<Vec<u8>> {
+    let payload = serde_json::json!({
+        "session_id": request.session_id,
+        "input": request,
+        "output": hex::encode(output),
+        "modified_objects": modified_objects,
+    });
+    let digest = canonical_digest(ATTESTATION_DOMAIN, &payload)?.digest;
+    Ok(hex::decode(digest.trim_start_matches("0x"))?)
 }
 
 impl OffchainExecutionManager {
@@ -135,180 +532,600 @@ impl OffchainExecutionManager {
     ) -> Result<Self> {
         info!("🚀 Initializing Offchain Execution Manager");
 
+        let metrics = Arc::new(MetricsCollector::new());
+        let hotspot_config = HotspotConfig::default();
+        let hotspots = Arc::new(HotspotTracker::new(
+            hotspot_config.clone(),
+            metrics.clone(),
+        ));
+        let coalescer = SessionCoalescer::new(hotspots.clone(), metrics.clone());
+        let mut alerts = AlertManager::new();
+        alerts.add_rule(hotspot_alert_rule());
+
+        let vm_pool = Arc::new(VmPool::new(vm_manager.clone(), VmPoolConfig::default()));
+
         Ok(Self {
             sui_adapter,
             vm_manager,
+            vm_pool,
             code_loader,
             locked_objects: Arc::new(RwLock::new(HashMap::new())),
             execution_sessions: Arc::new(RwLock::new(HashMap::new())),
+            session_config: SessionConfig::default(),
             pending_executions: Arc::new(Mutex::new(Vec::new())),
+            hotspot_config,
+            hotspots,
+            coalescer,
+            metrics,
+            alerts,
+            state: None,
+            locker: None,
+            lease_duration: Duration::from_secs(60),
+            submitter: None,
+            sync_config: SyncConfig::default(),
+            audit: None,
+            attestation: None,
+            replay_config: ReplayConfig::default(),
+            query_cache: None,
+            progress: ProgressChannels::new(),
         })
     }
 
+    /// 按配置替换会话使用的 VM 实例池
+    pub fn with_vm_pool(mut self, config: VmPoolConfig) -> Self {
+        self.vm_pool = Arc::new(VmPool::new(self.vm_manager.clone(), config));
+        self
+    }
+
+    /// 通过主网锁注册表获取对象租约
+    pub fn with_object_locker(mut self, locker: Arc<dyn ObjectLocker>, lease: Duration) -> Self {
+        self.locker = Some(locker);
+        self.lease_duration = lease;
+        self
+    }
+
+    /// 使用持久化状态存储暂存同步到链下的对象及执行结果
+    pub fn with_state_manager(mut self, state: Arc<StateManager>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// 结果以 PTB 回写主网
+    pub fn with_ptb_submitter(
+        mut self,
+        submitter: Arc<dyn PtbSubmitter>,
+        config: SyncConfig,
+    ) -> Self {
+        self.submitter = Some(submitter);
+        self.sync_config = config;
+        self
+    }
+
+    /// 对象加锁与解锁写入审计日志
+    pub fn with_audit_trail(mut self, audit: Arc<AuditTrail>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// 为每个成功执行的会话生成远程证明报告
+    pub fn with_attestation(mut self, provider: Arc<dyn AttestationProvider>) -> Self {
+        self.attestation = Some(provider);
+        self
+    }
+
+    /// 使用自定义会话保留配置
+    pub fn with_session_config(mut self, config: SessionConfig) -> Self {
+        self.session_config = config;
+        self
+    }
+
+    /// 录制会话的执行输入，供 [`Self::replay_transaction`] 重放；录制写入状态存储
+    pub fn with_replay_config(mut self, config: ReplayConfig) -> Self {
+        self.replay_config = config;
+        self
+    }
+
+    /// 使用自定义热点检测配置
+    pub fn with_hotspot_config(mut self, config: HotspotConfig) -> Self {
+        self.hotspots = Arc::new(HotspotTracker::new(config.clone(), self.metrics.clone()));
+        self.coalescer = SessionCoalescer::new(self.hotspots.clone(), self.metrics.clone());
+        self.hotspot_config = config;
+        self
+    }
+
+    /// 缓存只读查询的结果，需再调用 [`Self::follow_object_versions`] 才会提供结果
+    pub fn with_query_cache(mut self, config: QueryCacheConfig) -> Self {
+        self.query_cache = config
+            .enabled
+            .then(|| Arc::new(QueryCache::new(&config, self.metrics.clone())));
+        self
+    }
+
     /// Phase 1 完整执行流程
     pub async fn execute_offchain(
         &self,
         request: ExecutionRequest,
     ) -> Result<OffchainExecutionResult> {
-        let start_time = std::time::Instant::now();
+        // 任何返回路径上丢弃时都会关闭进度通道
+        let progress = self.progress.session(&request.session_id);
+
+        // 只读查询：输入对象版本未变时直接返回上次的结果，不加锁
+        if request.read_only {
+            if let Some(cached) = self.cached_query(&request) {
+                info!("⚡ Query cache hit for session {}", request.session_id);
+                return Ok(cached);
+            }
+        }
+
+        for object_id in &request.shared_objects {
+            self.hotspots.record_access(object_id);
+        }
+
+        // 热点共享对象：与其它待执行会话合并到同一次加锁中
+        if let Some(object_id) = self.coalescing_target(&request) {
+            info!(
+                "🔥 Coalescing session {} on hot object {}",
+                request.session_id, object_id
+            );
+            return self.coalescer.submit(&object_id, request, self).await;
+        }
+
+        let start_time = Instant::now();
         info!(
             "🎯 Starting offchain execution for session: {}",
             request.session_id
         );
 
         // Step 1: 锁定主网共享对象
+        progress.enter(ExecutionPhase::Locking);
         let locked_objects = self.lock_mainnet_objects(&request.shared_objects).await?;
+        let locked_at = Instant::now();
         info!("🔒 Locked {} objects on mainnet", locked_objects.len());
+        progress.objects_locked(locked_objects.len());
+
+        let result = self
+            .run_locked_session(&request, locked_objects, start_time, &progress)
+            .await;
+
+        // 部分块已提交：保留租约，由 resume_pending_syncs 提交剩余块后释放
+        if let Err(e) = &result {
+            if let Some(SyncError::Incomplete { .. }) = e.downcast_ref::<SyncError>() {
+                warn!(
+                    "⏸️ Keeping object leases for session {} until its sync journal is resumed",
+                    request.session_id
+                );
+                return result;
+            }
+        }
+
+        // Step 6: 释放锁定的对象
+        progress.enter(ExecutionPhase::Releasing);
+        self.unlock_mainnet_objects(&request.shared_objects).await?;
+        let held = locked_at.elapsed();
+        for object_id in &request.shared_objects {
+            self.hotspots.record_serialization(object_id, held);
+        }
+        info!("🔓 Released object locks on mainnet");
+
+        result
+    }
+
+    /// Step 2-5: 在对象已锁定的前提下执行单个会话
+    async fn run_locked_session(
+        &self,
+        request: &ExecutionRequest,
+        locked_objects: Vec<LockedObject>,
+        start_time: Instant,
+        progress: &SessionProgress,
+    ) -> Result<OffchainExecutionResult> {
+        let inputs: Vec<(String, u64)> = locked_objects
+            .iter()
+            .map(|object| (object.object_id.clone(), object.version))
+            .collect();
 
         // Step 2: 创建执行会话
-        let session = self
-            .create_execution_session(&request, locked_objects)
+        let handle = self
+            .create_execution_session(request, locked_objects)
             .await?;
+        let mut session = handle.lock().await;
         info!("📝 Created execution session: {}", session.session_id);
 
-        // Step 3: 同步状态到链下
-        self.sync_state_to_offchain(&session).await?;
-        info!("⬇️ Synced state to offchain environment");
-
-        // Step 4: 在 CKB-VM 中执行 Move 逻辑
-        let execution_result = self.execute_in_ckb_vm(&session, &request).await?;
-        info!("⚡ Completed execution in CKB-VM");
+        let result = self
+            .run_session_steps(&mut session, request, progress)
+            .await;
+        if let Err(e) = &result {
+            session.set_status(SessionStatus::Failed(e.to_string()));
+        }
+        let (execution_result, sync_result) = result?;
 
-        // Step 5: 同步结果回主网
-        let sync_result = self
-            .sync_results_to_mainnet(&session, &execution_result)
-            .await?;
-        info!("⬆️ Synced results back to mainnet");
+        let attestation = self.attest(request, &execution_result, &sync_result.modified_objects)?;
 
-        // Step 6: 释放锁定的对象
-        self.unlock_mainnet_objects(&request.shared_objects).await?;
-        info!("🔓 Released object locks on mainnet");
+        let inputs_hash = inputs_hash(request, &inputs)?;
+        let effects_digest = effects_digest(
+            &sync_result.modified_objects,
+            &sync_result.new_objects,
+            execution_result.gas_used,
+            &inputs_hash,
+        )?;
 
         let execution_time = start_time.elapsed().as_millis() as u64;
         info!("✅ Offchain execution completed in {}ms", execution_time);
 
-        Ok(OffchainExecutionResult {
-            session_id: request.session_id,
+        let result = OffchainExecutionResult {
+            session_id: request.session_id.clone(),
             success: execution_result.success,
             gas_used: execution_result.gas_used,
             modified_objects: sync_result.modified_objects,
             new_objects: sync_result.new_objects,
+            deleted_objects: sync_result.deleted_objects,
+            sync_transactions: sync_result.commits.iter().map(SyncTxRecord::from).collect(),
+            commits: sync_result.commits,
             error: execution_result.error,
             execution_time_ms: execution_time,
-        })
+            attestation,
+            inputs_hash,
+            effects_digest: Some(effects_digest),
+        };
+        // 存档结果，供 dubhe_verifyExecution 按会话 ID 核对
+        if let Some(state) = &self.state {
+            save_result(&state.storage(), &result)?;
+        }
+        if request.read_only {
+            self.cache_query(request, &session.contract, inputs, &result);
+        }
+        Ok(result)
+    }
+
+    /// 查询缓存；命中的结果换上本次的会话 ID
+    fn cached_query(&self, request: &ExecutionRequest) -> Option<OffchainExecutionResult> {
+        let cache = self.query_cache.as_ref()?;
+        let start_time = Instant::now();
+        // 执行前开始跟踪输入对象，执行期间的版本变化使结果不被写入
+        cache.watch(&request.shared_objects);
+        let key = cache.contract(&request.package_id).and_then(|contract| {
+            cache.key(
+                &request.package_id,
+                &request.function_name,
+                query_arguments(&contract, request)?,
+                &request.shared_objects,
+            )
+        });
+        let mut cached = cache
+            .get(key.as_ref())
+            // 本次预算不足以完成上次的执行时照常执行，由 VM 报告 gas 不足
+            .filter(|cached| cached.gas_used <= request.gas_budget)?;
+        cached.session_id = request.session_id.clone();
+        cached.execution_time_ms = start_time.elapsed().as_millis() as u64;
+        Some(cached)
+    }
+
+    /// 写入查询缓存；只有成功且没有任何对象效果、无需证明的结果可以复用
+    fn cache_query(
+        &self,
+        request: &ExecutionRequest,
+        contract: &Arc<CompiledContract>,
+        inputs: Vec<(String, u64)>,
+        result: &OffchainExecutionResult,
+    ) {
+        let Some(cache) = &self.query_cache else {
+            return;
+        };
+        let cacheable = result.success
+            && result.modified_objects.is_empty()
+            && result.new_objects.is_empty()
+            && result.deleted_objects.is_empty()
+            && result.commits.is_empty()
+            && result.attestation.is_none();
+        if !cacheable {
+            return;
+        }
+        let Some(arguments) = query_arguments(contract, request) else {
+            return;
+        };
+        cache.remember_contract(&request.package_id, contract.clone());
+        let key = QueryKey::new(
+            &request.package_id,
+            &request.function_name,
+            arguments,
+            inputs,
+        );
+        cache.insert(key, result.clone());
+    }
+
+    /// 启动对象版本订阅驱动查询缓存失效；未启用缓存时返回 `None`
+    pub async fn follow_object_versions(&self) -> Result<Option<JoinHandle<()>>> {
+        let Some(cache) = &self.query_cache else {
+            return Ok(None);
+        };
+        let updates = self.sui_adapter.subscribe_object_versions().await?;
+        Ok(Some(cache.follow(updates)))
+    }
+
+    /// 按会话存档的回写交易摘要从主网核对执行结果，需配置状态存储
+    pub async fn verify_execution(&self, session_id: &str) -> Result<VerificationReport> {
+        let state = self.state.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("Execution results are not archived without a state store")
+        })?;
+        let record = load_result(&state.storage(), session_id)?
+            .ok_or_else(|| anyhow::anyhow!("No recorded result for session {}", session_id))?;
+        let report = verify_result(&record, self.sui_adapter.as_ref()).await?;
+        info!(
+            "🔎 Verified session {} against mainnet: {}",
+            session_id,
+            if report.matches { "match" } else { "mismatch" }
+        );
+        Ok(report)
+    }
+
+    /// 查询缓存统计，未启用缓存时返回 `None`
+    pub fn query_cache_stats(&self) -> Option<QueryCacheStats> {
+        self.query_cache.as_ref().map(|cache| cache.stats())
+    }
+
+    pub fn vm_pool_stats(&self) -> VmPoolStats {
+        self.vm_pool.stats()
+    }
+
+    /// 订阅会话的执行进度，会话结束时通道关闭；可在会话开始前订阅
+    pub fn subscribe_progress(&self, session_id: &str) -> broadcast::Receiver<ExecutionProgress> {
+        self.progress.subscribe(session_id)
+    }
+
+    /// 为会话生成证明报告，未配置提供方或执行失败时跳过
+    fn attest(
+        &self,
+        request: &ExecutionRequest,
+        execution_result: &ExecutionResult,
+        modified_objects: &[ModifiedObject],
+    ) -> Result<Option<AttestationReport>> {
+        let Some(provider) = &self.attestation else {
+            return Ok(None);
+        };
+        if !execution_result.success {
+            return Ok(None);
+        }
+        let user_data = attestation_user_data(request, &execution_result.output, modified_objects)?;
+        let report = provider.generate_report(&user_data)?;
+        info!(
+            "🛡️ Attested session {} with {} provider",
+            request.session_id, report.provider
+        );
+        Ok(Some(report))
+    }
+
+    /// Step 3-5
+    async fn run_session_steps(
+        &self,
+        session: &mut ExecutionSession,
+        request: &ExecutionRequest,
+        progress: &SessionProgress,
+    ) -> Result<(ExecutionResult, SyncResult)> {
+        let execution_result = self.run_vm_steps(session, request, progress).await;
+        // 无论成败，执行结束后 VM 实例都不再需要
+        if let Some(vm_instance) = session.vm_instance.take() {
+            self.vm_pool.checkin(vm_instance).await;
+        }
+        let execution_result = execution_result?;
+
+        // Step 5: 同步结果回主网
+        progress.enter(ExecutionPhase::SyncingResults);
+        let sync_result = self
+            .sync_results_to_mainnet(session, &execution_result)
+            .await?;
+        info!("⬆️ Synced results back to mainnet");
+
+        if execution_result.success {
+            session.set_status(SessionStatus::Completed);
+        }
+
+        Ok((execution_result, sync_result))
+    }
+
+    /// Step 3-4
+    async fn run_vm_steps(
+        &self,
+        session: &mut ExecutionSession,
+        request: &ExecutionRequest,
+        progress: &SessionProgress,
+    ) -> Result<ExecutionResult> {
+        // Step 3: 同步状态到链下
+        progress.enter(ExecutionPhase::SyncingState);
+        self.sync_state_to_offchain(session, progress).await?;
+        info!("⬇️ Synced state to offchain environment");
+
+        // Step 4: 在 CKB-VM 中执行 Move 逻辑
+        progress.enter(ExecutionPhase::Executing);
+        let execution_result = self.execute_in_ckb_vm(session, request).await?;
+        progress.gas_used(execution_result.gas_used);
+        info!("⚡ Completed execution in CKB-VM");
+        Ok(execution_result)
+    }
+
+    /// 单共享对象且该对象为热点时返回合批目标
+    fn coalescing_target(&self, request: &ExecutionRequest) -> Option<String> {
+        if !self.hotspot_config.enable_coalescing || request.shared_objects.len() != 1 {
+            return None;
+        }
+        let object_id = &request.shared_objects[0];
+        self.hotspots
+            .is_hot(object_id)
+            .then(|| object_id.clone())
+    }
+
+    /// 当前热点对象
+    pub fn hot_objects(&self) -> Vec<HotspotReport> {
+        self.hotspots.hot_objects()
+    }
+
+    /// 链下执行相关指标
+    pub fn metrics(&self) -> Arc<MetricsCollector> {
+        self.metrics.clone()
+    }
+
+    /// 评估告警规则
+    pub fn evaluate_alerts(&self) -> Vec<Alert> {
+        self.alerts.evaluate(&self.metrics)
     }
 
     /// Step 1: 锁定主网共享对象
+    ///
+    /// 在锁注册表中为每个对象登记租约；任一对象加锁失败（交易失败或已被其它节点持有）时
+    /// 释放已获取的租约并返回错误
     async fn lock_mainnet_objects(&self, object_ids: &[String]) -> Result<Vec<LockedObject>> {
         info!(
             "🔒 Locking {} objects on Sui mainnet/testnet",
             object_ids.len()
         );
 
-        let mut locked_objects = Vec::new();
-
-        for object_id in object_ids {
-            // 获取对象当前状态
-            let contract_meta = self.sui_adapter.get_contract_meta(object_id).await?;
-
-            // 模拟主网锁定操作（实际需要调用 Sui 的对象锁定 API）
-            let locked_object = LockedObject {
-                object_id: object_id.clone(),
-                object_type: format!("{:?}", contract_meta.contract_type),
-                version: self.get_object_version(object_id).await?,
-                owner: contract_meta.creator.unwrap_or("shared".to_string()),
-                content: serde_json::from_str(&contract_meta.abi.unwrap_or("{}".to_string()))?,
-                locked_at: chrono::Utc::now().timestamp() as u64,
-                lock_hash: self.generate_lock_hash(object_id),
-            };
-
-            // 存储锁定状态
-            self.locked_objects
-                .write()
-                .await
-                .insert(object_id.clone(), locked_object.clone());
-            let version = locked_object.version;
-            locked_objects.push(locked_object);
+        let locker = self
+            .locker
+            .as_ref()
+            .ok_or(LockError::NotConfigured("lock_package_id"))?;
+
+        // 本节点已持有的租约同样视为冲突
+        {
+            let locked = self.locked_objects.read().await;
+            if let Some(object_id) = object_ids.iter().find(|id| locked.contains_key(*id)) {
+                return Err(LockError::AlreadyLeased(object_id.clone()).into());
+            }
+        }
+
+        let leases = acquire_all(locker.as_ref(), object_ids, self.lease_duration).await?;
 
-            info!("🔒 Locked object: {} (version {})", object_id, version);
+        let mut locked_objects = Vec::with_capacity(leases.len());
+        for lease in &leases {
+            match self.describe_locked_object(lease).await {
+                Ok(locked_object) => locked_objects.push(locked_object),
+                Err(e) => {
+                    release_all(locker.as_ref(), &leases).await;
+                    return Err(e);
+                }
+            }
+        }
+
+        let mut locked = self.locked_objects.write().await;
+        for locked_object in &locked_objects {
+            info!(
+                "🔒 Locked object: {} (version {}, lease {})",
+                locked_object.object_id, locked_object.version, locked_object.lock_hash
+            );
+            locked.insert(locked_object.object_id.clone(), locked_object.clone());
+            if let Some(audit) = &self.audit {
+                audit.record_event(AuditEvent::ObjectLocked {
+                    object_id: locked_object.object_id.clone(),
+                    version: locked_object.version,
+                    lease: locked_object.lock_hash.clone(),
+                });
+            }
         }
 
         Ok(locked_objects)
     }
 
+    /// 获取已加锁对象的元数据
+    async fn describe_locked_object(&self, lease: &ObjectLease) -> Result<LockedObject> {
+        let contract_meta = self.sui_adapter.get_contract_meta(&lease.object_id).await?;
+        Ok(LockedObject {
+            object_id: lease.object_id.clone(),
+            object_type: format!("{:?}", contract_meta.contract_type),
+            version: lease.version,
+            owner: contract_meta.creator.unwrap_or("shared".to_string()),
+            content: serde_json::from_str(&contract_meta.abi.unwrap_or("{}".to_string()))?,
+            locked_at: chrono::Utc::now().timestamp() as u64,
+            lock_hash: lease.digest.clone(),
+            lease_expires_at_ms: lease.expires_at_ms,
+        })
+    }
+
     /// Step 2: 创建执行会话
     async fn create_execution_session(
         &self,
         request: &ExecutionRequest,
         locked_objects: Vec<LockedObject>,
-    ) -> Result<ExecutionSession> {
+    ) -> Result<SessionHandle> {
         info!("📝 Creating execution session: {}", request.session_id);
 
-        // 创建 CKB-VM 实例
-        let vm_instance = self.vm_manager.create_instance(Some(VmType::CkbVM))?;
-
-        // 加载 Move 包到 VM
+        // 从实例池签出已加载该 Move 包的 CKB-VM 实例，guest 通过宿主函数读取会话同步的对象状态
+        let host = Arc::new(ObjectStateHost::new());
         let package_meta = self
             .sui_adapter
             .get_contract_meta(&request.package_id)
             .await?;
         let compiled_contract = self.code_loader.load_contract(&package_meta).await?;
-
-        let mut vm_instance = vm_instance;
-        vm_instance
-            .load_code(&compiled_contract.risc_v_code)
+        let vm_instance = self
+            .vm_pool
+            .checkout(
+                Some(VmType::CkbVM),
+                &compiled_contract.risc_v_code,
+                Some(host.clone()),
+            )
             .await?;
-
-        let session = ExecutionSession {
+        let contract = Arc::new(compiled_contract);
+        let recording = self.recording_enabled().then(|| ExecutionRecording {
             session_id: request.session_id.clone(),
             package_id: request.package_id.clone(),
-            locked_objects: locked_objects
-                .iter()
-                .map(|obj| obj.object_id.clone())
-                .collect(),
-            vm_instance,
-            created_at: chrono::Utc::now().timestamp() as u64,
-            status: SessionStatus::ObjectsLocked,
-        };
-
-        self.execution_sessions
-            .write()
-            .await
-            .insert(request.session_id.clone(), session);
+            vm_type: vm_instance.vm_type(),
+            code_hash: code_hash(&contract.risc_v_code),
+            code: contract.risc_v_code.clone(),
+            regions: vec![],
+            objects: vec![],
+            input: vec![],
+            gas_budget: request.gas_budget,
+            result: ExecutionResult::default(),
+            recorded_at: 0,
+        });
 
-        Ok(ExecutionSession {
+        let handle = Arc::new(Mutex::new(ExecutionSession {
             session_id: request.session_id.clone(),
             package_id: request.package_id.clone(),
             locked_objects: locked_objects
                 .iter()
                 .map(|obj| obj.object_id.clone())
                 .collect(),
-            vm_instance: self.vm_manager.create_instance(Some(VmType::CkbVM))?,
+            vm_instance: Some(vm_instance),
+            host,
+            contract,
             created_at: chrono::Utc::now().timestamp() as u64,
             status: SessionStatus::ObjectsLocked,
-        })
+            updated_at: Instant::now(),
+            recording,
+        }));
+
+        let mut sessions = self.execution_sessions.write().await;
+        if sessions.contains_key(&request.session_id) {
+            return Err(anyhow::anyhow!(
+                "Session already exists: {}",
+                request.session_id
+            ));
+        }
+        sessions.insert(request.session_id.clone(), handle.clone());
+
+        Ok(handle)
     }
 
     /// Step 3: 同步状态到链下 (真实实现)
-    async fn sync_state_to_offchain(&self, session: &ExecutionSession) -> Result<()> {
+    async fn sync_state_to_offchain(
+        &self,
+        session: &mut ExecutionSession,
+        progress: &SessionProgress,
+    ) -> Result<()> {
         info!(
             "⬇️ Syncing state to offchain for session: {}",
             session.session_id
         );
 
-        // 更新会话状态
-        if let Some(mut stored_session) = self
-            .execution_sessions
-            .write()
-            .await
-            .get_mut(&session.session_id)
-        {
-            stored_session.status = SessionStatus::StateSync;
-        }
+        session.set_status(SessionStatus::StateSync);
 
         // 真实的状态同步逻辑
-        for object_id in &session.locked_objects {
-            if let Some(_locked_object) = self.locked_objects.read().await.get(object_id) {
+        let object_ids = session.locked_objects.clone();
+        for object_id in &object_ids {
+            let locked_version = self
+                .locked_objects
+                .read()
+                .await
+                .get(object_id)
+                .map(|locked| locked.version);
+            if let Some(locked_version) = locked_version {
                 info!("📦 Syncing object {} to VM memory", object_id);
 
                 // 1. 从 Sui 网络获取对象的真实 BCS 数据
@@ -318,32 +1135,38 @@ impl OffchainExecutionManager {
                     bcs_data.len(),
                     object_id
                 );
+                progress.add_bytes_synced(bcs_data.len() as u64);
 
                 // 2. 获取对象的完整状态数据
                 let object_data = self.sui_adapter.get_object_data(object_id).await?;
                 info!("✅ Retrieved complete object data for {}", object_id);
 
+                // 暂存锁定版本的对象状态
+                if let Some(state) = &self.state {
+                    state
+                        .storage()
+                        .put_object(object_id, locked_version, &bcs_data)?;
+                }
+
                 // 3. 将真实状态加载到 VM 内存空间
-                if let Some(stored_session) = self
-                    .execution_sessions
-                    .write()
-                    .await
-                    .get_mut(&session.session_id)
-                {
-                    // 将 BCS 数据和对象状态写入 VM 内存
-                    let memory_layout =
-                        self.prepare_object_memory_layout(object_id, &bcs_data, &object_data)?;
-
-                    // 使用 load_code 方法代替不存在的 load_state_data
-                    stored_session.vm_instance.load_code(&memory_layout).await?;
-
-                    info!(
-                        "✅ Loaded real state data for object {} into VM memory",
-                        object_id
-                    );
-                } else {
-                    return Err(anyhow::anyhow!("Session not found: {}", session.session_id));
+                // 将 BCS 数据和对象状态作为可写状态区域加载，合约通过系统调用读写
+                let region =
+                    self.prepare_object_memory_layout(object_id, &bcs_data, &object_data)?;
+                if let Some(recording) = &mut session.recording {
+                    recording.regions.push(region.clone());
+                    recording.objects.push(RecordedObject {
+                        object_id: object_id.clone(),
+                        bcs_data: bcs_data.clone(),
+                        object_data: object_data.clone(),
+                    });
                 }
+                session_vm(session)?.load_state(region).await?;
+                session.host.load_object(object_id, &bcs_data, &object_data);
+
+                info!(
+                    "✅ Loaded real state data for object {} into VM memory",
+                    object_id
+                );
             }
         }
 
@@ -357,7 +1180,7 @@ impl OffchainExecutionManager {
     /// Step 4: 在 CKB-VM 中执行 Move 逻辑
     async fn execute_in_ckb_vm(
         &self,
-        session: &ExecutionSession,
+        session: &mut ExecutionSession,
         request: &ExecutionRequest,
     ) -> Result<ExecutionResult> {
         info!(
@@ -365,39 +1188,35 @@ impl OffchainExecutionManager {
             session.session_id
         );
 
-        // 更新会话状态
-        if let Some(mut stored_session) = self
-            .execution_sessions
-            .write()
-            .await
-            .get_mut(&session.session_id)
-        {
-            stored_session.status = SessionStatus::Executing;
-        }
+        session.set_status(SessionStatus::Executing);
 
         // 准备执行输入
-        let execution_input = self.prepare_execution_input(request)?;
+        let inputs = self.session_inputs(session).await;
+        let execution_input = self.prepare_execution_input(&session.contract, request, &inputs)?;
 
-        // 在 VM 中执行
-        let mut vm_sessions = self.execution_sessions.write().await;
-        if let Some(stored_session) = vm_sessions.get_mut(&session.session_id) {
-            let result = stored_session.vm_instance.execute(&execution_input).await?;
+        // 在加载过代码与状态的同一个 VM 实例中执行，周期上限由请求的 gas 预算决定
+        let vm_instance = session_vm(session)?;
+        vm_instance.set_limits(self.vm_manager.limits_for_gas(request.gas_budget));
+        let result = vm_instance.execute(&execution_input).await?;
 
-            info!(
-                "🎯 Execution completed: success={}, gas_used={}",
-                result.success, result.gas_used
-            );
-
-            stored_session.status = if result.success {
-                SessionStatus::Completed
-            } else {
-                SessionStatus::Failed(result.error.clone().unwrap_or("Unknown error".to_string()))
-            };
+        info!(
+            "🎯 Execution completed: success={}, gas_used={}",
+            result.success, result.gas_used
+        );
+        if let Some(mut recording) = session.recording.take() {
+            recording.input = execution_input;
+            recording.result = result.clone();
+            recording.recorded_at = chrono::Utc::now().timestamp() as u64;
+            self.save_recording(&recording);
+        }
 
-            Ok(result)
-        } else {
-            Err(anyhow::anyhow!("Session not found: {}", session.session_id))
+        if !result.success {
+            session.set_status(SessionStatus::Failed(
+                result.error.clone().unwrap_or("Unknown error".to_string()),
+            ));
         }
+
+        Ok(result)
     }
 
     /// Step 5: 同步结果回主网 (真实实现)
@@ -416,45 +1235,72 @@ impl OffchainExecutionManager {
             return Ok(SyncResult {
                 modified_objects: vec![],
                 new_objects: vec![],
+                deleted_objects: vec![],
+                commits: vec![],
             });
         }
 
-        // 解析执行结果中的状态变更
-        let modified_objects = self
-            .extract_modified_objects(&execution_result.output)
-            .await?;
-        let new_objects = self
-            .extract_created_objects(&execution_result.output)
-            .await?;
+        // 解析执行结果中的状态变更，校验失败则本次执行失败
+        let inputs = self.session_inputs(session).await;
+        let ExecutionEffects {
+            modified: modified_objects,
+            created: new_objects,
+            deleted: deleted_objects,
+        } = parse_effects(&execution_result.output, &inputs)?;
 
         info!(
-            "📊 Found {} modified objects, {} new objects",
+            "📊 Found {} modified objects, {} new objects, {} deleted objects",
             modified_objects.len(),
-            new_objects.len()
+            new_objects.len(),
+            deleted_objects.len()
         );
 
-        // 真实的同步逻辑 - 发送交易到 Sui 测试网
-        for modified_obj in &modified_objects {
-            info!("🔄 Syncing modified object: {}", modified_obj.object_id);
+        // 所有变更打包为 PTB 原子提交，超限时按顺序切块并记录回写日志
+        let calls: Vec<(String, MoveCall)> = modified_objects
+            .iter()
+            .map(|obj| (obj.object_id.clone(), self.update_call(session, obj)))
+            .chain(
+                new_objects
+                    .iter()
+                    .map(|obj| (obj.object_type.clone(), self.create_call(session, obj))),
+            )
+            .chain(
+                deleted_objects
+                    .iter()
+                    .map(|obj| (obj.object_id.clone(), self.delete_call(session, obj))),
+            )
+            .collect();
+        let commits = if calls.is_empty() {
+            vec![]
+        } else {
+            self.commit_calls(session, calls).await?
+        };
 
-            // 根据修改类型构建相应的 Move 调用
-            let tx_result = self
-                .build_and_execute_update_transaction(session, modified_obj)
-                .await?;
-            info!(
-                "✅ Object {} updated via transaction: {}",
-                modified_obj.object_id, tx_result
-            );
+        // 回写成功的对象新版本原子写入状态存储
+        if let Some(state) = &self.state {
+            let changes = modified_objects
+                .iter()
+                .map(|object| {
+                    Ok(StateChange {
+                        object_id: object.object_id.clone(),
+                        version: object.old_version + 1,
+                        data: serde_json::to_vec(&object.new_content)?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            state.storage().apply_changes(&changes)?;
         }
-
-        for new_obj in &new_objects {
-            info!("✨ Syncing new object: {}", new_obj.object_type);
-
-            // 构建创建新对象的交易
-            let tx_result = self
-                .build_and_execute_create_transaction(session, new_obj)
-                .await?;
-            info!("✅ New object created via transaction: {}", tx_result);
+        if let Some(audit) = &self.audit {
+            for object in &modified_objects {
+                audit.record_event(AuditEvent::ObjectModified {
+                    object_id: object.object_id.clone(),
+                    session_id: session.session_id.clone(),
+                    old_version: object.old_version,
+                    fields_modified: object.changes.fields_modified.clone(),
+                    fields_added: object.changes.fields_added.clone(),
+                    fields_removed: object.changes.fields_removed.clone(),
+                });
+            }
         }
 
         info!(
@@ -465,80 +1311,355 @@ impl OffchainExecutionManager {
         Ok(SyncResult {
             modified_objects,
             new_objects,
+            deleted_objects,
+            commits,
         })
     }
 
+    /// 写入回写日志后逐块提交；全部提交后删除日志
+    async fn commit_calls(
+        &self,
+        session: &ExecutionSession,
+        calls: Vec<(String, MoveCall)>,
+    ) -> Result<Vec<SyncCommit>> {
+        let submitter = self
+            .submitter
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No PTB submitter configured for result sync"))?;
+
+        let leases = {
+            let locked = self.locked_objects.read().await;
+            session
+                .locked_objects
+                .iter()
+                .filter_map(|object_id| locked.get(object_id))
+                .map(|object| {
+                    let lease = object.lease();
+                    JournalLease {
+                        object_id: lease.object_id,
+                        version: lease.version,
+                        lock_digest: lease.digest,
+                        expires_at_ms: lease.expires_at_ms,
+                    }
+                })
+                .collect()
+        };
+        let mut record = SyncJournalRecord {
+            session_id: session.session_id.clone(),
+            leases,
+            chunks: split_calls(calls, &self.sync_config),
+        };
+        info!(
+            "📦 Syncing session {} in {} PTB(s)",
+            session.session_id,
+            record.chunks.len()
+        );
+
+        let journal = self.state.as_ref().map(|state| state.journal());
+        if let Some(journal) = &journal {
+            journal.begin(&record)?;
+        }
+        let commits = submit_journaled(journal.as_ref(), submitter.as_ref(), &mut record).await?;
+        if let Some(journal) = &journal {
+            journal.complete(&session.session_id)?;
+        }
+        Ok(commits)
+    }
+
+    /// 重启后继续未完成的回写：提交剩余的块，然后释放租约并删除日志，返回完成的会话数
+    pub async fn resume_pending_syncs(&self) -> Result<usize> {
+        let (Some(state), Some(submitter)) = (&self.state, &self.submitter) else {
+            return Ok(0);
+        };
+        let journal = state.journal();
+
+        let mut resumed = 0;
+        for mut record in journal.pending()? {
+            info!("♻️ Resuming result sync for session {}", record.session_id);
+            if let Err(e) = submit_journaled(Some(&journal), submitter.as_ref(), &mut record).await
+            {
+                warn!(
+                    "❌ Resumed sync for session {} failed: {}",
+                    record.session_id, e
+                );
+                continue;
+            }
+
+            let leases: Vec<ObjectLease> = record
+                .leases
+                .iter()
+                .map(|lease| ObjectLease {
+                    object_id: lease.object_id.clone(),
+                    version: lease.version,
+                    digest: lease.lock_digest.clone(),
+                    expires_at_ms: lease.expires_at_ms,
+                })
+                .collect();
+            {
+                let mut locked = self.locked_objects.write().await;
+                for lease in &leases {
+                    locked.remove(&lease.object_id);
+                }
+            }
+            if let Some(locker) = &self.locker {
+                release_all(locker.as_ref(), &leases).await;
+            }
+            journal.complete(&record.session_id)?;
+            resumed += 1;
+        }
+        Ok(resumed)
+    }
+
     /// Step 6: 释放主网对象锁
     async fn unlock_mainnet_objects(&self, object_ids: &[String]) -> Result<()> {
         info!("🔓 Unlocking {} objects on mainnet", object_ids.len());
 
-        for object_id in object_ids {
-            if let Some(_locked_object) = self.locked_objects.write().await.remove(object_id) {
-                info!("🔓 Unlocked object: {}", object_id);
-                // TODO: 调用 Sui API 释放对象锁
+        let leases: Vec<ObjectLease> = {
+            let mut locked = self.locked_objects.write().await;
+            object_ids
+                .iter()
+                .filter_map(|object_id| locked.remove(object_id))
+                .map(|locked_object| locked_object.lease())
+                .collect()
+        };
+        let Some(locker) = &self.locker else {
+            return Ok(());
+        };
+
+        let failed = release_all(locker.as_ref(), &leases).await;
+        if let Some(audit) = &self.audit {
+            for lease in &leases {
+                audit.record_event(AuditEvent::ObjectUnlocked {
+                    object_id: lease.object_id.clone(),
+                    lease: lease.digest.clone(),
+                });
             }
         }
-
+        if failed > 0 {
+            return Err(anyhow::anyhow!(
+                "Failed to release {} of {} object leases",
+                failed,
+                leases.len()
+            ));
+        }
         Ok(())
     }
 
-    // 辅助方法
-    async fn get_object_version(&self, object_id: &str) -> Result<u64> {
-        // 简化实现，实际需要查询 Sui 对象版本
-        Ok(1)
+    /// 释放所有仍处于锁定状态的对象（停机时调用），返回释放数量
+    pub async fn release_all_locks(&self) -> Result<usize> {
+        let object_ids: Vec<String> = self.locked_objects.read().await.keys().cloned().collect();
+        if object_ids.is_empty() {
+            return Ok(0);
+        }
+
+        warn!(
+            "🔓 Releasing {} objects still locked at shutdown",
+            object_ids.len()
+        );
+        self.unlock_mainnet_objects(&object_ids).await?;
+        Ok(object_ids.len())
     }
 
-    fn generate_lock_hash(&self, object_id: &str) -> String {
-        // 简化实现，实际需要生成加密哈希
-        format!("lock_{}_hash", object_id)
+    // 辅助方法
+
+    /// 会话的输入对象（锁定时的版本与内容）
+    async fn session_inputs(&self, session: &ExecutionSession) -> Vec<LockedObject> {
+        let locked = self.locked_objects.read().await;
+        session
+            .locked_objects
+            .iter()
+            .filter_map(|object_id| locked.get(object_id).cloned())
+            .collect()
     }
 
-    fn prepare_execution_input(&self, request: &ExecutionRequest) -> Result<Vec<u8>> {
-        // 将执行请求序列化为 VM 输入
+    /// 将执行请求编码为 VM 输入
+    ///
+    /// 编译产物带有入口函数元数据时按入口调用约定 BCS 编码参数，参数不符在此报错；
+    /// 否则序列化为 JSON，`objects` 告知 guest 各输入对象的版本，
+    /// 效果中的 `old_version` 必须与之一致
+    fn prepare_execution_input(
+        &self,
+        contract: &CompiledContract,
+        request: &ExecutionRequest,
+        inputs: &[LockedObject],
+    ) -> Result<Vec<u8>> {
+        if !contract.metadata.exports.is_empty() {
+            return Ok(contract.encode_entry_call(&request.function_name, &request.arguments)?);
+        }
+
+        let objects: Vec<serde_json::Value> = inputs
+            .iter()
+            .map(|object| {
+                serde_json::json!({
+                    "object_id": object.object_id,
+                    "version": object.version,
+                })
+            })
+            .collect();
         let input = serde_json::json!({
             "function": request.function_name,
             "arguments": request.arguments,
-            "gas_budget": request.gas_budget
+            "gas_budget": request.gas_budget,
+            "objects": objects,
         });
 
         Ok(input.to_string().as_bytes().to_vec())
     }
 
-    async fn extract_modified_objects(&self, output: &[u8]) -> Result<Vec<ModifiedObject>> {
-        // 从 VM 输出中解析修改的对象
-        // 这里是简化实现
-        Ok(vec![])
+    /// 关闭并移除会话，返回会话是否存在
+    pub async fn close_session(&self, session_id: &str) -> bool {
+        let removed = self.execution_sessions.write().await.remove(session_id);
+        if removed.is_some() {
+            info!("🗑️ Closed execution session: {}", session_id);
+        }
+        removed.is_some()
+    }
+
+    /// 清理超过保留期的已结束会话与超过 TTL 的卡死会话，返回清理数量
+    ///
+    /// 正在执行的会话持有自身的锁，跳过即可，下一轮再判断
+    pub async fn cleanup_sessions(&self, now: Instant) -> usize {
+        let mut sessions = self.execution_sessions.write().await;
+        let before = sessions.len();
+        sessions.retain(|_, handle| match handle.try_lock() {
+            Ok(session) => !session.is_expired(&self.session_config, now),
+            Err(_) => true,
+        });
+        let removed = before - sessions.len();
+        if removed > 0 {
+            info!("🗑️ Cleaned up {} expired execution sessions", removed);
+        }
+        removed
+    }
+
+    /// 周期性清理会话、空闲过期的池化 VM 实例与过期的执行录制
+    pub fn spawn_session_cleanup(self: &Arc<Self>) -> JoinHandle<()> {
+        let manager = self.clone();
+        let interval = Duration::from_secs(manager.session_config.cleanup_interval_secs.max(1));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                manager.cleanup_sessions(Instant::now()).await;
+                manager.vm_pool.evict_idle(Instant::now());
+                manager.prune_recordings(chrono::Utc::now().timestamp() as u64);
+            }
+        })
+    }
+
+    /// 录制模式已开启且配置了状态存储
+    fn recording_enabled(&self) -> bool {
+        self.replay_config.enabled && self.state.is_some()
+    }
+
+    /// 写入执行录制，失败只记录日志，不影响执行结果
+    fn save_recording(&self, recording: &ExecutionRecording) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        match state
+            .replays()
+            .record(recording, self.replay_config.max_recording_bytes)
+        {
+            Ok(true) => info!("🎞️ Recorded execution of session {}", recording.session_id),
+            Ok(false) => warn!(
+                "⚠️ Execution recording of session {} exceeds {} bytes, skipped",
+                recording.session_id, self.replay_config.max_recording_bytes
+            ),
+            Err(e) => warn!(
+                "⚠️ Failed to record execution of session {}: {}",
+                recording.session_id, e
+            ),
+        }
     }
 
-    async fn extract_created_objects(&self, output: &[u8]) -> Result<Vec<CreatedObject>> {
-        // 从 VM 输出中解析创建的对象
-        // 这里是简化实现
-        Ok(vec![])
+    /// 删除超过保留期的执行录制，返回删除数量
+    pub fn prune_recordings(&self, now_secs: u64) -> usize {
+        if !self.recording_enabled() {
+            return 0;
+        }
+        let Some(state) = &self.state else {
+            return 0;
+        };
+        let cutoff = now_secs.saturating_sub(self.replay_config.retention_secs);
+        match state.replays().prune(cutoff) {
+            Ok(removed) => {
+                if removed > 0 {
+                    info!("🗑️ Pruned {} expired execution recordings", removed);
+                }
+                removed
+            }
+            Err(e) => {
+                warn!("⚠️ Failed to prune execution recordings: {}", e);
+                0
+            }
+        }
+    }
+
+    /// 在新的 VM 实例中重放录制的会话，返回原始结果、重放结果与逐字段差异
+    pub async fn replay_transaction(&self, session_id: &str) -> Result<ReplayReport> {
+        let state = self
+            .state
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Replay requires a state manager"))?;
+        let recording = state
+            .replays()
+            .get(session_id)?
+            .ok_or_else(|| anyhow::anyhow!("No execution recording for session {}", session_id))?;
+
+        info!("🔁 Replaying session {}", session_id);
+        let host = Arc::new(ObjectStateHost::new());
+        let vm_instance = self
+            .vm_manager
+            .create_instance_with_host(Some(recording.vm_type), host.clone())?;
+        let report = replay_recording(
+            &recording,
+            vm_instance,
+            &host,
+            self.vm_manager.limits_for_gas(recording.gas_budget),
+        )
+        .await?;
+        if !report.identical {
+            warn!(
+                "⚠️ Replay of session {} diverged in {} fields",
+                session_id,
+                report.differences.len()
+            );
+        }
+        Ok(report)
     }
 
     /// 获取执行统计信息
     pub async fn get_execution_stats(&self) -> ExecutionStats {
+        let handles: Vec<SessionHandle> =
+            self.execution_sessions.read().await.values().cloned().collect();
+        let mut active_sessions = 0;
+        for handle in handles {
+            if !handle.lock().await.is_finished() {
+                active_sessions += 1;
+            }
+        }
         let locked_objects = self.locked_objects.read().await;
-        let sessions = self.execution_sessions.read().await;
         let pending = self.pending_executions.lock().await;
 
         ExecutionStats {
-            active_sessions: sessions.len(),
+            active_sessions,
             locked_objects: locked_objects.len(),
             pending_executions: pending.len(),
             total_gas_saved: 0, // TODO: 实现 gas 节省统计
+            hot_objects: self.hotspots.hot_objects().len(),
         }
     }
 
     // 真实状态同步的辅助方法
 
-    /// 准备对象的内存布局
+    /// 准备对象的内存布局（以对象 ID 为键的状态区域）
     fn prepare_object_memory_layout(
         &self,
         object_id: &str,
         bcs_data: &[u8],
         object_data: &serde_json::Value,
-    ) -> Result<Vec<u8>> {
+    ) -> Result<StateRegion> {
         info!("Preparing memory layout for object: {}", object_id);
 
         // 构建 VM 内存布局结构
@@ -557,118 +1678,68 @@ impl OffchainExecutionManager {
         });
 
         // 序列化为字节数组供 VM 使用
-        Ok(memory_layout.to_string().as_bytes().to_vec())
+        Ok(StateRegion::read_write(
+            object_id,
+            memory_layout.to_string().into_bytes(),
+        ))
     }
 
-    /// 构建并执行更新对象的交易
-    async fn build_and_execute_update_transaction(
-        &self,
-        session: &ExecutionSession,
-        modified_obj: &ModifiedObject,
-    ) -> Result<String> {
-        info!(
-            "Building update transaction for object: {}",
-            modified_obj.object_id
-        );
-
-        // 解析 package_id 和 module
-        let package_parts: Vec<&str> = session.package_id.split("::").collect();
-        let package_id = &session.package_id;
-        let module = "counter"; // 暂时硬编码，实际应该从 modified_obj 中解析
-        let function = "set_value"; // 根据修改的字段确定函数
-
-        // 构建参数 - 这里简化处理，实际需要根据修改内容动态构建
-        let arguments = vec![
-            serde_json::json!(modified_obj.object_id),
-            serde_json::json!(100), // 简化：设置为固定值
-        ];
-
-        // 获取当前用户地址 (简化实现)
-        let sender = "0x105b79ec1ee0a31c2faa544104f93b084f78cd8a9d9bb6a02654db21ac9fef8f"; // 使用测试地址
-
-        // 构建交易
-        let tx_data = self
-            .sui_adapter
-            .build_move_call_transaction(
-                sender,
-                package_id,
-                module,
-                function,
-                vec![], // type_arguments
-                arguments,
-                50000, // gas_budget
-            )
-            .await?;
-
-        // 执行干跑验证
-        let dry_run_result = self.sui_adapter.dry_run_transaction(&tx_data).await?;
-
-        if dry_run_result["effects"]["status"]["status"] != "success" {
-            return Err(anyhow::anyhow!(
-                "Dry run failed: {}",
-                dry_run_result["effects"]["status"]
-            ));
+    /// 更新对象的 Move 调用
+    fn update_call(&self, session: &ExecutionSession, modified_obj: &ModifiedObject) -> MoveCall {
+        MoveCall {
+            package: session.package_id.clone(),
+            module: "counter".to_string(), // 暂时硬编码，实际应该从 modified_obj 中解析
+            function: "set_value".to_string(), // 根据修改的字段确定函数
+            type_arguments: vec![],
+            // 构建参数 - 这里简化处理，实际需要根据修改内容动态构建
+            arguments: vec![
+                serde_json::json!(modified_obj.object_id),
+                serde_json::json!(100), // 简化：设置为固定值
+            ],
         }
-
-        info!("✅ Dry run successful for update transaction");
-
-        // 注意：这里返回干跑结果的哈希，实际需要签名后执行
-        // 为了演示目的，我们模拟一个交易哈希
-        let mut hasher = std::collections::hash_map::DefaultHasher::new();
-        format!("{:?}", tx_data).hash(&mut hasher);
-        let mock_tx_hash = format!("0x{:016x}", hasher.finish());
-
-        info!("✅ Mock transaction hash for update: {}", mock_tx_hash);
-        Ok(mock_tx_hash)
     }
 
-    /// 构建并执行创建对象的交易
-    async fn build_and_execute_create_transaction(
-        &self,
-        session: &ExecutionSession,
-        new_obj: &CreatedObject,
-    ) -> Result<String> {
-        info!(
-            "Building create transaction for object type: {}",
-            new_obj.object_type
-        );
-
-        let package_id = &session.package_id;
-        let module = "counter";
-        let function = "create";
-
-        let arguments = vec![];
-        let sender = "0x105b79ec1ee0a31c2faa544104f93b084f78cd8a9d9bb6a02654db21ac9fef8f";
-
-        let tx_data = self
-            .sui_adapter
-            .build_move_call_transaction(
-                sender,
-                package_id,
-                module,
-                function,
-                vec![],
-                arguments,
-                50000,
-            )
-            .await?;
-
-        let dry_run_result = self.sui_adapter.dry_run_transaction(&tx_data).await?;
+    /// 删除对象的 Move 调用
+    fn delete_call(&self, session: &ExecutionSession, deleted_obj: &DeletedObject) -> MoveCall {
+        MoveCall {
+            package: session.package_id.clone(),
+            module: "counter".to_string(),
+            function: "delete".to_string(),
+            type_arguments: vec![],
+            arguments: vec![serde_json::json!(deleted_obj.object_id)],
+        }
+    }
 
-        if dry_run_result["effects"]["status"]["status"] != "success" {
-            return Err(anyhow::anyhow!(
-                "Dry run failed: {}",
-                dry_run_result["effects"]["status"]
-            ));
+    /// 创建对象的 Move 调用
+    fn create_call(&self, session: &ExecutionSession, _new_obj: &CreatedObject) -> MoveCall {
+        MoveCall {
+            package: session.package_id.clone(),
+            module: "counter".to_string(),
+            function: "create".to_string(),
+            type_arguments: vec![],
+            arguments: vec![],
         }
+    }
+}
 
-        let mock_tx_hash = format!(
-            "0x{:016x}",
-            std::collections::hash_map::DefaultHasher::default().finish()
-        );
+/// 会话尚未归还实例池的 VM 实例
+fn session_vm(session: &mut ExecutionSession) -> Result<&mut PooledVm> {
+    session.vm_instance.as_mut().ok_or_else(|| {
+        anyhow::anyhow!(
+            "VM instance of session {} was already released",
+            session.session_id
+        )
+    })
+}
 
-        info!("✅ Mock transaction hash for create: {}", mock_tx_hash);
-        Ok(mock_tx_hash)
+/// 查询缓存键中的参数编码：有入口函数元数据时与 VM 输入相同的 BCS，否则为参数的 JSON
+fn query_arguments(contract: &CompiledContract, request: &ExecutionRequest) -> Option<Vec<u8>> {
+    if contract.metadata.exports.is_empty() {
+        serde_json::to_vec(&request.arguments).ok()
+    } else {
+        contract
+            .encode_entry_call(&request.function_name, &request.arguments)
+            .ok()
     }
 }
 
@@ -677,6 +1748,8 @@ impl OffchainExecutionManager {
 struct SyncResult {
     modified_objects: Vec<ModifiedObject>,
     new_objects: Vec<CreatedObject>,
+    deleted_objects: Vec<DeletedObject>,
+    commits: Vec<SyncCommit>,
 }
 
 /// 执行统计
@@ -686,15 +1759,602 @@ pub struct ExecutionStats {
     pub locked_objects: usize,
     pub pending_executions: usize,
     pub total_gas_saved: u64,
+    pub hot_objects: usize,
+}
+
+#[async_trait]
+impl CoalescedExecutor for OffchainExecutionManager {
+    async fn acquire_object_lock(&self, object_id: &str) -> Result<()> {
+        self.lock_mainnet_objects(&[object_id.to_string()]).await?;
+        Ok(())
+    }
+
+    async fn execute_locked(&self, request: &ExecutionRequest) -> Result<OffchainExecutionResult> {
+        let locked_objects = {
+            let locked = self.locked_objects.read().await;
+            request
+                .shared_objects
+                .iter()
+                .filter_map(|object_id| locked.get(object_id).cloned())
+                .collect::<Vec<_>>()
+        };
+        let progress = self.progress.session(&request.session_id);
+        progress.objects_locked(locked_objects.len());
+        self.run_locked_session(request, locked_objects, Instant::now(), &progress)
+            .await
+    }
+
+    async fn release_object_lock(&self, object_id: &str) -> Result<()> {
+        self.unlock_mainnet_objects(&[object_id.to_string()]).await
+    }
+}
+
+/// dubhe_executeOffchain 的执行后端，结果以 camelCase JSON 返回
+#[async_trait]
+impl OffchainHandler for OffchainExecutionManager {
+    async fn execute(
+        &self,
+        session_id: String,
+        params: OffchainExecutionParams,
+    ) -> Result<serde_json::Value> {
+        let result = self
+            .execute_offchain(ExecutionRequest {
+                session_id,
+                package_id: params.package_id,
+                function_name: params.function_name,
+                arguments: params.arguments,
+                shared_objects: params.shared_objects,
+                gas_budget: params.gas_budget,
+                read_only: params.read_only,
+            })
+            .await?;
+        Ok(serde_json::to_value(result)?)
+    }
+
+    async fn verify(&self, session_id: String) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(
+            self.verify_execution(&session_id).await?,
+        )?)
+    }
+
+    async fn query_cache_stats(&self) -> Result<serde_json::Value> {
+        match self.query_cache_stats() {
+            Some(stats) => Ok(serde_json::to_value(stats)?),
+            None => anyhow::bail!("Query cache is disabled"),
+        }
+    }
+
+    async fn replay(&self, session_id: String) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(
+            self.replay_transaction(&session_id).await?,
+        )?)
+    }
+
+    fn subscribe_progress(
+        &self,
+        session_id: &str,
+    ) -> Option<BoxStream<'static, serde_json::Value>> {
+        let events = self.progress.subscribe(session_id);
+        let stream = futures::stream::unfold(events, |mut events| async move {
+            loop {
+                match events.recv().await {
+                    Ok(progress) => match serde_json::to_value(progress) {
+                        Ok(value) => return Some((value, events)),
+                        Err(e) => warn!("Failed to serialize execution progress: {}", e),
+                    },
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        debug!("Progress subscriber lagged, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+        Some(stream.boxed())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use dubhe_adapter::{SuiConfig, SuiNetworkType};
+    use dubhe_security::SimulatedProvider;
+    use dubhe_vm_runtime::{ExecutionLimits, VmResult, VmSnapshot};
+
+    /// 不依赖具体 VM 后端的实例，执行即成功
+    struct StubVm;
+
+    #[async_trait]
+    impl VmInstance for StubVm {
+        async fn load_code(&mut self, _code: &[u8]) -> VmResult<()> {
+            Ok(())
+        }
+
+        async fn load_state(&mut self, _region: StateRegion) -> VmResult<()> {
+            Ok(())
+        }
+
+        async fn execute(&mut self, _input: &[u8]) -> VmResult<ExecutionResult> {
+            Ok(ExecutionResult {
+                success: true,
+                output: vec![],
+                gas_used: 42,
+                cycles_used: 0,
+                error: None,
+                events: vec![],
+            })
+        }
+
+        async fn snapshot(&self) -> VmResult<VmSnapshot> {
+            Ok(VmSnapshot {
+                data: vec![],
+                vm_type: VmType::CkbVM,
+            })
+        }
+
+        async fn restore(&mut self, _snapshot: &VmSnapshot) -> VmResult<()> {
+            Ok(())
+        }
+
+        fn vm_type(&self) -> VmType {
+            VmType::CkbVM
+        }
+
+        fn set_limits(&mut self, _limits: ExecutionLimits) {}
+    }
+
+    /// 输出为已加载状态与调用输入的拼接，结果只取决于录制的输入
+    #[derive(Default)]
+    struct EchoVm {
+        state: Vec<u8>,
+    }
+
+    #[async_trait]
+    impl VmInstance for EchoVm {
+        async fn load_code(&mut self, _code: &[u8]) -> VmResult<()> {
+            Ok(())
+        }
+
+        async fn load_state(&mut self, region: StateRegion) -> VmResult<()> {
+            self.state.extend_from_slice(&region.data);
+            Ok(())
+        }
+
+        async fn execute(&mut self, input: &[u8]) -> VmResult<ExecutionResult> {
+            let mut output = self.state.clone();
+            output.extend_from_slice(input);
+            Ok(ExecutionResult {
+                success: true,
+                gas_used: output.len() as u64,
+                cycles_used: 2 * output.len() as u64,
+                output,
+                error: None,
+                events: vec![],
+            })
+        }
+
+        async fn snapshot(&self) -> VmResult<VmSnapshot> {
+            Ok(VmSnapshot {
+                data: self.state.clone(),
+                vm_type: VmType::CkbVM,
+            })
+        }
+
+        async fn restore(&mut self, snapshot: &VmSnapshot) -> VmResult<()> {
+            self.state = snapshot.data.clone();
+            Ok(())
+        }
+
+        fn vm_type(&self) -> VmType {
+            VmType::CkbVM
+        }
+
+        fn set_limits(&mut self, _limits: ExecutionLimits) {}
+    }
+
+    /// 不带入口函数元数据的编译产物
+    fn placeholder_contract() -> CompiledContract {
+        CompiledContract {
+            original_address: "0xpkg".to_string(),
+            source_type: dubhe_adapter::ContractType::Move,
+            risc_v_code: vec![],
+            artifact: dubhe_loader::ArtifactKind::NativeRiscV,
+            entry_points: vec!["main".to_string()],
+            metadata: dubhe_loader::ContractMetadata {
+                gas_metering: true,
+                memory_limit: 0,
+                stack_limit: 0,
+                call_depth_limit: 0,
+                exports: HashMap::new(),
+                storage_access: None,
+            },
+            compiled_at: 0,
+        }
+    }
+
+    fn locked(object_id: &str, version: u64, content: serde_json::Value) -> LockedObject {
+        LockedObject {
+            object_id: object_id.to_string(),
+            object_type: "0xpkg::counter::Counter".to_string(),
+            version,
+            owner: "shared".to_string(),
+            content,
+            locked_at: 0,
+            lock_hash: "lock".to_string(),
+            lease_expires_at_ms: u64::MAX,
+        }
+    }
+
+    /// 按信封格式封装效果 JSON
+    fn envelope(json: &str) -> Vec<u8> {
+        let mut blob = EFFECTS_MAGIC.to_vec();
+        blob.extend_from_slice(&EFFECTS_FORMAT_VERSION.to_le_bytes());
+        blob.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        blob.extend_from_slice(json.as_bytes());
+        blob
+    }
+
+    #[test]
+    fn test_parse_effects_fixture() {
+        let inputs = vec![
+            locked("0xa", 7, serde_json::json!({"value": 1, "owner": "0x1"})),
+            locked("0xb", 3, serde_json::json!({})),
+        ];
+        // {"value":2,"label":"x"} / {"value":0}
+        let fixture = envelope(concat!(
+            r#"{"effects":["#,
+            r#"{"kind":"modified","object_id":"0xa","old_version":7,"content":"7b2276616c7565223a322c226c6162656c223a2278227d"},"#,
+            r#"{"kind":"created","object_type":"0xpkg::counter::Counter","owner":"0x2","content":"7b2276616c7565223a307d"},"#,
+            r#"{"kind":"deleted","object_id":"0xb","old_version":3}"#,
+            r#"]}"#
+        ));
+
+        let effects = parse_effects(&fixture, &inputs).unwrap();
+        assert_eq!(effects.modified.len(), 1);
+        let modified = &effects.modified[0];
+        assert_eq!(modified.object_id, "0xa");
+        assert_eq!(modified.old_version, 7);
+        assert_eq!(modified.new_content, serde_json::json!({"value": 2, "label": "x"}));
+        assert_eq!(modified.changes.fields_modified, vec!["value"]);
+        assert_eq!(modified.changes.fields_added, vec!["label"]);
+        assert_eq!(modified.changes.fields_removed, vec!["owner"]);
+
+        assert_eq!(effects.created.len(), 1);
+        assert_eq!(effects.created[0].object_type, "0xpkg::counter::Counter");
+        assert_eq!(effects.created[0].owner, "0x2");
+        assert_eq!(effects.created[0].content, serde_json::json!({"value": 0}));
+
+        assert_eq!(
+            effects.deleted,
+            vec![DeletedObject {
+                object_id: "0xb".to_string(),
+                old_version: 3,
+            }]
+        );
+
+        // 普通返回值不含效果
+        assert!(parse_effects(b"ok", &inputs).unwrap().modified.is_empty());
+    }
+
+    #[test]
+    fn test_parse_effects_rejects_invalid_entries() {
+        let inputs = vec![locked("0xa", 7, serde_json::json!({}))];
+        let parse = |json: &str| parse_effects(&envelope(json), &inputs).unwrap_err();
+
+        assert_eq!(
+            parse(r#"{"effects":[{"kind":"modified","object_id":"0xc","old_version":1,"content":""}]}"#),
+            EffectsError::UnknownObject("0xc".to_string())
+        );
+        assert!(matches!(
+            parse(r#"{"effects":[{"kind":"modified","object_id":"0xa","old_version":6,"content":""}]}"#),
+            EffectsError::VersionRegression { given: 7, reported: 6, .. }
+        ));
+        assert_eq!(
+            parse(concat!(
+                r#"{"effects":[{"kind":"modified","object_id":"0xa","old_version":7,"content":""},"#,
+                r#"{"kind":"deleted","object_id":"0xa","old_version":7}]}"#
+            )),
+            EffectsError::DuplicateEntry("0xa".to_string())
+        );
+        assert!(matches!(
+            parse(r#"{"effects":[{"kind":"created","content":""}]}"#),
+            EffectsError::MissingField { field: "object_type", .. }
+        ));
+
+        let mut truncated = envelope(r#"{"effects":[]}"#);
+        truncated.pop();
+        assert!(matches!(
+            parse_effects(&truncated, &inputs),
+            Err(EffectsError::Malformed(_))
+        ));
+    }
 
     #[tokio::test]
-    async fn test_offchain_execution_flow() -> Result<()> {
-        // 这里可以添加集成测试
+    async fn test_session_removed_after_retention() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let sui_adapter = Arc::new(
+            SuiAdapter::new(SuiConfig {
+                enabled: true,
+                rpc_url: "http://127.0.0.1:9000".to_string(),
+                ws_url: None,
+                network_type: SuiNetworkType::Localnet,
+                package_ids: vec![],
+                signer: None,
+                rpc_urls: vec![],
+                health_check: dubhe_adapter::HealthCheckConfig::default(),
+            })
+            .await?,
+        );
+        let manager = OffchainExecutionManager::new(
+            sui_adapter,
+            Arc::new(VmManager::new(VmType::CkbVM)),
+            Arc::new(CodeLoader::with_cache_dir(dir.path())?),
+        )
+        .await?
+        .with_session_config(SessionConfig {
+            ttl_secs: 600,
+            retention_secs: 30,
+            cleanup_interval_secs: 1,
+        });
+
+        let request = ExecutionRequest {
+            session_id: "session-1".to_string(),
+            package_id: "0xpkg".to_string(),
+            function_name: "increment".to_string(),
+            arguments: vec![],
+            shared_objects: vec![],
+            gas_budget: 1_000,
+            read_only: false,
+        };
+        let handle = Arc::new(Mutex::new(ExecutionSession {
+            session_id: request.session_id.clone(),
+            package_id: request.package_id.clone(),
+            locked_objects: vec![],
+            vm_instance: Some(PooledVm::detached(Box::new(StubVm))),
+            host: Arc::new(ObjectStateHost::new()),
+            contract: Arc::new(placeholder_contract()),
+            created_at: 0,
+            status: SessionStatus::ObjectsLocked,
+            updated_at: Instant::now(),
+            recording: None,
+        }));
+        manager
+            .execution_sessions
+            .write()
+            .await
+            .insert(request.session_id.clone(), handle.clone());
+        assert_eq!(manager.get_execution_stats().await.active_sessions, 1);
+
+        // 执行期间会话被锁住，清理任务跳过
+        {
+            let mut session = handle.lock().await;
+            assert_eq!(manager.cleanup_sessions(Instant::now()).await, 0);
+            let progress = manager.progress.session(&request.session_id);
+            let (result, _) = manager
+                .run_session_steps(&mut session, &request, &progress)
+                .await?;
+            assert_eq!(result.gas_used, 42);
+            assert!(matches!(session.status, SessionStatus::Completed));
+        }
+        let finished_at = handle.lock().await.updated_at;
+        assert_eq!(manager.get_execution_stats().await.active_sessions, 0);
+
+        // 保留期内仍可查询，之后被清理
+        assert_eq!(
+            manager
+                .cleanup_sessions(finished_at + Duration::from_secs(29))
+                .await,
+            0
+        );
+        assert_eq!(
+            manager
+                .cleanup_sessions(finished_at + Duration::from_secs(30))
+                .await,
+            1
+        );
+        assert!(manager.execution_sessions.read().await.is_empty());
+        assert!(!manager.close_session(&request.session_id).await);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_session_attestation_round_trip() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let sui_adapter = Arc::new(
+            SuiAdapter::new(SuiConfig {
+                enabled: true,
+                rpc_url: "http://127.0.0.1:9000".to_string(),
+                ws_url: None,
+                network_type: SuiNetworkType::Localnet,
+                package_ids: vec![],
+                signer: None,
+                rpc_urls: vec![],
+                health_check: dubhe_adapter::HealthCheckConfig::default(),
+            })
+            .await?,
+        );
+        let provider = Arc::new(SimulatedProvider::from_seed(
+            [3u8; 32],
+            SimulatedProvider::default_measurement(),
+        ));
+        let manager = OffchainExecutionManager::new(
+            sui_adapter,
+            Arc::new(VmManager::new(VmType::CkbVM)),
+            Arc::new(CodeLoader::with_cache_dir(dir.path())?),
+        )
+        .await?
+        .with_attestation(provider.clone());
+
+        let request = ExecutionRequest {
+            session_id: "session-1".to_string(),
+            package_id: "0xpkg".to_string(),
+            function_name: "increment".to_string(),
+            arguments: vec![serde_json::json!(1)],
+            shared_objects: vec!["0xa".to_string()],
+            gas_budget: 1_000,
+            read_only: false,
+        };
+        let mut execution_result = StubVm.execute(&[]).await?;
+        execution_result.output = vec![1, 2, 3];
+        let modified = vec![ModifiedObject {
+            object_id: "0xa".to_string(),
+            old_version: 7,
+            new_content: serde_json::json!({"value": 2}),
+            changes: diff_objects(
+                &serde_json::json!({"value": 1}),
+                &serde_json::json!({"value": 2}),
+            ),
+        }];
+
+        let report = manager
+            .attest(&request, &execution_result, &modified)?
+            .expect("attestation configured");
+        let user_data = attestation_user_data(&request, &execution_result.output, &modified)?;
+        assert_eq!(report.user_data, hex::encode(&user_data));
+        let verdict = provider.verify_report(&report);
+        assert!(verdict.valid, "{:?}", verdict.reason);
+        assert_eq!(
+            verdict.measurement,
+            hex::encode(SimulatedProvider::default_measurement())
+        );
+
+        // 输出不同则用户数据不同
+        assert_ne!(attestation_user_data(&request, &[9], &modified)?, user_data);
+
+        // 执行失败不出具证明
+        execution_result.success = false;
+        assert!(manager
+            .attest(&request, &execution_result, &modified)?
+            .is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recorded_session_replays_deterministically() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let state = Arc::new(StateManager::new(dir.path().join("state"))?);
+        let sui_adapter = Arc::new(
+            SuiAdapter::new(SuiConfig {
+                enabled: true,
+                rpc_url: "http://127.0.0.1:9000".to_string(),
+                ws_url: None,
+                network_type: SuiNetworkType::Localnet,
+                package_ids: vec![],
+                signer: None,
+                rpc_urls: vec![],
+                health_check: dubhe_adapter::HealthCheckConfig::default(),
+            })
+            .await?,
+        );
+        let manager = OffchainExecutionManager::new(
+            sui_adapter,
+            Arc::new(VmManager::new(VmType::CkbVM)),
+            Arc::new(CodeLoader::with_cache_dir(dir.path().join("cache"))?),
+        )
+        .await?
+        .with_state_manager(state.clone())
+        .with_replay_config(ReplayConfig {
+            enabled: true,
+            ..Default::default()
+        });
+
+        let request = ExecutionRequest {
+            session_id: "session-1".to_string(),
+            package_id: "0xpkg".to_string(),
+            function_name: "main".to_string(),
+            arguments: vec![],
+            shared_objects: vec![],
+            gas_budget: 1_000,
+            read_only: false,
+        };
+        // 模拟已同步的对象状态
+        let region = StateRegion::read_write("0xa", br#"{"value":1}"#.to_vec());
+        let mut vm_instance = EchoVm::default();
+        vm_instance.load_state(region.clone()).await?;
+        let mut session = ExecutionSession {
+            session_id: request.session_id.clone(),
+            package_id: request.package_id.clone(),
+            locked_objects: vec![],
+            vm_instance: Some(PooledVm::detached(Box::new(vm_instance))),
+            host: Arc::new(ObjectStateHost::new()),
+            contract: Arc::new(placeholder_contract()),
+            created_at: 0,
+            status: SessionStatus::ObjectsLocked,
+            updated_at: Instant::now(),
+            recording: Some(ExecutionRecording {
+                session_id: request.session_id.clone(),
+                package_id: request.package_id.clone(),
+                vm_type: VmType::CkbVM,
+                code_hash: code_hash(&[]),
+                code: vec![],
+                regions: vec![region],
+                objects: vec![],
+                input: vec![],
+                gas_budget: request.gas_budget,
+                result: ExecutionResult::default(),
+                recorded_at: 0,
+            }),
+        };
+        let progress = manager.progress.session(&request.session_id);
+        let (result, _) = manager
+            .run_session_steps(&mut session, &request, &progress)
+            .await?;
+        assert!(session.recording.is_none());
+
+        let mut recording = state.replays().get(&request.session_id)?.unwrap();
+        assert_eq!(recording.result.output, result.output);
+        assert_eq!(recording.gas_budget, 1_000);
+        assert!(recording.recorded_at > 0);
+
+        // 原样重放，结果一致
+        let host = ObjectStateHost::new();
+        let limits = ExecutionLimits::for_gas_budget(recording.gas_budget, Default::default());
+        let report = replay_recording(
+            &recording,
+            Box::new(EchoVm::default()),
+            &host,
+            limits.clone(),
+        )
+        .await?;
+        assert!(report.identical, "{:?}", report.differences);
+        assert_eq!(report.replayed.output, result.output);
+
+        // 篡改录制的状态，差异体现在输出上
+        recording.regions[0].data = br#"{"value":2}"#.to_vec();
+        let report = replay_recording(
+            &recording,
+            Box::new(EchoVm::default()),
+            &host,
+            limits.clone(),
+        )
+        .await?;
+        assert!(!report.identical);
+        let fields: Vec<&str> = report
+            .differences
+            .iter()
+            .map(|diff| diff.field.as_str())
+            .collect();
+        assert_eq!(fields, vec!["output"]);
+
+        // 代码被替换时拒绝重放
+        recording.code = vec![1];
+        assert!(
+            replay_recording(&recording, Box::new(EchoVm::default()), &host, limits)
+                .await
+                .is_err()
+        );
+
+        // 超过保留期的录制被清理
+        assert_eq!(manager.prune_recordings(recording.recorded_at), 0);
+        assert_eq!(
+            manager.prune_recordings(
+                recording.recorded_at + ReplayConfig::default().retention_secs + 1
+            ),
+            1
+        );
+        assert!(state.replays().get(&request.session_id)?.is_none());
+
         Ok(())
     }
 }
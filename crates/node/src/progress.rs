@@ -0,0 +1,185 @@
+//! 链下执行进度
+//!
+//! 一次链下执行依次经过加锁、状态同步、VM 执行、结果回写与释放，整体可能耗时数十秒。
+//! 每个会话一个广播通道，进入与离开每个阶段时推送一次累计进度；
+//! 会话结束（[`SessionProgress`] 被丢弃）时通道随之关闭，订阅方据此得知不会再有进度。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::broadcast;
+
+/// 每个会话最多缓冲的进度事件数
+const PROGRESS_CHANNEL_CAPACITY: usize = 64;
+
+/// 执行阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionPhase {
+    Locking,
+    SyncingState,
+    Executing,
+    SyncingResults,
+    Releasing,
+}
+
+/// 会话进度快照，计数均为会话开始以来的累计值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionProgress {
+    pub phase: ExecutionPhase,
+    /// 离开阶段时为该阶段耗时；刚进入阶段时为 `None`
+    pub duration_ms: Option<u64>,
+    pub objects_locked: usize,
+    pub bytes_synced: u64,
+    pub gas_used: u64,
+}
+
+type Channels = Arc<Mutex<HashMap<String, broadcast::Sender<ExecutionProgress>>>>;
+
+/// 各会话的进度通道
+#[derive(Default)]
+pub struct ProgressChannels {
+    channels: Channels,
+}
+
+impl ProgressChannels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 订阅会话进度；会话尚未开始时预先建立通道，开始后的进度不会遗漏
+    pub fn subscribe(&self, session_id: &str) -> broadcast::Receiver<ExecutionProgress> {
+        self.sender(session_id).subscribe()
+    }
+
+    /// 开始上报会话进度
+    pub fn session(&self, session_id: &str) -> SessionProgress {
+        SessionProgress {
+            channels: self.channels.clone(),
+            session_id: session_id.to_string(),
+            sender: self.sender(session_id),
+            state: Mutex::new(ProgressState::default()),
+        }
+    }
+
+    /// 当前持有通道的会话数
+    pub fn active_sessions(&self) -> usize {
+        self.channels.lock().unwrap().len()
+    }
+
+    fn sender(&self, session_id: &str) -> broadcast::Sender<ExecutionProgress> {
+        self.channels
+            .lock()
+            .unwrap()
+            .entry(session_id.to_string())
+            .or_insert_with(|| broadcast::channel(PROGRESS_CHANNEL_CAPACITY).0)
+            .clone()
+    }
+}
+
+#[derive(Default)]
+struct ProgressState {
+    phase: Option<(ExecutionPhase, Instant)>,
+    objects_locked: usize,
+    bytes_synced: u64,
+    gas_used: u64,
+}
+
+/// 单个会话的进度上报；丢弃时结束当前阶段并关闭通道
+pub struct SessionProgress {
+    channels: Channels,
+    session_id: String,
+    sender: broadcast::Sender<ExecutionProgress>,
+    state: Mutex<ProgressState>,
+}
+
+impl SessionProgress {
+    /// 结束当前阶段并进入下一阶段
+    pub fn enter(&self, phase: ExecutionPhase) {
+        let mut state = self.state.lock().unwrap();
+        self.finish_phase(&mut state);
+        state.phase = Some((phase, Instant::now()));
+        self.emit(&state, phase, None);
+    }
+
+    pub fn objects_locked(&self, count: usize) {
+        self.state.lock().unwrap().objects_locked = count;
+    }
+
+    pub fn add_bytes_synced(&self, bytes: u64) {
+        self.state.lock().unwrap().bytes_synced += bytes;
+    }
+
+    pub fn gas_used(&self, gas_used: u64) {
+        self.state.lock().unwrap().gas_used = gas_used;
+    }
+
+    fn finish_phase(&self, state: &mut ProgressState) {
+        if let Some((phase, started)) = state.phase.take() {
+            let duration_ms = started.elapsed().as_millis() as u64;
+            self.emit(state, phase, Some(duration_ms));
+        }
+    }
+
+    fn emit(&self, state: &ProgressState, phase: ExecutionPhase, duration_ms: Option<u64>) {
+        // 没有订阅者时发送失败，忽略即可
+        let _ = self.sender.send(ExecutionProgress {
+            phase,
+            duration_ms,
+            objects_locked: state.objects_locked,
+            bytes_synced: state.bytes_synced,
+            gas_used: state.gas_used,
+        });
+    }
+}
+
+impl Drop for SessionProgress {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        self.finish_phase(&mut state);
+
+        // 合批执行时同一会话可能有内外两层上报，只移除仍是本通道的条目
+        let mut channels = self.channels.lock().unwrap();
+        if channels
+            .get(&self.session_id)
+            .is_some_and(|sender| sender.same_channel(&self.sender))
+        {
+            channels.remove(&self.session_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_phase_events_and_close() {
+        let channels = ProgressChannels::new();
+        let mut events = channels.subscribe("s1");
+
+        let progress = channels.session("s1");
+        progress.enter(ExecutionPhase::Locking);
+        progress.objects_locked(2);
+        progress.enter(ExecutionPhase::SyncingState);
+        progress.add_bytes_synced(128);
+        drop(progress);
+        assert_eq!(channels.active_sessions(), 0);
+
+        let mut received = Vec::new();
+        while let Ok(event) = events.recv().await {
+            received.push((event.phase, event.duration_ms.is_some()));
+        }
+        assert_eq!(
+            received,
+            vec![
+                (ExecutionPhase::Locking, false),
+                (ExecutionPhase::Locking, true),
+                (ExecutionPhase::SyncingState, false),
+                (ExecutionPhase::SyncingState, true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,434 @@
+//! 只读查询结果缓存
+//!
+//! 大量流量是对很少变化的共享对象反复发起的只读 Move 调用（余额查询一类的视图函数）。
+//! 标记为 `read_only` 的请求先查本缓存，键为包 ID、函数、编码后的调用参数与各输入对象的版本；
+//! 未命中时照常执行，成功且没有任何对象效果的结果写入缓存。
+//!
+//! 输入对象的当前版本来自 Sui 检查点订阅（`SuiAdapter::subscribe_object_versions`）：
+//! 订阅报告某对象的新版本后，键中含该对象旧版本的条目立即失效，之后的查询按新版本组键，
+//! 必然未命中并重新执行。执行期间对象版本发生变化时结果不写入缓存。
+//! 订阅未运行时缓存不提供任何结果：看不到版本变化就无法判断结果是否过期
+
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use dubhe_adapter::sui_types::ObjectVersionUpdate;
+use dubhe_loader::CompiledContract;
+use dubhe_observability::MetricsCollector;
+
+use crate::offchain_execution::OffchainExecutionResult;
+
+/// 命中次数
+pub const QUERY_CACHE_HITS_COUNTER: &str = "dubhe_query_cache_hits_total";
+/// 未命中次数
+pub const QUERY_CACHE_MISSES_COUNTER: &str = "dubhe_query_cache_misses_total";
+/// 因对象版本变化而失效的条目数
+pub const QUERY_CACHE_INVALIDATIONS_COUNTER: &str = "dubhe_query_cache_invalidations_total";
+
+/// 跟踪版本的对象数超过缓存容量的该倍数时，清理不再被任何条目引用的对象
+const TRACKED_OBJECTS_FACTOR: usize = 4;
+
+/// 查询缓存配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryCacheConfig {
+    pub enabled: bool,
+    /// 缓存的结果数上限，超过时淘汰最久未使用的结果
+    pub max_entries: usize,
+}
+
+impl Default for QueryCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_entries: 10_000,
+        }
+    }
+}
+
+/// 缓存键
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QueryKey {
+    pub package_id: String,
+    pub function: String,
+    /// 与 VM 输入相同的参数编码：有入口函数元数据时为 BCS，否则为 JSON
+    pub arguments: Vec<u8>,
+    /// 按对象 ID 排序的（对象 ID，版本）
+    pub inputs: Vec<(String, u64)>,
+}
+
+impl QueryKey {
+    pub fn new(
+        package_id: &str,
+        function: &str,
+        arguments: Vec<u8>,
+        mut inputs: Vec<(String, u64)>,
+    ) -> Self {
+        inputs.sort();
+        inputs.dedup();
+        Self {
+            package_id: package_id.to_string(),
+            function: function.to_string(),
+            arguments,
+            inputs,
+        }
+    }
+}
+
+/// dubhe_queryCacheStats 的返回值
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub invalidations: u64,
+    pub entries: usize,
+    pub tracked_objects: usize,
+    /// 对象版本订阅是否在运行；未运行时缓存不提供结果
+    pub following: bool,
+}
+
+struct CacheState {
+    entries: LruCache<QueryKey, OffchainExecutionResult>,
+    /// 被查询过的对象及其最新已知版本，`None` 表示尚未见到版本
+    versions: HashMap<String, Option<u64>>,
+    /// 对象 → 以它为输入的键
+    by_object: HashMap<String, HashSet<QueryKey>>,
+    /// 包 ID → 编译产物，用于在执行前编码参数；Sui 包不可变，无需失效
+    contracts: HashMap<String, Arc<CompiledContract>>,
+    max_tracked: usize,
+}
+
+impl CacheState {
+    fn unlink(&mut self, key: &QueryKey) {
+        for (object_id, _) in &key.inputs {
+            if let Some(keys) = self.by_object.get_mut(object_id) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    self.by_object.remove(object_id);
+                }
+            }
+        }
+    }
+}
+
+/// 按输入对象版本失效的只读查询结果缓存
+pub struct QueryCache {
+    state: Mutex<CacheState>,
+    following: AtomicBool,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+    metrics: Arc<MetricsCollector>,
+}
+
+impl QueryCache {
+    pub fn new(config: &QueryCacheConfig, metrics: Arc<MetricsCollector>) -> Self {
+        let capacity = NonZeroUsize::new(config.max_entries.max(1)).unwrap();
+        Self {
+            state: Mutex::new(CacheState {
+                entries: LruCache::new(capacity),
+                versions: HashMap::new(),
+                by_object: HashMap::new(),
+                contracts: HashMap::new(),
+                max_tracked: capacity.get() * TRACKED_OBJECTS_FACTOR,
+            }),
+            following: AtomicBool::new(false),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+            metrics,
+        }
+    }
+
+    /// 开始跟踪对象的版本，须在执行前调用，执行期间的版本变化才能使结果不被写入
+    pub fn watch(&self, object_ids: &[String]) {
+        let mut state = self.state.lock().unwrap();
+        if state.versions.len() + object_ids.len() > state.max_tracked {
+            let CacheState {
+                versions,
+                by_object,
+                ..
+            } = &mut *state;
+            versions.retain(|object_id, _| by_object.contains_key(object_id));
+        }
+        for object_id in object_ids {
+            state.versions.entry(object_id.clone()).or_insert(None);
+        }
+    }
+
+    /// 按最新已知版本组键；订阅未运行或有输入对象版本未知时返回 `None`
+    pub fn key(
+        &self,
+        package_id: &str,
+        function: &str,
+        arguments: Vec<u8>,
+        object_ids: &[String],
+    ) -> Option<QueryKey> {
+        if !self.is_following() {
+            return None;
+        }
+        let state = self.state.lock().unwrap();
+        let inputs = object_ids
+            .iter()
+            .map(|object_id| Some((object_id.clone(), (*state.versions.get(object_id)?)?)))
+            .collect::<Option<Vec<_>>>()?;
+        Some(QueryKey::new(package_id, function, arguments, inputs))
+    }
+
+    /// 查询缓存，`key` 为空时计为未命中
+    pub fn get(&self, key: Option<&QueryKey>) -> Option<OffchainExecutionResult> {
+        let cached = key.and_then(|key| self.state.lock().unwrap().entries.get(key).cloned());
+        if cached.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            self.metrics.inc_counter(QUERY_CACHE_HITS_COUNTER, 1);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            self.metrics.inc_counter(QUERY_CACHE_MISSES_COUNTER, 1);
+        }
+        cached
+    }
+
+    /// 写入结果，返回是否写入
+    ///
+    /// 输入对象未被跟踪，或已知版本比键中的更新（执行期间对象已变化）时丢弃
+    pub fn insert(&self, key: QueryKey, result: OffchainExecutionResult) -> bool {
+        if !self.is_following() {
+            return false;
+        }
+        let mut state = self.state.lock().unwrap();
+        for (object_id, version) in &key.inputs {
+            match state.versions.get(object_id) {
+                None => return false,
+                Some(Some(known)) if known > version => return false,
+                _ => {}
+            }
+        }
+        for (object_id, version) in &key.inputs {
+            state.versions.insert(object_id.clone(), Some(*version));
+            state
+                .by_object
+                .entry(object_id.clone())
+                .or_default()
+                .insert(key.clone());
+        }
+        if let Some((evicted, _)) = state.entries.push(key.clone(), result) {
+            if evicted != key {
+                state.unlink(&evicted);
+            }
+        }
+        true
+    }
+
+    /// 应用订阅报告的新版本：使键中含该对象旧版本的条目失效，返回失效条目数
+    pub fn apply_version(&self, object_id: &str, version: u64) -> usize {
+        let mut state = self.state.lock().unwrap();
+        match state.versions.get_mut(object_id) {
+            // 未被查询过的对象无需跟踪
+            None => return 0,
+            Some(Some(known)) if *known >= version => return 0,
+            Some(known) => *known = Some(version),
+        }
+
+        let keys = state.by_object.remove(object_id).unwrap_or_default();
+        let mut removed = 0;
+        for key in keys {
+            if state.entries.pop(&key).is_some() {
+                removed += 1;
+            }
+            state.unlink(&key);
+        }
+        if removed > 0 {
+            debug!(
+                "Object {} moved to version {}, invalidated {} cached queries",
+                object_id, version, removed
+            );
+            self.invalidations
+                .fetch_add(removed as u64, Ordering::Relaxed);
+            self.metrics
+                .inc_counter(QUERY_CACHE_INVALIDATIONS_COUNTER, removed as u64);
+        }
+        removed
+    }
+
+    /// 由对象版本订阅驱动失效；订阅结束后清空缓存并停止提供结果
+    pub fn follow(
+        self: &Arc<Self>,
+        mut updates: mpsc::Receiver<ObjectVersionUpdate>,
+    ) -> JoinHandle<()> {
+        self.following.store(true, Ordering::SeqCst);
+        info!("🧮 Query cache following object version updates");
+        let cache = self.clone();
+        tokio::spawn(async move {
+            while let Some(update) = updates.recv().await {
+                cache.apply_version(&update.object_id, update.version);
+            }
+            warn!("⚠️ Object version subscription ended, query cache disabled");
+            cache.following.store(false, Ordering::SeqCst);
+            cache.clear();
+        })
+    }
+
+    pub fn is_following(&self) -> bool {
+        self.following.load(Ordering::SeqCst)
+    }
+
+    /// 清空所有结果与版本
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.clear();
+        state.versions.clear();
+        state.by_object.clear();
+    }
+
+    /// 记住包的编译产物，之后的查询可在执行前编码参数
+    pub fn remember_contract(&self, package_id: &str, contract: Arc<CompiledContract>) {
+        self.state
+            .lock()
+            .unwrap()
+            .contracts
+            .insert(package_id.to_string(), contract);
+    }
+
+    pub fn contract(&self, package_id: &str) -> Option<Arc<CompiledContract>> {
+        self.state
+            .lock()
+            .unwrap()
+            .contracts
+            .get(package_id)
+            .cloned()
+    }
+
+    pub fn stats(&self) -> QueryCacheStats {
+        let state = self.state.lock().unwrap();
+        QueryCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+            entries: state.entries.len(),
+            tracked_objects: state.versions.len(),
+            following: self.is_following(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn balance(value: u64) -> OffchainExecutionResult {
+        OffchainExecutionResult {
+            session_id: format!("balance-{}", value),
+            success: true,
+            gas_used: value,
+            modified_objects: vec![],
+            new_objects: vec![],
+            deleted_objects: vec![],
+            commits: vec![],
+            error: None,
+            execution_time_ms: 5,
+            attestation: None,
+            inputs_hash: String::new(),
+            effects_digest: None,
+            sync_transactions: vec![],
+        }
+    }
+
+    fn key(cache: &QueryCache) -> Option<QueryKey> {
+        cache.key(
+            "0xpkg",
+            "get_balance",
+            b"[\"0x1\"]".to_vec(),
+            &["0xa".to_string()],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_version_bump_forces_recompute() {
+        let cache = Arc::new(QueryCache::new(
+            &QueryCacheConfig::default(),
+            Arc::new(MetricsCollector::new()),
+        ));
+        let (updates, receiver) = mpsc::channel(8);
+        let follower = cache.follow(receiver);
+
+        // 首次查询：版本未知，未命中；执行时锁定的版本为 3
+        cache.watch(&["0xa".to_string()]);
+        assert!(cache.get(key(&cache).as_ref()).is_none());
+        let executed_at_3 = QueryKey::new(
+            "0xpkg",
+            "get_balance",
+            b"[\"0x1\"]".to_vec(),
+            vec![("0xa".to_string(), 3)],
+        );
+        assert!(cache.insert(executed_at_3.clone(), balance(100)));
+        assert_eq!(key(&cache), Some(executed_at_3.clone()));
+        assert_eq!(cache.get(key(&cache).as_ref()).unwrap().gas_used, 100);
+
+        // 未被查询过的对象不跟踪；0xa 升到版本 4
+        updates
+            .send(ObjectVersionUpdate {
+                object_id: "0xb".to_string(),
+                version: 9,
+                checkpoint: 10,
+            })
+            .await
+            .unwrap();
+        updates
+            .send(ObjectVersionUpdate {
+                object_id: "0xa".to_string(),
+                version: 4,
+                checkpoint: 11,
+            })
+            .await
+            .unwrap();
+        while cache.stats().invalidations == 0 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        // 版本变化后下一次查询必须未命中并重新计算
+        let next = key(&cache).unwrap();
+        assert_eq!(next.inputs, vec![("0xa".to_string(), 4)]);
+        assert!(cache.get(Some(&next)).is_none());
+        // 版本 3 上执行的结果迟到时被丢弃
+        assert!(!cache.insert(executed_at_3, balance(100)));
+        assert!(cache.insert(next.clone(), balance(250)));
+        assert_eq!(cache.get(Some(&next)).unwrap().gas_used, 250);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.invalidations), (2, 2, 1));
+        assert_eq!(stats.entries, 1);
+
+        // 订阅结束后缓存停用
+        drop(updates);
+        follower.await.unwrap();
+        assert!(!cache.stats().following);
+        assert_eq!(key(&cache), None);
+        assert!(!cache.insert(next, balance(250)));
+    }
+
+    #[test]
+    fn test_unwatched_or_unfollowed_inputs_are_not_cached() {
+        let cache = QueryCache::new(
+            &QueryCacheConfig::default(),
+            Arc::new(MetricsCollector::new()),
+        );
+        let entry = QueryKey::new("0xpkg", "get_balance", vec![], vec![("0xa".to_string(), 1)]);
+        // 没有订阅时无法判断是否过期
+        assert!(!cache.insert(entry.clone(), balance(1)));
+
+        cache.following.store(true, Ordering::SeqCst);
+        assert!(!cache.insert(entry.clone(), balance(1)));
+        cache.watch(&["0xa".to_string()]);
+        assert!(cache.insert(entry, balance(1)));
+        assert_eq!(cache.stats().entries, 1);
+    }
+}
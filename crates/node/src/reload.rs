@@ -0,0 +1,201 @@
+//! 配置热加载
+//!
+//! SIGHUP 或 `dubhe_reloadConfig` 触发时重新读取配置文件，只应用可在运行时生效的配置段：
+//! 调度器 batch_size / timeout_ms、API 限流参数和告警规则。监听地址等字段发生变化时
+//! 拒绝并记录日志，需要重启节点才能生效；其余字段的变化在下次启动时生效。
+//! 同一管理接口还转发 `dubhe_createSnapshot`（见 [`crate::snapshot`]）。
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use dubhe_api::{AdminHandler, Authenticator, ConfigReloadReport, SnapshotReport};
+use dubhe_observability::AlertManager;
+use dubhe_scheduler::ParallelScheduler;
+
+use crate::config::NodeConfig;
+use crate::snapshot::{resolve_snapshot_path, Snapshotter};
+
+/// 持有当前生效的配置与可热更新的组件
+pub struct ConfigReloader {
+    config_path: Option<PathBuf>,
+    current: Mutex<NodeConfig>,
+    scheduler: Arc<ParallelScheduler>,
+    auth: Arc<Authenticator>,
+    alerts: Arc<Mutex<AlertManager>>,
+    snapshotter: Option<Arc<Snapshotter>>,
+}
+
+impl ConfigReloader {
+    pub fn new(
+        config: NodeConfig,
+        config_path: Option<PathBuf>,
+        scheduler: Arc<ParallelScheduler>,
+        auth: Arc<Authenticator>,
+        alerts: Arc<Mutex<AlertManager>>,
+    ) -> Self {
+        Self {
+            config_path,
+            current: Mutex::new(config),
+            scheduler,
+            auth,
+            alerts,
+            snapshotter: None,
+        }
+    }
+
+    /// 启用 dubhe_createSnapshot
+    pub fn with_snapshotter(mut self, snapshotter: Arc<Snapshotter>) -> Self {
+        self.snapshotter = Some(snapshotter);
+        self
+    }
+
+    fn load(&self) -> Result<(NodeConfig, &PathBuf)> {
+        let path = self
+            .config_path
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Node was not started from a config file"))?;
+        Ok((NodeConfig::load(path)?, path))
+    }
+
+    /// 应用新配置中可在运行时生效的部分
+    pub async fn apply(&self, next: NodeConfig) -> ConfigReloadReport {
+        let mut current = self.current.lock().await;
+        let mut report = ConfigReloadReport::default();
+
+        for field in current.immutable_changes(&next) {
+            warn!(
+                "⛔ Config field {} cannot change at runtime, restart the node to apply it",
+                field
+            );
+            report.rejected.push(field);
+        }
+
+        let scheduler = &next.scheduler;
+        if scheduler.batch_size != current.scheduler.batch_size
+            || scheduler.timeout_ms != current.scheduler.timeout_ms
+        {
+            self.scheduler
+                .update_limits(scheduler.batch_size, scheduler.timeout_ms);
+            if scheduler.batch_size != current.scheduler.batch_size {
+                report.applied.push("scheduler.batch_size".to_string());
+            }
+            if scheduler.timeout_ms != current.scheduler.timeout_ms {
+                report.applied.push("scheduler.timeout_ms".to_string());
+            }
+            current.scheduler.batch_size = scheduler.batch_size;
+            current.scheduler.timeout_ms = scheduler.timeout_ms;
+        }
+
+        if !same(&next.api.auth.rate_limits, &current.api.auth.rate_limits) {
+            self.auth
+                .update_rate_limits(next.api.auth.rate_limits.clone());
+            report.applied.push("api.auth.rate_limits".to_string());
+            current.api.auth.rate_limits = next.api.auth.rate_limits.clone();
+        }
+
+        if !same(&next.alerting.rules, &current.alerting.rules) {
+            self.alerts
+                .lock()
+                .await
+                .replace_rules(next.alerting.rules.clone());
+            report.applied.push("alerting.rules".to_string());
+            current.alerting.rules = next.alerting.rules;
+        }
+
+        info!(
+            "🔄 Config reloaded: {} applied, {} rejected",
+            report.applied.len(),
+            report.rejected.len()
+        );
+        report
+    }
+}
+
+#[async_trait]
+impl AdminHandler for ConfigReloader {
+    async fn reload_alert_rules(&self) -> Result<usize> {
+        let (config, path) = self.load()?;
+        let rules = config.alerting.rules;
+        let count = rules.len();
+        self.alerts.lock().await.replace_rules(rules.clone());
+        self.current.lock().await.alerting.rules = rules;
+        info!("🚨 Reloaded {} alert rules from {}", count, path.display());
+        Ok(count)
+    }
+
+    async fn reload_config(&self) -> Result<ConfigReloadReport> {
+        let (config, path) = self.load()?;
+        info!("🔄 Reloading configuration from {}", path.display());
+        Ok(self.apply(config).await)
+    }
+
+    async fn create_snapshot(&self, out: String, base: Option<String>) -> Result<SnapshotReport> {
+        let snapshotter = self
+            .snapshotter
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Snapshots are not enabled on this node"))?;
+        // 调用方只能指定快照根目录下的相对路径
+        let root = self.current.lock().await.node.snapshot_root();
+        let out = resolve_snapshot_path(&root, &out)?;
+        let base = base
+            .map(|base| resolve_snapshot_path(&root, &base))
+            .transpose()?;
+        // 检查点与哈希计算都是阻塞 IO
+        tokio::task::spawn_blocking(move || snapshotter.create(&out, base.as_deref())).await?
+    }
+}
+
+/// 配置段没有实现 PartialEq，按序列化结果比较
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dubhe_api::AuthConfig;
+    use dubhe_scheduler::StrategyType;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_reload_applies_scheduler_limits_and_rejects_binds() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("node.toml");
+        let config = NodeConfig::default();
+        config.save(&path)?;
+
+        let scheduler = Arc::new(ParallelScheduler::new(
+            StrategyType::Sequential,
+            config.scheduler.clone(),
+        )?);
+        let reloader = ConfigReloader::new(
+            config.clone(),
+            Some(path.clone()),
+            scheduler.clone(),
+            Arc::new(Authenticator::new(&AuthConfig::default())),
+            Arc::new(Mutex::new(AlertManager::new())),
+        );
+
+        let mut edited = config.clone();
+        edited.scheduler.batch_size = 7;
+        edited.api.rpc_bind = "0.0.0.0:18545".to_string();
+        edited.save(&path)?;
+
+        let report = reloader.reload_config().await?;
+        assert_eq!(report.applied, vec!["scheduler.batch_size"]);
+        assert_eq!(report.rejected, vec!["api.rpc_bind"]);
+        assert_eq!(scheduler.config().batch_size, 7);
+        assert_eq!(scheduler.config().timeout_ms, config.scheduler.timeout_ms);
+
+        // 被拒绝的字段仍按原值比较，再次加载时继续提示
+        let report = reloader.reload_config().await?;
+        assert!(report.applied.is_empty());
+        assert_eq!(report.rejected, vec!["api.rpc_bind"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+//! 确定性重放
+//!
+//! 录制模式下链下执行会话的输入保存在状态存储中（见 [`dubhe_state::ReplayStore`]），
+//! `dubhe_replayTransaction` 在新的 VM 实例中按录制重新执行，逐字段比对原始结果与重放结果，
+//! 用于排查非确定性执行
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use dubhe_state::ExecutionRecording;
+use dubhe_vm_runtime::{ExecutionLimits, ExecutionResult, VmInstance};
+
+use crate::object_host::ObjectStateHost;
+
+/// 录制配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplayConfig {
+    /// 是否录制链下执行会话
+    pub enabled: bool,
+    /// 单条录制序列化后的大小上限（字节），超过时不保存
+    pub max_recording_bytes: usize,
+    /// 录制保留时间（秒）
+    pub retention_secs: u64,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_recording_bytes: 4 * 1024 * 1024,
+            retention_secs: 86_400,
+        }
+    }
+}
+
+/// 结果中不一致的字段
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub original: Value,
+    pub replayed: Value,
+}
+
+/// 一次重放的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayReport {
+    pub session_id: String,
+    pub identical: bool,
+    pub original: ExecutionResult,
+    pub replayed: ExecutionResult,
+    pub differences: Vec<FieldDiff>,
+}
+
+/// 代码哈希，录制与重放时一致
+pub fn code_hash(code: &[u8]) -> String {
+    hex::encode(Sha256::digest(code))
+}
+
+/// 逐字段比对两次执行结果
+pub fn diff_results(
+    original: &ExecutionResult,
+    replayed: &ExecutionResult,
+) -> Result<Vec<FieldDiff>> {
+    let (Value::Object(original), Value::Object(mut replayed)) = (
+        serde_json::to_value(original)?,
+        serde_json::to_value(replayed)?,
+    ) else {
+        anyhow::bail!("Execution result is not a JSON object");
+    };
+    Ok(original
+        .into_iter()
+        .filter_map(|(field, original)| {
+            let replayed = replayed.remove(&field).unwrap_or(Value::Null);
+            (original != replayed).then_some(FieldDiff {
+                field,
+                original,
+                replayed,
+            })
+        })
+        .collect())
+}
+
+/// 在新的 VM 实例中按录制重新执行
+///
+/// `vm_instance` 须以 `host` 为宿主函数创建；录制的状态区域与对象按原顺序加载
+pub async fn replay_recording(
+    recording: &ExecutionRecording,
+    mut vm_instance: Box<dyn VmInstance + Send + Sync>,
+    host: &ObjectStateHost,
+    limits: ExecutionLimits,
+) -> Result<ReplayReport> {
+    let hash = code_hash(&recording.code);
+    if hash != recording.code_hash {
+        anyhow::bail!(
+            "Recorded code hash {} does not match code {}",
+            recording.code_hash,
+            hash
+        );
+    }
+
+    vm_instance.load_code(&recording.code).await?;
+    for region in &recording.regions {
+        vm_instance.load_state(region.clone()).await?;
+    }
+    for object in &recording.objects {
+        host.load_object(&object.object_id, &object.bcs_data, &object.object_data);
+    }
+    vm_instance.set_limits(limits);
+    let replayed = vm_instance.execute(&recording.input).await?;
+
+    let differences = diff_results(&recording.result, &replayed)?;
+    Ok(ReplayReport {
+        session_id: recording.session_id.clone(),
+        identical: differences.is_empty(),
+        original: recording.result.clone(),
+        replayed,
+        differences,
+    })
+}
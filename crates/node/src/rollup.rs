@@ -0,0 +1,552 @@
+//! 最小 rollup 流程
+//!
+//! 把各模块串成一条完整管线，供端到端示例和集成测试复用：
+//! mock 开发链 → 部署包 → 调度器规划 → CKB-VM 执行 → 状态 syscall 层 → 封装伪区块 → 回写开发链
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use dubhe_adapter::{ChainAdapter, ContractMeta};
+use dubhe_loader::{CodeLoader, CompiledContract};
+use dubhe_scheduler::{
+    BatchResult, ExecutionPlan, ExecutionStats, ParallelScheduler, SchedulerConfig, StrategyType,
+    Transaction, TransactionResult,
+};
+use dubhe_security::canonical_payload;
+use dubhe_security::{canonical_digest, CanonicalPayload};
+use dubhe_vm_runtime::{VmManager, VmType};
+
+use crate::devnet::{ChainObject, DevChain};
+
+/// 状态根的摘要域
+const STATE_ROOT_DOMAIN: &str = "dubhe.node.state_root";
+
+/// 合约通过 syscall 对状态层发起的读写
+///
+/// 交易数据与 VM 输出均为 JSON 编码的 syscall 列表
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum StateSyscall {
+    /// 数值字段累加
+    Increment {
+        object_id: String,
+        field: String,
+        amount: u64,
+    },
+    /// 字段赋值
+    Store {
+        object_id: String,
+        field: String,
+        value: serde_json::Value,
+    },
+}
+
+impl StateSyscall {
+    pub fn object_id(&self) -> &str {
+        match self {
+            StateSyscall::Increment { object_id, .. } | StateSyscall::Store { object_id, .. } => {
+                object_id
+            }
+        }
+    }
+}
+
+/// 链下状态层
+///
+/// 缓存从开发链拉取的对象，记录自上次回写以来被修改的对象
+#[derive(Debug, Default)]
+pub struct RollupState {
+    objects: BTreeMap<String, ChainObject>,
+    dirty: BTreeSet<String>,
+}
+
+impl RollupState {
+    pub fn object(&self, object_id: &str) -> Option<&ChainObject> {
+        self.objects.get(object_id)
+    }
+
+    /// 原子地应用一笔交易的全部 syscall：任一失败则不修改状态
+    pub fn apply(&mut self, write_set: &[String], syscalls: &[StateSyscall]) -> Result<()> {
+        let mut staged: HashMap<String, serde_json::Value> = HashMap::new();
+
+        for syscall in syscalls {
+            let object_id = syscall.object_id();
+            if !write_set.iter().any(|id| id == object_id) {
+                return Err(anyhow!("Object {} is not in the write set", object_id));
+            }
+
+            let content = match staged.get_mut(object_id) {
+                Some(content) => content,
+                None => {
+                    let current = self
+                        .objects
+                        .get(object_id)
+                        .ok_or_else(|| anyhow!("Object {} not loaded", object_id))?;
+                    staged
+                        .entry(object_id.to_string())
+                        .or_insert_with(|| current.content.clone())
+                }
+            };
+            // 按字段写入只对 JSON 对象有效，其余类型直接索引赋值会 panic
+            if !content.is_object() {
+                return Err(anyhow!("Object {} content is not a JSON object", object_id));
+            }
+
+            match syscall {
+                StateSyscall::Increment { field, amount, .. } => {
+                    let value = content.get(field).and_then(|v| v.as_u64()).unwrap_or(0);
+                    let next = value
+                        .checked_add(*amount)
+                        .ok_or_else(|| anyhow!("Overflow incrementing {}.{}", object_id, field))?;
+                    content[field.as_str()] = serde_json::json!(next);
+                }
+                StateSyscall::Store { field, value, .. } => {
+                    content[field.as_str()] = value.clone();
+                }
+            }
+        }
+
+        for (object_id, content) in staged {
+            if let Some(object) = self.objects.get_mut(&object_id) {
+                object.content = content;
+            }
+            self.dirty.insert(object_id);
+        }
+
+        Ok(())
+    }
+
+    /// 当前状态根
+    pub fn state_root(&self) -> Result<String> {
+        Ok(canonical_digest(STATE_ROOT_DOMAIN, &self.objects)?.to_string())
+    }
+}
+
+/// 伪区块
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PseudoBlock {
+    pub number: u64,
+    pub parent_hash: String,
+    pub batch_hash: String,
+    pub state_root: String,
+    pub tx_count: usize,
+    pub successful_txs: usize,
+    pub gas_used: u64,
+}
+
+canonical_payload!(PseudoBlock, "dubhe.node.pseudo_block");
+
+/// 封装后的区块及批次结果
+#[derive(Debug, Clone)]
+pub struct SealedBlock {
+    pub block: PseudoBlock,
+    pub block_hash: String,
+    pub batch: BatchResult,
+}
+
+/// 回写结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub tx_hash: Option<String>,
+    pub objects_updated: usize,
+}
+
+/// 管线配置
+#[derive(Debug, Clone)]
+pub struct RollupConfig {
+    /// 每个伪区块包含的最大交易数
+    pub block_size: usize,
+    pub strategy: StrategyType,
+    pub vm_type: VmType,
+}
+
+impl Default for RollupConfig {
+    fn default() -> Self {
+        Self {
+            block_size: 16,
+            strategy: StrategyType::SolanaParallel,
+            vm_type: VmType::CkbVM,
+        }
+    }
+}
+
+/// 最小 rollup 管线
+pub struct RollupPipeline {
+    config: RollupConfig,
+    chain: Arc<DevChain>,
+    loader: CodeLoader,
+    scheduler: ParallelScheduler,
+    vm_manager: VmManager,
+    packages: HashMap<String, CompiledContract>,
+    state: RollupState,
+    blocks: Vec<SealedBlock>,
+}
+
+impl RollupPipeline {
+    pub fn new<P: AsRef<Path>>(
+        config: RollupConfig,
+        chain: Arc<DevChain>,
+        cache_dir: P,
+    ) -> Result<Self> {
+        let loader = CodeLoader::with_cache_dir(cache_dir)?;
+        let scheduler = ParallelScheduler::new(
+            config.strategy,
+            SchedulerConfig {
+                worker_threads: 2,
+                batch_size: config.block_size,
+                ..SchedulerConfig::default()
+            },
+        )?;
+        let vm_manager = VmManager::new(config.vm_type);
+
+        info!(
+            "🧩 Rollup pipeline ready (block size {}, {:?}, {:?})",
+            config.block_size, config.strategy, config.vm_type
+        );
+
+        Ok(Self {
+            config,
+            chain,
+            loader,
+            scheduler,
+            vm_manager,
+            packages: HashMap::new(),
+            state: RollupState::default(),
+            blocks: Vec::new(),
+        })
+    }
+
+    /// 从开发链拉取包并编译为 RISC-V
+    pub async fn load_package(&mut self, package_id: &str) -> Result<&CompiledContract> {
+        let meta: ContractMeta = self.chain.get_contract_meta(package_id).await?;
+        let compiled = self.loader.load_contract(&meta).await?;
+        info!(
+            "📥 Loaded package {} ({} bytes RISC-V)",
+            package_id,
+            compiled.risc_v_code.len()
+        );
+        Ok(self.packages.entry(package_id.to_string()).or_insert(compiled))
+    }
+
+    /// 提交交易流，按区块大小切分，逐块执行并封装
+    pub async fn submit(&mut self, transactions: Vec<Transaction>) -> Result<Vec<SealedBlock>> {
+        let mut sealed = Vec::new();
+        for chunk in transactions.chunks(self.config.block_size.max(1)) {
+            sealed.push(self.execute_block(chunk).await?);
+        }
+        Ok(sealed)
+    }
+
+    /// 将被修改的对象回写开发链
+    pub async fn sync_to_chain(&mut self) -> Result<SyncReport> {
+        let updates: Vec<ChainObject> = self
+            .state
+            .dirty
+            .iter()
+            .filter_map(|id| self.state.objects.get(id).cloned())
+            .collect();
+
+        if updates.is_empty() {
+            return Ok(SyncReport {
+                tx_hash: None,
+                objects_updated: 0,
+            });
+        }
+
+        let objects_updated = updates.len();
+        let tx_hash = self.chain.apply_updates(updates).await?;
+
+        // 回写成功后以链上版本为准
+        for object_id in std::mem::take(&mut self.state.dirty) {
+            if let Some(object) = self.chain.get_object(&object_id).await {
+                self.state.objects.insert(object_id, object);
+            }
+        }
+
+        info!(
+            "⬆️ Synced {} objects back to dev chain: {}",
+            objects_updated, tx_hash
+        );
+
+        Ok(SyncReport {
+            tx_hash: Some(tx_hash),
+            objects_updated,
+        })
+    }
+
+    pub fn state(&self) -> &RollupState {
+        &self.state
+    }
+
+    pub fn blocks(&self) -> &[SealedBlock] {
+        &self.blocks
+    }
+
+    async fn execute_block(&mut self, transactions: &[Transaction]) -> Result<SealedBlock> {
+        for tx in transactions {
+            for object_id in tx.read_set.iter().chain(&tx.write_set) {
+                self.ensure_object_loaded(object_id).await?;
+            }
+        }
+
+        let plan = self.scheduler.plan_batch(transactions).await?;
+        let order = execution_order(&plan, transactions.len());
+
+        let mut results: Vec<Option<TransactionResult>> = vec![None; transactions.len()];
+        for index in order {
+            let tx = &transactions[index];
+            results[index] = Some(self.execute_transaction(tx).await?);
+        }
+        let transaction_results: Vec<TransactionResult> = results.into_iter().flatten().collect();
+
+        let successful = transaction_results.iter().filter(|r| r.success).count();
+        let gas_used = transaction_results.iter().map(|r| r.gas_used).sum();
+        let batch = BatchResult {
+            execution_stats: ExecutionStats {
+                total_transactions: transaction_results.len(),
+                successful_transactions: successful,
+                failed_transactions: transaction_results.len() - successful,
+                total_gas_used: gas_used,
+                ..ExecutionStats::default()
+            },
+            transaction_results,
+        };
+
+        let block = PseudoBlock {
+            number: self.blocks.len() as u64 + 1,
+            parent_hash: self
+                .blocks
+                .last()
+                .map(|b| b.block_hash.clone())
+                .unwrap_or_default(),
+            batch_hash: batch.result_hash()?.to_string(),
+            state_root: self.state.state_root()?,
+            tx_count: transactions.len(),
+            successful_txs: successful,
+            gas_used,
+        };
+        let block_hash = block.canonical_digest()?.to_string();
+
+        info!(
+            "🧱 Sealed block #{} ({} txs, {} gas): {}",
+            block.number, block.tx_count, block.gas_used, block_hash
+        );
+
+        let sealed = SealedBlock {
+            block,
+            block_hash,
+            batch,
+        };
+        self.blocks.push(sealed.clone());
+        Ok(sealed)
+    }
+
+    async fn execute_transaction(&mut self, tx: &Transaction) -> Result<TransactionResult> {
+        let package_id = tx
+            .to
+            .as_deref()
+            .ok_or_else(|| anyhow!("Transaction {} has no target package", tx.hash))?;
+        let code = self
+            .packages
+            .get(package_id)
+            .ok_or_else(|| anyhow!("Package {} not loaded", package_id))?
+            .risc_v_code
+            .clone();
+
+        let mut vm = self.vm_manager.create_instance(None)?;
+        vm.load_code(&code).await?;
+        let execution = vm.execute(&tx.data).await?;
+
+        let outcome = if !execution.success {
+            Err(execution
+                .error
+                .clone()
+                .unwrap_or_else(|| "VM execution failed".to_string()))
+        } else if execution.gas_used > tx.gas_limit {
+            Err(format!(
+                "Out of gas: used {} > limit {}",
+                execution.gas_used, tx.gas_limit
+            ))
+        } else {
+            serde_json::from_slice::<Vec<StateSyscall>>(&execution.output)
+                .map_err(|e| format!("Invalid syscall output: {}", e))
+                .and_then(|syscalls| {
+                    self.state
+                        .apply(&tx.write_set, &syscalls)
+                        .map_err(|e| e.to_string())
+                })
+        };
+
+        if let Err(e) = &outcome {
+            warn!("Transaction {} failed: {}", tx.hash, e);
+        }
+
+        Ok(TransactionResult {
+            tx_hash: tx.hash.clone(),
+            success: outcome.is_ok(),
+            gas_used: execution.gas_used.min(tx.gas_limit),
+            output: execution.output,
+            logs: vec![],
+            error: outcome.err(),
+        })
+    }
+
+    async fn ensure_object_loaded(&mut self, object_id: &str) -> Result<()> {
+        if self.state.objects.contains_key(object_id) {
+            return Ok(());
+        }
+        let object = self
+            .chain
+            .get_object(object_id)
+            .await
+            .ok_or_else(|| anyhow!("Object {} not found on dev chain", object_id))?;
+        self.state.objects.insert(object_id.to_string(), object);
+        Ok(())
+    }
+}
+
+/// 按执行计划展开执行顺序；计划未覆盖全部交易时退回原始顺序
+fn execution_order(plan: &ExecutionPlan, len: usize) -> Vec<usize> {
+    let order: Vec<usize> = plan.scheduled_groups().into_iter().flatten().collect();
+    let mut seen = BTreeSet::new();
+    if order.len() == len && order.iter().all(|&i| i < len && seen.insert(i)) {
+        order
+    } else {
+        (0..len).collect()
+    }
+}
+
+/// 构造一笔 counter 加一交易
+pub fn counter_increment_tx(package_id: &str, counter_id: &str, nonce: u64) -> Result<Transaction> {
+    let syscalls = vec![StateSyscall::Increment {
+        object_id: counter_id.to_string(),
+        field: "value".to_string(),
+        amount: 1,
+    }];
+
+    Ok(Transaction {
+        hash: format!("0x{:064x}", nonce),
+        from: "0xdev".to_string(),
+        to: Some(package_id.to_string()),
+        data: serde_json::to_vec(&syscalls)?,
+        gas_limit: 50_000,
+        gas_price: 1,
+        nonce,
+        read_set: vec![counter_id.to_string()],
+        write_set: vec![counter_id.to_string()],
+        access_list: None,
+        max_priority_fee: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devnet::counter_package_meta;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_counter_pipeline_end_to_end() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let chain = Arc::new(DevChain::new());
+        let package_id = chain.deploy_package(counter_package_meta("0xc0ffee")).await;
+        chain
+            .create_object("0xa", serde_json::json!({ "value": 0 }))
+            .await;
+        chain
+            .create_object("0xb", serde_json::json!({ "value": 10 }))
+            .await;
+
+        let mut pipeline = RollupPipeline::new(
+            RollupConfig {
+                block_size: 4,
+                ..RollupConfig::default()
+            },
+            chain.clone(),
+            temp_dir.path(),
+        )?;
+        pipeline.load_package(&package_id).await?;
+
+        let mut txs = Vec::new();
+        for nonce in 0..10 {
+            let counter = if nonce % 2 == 0 { "0xa" } else { "0xb" };
+            txs.push(counter_increment_tx(&package_id, counter, nonce)?);
+        }
+
+        let blocks = pipeline.submit(txs).await?;
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[1].block.parent_hash, blocks[0].block_hash);
+        assert!(blocks.iter().all(|b| b.block.successful_txs == b.block.tx_count));
+
+        let report = pipeline.sync_to_chain().await?;
+        assert_eq!(report.objects_updated, 2);
+
+        let a = chain.get_object("0xa").await.unwrap();
+        let b = chain.get_object("0xb").await.unwrap();
+        assert_eq!(a.content["value"], 5);
+        assert_eq!(b.content["value"], 15);
+        assert_eq!(a.version, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_syscall_leaves_state_untouched() {
+        let mut state = RollupState::default();
+        state.objects.insert(
+            "0xa".to_string(),
+            ChainObject {
+                object_id: "0xa".to_string(),
+                version: 1,
+                content: serde_json::json!({ "value": 1 }),
+            },
+        );
+
+        let syscalls = vec![
+            StateSyscall::Increment {
+                object_id: "0xa".to_string(),
+                field: "value".to_string(),
+                amount: 1,
+            },
+            StateSyscall::Store {
+                object_id: "0xb".to_string(),
+                field: "value".to_string(),
+                value: serde_json::json!(1),
+            },
+        ];
+        assert!(state
+            .apply(&["0xa".to_string(), "0xb".to_string()], &syscalls)
+            .is_err());
+        assert_eq!(state.object("0xa").unwrap().content["value"], 1);
+        assert!(state.dirty.is_empty());
+    }
+
+    #[test]
+    fn test_non_object_content_is_rejected() {
+        let mut state = RollupState::default();
+        state.objects.insert(
+            "0xa".to_string(),
+            ChainObject {
+                object_id: "0xa".to_string(),
+                version: 1,
+                content: serde_json::json!([1, 2, 3]),
+            },
+        );
+
+        let syscalls = vec![StateSyscall::Store {
+            object_id: "0xa".to_string(),
+            field: "value".to_string(),
+            value: serde_json::json!(1),
+        }];
+        assert!(state.apply(&["0xa".to_string()], &syscalls).is_err());
+        assert_eq!(
+            state.object("0xa").unwrap().content,
+            serde_json::json!([1, 2, 3])
+        );
+        assert!(state.dirty.is_empty());
+    }
+}
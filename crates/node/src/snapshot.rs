@@ -0,0 +1,513 @@
+//! 节点状态快照
+//!
+//! 新副本从快照目录引导，而不是从主网重新同步。快照包含：
+//! - `state/objects`：对象存储，元数据列族中同时保存适配器游标、回写日志与执行录制
+//! - `state/index`：二级索引
+//! - `cache`：编译缓存
+//!
+//! 各库通过 RocksDB 检查点取得一致的时间点，节点可以照常处理请求；调度器目前不持久化任何状态，
+//! 无需包含。`manifest.json` 记录每个文件的大小与 SHA-256，恢复前全部校验。
+//! SST 文件写入后不再变化，增量快照只写入与基准快照哈希不同的文件，其余在清单中标记为取自基准。
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::info;
+
+use dubhe_api::SnapshotReport;
+use dubhe_loader::CompilationCache;
+use dubhe_state::StateManager;
+
+/// 清单文件名
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// 当前清单格式版本
+const FORMAT_VERSION: u32 = 1;
+
+/// 快照内状态存储与编译缓存的目录
+const STATE_DIR: &str = "state";
+const CACHE_DIR: &str = "cache";
+
+/// 清单中的一个文件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    /// 相对快照根目录的路径，以 `/` 分隔
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+    /// 文件未写入本快照，取自基准快照的同一路径
+    #[serde(default)]
+    pub from_base: bool,
+}
+
+/// 快照清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub format_version: u32,
+    /// 由文件列表计算，基准引用与恢复时据此校验
+    pub id: String,
+    /// 创建时间（Unix 秒）
+    pub created_at: u64,
+    /// 增量快照的基准快照 ID
+    pub base: Option<String>,
+    pub files: Vec<SnapshotFile>,
+}
+
+impl SnapshotManifest {
+    fn new(created_at: u64, base: Option<String>, files: Vec<SnapshotFile>) -> Self {
+        let mut manifest = Self {
+            format_version: FORMAT_VERSION,
+            id: String::new(),
+            created_at,
+            base,
+            files,
+        };
+        manifest.id = manifest.compute_id();
+        manifest
+    }
+
+    fn compute_id(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.created_at.to_be_bytes());
+        hasher.update(self.base.as_deref().unwrap_or_default());
+        for file in &self.files {
+            hasher.update(format!("{} {} {}\n", file.path, file.size, file.sha256));
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// 读取并校验快照目录中的清单
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let path = dir.as_ref().join(MANIFEST_FILE);
+        let manifest: Self = serde_json::from_slice(
+            &fs::read(&path).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?,
+        )?;
+        if manifest.format_version != FORMAT_VERSION {
+            bail!(
+                "Unsupported snapshot format version {} in {}",
+                manifest.format_version,
+                path.display()
+            );
+        }
+        if manifest.compute_id() != manifest.id {
+            bail!("Snapshot manifest {} has been modified", path.display());
+        }
+        Ok(manifest)
+    }
+
+    fn save(&self, dir: &Path) -> Result<()> {
+        fs::write(dir.join(MANIFEST_FILE), serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    fn report(&self, dir: &Path) -> SnapshotReport {
+        let written: Vec<_> = self.files.iter().filter(|file| !file.from_base).collect();
+        SnapshotReport {
+            id: self.id.clone(),
+            path: dir.display().to_string(),
+            base: self.base.clone(),
+            files: self.files.len(),
+            written_files: written.len(),
+            written_bytes: written.iter().map(|file| file.size).sum(),
+        }
+    }
+}
+
+/// 从运行中的节点创建快照
+pub struct Snapshotter {
+    state: Arc<StateManager>,
+    cache: Arc<CompilationCache>,
+}
+
+impl Snapshotter {
+    pub fn new(state: Arc<StateManager>, cache: Arc<CompilationCache>) -> Self {
+        Self { state, cache }
+    }
+
+    /// 在 `out`（不存在或为空目录）创建快照；给出 `base` 时创建相对该快照的增量快照
+    pub fn create(&self, out: &Path, base: Option<&Path>) -> Result<SnapshotReport> {
+        let base = base.map(SnapshotManifest::load).transpose()?;
+        if let Some(base) = &base {
+            if base.base.is_some() {
+                bail!("Base snapshot {} is itself incremental", base.id);
+            }
+        }
+        if !is_empty_dir(out)? {
+            bail!("Snapshot directory {} is not empty", out.display());
+        }
+        fs::create_dir_all(out)?;
+
+        self.state.checkpoint(out.join(STATE_DIR))?;
+        self.cache.checkpoint(out.join(CACHE_DIR))?;
+
+        let base_files: HashMap<&str, &str> = base
+            .iter()
+            .flat_map(|base| &base.files)
+            .map(|file| (file.path.as_str(), file.sha256.as_str()))
+            .collect();
+        let mut files = Vec::new();
+        for path in list_files(out)? {
+            let full = out.join(&path);
+            let (size, sha256) = hash_file(&full)?;
+            // 与基准相同的文件（检查点中多为硬链接）从本快照中移除
+            let from_base = base_files.get(path.as_str()) == Some(&sha256.as_str());
+            if from_base {
+                fs::remove_file(&full)?;
+            }
+            files.push(SnapshotFile {
+                path,
+                size,
+                sha256,
+                from_base,
+            });
+        }
+
+        let manifest = SnapshotManifest::new(now_secs(), base.map(|base| base.id), files);
+        manifest.save(out)?;
+        let report = manifest.report(out);
+        info!(
+            "📸 Snapshot {} written to {}: {} files, {} written ({} bytes)",
+            report.id,
+            out.display(),
+            report.files,
+            report.written_files,
+            report.written_bytes
+        );
+        Ok(report)
+    }
+}
+
+/// 将快照恢复到状态目录与编译缓存目录
+///
+/// 所有文件在写入前按清单校验；目标目录非空时除非 `force` 否则拒绝，`force` 时先清空。
+/// 增量快照需要给出其基准快照目录
+pub fn restore_snapshot(
+    snapshot: &Path,
+    base: Option<&Path>,
+    state_dir: &Path,
+    cache_dir: &Path,
+    force: bool,
+) -> Result<SnapshotManifest> {
+    let manifest = SnapshotManifest::load(snapshot)?;
+    let base_dir = match (&manifest.base, base) {
+        (Some(expected), Some(base)) => {
+            let base_manifest = SnapshotManifest::load(base)?;
+            if &base_manifest.id != expected {
+                bail!(
+                    "Snapshot {} is based on {}, but {} contains snapshot {}",
+                    manifest.id,
+                    expected,
+                    base.display(),
+                    base_manifest.id
+                );
+            }
+            Some(base)
+        }
+        (Some(expected), None) => bail!(
+            "Snapshot {} is incremental, the base snapshot {} is required",
+            manifest.id,
+            expected
+        ),
+        (None, _) => None,
+    };
+
+    // 先校验全部文件，任何不一致都不会改动目标目录
+    let mut sources = Vec::with_capacity(manifest.files.len());
+    for file in &manifest.files {
+        let target = target_path(&file.path, state_dir, cache_dir)?;
+        let source = match (file.from_base, base_dir) {
+            (true, Some(base)) => base.join(&file.path),
+            (true, None) => bail!("{} refers to a base snapshot that is not given", file.path),
+            (false, _) => snapshot.join(&file.path),
+        };
+        let (size, sha256) = hash_file(&source)?;
+        if size != file.size || sha256 != file.sha256 {
+            bail!(
+                "Snapshot file {} does not match the manifest (sha256 {}, expected {})",
+                source.display(),
+                sha256,
+                file.sha256
+            );
+        }
+        sources.push((source, target));
+    }
+
+    for dir in [state_dir, cache_dir] {
+        if !is_empty_dir(dir)? {
+            if !force {
+                bail!(
+                    "Refusing to restore into non-empty directory {} without --force",
+                    dir.display()
+                );
+            }
+            fs::remove_dir_all(dir)?;
+        }
+        fs::create_dir_all(dir)?;
+    }
+    for (source, target) in &sources {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(source, target)?;
+    }
+
+    info!(
+        "📸 Restored snapshot {} ({} files) into {} and {}",
+        manifest.id,
+        manifest.files.len(),
+        state_dir.display(),
+        cache_dir.display()
+    );
+    Ok(manifest)
+}
+
+/// API 请求中的快照目录：只接受相对 `root` 的普通路径，拒绝绝对路径与 `..`
+pub fn resolve_snapshot_path(root: &Path, path: &str) -> Result<PathBuf> {
+    let relative = Path::new(path);
+    if path.is_empty()
+        || !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+    {
+        bail!(
+            "Snapshot path {} must be a relative path under the snapshot directory",
+            path
+        );
+    }
+    Ok(root.join(relative))
+}
+
+/// 清单路径对应的恢复位置，拒绝越出快照布局的路径
+fn target_path(path: &str, state_dir: &Path, cache_dir: &Path) -> Result<PathBuf> {
+    let relative = Path::new(path);
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        bail!("Invalid path {} in snapshot manifest", path);
+    }
+    if let Ok(rest) = relative.strip_prefix(STATE_DIR) {
+        Ok(state_dir.join(rest))
+    } else if let Ok(rest) = relative.strip_prefix(CACHE_DIR) {
+        Ok(cache_dir.join(rest))
+    } else {
+        bail!("Unexpected path {} in snapshot manifest", path)
+    }
+}
+
+/// 按字典序列出目录下的全部文件（不含清单），返回以 `/` 分隔的相对路径
+fn list_files(root: &Path) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let relative = path
+                .strip_prefix(root)?
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if relative != MANIFEST_FILE {
+                files.push(relative);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn hash_file(path: &Path) -> Result<(u64, String)> {
+    let mut file =
+        File::open(path).map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let size = std::io::copy(&mut file, &mut hasher)?;
+    Ok((size, hex::encode(hasher.finalize())))
+}
+
+fn is_empty_dir(path: &Path) -> Result<bool> {
+    match fs::read_dir(path) {
+        Ok(mut entries) => Ok(entries.next().is_none()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(true),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dubhe_adapter::{EventLog, TransactionReceipt, TransactionStatus};
+    use dubhe_loader::CacheConfig;
+    use dubhe_state::{EventQuery, IndexedEvent};
+    use tempfile::tempdir;
+
+    fn receipt(block: u64) -> TransactionReceipt {
+        TransactionReceipt {
+            tx_hash: format!("0x{:08x}", block),
+            block_hash: format!("0xblock{}", block),
+            block_number: block,
+            transaction_index: 0,
+            from: "0xsender".to_string(),
+            to: None,
+            gas_used: 21000,
+            status: TransactionStatus::Success,
+            logs: vec![EventLog {
+                address: "0xpkg".to_string(),
+                topics: vec!["Transfer".to_string()],
+                data: format!("0x{:02x}", block),
+            }],
+            contract_address: (block % 10 == 0).then(|| format!("0xcontract{}", block)),
+        }
+    }
+
+    fn populate(state: &StateManager, blocks: std::ops::Range<u64>) -> Result<()> {
+        let indexer = state.indexer();
+        for block in blocks {
+            indexer.index_receipt(&receipt(block))?;
+            state.storage().put_object(
+                &format!("0xobj{}", block % 5),
+                block,
+                &block.to_be_bytes(),
+            )?;
+        }
+        // 检查点之前落盘，使两次快照之间有不变的 SST 文件可共享
+        state.storage().flush()?;
+        Ok(())
+    }
+
+    fn events(state: &StateManager) -> Result<Vec<IndexedEvent>> {
+        let query = EventQuery {
+            package: "0xpkg".to_string(),
+            ..EventQuery::default()
+        };
+        Ok(state.indexer().query_events(&query, None, 1000)?.items)
+    }
+
+    #[test]
+    fn test_incremental_snapshot_restores_identical_queries() -> Result<()> {
+        let dir = tempdir()?;
+        let state = Arc::new(StateManager::new(dir.path().join("node/state"))?);
+        let cache = Arc::new(CompilationCache::new(
+            dir.path().join("node/cache"),
+            CacheConfig::default(),
+        )?);
+        let snapshotter = Snapshotter::new(state.clone(), cache);
+
+        populate(&state, 0..50)?;
+        let full_dir = dir.path().join("full");
+        let full = snapshotter.create(&full_dir, None)?;
+        assert!(full.base.is_none());
+        assert_eq!(full.written_files, full.files);
+
+        populate(&state, 50..60)?;
+        let incremental_dir = dir.path().join("incremental");
+        let incremental = snapshotter.create(&incremental_dir, Some(&full_dir))?;
+        assert_eq!(incremental.base.as_deref(), Some(full.id.as_str()));
+        assert!(incremental.written_files < incremental.files);
+
+        // 增量快照缺少基准时无法恢复
+        let restored = dir.path().join("restored");
+        let (state_dir, cache_dir) = (restored.join("state"), restored.join("cache"));
+        assert!(restore_snapshot(&incremental_dir, None, &state_dir, &cache_dir, false).is_err());
+        assert!(!state_dir.exists());
+
+        restore_snapshot(
+            &incremental_dir,
+            Some(&full_dir),
+            &state_dir,
+            &cache_dir,
+            false,
+        )?;
+        let replica = StateManager::new(&state_dir)?;
+        assert_eq!(events(&replica)?, events(&state)?);
+        assert_eq!(events(&replica)?.len(), 60);
+        assert_eq!(replica.indexer().indexed_block()?, Some(59));
+        assert_eq!(
+            replica
+                .indexer()
+                .query_objects_by_owner("0xsender", None, 100)?,
+            state
+                .indexer()
+                .query_objects_by_owner("0xsender", None, 100)?
+        );
+        assert_eq!(
+            replica.storage().get_latest("0xobj4")?,
+            Some((59, 59u64.to_be_bytes().to_vec()))
+        );
+        CompilationCache::new(&cache_dir, CacheConfig::default())?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_validates_files_and_target() -> Result<()> {
+        let dir = tempdir()?;
+        let state = Arc::new(StateManager::new(dir.path().join("node/state"))?);
+        let cache = Arc::new(CompilationCache::new(
+            dir.path().join("node/cache"),
+            CacheConfig::default(),
+        )?);
+        populate(&state, 0..20)?;
+        let snapshot = dir.path().join("snapshot");
+        Snapshotter::new(state, cache).create(&snapshot, None)?;
+
+        // 非空目标目录需要 force
+        let state_dir = dir.path().join("replica/state");
+        let cache_dir = dir.path().join("replica/cache");
+        fs::create_dir_all(&state_dir)?;
+        fs::write(state_dir.join("stale"), b"stale")?;
+        assert!(restore_snapshot(&snapshot, None, &state_dir, &cache_dir, false).is_err());
+        restore_snapshot(&snapshot, None, &state_dir, &cache_dir, true)?;
+        assert!(!state_dir.join("stale").exists());
+        assert_eq!(events(&StateManager::new(&state_dir)?)?.len(), 20);
+
+        // 被篡改的文件在写入目标目录前被拒绝
+        let manifest = SnapshotManifest::load(&snapshot)?;
+        let victim = manifest
+            .files
+            .iter()
+            .find(|file| file.path.ends_with(".sst"))
+            .expect("checkpoint contains SST files");
+        fs::write(snapshot.join(&victim.path), b"corrupted")?;
+        let fresh = dir.path().join("fresh");
+        let err = restore_snapshot(
+            &snapshot,
+            None,
+            &fresh.join("state"),
+            &fresh.join("cache"),
+            false,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("does not match the manifest"));
+        assert!(!fresh.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_api_snapshot_paths_stay_under_root() -> Result<()> {
+        let root = Path::new("/var/lib/dubhe/snapshots");
+        assert_eq!(
+            resolve_snapshot_path(root, "daily/2024-01-01")?,
+            root.join("daily/2024-01-01")
+        );
+        for path in ["", "/tmp/out", "../out", "daily/../../out", "./out"] {
+            assert!(resolve_snapshot_path(root, path).is_err(), "{}", path);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+//! 状态页接入
+//!
+//! 为 [`Dashboard`](dubhe_observability::Dashboard) 收集节点各组件的状态，
+//! 并让 `/status.json` 复用 API Key 认证：配置了 Key 时任意有效 Key 即可访问（只读角色足够）
+
+use async_trait::async_trait;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use dubhe_adapter::AdapterManager;
+use dubhe_api::auth::Caller;
+use dubhe_api::Authenticator;
+use dubhe_loader::CodeLoader;
+use dubhe_observability::{
+    CacheSummary, ChainHealth, NodeStatus, OffchainSummary, StatusAuth, StatusProvider,
+};
+use dubhe_scheduler::ParallelScheduler;
+
+use crate::offchain_execution::OffchainExecutionManager;
+
+/// 限流时 `/status.json` 归入的方法，属于只读类别
+const STATUS_METHOD: &str = "dubhe_status";
+
+/// 从节点组件收集状态
+pub struct NodeStatusProvider {
+    scheduler: Arc<ParallelScheduler>,
+    adapter_manager: Arc<AdapterManager>,
+    code_loader: Arc<CodeLoader>,
+    offchain_manager: Arc<OffchainExecutionManager>,
+}
+
+impl NodeStatusProvider {
+    pub fn new(
+        scheduler: Arc<ParallelScheduler>,
+        adapter_manager: Arc<AdapterManager>,
+        code_loader: Arc<CodeLoader>,
+        offchain_manager: Arc<OffchainExecutionManager>,
+    ) -> Self {
+        Self {
+            scheduler,
+            adapter_manager,
+            code_loader,
+            offchain_manager,
+        }
+    }
+}
+
+#[async_trait]
+impl StatusProvider for NodeStatusProvider {
+    async fn node_status(&self) -> NodeStatus {
+        let scheduler = self.scheduler.get_status().await;
+
+        let chains = self
+            .adapter_manager
+            .chain_statuses()
+            .await
+            .into_iter()
+            .map(|status| ChainHealth {
+                chain: format!("{:?}", status.chain_type),
+                state: format!("{:?}", status.state).to_lowercase(),
+                error: status.error,
+            })
+            .collect();
+
+        let compilation = self.code_loader.cache().stats().await;
+        let vm_pool = self.offchain_manager.vm_pool_stats();
+        let mut caches = vec![
+            CacheSummary::new("compilation", compilation.hits, compilation.misses),
+            CacheSummary::new("vm_pool", vm_pool.hits, vm_pool.misses),
+        ];
+        if let Some(query) = self.offchain_manager.query_cache_stats() {
+            caches.push(CacheSummary::new("query", query.hits, query.misses));
+        }
+
+        let execution = self.offchain_manager.get_execution_stats().await;
+        NodeStatus {
+            strategy: format!("{:?}", scheduler.strategy_type),
+            worker_threads: scheduler.worker_threads,
+            chains,
+            caches,
+            offchain: OffchainSummary {
+                active_sessions: execution.active_sessions,
+                locked_objects: execution.locked_objects,
+                pending_executions: execution.pending_executions,
+                hot_objects: execution.hot_objects,
+            },
+        }
+    }
+}
+
+/// `/status.json` 的 API Key 认证，与 RPC 共用 Key 与限流
+pub struct ApiKeyStatusAuth {
+    auth: Arc<Authenticator>,
+}
+
+impl ApiKeyStatusAuth {
+    pub fn new(auth: Arc<Authenticator>) -> Self {
+        Self { auth }
+    }
+}
+
+impl StatusAuth for ApiKeyStatusAuth {
+    fn authorize(&self, authorization: Option<&str>, ip: IpAddr) -> Result<(), String> {
+        let caller = self
+            .auth
+            .identify(authorization, ip)
+            .map_err(|e| e.to_string())?;
+        // 未配置 Key 时与 RPC 一样按 IP 放行
+        if self.auth.enabled() && matches!(caller, Caller::Ip(_)) {
+            return Err("Status page requires an API key".to_string());
+        }
+        self.auth
+            .authorize(&caller, STATUS_METHOD)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dubhe_api::auth::{hash_api_key, ApiKeyConfig};
+    use dubhe_api::AuthConfig;
+
+    fn authenticator(keys: &[(&str, &str)]) -> Arc<Authenticator> {
+        let config = AuthConfig {
+            api_keys: keys
+                .iter()
+                .map(|(name, key)| ApiKeyConfig {
+                    name: name.to_string(),
+                    key_hash: hash_api_key(key),
+                })
+                .collect(),
+            ..Default::default()
+        };
+        Arc::new(Authenticator::new(&config))
+    }
+
+    #[test]
+    fn test_status_auth_requires_key_when_configured() {
+        let ip: IpAddr = "127.0.0.1".parse().unwrap();
+
+        let open = ApiKeyStatusAuth::new(authenticator(&[]));
+        assert!(open.authorize(None, ip).is_ok());
+
+        let auth = ApiKeyStatusAuth::new(authenticator(&[("dashboard", "secret")]));
+        assert!(auth.authorize(None, ip).is_err());
+        assert!(auth.authorize(Some("Bearer wrong"), ip).is_err());
+        assert!(auth.authorize(Some("Bearer secret"), ip).is_ok());
+    }
+}
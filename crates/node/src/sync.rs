@@ -0,0 +1,376 @@
+//! 结果回写主网
+//!
+//! 一次会话的所有对象变更打包为 PTB 原子提交；超出 Sui 单笔交易的命令数或字节数限制时，
+//! 按调用顺序确定性地切分为多块，并通过状态层的回写日志记录每块的提交结果，
+//! 使重启后可以只提交剩余的块。
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use dubhe_adapter::sui::SuiAdapter;
+use dubhe_adapter::MoveCall;
+use dubhe_state::{SyncChunk, SyncJournal, SyncJournalRecord};
+
+/// Sui 单个 PTB 的最大命令数
+pub const MAX_PTB_COMMANDS: usize = 1024;
+/// Sui 单笔交易的最大字节数
+pub const MAX_PTB_BYTES: usize = 128 * 1024;
+
+/// 未配置签名者时干跑使用的发送方
+const DRY_RUN_SENDER: &str = "0x105b79ec1ee0a31c2faa544104f93b084f78cd8a9d9bb6a02654db21ac9fef8f";
+
+/// 回写配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConfig {
+    /// 每个 PTB 最多包含的 Move 调用数
+    pub max_calls_per_ptb: usize,
+    /// 每个 PTB 调用参数的最大估算字节数
+    pub max_ptb_bytes: usize,
+    /// 每个 PTB 的 gas 预算
+    pub gas_budget: u64,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            max_calls_per_ptb: MAX_PTB_COMMANDS,
+            max_ptb_bytes: MAX_PTB_BYTES,
+            gas_budget: 50_000_000,
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SyncError {
+    #[error(
+        "Sync for session {session_id} stopped after {committed} of {total} chunks: {reason}"
+    )]
+    Incomplete {
+        session_id: String,
+        committed: usize,
+        total: usize,
+        reason: String,
+    },
+}
+
+/// 一个已提交 PTB 及其包含的变更
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncCommit {
+    pub digest: String,
+    pub changes: Vec<String>,
+}
+
+/// PTB 提交
+#[async_trait]
+pub trait PtbSubmitter: Send + Sync {
+    /// 原子提交一组 Move 调用，返回交易摘要
+    async fn submit(&self, calls: &[MoveCall]) -> Result<String>;
+}
+
+/// 在本地构建 PTB，干跑后由适配器的签名者签名提交
+pub struct SuiPtbSubmitter {
+    adapter: Arc<SuiAdapter>,
+    gas_budget: u64,
+}
+
+impl SuiPtbSubmitter {
+    pub fn new(adapter: Arc<SuiAdapter>, gas_budget: u64) -> Self {
+        if adapter.signer().is_none() {
+            warn!("⚠️ No signer configured, result sync transactions are dry-run only");
+        }
+        Self {
+            adapter,
+            gas_budget,
+        }
+    }
+}
+
+#[async_trait]
+impl PtbSubmitter for SuiPtbSubmitter {
+    async fn submit(&self, calls: &[MoveCall]) -> Result<String> {
+        let sender = match self.adapter.signer() {
+            Some(signer) => signer.address(),
+            None => DRY_RUN_SENDER.to_string(),
+        };
+        let tx_bytes = self
+            .adapter
+            .build_ptb(&sender, calls, self.gas_budget)
+            .await?;
+
+        let dry_run = self.adapter.dry_run_transaction(&tx_bytes).await?;
+        check_status("Dry run", &dry_run)?;
+
+        match self.adapter.signer() {
+            Some(_) => {
+                let response = self
+                    .adapter
+                    .sign_and_execute_transaction_block(&tx_bytes)
+                    .await?;
+                // 上链但执行失败的交易不能记入日志，否则恢复时会跳过这一块
+                check_status("Transaction", &response)?;
+                Ok(response["digest"].as_str().unwrap_or_default().to_string())
+            }
+            None => Ok(dry_run["effects"]["transactionDigest"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()),
+        }
+    }
+}
+
+/// 干跑或执行响应的 `effects.status` 不是成功时返回错误
+fn check_status(stage: &str, response: &Value) -> Result<()> {
+    let status = &response["effects"]["status"];
+    if status["status"] != "success" {
+        return Err(anyhow::anyhow!("{} failed: {}", stage, status));
+    }
+    Ok(())
+}
+
+/// 按顺序切分调用：当前块再加入一个调用会超出命令数或字节数限制时另起一块。
+/// 单个超限调用独占一块，由链上决定是否接受
+pub fn split_calls(calls: Vec<(String, MoveCall)>, config: &SyncConfig) -> Vec<SyncChunk> {
+    let max_calls = config.max_calls_per_ptb.max(1);
+    let mut chunks = Vec::new();
+    let mut current = SyncChunk {
+        calls: Vec::new(),
+        changes: Vec::new(),
+        digest: None,
+    };
+    let mut current_bytes = 0;
+
+    for (change, call) in calls {
+        let size = serde_json::to_vec(&call).map(|bytes| bytes.len()).unwrap_or(0);
+        let full = current.calls.len() >= max_calls
+            || (!current.calls.is_empty() && current_bytes + size > config.max_ptb_bytes);
+        if full {
+            chunks.push(std::mem::replace(
+                &mut current,
+                SyncChunk {
+                    calls: Vec::new(),
+                    changes: Vec::new(),
+                    digest: None,
+                },
+            ));
+            current_bytes = 0;
+        }
+        current.calls.push(call);
+        current.changes.push(change);
+        current_bytes += size;
+    }
+
+    if !current.calls.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// 依次提交记录中尚未提交的块，每块成功后写入日志。
+/// 中途失败返回 [`SyncError::Incomplete`]，已提交的块保留在日志中
+pub async fn submit_journaled(
+    journal: Option<&SyncJournal>,
+    submitter: &dyn PtbSubmitter,
+    record: &mut SyncJournalRecord,
+) -> Result<Vec<SyncCommit>> {
+    let total = record.chunks.len();
+    let mut commits = Vec::with_capacity(total);
+
+    for (index, chunk) in record.chunks.iter_mut().enumerate() {
+        let digest = match &chunk.digest {
+            Some(digest) => digest.clone(),
+            None => {
+                let digest = submitter.submit(&chunk.calls).await.map_err(|e| {
+                    SyncError::Incomplete {
+                        session_id: record.session_id.clone(),
+                        committed: index,
+                        total,
+                        reason: e.to_string(),
+                    }
+                })?;
+                if let Some(journal) = journal {
+                    journal.mark_committed(&record.session_id, index, &digest)?;
+                }
+                info!(
+                    "⬆️ Committed chunk {}/{} of session {} ({} calls) in {}",
+                    index + 1,
+                    total,
+                    record.session_id,
+                    chunk.calls.len(),
+                    digest
+                );
+                chunk.digest = Some(digest.clone());
+                digest
+            }
+        };
+        commits.push(SyncCommit {
+            digest,
+            changes: chunk.changes.clone(),
+        });
+    }
+
+    Ok(commits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dubhe_state::{JournalLease, StateManager};
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    /// 记录提交的 PTB，第 `fail_at` 次提交起失败
+    struct MockSubmitter {
+        fail_at: Option<usize>,
+        submitted: Mutex<Vec<Vec<String>>>,
+    }
+
+    impl MockSubmitter {
+        fn new(fail_at: Option<usize>) -> Self {
+            Self {
+                fail_at,
+                submitted: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl PtbSubmitter for MockSubmitter {
+        async fn submit(&self, calls: &[MoveCall]) -> Result<String> {
+            let mut submitted = self.submitted.lock().unwrap();
+            if Some(submitted.len()) == self.fail_at {
+                return Err(anyhow::anyhow!("connection reset"));
+            }
+            submitted.push(calls.iter().map(|call| call.function.clone()).collect());
+            Ok(format!("digest-{}", submitted.len()))
+        }
+    }
+
+    fn call(function: &str) -> (String, MoveCall) {
+        (
+            format!("0x{}", function),
+            MoveCall {
+                package: "0xpkg".to_string(),
+                module: "counter".to_string(),
+                function: function.to_string(),
+                type_arguments: vec![],
+                arguments: vec![json!(function)],
+            },
+        )
+    }
+
+    #[test]
+    fn test_split_is_deterministic() {
+        let config = SyncConfig {
+            max_calls_per_ptb: 2,
+            ..Default::default()
+        };
+        let calls: Vec<_> = ["a", "b", "c", "d", "e"].iter().map(|f| call(f)).collect();
+        let chunks = split_calls(calls.clone(), &config);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].changes, vec!["0xe"]);
+        assert_eq!(chunks, split_calls(calls.clone(), &config));
+
+        // 字节数限制
+        let size = serde_json::to_vec(&calls[0].1).unwrap().len();
+        let config = SyncConfig {
+            max_ptb_bytes: size * 3,
+            ..Default::default()
+        };
+        let chunks = split_calls(calls, &config);
+        assert_eq!(
+            chunks.iter().map(|c| c.calls.len()).collect::<Vec<_>>(),
+            vec![3, 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resume_after_partial_sync() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let state = StateManager::new(dir.path())?;
+        let journal = state.journal();
+        let config = SyncConfig {
+            max_calls_per_ptb: 2,
+            ..Default::default()
+        };
+
+        let mut record = SyncJournalRecord {
+            session_id: "session-1".to_string(),
+            leases: vec![JournalLease {
+                object_id: "0xshared".to_string(),
+                version: 3,
+                lock_digest: "lock".to_string(),
+                expires_at_ms: u64::MAX,
+            }],
+            chunks: split_calls(
+                ["a", "b", "c", "d"].iter().map(|f| call(f)).collect(),
+                &config,
+            ),
+        };
+        journal.begin(&record)?;
+
+        // 第一块提交后连接中断
+        let failing = MockSubmitter::new(Some(1));
+        let err = submit_journaled(Some(&journal), &failing, &mut record)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<SyncError>(),
+            Some(SyncError::Incomplete {
+                committed: 1,
+                total: 2,
+                ..
+            })
+        ));
+
+        // 模拟重启：从持久化日志恢复，只提交剩余的块
+        drop(record);
+        let mut pending = journal.pending()?;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].chunks[0].digest.as_deref(), Some("digest-1"));
+        assert!(!pending[0].is_complete());
+
+        let resumed = MockSubmitter::new(None);
+        let commits = submit_journaled(Some(&journal), &resumed, &mut pending[0]).await?;
+        assert_eq!(*resumed.submitted.lock().unwrap(), vec![vec!["c", "d"]]);
+        assert_eq!(
+            commits,
+            vec![
+                SyncCommit {
+                    digest: "digest-1".to_string(),
+                    changes: vec!["0xa".to_string(), "0xb".to_string()],
+                },
+                SyncCommit {
+                    digest: "digest-1".to_string(),
+                    changes: vec!["0xc".to_string(), "0xd".to_string()],
+                },
+            ]
+        );
+        assert!(journal.get("session-1")?.unwrap().is_complete());
+
+        journal.complete("session-1")?;
+        assert!(journal.pending()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_execution_is_not_committed() {
+        let success = json!({ "digest": "abc", "effects": { "status": { "status": "success" } } });
+        assert!(check_status("Transaction", &success).is_ok());
+
+        // 交易已上链但 Move 中止
+        let aborted = json!({
+            "digest": "abc",
+            "effects": { "status": { "status": "failure", "error": "MoveAbort(..., 3) in command 0" } }
+        });
+        let err = check_status("Transaction", &aborted).unwrap_err();
+        assert!(err.to_string().starts_with("Transaction failed"));
+
+        // 缺少执行状态同样视为失败
+        assert!(check_status("Transaction", &json!({ "digest": "abc" })).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+//! 威胁检测接入
+//!
+//! 把调度器的交易结果与适配器事件总线上的新交易喂给 [`ThreatDetector`]，
+//! 命中的规则作为告警发给 [`AlertManager`] 的通知后端
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use dubhe_adapter::{AdapterManager, ChainEventKind, ChainType, TransactionStatus};
+use dubhe_observability::{Alert, AlertManager, AlertSeverity};
+use dubhe_scheduler::ParallelScheduler;
+use dubhe_security::{Threat, ThreatDetector, ThreatSink, TransactionObservation};
+
+/// 检查限流是否到期的间隔
+const RELEASE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 将威胁转为一次性告警
+pub struct AlertThreatSink {
+    alerts: Arc<Mutex<AlertManager>>,
+}
+
+impl AlertThreatSink {
+    pub fn new(alerts: Arc<Mutex<AlertManager>>) -> Self {
+        Self { alerts }
+    }
+}
+
+impl ThreatSink for AlertThreatSink {
+    fn on_threat(&self, threat: &Threat) {
+        let alert = Alert {
+            rule: format!("threat_{}", threat.rule.name()),
+            metric: threat.subject.clone(),
+            value: threat.observed as f64,
+            severity: AlertSeverity::Critical,
+            message: format!(
+                "{} on {}: observed {} > {} (source {}{})",
+                threat.rule.name(),
+                threat.subject,
+                threat.observed,
+                threat.threshold,
+                threat.source,
+                if threat.throttled { ", throttled" } else { "" }
+            ),
+            triggered_at: chrono::Utc::now().timestamp() as u64,
+        };
+        let alerts = self.alerts.clone();
+        tokio::spawn(async move { AlertManager::raise(&alerts, alert).await });
+    }
+}
+
+/// 持续把调度器结果与 `chain_type` 的新交易交给检测器，并定期解除到期的限流
+pub fn spawn_threat_monitor(
+    detector: Arc<ThreatDetector>,
+    scheduler: &ParallelScheduler,
+    adapters: Arc<AdapterManager>,
+    chain_type: ChainType,
+) -> JoinHandle<()> {
+    let mut outcomes = scheduler.subscribe_outcomes();
+    let mut events = adapters.subscribe_events();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(RELEASE_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    detector.tick(Instant::now());
+                }
+                batch = outcomes.recv() => match batch {
+                    Ok(batch) => {
+                        for outcome in batch {
+                            detector.observe(
+                                &TransactionObservation {
+                                    source: outcome.from,
+                                    contract: outcome.to,
+                                    gas_used: outcome.gas_used,
+                                    success: outcome.success,
+                                },
+                                Instant::now(),
+                            );
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Threat monitor lagged, skipped {} scheduler batches", skipped)
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                event = events.recv() => match event {
+                    Ok(event)
+                        if event.chain_type == chain_type
+                            && event.kind == ChainEventKind::NewTransaction =>
+                    {
+                        let receipt = match adapters
+                            .get_transaction_receipt(chain_type, &event.payload)
+                            .await
+                        {
+                            Ok(receipt) => receipt,
+                            Err(e) => {
+                                debug!("Failed to fetch receipt for {}: {}", event.payload, e);
+                                continue;
+                            }
+                        };
+                        let success = match receipt.status {
+                            TransactionStatus::Success => true,
+                            TransactionStatus::Failed => false,
+                            TransactionStatus::Pending => continue,
+                        };
+                        detector.observe(
+                            &TransactionObservation {
+                                source: receipt.from,
+                                contract: receipt.to.or(receipt.contract_address),
+                                gas_used: receipt.gas_used,
+                                success,
+                            },
+                            Instant::now(),
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Threat monitor lagged, skipped {} chain events", skipped)
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            }
+        }
+        debug!("Threat monitor stopped");
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use dubhe_api::auth::{AuthConfig, Authenticator, Caller};
+    use dubhe_observability::{AlertEvent, AlertNotifier};
+    use dubhe_scheduler::{SchedulerConfig, StrategyType, Transaction};
+    use dubhe_security::{ThreatDetectionConfig, ThreatRule, Throttle};
+
+    #[derive(Default)]
+    struct Recorder(std::sync::Mutex<Vec<AlertEvent>>);
+
+    #[async_trait]
+    impl AlertNotifier for Recorder {
+        async fn notify(&self, event: &AlertEvent) -> Result<()> {
+            self.0.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+    }
+
+    fn tx(index: usize, from: &str) -> Transaction {
+        Transaction {
+            hash: format!("0x{:04x}", index),
+            from: from.to_string(),
+            to: Some("0xcounter".to_string()),
+            data: vec![],
+            gas_limit: 21_000,
+            gas_price: 1,
+            nonce: index as u64,
+            read_set: vec![],
+            write_set: vec![format!("{}:{}", from, index)],
+            access_list: None,
+            max_priority_fee: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_burst_raises_alert_and_throttles() {
+        let recorder = Arc::new(Recorder::default());
+        let mut alerts = AlertManager::new();
+        alerts.add_notifier(recorder.clone());
+        let auth = Arc::new(Authenticator::new(&AuthConfig::default()));
+
+        let detector = Arc::new(
+            ThreatDetector::new(ThreatDetectionConfig {
+                enabled: true,
+                max_transactions_per_minute: 5,
+                throttle: true,
+                cooldown_secs: 60,
+                ..Default::default()
+            })
+            .with_sink(Arc::new(AlertThreatSink::new(Arc::new(Mutex::new(alerts)))))
+            .with_throttle(auth.clone() as Arc<dyn Throttle>),
+        );
+        let scheduler =
+            ParallelScheduler::new(StrategyType::Sequential, SchedulerConfig::default()).unwrap();
+        let monitor = spawn_threat_monitor(
+            detector.clone(),
+            &scheduler,
+            Arc::new(AdapterManager::new()),
+            ChainType::Sui,
+        );
+
+        // 同一 IP 来源的突发交易
+        let source = "10.0.0.9";
+        scheduler
+            .submit_batch((0..8).map(|i| tx(i, source)).collect())
+            .await
+            .unwrap();
+        for _ in 0..50 {
+            if !recorder.0.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        let events = recorder.0.lock().unwrap().clone();
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].alert.rule,
+            format!("threat_{}", ThreatRule::SenderRate.name())
+        );
+        assert!(detector.is_throttled(source, Instant::now()));
+        let caller = Caller::Ip(source.parse().unwrap());
+        assert!(auth.authorize(&caller, "eth_blockNumber").is_err());
+
+        // 冷却期结束后解除
+        let released = detector.tick(Instant::now() + Duration::from_secs(61));
+        assert_eq!(released, vec![source.to_string()]);
+        assert!(auth.authorize(&caller, "eth_blockNumber").is_ok());
+        monitor.abort();
+    }
+}
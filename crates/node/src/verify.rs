@@ -0,0 +1,437 @@
+//! 执行结果的可验证摘要与链上核对
+//!
+//! 每个会话结果带有 `effects_digest`：修改对象、新建对象、gas 用量与会话输入摘要
+//! （包、函数、参数、gas 预算及各输入对象的版本）规范序列化后的摘要，以及回写主网的各笔交易摘要。
+//! dubhe_verifyExecution 按记录中的交易摘要重新从主网取回效果，读取每个修改对象在回写之后的
+//! 版本内容，用链上内容替换记录中的 `new_content` 后重算摘要：与记录的摘要一致，
+//! 说明节点声称的结果确实落在了链上
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use dubhe_adapter::sui::{object_versions, SuiAdapter};
+use dubhe_security::{canonical_digest, digest_versioned, VersionedDigest};
+use dubhe_state::Storage;
+
+use crate::offchain_execution::{
+    CreatedObject, ExecutionRequest, ModifiedObject, OffchainExecutionResult,
+};
+use crate::sync::SyncCommit;
+
+/// 效果摘要的摘要域
+pub const EFFECTS_DOMAIN: &str = "dubhe.offchain.effects";
+/// 会话输入摘要的摘要域
+pub const INPUTS_DOMAIN: &str = "dubhe.offchain.inputs";
+
+/// 状态存储中会话结果的键前缀
+const RESULT_PREFIX: &str = "execution_result:";
+
+/// 回写交易的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncTxStatus {
+    /// 已提交，主网返回了交易摘要
+    Submitted,
+    /// 核对时主网报告执行成功
+    Confirmed,
+    /// 核对时主网报告执行失败
+    Failed,
+}
+
+/// 一笔回写交易
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncTxRecord {
+    pub digest: String,
+    /// 交易包含的变更：修改与删除的对象 ID，新建对象的类型
+    pub objects: Vec<String>,
+    pub status: SyncTxStatus,
+}
+
+impl From<&SyncCommit> for SyncTxRecord {
+    fn from(commit: &SyncCommit) -> Self {
+        Self {
+            digest: commit.digest.clone(),
+            objects: commit.changes.clone(),
+            status: SyncTxStatus::Submitted,
+        }
+    }
+}
+
+/// 单笔回写交易的核对结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncTxCheck {
+    pub digest: String,
+    pub status: SyncTxStatus,
+    pub error: Option<String>,
+}
+
+/// dubhe_verifyExecution 的返回值
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationReport {
+    pub session_id: String,
+    pub recorded_digest: VersionedDigest,
+    /// 以链上对象内容重算的摘要
+    pub onchain_digest: VersionedDigest,
+    /// 记录自身与其摘要一致（未被篡改）
+    pub record_consistent: bool,
+    pub transactions: Vec<SyncTxCheck>,
+    /// 链上内容与记录不一致，或在回写交易中找不到的对象
+    pub mismatched_objects: Vec<String>,
+    pub matches: bool,
+}
+
+/// 核对所需的主网数据
+#[async_trait]
+pub trait OnchainEffectsSource: Send + Sync {
+    /// 交易的执行效果（Sui JSON-RPC 的 `effects` 结构）
+    async fn transaction_effects(&self, digest: &str) -> Result<Value>;
+
+    /// 对象在指定版本的 Move 字段，版本不存在时返回 `None`
+    async fn object_fields(&self, object_id: &str, version: u64) -> Result<Option<Value>>;
+}
+
+#[async_trait]
+impl OnchainEffectsSource for SuiAdapter {
+    async fn transaction_effects(&self, digest: &str) -> Result<Value> {
+        self.get_transaction_effects(digest).await
+    }
+
+    async fn object_fields(&self, object_id: &str, version: u64) -> Result<Option<Value>> {
+        Ok(self
+            .get_past_object(object_id, version)
+            .await?
+            .map(|details| details["content"]["fields"].clone()))
+    }
+}
+
+/// 会话输入摘要：调用本身与各输入对象被锁定时的版本，不含会话 ID
+pub fn inputs_hash(request: &ExecutionRequest, inputs: &[(String, u64)]) -> Result<String> {
+    let payload = json!({
+        "package_id": request.package_id,
+        "function_name": request.function_name,
+        "arguments": request.arguments,
+        "gas_budget": request.gas_budget,
+        "objects": inputs,
+    });
+    Ok(canonical_digest(INPUTS_DOMAIN, &payload)?.digest)
+}
+
+fn effects_payload(
+    modified_objects: &[ModifiedObject],
+    new_objects: &[CreatedObject],
+    gas_used: u64,
+    inputs_hash: &str,
+) -> Value {
+    json!({
+        "modified_objects": modified_objects,
+        "new_objects": new_objects,
+        "gas_used": gas_used,
+        "inputs_hash": inputs_hash,
+    })
+}
+
+/// 效果摘要
+pub fn effects_digest(
+    modified_objects: &[ModifiedObject],
+    new_objects: &[CreatedObject],
+    gas_used: u64,
+    inputs_hash: &str,
+) -> Result<VersionedDigest> {
+    canonical_digest(
+        EFFECTS_DOMAIN,
+        &effects_payload(modified_objects, new_objects, gas_used, inputs_hash),
+    )
+}
+
+/// 存档会话结果
+pub fn save_result(storage: &Storage, result: &OffchainExecutionResult) -> Result<()> {
+    storage.put_metadata(
+        &format!("{}{}", RESULT_PREFIX, result.session_id),
+        &serde_json::to_vec(result)?,
+    )
+}
+
+/// 读取存档的会话结果
+pub fn load_result(storage: &Storage, session_id: &str) -> Result<Option<OffchainExecutionResult>> {
+    storage
+        .get_metadata(&format!("{}{}", RESULT_PREFIX, session_id))?
+        .map(|bytes| Ok(serde_json::from_slice(&bytes)?))
+        .transpose()
+}
+
+/// 链上字段与记录一致；Sui JSON-RPC 把 u64 等大整数字段渲染为字符串，按数值比较
+fn same_value(recorded: &Value, onchain: &Value) -> bool {
+    match (recorded, onchain) {
+        (Value::Number(number), Value::String(text))
+        | (Value::String(text), Value::Number(number)) => number.to_string() == *text,
+        (Value::Object(recorded), Value::Object(onchain)) => {
+            recorded.len() == onchain.len()
+                && recorded.iter().all(|(key, value)| {
+                    onchain
+                        .get(key)
+                        .is_some_and(|other| same_value(value, other))
+                })
+        }
+        (Value::Array(recorded), Value::Array(onchain)) => {
+            recorded.len() == onchain.len()
+                && recorded
+                    .iter()
+                    .zip(onchain)
+                    .all(|(value, other)| same_value(value, other))
+        }
+        _ => recorded == onchain,
+    }
+}
+
+/// 链上对象内容中与记录对应的部分：记录为对象时只取记录中出现的字段（链上还有 `id` 等字段）
+fn onchain_content(recorded: &Value, fields: &Value) -> Value {
+    let projected = match (recorded, fields) {
+        (Value::Object(recorded), Value::Object(fields)) => Value::Object(
+            recorded
+                .keys()
+                .filter_map(|key| Some((key.clone(), fields.get(key)?.clone())))
+                .collect(),
+        ),
+        _ => fields.clone(),
+    };
+    // 语义一致时沿用记录中的表示，摘要才能复现
+    if same_value(recorded, &projected) {
+        recorded.clone()
+    } else {
+        projected
+    }
+}
+
+/// 按记录的回写交易核对会话结果
+pub async fn verify_result(
+    record: &OffchainExecutionResult,
+    source: &dyn OnchainEffectsSource,
+) -> Result<VerificationReport> {
+    let Some(recorded_digest) = &record.effects_digest else {
+        bail!("Session {} has no effects digest", record.session_id);
+    };
+
+    let mut versions: HashMap<String, u64> = HashMap::new();
+    let mut transactions = Vec::with_capacity(record.sync_transactions.len());
+    for tx in &record.sync_transactions {
+        let effects = source.transaction_effects(&tx.digest).await?;
+        let confirmed = effects["status"]["status"].as_str() == Some("success");
+        if confirmed {
+            for update in object_versions(&effects, 0) {
+                let version = versions.entry(update.object_id).or_default();
+                *version = (*version).max(update.version);
+            }
+        }
+        transactions.push(SyncTxCheck {
+            digest: tx.digest.clone(),
+            status: if confirmed {
+                SyncTxStatus::Confirmed
+            } else {
+                SyncTxStatus::Failed
+            },
+            error: effects["status"]["error"].as_str().map(str::to_string),
+        });
+    }
+
+    let mut mismatched_objects = Vec::new();
+    let mut onchain_modified = Vec::with_capacity(record.modified_objects.len());
+    for object in &record.modified_objects {
+        let fields = match versions.get(&object.object_id) {
+            Some(&version) => source.object_fields(&object.object_id, version).await?,
+            None => None,
+        };
+        let content = fields.map(|fields| onchain_content(&object.new_content, &fields));
+        if content.as_ref() != Some(&object.new_content) {
+            mismatched_objects.push(object.object_id.clone());
+        }
+        onchain_modified.push(ModifiedObject {
+            new_content: content.unwrap_or(Value::Null),
+            ..object.clone()
+        });
+    }
+    for object in &record.deleted_objects {
+        if !versions.contains_key(&object.object_id) {
+            mismatched_objects.push(object.object_id.clone());
+        }
+    }
+
+    let record_digest = digest_versioned(
+        EFFECTS_DOMAIN,
+        &effects_payload(
+            &record.modified_objects,
+            &record.new_objects,
+            record.gas_used,
+            &record.inputs_hash,
+        ),
+        recorded_digest.version,
+    )?;
+    let onchain_digest = digest_versioned(
+        EFFECTS_DOMAIN,
+        &effects_payload(
+            &onchain_modified,
+            &record.new_objects,
+            record.gas_used,
+            &record.inputs_hash,
+        ),
+        recorded_digest.version,
+    )?;
+    let record_consistent = record_digest
+        .digest
+        .eq_ignore_ascii_case(&recorded_digest.digest);
+    let matches = record_consistent
+        && onchain_digest
+            .digest
+            .eq_ignore_ascii_case(&recorded_digest.digest)
+        && mismatched_objects.is_empty()
+        && transactions
+            .iter()
+            .all(|tx| tx.status == SyncTxStatus::Confirmed);
+
+    Ok(VerificationReport {
+        session_id: record.session_id.clone(),
+        recorded_digest: recorded_digest.clone(),
+        onchain_digest,
+        record_consistent,
+        transactions,
+        mismatched_objects,
+        matches,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offchain_execution::ObjectChanges;
+
+    /// 固定的主网交易效果与对象历史版本
+    #[derive(Default)]
+    struct MockChain {
+        effects: HashMap<String, Value>,
+        objects: HashMap<(String, u64), Value>,
+    }
+
+    #[async_trait]
+    impl OnchainEffectsSource for MockChain {
+        async fn transaction_effects(&self, digest: &str) -> Result<Value> {
+            self.effects
+                .get(digest)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("Unknown transaction {}", digest))
+        }
+
+        async fn object_fields(&self, object_id: &str, version: u64) -> Result<Option<Value>> {
+            Ok(self.objects.get(&(object_id.to_string(), version)).cloned())
+        }
+    }
+
+    fn request() -> ExecutionRequest {
+        ExecutionRequest {
+            session_id: "session-1".to_string(),
+            package_id: "0xpkg".to_string(),
+            function_name: "increment".to_string(),
+            arguments: vec![],
+            shared_objects: vec!["0xcounter".to_string()],
+            gas_budget: 1_000,
+            read_only: false,
+        }
+    }
+
+    /// 把 0xcounter 从版本 7 改为 value = 8，经一笔交易回写
+    fn record() -> OffchainExecutionResult {
+        let request = request();
+        let modified_objects = vec![ModifiedObject {
+            object_id: "0xcounter".to_string(),
+            old_version: 7,
+            new_content: json!({ "value": 8 }),
+            changes: ObjectChanges {
+                fields_modified: vec!["value".to_string()],
+                ..Default::default()
+            },
+        }];
+        let inputs_hash = inputs_hash(&request, &[("0xcounter".to_string(), 7)]).unwrap();
+        let effects_digest = effects_digest(&modified_objects, &[], 42, &inputs_hash).unwrap();
+        let commit = SyncCommit {
+            digest: "0xsync".to_string(),
+            changes: vec!["0xcounter".to_string()],
+        };
+        OffchainExecutionResult {
+            session_id: request.session_id,
+            success: true,
+            gas_used: 42,
+            modified_objects,
+            new_objects: vec![],
+            deleted_objects: vec![],
+            sync_transactions: vec![SyncTxRecord::from(&commit)],
+            commits: vec![commit],
+            error: None,
+            execution_time_ms: 3,
+            attestation: None,
+            inputs_hash,
+            effects_digest: Some(effects_digest),
+        }
+    }
+
+    fn chain(onchain_value: &str) -> MockChain {
+        let mut chain = MockChain::default();
+        chain.effects.insert(
+            "0xsync".to_string(),
+            json!({
+                "status": { "status": "success" },
+                "mutated": [{
+                    "owner": { "Shared": { "initial_shared_version": 1 } },
+                    "reference": { "objectId": "0xcounter", "version": 12, "digest": "d" },
+                }],
+            }),
+        );
+        // 链上字段以字符串渲染 u64，另有记录中没有的 id 字段
+        chain.objects.insert(
+            ("0xcounter".to_string(), 12),
+            json!({ "id": { "id": "0xcounter" }, "value": onchain_value }),
+        );
+        chain
+    }
+
+    #[tokio::test]
+    async fn test_onchain_outcome_matches_recorded_digest() {
+        let report = verify_result(&record(), &chain("8")).await.unwrap();
+        assert!(report.matches);
+        assert!(report.record_consistent);
+        assert_eq!(report.onchain_digest, report.recorded_digest);
+        assert_eq!(report.transactions[0].status, SyncTxStatus::Confirmed);
+        assert!(report.mismatched_objects.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_onchain_mismatch_is_reported() {
+        // 链上的值与节点声称的不同
+        let report = verify_result(&record(), &chain("100")).await.unwrap();
+        assert!(!report.matches);
+        assert!(report.record_consistent);
+        assert_ne!(report.onchain_digest, report.recorded_digest);
+        assert_eq!(report.mismatched_objects, vec!["0xcounter".to_string()]);
+
+        // 回写交易在链上失败
+        let mut failed = chain("8");
+        failed.effects.insert(
+            "0xsync".to_string(),
+            json!({ "status": { "status": "failure", "error": "MoveAbort" } }),
+        );
+        let report = verify_result(&record(), &failed).await.unwrap();
+        assert!(!report.matches);
+        assert_eq!(report.transactions[0].status, SyncTxStatus::Failed);
+        assert_eq!(report.transactions[0].error.as_deref(), Some("MoveAbort"));
+
+        // 记录被改动后与摘要不符
+        let mut tampered = record();
+        tampered.gas_used = 1;
+        let report = verify_result(&tampered, &chain("8")).await.unwrap();
+        assert!(!report.record_consistent);
+        assert!(!report.matches);
+    }
+}
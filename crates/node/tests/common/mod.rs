@@ -0,0 +1,617 @@
+//! 端到端测试工具
+//!
+//! [`MockChainBackend`] 在进程内模拟一条 Sui 链，对象、包与检查点由测试脚本化设置。
+//! 它以 [`ChainAdapter`] 注册到适配器管理器，在临时端口上提供 `SuiAdapter` 用到的 JSON-RPC 方法，
+//! 同时充当锁注册表与 PTB 提交端，记录节点发往主网的交易。
+//! [`TestNode`] 用它组装节点各组件，API 服务全部监听在临时端口上
+
+// 各测试文件只用到其中一部分
+#![allow(dead_code)]
+
+use anyhow::Result;
+use async_trait::async_trait;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+use dubhe_adapter::sui::SuiAdapter;
+use dubhe_adapter::{
+    AdapterError, AdapterManager, AdapterResult, ChainAdapter, ChainType, ContractMeta,
+    ContractType, HealthCheckConfig, MoveCall, SuiConfig, SuiNetworkType, TransactionReceipt,
+    TransactionStatus,
+};
+use dubhe_api::{ApiConfig, ApiListeners, ApiServer};
+use dubhe_loader::abi::{SYS_EXIT, SYS_STORAGE_READ, SYS_WRITE_OUTPUT};
+use dubhe_loader::riscv::*;
+use dubhe_loader::{CodeLoader, CompilationConfig, Plugin, PluginCapabilities};
+use dubhe_node::locking::{ObjectLease, ObjectLocker, ReleaseOutcome};
+use dubhe_node::sync::{PtbSubmitter, SyncConfig};
+use dubhe_node::{OffchainExecutionManager, EFFECTS_FORMAT_VERSION, EFFECTS_MAGIC};
+use dubhe_scheduler::{ParallelScheduler, SchedulerConfig, StrategyType};
+use dubhe_vm_runtime::{VmManager, VmType};
+
+/// 模拟链上的共享对象
+#[derive(Debug, Clone)]
+pub struct MockObject {
+    pub object_type: String,
+    pub version: u64,
+    /// Move 结构体字段，即 `content.fields`
+    pub fields: Value,
+}
+
+#[derive(Default)]
+struct ChainState {
+    objects: HashMap<String, MockObject>,
+    packages: HashMap<String, Vec<u8>>,
+    checkpoint: u64,
+    /// 当前持有的租约
+    leases: HashMap<String, ObjectLease>,
+    /// 已释放租约的对象，按释放顺序
+    released: Vec<String>,
+    /// 收到的 PTB：(交易摘要, Move 调用)
+    transactions: Vec<(String, Vec<MoveCall>)>,
+    block_subscribers: Vec<mpsc::Sender<String>>,
+    tx_subscribers: Vec<mpsc::Sender<String>>,
+}
+
+impl ChainState {
+    /// `sui_getObject` 的返回：对象带内容字段，包的 `bcs` 为十六进制字节码，不存在时只有错误
+    fn object_response(&self, object_id: &str) -> Value {
+        if let Some(object) = self.objects.get(object_id) {
+            return json!({
+                "data": {
+                    "objectId": object_id,
+                    "version": object.version.to_string(),
+                    "type": object.object_type,
+                    "owner": { "Shared": { "initial_shared_version": 1 } },
+                    "content": {
+                        "dataType": "moveObject",
+                        "type": object.object_type,
+                        "hasPublicTransfer": false,
+                        "fields": object.fields,
+                    },
+                    "bcs": hex::encode(object.fields.to_string()),
+                }
+            });
+        }
+        if let Some(code) = self.packages.get(object_id) {
+            return json!({
+                "data": {
+                    "objectId": object_id,
+                    "version": "1",
+                    "type": "package",
+                    "owner": "Immutable",
+                    "content": { "dataType": "package" },
+                    "bcs": hex::encode(code),
+                }
+            });
+        }
+        json!({ "error": { "code": "notExists", "object_id": object_id } })
+    }
+}
+
+/// 向仍在订阅的接收端推送，丢弃已关闭的订阅
+fn broadcast(subscribers: &mut Vec<mpsc::Sender<String>>, payload: &str) {
+    subscribers.retain(|subscriber| {
+        let _ = subscriber.try_send(payload.to_string());
+        !subscriber.is_closed()
+    });
+}
+
+/// 可脚本化的模拟 Sui 后端，克隆共享同一份链状态
+#[derive(Clone, Default)]
+pub struct MockChainBackend {
+    state: Arc<Mutex<ChainState>>,
+}
+
+impl MockChainBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加版本为 1 的共享对象
+    pub fn with_object(self, object_id: &str, object_type: &str, fields: Value) -> Self {
+        self.state().objects.insert(
+            object_id.to_string(),
+            MockObject {
+                object_type: object_type.to_string(),
+                version: 1,
+                fields,
+            },
+        );
+        self
+    }
+
+    /// 发布包，`code` 即节点加载到的包字节码
+    pub fn with_package(self, package_id: &str, code: Vec<u8>) -> Self {
+        self.state().packages.insert(package_id.to_string(), code);
+        self
+    }
+
+    fn state(&self) -> MutexGuard<'_, ChainState> {
+        self.state.lock().expect("mock chain poisoned")
+    }
+
+    pub fn object(&self, object_id: &str) -> Option<MockObject> {
+        self.state().objects.get(object_id).cloned()
+    }
+
+    /// 产出新检查点并推送给区块订阅者，返回其序号
+    pub fn advance_checkpoint(&self) -> u64 {
+        let mut state = self.state();
+        state.checkpoint += 1;
+        let checkpoint = state.checkpoint;
+        broadcast(&mut state.block_subscribers, &checkpoint.to_string());
+        checkpoint
+    }
+
+    /// 收到的全部 PTB（交易摘要与 Move 调用），按提交顺序
+    pub fn transactions(&self) -> Vec<(String, Vec<MoveCall>)> {
+        self.state().transactions.clone()
+    }
+
+    /// 当前持有租约的对象
+    pub fn leased_objects(&self) -> Vec<String> {
+        let mut leased: Vec<String> = self.state().leases.keys().cloned().collect();
+        leased.sort();
+        leased
+    }
+
+    /// 已释放租约的对象，按释放顺序
+    pub fn released_objects(&self) -> Vec<String> {
+        self.state().released.clone()
+    }
+
+    /// 在临时端口上提供 Sui JSON-RPC，返回其 URL
+    pub async fn serve_rpc(&self) -> Result<String> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let backend = self.clone();
+        let make_service = make_service_fn(move |_| {
+            let backend = backend.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let backend = backend.clone();
+                    async move { backend.handle_rpc(request).await }
+                }))
+            }
+        });
+        let server = hyper::Server::from_tcp(listener)?.serve(make_service);
+        tokio::spawn(server);
+        Ok(url)
+    }
+
+    async fn handle_rpc(&self, request: Request<Body>) -> Result<Response<Body>, Infallible> {
+        let body = hyper::body::to_bytes(request.into_body())
+            .await
+            .unwrap_or_default();
+        let request: Value = serde_json::from_slice(&body).unwrap_or_default();
+        let method = request["method"].as_str().unwrap_or_default();
+        let reply = match self.rpc_result(method, &request["params"]) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+            Err(message) => json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "error": { "code": -32601, "message": message },
+            }),
+        };
+        Ok(Response::builder()
+            .header("content-type", "application/json")
+            .body(Body::from(reply.to_string()))
+            .expect("valid response"))
+    }
+
+    /// 只实现节点执行链下会话用到的方法，其余方法返回错误
+    /// （规范化模块取不到时适配器退回对象内容）
+    fn rpc_result(&self, method: &str, params: &Value) -> Result<Value, String> {
+        let state = self.state();
+        match method {
+            "sui_getObject" => Ok(state.object_response(params[0].as_str().unwrap_or_default())),
+            "sui_getLatestCheckpointSequenceNumber" => Ok(json!(state.checkpoint.to_string())),
+            _ => Err(format!(
+                "Method {} is not supported by the mock chain",
+                method
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl ChainAdapter for MockChainBackend {
+    async fn get_contract_meta(&self, address: &str) -> AdapterResult<ContractMeta> {
+        let bytecode = self.state().packages.get(address).cloned().ok_or_else(|| {
+            AdapterError::ContractNotFound {
+                chain: ChainType::Sui,
+                address: address.to_string(),
+            }
+        })?;
+        Ok(ContractMeta {
+            address: address.to_string(),
+            chain_type: ChainType::Sui,
+            contract_type: ContractType::Move,
+            bytecode,
+            abi: Some("{}".to_string()),
+            source_code: None,
+            compiler_version: Some("move".to_string()),
+            created_at: 0,
+            creator: None,
+            abi_source: None,
+            modules: vec![],
+        })
+    }
+
+    async fn get_transaction_receipt(&self, tx_hash: &str) -> AdapterResult<TransactionReceipt> {
+        let state = self.state();
+        let index = state
+            .transactions
+            .iter()
+            .position(|(digest, _)| digest == tx_hash)
+            .ok_or_else(|| AdapterError::Rpc {
+                chain: ChainType::Sui,
+                message: format!("Transaction {} not found", tx_hash),
+            })?;
+        Ok(TransactionReceipt {
+            tx_hash: tx_hash.to_string(),
+            block_hash: format!("checkpoint-{}", state.checkpoint),
+            block_number: state.checkpoint,
+            transaction_index: index as u32,
+            from: "0xnode".to_string(),
+            to: None,
+            gas_used: 0,
+            status: TransactionStatus::Success,
+            logs: vec![],
+            contract_address: None,
+        })
+    }
+
+    async fn get_balance(&self, _address: &str) -> AdapterResult<u64> {
+        Ok(0)
+    }
+
+    async fn get_nonce(&self, _address: &str) -> AdapterResult<u64> {
+        Ok(0)
+    }
+
+    async fn get_block_number(&self) -> AdapterResult<u64> {
+        Ok(self.state().checkpoint)
+    }
+
+    async fn subscribe_new_blocks(&self) -> AdapterResult<mpsc::Receiver<String>> {
+        let (tx, rx) = mpsc::channel(64);
+        self.state().block_subscribers.push(tx);
+        Ok(rx)
+    }
+
+    async fn subscribe_new_transactions(&self) -> AdapterResult<mpsc::Receiver<String>> {
+        let (tx, rx) = mpsc::channel(64);
+        self.state().tx_subscribers.push(tx);
+        Ok(rx)
+    }
+}
+
+#[async_trait]
+impl ObjectLocker for MockChainBackend {
+    async fn acquire(&self, object_id: &str, lease: Duration) -> Result<ObjectLease> {
+        let mut state = self.state();
+        let version = state
+            .objects
+            .get(object_id)
+            .map(|object| object.version)
+            .ok_or_else(|| anyhow::anyhow!("Object {} does not exist", object_id))?;
+        if state.leases.contains_key(object_id) {
+            anyhow::bail!("Object {} is already leased", object_id);
+        }
+        let lease = ObjectLease {
+            object_id: object_id.to_string(),
+            version,
+            digest: format!("lock-{}-{}", object_id, version),
+            expires_at_ms: chrono::Utc::now().timestamp_millis() as u64 + lease.as_millis() as u64,
+        };
+        state.leases.insert(object_id.to_string(), lease.clone());
+        Ok(lease)
+    }
+
+    async fn release(&self, lease: &ObjectLease) -> Result<ReleaseOutcome> {
+        let mut state = self.state();
+        if state.leases.remove(&lease.object_id).is_none() {
+            return Ok(ReleaseOutcome::Expired);
+        }
+        state.released.push(lease.object_id.clone());
+        Ok(ReleaseOutcome::Released(format!(
+            "unlock-{}",
+            lease.object_id
+        )))
+    }
+}
+
+#[async_trait]
+impl PtbSubmitter for MockChainBackend {
+    async fn submit(&self, calls: &[MoveCall]) -> Result<String> {
+        let mut state = self.state();
+        let digest = format!("0xptb{}", state.transactions.len() + 1);
+        state.transactions.push((digest.clone(), calls.to_vec()));
+        broadcast(&mut state.tx_subscribers, &digest);
+        Ok(digest)
+    }
+}
+
+/// 把包字节码原样当作 RISC-V 代码的编译插件，测试包直接发布汇编好的程序
+pub struct PassThroughPlugin;
+
+impl Plugin for PassThroughPlugin {
+    fn name(&self) -> &str {
+        "pass-through"
+    }
+
+    fn version(&self) -> &str {
+        "0.1.0"
+    }
+
+    fn capabilities(&self) -> PluginCapabilities {
+        PluginCapabilities {
+            contract_types: vec![ContractType::Move],
+        }
+    }
+
+    fn compile(&self, bytecode: &[u8], _config: &CompilationConfig) -> Result<Vec<u8>> {
+        Ok(bytecode.to_vec())
+    }
+}
+
+/// 计数器递增程序：经 `SYS_STORAGE_READ` 读取各对象的 `value` 字段，加一后以效果信封输出新内容
+///
+/// `objects` 为 (对象 ID, 加锁时的版本)；`value` 须为一位十进制数（JSON 数字 0-8）
+pub fn counter_increment_program(objects: &[(&str, u64)]) -> Vec<u8> {
+    // 新内容为 `{"value":N}` 的十六进制：数字 N 编码为 0x3N，低半字节的十六进制字符恰好是 N 本身
+    let placeholder = hex::encode(br#"{"value":0}"#);
+    let digit = placeholder.len() - 3;
+
+    let effects: Vec<Value> = objects
+        .iter()
+        .map(|(object_id, version)| {
+            json!({
+                "kind": "modified",
+                "object_id": object_id,
+                "old_version": version,
+                "content": placeholder,
+            })
+        })
+        .collect();
+    let body = json!({ "effects": effects }).to_string();
+    let mut envelope = EFFECTS_MAGIC.to_vec();
+    envelope.extend(EFFECTS_FORMAT_VERSION.to_le_bytes());
+    envelope.extend((body.len() as u32).to_le_bytes());
+    let body_start = envelope.len();
+    envelope.extend(body.as_bytes());
+
+    // 各效果内容中数字字符的位置，按效果顺序
+    let mut patches = Vec::new();
+    let mut from = 0;
+    for _ in objects {
+        let at = from + body[from..].find(&placeholder).expect("effect content");
+        patches.push(body_start + at + digit);
+        from = at + placeholder.len();
+    }
+
+    // 栈帧：[信封][各对象的键][1 字节读缓冲]
+    let keys: Vec<String> = objects
+        .iter()
+        .map(|(object_id, _)| format!("{}/value", object_id))
+        .collect();
+    let buffer = envelope.len() + keys.iter().map(String::len).sum::<usize>();
+    let frame = (buffer + 16) & !15;
+    assert!(
+        frame < 2048,
+        "counter program frame exceeds the immediate range"
+    );
+
+    let mut words = vec![addi(SP, SP, -(frame as i32))];
+    store_bytes(&mut words, &envelope, 0);
+    let mut key_offset = envelope.len();
+    for (key, patch) in keys.iter().zip(&patches) {
+        store_bytes(&mut words, key.as_bytes(), key_offset);
+        words.extend([
+            addi(A0, SP, key_offset as i32),
+            addi(A1, ZERO, key.len() as i32),
+        ]);
+        words.extend([
+            addi(A2, SP, buffer as i32),
+            addi(A3, ZERO, 1),
+            addi(A4, ZERO, 0),
+        ]);
+        words.extend(syscall(SYS_STORAGE_READ));
+        words.extend([
+            lbu(T0, SP, buffer as i32),
+            addi(T0, T0, 1),
+            sb(T0, SP, *patch as i32),
+        ]);
+        key_offset += key.len();
+    }
+
+    words.extend([addi(A0, SP, 0), addi(A1, ZERO, envelope.len() as i32)]);
+    words.extend(syscall(SYS_WRITE_OUTPUT));
+    words.push(addi(A0, ZERO, 0));
+    words.extend(syscall(SYS_EXIT));
+    assemble(&words)
+}
+
+/// 逐字节写入栈上 `offset` 处
+fn store_bytes(words: &mut Vec<u32>, bytes: &[u8], offset: usize) {
+    for (i, byte) in bytes.iter().enumerate() {
+        words.extend(li(T0, *byte as i32));
+        words.push(sb(T0, SP, (offset + i) as i32));
+    }
+}
+
+/// 组装完成的测试节点
+pub struct TestNode {
+    pub backend: MockChainBackend,
+    pub adapter_manager: Arc<AdapterManager>,
+    pub code_loader: Arc<CodeLoader>,
+    pub vm_manager: Arc<VmManager>,
+    pub scheduler: Arc<ParallelScheduler>,
+    pub offchain: Arc<OffchainExecutionManager>,
+    /// 节点 JSON-RPC 地址
+    pub rpc_url: String,
+    pub grpc_addr: SocketAddr,
+    pub ws_url: String,
+    _cache_dir: TempDir,
+}
+
+/// [`TestNode`] 构建器
+pub struct TestNodeBuilder {
+    backend: MockChainBackend,
+    strategy: StrategyType,
+    plugins: Vec<Box<dyn Plugin>>,
+}
+
+impl TestNodeBuilder {
+    pub fn with_strategy(mut self, strategy: StrategyType) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// 在代码加载器上注册编译插件
+    pub fn with_plugin(mut self, plugin: Box<dyn Plugin>) -> Self {
+        self.plugins.push(plugin);
+        self
+    }
+
+    pub async fn build(self) -> Result<TestNode> {
+        let chain_url = self.backend.serve_rpc().await?;
+
+        let adapter_manager = Arc::new(AdapterManager::new());
+        adapter_manager
+            .register_adapter(ChainType::Sui, Box::new(self.backend.clone()))
+            .await;
+
+        let cache_dir = tempfile::tempdir()?;
+        let mut code_loader = CodeLoader::with_cache_dir(cache_dir.path())?;
+        for plugin in self.plugins {
+            code_loader.register_plugin(plugin)?;
+        }
+        let code_loader = Arc::new(code_loader);
+
+        let vm_manager = Arc::new(VmManager::new(VmType::CkbVM));
+        let scheduler = Arc::new(ParallelScheduler::new(
+            self.strategy,
+            SchedulerConfig::default(),
+        )?);
+
+        let sui_adapter = Arc::new(
+            SuiAdapter::new(SuiConfig {
+                enabled: true,
+                rpc_url: chain_url,
+                ws_url: None,
+                network_type: SuiNetworkType::Localnet,
+                package_ids: vec![],
+                signer: None,
+                rpc_urls: vec![],
+                health_check: HealthCheckConfig::default(),
+            })
+            .await?,
+        );
+        let offchain = Arc::new(
+            OffchainExecutionManager::new(sui_adapter, vm_manager.clone(), code_loader.clone())
+                .await?
+                .with_object_locker(Arc::new(self.backend.clone()), Duration::from_secs(60))
+                .with_ptb_submitter(Arc::new(self.backend.clone()), SyncConfig::default()),
+        );
+
+        let api_server = Arc::new(
+            ApiServer::new(ApiConfig::default())
+                .with_scheduler(scheduler.clone())
+                .with_adapters(adapter_manager.clone())
+                .with_offchain(offchain.clone()),
+        );
+        let listeners = ApiListeners {
+            rpc: TcpListener::bind("127.0.0.1:0").await?,
+            grpc: TcpListener::bind("127.0.0.1:0").await?,
+            ws: TcpListener::bind("127.0.0.1:0").await?,
+        };
+        let rpc_url = format!("http://{}", listeners.rpc.local_addr()?);
+        let grpc_addr = listeners.grpc.local_addr()?;
+        let ws_url = format!("ws://{}", listeners.ws.local_addr()?);
+        tokio::spawn(async move { api_server.serve(listeners).await });
+
+        Ok(TestNode {
+            backend: self.backend,
+            adapter_manager,
+            code_loader,
+            vm_manager,
+            scheduler,
+            offchain,
+            rpc_url,
+            grpc_addr,
+            ws_url,
+            _cache_dir: cache_dir,
+        })
+    }
+}
+
+impl TestNode {
+    pub fn builder(backend: MockChainBackend) -> TestNodeBuilder {
+        TestNodeBuilder {
+            backend,
+            strategy: StrategyType::SuiObject,
+            plugins: vec![],
+        }
+    }
+
+    /// 向节点 JSON-RPC 发送一次调用，返回完整响应
+    pub async fn rpc_call(&self, method: &str, params: Value) -> Result<Value> {
+        let response = reqwest::Client::new()
+            .post(&self.rpc_url)
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(response.json().await?)
+    }
+
+    /// 调用并取出 `result`，响应为错误时失败
+    pub async fn rpc_result(&self, method: &str, params: Value) -> Result<Value> {
+        let response = self.rpc_call(method, params).await?;
+        if let Some(error) = response.get("error") {
+            anyhow::bail!("{} failed: {}", method, error);
+        }
+        Ok(response["result"].clone())
+    }
+
+    /// 断言后端收到了更新该对象的 `set_value` 调用
+    pub fn assert_update_submitted(&self, object_id: &str) {
+        let transactions = self.backend.transactions();
+        let updated = transactions
+            .iter()
+            .flat_map(|(_, calls)| calls)
+            .any(|call| {
+                call.function == "set_value" && call.arguments.first() == Some(&json!(object_id))
+            });
+        assert!(
+            updated,
+            "no update call for {} in {:?}",
+            object_id, transactions
+        );
+    }
+
+    /// 断言这些对象的租约均已释放，且后端没有遗留租约
+    pub fn assert_unlocked(&self, object_ids: &[&str]) {
+        let released = self.backend.released_objects();
+        for object_id in object_ids {
+            assert!(
+                released.iter().any(|released| released == object_id),
+                "lease on {} was not released",
+                object_id
+            );
+        }
+        assert_eq!(self.backend.leased_objects(), Vec::<String>::new());
+    }
+}
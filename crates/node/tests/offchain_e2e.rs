@@ -0,0 +1,163 @@
+//! 链下执行端到端测试
+//!
+//! 经 JSON-RPC 提交执行请求，节点锁定模拟链上的对象、在 CKB-VM 中执行计数器递增，
+//! 再把结果以 PTB 回写模拟链并释放租约
+
+mod common;
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use common::{counter_increment_program, MockChainBackend, PassThroughPlugin, TestNode};
+use dubhe_node::progress::ExecutionPhase;
+use dubhe_node::ExecutionRequest;
+
+const PACKAGE: &str = "0xc0ffee";
+const COUNTER_TYPE: &str = "0xc0ffee::counter::Counter";
+
+#[tokio::test]
+async fn test_offchain_execution_flow() -> Result<()> {
+    let backend = MockChainBackend::new()
+        .with_object("0xa", COUNTER_TYPE, json!({ "value": 1 }))
+        .with_object("0xb", COUNTER_TYPE, json!({ "value": 3 }))
+        .with_package(
+            PACKAGE,
+            counter_increment_program(&[("0xa", 1), ("0xb", 1)]),
+        );
+    let node = TestNode::builder(backend.clone())
+        .with_plugin(Box::new(PassThroughPlugin))
+        .build()
+        .await?;
+
+    let result = node
+        .rpc_result(
+            "dubhe_executeOffchain",
+            json!([{
+                "packageId": PACKAGE,
+                "functionName": "increment",
+                "arguments": [],
+                "sharedObjects": ["0xa", "0xb"],
+                "gasBudget": 10_000_000,
+            }]),
+        )
+        .await?;
+    assert_eq!(result["success"], true, "{}", result);
+
+    // guest 读取锁定版本的字段并各自加一
+    let modified: Vec<(Value, Value)> = result["modifiedObjects"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|object| (object["objectId"].clone(), object["newContent"].clone()))
+        .collect();
+    assert_eq!(
+        modified,
+        vec![
+            (json!("0xa"), json!({ "value": 2 })),
+            (json!("0xb"), json!({ "value": 4 })),
+        ]
+    );
+    assert_eq!(
+        result["modifiedObjects"][0]["changes"]["fieldsModified"],
+        json!(["value"])
+    );
+
+    // 两个对象的更新在同一笔 PTB 中回写，摘要返回给调用方
+    let transactions = backend.transactions();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].1.len(), 2);
+    node.assert_update_submitted("0xa");
+    node.assert_update_submitted("0xb");
+    assert_eq!(result["commits"][0]["digest"], transactions[0].0);
+
+    node.assert_unlocked(&["0xa", "0xb"]);
+
+    let status = node
+        .rpc_result("dubhe_getExecutionStatus", json!([result["sessionId"]]))
+        .await?;
+    assert_eq!(status["status"], "completed");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_locked_object_missing_on_chain_fails() -> Result<()> {
+    let backend = MockChainBackend::new()
+        .with_object("0xa", COUNTER_TYPE, json!({ "value": 1 }))
+        .with_package(PACKAGE, counter_increment_program(&[("0xa", 1)]));
+    let node = TestNode::builder(backend.clone())
+        .with_plugin(Box::new(PassThroughPlugin))
+        .build()
+        .await?;
+
+    let response = node
+        .rpc_call(
+            "dubhe_executeOffchain",
+            json!([{
+                "packageId": PACKAGE,
+                "functionName": "increment",
+                "sharedObjects": ["0xa", "0xmissing"],
+                "gasBudget": 10_000_000,
+            }]),
+        )
+        .await?;
+    assert!(response["error"].is_object(), "{}", response);
+
+    // 已获取的租约被释放，没有任何回写
+    node.assert_unlocked(&["0xa"]);
+    assert!(backend.transactions().is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_execution_progress_phases() -> Result<()> {
+    let backend = MockChainBackend::new()
+        .with_object("0xa", COUNTER_TYPE, json!({ "value": 1 }))
+        .with_package(PACKAGE, counter_increment_program(&[("0xa", 1)]));
+    let node = TestNode::builder(backend.clone())
+        .with_plugin(Box::new(PassThroughPlugin))
+        .build()
+        .await?;
+
+    // 会话开始前订阅
+    let mut events = node.offchain.subscribe_progress("progress-1");
+    node.offchain
+        .execute_offchain(ExecutionRequest {
+            session_id: "progress-1".to_string(),
+            package_id: PACKAGE.to_string(),
+            function_name: "increment".to_string(),
+            arguments: vec![],
+            shared_objects: vec!["0xa".to_string()],
+            gas_budget: 10_000_000,
+            read_only: false,
+        })
+        .await?;
+
+    let mut received = Vec::new();
+    while let Ok(event) = events.recv().await {
+        received.push(event);
+    }
+    let entered: Vec<ExecutionPhase> = received
+        .iter()
+        .filter(|event| event.duration_ms.is_none())
+        .map(|event| event.phase)
+        .collect();
+    assert_eq!(
+        entered,
+        vec![
+            ExecutionPhase::Locking,
+            ExecutionPhase::SyncingState,
+            ExecutionPhase::Executing,
+            ExecutionPhase::SyncingResults,
+            ExecutionPhase::Releasing,
+        ]
+    );
+
+    // 最后一条为释放阶段结束，计数为整个会话的累计值
+    let last = received.last().unwrap();
+    assert_eq!(last.phase, ExecutionPhase::Releasing);
+    assert!(last.duration_ms.is_some());
+    assert_eq!(last.objects_locked, 1);
+    assert!(last.bytes_synced > 0);
+    assert!(last.gas_used > 0);
+    Ok(())
+}
@@ -1,9 +1,547 @@
 //! 告警模块
+//!
+//! 规则按周期对进程内指标求值，每条规则维护 Inactive → Pending → Firing 状态：
+//! 条件持续满足 `duration_secs` 后进入 Firing 并通知一次，条件消失时通知一次 Resolved，
+//! 状态不变的评估周期不会重复通知
 
-pub struct AlertManager;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::metrics::{MetricsCollector, NodeMetrics};
+
+/// 保留的最近告警事件数，供状态页展示
+const RECENT_EVENTS: usize = 50;
+
+/// 告警级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// 阈值比较方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    GreaterThan,
+    LessThan,
+}
+
+/// 告警规则：指标越过阈值时触发
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: String,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    /// 条件需持续满足多久才触发（秒），0 表示立即触发
+    #[serde(default)]
+    pub duration_secs: u64,
+    pub severity: AlertSeverity,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl AlertRule {
+    fn matches(&self, value: f64) -> bool {
+        match self.comparison {
+            Comparison::GreaterThan => value > self.threshold,
+            Comparison::LessThan => value < self.threshold,
+        }
+    }
+}
+
+/// 已触发的告警
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub rule: String,
+    pub metric: String,
+    pub value: f64,
+    pub severity: AlertSeverity,
+    pub message: String,
+    pub triggered_at: u64,
+}
+
+/// 告警状态变更
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertStatus {
+    Firing,
+    Resolved,
+}
+
+/// 发给通知后端的事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertEvent {
+    pub status: AlertStatus,
+    pub alert: Alert,
+}
+
+/// 可按名称读取数值的指标源
+pub trait MetricSource: Send + Sync {
+    fn value(&self, name: &str) -> Option<f64>;
+}
+
+impl MetricSource for MetricsCollector {
+    fn value(&self, name: &str) -> Option<f64> {
+        MetricsCollector::value(self, name)
+    }
+}
+
+impl MetricSource for NodeMetrics {
+    /// 按完整名称（含 `dubhe_` 前缀）读取计数器或仪表盘
+    fn value(&self, name: &str) -> Option<f64> {
+        let family = self
+            .registry()
+            .gather()
+            .into_iter()
+            .find(|family| family.get_name() == name)?;
+        let metric = family.get_metric().first()?;
+        match family.get_field_type() {
+            prometheus::proto::MetricType::COUNTER => Some(metric.get_counter().get_value()),
+            prometheus::proto::MetricType::GAUGE => Some(metric.get_gauge().get_value()),
+            _ => None,
+        }
+    }
+}
+
+/// 告警通知后端
+#[async_trait]
+pub trait AlertNotifier: Send + Sync {
+    async fn notify(&self, event: &AlertEvent) -> Result<()>;
+}
+
+/// 写入日志
+pub struct LogNotifier;
+
+#[async_trait]
+impl AlertNotifier for LogNotifier {
+    async fn notify(&self, event: &AlertEvent) -> Result<()> {
+        match event.status {
+            AlertStatus::Firing => warn!(
+                "ALERT FIRING [{:?}] {}: {}",
+                event.alert.severity, event.alert.rule, event.alert.message
+            ),
+            AlertStatus::Resolved => info!(
+                "ALERT RESOLVED {}: {}",
+                event.alert.rule, event.alert.message
+            ),
+        }
+        Ok(())
+    }
+}
+
+/// 以 JSON POST 到通用 webhook
+pub struct WebhookNotifier {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[async_trait]
+impl AlertNotifier for WebhookNotifier {
+    async fn notify(&self, event: &AlertEvent) -> Result<()> {
+        self.client
+            .post(&self.url)
+            .json(event)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RuleState {
+    Pending { since: Instant },
+    Firing { value: f64 },
+}
+
+/// 告警管理器
+#[derive(Default)]
+pub struct AlertManager {
+    rules: Vec<AlertRule>,
+    // 规则名 → 状态，缺省即 Inactive
+    states: HashMap<String, RuleState>,
+    notifiers: Vec<Arc<dyn AlertNotifier>>,
+    recent: VecDeque<AlertEvent>,
+}
+
+impl std::fmt::Debug for AlertManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlertManager")
+            .field("rules", &self.rules)
+            .field("states", &self.states)
+            .field("notifiers", &self.notifiers.len())
+            .field("recent", &self.recent.len())
+            .finish()
+    }
+}
 
 impl AlertManager {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// 注册告警规则（同名规则会被替换）
+    pub fn add_rule(&mut self, rule: AlertRule) {
+        self.rules.retain(|r| r.name != rule.name);
+        self.states.remove(&rule.name);
+        self.rules.push(rule);
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    /// 替换全部规则（热加载）；定义未变的规则保留当前状态，不会重复通知
+    pub fn replace_rules(&mut self, rules: Vec<AlertRule>) {
+        let unchanged: Vec<String> = rules
+            .iter()
+            .filter(|rule| self.rules.contains(rule))
+            .map(|rule| rule.name.clone())
+            .collect();
+        self.states.retain(|name, _| unchanged.contains(name));
+        self.rules = rules;
+    }
+
+    pub fn add_notifier(&mut self, notifier: Arc<dyn AlertNotifier>) {
+        self.notifiers.push(notifier);
+    }
+
+    /// 用当前指标评估所有规则（无状态，返回所有满足条件的规则）
+    pub fn evaluate(&self, metrics: &MetricsCollector) -> Vec<Alert> {
+        let now = unix_now();
+
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let value = metrics.value(&rule.metric)?;
+                rule.matches(value).then(|| alert(rule, value, now))
+            })
+            .collect()
+    }
+
+    /// 推进各规则状态，返回本次发生的 Firing / Resolved 转换
+    pub fn evaluate_transitions(
+        &mut self,
+        sources: &[Arc<dyn MetricSource>],
+        now: Instant,
+    ) -> Vec<AlertEvent> {
+        let timestamp = unix_now();
+        let mut events = Vec::new();
+
+        for rule in &self.rules {
+            let value = sources.iter().find_map(|source| source.value(&rule.metric));
+            let active = value.is_some_and(|value| rule.matches(value));
+            let hold = Duration::from_secs(rule.duration_secs);
+
+            let next = match (self.states.get(&rule.name).copied(), value) {
+                (None, Some(value)) if active && hold.is_zero() => {
+                    Some(RuleState::Firing { value })
+                }
+                (None, _) if active => Some(RuleState::Pending { since: now }),
+                (Some(RuleState::Pending { since }), Some(value)) if active => {
+                    if now.saturating_duration_since(since) >= hold {
+                        Some(RuleState::Firing { value })
+                    } else {
+                        Some(RuleState::Pending { since })
+                    }
+                }
+                (Some(RuleState::Firing { .. }), Some(value)) if active => {
+                    Some(RuleState::Firing { value })
+                }
+                _ => None,
+            };
+
+            match (self.states.get(&rule.name), &next) {
+                (Some(RuleState::Firing { .. }), Some(RuleState::Firing { .. })) => {}
+                (_, Some(RuleState::Firing { value })) => events.push(AlertEvent {
+                    status: AlertStatus::Firing,
+                    alert: alert(rule, *value, timestamp),
+                }),
+                (Some(RuleState::Firing { value: last }), None) => events.push(AlertEvent {
+                    status: AlertStatus::Resolved,
+                    alert: alert(rule, value.unwrap_or(*last), timestamp),
+                }),
+                _ => {}
+            }
+
+            match next {
+                Some(state) => {
+                    self.states.insert(rule.name.clone(), state);
+                }
+                None => {
+                    self.states.remove(&rule.name);
+                }
+            }
+        }
+
+        self.remember(&events);
+        events
+    }
+
+    fn remember(&mut self, events: &[AlertEvent]) {
+        for event in events {
+            if self.recent.len() == RECENT_EVENTS {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(event.clone());
+        }
+    }
+
+    /// 最近的告警事件，新的在前
+    pub fn recent_events(&self) -> Vec<AlertEvent> {
+        self.recent.iter().rev().cloned().collect()
+    }
+
+    /// 当前处于 Firing 状态的规则
+    pub fn firing(&self) -> Vec<String> {
+        self.states
+            .iter()
+            .filter(|(_, state)| matches!(state, RuleState::Firing { .. }))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// 评估一次并把状态转换发给所有通知后端
+    pub async fn tick(manager: &Mutex<Self>, sources: &[Arc<dyn MetricSource>]) {
+        let (events, notifiers) = {
+            let mut manager = manager.lock().await;
+            let events = manager.evaluate_transitions(sources, Instant::now());
+            (events, manager.notifiers.clone())
+        };
+
+        for event in &events {
+            for notifier in &notifiers {
+                if let Err(e) = notifier.notify(event).await {
+                    error!("Failed to deliver alert {}: {}", event.alert.rule, e);
+                }
+            }
+        }
+    }
+
+    /// 不经规则评估直接发出一次告警（例如威胁检测命中），不进入规则状态机
+    pub async fn raise(manager: &Mutex<Self>, alert: Alert) {
+        let event = AlertEvent {
+            status: AlertStatus::Firing,
+            alert,
+        };
+        let notifiers = {
+            let mut manager = manager.lock().await;
+            manager.remember(std::slice::from_ref(&event));
+            manager.notifiers.clone()
+        };
+        for notifier in &notifiers {
+            if let Err(e) = notifier.notify(&event).await {
+                error!("Failed to deliver alert {}: {}", event.alert.rule, e);
+            }
+        }
+    }
+
+    /// 周期性评估
+    pub fn spawn_evaluator(
+        manager: Arc<Mutex<Self>>,
+        sources: Vec<Arc<dyn MetricSource>>,
+        interval: Duration,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                Self::tick(&manager, &sources).await;
+            }
+        })
+    }
+}
+
+fn alert(rule: &AlertRule, value: f64, triggered_at: u64) -> Alert {
+    Alert {
+        rule: rule.name.clone(),
+        metric: rule.metric.clone(),
+        value,
+        severity: rule.severity,
+        message: format!("{}: {} = {}", rule.description, rule.metric, value),
+        triggered_at,
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threshold_rule() {
+        let metrics = MetricsCollector::new();
+        let mut manager = AlertManager::new();
+        manager.add_rule(AlertRule {
+            name: "HighQueue".to_string(),
+            metric: "queue_depth".to_string(),
+            comparison: Comparison::GreaterThan,
+            threshold: 10.0,
+            duration_secs: 0,
+            severity: AlertSeverity::Warning,
+            description: "Queue is backing up".to_string(),
+        });
+
+        // 指标缺失时不触发
+        assert!(manager.evaluate(&metrics).is_empty());
+
+        metrics.set_gauge("queue_depth", 5.0);
+        assert!(manager.evaluate(&metrics).is_empty());
+
+        metrics.set_gauge("queue_depth", 11.0);
+        let alerts = manager.evaluate(&metrics);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].rule, "HighQueue");
+        assert_eq!(alerts[0].severity, AlertSeverity::Warning);
+    }
+
+    /// 记录收到的通知
+    #[derive(Default)]
+    struct Recorder(std::sync::Mutex<Vec<AlertEvent>>);
+
+    #[async_trait]
+    impl AlertNotifier for Recorder {
+        async fn notify(&self, event: &AlertEvent) -> Result<()> {
+            self.0.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notifies_once_per_transition() {
+        let metrics = Arc::new(MetricsCollector::new());
+        let recorder = Arc::new(Recorder::default());
+        let mut manager = AlertManager::new();
+        manager.add_rule(AlertRule {
+            name: "HighLatency".to_string(),
+            metric: "latency_ms".to_string(),
+            comparison: Comparison::GreaterThan,
+            threshold: 100.0,
+            duration_secs: 0,
+            severity: AlertSeverity::Critical,
+            description: "Latency too high".to_string(),
+        });
+        manager.add_notifier(recorder.clone());
+        let manager = Mutex::new(manager);
+        let sources: Vec<Arc<dyn MetricSource>> = vec![metrics.clone()];
+
+        for latency in [50.0, 150.0, 180.0, 200.0, 170.0, 40.0, 30.0] {
+            metrics.set_gauge("latency_ms", latency);
+            AlertManager::tick(&manager, &sources).await;
+        }
+
+        let events = recorder.0.lock().unwrap().clone();
+        let statuses: Vec<AlertStatus> = events.iter().map(|e| e.status).collect();
+        assert_eq!(statuses, vec![AlertStatus::Firing, AlertStatus::Resolved]);
+        assert_eq!(events[0].alert.value, 150.0);
+        assert_eq!(events[1].alert.value, 40.0);
+        assert!(manager.lock().await.firing().is_empty());
+
+        // 状态页展示的最近事件，新的在前
+        let recent: Vec<AlertStatus> = manager
+            .lock()
+            .await
+            .recent_events()
+            .iter()
+            .map(|e| e.status)
+            .collect();
+        assert_eq!(recent, vec![AlertStatus::Resolved, AlertStatus::Firing]);
+    }
+
+    #[tokio::test]
+    async fn test_raise_notifies_without_rule() {
+        let recorder = Arc::new(Recorder::default());
+        let mut manager = AlertManager::new();
+        manager.add_notifier(recorder.clone());
+        let manager = Mutex::new(manager);
+
+        AlertManager::raise(
+            &manager,
+            Alert {
+                rule: "threat:sender_rate".to_string(),
+                metric: "0xspam".to_string(),
+                value: 11.0,
+                severity: AlertSeverity::Critical,
+                message: "sender rate exceeded".to_string(),
+                triggered_at: unix_now(),
+            },
+        )
+        .await;
+
+        let events = recorder.0.lock().unwrap().clone();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].status, AlertStatus::Firing);
+        assert!(manager.lock().await.firing().is_empty());
+    }
+
+    #[test]
+    fn test_pending_duration_and_reload() {
+        let metrics: Arc<dyn MetricSource> = Arc::new(MetricsCollector::new());
+        let collector = MetricsCollector::new();
+        collector.set_gauge("queue_depth", 20.0);
+        let sources: Vec<Arc<dyn MetricSource>> = vec![Arc::new(collector), metrics];
+
+        let rule = AlertRule {
+            name: "QueueBacklog".to_string(),
+            metric: "queue_depth".to_string(),
+            comparison: Comparison::GreaterThan,
+            threshold: 10.0,
+            duration_secs: 30,
+            severity: AlertSeverity::Warning,
+            description: String::new(),
+        };
+        let mut manager = AlertManager::new();
+        manager.add_rule(rule.clone());
+
+        let start = Instant::now();
+        assert!(manager.evaluate_transitions(&sources, start).is_empty());
+        assert!(manager
+            .evaluate_transitions(&sources, start + Duration::from_secs(10))
+            .is_empty());
+        let fired = manager.evaluate_transitions(&sources, start + Duration::from_secs(30));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].status, AlertStatus::Firing);
+
+        // 定义未变的规则重载后保持 Firing，不重复通知
+        manager.replace_rules(vec![rule.clone()]);
+        assert!(manager
+            .evaluate_transitions(&sources, start + Duration::from_secs(40))
+            .is_empty());
+
+        // 阈值变化视为新规则，重新经历 Pending
+        manager.replace_rules(vec![AlertRule {
+            threshold: 15.0,
+            ..rule
+        }]);
+        assert!(manager.firing().is_empty());
+        assert!(manager
+            .evaluate_transitions(&sources, start + Duration::from_secs(50))
+            .is_empty());
     }
 }
@@ -1,9 +1,553 @@
-//! 仪表板模块
+//! 内置状态页
+//!
+//! 与 Prometheus 导出端共用一个服务：`GET /status` 返回自包含的 HTML 页面（样式与脚本内联，
+//! 不引用任何外部资源），页面定时拉取 `GET /status.json` 刷新。数据来自调度器等组件共享的
+//! [`NodeMetrics`] 句柄、告警管理器，以及节点实现的 [`StatusProvider`]。
+//!
+//! 配置了 [`StatusAuth`] 时 `/status.json` 需要通过认证；HTML 页面本身不含数据，
+//! 收到 401 时由页面脚本提示输入 API Key
 
-pub struct Dashboard;
+use async_trait::async_trait;
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::VecDeque;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+use crate::alerts::{AlertEvent, AlertManager};
+use crate::metrics::NodeMetrics;
+
+/// 计算近期 TPS 的时间窗口
+const TPS_WINDOW: Duration = Duration::from_secs(60);
+
+/// 构建与版本信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInfo {
+    pub version: String,
+    /// 构建时由 `DUBHE_GIT_COMMIT` 环境变量注入
+    pub git_commit: Option<String>,
+    pub started_at: u64,
+    pub uptime_secs: u64,
+}
+
+/// 调度器概况
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchedulerSummary {
+    pub strategy: String,
+    pub worker_threads: usize,
+    pub queue_depth: i64,
+    /// 最近一分钟内的平均 TPS
+    pub recent_tps: f64,
+    pub transactions_processed: u64,
+    pub transactions_failed: u64,
+}
+
+/// 单条链的适配器状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainHealth {
+    pub chain: String,
+    /// `active` / `degraded` / `disabled`
+    pub state: String,
+    pub error: Option<String>,
+}
+
+/// 缓存命中情况
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheSummary {
+    pub name: String,
+    pub hits: u64,
+    pub misses: u64,
+    /// 尚无访问时为 `None`
+    pub hit_rate: Option<f64>,
+}
+
+impl CacheSummary {
+    pub fn new(name: impl Into<String>, hits: u64, misses: u64) -> Self {
+        let total = hits + misses;
+        Self {
+            name: name.into(),
+            hits,
+            misses,
+            hit_rate: (total > 0).then(|| hits as f64 / total as f64),
+        }
+    }
+}
+
+/// 链下执行概况
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OffchainSummary {
+    pub active_sessions: usize,
+    pub locked_objects: usize,
+    pub pending_executions: usize,
+    pub hot_objects: usize,
+}
+
+/// 告警概况
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertSummary {
+    /// 处于 Firing 状态的规则
+    pub firing: Vec<String>,
+    /// 最近的告警事件，新的在前
+    pub recent: Vec<AlertEvent>,
+}
+
+/// 节点组件的状态，由 [`StatusProvider`] 从各组件句柄收集
+#[derive(Debug, Clone, Default)]
+pub struct NodeStatus {
+    pub strategy: String,
+    pub worker_threads: usize,
+    pub chains: Vec<ChainHealth>,
+    pub caches: Vec<CacheSummary>,
+    pub offchain: OffchainSummary,
+}
+
+/// `/status.json` 的内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusReport {
+    pub build: BuildInfo,
+    pub scheduler: SchedulerSummary,
+    pub chains: Vec<ChainHealth>,
+    pub caches: Vec<CacheSummary>,
+    pub offchain: OffchainSummary,
+    pub alerts: AlertSummary,
+    pub generated_at: u64,
+}
+
+/// 节点组件状态来源，由节点实现
+#[async_trait]
+pub trait StatusProvider: Send + Sync {
+    async fn node_status(&self) -> NodeStatus;
+}
+
+/// `/status.json` 的访问检查，由节点接入 API Key 认证
+pub trait StatusAuth: Send + Sync {
+    /// `authorization` 为请求的 Authorization 头；拒绝时返回原因
+    fn authorize(&self, authorization: Option<&str>, ip: IpAddr) -> Result<(), String>;
+}
+
+/// 状态页
+pub struct Dashboard {
+    metrics: Arc<NodeMetrics>,
+    alerts: Option<Arc<Mutex<AlertManager>>>,
+    provider: Option<Arc<dyn StatusProvider>>,
+    auth: Option<Arc<dyn StatusAuth>>,
+    started_at: u64,
+    started: Instant,
+    // (采样时间, 累计处理交易数)
+    tps_samples: std::sync::Mutex<VecDeque<(Instant, u64)>>,
+}
 
 impl Dashboard {
-    pub fn new() -> Self {
-        Self
+    pub fn new(metrics: Arc<NodeMetrics>) -> Self {
+        Self {
+            metrics,
+            alerts: None,
+            provider: None,
+            auth: None,
+            started_at: unix_now(),
+            started: Instant::now(),
+            tps_samples: std::sync::Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// 展示该告警管理器中的 Firing 规则与最近事件
+    pub fn with_alerts(mut self, alerts: Arc<Mutex<AlertManager>>) -> Self {
+        self.alerts = Some(alerts);
+        self
+    }
+
+    pub fn with_provider(mut self, provider: Arc<dyn StatusProvider>) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    pub fn with_auth(mut self, auth: Arc<dyn StatusAuth>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// 收集当前状态
+    pub async fn report(&self) -> StatusReport {
+        let node = match &self.provider {
+            Some(provider) => provider.node_status().await,
+            None => NodeStatus::default(),
+        };
+        let alerts = match &self.alerts {
+            Some(alerts) => {
+                let alerts = alerts.lock().await;
+                let mut firing = alerts.firing();
+                firing.sort();
+                AlertSummary {
+                    firing,
+                    recent: alerts.recent_events(),
+                }
+            }
+            None => AlertSummary::default(),
+        };
+
+        let processed = self.metrics.transactions_processed.get();
+        StatusReport {
+            build: BuildInfo {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                git_commit: option_env!("DUBHE_GIT_COMMIT").map(str::to_string),
+                started_at: self.started_at,
+                uptime_secs: self.started.elapsed().as_secs(),
+            },
+            scheduler: SchedulerSummary {
+                strategy: node.strategy,
+                worker_threads: node.worker_threads,
+                queue_depth: self.metrics.scheduler_queue_length.get(),
+                recent_tps: self.recent_tps(processed, Instant::now()),
+                transactions_processed: processed,
+                transactions_failed: self.metrics.transactions_failed.get(),
+            },
+            chains: node.chains,
+            caches: node.caches,
+            offchain: node.offchain,
+            alerts,
+            generated_at: unix_now(),
+        }
+    }
+
+    /// 记录一次累计处理数采样，返回窗口内的平均 TPS
+    fn recent_tps(&self, processed: u64, now: Instant) -> f64 {
+        let mut samples = self.tps_samples.lock().unwrap();
+        samples.push_back((now, processed));
+        while samples.len() > 2 && now.saturating_duration_since(samples[1].0) >= TPS_WINDOW {
+            samples.pop_front();
+        }
+        let (first_at, first) = samples[0];
+        let elapsed = now.saturating_duration_since(first_at).as_secs_f64();
+        if elapsed > 0.0 {
+            processed.saturating_sub(first) as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+
+    /// `/status` 与 `/status.json` 路由；认证需要对端地址，须以
+    /// `into_make_service_with_connect_info::<SocketAddr>()` 提供服务
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/status", get(Self::handle_page))
+            .route("/status.json", get(Self::handle_status))
+            .with_state(self)
+    }
+
+    async fn handle_page() -> Html<&'static str> {
+        Html(STATUS_PAGE)
+    }
+
+    async fn handle_status(
+        State(dashboard): State<Arc<Self>>,
+        ConnectInfo(peer): ConnectInfo<SocketAddr>,
+        headers: HeaderMap,
+    ) -> Response {
+        if let Some(auth) = &dashboard.auth {
+            let authorization = headers
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok());
+            if let Err(reason) = auth.authorize(authorization, peer.ip()) {
+                return (StatusCode::UNAUTHORIZED, Json(json!({ "error": reason })))
+                    .into_response();
+            }
+        }
+        Json(dashboard.report().await).into_response()
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 状态页，样式与脚本全部内联
+const STATUS_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Dubhe Channel status</title>
+<style>
+  body { font-family: system-ui, sans-serif; margin: 0; background: #f4f5f7; color: #1d2330; }
+  header { background: #1d2330; color: #fff; padding: 12px 24px; display: flex; justify-content: space-between; }
+  main { display: grid; grid-template-columns: repeat(auto-fit, minmax(320px, 1fr)); gap: 16px; padding: 16px 24px; }
+  section { background: #fff; border-radius: 6px; padding: 12px 16px; box-shadow: 0 1px 2px rgba(0,0,0,.08); }
+  h2 { font-size: 15px; margin: 0 0 8px; }
+  table { width: 100%; border-collapse: collapse; font-size: 13px; }
+  td, th { text-align: left; padding: 4px 6px; border-bottom: 1px solid #eceef2; }
+  .active, .Resolved { color: #1a7f37; } .degraded, .Firing { color: #cf222e; } .disabled { color: #8c959f; }
+  #error { color: #cf222e; padding: 0 24px; }
+</style>
+</head>
+<body>
+<header><strong>Dubhe Channel</strong><span id="build">loading…</span></header>
+<p id="error"></p>
+<main>
+  <section><h2>Scheduler</h2><table id="scheduler"></table></section>
+  <section><h2>Chains</h2><table id="chains"></table></section>
+  <section><h2>Caches</h2><table id="caches"></table></section>
+  <section><h2>Offchain sessions</h2><table id="offchain"></table></section>
+  <section><h2>Alerts</h2><table id="alerts"></table></section>
+</main>
+<script>
+const KEY = "dubheStatusApiKey";
+
+function fill(id, header, rows) {
+  const table = document.getElementById(id);
+  table.replaceChildren();
+  const line = (cells, tag) => {
+    const tr = table.insertRow();
+    for (const cell of cells) {
+      const td = document.createElement(tag);
+      const [text, cls] = Array.isArray(cell) ? cell : [cell, ""];
+      td.textContent = text ?? "-";
+      if (cls) td.className = cls;
+      tr.appendChild(td);
+    }
+  };
+  if (header) line(header, "th");
+  for (const row of rows) line(row, "td");
+}
+
+function percent(rate) {
+  return rate == null ? "-" : (rate * 100).toFixed(1) + "%";
+}
+
+function render(s) {
+  const b = s.build;
+  document.getElementById("build").textContent =
+    `v${b.version}${b.gitCommit ? " (" + b.gitCommit.slice(0, 8) + ")" : ""} · up ${b.uptimeSecs}s`;
+  const sc = s.scheduler;
+  fill("scheduler", null, [
+    ["Strategy", sc.strategy || "-"], ["Workers", sc.workerThreads], ["Queue depth", sc.queueDepth],
+    ["Recent TPS", sc.recentTps.toFixed(1)], ["Processed", sc.transactionsProcessed],
+    ["Failed", sc.transactionsFailed],
+  ]);
+  fill("chains", ["Chain", "State", "Error"],
+    s.chains.map(c => [c.chain, [c.state, c.state], c.error]));
+  fill("caches", ["Cache", "Hits", "Misses", "Hit rate"],
+    s.caches.map(c => [c.name, c.hits, c.misses, percent(c.hitRate)]));
+  const o = s.offchain;
+  fill("offchain", null, [
+    ["Active sessions", o.activeSessions], ["Locked objects", o.lockedObjects],
+    ["Pending executions", o.pendingExecutions], ["Hot objects", o.hotObjects],
+  ]);
+  fill("alerts", ["Time", "Status", "Rule", "Message"], s.alerts.recent.map(e => [
+    new Date(e.alert.triggered_at * 1000).toLocaleTimeString(),
+    [e.status, e.status], e.alert.rule, e.alert.message,
+  ]));
+}
+
+async function refresh() {
+  const headers = {};
+  const key = sessionStorage.getItem(KEY);
+  if (key) headers["Authorization"] = "Bearer " + key;
+  try {
+    const response = await fetch("status.json", { headers, cache: "no-store" });
+    if (response.status === 401) {
+      const entered = prompt("API key for the status page");
+      if (entered) sessionStorage.setItem(KEY, entered);
+      throw new Error("unauthorized");
+    }
+    render(await response.json());
+    document.getElementById("error").textContent = "";
+  } catch (e) {
+    document.getElementById("error").textContent = "Failed to refresh: " + e.message;
+  }
+}
+
+refresh();
+setInterval(refresh, 5000);
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::{Alert, AlertSeverity};
+    use crate::exporter::MetricsExporter;
+    use serde_json::Value;
+
+    struct FixedStatus;
+
+    #[async_trait]
+    impl StatusProvider for FixedStatus {
+        async fn node_status(&self) -> NodeStatus {
+            NodeStatus {
+                strategy: "SolanaParallel".to_string(),
+                worker_threads: 8,
+                chains: vec![ChainHealth {
+                    chain: "Sui".to_string(),
+                    state: "active".to_string(),
+                    error: None,
+                }],
+                caches: vec![CacheSummary::new("compilation", 3, 1)],
+                offchain: OffchainSummary {
+                    active_sessions: 2,
+                    ..Default::default()
+                },
+            }
+        }
+    }
+
+    /// 只接受 `Bearer secret`
+    struct SecretAuth;
+
+    impl StatusAuth for SecretAuth {
+        fn authorize(&self, authorization: Option<&str>, _ip: IpAddr) -> Result<(), String> {
+            match authorization {
+                Some("Bearer secret") => Ok(()),
+                _ => Err("status page requires an API key".to_string()),
+            }
+        }
+    }
+
+    async fn get(addr: SocketAddr, path: &str, key: Option<&str>) -> (StatusCode, String) {
+        let mut request = hyper::Request::get(format!("http://{}{}", addr, path));
+        if let Some(key) = key {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", key));
+        }
+        let response = hyper::Client::new()
+            .request(request.body(hyper::Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_status_json_schema() {
+        let metrics = Arc::new(NodeMetrics::new().unwrap());
+        metrics.transactions_processed.inc_by(5);
+        metrics.scheduler_queue_length.set(3);
+        let alerts = Arc::new(Mutex::new(AlertManager::new()));
+        AlertManager::raise(
+            &alerts,
+            Alert {
+                rule: "HighQueue".to_string(),
+                metric: "queue_depth".to_string(),
+                value: 11.0,
+                severity: AlertSeverity::Warning,
+                message: "queue is backing up".to_string(),
+                triggered_at: unix_now(),
+            },
+        )
+        .await;
+        let dashboard = Dashboard::new(metrics.clone())
+            .with_alerts(alerts)
+            .with_provider(Arc::new(FixedStatus))
+            .with_auth(Arc::new(SecretAuth));
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let exporter = MetricsExporter::new(metrics).with_dashboard(Arc::new(dashboard));
+        tokio::spawn(async move { exporter.serve(listener).await });
+
+        let (status, _) = get(addr, "/status.json", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = get(addr, "/status.json", Some("wrong")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, body) = get(addr, "/status.json", Some("secret")).await;
+        assert_eq!(status, StatusCode::OK);
+        let report: Value = serde_json::from_str(&body).unwrap();
+        for (section, fields) in [
+            (
+                "build",
+                &["version", "gitCommit", "startedAt", "uptimeSecs"][..],
+            ),
+            (
+                "scheduler",
+                &[
+                    "strategy",
+                    "workerThreads",
+                    "queueDepth",
+                    "recentTps",
+                    "transactionsProcessed",
+                    "transactionsFailed",
+                ][..],
+            ),
+            (
+                "offchain",
+                &[
+                    "activeSessions",
+                    "lockedObjects",
+                    "pendingExecutions",
+                    "hotObjects",
+                ][..],
+            ),
+            ("alerts", &["firing", "recent"][..]),
+        ] {
+            for field in fields {
+                assert!(
+                    report[section].get(field).is_some(),
+                    "missing {}.{} in {}",
+                    section,
+                    field,
+                    body
+                );
+            }
+        }
+        assert!(report["generatedAt"].is_u64());
+        assert_eq!(report["scheduler"]["strategy"], "SolanaParallel");
+        assert_eq!(report["scheduler"]["queueDepth"], 3);
+        assert_eq!(report["scheduler"]["transactionsProcessed"], 5);
+        assert_eq!(
+            report["chains"],
+            json!([{ "chain": "Sui", "state": "active", "error": null }])
+        );
+        assert_eq!(
+            report["caches"],
+            json!([{ "name": "compilation", "hits": 3, "misses": 1, "hitRate": 0.75 }])
+        );
+        assert_eq!(report["offchain"]["activeSessions"], 2);
+        assert_eq!(report["alerts"]["recent"][0]["alert"]["rule"], "HighQueue");
+        assert_eq!(report["alerts"]["recent"][0]["status"], "Firing");
+
+        // 页面不需要认证，且不引用外部资源
+        let (status, page) = get(addr, "/status", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(page.contains("status.json"));
+        assert!(!page.contains("http://") && !page.contains("https://"));
+
+        // 原有的指标端点不受影响
+        let (status, _) = get(addr, "/metrics", None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[test]
+    fn test_recent_tps_window() {
+        let dashboard = Dashboard::new(Arc::new(NodeMetrics::new().unwrap()));
+        let start = Instant::now();
+        assert_eq!(dashboard.recent_tps(0, start), 0.0);
+        assert_eq!(
+            dashboard.recent_tps(100, start + Duration::from_secs(10)),
+            10.0
+        );
+        // 窗口外的采样被丢弃，速率只反映最近一分钟
+        let rate = dashboard.recent_tps(700, start + Duration::from_secs(70));
+        assert_eq!(rate, 10.0);
     }
 }
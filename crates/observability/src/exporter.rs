@@ -0,0 +1,77 @@
+//! Prometheus 导出端
+//!
+//! 在独立端口上提供 `GET /metrics`，供 Kubernetes 中的 Prometheus 抓取；
+//! 挂载状态页时同一端口还提供 `GET /status` 与 `GET /status.json`
+
+use anyhow::Result;
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use std::net::{SocketAddr, TcpListener};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::dashboards::Dashboard;
+use crate::metrics::NodeMetrics;
+
+/// Prometheus 文本格式的 Content-Type
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// 指标导出服务
+pub struct MetricsExporter {
+    metrics: Arc<NodeMetrics>,
+    dashboard: Option<Arc<Dashboard>>,
+}
+
+impl MetricsExporter {
+    pub fn new(metrics: Arc<NodeMetrics>) -> Self {
+        Self {
+            metrics,
+            dashboard: None,
+        }
+    }
+
+    /// 在同一端口上挂载状态页
+    pub fn with_dashboard(mut self, dashboard: Arc<Dashboard>) -> Self {
+        self.dashboard = Some(dashboard);
+        self
+    }
+
+    pub async fn start(&self, bind_addr: &str) -> Result<()> {
+        let listener = TcpListener::bind(bind_addr)?;
+        info!("Prometheus exporter listening on {}", bind_addr);
+        self.serve(listener).await
+    }
+
+    /// 在已绑定的监听器上提供服务
+    pub async fn serve(&self, listener: TcpListener) -> Result<()> {
+        let mut app = Router::new()
+            .route("/metrics", get(Self::handle_metrics))
+            .with_state(self.metrics.clone());
+        if let Some(dashboard) = &self.dashboard {
+            app = app.merge(dashboard.clone().router());
+        }
+
+        listener.set_nonblocking(true)?;
+        hyper::Server::from_tcp(listener)?
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+            .await?;
+        Ok(())
+    }
+
+    async fn handle_metrics(State(metrics): State<Arc<NodeMetrics>>) -> impl IntoResponse {
+        match metrics.encode() {
+            Ok(body) => {
+                (StatusCode::OK, [(header::CONTENT_TYPE, CONTENT_TYPE)], body).into_response()
+            }
+            Err(e) => {
+                error!("Failed to encode metrics: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
@@ -4,18 +4,39 @@
 
 pub mod alerts;
 pub mod dashboards;
+pub mod exporter;
 pub mod metrics;
 pub mod tracing_ext;
 
+pub use alerts::{
+    Alert, AlertEvent, AlertManager, AlertNotifier, AlertRule, AlertSeverity, AlertStatus,
+    Comparison, LogNotifier, MetricSource, WebhookNotifier,
+};
+pub use dashboards::{
+    BuildInfo, CacheSummary, ChainHealth, Dashboard, NodeStatus, OffchainSummary, StatusAuth,
+    StatusProvider, StatusReport,
+};
+pub use exporter::MetricsExporter;
+pub use metrics::{MetricsCollector, MetricsSnapshot, NodeMetrics};
+pub use tracing_ext::{build_subscriber, init_tracing, TracingConfig, TracingGuard, TRACE_TARGET};
+
 use anyhow::Result;
 
-/// 可观测性管理器
+/// 可观测性管理器：持有全局日志与追踪的生命周期，释放时导出剩余的 span
 pub struct ObservabilityManager {
-    // TODO: 实现可观测性功能
+    tracing: TracingGuard,
 }
 
 impl ObservabilityManager {
-    pub fn new() -> Result<Self> {
-        Ok(Self {})
+    /// 安装全局 subscriber，`log_filter` 为 `RUST_LOG` 语法（环境变量优先）
+    pub fn init(log_filter: &str, tracing: &TracingConfig) -> Result<Self> {
+        Ok(Self {
+            tracing: init_tracing(log_filter, tracing)?,
+        })
+    }
+
+    /// 是否向 OTLP 端点导出 trace
+    pub fn is_exporting_traces(&self) -> bool {
+        self.tracing.is_exporting()
     }
 }
@@ -1,9 +1,315 @@
 //! 指标收集模块
+//!
+//! - `MetricsCollector`：进程内的计数器 / 仪表盘，供告警规则读取
+//! - `NodeMetrics`：Prometheus 注册表与类型化指标句柄，由调度器、加载器和 VM 运行时共享
 
-pub struct MetricsCollector;
+use anyhow::Result;
+use prometheus::{
+    Encoder, Gauge, Histogram, HistogramOpts, IntCounter, IntGauge, Registry, TextEncoder,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// 指标收集器
+///
+/// 进程内的计数器 / 仪表盘存储，告警规则和导出端都从这里读取
+#[derive(Debug, Default)]
+pub struct MetricsCollector {
+    counters: RwLock<HashMap<String, u64>>,
+    gauges: RwLock<HashMap<String, f64>>,
+}
 
 impl MetricsCollector {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// 计数器累加
+    pub fn inc_counter(&self, name: &str, value: u64) {
+        let mut counters = self.counters.write().unwrap();
+        *counters.entry(name.to_string()).or_insert(0) += value;
+    }
+
+    /// 设置仪表盘数值
+    pub fn set_gauge(&self, name: &str, value: f64) {
+        self.gauges
+            .write()
+            .unwrap()
+            .insert(name.to_string(), value);
+    }
+
+    /// 读取计数器（不存在时为 0）
+    pub fn counter(&self, name: &str) -> u64 {
+        self.counters
+            .read()
+            .unwrap()
+            .get(name)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// 读取仪表盘数值
+    pub fn gauge(&self, name: &str) -> Option<f64> {
+        self.gauges.read().unwrap().get(name).copied()
+    }
+
+    /// 按名称读取指标，计数器优先于仪表盘
+    pub fn value(&self, name: &str) -> Option<f64> {
+        if let Some(value) = self.counters.read().unwrap().get(name) {
+            return Some(*value as f64);
+        }
+        self.gauge(name)
+    }
+
+    /// 导出当前所有指标
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            counters: self.counters.read().unwrap().clone(),
+            gauges: self.gauges.read().unwrap().clone(),
+        }
+    }
+}
+
+/// 指标快照
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub counters: HashMap<String, u64>,
+    pub gauges: HashMap<String, f64>,
+}
+
+/// 节点级 Prometheus 指标
+pub struct NodeMetrics {
+    registry: Registry,
+    pub transactions_processed: IntCounter,
+    pub transactions_failed: IntCounter,
+    pub batch_execution_seconds: Histogram,
+    pub transaction_gas_used: Histogram,
+    pub scheduler_queue_length: IntGauge,
+    /// 批次在调度器提交队列中的等待时长
+    pub batch_queue_seconds: Histogram,
+    /// 提交队列已满而被拒绝的批次数
+    pub scheduler_rejected_batches: IntCounter,
+    /// 最近一个批次的进程 CPU 利用率（0-1，按核数归一化）
+    pub batch_cpu_utilization: Gauge,
+    /// 最近一个批次前后的常驻内存变化
+    pub batch_rss_delta_bytes: IntGauge,
+    /// 最近一个批次期间的常驻内存峰值
+    pub batch_peak_rss_bytes: IntGauge,
+    /// 最近一个批次期间进程读写的字节数
+    pub batch_io_bytes: IntGauge,
+    pub cache_hit_ratio: Gauge,
+    /// 本轮后台重编译已完成的缓存条目数
+    pub recompile_completed: IntGauge,
+    /// 本轮后台重编译失败的缓存条目数
+    pub recompile_failed: IntGauge,
+    /// 本轮后台重编译尚未处理的缓存条目数
+    pub recompile_remaining: IntGauge,
+    pub active_vm_instances: IntGauge,
+    /// 交易池中可立即执行（nonce 连续）的交易数
+    pub mempool_pending: IntGauge,
+    /// 交易池中因 nonce 空缺而等待的交易数
+    pub mempool_queued: IntGauge,
+}
+
+impl NodeMetrics {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new_custom(Some("dubhe".to_string()), None)?;
+
+        let transactions_processed = IntCounter::new(
+            "transactions_processed_total",
+            "Transactions executed by the scheduler",
+        )?;
+        let transactions_failed = IntCounter::new(
+            "transactions_failed_total",
+            "Transactions that finished unsuccessfully",
+        )?;
+        let batch_execution_seconds = Histogram::with_opts(HistogramOpts::new(
+            "batch_execution_seconds",
+            "End-to-end latency of a scheduler batch",
+        ))?;
+        let transaction_gas_used = Histogram::with_opts(
+            HistogramOpts::new("transaction_gas_used", "Gas used per transaction").buckets(
+                prometheus::exponential_buckets(1_000.0, 4.0, 10)?,
+            ),
+        )?;
+        let scheduler_queue_length = IntGauge::new(
+            "scheduler_queue_length",
+            "Transactions queued or executing in the scheduler submission queue",
+        )?;
+        let batch_queue_seconds = Histogram::with_opts(HistogramOpts::new(
+            "batch_queue_seconds",
+            "Time a batch waited in the scheduler submission queue",
+        ))?;
+        let scheduler_rejected_batches = IntCounter::new(
+            "scheduler_rejected_batches_total",
+            "Batches rejected because the scheduler submission queue was full",
+        )?;
+        let batch_cpu_utilization = Gauge::new(
+            "scheduler_batch_cpu_utilization",
+            "Process CPU utilization during the last batch, normalized by core count",
+        )?;
+        let batch_rss_delta_bytes = IntGauge::new(
+            "scheduler_batch_rss_delta_bytes",
+            "Resident memory change across the last batch",
+        )?;
+        let batch_peak_rss_bytes = IntGauge::new(
+            "scheduler_batch_peak_rss_bytes",
+            "Peak resident memory observed during the last batch",
+        )?;
+        let batch_io_bytes = IntGauge::new(
+            "scheduler_batch_io_bytes",
+            "Bytes read and written by the process during the last batch",
+        )?;
+        let cache_hit_ratio = Gauge::new(
+            "compilation_cache_hit_ratio",
+            "Hit ratio of the compilation cache",
+        )?;
+        let recompile_completed = IntGauge::new(
+            "compilation_recompile_completed",
+            "Cached artifacts recompiled since the last artifact version change",
+        )?;
+        let recompile_failed = IntGauge::new(
+            "compilation_recompile_failed",
+            "Cached artifacts that failed to recompile since the last artifact version change",
+        )?;
+        let recompile_remaining = IntGauge::new(
+            "compilation_recompile_remaining",
+            "Cached artifacts still queued for background recompilation",
+        )?;
+        let active_vm_instances =
+            IntGauge::new("vm_active_instances", "VM instances currently alive")?;
+        let mempool_pending = IntGauge::new(
+            "mempool_pending_transactions",
+            "Mempool transactions ready for execution",
+        )?;
+        let mempool_queued = IntGauge::new(
+            "mempool_queued_transactions",
+            "Mempool transactions held back by a nonce gap",
+        )?;
+
+        registry.register(Box::new(transactions_processed.clone()))?;
+        registry.register(Box::new(transactions_failed.clone()))?;
+        registry.register(Box::new(batch_execution_seconds.clone()))?;
+        registry.register(Box::new(transaction_gas_used.clone()))?;
+        registry.register(Box::new(scheduler_queue_length.clone()))?;
+        registry.register(Box::new(batch_queue_seconds.clone()))?;
+        registry.register(Box::new(scheduler_rejected_batches.clone()))?;
+        registry.register(Box::new(batch_cpu_utilization.clone()))?;
+        registry.register(Box::new(batch_rss_delta_bytes.clone()))?;
+        registry.register(Box::new(batch_peak_rss_bytes.clone()))?;
+        registry.register(Box::new(batch_io_bytes.clone()))?;
+        registry.register(Box::new(cache_hit_ratio.clone()))?;
+        registry.register(Box::new(recompile_completed.clone()))?;
+        registry.register(Box::new(recompile_failed.clone()))?;
+        registry.register(Box::new(recompile_remaining.clone()))?;
+        registry.register(Box::new(active_vm_instances.clone()))?;
+        registry.register(Box::new(mempool_pending.clone()))?;
+        registry.register(Box::new(mempool_queued.clone()))?;
+
+        Ok(Self {
+            registry,
+            transactions_processed,
+            transactions_failed,
+            batch_execution_seconds,
+            transaction_gas_used,
+            scheduler_queue_length,
+            batch_queue_seconds,
+            scheduler_rejected_batches,
+            batch_cpu_utilization,
+            batch_rss_delta_bytes,
+            batch_peak_rss_bytes,
+            batch_io_bytes,
+            cache_hit_ratio,
+            recompile_completed,
+            recompile_failed,
+            recompile_remaining,
+            active_vm_instances,
+            mempool_pending,
+            mempool_queued,
+        })
+    }
+
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// 记录一个已完成的批次
+    pub fn record_batch(
+        &self,
+        elapsed: Duration,
+        failed: usize,
+        gas_used: impl IntoIterator<Item = u64>,
+    ) {
+        let mut total = 0;
+        for gas in gas_used {
+            self.transaction_gas_used.observe(gas as f64);
+            total += 1;
+        }
+        self.transactions_processed.inc_by(total);
+        self.transactions_failed.inc_by(failed as u64);
+        self.batch_execution_seconds.observe(elapsed.as_secs_f64());
+    }
+
+    /// 记录最近一个批次的资源使用
+    pub fn record_batch_resources(
+        &self,
+        cpu_utilization: f64,
+        rss_delta_bytes: i64,
+        peak_rss_bytes: u64,
+        io_bytes: u64,
+    ) {
+        self.batch_cpu_utilization.set(cpu_utilization);
+        self.batch_rss_delta_bytes.set(rss_delta_bytes);
+        self.batch_peak_rss_bytes.set(peak_rss_bytes as i64);
+        self.batch_io_bytes.set(io_bytes as i64);
+    }
+
+    /// Prometheus 文本格式
+    pub fn encode(&self) -> Result<String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters_and_gauges() {
+        let metrics = MetricsCollector::new();
+
+        metrics.inc_counter("requests", 2);
+        metrics.inc_counter("requests", 3);
+        metrics.set_gauge("queue_depth", 7.0);
+
+        assert_eq!(metrics.counter("requests"), 5);
+        assert_eq!(metrics.counter("missing"), 0);
+        assert_eq!(metrics.gauge("queue_depth"), Some(7.0));
+        assert_eq!(metrics.value("requests"), Some(5.0));
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.counters.len(), 1);
+        assert_eq!(snapshot.gauges.len(), 1);
+    }
+
+    #[test]
+    fn test_node_metrics_encode() {
+        let metrics = NodeMetrics::new().unwrap();
+        metrics.record_batch(Duration::from_millis(20), 1, [21_000, 50_000]);
+        metrics.active_vm_instances.inc();
+        metrics.record_batch_resources(0.5, -4096, 1 << 20, 512);
+
+        assert_eq!(metrics.transactions_processed.get(), 2);
+        assert_eq!(metrics.transactions_failed.get(), 1);
+        let text = metrics.encode().unwrap();
+        assert!(text.contains("dubhe_transactions_processed_total 2"));
+        assert!(text.contains("dubhe_transaction_gas_used_count 2"));
+        assert!(text.contains("dubhe_vm_active_instances 1"));
+        assert!(text.contains("dubhe_scheduler_batch_cpu_utilization 0.5"));
+        assert!(text.contains("dubhe_scheduler_batch_rss_delta_bytes -4096"));
     }
 }
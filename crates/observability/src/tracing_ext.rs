@@ -1,9 +1,192 @@
-//! 扩展追踪模块
+//! 分布式追踪
+//!
+//! 调度器 → 分发器 → 加载器 → VM → 适配器的 span 统一使用 [`TRACE_TARGET`] 作为 target，
+//! 按 `<模块>.<操作>` 命名，属性为点分小写键：
+//!
+//! | span | 属性 |
+//! |---|---|
+//! | `scheduler.submit_batch` | `batch.size`, `scheduler.strategy` |
+//! | `scheduler.execute_transaction` | `tx.hash`, `worker.id`, `tx.success`, `tx.gas_used` |
+//! | `loader.load_contract` | `contract.address`, `contract.type`, `cache.hit` |
+//! | `vm.execute` | `vm.type`, `vm.input_bytes`, `vm.gas_used`, `vm.cycles` |
+//! | `adapter.call_rpc` | `chain`, `rpc.method`, `rpc.endpoint`, `rpc.latency_ms` |
+//!
+//! 父子关系沿 tracing 上下文传递：批次 span 覆盖 `submit_batch`，分发器的 worker 任务以它为父
+//! 创建交易 span，加载器、VM 与适配器在当前 span 下创建子 span，因此一个 trace 即一次请求的
+//! 完整调用树。配置了 OTLP 端点时这些 span 经 tracing-opentelemetry 导出；未配置时该 target
+//! 在过滤器中关闭，span 宏只剩一次缓存的 interest 检查
 
-pub struct TracingExtension;
+use anyhow::Result;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::{self as sdktrace, Sampler};
+use opentelemetry_sdk::{runtime, Resource};
+use serde::{Deserialize, Serialize};
+use tracing::Subscriber;
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
 
-impl TracingExtension {
-    pub fn new() -> Self {
-        Self
+/// 跨模块追踪 span 的 target
+pub const TRACE_TARGET: &str = "dubhe_trace";
+
+/// OpenTelemetry 导出配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TracingConfig {
+    /// OTLP/gRPC 端点，如 `http://localhost:4317`；为空时不导出
+    pub otlp_endpoint: Option<String>,
+    /// 按 trace ID 采样的比例（0.0 ~ 1.0），父 span 已采样时跟随父级
+    pub sampling_ratio: f64,
+    /// 上报的 `service.name`
+    pub service_name: String,
+}
+
+impl Default for TracingConfig {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            sampling_ratio: 1.0,
+            service_name: "dubhe-channel".to_string(),
+        }
+    }
+}
+
+impl TracingConfig {
+    fn endpoint(&self) -> Option<&str> {
+        self.otlp_endpoint
+            .as_deref()
+            .map(str::trim)
+            .filter(|endpoint| !endpoint.is_empty())
+    }
+}
+
+/// 持有期间保持导出；释放时把缓冲的 span 发送出去
+#[must_use = "dropping the guard shuts the trace exporter down"]
+pub struct TracingGuard {
+    exporting: bool,
+}
+
+impl TracingGuard {
+    /// 是否配置了 OTLP 导出
+    pub fn is_exporting(&self) -> bool {
+        self.exporting
+    }
+}
+
+impl Drop for TracingGuard {
+    fn drop(&mut self) {
+        if self.exporting {
+            opentelemetry::global::shutdown_tracer_provider();
+        }
+    }
+}
+
+/// 构建 subscriber：日志层写入 `writer`，配置了端点时另加 OpenTelemetry 层
+///
+/// `log_filter` 为 `RUST_LOG` 语法，环境变量 `RUST_LOG` 存在时优先。
+/// 日志层总是关闭 [`TRACE_TARGET`]，追踪 span 只交给 OpenTelemetry 层。
+/// 导出使用 Tokio 运行时，须在运行时内调用
+pub fn build_subscriber<W>(
+    log_filter: &str,
+    config: &TracingConfig,
+    writer: W,
+) -> Result<(impl Subscriber + Send + Sync + 'static, TracingGuard)>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let log_filter = match EnvFilter::try_from_default_env() {
+        Ok(filter) => filter,
+        Err(_) => EnvFilter::try_new(log_filter)?,
+    }
+    .add_directive(format!("{}=off", TRACE_TARGET).parse()?);
+    let log_layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_filter(log_filter);
+
+    let otel_layer = match config.endpoint() {
+        Some(endpoint) => {
+            let ratio = config.sampling_ratio.clamp(0.0, 1.0);
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(endpoint),
+                )
+                .with_trace_config(
+                    sdktrace::config()
+                        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+                            ratio,
+                        ))))
+                        .with_resource(Resource::new(vec![KeyValue::new(
+                            "service.name",
+                            config.service_name.clone(),
+                        )])),
+                )
+                .install_batch(runtime::Tokio)?;
+            Some(
+                tracing_opentelemetry::layer()
+                    .with_tracer(tracer)
+                    .with_filter(Targets::new().with_target(TRACE_TARGET, LevelFilter::TRACE)),
+            )
+        }
+        None => None,
+    };
+    let exporting = otel_layer.is_some();
+
+    // 空的导出层对所有 span 都感兴趣，不导出时须在全局过滤器中关闭追踪 target
+    let trace_off = (!exporting).then(|| {
+        Targets::new()
+            .with_default(LevelFilter::TRACE)
+            .with_target(TRACE_TARGET, LevelFilter::OFF)
+    });
+
+    let subscriber = Registry::default()
+        .with(trace_off)
+        .with(otel_layer)
+        .with(log_layer);
+    Ok((subscriber, TracingGuard { exporting }))
+}
+
+/// 构建日志输出到标准输出的 subscriber 并安装为全局默认
+pub fn init_tracing(log_filter: &str, config: &TracingConfig) -> Result<TracingGuard> {
+    let (subscriber, guard) = build_subscriber(log_filter, config, std::io::stdout)?;
+    subscriber.try_init()?;
+    Ok(guard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_spans_disabled_without_exporter() {
+        let (subscriber, guard) =
+            build_subscriber("info", &TracingConfig::default(), std::io::sink).unwrap();
+        assert!(!guard.is_exporting());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let traced = tracing::info_span!(target: TRACE_TARGET, "scheduler.submit_batch");
+            assert!(traced.is_disabled());
+            let logged = tracing::info_span!("request");
+            assert!(!logged.is_disabled());
+        });
+    }
+
+    #[test]
+    fn test_blank_endpoint_is_unconfigured() {
+        let config = TracingConfig {
+            otlp_endpoint: Some("  ".to_string()),
+            ..TracingConfig::default()
+        };
+        assert_eq!(config.endpoint(), None);
+        let config = TracingConfig {
+            otlp_endpoint: Some("http://collector:4317".to_string()),
+            ..TracingConfig::default()
+        };
+        assert_eq!(config.endpoint(), Some("http://collector:4317"));
     }
 }
@@ -0,0 +1,121 @@
+//! 交易读写集合解析
+//!
+//! 优先使用交易声明的访问列表；未声明时通过编译产物中的存储访问摘要估算
+
+use anyhow::Result;
+use dubhe_adapter::ContractMeta;
+use dubhe_loader::CodeLoader;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+use crate::types::{AccessList, Transaction};
+
+/// 读写集合来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AccessSource {
+    Declared,  // 交易声明（access_list 或 read_set/write_set）
+    Estimated, // 由编译产物估算，可能偏大
+}
+
+/// 读写集合估算器
+///
+/// 通过 CodeLoader 取得目标合约的编译产物，读取其存储访问摘要：
+/// - 槽位可静态确定时，读写 `<合约>:<槽位>`，并读取合约键 `<合约>`
+/// - 含动态键、合约未登记或没有摘要时，保守地视为写整个合约（写合约键）
+///
+/// 合约键让整合约写入与任意槽位访问产生冲突
+pub struct AccessSetEstimator {
+    loader: Arc<CodeLoader>,
+    contracts: RwLock<HashMap<String, ContractMeta>>,
+}
+
+impl AccessSetEstimator {
+    pub fn new(loader: Arc<CodeLoader>) -> Self {
+        Self {
+            loader,
+            contracts: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 登记可估算的合约
+    pub async fn register_contract(&self, meta: ContractMeta) {
+        self.contracts
+            .write()
+            .await
+            .insert(meta.address.clone(), meta);
+    }
+
+    /// 估算交易的读写集合
+    pub async fn estimate(&self, transaction: &Transaction) -> Result<AccessList> {
+        // 合约创建不触及已有存储
+        let Some(contract) = transaction.to.as_deref() else {
+            return Ok(AccessList::default());
+        };
+
+        let Some(meta) = self.contracts.read().await.get(contract).cloned() else {
+            debug!("Contract {} not registered, assuming full write", contract);
+            return Ok(whole_contract_write(contract));
+        };
+
+        let compiled = self.loader.load_contract(&meta).await?;
+        let access = match compiled.metadata.storage_access {
+            Some(access) if !access.has_dynamic() => access,
+            _ => return Ok(whole_contract_write(contract)),
+        };
+
+        let slot = |slot: &String| format!("{}:{}", contract, slot);
+        let mut reads = vec![contract.to_string()];
+        reads.extend(access.reads.iter().map(slot));
+        Ok(AccessList {
+            reads,
+            writes: access.writes.iter().map(slot).collect(),
+        })
+    }
+}
+
+fn whole_contract_write(contract: &str) -> AccessList {
+    AccessList {
+        reads: vec![],
+        writes: vec![contract.to_string()],
+    }
+}
+
+/// 解析单笔交易的读写集合
+pub(crate) async fn resolve_access(
+    transaction: &Transaction,
+    estimator: Option<&AccessSetEstimator>,
+) -> (AccessList, AccessSource) {
+    if let Some(declared) = &transaction.access_list {
+        return (declared.clone(), AccessSource::Declared);
+    }
+
+    if !transaction.read_set.is_empty() || !transaction.write_set.is_empty() {
+        let declared = AccessList {
+            reads: transaction.read_set.clone(),
+            writes: transaction.write_set.clone(),
+        };
+        return (declared, AccessSource::Declared);
+    }
+
+    match estimator {
+        Some(estimator) => {
+            let estimated = estimator.estimate(transaction).await.unwrap_or_else(|e| {
+                warn!(
+                    "Access set estimation failed for {}: {}",
+                    transaction.hash, e
+                );
+                transaction
+                    .to
+                    .as_deref()
+                    .map(whole_contract_write)
+                    .unwrap_or_default()
+            });
+            (estimated, AccessSource::Estimated)
+        }
+        // 没有任何信息时视为无冲突
+        None => (AccessList::default(), AccessSource::Declared),
+    }
+}
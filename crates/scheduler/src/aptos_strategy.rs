@@ -1,30 +1,283 @@
 //! Aptos Block-STM 策略
+//!
+//! 批次内交易先乐观并行执行，执行中的读写经过多版本内存；每轮结束后按下标顺序
+//! 验证读集合，失效的交易递增执行轮次后重执行。任一交易重执行次数超过上限时，
+//! 从最小的失效交易起回退为串行执行
 
 use async_trait::async_trait;
-use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
 
 use crate::strategy::ExecutionStrategy;
 use crate::types::*;
 use crate::conflict::ConflictGraph;
+use crate::error::{SchedulerError, SchedulerResult};
+use crate::mvmemory::{
+    validate_reads, MultiVersionMemory, OptimisticOutcome, TxnView, Version, VersionedExecutor,
+};
 
-pub struct AptosStrategy;
+/// 单笔交易的默认最大执行次数
+pub const DEFAULT_MAX_INCARNATIONS: usize = 4;
+
+pub struct AptosStrategy {
+    max_incarnations: usize,
+    workers: usize,
+}
+
+/// 单笔交易最近一次执行的记录
+struct Execution {
+    result: TransactionResult,
+    reads: HashMap<String, Option<Version>>,
+    written: Vec<String>,
+}
 
 impl AptosStrategy {
     pub fn new() -> Self {
-        Self
+        Self {
+            max_incarnations: DEFAULT_MAX_INCARNATIONS,
+            workers: num_cpus::get(),
+        }
+    }
+
+    /// 单笔交易的最大执行次数，超过后回退为串行
+    pub fn with_max_incarnations(mut self, max_incarnations: usize) -> Self {
+        self.max_incarnations = max_incarnations.max(1);
+        self
+    }
+
+    /// 乐观执行的并发度
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Block-STM 执行整个批次并提交最终写集合
+    pub async fn execute_block(
+        &self,
+        transactions: &[Transaction],
+        executor: Arc<dyn VersionedExecutor>,
+        cancel: &CancellationToken,
+    ) -> SchedulerResult<OptimisticOutcome> {
+        let len = transactions.len();
+        let memory = Arc::new(MultiVersionMemory::new());
+        let mut executions: Vec<Option<Execution>> = (0..len).map(|_| None).collect();
+        let mut incarnations = vec![0usize; len];
+        let mut pending: Vec<usize> = (0..len).collect();
+        let mut aborts = 0;
+        let mut executed = 0;
+        let mut fell_back = false;
+
+        while let Some(&first) = pending.first() {
+            executed += pending.len();
+            let round = self
+                .execute_round(
+                    &pending,
+                    &incarnations,
+                    &executions,
+                    transactions,
+                    &memory,
+                    &executor,
+                    cancel,
+                )
+                .await?;
+            for (index, execution) in round {
+                executions[index] = Some(execution);
+            }
+
+            // first 之前的交易未被重执行，其读集合已稳定
+            let invalid: Vec<usize> = (first..len)
+                .filter(|&i| {
+                    let reads = &executions[i].as_ref().expect("executed").reads;
+                    !validate_reads(&memory, i, reads)
+                })
+                .collect();
+            if invalid.is_empty() {
+                break;
+            }
+
+            aborts += invalid.len();
+            for &index in &invalid {
+                incarnations[index] += 1;
+            }
+
+            if invalid.iter().any(|&i| incarnations[i] >= self.max_incarnations) {
+                warn!(
+                    "Block-STM retry limit reached, executing transactions {}..{} sequentially",
+                    invalid[0], len
+                );
+                fell_back = true;
+                for index in invalid[0]..len {
+                    if cancel.is_cancelled() {
+                        return Err(SchedulerError::Cancelled);
+                    }
+                    if !invalid.contains(&index) {
+                        incarnations[index] += 1;
+                    }
+                    let previous = written(&executions[index]);
+                    executions[index] = Some(
+                        execute_one(
+                            &memory,
+                            executor.as_ref(),
+                            &transactions[index],
+                            index,
+                            incarnations[index],
+                            &previous,
+                        )
+                        .await,
+                    );
+                    executed += 1;
+                }
+                break;
+            }
+
+            pending = invalid;
+        }
+
+        debug!(
+            "Block-STM executed {} transactions with {} executions and {} aborts",
+            len, executed, aborts
+        );
+
+        executor.commit(memory.snapshot()).await?;
+
+        Ok(OptimisticOutcome {
+            results: executions
+                .into_iter()
+                .map(|execution| execution.expect("executed").result)
+                .collect(),
+            aborts,
+            executions: executed,
+            fell_back,
+        })
+    }
+
+    /// 并行执行一轮，返回各交易的执行记录
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_round(
+        &self,
+        pending: &[usize],
+        incarnations: &[usize],
+        executions: &[Option<Execution>],
+        transactions: &[Transaction],
+        memory: &Arc<MultiVersionMemory>,
+        executor: &Arc<dyn VersionedExecutor>,
+        cancel: &CancellationToken,
+    ) -> SchedulerResult<Vec<(usize, Execution)>> {
+        let permits = Arc::new(Semaphore::new(self.workers));
+        let mut tasks = JoinSet::new();
+
+        for &index in pending {
+            let transaction = transactions[index].clone();
+            let incarnation = incarnations[index];
+            let previous = written(&executions[index]);
+            let memory = memory.clone();
+            let executor = executor.clone();
+            let permits = permits.clone();
+
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let execution = execute_one(
+                    &memory,
+                    executor.as_ref(),
+                    &transaction,
+                    index,
+                    incarnation,
+                    &previous,
+                )
+                .await;
+                (index, execution)
+            });
+        }
+
+        let mut round = Vec::with_capacity(pending.len());
+        loop {
+            tokio::select! {
+                joined = tasks.join_next() => match joined {
+                    Some(Ok(executed)) => round.push(executed),
+                    Some(Err(e)) => return Err(SchedulerError::WorkerFailed(e)),
+                    None => break,
+                },
+                _ = cancel.cancelled() => {
+                    tasks.abort_all();
+                    return Err(SchedulerError::Cancelled);
+                }
+            }
+        }
+
+        Ok(round)
+    }
+}
+
+fn written(execution: &Option<Execution>) -> Vec<String> {
+    execution
+        .as_ref()
+        .map(|execution| execution.written.clone())
+        .unwrap_or_default()
+}
+
+/// 执行一次交易并把写集合记录到多版本内存
+async fn execute_one(
+    memory: &MultiVersionMemory,
+    executor: &dyn VersionedExecutor,
+    transaction: &Transaction,
+    index: usize,
+    incarnation: usize,
+    previous: &[String],
+) -> Execution {
+    let view = TxnView::new(memory, executor, index);
+    let (result, writes) = match executor.execute(transaction, &view).await {
+        Ok(output) => (output.result, output.writes),
+        // 失败的交易不产生写入
+        Err(e) => (
+            TransactionResult {
+                tx_hash: transaction.hash.clone(),
+                success: false,
+                gas_used: 0,
+                output: vec![],
+                logs: vec![],
+                error: Some(e.to_string()),
+            },
+            vec![],
+        ),
+    };
+
+    memory.record(index, incarnation, &writes, previous);
+    Execution {
+        result,
+        reads: view.into_reads(),
+        written: writes.into_iter().map(|(location, _)| location).collect(),
     }
 }
 
 #[async_trait]
 impl ExecutionStrategy for AptosStrategy {
-    async fn plan_execution(&self, transactions: &[Transaction], _conflict_graph: &ConflictGraph) -> Result<ExecutionPlan> {
-        // TODO: 实现 Aptos Block-STM 乐观并发控制
+    async fn plan_execution(
+        &self,
+        transactions: &[Transaction],
+        _conflict_graph: &ConflictGraph,
+    ) -> SchedulerResult<ExecutionPlan> {
+        // 乐观执行不预先拆分，整个批次作为一组
+        let all: Vec<usize> = (0..transactions.len()).collect();
         Ok(ExecutionPlan {
-            parallel_groups: vec![],
-            dependency_order: vec![],
+            parallel_groups: if all.is_empty() { vec![] } else { vec![all.clone()] },
+            dependency_order: all,
+            unordered: vec![],
         })
     }
 
+    async fn execute_optimistic(
+        &self,
+        transactions: &[Transaction],
+        executor: Arc<dyn VersionedExecutor>,
+        cancel: &CancellationToken,
+    ) -> SchedulerResult<Option<OptimisticOutcome>> {
+        self.execute_block(transactions, executor, cancel).await.map(Some)
+    }
+
     fn name(&self) -> &str {
         "aptos_block_stm"
     }
@@ -32,4 +285,150 @@ impl ExecutionStrategy for AptosStrategy {
     fn description(&self) -> &str {
         "Aptos Block-STM optimistic concurrent execution"
     }
-} 
\ No newline at end of file
+
+    fn strategy_type(&self) -> StrategyType {
+        StrategyType::AptosSTM
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mvmemory::{StateView, VersionedOutput};
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use tokio::sync::Barrier;
+
+    /// 对 `to` 位置的计数器加一，输出加一后的值
+    struct CounterExecutor {
+        storage: Mutex<BTreeMap<String, Vec<u8>>>,
+        // 前 N 次执行在读写之间同步，确保首轮全部读到初始状态
+        barrier: Option<(Barrier, usize)>,
+        calls: AtomicUsize,
+    }
+
+    impl CounterExecutor {
+        fn new(barrier: Option<usize>) -> Self {
+            Self {
+                storage: Mutex::new(BTreeMap::new()),
+                barrier: barrier.map(|parties| (Barrier::new(parties), parties)),
+                calls: AtomicUsize::new(0),
+            }
+        }
+
+        fn value(&self, location: &str) -> u64 {
+            self.storage
+                .lock()
+                .unwrap()
+                .get(location)
+                .map(|bytes| decode(bytes))
+                .unwrap_or(0)
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> u64 {
+        u64::from_le_bytes(bytes.try_into().unwrap())
+    }
+
+    #[async_trait]
+    impl VersionedExecutor for CounterExecutor {
+        fn storage_read(&self, location: &str) -> Option<Vec<u8>> {
+            self.storage.lock().unwrap().get(location).cloned()
+        }
+
+        async fn execute(
+            &self,
+            transaction: &Transaction,
+            view: &dyn StateView,
+        ) -> SchedulerResult<VersionedOutput> {
+            let location = transaction.to.clone().unwrap();
+            let current = view.read(&location).map(|bytes| decode(&bytes)).unwrap_or(0);
+
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if let Some((barrier, parties)) = &self.barrier {
+                if call < *parties {
+                    barrier.wait().await;
+                }
+            }
+
+            let next = (current + 1).to_le_bytes().to_vec();
+            Ok(VersionedOutput {
+                result: TransactionResult {
+                    tx_hash: transaction.hash.clone(),
+                    success: true,
+                    gas_used: 1,
+                    output: next.clone(),
+                    logs: vec![],
+                    error: None,
+                },
+                writes: vec![(location, next)],
+            })
+        }
+
+        async fn commit(&self, writes: BTreeMap<String, Vec<u8>>) -> SchedulerResult<()> {
+            self.storage.lock().unwrap().extend(writes);
+            Ok(())
+        }
+    }
+
+    fn transactions(count: usize, hot_keys: usize) -> Vec<Transaction> {
+        (0..count)
+            .map(|i| Transaction {
+                hash: format!("0x{:02x}", i),
+                from: format!("0xsender{}", i),
+                to: Some(format!("counter{}", i % hot_keys)),
+                data: vec![],
+                gas_limit: 21000,
+                gas_price: 1,
+                nonce: 0,
+                read_set: vec![],
+                write_set: vec![],
+                access_list: None,
+                max_priority_fee: None,
+            })
+            .collect()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_block_stm_matches_sequential_execution() {
+        let executor = Arc::new(CounterExecutor::new(None));
+        let transactions = transactions(32, 2);
+
+        let outcome = AptosStrategy::new()
+            .with_workers(8)
+            .execute_block(&transactions, executor.clone(), &CancellationToken::new())
+            .await
+            .unwrap();
+
+        // 每笔交易看到的都是串行顺序下的计数
+        for (i, result) in outcome.results.iter().enumerate() {
+            assert!(result.success);
+            assert_eq!(decode(&result.output), (i / 2 + 1) as u64);
+        }
+        assert_eq!(executor.value("counter0"), 16);
+        assert_eq!(executor.value("counter1"), 16);
+        assert_eq!(outcome.executions, 32 + outcome.aborts);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_retry_limit_falls_back_to_sequential() {
+        let count = 6;
+        let executor = Arc::new(CounterExecutor::new(Some(count)));
+        let transactions = transactions(count, 1);
+
+        let outcome = AptosStrategy::new()
+            .with_workers(count)
+            .with_max_incarnations(1)
+            .execute_block(&transactions, executor.clone(), &CancellationToken::new())
+            .await
+            .unwrap();
+
+        // 首轮全部读到 0，除第一笔外全部失效
+        assert_eq!(outcome.aborts, count - 1);
+        assert!(outcome.fell_back);
+        assert_eq!(outcome.executions, count + count - 1);
+        assert_eq!(executor.value("counter0"), count as u64);
+        assert_eq!(decode(&outcome.results[count - 1].output), count as u64);
+    }
+}
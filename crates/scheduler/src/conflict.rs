@@ -1,42 +1,180 @@
 //! 冲突分析模块
 
 use anyhow::Result;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
 
+use crate::access::{resolve_access, AccessSetEstimator, AccessSource};
 use crate::types::Transaction;
 
+/// 关键路径估算中每毫秒执行的 gas，只用于量级比较
+pub const ESTIMATED_GAS_PER_MS: u64 = 100_000;
+
+/// 冲突图的一个连通分量
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictComponent {
+    /// 分量内的交易，按下标升序
+    pub members: Vec<usize>,
+    /// 分量内的强连通分量，按拓扑序排列，各自按下标升序
+    pub sccs: Vec<Vec<usize>>,
+    /// 分量内交易的估算 gas 之和
+    pub total_gas: u64,
+    /// 最长依赖链的估算 gas；同一强连通分量内的交易只能串行，按其 gas 之和计入
+    pub critical_path_gas: u64,
+}
+
+impl ConflictComponent {
+    /// 关键路径的估算时长
+    pub fn critical_path_ms(&self) -> f64 {
+        gas_to_ms(self.critical_path_gas)
+    }
+}
+
 /// 冲突图
+///
+/// 边为去重后的 `(较小下标, 较大下标)`，按下标升序排列；任一端读写集合为估算所得的边标记为 `Estimated`；
+/// 乐观策略不应在估算边上做推测执行。
+///
+/// 边 `(a, b)` 同时视为 a 先于 b 执行的依赖：连通分量、强连通分量、拓扑序与关键路径在构建时算好，
+/// 各策略共用，不必各自重新推导
 #[derive(Debug, Clone)]
 pub struct ConflictGraph {
     pub nodes: usize,
     pub edges: Vec<(usize, usize)>,
     pub read_conflicts: HashMap<String, Vec<usize>>,
     pub write_conflicts: HashMap<String, Vec<usize>>,
+    /// 每笔交易读写集合的来源
+    pub access_sources: Vec<AccessSource>,
+    /// 与 `edges` 一一对应的边来源
+    pub edge_sources: Vec<AccessSource>,
+    /// 每笔交易的估算 gas（交易的 gas 上限）
+    pub gas_estimates: Vec<u64>,
+    components: Vec<ConflictComponent>,
+}
+
+impl ConflictGraph {
+    /// 由依赖边与各交易的估算 gas 构建，不含读写映射，边来源均记为声明
+    pub fn from_dependencies(gas_estimates: Vec<u64>, edges: Vec<(usize, usize)>) -> Self {
+        let nodes = gas_estimates.len();
+        Self {
+            nodes,
+            edge_sources: vec![AccessSource::Declared; edges.len()],
+            edges,
+            read_conflicts: HashMap::new(),
+            write_conflicts: HashMap::new(),
+            access_sources: vec![AccessSource::Declared; nodes],
+            gas_estimates,
+            components: Vec::new(),
+        }
+        .with_components()
+    }
+
+    /// 连通分量，按各自最小下标升序
+    pub fn components(&self) -> &[ConflictComponent] {
+        &self.components
+    }
+
+    /// 批次关键路径的估算时长，即各连通分量中最长的关键路径
+    pub fn critical_path_ms(&self) -> f64 {
+        gas_to_ms(self.critical_path_gas())
+    }
+
+    /// 批次关键路径的估算 gas
+    pub fn critical_path_gas(&self) -> u64 {
+        self.components
+            .iter()
+            .map(|component| component.critical_path_gas)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// 理论并行度上限：估算 gas 之和除以关键路径的 gas，没有估算 gas 时为 0
+    pub fn parallelism_bound(&self) -> f64 {
+        match self.critical_path_gas() {
+            0 => 0.0,
+            critical => self.gas_estimates.iter().sum::<u64>() as f64 / critical as f64,
+        }
+    }
+
+    fn with_components(mut self) -> Self {
+        self.components = analyze_components(self.nodes, &self.edges, &self.gas_estimates);
+        self
+    }
+
+    /// 来源为估算的冲突边
+    pub fn estimated_edges(&self) -> impl Iterator<Item = &(usize, usize)> {
+        self.edges
+            .iter()
+            .zip(&self.edge_sources)
+            .filter(|(_, source)| **source == AccessSource::Estimated)
+            .map(|(edge, _)| edge)
+    }
+
+    /// 按冲突边把前 `len` 笔交易中满足 `include` 的交易分层
+    ///
+    /// 交易位于其所有冲突前驱之后的最早一层，同层内互不冲突；层内按下标升序
+    pub fn layered_groups(&self, len: usize, include: impl Fn(usize) -> bool) -> Vec<Vec<usize>> {
+        let mut edges: Vec<(usize, usize)> = self
+            .edges
+            .iter()
+            .filter(|&&(a, b)| a != b && a < len && b < len && include(a) && include(b))
+            .map(|&(a, b)| ordered(a, b))
+            .collect();
+        edges.sort_unstable();
+
+        let mut levels = vec![0usize; len];
+        for (earlier, later) in edges {
+            levels[later] = levels[later].max(levels[earlier] + 1);
+        }
+
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for (index, level) in levels.into_iter().enumerate() {
+            if !include(index) {
+                continue;
+            }
+            if groups.len() <= level {
+                groups.resize_with(level + 1, Vec::new);
+            }
+            groups[level].push(index);
+        }
+        groups
+    }
 }
 
 /// 冲突分析器
 pub struct ConflictAnalyzer {
-    // 状态分析缓存
+    estimator: Option<Arc<AccessSetEstimator>>,
 }
 
 impl ConflictAnalyzer {
     pub fn new() -> Self {
-        Self {}
+        Self { estimator: None }
+    }
+
+    /// 未声明读写集合的交易通过估算器补全
+    pub fn with_estimator(estimator: Arc<AccessSetEstimator>) -> Self {
+        Self {
+            estimator: Some(estimator),
+        }
     }
 
     /// 分析交易冲突并构建冲突图
     pub async fn analyze(&mut self, transactions: &[Transaction]) -> Result<ConflictGraph> {
         let mut read_conflicts = HashMap::new();
         let mut write_conflicts = HashMap::new();
-        let mut edges = Vec::new();
+        let mut edges = BTreeSet::new();
+        let mut access_sources = Vec::with_capacity(transactions.len());
 
         // 构建读写映射
         for (i, tx) in transactions.iter().enumerate() {
-            for addr in &tx.read_set {
-                read_conflicts.entry(addr.clone()).or_insert_with(Vec::new).push(i);
+            let (access, source) = resolve_access(tx, self.estimator.as_deref()).await;
+            access_sources.push(source);
+
+            for addr in access.reads {
+                read_conflicts.entry(addr).or_insert_with(Vec::new).push(i);
             }
-            for addr in &tx.write_set {
-                write_conflicts.entry(addr.clone()).or_insert_with(Vec::new).push(i);
+            for addr in access.writes {
+                write_conflicts.entry(addr).or_insert_with(Vec::new).push(i);
             }
         }
 
@@ -45,7 +183,7 @@ impl ConflictAnalyzer {
             // Write-Write 冲突
             for i in 0..writers.len() {
                 for j in i + 1..writers.len() {
-                    edges.push((writers[i], writers[j]));
+                    edges.insert(ordered(writers[i], writers[j]));
                 }
             }
 
@@ -54,18 +192,342 @@ impl ConflictAnalyzer {
                 for &writer in writers {
                     for &reader in readers {
                         if writer != reader {
-                            edges.push((writer, reader));
+                            edges.insert(ordered(writer, reader));
                         }
                     }
                 }
             }
         }
 
+        let edges: Vec<(usize, usize)> = edges.into_iter().collect();
+        let edge_sources = edges
+            .iter()
+            .map(|&(a, b)| {
+                if access_sources[a] == AccessSource::Estimated
+                    || access_sources[b] == AccessSource::Estimated
+                {
+                    AccessSource::Estimated
+                } else {
+                    AccessSource::Declared
+                }
+            })
+            .collect();
+
         Ok(ConflictGraph {
             nodes: transactions.len(),
             edges,
             read_conflicts,
             write_conflicts,
-        })
+            access_sources,
+            edge_sources,
+            gas_estimates: transactions.iter().map(|tx| tx.gas_limit).collect(),
+            components: Vec::new(),
+        }
+        .with_components())
     }
-} 
\ No newline at end of file
+}
+
+fn ordered(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+fn gas_to_ms(gas: u64) -> f64 {
+    gas as f64 / ESTIMATED_GAS_PER_MS as f64
+}
+
+/// 并查集
+struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+        }
+    }
+
+    fn find(&mut self, mut node: usize) -> usize {
+        while self.parent[node] != node {
+            self.parent[node] = self.parent[self.parent[node]];
+            node = self.parent[node];
+        }
+        node
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+    }
+}
+
+/// Tarjan 算法（非递归）求强连通分量，返回每个节点所属分量的编号
+///
+/// 编号按逆拓扑序分配：分量能到达的其他分量编号都比它小
+fn strongly_connected(adjacency: &[Vec<usize>]) -> Vec<usize> {
+    const UNVISITED: usize = usize::MAX;
+    let len = adjacency.len();
+    let mut index = vec![UNVISITED; len];
+    let mut low = vec![0; len];
+    let mut on_stack = vec![false; len];
+    let mut stack = Vec::new();
+    let mut component = vec![UNVISITED; len];
+    let (mut next_index, mut count) = (0, 0);
+
+    for root in 0..len {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        // (节点, 下一个待访问的后继)
+        let mut calls = vec![(root, 0)];
+        index[root] = next_index;
+        low[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some((node, child)) = calls.last().copied() {
+            if let Some(&next) = adjacency[node].get(child) {
+                calls.last_mut().unwrap().1 += 1;
+                if index[next] == UNVISITED {
+                    index[next] = next_index;
+                    low[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    calls.push((next, 0));
+                } else if on_stack[next] {
+                    low[node] = low[node].min(index[next]);
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                low[parent] = low[parent].min(low[node]);
+            }
+            if low[node] == index[node] {
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component[member] = count;
+                    if member == node {
+                        break;
+                    }
+                }
+                count += 1;
+            }
+        }
+    }
+    component
+}
+
+/// 求连通分量，并在每个分量内按强连通分量的拓扑序累计关键路径
+fn analyze_components(
+    nodes: usize,
+    edges: &[(usize, usize)],
+    gas: &[u64],
+) -> Vec<ConflictComponent> {
+    let edges: Vec<(usize, usize)> = edges
+        .iter()
+        .copied()
+        .filter(|&(a, b)| a != b && a < nodes && b < nodes)
+        .collect();
+
+    let mut sets = DisjointSet::new(nodes);
+    let mut adjacency = vec![Vec::new(); nodes];
+    for &(from, to) in &edges {
+        sets.union(from, to);
+        adjacency[from].push(to);
+    }
+
+    let scc = strongly_connected(&adjacency);
+    let scc_count = scc.iter().map(|&id| id + 1).max().unwrap_or(0);
+    let mut scc_members = vec![Vec::new(); scc_count];
+    let mut weights = vec![0u64; scc_count];
+    for node in 0..nodes {
+        scc_members[scc[node]].push(node);
+        weights[scc[node]] += gas.get(node).copied().unwrap_or(0);
+    }
+
+    // 编号越大拓扑序越靠前，逆序遍历时前驱总是先于后继完成
+    let mut successors = vec![Vec::new(); scc_count];
+    for &(from, to) in &edges {
+        if scc[from] != scc[to] {
+            successors[scc[from]].push(scc[to]);
+        }
+    }
+    let mut finish = weights.clone();
+    for id in (0..scc_count).rev() {
+        for &next in &successors[id] {
+            finish[next] = finish[next].max(finish[id] + weights[next]);
+        }
+    }
+
+    let mut roots: HashMap<usize, usize> = HashMap::new();
+    let mut components: Vec<ConflictComponent> = Vec::new();
+    for node in 0..nodes {
+        let root = sets.find(node);
+        let position = *roots.entry(root).or_insert_with(|| {
+            components.push(ConflictComponent {
+                members: Vec::new(),
+                sccs: Vec::new(),
+                total_gas: 0,
+                critical_path_gas: 0,
+            });
+            components.len() - 1
+        });
+        let component = &mut components[position];
+        component.members.push(node);
+        component.total_gas += gas.get(node).copied().unwrap_or(0);
+    }
+    for component in &mut components {
+        let mut ids: Vec<usize> = component.members.iter().map(|&node| scc[node]).collect();
+        ids.sort_unstable_by(|a, b| b.cmp(a));
+        ids.dedup();
+        component.critical_path_gas = ids.iter().map(|&id| finish[id]).max().unwrap_or(0);
+        component.sccs = ids
+            .into_iter()
+            .map(|id| std::mem::take(&mut scc_members[id]))
+            .collect();
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::AccessList;
+    use dubhe_adapter::{ChainType, ContractMeta, ContractType};
+    use dubhe_loader::CodeLoader;
+
+    fn tx(hash: &str, from: &str, to: &str) -> Transaction {
+        Transaction {
+            hash: hash.to_string(),
+            from: from.to_string(),
+            to: Some(to.to_string()),
+            data: vec![],
+            gas_limit: 21000,
+            gas_price: 1,
+            nonce: 0,
+            read_set: vec![],
+            write_set: vec![],
+            access_list: None,
+            max_priority_fee: None,
+        }
+    }
+
+    /// 读写槽位 0 的计数器合约
+    fn counter_contract(address: &str) -> ContractMeta {
+        ContractMeta {
+            address: address.to_string(),
+            chain_type: ChainType::Ethereum,
+            contract_type: ContractType::EVM,
+            // PUSH1 0 SLOAD PUSH1 1 ADD PUSH1 0 SSTORE STOP
+            bytecode: vec![0x60, 0x00, 0x54, 0x60, 0x01, 0x01, 0x60, 0x00, 0x55, 0x00],
+            abi: None,
+            source_code: None,
+            compiler_version: None,
+            created_at: 0,
+            creator: None,
+            abi_source: None,
+            modules: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_estimated_conflicts_are_serialized() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let loader = Arc::new(CodeLoader::with_cache_dir(temp_dir.path()).unwrap());
+        let estimator = Arc::new(AccessSetEstimator::new(loader));
+        estimator.register_contract(counter_contract("0xcounter")).await;
+
+        let mut declared = tx("c", "0xcarol", "0xother");
+        declared.access_list = Some(AccessList {
+            reads: vec![],
+            writes: vec!["0xother".to_string()],
+        });
+        let transactions = vec![
+            tx("a", "0xalice", "0xcounter"),
+            tx("b", "0xbob", "0xcounter"),
+            declared,
+        ];
+
+        let mut analyzer = ConflictAnalyzer::with_estimator(estimator);
+        let graph = analyzer.analyze(&transactions).await.unwrap();
+
+        assert_eq!(
+            graph.access_sources,
+            vec![
+                AccessSource::Estimated,
+                AccessSource::Estimated,
+                AccessSource::Declared
+            ]
+        );
+        assert!(graph.write_conflicts["0xcounter:0x0"].contains(&1));
+        assert_eq!(graph.estimated_edges().collect::<Vec<_>>(), vec![&(0, 1)]);
+
+        // 两笔估算交易写同一槽位，被放入先后两组
+        #[cfg(feature = "solana_parallel")]
+        {
+            use crate::solana_strategy::SolanaStrategy;
+            use crate::strategy::ExecutionStrategy;
+
+            let plan = SolanaStrategy::new()
+                .plan_execution(&transactions, &graph)
+                .await
+                .unwrap();
+            assert_eq!(plan.parallel_groups, vec![vec![0, 2], vec![1]]);
+        }
+    }
+
+    #[test]
+    fn test_components_and_critical_path() {
+        // 0 -> 1 -> 3、0 -> 2 -> 3 构成一个分量，4 <-> 5 成环，6 独立
+        let graph = ConflictGraph::from_dependencies(
+            vec![100_000, 200_000, 500_000, 100_000, 300_000, 300_000, 50_000],
+            vec![(0, 1), (0, 2), (1, 3), (2, 3), (4, 5), (5, 4)],
+        );
+
+        let members: Vec<&[usize]> = graph
+            .components()
+            .iter()
+            .map(|component| component.members.as_slice())
+            .collect();
+        assert_eq!(members, vec![&[0, 1, 2, 3][..], &[4, 5], &[6]]);
+
+        let diamond = &graph.components()[0];
+        assert_eq!(diamond.sccs, vec![vec![0], vec![2], vec![1], vec![3]]);
+        assert_eq!(diamond.total_gas, 900_000);
+        // 经较重的 2：100k + 500k + 100k
+        assert_eq!(diamond.critical_path_gas, 700_000);
+        assert_eq!(diamond.critical_path_ms(), 7.0);
+
+        // 环内的交易只能串行
+        let cycle = &graph.components()[1];
+        assert_eq!(cycle.sccs, vec![vec![4, 5]]);
+        assert_eq!(cycle.critical_path_gas, 600_000);
+
+        assert_eq!(graph.critical_path_ms(), 7.0);
+        assert_eq!(graph.parallelism_bound(), 1_550_000.0 / 700_000.0);
+    }
+
+    #[tokio::test]
+    async fn test_without_estimator_empty_sets_do_not_conflict() {
+        let transactions = vec![tx("a", "0xalice", "0xcounter"), tx("b", "0xbob", "0xcounter")];
+        let graph = ConflictAnalyzer::new().analyze(&transactions).await.unwrap();
+        assert!(graph.edges.is_empty());
+        assert_eq!(graph.access_sources, vec![AccessSource::Declared; 2]);
+        // 互不冲突的两笔交易可完全并行
+        assert_eq!(graph.components().len(), 2);
+        assert_eq!(graph.parallelism_bound(), 2.0);
+    }
+}
@@ -1,28 +1,493 @@
 //! 交易分发器
+//!
+//! 每个 worker 持有本地双端队列，交易按首个写集合键的哈希分区，
+//! 冲突的交易倾向于落在同一 worker；本地队列为空的 worker 从其他队列窃取。
+//! 执行结果经汇总通道按交易下标归位，与执行计划的顺序一致
 
 use anyhow::Result;
+use async_trait::async_trait;
+use crossbeam::deque::{Steal, Stealer, Worker};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+use tracing::{field, info_span, warn, Instrument, Span};
 
+use dubhe_observability::TRACE_TARGET;
+
+use crate::error::{SchedulerError, SchedulerResult};
 use crate::types::*;
 
+/// 单笔交易执行器
+#[async_trait]
+pub trait TransactionExecutor: Send + Sync {
+    async fn execute(&self, transaction: &Transaction) -> SchedulerResult<TransactionResult>;
+}
+
+/// 占位执行器：不执行合约，直接返回成功（未接入 VM 时使用）
+pub struct NoopExecutor;
+
+#[async_trait]
+impl TransactionExecutor for NoopExecutor {
+    async fn execute(&self, transaction: &Transaction) -> SchedulerResult<TransactionResult> {
+        Ok(TransactionResult {
+            tx_hash: transaction.hash.clone(),
+            success: true,
+            gas_used: 0,
+            output: vec![],
+            logs: vec![],
+            error: None,
+        })
+    }
+}
+
 /// 交易分发器
 pub struct TransactionDispatcher {
     worker_threads: usize,
+    executor: Arc<dyn TransactionExecutor>,
+    timeout_ms: AtomicU64,
+    /// 各 worker 队列中尚未取出的交易数
+    depths: Arc<Vec<AtomicUsize>>,
+    /// 已取出、正在执行的交易数
+    executing: Arc<AtomicUsize>,
+    stolen: Arc<AtomicU64>,
 }
 
 impl TransactionDispatcher {
     pub fn new(worker_threads: usize) -> Result<Self> {
-        Ok(Self { worker_threads })
+        let worker_threads = worker_threads.max(1);
+        Ok(Self {
+            worker_threads,
+            executor: Arc::new(NoopExecutor),
+            timeout_ms: AtomicU64::new(SchedulerConfig::default().timeout_ms),
+            depths: Arc::new((0..worker_threads).map(|_| AtomicUsize::new(0)).collect()),
+            executing: Arc::new(AtomicUsize::new(0)),
+            stolen: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// 设置单笔交易执行器
+    pub fn with_executor(mut self, executor: Arc<dyn TransactionExecutor>) -> Self {
+        self.executor = executor;
+        self
+    }
+
+    /// 设置单笔交易超时
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.set_timeout(timeout);
+        self
+    }
+
+    /// 运行时调整单笔交易超时，对之后开始的批次生效
+    pub fn set_timeout(&self, timeout: Duration) {
+        self.timeout_ms.store(timeout.as_millis() as u64, Ordering::SeqCst);
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms.load(Ordering::SeqCst))
     }
 
     /// 并行执行交易
-    pub async fn execute_parallel(&self, plan: ExecutionPlan) -> Result<Vec<TransactionResult>> {
-        // TODO: 实现并行执行逻辑
-        Ok(vec![])
+    ///
+    /// 按计划逐组执行，组内交易分区到至多 worker 数个本地队列。单笔交易超时只使该交易失败，
+    /// `cancel` 触发时中止整个批次。worker 任务中的交易 span 以调用方的当前 span 为父
+    pub async fn execute_parallel(
+        &self,
+        plan: ExecutionPlan,
+        transactions: &[Transaction],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<TransactionResult>> {
+        let mut results: Vec<Option<TransactionResult>> = vec![None; transactions.len()];
+        let shared: Arc<Vec<Transaction>> = Arc::new(transactions.to_vec());
+        let timeout = self.timeout();
+        let batch_span = Span::current();
+
+        for group in execution_groups(plan, transactions.len()) {
+            let workers = self.worker_threads.min(group.len());
+            let locals: Vec<Worker<usize>> = (0..workers).map(|_| Worker::new_fifo()).collect();
+            for index in group {
+                let worker = partition(&transactions[index], workers);
+                locals[worker].push(index);
+                self.depths[worker].fetch_add(1, Ordering::SeqCst);
+            }
+            let stealers: Arc<Vec<Stealer<usize>>> =
+                Arc::new(locals.iter().map(Worker::stealer).collect());
+            let _undrained = UndrainedGuard {
+                stealers: stealers.clone(),
+                depths: self.depths.clone(),
+            };
+
+            // 各 worker 把结果送入汇总通道，全部 worker 结束后通道关闭
+            let (completed_tx, mut completed_rx) = mpsc::unbounded_channel();
+            let mut tasks = JoinSet::new();
+            for (id, local) in locals.into_iter().enumerate() {
+                tasks.spawn(run_worker(
+                    local,
+                    WorkerContext {
+                        id,
+                        stealers: stealers.clone(),
+                        depths: self.depths.clone(),
+                        executing: self.executing.clone(),
+                        stolen: self.stolen.clone(),
+                        executor: self.executor.clone(),
+                        transactions: shared.clone(),
+                        timeout,
+                        batch_span: batch_span.clone(),
+                    },
+                    completed_tx.clone(),
+                ));
+            }
+            drop(completed_tx);
+
+            loop {
+                tokio::select! {
+                    completed = completed_rx.recv() => match completed {
+                        Some((index, result)) => results[index] = Some(result),
+                        None => break,
+                    },
+                    _ = cancel.cancelled() => {
+                        tasks.abort_all();
+                        // 等待被中止的任务释放计数
+                        while tasks.join_next().await.is_some() {}
+                        return Err(SchedulerError::Cancelled.into());
+                    }
+                }
+            }
+            while let Some(joined) = tasks.join_next().await {
+                joined.map_err(SchedulerError::WorkerFailed)?;
+            }
+        }
+
+        Ok(results.into_iter().flatten().collect())
     }
 
-    /// 获取队列长度
+    /// 获取队列长度：各 worker 队列中的交易数之和，加上正在执行的交易
     pub async fn queue_length(&self) -> usize {
-        0 // TODO: 实现队列长度统计
+        let queued: usize = self
+            .depths
+            .iter()
+            .map(|depth| depth.load(Ordering::SeqCst))
+            .sum();
+        queued + self.executing.load(Ordering::SeqCst)
+    }
+
+    /// 累计被其他 worker 窃取执行的交易数
+    pub fn stolen(&self) -> u64 {
+        self.stolen.load(Ordering::Relaxed)
+    }
+}
+
+/// 按首个写集合键（没有写集合时按交易哈希）分区
+fn partition(transaction: &Transaction, workers: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    transaction
+        .write_set
+        .first()
+        .unwrap_or(&transaction.hash)
+        .hash(&mut hasher);
+    (hasher.finish() % workers as u64) as usize
+}
+
+/// 单个 worker 执行所需的共享状态（本地队列不可跨线程共享，单独持有）
+struct WorkerContext {
+    id: usize,
+    stealers: Arc<Vec<Stealer<usize>>>,
+    depths: Arc<Vec<AtomicUsize>>,
+    executing: Arc<AtomicUsize>,
+    stolen: Arc<AtomicU64>,
+    executor: Arc<dyn TransactionExecutor>,
+    transactions: Arc<Vec<Transaction>>,
+    timeout: Duration,
+    /// 交易 span 的父 span（worker 任务不继承提交方的上下文）
+    batch_span: Span,
+}
+
+impl WorkerContext {
+    /// 先取本地队列，为空时依次从其他 worker 窃取；所有队列都为空时返回 None
+    fn next(&self, local: &Worker<usize>) -> Option<usize> {
+        if let Some(index) = local.pop() {
+            self.depths[self.id].fetch_sub(1, Ordering::SeqCst);
+            return Some(index);
+        }
+
+        let workers = self.stealers.len();
+        loop {
+            let mut retry = false;
+            for offset in 1..workers {
+                let victim = (self.id + offset) % workers;
+                match self.stealers[victim].steal() {
+                    Steal::Success(index) => {
+                        self.depths[victim].fetch_sub(1, Ordering::SeqCst);
+                        self.stolen.fetch_add(1, Ordering::Relaxed);
+                        return Some(index);
+                    }
+                    Steal::Retry => retry = true,
+                    Steal::Empty => {}
+                }
+            }
+            if !retry {
+                return None;
+            }
+        }
+    }
+}
+
+async fn run_worker(
+    local: Worker<usize>,
+    context: WorkerContext,
+    completed: mpsc::UnboundedSender<(usize, TransactionResult)>,
+) {
+    // 本地队列不是 Sync，借用不能跨越 await
+    loop {
+        let Some(index) = context.next(&local) else {
+            return;
+        };
+        context.executing.fetch_add(1, Ordering::SeqCst);
+        let _executing = PendingGuard(context.executing.clone());
+        let transaction = &context.transactions[index];
+        let span = info_span!(
+            target: TRACE_TARGET,
+            parent: &context.batch_span,
+            "scheduler.execute_transaction",
+            tx.hash = %transaction.hash,
+            worker.id = context.id,
+            tx.success = field::Empty,
+            tx.gas_used = field::Empty,
+        );
+        let result = run_with_timeout(context.executor.as_ref(), transaction, context.timeout)
+            .instrument(span.clone())
+            .await;
+        span.record("tx.success", result.success);
+        span.record("tx.gas_used", result.gas_used);
+        if completed.send((index, result)).is_err() {
+            return;
+        }
+    }
+}
+
+/// 批次结束或被中止时，把队列中未取出的交易从计数中扣除
+struct UndrainedGuard {
+    stealers: Arc<Vec<Stealer<usize>>>,
+    depths: Arc<Vec<AtomicUsize>>,
+}
+
+impl Drop for UndrainedGuard {
+    fn drop(&mut self) {
+        for (worker, stealer) in self.stealers.iter().enumerate() {
+            self.depths[worker].fetch_sub(stealer.len(), Ordering::SeqCst);
+        }
     }
-} 
\ No newline at end of file
+}
+
+/// 排队计数，任务结束或被中止时减一
+struct PendingGuard(Arc<AtomicUsize>);
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+async fn run_with_timeout(
+    executor: &dyn TransactionExecutor,
+    transaction: &Transaction,
+    timeout: Duration,
+) -> TransactionResult {
+    let error = match tokio::time::timeout(timeout, executor.execute(transaction)).await {
+        Ok(Ok(result)) => return result,
+        Ok(Err(e)) => e.to_string(),
+        Err(_) => {
+            warn!(
+                "Transaction {} exceeded timeout of {:?}",
+                transaction.hash, timeout
+            );
+            SchedulerError::TimedOut {
+                tx_hash: transaction.hash.clone(),
+                timeout_ms: timeout.as_millis() as u64,
+            }
+            .to_string()
+        }
+    };
+
+    TransactionResult {
+        tx_hash: transaction.hash.clone(),
+        success: false,
+        gas_used: 0,
+        output: vec![],
+        logs: vec![],
+        error: Some(error),
+    }
+}
+
+/// 计划中的并行组（含并入首组的无序交易），未被任何组覆盖的交易随后逐笔执行
+pub(crate) fn execution_groups(plan: ExecutionPlan, len: usize) -> Vec<Vec<usize>> {
+    let mut seen = vec![false; len];
+    let mut groups: Vec<Vec<usize>> = plan
+        .scheduled_groups()
+        .into_iter()
+        .map(|group| {
+            group
+                .into_iter()
+                .filter(|&i| i < len && !std::mem::replace(&mut seen[i], true))
+                .collect::<Vec<_>>()
+        })
+        .filter(|group| !group.is_empty())
+        .collect();
+
+    groups.extend((0..len).filter(|&i| !seen[i]).map(|i| vec![i]));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// hash 为 "slow" 的交易长时间不返回
+    struct SleepyExecutor;
+
+    #[async_trait]
+    impl TransactionExecutor for SleepyExecutor {
+        async fn execute(&self, transaction: &Transaction) -> SchedulerResult<TransactionResult> {
+            if transaction.hash == "slow" {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+            NoopExecutor.execute(transaction).await
+        }
+    }
+
+    fn transactions(count: usize) -> Vec<Transaction> {
+        (0..count)
+            .map(|i| Transaction {
+                hash: if i == 3 { "slow".to_string() } else { format!("0x{:02x}", i) },
+                from: "0xsender".to_string(),
+                to: None,
+                data: vec![],
+                gas_limit: 21000,
+                gas_price: 1,
+                nonce: i as u64,
+                read_set: vec![],
+                write_set: vec![],
+                access_list: None,
+                max_priority_fee: None,
+            })
+            .collect()
+    }
+
+    fn single_group(count: usize) -> ExecutionPlan {
+        ExecutionPlan {
+            parallel_groups: vec![(0..count).collect()],
+            dependency_order: (0..count).collect(),
+            unordered: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_transaction_does_not_stall_batch() {
+        let dispatcher = TransactionDispatcher::new(4)
+            .unwrap()
+            .with_executor(Arc::new(SleepyExecutor))
+            .with_timeout(Duration::from_millis(100));
+
+        let started = std::time::Instant::now();
+        let results = dispatcher
+            .execute_parallel(single_group(10), &transactions(10), &CancellationToken::new())
+            .await
+            .unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        assert_eq!(results.len(), 10);
+        assert_eq!(results.iter().filter(|r| r.success).count(), 9);
+        let timed_out = &results[3];
+        assert!(!timed_out.success);
+        assert!(timed_out.error.as_deref().unwrap().contains("timed out"));
+        assert_eq!(dispatcher.queue_length().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_aborts_batch() {
+        let dispatcher = TransactionDispatcher::new(4)
+            .unwrap()
+            .with_executor(Arc::new(SleepyExecutor));
+        let cancel = CancellationToken::new();
+
+        let canceller = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            canceller.cancel();
+        });
+
+        let error = dispatcher
+            .execute_parallel(single_group(10), &transactions(10), &cancel)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<SchedulerError>(),
+            Some(SchedulerError::Cancelled)
+        ));
+        assert_eq!(dispatcher.queue_length().await, 0);
+    }
+
+    /// 输出只取决于交易本身，执行前让出调度以便其他 worker 窃取
+    struct EchoExecutor;
+
+    #[async_trait]
+    impl TransactionExecutor for EchoExecutor {
+        async fn execute(&self, transaction: &Transaction) -> SchedulerResult<TransactionResult> {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            Ok(TransactionResult {
+                tx_hash: transaction.hash.clone(),
+                success: true,
+                gas_used: transaction.nonce,
+                output: transaction.hash.as_bytes().to_vec(),
+                logs: vec![],
+                error: None,
+            })
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_results_identical_with_work_stealing() {
+        // 同一首写键的交易全部分到同一队列，其余 worker 只能窃取
+        let batch: Vec<Transaction> = transactions(64)
+            .into_iter()
+            .map(|mut tx| {
+                tx.hash = format!("0x{:02x}", tx.nonce);
+                tx.write_set = vec!["0xhot".to_string(), format!("0xown{}", tx.nonce)];
+                tx
+            })
+            .collect();
+
+        let run = |workers: usize| {
+            let batch = batch.clone();
+            async move {
+                let dispatcher = TransactionDispatcher::new(workers)
+                    .unwrap()
+                    .with_executor(Arc::new(EchoExecutor));
+                let results = dispatcher
+                    .execute_parallel(single_group(64), &batch, &CancellationToken::new())
+                    .await
+                    .unwrap();
+                assert_eq!(dispatcher.queue_length().await, 0);
+                (results, dispatcher.stolen())
+            }
+        };
+
+        let (sequential, stolen) = run(1).await;
+        assert_eq!(stolen, 0);
+        let (stealing, stolen) = run(4).await;
+        assert!(stolen > 0);
+
+        let summary = |results: &[TransactionResult]| {
+            results
+                .iter()
+                .map(|r| (r.tx_hash.clone(), r.gas_used, r.output.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summary(&sequential), summary(&stealing));
+        assert_eq!(stealing[10].tx_hash, "0x0a");
+    }
+}
@@ -1,7 +1,13 @@
 //! Scheduler 错误类型
+//!
+//! 执行器与执行策略接口返回 [`SchedulerResult`]。未分类的失败经 `From<anyhow::Error>`
+//! 归入 [`SchedulerError::Internal`] 并保留原始错误链；链上已是 `SchedulerError` 的错误原样取出
 
 use thiserror::Error;
 
+use crate::types::StrategyType;
+use crate::verify::VerificationIssue;
+
 #[derive(Error, Debug)]
 pub enum SchedulerError {
     #[error("Execution failed: {0}")]
@@ -15,4 +21,44 @@ pub enum SchedulerError {
 
     #[error("Configuration error: {0}")]
     ConfigError(String),
+
+    #[error("Scheduler is shutting down")]
+    ShuttingDown,
+
+    #[error("Scheduler queue is full ({depth}/{capacity} transactions queued, estimated drain {estimated_drain_ms}ms)")]
+    QueueFull {
+        depth: usize,
+        capacity: usize,
+        estimated_drain_ms: u64,
+    },
+
+    #[error("Batch cancelled by scheduler shutdown")]
+    Cancelled,
+
+    #[error("Transaction {tx_hash} timed out after {timeout_ms}ms")]
+    TimedOut { tx_hash: String, timeout_ms: u64 },
+
+    #[error("Execution plan is unsafe: {} conflicting pairs, first: {}", .issues.len(), .issues[0])]
+    UnsafePlan { issues: Vec<VerificationIssue> },
+
+    #[error("Unsupported strategy type: {0:?}")]
+    UnsupportedStrategy(StrategyType),
+
+    #[error("Worker task failed")]
+    WorkerFailed(#[from] tokio::task::JoinError),
+
+    /// 未分类的内部错误
+    #[error(transparent)]
+    Internal(anyhow::Error),
+}
+
+pub type SchedulerResult<T> = std::result::Result<T, SchedulerError>;
+
+impl From<anyhow::Error> for SchedulerError {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast::<SchedulerError>() {
+            Ok(error) => error,
+            Err(error) => Self::Internal(error),
+        }
+    }
 }
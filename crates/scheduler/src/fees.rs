@@ -0,0 +1,231 @@
+//! 优先费
+//!
+//! 交易的优先费（[`Transaction::priority_fee`]）决定批次内的执行先后：调度器按
+//! [`priority_order`] 重排批次，冲突交易之间由批次内位置决定依赖方向，因此出价高者先执行；
+//! 同一发送方的交易保持原有 nonce 顺序。
+//!
+//! [`FeeHistory`] 记录最近批次各笔交易的优先费，按 gas 加权给出分位数，供钱包估算出价
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::sync::Mutex;
+use thiserror::Error;
+
+use crate::types::Transaction;
+
+/// 默认保留的批次数
+pub const DEFAULT_FEE_HISTORY_BATCHES: usize = 1024;
+
+/// 按优先费排列批次，返回原下标序列
+///
+/// 每个发送方的交易按批次内原有先后排队，队首之间优先费高者先出；同价时原下标小者先出，
+/// 优先费都相同时顺序不变
+pub fn priority_order(transactions: &[Transaction]) -> Vec<usize> {
+    let mut queues: HashMap<&str, VecDeque<usize>> = HashMap::new();
+    for (index, tx) in transactions.iter().enumerate() {
+        queues.entry(tx.from.as_str()).or_default().push_back(index);
+    }
+
+    // (优先费, 原下标取反)
+    let mut heads: BinaryHeap<(u64, std::cmp::Reverse<usize>)> = queues
+        .values()
+        .filter_map(|queue| queue.front())
+        .map(|&index| (transactions[index].priority_fee(), std::cmp::Reverse(index)))
+        .collect();
+
+    let mut order = Vec::with_capacity(transactions.len());
+    while let Some((_, std::cmp::Reverse(index))) = heads.pop() {
+        order.push(index);
+        let queue = queues.get_mut(transactions[index].from.as_str()).unwrap();
+        queue.pop_front();
+        if let Some(&next) = queue.front() {
+            heads.push((transactions[next].priority_fee(), std::cmp::Reverse(next)));
+        }
+    }
+    order
+}
+
+/// 单个批次的费用
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchFees {
+    pub batch_id: u64,
+    /// 各笔交易的 (优先费, gas 用量)，按优先费升序
+    pub transactions: Vec<(u64, u64)>,
+    /// gas 用量与 gas price 之积的总和
+    pub total_fees: u64,
+}
+
+impl BatchFees {
+    pub fn new(batch_id: u64, mut transactions: Vec<(u64, u64)>, total_fees: u64) -> Self {
+        transactions.sort_unstable();
+        Self {
+            batch_id,
+            transactions,
+            total_fees,
+        }
+    }
+
+    /// 按 gas 加权的优先费分位数：累计 gas 首次达到总量的 `percentile`% 的交易的优先费
+    ///
+    /// 批次没有消耗 gas 时按交易数计；空批次为 0
+    fn percentile(&self, percentile: f64) -> u64 {
+        let batch_gas = self.gas_used();
+        let weight = |gas_used: u64| if batch_gas == 0 { 1 } else { gas_used };
+        let total: u64 = self.transactions.iter().map(|&(_, gas)| weight(gas)).sum();
+        let threshold = total as f64 * percentile / 100.0;
+        let mut cumulative = 0u64;
+        for &(fee, gas_used) in &self.transactions {
+            cumulative += weight(gas_used);
+            if cumulative as f64 >= threshold {
+                return fee;
+            }
+        }
+        self.transactions.last().map_or(0, |&(fee, _)| fee)
+    }
+
+    /// 优先费总额：各笔交易的 gas 用量与优先费之积的总和
+    pub fn priority_fees(&self) -> u64 {
+        self.transactions
+            .iter()
+            .fold(0u64, |total, &(fee, gas_used)| {
+                total.saturating_add(fee.saturating_mul(gas_used))
+            })
+    }
+
+    fn gas_used(&self) -> u64 {
+        self.transactions.iter().map(|&(_, gas)| gas).sum()
+    }
+}
+
+/// dubhe_feeHistory 的结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistoryReport {
+    /// 最早一个批次的序号；没有记录时为 `None`
+    pub oldest_batch: Option<u64>,
+    /// 每个批次按请求的分位数给出的优先费，批次从旧到新
+    pub reward: Vec<Vec<u64>>,
+    /// 每个批次的费用总额
+    pub total_fees: Vec<u64>,
+    /// 每个批次的 gas 用量
+    pub gas_used: Vec<u64>,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum FeeHistoryError {
+    #[error("percentiles must be ascending values in [0, 100], got {0:?}")]
+    InvalidPercentiles(Vec<f64>),
+}
+
+/// 最近批次的优先费记录
+pub struct FeeHistory {
+    capacity: usize,
+    batches: Mutex<VecDeque<BatchFees>>,
+}
+
+impl Default for FeeHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_FEE_HISTORY_BATCHES)
+    }
+}
+
+impl FeeHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            batches: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn record(&self, fees: BatchFees) {
+        let mut batches = self.batches.lock().unwrap();
+        if batches.len() == self.capacity {
+            batches.pop_front();
+        }
+        batches.push_back(fees);
+    }
+
+    /// 最近至多 `count` 个批次在各分位数上的优先费
+    pub fn query(
+        &self,
+        count: usize,
+        percentiles: &[f64],
+    ) -> Result<FeeHistoryReport, FeeHistoryError> {
+        let valid = percentiles.iter().all(|p| (0.0..=100.0).contains(p))
+            && percentiles.windows(2).all(|pair| pair[0] <= pair[1]);
+        if !valid {
+            return Err(FeeHistoryError::InvalidPercentiles(percentiles.to_vec()));
+        }
+
+        let batches = self.batches.lock().unwrap();
+        let recent: Vec<&BatchFees> = batches
+            .iter()
+            .skip(batches.len().saturating_sub(count))
+            .collect();
+        Ok(FeeHistoryReport {
+            oldest_batch: recent.first().map(|fees| fees.batch_id),
+            reward: recent
+                .iter()
+                .map(|fees| percentiles.iter().map(|&p| fees.percentile(p)).collect())
+                .collect(),
+            total_fees: recent.iter().map(|fees| fees.total_fees).collect(),
+            gas_used: recent.iter().map(|fees| fees.gas_used()).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx(from: &str, gas_price: u64, max_priority_fee: Option<u64>) -> Transaction {
+        Transaction {
+            hash: format!("{}-{}", from, gas_price),
+            from: from.to_string(),
+            to: None,
+            data: vec![],
+            gas_limit: 21000,
+            gas_price,
+            nonce: 0,
+            read_set: vec![],
+            write_set: vec![],
+            access_list: None,
+            max_priority_fee,
+        }
+    }
+
+    #[test]
+    fn test_priority_order_respects_sender_sequence() {
+        let transactions = vec![
+            tx("0xa", 1, None),
+            tx("0xa", 50, None),
+            tx("0xb", 10, None),
+            // 小费上限低于 gas price 时以小费为准
+            tx("0xc", 100, Some(5)),
+        ];
+        // 0xa 的第二笔出价最高，但须等第一笔先出
+        assert_eq!(priority_order(&transactions), vec![2, 3, 0, 1]);
+
+        let same = vec![tx("0xa", 1, None), tx("0xb", 1, None), tx("0xc", 1, None)];
+        assert_eq!(priority_order(&same), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_gas_weighted_percentiles() {
+        let history = FeeHistory::new(2);
+        history.record(BatchFees::new(0, vec![(1, 100)], 100));
+        // 优先费 2 的交易占 gas 的 90%
+        history.record(BatchFees::new(1, vec![(10, 10), (2, 90)], 380));
+        history.record(BatchFees::new(2, vec![], 0));
+
+        let report = history.query(10, &[10.0, 50.0, 95.0]).unwrap();
+        assert_eq!(report.oldest_batch, Some(1));
+        assert_eq!(report.reward, vec![vec![2, 2, 10], vec![0, 0, 0]]);
+        assert_eq!(report.total_fees, vec![380, 0]);
+        assert_eq!(report.gas_used, vec![100, 0]);
+
+        assert_eq!(history.query(1, &[]).unwrap().oldest_batch, Some(2));
+        assert!(history.query(1, &[50.0, 10.0]).is_err());
+        assert!(history.query(1, &[101.0]).is_err());
+    }
+}
@@ -8,10 +8,18 @@
 //! 3. Sui Object-DAG (DAG + Fast-path)
 
 pub mod strategy;
+pub mod access;
 pub mod conflict;
 pub mod dispatcher;
 pub mod types;
 pub mod error;
+pub mod fees;
+pub mod mempool;
+pub mod metrics;
+pub mod mvmemory;
+pub mod queue;
+pub mod resources;
+pub mod verify;
 
 #[cfg(feature = "solana_parallel")]
 pub mod solana_strategy;
@@ -23,21 +31,99 @@ pub mod aptos_strategy;
 pub mod sui_strategy;
 
 pub use strategy::*;
+pub use access::*;
 pub use conflict::*;
 pub use dispatcher::*;
 pub use types::*;
 pub use error::*;
+pub use fees::{FeeHistoryError, FeeHistoryReport};
+pub use mempool::*;
+pub use mvmemory::{OptimisticOutcome, StateView, VersionedExecutor, VersionedOutput};
+pub use queue::SubmissionLane;
+pub use resources::{ResourceMonitor, ResourceUsage};
+pub use verify::{ConflictKind, IssueSeverity, VerificationIssue};
 
 use anyhow::Result;
-use std::sync::Arc;
-use tokio::sync::mpsc;
-use tracing::info;
+use dubhe_observability::{NodeMetrics, TRACE_TARGET};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, Notify};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, info_span, warn, Instrument};
+
+use crate::fees::{priority_order, BatchFees, FeeHistory};
+use crate::metrics::{
+    efficiency_ratio, plan_efficiency_ppm, SchedulerMetrics, DEFAULT_EFFICIENCY_WINDOW,
+};
+use crate::queue::SubmissionQueue;
+
+/// 批次执行统计广播的缓冲批次数
+const STATS_CHANNEL_CAPACITY: usize = 64;
 
 /// 并行调度器主管理器
 pub struct ParallelScheduler {
     strategy: Arc<dyn ExecutionStrategy + Send + Sync>,
     dispatcher: TransactionDispatcher,
-    config: SchedulerConfig,
+    config: RwLock<SchedulerConfig>,
+    metrics: Arc<SchedulerMetrics>,
+    access_estimator: Option<Arc<AccessSetEstimator>>,
+    versioned_executor: Option<Arc<dyn VersionedExecutor>>,
+    node_metrics: Option<Arc<NodeMetrics>>,
+    resources: ResourceMonitor,
+    fee_history: FeeHistory,
+    stats_tx: broadcast::Sender<ExecutionStats>,
+    outcomes_tx: broadcast::Sender<Vec<TransactionOutcome>>,
+    queue: SubmissionQueue,
+    next_batch_id: AtomicU64,
+
+    // 停机排空
+    accepting: AtomicBool,
+    in_flight: AtomicUsize,
+    drained: Notify,
+    cancel: CancellationToken,
+}
+
+/// 排空结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DrainOutcome {
+    /// 所有在途批次已完成
+    Drained,
+    /// 超时后强制取消的批次数
+    Cancelled { remaining: usize },
+}
+
+/// 在途批次计数，drop 时减一并唤醒排空等待者
+struct InFlightGuard<'a> {
+    scheduler: &'a ParallelScheduler,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        if self.scheduler.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.scheduler.drained.notify_waiters();
+        }
+    }
+}
+
+/// 批次交易计入提交队列长度，批次结束、取消或被拒绝时扣除
+struct QueuedGuard {
+    metrics: Arc<NodeMetrics>,
+    count: i64,
+}
+
+impl QueuedGuard {
+    fn new(metrics: Arc<NodeMetrics>, count: i64) -> Self {
+        metrics.scheduler_queue_length.add(count);
+        Self { metrics, count }
+    }
+}
+
+impl Drop for QueuedGuard {
+    fn drop(&mut self) {
+        self.metrics.scheduler_queue_length.sub(self.count);
+    }
 }
 
 impl ParallelScheduler {
@@ -47,42 +133,398 @@ impl ParallelScheduler {
             StrategyType::SolanaParallel => Arc::new(solana_strategy::SolanaStrategy::new()),
             
             #[cfg(feature = "aptos_stm")]
-            StrategyType::AptosSTM => Arc::new(aptos_strategy::AptosStrategy::new()),
+            StrategyType::AptosSTM => Arc::new(
+                aptos_strategy::AptosStrategy::new().with_workers(config.worker_threads),
+            ),
             
             #[cfg(feature = "sui_object")]
             StrategyType::SuiObject => Arc::new(sui_strategy::SuiStrategy::new()),
+
+            StrategyType::Sequential => Arc::new(SequentialStrategy),
             
-            _ => return Err(anyhow::anyhow!("Unsupported strategy type: {:?}", strategy_type)),
+            _ => return Err(SchedulerError::UnsupportedStrategy(strategy_type).into()),
         };
 
-        let dispatcher = TransactionDispatcher::new(config.worker_threads)?;
-
         info!("Parallel scheduler initialized with strategy: {:?}", strategy_type);
 
+        Self::with_strategy(strategy, config)
+    }
+
+    /// 使用自定义执行策略创建调度器
+    pub fn with_strategy(
+        strategy: Arc<dyn ExecutionStrategy + Send + Sync>,
+        config: SchedulerConfig,
+    ) -> Result<Self> {
+        let dispatcher = TransactionDispatcher::new(config.worker_threads)?
+            .with_timeout(Duration::from_millis(config.timeout_ms));
+
         Ok(Self {
             strategy,
             dispatcher,
-            config,
+            config: RwLock::new(config),
+            metrics: Arc::new(SchedulerMetrics::new(DEFAULT_EFFICIENCY_WINDOW)),
+            access_estimator: None,
+            versioned_executor: None,
+            node_metrics: None,
+            resources: ResourceMonitor::new(),
+            fee_history: FeeHistory::default(),
+            stats_tx: broadcast::channel(STATS_CHANNEL_CAPACITY).0,
+            outcomes_tx: broadcast::channel(STATS_CHANNEL_CAPACITY).0,
+            queue: SubmissionQueue::new(),
+            next_batch_id: AtomicU64::new(0),
+            accepting: AtomicBool::new(true),
+            in_flight: AtomicUsize::new(0),
+            drained: Notify::new(),
+            cancel: CancellationToken::new(),
         })
     }
 
-    /// 提交交易批次进行并行执行
+    /// 设置单笔交易执行器（默认不执行合约）
+    pub fn with_executor(mut self, executor: Arc<dyn TransactionExecutor>) -> Self {
+        self.dispatcher = self.dispatcher.with_executor(executor);
+        self
+    }
+
+    /// 为未声明读写集合的交易启用估算
+    pub fn with_access_estimator(mut self, estimator: Arc<AccessSetEstimator>) -> Self {
+        self.access_estimator = Some(estimator);
+        self
+    }
+
+    /// 设置版本化执行器，支持乐观执行的策略（Block-STM）将用它自行执行批次
+    pub fn with_versioned_executor(mut self, executor: Arc<dyn VersionedExecutor>) -> Self {
+        self.versioned_executor = Some(executor);
+        self
+    }
+
+    /// 将批次执行情况记录到共享的 Prometheus 指标
+    pub fn with_node_metrics(mut self, metrics: Arc<NodeMetrics>) -> Self {
+        self.node_metrics = Some(metrics);
+        self
+    }
+
+    /// 当前生效的调度参数
+    pub fn config(&self) -> SchedulerConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /// 运行时调整批次大小与单笔交易超时，从下一个批次起生效
+    pub fn update_limits(&self, batch_size: usize, timeout_ms: u64) {
+        let mut config = self.config.write().unwrap();
+        config.batch_size = batch_size;
+        config.timeout_ms = timeout_ms;
+        self.dispatcher.set_timeout(Duration::from_millis(timeout_ms));
+        info!(
+            "Scheduler limits updated: batch_size={}, timeout_ms={}",
+            batch_size, timeout_ms
+        );
+    }
+
+    /// 订阅每个已完成批次的执行统计
+    pub fn subscribe_stats(&self) -> broadcast::Receiver<ExecutionStats> {
+        self.stats_tx.subscribe()
+    }
+
+    /// 订阅每个已完成批次中各笔交易的结果（含发送方与目标合约）
+    pub fn subscribe_outcomes(&self) -> broadcast::Receiver<Vec<TransactionOutcome>> {
+        self.outcomes_tx.subscribe()
+    }
+
+    /// 中止所有在途批次的句柄
+    pub fn cancel_handle(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// 提交交易批次进行并行执行（用户通道）
     pub async fn submit_batch(&self, transactions: Vec<Transaction>) -> Result<BatchResult> {
-        info!("Submitting batch of {} transactions", transactions.len());
+        self.submit_to_lane(transactions, SubmissionLane::User)
+            .await
+    }
+
+    /// 经指定通道提交批次；用户通道已满时立即返回 [`SchedulerError::QueueFull`]
+    pub async fn submit_to_lane(
+        &self,
+        transactions: Vec<Transaction>,
+        lane: SubmissionLane,
+    ) -> Result<BatchResult> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let _guard = InFlightGuard { scheduler: self };
+
+        if !self.accepting.load(Ordering::SeqCst) {
+            return Err(SchedulerError::ShuttingDown.into());
+        }
+
+        let capacity = self.config().max_queue_size;
+        let mut ticket = match self.queue.enqueue(lane, transactions.len(), capacity) {
+            Ok(ticket) => ticket,
+            Err(e) => {
+                debug!(
+                    "Rejected batch of {} transactions: {}",
+                    transactions.len(),
+                    e
+                );
+                if let Some(metrics) = &self.node_metrics {
+                    metrics.scheduler_rejected_batches.inc();
+                }
+                return Err(e.into());
+            }
+        };
+        let _queued = self
+            .node_metrics
+            .as_ref()
+            .map(|metrics| QueuedGuard::new(metrics.clone(), transactions.len() as i64));
+
+        // 批次内的交易、加载与 VM 执行 span 都挂在这个 span 下
+        let span = info_span!(
+            target: TRACE_TARGET,
+            "scheduler.submit_batch",
+            batch.size = transactions.len(),
+            scheduler.strategy = ?self.strategy.strategy_type(),
+        );
+        let batch = async {
+            let queue_time = ticket.acquire().await;
+            self.execute_batch(transactions, queue_time).await
+        };
+        tokio::select! {
+            result = batch.instrument(span) => result,
+            _ = self.cancel.cancelled() => Err(SchedulerError::Cancelled.into()),
+        }
+    }
 
-        // 1. 冲突检测与依赖分析
+    /// 用户通道能否再接受 `transactions` 笔交易，供上游在取出批次前退避
+    pub fn has_queue_capacity(&self, transactions: usize) -> bool {
+        self.queue
+            .has_capacity(transactions, self.config().max_queue_size)
+    }
+
+    /// 停止接收新批次，等待在途批次完成；超时后强制取消
+    pub async fn drain(&self, timeout: Duration) -> DrainOutcome {
+        self.accepting.store(false, Ordering::SeqCst);
+        info!(
+            "Draining scheduler: {} batches in flight",
+            self.in_flight_batches()
+        );
+
+        if tokio::time::timeout(timeout, self.wait_idle()).await.is_ok() {
+            return DrainOutcome::Drained;
+        }
+
+        let remaining = self.in_flight_batches();
+        warn!(
+            "Scheduler drain timed out after {:?}, cancelling {} batches",
+            timeout, remaining
+        );
+        self.cancel.cancel();
+        self.wait_idle().await;
+
+        DrainOutcome::Cancelled { remaining }
+    }
+
+    /// 当前在途批次数
+    pub fn in_flight_batches(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    async fn wait_idle(&self) {
+        loop {
+            let notified = self.drained.notified();
+            if self.in_flight_batches() == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    async fn execute_batch(
+        &self,
+        submitted: Vec<Transaction>,
+        queue_time: Duration,
+    ) -> Result<BatchResult> {
+        info!("Submitting batch of {} transactions", submitted.len());
+        let started = Instant::now();
+        let resources_start = self.resources.sample();
+        let config = self.config();
+        let seed = if config.deterministic {
+            Some(batch_seed(&submitted)?)
+        } else {
+            None
+        };
+        // 策略按批次内位置决定冲突交易的先后，按优先费重排后出价高者先执行
+        let transactions: Vec<Transaction> = priority_order(&submitted)
+            .into_iter()
+            .map(|index| submitted[index].clone())
+            .collect();
+        let ctx = BatchContext {
+            batch_id: self.next_batch_id.fetch_add(1, Ordering::Relaxed),
+            transactions: &transactions,
+            seed,
+        };
+        self.strategy.on_batch_start(&ctx).await?;
+
+        // 1. 冲突分析
         let conflict_graph = self.analyze_conflicts(&transactions).await?;
-        
+        let parallelism_bound = conflict_graph.parallelism_bound();
+        debug!(
+            "Batch has {} conflict components, critical path {:.2}ms, parallelism bound {:.2}",
+            conflict_graph.components().len(),
+            conflict_graph.critical_path_ms(),
+            parallelism_bound
+        );
+
         // 2. 生成执行计划
-        let execution_plan = self.strategy.plan_execution(&transactions, &conflict_graph).await?;
-        
-        // 3. 并行执行
-        let results = self.dispatcher.execute_parallel(execution_plan).await?;
-        
-        // 4. 收集结果
+        let execution_plan = self
+            .strategy
+            .plan_execution(&transactions, &conflict_graph)
+            .await?;
+        let efficiency_ppm =
+            plan_efficiency_ppm(&execution_plan, transactions.len(), config.worker_threads);
+
+        // 3. 并行执行：策略支持乐观执行时由策略执行，冲突数取实际中止次数
+        let optimistic = match &self.versioned_executor {
+            Some(executor) => {
+                self.strategy
+                    .execute_optimistic(&transactions, executor.clone(), &self.cancel)
+                    .await?
+            }
+            None => None,
+        };
+        let (mut results, conflicts) = match optimistic {
+            Some(outcome) => (outcome.results, outcome.aborts),
+            None => {
+                // 乐观执行不按计划执行，只校验交给分发器的计划
+                if config.verify_plans {
+                    self.check_plan(&execution_plan, &transactions, &conflict_graph)?;
+                }
+                let results = self
+                    .dispatcher
+                    .execute_parallel(execution_plan, &transactions, &self.cancel)
+                    .await?;
+                (results, conflict_graph.edges.len())
+            }
+        };
+        if config.deterministic {
+            results = order_by_index(&submitted, results);
+        }
+
+        // 4. 策略提交跨批次状态，收集结果并更新统计
+        self.strategy.on_batch_commit(&ctx, &results).await?;
+        self.metrics.record_batch(
+            self.strategy.strategy_type(),
+            transactions.len(),
+            conflicts,
+            efficiency_ppm,
+        );
+
+        let resources = resources_start
+            .zip(self.resources.sample())
+            .map(|(start, end)| self.resources.usage(&start, &end));
+        let fees = batch_fees(ctx.batch_id, &transactions, &results);
+        let successful = results.iter().filter(|r| r.success).count();
+        let execution_stats = ExecutionStats {
+            total_transactions: transactions.len(),
+            successful_transactions: successful,
+            failed_transactions: results.len() - successful,
+            total_gas_used: results.iter().map(|r| r.gas_used).sum(),
+            execution_time_ms: started.elapsed().as_millis() as u64,
+            parallel_efficiency: efficiency_ratio(efficiency_ppm),
+            conflicts_detected: conflicts,
+            queue_time_ms: queue_time.as_millis() as u64,
+            parallelism_bound,
+            resources,
+            total_fees: fees.total_fees,
+            priority_fees: fees.priority_fees(),
+        };
+        self.fee_history.record(fees);
+        if let Some(metrics) = &self.node_metrics {
+            metrics
+                .batch_queue_seconds
+                .observe(queue_time.as_secs_f64());
+            metrics.record_batch(
+                started.elapsed(),
+                execution_stats.failed_transactions,
+                results.iter().map(|r| r.gas_used),
+            );
+            if let Some(usage) = &resources {
+                metrics.record_batch_resources(
+                    usage.cpu_utilization,
+                    usage.rss_delta_bytes,
+                    usage.peak_rss_bytes,
+                    usage.io_bytes,
+                );
+            }
+        }
+
+        // 没有订阅者时发送失败，忽略即可
+        let _ = self.stats_tx.send(execution_stats.clone());
+        if self.outcomes_tx.receiver_count() > 0 {
+            let senders: HashMap<&str, &Transaction> = transactions
+                .iter()
+                .map(|tx| (tx.hash.as_str(), tx))
+                .collect();
+            let outcomes = results
+                .iter()
+                .filter_map(|result| {
+                    let tx = senders.get(result.tx_hash.as_str())?;
+                    Some(TransactionOutcome {
+                        tx_hash: result.tx_hash.clone(),
+                        from: tx.from.clone(),
+                        to: tx.to.clone(),
+                        gas_used: result.gas_used,
+                        success: result.success,
+                    })
+                })
+                .collect();
+            let _ = self.outcomes_tx.send(outcomes);
+        }
+
         Ok(BatchResult {
             transaction_results: results,
-            execution_stats: ExecutionStats::default(), // TODO: 收集实际统计
+            execution_stats,
+        })
+    }
+
+    /// 冲突检测与依赖分析后生成执行计划（不执行）
+    ///
+    /// 与执行时一样按优先费重排后规划，返回的下标仍指向传入的批次
+    pub async fn plan_batch(&self, transactions: &[Transaction]) -> Result<ExecutionPlan> {
+        let order = priority_order(transactions);
+        let ordered: Vec<Transaction> = order
+            .iter()
+            .map(|&index| transactions[index].clone())
+            .collect();
+        let conflict_graph = self.analyze_conflicts(&ordered).await?;
+        let plan = self
+            .strategy
+            .plan_execution(&ordered, &conflict_graph)
+            .await?;
+        if self.config().verify_plans {
+            self.check_plan(&plan, &ordered, &conflict_graph)?;
+        }
+        Ok(plan.remap(&order))
+    }
+
+    /// 最近至多 `batches` 个批次在各分位数上的优先费（按 gas 加权）
+    pub fn fee_history(
+        &self,
+        batches: usize,
+        percentiles: &[f64],
+    ) -> Result<FeeHistoryReport, FeeHistoryError> {
+        self.fee_history.query(batches, percentiles)
+    }
+
+    fn check_plan(
+        &self,
+        plan: &ExecutionPlan,
+        transactions: &[Transaction],
+        conflict_graph: &ConflictGraph,
+    ) -> Result<()> {
+        verify::ensure_safe_plan(plan, transactions, conflict_graph).map_err(|error| {
+            warn!(
+                "❌ Strategy {} produced an unsafe plan: {}",
+                self.strategy.name(),
+                error
+            );
+            error.into()
         })
     }
 
@@ -90,22 +532,694 @@ impl ParallelScheduler {
     pub async fn get_status(&self) -> SchedulerStatus {
         SchedulerStatus {
             strategy_type: self.get_strategy_type(),
-            worker_threads: self.config.worker_threads,
-            queue_length: self.dispatcher.queue_length().await,
-            total_processed: 0, // TODO: 实现统计
-            conflicts_detected: 0, // TODO: 实现统计
-            parallel_efficiency: 0.95, // TODO: 计算实际效率
+            worker_threads: self.config().worker_threads,
+            queue_length: self.queue.depth(),
+            total_processed: self.metrics.total_processed(),
+            conflicts_detected: self.metrics.conflicts_detected(),
+            parallel_efficiency: self.metrics.parallel_efficiency(),
+            per_strategy: self.metrics.per_strategy(),
         }
     }
 
     /// 分析交易冲突
     async fn analyze_conflicts(&self, transactions: &[Transaction]) -> Result<ConflictGraph> {
-        let mut analyzer = ConflictAnalyzer::new();
+        let mut analyzer = match &self.access_estimator {
+            Some(estimator) => ConflictAnalyzer::with_estimator(estimator.clone()),
+            None => ConflictAnalyzer::new(),
+        };
         analyzer.analyze(transactions).await
     }
 
     fn get_strategy_type(&self) -> StrategyType {
-        // TODO: 从 strategy 获取类型
-        StrategyType::SolanaParallel
+        self.strategy.strategy_type()
+    }
+}
+
+/// 将结果按交易在批次中的原始序号重排，与执行交错无关
+///
+/// 同一哈希出现多次时按出现顺序依次对应
+fn order_by_index(
+    transactions: &[Transaction],
+    results: Vec<TransactionResult>,
+) -> Vec<TransactionResult> {
+    let mut positions: HashMap<&str, VecDeque<usize>> = HashMap::new();
+    for (index, tx) in transactions.iter().enumerate() {
+        positions
+            .entry(tx.hash.as_str())
+            .or_default()
+            .push_back(index);
     }
-} 
\ No newline at end of file
+    let mut indexed: Vec<(usize, TransactionResult)> = results
+        .into_iter()
+        .map(|result| {
+            let index = positions
+                .get_mut(result.tx_hash.as_str())
+                .and_then(|queue| queue.pop_front())
+                .unwrap_or(usize::MAX);
+            (index, result)
+        })
+        .collect();
+    indexed.sort_by_key(|(index, _)| *index);
+    indexed.into_iter().map(|(_, result)| result).collect()
+}
+
+/// 批次中各笔交易的优先费与 gas 用量，以及费用总额；按哈希对应，未找到交易的结果不计
+fn batch_fees(
+    batch_id: u64,
+    transactions: &[Transaction],
+    results: &[TransactionResult],
+) -> BatchFees {
+    let by_hash: HashMap<&str, &Transaction> = transactions
+        .iter()
+        .map(|tx| (tx.hash.as_str(), tx))
+        .collect();
+    let mut fees = Vec::with_capacity(results.len());
+    let mut total_fees = 0u64;
+    for result in results {
+        if let Some(tx) = by_hash.get(result.tx_hash.as_str()) {
+            fees.push((tx.priority_fee(), result.gas_used));
+            total_fees = total_fees.saturating_add(result.gas_used.saturating_mul(tx.gas_price));
+        }
+    }
+    BatchFees::new(batch_id, fees, total_fees)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    /// 规划阶段耗时固定的策略，模拟长时间运行的批次
+    struct SlowStrategy {
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl ExecutionStrategy for SlowStrategy {
+        async fn plan_execution(
+            &self,
+            transactions: &[Transaction],
+            conflict_graph: &ConflictGraph,
+        ) -> SchedulerResult<ExecutionPlan> {
+            tokio::time::sleep(self.delay).await;
+            SequentialStrategy
+                .plan_execution(transactions, conflict_graph)
+                .await
+        }
+
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn description(&self) -> &str {
+            "Slow strategy for shutdown tests"
+        }
+
+        fn strategy_type(&self) -> StrategyType {
+            StrategyType::Sequential
+        }
+    }
+
+    fn scheduler(delay: Duration) -> Arc<ParallelScheduler> {
+        Arc::new(
+            ParallelScheduler::with_strategy(
+                Arc::new(SlowStrategy { delay }),
+                SchedulerConfig {
+                    worker_threads: 1,
+                    ..SchedulerConfig::default()
+                },
+            )
+            .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_drain_waits_for_in_flight_batch() {
+        let scheduler = scheduler(Duration::from_millis(100));
+        let batch = tokio::spawn({
+            let scheduler = scheduler.clone();
+            async move { scheduler.submit_batch(vec![]).await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(scheduler.in_flight_batches(), 1);
+
+        let outcome = scheduler.drain(Duration::from_secs(5)).await;
+        assert_eq!(outcome, DrainOutcome::Drained);
+        assert!(batch.await.unwrap().is_ok());
+
+        // 排空后拒绝新批次
+        assert!(scheduler.submit_batch(vec![]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_drain_cancels_after_timeout() {
+        let scheduler = scheduler(Duration::from_secs(60));
+        let batch = tokio::spawn({
+            let scheduler = scheduler.clone();
+            async move { scheduler.submit_batch(vec![]).await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let started = std::time::Instant::now();
+        let outcome = scheduler.drain(Duration::from_millis(100)).await;
+        assert_eq!(outcome, DrainOutcome::Cancelled { remaining: 1 });
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let error = batch.await.unwrap().unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<SchedulerError>(),
+            Some(SchedulerError::Cancelled)
+        ));
+        assert_eq!(scheduler.in_flight_batches(), 0);
+    }
+
+    fn tx(hash: &str, read_set: &[&str], write_set: &[&str]) -> Transaction {
+        Transaction {
+            hash: hash.to_string(),
+            from: "0xsender".to_string(),
+            to: None,
+            data: vec![],
+            gas_limit: 21000,
+            gas_price: 1,
+            nonce: 0,
+            read_set: read_set.iter().map(|s| s.to_string()).collect(),
+            write_set: write_set.iter().map(|s| s.to_string()).collect(),
+            access_list: None,
+            max_priority_fee: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_sheds_user_batches_until_drained() {
+        let scheduler = Arc::new(
+            ParallelScheduler::with_strategy(
+                Arc::new(SlowStrategy {
+                    delay: Duration::from_millis(50),
+                }),
+                SchedulerConfig {
+                    worker_threads: 1,
+                    max_queue_size: 4,
+                    ..SchedulerConfig::default()
+                },
+            )
+            .unwrap(),
+        );
+        let submit = |hashes: [&str; 2]| {
+            let scheduler = scheduler.clone();
+            let batch = hashes
+                .into_iter()
+                .map(|hash| tx(hash, &[], &[hash]))
+                .collect();
+            tokio::spawn(async move { scheduler.submit_batch(batch).await })
+        };
+
+        // 一个批次执行中、一个批次等待，队列恰好满
+        let running = submit(["0x1", "0x2"]);
+        let waiting = submit(["0x3", "0x4"]);
+        while scheduler.get_status().await.queue_length < 4 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        for _ in 0..10 {
+            let error = scheduler
+                .submit_batch(vec![tx("0x5", &[], &["0x5"])])
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                error.downcast_ref::<SchedulerError>(),
+                Some(SchedulerError::QueueFull {
+                    depth: 4,
+                    capacity: 4,
+                    ..
+                })
+            ));
+        }
+        assert!(!scheduler.has_queue_capacity(1));
+
+        // 共识通道不受容量限制
+        let ordered = scheduler
+            .submit_to_lane(vec![tx("0x6", &[], &["0x6"])], SubmissionLane::Consensus)
+            .await
+            .unwrap();
+        assert_eq!(ordered.execution_stats.total_transactions, 1);
+
+        running.await.unwrap().unwrap();
+        let waited = waiting.await.unwrap().unwrap();
+        assert!(waited.execution_stats.queue_time_ms >= 40);
+        assert_eq!(scheduler.get_status().await.queue_length, 0);
+
+        // 排空后恢复接收
+        assert!(scheduler.has_queue_capacity(4));
+        let batch = scheduler
+            .submit_batch(vec![tx("0x5", &[], &["0x5"])])
+            .await
+            .unwrap();
+        assert_eq!(batch.execution_stats.total_transactions, 1);
+        assert_eq!(scheduler.get_status().await.total_processed, 6);
+    }
+
+    /// 记录钩子调用顺序并跨批次累计已提交交易数
+    #[derive(Default)]
+    struct RecordingStrategy {
+        events: std::sync::Mutex<Vec<String>>,
+        committed: AtomicUsize,
+    }
+
+    impl RecordingStrategy {
+        fn record(&self, event: String) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    #[async_trait]
+    impl ExecutionStrategy for RecordingStrategy {
+        async fn on_batch_start(&self, ctx: &BatchContext<'_>) -> SchedulerResult<()> {
+            self.record(format!("start {}", ctx.batch_id));
+            Ok(())
+        }
+
+        async fn plan_execution(
+            &self,
+            transactions: &[Transaction],
+            conflict_graph: &ConflictGraph,
+        ) -> SchedulerResult<ExecutionPlan> {
+            self.record("plan".to_string());
+            SequentialStrategy
+                .plan_execution(transactions, conflict_graph)
+                .await
+        }
+
+        async fn on_batch_commit(
+            &self,
+            ctx: &BatchContext<'_>,
+            results: &[TransactionResult],
+        ) -> SchedulerResult<()> {
+            let total = self.committed.fetch_add(results.len(), Ordering::SeqCst) + results.len();
+            self.record(format!("commit {} {}", ctx.batch_id, total));
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn description(&self) -> &str {
+            "Records lifecycle hook calls"
+        }
+
+        fn strategy_type(&self) -> StrategyType {
+            StrategyType::SolanaParallel
+        }
+    }
+
+    #[tokio::test]
+    async fn test_strategy_hooks_wrap_each_batch() {
+        let strategy = Arc::new(RecordingStrategy::default());
+        let scheduler =
+            ParallelScheduler::with_strategy(strategy.clone(), SchedulerConfig::default()).unwrap();
+
+        for batch in 0..3 {
+            let transactions = (0..=batch)
+                .map(|i| tx(&format!("0x{}{}", batch, i), &[], &["0x01"]))
+                .collect();
+            scheduler.submit_batch(transactions).await.unwrap();
+        }
+
+        assert_eq!(
+            *strategy.events.lock().unwrap(),
+            vec![
+                "start 0",
+                "plan",
+                "commit 0 1",
+                "start 1",
+                "plan",
+                "commit 1 3",
+                "start 2",
+                "plan",
+                "commit 2 6",
+            ]
+        );
+        assert_eq!(strategy.committed.load(Ordering::SeqCst), 6);
+        // 状态报告的是策略自身的类型
+        assert_eq!(
+            scheduler.get_status().await.strategy_type,
+            StrategyType::SolanaParallel
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status_counters_accumulate() {
+        let scheduler = ParallelScheduler::new(
+            StrategyType::Sequential,
+            SchedulerConfig {
+                worker_threads: 2,
+                ..SchedulerConfig::default()
+            },
+        )
+        .unwrap();
+
+        // 批次一：0x01 写写冲突一次
+        scheduler
+            .submit_batch(vec![tx("a", &[], &["0x01"]), tx("b", &[], &["0x01"])])
+            .await
+            .unwrap();
+        // 批次二：写读冲突一次，另一笔无冲突
+        let batch = scheduler
+            .submit_batch(vec![
+                tx("c", &[], &["0x02"]),
+                tx("d", &["0x02"], &[]),
+                tx("e", &[], &["0x03"]),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(batch.execution_stats.total_transactions, 3);
+        assert_eq!(batch.execution_stats.conflicts_detected, 1);
+
+        let status = scheduler.get_status().await;
+        assert_eq!(status.strategy_type, StrategyType::Sequential);
+        assert_eq!(status.total_processed, 5);
+        assert_eq!(status.conflicts_detected, 2);
+        // 串行策略在两个 worker 上的利用率为 50%
+        assert!((status.parallel_efficiency - 0.5).abs() < 1e-9);
+        assert_eq!(
+            status.per_strategy[&StrategyType::Sequential],
+            StrategyCounters {
+                batches: 2,
+                transactions: 5,
+                conflicts: 2,
+            }
+        );
+    }
+
+    /// 执行耗时随交易倒序递减，使完成顺序与批次顺序相反
+    struct JitterExecutor;
+
+    #[async_trait]
+    impl TransactionExecutor for JitterExecutor {
+        async fn execute(&self, transaction: &Transaction) -> SchedulerResult<TransactionResult> {
+            tokio::time::sleep(Duration::from_millis(20 - transaction.nonce)).await;
+            Ok(TransactionResult {
+                tx_hash: transaction.hash.clone(),
+                success: transaction.nonce % 3 != 0,
+                gas_used: 21000 + transaction.nonce,
+                output: transaction.hash.as_bytes().to_vec(),
+                logs: vec![],
+                error: None,
+            })
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_deterministic_mode_digest_matches_across_schedulers() {
+        let batch: Vec<Transaction> = (0..16u64)
+            .map(|i| {
+                let mut tx = tx(&format!("0x{:02x}", i), &[], &[&format!("0x{}", i % 4)]);
+                tx.nonce = i;
+                tx
+            })
+            .collect();
+
+        let run = |workers: usize| {
+            let batch = batch.clone();
+            async move {
+                let scheduler = ParallelScheduler::new(
+                    StrategyType::SolanaParallel,
+                    SchedulerConfig {
+                        worker_threads: workers,
+                        deterministic: true,
+                        ..SchedulerConfig::default()
+                    },
+                )
+                .unwrap()
+                .with_executor(Arc::new(JitterExecutor));
+                scheduler.submit_batch(batch).await.unwrap()
+            }
+        };
+        let first = run(1).await;
+        let second = run(8).await;
+
+        let hashes: Vec<&str> = batch.iter().map(|tx| tx.hash.as_str()).collect();
+        for result in [&first, &second] {
+            let ordered: Vec<&str> = result
+                .transaction_results
+                .iter()
+                .map(|r| r.tx_hash.as_str())
+                .collect();
+            assert_eq!(ordered, hashes);
+        }
+        assert_eq!(
+            first.canonical_digest().unwrap(),
+            second.canonical_digest().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_order_by_index_restores_batch_order() {
+        let batch = vec![tx("a", &[], &[]), tx("b", &[], &[]), tx("a", &[], &[])];
+        let result = |hash: &str, gas_used: u64| TransactionResult {
+            tx_hash: hash.to_string(),
+            success: true,
+            gas_used,
+            output: vec![],
+            logs: vec![],
+            error: None,
+        };
+        let ordered = order_by_index(&batch, vec![result("b", 2), result("a", 1), result("a", 3)]);
+        let order: Vec<(&str, u64)> = ordered
+            .iter()
+            .map(|r| (r.tx_hash.as_str(), r.gas_used))
+            .collect();
+        assert_eq!(order, vec![("a", 1), ("b", 2), ("a", 3)]);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_after_batch() {
+        let metrics = Arc::new(NodeMetrics::new().unwrap());
+        let scheduler = ParallelScheduler::new(StrategyType::Sequential, SchedulerConfig::default())
+            .unwrap()
+            .with_node_metrics(metrics.clone());
+        scheduler
+            .submit_batch(vec![tx("0x1", &[], &["a"]), tx("0x2", &[], &["b"])])
+            .await
+            .unwrap();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let exporter = dubhe_observability::MetricsExporter::new(metrics);
+        tokio::spawn(async move { exporter.serve(listener).await });
+
+        let uri = format!("http://{}/metrics", addr).parse().unwrap();
+        let response = hyper::Client::new().get(uri).await.unwrap();
+        assert!(response.status().is_success());
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        for family in [
+            "dubhe_transactions_processed_total",
+            "dubhe_transactions_failed_total",
+            "dubhe_batch_execution_seconds",
+            "dubhe_transaction_gas_used",
+            "dubhe_scheduler_queue_length",
+            "dubhe_compilation_cache_hit_ratio",
+            "dubhe_vm_active_instances",
+            "dubhe_scheduler_batch_cpu_utilization",
+            "dubhe_scheduler_batch_peak_rss_bytes",
+        ] {
+            assert!(body.contains(&format!("# TYPE {} ", family)), "missing {}", family);
+        }
+        assert!(body.contains("dubhe_transactions_processed_total 2"));
+        assert!(body.contains("dubhe_batch_execution_seconds_count 1"));
+        assert!(body.contains("dubhe_scheduler_queue_length 0"));
+    }
+
+    /// 规划阶段空转 CPU 的策略，模拟计算密集的批次
+    struct BusyStrategy {
+        spin: Duration,
+    }
+
+    #[async_trait]
+    impl ExecutionStrategy for BusyStrategy {
+        async fn plan_execution(
+            &self,
+            transactions: &[Transaction],
+            conflict_graph: &ConflictGraph,
+        ) -> SchedulerResult<ExecutionPlan> {
+            let started = Instant::now();
+            let mut x = 0u64;
+            while started.elapsed() < self.spin {
+                x = std::hint::black_box(x.wrapping_mul(31).wrapping_add(7));
+            }
+            SequentialStrategy
+                .plan_execution(transactions, conflict_graph)
+                .await
+        }
+
+        fn name(&self) -> &str {
+            "busy"
+        }
+
+        fn description(&self) -> &str {
+            "CPU-bound strategy for resource sampling tests"
+        }
+
+        fn strategy_type(&self) -> StrategyType {
+            StrategyType::Sequential
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_busy_batch_reports_cpu_usage() {
+        let metrics = Arc::new(NodeMetrics::new().unwrap());
+        let scheduler = ParallelScheduler::with_strategy(
+            Arc::new(BusyStrategy {
+                spin: Duration::from_millis(200),
+            }),
+            SchedulerConfig::default(),
+        )
+        .unwrap()
+        .with_node_metrics(metrics.clone());
+
+        let result = scheduler
+            .submit_batch(vec![tx("0x1", &[], &["a"])])
+            .await
+            .unwrap();
+        let usage = result.execution_stats.resources.unwrap();
+        assert!(usage.cpu_time_ms > 0, "{:?}", usage);
+        assert!(usage.cpu_utilization > 0.0);
+        assert!(metrics.batch_cpu_utilization.get() > 0.0);
+        assert!(metrics.batch_peak_rss_bytes.get() > 0);
+    }
+
+    /// 不做冲突分析、把整个批次放进一组的策略
+    struct SingleGroupStrategy;
+
+    #[async_trait]
+    impl ExecutionStrategy for SingleGroupStrategy {
+        async fn plan_execution(
+            &self,
+            transactions: &[Transaction],
+            _conflict_graph: &ConflictGraph,
+        ) -> SchedulerResult<ExecutionPlan> {
+            let all: Vec<usize> = (0..transactions.len()).collect();
+            Ok(ExecutionPlan {
+                parallel_groups: vec![all.clone()],
+                dependency_order: all,
+                unordered: vec![],
+            })
+        }
+
+        fn name(&self) -> &str {
+            "single_group"
+        }
+
+        fn description(&self) -> &str {
+            "Runs the whole batch as one unordered group"
+        }
+
+        fn strategy_type(&self) -> StrategyType {
+            StrategyType::Sequential
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_plans_rejects_unsafe_plan() {
+        let scheduler = |verify_plans| {
+            ParallelScheduler::with_strategy(
+                Arc::new(SingleGroupStrategy),
+                SchedulerConfig {
+                    verify_plans,
+                    ..SchedulerConfig::default()
+                },
+            )
+            .unwrap()
+        };
+        let conflicting = || vec![tx("0x1", &[], &["a"]), tx("0x2", &["a"], &[])];
+
+        let error = scheduler(true)
+            .submit_batch(conflicting())
+            .await
+            .unwrap_err();
+        match error.downcast_ref::<SchedulerError>() {
+            Some(SchedulerError::UnsafePlan { issues }) => {
+                assert_eq!(issues.len(), 1);
+                assert_eq!(issues[0].conflict, ConflictKind::ReadWrite);
+                assert_eq!(issues[0].key, "a");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(scheduler(true).plan_batch(&conflicting()).await.is_err());
+
+        // 无冲突的批次与未开启校验时照常执行
+        let disjoint = vec![tx("0x1", &[], &["a"]), tx("0x2", &[], &["b"])];
+        assert!(scheduler(true).submit_batch(disjoint).await.is_ok());
+        assert!(scheduler(false).submit_batch(conflicting()).await.is_ok());
+    }
+
+    /// 记录执行顺序的执行器，每笔交易消耗 21000 gas
+    #[derive(Default)]
+    struct RecordingExecutor {
+        executed: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl TransactionExecutor for RecordingExecutor {
+        async fn execute(&self, transaction: &Transaction) -> SchedulerResult<TransactionResult> {
+            self.executed.lock().unwrap().push(transaction.hash.clone());
+            Ok(TransactionResult {
+                tx_hash: transaction.hash.clone(),
+                success: true,
+                gas_used: 21000,
+                output: vec![],
+                logs: vec![],
+                error: None,
+            })
+        }
+    }
+
+    #[cfg(feature = "solana_parallel")]
+    #[tokio::test]
+    async fn test_high_fee_transaction_runs_before_conflicting_low_fee_ones() {
+        let executor = Arc::new(RecordingExecutor::default());
+        let scheduler = ParallelScheduler::new(
+            StrategyType::SolanaParallel,
+            SchedulerConfig {
+                deterministic: true,
+                ..SchedulerConfig::default()
+            },
+        )
+        .unwrap()
+        .with_executor(executor.clone());
+
+        let low: Vec<String> = (0..10).map(|i| format!("0xlow{}", i)).collect();
+        let mut batch: Vec<Transaction> = low
+            .iter()
+            .map(|hash| Transaction {
+                from: hash.clone(),
+                ..tx(hash, &[], &["pool"])
+            })
+            .collect();
+        batch.push(Transaction {
+            from: "0xwhale".to_string(),
+            gas_price: 100,
+            ..tx("0xhigh", &[], &["pool"])
+        });
+
+        // 下标仍指向提交的批次
+        let plan = scheduler.plan_batch(&batch).await.unwrap();
+        assert_eq!(plan.parallel_groups[0], vec![10]);
+
+        let result = scheduler.submit_batch(batch).await.unwrap();
+        let executed = executor.executed.lock().unwrap().clone();
+        assert_eq!(executed[0], "0xhigh");
+        assert_eq!(executed[1..], low[..]);
+        // 确定性模式下结果仍按提交顺序排列
+        assert_eq!(result.transaction_results[10].tx_hash, "0xhigh");
+
+        let stats = &result.execution_stats;
+        assert_eq!(stats.total_fees, 21000 * 110);
+        assert_eq!(stats.priority_fees, 21000 * 110);
+        let history = scheduler.fee_history(1, &[50.0, 100.0]).unwrap();
+        assert_eq!(history.reward, vec![vec![1, 100]]);
+    }
+}
@@ -0,0 +1,674 @@
+//! 交易池
+//!
+//! 有界的待执行交易池：
+//! - 按发送方维护 nonce 顺序：从下一个待执行 nonce 起连续的交易可执行（pending），
+//!   空缺之后的交易等待（queued），空缺补齐后自动转为可执行
+//! - 同一 (发送方, nonce) 的新交易 gas price 至少高出配置的百分比时替换旧交易
+//! - 池满时驱逐优先费最低的交易；超过存活时间的交易被清理
+//! - 按交易哈希去重（已交给调度器的交易在一段时间内仍视为已知）
+//!
+//! 积累到调度器的批次大小或距上次出批超过批次间隔时，只取可执行交易、在 nonce 约束内按优先费
+//! （[`Transaction::priority_fee`]）从高到低组成批次交给
+//! [`ParallelScheduler::submit_batch`]，批次结果通过广播通道发布
+
+use dubhe_observability::NodeMetrics;
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::{broadcast, Notify};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use crate::types::{BatchResult, Transaction};
+use crate::ParallelScheduler;
+
+/// 批次结果广播的缓冲批次数
+const RESULTS_CHANNEL_CAPACITY: usize = 64;
+
+/// 交易池配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MempoolConfig {
+    /// 最多容纳的交易数（pending 与 queued 合计）
+    pub capacity: usize,
+    /// 不足一个批次时，最长等待多久出批（毫秒）
+    pub batch_interval_ms: u64,
+    /// 替换同一 nonce 的交易时，gas price 至少高出的百分比
+    pub price_bump_percent: u64,
+    /// 交易在池中的最长存活时间（秒）
+    pub ttl_secs: u64,
+}
+
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10_000,
+            batch_interval_ms: 200,
+            price_bump_percent: 10,
+            ttl_secs: 3 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MempoolError {
+    #[error("Mempool is full ({capacity} transactions)")]
+    Full { capacity: usize },
+
+    #[error("replacement transaction underpriced: gas price {offered} < required {required}")]
+    Underpriced { offered: u64, required: u64 },
+
+    #[error("nonce too low: next nonce {expected}, transaction nonce {actual}")]
+    NonceTooLow { expected: u64, actual: u64 },
+}
+
+/// 入池结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InsertOutcome {
+    Added,
+    /// 替换了同一 nonce 的旧交易
+    Replaced {
+        previous: String,
+    },
+    /// 交易已在池中或刚交给调度器，未重复加入
+    Known,
+}
+
+/// 交易池计数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolStatus {
+    pub pending: usize,
+    pub queued: usize,
+}
+
+/// 交易池内容：发送方 → nonce → 交易
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MempoolContent {
+    pub pending: BTreeMap<String, BTreeMap<u64, Transaction>>,
+    pub queued: BTreeMap<String, BTreeMap<u64, Transaction>>,
+}
+
+struct PooledTransaction {
+    transaction: Transaction,
+    /// 入池时计算的优先费，出批与驱逐按它比较
+    priority: u64,
+    inserted_at: Instant,
+}
+
+#[derive(Default)]
+struct SenderQueue {
+    /// 下一个待执行的 nonce
+    next_nonce: u64,
+    transactions: BTreeMap<u64, PooledTransaction>,
+}
+
+impl SenderQueue {
+    /// 从 next_nonce 起连续的交易数
+    fn executable(&self) -> usize {
+        self.transactions
+            .keys()
+            .zip(self.next_nonce..)
+            .take_while(|(nonce, expected)| **nonce == *expected)
+            .count()
+    }
+}
+
+struct Pool {
+    senders: HashMap<String, SenderQueue>,
+    /// 交易哈希 → (发送方, nonce)
+    by_hash: HashMap<String, (String, u64)>,
+    /// 最近出批的交易哈希
+    submitted: LruCache<String, ()>,
+}
+
+impl Pool {
+    fn remove(&mut self, sender: &str, nonce: u64) -> Option<Transaction> {
+        let queue = self.senders.get_mut(sender)?;
+        let removed = queue.transactions.remove(&nonce)?;
+        self.by_hash.remove(&removed.transaction.hash);
+        Some(removed.transaction)
+    }
+
+    /// 优先费最低的驱逐候选：只考虑各发送方 nonce 最大的交易，驱逐不会制造空缺
+    fn cheapest_tail(&self) -> Option<(String, u64, u64)> {
+        self.senders
+            .iter()
+            .filter_map(|(sender, queue)| {
+                let (nonce, pooled) = queue.transactions.last_key_value()?;
+                Some((sender.clone(), *nonce, pooled.priority))
+            })
+            .min_by_key(|(_, _, priority)| *priority)
+    }
+
+    fn status(&self) -> MempoolStatus {
+        let pending: usize = self.senders.values().map(SenderQueue::executable).sum();
+        MempoolStatus {
+            pending,
+            queued: self.by_hash.len() - pending,
+        }
+    }
+}
+
+/// 有界交易池
+pub struct Mempool {
+    config: MempoolConfig,
+    pool: Mutex<Pool>,
+    added: Notify,
+    results_tx: broadcast::Sender<BatchResult>,
+    metrics: Option<Arc<NodeMetrics>>,
+}
+
+impl Mempool {
+    pub fn new(config: MempoolConfig) -> Self {
+        let remembered = NonZeroUsize::new(config.capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            pool: Mutex::new(Pool {
+                senders: HashMap::new(),
+                by_hash: HashMap::new(),
+                submitted: LruCache::new(remembered),
+            }),
+            added: Notify::new(),
+            results_tx: broadcast::channel(RESULTS_CHANNEL_CAPACITY).0,
+            metrics: None,
+            config,
+        }
+    }
+
+    /// 将 pending / queued 计数同步到 Prometheus 仪表盘
+    pub fn with_metrics(mut self, metrics: Arc<NodeMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn config(&self) -> &MempoolConfig {
+        &self.config
+    }
+
+    /// 加入交易，`account_nonce` 为发送方的链上 nonce；重复提交是幂等的
+    pub fn insert(
+        &self,
+        transaction: Transaction,
+        account_nonce: u64,
+    ) -> Result<InsertOutcome, MempoolError> {
+        let mut pool = self.pool.lock().unwrap();
+        if pool.by_hash.contains_key(&transaction.hash)
+            || pool.submitted.contains(&transaction.hash)
+        {
+            debug!("Transaction {} already known", transaction.hash);
+            return Ok(InsertOutcome::Known);
+        }
+
+        let sender = transaction.from.clone();
+        let nonce = transaction.nonce;
+        let next_nonce = pool
+            .senders
+            .get(&sender)
+            .map_or(account_nonce, |queue| queue.next_nonce.max(account_nonce));
+        if nonce < next_nonce {
+            return Err(MempoolError::NonceTooLow {
+                expected: next_nonce,
+                actual: nonce,
+            });
+        }
+
+        // 链上 nonce 已越过的交易不会再执行
+        let stale: Vec<u64> = pool
+            .senders
+            .get(&sender)
+            .map(|queue| {
+                queue
+                    .transactions
+                    .range(..next_nonce)
+                    .map(|(n, _)| *n)
+                    .collect()
+            })
+            .unwrap_or_default();
+        for stale_nonce in stale {
+            pool.remove(&sender, stale_nonce);
+        }
+
+        // 同一 nonce：出价足够高时替换
+        let existing = pool
+            .senders
+            .get(&sender)
+            .and_then(|queue| queue.transactions.get(&nonce))
+            .map(|pooled| pooled.transaction.gas_price);
+        let mut outcome = InsertOutcome::Added;
+        if let Some(gas_price) = existing {
+            // 出价至少高出配置的百分比，且严格高于旧交易
+            let required = (gas_price.saturating_mul(100 + self.config.price_bump_percent) / 100)
+                .max(gas_price.saturating_add(1));
+            if transaction.gas_price < required {
+                return Err(MempoolError::Underpriced {
+                    offered: transaction.gas_price,
+                    required,
+                });
+            }
+            let previous = pool.remove(&sender, nonce).expect("replaced transaction");
+            outcome = InsertOutcome::Replaced {
+                previous: previous.hash,
+            };
+        } else if pool.by_hash.len() >= self.config.capacity {
+            // 池满：新交易优先费高于最低者时驱逐之
+            match pool.cheapest_tail() {
+                Some((evicted_sender, evicted_nonce, priority))
+                    if priority < transaction.priority_fee() =>
+                {
+                    if let Some(evicted) = pool.remove(&evicted_sender, evicted_nonce) {
+                        debug!(
+                            "Evicted transaction {} (priority fee {})",
+                            evicted.hash, priority
+                        );
+                    }
+                }
+                _ => {
+                    return Err(MempoolError::Full {
+                        capacity: self.config.capacity,
+                    })
+                }
+            }
+        }
+
+        pool.by_hash
+            .insert(transaction.hash.clone(), (sender.clone(), nonce));
+        let queue = pool.senders.entry(sender).or_default();
+        queue.next_nonce = next_nonce;
+        queue.transactions.insert(
+            nonce,
+            PooledTransaction {
+                priority: transaction.priority_fee(),
+                transaction,
+                inserted_at: Instant::now(),
+            },
+        );
+        self.update_metrics(&pool);
+        drop(pool);
+        self.added.notify_one();
+        Ok(outcome)
+    }
+
+    /// 交易总数
+    pub fn len(&self) -> usize {
+        self.pool.lock().unwrap().by_hash.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// pending / queued 计数
+    pub fn status(&self) -> MempoolStatus {
+        self.pool.lock().unwrap().status()
+    }
+
+    /// 按发送方列出的 pending / queued 交易
+    pub fn content(&self) -> MempoolContent {
+        let pool = self.pool.lock().unwrap();
+        let mut content = MempoolContent::default();
+        for (sender, queue) in &pool.senders {
+            let executable = queue.executable();
+            for (i, (nonce, pooled)) in queue.transactions.iter().enumerate() {
+                let section = if i < executable {
+                    &mut content.pending
+                } else {
+                    &mut content.queued
+                };
+                section
+                    .entry(sender.clone())
+                    .or_default()
+                    .insert(*nonce, pooled.transaction.clone());
+            }
+        }
+        content
+    }
+
+    /// 清理超过存活时间的交易，返回清理数
+    pub fn prune_expired(&self) -> usize {
+        let ttl = Duration::from_secs(self.config.ttl_secs);
+        let mut pool = self.pool.lock().unwrap();
+        let expired: Vec<(String, u64)> = pool
+            .senders
+            .iter()
+            .flat_map(|(sender, queue)| {
+                queue
+                    .transactions
+                    .iter()
+                    .filter(|(_, pooled)| pooled.inserted_at.elapsed() >= ttl)
+                    .map(move |(nonce, _)| (sender.clone(), *nonce))
+            })
+            .collect();
+        for (sender, nonce) in &expired {
+            pool.remove(sender, *nonce);
+        }
+        pool.senders
+            .retain(|_, queue| !queue.transactions.is_empty());
+        if !expired.is_empty() {
+            info!("🧹 Pruned {} expired mempool transactions", expired.len());
+            self.update_metrics(&pool);
+        }
+        expired.len()
+    }
+
+    /// 取出至多 `max` 笔可执行交易：各发送方按 nonce 顺序，发送方之间按优先费优先
+    fn take_batch(&self, max: usize) -> Vec<Transaction> {
+        let mut pool = self.pool.lock().unwrap();
+        // (优先费, 发送方)，同价时按发送方排序保证确定性
+        let mut heads: BinaryHeap<(u64, std::cmp::Reverse<String>)> = pool
+            .senders
+            .iter()
+            .filter_map(|(sender, queue)| {
+                let pooled = queue.transactions.get(&queue.next_nonce)?;
+                Some((pooled.priority, std::cmp::Reverse(sender.clone())))
+            })
+            .collect();
+
+        let mut batch = Vec::new();
+        while batch.len() < max {
+            let Some((_, std::cmp::Reverse(sender))) = heads.pop() else {
+                break;
+            };
+            let Some(queue) = pool.senders.get_mut(&sender) else {
+                continue;
+            };
+            let nonce = queue.next_nonce;
+            queue.next_nonce += 1;
+            let Some(transaction) = pool.remove(&sender, nonce) else {
+                continue;
+            };
+            pool.submitted.put(transaction.hash.clone(), ());
+            batch.push(transaction);
+
+            if let Some(next) = pool
+                .senders
+                .get(&sender)
+                .and_then(|queue| queue.transactions.get(&(nonce + 1)))
+            {
+                heads.push((next.priority, std::cmp::Reverse(sender)));
+            }
+        }
+        self.update_metrics(&pool);
+        batch
+    }
+
+    fn update_metrics(&self, pool: &Pool) {
+        if let Some(metrics) = &self.metrics {
+            let status = pool.status();
+            metrics.mempool_pending.set(status.pending as i64);
+            metrics.mempool_queued.set(status.queued as i64);
+        }
+    }
+
+    /// 订阅交易池提交的批次结果
+    pub fn subscribe_results(&self) -> broadcast::Receiver<BatchResult> {
+        self.results_tx.subscribe()
+    }
+
+    /// 启动出批任务：可执行交易达到调度器的批次大小或批次间隔到期时提交
+    pub fn spawn_batcher(
+        self: &Arc<Self>,
+        scheduler: Arc<ParallelScheduler>,
+        cancel: CancellationToken,
+    ) -> JoinHandle<()> {
+        let mempool = self.clone();
+        let interval = Duration::from_millis(self.config.batch_interval_ms);
+        info!(
+            "🧺 Mempool batcher started (capacity {}, interval {:?})",
+            self.config.capacity, interval
+        );
+        tokio::spawn(async move {
+            let mut deadline = tokio::time::Instant::now() + interval;
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => return,
+                    _ = mempool.added.notified() => {}
+                    _ = tokio::time::sleep_until(deadline) => {}
+                }
+
+                // 批次大小可热加载，每次出批前重新读取
+                let batch_size = scheduler.config().batch_size.max(1);
+                if mempool.status().pending < batch_size && tokio::time::Instant::now() < deadline {
+                    continue;
+                }
+                mempool.prune_expired();
+                loop {
+                    // 调度器队列已满时把交易留在池中，池满后由入池拒绝向上游传导
+                    if !scheduler.has_queue_capacity(batch_size) {
+                        debug!("Scheduler queue is full, holding mempool batch back");
+                        break;
+                    }
+                    let batch = mempool.take_batch(batch_size);
+                    if batch.is_empty() {
+                        break;
+                    }
+                    let count = batch.len();
+                    match scheduler.submit_batch(batch).await {
+                        Ok(result) => {
+                            let _ = mempool.results_tx.send(result);
+                        }
+                        Err(e) => warn!("⚠️ Mempool batch of {} transactions failed: {}", count, e),
+                    }
+                }
+                deadline = tokio::time::Instant::now() + interval;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{SchedulerConfig, StrategyType};
+
+    fn transaction(hash: &str, from: &str, nonce: u64, gas_price: u64) -> Transaction {
+        Transaction {
+            hash: hash.to_string(),
+            from: from.to_string(),
+            to: Some("0xcontract".to_string()),
+            data: vec![],
+            gas_limit: 21_000,
+            gas_price,
+            nonce,
+            read_set: vec![],
+            write_set: vec![],
+            access_list: None,
+            max_priority_fee: None,
+        }
+    }
+
+    fn hashes(batch: &[Transaction]) -> Vec<&str> {
+        batch.iter().map(|tx| tx.hash.as_str()).collect()
+    }
+
+    #[test]
+    fn test_insert_is_idempotent_and_bounded() {
+        let mempool = Mempool::new(MempoolConfig {
+            capacity: 2,
+            ..Default::default()
+        });
+        let insert =
+            |hash, from, gas_price| mempool.insert(transaction(hash, from, 0, gas_price), 0);
+        assert_eq!(insert("0x1", "0xa", 5), Ok(InsertOutcome::Added));
+        assert_eq!(insert("0x1", "0xa", 5), Ok(InsertOutcome::Known));
+        assert_eq!(insert("0x2", "0xb", 3), Ok(InsertOutcome::Added));
+        // 出价不高于最低者时拒绝，高于时驱逐最低者
+        assert_eq!(
+            insert("0x3", "0xc", 3),
+            Err(MempoolError::Full { capacity: 2 })
+        );
+        assert_eq!(insert("0x3", "0xc", 4), Ok(InsertOutcome::Added));
+        assert_eq!(mempool.len(), 2);
+        assert!(mempool.content().pending.get("0xb").is_none());
+
+        // 出批后的交易仍被视为已知
+        assert_eq!(hashes(&mempool.take_batch(1)), vec!["0x1"]);
+        assert_eq!(insert("0x1", "0xa", 5), Ok(InsertOutcome::Known));
+    }
+
+    #[test]
+    fn test_nonce_gap_holds_back_later_transactions() {
+        let mempool = Mempool::new(MempoolConfig::default());
+        for nonce in [4, 6, 7] {
+            let hash = format!("0x{}", nonce);
+            mempool
+                .insert(transaction(&hash, "0xa", nonce, 1), 4)
+                .unwrap();
+        }
+        assert_eq!(
+            mempool.status(),
+            MempoolStatus {
+                pending: 1,
+                queued: 2
+            }
+        );
+        let content = mempool.content();
+        assert_eq!(
+            content.queued["0xa"].keys().collect::<Vec<_>>(),
+            vec![&6, &7]
+        );
+
+        // 5 缺失时只有 4 可执行
+        assert_eq!(hashes(&mempool.take_batch(10)), vec!["0x4"]);
+        assert!(mempool.take_batch(10).is_empty());
+        assert_eq!(
+            mempool.insert(transaction("0x4b", "0xa", 4, 9), 4),
+            Err(MempoolError::NonceTooLow {
+                expected: 5,
+                actual: 4
+            })
+        );
+
+        // 补齐 5 后 6、7 一并解除阻塞
+        mempool.insert(transaction("0x5", "0xa", 5, 1), 4).unwrap();
+        assert_eq!(
+            mempool.status(),
+            MempoolStatus {
+                pending: 3,
+                queued: 0
+            }
+        );
+        assert_eq!(hashes(&mempool.take_batch(10)), vec!["0x5", "0x6", "0x7"]);
+        assert!(mempool.is_empty());
+    }
+
+    #[test]
+    fn test_replace_by_fee_threshold() {
+        let mempool = Mempool::new(MempoolConfig {
+            price_bump_percent: 10,
+            ..Default::default()
+        });
+        mempool
+            .insert(transaction("0xold", "0xa", 0, 100), 0)
+            .unwrap();
+
+        assert_eq!(
+            mempool.insert(transaction("0xlow", "0xa", 0, 109), 0),
+            Err(MempoolError::Underpriced {
+                offered: 109,
+                required: 110
+            })
+        );
+        assert_eq!(
+            mempool.insert(transaction("0xnew", "0xa", 0, 110), 0),
+            Ok(InsertOutcome::Replaced {
+                previous: "0xold".to_string()
+            })
+        );
+        assert_eq!(mempool.len(), 1);
+        assert_eq!(hashes(&mempool.take_batch(10)), vec!["0xnew"]);
+    }
+
+    #[test]
+    fn test_batches_prefer_higher_gas_price_and_expire() {
+        let mempool = Mempool::new(MempoolConfig::default());
+        mempool.insert(transaction("0xa0", "0xa", 0, 1), 0).unwrap();
+        mempool
+            .insert(transaction("0xa1", "0xa", 1, 50), 0)
+            .unwrap();
+        mempool
+            .insert(transaction("0xb0", "0xb", 0, 10), 0)
+            .unwrap();
+        assert_eq!(hashes(&mempool.take_batch(2)), vec!["0xb0", "0xa0"]);
+        assert_eq!(hashes(&mempool.take_batch(2)), vec!["0xa1"]);
+
+        let expiring = Mempool::new(MempoolConfig {
+            ttl_secs: 0,
+            ..Default::default()
+        });
+        expiring
+            .insert(transaction("0xc0", "0xc", 0, 1), 0)
+            .unwrap();
+        assert_eq!(expiring.prune_expired(), 1);
+        assert!(expiring.is_empty());
+    }
+
+    #[test]
+    fn test_batches_order_by_priority_fee() {
+        let mempool = Mempool::new(MempoolConfig::default());
+        let tipped = |hash, from, gas_price, tip| Transaction {
+            max_priority_fee: Some(tip),
+            ..transaction(hash, from, 0, gas_price)
+        };
+        // gas price 最高但小费最低
+        mempool.insert(tipped("0xa0", "0xa", 100, 1), 0).unwrap();
+        mempool.insert(tipped("0xb0", "0xb", 20, 20), 0).unwrap();
+        mempool.insert(transaction("0xc0", "0xc", 0, 5), 0).unwrap();
+        assert_eq!(hashes(&mempool.take_batch(3)), vec!["0xb0", "0xc0", "0xa0"]);
+    }
+
+    #[test]
+    fn test_status_feeds_gauges() {
+        let metrics = Arc::new(NodeMetrics::new().unwrap());
+        let mempool = Mempool::new(MempoolConfig::default()).with_metrics(metrics.clone());
+        mempool.insert(transaction("0x0", "0xa", 0, 1), 0).unwrap();
+        mempool.insert(transaction("0x2", "0xa", 2, 1), 0).unwrap();
+        assert_eq!(metrics.mempool_pending.get(), 1);
+        assert_eq!(metrics.mempool_queued.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_batcher_submits_on_size_and_interval() {
+        let scheduler = Arc::new(
+            ParallelScheduler::new(
+                StrategyType::Sequential,
+                SchedulerConfig {
+                    batch_size: 2,
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+        let mempool = Arc::new(Mempool::new(MempoolConfig {
+            capacity: 10,
+            batch_interval_ms: 50,
+            ..Default::default()
+        }));
+        let mut results = mempool.subscribe_results();
+        let cancel = CancellationToken::new();
+        let batcher = mempool.spawn_batcher(scheduler, cancel.clone());
+
+        // 凑满一个批次立即提交
+        mempool.insert(transaction("0xa", "0xa", 0, 1), 0).unwrap();
+        mempool.insert(transaction("0xb", "0xa", 1, 1), 0).unwrap();
+        let batch = results.recv().await.unwrap();
+        let executed: Vec<_> = batch
+            .transaction_results
+            .iter()
+            .map(|r| r.tx_hash.as_str())
+            .collect();
+        assert_eq!(executed, vec!["0xa", "0xb"]);
+
+        // 不足一批时等到间隔到期
+        mempool.insert(transaction("0xc", "0xa", 2, 1), 0).unwrap();
+        let batch = results.recv().await.unwrap();
+        assert_eq!(batch.transaction_results.len(), 1);
+        assert!(mempool.is_empty());
+
+        cancel.cancel();
+        batcher.await.unwrap();
+    }
+}
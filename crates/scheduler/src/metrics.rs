@@ -0,0 +1,165 @@
+//! 调度器运行统计
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::types::{ExecutionPlan, StrategyCounters, StrategyType};
+
+/// 并行效率滑动窗口的默认批次数
+pub const DEFAULT_EFFICIENCY_WINDOW: usize = 100;
+
+/// 并行效率的定点精度：1.0 对应百万分之一百万
+pub const EFFICIENCY_SCALE: u64 = 1_000_000;
+
+/// 调度器累计统计，由 submit_batch 更新
+pub(crate) struct SchedulerMetrics {
+    total_processed: AtomicU64,
+    conflicts_detected: AtomicU64,
+    window: usize,
+    // 以百万分比（ppm）记录，窗口内累加不引入浮点误差
+    efficiency: Mutex<VecDeque<u64>>,
+    // 按策略类型记录，策略切换后仍能区分各自的贡献
+    per_strategy: Mutex<HashMap<StrategyType, StrategyCounters>>,
+}
+
+impl SchedulerMetrics {
+    pub(crate) fn new(window: usize) -> Self {
+        Self {
+            total_processed: AtomicU64::new(0),
+            conflicts_detected: AtomicU64::new(0),
+            window: window.max(1),
+            efficiency: Mutex::new(VecDeque::new()),
+            per_strategy: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 记录一个已完成的批次
+    pub(crate) fn record_batch(
+        &self,
+        strategy: StrategyType,
+        transactions: usize,
+        conflicts: usize,
+        efficiency_ppm: u64,
+    ) {
+        self.total_processed
+            .fetch_add(transactions as u64, Ordering::Relaxed);
+        self.conflicts_detected
+            .fetch_add(conflicts as u64, Ordering::Relaxed);
+
+        // 空批次不计入效率窗口
+        if transactions > 0 {
+            let mut window = self.efficiency.lock().unwrap();
+            if window.len() == self.window {
+                window.pop_front();
+            }
+            window.push_back(efficiency_ppm);
+        }
+
+        let mut per_strategy = self.per_strategy.lock().unwrap();
+        let counters = per_strategy.entry(strategy).or_default();
+        counters.batches += 1;
+        counters.transactions += transactions as u64;
+        counters.conflicts += conflicts as u64;
+    }
+
+    pub(crate) fn total_processed(&self) -> u64 {
+        self.total_processed.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn conflicts_detected(&self) -> u64 {
+        self.conflicts_detected.load(Ordering::Relaxed)
+    }
+
+    /// 最近 N 个批次的平均并行效率（ppm），尚无数据时为 0
+    pub(crate) fn parallel_efficiency_ppm(&self) -> u64 {
+        let window = self.efficiency.lock().unwrap();
+        if window.is_empty() {
+            0
+        } else {
+            window.iter().sum::<u64>() / window.len() as u64
+        }
+    }
+
+    /// 最近 N 个批次的平均并行效率，仅在对外报告时换算为浮点数
+    pub(crate) fn parallel_efficiency(&self) -> f64 {
+        efficiency_ratio(self.parallel_efficiency_ppm())
+    }
+
+    pub(crate) fn per_strategy(&self) -> HashMap<StrategyType, StrategyCounters> {
+        self.per_strategy.lock().unwrap().clone()
+    }
+}
+
+/// 执行计划的并行效率：工作线程槽位的利用率
+///
+/// 每个并行组按 worker 数切分为若干轮，效率 = 交易数 / (轮数 × 可用 worker 数)
+pub fn plan_efficiency(plan: &ExecutionPlan, transactions: usize, workers: usize) -> f64 {
+    efficiency_ratio(plan_efficiency_ppm(plan, transactions, workers))
+}
+
+/// [`plan_efficiency`] 的定点版本（ppm），全程整数运算，跨节点结果一致
+pub fn plan_efficiency_ppm(plan: &ExecutionPlan, transactions: usize, workers: usize) -> u64 {
+    if transactions == 0 {
+        return 0;
+    }
+    let workers = workers.max(1);
+    let rounds: usize = plan
+        .scheduled_groups()
+        .iter()
+        .map(|group| group.len().div_ceil(workers))
+        .sum();
+    if rounds == 0 {
+        return 0;
+    }
+
+    let slots = (rounds * workers.min(transactions)) as u64;
+    (transactions as u64 * EFFICIENCY_SCALE / slots).min(EFFICIENCY_SCALE)
+}
+
+/// 将 ppm 换算为 0~1 的比例
+pub fn efficiency_ratio(ppm: u64) -> f64 {
+    ppm as f64 / EFFICIENCY_SCALE as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(groups: Vec<Vec<usize>>) -> ExecutionPlan {
+        ExecutionPlan {
+            dependency_order: groups.iter().flatten().copied().collect(),
+            parallel_groups: groups,
+            unordered: vec![],
+        }
+    }
+
+    #[test]
+    fn test_plan_efficiency() {
+        // 完全并行
+        assert_eq!(plan_efficiency(&plan(vec![vec![0, 1, 2, 3]]), 4, 4), 1.0);
+        // 完全串行
+        assert_eq!(
+            plan_efficiency(&plan(vec![vec![0], vec![1], vec![2], vec![3]]), 4, 4),
+            0.25
+        );
+        assert_eq!(plan_efficiency(&plan(vec![]), 0, 4), 0.0);
+        // 三笔交易占两轮两个槽位
+        assert_eq!(
+            plan_efficiency_ppm(&plan(vec![vec![0, 1, 2]]), 3, 2),
+            750_000
+        );
+    }
+
+    #[test]
+    fn test_efficiency_window_rolls() {
+        let metrics = SchedulerMetrics::new(2);
+        metrics.record_batch(StrategyType::Sequential, 1, 0, 100_000);
+        metrics.record_batch(StrategyType::Sequential, 1, 0, 500_000);
+        metrics.record_batch(StrategyType::Sequential, 1, 0, 900_000);
+
+        assert_eq!(metrics.parallel_efficiency_ppm(), 700_000);
+        assert!((metrics.parallel_efficiency() - 0.7).abs() < 1e-9);
+        assert_eq!(metrics.total_processed(), 3);
+    }
+}
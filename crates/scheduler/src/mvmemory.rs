@@ -0,0 +1,204 @@
+//! 多版本内存（Block-STM）
+//!
+//! 以 (位置, 交易下标) 为键保存每笔交易最近一次执行写入的值，
+//! 读取时返回下标更小的交易中最近一次写入，不存在时回落到区块前的已提交状态
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use crate::error::SchedulerResult;
+use crate::types::{Transaction, TransactionResult};
+
+/// 写入版本：(交易下标, 执行轮次)
+pub type Version = (usize, usize);
+
+/// 交易执行时看到的状态视图
+pub trait StateView: Send + Sync {
+    fn read(&self, location: &str) -> Option<Vec<u8>>;
+}
+
+/// 在状态视图上执行交易的执行器，供乐观并发策略使用
+#[async_trait]
+pub trait VersionedExecutor: Send + Sync {
+    /// 读取区块执行前的已提交状态
+    fn storage_read(&self, location: &str) -> Option<Vec<u8>>;
+
+    /// 执行交易，所有状态读取必须经过 `view`
+    async fn execute(
+        &self,
+        transaction: &Transaction,
+        view: &dyn StateView,
+    ) -> SchedulerResult<VersionedOutput>;
+
+    /// 提交整个批次的最终写集合
+    async fn commit(&self, writes: BTreeMap<String, Vec<u8>>) -> SchedulerResult<()>;
+}
+
+/// 单笔交易的执行输出
+#[derive(Debug, Clone)]
+pub struct VersionedOutput {
+    pub result: TransactionResult,
+    pub writes: Vec<(String, Vec<u8>)>,
+}
+
+/// 乐观执行的批次结果
+#[derive(Debug, Clone)]
+pub struct OptimisticOutcome {
+    pub results: Vec<TransactionResult>,
+    /// 验证失败导致的中止次数
+    pub aborts: usize,
+    /// 执行总次数（含重执行）
+    pub executions: usize,
+    /// 超过重试上限后是否回退为串行执行
+    pub fell_back: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    incarnation: usize,
+    value: Vec<u8>,
+}
+
+/// 多版本数据结构
+#[derive(Default)]
+pub struct MultiVersionMemory {
+    data: DashMap<String, BTreeMap<usize, Entry>>,
+}
+
+impl MultiVersionMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 交易 `txn_index` 读取 `location`：返回更小下标中最近一次写入的版本与值
+    pub fn read(&self, location: &str, txn_index: usize) -> Option<(Version, Vec<u8>)> {
+        let versions = self.data.get(location)?;
+        versions
+            .range(..txn_index)
+            .next_back()
+            .map(|(&index, entry)| ((index, entry.incarnation), entry.value.clone()))
+    }
+
+    /// 记录一次执行的写集合，并移除上一轮写过但本轮未写的位置
+    pub fn record(
+        &self,
+        txn_index: usize,
+        incarnation: usize,
+        writes: &[(String, Vec<u8>)],
+        previous: &[String],
+    ) {
+        for location in previous {
+            if !writes.iter().any(|(written, _)| written == location) {
+                if let Some(mut versions) = self.data.get_mut(location) {
+                    versions.remove(&txn_index);
+                }
+            }
+        }
+
+        for (location, value) in writes {
+            self.data.entry(location.clone()).or_default().insert(
+                txn_index,
+                Entry {
+                    incarnation,
+                    value: value.clone(),
+                },
+            );
+        }
+    }
+
+    /// 每个位置上下标最大的写入，即批次的最终写集合
+    pub fn snapshot(&self) -> BTreeMap<String, Vec<u8>> {
+        self.data
+            .iter()
+            .filter_map(|versions| {
+                versions
+                    .values()
+                    .next_back()
+                    .map(|entry| (versions.key().clone(), entry.value.clone()))
+            })
+            .collect()
+    }
+}
+
+/// 单笔交易某一轮执行的视图，记录读取到的版本（`None` 表示来自已提交状态）
+pub struct TxnView<'a> {
+    memory: &'a MultiVersionMemory,
+    executor: &'a dyn VersionedExecutor,
+    txn_index: usize,
+    reads: Mutex<HashMap<String, Option<Version>>>,
+}
+
+impl<'a> TxnView<'a> {
+    pub fn new(
+        memory: &'a MultiVersionMemory,
+        executor: &'a dyn VersionedExecutor,
+        txn_index: usize,
+    ) -> Self {
+        Self {
+            memory,
+            executor,
+            txn_index,
+            reads: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 本轮执行的读集合
+    pub fn into_reads(self) -> HashMap<String, Option<Version>> {
+        self.reads.into_inner().unwrap()
+    }
+}
+
+impl StateView for TxnView<'_> {
+    fn read(&self, location: &str) -> Option<Vec<u8>> {
+        let (version, value) = match self.memory.read(location, self.txn_index) {
+            Some((version, value)) => (Some(version), Some(value)),
+            None => (None, self.executor.storage_read(location)),
+        };
+        // 同一轮内重复读取以第一次看到的版本为准
+        self.reads
+            .lock()
+            .unwrap()
+            .entry(location.to_string())
+            .or_insert(version);
+        value
+    }
+}
+
+/// 读集合中的版本是否仍是当前可见的版本
+pub fn validate_reads(
+    memory: &MultiVersionMemory,
+    txn_index: usize,
+    reads: &HashMap<String, Option<Version>>,
+) -> bool {
+    reads.iter().all(|(location, version)| {
+        memory.read(location, txn_index).map(|(current, _)| current) == *version
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_see_latest_lower_write() {
+        let memory = MultiVersionMemory::new();
+        memory.record(1, 0, &[("x".to_string(), vec![1])], &[]);
+        memory.record(3, 0, &[("x".to_string(), vec![3])], &[]);
+
+        assert_eq!(memory.read("x", 0), None);
+        assert_eq!(memory.read("x", 2), Some(((1, 0), vec![1])));
+        assert_eq!(memory.read("x", 5), Some(((3, 0), vec![3])));
+
+        // 重执行后不再写 x，旧写入被移除
+        memory.record(3, 1, &[], &["x".to_string()]);
+        assert_eq!(memory.read("x", 5), Some(((1, 0), vec![1])));
+        assert_eq!(memory.snapshot()["x"], vec![1]);
+
+        let reads = HashMap::from([("x".to_string(), Some((1, 0)))]);
+        assert!(validate_reads(&memory, 5, &reads));
+        memory.record(1, 1, &[("x".to_string(), vec![9])], &["x".to_string()]);
+        assert!(!validate_reads(&memory, 5, &reads));
+    }
+}
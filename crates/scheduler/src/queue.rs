@@ -0,0 +1,234 @@
+//! 调度器提交队列
+//!
+//! API / 交易池与批次执行之间的有界队列，深度按交易数计量（等待中与执行中的批次）。
+//! 用户通道的深度超过 `max_queue_size` 时立即以 [`SchedulerError::QueueFull`] 拒绝，
+//! 调用方据此退避，而不是在调度器之后无限堆积 future；共识通道的批次已经过排序，
+//! 不受容量限制且优先执行。批次按通道内的入队顺序逐个取得执行槽，
+//! 单个批次已经占满分发器的全部 worker
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+use crate::error::SchedulerError;
+
+/// 排空时间估算中每笔交易耗时的平滑系数
+const DRAIN_RATE_ALPHA: f64 = 0.2;
+
+/// 批次进入的通道
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SubmissionLane {
+    /// 共识排序后的批次，不受容量限制，优先于用户通道
+    Consensus,
+    /// API 与交易池提交的批次
+    User,
+}
+
+struct QueueState {
+    next_ticket: u64,
+    consensus: VecDeque<u64>,
+    user: VecDeque<u64>,
+    /// 已入队、尚未完成的交易数
+    depth: usize,
+    running: bool,
+    /// 每笔交易的平均执行耗时（微秒），尚无已完成批次时为空
+    per_tx_micros: Option<f64>,
+}
+
+impl QueueState {
+    fn lane(&mut self, lane: SubmissionLane) -> &mut VecDeque<u64> {
+        match lane {
+            SubmissionLane::Consensus => &mut self.consensus,
+            SubmissionLane::User => &mut self.user,
+        }
+    }
+
+    /// 执行槽空闲且该批次排在最前（共识通道为空时才轮到用户通道）
+    fn is_next(&self, lane: SubmissionLane, ticket: u64) -> bool {
+        if self.running {
+            return false;
+        }
+        match lane {
+            SubmissionLane::Consensus => self.consensus.front() == Some(&ticket),
+            SubmissionLane::User => self.consensus.is_empty() && self.user.front() == Some(&ticket),
+        }
+    }
+
+    fn estimated_drain_ms(&self) -> u64 {
+        self.per_tx_micros
+            .map(|micros| (micros * self.depth as f64 / 1_000.0).ceil() as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// 有界的两通道提交队列
+pub(crate) struct SubmissionQueue {
+    state: Mutex<QueueState>,
+    ready: Notify,
+}
+
+impl SubmissionQueue {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                next_ticket: 0,
+                consensus: VecDeque::new(),
+                user: VecDeque::new(),
+                depth: 0,
+                running: false,
+                per_tx_micros: None,
+            }),
+            ready: Notify::new(),
+        }
+    }
+
+    /// 当前深度（交易数）
+    pub(crate) fn depth(&self) -> usize {
+        self.state.lock().unwrap().depth
+    }
+
+    /// 用户通道能否再容纳 `transactions` 笔交易
+    pub(crate) fn has_capacity(&self, transactions: usize, capacity: usize) -> bool {
+        let state = self.state.lock().unwrap();
+        state.depth == 0 || state.depth + transactions <= capacity
+    }
+
+    /// 入队；用户通道超过容量时拒绝
+    ///
+    /// 空队列总是接受一个批次，避免容量小于批次大小时永远拒绝
+    pub(crate) fn enqueue(
+        &self,
+        lane: SubmissionLane,
+        transactions: usize,
+        capacity: usize,
+    ) -> Result<QueueTicket<'_>, SchedulerError> {
+        let mut state = self.state.lock().unwrap();
+        if lane == SubmissionLane::User && state.depth > 0 && state.depth + transactions > capacity
+        {
+            return Err(SchedulerError::QueueFull {
+                depth: state.depth,
+                capacity,
+                estimated_drain_ms: state.estimated_drain_ms(),
+            });
+        }
+
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        state.lane(lane).push_back(ticket);
+        state.depth += transactions;
+        Ok(QueueTicket {
+            queue: self,
+            ticket,
+            lane,
+            transactions,
+            enqueued_at: Instant::now(),
+            started_at: None,
+        })
+    }
+}
+
+/// 队列中的一个批次；drop 时出队，执行中的批次释放执行槽
+pub(crate) struct QueueTicket<'a> {
+    queue: &'a SubmissionQueue,
+    ticket: u64,
+    lane: SubmissionLane,
+    transactions: usize,
+    enqueued_at: Instant,
+    started_at: Option<Instant>,
+}
+
+impl QueueTicket<'_> {
+    /// 等待轮到该批次执行，返回排队时长
+    pub(crate) async fn acquire(&mut self) -> Duration {
+        loop {
+            let notified = self.queue.ready.notified();
+            {
+                let mut state = self.queue.state.lock().unwrap();
+                if state.is_next(self.lane, self.ticket) {
+                    state.lane(self.lane).pop_front();
+                    state.running = true;
+                    let now = Instant::now();
+                    self.started_at = Some(now);
+                    return now - self.enqueued_at;
+                }
+            }
+            notified.await;
+        }
+    }
+}
+
+impl Drop for QueueTicket<'_> {
+    fn drop(&mut self) {
+        let mut state = self.queue.state.lock().unwrap();
+        state.depth -= self.transactions;
+        match self.started_at {
+            Some(started_at) => {
+                state.running = false;
+                if self.transactions > 0 {
+                    let micros = started_at.elapsed().as_micros() as f64 / self.transactions as f64;
+                    state.per_tx_micros = Some(match state.per_tx_micros {
+                        Some(average) => average + DRAIN_RATE_ALPHA * (micros - average),
+                        None => micros,
+                    });
+                }
+            }
+            // 等待中被取消（调用方超时或停机），从通道中移除
+            None => {
+                let ticket = self.ticket;
+                state.lane(self.lane).retain(|queued| *queued != ticket);
+            }
+        }
+        drop(state);
+        self.queue.ready.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_consensus_lane_runs_first() {
+        let queue = SubmissionQueue::new();
+        let mut running = queue.enqueue(SubmissionLane::User, 1, 10).unwrap();
+        running.acquire().await;
+
+        let mut user = queue.enqueue(SubmissionLane::User, 1, 10).unwrap();
+        let mut consensus = queue.enqueue(SubmissionLane::Consensus, 1, 10).unwrap();
+        assert_eq!(queue.depth(), 3);
+        drop(running);
+
+        // 用户批次先入队，但共识批次先取得执行槽
+        tokio::select! {
+            biased;
+            _ = user.acquire() => panic!("user batch overtook the consensus lane"),
+            _ = consensus.acquire() => {}
+        }
+        drop(consensus);
+        user.acquire().await;
+        drop(user);
+        assert_eq!(queue.depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiter_leaves_queue() {
+        let queue = SubmissionQueue::new();
+        let mut running = queue.enqueue(SubmissionLane::User, 2, 4).unwrap();
+        running.acquire().await;
+
+        let mut waiting = queue.enqueue(SubmissionLane::User, 2, 4).unwrap();
+        assert!(
+            tokio::time::timeout(Duration::from_millis(10), waiting.acquire())
+                .await
+                .is_err()
+        );
+        drop(waiting);
+        assert_eq!(queue.depth(), 2);
+
+        let mut next = queue.enqueue(SubmissionLane::User, 2, 4).unwrap();
+        drop(running);
+        next.acquire().await;
+    }
+}
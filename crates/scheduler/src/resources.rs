@@ -0,0 +1,200 @@
+//! 进程资源采样
+//!
+//! 批次开始与结束时各采样一次，得到批次期间的 CPU 利用率、常驻内存变化与读写字节数。
+//! Linux 上解析 `/proc/self`；其它平台或读取失败时采样为 `None`，调用方据此跳过记录。
+//! 采样覆盖整个进程，并发执行的批次会计入彼此的资源使用
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// 某一时刻的进程资源快照
+#[derive(Debug, Clone, Copy)]
+pub struct ResourceSample {
+    at: Instant,
+    /// 用户态与内核态 CPU 时间，所有线程合计
+    cpu_time: Duration,
+    rss_bytes: u64,
+    /// 进程生命周期内的常驻内存峰值（VmHWM）
+    peak_rss_bytes: u64,
+    /// 经 read / write 类系统调用读写的字节数（rchar + wchar）
+    io_bytes: u64,
+}
+
+/// 批次期间的资源使用
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    /// CPU 时间除以墙钟时间与核数之积，取值 0-1
+    pub cpu_utilization: f64,
+    pub cpu_time_ms: u64,
+    /// 结束与开始时常驻内存之差，可为负
+    pub rss_delta_bytes: i64,
+    /// 批次期间常驻内存峰值：期间刷新了进程峰值时为精确值，否则为首尾两次采样的较大者
+    pub peak_rss_bytes: u64,
+    /// 进程读写的字节数，含状态存储、日志与网络，作为 IO 压力的近似
+    pub io_bytes: u64,
+}
+
+/// 进程资源采样器
+#[derive(Debug, Clone)]
+pub struct ResourceMonitor {
+    cores: usize,
+}
+
+impl Default for ResourceMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResourceMonitor {
+    pub fn new() -> Self {
+        Self {
+            cores: num_cpus::get().max(1),
+        }
+    }
+
+    /// 当前快照；不支持的平台返回 `None`
+    pub fn sample(&self) -> Option<ResourceSample> {
+        platform::sample()
+    }
+
+    /// 两次快照之间的资源使用
+    pub fn usage(&self, start: &ResourceSample, end: &ResourceSample) -> ResourceUsage {
+        let wall = end.at.saturating_duration_since(start.at).as_secs_f64();
+        let cpu_time = end.cpu_time.saturating_sub(start.cpu_time);
+        let cpu_utilization = if wall > 0.0 {
+            (cpu_time.as_secs_f64() / (wall * self.cores as f64)).min(1.0)
+        } else {
+            0.0
+        };
+        let peak_rss_bytes = if end.peak_rss_bytes > start.peak_rss_bytes {
+            end.peak_rss_bytes
+        } else {
+            start.rss_bytes.max(end.rss_bytes)
+        };
+        ResourceUsage {
+            cpu_utilization,
+            cpu_time_ms: cpu_time.as_millis() as u64,
+            rss_delta_bytes: end.rss_bytes as i64 - start.rss_bytes as i64,
+            peak_rss_bytes,
+            io_bytes: end.io_bytes.saturating_sub(start.io_bytes),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::ResourceSample;
+    use std::fs;
+    use std::time::{Duration, Instant};
+
+    /// `/proc/<pid>/stat` 中 CPU 时间的单位（USER_HZ），Linux 对用户空间固定为 100
+    const CLOCK_TICKS_PER_SEC: u64 = 100;
+
+    pub(super) fn sample() -> Option<ResourceSample> {
+        let at = Instant::now();
+
+        // 进程名可能含空格，从最后一个 ')' 之后计数：其后第一个字段为第 3 个字段，
+        // utime / stime 为第 14、15 个字段
+        let stat = fs::read_to_string("/proc/self/stat").ok()?;
+        let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+        let utime: u64 = fields.get(11)?.parse().ok()?;
+        let stime: u64 = fields.get(12)?.parse().ok()?;
+
+        let status = fs::read_to_string("/proc/self/status").ok()?;
+        let rss_kib = status_kib(&status, "VmRSS:")?;
+        let peak_kib = status_kib(&status, "VmHWM:").unwrap_or(rss_kib);
+
+        // 受限环境下 /proc/self/io 可能不可读，此时读写字节数记为 0
+        let io_bytes = fs::read_to_string("/proc/self/io")
+            .map(|io| io_field(&io, "rchar:") + io_field(&io, "wchar:"))
+            .unwrap_or(0);
+
+        Some(ResourceSample {
+            at,
+            cpu_time: Duration::from_millis((utime + stime) * 1000 / CLOCK_TICKS_PER_SEC),
+            rss_bytes: rss_kib * 1024,
+            peak_rss_bytes: peak_kib * 1024,
+            io_bytes,
+        })
+    }
+
+    fn status_kib(status: &str, key: &str) -> Option<u64> {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(key))?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    }
+
+    fn io_field(io: &str, key: &str) -> u64 {
+        io.lines()
+            .find_map(|line| line.strip_prefix(key))
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use super::ResourceSample;
+
+    pub(super) fn sample() -> Option<ResourceSample> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(at: Instant, cpu_ms: u64, rss: u64, peak: u64, io: u64) -> ResourceSample {
+        ResourceSample {
+            at,
+            cpu_time: Duration::from_millis(cpu_ms),
+            rss_bytes: rss,
+            peak_rss_bytes: peak,
+            io_bytes: io,
+        }
+    }
+
+    #[test]
+    fn test_usage_between_samples() {
+        let monitor = ResourceMonitor { cores: 4 };
+        let start = Instant::now();
+        let end = start + Duration::from_secs(1);
+
+        let usage = monitor.usage(
+            &sample(start, 1_000, 100, 150, 10),
+            &sample(end, 3_000, 80, 150, 4_106),
+        );
+        assert_eq!(usage.cpu_time_ms, 2_000);
+        assert!((usage.cpu_utilization - 0.5).abs() < 1e-9);
+        assert_eq!(usage.rss_delta_bytes, -20);
+        // 进程峰值未刷新，取首尾较大者
+        assert_eq!(usage.peak_rss_bytes, 100);
+        assert_eq!(usage.io_bytes, 4_096);
+
+        // 期间刷新了进程峰值
+        let usage = monitor.usage(&sample(start, 0, 100, 150, 0), &sample(end, 0, 120, 300, 0));
+        assert_eq!(usage.peak_rss_bytes, 300);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_busy_loop_uses_cpu() {
+        let monitor = ResourceMonitor::new();
+        let start = monitor.sample().unwrap();
+        let started = Instant::now();
+        let mut x = 0u64;
+        while started.elapsed() < Duration::from_millis(200) {
+            x = std::hint::black_box(x.wrapping_mul(31).wrapping_add(7));
+        }
+        let usage = monitor.usage(&start, &monitor.sample().unwrap());
+        assert!(usage.cpu_time_ms > 0, "{:?}", usage);
+        assert!(usage.cpu_utilization > 0.0);
+        assert!(usage.peak_rss_bytes > 0);
+    }
+}
@@ -1,8 +1,8 @@
 //! Solana Sealevel 并行策略
 
 use async_trait::async_trait;
-use anyhow::Result;
 
+use crate::error::SchedulerResult;
 use crate::strategy::ExecutionStrategy;
 use crate::types::*;
 use crate::conflict::ConflictGraph;
@@ -22,14 +22,15 @@ impl ExecutionStrategy for SolanaStrategy {
         &self,
         transactions: &[Transaction],
         conflict_graph: &ConflictGraph,
-    ) -> Result<ExecutionPlan> {
-        // TODO: 实现 Solana 账号读写集合并行算法
-        let parallel_groups = vec![transactions.iter().enumerate().map(|(i, _)| i).collect()];
-        let dependency_order = (0..transactions.len()).collect();
+    ) -> SchedulerResult<ExecutionPlan> {
+        // 按冲突边分层，同层内互不冲突
+        let parallel_groups = conflict_graph.layered_groups(transactions.len(), |_| true);
+        let dependency_order = parallel_groups.iter().flatten().copied().collect();
 
         Ok(ExecutionPlan {
             parallel_groups,
             dependency_order,
+            unordered: vec![],
         })
     }
 
@@ -40,4 +41,8 @@ impl ExecutionStrategy for SolanaStrategy {
     fn description(&self) -> &str {
         "Solana Sealevel account read/write set parallel execution"
     }
+
+    fn strategy_type(&self) -> StrategyType {
+        StrategyType::SolanaParallel
+    }
 } 
\ No newline at end of file
@@ -1,26 +1,75 @@
 //! 执行策略模块
 
 use async_trait::async_trait;
-use anyhow::Result;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
+use crate::error::SchedulerResult;
 use crate::types::*;
 use crate::conflict::ConflictGraph;
+use crate::mvmemory::{OptimisticOutcome, VersionedExecutor};
+
+/// 交给策略生命周期钩子的批次信息
+#[derive(Debug, Clone, Copy)]
+pub struct BatchContext<'a> {
+    /// 调度器内单调递增的批次序号
+    pub batch_id: u64,
+    pub transactions: &'a [Transaction],
+    /// 确定性模式下由批次哈希派生的种子（见 [`batch_seed`]），策略内部的随机选择必须由它驱动；
+    /// 非确定性模式下为 `None`
+    pub seed: Option<u64>,
+}
 
 /// 执行策略 trait
+///
+/// 调度器对每个批次依次调用 [`on_batch_start`](Self::on_batch_start)、
+/// [`plan_execution`](Self::plan_execution)、（可选的）乐观执行与
+/// [`on_batch_commit`](Self::on_batch_commit)。钩子只接收 `&self`，需要跨批次维护索引的策略
+/// 自行用内部可变性与同步保存状态；批次按提交队列逐个执行，但 [`plan_execution`](Self::plan_execution)
+/// 也可能经 `plan_batch` 在批次之外被调用
 #[async_trait]
 pub trait ExecutionStrategy {
+    /// 批次开始执行前调用，返回错误时该批次失败
+    async fn on_batch_start(&self, _ctx: &BatchContext<'_>) -> SchedulerResult<()> {
+        Ok(())
+    }
+
     /// 分析交易并生成执行计划
     async fn plan_execution(
         &self,
         transactions: &[Transaction],
         conflict_graph: &ConflictGraph,
-    ) -> Result<ExecutionPlan>;
+    ) -> SchedulerResult<ExecutionPlan>;
+
+    /// 由策略自行执行整个批次（如 Block-STM 乐观执行）
+    ///
+    /// 返回 `None` 时由分发器按执行计划执行
+    async fn execute_optimistic(
+        &self,
+        _transactions: &[Transaction],
+        _executor: Arc<dyn VersionedExecutor>,
+        _cancel: &CancellationToken,
+    ) -> SchedulerResult<Option<OptimisticOutcome>> {
+        Ok(None)
+    }
+
+    /// 批次执行完成后调用，`results` 为各交易的最终结果；批次失败或被取消时不调用
+    async fn on_batch_commit(
+        &self,
+        _ctx: &BatchContext<'_>,
+        _results: &[TransactionResult],
+    ) -> SchedulerResult<()> {
+        Ok(())
+    }
 
     /// 获取策略名称
     fn name(&self) -> &str;
 
     /// 获取策略描述
     fn description(&self) -> &str;
+
+    /// 获取策略类型
+    fn strategy_type(&self) -> StrategyType;
 }
 
 /// 默认串行执行策略（用于测试和回退）
@@ -32,7 +81,7 @@ impl ExecutionStrategy for SequentialStrategy {
         &self,
         transactions: &[Transaction],
         _conflict_graph: &ConflictGraph,
-    ) -> Result<ExecutionPlan> {
+    ) -> SchedulerResult<ExecutionPlan> {
         // 串行执行：每个交易单独一组
         let parallel_groups = transactions
             .iter()
@@ -45,6 +94,7 @@ impl ExecutionStrategy for SequentialStrategy {
         Ok(ExecutionPlan {
             parallel_groups,
             dependency_order,
+            unordered: vec![],
         })
     }
 
@@ -55,4 +105,8 @@ impl ExecutionStrategy for SequentialStrategy {
     fn description(&self) -> &str {
         "Sequential execution strategy (fallback)"
     }
+
+    fn strategy_type(&self) -> StrategyType {
+        StrategyType::Sequential
+    }
 } 
\ No newline at end of file
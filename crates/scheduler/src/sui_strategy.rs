@@ -1,30 +1,135 @@
 //! Sui Object-DAG 策略
+//!
+//! 写集合只含独占对象、且与批次内其他交易无冲突的交易走 fast path：不排序，
+//! 直接分散到各 worker；写共享对象（或与之冲突）的交易按对象 DAG 分层执行。
+//!
+//! 策略跨批次维护独占对象的所有者索引：批次提交后记录每个被成功写入的对象的发送方，
+//! 被不同发送方写入过的对象此后视同共享对象排序执行
 
 use async_trait::async_trait;
-use anyhow::Result;
+use lru::LruCache;
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
+use std::sync::{Mutex, RwLock};
+use tracing::debug;
 
-use crate::strategy::ExecutionStrategy;
+use crate::error::SchedulerResult;
+use crate::strategy::{BatchContext, ExecutionStrategy};
 use crate::types::*;
 use crate::conflict::ConflictGraph;
 
-pub struct SuiStrategy;
+/// 所有者索引默认记住的对象数
+pub const DEFAULT_OWNER_INDEX_CAPACITY: usize = 100_000;
+
+pub struct SuiStrategy {
+    shared_objects: HashSet<String>,
+    /// 对象 → 最近一次成功写入它的发送方
+    owners: Mutex<LruCache<String, String>>,
+    /// 被不同发送方写入过的对象，视同共享对象
+    learned_shared: RwLock<HashSet<String>>,
+}
 
 impl SuiStrategy {
     pub fn new() -> Self {
-        Self
+        Self {
+            shared_objects: HashSet::new(),
+            owners: Mutex::new(LruCache::new(
+                NonZeroUsize::new(DEFAULT_OWNER_INDEX_CAPACITY).unwrap(),
+            )),
+            learned_shared: RwLock::new(HashSet::new()),
+        }
+    }
+
+    /// 登记共享对象 ID，写这些对象的交易需要排序
+    pub fn with_shared_objects(mut self, object_ids: impl IntoIterator<Item = String>) -> Self {
+        self.shared_objects.extend(object_ids);
+        self
+    }
+
+    /// 写集合是否包含共享对象（登记的或由所有者索引识别的）
+    pub fn writes_shared(&self, transaction: &Transaction) -> bool {
+        let learned = self.learned_shared.read().unwrap();
+        transaction
+            .write_set
+            .iter()
+            .any(|object_id| self.shared_objects.contains(object_id) || learned.contains(object_id))
+    }
+
+    /// 由所有者索引识别为共享的对象数
+    pub fn learned_shared_count(&self) -> usize {
+        self.learned_shared.read().unwrap().len()
     }
 }
 
 #[async_trait]
 impl ExecutionStrategy for SuiStrategy {
-    async fn plan_execution(&self, transactions: &[Transaction], _conflict_graph: &ConflictGraph) -> Result<ExecutionPlan> {
-        // TODO: 实现 Sui Object-DAG 对象级并行
+    async fn plan_execution(
+        &self,
+        transactions: &[Transaction],
+        conflict_graph: &ConflictGraph,
+    ) -> SchedulerResult<ExecutionPlan> {
+        // 与任何交易存在冲突边的交易同样需要排序（如同一独占对象在批次内被重复使用）
+        let mut ordered: Vec<bool> = transactions.iter().map(|tx| self.writes_shared(tx)).collect();
+        for &(a, b) in &conflict_graph.edges {
+            if a < ordered.len() && b < ordered.len() {
+                ordered[a] = true;
+                ordered[b] = true;
+            }
+        }
+
+        let unordered: Vec<usize> = (0..transactions.len()).filter(|&i| !ordered[i]).collect();
+        let parallel_groups = conflict_graph.layered_groups(transactions.len(), |i| ordered[i]);
+        let dependency_order = parallel_groups.iter().flatten().copied().collect();
+
         Ok(ExecutionPlan {
-            parallel_groups: vec![],
-            dependency_order: vec![],
+            parallel_groups,
+            dependency_order,
+            unordered,
         })
     }
 
+    async fn on_batch_commit(
+        &self,
+        ctx: &BatchContext<'_>,
+        results: &[TransactionResult],
+    ) -> SchedulerResult<()> {
+        let succeeded: HashSet<&str> = results
+            .iter()
+            .filter(|result| result.success)
+            .map(|result| result.tx_hash.as_str())
+            .collect();
+
+        let mut owners = self.owners.lock().unwrap();
+        let mut learned = Vec::new();
+        for transaction in ctx.transactions {
+            if !succeeded.contains(transaction.hash.as_str()) || self.writes_shared(transaction) {
+                continue;
+            }
+            for object_id in &transaction.write_set {
+                match owners.get(object_id) {
+                    Some(owner) if *owner != transaction.from => learned.push(object_id.clone()),
+                    _ => {
+                        owners.put(object_id.clone(), transaction.from.clone());
+                    }
+                }
+            }
+        }
+        for object_id in &learned {
+            owners.pop(object_id);
+        }
+        drop(owners);
+
+        if !learned.is_empty() {
+            debug!(
+                "Batch {} revealed {} objects written by multiple senders",
+                ctx.batch_id,
+                learned.len()
+            );
+            self.learned_shared.write().unwrap().extend(learned);
+        }
+        Ok(())
+    }
+
     fn name(&self) -> &str {
         "sui_object_dag"
     }
@@ -32,4 +137,100 @@ impl ExecutionStrategy for SuiStrategy {
     fn description(&self) -> &str {
         "Sui Object-DAG object-level parallel execution"
     }
-} 
\ No newline at end of file
+
+    fn strategy_type(&self) -> StrategyType {
+        StrategyType::SuiObject
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conflict::ConflictAnalyzer;
+    use crate::metrics::plan_efficiency;
+
+    fn object_tx(index: usize, object_id: &str) -> Transaction {
+        Transaction {
+            hash: format!("0x{:04x}", index),
+            from: format!("0xsender{}", index),
+            to: None,
+            data: vec![],
+            gas_limit: 21000,
+            gas_price: 1,
+            nonce: 0,
+            read_set: vec![object_id.to_string()],
+            write_set: vec![object_id.to_string()],
+            access_list: None,
+            max_priority_fee: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_owned_objects_take_fast_path() {
+        let mut transactions: Vec<Transaction> = (0..100)
+            .map(|i| object_tx(i, &format!("0xowned{}", i)))
+            .collect();
+        transactions.extend((100..105).map(|i| object_tx(i, "0xpool")));
+
+        let graph = ConflictAnalyzer::new().analyze(&transactions).await.unwrap();
+        let strategy = SuiStrategy::new().with_shared_objects(["0xpool".to_string()]);
+        let plan = strategy.plan_execution(&transactions, &graph).await.unwrap();
+
+        assert_eq!(plan.unordered, (0..100).collect::<Vec<_>>());
+        let ordered: Vec<usize> = plan.parallel_groups.iter().flatten().copied().collect();
+        assert!(ordered.len() <= 5);
+        assert_eq!(ordered, (100..105).collect::<Vec<_>>());
+        // 共享对象上的交易逐个排序
+        assert!(plan.parallel_groups.iter().all(|group| group.len() == 1));
+
+        // fast path 交易与第一层同时调度
+        let groups = plan.scheduled_groups();
+        assert_eq!(groups[0].len(), 101);
+        assert!(plan_efficiency(&plan, transactions.len(), 8) > 0.5);
+    }
+
+    /// 以全部成功的结果提交批次
+    async fn commit(strategy: &SuiStrategy, transactions: &[Transaction], batch_id: u64) {
+        let results: Vec<TransactionResult> = transactions
+            .iter()
+            .map(|tx| TransactionResult {
+                tx_hash: tx.hash.clone(),
+                success: true,
+                gas_used: 1,
+                output: vec![],
+                logs: vec![],
+                error: None,
+            })
+            .collect();
+        let ctx = BatchContext {
+            batch_id,
+            transactions,
+            seed: None,
+        };
+        strategy.on_batch_commit(&ctx, &results).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_owner_index_learns_shared_objects_across_batches() {
+        let strategy = SuiStrategy::new();
+
+        // 批次一：0xcoin 由 sender1 写入，仍是独占对象
+        let first = vec![object_tx(1, "0xcoin")];
+        commit(&strategy, &first, 0).await;
+        assert!(!strategy.writes_shared(&first[0]));
+
+        // 批次二：同一发送方再次写入不改变判断，另一发送方写入后视同共享
+        commit(&strategy, &[object_tx(1, "0xcoin")], 1).await;
+        assert_eq!(strategy.learned_shared_count(), 0);
+        let second = vec![object_tx(2, "0xcoin")];
+        commit(&strategy, &second, 2).await;
+        assert_eq!(strategy.learned_shared_count(), 1);
+
+        // 批次三：写 0xcoin 的交易不再走 fast path
+        let transactions = vec![object_tx(3, "0xcoin"), object_tx(4, "0xother")];
+        let graph = ConflictAnalyzer::new().analyze(&transactions).await.unwrap();
+        let plan = strategy.plan_execution(&transactions, &graph).await.unwrap();
+        assert_eq!(plan.unordered, vec![1]);
+        assert_eq!(plan.parallel_groups, vec![vec![0]]);
+    }
+}
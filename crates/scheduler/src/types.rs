@@ -1,14 +1,25 @@
 //! Scheduler 类型定义
 
+use dubhe_security::{canonical_digest, VersionedDigest};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// 批次结果的摘要域
+pub const BATCH_RESULT_DOMAIN: &str = "dubhe.scheduler.batch_result";
+
+/// 确定性模式下规范化结果摘要的域
+pub const CANONICAL_RESULT_DOMAIN: &str = "dubhe.scheduler.canonical_result";
+
+/// 由批次哈希派生策略随机种子的域
+pub const BATCH_SEED_DOMAIN: &str = "dubhe.scheduler.batch_seed";
+
 /// 调度策略类型
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum StrategyType {
     SolanaParallel, // Solana Sealevel 账号读写集合并行
     AptosSTM,       // Aptos Block-STM 乐观并发控制
     SuiObject,      // Sui Object-DAG 对象级并行
+    Sequential,     // 串行执行（测试与回退）
 }
 
 /// 交易表示
@@ -23,6 +34,27 @@ pub struct Transaction {
     pub nonce: u64,
     pub read_set: Vec<String>,  // 读取的状态地址
     pub write_set: Vec<String>, // 写入的状态地址
+    /// 请求方声明的访问列表（Solana 风格），优先于 read_set/write_set
+    #[serde(default)]
+    pub access_list: Option<AccessList>,
+    /// EIP-1559 风格的小费上限；未设置时整个 gas price 视为优先费
+    #[serde(default)]
+    pub max_priority_fee: Option<u64>,
+}
+
+impl Transaction {
+    /// 实际优先费：通道没有基础费，gas price 即费用上限，小费不超过它
+    pub fn priority_fee(&self) -> u64 {
+        self.max_priority_fee
+            .map_or(self.gas_price, |tip| tip.min(self.gas_price))
+    }
+}
+
+/// 声明的访问列表
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessList {
+    pub reads: Vec<String>,
+    pub writes: Vec<String>,
 }
 
 /// 执行计划
@@ -30,6 +62,37 @@ pub struct Transaction {
 pub struct ExecutionPlan {
     pub parallel_groups: Vec<Vec<usize>>, // 可并行执行的交易组
     pub dependency_order: Vec<usize>,     // 依赖顺序
+    /// 无排序约束的交易（如只触及独占对象的 Sui 交易），不进入冲突图，可在任意 worker 上立即执行
+    pub unordered: Vec<usize>,
+}
+
+impl ExecutionPlan {
+    /// 实际调度的分组：无序交易并入第一组，与有序部分同时开始
+    pub fn scheduled_groups(&self) -> Vec<Vec<usize>> {
+        let mut groups = self.parallel_groups.clone();
+        if !self.unordered.is_empty() {
+            match groups.first_mut() {
+                Some(first) => first.extend(&self.unordered),
+                None => groups.push(self.unordered.clone()),
+            }
+        }
+        groups
+    }
+
+    /// 把基于重排后批次的下标映射回原批次，`order[i]` 为重排后第 i 笔交易的原下标
+    pub fn remap(self, order: &[usize]) -> Self {
+        let map = |indices: Vec<usize>| -> Vec<usize> {
+            indices
+                .into_iter()
+                .filter_map(|i| order.get(i).copied())
+                .collect()
+        };
+        Self {
+            parallel_groups: self.parallel_groups.into_iter().map(map).collect(),
+            dependency_order: map(self.dependency_order),
+            unordered: map(self.unordered),
+        }
+    }
 }
 
 /// 交易执行结果
@@ -43,6 +106,16 @@ pub struct TransactionResult {
     pub error: Option<String>,
 }
 
+/// 单笔交易的执行结果及其发送方与目标合约，供工作负载监控使用
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionOutcome {
+    pub tx_hash: String,
+    pub from: String,
+    pub to: Option<String>,
+    pub gas_used: u64,
+    pub success: bool,
+}
+
 /// 批次执行结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchResult {
@@ -50,6 +123,56 @@ pub struct BatchResult {
     pub execution_stats: ExecutionStats,
 }
 
+impl BatchResult {
+    /// 批次结果的规范化摘要
+    ///
+    /// 只覆盖交易结果；执行统计含浮点数且不属于共识数据
+    pub fn result_hash(&self) -> anyhow::Result<VersionedDigest> {
+        canonical_digest(BATCH_RESULT_DOMAIN, &self.transaction_results)
+    }
+
+    /// 按交易在批次中的原始序号哈希各笔交易的执行结果，供共识比对
+    ///
+    /// 要求结果已按原始序号排列（确定性模式下调度器保证这一点）；执行统计不参与
+    pub fn canonical_digest(&self) -> anyhow::Result<VersionedDigest> {
+        let outcomes: Vec<CanonicalOutcome<'_>> = self
+            .transaction_results
+            .iter()
+            .enumerate()
+            .map(|(index, result)| CanonicalOutcome {
+                index: index as u64,
+                tx_hash: &result.tx_hash,
+                success: result.success,
+                gas_used: result.gas_used,
+                output: &result.output,
+                logs: &result.logs,
+                error: result.error.as_deref(),
+            })
+            .collect();
+        canonical_digest(CANONICAL_RESULT_DOMAIN, &outcomes)
+    }
+}
+
+/// 规范化摘要中的单笔交易结果，只含整数、布尔与字符串
+#[derive(Serialize)]
+struct CanonicalOutcome<'a> {
+    index: u64,
+    tx_hash: &'a str,
+    success: bool,
+    gas_used: u64,
+    output: &'a [u8],
+    logs: &'a [String],
+    error: Option<&'a str>,
+}
+
+/// 由批次哈希（按序的交易哈希）派生的随机种子，同一批次在任意节点上相同
+pub fn batch_seed(transactions: &[Transaction]) -> anyhow::Result<u64> {
+    let hashes: Vec<&str> = transactions.iter().map(|tx| tx.hash.as_str()).collect();
+    let digest = canonical_digest(BATCH_SEED_DOMAIN, &hashes)?;
+    let hex = digest.digest.trim_start_matches("0x");
+    Ok(u64::from_str_radix(&hex[..16], 16)?)
+}
+
 /// 执行统计
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ExecutionStats {
@@ -60,6 +183,21 @@ pub struct ExecutionStats {
     pub execution_time_ms: u64,
     pub parallel_efficiency: f64,
     pub conflicts_detected: usize,
+    /// 批次在提交队列中等待执行槽的时长
+    #[serde(default)]
+    pub queue_time_ms: u64,
+    /// 冲突图给出的理论并行度上限：估算 gas 之和除以关键路径的 gas
+    #[serde(default)]
+    pub parallelism_bound: f64,
+    /// 批次期间的进程资源使用，按进程采样，并发批次互相计入；不支持的平台为 `None`
+    #[serde(default)]
+    pub resources: Option<crate::resources::ResourceUsage>,
+    /// 成功与失败交易的 gas 用量与 gas price 之积的总和
+    #[serde(default)]
+    pub total_fees: u64,
+    /// 其中的优先费部分（gas 用量与优先费之积）
+    #[serde(default)]
+    pub priority_fees: u64,
 }
 
 /// 调度器配置
@@ -67,9 +205,17 @@ pub struct ExecutionStats {
 pub struct SchedulerConfig {
     pub worker_threads: usize,
     pub batch_size: usize,
+    /// 提交队列容量（交易数），用户提交超过时被拒绝
     pub max_queue_size: usize,
     pub timeout_ms: u64,
     pub enable_optimistic_execution: bool,
+    /// 确定性模式：结果按交易原始序号排列，策略随机性由批次哈希派生的种子驱动，
+    /// 用于需要跨节点比对 [`BatchResult::canonical_digest`] 的共识接入
+    #[serde(default)]
+    pub deterministic: bool,
+    /// 分发器执行计划前校验组内与跨组的读写冲突，拒绝不安全的计划（见 [`crate::verify`]）
+    #[serde(default)]
+    pub verify_plans: bool,
 }
 
 impl Default for SchedulerConfig {
@@ -80,6 +226,8 @@ impl Default for SchedulerConfig {
             max_queue_size: 10000,
             timeout_ms: 30000,
             enable_optimistic_execution: true,
+            deterministic: false,
+            verify_plans: false,
         }
     }
 }
@@ -89,8 +237,73 @@ impl Default for SchedulerConfig {
 pub struct SchedulerStatus {
     pub strategy_type: StrategyType,
     pub worker_threads: usize,
+    /// 提交队列中等待与执行中的交易数
     pub queue_length: usize,
     pub total_processed: u64,
     pub conflicts_detected: u64,
     pub parallel_efficiency: f64,
+    /// 按策略划分的累计计数
+    pub per_strategy: HashMap<StrategyType, StrategyCounters>,
+}
+
+/// 单个策略的累计计数
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StrategyCounters {
+    pub batches: u64,
+    pub transactions: u64,
+    pub conflicts: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_result_hash_ignores_stats() {
+        let mut batch = BatchResult {
+            transaction_results: vec![TransactionResult {
+                tx_hash: "0x01".to_string(),
+                success: true,
+                gas_used: 21000,
+                output: vec![1, 2, 3],
+                logs: vec![],
+                error: None,
+            }],
+            execution_stats: ExecutionStats::default(),
+        };
+
+        let digest = batch.result_hash().unwrap();
+        assert!(digest.to_string().starts_with("v1:0x"));
+
+        batch.execution_stats.parallel_efficiency = 0.75;
+        assert_eq!(batch.result_hash().unwrap(), digest);
+
+        batch.transaction_results[0].gas_used = 0;
+        assert_ne!(batch.result_hash().unwrap(), digest);
+    }
+
+    #[test]
+    fn test_canonical_digest_depends_on_order() {
+        let result = |hash: &str| TransactionResult {
+            tx_hash: hash.to_string(),
+            success: true,
+            gas_used: 21000,
+            output: vec![],
+            logs: vec![],
+            error: None,
+        };
+        let mut batch = BatchResult {
+            transaction_results: vec![result("0x01"), result("0x02")],
+            execution_stats: ExecutionStats::default(),
+        };
+
+        let digest = batch.canonical_digest().unwrap();
+        assert_ne!(digest, batch.result_hash().unwrap());
+
+        batch.execution_stats.parallelism_bound = 2.0;
+        assert_eq!(batch.canonical_digest().unwrap(), digest);
+
+        batch.transaction_results.reverse();
+        assert_ne!(batch.canonical_digest().unwrap(), digest);
+    }
 }
@@ -0,0 +1,235 @@
+//! 执行计划校验
+//!
+//! 分发器按组依次执行计划：组内交易并发、互不排序，后一组在前一组全部完成后开始。
+//! 因此计划安全当且仅当：
+//! - 同一组内任意两笔交易的读写集合没有写-写或读-写交集；
+//! - 跨组的冲突交易按冲突图的依赖方向（下标小者在前）落在先后两组。
+//!
+//! 读写集合取自冲突图（声明的或估算的访问集合）。写-写冲突会丢失更新，严重程度为
+//! [`IssueSeverity::Critical`]；读-写冲突使读到的版本不确定，为 [`IssueSeverity::High`]
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+use crate::conflict::ConflictGraph;
+use crate::dispatcher::execution_groups;
+use crate::error::SchedulerError;
+use crate::types::{ExecutionPlan, Transaction};
+
+/// 冲突类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConflictKind {
+    WriteWrite,
+    ReadWrite,
+}
+
+/// 问题严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum IssueSeverity {
+    High,
+    Critical,
+}
+
+impl ConflictKind {
+    pub fn severity(self) -> IssueSeverity {
+        match self {
+            ConflictKind::WriteWrite => IssueSeverity::Critical,
+            ConflictKind::ReadWrite => IssueSeverity::High,
+        }
+    }
+}
+
+/// 计划中一对未被正确排序的冲突交易
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationIssue {
+    pub conflict: ConflictKind,
+    pub severity: IssueSeverity,
+    /// 两笔交易在批次中的下标，按下标升序
+    pub transactions: (usize, usize),
+    pub tx_hashes: (String, String),
+    /// 争用的状态地址
+    pub key: String,
+    /// 两笔交易所在的执行组；相同为组内冲突，否则为跨组顺序颠倒
+    pub groups: (usize, usize),
+}
+
+impl VerificationIssue {
+    pub fn is_same_group(&self) -> bool {
+        self.groups.0 == self.groups.1
+    }
+}
+
+impl fmt::Display for VerificationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} conflict on {} between {} (group {}) and {} (group {})",
+            self.conflict,
+            self.key,
+            self.tx_hashes.0,
+            self.groups.0,
+            self.tx_hashes.1,
+            self.groups.1
+        )
+    }
+}
+
+/// 校验执行计划，返回按交易对与地址排序的全部问题；为空表示计划安全
+pub fn verify_plan(
+    plan: &ExecutionPlan,
+    transactions: &[Transaction],
+    conflict_graph: &ConflictGraph,
+) -> Vec<VerificationIssue> {
+    let len = transactions.len();
+    let mut group_of = vec![0usize; len];
+    for (group, members) in execution_groups(plan.clone(), len).iter().enumerate() {
+        for &index in members {
+            group_of[index] = group;
+        }
+    }
+
+    let mut issues = Vec::new();
+    for (key, writers) in &conflict_graph.write_conflicts {
+        let writers: BTreeSet<usize> = writers.iter().copied().filter(|&i| i < len).collect();
+        // 同时读写同一地址的交易只按写者计
+        let readers: BTreeSet<usize> = conflict_graph
+            .read_conflicts
+            .get(key)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|&i| i < len && !writers.contains(&i))
+            .collect();
+
+        let mut pairs: HashSet<(usize, usize, ConflictKind)> = HashSet::new();
+        for &a in &writers {
+            for &b in writers.range(a + 1..) {
+                pairs.insert((a, b, ConflictKind::WriteWrite));
+            }
+            for &reader in &readers {
+                pairs.insert((a.min(reader), a.max(reader), ConflictKind::ReadWrite));
+            }
+        }
+
+        // 下标小者必须在更早的组执行
+        for (a, b, conflict) in pairs {
+            if group_of[a] < group_of[b] {
+                continue;
+            }
+            issues.push(VerificationIssue {
+                conflict,
+                severity: conflict.severity(),
+                transactions: (a, b),
+                tx_hashes: (transactions[a].hash.clone(), transactions[b].hash.clone()),
+                key: key.clone(),
+                groups: (group_of[a], group_of[b]),
+            });
+        }
+    }
+
+    issues.sort_by(|x, y| (x.transactions, &x.key).cmp(&(y.transactions, &y.key)));
+    issues
+}
+
+/// 校验执行计划，存在问题时返回 [`SchedulerError::UnsafePlan`]
+pub fn ensure_safe_plan(
+    plan: &ExecutionPlan,
+    transactions: &[Transaction],
+    conflict_graph: &ConflictGraph,
+) -> Result<(), SchedulerError> {
+    let issues = verify_plan(plan, transactions, conflict_graph);
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(SchedulerError::UnsafePlan { issues })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conflict::ConflictAnalyzer;
+
+    fn tx(hash: &str, read_set: &[&str], write_set: &[&str]) -> Transaction {
+        Transaction {
+            hash: hash.to_string(),
+            from: "0xsender".to_string(),
+            to: None,
+            data: vec![],
+            gas_limit: 21000,
+            gas_price: 1,
+            nonce: 0,
+            read_set: read_set.iter().map(|s| s.to_string()).collect(),
+            write_set: write_set.iter().map(|s| s.to_string()).collect(),
+            access_list: None,
+            max_priority_fee: None,
+        }
+    }
+
+    fn plan(groups: Vec<Vec<usize>>) -> ExecutionPlan {
+        ExecutionPlan {
+            dependency_order: groups.iter().flatten().copied().collect(),
+            parallel_groups: groups,
+            unordered: vec![],
+        }
+    }
+
+    fn batch() -> Vec<Transaction> {
+        vec![
+            tx("0x0", &[], &["a"]),
+            tx("0x1", &[], &["a"]),
+            tx("0x2", &["a"], &["b"]),
+            tx("0x3", &["c"], &[]),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_plan_reports_each_pair() {
+        let transactions = batch();
+        let graph = ConflictAnalyzer::new()
+            .analyze(&transactions)
+            .await
+            .unwrap();
+
+        // 0、1 同组写 a；2 读 a 却排在 0、1 之前
+        let issues = verify_plan(&plan(vec![vec![2, 3], vec![0, 1]]), &transactions, &graph);
+        let summary: Vec<_> = issues
+            .iter()
+            .map(|issue| (issue.transactions, issue.groups, issue.conflict))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ((0, 1), (1, 1), ConflictKind::WriteWrite),
+                ((0, 2), (1, 0), ConflictKind::ReadWrite),
+                ((1, 2), (1, 0), ConflictKind::ReadWrite),
+            ]
+        );
+        assert!(issues.iter().all(|issue| issue.key == "a"));
+        assert_eq!(issues[0].severity, IssueSeverity::Critical);
+        assert_eq!(issues[1].severity, IssueSeverity::High);
+        assert!(issues[0].is_same_group());
+        assert_eq!(issues[0].tx_hashes, ("0x0".to_string(), "0x1".to_string()));
+
+        let error =
+            ensure_safe_plan(&plan(vec![vec![0, 1, 2, 3]]), &transactions, &graph).unwrap_err();
+        assert!(matches!(error, SchedulerError::UnsafePlan { ref issues } if issues.len() == 3));
+    }
+
+    #[tokio::test]
+    async fn test_layered_plan_passes() {
+        let transactions = batch();
+        let graph = ConflictAnalyzer::new()
+            .analyze(&transactions)
+            .await
+            .unwrap();
+
+        let layered = plan(graph.layered_groups(transactions.len(), |_| true));
+        assert!(verify_plan(&layered, &transactions, &graph).is_empty());
+
+        // 未被任何组覆盖的交易在所有组之后逐笔执行
+        let partial = plan(vec![vec![0, 3]]);
+        assert!(ensure_safe_plan(&partial, &transactions, &graph).is_ok());
+    }
+}
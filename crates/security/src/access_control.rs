@@ -1,9 +1,326 @@
 //! 访问控制模块
+//!
+//! 基于角色的权限控制：API Key 对应的主体被分配一个角色，角色决定可执行的特权操作
+//! （插件加载 / 卸载、配置与告警规则热加载、缓存失效、状态快照）。每次允许或拒绝的决定都交给
+//! 审计钩子记录。未启用访问控制时所有检查直接放行，但仍会被审计。
 
-pub struct AccessControl;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tracing::{debug, warn};
+
+/// 特权操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    ReloadAlertRules,
+    ReloadConfig,
+    InvalidateCache,
+    LoadPlugin,
+    UnloadPlugin,
+    CreateSnapshot,
+}
+
+/// 角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// 全部特权操作
+    Admin,
+    /// 运维操作，不能加载或卸载插件
+    Operator,
+    /// 不能执行任何特权操作
+    ReadOnly,
+}
+
+impl Role {
+    pub fn permissions(&self) -> &'static [Permission] {
+        match self {
+            Self::Admin => &[
+                Permission::ReloadAlertRules,
+                Permission::ReloadConfig,
+                Permission::InvalidateCache,
+                Permission::LoadPlugin,
+                Permission::UnloadPlugin,
+                Permission::CreateSnapshot,
+            ],
+            Self::Operator => &[
+                Permission::ReloadAlertRules,
+                Permission::ReloadConfig,
+                Permission::InvalidateCache,
+                Permission::CreateSnapshot,
+            ],
+            Self::ReadOnly => &[],
+        }
+    }
+
+    pub fn allows(&self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+}
+
+/// 请求特权操作的主体
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Principal {
+    /// 以 API Key 名称标识
+    ApiKey(String),
+    /// 未携带 API Key 的调用方
+    Anonymous,
+    /// 节点内部触发（例如 SIGHUP），不经过 API
+    Internal,
+}
+
+impl fmt::Display for Principal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ApiKey(name) => write!(f, "api_key:{}", name),
+            Self::Anonymous => f.write_str("anonymous"),
+            Self::Internal => f.write_str("internal"),
+        }
+    }
+}
+
+/// 访问控制配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccessControlConfig {
+    /// API Key 名称与角色的对应关系，未列出的 Key 视为 ReadOnly
+    #[serde(default)]
+    pub principals: Vec<PrincipalConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrincipalConfig {
+    /// `api.auth.api_keys` 中的 Key 名称
+    pub api_key: String,
+    pub role: Role,
+}
+
+/// 一次访问控制决定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessDecision {
+    pub principal: Principal,
+    pub permission: Permission,
+    pub allowed: bool,
+    /// Unix 时间戳（毫秒）
+    pub timestamp_ms: u64,
+}
+
+/// 审计钩子，记录每一次访问控制决定
+pub trait AuditHook: Send + Sync {
+    fn record(&self, decision: &AccessDecision);
+}
+
+/// 进程内审计记录
+#[derive(Debug, Default)]
+pub struct MemoryAuditLog {
+    decisions: Mutex<Vec<AccessDecision>>,
+}
+
+impl MemoryAuditLog {
+    pub fn decisions(&self) -> Vec<AccessDecision> {
+        self.decisions.lock().unwrap().clone()
+    }
+
+    pub fn denials(&self) -> Vec<AccessDecision> {
+        self.decisions()
+            .into_iter()
+            .filter(|decision| !decision.allowed)
+            .collect()
+    }
+}
+
+impl AuditHook for MemoryAuditLog {
+    fn record(&self, decision: &AccessDecision) {
+        self.decisions.lock().unwrap().push(decision.clone());
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{principal} is not allowed to {permission:?}")]
+pub struct AccessDenied {
+    pub principal: Principal,
+    pub permission: Permission,
+}
+
+/// 已通过检查的特权操作凭证，只能由 [`AccessControl`] 签发
+#[derive(Debug, Clone)]
+pub struct Capability {
+    principal: Principal,
+    permission: Permission,
+}
+
+impl Capability {
+    pub fn principal(&self) -> &Principal {
+        &self.principal
+    }
+
+    pub fn permission(&self) -> Permission {
+        self.permission
+    }
+}
+
+/// 访问控制组件
+pub struct AccessControl {
+    enabled: bool,
+    roles: HashMap<String, Role>,
+    hooks: Vec<Arc<dyn AuditHook>>,
+}
 
 impl AccessControl {
-    pub fn new() -> Self {
-        Self
+    pub fn new(config: &AccessControlConfig) -> Self {
+        Self {
+            enabled: true,
+            roles: config
+                .principals
+                .iter()
+                .map(|principal| (principal.api_key.clone(), principal.role))
+                .collect(),
+            hooks: Vec::new(),
+        }
+    }
+
+    /// 不做限制的访问控制，用于未启用访问控制的节点
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            roles: HashMap::new(),
+            hooks: Vec::new(),
+        }
+    }
+
+    pub fn with_audit_hook(mut self, hook: Arc<dyn AuditHook>) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 主体的角色；节点内部调用视为 Admin，匿名调用方与未配置的 Key 视为 ReadOnly
+    pub fn role_of(&self, principal: &Principal) -> Role {
+        match principal {
+            Principal::Internal => Role::Admin,
+            Principal::ApiKey(name) => self.roles.get(name).copied().unwrap_or(Role::ReadOnly),
+            Principal::Anonymous => Role::ReadOnly,
+        }
+    }
+
+    /// 检查主体能否执行该操作，并记录审计
+    pub fn check(&self, principal: &Principal, permission: Permission) -> Result<(), AccessDenied> {
+        let allowed = !self.enabled || self.role_of(principal).allows(permission);
+        let decision = AccessDecision {
+            principal: principal.clone(),
+            permission,
+            allowed,
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis() as u64)
+                .unwrap_or_default(),
+        };
+        for hook in &self.hooks {
+            hook.record(&decision);
+        }
+
+        if allowed {
+            debug!("Access granted: {} -> {:?}", principal, permission);
+            Ok(())
+        } else {
+            warn!("🚫 Access denied: {} -> {:?}", principal, permission);
+            Err(AccessDenied {
+                principal: principal.clone(),
+                permission,
+            })
+        }
+    }
+
+    /// 检查通过后签发凭证，供需要显式授权的操作（如插件加载）使用
+    pub fn authorize(
+        &self,
+        principal: &Principal,
+        permission: Permission,
+    ) -> Result<Capability, AccessDenied> {
+        self.check(principal, permission)?;
+        Ok(Capability {
+            principal: principal.clone(),
+            permission,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn access_control(audit: Arc<MemoryAuditLog>) -> AccessControl {
+        AccessControl::new(&AccessControlConfig {
+            principals: vec![
+                PrincipalConfig {
+                    api_key: "ops".to_string(),
+                    role: Role::Operator,
+                },
+                PrincipalConfig {
+                    api_key: "root".to_string(),
+                    role: Role::Admin,
+                },
+            ],
+        })
+        .with_audit_hook(audit)
+    }
+
+    #[test]
+    fn test_operator_can_reload_rules_but_not_load_plugins() {
+        let audit = Arc::new(MemoryAuditLog::default());
+        let access = access_control(audit.clone());
+        let operator = Principal::ApiKey("ops".to_string());
+
+        assert!(access
+            .check(&operator, Permission::ReloadAlertRules)
+            .is_ok());
+        let denied = access
+            .authorize(&operator, Permission::LoadPlugin)
+            .unwrap_err();
+        assert_eq!(denied.permission, Permission::LoadPlugin);
+
+        let root = Principal::ApiKey("root".to_string());
+        let capability = access.authorize(&root, Permission::LoadPlugin).unwrap();
+        assert_eq!(capability.permission(), Permission::LoadPlugin);
+
+        // 每个决定都被记录，拒绝记录包含主体与操作
+        assert_eq!(audit.decisions().len(), 3);
+        let denials = audit.denials();
+        assert_eq!(denials.len(), 1);
+        assert_eq!(denials[0].principal, operator);
+        assert_eq!(denials[0].permission, Permission::LoadPlugin);
+    }
+
+    #[test]
+    fn test_unknown_and_anonymous_callers_are_read_only() {
+        let audit = Arc::new(MemoryAuditLog::default());
+        let access = access_control(audit.clone());
+
+        assert!(access
+            .check(
+                &Principal::ApiKey("unknown".to_string()),
+                Permission::ReloadConfig
+            )
+            .is_err());
+        assert!(access
+            .check(&Principal::Anonymous, Permission::InvalidateCache)
+            .is_err());
+        assert!(access
+            .check(&Principal::Internal, Permission::ReloadConfig)
+            .is_ok());
+        assert_eq!(audit.denials().len(), 2);
+
+        // 未启用时放行，但仍然审计
+        let disabled = AccessControl::disabled().with_audit_hook(audit.clone());
+        assert!(disabled
+            .check(&Principal::Anonymous, Permission::LoadPlugin)
+            .is_ok());
+        assert_eq!(audit.decisions().len(), 4);
     }
 }
@@ -1,9 +1,400 @@
 //! 审计追踪模块
+//!
+//! 只追加的安全事件日志。每条记录的哈希覆盖上一条记录的哈希、事件的规范 JSON 与时间戳：
+//! `entry_hash = SHA-256(prev_hash || canonical_json(event) || timestamp_ms)`，
+//! 事后修改或删除任一条记录都会让 [`AuditTrail::verify_integrity`] 在该处断链。
+//! 记录以 JSON Lines 写入文件，每次追加后 fsync。
 
-pub struct AuditTrail;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::ops::RangeBounds;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::access_control::{AccessDecision, AuditHook, Permission};
+use crate::canonical::to_canonical_json;
+
+/// 第一条记录的 prev_hash
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// 安全相关事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditEvent {
+    PluginLoaded {
+        name: String,
+        version: String,
+        path: String,
+    },
+    PluginUnloaded {
+        name: String,
+        path: String,
+    },
+    ObjectLocked {
+        object_id: String,
+        version: u64,
+        lease: String,
+    },
+    ObjectUnlocked {
+        object_id: String,
+        lease: String,
+    },
+    /// 链下执行的结果已回写主网，字段以路径表示
+    ObjectModified {
+        object_id: String,
+        session_id: String,
+        old_version: u64,
+        fields_modified: Vec<String>,
+        fields_added: Vec<String>,
+        fields_removed: Vec<String>,
+    },
+    /// 特权 RPC 的访问控制决定
+    AdminRpcInvoked {
+        principal: String,
+        permission: Permission,
+        allowed: bool,
+    },
+    ValidatorSlashed {
+        validator: String,
+        amount: u64,
+        reason: String,
+    },
+    /// 威胁检测规则命中，`throttled_until_ms` 为限流解除时间
+    ThreatDetected {
+        rule: String,
+        subject: String,
+        observed: u64,
+        threshold: u64,
+        throttled_until_ms: Option<u64>,
+    },
+}
+
+impl AuditEvent {
+    /// 事件类型，与序列化后的 `type` 字段一致
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            Self::PluginLoaded { .. } => "plugin_loaded",
+            Self::PluginUnloaded { .. } => "plugin_unloaded",
+            Self::ObjectLocked { .. } => "object_locked",
+            Self::ObjectUnlocked { .. } => "object_unlocked",
+            Self::ObjectModified { .. } => "object_modified",
+            Self::AdminRpcInvoked { .. } => "admin_rpc_invoked",
+            Self::ValidatorSlashed { .. } => "validator_slashed",
+            Self::ThreatDetected { .. } => "threat_detected",
+        }
+    }
+}
+
+/// 审计记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub sequence: u64,
+    /// Unix 时间戳（毫秒）
+    pub timestamp_ms: u64,
+    pub event: AuditEvent,
+    pub prev_hash: String,
+    pub entry_hash: String,
+}
+
+impl AuditEntry {
+    pub fn compute_hash(prev_hash: &str, event: &AuditEvent, timestamp_ms: u64) -> Result<String> {
+        let mut hasher = Sha256::new();
+        hasher.update(prev_hash.as_bytes());
+        hasher.update(to_canonical_json(event)?);
+        hasher.update(timestamp_ms.to_be_bytes());
+        Ok(hex::encode(hasher.finalize()))
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityError {
+    #[error("Audit entry {sequence} cannot be parsed: {reason}")]
+    Unreadable { sequence: u64, reason: String },
+
+    #[error("Audit entry {sequence} has sequence {found}")]
+    OutOfOrder { sequence: u64, found: u64 },
+
+    #[error("Audit entry {sequence} does not link to the previous entry")]
+    BrokenLink { sequence: u64 },
+
+    #[error("Audit entry {sequence} hash mismatch: stored {stored}, computed {computed}")]
+    HashMismatch {
+        sequence: u64,
+        stored: String,
+        computed: String,
+    },
+}
+
+impl IntegrityError {
+    /// 第一个断链的记录序号
+    pub fn sequence(&self) -> u64 {
+        match self {
+            Self::Unreadable { sequence, .. }
+            | Self::OutOfOrder { sequence, .. }
+            | Self::BrokenLink { sequence }
+            | Self::HashMismatch { sequence, .. } => *sequence,
+        }
+    }
+}
+
+struct Tail {
+    file: File,
+    next_sequence: u64,
+    last_hash: String,
+}
+
+/// 哈希链审计日志
+pub struct AuditTrail {
+    path: PathBuf,
+    tail: Mutex<Tail>,
+}
 
 impl AuditTrail {
-    pub fn new() -> Self {
-        Self
+    /// 打开（或创建）审计日志，从最后一条记录继续追加
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        let mut tail = Tail {
+            file,
+            next_sequence: 0,
+            last_hash: GENESIS_HASH.to_string(),
+        };
+        let mut last_line = None;
+        for line in BufReader::new(File::open(&path)?).lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                last_line = Some(line);
+            }
+        }
+        if let Some(line) = last_line {
+            let last: AuditEntry = serde_json::from_str(&line)?;
+            tail.next_sequence = last.sequence + 1;
+            tail.last_hash = last.entry_hash;
+        }
+
+        info!(
+            "📜 Audit trail opened at {} ({} entries)",
+            path.display(),
+            tail.next_sequence
+        );
+        Ok(Self {
+            path,
+            tail: Mutex::new(tail),
+        })
+    }
+
+    /// 追加事件，写入并 fsync 后返回记录
+    pub fn append(&self, event: AuditEvent) -> Result<AuditEntry> {
+        let mut tail = self.tail.lock().unwrap();
+        let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+        let entry = AuditEntry {
+            sequence: tail.next_sequence,
+            timestamp_ms,
+            entry_hash: AuditEntry::compute_hash(&tail.last_hash, &event, timestamp_ms)?,
+            prev_hash: tail.last_hash.clone(),
+            event,
+        };
+
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        tail.file.write_all(&line)?;
+        tail.file.sync_data()?;
+
+        tail.next_sequence += 1;
+        tail.last_hash = entry.entry_hash.clone();
+        Ok(entry)
+    }
+
+    /// 追加事件，失败只记录日志，供不能因审计失败而中断的调用点使用
+    pub fn record_event(&self, event: AuditEvent) {
+        let event_type = event.event_type();
+        if let Err(e) = self.append(event) {
+            warn!("⚠️ Failed to append {} to audit trail: {}", event_type, e);
+        }
+    }
+
+    /// 读取全部记录
+    pub fn entries(&self) -> Result<Vec<AuditEntry>> {
+        let mut entries = Vec::new();
+        for line in self.lines()? {
+            entries.push(serde_json::from_str(&line)?);
+        }
+        Ok(entries)
+    }
+
+    /// 按时间范围（毫秒，闭区间）与事件类型查询
+    pub fn query(
+        &self,
+        from_ms: u64,
+        to_ms: u64,
+        event_type: Option<&str>,
+    ) -> Result<Vec<AuditEntry>> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .filter(|entry| entry.timestamp_ms >= from_ms && entry.timestamp_ms <= to_ms)
+            .filter(|entry| event_type.is_none_or(|t| entry.event.event_type() == t))
+            .collect())
+    }
+
+    /// 重新计算 `range` 内记录的哈希链，返回校验的记录数或第一个断链处
+    ///
+    /// 范围内第一条记录的 prev_hash 与其前一条记录比对，因此也能发现范围边界处的篡改
+    pub fn verify_integrity<R: RangeBounds<u64>>(&self, range: R) -> Result<usize, IntegrityError> {
+        let lines = self.lines().map_err(|e| IntegrityError::Unreadable {
+            sequence: 0,
+            reason: e.to_string(),
+        })?;
+
+        let mut expected_prev = GENESIS_HASH.to_string();
+        let mut verified = 0;
+        for (index, line) in lines.iter().enumerate() {
+            let sequence = index as u64;
+            let entry: AuditEntry =
+                serde_json::from_str(line).map_err(|e| IntegrityError::Unreadable {
+                    sequence,
+                    reason: e.to_string(),
+                })?;
+
+            if range.contains(&sequence) {
+                if entry.sequence != sequence {
+                    return Err(IntegrityError::OutOfOrder {
+                        sequence,
+                        found: entry.sequence,
+                    });
+                }
+                if entry.prev_hash != expected_prev {
+                    return Err(IntegrityError::BrokenLink { sequence });
+                }
+                let computed =
+                    AuditEntry::compute_hash(&entry.prev_hash, &entry.event, entry.timestamp_ms)
+                        .map_err(|e| IntegrityError::Unreadable {
+                            sequence,
+                            reason: e.to_string(),
+                        })?;
+                if computed != entry.entry_hash {
+                    return Err(IntegrityError::HashMismatch {
+                        sequence,
+                        stored: entry.entry_hash,
+                        computed,
+                    });
+                }
+                verified += 1;
+            }
+            expected_prev = entry.entry_hash;
+        }
+        Ok(verified)
+    }
+
+    fn lines(&self) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+        for line in BufReader::new(File::open(&self.path)?).lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                lines.push(line);
+            }
+        }
+        Ok(lines)
+    }
+}
+
+/// 访问控制决定写入审计日志
+impl AuditHook for AuditTrail {
+    fn record(&self, decision: &AccessDecision) {
+        self.record_event(AuditEvent::AdminRpcInvoked {
+            principal: decision.principal.to_string(),
+            permission: decision.permission,
+            allowed: decision.allowed,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_control::{AccessControl, AccessControlConfig, Principal};
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    fn locked(object_id: &str) -> AuditEvent {
+        AuditEvent::ObjectLocked {
+            object_id: object_id.to_string(),
+            version: 1,
+            lease: "0xlease".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_tampered_entry_breaks_the_chain() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("audit.log");
+
+        let trail = AuditTrail::open(&path)?;
+        for index in 0..5 {
+            trail.append(locked(&format!("0xobj{}", index)))?;
+        }
+        assert_eq!(trail.verify_integrity(..), Ok(5));
+
+        // 重新打开后从链尾继续
+        drop(trail);
+        let trail = AuditTrail::open(&path)?;
+        let entry = trail.append(AuditEvent::PluginLoaded {
+            name: "move-riscv".to_string(),
+            version: "1.0.0".to_string(),
+            path: "builtin:move-riscv".to_string(),
+        })?;
+        assert_eq!(entry.sequence, 5);
+        assert_eq!(trail.verify_integrity(..), Ok(6));
+
+        // 篡改第 2 条记录的事件内容
+        let content = std::fs::read_to_string(&path)?;
+        let tampered = content.replacen("0xobj2", "0xevil", 1);
+        assert_ne!(content, tampered);
+        std::fs::write(&path, tampered)?;
+
+        let err = trail.verify_integrity(..).unwrap_err();
+        assert_eq!(err.sequence(), 2);
+        assert!(matches!(err, IntegrityError::HashMismatch { .. }));
+        // 篡改点之前的范围仍然完好
+        assert_eq!(trail.verify_integrity(0..2), Ok(2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_and_access_decisions() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("audit.log");
+
+        let trail = Arc::new(AuditTrail::open(&path)?);
+        trail.append(locked("0xobj"))?;
+        let access =
+            AccessControl::new(&AccessControlConfig::default()).with_audit_hook(trail.clone());
+        assert!(access
+            .check(&Principal::Anonymous, Permission::LoadPlugin)
+            .is_err());
+
+        let admin = trail.query(0, u64::MAX, Some("admin_rpc_invoked"))?;
+        assert_eq!(admin.len(), 1);
+        assert_eq!(
+            admin[0].event,
+            AuditEvent::AdminRpcInvoked {
+                principal: "anonymous".to_string(),
+                permission: Permission::LoadPlugin,
+                allowed: false,
+            }
+        );
+        assert_eq!(trail.query(0, u64::MAX, None)?.len(), 2);
+        assert!(trail.query(0, 1, None)?.is_empty());
+        Ok(())
     }
 }
@@ -0,0 +1,255 @@
+//! 规范化序列化模块
+//!
+//! 所有对外可见的哈希 / 签名载荷都经由此处编码，保证与外部实现一致：
+//! - 对象键按 UTF-8 字节序排序，不输出任何空白
+//! - 整数按十进制原样输出，签名载荷中禁止浮点数
+//! - 载荷包裹在 `{"domain","payload","version"}` 信封中再做 SHA-256
+//!
+//! 摘要带版本号（`v1:0x...`），旧版本（v0，即未规范化的 serde_json 编码）数据仍可校验。
+//! 测试向量见 `testdata/canonical_json_v1.json`。
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// 当前规范化编码版本
+pub const CANONICAL_VERSION: u32 = 1;
+
+/// 未规范化的历史编码版本
+pub const LEGACY_VERSION: u32 = 0;
+
+/// 将任意可序列化值编码为规范化 JSON 字节
+pub fn to_canonical_json<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    let value = serde_json::to_value(value)?;
+    let mut out = Vec::new();
+    write_canonical(&value, &mut out)?;
+    Ok(out)
+}
+
+fn write_canonical(value: &Value, out: &mut Vec<u8>) -> Result<()> {
+    match value {
+        Value::Null => out.extend_from_slice(b"null"),
+        Value::Bool(true) => out.extend_from_slice(b"true"),
+        Value::Bool(false) => out.extend_from_slice(b"false"),
+        Value::Number(number) => {
+            if let Some(n) = number.as_u64() {
+                out.extend_from_slice(n.to_string().as_bytes());
+            } else if let Some(n) = number.as_i64() {
+                out.extend_from_slice(n.to_string().as_bytes());
+            } else {
+                return Err(anyhow!(
+                    "Floating point value {} is not allowed in canonical payloads",
+                    number
+                ));
+            }
+        }
+        Value::String(s) => out.extend_from_slice(serde_json::to_string(s)?.as_bytes()),
+        Value::Array(items) => {
+            out.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_canonical(item, out)?;
+            }
+            out.push(b']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+
+            out.push(b'{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                out.extend_from_slice(serde_json::to_string(key)?.as_bytes());
+                out.push(b':');
+                write_canonical(item, out)?;
+            }
+            out.push(b'}');
+        }
+    }
+    Ok(())
+}
+
+/// 带版本号的摘要
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionedDigest {
+    pub version: u32,
+    /// `0x` 前缀的十六进制 SHA-256
+    pub digest: String,
+}
+
+impl fmt::Display for VersionedDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}:{}", self.version, self.digest)
+    }
+}
+
+impl FromStr for VersionedDigest {
+    type Err = anyhow::Error;
+
+    /// 无版本前缀的摘要按 v0 处理
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            Some((version, digest)) if version.starts_with('v') => Ok(Self {
+                version: version[1..].parse()?,
+                digest: digest.to_string(),
+            }),
+            _ => Ok(Self {
+                version: LEGACY_VERSION,
+                digest: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// 摘要信封
+#[derive(Serialize)]
+struct Envelope<'a> {
+    domain: &'a str,
+    payload: Value,
+    version: u32,
+}
+
+/// 计算指定版本的摘要
+pub fn digest_versioned<T: Serialize + ?Sized>(
+    domain: &str,
+    payload: &T,
+    version: u32,
+) -> Result<VersionedDigest> {
+    let bytes = match version {
+        LEGACY_VERSION => serde_json::to_vec(payload)?,
+        CANONICAL_VERSION => to_canonical_json(&Envelope {
+            domain,
+            payload: serde_json::to_value(payload)?,
+            version,
+        })?,
+        other => return Err(anyhow!("Unsupported canonical encoding version: {}", other)),
+    };
+
+    Ok(VersionedDigest {
+        version,
+        digest: format!("0x{}", hex::encode(Sha256::digest(&bytes))),
+    })
+}
+
+/// 使用当前版本计算摘要
+pub fn canonical_digest<T: Serialize + ?Sized>(domain: &str, payload: &T) -> Result<VersionedDigest> {
+    digest_versioned(domain, payload, CANONICAL_VERSION)
+}
+
+/// 按摘要自带的版本重新计算并比对
+pub fn verify_digest<T: Serialize + ?Sized>(
+    domain: &str,
+    payload: &T,
+    expected: &VersionedDigest,
+) -> Result<bool> {
+    let actual = digest_versioned(domain, payload, expected.version)?;
+    Ok(actual.digest.eq_ignore_ascii_case(&expected.digest))
+}
+
+/// 可签名 / 可哈希载荷
+///
+/// 通过 [`canonical_payload!`](crate::canonical_payload) 为类型声明摘要域
+pub trait CanonicalPayload: Serialize {
+    const DOMAIN: &'static str;
+
+    fn canonical_bytes(&self) -> Result<Vec<u8>> {
+        to_canonical_json(self)
+    }
+
+    fn canonical_digest(&self) -> Result<VersionedDigest> {
+        canonical_digest(Self::DOMAIN, self)
+    }
+
+    fn verify_digest(&self, expected: &VersionedDigest) -> Result<bool> {
+        verify_digest(Self::DOMAIN, self, expected)
+    }
+}
+
+/// 为类型实现 [`CanonicalPayload`]
+///
+/// ```ignore
+/// dubhe_security::canonical_payload!(LockProof, "dubhe.node.lock_proof");
+/// ```
+#[macro_export]
+macro_rules! canonical_payload {
+    ($ty:ty, $domain:expr) => {
+        impl $crate::canonical::CanonicalPayload for $ty {
+            const DOMAIN: &'static str = $domain;
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Sample {
+        b: u64,
+        a: String,
+    }
+
+    crate::canonical_payload!(Sample, "dubhe.test");
+
+    #[test]
+    fn test_golden_vectors() {
+        let file: Value =
+            serde_json::from_str(include_str!("../testdata/canonical_json_v1.json")).unwrap();
+        let vectors = file["vectors"].as_array().unwrap();
+        assert!(!vectors.is_empty());
+
+        for vector in vectors {
+            let name = vector["name"].as_str().unwrap();
+            let domain = vector["domain"].as_str().unwrap();
+            let payload = &vector["payload"];
+
+            let canonical = String::from_utf8(to_canonical_json(payload).unwrap()).unwrap();
+            assert_eq!(canonical, vector["canonical"].as_str().unwrap(), "{}", name);
+
+            let digest = canonical_digest(domain, payload).unwrap();
+            assert_eq!(digest.digest, vector["digest"].as_str().unwrap(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_floats_rejected() {
+        assert!(to_canonical_json(&serde_json::json!({ "ratio": 0.5 })).is_err());
+        assert!(to_canonical_json(&serde_json::json!({ "count": 5 })).is_ok());
+    }
+
+    #[test]
+    fn test_derived_payload() {
+        let sample = Sample {
+            b: 1,
+            a: "x".to_string(),
+        };
+        assert_eq!(sample.canonical_bytes().unwrap(), br#"{"a":"x","b":1}"#.to_vec());
+
+        let digest = sample.canonical_digest().unwrap();
+        assert_eq!(digest.version, CANONICAL_VERSION);
+        assert!(sample.verify_digest(&digest).unwrap());
+        assert_eq!(digest, digest.to_string().parse().unwrap());
+    }
+
+    #[test]
+    fn test_legacy_digest_still_verifies() {
+        let sample = Sample {
+            b: 1,
+            a: "x".to_string(),
+        };
+        let legacy = digest_versioned("dubhe.test", &sample, LEGACY_VERSION).unwrap();
+        assert!(sample.verify_digest(&legacy).unwrap());
+
+        // 无版本前缀的旧摘要解析为 v0
+        let parsed: VersionedDigest = legacy.digest.parse().unwrap();
+        assert_eq!(parsed.version, LEGACY_VERSION);
+        assert!(sample.verify_digest(&parsed).unwrap());
+    }
+}
@@ -1,9 +1,619 @@
 //! 密钥管理模块
+//!
+//! 加密存储的签名密钥库。每个密钥以名称引用，对应目录下的一个 `<name>.key.json` 文件，
+//! 私钥用口令经 PBKDF2-SHA256 派生的密钥做 AES-256-GCM 加密（名称作为附加数据，
+//! 文件被改名后无法解密）。打开密钥库时一次性解密，内存中的私钥、口令与中间缓冲区
+//! 在释放时清零；调用方只能通过 [`Keystore::sign`] / [`KeyHandle`] 使用私钥，拿不到原始字节。
+//!
+//! 轮换后上一把密钥以加密形式保留在同一文件中，只用于验证轮换前产生的签名。
 
-pub struct KeyManagement;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use ed25519_dalek::{Signature, Signer as _, SigningKey, Verifier as _, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tracing::info;
+use zeroize::Zeroizing;
 
-impl KeyManagement {
-    pub fn new() -> Self {
-        Self
+/// 默认保存口令的环境变量
+pub const DEFAULT_PASSPHRASE_ENV: &str = "DUBHE_KEYSTORE_PASSPHRASE";
+
+/// 新密钥使用的 PBKDF2 迭代次数；已有文件按文件中记录的次数解密
+pub const DEFAULT_KDF_ITERATIONS: u32 = 600_000;
+
+const KEY_FILE_SUFFIX: &str = ".key.json";
+const KEY_FILE_VERSION: u32 = 1;
+const ALGORITHM: &str = "ed25519";
+const SALT_LEN: usize = 16;
+
+/// 密钥库配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreConfig {
+    /// 加密密钥文件所在目录
+    #[serde(default = "default_keystore_dir")]
+    pub dir: String,
+    /// 保存口令的环境变量；未设置时从终端读取
+    #[serde(default = "default_passphrase_env")]
+    pub passphrase_env: String,
+}
+
+fn default_keystore_dir() -> String {
+    "./data/keys".to_string()
+}
+
+fn default_passphrase_env() -> String {
+    DEFAULT_PASSPHRASE_ENV.to_string()
+}
+
+impl Default for KeystoreConfig {
+    fn default() -> Self {
+        Self {
+            dir: default_keystore_dir(),
+            passphrase_env: default_passphrase_env(),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum KeystoreError {
+    #[error("wrong passphrase for key {0}")]
+    WrongPassphrase(String),
+    #[error("unknown key {0}")]
+    UnknownKey(String),
+    #[error("key {0} already exists")]
+    KeyExists(String),
+    #[error("invalid key name {0:?}")]
+    InvalidName(String),
+    #[error("key file for {name} is corrupted: {reason}")]
+    Corrupted { name: String, reason: String },
+    #[error("passphrase unavailable: {0}")]
+    Passphrase(String),
+    #[error("keystore I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub type KeystoreResult<T> = std::result::Result<T, KeystoreError>;
+
+/// 密钥库口令，释放时清零
+pub struct Passphrase(Zeroizing<String>);
+
+impl Passphrase {
+    pub fn new(passphrase: impl Into<String>) -> Self {
+        Self(Zeroizing::new(passphrase.into()))
+    }
+
+    pub fn from_env(var: &str) -> KeystoreResult<Self> {
+        std::env::var(var).map(Self::new).map_err(|_| {
+            KeystoreError::Passphrase(format!("environment variable {} is not set", var))
+        })
+    }
+
+    /// 从标准输入读取一行作为口令
+    pub fn prompt(message: &str) -> KeystoreResult<Self> {
+        eprint!("{}", message);
+        std::io::stderr().flush()?;
+        let mut line = Zeroizing::new(String::new());
+        std::io::stdin().read_line(&mut line)?;
+        let passphrase = line.trim_end_matches(['\r', '\n']);
+        if passphrase.is_empty() {
+            return Err(KeystoreError::Passphrase("empty passphrase".to_string()));
+        }
+        Ok(Self::new(passphrase))
+    }
+
+    /// 优先读取环境变量，未设置时提示输入
+    pub fn from_env_or_prompt(var: &str) -> KeystoreResult<Self> {
+        Self::from_env(var).or_else(|_| Self::prompt("Keystore passphrase: "))
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Passphrase(***)")
+    }
+}
+
+/// 单把加密私钥
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EncryptedKey {
+    algorithm: String,
+    public_key: String,
+    kdf_iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+    created_at_ms: u64,
+}
+
+/// 密钥文件内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyFile {
+    version: u32,
+    name: String,
+    current: EncryptedKey,
+    /// 轮换前的密钥，仅用于验证
+    #[serde(default)]
+    previous: Option<EncryptedKey>,
+}
+
+/// 已解密的密钥；`SigningKey` 释放时清零
+struct KeyRing {
+    current: SigningKey,
+    previous: Option<SigningKey>,
+    file: KeyFile,
+}
+
+/// 加密密钥库
+pub struct Keystore {
+    dir: PathBuf,
+    passphrase: Passphrase,
+    kdf_iterations: u32,
+    keys: RwLock<HashMap<String, KeyRing>>,
+}
+
+impl Keystore {
+    /// 打开目录下的全部密钥文件并解密；目录不存在时创建
+    pub fn open<P: AsRef<Path>>(dir: P, passphrase: Passphrase) -> KeystoreResult<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+
+        let mut keys = HashMap::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let Some(name) = path
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .and_then(|file_name| file_name.strip_suffix(KEY_FILE_SUFFIX))
+            else {
+                continue;
+            };
+            let name = name.to_string();
+            let corrupted = |reason: String| KeystoreError::Corrupted {
+                name: name.clone(),
+                reason,
+            };
+
+            let file: KeyFile = serde_json::from_str(&std::fs::read_to_string(&path)?)
+                .map_err(|e| corrupted(e.to_string()))?;
+            if file.version != KEY_FILE_VERSION || file.name != name {
+                return Err(corrupted(format!(
+                    "unexpected version {} or name {}",
+                    file.version, file.name
+                )));
+            }
+            let current = decrypt(&name, &file.current, &passphrase)?;
+            let previous = file
+                .previous
+                .as_ref()
+                .map(|previous| decrypt(&name, previous, &passphrase))
+                .transpose()?;
+            keys.insert(
+                name,
+                KeyRing {
+                    current,
+                    previous,
+                    file,
+                },
+            );
+        }
+
+        info!("🔐 Keystore opened at {:?} with {} keys", dir, keys.len());
+        Ok(Self {
+            dir,
+            passphrase,
+            kdf_iterations: DEFAULT_KDF_ITERATIONS,
+            keys: RwLock::new(keys),
+        })
+    }
+
+    /// 新写入密钥使用的 PBKDF2 迭代次数
+    pub fn with_kdf_iterations(mut self, iterations: u32) -> Self {
+        self.kdf_iterations = iterations.max(1);
+        self
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.keys.read().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.keys.read().unwrap().contains_key(name)
+    }
+
+    /// 生成新密钥，返回公钥
+    pub fn generate(&self, name: &str) -> KeystoreResult<[u8; 32]> {
+        self.insert(name, random_key())
+    }
+
+    /// 导入已有私钥，返回公钥
+    pub fn import(&self, name: &str, secret: Zeroizing<[u8; 32]>) -> KeystoreResult<[u8; 32]> {
+        self.insert(name, SigningKey::from_bytes(&secret))
+    }
+
+    fn insert(&self, name: &str, key: SigningKey) -> KeystoreResult<[u8; 32]> {
+        validate_name(name)?;
+        let mut keys = self.keys.write().unwrap();
+        if keys.contains_key(name) {
+            return Err(KeystoreError::KeyExists(name.to_string()));
+        }
+
+        let file = KeyFile {
+            version: KEY_FILE_VERSION,
+            name: name.to_string(),
+            current: self.encrypt(name, &key)?,
+            previous: None,
+        };
+        self.write_file(&file)?;
+
+        let public_key = key.verifying_key().to_bytes();
+        keys.insert(
+            name.to_string(),
+            KeyRing {
+                current: key,
+                previous: None,
+                file,
+            },
+        );
+        info!("🔑 Key {} added to keystore", name);
+        Ok(public_key)
+    }
+
+    /// 轮换密钥：生成新密钥作为当前密钥，原密钥保留用于验证；返回新公钥
+    pub fn rotate(&self, name: &str) -> KeystoreResult<[u8; 32]> {
+        let mut keys = self.keys.write().unwrap();
+        let ring = keys
+            .get_mut(name)
+            .ok_or_else(|| KeystoreError::UnknownKey(name.to_string()))?;
+
+        let key = random_key();
+        let file = KeyFile {
+            version: KEY_FILE_VERSION,
+            name: name.to_string(),
+            current: self.encrypt(name, &key)?,
+            previous: Some(ring.file.current.clone()),
+        };
+        self.write_file(&file)?;
+
+        let public_key = key.verifying_key().to_bytes();
+        ring.previous = Some(std::mem::replace(&mut ring.current, key));
+        ring.file = file;
+        info!("🔄 Key {} rotated", name);
+        Ok(public_key)
+    }
+
+    /// 用当前密钥签名
+    pub fn sign(&self, name: &str, message: &[u8]) -> KeystoreResult<[u8; 64]> {
+        self.with_ring(name, |ring| ring.current.sign(message).to_bytes())
+    }
+
+    /// 当前公钥
+    pub fn public_key(&self, name: &str) -> KeystoreResult<[u8; 32]> {
+        self.with_ring(name, |ring| ring.current.verifying_key().to_bytes())
+    }
+
+    /// 轮换前的公钥
+    pub fn previous_public_key(&self, name: &str) -> KeystoreResult<Option<[u8; 32]>> {
+        self.with_ring(name, |ring| {
+            ring.previous
+                .as_ref()
+                .map(|previous| previous.verifying_key().to_bytes())
+        })
+    }
+
+    /// 验证签名，当前密钥或轮换前的密钥签发的都视为有效
+    pub fn verify(&self, name: &str, message: &[u8], signature: &[u8]) -> KeystoreResult<bool> {
+        let Ok(signature) = Signature::from_slice(signature) else {
+            return Ok(false);
+        };
+        self.with_ring(name, |ring| {
+            std::iter::once(&ring.current)
+                .chain(ring.previous.as_ref())
+                .any(|key| key.verifying_key().verify(message, &signature).is_ok())
+        })
+    }
+
+    /// 按名称引用密钥，供适配器等组件持有
+    pub fn handle(self: &Arc<Self>, name: &str) -> KeystoreResult<KeyHandle> {
+        if !self.contains(name) {
+            return Err(KeystoreError::UnknownKey(name.to_string()));
+        }
+        Ok(KeyHandle {
+            keystore: self.clone(),
+            name: name.to_string(),
+        })
+    }
+
+    fn with_ring<T>(&self, name: &str, f: impl FnOnce(&KeyRing) -> T) -> KeystoreResult<T> {
+        self.keys
+            .read()
+            .unwrap()
+            .get(name)
+            .map(f)
+            .ok_or_else(|| KeystoreError::UnknownKey(name.to_string()))
+    }
+
+    fn encrypt(&self, name: &str, key: &SigningKey) -> KeystoreResult<EncryptedKey> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let cipher = cipher(&self.passphrase, &salt, self.kdf_iterations);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let secret = Zeroizing::new(key.to_bytes());
+        let ciphertext = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: secret.as_slice(),
+                    aad: name.as_bytes(),
+                },
+            )
+            .map_err(|_| KeystoreError::Corrupted {
+                name: name.to_string(),
+                reason: "encryption failed".to_string(),
+            })?;
+
+        Ok(EncryptedKey {
+            algorithm: ALGORITHM.to_string(),
+            public_key: hex::encode(key.verifying_key().to_bytes()),
+            kdf_iterations: self.kdf_iterations,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+            created_at_ms: now_ms(),
+        })
+    }
+
+    /// 先写临时文件再改名，避免写到一半的密钥文件
+    fn write_file(&self, file: &KeyFile) -> KeystoreResult<()> {
+        let path = self.dir.join(format!("{}{}", file.name, KEY_FILE_SUFFIX));
+        let tmp = path.with_extension("tmp");
+        let content = serde_json::to_vec_pretty(file).map_err(|e| KeystoreError::Corrupted {
+            name: file.name.clone(),
+            reason: e.to_string(),
+        })?;
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut out = options.open(&tmp)?;
+        out.write_all(&content)?;
+        out.sync_all()?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Keystore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keystore")
+            .field("dir", &self.dir)
+            .field("keys", &self.names())
+            .finish()
+    }
+}
+
+/// 密钥库中某把密钥的引用，只能签名与验证
+#[derive(Clone)]
+pub struct KeyHandle {
+    keystore: Arc<Keystore>,
+    name: String,
+}
+
+impl KeyHandle {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn sign(&self, message: &[u8]) -> KeystoreResult<[u8; 64]> {
+        self.keystore.sign(&self.name, message)
+    }
+
+    pub fn public_key(&self) -> KeystoreResult<[u8; 32]> {
+        self.keystore.public_key(&self.name)
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> KeystoreResult<bool> {
+        self.keystore.verify(&self.name, message, signature)
+    }
+}
+
+impl fmt::Debug for KeyHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyHandle")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// 只凭公钥验证 Ed25519 签名，供不持有该密钥的一方（如离线节点）使用
+pub fn verify_signature(public_key: &[u8; 32], message: &[u8], signature: &[u8]) -> bool {
+    let Ok(key) = VerifyingKey::from_bytes(public_key) else {
+        return false;
+    };
+    let Ok(signature) = Signature::from_slice(signature) else {
+        return false;
+    };
+    key.verify(message, &signature).is_ok()
+}
+
+fn validate_name(name: &str) -> KeystoreResult<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(KeystoreError::InvalidName(name.to_string()))
+    }
+}
+
+fn random_key() -> SigningKey {
+    let mut secret = Zeroizing::new([0u8; 32]);
+    OsRng.fill_bytes(secret.as_mut_slice());
+    SigningKey::from_bytes(&secret)
+}
+
+fn cipher(passphrase: &Passphrase, salt: &[u8], iterations: u32) -> Aes256Gcm {
+    let mut key = Zeroizing::new([0u8; 32]);
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, key.as_mut_slice());
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_slice()))
+}
+
+fn decrypt(
+    name: &str,
+    encrypted: &EncryptedKey,
+    passphrase: &Passphrase,
+) -> KeystoreResult<SigningKey> {
+    let corrupted = |reason: &str| KeystoreError::Corrupted {
+        name: name.to_string(),
+        reason: reason.to_string(),
+    };
+    if encrypted.algorithm != ALGORITHM {
+        return Err(corrupted("unsupported algorithm"));
+    }
+    let salt = hex::decode(&encrypted.salt).map_err(|_| corrupted("invalid salt"))?;
+    let nonce = hex::decode(&encrypted.nonce).map_err(|_| corrupted("invalid nonce"))?;
+    if nonce.len() != 12 {
+        return Err(corrupted("invalid nonce"));
+    }
+    let ciphertext =
+        hex::decode(&encrypted.ciphertext).map_err(|_| corrupted("invalid ciphertext"))?;
+
+    // 认证失败即口令错误（或文件被篡改），两者无法区分
+    let secret = Zeroizing::new(
+        cipher(passphrase, &salt, encrypted.kdf_iterations)
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: name.as_bytes(),
+                },
+            )
+            .map_err(|_| KeystoreError::WrongPassphrase(name.to_string()))?,
+    );
+    let secret: Zeroizing<[u8; 32]> = Zeroizing::new(
+        secret
+            .as_slice()
+            .try_into()
+            .map_err(|_| corrupted("invalid secret length"))?,
+    );
+
+    let key = SigningKey::from_bytes(&secret);
+    if hex::encode(key.verifying_key().to_bytes()) != encrypted.public_key {
+        return Err(corrupted("public key mismatch"));
+    }
+    Ok(key)
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 8032 测试向量 1
+    const SECRET: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+    const PUBLIC_KEY: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+    const EMPTY_MESSAGE_SIGNATURE: &str = "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b";
+
+    fn open(dir: &Path, passphrase: &str) -> KeystoreResult<Keystore> {
+        Keystore::open(dir, Passphrase::new(passphrase)).map(|ks| ks.with_kdf_iterations(1_000))
+    }
+
+    fn secret() -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(hex::decode(SECRET).unwrap().try_into().unwrap())
+    }
+
+    #[test]
+    fn test_known_vector_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = open(dir.path(), "correct horse").unwrap();
+        let public_key = keystore.import("sui-signer", secret()).unwrap();
+        assert_eq!(hex::encode(public_key), PUBLIC_KEY);
+        drop(keystore);
+
+        // 文件中不含明文私钥
+        let content = std::fs::read_to_string(dir.path().join("sui-signer.key.json")).unwrap();
+        assert!(!content.contains(SECRET));
+
+        let keystore = Arc::new(open(dir.path(), "correct horse").unwrap());
+        let handle = keystore.handle("sui-signer").unwrap();
+        let signature = handle.sign(b"").unwrap();
+        assert_eq!(hex::encode(signature), EMPTY_MESSAGE_SIGNATURE);
+        assert!(handle.verify(b"", &signature).unwrap());
+        assert!(keystore.handle("missing").is_err());
+    }
+
+    #[test]
+    fn test_wrong_passphrase_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        open(dir.path(), "correct horse")
+            .unwrap()
+            .import("sui-signer", secret())
+            .unwrap();
+
+        let err = open(dir.path(), "battery staple").unwrap_err();
+        assert!(matches!(err, KeystoreError::WrongPassphrase(name) if name == "sui-signer"));
+    }
+
+    #[test]
+    fn test_rotation_keeps_previous_key_for_verification() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = open(dir.path(), "correct horse").unwrap();
+        keystore.import("sui-signer", secret()).unwrap();
+        let old_signature = keystore.sign("sui-signer", b"dubhe").unwrap();
+
+        let new_public_key = keystore.rotate("sui-signer").unwrap();
+        assert_ne!(hex::encode(new_public_key), PUBLIC_KEY);
+        assert_eq!(
+            keystore
+                .previous_public_key("sui-signer")
+                .unwrap()
+                .map(hex::encode),
+            Some(PUBLIC_KEY.to_string())
+        );
+
+        // 轮换后重新打开：新签名用新密钥，旧签名仍可验证
+        drop(keystore);
+        let keystore = open(dir.path(), "correct horse").unwrap();
+        assert_eq!(keystore.public_key("sui-signer").unwrap(), new_public_key);
+        let new_signature = keystore.sign("sui-signer", b"dubhe").unwrap();
+        assert_ne!(new_signature, old_signature);
+        assert!(keystore
+            .verify("sui-signer", b"dubhe", &old_signature)
+            .unwrap());
+        assert!(keystore
+            .verify("sui-signer", b"dubhe", &new_signature)
+            .unwrap());
+        assert!(!keystore
+            .verify("sui-signer", b"other", &old_signature)
+            .unwrap());
+
+        // 只持有公钥的一方
+        assert!(verify_signature(&new_public_key, b"dubhe", &new_signature));
+        assert!(!verify_signature(&new_public_key, b"dubhe", &old_signature));
     }
 }
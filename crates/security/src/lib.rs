@@ -1,22 +1,74 @@
 //! Dubhe Channel Security
 //!
-//! TEE、SGX、访问控制、审计追踪
+//! TEE、SGX、访问控制、审计追踪、密钥管理
 
 pub mod access_control;
 pub mod audit_trail;
+pub mod canonical;
 pub mod key_management;
 pub mod tee_integration;
 pub mod threat_detection;
 
+pub use audit_trail::{AuditEntry, AuditEvent, AuditTrail, IntegrityError, GENESIS_HASH};
+pub use canonical::{
+    canonical_digest, digest_versioned, to_canonical_json, verify_digest, CanonicalPayload,
+    VersionedDigest, CANONICAL_VERSION, LEGACY_VERSION,
+};
+pub use key_management::{
+    verify_signature, KeyHandle, Keystore, KeystoreConfig, KeystoreError, KeystoreResult,
+    Passphrase, DEFAULT_PASSPHRASE_ENV,
+};
+pub use tee_integration::{
+    default_provider as default_attestation_provider, AttestationProvider, AttestationReport,
+    AttestationStatus, AttestationVerdict, SimulatedProvider, MAX_USER_DATA_LEN,
+    SIMULATED_PROVIDER,
+};
+pub use threat_detection::{
+    Threat, ThreatDetectionConfig, ThreatDetector, ThreatRule, ThreatSink, Throttle,
+    TransactionObservation,
+};
+
 use anyhow::Result;
+use std::sync::Arc;
+
+pub use access_control::{
+    AccessControl, AccessControlConfig, AccessDecision, AccessDenied, AuditHook, Capability,
+    MemoryAuditLog, Permission, Principal, PrincipalConfig, Role,
+};
 
 /// 安全管理器
 pub struct SecurityManager {
-    // TODO: 实现安全管理功能
+    access_control: Arc<AccessControl>,
+    audit_trail: Option<Arc<AuditTrail>>,
 }
 
 impl SecurityManager {
-    pub fn new() -> Result<Self> {
-        Ok(Self {})
+    /// 由节点的 `security` 配置段构造；未启用访问控制时所有特权检查放行，
+    /// 配置了审计日志时每个访问控制决定都会写入
+    pub fn new(
+        enable_access_control: bool,
+        access_control: &AccessControlConfig,
+        audit_trail: Option<Arc<AuditTrail>>,
+    ) -> Result<Self> {
+        let mut access = if enable_access_control {
+            AccessControl::new(access_control)
+        } else {
+            AccessControl::disabled()
+        };
+        if let Some(audit) = &audit_trail {
+            access = access.with_audit_hook(audit.clone());
+        }
+        Ok(Self {
+            access_control: Arc::new(access),
+            audit_trail,
+        })
+    }
+
+    pub fn access_control(&self) -> Arc<AccessControl> {
+        self.access_control.clone()
+    }
+
+    pub fn audit_trail(&self) -> Option<Arc<AuditTrail>> {
+        self.audit_trail.clone()
     }
 }
@@ -1,9 +1,471 @@
 //! 威胁检测模块
+//!
+//! 对已执行的交易流按规则做异常检测：单个发送方每分钟交易数过多、合约单次调用的 gas
+//! 突然超过其滚动基线的若干倍、同一来源在一分钟内反复执行失败。规则命中后写入审计日志、
+//! 通知告警接收方，并可（`throttle = true` 时）在冷却期内限流该来源，冷却期结束后由
+//! [`ThreatDetector::tick`] 解除。
+//!
+//! 本模块只维护规则状态；交易流的订阅以及告警、限流的具体实现由节点接入。
 
-pub struct ThreatDetection;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+use tracing::warn;
 
-impl ThreatDetection {
-    pub fn new() -> Self {
-        Self
+use crate::audit_trail::{AuditEvent, AuditTrail};
+
+/// 频率类规则的统计窗口
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// 威胁检测配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThreatDetectionConfig {
+    pub enabled: bool,
+    /// 单个发送方每分钟允许的交易数
+    pub max_transactions_per_minute: u64,
+    /// 单次调用 gas 超过基线的倍数即视为突增
+    pub gas_spike_factor: f64,
+    /// 每个合约保留的最近调用数，其平均 gas 作为基线
+    pub gas_baseline_window: usize,
+    /// 基线至少包含多少次调用才开始检测突增
+    pub gas_baseline_min_samples: usize,
+    /// 同一来源每分钟允许的失败执行数
+    pub max_failures_per_minute: u64,
+    /// 命中规则后是否限流该来源
+    pub throttle: bool,
+    /// 同一规则对同一对象的告警间隔，也是限流时长（秒）
+    pub cooldown_secs: u64,
+}
+
+impl Default for ThreatDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_transactions_per_minute: 600,
+            gas_spike_factor: 10.0,
+            gas_baseline_window: 100,
+            gas_baseline_min_samples: 10,
+            max_failures_per_minute: 20,
+            throttle: false,
+            cooldown_secs: 300,
+        }
+    }
+}
+
+impl ThreatDetectionConfig {
+    pub fn cooldown(&self) -> Duration {
+        Duration::from_secs(self.cooldown_secs)
+    }
+}
+
+/// 检测规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreatRule {
+    /// 单个发送方交易频率过高
+    SenderRate,
+    /// 合约单次调用 gas 突增
+    GasSpike,
+    /// 同一来源反复执行失败
+    RepeatedFailures,
+}
+
+impl ThreatRule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SenderRate => "sender_rate",
+            Self::GasSpike => "gas_spike",
+            Self::RepeatedFailures => "repeated_failures",
+        }
+    }
+}
+
+/// 一笔已执行交易
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionObservation {
+    /// 发送方地址或 API Key 名称
+    pub source: String,
+    /// 被调用的合约
+    pub contract: Option<String>,
+    pub gas_used: u64,
+    pub success: bool,
+}
+
+/// 一次规则命中
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Threat {
+    pub rule: ThreatRule,
+    /// 命中规则的对象：频率类规则为来源，gas 突增为合约
+    pub subject: String,
+    /// 触发交易的来源，限流作用于它
+    pub source: String,
+    pub observed: u64,
+    pub threshold: u64,
+    pub throttled: bool,
+}
+
+/// 接收检测结果（例如转发给告警管理器）
+pub trait ThreatSink: Send + Sync {
+    fn on_threat(&self, threat: &Threat);
+}
+
+/// 限流钩子（例如 API 限流器）
+pub trait Throttle: Send + Sync {
+    fn throttle(&self, source: &str, cooldown: Duration);
+    fn release(&self, source: &str);
+}
+
+#[derive(Default)]
+struct DetectorState {
+    sends: HashMap<String, VecDeque<Instant>>,
+    failures: HashMap<String, VecDeque<Instant>>,
+    gas: HashMap<String, VecDeque<u64>>,
+    // (规则, 对象) → 冷却结束时间，期间不重复告警
+    cooling: HashMap<(ThreatRule, String), Instant>,
+    // 来源 → 限流结束时间
+    throttled: HashMap<String, Instant>,
+}
+
+/// 威胁检测器
+pub struct ThreatDetector {
+    config: ThreatDetectionConfig,
+    state: Mutex<DetectorState>,
+    audit: Option<Arc<AuditTrail>>,
+    sinks: Vec<Arc<dyn ThreatSink>>,
+    throttles: Vec<Arc<dyn Throttle>>,
+}
+
+impl ThreatDetector {
+    pub fn new(config: ThreatDetectionConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(DetectorState::default()),
+            audit: None,
+            sinks: Vec::new(),
+            throttles: Vec::new(),
+        }
+    }
+
+    pub fn with_audit_trail(mut self, audit: Arc<AuditTrail>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    pub fn with_sink(mut self, sink: Arc<dyn ThreatSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    pub fn with_throttle(mut self, throttle: Arc<dyn Throttle>) -> Self {
+        self.throttles.push(throttle);
+        self
+    }
+
+    pub fn config(&self) -> &ThreatDetectionConfig {
+        &self.config
+    }
+
+    /// 评估一笔交易，返回本次新命中的规则
+    pub fn observe(&self, observation: &TransactionObservation, now: Instant) -> Vec<Threat> {
+        let config = &self.config;
+        let mut hits = Vec::new();
+        let mut state = self.state.lock().unwrap();
+
+        let sends = state.sends.entry(observation.source.clone()).or_default();
+        let count = record_in_window(sends, now);
+        if count > config.max_transactions_per_minute {
+            hits.push((
+                ThreatRule::SenderRate,
+                observation.source.clone(),
+                count,
+                config.max_transactions_per_minute,
+            ));
+        }
+
+        if !observation.success {
+            let failures = state
+                .failures
+                .entry(observation.source.clone())
+                .or_default();
+            let count = record_in_window(failures, now);
+            if count > config.max_failures_per_minute {
+                hits.push((
+                    ThreatRule::RepeatedFailures,
+                    observation.source.clone(),
+                    count,
+                    config.max_failures_per_minute,
+                ));
+            }
+        }
+
+        if let Some(contract) = &observation.contract {
+            let samples = state.gas.entry(contract.clone()).or_default();
+            if samples.len() >= config.gas_baseline_min_samples.max(1) {
+                let baseline = samples.iter().sum::<u64>() as f64 / samples.len() as f64;
+                let threshold = (baseline * config.gas_spike_factor).ceil() as u64;
+                if observation.gas_used > threshold {
+                    hits.push((
+                        ThreatRule::GasSpike,
+                        contract.clone(),
+                        observation.gas_used,
+                        threshold,
+                    ));
+                }
+            }
+            samples.push_back(observation.gas_used);
+            while samples.len() > config.gas_baseline_window.max(1) {
+                samples.pop_front();
+            }
+        }
+
+        let cooldown = config.cooldown();
+        let mut threats = Vec::new();
+        for (rule, subject, observed, threshold) in hits {
+            let key = (rule, subject.clone());
+            if state.cooling.get(&key).is_some_and(|until| *until > now) {
+                continue;
+            }
+            state.cooling.insert(key, now + cooldown);
+            if config.throttle {
+                state
+                    .throttled
+                    .insert(observation.source.clone(), now + cooldown);
+            }
+            threats.push(Threat {
+                rule,
+                subject,
+                source: observation.source.clone(),
+                observed,
+                threshold,
+                throttled: config.throttle,
+            });
+        }
+        drop(state);
+
+        for threat in &threats {
+            self.report(threat);
+        }
+        threats
+    }
+
+    /// 解除冷却期已过的限流，返回被解除的来源
+    pub fn tick(&self, now: Instant) -> Vec<String> {
+        let released: Vec<String> = {
+            let mut guard = self.state.lock().unwrap();
+            let state = &mut *guard;
+            state.cooling.retain(|_, until| *until > now);
+            for window in [&mut state.sends, &mut state.failures] {
+                window.retain(|_, times| {
+                    times
+                        .back()
+                        .is_some_and(|last| now.saturating_duration_since(*last) < RATE_WINDOW)
+                });
+            }
+
+            let expired: Vec<String> = state
+                .throttled
+                .iter()
+                .filter(|(_, until)| **until <= now)
+                .map(|(source, _)| source.clone())
+                .collect();
+            for source in &expired {
+                state.throttled.remove(source);
+            }
+            expired
+        };
+
+        for source in &released {
+            warn!("✅ Throttle on {} released after cooldown", source);
+            for throttle in &self.throttles {
+                throttle.release(source);
+            }
+        }
+        released
+    }
+
+    /// 来源当前是否被限流
+    pub fn is_throttled(&self, source: &str, now: Instant) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .throttled
+            .get(source)
+            .is_some_and(|until| *until > now)
+    }
+
+    /// 周期性解除到期的限流
+    pub fn spawn_release_timer(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.tick(Instant::now());
+            }
+        })
+    }
+
+    fn report(&self, threat: &Threat) {
+        warn!(
+            "🚨 Threat detected: {} on {} (observed {}, threshold {}, source {})",
+            threat.rule.name(),
+            threat.subject,
+            threat.observed,
+            threat.threshold,
+            threat.source
+        );
+
+        let cooldown = self.config.cooldown();
+        if let Some(audit) = &self.audit {
+            audit.record_event(AuditEvent::ThreatDetected {
+                rule: threat.rule.name().to_string(),
+                subject: threat.subject.clone(),
+                observed: threat.observed,
+                threshold: threat.threshold,
+                throttled_until_ms: threat
+                    .throttled
+                    .then(|| unix_now_ms() + cooldown.as_millis() as u64),
+            });
+        }
+        for sink in &self.sinks {
+            sink.on_threat(threat);
+        }
+        if threat.throttled {
+            for throttle in &self.throttles {
+                throttle.throttle(&threat.source, cooldown);
+            }
+        }
+    }
+}
+
+/// 记录一次发生并丢弃窗口外的记录，返回窗口内的次数
+fn record_in_window(times: &mut VecDeque<Instant>, now: Instant) -> u64 {
+    times.push_back(now);
+    while times
+        .front()
+        .is_some_and(|first| now.saturating_duration_since(*first) >= RATE_WINDOW)
+    {
+        times.pop_front();
+    }
+    times.len() as u64
+}
+
+fn unix_now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingThrottle {
+        active: Mutex<Vec<String>>,
+    }
+
+    impl Throttle for RecordingThrottle {
+        fn throttle(&self, source: &str, _cooldown: Duration) {
+            self.active.lock().unwrap().push(source.to_string());
+        }
+
+        fn release(&self, source: &str) {
+            self.active.lock().unwrap().retain(|s| s != source);
+        }
+    }
+
+    fn tx(source: &str, contract: &str, gas_used: u64, success: bool) -> TransactionObservation {
+        TransactionObservation {
+            source: source.to_string(),
+            contract: Some(contract.to_string()),
+            gas_used,
+            success,
+        }
+    }
+
+    fn config() -> ThreatDetectionConfig {
+        ThreatDetectionConfig {
+            enabled: true,
+            max_transactions_per_minute: 10,
+            max_failures_per_minute: 3,
+            gas_baseline_min_samples: 5,
+            throttle: true,
+            cooldown_secs: 30,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_burst_engages_and_releases_throttle() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit = Arc::new(AuditTrail::open(dir.path().join("audit.log")).unwrap());
+        let throttle = Arc::new(RecordingThrottle::default());
+        let detector = ThreatDetector::new(config())
+            .with_audit_trail(audit.clone())
+            .with_throttle(throttle.clone());
+        let start = Instant::now();
+
+        // 一秒内 15 笔：第 11 笔命中，之后冷却期内不重复上报
+        let mut threats = Vec::new();
+        for i in 0..15 {
+            let now = start + Duration::from_millis(i * 50);
+            threats.extend(detector.observe(&tx("0xspam", "0xcounter", 100, true), now));
+        }
+        assert_eq!(threats.len(), 1);
+        assert_eq!(threats[0].rule, ThreatRule::SenderRate);
+        assert_eq!(threats[0].observed, 11);
+        assert!(threats[0].throttled);
+        assert!(detector.is_throttled("0xspam", start + Duration::from_secs(1)));
+        assert_eq!(*throttle.active.lock().unwrap(), vec!["0xspam".to_string()]);
+
+        // 其他发送方不受影响
+        assert!(detector
+            .observe(&tx("0xalice", "0xcounter", 100, true), start)
+            .is_empty());
+
+        let entries = audit.query(0, u64::MAX, Some("threat_detected")).unwrap();
+        assert_eq!(entries.len(), 1);
+
+        // 冷却期内不解除，到期后解除
+        assert!(detector.tick(start + Duration::from_secs(10)).is_empty());
+        let released = detector.tick(start + Duration::from_secs(31));
+        assert_eq!(released, vec!["0xspam".to_string()]);
+        assert!(!detector.is_throttled("0xspam", start + Duration::from_secs(31)));
+        assert!(throttle.active.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_gas_spike_and_repeated_failures() {
+        let detector = ThreatDetector::new(ThreatDetectionConfig {
+            throttle: false,
+            ..config()
+        });
+        let now = Instant::now();
+
+        // 基线不足时不检测
+        assert!(detector
+            .observe(&tx("0xalice", "0xpool", 50_000, true), now)
+            .is_empty());
+        for _ in 0..5 {
+            detector.observe(&tx("0xalice", "0xpool", 1_000, true), now);
+        }
+        let threats = detector.observe(&tx("0xbob", "0xpool", 200_000, true), now);
+        assert_eq!(threats.len(), 1);
+        assert_eq!(threats[0].rule, ThreatRule::GasSpike);
+        assert_eq!(threats[0].subject, "0xpool");
+        assert_eq!(threats[0].source, "0xbob");
+        assert!(!threats[0].throttled);
+        assert!(!detector.is_throttled("0xbob", now));
+
+        let failures: Vec<Threat> = (0..4)
+            .flat_map(|_| detector.observe(&tx("0xcarol", "0xother", 10, false), now))
+            .collect();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].rule, ThreatRule::RepeatedFailures);
+        assert_eq!(failures[0].observed, 4);
     }
 }
@@ -0,0 +1,106 @@
+//! 区块日志布隆过滤器
+//!
+//! 与以太坊 `logsBloom` 相同的 2048 位过滤器：每个输入取 keccak256 的前三对字节，
+//! 各取低 11 位置位。以 `0x` 开头的十六进制地址与 topic 按解码后的字节计算，
+//! 与以太坊区块头中的值一致；其他链的地址与事件类型按 UTF-8 字节计算
+
+use sha3::{Digest, Keccak256};
+
+/// 布隆过滤器字节数
+pub const BLOOM_BYTES: usize = 256;
+
+/// 一个区块内所有日志地址与 topic 的布隆过滤器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogsBloom([u8; BLOOM_BYTES]);
+
+impl Default for LogsBloom {
+    fn default() -> Self {
+        Self([0; BLOOM_BYTES])
+    }
+}
+
+impl LogsBloom {
+    /// 从存储的字节恢复，长度不符时返回 `None`
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok().map(Self)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; BLOOM_BYTES] {
+        &self.0
+    }
+
+    /// 加入一个地址或 topic
+    pub fn accrue(&mut self, value: &str) {
+        for (index, bit) in bloom_bits(value) {
+            self.0[index] |= bit;
+        }
+    }
+
+    /// 合并另一个过滤器
+    pub fn accrue_bloom(&mut self, other: &LogsBloom) {
+        for (byte, other) in self.0.iter_mut().zip(other.0.iter()) {
+            *byte |= other;
+        }
+    }
+
+    /// 可能包含 `value`；返回 `false` 时一定不包含
+    pub fn may_contain(&self, value: &str) -> bool {
+        bloom_bits(value)
+            .into_iter()
+            .all(|(index, bit)| self.0[index] & bit != 0)
+    }
+
+    /// 可能包含 `values` 中任意一个；空列表视为通配
+    pub fn may_contain_any(&self, values: &[String]) -> bool {
+        values.is_empty() || values.iter().any(|value| self.may_contain(value))
+    }
+
+    pub fn to_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.0))
+    }
+}
+
+/// 十六进制输入按字节计算，其余按 UTF-8
+fn bloom_input(value: &str) -> Vec<u8> {
+    value
+        .strip_prefix("0x")
+        .filter(|digits| digits.len() % 2 == 0)
+        .and_then(|digits| hex::decode(digits).ok())
+        .unwrap_or_else(|| value.as_bytes().to_vec())
+}
+
+/// 三个（字节下标，位掩码）
+fn bloom_bits(value: &str) -> [(usize, u8); 3] {
+    let hash = Keccak256::digest(bloom_input(value));
+    [0, 2, 4].map(|i| {
+        let bit = ((hash[i] as usize) << 8 | hash[i + 1] as usize) & 2047;
+        (BLOOM_BYTES - 1 - bit / 8, 1 << (bit % 8))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bloom_membership() {
+        let mut bloom = LogsBloom::default();
+        assert!(!bloom.may_contain("0xpkg"));
+
+        bloom.accrue("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48");
+        bloom.accrue("Transfer");
+        assert!(bloom.may_contain("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"));
+        // 十六进制按解码后的字节计算，大小写不影响
+        assert!(bloom.may_contain("0xA0B86991C6218B36C1D19D4A2E9EB0CE3606EB48"));
+        assert!(bloom.may_contain("Transfer"));
+        assert!(!bloom.may_contain("Approval"));
+        assert!(bloom.may_contain_any(&[]));
+        assert!(bloom.may_contain_any(&["Approval".to_string(), "Transfer".to_string()]));
+
+        let mut merged = LogsBloom::default();
+        merged.accrue_bloom(&bloom);
+        assert_eq!(LogsBloom::from_slice(merged.as_bytes()), Some(bloom));
+        assert_eq!(bloom.to_hex().len(), 2 + 2 * BLOOM_BYTES);
+        assert!(LogsBloom::from_slice(&[0; 8]).is_none());
+    }
+}
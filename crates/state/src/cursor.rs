@@ -0,0 +1,73 @@
+//! 订阅游标存储
+//!
+//! 适配器每投递一个检查点就写入游标，与对象存储共用元数据列族，
+//! 节点重启后订阅从游标之后继续
+
+use anyhow::Result;
+use std::sync::Arc;
+
+use dubhe_adapter::CursorStore;
+
+use crate::storage::Storage;
+
+/// 游标在元数据列族中的键前缀
+const CURSOR_PREFIX: &str = "chain_cursor:";
+
+/// RocksDB 持久化的订阅游标
+#[derive(Clone)]
+pub struct RocksCursorStore {
+    storage: Arc<Storage>,
+}
+
+impl RocksCursorStore {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+}
+
+impl CursorStore for RocksCursorStore {
+    fn load(&self, key: &str) -> Result<Option<u64>> {
+        self.storage
+            .get_metadata(&cursor_key(key))?
+            .map(|bytes| {
+                let bytes: [u8; 8] = bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("Corrupted cursor {}", key))?;
+                Ok(u64::from_be_bytes(bytes))
+            })
+            .transpose()
+    }
+
+    fn save(&self, key: &str, checkpoint: u64) -> Result<()> {
+        self.storage.put_metadata(&cursor_key(key), &checkpoint.to_be_bytes())
+    }
+}
+
+fn cursor_key(key: &str) -> String {
+    format!("{}{}", CURSOR_PREFIX, key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_cursor_survives_reopen() -> Result<()> {
+        let dir = tempdir()?;
+        {
+            let cursors = RocksCursorStore::new(Arc::new(Storage::open(dir.path())?));
+            assert_eq!(cursors.load("sui:Testnet:checkpoints")?, None);
+            cursors.save("sui:Testnet:checkpoints", 41)?;
+            cursors.save("sui:Testnet:checkpoints", 42)?;
+            cursors.save("sui:Testnet:transactions", 40)?;
+        }
+
+        let cursors = RocksCursorStore::new(Arc::new(Storage::open(dir.path())?));
+        assert_eq!(cursors.load("sui:Testnet:checkpoints")?, Some(42));
+        assert_eq!(cursors.load("sui:Testnet:transactions")?, Some(40));
+        assert_eq!(cursors.load("sui:Mainnet:checkpoints")?, None);
+        Ok(())
+    }
+}
@@ -1,9 +1,1208 @@
 //! 索引模块
+//!
+//! 消费适配器产生的交易回执，在 RocksDB 中维护二级索引：
+//! - 事件：(package, event_type, block_number, 交易序号, 日志序号)
+//! - 对象：(owner, object_id)，另存 object_id → owner 以便所有权转移时删除旧索引
+//! - 交易：(sender, block_number, 交易序号)
+//! - 日志：(block_number, 交易序号, 日志序号)，另存每个区块所有日志的布隆过滤器，
+//!   `eth_getLogs` 按区块范围查询时先用布隆过滤器跳过不可能匹配的区块
+//!
+//! 索引键完全由回执内容决定，崩溃后从任意区块重新索引只会覆盖相同的键；
+//! 布隆过滤器按位合并，重复索引同样不变
 
-pub struct Indexer;
+use anyhow::{anyhow, Result};
+use rocksdb::checkpoint::Checkpoint;
+use rocksdb::{ColumnFamily, Direction, IteratorMode, Options, WriteBatch, DB};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::bloom::LogsBloom;
+use dubhe_adapter::{AdapterManager, ChainEventKind, ChainType, TransactionReceipt};
+
+const CF_EVENTS: &str = "events";
+const CF_OWNED_OBJECTS: &str = "owned_objects";
+const CF_OBJECT_OWNERS: &str = "object_owners";
+const CF_SENDER_TXS: &str = "sender_txs";
+const CF_BLOCK_LOGS: &str = "block_logs";
+const CF_BLOOMS: &str = "blooms";
+const CF_META: &str = "meta";
+
+/// 已索引的最高区块
+const INDEXED_BLOCK_KEY: &[u8] = b"indexed_block";
+
+/// 单页最大条目数
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// 日志查询的范围与结果数限制
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum LogQueryError {
+    #[error("Invalid block range: fromBlock {from} is after toBlock {to}")]
+    InvalidRange { from: u64, to: u64 },
+
+    #[error("Block range {from}..={to} spans more than {max} blocks, narrow the range")]
+    BlockRangeTooLarge { from: u64, to: u64, max: u64 },
+
+    #[error("Query returned more than {max} logs, narrow the block range or add filters")]
+    TooManyResults { max: usize },
+}
+
+/// 区块回填的统计
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackfillReport {
+    /// 已完成的区块数
+    pub blocks: u64,
+    /// 已索引的回执数
+    pub receipts: usize,
+    /// 链上取不到回执的交易，其余回执照常索引
+    pub missing: Vec<String>,
+}
+
+/// 回填在某个区块中断：该块之前的区块已全部写入索引，可从 `block` 重新开始
+#[derive(Debug, Error)]
+#[error("Backfill stopped at block {block}: {source}")]
+pub struct BackfillError {
+    pub block: u64,
+    /// 中断前完成的部分
+    pub report: BackfillReport,
+    #[source]
+    pub source: anyhow::Error,
+}
+
+/// 已索引的事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedEvent {
+    pub package: String,
+    pub event_type: String,
+    pub block_number: u64,
+    /// 升级前索引的事件没有区块哈希
+    #[serde(default)]
+    pub block_hash: String,
+    pub tx_hash: String,
+    pub transaction_index: u32,
+    pub log_index: u32,
+    pub topics: Vec<String>,
+    pub data: String,
+}
+
+/// 已索引的对象所有权
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnedObject {
+    pub object_id: String,
+    pub owner: String,
+    /// 所有权生效的区块
+    pub block_number: u64,
+}
+
+/// 已索引的交易
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedTransaction {
+    pub tx_hash: String,
+    pub sender: String,
+    pub block_number: u64,
+    pub transaction_index: u32,
+}
+
+/// 事件查询条件，区块范围为闭区间
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventQuery {
+    pub package: String,
+    #[serde(default)]
+    pub event_type: Option<String>,
+    #[serde(default)]
+    pub from_block: Option<u64>,
+    #[serde(default)]
+    pub to_block: Option<u64>,
+}
+
+/// 日志过滤条件（`eth_getLogs`），区块范围为闭区间
+///
+/// 地址列表与每个 topic 位置的候选列表内为“或”，各条件之间为“与”；
+/// 空列表与 `None` 表示通配，但日志须至少有同样多的 topic。以 `0x` 开头的十六进制值按小写比较
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogFilter {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub addresses: Vec<String>,
+    pub topics: Vec<Option<Vec<String>>>,
+}
+
+impl LogFilter {
+    fn matches(&self, event: &IndexedEvent) -> bool {
+        (self.from_block..=self.to_block).contains(&event.block_number)
+            && (self.addresses.is_empty() || self.addresses.contains(&event.package))
+            // 与 geth 一致：topic 数少于过滤条件位置数的日志不匹配，即使多出的位置是通配
+            && event.topics.len() >= self.topics.len()
+            && self.topics.iter().enumerate().all(|(position, expected)| {
+                match expected.as_deref() {
+                    None | Some([]) => true,
+                    Some(expected) => event
+                        .topics
+                        .get(position)
+                        .is_some_and(|topic| expected.contains(topic)),
+                }
+            })
+    }
+
+    fn may_match(&self, bloom: &LogsBloom) -> bool {
+        bloom.may_contain_any(&self.addresses)
+            && self
+                .topics
+                .iter()
+                .flatten()
+                .all(|expected| bloom.may_contain_any(expected))
+    }
+
+    /// 十六进制地址与 topic 统一为小写，与适配器写入的格式一致
+    fn normalized(&self) -> Self {
+        let normalize = |values: &Vec<String>| -> Vec<String> {
+            values.iter().map(|v| normalize_hex(v)).collect()
+        };
+        Self {
+            from_block: self.from_block,
+            to_block: self.to_block,
+            addresses: normalize(&self.addresses),
+            topics: self
+                .topics
+                .iter()
+                .map(|expected| expected.as_ref().map(normalize))
+                .collect(),
+        }
+    }
+}
+
+/// 分页结果；`next_cursor` 为空表示没有更多数据
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+/// 二级索引
+pub struct Indexer {
+    db: DB,
+    // 串行化所有权更新中的“读旧 owner + 写入”
+    write_lock: Mutex<()>,
+}
 
 impl Indexer {
-    pub fn new() -> Self {
-        Self
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        let db = DB::open_cf(
+            &opts,
+            path,
+            [
+                CF_EVENTS,
+                CF_OWNED_OBJECTS,
+                CF_OBJECT_OWNERS,
+                CF_SENDER_TXS,
+                CF_BLOCK_LOGS,
+                CF_BLOOMS,
+                CF_META,
+            ],
+        )?;
+        Ok(Self {
+            db,
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    /// 索引一笔交易回执：事件、区块日志与布隆过滤器、发送方，以及合约创建交易产生的对象
+    pub fn index_receipt(&self, receipt: &TransactionReceipt) -> Result<()> {
+        let events = self.cf(CF_EVENTS)?;
+        let block_logs = self.cf(CF_BLOCK_LOGS)?;
+        let sender_txs = self.cf(CF_SENDER_TXS)?;
+
+        let _guard = self.write_lock.lock().unwrap();
+        let mut batch = WriteBatch::default();
+        let mut bloom = LogsBloom::default();
+
+        for (log_index, log) in receipt.logs.iter().enumerate() {
+            let event = IndexedEvent {
+                package: log.address.clone(),
+                event_type: log.topics.first().cloned().unwrap_or_default(),
+                block_number: receipt.block_number,
+                block_hash: receipt.block_hash.clone(),
+                tx_hash: receipt.tx_hash.clone(),
+                transaction_index: receipt.transaction_index,
+                log_index: log_index as u32,
+                topics: log.topics.clone(),
+                data: log.data.clone(),
+            };
+            let key = KeyBuilder::new()
+                .segment(&event.package)
+                .segment(&event.event_type)
+                .u64(event.block_number)
+                .u32(event.transaction_index)
+                .u32(event.log_index)
+                .finish();
+            let value = serde_json::to_vec(&event)?;
+            batch.put_cf(events, key, &value);
+            batch.put_cf(
+                block_logs,
+                block_log_key(event.block_number, event.transaction_index, event.log_index),
+                &value,
+            );
+
+            bloom.accrue(&event.package);
+            for topic in &event.topics {
+                bloom.accrue(topic);
+            }
+        }
+        if !receipt.logs.is_empty() {
+            self.stage_bloom(&mut batch, receipt.block_number, &bloom)?;
+        }
+
+        let transaction = IndexedTransaction {
+            tx_hash: receipt.tx_hash.clone(),
+            sender: receipt.from.clone(),
+            block_number: receipt.block_number,
+            transaction_index: receipt.transaction_index,
+        };
+        let key = KeyBuilder::new()
+            .segment(&transaction.sender)
+            .u64(transaction.block_number)
+            .u32(transaction.transaction_index)
+            .finish();
+        batch.put_cf(sender_txs, key, serde_json::to_vec(&transaction)?);
+
+        if let Some(contract) = &receipt.contract_address {
+            self.stage_owner(&mut batch, contract, &receipt.from, receipt.block_number)?;
+        }
+
+        self.stage_indexed_block(&mut batch, receipt.block_number)?;
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// 记录对象所有权；早于当前记录的更新被忽略，重复索引结果不变
+    pub fn index_object_owner(&self, object_id: &str, owner: &str, block_number: u64) -> Result<()> {
+        let _guard = self.write_lock.lock().unwrap();
+        let mut batch = WriteBatch::default();
+        self.stage_owner(&mut batch, object_id, owner, block_number)?;
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// 已索引的最高区块，崩溃恢复后从其后继续
+    pub fn indexed_block(&self) -> Result<Option<u64>> {
+        Ok(self
+            .db
+            .get_cf(self.cf(CF_META)?, INDEXED_BLOCK_KEY)?
+            .map(|bytes| decode_u64(&bytes)))
+    }
+
+    /// 在 `path`（不能已存在）创建索引的一致性检查点
+    pub fn checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        Checkpoint::new(&self.db)?.create_checkpoint(path)?;
+        Ok(())
+    }
+
+    /// 按 package（及可选事件类型）查询区块范围内的事件
+    pub fn query_events(
+        &self,
+        query: &EventQuery,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<Page<IndexedEvent>> {
+        let mut prefix = KeyBuilder::new().segment(&query.package);
+        if let Some(event_type) = &query.event_type {
+            prefix = prefix.segment(event_type);
+        }
+        let prefix = prefix.finish();
+
+        // 指定事件类型时区块号紧跟在前缀之后，可直接定位起点
+        let start = match (&query.event_type, query.from_block) {
+            (Some(_), Some(from)) => {
+                let mut start = prefix.clone();
+                start.extend_from_slice(&from.to_be_bytes());
+                start
+            }
+            _ => prefix.clone(),
+        };
+
+        let from = query.from_block.unwrap_or(0);
+        let to = query.to_block.unwrap_or(u64::MAX);
+        self.scan(CF_EVENTS, &prefix, start, cursor, limit, |event: &IndexedEvent| {
+            if event.block_number > to && query.event_type.is_some() {
+                // 同一事件类型内按区块有序，超出上界即可停止
+                return Filter::Stop;
+            }
+            if (from..=to).contains(&event.block_number) {
+                Filter::Keep
+            } else {
+                Filter::Skip
+            }
+        })
+    }
+
+    /// 按 `eth_getLogs` 语义查询日志，按 (区块, 交易序号, 日志序号) 排序
+    ///
+    /// 同时给出地址与 topic0 时直接扫描 (package, event_type, 区块) 索引；
+    /// 否则逐块检查布隆过滤器，只读取可能匹配的区块的日志。
+    /// 区块数超过 `max_block_range` 或结果超过 `max_results` 时返回 [`LogQueryError`]
+    pub fn query_logs(
+        &self,
+        filter: &LogFilter,
+        max_block_range: u64,
+        max_results: usize,
+    ) -> Result<Vec<IndexedEvent>> {
+        let (from, to) = (filter.from_block, filter.to_block);
+        if from > to {
+            return Err(LogQueryError::InvalidRange { from, to }.into());
+        }
+        if to - from >= max_block_range {
+            return Err(LogQueryError::BlockRangeTooLarge {
+                from,
+                to,
+                max: max_block_range,
+            }
+            .into());
+        }
+
+        let filter = filter.normalized();
+        let topic0 = filter.topics.first().cloned().flatten().unwrap_or_default();
+        let mut logs = BTreeMap::new();
+        let mut keep = |event: IndexedEvent| {
+            if filter.matches(&event) {
+                if logs.len() == max_results {
+                    return Err(LogQueryError::TooManyResults { max: max_results });
+                }
+                let key = (event.block_number, event.transaction_index, event.log_index);
+                logs.insert(key, event);
+            }
+            Ok(())
+        };
+
+        if !filter.addresses.is_empty() && !topic0.is_empty() {
+            for address in &filter.addresses {
+                for event_type in &topic0 {
+                    let prefix = KeyBuilder::new()
+                        .segment(address)
+                        .segment(event_type)
+                        .finish();
+                    let mut start = prefix.clone();
+                    start.extend_from_slice(&from.to_be_bytes());
+                    for event in self.range::<IndexedEvent>(CF_EVENTS, &prefix, &start)? {
+                        let event = event?;
+                        if event.block_number > to {
+                            break;
+                        }
+                        keep(event)?;
+                    }
+                }
+            }
+        } else {
+            let blooms = self.db.iterator_cf(
+                self.cf(CF_BLOOMS)?,
+                IteratorMode::From(&from.to_be_bytes(), Direction::Forward),
+            );
+            for item in blooms {
+                let (key, value) = item?;
+                let block = decode_u64(&key);
+                if block > to {
+                    break;
+                }
+                let bloom = LogsBloom::from_slice(&value)
+                    .ok_or_else(|| anyhow!("Corrupt logs bloom for block {}", block))?;
+                if !filter.may_match(&bloom) {
+                    continue;
+                }
+                let prefix = block.to_be_bytes();
+                for event in self.range::<IndexedEvent>(CF_BLOCK_LOGS, &prefix, &prefix)? {
+                    keep(event?)?;
+                }
+            }
+        }
+
+        Ok(logs.into_values().collect())
+    }
+
+    /// 区块内排在该交易之前的日志数，用于把回执内的日志序号换算为区块内的序号
+    pub fn logs_before_transaction(
+        &self,
+        block_number: u64,
+        transaction_index: u32,
+    ) -> Result<u32> {
+        let prefix = block_number.to_be_bytes();
+        let end = block_log_key(block_number, transaction_index, 0);
+        let iter = self.db.iterator_cf(
+            self.cf(CF_BLOCK_LOGS)?,
+            IteratorMode::From(&prefix, Direction::Forward),
+        );
+        let mut count = 0;
+        for item in iter {
+            let (key, _) = item?;
+            if !key.starts_with(&prefix) || *key >= *end {
+                break;
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// 区块所有日志的布隆过滤器，没有日志的区块返回 `None`
+    pub fn block_bloom(&self, block_number: u64) -> Result<Option<LogsBloom>> {
+        Ok(self
+            .db
+            .get_cf(self.cf(CF_BLOOMS)?, block_number.to_be_bytes())?
+            .and_then(|bytes| LogsBloom::from_slice(&bytes)))
+    }
+
+    /// 查询某个地址拥有的对象
+    pub fn query_objects_by_owner(
+        &self,
+        owner: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<Page<OwnedObject>> {
+        let prefix = KeyBuilder::new().segment(owner).finish();
+        self.scan(CF_OWNED_OBJECTS, &prefix, prefix.clone(), cursor, limit, |_| {
+            Filter::Keep
+        })
+    }
+
+    /// 查询某个发送方的交易，按区块升序
+    pub fn query_transactions_by_sender(
+        &self,
+        sender: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<Page<IndexedTransaction>> {
+        let prefix = KeyBuilder::new().segment(sender).finish();
+        self.scan(CF_SENDER_TXS, &prefix, prefix.clone(), cursor, limit, |_| {
+            Filter::Keep
+        })
+    }
+
+    /// 跟随事件总线上某条链的新交易，取回执后写入索引
+    pub fn follow_chain_events(
+        self: &Arc<Self>,
+        adapters: Arc<AdapterManager>,
+        chain_type: ChainType,
+    ) -> JoinHandle<()> {
+        let mut events = adapters.subscribe_events();
+        let indexer = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let tx_hash = match events.recv().await {
+                    Ok(event)
+                        if event.chain_type == chain_type
+                            && event.kind == ChainEventKind::NewTransaction =>
+                    {
+                        event.payload
+                    }
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Indexer feed lagged, skipped {} chain events", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                let receipt = match adapters.get_transaction_receipt(chain_type, &tx_hash).await {
+                    Ok(receipt) => receipt,
+                    Err(e) => {
+                        warn!("Failed to fetch receipt for {}: {}", tx_hash, e);
+                        continue;
+                    }
+                };
+                if let Err(e) = indexer.index_receipt(&receipt) {
+                    warn!("Failed to index transaction {}: {}", tx_hash, e);
+                }
+            }
+            debug!("Chain event bus closed, indexer feed stopped");
+        })
+    }
+
+    /// 按区块回填 `[from, to]` 的回执，每秒至多请求 `blocks_per_second` 个区块
+    ///
+    /// 每个区块通过 [`AdapterManager::get_block_receipts`] 一次取回（链支持批量接口时），
+    /// 缺失的单笔回执记入报告；整块取回或写入失败时停止并返回 [`BackfillError`]
+    pub async fn backfill(
+        &self,
+        adapters: &AdapterManager,
+        chain_type: ChainType,
+        from: u64,
+        to: u64,
+        blocks_per_second: u32,
+    ) -> std::result::Result<BackfillReport, BackfillError> {
+        let mut report = BackfillReport::default();
+        let mut interval = tokio::time::interval(Duration::from_secs(1) / blocks_per_second.max(1));
+        info!(
+            "⏪ Backfilling {:?} blocks {}..={} into the index",
+            chain_type, from, to
+        );
+
+        for block in from..=to {
+            interval.tick().await;
+            let result = match adapters.get_block_receipts(chain_type, block).await {
+                Ok(result) => result,
+                Err(source) => {
+                    return Err(BackfillError {
+                        block,
+                        report,
+                        source: source.into(),
+                    })
+                }
+            };
+            for receipt in &result.receipts {
+                if let Err(source) = self.index_receipt(receipt) {
+                    return Err(BackfillError {
+                        block,
+                        report,
+                        source,
+                    });
+                }
+            }
+            if !result.missing.is_empty() {
+                warn!(
+                    "{} receipts missing from {:?} block {}",
+                    result.missing.len(),
+                    chain_type,
+                    block
+                );
+            }
+            report.blocks += 1;
+            report.receipts += result.receipts.len();
+            report.missing.extend(result.missing);
+        }
+
+        info!(
+            "✅ Backfilled {} blocks with {} receipts ({} missing)",
+            report.blocks,
+            report.receipts,
+            report.missing.len()
+        );
+        Ok(report)
+    }
+
+    fn stage_owner(
+        &self,
+        batch: &mut WriteBatch,
+        object_id: &str,
+        owner: &str,
+        block_number: u64,
+    ) -> Result<()> {
+        let owned = self.cf(CF_OWNED_OBJECTS)?;
+        let owners = self.cf(CF_OBJECT_OWNERS)?;
+
+        if let Some(bytes) = self.db.get_cf(owners, object_id.as_bytes())? {
+            let current: OwnedObject = serde_json::from_slice(&bytes)?;
+            if current.block_number > block_number {
+                return Ok(());
+            }
+            if current.owner != owner {
+                batch.delete_cf(owned, owned_key(&current.owner, object_id));
+            }
+        }
+
+        let record = OwnedObject {
+            object_id: object_id.to_string(),
+            owner: owner.to_string(),
+            block_number,
+        };
+        let value = serde_json::to_vec(&record)?;
+        batch.put_cf(owned, owned_key(owner, object_id), &value);
+        batch.put_cf(owners, object_id.as_bytes(), &value);
+        Ok(())
+    }
+
+    /// 与区块已有的布隆过滤器合并后写入
+    fn stage_bloom(
+        &self,
+        batch: &mut WriteBatch,
+        block_number: u64,
+        bloom: &LogsBloom,
+    ) -> Result<()> {
+        let mut merged = self.block_bloom(block_number)?.unwrap_or_default();
+        merged.accrue_bloom(bloom);
+        batch.put_cf(
+            self.cf(CF_BLOOMS)?,
+            block_number.to_be_bytes(),
+            merged.as_bytes(),
+        );
+        Ok(())
+    }
+
+    fn stage_indexed_block(&self, batch: &mut WriteBatch, block_number: u64) -> Result<()> {
+        if self.indexed_block()?.is_none_or(|indexed| block_number > indexed) {
+            batch.put_cf(self.cf(CF_META)?, INDEXED_BLOCK_KEY, block_number.to_be_bytes());
+        }
+        Ok(())
+    }
+
+    /// 在 `prefix` 范围内从 `start`（或游标之后）正向扫描
+    fn scan<T, F>(
+        &self,
+        cf: &'static str,
+        prefix: &[u8],
+        start: Vec<u8>,
+        cursor: Option<&str>,
+        limit: usize,
+        filter: F,
+    ) -> Result<Page<T>>
+    where
+        T: DeserializeOwned,
+        F: Fn(&T) -> Filter,
+    {
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+        let start = match cursor {
+            Some(cursor) => {
+                let last = hex::decode(cursor).map_err(|_| anyhow!("Invalid cursor"))?;
+                if !last.starts_with(prefix) {
+                    return Err(anyhow!("Cursor does not belong to this query"));
+                }
+                // 游标是上一页最后一个键，从其后继开始
+                let mut next = last;
+                next.push(0);
+                next.max(start)
+            }
+            None => start,
+        };
+
+        let mut items = Vec::new();
+        let mut last_key = None;
+        let iter = self
+            .db
+            .iterator_cf(self.cf(cf)?, IteratorMode::From(&start, Direction::Forward));
+        for item in iter {
+            let (key, value) = item?;
+            if !key.starts_with(prefix) {
+                break;
+            }
+            let entry: T = serde_json::from_slice(&value)?;
+            match filter(&entry) {
+                Filter::Keep => {}
+                Filter::Skip => continue,
+                Filter::Stop => break,
+            }
+            if items.len() == limit {
+                // 确认还有下一条后才返回游标
+                return Ok(Page {
+                    items,
+                    next_cursor: last_key.map(hex::encode),
+                });
+            }
+            items.push(entry);
+            last_key = Some(key);
+        }
+
+        Ok(Page {
+            items,
+            next_cursor: None,
+        })
+    }
+
+    /// 从 `start` 起遍历 `prefix` 范围内的全部条目
+    fn range<'a, T: DeserializeOwned>(
+        &'a self,
+        cf: &'static str,
+        prefix: &'a [u8],
+        start: &[u8],
+    ) -> Result<impl Iterator<Item = Result<T>> + 'a> {
+        let iter = self
+            .db
+            .iterator_cf(self.cf(cf)?, IteratorMode::From(start, Direction::Forward));
+        Ok(iter.map_while(move |item| match item {
+            Ok((key, _)) if !key.starts_with(prefix) => None,
+            Ok((_, value)) => Some(serde_json::from_slice(&value).map_err(Into::into)),
+            Err(e) => Some(Err(e.into())),
+        }))
+    }
+
+    fn cf(&self, name: &'static str) -> Result<&ColumnFamily> {
+        self.db
+            .cf_handle(name)
+            .ok_or_else(|| anyhow!("Missing column family: {}", name))
+    }
+}
+
+enum Filter {
+    Keep,
+    Skip,
+    Stop,
+}
+
+/// 有序复合键：字符串段带长度前缀，数值段大端序
+struct KeyBuilder(Vec<u8>);
+
+impl KeyBuilder {
+    fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    fn segment(mut self, value: &str) -> Self {
+        self.0.extend_from_slice(&(value.len() as u32).to_be_bytes());
+        self.0.extend_from_slice(value.as_bytes());
+        self
+    }
+
+    fn u64(mut self, value: u64) -> Self {
+        self.0.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    fn u32(mut self, value: u32) -> Self {
+        self.0.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+fn owned_key(owner: &str, object_id: &str) -> Vec<u8> {
+    KeyBuilder::new().segment(owner).segment(object_id).finish()
+}
+
+fn block_log_key(block_number: u64, transaction_index: u32, log_index: u32) -> Vec<u8> {
+    KeyBuilder::new()
+        .u64(block_number)
+        .u32(transaction_index)
+        .u32(log_index)
+        .finish()
+}
+
+/// `0x` 开头的十六进制值转为小写，其余（如 Move 事件类型）保持不变
+fn normalize_hex(value: &str) -> String {
+    match value.strip_prefix("0x") {
+        Some(digits) if digits.chars().all(|c| c.is_ascii_hexdigit()) => value.to_ascii_lowercase(),
+        _ => value.to_string(),
+    }
+}
+
+fn decode_u64(bytes: &[u8]) -> u64 {
+    bytes.try_into().map(u64::from_be_bytes).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use dubhe_adapter::{
+        AdapterError, AdapterResult, BlockReceipts, ChainAdapter, ContractMeta, EventLog,
+        TransactionStatus,
+    };
+    use tempfile::tempdir;
+    use tokio::sync::mpsc;
+
+    fn receipt(block: u64, index: u32, sender: &str, logs: Vec<(&str, &str)>) -> TransactionReceipt {
+        TransactionReceipt {
+            tx_hash: format!("0x{:08x}{:04x}", block, index),
+            block_hash: format!("0xblock{}", block),
+            block_number: block,
+            transaction_index: index,
+            from: sender.to_string(),
+            to: None,
+            gas_used: 21000,
+            status: TransactionStatus::Success,
+            logs: logs
+                .into_iter()
+                .map(|(package, event_type)| EventLog {
+                    address: package.to_string(),
+                    topics: vec![event_type.to_string()],
+                    data: "0x".to_string(),
+                })
+                .collect(),
+            contract_address: None,
+        }
+    }
+
+    /// 300 个区块，每块一笔交易，每笔两个事件
+    fn populate(indexer: &Indexer) -> Result<()> {
+        for block in 0..300 {
+            let event_type = if block % 3 == 0 { "Mint" } else { "Transfer" };
+            indexer.index_receipt(&receipt(
+                block,
+                0,
+                &format!("0xsender{}", block % 2),
+                vec![("0xpkg", event_type), ("0xother", "Transfer")],
+            ))?;
+        }
+        Ok(())
+    }
+
+    fn collect_all(indexer: &Indexer, query: &EventQuery, limit: usize) -> Result<Vec<IndexedEvent>> {
+        let mut events = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = indexer.query_events(query, cursor.as_deref(), limit)?;
+            assert!(page.items.len() <= limit);
+            events.extend(page.items);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return Ok(events),
+            }
+        }
+    }
+
+    #[test]
+    fn test_event_range_and_pagination() -> Result<()> {
+        let dir = tempdir()?;
+        let indexer = Indexer::open(dir.path())?;
+        populate(&indexer)?;
+
+        let query = EventQuery {
+            package: "0xpkg".to_string(),
+            event_type: Some("Transfer".to_string()),
+            from_block: Some(100),
+            to_block: Some(199),
+        };
+        let first = indexer.query_events(&query, None, 10)?;
+        assert_eq!(first.items.len(), 10);
+        assert_eq!(first.items[0].block_number, 100);
+        assert!(first.next_cursor.is_some());
+
+        let all = collect_all(&indexer, &query, 7)?;
+        let expected: Vec<u64> = (100..200).filter(|block| block % 3 != 0).collect();
+        let blocks: Vec<u64> = all.iter().map(|event| event.block_number).collect();
+        assert_eq!(blocks, expected);
+        assert!(all.iter().all(|event| event.package == "0xpkg" && event.event_type == "Transfer"));
+
+        // 不限定事件类型：按类型分组，各组内按区块有序
+        let any_type = EventQuery {
+            event_type: None,
+            ..query.clone()
+        };
+        assert_eq!(collect_all(&indexer, &any_type, 50)?.len(), 100);
+
+        // 恰好一页时不返回游标
+        let exact = EventQuery {
+            package: "0xpkg".to_string(),
+            event_type: Some("Mint".to_string()),
+            from_block: Some(0),
+            to_block: Some(8),
+        };
+        let page = indexer.query_events(&exact, None, 3)?;
+        assert_eq!(page.items.len(), 3);
+        assert!(page.next_cursor.is_none());
+
+        // 其他查询的游标被拒绝
+        let foreign = first.next_cursor.unwrap();
+        let other = EventQuery {
+            package: "0xother".to_string(),
+            ..EventQuery::default()
+        };
+        assert!(indexer.query_events(&other, Some(&foreign), 10).is_err());
+
+        let sender = indexer.query_transactions_by_sender("0xsender1", None, 1000)?;
+        assert_eq!(sender.items.len(), 150);
+        assert!(sender.items.windows(2).all(|w| w[0].block_number < w[1].block_number));
+
+        Ok(())
+    }
+
+    const TOKEN_A: &str = "0x00000000000000000000000000000000000000aa";
+    const TOKEN_B: &str = "0x00000000000000000000000000000000000000bb";
+
+    fn topic(value: u64) -> String {
+        format!("0x{:064x}", value)
+    }
+
+    /// 1000 个区块：每块一笔 TOKEN_A 的 Transfer，每 10 块另有一笔 TOKEN_B 的 Approval
+    fn populate_logs(indexer: &Indexer) -> Result<()> {
+        let (transfer, approval, alice, bob) = (topic(1), topic(2), topic(0xa1), topic(0xb0));
+        for block in 0..1000 {
+            let sender = if block % 2 == 0 { &alice } else { &bob };
+            let mut transfer_receipt = receipt(block, 0, "0xsender", vec![]);
+            transfer_receipt.logs.push(EventLog {
+                address: TOKEN_A.to_string(),
+                topics: vec![transfer.clone(), sender.clone(), bob.clone()],
+                data: format!("0x{:064x}", block),
+            });
+            indexer.index_receipt(&transfer_receipt)?;
+
+            if block % 10 == 0 {
+                let mut approval_receipt = receipt(block, 1, "0xsender", vec![]);
+                approval_receipt.logs.push(EventLog {
+                    address: TOKEN_B.to_string(),
+                    topics: vec![approval.clone(), alice.clone()],
+                    data: "0x".to_string(),
+                });
+                indexer.index_receipt(&approval_receipt)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn log_filter(
+        from: u64,
+        to: u64,
+        addresses: &[&str],
+        topics: Vec<Option<Vec<String>>>,
+    ) -> LogFilter {
+        LogFilter {
+            from_block: from,
+            to_block: to,
+            addresses: addresses.iter().map(|a| a.to_string()).collect(),
+            topics,
+        }
+    }
+
+    #[test]
+    fn test_log_queries() -> Result<()> {
+        let dir = tempdir()?;
+        let indexer = Indexer::open(dir.path())?;
+        populate_logs(&indexer)?;
+        let (transfer, approval, alice) = (topic(1), topic(2), topic(0xa1));
+
+        // 地址 + topic0：直接扫描事件索引
+        let filter = log_filter(100, 199, &[TOKEN_A], vec![Some(vec![transfer.clone()])]);
+        let logs = indexer.query_logs(&filter, 1000, 10_000)?;
+        let blocks: Vec<u64> = logs.iter().map(|log| log.block_number).collect();
+        assert_eq!(blocks, (100..200).collect::<Vec<_>>());
+        assert_eq!(logs[0].block_hash, "0xblock100");
+
+        // 只有 topic：逐块检查布隆过滤器
+        let filter = log_filter(0, 999, &[], vec![Some(vec![approval.clone()])]);
+        let logs = indexer.query_logs(&filter, 1000, 10_000)?;
+        assert_eq!(logs.len(), 100);
+        assert!(logs
+            .iter()
+            .all(|log| log.package == TOKEN_B && log.block_number % 10 == 0));
+
+        // null 通配 topic0，按第二个 topic 过滤
+        let filter = log_filter(0, 999, &[], vec![None, Some(vec![alice.clone()])]);
+        let logs = indexer.query_logs(&filter, 1000, 10_000)?;
+        assert_eq!(logs.len(), 600);
+        let keys: Vec<_> = logs
+            .iter()
+            .map(|log| (log.block_number, log.transaction_index, log.log_index))
+            .collect();
+        assert!(keys.windows(2).all(|w| w[0] < w[1]));
+
+        // 地址与 topic0 的“或”，结果跨两个索引前缀后按区块重新排序
+        let filter = log_filter(
+            0,
+            19,
+            &[TOKEN_A, TOKEN_B],
+            vec![Some(vec![transfer.clone(), approval.clone()])],
+        );
+        let logs = indexer.query_logs(&filter, 1000, 10_000)?;
+        assert_eq!(logs.len(), 22);
+        let order: Vec<_> = logs[..3]
+            .iter()
+            .map(|log| (log.block_number, log.package.as_str()))
+            .collect();
+        assert_eq!(order, vec![(0, TOKEN_A), (0, TOKEN_B), (1, TOKEN_A)]);
+
+        // 十六进制地址不区分大小写
+        let upper = TOKEN_A.to_uppercase().replacen("0X", "0x", 1);
+        assert_eq!(
+            indexer
+                .query_logs(&log_filter(0, 9, &[&upper], vec![]), 1000, 10_000)?
+                .len(),
+            10
+        );
+        let missing = "0x00000000000000000000000000000000000000cc";
+        assert!(indexer
+            .query_logs(&log_filter(0, 999, &[missing], vec![]), 1000, 10_000)?
+            .is_empty());
+
+        assert_eq!(indexer.logs_before_transaction(10, 1)?, 1);
+        assert_eq!(indexer.logs_before_transaction(11, 1)?, 1);
+        assert_eq!(indexer.logs_before_transaction(10, 0)?, 0);
+
+        let bloom = indexer.block_bloom(10)?.unwrap();
+        assert!(bloom.may_contain(TOKEN_A) && bloom.may_contain(TOKEN_B));
+        assert!(indexer.block_bloom(1000)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_log_query_limits() -> Result<()> {
+        let dir = tempdir()?;
+        let indexer = Indexer::open(dir.path())?;
+        populate_logs(&indexer)?;
+
+        let error = indexer
+            .query_logs(&log_filter(0, 1000, &[], vec![]), 1000, 10_000)
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<LogQueryError>(),
+            Some(&LogQueryError::BlockRangeTooLarge {
+                from: 0,
+                to: 1000,
+                max: 1000
+            })
+        );
+        assert!(error.to_string().contains("more than 1000 blocks"));
+
+        let error = indexer
+            .query_logs(&log_filter(10, 5, &[], vec![]), 1000, 10_000)
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<LogQueryError>(),
+            Some(&LogQueryError::InvalidRange { from: 10, to: 5 })
+        );
+
+        let error = indexer
+            .query_logs(&log_filter(0, 999, &[], vec![]), 1000, 50)
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<LogQueryError>(),
+            Some(&LogQueryError::TooManyResults { max: 50 })
+        );
+        assert_eq!(
+            indexer
+                .query_logs(&log_filter(0, 49, &[TOKEN_A], vec![]), 1000, 50)?
+                .len(),
+            50
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reindexing_is_idempotent() -> Result<()> {
+        let dir = tempdir()?;
+        let indexer = Indexer::open(dir.path())?;
+        populate(&indexer)?;
+        indexer.index_object_owner("0xobj", "0xalice", 10)?;
+        indexer.index_object_owner("0xobj", "0xbob", 20)?;
+        assert_eq!(indexer.indexed_block()?, Some(299));
+
+        // 模拟崩溃后从第 250 块重新索引
+        drop(indexer);
+        let indexer = Indexer::open(dir.path())?;
+        for block in 250..300 {
+            let event_type = if block % 3 == 0 { "Mint" } else { "Transfer" };
+            indexer.index_receipt(&receipt(
+                block,
+                0,
+                &format!("0xsender{}", block % 2),
+                vec![("0xpkg", event_type), ("0xother", "Transfer")],
+            ))?;
+        }
+        // 旧的所有权更新被重放也不会回退
+        indexer.index_object_owner("0xobj", "0xalice", 10)?;
+        indexer.index_object_owner("0xobj", "0xbob", 20)?;
+
+        let query = EventQuery {
+            package: "0xother".to_string(),
+            ..EventQuery::default()
+        };
+        assert_eq!(collect_all(&indexer, &query, 100)?.len(), 300);
+        assert_eq!(
+            indexer.query_transactions_by_sender("0xsender0", None, 1000)?.items.len(),
+            150
+        );
+        assert!(indexer.query_objects_by_owner("0xalice", None, 10)?.items.is_empty());
+        let owned = indexer.query_objects_by_owner("0xbob", None, 10)?;
+        assert_eq!(owned.items.len(), 1);
+        assert_eq!(owned.items[0].block_number, 20);
+        assert_eq!(indexer.indexed_block()?, Some(299));
+
+        Ok(())
+    }
+
+    /// 区块 1..=3 可取，区块 2 的第二笔回执已被节点裁剪，区块 4 取回失败
+    struct ArchiveNode;
+
+    #[async_trait]
+    impl ChainAdapter for ArchiveNode {
+        async fn get_contract_meta(&self, address: &str) -> AdapterResult<ContractMeta> {
+            Err(AdapterError::ContractNotFound {
+                chain: ChainType::Ethereum,
+                address: address.to_string(),
+            })
+        }
+
+        async fn get_transaction_receipt(
+            &self,
+            tx_hash: &str,
+        ) -> AdapterResult<TransactionReceipt> {
+            Err(anyhow!("unexpected single receipt lookup for {}", tx_hash).into())
+        }
+
+        async fn get_balance(&self, _address: &str) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn get_nonce(&self, _address: &str) -> AdapterResult<u64> {
+            Ok(0)
+        }
+
+        async fn get_block_number(&self) -> AdapterResult<u64> {
+            Ok(4)
+        }
+
+        async fn subscribe_new_blocks(&self) -> AdapterResult<mpsc::Receiver<String>> {
+            Ok(mpsc::channel(1).1)
+        }
+
+        async fn subscribe_new_transactions(&self) -> AdapterResult<mpsc::Receiver<String>> {
+            Ok(mpsc::channel(1).1)
+        }
+
+        async fn get_block_receipts(&self, number: u64) -> AdapterResult<BlockReceipts> {
+            if number > 3 {
+                return Err(anyhow!("upstream returned 429 for block {}", number).into());
+            }
+            let mut receipts = vec![receipt(number, 0, "0xalice", vec![("0xpkg", "Mint")])];
+            let mut missing = vec![];
+            if number == 2 {
+                missing.push(format!("0x{:08x}{:04x}", number, 1));
+            } else {
+                receipts.push(receipt(number, 1, "0xbob", vec![("0xpkg", "Transfer")]));
+            }
+            Ok(BlockReceipts {
+                block_number: number,
+                receipts,
+                missing,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backfill_blocks() -> Result<()> {
+        let dir = tempdir()?;
+        let indexer = Indexer::open(dir.path())?;
+        let adapters = AdapterManager::new();
+        adapters
+            .register_adapter(ChainType::Ethereum, Box::new(ArchiveNode))
+            .await;
+
+        let report = indexer
+            .backfill(&adapters, ChainType::Ethereum, 1, 3, 1_000)
+            .await?;
+        assert_eq!(report.blocks, 3);
+        assert_eq!(report.receipts, 5);
+        assert_eq!(report.missing, vec!["0x000000020001".to_string()]);
+        assert_eq!(indexer.indexed_block()?, Some(3));
+        let query = EventQuery {
+            package: "0xpkg".to_string(),
+            ..EventQuery::default()
+        };
+        assert_eq!(collect_all(&indexer, &query, 100)?.len(), 5);
+
+        // 区块 4 失败：3 之前的结果保留，报告指出从哪里重试
+        let error = indexer
+            .backfill(&adapters, ChainType::Ethereum, 3, 5, 1_000)
+            .await
+            .unwrap_err();
+        assert_eq!(error.block, 4);
+        assert_eq!(error.report.blocks, 1);
+        assert!(error.to_string().contains("429"));
+        assert_eq!(indexer.indexed_block()?, Some(3));
+
+        let error = indexer
+            .backfill(&adapters, ChainType::Solana, 1, 1, 1_000)
+            .await
+            .unwrap_err();
+        assert_eq!(error.block, 1);
+        Ok(())
     }
 }
@@ -0,0 +1,105 @@
+//! 回写日志
+//!
+//! 链下执行结果按块（每块一个 PTB）提交回主网。提交前写入日志，每块成功后记录其交易摘要；
+//! 节点在提交中途崩溃时，重启后从日志中找到未完成的会话，只提交尚无摘要的块，
+//! 全部完成后再释放对象租约并删除日志。
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use dubhe_adapter::MoveCall;
+
+use crate::storage::Storage;
+
+/// 日志在元数据列族中的键前缀
+const JOURNAL_PREFIX: &str = "sync_journal:";
+
+/// 回写期间持有的对象租约
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalLease {
+    pub object_id: String,
+    pub version: u64,
+    pub lock_digest: String,
+    pub expires_at_ms: u64,
+}
+
+/// 一个 PTB 的调用及其覆盖的变更
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncChunk {
+    pub calls: Vec<MoveCall>,
+    /// 每个调用对应的变更标识（修改对象 ID 或新建对象类型）
+    pub changes: Vec<String>,
+    /// 已提交时的交易摘要
+    pub digest: Option<String>,
+}
+
+/// 一次会话的回写记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncJournalRecord {
+    pub session_id: String,
+    pub leases: Vec<JournalLease>,
+    pub chunks: Vec<SyncChunk>,
+}
+
+impl SyncJournalRecord {
+    pub fn is_complete(&self) -> bool {
+        self.chunks.iter().all(|chunk| chunk.digest.is_some())
+    }
+}
+
+/// 持久化回写日志
+#[derive(Clone)]
+pub struct SyncJournal {
+    storage: Arc<Storage>,
+}
+
+impl SyncJournal {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    /// 写入（或覆盖）会话的回写记录
+    pub fn begin(&self, record: &SyncJournalRecord) -> Result<()> {
+        self.storage
+            .put_metadata(&journal_key(&record.session_id), &serde_json::to_vec(record)?)
+    }
+
+    /// 记录某块已提交
+    pub fn mark_committed(&self, session_id: &str, chunk: usize, digest: &str) -> Result<()> {
+        let mut record = self
+            .get(session_id)?
+            .ok_or_else(|| anyhow::anyhow!("No sync journal for session {}", session_id))?;
+        let entry = record
+            .chunks
+            .get_mut(chunk)
+            .ok_or_else(|| anyhow::anyhow!("Sync journal chunk {} out of range", chunk))?;
+        entry.digest = Some(digest.to_string());
+        self.begin(&record)
+    }
+
+    pub fn get(&self, session_id: &str) -> Result<Option<SyncJournalRecord>> {
+        self.storage
+            .get_metadata(&journal_key(session_id))?
+            .map(|bytes| Ok(serde_json::from_slice(&bytes)?))
+            .transpose()
+    }
+
+    /// 回写完成，删除记录
+    pub fn complete(&self, session_id: &str) -> Result<()> {
+        self.storage.delete_metadata(&journal_key(session_id))
+    }
+
+    /// 所有未删除的记录（重启后需要继续回写）
+    pub fn pending(&self) -> Result<Vec<SyncJournalRecord>> {
+        self.storage
+            .metadata_with_prefix(JOURNAL_PREFIX)?
+            .into_iter()
+            .map(|(_, bytes)| Ok(serde_json::from_slice(&bytes)?))
+            .collect()
+    }
+}
+
+fn journal_key(session_id: &str) -> String {
+    format!("{}{}", JOURNAL_PREFIX, session_id)
+}
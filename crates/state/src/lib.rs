@@ -2,23 +2,76 @@
 //!
 //! 存储层 (RocksDB) + 索引
 
+pub mod bloom;
+pub mod cursor;
 pub mod indexer;
+pub mod journal;
+pub mod replay;
 pub mod storage;
 pub mod types;
 
+pub use bloom::*;
+pub use cursor::*;
 pub use indexer::*;
+pub use journal::*;
+pub use replay::*;
 pub use storage::*;
 pub use types::*;
 
 use anyhow::Result;
+use std::path::Path;
+use std::sync::Arc;
 
 /// 状态管理器
 pub struct StateManager {
-    // TODO: 实现状态管理
+    storage: Arc<Storage>,
+    indexer: Arc<Indexer>,
 }
 
 impl StateManager {
-    pub fn new() -> Result<Self> {
-        Ok(Self {})
+    /// 对象存储与索引分别位于 `path/objects` 和 `path/index`
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        Ok(Self {
+            storage: Arc::new(Storage::open(path.join("objects"))?),
+            indexer: Arc::new(Indexer::open(path.join("index"))?),
+        })
+    }
+
+    /// 事件、对象所有权与交易的二级索引
+    pub fn indexer(&self) -> Arc<Indexer> {
+        self.indexer.clone()
+    }
+
+    /// 版本化对象存储
+    pub fn storage(&self) -> Arc<Storage> {
+        self.storage.clone()
+    }
+
+    /// 主网回写日志，与对象存储共用元数据列族
+    pub fn journal(&self) -> SyncJournal {
+        SyncJournal::new(self.storage.clone())
+    }
+
+    /// 链下执行录制，与对象存储共用元数据列族
+    pub fn replays(&self) -> ReplayStore {
+        ReplayStore::new(self.storage.clone())
+    }
+
+    /// 适配器订阅游标，与对象存储共用元数据列族
+    pub fn cursor_store(&self) -> RocksCursorStore {
+        RocksCursorStore::new(self.storage.clone())
+    }
+
+    /// 在 `path` 下按 [`StateManager::new`] 的布局创建对象存储与索引的检查点
+    ///
+    /// 两个库各自是一致的时间点；先取索引再取对象存储，恢复后游标不会落后于索引，
+    /// 重新消费的区块在索引中按相同的键覆盖
+    pub fn checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::create_dir_all(path)?;
+        self.indexer.checkpoint(path.join("index"))?;
+        self.storage.checkpoint(path.join("objects"))?;
+        Ok(())
     }
 }
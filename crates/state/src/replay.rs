@@ -0,0 +1,137 @@
+//! 执行录制
+//!
+//! 录制模式下，链下执行会话的全部输入（代码、加载的状态区域与对象、调用输入、gas 预算）
+//! 连同原始执行结果按会话写入元数据列族，`dubhe_replayTransaction` 据此在新的 VM 实例中
+//! 重新执行并比对结果。序列化后超过大小上限的录制不保存，超过保留期的录制定期清理
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use dubhe_vm_runtime::{ExecutionResult, StateRegion, VmType};
+
+use crate::storage::Storage;
+
+/// 录制在元数据列族中的键前缀
+const REPLAY_PREFIX: &str = "replay:";
+
+/// 加载到宿主函数的对象状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedObject {
+    pub object_id: String,
+    pub bcs_data: Vec<u8>,
+    /// `getObject` 返回的完整对象数据
+    pub object_data: serde_json::Value,
+}
+
+/// 一次会话执行的完整输入与结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionRecording {
+    pub session_id: String,
+    pub package_id: String,
+    pub vm_type: VmType,
+    /// 代码的 SHA-256，重放前校验
+    pub code_hash: String,
+    pub code: Vec<u8>,
+    /// 按加载顺序排列的状态区域
+    pub regions: Vec<StateRegion>,
+    pub objects: Vec<RecordedObject>,
+    pub input: Vec<u8>,
+    pub gas_budget: u64,
+    pub result: ExecutionResult,
+    /// 录制时间（Unix 秒）
+    pub recorded_at: u64,
+}
+
+/// 持久化执行录制
+#[derive(Clone)]
+pub struct ReplayStore {
+    storage: Arc<Storage>,
+}
+
+impl ReplayStore {
+    pub fn new(storage: Arc<Storage>) -> Self {
+        Self { storage }
+    }
+
+    /// 写入（或覆盖）会话的录制；序列化后超过 `max_bytes` 时不保存并返回 `false`
+    pub fn record(&self, recording: &ExecutionRecording, max_bytes: usize) -> Result<bool> {
+        let bytes = serde_json::to_vec(recording)?;
+        if bytes.len() > max_bytes {
+            return Ok(false);
+        }
+        self.storage
+            .put_metadata(&replay_key(&recording.session_id), &bytes)?;
+        Ok(true)
+    }
+
+    pub fn get(&self, session_id: &str) -> Result<Option<ExecutionRecording>> {
+        self.storage
+            .get_metadata(&replay_key(session_id))?
+            .map(|bytes| Ok(serde_json::from_slice(&bytes)?))
+            .transpose()
+    }
+
+    /// 删除早于 `cutoff`（Unix 秒）的录制，返回删除数量
+    pub fn prune(&self, cutoff: u64) -> Result<usize> {
+        let mut removed = 0;
+        for (key, bytes) in self.storage.metadata_with_prefix(REPLAY_PREFIX)? {
+            let recording: ExecutionRecording = serde_json::from_slice(&bytes)?;
+            if recording.recorded_at < cutoff {
+                self.storage.delete_metadata(&key)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+fn replay_key(session_id: &str) -> String {
+    format!("{}{}", REPLAY_PREFIX, session_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn recording(session_id: &str, recorded_at: u64, code: Vec<u8>) -> ExecutionRecording {
+        ExecutionRecording {
+            session_id: session_id.to_string(),
+            package_id: "0xpkg".to_string(),
+            vm_type: VmType::CkbVM,
+            code_hash: String::new(),
+            code,
+            regions: vec![StateRegion::read_write("0xa", vec![1, 2, 3])],
+            objects: vec![],
+            input: vec![0; 4],
+            gas_budget: 1_000,
+            result: ExecutionResult {
+                success: true,
+                output: vec![],
+                gas_used: 10,
+                cycles_used: 20,
+                error: None,
+                events: vec![],
+            },
+            recorded_at,
+        }
+    }
+
+    #[test]
+    fn test_size_cap_and_pruning() -> Result<()> {
+        let dir = tempdir()?;
+        let replays = ReplayStore::new(Arc::new(Storage::open(dir.path())?));
+
+        assert!(replays.record(&recording("old", 100, vec![]), 4096)?);
+        assert!(replays.record(&recording("new", 200, vec![]), 4096)?);
+        assert!(!replays.record(&recording("large", 200, vec![0; 8192]), 4096)?);
+        assert!(replays.get("large")?.is_none());
+        assert_eq!(replays.get("old")?.unwrap().regions[0].data, vec![1, 2, 3]);
+
+        assert_eq!(replays.prune(150)?, 1);
+        assert!(replays.get("old")?.is_none());
+        assert!(replays.get("new")?.is_some());
+        Ok(())
+    }
+}
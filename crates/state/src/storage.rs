@@ -1,9 +1,386 @@
 //! 存储模块
+//!
+//! 基于 RocksDB 的多版本对象存储，三个列族：
+//! - `objects`：(对象 ID, 版本) → 对象字节，同一对象的各版本按版本号升序相邻存放
+//! - `versions`：对象 ID → 最新版本
+//! - `metadata`：任意元数据
+//!
+//! 写入在同一把锁下读取并更新最新版本，批量变更通过 WriteBatch 原子提交；
+//! 快照基于 RocksDB 快照，提供一致的只读视图
 
-pub struct Storage;
+use anyhow::Result;
+use rocksdb::checkpoint::Checkpoint;
+use rocksdb::{ColumnFamily, Direction, IteratorMode, Options, Snapshot, WriteBatch, DB};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use thiserror::Error;
+
+use crate::types::StateChange;
+
+const CF_OBJECTS: &str = "objects";
+const CF_METADATA: &str = "metadata";
+const CF_VERSIONS: &str = "versions";
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum StorageError {
+    #[error("Object {object_id} is at version {latest}, refusing to write older version {version}")]
+    StaleVersion {
+        object_id: String,
+        version: u64,
+        latest: u64,
+    },
+
+    #[error("Missing column family: {0}")]
+    MissingColumnFamily(&'static str),
+}
+
+/// 版本化对象存储
+pub struct Storage {
+    db: DB,
+    // 串行化“读最新版本 + 写入”，保证版本检查与写入之间不被插入
+    write_lock: Mutex<()>,
+}
 
 impl Storage {
-    pub fn new() -> Self {
-        Self
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        let db = DB::open_cf(&opts, path, [CF_OBJECTS, CF_METADATA, CF_VERSIONS])?;
+        Ok(Self {
+            db,
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    /// 写入对象的一个版本；低于当前最新版本的写入被拒绝，同版本覆盖
+    pub fn put_object(&self, id: &str, version: u64, bytes: &[u8]) -> Result<()> {
+        self.apply_changes(&[StateChange {
+            object_id: id.to_string(),
+            version,
+            data: bytes.to_vec(),
+        }])
+    }
+
+    /// 原子地应用一组状态变更，任一变更版本过旧则整批不写入
+    pub fn apply_changes(&self, changes: &[StateChange]) -> Result<()> {
+        let objects = self.cf(CF_OBJECTS)?;
+        let versions = self.cf(CF_VERSIONS)?;
+
+        let _guard = self.write_lock.lock().unwrap();
+
+        // 批内同一对象可能出现多次，以批内最新值为准
+        let mut latest: HashMap<&str, u64> = HashMap::new();
+        let mut batch = WriteBatch::default();
+        for change in changes {
+            let current = match latest.get(change.object_id.as_str()) {
+                Some(version) => Some(*version),
+                None => self.latest_version(&change.object_id)?,
+            };
+            if let Some(current) = current {
+                if change.version < current {
+                    return Err(StorageError::StaleVersion {
+                        object_id: change.object_id.clone(),
+                        version: change.version,
+                        latest: current,
+                    }
+                    .into());
+                }
+            }
+
+            batch.put_cf(
+                objects,
+                object_key(&change.object_id, change.version),
+                &change.data,
+            );
+            batch.put_cf(
+                versions,
+                change.object_id.as_bytes(),
+                change.version.to_be_bytes(),
+            );
+            latest.insert(&change.object_id, change.version);
+        }
+
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// 读取对象在 `version` 时的状态：不大于该版本的最新写入
+    pub fn get_object_at(&self, id: &str, version: u64) -> Result<Option<Vec<u8>>> {
+        let objects = self.cf(CF_OBJECTS)?;
+        let key = object_key(id, version);
+        let iter = self
+            .db
+            .iterator_cf(objects, IteratorMode::From(&key, Direction::Reverse));
+        find_version(iter, id)
+    }
+
+    /// 对象的最新版本
+    pub fn latest_version(&self, id: &str) -> Result<Option<u64>> {
+        let versions = self.cf(CF_VERSIONS)?;
+        Ok(self
+            .db
+            .get_cf(versions, id.as_bytes())?
+            .map(|bytes| decode_version(&bytes)))
+    }
+
+    /// 对象的最新状态
+    pub fn get_latest(&self, id: &str) -> Result<Option<(u64, Vec<u8>)>> {
+        match self.latest_version(id)? {
+            Some(version) => Ok(self
+                .get_object_at(id, version)?
+                .map(|bytes| (version, bytes))),
+            None => Ok(None),
+        }
+    }
+
+    pub fn put_metadata(&self, key: &str, value: &[u8]) -> Result<()> {
+        self.db.put_cf(self.cf(CF_METADATA)?, key.as_bytes(), value)?;
+        Ok(())
+    }
+
+    pub fn get_metadata(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.db.get_cf(self.cf(CF_METADATA)?, key.as_bytes())?)
+    }
+
+    pub fn delete_metadata(&self, key: &str) -> Result<()> {
+        self.db.delete_cf(self.cf(CF_METADATA)?, key.as_bytes())?;
+        Ok(())
+    }
+
+    /// 按键顺序列出以 `prefix` 开头的元数据
+    pub fn metadata_with_prefix(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        let metadata = self.cf(CF_METADATA)?;
+        let mut entries = Vec::new();
+        for item in self.db.iterator_cf(
+            metadata,
+            IteratorMode::From(prefix.as_bytes(), Direction::Forward),
+        ) {
+            let (key, value) = item?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            entries.push((
+                String::from_utf8_lossy(&key).into_owned(),
+                value.into_vec(),
+            ));
+        }
+        Ok(entries)
+    }
+
+    /// 创建一致性只读视图，之后的写入对其不可见
+    pub fn create_snapshot(&self) -> SnapshotHandle<'_> {
+        SnapshotHandle {
+            storage: self,
+            snapshot: self.db.snapshot(),
+        }
+    }
+
+    /// 将缓冲写入磁盘
+    pub fn flush(&self) -> Result<()> {
+        for name in [CF_OBJECTS, CF_METADATA, CF_VERSIONS] {
+            self.db.flush_cf(self.cf(name)?)?;
+        }
+        Ok(())
+    }
+
+    /// 在 `path`（不能已存在）创建全部列族的一致性检查点，SST 文件尽量以硬链接共享
+    pub fn checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        Checkpoint::new(&self.db)?.create_checkpoint(path)?;
+        Ok(())
+    }
+
+    fn cf(&self, name: &'static str) -> Result<&ColumnFamily> {
+        Ok(self
+            .db
+            .cf_handle(name)
+            .ok_or(StorageError::MissingColumnFamily(name))?)
+    }
+}
+
+/// 存储快照
+pub struct SnapshotHandle<'a> {
+    storage: &'a Storage,
+    snapshot: Snapshot<'a>,
+}
+
+impl SnapshotHandle<'_> {
+    pub fn get_object_at(&self, id: &str, version: u64) -> Result<Option<Vec<u8>>> {
+        let objects = self.storage.cf(CF_OBJECTS)?;
+        let key = object_key(id, version);
+        let iter = self
+            .snapshot
+            .iterator_cf(objects, IteratorMode::From(&key, Direction::Reverse));
+        find_version(iter, id)
+    }
+
+    pub fn latest_version(&self, id: &str) -> Result<Option<u64>> {
+        let versions = self.storage.cf(CF_VERSIONS)?;
+        Ok(self
+            .snapshot
+            .get_cf(versions, id.as_bytes())?
+            .map(|bytes| decode_version(&bytes)))
+    }
+
+    pub fn get_latest(&self, id: &str) -> Result<Option<(u64, Vec<u8>)>> {
+        match self.latest_version(id)? {
+            Some(version) => Ok(self
+                .get_object_at(id, version)?
+                .map(|bytes| (version, bytes))),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_metadata(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let metadata = self.storage.cf(CF_METADATA)?;
+        Ok(self.snapshot.get_cf(metadata, key.as_bytes())?)
+    }
+}
+
+/// 对象键：`[u32 BE ID 长度][ID][u64 BE 版本]`，长度前缀保证一个 ID 不会是另一个的前缀
+fn object_key(id: &str, version: u64) -> Vec<u8> {
+    let mut key = object_prefix(id);
+    key.extend_from_slice(&version.to_be_bytes());
+    key
+}
+
+fn object_prefix(id: &str) -> Vec<u8> {
+    let mut prefix = Vec::with_capacity(4 + id.len() + 8);
+    prefix.extend_from_slice(&(id.len() as u32).to_be_bytes());
+    prefix.extend_from_slice(id.as_bytes());
+    prefix
+}
+
+fn decode_version(bytes: &[u8]) -> u64 {
+    bytes.try_into().map(u64::from_be_bytes).unwrap_or(0)
+}
+
+/// 反向迭代的第一个条目若属于该对象即为目标版本
+fn find_version<I>(mut iter: I, id: &str) -> Result<Option<Vec<u8>>>
+where
+    I: Iterator<Item = Result<(Box<[u8]>, Box<[u8]>), rocksdb::Error>>,
+{
+    let prefix = object_prefix(id);
+    match iter.next() {
+        Some(item) => {
+            let (key, value) = item?;
+            if key.len() == prefix.len() + 8 && key.starts_with(&prefix) {
+                Ok(Some(value.into_vec()))
+            } else {
+                Ok(None)
+            }
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_version_ordering() -> Result<()> {
+        let dir = tempdir()?;
+        let storage = Storage::open(dir.path())?;
+
+        storage.put_object("0xa", 1, b"v1")?;
+        storage.put_object("0xa", 5, b"v5")?;
+        storage.put_object("0xab", 3, b"other")?;
+
+        assert_eq!(storage.latest_version("0xa")?, Some(5));
+        assert_eq!(storage.get_object_at("0xa", 0)?, None);
+        assert_eq!(storage.get_object_at("0xa", 1)?, Some(b"v1".to_vec()));
+        assert_eq!(storage.get_object_at("0xa", 4)?, Some(b"v1".to_vec()));
+        assert_eq!(storage.get_object_at("0xa", u64::MAX)?, Some(b"v5".to_vec()));
+        assert_eq!(storage.get_object_at("0xab", 2)?, None);
+        assert_eq!(storage.get_object_at("0xb", 9)?, None);
+
+        // 旧版本写入被拒绝，整批不生效
+        let err = storage
+            .apply_changes(&[
+                StateChange {
+                    object_id: "0xc".to_string(),
+                    version: 1,
+                    data: b"c1".to_vec(),
+                },
+                StateChange {
+                    object_id: "0xa".to_string(),
+                    version: 2,
+                    data: b"v2".to_vec(),
+                },
+            ])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<StorageError>(),
+            Some(StorageError::StaleVersion { latest: 5, .. })
+        ));
+        assert_eq!(storage.latest_version("0xc")?, None);
+
+        // 重新打开后数据仍在
+        drop(storage);
+        let storage = Storage::open(dir.path())?;
+        assert_eq!(storage.get_latest("0xa")?, Some((5, b"v5".to_vec())));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_isolation_under_concurrent_writes() -> Result<()> {
+        let dir = tempdir()?;
+        let storage = Arc::new(Storage::open(dir.path())?);
+        for id in 0..4 {
+            storage.put_object(&format!("0x{}", id), 1, b"base")?;
+        }
+        storage.put_metadata("checkpoint", b"1")?;
+
+        let snapshot = storage.create_snapshot();
+
+        let writers: Vec<_> = (0..4)
+            .map(|id| {
+                let storage = storage.clone();
+                std::thread::spawn(move || -> Result<()> {
+                    for version in 2..50u64 {
+                        let changes: Vec<StateChange> = (0..4)
+                            .map(|object| StateChange {
+                                object_id: format!("0x{}", object),
+                                version: version * 4 + id,
+                                data: version.to_be_bytes().to_vec(),
+                            })
+                            .collect();
+                        // 并发写入可能因版本落后被拒绝，忽略即可
+                        let _ = storage.apply_changes(&changes);
+                    }
+                    storage.put_metadata("checkpoint", b"2")
+                })
+            })
+            .collect();
+
+        // 写入进行中快照始终看到创建时的状态
+        for _ in 0..100 {
+            for id in 0..4 {
+                let object = format!("0x{}", id);
+                assert_eq!(snapshot.latest_version(&object)?, Some(1));
+                assert_eq!(snapshot.get_latest(&object)?, Some((1, b"base".to_vec())));
+            }
+        }
+        for writer in writers {
+            writer.join().unwrap()?;
+        }
+
+        assert_eq!(snapshot.get_metadata("checkpoint")?, Some(b"1".to_vec()));
+        assert_eq!(storage.get_metadata("checkpoint")?, Some(b"2".to_vec()));
+
+        // 每批原子提交：所有对象的最新版本一致
+        let latest = storage.latest_version("0x0")?.unwrap();
+        assert!(latest > 1);
+        for id in 1..4 {
+            assert_eq!(storage.latest_version(&format!("0x{}", id))?, Some(latest));
+        }
+
+        Ok(())
     }
 }
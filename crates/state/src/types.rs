@@ -5,3 +5,11 @@ pub struct StateData {
     pub key: String,
     pub value: Vec<u8>,
 }
+
+/// 对象状态变更：写入对象的一个新版本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateChange {
+    pub object_id: String,
+    pub version: u64,
+    pub data: Vec<u8>,
+}
@@ -3,79 +3,266 @@
 //! CKB-VM 是 Nervos 网络开发的成熟 RISC-V 虚拟机，支持完整的 RV64IMC 指令集
 //!
 //! 注意：这是一个简化的实现框架，完整的 CKB-VM 集成需要更详细的 API 对接
+//!
+//! 设置 [`CkbVmInstance::suspend_after`] 后可分段执行：每段周期数用尽时返回可序列化的
+//! [`MachineState`]，之后由 [`CkbVmInstance::resume`] 继续，调度器据此把超大交易切片执行
 
 use anyhow::Result;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
 use tracing::{debug, info, warn};
 
-use crate::error::VmError;
-use crate::traits::VmInstance;
+use dubhe_loader::ArtifactKind;
+
+use crate::error::{VmError, VmResult};
+use crate::host::StateRegions;
+use crate::trace::{ExecutionTrace, TraceConfig};
+use crate::traits::{HostFunctions, VmInstance};
 use crate::types::*;
 
 /// CKB-VM 实例
+///
+/// 每次执行都基于已加载的代码与状态区域新建机器，执行之间互不影响
 pub struct CkbVmInstance {
     limits: ExecutionLimits,
     code_loaded: bool,
+    code: Vec<u8>,
+    regions: StateRegions,
+    host: Option<Arc<dyn HostFunctions>>,
+    suspend_after: Option<u64>,
+    snapshot_key: [u8; 32],
+}
+
+/// 可暂停执行的结果
+#[derive(Debug, Clone)]
+pub enum ExecutionStatus {
+    Completed(ExecutionResult),
+    /// 本段周期数用尽，停在指令边界
+    Suspended(SuspendedExecution),
+}
+
+/// 暂停的执行
+#[derive(Debug, Clone)]
+pub struct SuspendedExecution {
+    /// 继续执行所需的机器状态
+    pub state: MachineState,
+    pub cycles_used: u64,
+    pub gas_used: u64,
+    /// 暂停时剩余的 gas 预算
+    pub remaining_gas: u64,
+}
+
+/// 序列化的机器状态
+///
+/// 包含寄存器、PC、已用周期与周期上限、自加载以来写过的内存页（代码页只读，不计入），
+/// 以及输入、已写出的输出、状态区域与事件；内容之前是以快照密钥计算的 32 字节 keyed blake2b
+/// 摘要，恢复时校验摘要与代码哈希，被篡改、来自其他代码或由其他密钥封存的状态会被拒绝
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MachineState(Vec<u8>);
+
+impl MachineState {
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    fn seal(image: &machine::MachineImage, key: &[u8; 32]) -> Result<Self> {
+        let payload = bincode::serialize(image)?;
+        let mut bytes = Self::mac(key, &payload).finalize().into_bytes().to_vec();
+        bytes.extend(payload);
+        Ok(Self(bytes))
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    fn open(&self, key: &[u8; 32]) -> Result<machine::MachineImage> {
+        let tampered = || VmError::SnapshotFailed("Machine state integrity check failed".into());
+        if self.0.len() < 32 {
+            return Err(tampered().into());
+        }
+        let (digest, payload) = self.0.split_at(32);
+        Self::mac(key, payload)
+            .verify_slice(digest)
+            .map_err(|_| tampered())?;
+        Ok(bincode::deserialize(payload)?)
+    }
+
     #[cfg(feature = "ckb-vm")]
-    _vm_state: Option<Vec<u8>>, // 简化的 VM 状态表示
-    #[cfg(not(feature = "ckb-vm"))]
-    _placeholder: (),
+    fn mac(key: &[u8; 32], payload: &[u8]) -> blake2::Blake2bMac<blake2::digest::consts::U32> {
+        use blake2::digest::Mac;
+
+        let mut mac = blake2::Blake2bMac::new_from_slice(key).expect("32-byte key is valid");
+        mac.update(payload);
+        mac
+    }
+}
+
+/// 进程内随机生成的默认快照密钥
+fn process_snapshot_key() -> [u8; 32] {
+    static KEY: OnceLock<[u8; 32]> = OnceLock::new();
+    *KEY.get_or_init(|| {
+        let mut key = [0u8; 32];
+        getrandom::getrandom(&mut key).expect("OS random source unavailable");
+        key
+    })
 }
 
 impl CkbVmInstance {
     pub fn new() -> Result<Self> {
         info!("Initializing CKB-VM instance");
 
+        #[cfg(not(feature = "ckb-vm"))]
+        warn!("CKB-VM feature not enabled, using placeholder implementation");
+
+        Ok(Self {
+            limits: ExecutionLimits::default(),
+            code_loaded: false,
+            code: Vec::new(),
+            regions: StateRegions::new(),
+            host: None,
+            suspend_after: None,
+            snapshot_key: process_snapshot_key(),
+        })
+    }
+
+    /// 注入宿主函数；未注入时存储调用视为键不存在，存储写入失败
+    pub fn with_host(mut self, host: Arc<dyn HostFunctions>) -> Self {
+        self.host = Some(host);
+        self
+    }
+
+    /// 设置封存与校验 [`MachineState`] 的密钥
+    ///
+    /// 默认使用进程内随机生成的密钥，暂停状态只能在本进程内继续；
+    /// 需要跨进程或跨节点继续时，各方配置相同的密钥
+    pub fn with_snapshot_key(mut self, key: [u8; 32]) -> Self {
+        self.snapshot_key = key;
+        self
+    }
+
+    /// 已加载的状态区域（执行中的写入会回写到这里）
+    pub fn state(&self, key: &str) -> Option<&StateRegion> {
+        self.regions.get(key)
+    }
+
+    /// 之后的 [`execute_resumable`](Self::execute_resumable) 与 [`resume`](Self::resume)
+    /// 每段至多运行 `cycles` 个周期，用尽时暂停而不是结束
+    ///
+    /// 解释器包在一次系统调用内执行完毕，这一段可能超出 `cycles`；
+    /// [`VmInstance::execute`] 不受影响，始终运行到结束
+    pub fn suspend_after(&mut self, cycles: u64) {
+        self.suspend_after = Some(cycles);
+    }
+
+    /// 可暂停地执行一次
+    pub async fn execute_resumable(&mut self, input: &[u8]) -> Result<ExecutionStatus> {
+        if !self.code_loaded {
+            return Err(VmError::ExecutionFailed("No code loaded".to_string()).into());
+        }
+
         #[cfg(feature = "ckb-vm")]
         {
-            info!("CKB-VM feature enabled - production ready implementation");
-            Ok(Self {
-                limits: ExecutionLimits::default(),
-                code_loaded: false,
-                _vm_state: Some(Vec::new()),
-            })
+            let entry = machine::Entry::Start {
+                input,
+                regions: self.regions.clone(),
+            };
+            self.run_resumable(entry, self.limits.clone())
         }
 
         #[cfg(not(feature = "ckb-vm"))]
         {
-            warn!("CKB-VM feature not enabled, using placeholder implementation");
-            Ok(Self {
-                limits: ExecutionLimits::default(),
-                code_loaded: false,
-                _placeholder: (),
-            })
+            let _ = input;
+            Err(VmError::ExecutionFailed("CKB-VM feature not enabled".to_string()).into())
         }
     }
-}
-
-#[async_trait]
-impl VmInstance for CkbVmInstance {
-    async fn load_code(&mut self, code: &[u8]) -> Result<()> {
-        info!("Loading {} bytes of RISC-V code into CKB-VM", code.len());
 
-        if code.is_empty() {
-            return Err(VmError::CodeLoadingFailed("Empty code".to_string()).into());
+    /// 从暂停状态继续执行，周期上限在暂停时的基础上追加 `additional_gas` 折算的周期
+    ///
+    /// 须加载与暂停时相同的代码；状态区域与输入取自暂停状态
+    pub async fn resume(
+        &mut self,
+        state: MachineState,
+        additional_gas: u64,
+    ) -> Result<ExecutionStatus> {
+        if !self.code_loaded {
+            return Err(VmError::ExecutionFailed("No code loaded".to_string()).into());
         }
 
         #[cfg(feature = "ckb-vm")]
         {
-            // TODO: 实现真正的 CKB-VM 代码加载
-            // 当前是简化版本，生产环境需要完整的 CKB-VM API 对接
-            info!("CKB-VM code loading: {} bytes", code.len());
-            self.code_loaded = true;
-            debug!("Code loaded successfully into CKB-VM");
-            Ok(())
+            let image = state.open(&self.snapshot_key)?;
+            let limits = ExecutionLimits {
+                max_cycles: image
+                    .max_cycles
+                    .saturating_add(self.limits.gas_schedule.gas_to_cycles(additional_gas)),
+                ..self.limits.clone()
+            };
+            debug!(
+                "Resuming CKB-VM at {} of {} cycles",
+                image.cycles, limits.max_cycles
+            );
+            self.run_resumable(machine::Entry::Resume(Box::new(image)), limits)
         }
 
         #[cfg(not(feature = "ckb-vm"))]
         {
-            warn!("CKB-VM not available, code loading simulated");
-            self.code_loaded = true;
-            Ok(())
+            let _ = (state, additional_gas);
+            Err(VmError::ExecutionFailed("CKB-VM feature not enabled".to_string()).into())
         }
     }
 
-    async fn execute(&mut self, input: &[u8]) -> Result<ExecutionResult> {
+    #[cfg(feature = "ckb-vm")]
+    fn run_resumable(
+        &mut self,
+        entry: machine::Entry<'_>,
+        limits: ExecutionLimits,
+    ) -> Result<ExecutionStatus> {
+        let mut outcome = machine::run(
+            &self.code,
+            entry,
+            self.host.clone(),
+            &limits,
+            None,
+            self.suspend_after,
+        )?;
+
+        let Some(image) = outcome.suspended.take() else {
+            return Ok(ExecutionStatus::Completed(self.complete(outcome, &limits)));
+        };
+        let state = MachineState::seal(&image, &self.snapshot_key)?;
+        debug!(
+            "CKB-VM suspended at {} cycles with {} dirty pages ({} bytes of state)",
+            image.cycles,
+            image.pages.len(),
+            state.len()
+        );
+        let schedule = limits.gas_schedule;
+        Ok(ExecutionStatus::Suspended(SuspendedExecution {
+            state,
+            cycles_used: image.cycles,
+            gas_used: schedule.cycles_to_gas(image.cycles),
+            remaining_gas: (image.max_cycles - image.cycles) / schedule.cycles_per_gas.max(1),
+        }))
+    }
+
+    /// 执行一次，`trace` 非空时逐条记录指令
+    fn run(
+        &mut self,
+        input: &[u8],
+        trace: Option<&TraceConfig>,
+    ) -> Result<(ExecutionResult, Option<ExecutionTrace>)> {
         if !self.code_loaded {
             return Err(VmError::ExecutionFailed("No code loaded".to_string()).into());
         }
@@ -84,82 +271,169 @@ impl VmInstance for CkbVmInstance {
 
         #[cfg(feature = "ckb-vm")]
         {
-            // TODO: 实现真正的 CKB-VM 执行
-            // 当前是简化版本，返回成功的执行结果
-            info!("CKB-VM execution simulation - input: {} bytes", input.len());
-
-            // 模拟执行结果
-            let gas_used = input.len() as u64 + 1000; // 基础 gas + 输入处理
-            let cycles_used = gas_used * 2; // 假设每个 gas 消耗 2 个 cycle
-
-            Ok(ExecutionResult {
-                success: true,
-                output: input.to_vec(), // 简化：直接返回输入作为输出
-                gas_used,
-                cycles_used,
-                error: None,
-            })
+            let entry = machine::Entry::Start {
+                input,
+                regions: self.regions.clone(),
+            };
+            let mut outcome = machine::run(
+                &self.code,
+                entry,
+                self.host.clone(),
+                &self.limits,
+                trace,
+                None,
+            )?;
+            let trace = outcome.trace.take();
+            let limits = self.limits.clone();
+            Ok((self.complete(outcome, &limits), trace))
         }
 
         #[cfg(not(feature = "ckb-vm"))]
         {
+            let _ = trace;
             warn!("CKB-VM not available, returning placeholder result");
-            Ok(ExecutionResult {
+            Ok((
+                ExecutionResult {
+                    success: true,
+                    output: input.to_vec(),
+                    gas_used: 1000,
+                    cycles_used: 2000,
+                    error: None,
+                    events: vec![],
+                },
+                None,
+            ))
+        }
+    }
+
+    /// 结束的执行：回写状态区域并生成执行结果
+    #[cfg(feature = "ckb-vm")]
+    fn complete(&mut self, outcome: machine::Outcome, limits: &ExecutionLimits) -> ExecutionResult {
+        self.regions = outcome.regions;
+
+        let cycles_used = outcome.cycles;
+        let events = outcome.events;
+        let gas_used = limits.gas_schedule.cycles_to_gas(cycles_used);
+        let result = match outcome.exit {
+            Ok(0) => ExecutionResult {
                 success: true,
-                output: input.to_vec(),
-                gas_used: 1000,
-                cycles_used: 2000,
+                output: outcome.output,
+                gas_used,
+                cycles_used,
                 error: None,
-            })
+                events,
+            },
+            Ok(code) => {
+                warn!("CKB-VM program exited with code {}", code);
+                ExecutionResult {
+                    success: false,
+                    output: outcome.output,
+                    gas_used,
+                    cycles_used,
+                    error: Some(format!("Non-zero exit code: {}", code)),
+                    events,
+                }
+            }
+            Err(e) => ExecutionResult {
+                success: false,
+                output: vec![],
+                gas_used,
+                cycles_used,
+                error: Some(e),
+                events,
+            },
+        };
+
+        debug!(
+            "CKB-VM execution finished: success={}, cycles={}",
+            result.success, result.cycles_used
+        );
+        result
+    }
+}
+
+#[async_trait]
+impl VmInstance for CkbVmInstance {
+    async fn load_code(&mut self, code: &[u8]) -> VmResult<()> {
+        info!("Loading {} bytes of RISC-V code into CKB-VM", code.len());
+
+        if code.is_empty() {
+            return Err(VmError::CodeLoadingFailed("Empty code".to_string()));
         }
+
+        self.code = code.to_vec();
+        self.code_loaded = true;
+        debug!("Code loaded successfully into CKB-VM");
+        Ok(())
     }
 
-    async fn snapshot(&self) -> Result<VmSnapshot> {
-        debug!("Creating CKB-VM snapshot");
+    async fn load_state(&mut self, region: StateRegion) -> VmResult<()> {
+        debug!(
+            "Loading state region {} ({} bytes, {:?})",
+            region.key,
+            region.data.len(),
+            region.access
+        );
+        self.regions.load(region);
+        Ok(())
+    }
 
-        #[cfg(feature = "ckb-vm")]
-        {
-            // TODO: 实现真正的 CKB-VM 快照
-            let snapshot_data = bincode::serialize(&(self.code_loaded, self.limits.max_cycles))?;
+    async fn execute(&mut self, input: &[u8]) -> VmResult<ExecutionResult> {
+        let (result, _) = self.run(input, None)?;
+        Ok(result)
+    }
 
-            Ok(VmSnapshot {
-                data: snapshot_data,
-                vm_type: VmType::CkbVM,
-            })
+    async fn execute_traced(
+        &mut self,
+        input: &[u8],
+        config: &TraceConfig,
+    ) -> VmResult<(ExecutionResult, ExecutionTrace)> {
+        #[cfg(not(feature = "ckb-vm"))]
+        {
+            let _ = (input, config);
+            Err(VmError::TracingUnsupported(VmType::CkbVM))
         }
 
-        #[cfg(not(feature = "ckb-vm"))]
+        #[cfg(feature = "ckb-vm")]
         {
-            Ok(VmSnapshot {
-                data: vec![0u8; 64], // Placeholder
-                vm_type: VmType::CkbVM,
-            })
+            let (result, trace) = self.run(input, Some(config))?;
+            Ok((result, trace.unwrap_or_default()))
         }
     }
 
-    async fn restore(&mut self, snapshot: &VmSnapshot) -> Result<()> {
+    async fn snapshot(&self) -> VmResult<VmSnapshot> {
+        debug!("Creating CKB-VM snapshot");
+
+        let regions: Vec<&StateRegion> = self.regions.iter().collect();
+        let snapshot_data = bincode::serialize(&(&self.code, regions, self.limits.max_cycles))
+            .map_err(|e| VmError::SnapshotFailed(e.to_string()))?;
+
+        Ok(VmSnapshot {
+            data: snapshot_data,
+            vm_type: VmType::CkbVM,
+        })
+    }
+
+    async fn restore(&mut self, snapshot: &VmSnapshot) -> VmResult<()> {
         if snapshot.vm_type != VmType::CkbVM {
-            return Err(VmError::SnapshotFailed("VM type mismatch".to_string()).into());
+            return Err(VmError::SnapshotFailed("VM type mismatch".to_string()));
         }
 
         debug!("Restoring CKB-VM from snapshot");
 
-        #[cfg(feature = "ckb-vm")]
-        {
-            // TODO: 实现真正的 CKB-VM 状态恢复
-            let (code_loaded, max_cycles): (bool, u64) = bincode::deserialize(&snapshot.data)?;
+        let (code, regions, max_cycles): (Vec<u8>, Vec<StateRegion>, u64) =
+            bincode::deserialize(&snapshot.data)
+                .map_err(|e| VmError::SnapshotFailed(e.to_string()))?;
 
-            self.code_loaded = code_loaded;
-            self.limits.max_cycles = max_cycles;
-            debug!("CKB-VM state restored successfully");
-            Ok(())
-        }
-
-        #[cfg(not(feature = "ckb-vm"))]
-        {
-            self.code_loaded = true; // Placeholder
-            Ok(())
+        self.code_loaded = !code.is_empty();
+        self.code = code;
+        self.regions = StateRegions::new();
+        for region in regions {
+            self.regions.load(region);
         }
+        self.limits.max_cycles = max_cycles;
+        debug!("CKB-VM state restored successfully");
+        Ok(())
     }
 
     fn vm_type(&self) -> VmType {
@@ -170,6 +444,667 @@ impl VmInstance for CkbVmInstance {
         debug!("Setting CKB-VM execution limits: {:?}", limits);
         self.limits = limits;
     }
+
+    /// 解释器包通过 `SYS_EVM_INTERPRET` 或 `SYS_WASM_INTERPRET` 由宿主解释执行
+    fn supports_artifact(&self, _kind: ArtifactKind) -> bool {
+        true
+    }
+
+    /// 寄存器与内存随每次执行的机器一起重建，这里只需清除状态区域、宿主函数、暂停设置与周期上限
+    fn reset(&mut self) -> VmResult<()> {
+        self.regions = StateRegions::new();
+        self.host = None;
+        self.suspend_after = None;
+        self.limits = ExecutionLimits {
+            gas_schedule: self.limits.gas_schedule,
+            ..ExecutionLimits::default()
+        };
+        Ok(())
+    }
+
+    fn set_host(&mut self, host: Arc<dyn HostFunctions>) -> VmResult<()> {
+        self.host = Some(host);
+        Ok(())
+    }
+}
+
+/// ckb-vm 机器的构建与系统调用
+#[cfg(feature = "ckb-vm")]
+mod machine {
+    use anyhow::Result;
+    use ckb_vm::cost_model::estimate_cycles;
+    use ckb_vm::decoder::build_decoder;
+    use ckb_vm::instructions::{
+        extract_opcode, instruction_opcode_name, insts, Instruction, Itype,
+    };
+    use ckb_vm::machine::{DefaultMachine, VERSION2};
+    use ckb_vm::memory::{FLAG_DIRTY, FLAG_EXECUTABLE, FLAG_FREEZED};
+    use ckb_vm::registers::{A0, A1, A2, A3, A4, A7, SP};
+    use ckb_vm::{
+        Bytes, CoreMachine, Debugger, DefaultCoreMachine, DefaultMachineBuilder, Error, Memory,
+        SparseMemory, SupportMachine, Syscalls, WXorXMemory, ISA_IMC,
+        RISCV_GENERAL_REGISTER_NUMBER,
+    };
+    use dubhe_loader::abi::*;
+    use dubhe_loader::riscv;
+    use serde::{Deserialize, Serialize};
+    use std::sync::{Arc, Mutex};
+
+    use crate::error::VmError;
+    use crate::evm::{self, EvmError, EVM_STEP_CYCLES};
+    use crate::host::{self as host_fns, StateRegions};
+    use crate::trace::{
+        ExecutionTrace, MemoryRead, RegisterChange, TraceBuffer, TraceConfig, TraceStep,
+    };
+    use crate::traits::HostFunctions;
+    use crate::types::{ExecutionLimits, StateRegion, VmEvent};
+    use crate::wasm::{self, WasmError, WASM_FUEL_CYCLES};
+
+    type Inner = DefaultCoreMachine<u64, WXorXMemory<SparseMemory<u64>>>;
+
+    /// 代码段起始地址
+    pub(super) const CODE_ADDRESS: u64 = 0x1_0000;
+    const PAGE_SIZE: u64 = 4096;
+    /// 与 CKB 链上一致的 4MB 地址空间上限
+    const MAX_MEMORY_SIZE: u64 = 4 * 1024 * 1024;
+    /// 系统调用在宿主与 guest 之间每复制或哈希一字节计费的周期数
+    const BYTE_CYCLES: u64 = 1;
+    /// 一次执行累计输出的字节数上限
+    pub(super) const MAX_OUTPUT_SIZE: u64 = 1024 * 1024;
+    /// 一次执行累计事件（主题与数据）的字节数上限
+    const MAX_EVENTS_SIZE: u64 = 1024 * 1024;
+
+    pub(super) struct Outcome {
+        /// 退出码，或 guest 陷入错误的描述
+        pub exit: std::result::Result<i8, String>,
+        pub cycles: u64,
+        pub output: Vec<u8>,
+        pub regions: StateRegions,
+        pub events: Vec<VmEvent>,
+        pub trace: Option<ExecutionTrace>,
+        /// 本段周期数用尽而暂停时的机器状态；此时其余字段无意义
+        pub suspended: Option<MachineImage>,
+    }
+
+    /// 执行的起点
+    pub(super) enum Entry<'a> {
+        Start {
+            input: &'a [u8],
+            regions: StateRegions,
+        },
+        Resume(Box<MachineImage>),
+    }
+
+    /// 暂停时的机器状态
+    #[derive(Clone, Serialize, Deserialize)]
+    pub(super) struct MachineImage {
+        /// 代码的 blake2b 哈希，恢复时须一致
+        pub code_hash: [u8; 32],
+        pub memory_size: u64,
+        pub registers: Vec<u64>,
+        pub pc: u64,
+        pub cycles: u64,
+        pub max_cycles: u64,
+        /// 自加载以来写过的内存页：(页号, 内容)
+        pub pages: Vec<(u64, Vec<u8>)>,
+        pub input: Vec<u8>,
+        pub output: Vec<u8>,
+        pub regions: Vec<StateRegion>,
+        pub events: Vec<VmEvent>,
+    }
+
+    struct HostContext {
+        input: Vec<u8>,
+        output: Vec<u8>,
+        regions: StateRegions,
+        functions: Option<Arc<dyn HostFunctions>>,
+        events: Vec<VmEvent>,
+        /// 已发出事件的主题与数据总字节数
+        event_bytes: usize,
+        /// 整体周期上限；分段执行时机器的上限为本段的暂停点
+        max_cycles: u64,
+    }
+
+    impl HostContext {
+        fn push_event(&mut self, event: VmEvent) {
+            self.event_bytes += event_size(&event);
+            self.events.push(event);
+        }
+    }
+
+    /// 实现 guest ABI 中的输入输出、状态区域与宿主函数调用
+    struct HostSyscalls {
+        host: Arc<Mutex<HostContext>>,
+    }
+
+    impl Syscalls<Inner> for HostSyscalls {
+        fn initialize(&mut self, _machine: &mut Inner) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn ecall(&mut self, machine: &mut Inner) -> Result<bool, Error> {
+            let mut host = self.host.lock().expect("host context poisoned");
+
+            // 系统调用不能中途暂停，按整体上限计费；越过本段暂停点时在下一条指令前暂停
+            let slice = machine.max_cycles();
+            machine.set_max_cycles(host.max_cycles);
+            let handled = host_call(machine, &mut host)?;
+            machine.set_max_cycles(slice);
+
+            match handled {
+                Some(ret) => {
+                    machine.set_register(A0, ret);
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        }
+    }
+
+    /// 执行一次系统调用，返回写入 a0 的值；不认识的调用号返回 `None`
+    ///
+    /// 在宿主与 guest 之间复制或哈希的数据按字节计费，计费先于读取与副作用
+    fn host_call(machine: &mut Inner, host: &mut HostContext) -> Result<Option<u64>, Error> {
+        let registers = machine.registers();
+        let (a0, a1, a2, a3, a4) = (
+            registers[A0],
+            registers[A1],
+            registers[A2],
+            registers[A3],
+            registers[A4],
+        );
+        let number = registers[A7];
+
+        let ret = match number {
+            SYS_INPUT_LENGTH => host.input.len() as u64,
+            SYS_LOAD_INPUT => copy_to_guest(machine, &host.input, a0, a1, a2)?,
+            SYS_WRITE_OUTPUT => {
+                charge_bytes(machine, a1)?;
+                check_output(host, a1)?;
+                let data = machine.memory_mut().load_bytes(a0, a1)?;
+                host.output.extend_from_slice(&data);
+                0
+            }
+            SYS_STATE_READ => {
+                charge_bytes(machine, a1)?;
+                let key = machine.memory_mut().load_bytes(a0, a1)?;
+                match host.regions.get_by_bytes(&key) {
+                    Some(region) => {
+                        copy_to_guest(machine, &region.data, a2, a3, a4)?;
+                        region.data.len() as u64
+                    }
+                    None => STATE_NOT_FOUND,
+                }
+            }
+            SYS_STATE_WRITE => {
+                charge_bytes(machine, a1.saturating_add(a3))?;
+                let key = machine.memory_mut().load_bytes(a0, a1)?;
+                let data = machine.memory_mut().load_bytes(a2, a3)?;
+                host.regions
+                    .write(&key, data.to_vec())
+                    .map_err(|e| Error::External(e.to_string()))?;
+                0
+            }
+            SYS_STORAGE_READ => {
+                charge_bytes(machine, a1)?;
+                let key = machine.memory_mut().load_bytes(a0, a1)?;
+                match host.functions.as_ref().and_then(|f| f.storage_read(&key)) {
+                    Some(value) => {
+                        copy_to_guest(machine, &value, a2, a3, a4)?;
+                        value.len() as u64
+                    }
+                    None => STATE_NOT_FOUND,
+                }
+            }
+            SYS_STORAGE_WRITE => {
+                charge_bytes(machine, a1.saturating_add(a3))?;
+                let key = machine.memory_mut().load_bytes(a0, a1)?;
+                let value = machine.memory_mut().load_bytes(a2, a3)?;
+                let functions = host.functions.as_ref().ok_or_else(|| {
+                    Error::External("No host functions for storage write".to_string())
+                })?;
+                functions
+                    .storage_write(&key, &value)
+                    .map_err(|e| Error::External(e.to_string()))?;
+                0
+            }
+            SYS_EMIT_EVENT => {
+                let size = a1.saturating_add(a3);
+                charge_bytes(machine, size)?;
+                check_events(host, size)?;
+                let topic = machine.memory_mut().load_bytes(a0, a1)?.to_vec();
+                let data = machine.memory_mut().load_bytes(a2, a3)?.to_vec();
+                if let Some(functions) = &host.functions {
+                    functions
+                        .emit_event(&topic, &data)
+                        .map_err(|e| Error::External(e.to_string()))?;
+                }
+                host.push_event(VmEvent { topic, data });
+                0
+            }
+            SYS_KECCAK256 | SYS_BLAKE2B256 => {
+                charge_bytes(machine, a1.saturating_add(32))?;
+                let data = machine.memory_mut().load_bytes(a0, a1)?;
+                let digest = match (&host.functions, number == SYS_KECCAK256) {
+                    (Some(functions), true) => functions.keccak256(&data),
+                    (Some(functions), false) => functions.blake2b256(&data),
+                    (None, true) => host_fns::keccak256(&data),
+                    (None, false) => host_fns::blake2b256(&data),
+                };
+                machine.memory_mut().store_bytes(a2, &digest)?;
+                0
+            }
+            SYS_EVM_INTERPRET => {
+                charge_bytes(machine, a1)?;
+                let code = machine.memory_mut().load_bytes(a0, a1)?;
+                let budget = machine.max_cycles().saturating_sub(machine.cycles());
+                let outcome = evm::interpret(
+                    &code,
+                    &host.input,
+                    host.functions.as_deref(),
+                    budget / EVM_STEP_CYCLES,
+                );
+                match outcome {
+                    Ok(outcome) => {
+                        machine.add_cycles(outcome.steps * EVM_STEP_CYCLES)?;
+                        check_output(host, outcome.output.len() as u64)?;
+                        host.output.extend_from_slice(&outcome.output);
+                        outcome.reverted as u64
+                    }
+                    Err(e) => {
+                        // 步数用尽即剩余周期耗尽，交由 VM 按周期超限结束
+                        if e == EvmError::StepLimit {
+                            machine.add_cycles(budget + 1)?;
+                        }
+                        return Err(Error::External(e.to_string()));
+                    }
+                }
+            }
+            SYS_WASM_INTERPRET => {
+                charge_bytes(machine, a1)?;
+                let code = machine.memory_mut().load_bytes(a0, a1)?;
+                let budget = machine.max_cycles().saturating_sub(machine.cycles());
+                let outcome = wasm::interpret(
+                    &code,
+                    &host.input,
+                    host.functions.clone(),
+                    budget / WASM_FUEL_CYCLES,
+                );
+                match outcome {
+                    Ok(outcome) => {
+                        machine.add_cycles(outcome.fuel_used * WASM_FUEL_CYCLES)?;
+                        check_output(host, outcome.output.len() as u64)?;
+                        let size: usize = outcome.events.iter().map(event_size).sum();
+                        check_events(host, size as u64)?;
+                        host.output.extend_from_slice(&outcome.output);
+                        for event in outcome.events {
+                            host.push_event(event);
+                        }
+                        0
+                    }
+                    Err(e) => {
+                        if e == WasmError::FuelExhausted {
+                            machine.add_cycles(budget + 1)?;
+                        }
+                        return Err(Error::External(e.to_string()));
+                    }
+                }
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(ret))
+    }
+
+    /// 按字节数计费
+    fn charge_bytes(machine: &mut Inner, bytes: u64) -> Result<(), Error> {
+        machine.add_cycles(bytes.saturating_mul(BYTE_CYCLES))
+    }
+
+    fn check_output(host: &HostContext, additional: u64) -> Result<(), Error> {
+        if (host.output.len() as u64).saturating_add(additional) > MAX_OUTPUT_SIZE {
+            return Err(Error::External(format!(
+                "Output exceeds {} bytes",
+                MAX_OUTPUT_SIZE
+            )));
+        }
+        Ok(())
+    }
+
+    fn check_events(host: &HostContext, additional: u64) -> Result<(), Error> {
+        if (host.event_bytes as u64).saturating_add(additional) > MAX_EVENTS_SIZE {
+            return Err(Error::External(format!(
+                "Events exceed {} bytes",
+                MAX_EVENTS_SIZE
+            )));
+        }
+        Ok(())
+    }
+
+    fn event_size(event: &VmEvent) -> usize {
+        event.topic.len() + event.data.len()
+    }
+
+    /// 从 `offset` 处复制至多 `len` 字节到 guest 内存，返回复制的字节数
+    fn copy_to_guest(
+        machine: &mut Inner,
+        data: &[u8],
+        addr: u64,
+        len: u64,
+        offset: u64,
+    ) -> Result<u64, Error> {
+        let start = (offset.min(data.len() as u64)) as usize;
+        let end = start + (len.min((data.len() - start) as u64)) as usize;
+        charge_bytes(machine, (end - start) as u64)?;
+        machine.memory_mut().store_bytes(addr, &data[start..end])?;
+        Ok((end - start) as u64)
+    }
+
+    /// ebreak 视为正常结束（与编译器生成的占位代码约定一致）
+    struct HaltOnEbreak;
+
+    impl Debugger<Inner> for HaltOnEbreak {
+        fn initialize(&mut self, _machine: &mut Inner) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn ebreak(&mut self, machine: &mut Inner) -> Result<(), Error> {
+            machine.set_running(false);
+            Ok(())
+        }
+    }
+
+    /// 代码末尾追加的退出桩：执行流越过最后一条指令时以 0 退出
+    fn exit_stub() -> Vec<u32> {
+        let mut words = vec![riscv::addi(riscv::A0, riscv::ZERO, 0)];
+        words.extend(riscv::syscall(SYS_EXIT));
+        words
+    }
+
+    /// 执行到结束，或在 `suspend_after` 个周期后暂停
+    pub(super) fn run(
+        code: &[u8],
+        entry: Entry<'_>,
+        functions: Option<Arc<dyn HostFunctions>>,
+        limits: &ExecutionLimits,
+        trace: Option<&TraceConfig>,
+        suspend_after: Option<u64>,
+    ) -> Result<Outcome> {
+        let code_hash = host_fns::blake2b256(code);
+        let memory_size = match &entry {
+            Entry::Start { .. } => {
+                let memory_size = limits.max_memory.min(MAX_MEMORY_SIZE);
+                memory_size - memory_size % PAGE_SIZE
+            }
+            Entry::Resume(saved) => {
+                if saved.code_hash != code_hash {
+                    return Err(VmError::SnapshotFailed(
+                        "Machine state was taken from different code".to_string(),
+                    )
+                    .into());
+                }
+                saved.memory_size
+            }
+        };
+
+        let mut image = code.to_vec();
+        image.extend(riscv::assemble(&exit_stub()));
+        let image_size = (image.len() as u64).div_ceil(PAGE_SIZE) * PAGE_SIZE;
+
+        if let Entry::Resume(saved) = &entry {
+            validate_image(saved, CODE_ADDRESS + image_size, limits)?;
+        }
+
+        // 代码段之上至少保留一页栈空间
+        if CODE_ADDRESS + image_size + PAGE_SIZE > memory_size {
+            return Err(VmError::ResourceLimitExceeded(format!(
+                "Program of {} bytes does not fit in {} bytes of memory",
+                code.len(),
+                limits.max_memory
+            ))
+            .into());
+        }
+
+        let (context, frame) = match entry {
+            Entry::Start { input, regions } => {
+                let context = HostContext {
+                    input: input.to_vec(),
+                    output: Vec::new(),
+                    regions,
+                    functions,
+                    events: Vec::new(),
+                    event_bytes: 0,
+                    max_cycles: limits.max_cycles,
+                };
+                (context, None)
+            }
+            Entry::Resume(saved) => {
+                let saved = *saved;
+                let mut regions = StateRegions::new();
+                for region in saved.regions {
+                    regions.load(region);
+                }
+                let context = HostContext {
+                    input: saved.input,
+                    output: saved.output,
+                    regions,
+                    functions,
+                    event_bytes: saved.events.iter().map(event_size).sum(),
+                    events: saved.events,
+                    max_cycles: limits.max_cycles,
+                };
+                let frame = (saved.registers, saved.pc, saved.cycles, saved.pages);
+                (context, Some(frame))
+            }
+        };
+        let host = Arc::new(Mutex::new(context));
+
+        let core = Inner::new_with_memory(ISA_IMC, VERSION2, limits.max_cycles, memory_size as usize);
+        let mut machine = DefaultMachineBuilder::new(core)
+            .instruction_cycle_func(Box::new(estimate_cycles))
+            .syscall(Box::new(HostSyscalls { host: host.clone() }))
+            .debugger(Box::new(HaltOnEbreak))
+            .build();
+
+        machine
+            .memory_mut()
+            .init_pages(
+                CODE_ADDRESS,
+                image_size,
+                FLAG_EXECUTABLE | FLAG_FREEZED,
+                Some(Bytes::from(image)),
+                0,
+            )
+            .map_err(|e| VmError::CodeLoadingFailed(e.to_string()))?;
+        machine.update_pc(CODE_ADDRESS);
+        machine.commit_pc();
+        machine.set_register(SP, memory_size);
+
+        if let Some((registers, pc, cycles, pages)) = frame {
+            for (index, value) in registers.into_iter().enumerate() {
+                machine.set_register(index, value);
+            }
+            machine.update_pc(pc);
+            machine.commit_pc();
+            machine.set_cycles(cycles);
+            for (page, data) in pages {
+                machine
+                    .memory_mut()
+                    .store_bytes(page * PAGE_SIZE, &data)
+                    .map_err(|e| VmError::SnapshotFailed(e.to_string()))?;
+            }
+        }
+
+        // 本段的暂停点早于整体上限时，以它作为机器的周期上限
+        let pause_at = suspend_after
+            .map(|cycles| machine.cycles().saturating_add(cycles))
+            .filter(|&pause_at| pause_at < limits.max_cycles);
+        if let Some(pause_at) = pause_at {
+            machine.set_max_cycles(pause_at);
+        }
+
+        let mut buffer = trace.map(TraceBuffer::new);
+        let exit = match (trace, buffer.as_mut()) {
+            (Some(config), Some(buffer)) => run_traced(&mut machine, config, buffer),
+            _ => machine.run(),
+        };
+        let cycles = machine.cycles();
+
+        // 周期超限发生在计费之前，机器停在尚未执行的指令处，可原样继续
+        let suspended = match &exit {
+            Err(Error::CyclesExceeded { .. }) if machine.max_cycles() < limits.max_cycles => {
+                let mut host = host.lock().expect("host context poisoned");
+                Some(MachineImage {
+                    code_hash,
+                    memory_size,
+                    registers: machine.registers().to_vec(),
+                    pc: *machine.pc(),
+                    cycles,
+                    max_cycles: limits.max_cycles,
+                    pages: dirty_pages(&mut machine, memory_size)
+                        .map_err(|e| VmError::SnapshotFailed(e.to_string()))?,
+                    input: std::mem::take(&mut host.input),
+                    output: std::mem::take(&mut host.output),
+                    regions: host.regions.iter().cloned().collect(),
+                    events: std::mem::take(&mut host.events),
+                })
+            }
+            _ => None,
+        };
+        drop(machine);
+
+        let exit = match exit {
+            Err(Error::CyclesExceeded { .. }) if suspended.is_some() => Ok(0),
+            Ok(code) => Ok(code),
+            Err(Error::CyclesExceeded { .. }) => {
+                return Err(VmError::OutOfGas {
+                    gas_limit: limits.gas_limit(),
+                    gas_used: limits.gas_schedule.cycles_to_gas(cycles),
+                }
+                .into());
+            }
+            Err(e) => Err(e.to_string()),
+        };
+
+        let mut host = host.lock().expect("host context poisoned");
+        Ok(Outcome {
+            exit,
+            cycles,
+            output: std::mem::take(&mut host.output),
+            regions: std::mem::take(&mut host.regions),
+            events: std::mem::take(&mut host.events),
+            trace: buffer.map(TraceBuffer::finish),
+            suspended,
+        })
+    }
+
+    /// 在重建机器之前检查恢复的状态，越界的内存大小、寄存器与页不会进入机器
+    fn validate_image(saved: &MachineImage, code_end: u64, limits: &ExecutionLimits) -> Result<()> {
+        let invalid = |reason: String| anyhow::Error::from(VmError::SnapshotFailed(reason));
+        if saved.registers.len() != RISCV_GENERAL_REGISTER_NUMBER {
+            return Err(invalid(format!(
+                "Machine state has {} registers, expected {}",
+                saved.registers.len(),
+                RISCV_GENERAL_REGISTER_NUMBER
+            )));
+        }
+        if saved.memory_size % PAGE_SIZE != 0
+            || saved.memory_size > limits.max_memory.min(MAX_MEMORY_SIZE)
+        {
+            return Err(invalid(format!(
+                "Machine state memory size {} exceeds the {} byte limit",
+                saved.memory_size, limits.max_memory
+            )));
+        }
+        for (page, data) in &saved.pages {
+            let address = page.saturating_mul(PAGE_SIZE);
+            if address < code_end || address >= saved.memory_size || data.len() as u64 != PAGE_SIZE
+            {
+                return Err(invalid(format!(
+                    "Machine state has an invalid page {}",
+                    page
+                )));
+            }
+        }
+        if saved.output.len() as u64 > MAX_OUTPUT_SIZE {
+            return Err(invalid(
+                "Machine state output exceeds the size limit".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 自加载以来写过的非代码页
+    fn dirty_pages(
+        machine: &mut DefaultMachine<Inner>,
+        memory_size: u64,
+    ) -> Result<Vec<(u64, Vec<u8>)>, Error> {
+        let mut pages = Vec::new();
+        for page in 0..memory_size / PAGE_SIZE {
+            let flag = machine.memory_mut().fetch_flag(page)?;
+            if flag & FLAG_DIRTY != 0 && flag & FLAG_EXECUTABLE == 0 {
+                let address = page * PAGE_SIZE;
+                let data = machine.memory_mut().load_bytes(address, PAGE_SIZE)?;
+                pages.push((page, data.to_vec()));
+            }
+        }
+        Ok(pages)
+    }
+
+    /// 与 `DefaultMachine::run` 相同的执行循环，每步执行前后记录指令与寄存器变化
+    fn run_traced(
+        machine: &mut DefaultMachine<Inner>,
+        config: &TraceConfig,
+        buffer: &mut TraceBuffer,
+    ) -> Result<i8, Error> {
+        let mut decoder = build_decoder::<u64>(machine.isa(), machine.version());
+        machine.set_running(true);
+        while machine.running() {
+            let pc = *machine.pc();
+            let instruction = decoder.decode(machine.memory_mut(), pc)?;
+            let mnemonic = instruction_opcode_name(extract_opcode(instruction)).to_lowercase();
+            let mut step = TraceStep::new(pc, mnemonic);
+            if config.capture_memory_reads {
+                step.memory_reads.extend(memory_read(machine, instruction));
+            }
+            let before = machine.registers().to_vec();
+            buffer.push(step);
+
+            machine.step(&mut decoder)?;
+
+            if config.capture_registers {
+                if let Some(step) = buffer.last_mut() {
+                    for (index, (old, new)) in before.iter().zip(machine.registers()).enumerate() {
+                        if old != new {
+                            step.registers.push(RegisterChange::new(index, *new));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(machine.exit_code())
+    }
+
+    /// load 指令将读取的地址与内容；压缩指令解码后同样是 I 型 load
+    fn memory_read(
+        machine: &mut DefaultMachine<Inner>,
+        instruction: Instruction,
+    ) -> Option<MemoryRead> {
+        let size = match extract_opcode(instruction) {
+            insts::OP_LB | insts::OP_LBU => 1,
+            insts::OP_LH | insts::OP_LHU => 2,
+            insts::OP_LW | insts::OP_LWU => 4,
+            insts::OP_LD => 8,
+            _ => return None,
+        };
+        let itype = Itype(instruction);
+        let address =
+            machine.registers()[itype.rs1()].wrapping_add(itype.immediate_s() as i64 as u64);
+        let data = machine.memory_mut().load_bytes(address, size).ok()?;
+        Some(MemoryRead {
+            address,
+            data: data.to_vec(),
+        })
+    }
 }
 
 // 生产环境集成指南
@@ -205,6 +1140,7 @@ mod integration_notes {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use dubhe_loader::riscv;
 
     #[tokio::test]
     async fn test_ckb_vm_creation() {
@@ -223,12 +1159,712 @@ mod tests {
     #[tokio::test]
     async fn test_ckb_vm_execution() {
         let mut vm = CkbVmInstance::new().unwrap();
-        let code = vec![0x93, 0x02, 0x00, 0x00];
-        vm.load_code(&code).await.unwrap();
+        vm.load_code(&riscv::assemble(&riscv::echo_program()))
+            .await
+            .unwrap();
 
         let input = vec![1, 2, 3, 4];
         let result = vm.execute(&input).await.unwrap();
         assert!(result.success);
-        assert!(!result.output.is_empty());
+        assert_eq!(result.output, input);
+    }
+
+    /// 在 `sp + offset` 处逐字节写入键
+    #[cfg(feature = "ckb-vm")]
+    fn store_key(words: &mut Vec<u32>, key: &[u8], offset: i32) {
+        for (i, byte) in key.iter().enumerate() {
+            words.extend(riscv::li(riscv::T0, *byte as i32));
+            words.push(riscv::sb(riscv::T0, riscv::SP, offset + i as i32));
+        }
+    }
+
+    /// 依次读取两个状态区域并输出其内容
+    #[cfg(feature = "ckb-vm")]
+    fn read_regions_program(keys: &[&[u8]]) -> Vec<u32> {
+        use dubhe_loader::abi::{SYS_EXIT, SYS_STATE_READ, SYS_WRITE_OUTPUT};
+        use riscv::*;
+
+        let mut words = vec![addi(SP, SP, -256)];
+        for (i, key) in keys.iter().enumerate() {
+            store_key(&mut words, key, 16 * i as i32);
+        }
+        for (i, key) in keys.iter().enumerate() {
+            words.push(addi(A0, SP, 16 * i as i32));
+            words.push(addi(A1, ZERO, key.len() as i32));
+            words.push(addi(A2, SP, 64));
+            words.push(addi(A3, ZERO, 128));
+            words.push(addi(A4, ZERO, 0));
+            words.extend(syscall(SYS_STATE_READ));
+            words.push(addi(A1, A0, 0));
+            words.push(addi(A0, SP, 64));
+            words.extend(syscall(SYS_WRITE_OUTPUT));
+        }
+        words.push(addi(A0, ZERO, 0));
+        words.extend(syscall(SYS_EXIT));
+        words
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_guest_reads_state_regions() {
+        let mut vm = CkbVmInstance::new().unwrap();
+        vm.load_code(&riscv::assemble(&read_regions_program(&[b"alice", b"bob"])))
+            .await
+            .unwrap();
+        vm.load_state(StateRegion::read_only("alice", b"balance=10;".to_vec()))
+            .await
+            .unwrap();
+        vm.load_state(StateRegion::read_write("bob", b"balance=32;".to_vec()))
+            .await
+            .unwrap();
+
+        let result = vm.execute(&[]).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, b"balance=10;balance=32;".to_vec());
+        assert!(result.cycles_used > 0);
+
+        // 代码与状态相互独立：重新加载状态不影响代码
+        vm.load_state(StateRegion::read_only("alice", b"x".to_vec()))
+            .await
+            .unwrap();
+        let result = vm.execute(&[]).await.unwrap();
+        assert_eq!(result.output, b"xbalance=32;".to_vec());
+        assert_eq!(vm.state("bob").unwrap().data, b"balance=32;".to_vec());
+    }
+
+    /// 写入存储后读回并输出，再输出值的 Keccak-256 摘要并发出事件
+    #[cfg(feature = "ckb-vm")]
+    fn storage_round_trip_program() -> Vec<u32> {
+        use dubhe_loader::abi::*;
+        use riscv::*;
+
+        let mut words = vec![addi(SP, SP, -256)];
+        store_key(&mut words, b"counter", 0);
+        store_key(&mut words, b"v42", 16);
+        store_key(&mut words, b"set", 32);
+
+        words.extend([addi(A0, SP, 0), addi(A1, ZERO, 7)]);
+        words.extend([addi(A2, SP, 16), addi(A3, ZERO, 3)]);
+        words.extend(syscall(SYS_STORAGE_WRITE));
+
+        words.extend([addi(A0, SP, 0), addi(A1, ZERO, 7), addi(A2, SP, 64)]);
+        words.extend([addi(A3, ZERO, 64), addi(A4, ZERO, 0)]);
+        words.extend(syscall(SYS_STORAGE_READ));
+        words.extend([addi(A1, A0, 0), addi(A0, SP, 64)]);
+        words.extend(syscall(SYS_WRITE_OUTPUT));
+
+        words.extend([addi(A0, SP, 16), addi(A1, ZERO, 3), addi(A2, SP, 128)]);
+        words.extend(syscall(SYS_KECCAK256));
+        words.extend([addi(A0, SP, 128), addi(A1, ZERO, 32)]);
+        words.extend(syscall(SYS_WRITE_OUTPUT));
+
+        words.extend([addi(A0, SP, 32), addi(A1, ZERO, 3)]);
+        words.extend([addi(A2, SP, 16), addi(A3, ZERO, 3)]);
+        words.extend(syscall(SYS_EMIT_EVENT));
+
+        words.push(addi(A0, ZERO, 0));
+        words.extend(syscall(SYS_EXIT));
+        words
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_host_functions_round_trip() {
+        use crate::host::{keccak256, MemoryHost};
+
+        let host = Arc::new(MemoryHost::new());
+        let mut vm = CkbVmInstance::new().unwrap().with_host(host.clone());
+        vm.load_code(&riscv::assemble(&storage_round_trip_program()))
+            .await
+            .unwrap();
+
+        let result = vm.execute(&[]).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        let mut expected = b"v42".to_vec();
+        expected.extend(keccak256(b"v42"));
+        assert_eq!(result.output, expected);
+        assert_eq!(host.get(b"counter"), Some(b"v42".to_vec()));
+
+        let event = VmEvent {
+            topic: b"set".to_vec(),
+            data: b"v42".to_vec(),
+        };
+        assert_eq!(result.events, vec![event.clone()]);
+        assert_eq!(host.events(), vec![event]);
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_storage_write_without_host_fails() {
+        let mut vm = CkbVmInstance::new().unwrap();
+        vm.load_code(&riscv::assemble(&storage_round_trip_program()))
+            .await
+            .unwrap();
+
+        let result = vm.execute(&[]).await.unwrap();
+        assert!(!result.success);
+        assert!(result.events.is_empty());
+    }
+
+    /// 规范化 ABI 中的结构体引用
+    #[cfg(feature = "ckb-vm")]
+    fn struct_ref(address: &str, module: &str, name: &str) -> serde_json::Value {
+        serde_json::json!({"MutableReference": {"Struct": {
+            "address": address,
+            "module": module,
+            "name": name,
+            "typeArguments": []
+        }}})
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_move_entry_dispatch() {
+        use dubhe_adapter::{ChainType, ContractMeta, ContractType};
+        use dubhe_loader::move_compiler::*;
+        use serde_json::json;
+
+        let counter = struct_ref("0xc0de", "counter", "Counter");
+        let ctx = struct_ref("0x2", "tx_context", "TxContext");
+        let abi = json!({
+            "counter": {"exposedFunctions": {
+                "increment": {
+                    "visibility": "Public",
+                    "isEntry": true,
+                    "parameters": [counter, ctx],
+                    "return": []
+                },
+                "set_value": {
+                    "visibility": "Public",
+                    "isEntry": true,
+                    "parameters": [counter, "U64", ctx],
+                    "return": []
+                }
+            }}
+        });
+        let meta = ContractMeta {
+            address: "0xc0de".to_string(),
+            chain_type: ChainType::Sui,
+            contract_type: ContractType::Move,
+            bytecode: vec![],
+            abi: Some(abi.to_string()),
+            source_code: None,
+            compiler_version: Some("move".to_string()),
+            created_at: 0,
+            creator: None,
+            abi_source: None,
+            modules: vec![],
+        };
+        let compiler = MoveToRiscVCompiler::new(MoveCompilerConfig {
+            target_arch: RiscVTarget::RV64IMC,
+            optimization_level: OptimizationLevel::Speed,
+            enable_gas_metering: true,
+            enable_debug_info: false,
+            stackless_bytecode: true,
+        })
+        .unwrap();
+        let contract = compiler.compile_sui_package(&meta).await.unwrap();
+        assert_eq!(
+            contract.entry_points,
+            vec!["counter::increment", "counter::set_value"]
+        );
+
+        let mut vm = CkbVmInstance::new().unwrap();
+        vm.load_code(&contract.risc_v_code).await.unwrap();
+
+        // 函数体输出自身序号与收到的参数
+        let input = contract
+            .encode_entry_call("increment", &[json!("0xc0ffee")])
+            .unwrap();
+        let result = vm.execute(&input).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output[0], 0);
+        assert_eq!(result.output[1..], input[4..]);
+
+        let input = contract
+            .encode_entry_call("set_value", &[json!("0xc0ffee"), json!(42)])
+            .unwrap();
+        let result = vm.execute(&input).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output[0], 1);
+        assert_eq!(result.output[33..], 42u64.to_le_bytes());
+
+        let result = vm.execute(&7u32.to_le_bytes()).await.unwrap();
+        assert!(!result.success);
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_evm_interpreter_bundle() {
+        use dubhe_adapter::{ChainType, ContractMeta, ContractType};
+        use dubhe_loader::CodeLoader;
+
+        // PUSH1 2, PUSH1 3, ADD, MSTORE(0), RETURN(0, 32)
+        let bytecode = vec![
+            0x60, 0x02, 0x60, 0x03, 0x01, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+        ];
+        let meta = ContractMeta {
+            address: "0xadd".to_string(),
+            chain_type: ChainType::Ethereum,
+            contract_type: ContractType::EVM,
+            bytecode,
+            abi: None,
+            source_code: None,
+            compiler_version: None,
+            created_at: 0,
+            creator: None,
+            abi_source: None,
+            modules: vec![],
+        };
+        let cache_dir = tempfile::tempdir().unwrap();
+        let loader = CodeLoader::with_cache_dir(cache_dir.path()).unwrap();
+        let contract = loader.load_contract(&meta).await.unwrap();
+        assert_eq!(contract.artifact, ArtifactKind::InterpreterBundle);
+
+        let mut vm = CkbVmInstance::new().unwrap();
+        assert!(vm.supports_artifact(contract.artifact));
+        vm.load_code(&contract.risc_v_code).await.unwrap();
+
+        let result = vm.execute(&[]).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        let mut expected = [0u8; 32];
+        expected[31] = 5;
+        assert_eq!(result.output, expected);
+
+        // 解释执行同样受 gas 预算约束
+        vm.set_limits(ExecutionLimits {
+            max_cycles: 40,
+            ..ExecutionLimits::default()
+        });
+        let error = vm.execute(&[]).await.unwrap_err();
+        assert!(matches!(error, VmError::OutOfGas { .. }));
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_wasm_interpreter_bundle() {
+        use crate::host::MemoryHost;
+        use dubhe_adapter::{ChainType, ContractMeta, ContractType};
+        use dubhe_loader::CodeLoader;
+        use serde_json::json;
+
+        let meta = ContractMeta {
+            address: "counter.wasm".to_string(),
+            chain_type: ChainType::Ethereum,
+            contract_type: ContractType::WASM,
+            bytecode: wat::parse_str(include_str!("../../../tests/fixtures/counter.wat")).unwrap(),
+            abi: None,
+            source_code: None,
+            compiler_version: None,
+            created_at: 0,
+            creator: None,
+            abi_source: None,
+            modules: vec![],
+        };
+        let cache_dir = tempfile::tempdir().unwrap();
+        let loader = CodeLoader::with_cache_dir(cache_dir.path()).unwrap();
+        let contract = loader.load_contract(&meta).await.unwrap();
+        assert_eq!(contract.artifact, ArtifactKind::InterpreterBundle);
+
+        let host = Arc::new(MemoryHost::new());
+        let mut vm = CkbVmInstance::new().unwrap().with_host(host.clone());
+        vm.load_code(&contract.risc_v_code).await.unwrap();
+
+        // 计数写入宿主存储，跨调用保持
+        let input = contract
+            .encode_entry_call("increment", &[json!(5)])
+            .unwrap();
+        let result = vm.execute(&input).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, 5i64.to_le_bytes());
+        let result = vm.execute(&input).await.unwrap();
+        assert_eq!(result.output, 10i64.to_le_bytes());
+        assert_eq!(host.get(b"count"), Some(10i64.to_le_bytes().to_vec()));
+
+        vm.set_limits(ExecutionLimits {
+            max_cycles: 40,
+            ..ExecutionLimits::default()
+        });
+        let error = vm.execute(&input).await.unwrap_err();
+        assert!(matches!(error, VmError::OutOfGas { .. }));
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_cycle_limit_exceeded() {
+        let mut vm = CkbVmInstance::new().unwrap();
+        vm.set_limits(ExecutionLimits {
+            max_cycles: 2,
+            ..ExecutionLimits::default()
+        });
+        vm.load_code(&riscv::assemble(&riscv::echo_program()))
+            .await
+            .unwrap();
+
+        let error = vm.execute(&[1, 2, 3]).await.unwrap_err();
+        assert!(matches!(error, VmError::OutOfGas { gas_limit: 1, .. }));
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_infinite_loop_runs_out_of_gas() {
+        let schedule = GasSchedule { cycles_per_gas: 4 };
+        let mut vm = CkbVmInstance::new().unwrap();
+        vm.set_limits(ExecutionLimits::for_gas_budget(1_000, schedule));
+        // jal zero, 0：原地跳转
+        vm.load_code(&riscv::assemble(&[riscv::jal(riscv::ZERO, 0)]))
+            .await
+            .unwrap();
+
+        let error = vm.execute(&[]).await.unwrap_err();
+        match error {
+            VmError::OutOfGas {
+                gas_limit,
+                gas_used,
+            } => {
+                assert_eq!(gas_limit, 1_000);
+                assert!(gas_used >= 1_000);
+            }
+            other => panic!("expected OutOfGas, got {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_gas_used_is_deterministic() {
+        let schedule = GasSchedule { cycles_per_gas: 3 };
+        let mut vm = CkbVmInstance::new().unwrap();
+        vm.set_limits(ExecutionLimits::for_gas_budget(1_000_000, schedule));
+        vm.load_code(&riscv::assemble(&riscv::echo_program()))
+            .await
+            .unwrap();
+
+        let first = vm.execute(&[1, 2, 3]).await.unwrap();
+        let second = vm.execute(&[1, 2, 3]).await.unwrap();
+        assert!(first.success);
+        assert!(first.gas_used > 0);
+        assert_eq!(first.gas_used, first.cycles_used.div_ceil(3));
+        assert_eq!(
+            (first.gas_used, first.cycles_used),
+            (second.gas_used, second.cycles_used)
+        );
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_host_copies_are_charged_per_byte() {
+        let mut vm = CkbVmInstance::new().unwrap();
+        vm.load_code(&riscv::assemble(&riscv::echo_program()))
+            .await
+            .unwrap();
+
+        let small = vm.execute(&[7u8; 10]).await.unwrap();
+        let large = vm.execute(&[7u8; 1_010]).await.unwrap();
+        assert!(small.success && large.success);
+        // 读入输入与写出输出各多复制 1000 字节
+        assert_eq!(large.cycles_used - small.cycles_used, 2_000);
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_output_size_is_capped() {
+        use riscv::*;
+
+        // 反复把栈上的 1KB 写到输出，累计超过输出上限
+        let mut body = vec![addi(A0, SP, 0), addi(A1, ZERO, 1024)];
+        body.extend(syscall(dubhe_loader::abi::SYS_WRITE_OUTPUT));
+        body.push(addi(T0, T0, -1));
+        let mut words = li(T0, 1_100);
+        words.push(addi(SP, SP, -1024));
+        words.extend(&body);
+        words.push(bne(T0, ZERO, -4 * body.len() as i32));
+
+        let mut vm = CkbVmInstance::new().unwrap();
+        vm.set_limits(ExecutionLimits {
+            max_cycles: 10_000_000,
+            ..ExecutionLimits::default()
+        });
+        vm.load_code(&riscv::assemble(&words)).await.unwrap();
+
+        let result = vm.execute(&[]).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Output exceeds"));
+    }
+
+    /// 20 条指令的跟踪用例：三轮循环累加、经栈存取后做算术，跳过一条 ebreak 后退出
+    #[cfg(feature = "ckb-vm")]
+    fn trace_fixture() -> Vec<u32> {
+        use riscv::*;
+
+        vec![
+            addi(T0, ZERO, 3),
+            addi(T1, ZERO, 0),
+            addi(T1, T1, 5),
+            addi(T0, T0, -1),
+            bne(T0, ZERO, -8),
+            addi(SP, SP, -16),
+            sd(T1, SP, 0),
+            ld(A1, SP, 0),
+            add(A2, A1, A1),
+            mul(A3, A2, A1),
+            slli(A4, A3, 1),
+            srli(A5, A4, 1),
+            sub(A5, A5, A3),
+            andi(A5, A5, 7),
+            addi(SP, SP, 16),
+            jal(ZERO, 8),
+            ebreak(),
+            addi(A0, ZERO, 0),
+            addi(A7, ZERO, dubhe_loader::abi::SYS_EXIT as i32),
+            ecall(),
+        ]
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_trace_records_exact_pc_sequence() {
+        use crate::trace::{RegisterChange, TraceConfig};
+
+        let fixture = trace_fixture();
+        assert_eq!(fixture.len(), 20);
+        let mut vm = CkbVmInstance::new().unwrap();
+        vm.load_code(&riscv::assemble(&fixture)).await.unwrap();
+
+        let config = TraceConfig {
+            max_steps: 100,
+            capture_memory_reads: true,
+            capture_registers: true,
+        };
+        let (result, trace) = vm.execute_traced(&[], &config).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+
+        let pc = |index: u64| machine::CODE_ADDRESS + 4 * index;
+        let mut expected = vec![pc(0), pc(1)];
+        for _ in 0..3 {
+            expected.extend([pc(2), pc(3), pc(4)]);
+        }
+        expected.extend((5..=15).map(pc));
+        expected.extend([pc(17), pc(18), pc(19)]);
+        assert_eq!(trace.pcs(), expected);
+        assert_eq!(trace.total_steps, 25);
+        assert!(!trace.truncated);
+
+        let steps: Vec<_> = trace.steps.iter().collect();
+        assert_eq!(steps[0].opcode, "addi");
+        assert_eq!(steps[0].registers, vec![RegisterChange::new(5, 3)]);
+        assert_eq!(steps[4].opcode, "bne");
+        assert!(steps[4].registers.is_empty());
+        // ld a1, 0(sp) 读出循环累加的 15
+        let load = steps[13];
+        assert_eq!(load.opcode, "ld");
+        assert_eq!(load.memory_reads[0].data, 15u64.to_le_bytes());
+        assert_eq!(load.registers, vec![RegisterChange::new(11, 15)]);
+        assert_eq!(steps[24].opcode, "ecall");
+
+        // 环形缓冲区只保留最后的步骤
+        let config = TraceConfig {
+            max_steps: 5,
+            ..TraceConfig::default()
+        };
+        let (_, trace) = vm.execute_traced(&[], &config).await.unwrap();
+        assert!(trace.truncated);
+        assert_eq!(trace.total_steps, 25);
+        assert_eq!(trace.pcs(), expected[20..]);
+        assert!(trace.steps.iter().all(|step| step.memory_reads.is_empty()));
+
+        // 跟踪不改变执行结果
+        assert_eq!(
+            vm.execute(&[]).await.unwrap().cycles_used,
+            result.cycles_used
+        );
+    }
+
+    /// 把 `iterations..=1` 逐次累加到栈上，最后写出 8 字节的和
+    #[cfg(feature = "ckb-vm")]
+    fn counting_loop(iterations: i32) -> Vec<u32> {
+        use riscv::*;
+
+        let mut words = li(T0, iterations);
+        words.extend([
+            addi(T1, ZERO, 0),
+            addi(SP, SP, -16),
+            add(T1, T1, T0),
+            sd(T1, SP, 0),
+            addi(T0, T0, -1),
+            bne(T0, ZERO, -12),
+            addi(A0, SP, 0),
+            addi(A1, ZERO, 8),
+        ]);
+        words.extend(syscall(dubhe_loader::abi::SYS_WRITE_OUTPUT));
+        words
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    async fn resume_to_completion(
+        vm: &mut CkbVmInstance,
+        mut status: ExecutionStatus,
+    ) -> (ExecutionResult, usize) {
+        let mut suspensions = 0;
+        loop {
+            match status {
+                ExecutionStatus::Completed(result) => return (result, suspensions),
+                ExecutionStatus::Suspended(suspended) => {
+                    suspensions += 1;
+                    // 只有栈所在的一页被写过
+                    let size = suspended.state.len();
+                    assert!(size < 2 * 4096, "{}", size);
+                    status = vm.resume(suspended.state, 0).await.unwrap();
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_suspend_and_resume_matches_uninterrupted_run() {
+        let mut vm = CkbVmInstance::new().unwrap();
+        vm.load_code(&riscv::assemble(&counting_loop(2_000)))
+            .await
+            .unwrap();
+
+        let uninterrupted = vm.execute(&[]).await.unwrap();
+        assert!(uninterrupted.success, "{:?}", uninterrupted.error);
+        assert_eq!(uninterrupted.output, 2_001_000u64.to_le_bytes());
+
+        vm.suspend_after(1_000);
+        let status = vm.execute_resumable(&[]).await.unwrap();
+        let ExecutionStatus::Suspended(first) = &status else {
+            panic!("expected suspension, got {:?}", status);
+        };
+        assert!(first.cycles_used <= 1_000);
+
+        let (result, suspensions) = resume_to_completion(&mut vm, status).await;
+        assert!(suspensions > 1);
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, uninterrupted.output);
+        assert_eq!(result.cycles_used, uninterrupted.cycles_used);
+        assert_eq!(result.gas_used, uninterrupted.gas_used);
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_tampered_machine_state_is_rejected() {
+        let mut vm = CkbVmInstance::new().unwrap();
+        vm.load_code(&riscv::assemble(&counting_loop(2_000)))
+            .await
+            .unwrap();
+        vm.suspend_after(500);
+        let status = vm.execute_resumable(&[]).await.unwrap();
+        let ExecutionStatus::Suspended(suspended) = status else {
+            panic!("expected suspension");
+        };
+
+        let mut bytes = suspended.state.as_bytes().to_vec();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let error = vm
+            .resume(MachineState::from_bytes(bytes), 0)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<VmError>(),
+            Some(VmError::SnapshotFailed(_))
+        ));
+
+        // 加载了其他代码的实例不能继续该状态
+        let mut other = CkbVmInstance::new().unwrap();
+        other
+            .load_code(&riscv::assemble(&counting_loop(3)))
+            .await
+            .unwrap();
+        assert!(other.resume(suspended.state.clone(), 0).await.is_err());
+
+        // 原状态不受影响
+        let status = vm.resume(suspended.state, 0).await.unwrap();
+        assert!(matches!(status, ExecutionStatus::Suspended(_)));
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_machine_state_is_keyed_and_validated() {
+        let code = riscv::assemble(&counting_loop(2_000));
+        let mut vm = CkbVmInstance::new().unwrap().with_snapshot_key([7; 32]);
+        vm.load_code(&code).await.unwrap();
+        vm.suspend_after(500);
+        let status = vm.execute_resumable(&[]).await.unwrap();
+        let ExecutionStatus::Suspended(suspended) = status else {
+            panic!("expected suspension");
+        };
+        let image = suspended.state.open(&[7; 32]).unwrap();
+
+        let is_snapshot_error = |error: anyhow::Error| {
+            matches!(
+                error.downcast_ref::<VmError>(),
+                Some(VmError::SnapshotFailed(_))
+            )
+        };
+
+        // 用无密钥摘要重新封存的状态被拒绝
+        let payload = bincode::serialize(&image).unwrap();
+        let mut bytes = crate::host::blake2b256(&payload).to_vec();
+        bytes.extend(payload);
+        let error = vm
+            .resume(MachineState::from_bytes(bytes), 0)
+            .await
+            .unwrap_err();
+        assert!(is_snapshot_error(error));
+
+        // 即使摘要有效，越界的寄存器、内存大小与页也不会进入机器
+        let mut registers = image.clone();
+        registers.registers.push(0);
+        let mut memory = image.clone();
+        memory.memory_size = u64::MAX - 4095;
+        let mut page = image.clone();
+        page.pages.push((0, vec![0; 4096]));
+        for tampered in [registers, memory, page] {
+            let state = MachineState::seal(&tampered, &[7; 32]).unwrap();
+            assert!(is_snapshot_error(vm.resume(state, 0).await.unwrap_err()));
+        }
+
+        // 配置相同密钥的其他实例可以继续，默认密钥的实例不行
+        let mut other = CkbVmInstance::new().unwrap();
+        other.load_code(&code).await.unwrap();
+        let error = other.resume(suspended.state.clone(), 0).await.unwrap_err();
+        assert!(is_snapshot_error(error));
+
+        let mut other = CkbVmInstance::new().unwrap().with_snapshot_key([7; 32]);
+        other.load_code(&code).await.unwrap();
+        let status = other.resume(suspended.state, 0).await.unwrap();
+        let (result, _) = resume_to_completion(&mut other, status).await;
+        assert_eq!(result.output, 2_001_000u64.to_le_bytes());
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_resume_extends_gas_budget() {
+        let schedule = GasSchedule { cycles_per_gas: 2 };
+        let mut vm = CkbVmInstance::new().unwrap();
+        vm.set_limits(ExecutionLimits::for_gas_budget(300, schedule));
+        vm.load_code(&riscv::assemble(&counting_loop(2_000)))
+            .await
+            .unwrap();
+        vm.suspend_after(500);
+
+        let status = vm.execute_resumable(&[]).await.unwrap();
+        let ExecutionStatus::Suspended(suspended) = status else {
+            panic!("expected suspension");
+        };
+        assert_eq!(suspended.remaining_gas, (600 - suspended.cycles_used) / 2);
+
+        // 剩余预算不足以跑完
+        let error = vm.resume(suspended.state.clone(), 0).await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<VmError>(),
+            Some(VmError::OutOfGas { .. })
+        ));
+
+        let status = vm.resume(suspended.state, 1_000_000).await.unwrap();
+        let (result, _) = resume_to_completion(&mut vm, status).await;
+        assert_eq!(result.output, 2_001_000u64.to_le_bytes());
     }
 }
@@ -6,7 +6,8 @@ use anyhow::Result;
 use async_trait::async_trait;
 use tracing::{debug, info, warn};
 
-use crate::error::VmError;
+use crate::error::{VmError, VmResult};
+use crate::host::StateRegions;
 use crate::traits::VmInstance;
 use crate::types::*;
 
@@ -18,6 +19,7 @@ pub struct CompleteCkbVmInstance {
     memory_size: usize,
     cycle_count: u64,
     registers: [u64; 32], // RISC-V 寄存器
+    regions: StateRegions,
 }
 
 impl CompleteCkbVmInstance {
@@ -31,6 +33,7 @@ impl CompleteCkbVmInstance {
             memory_size: 0,
             cycle_count: 0,
             registers: [0u64; 32],
+            regions: StateRegions::new(),
         })
     }
 
@@ -210,22 +213,23 @@ impl CompleteCkbVmInstance {
             } else {
                 Some(format!("Non-zero exit code: {}", return_value))
             },
+            events: vec![],
         }
     }
 }
 
 #[async_trait]
 impl VmInstance for CompleteCkbVmInstance {
-    async fn load_code(&mut self, code: &[u8]) -> Result<()> {
+    async fn load_code(&mut self, code: &[u8]) -> VmResult<()> {
         if code.is_empty() {
-            return Err(VmError::CodeLoadingFailed("Empty code".to_string()).into());
+            return Err(VmError::CodeLoadingFailed("Empty code".to_string()));
         }
 
         // 验证代码是否为 4 字节对齐
         if code.len() % 4 != 0 {
-            return Err(
-                VmError::CodeLoadingFailed("Code must be 4-byte aligned".to_string()).into(),
-            );
+            return Err(VmError::CodeLoadingFailed(
+                "Code must be 4-byte aligned".to_string(),
+            ));
         }
 
         info!("Loading {} bytes of RISC-V code", code.len());
@@ -242,9 +246,15 @@ impl VmInstance for CompleteCkbVmInstance {
         Ok(())
     }
 
-    async fn execute(&mut self, input: &[u8]) -> Result<ExecutionResult> {
+    async fn load_state(&mut self, region: StateRegion) -> VmResult<()> {
+        // 该示例解释器不支持系统调用，仅保存区域
+        self.regions.load(region);
+        Ok(())
+    }
+
+    async fn execute(&mut self, input: &[u8]) -> VmResult<ExecutionResult> {
         if !self.code_loaded {
-            return Err(VmError::ExecutionFailed("No code loaded".to_string()).into());
+            return Err(VmError::ExecutionFailed("No code loaded".to_string()));
         }
 
         info!("Executing RISC-V code with {} bytes input", input.len());
@@ -280,7 +290,9 @@ impl VmInstance for CompleteCkbVmInstance {
 
             // 检查超时
             if self.cycle_count > self.limits.max_cycles {
-                return Err(VmError::ResourceLimitExceeded("Execution timeout".to_string()).into());
+                return Err(VmError::ResourceLimitExceeded(
+                    "Execution timeout".to_string(),
+                ));
             }
         }
 
@@ -293,7 +305,7 @@ impl VmInstance for CompleteCkbVmInstance {
         Ok(result)
     }
 
-    async fn snapshot(&self) -> Result<VmSnapshot> {
+    async fn snapshot(&self) -> VmResult<VmSnapshot> {
         debug!("Creating VM snapshot");
 
         let snapshot_data = bincode::serialize(&VmState {
@@ -301,7 +313,8 @@ impl VmInstance for CompleteCkbVmInstance {
             cycle_count: self.cycle_count,
             memory_size: self.memory_size,
             code_loaded: self.code_loaded,
-        })?;
+        })
+        .map_err(|e| VmError::SnapshotFailed(e.to_string()))?;
 
         Ok(VmSnapshot {
             data: snapshot_data,
@@ -309,14 +322,15 @@ impl VmInstance for CompleteCkbVmInstance {
         })
     }
 
-    async fn restore(&mut self, snapshot: &VmSnapshot) -> Result<()> {
+    async fn restore(&mut self, snapshot: &VmSnapshot) -> VmResult<()> {
         if snapshot.vm_type != VmType::CkbVM {
-            return Err(VmError::SnapshotFailed("VM type mismatch".to_string()).into());
+            return Err(VmError::SnapshotFailed("VM type mismatch".to_string()));
         }
 
         debug!("Restoring VM from snapshot");
 
-        let state: VmState = bincode::deserialize(&snapshot.data)?;
+        let state: VmState = bincode::deserialize(&snapshot.data)
+            .map_err(|e| VmError::SnapshotFailed(e.to_string()))?;
 
         self.registers = state.registers;
         self.cycle_count = state.cycle_count;
@@ -1,7 +1,12 @@
 //! VM Runtime 错误类型
+//!
+//! VM 实例与宿主函数接口返回 [`VmResult`]。未分类的失败经 `From<anyhow::Error>`
+//! 归入 [`VmError::Internal`] 并保留原始错误链；链上已是 `VmError` 的错误原样取出
 
 use thiserror::Error;
 
+use crate::types::VmType;
+
 #[derive(Error, Debug)]
 pub enum VmError {
     #[error("Execution failed: {0}")]
@@ -18,4 +23,34 @@ pub enum VmError {
 
     #[error("Resource limit exceeded: {0}")]
     ResourceLimitExceeded(String),
+
+    #[error("Out of gas: used {gas_used} of {gas_limit}")]
+    OutOfGas { gas_limit: u64, gas_used: u64 },
+
+    #[error("Unsupported VM type: {0:?}")]
+    UnsupportedVm(VmType),
+
+    #[error("Host functions are not supported by {0:?}")]
+    HostFunctionsUnsupported(VmType),
+
+    #[error("Execution tracing is not supported by {0:?}")]
+    TracingUnsupported(VmType),
+
+    #[error("Instance reset is not supported by {0:?}")]
+    ResetUnsupported(VmType),
+
+    /// 未分类的内部错误
+    #[error(transparent)]
+    Internal(anyhow::Error),
+}
+
+pub type VmResult<T> = std::result::Result<T, VmError>;
+
+impl From<anyhow::Error> for VmError {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast::<VmError>() {
+            Ok(error) => error,
+            Err(error) => Self::Internal(error),
+        }
+    }
 }
@@ -0,0 +1,548 @@
+//! 宿主侧 EVM 解释器
+//!
+//! 执行解释器包（`ArtifactKind::InterpreterBundle`）：引导代码通过
+//! `SYS_EVM_INTERPRET` 把原始字节码交给这里。只覆盖常用操作码，
+//! 其余按无效指令处理，待真正的 EVM → RISC-V 转译实现后替换
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use thiserror::Error;
+
+use crate::host;
+use crate::traits::HostFunctions;
+
+/// 每条 EVM 指令折算的 VM 周期数
+pub const EVM_STEP_CYCLES: u64 = 8;
+
+const STACK_LIMIT: usize = 1024;
+const MEMORY_LIMIT: usize = 1024 * 1024;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EvmError {
+    #[error("EVM step limit reached")]
+    StepLimit,
+
+    #[error("EVM stack underflow at pc {0}")]
+    StackUnderflow(usize),
+
+    #[error("EVM stack overflow at pc {0}")]
+    StackOverflow(usize),
+
+    #[error("Invalid jump destination at pc {0}")]
+    InvalidJump(usize),
+
+    #[error("Invalid or unsupported opcode 0x{opcode:02x} at pc {pc}")]
+    InvalidOpcode { opcode: u8, pc: usize },
+
+    #[error("EVM memory limit exceeded")]
+    MemoryLimit,
+
+    #[error("Host storage error: {0}")]
+    Host(String),
+}
+
+/// 一次解释执行的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvmOutcome {
+    /// 以 `REVERT` 结束
+    pub reverted: bool,
+    pub output: Vec<u8>,
+    /// 执行的指令数
+    pub steps: u64,
+}
+
+/// 256 位无符号整数，小端 limb
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct U256([u64; 4]);
+
+impl U256 {
+    const ZERO: Self = U256([0; 4]);
+    const ONE: Self = U256([1, 0, 0, 0]);
+
+    fn from_u64(value: u64) -> Self {
+        U256([value, 0, 0, 0])
+    }
+
+    /// 大端字节（不足 32 字节左侧补零，超出取末尾 32 字节）
+    fn from_be_slice(bytes: &[u8]) -> Self {
+        let bytes = &bytes[bytes.len().saturating_sub(32)..];
+        let mut buf = [0u8; 32];
+        buf[32 - bytes.len()..].copy_from_slice(bytes);
+        let mut limbs = [0u64; 4];
+        for (i, chunk) in buf.chunks(8).enumerate() {
+            limbs[3 - i] = u64::from_be_bytes(chunk.try_into().expect("8-byte chunk"));
+        }
+        U256(limbs)
+    }
+
+    fn to_be_bytes(self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (i, chunk) in out.chunks_mut(8).enumerate() {
+            chunk.copy_from_slice(&self.0[3 - i].to_be_bytes());
+        }
+        out
+    }
+
+    fn is_zero(self) -> bool {
+        self == Self::ZERO
+    }
+
+    fn from_bool(value: bool) -> Self {
+        if value {
+            Self::ONE
+        } else {
+            Self::ZERO
+        }
+    }
+
+    fn as_usize(self) -> Option<usize> {
+        if self.0[1..].iter().any(|limb| *limb != 0) {
+            return None;
+        }
+        usize::try_from(self.0[0]).ok()
+    }
+
+    fn bit(self, index: usize) -> bool {
+        (self.0[index / 64] >> (index % 64)) & 1 == 1
+    }
+
+    fn wrapping_add(self, other: Self) -> Self {
+        let mut out = [0u64; 4];
+        let mut carry = false;
+        for (i, limb) in out.iter_mut().enumerate() {
+            let (sum, c1) = self.0[i].overflowing_add(other.0[i]);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+        U256(out)
+    }
+
+    fn wrapping_sub(self, other: Self) -> Self {
+        let mut out = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in out.iter_mut().enumerate() {
+            let (diff, b1) = self.0[i].overflowing_sub(other.0[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = b1 || b2;
+        }
+        U256(out)
+    }
+
+    fn wrapping_mul(self, other: Self) -> Self {
+        let mut out = [0u64; 4];
+        for (i, &a) in self.0.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, &b) in other.0.iter().take(4 - i).enumerate() {
+                let cur = out[i + j] as u128 + a as u128 * b as u128 + carry;
+                out[i + j] = cur as u64;
+                carry = cur >> 64;
+            }
+        }
+        U256(out)
+    }
+
+    /// 除数为零时商与余数均为零（EVM 语义）
+    fn div_rem(self, divisor: Self) -> (Self, Self) {
+        if divisor.is_zero() {
+            return (Self::ZERO, Self::ZERO);
+        }
+        let mut quotient = Self::ZERO;
+        let mut remainder = Self::ZERO;
+        for i in (0..256).rev() {
+            // 左移溢出时真实余数已超过 2^256，必然不小于除数
+            let overflow = remainder.bit(255);
+            remainder = remainder.shl(1);
+            if self.bit(i) {
+                remainder.0[0] |= 1;
+            }
+            if overflow || remainder >= divisor {
+                remainder = remainder.wrapping_sub(divisor);
+                quotient.0[i / 64] |= 1 << (i % 64);
+            }
+        }
+        (quotient, remainder)
+    }
+
+    fn shl(self, shift: usize) -> Self {
+        if shift >= 256 {
+            return Self::ZERO;
+        }
+        let (limbs, bits) = (shift / 64, shift % 64);
+        U256(std::array::from_fn(|i| {
+            if i < limbs {
+                return 0;
+            }
+            let mut limb = self.0[i - limbs] << bits;
+            if bits > 0 && i > limbs {
+                limb |= self.0[i - limbs - 1] >> (64 - bits);
+            }
+            limb
+        }))
+    }
+
+    fn shr(self, shift: usize) -> Self {
+        if shift >= 256 {
+            return Self::ZERO;
+        }
+        let (limbs, bits) = (shift / 64, shift % 64);
+        U256(std::array::from_fn(|i| {
+            if i + limbs >= 4 {
+                return 0;
+            }
+            let mut limb = self.0[i + limbs] >> bits;
+            if bits > 0 && i + limbs + 1 < 4 {
+                limb |= self.0[i + limbs + 1] << (64 - bits);
+            }
+            limb
+        }))
+    }
+
+    fn map_limbs(self, other: Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        U256(std::array::from_fn(|i| f(self.0[i], other.0[i])))
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 解释器状态
+struct Machine<'a> {
+    stack: Vec<U256>,
+    memory: Vec<u8>,
+    /// 本次执行内的存储写入，读取时优先于宿主
+    storage: HashMap<[u8; 32], [u8; 32]>,
+    functions: Option<&'a dyn HostFunctions>,
+    pc: usize,
+}
+
+impl Machine<'_> {
+    fn pop(&mut self) -> Result<U256, EvmError> {
+        self.stack.pop().ok_or(EvmError::StackUnderflow(self.pc))
+    }
+
+    fn push(&mut self, value: U256) -> Result<(), EvmError> {
+        if self.stack.len() >= STACK_LIMIT {
+            return Err(EvmError::StackOverflow(self.pc));
+        }
+        self.stack.push(value);
+        Ok(())
+    }
+
+    fn binary(&mut self, f: impl FnOnce(U256, U256) -> U256) -> Result<(), EvmError> {
+        let a = self.pop()?;
+        let b = self.pop()?;
+        self.push(f(a, b))
+    }
+
+    /// 按需扩展内存（32 字节对齐），返回 `[start, end)`
+    fn memory_range(&mut self, offset: U256, len: U256) -> Result<(usize, usize), EvmError> {
+        if len.is_zero() {
+            return Ok((0, 0));
+        }
+        let start = offset.as_usize().ok_or(EvmError::MemoryLimit)?;
+        let end = len
+            .as_usize()
+            .and_then(|len| start.checked_add(len))
+            .filter(|end| *end <= MEMORY_LIMIT)
+            .ok_or(EvmError::MemoryLimit)?;
+        let size = end.div_ceil(32) * 32;
+        if self.memory.len() < size {
+            self.memory.resize(size, 0);
+        }
+        Ok((start, end))
+    }
+
+    fn sload(&self, key: &[u8; 32]) -> U256 {
+        if let Some(value) = self.storage.get(key) {
+            return U256::from_be_slice(value);
+        }
+        self.functions
+            .and_then(|functions| functions.storage_read(key))
+            .map(|value| U256::from_be_slice(&value))
+            .unwrap_or_default()
+    }
+
+    fn sstore(&mut self, key: [u8; 32], value: [u8; 32]) -> Result<(), EvmError> {
+        if let Some(functions) = self.functions {
+            functions
+                .storage_write(&key, &value)
+                .map_err(|e| EvmError::Host(e.to_string()))?;
+        }
+        self.storage.insert(key, value);
+        Ok(())
+    }
+}
+
+/// 有效跳转目标（跳过 PUSH 数据中的 0x5b）
+fn jump_destinations(code: &[u8]) -> Vec<bool> {
+    let mut valid = vec![false; code.len()];
+    let mut pc = 0;
+    while pc < code.len() {
+        match code[pc] {
+            0x5b => valid[pc] = true,
+            op @ 0x60..=0x7f => pc += (op - 0x5f) as usize,
+            _ => {}
+        }
+        pc += 1;
+    }
+    valid
+}
+
+/// 以 `calldata` 执行 `code`，至多执行 `max_steps` 条指令
+pub fn interpret(
+    code: &[u8],
+    calldata: &[u8],
+    functions: Option<&dyn HostFunctions>,
+    max_steps: u64,
+) -> Result<EvmOutcome, EvmError> {
+    let jumpdests = jump_destinations(code);
+    let mut m = Machine {
+        stack: Vec::new(),
+        memory: Vec::new(),
+        storage: HashMap::new(),
+        functions,
+        pc: 0,
+    };
+    let mut steps = 0u64;
+    let finish = |reverted, output, steps| {
+        Ok(EvmOutcome {
+            reverted,
+            output,
+            steps,
+        })
+    };
+
+    loop {
+        // 越过代码末尾等同 STOP
+        let Some(&op) = code.get(m.pc) else {
+            return finish(false, vec![], steps);
+        };
+        if steps >= max_steps {
+            return Err(EvmError::StepLimit);
+        }
+        steps += 1;
+        let pc = m.pc;
+        m.pc += 1;
+
+        match op {
+            0x00 => return finish(false, vec![], steps),
+            0x01 => m.binary(|a, b| a.wrapping_add(b))?,
+            0x02 => m.binary(|a, b| a.wrapping_mul(b))?,
+            0x03 => m.binary(|a, b| a.wrapping_sub(b))?,
+            0x04 => m.binary(|a, b| a.div_rem(b).0)?,
+            0x06 => m.binary(|a, b| a.div_rem(b).1)?,
+            0x10 => m.binary(|a, b| U256::from_bool(a < b))?,
+            0x11 => m.binary(|a, b| U256::from_bool(a > b))?,
+            0x14 => m.binary(|a, b| U256::from_bool(a == b))?,
+            0x15 => {
+                let a = m.pop()?;
+                m.push(U256::from_bool(a.is_zero()))?;
+            }
+            0x16 => m.binary(|a, b| a.map_limbs(b, |x, y| x & y))?,
+            0x17 => m.binary(|a, b| a.map_limbs(b, |x, y| x | y))?,
+            0x18 => m.binary(|a, b| a.map_limbs(b, |x, y| x ^ y))?,
+            0x19 => {
+                let a = m.pop()?;
+                m.push(a.map_limbs(U256::ZERO, |x, _| !x))?;
+            }
+            0x1b => m.binary(|shift, value| value.shl(shift.as_usize().unwrap_or(256)))?,
+            0x1c => m.binary(|shift, value| value.shr(shift.as_usize().unwrap_or(256)))?,
+            // KECCAK256
+            0x20 => {
+                let (offset, len) = (m.pop()?, m.pop()?);
+                let (start, end) = m.memory_range(offset, len)?;
+                let data = &m.memory[start..end];
+                let digest = match m.functions {
+                    Some(functions) => functions.keccak256(data),
+                    None => host::keccak256(data),
+                };
+                m.push(U256::from_be_slice(&digest))?;
+            }
+            // CALLER / CALLVALUE：只读调用中均为零
+            0x33 | 0x34 => m.push(U256::ZERO)?,
+            // CALLDATALOAD
+            0x35 => {
+                let offset = m.pop()?.as_usize().unwrap_or(usize::MAX);
+                let mut word = [0u8; 32];
+                if offset < calldata.len() {
+                    let available = (calldata.len() - offset).min(32);
+                    word[..available].copy_from_slice(&calldata[offset..offset + available]);
+                }
+                m.push(U256::from_be_slice(&word))?;
+            }
+            0x36 => m.push(U256::from_u64(calldata.len() as u64))?,
+            // CALLDATACOPY
+            0x37 => {
+                let (dest, offset, len) = (m.pop()?, m.pop()?, m.pop()?);
+                let (start, end) = m.memory_range(dest, len)?;
+                let offset = offset.as_usize().unwrap_or(usize::MAX);
+                for (i, byte) in m.memory[start..end].iter_mut().enumerate() {
+                    *byte = offset
+                        .checked_add(i)
+                        .and_then(|index| calldata.get(index))
+                        .copied()
+                        .unwrap_or(0);
+                }
+            }
+            0x50 => {
+                m.pop()?;
+            }
+            // MLOAD
+            0x51 => {
+                let offset = m.pop()?;
+                let (start, end) = m.memory_range(offset, U256::from_u64(32))?;
+                let value = U256::from_be_slice(&m.memory[start..end]);
+                m.push(value)?;
+            }
+            // MSTORE
+            0x52 => {
+                let (offset, value) = (m.pop()?, m.pop()?);
+                let (start, end) = m.memory_range(offset, U256::from_u64(32))?;
+                m.memory[start..end].copy_from_slice(&value.to_be_bytes());
+            }
+            // MSTORE8
+            0x53 => {
+                let (offset, value) = (m.pop()?, m.pop()?);
+                let (start, _) = m.memory_range(offset, U256::ONE)?;
+                m.memory[start] = value.0[0] as u8;
+            }
+            0x54 => {
+                let key = m.pop()?.to_be_bytes();
+                let value = m.sload(&key);
+                m.push(value)?;
+            }
+            0x55 => {
+                let (key, value) = (m.pop()?, m.pop()?);
+                m.sstore(key.to_be_bytes(), value.to_be_bytes())?;
+            }
+            // JUMP / JUMPI
+            0x56 | 0x57 => {
+                let dest = m.pop()?;
+                let taken = op == 0x56 || !m.pop()?.is_zero();
+                if taken {
+                    let dest = dest
+                        .as_usize()
+                        .filter(|dest| jumpdests.get(*dest).copied().unwrap_or(false))
+                        .ok_or(EvmError::InvalidJump(pc))?;
+                    m.pc = dest;
+                }
+            }
+            0x58 => m.push(U256::from_u64(pc as u64))?,
+            0x59 => m.push(U256::from_u64(m.memory.len() as u64))?,
+            0x5a => m.push(U256::from_u64(max_steps - steps))?,
+            0x5b => {}
+            // PUSH0 - PUSH32，越过代码末尾的部分按零补齐
+            0x5f..=0x7f => {
+                let len = (op - 0x5f) as usize;
+                let mut word = vec![0u8; len];
+                let available = code.len().saturating_sub(m.pc).min(len);
+                word[..available].copy_from_slice(&code[m.pc..m.pc + available]);
+                m.pc += len;
+                m.push(U256::from_be_slice(&word))?;
+            }
+            // DUP1 - DUP16
+            0x80..=0x8f => {
+                let depth = (op - 0x7f) as usize;
+                let value = *m
+                    .stack
+                    .len()
+                    .checked_sub(depth)
+                    .and_then(|index| m.stack.get(index))
+                    .ok_or(EvmError::StackUnderflow(pc))?;
+                m.push(value)?;
+            }
+            // SWAP1 - SWAP16
+            0x90..=0x9f => {
+                let depth = (op - 0x8f) as usize;
+                let top = m.stack.len().checked_sub(1);
+                let other = m.stack.len().checked_sub(depth + 1);
+                match (top, other) {
+                    (Some(top), Some(other)) => m.stack.swap(top, other),
+                    _ => return Err(EvmError::StackUnderflow(pc)),
+                }
+            }
+            // RETURN / REVERT
+            0xf3 | 0xfd => {
+                let (offset, len) = (m.pop()?, m.pop()?);
+                let (start, end) = m.memory_range(offset, len)?;
+                return finish(op == 0xfd, m.memory[start..end].to_vec(), steps);
+            }
+            _ => return Err(EvmError::InvalidOpcode { opcode: op, pc }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::MemoryHost;
+
+    fn word(value: u64) -> Vec<u8> {
+        U256::from_u64(value).to_be_bytes().to_vec()
+    }
+
+    #[test]
+    fn test_u256_arithmetic() {
+        let max = U256([u64::MAX; 4]);
+        assert_eq!(max.wrapping_add(U256::ONE), U256::ZERO);
+        assert_eq!(U256::ZERO.wrapping_sub(U256::ONE), max);
+        let big = U256::ONE.shl(200);
+        let product = big.wrapping_mul(U256::from_u64(3));
+        assert_eq!(product.div_rem(big), (U256::from_u64(3), U256::ZERO));
+        assert_eq!(max.div_rem(max), (U256::ONE, U256::ZERO));
+        assert_eq!(big.shr(199), U256::from_u64(2));
+    }
+
+    #[test]
+    fn test_loop_with_storage() {
+        // i = 0; while i < calldata[0..32] { i += 1 } ; sstore(0, i); return i
+        let code = [
+            0x60, 0x00, // PUSH1 0            i
+            0x5b, // JUMPDEST (2)
+            0x80, // DUP1
+            0x60, 0x00, 0x35, // CALLDATALOAD(0)
+            0x11, // GT: n > i
+            0x15, 0x60, 0x13, 0x57, // ISZERO, PUSH1 19, JUMPI
+            0x60, 0x01, 0x01, // i + 1
+            0x60, 0x02, 0x56, // JUMP 2
+            0x00, // padding (18)
+            0x5b, // JUMPDEST (19)
+            0x80, 0x60, 0x00, 0x55, // SSTORE(0, i)
+            0x60, 0x00, 0x52, // MSTORE(0, i)
+            0x60, 0x20, 0x60, 0x00, 0xf3, // RETURN(0, 32)
+        ];
+        let host = MemoryHost::new();
+        let outcome = interpret(&code, &word(5), Some(&host), 1_000).unwrap();
+        assert!(!outcome.reverted);
+        assert_eq!(outcome.output, word(5));
+        assert_eq!(host.get(&[0u8; 32]), Some(word(5)));
+
+        assert_eq!(
+            interpret(&code, &word(1_000), None, 100),
+            Err(EvmError::StepLimit)
+        );
+    }
+
+    #[test]
+    fn test_revert_and_invalid_jump() {
+        // REVERT(0, 0)
+        let outcome = interpret(&[0x60, 0x00, 0x80, 0xfd], &[], None, 10).unwrap();
+        assert!(outcome.reverted);
+
+        assert_eq!(
+            interpret(&[0x60, 0x03, 0x56, 0x00], &[], None, 10),
+            Err(EvmError::InvalidJump(2))
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! 宿主侧状态区域与宿主函数
+//!
+//! 以键值形式保存加载到 VM 的状态，供各后端的系统调用读写
+
+use anyhow::Result;
+use blake2::digest::consts::U32;
+use blake2::Blake2b;
+use sha3::{Digest, Keccak256};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::error::{VmError, VmResult};
+use crate::traits::HostFunctions;
+use crate::types::{StateAccess, StateRegion, VmEvent};
+
+/// 状态区域集合
+#[derive(Debug, Clone, Default)]
+pub struct StateRegions {
+    regions: BTreeMap<String, StateRegion>,
+}
+
+impl StateRegions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 加载区域，同键覆盖
+    pub fn load(&mut self, region: StateRegion) {
+        self.regions.insert(region.key.clone(), region);
+    }
+
+    pub fn get(&self, key: &str) -> Option<&StateRegion> {
+        self.regions.get(key)
+    }
+
+    /// 按原始键字节查找（guest 传入的键）
+    pub fn get_by_bytes(&self, key: &[u8]) -> Option<&StateRegion> {
+        std::str::from_utf8(key)
+            .ok()
+            .and_then(|key| self.regions.get(key))
+    }
+
+    /// 覆盖可写区域的内容
+    pub fn write(&mut self, key: &[u8], data: Vec<u8>) -> Result<()> {
+        let key = String::from_utf8_lossy(key).into_owned();
+        let region = self
+            .regions
+            .get_mut(&key)
+            .ok_or_else(|| VmError::ExecutionFailed(format!("Unknown state region: {}", key)))?;
+
+        if region.access != StateAccess::ReadWrite {
+            return Err(
+                VmError::ExecutionFailed(format!("State region {} is read-only", key)).into(),
+            );
+        }
+
+        region.data = data;
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StateRegion> {
+        self.regions.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+}
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+pub fn blake2b256(data: &[u8]) -> [u8; 32] {
+    Blake2b::<U32>::digest(data).into()
+}
+
+/// 内存中的宿主函数实现，存储与事件仅在实例存活期间保留
+#[derive(Debug, Default)]
+pub struct MemoryHost {
+    storage: Mutex<BTreeMap<Vec<u8>, Vec<u8>>>,
+    events: Mutex<Vec<VmEvent>>,
+}
+
+impl MemoryHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.storage.lock().expect("host storage poisoned").get(key).cloned()
+    }
+
+    pub fn events(&self) -> Vec<VmEvent> {
+        self.events.lock().expect("host events poisoned").clone()
+    }
+}
+
+impl HostFunctions for MemoryHost {
+    fn storage_read(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.get(key)
+    }
+
+    fn storage_write(&self, key: &[u8], value: &[u8]) -> VmResult<()> {
+        self.storage
+            .lock()
+            .expect("host storage poisoned")
+            .insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn emit_event(&self, topic: &[u8], data: &[u8]) -> VmResult<()> {
+        self.events.lock().expect("host events poisoned").push(VmEvent {
+            topic: topic.to_vec(),
+            data: data.to_vec(),
+        });
+        Ok(())
+    }
+}
@@ -5,42 +5,231 @@
 pub mod ckb;
 pub mod ckb_complete;
 pub mod error;
+pub mod evm;
+pub mod host;
 pub mod polka;
+pub mod pool;
+pub mod trace;
 pub mod traits;
 pub mod types;
+pub mod wasm;
 
 pub use error::*;
+pub use host::{MemoryHost, StateRegions};
+pub use pool::{PooledVm, VmPool, VmPoolConfig, VmPoolStats};
+pub use trace::*;
 pub use traits::*;
 pub use types::*;
 
 use anyhow::Result;
+use async_trait::async_trait;
+use dubhe_observability::{NodeMetrics, TRACE_TARGET};
+use prometheus::IntGauge;
 use std::sync::Arc;
+use tracing::{field, info_span, Instrument};
 
 /// VM 实例管理器
 pub struct VmManager {
     default_vm: VmType,
+    gas_schedule: GasSchedule,
+    metrics: Option<Arc<NodeMetrics>>,
 }
 
 impl VmManager {
     pub fn new(default_vm: VmType) -> Self {
-        Self { default_vm }
+        Self {
+            default_vm,
+            gas_schedule: GasSchedule::default(),
+            metrics: None,
+        }
+    }
+
+    /// 设置所有实例共用的周期与 gas 换算比例
+    pub fn with_gas_schedule(mut self, gas_schedule: GasSchedule) -> Self {
+        self.gas_schedule = gas_schedule;
+        self
+    }
+
+    pub fn default_vm(&self) -> VmType {
+        self.default_vm
+    }
+
+    pub fn gas_schedule(&self) -> GasSchedule {
+        self.gas_schedule
+    }
+
+    /// 按 gas 预算生成执行限制
+    pub fn limits_for_gas(&self, gas_budget: u64) -> ExecutionLimits {
+        ExecutionLimits::for_gas_budget(gas_budget, self.gas_schedule)
+    }
+
+    /// 在共享的 Prometheus 指标中统计存活的 VM 实例数
+    pub fn with_metrics(mut self, metrics: Arc<NodeMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
     }
 
     /// 创建 VM 实例
     pub fn create_instance(
         &self,
         vm_type: Option<VmType>,
+    ) -> Result<Box<dyn VmInstance + Send + Sync>> {
+        self.build_instance(vm_type, None)
+    }
+
+    /// 创建注入宿主函数的 VM 实例，guest 可通过系统调用访问宿主存储与事件
+    pub fn create_instance_with_host(
+        &self,
+        vm_type: Option<VmType>,
+        host: Arc<dyn HostFunctions>,
+    ) -> Result<Box<dyn VmInstance + Send + Sync>> {
+        self.build_instance(vm_type, Some(host))
+    }
+
+    fn build_instance(
+        &self,
+        vm_type: Option<VmType>,
+        host: Option<Arc<dyn HostFunctions>>,
     ) -> Result<Box<dyn VmInstance + Send + Sync>> {
         let vm_type = vm_type.unwrap_or(self.default_vm);
 
-        match vm_type {
+        let mut instance: Box<dyn VmInstance + Send + Sync> = match vm_type {
             #[cfg(feature = "polkavm")]
-            VmType::PolkaVM => Ok(Box::new(polka::PolkaVmInstance::new()?)),
+            VmType::PolkaVM => {
+                let instance = polka::PolkaVmInstance::new()?;
+                Box::new(match host {
+                    Some(host) => instance.with_host(host),
+                    None => instance,
+                })
+            }
 
             #[cfg(feature = "ckb-vm")]
-            VmType::CkbVM => Ok(Box::new(ckb::CkbVmInstance::new()?)),
+            VmType::CkbVM => {
+                let instance = ckb::CkbVmInstance::new()?;
+                Box::new(match host {
+                    Some(host) => instance.with_host(host),
+                    None => instance,
+                })
+            }
+
+            _ => return Err(VmError::UnsupportedVm(vm_type).into()),
+        };
+        instance.set_limits(ExecutionLimits {
+            gas_schedule: self.gas_schedule,
+            ..ExecutionLimits::default()
+        });
+
+        let active = self
+            .metrics
+            .as_ref()
+            .map(|metrics| metrics.active_vm_instances.clone());
+        Ok(Box::new(MeteredInstance::new(instance, active)))
+    }
+}
+
+/// VM 包装：存活期间计入活跃实例数（配置了指标时），每次执行创建 `vm.execute` span
+struct MeteredInstance {
+    inner: Box<dyn VmInstance + Send + Sync>,
+    active: Option<IntGauge>,
+}
+
+impl MeteredInstance {
+    fn new(inner: Box<dyn VmInstance + Send + Sync>, active: Option<IntGauge>) -> Self {
+        if let Some(active) = &active {
+            active.inc();
+        }
+        Self { inner, active }
+    }
+}
 
-            _ => Err(anyhow::anyhow!("Unsupported VM type: {:?}", vm_type)),
+impl Drop for MeteredInstance {
+    fn drop(&mut self) {
+        if let Some(active) = &self.active {
+            active.dec();
         }
     }
 }
+
+#[async_trait]
+impl VmInstance for MeteredInstance {
+    async fn load_code(&mut self, code: &[u8]) -> VmResult<()> {
+        self.inner.load_code(code).await
+    }
+
+    async fn load_state(&mut self, region: StateRegion) -> VmResult<()> {
+        self.inner.load_state(region).await
+    }
+
+    async fn execute(&mut self, input: &[u8]) -> VmResult<ExecutionResult> {
+        let span = info_span!(
+            target: TRACE_TARGET,
+            "vm.execute",
+            vm.type = ?self.inner.vm_type(),
+            vm.input_bytes = input.len(),
+            vm.gas_used = field::Empty,
+            vm.cycles = field::Empty,
+        );
+        let result = self.inner.execute(input).instrument(span.clone()).await;
+        if let Ok(result) = &result {
+            span.record("vm.gas_used", result.gas_used);
+            span.record("vm.cycles", result.cycles_used);
+        }
+        result
+    }
+
+    async fn execute_traced(
+        &mut self,
+        input: &[u8],
+        config: &TraceConfig,
+    ) -> VmResult<(ExecutionResult, ExecutionTrace)> {
+        self.inner.execute_traced(input, config).await
+    }
+
+    async fn snapshot(&self) -> VmResult<VmSnapshot> {
+        self.inner.snapshot().await
+    }
+
+    async fn restore(&mut self, snapshot: &VmSnapshot) -> VmResult<()> {
+        self.inner.restore(snapshot).await
+    }
+
+    fn vm_type(&self) -> VmType {
+        self.inner.vm_type()
+    }
+
+    fn set_limits(&mut self, limits: ExecutionLimits) {
+        self.inner.set_limits(limits)
+    }
+
+    fn supports_artifact(&self, kind: dubhe_loader::ArtifactKind) -> bool {
+        self.inner.supports_artifact(kind)
+    }
+
+    fn reset(&mut self) -> VmResult<()> {
+        self.inner.reset()
+    }
+
+    fn set_host(&mut self, host: Arc<dyn HostFunctions>) -> VmResult<()> {
+        self.inner.set_host(host)
+    }
+}
+
+#[cfg(all(test, feature = "ckb-vm"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_active_instances_gauge() {
+        let metrics = Arc::new(NodeMetrics::new().unwrap());
+        let manager = VmManager::new(VmType::CkbVM).with_metrics(metrics.clone());
+
+        let first = manager.create_instance(None).unwrap();
+        let second = manager.create_instance(None).unwrap();
+        assert_eq!(metrics.active_vm_instances.get(), 2);
+        assert_eq!(first.vm_type(), VmType::CkbVM);
+
+        drop(first);
+        drop(second);
+        assert_eq!(metrics.active_vm_instances.get(), 0);
+    }
+}
@@ -2,35 +2,82 @@
 
 use async_trait::async_trait;
 use anyhow::Result;
+use dubhe_loader::abi::HOST_IMPORTS;
+use std::sync::Arc;
 
-use crate::traits::VmInstance;
+use crate::error::{VmError, VmResult};
+use crate::host::StateRegions;
+use crate::traits::{HostFunctions, VmInstance};
 use crate::types::*;
 
 pub struct PolkaVmInstance {
     // TODO: PolkaVM 实例
+    limits: ExecutionLimits,
+    regions: StateRegions,
+    host: Option<Arc<dyn HostFunctions>>,
 }
 
 impl PolkaVmInstance {
     pub fn new() -> Result<Self> {
-        Ok(Self {})
+        Ok(Self {
+            limits: ExecutionLimits::default(),
+            regions: StateRegions::new(),
+            host: None,
+        })
+    }
+
+    /// 注入宿主函数，guest 的导入函数分派到这里
+    pub fn with_host(mut self, host: Arc<dyn HostFunctions>) -> Self {
+        self.host = Some(host);
+        self
+    }
+
+    /// 将 guest 的导入函数名解析为与 CKB-VM 相同的系统调用号
+    ///
+    /// 存储导入需要已注入宿主函数，未知导入在加载代码时拒绝
+    pub fn resolve_import(&self, name: &str) -> Result<u64> {
+        let number = HOST_IMPORTS
+            .iter()
+            .find(|(import, _)| *import == name)
+            .map(|(_, number)| *number)
+            .ok_or_else(|| VmError::CodeLoadingFailed(format!("Unknown host import: {}", name)))?;
+
+        if name == "storage_write" && self.host.is_none() {
+            return Err(VmError::CodeLoadingFailed(
+                "Host import storage_write requires host functions".to_string(),
+            )
+            .into());
+        }
+        Ok(number)
+    }
+
+    /// PolkaVM 按 gas 计量，执行时的 gas 上限与 CKB-VM 使用同一换算比例
+    pub fn gas_limit(&self) -> u64 {
+        self.limits.gas_limit()
     }
 }
 
 #[async_trait]
 impl VmInstance for PolkaVmInstance {
-    async fn load_code(&mut self, _code: &[u8]) -> Result<()> {
+    async fn load_code(&mut self, _code: &[u8]) -> VmResult<()> {
         todo!("Implement PolkaVM code loading")
     }
+
+    async fn load_state(&mut self, region: StateRegion) -> VmResult<()> {
+        // 区域由宿主保存，guest 通过与 CKB-VM 相同的系统调用访问
+        self.regions.load(region);
+        Ok(())
+    }
     
-    async fn execute(&mut self, _input: &[u8]) -> Result<ExecutionResult> {
+    async fn execute(&mut self, _input: &[u8]) -> VmResult<ExecutionResult> {
         todo!("Implement PolkaVM execution")
     }
     
-    async fn snapshot(&self) -> Result<VmSnapshot> {
+    async fn snapshot(&self) -> VmResult<VmSnapshot> {
         todo!("Implement PolkaVM snapshot")
     }
     
-    async fn restore(&mut self, _snapshot: &VmSnapshot) -> Result<()> {
+    async fn restore(&mut self, _snapshot: &VmSnapshot) -> VmResult<()> {
         todo!("Implement PolkaVM restore")
     }
     
@@ -38,7 +85,7 @@ impl VmInstance for PolkaVmInstance {
         VmType::PolkaVM
     }
     
-    fn set_limits(&mut self, _limits: ExecutionLimits) {
-        todo!("Implement PolkaVM limits")
+    fn set_limits(&mut self, limits: ExecutionLimits) {
+        self.limits = limits;
     }
 } 
\ No newline at end of file
@@ -0,0 +1,564 @@
+//! VM 实例池
+//!
+//! 短小的只读调用中，构造机器与加载代码占了大部分耗时。池按 (VM 类型, 代码哈希) 保存已加载
+//! 同一份编译产物的空闲实例：签出时优先复用，归还时强制 [`VmInstance::reset`]，
+//! 不支持重置的实例直接丢弃。开启 `verify_reset`（调试构建默认开启）时，
+//! 归还的实例重置后须与刚加载代码时的快照一致，否则视为状态泄漏并丢弃。
+//!
+//! 空闲实例受每键上限与全局内存预算（按持有的代码字节计）约束，超出时淘汰最久未用的实例；
+//! 空闲超过 `idle_ttl_secs` 的实例在下次签出、归还或 [`VmPool::evict_idle`] 时过期
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, error};
+
+use crate::host::blake2b256;
+use crate::traits::{HostFunctions, VmInstance};
+use crate::types::VmType;
+use crate::VmManager;
+
+type Instance = Box<dyn VmInstance + Send + Sync>;
+
+/// 实例池配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VmPoolConfig {
+    pub enabled: bool,
+    /// 每个 (VM 类型, 代码哈希) 保留的空闲实例数上限
+    pub max_idle_per_key: usize,
+    /// 全部空闲实例持有的代码字节数上限
+    pub max_idle_bytes: u64,
+    /// 空闲超过该时长的实例被丢弃
+    pub idle_ttl_secs: u64,
+    /// 归还时比对重置后的快照与基线，发现状态泄漏时丢弃实例
+    pub verify_reset: bool,
+}
+
+impl Default for VmPoolConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_idle_per_key: 4,
+            max_idle_bytes: 256 * 1024 * 1024, // 256MB
+            idle_ttl_secs: 300,
+            verify_reset: cfg!(debug_assertions),
+        }
+    }
+}
+
+/// 实例池统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VmPoolStats {
+    /// 复用空闲实例的签出次数
+    pub hits: u64,
+    /// 新建实例的签出次数
+    pub misses: u64,
+    pub idle_instances: usize,
+    pub idle_bytes: u64,
+    /// 因上限、预算或过期被淘汰的空闲实例数
+    pub evictions: u64,
+    /// 重置校验发现的状态泄漏次数
+    pub leaks: u64,
+}
+
+/// 池键：同一键下的实例加载了相同的代码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct PoolKey {
+    vm_type: VmType,
+    code_hash: [u8; 32],
+}
+
+/// 空闲实例
+struct IdleVm {
+    instance: Instance,
+    size: u64,
+    baseline: Option<Vec<u8>>,
+    idle_since: Instant,
+}
+
+/// 签出的实例，用完后交还 [`VmPool::checkin`]；直接 drop 则不再复用
+pub struct PooledVm {
+    instance: Instance,
+    lease: Option<Lease>,
+}
+
+/// 签出记录，归还时据此入池与校验
+struct Lease {
+    key: PoolKey,
+    size: u64,
+    /// 刚加载代码时的快照，用于校验重置
+    baseline: Option<Vec<u8>>,
+    reused: bool,
+}
+
+impl PooledVm {
+    /// 不属于任何池的实例，归还时直接丢弃
+    pub fn detached(instance: Box<dyn VmInstance + Send + Sync>) -> Self {
+        Self {
+            instance,
+            lease: None,
+        }
+    }
+
+    /// 是否复用了池中的空闲实例
+    pub fn reused(&self) -> bool {
+        self.lease.as_ref().is_some_and(|lease| lease.reused)
+    }
+
+    pub fn into_inner(self) -> Box<dyn VmInstance + Send + Sync> {
+        self.instance
+    }
+}
+
+impl Deref for PooledVm {
+    type Target = dyn VmInstance + Send + Sync;
+
+    fn deref(&self) -> &Self::Target {
+        self.instance.as_ref()
+    }
+}
+
+impl DerefMut for PooledVm {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.instance.as_mut()
+    }
+}
+
+#[derive(Default)]
+struct Idle {
+    instances: HashMap<PoolKey, VecDeque<IdleVm>>,
+    bytes: u64,
+}
+
+impl Idle {
+    fn len(&self) -> usize {
+        self.instances.values().map(VecDeque::len).sum()
+    }
+
+    /// 丢弃空闲超过 `ttl` 的实例，返回丢弃数量
+    fn expire(&mut self, ttl: Duration, now: Instant) -> u64 {
+        let mut expired = 0;
+        let mut freed = 0;
+        self.instances.retain(|_, idle| {
+            idle.retain(|vm| {
+                let keep = now.saturating_duration_since(vm.idle_since) < ttl;
+                if !keep {
+                    expired += 1;
+                    freed += vm.size;
+                }
+                keep
+            });
+            !idle.is_empty()
+        });
+        self.bytes -= freed;
+        expired
+    }
+
+    /// 淘汰全局最久未用的空闲实例
+    fn evict_oldest(&mut self) -> bool {
+        let oldest = self
+            .instances
+            .iter()
+            .filter_map(|(key, idle)| idle.front().map(|vm| (*key, vm.idle_since)))
+            .min_by_key(|(_, idle_since)| *idle_since);
+        let Some((key, _)) = oldest else {
+            return false;
+        };
+        let idle = self.instances.get_mut(&key).expect("key exists");
+        let vm = idle.pop_front().expect("non-empty");
+        if idle.is_empty() {
+            self.instances.remove(&key);
+        }
+        self.bytes -= vm.size;
+        true
+    }
+}
+
+/// VM 实例池
+pub struct VmPool {
+    manager: Arc<VmManager>,
+    config: VmPoolConfig,
+    idle: Mutex<Idle>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    leaks: AtomicU64,
+}
+
+impl VmPool {
+    pub fn new(manager: Arc<VmManager>, config: VmPoolConfig) -> Self {
+        Self {
+            manager,
+            config,
+            idle: Mutex::new(Idle::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            leaks: AtomicU64::new(0),
+        }
+    }
+
+    pub fn config(&self) -> &VmPoolConfig {
+        &self.config
+    }
+
+    /// 签出已加载 `code` 的实例并注入宿主函数，没有空闲实例时新建
+    pub async fn checkout(
+        &self,
+        vm_type: Option<VmType>,
+        code: &[u8],
+        host: Option<Arc<dyn HostFunctions>>,
+    ) -> Result<PooledVm> {
+        let vm_type = vm_type.unwrap_or(self.manager.default_vm());
+        self.checkout_with(vm_type, code, host, || {
+            self.manager.build_instance(Some(vm_type), None)
+        })
+        .await
+    }
+
+    async fn checkout_with(
+        &self,
+        vm_type: VmType,
+        code: &[u8],
+        host: Option<Arc<dyn HostFunctions>>,
+        create: impl FnOnce() -> Result<Instance>,
+    ) -> Result<PooledVm> {
+        let key = PoolKey {
+            vm_type,
+            code_hash: blake2b256(code),
+        };
+
+        if let Some(idle) = self.take_idle(&key) {
+            let mut instance = idle.instance;
+            if let Some(host) = host {
+                instance.set_host(host)?;
+            }
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            debug!("Reusing pooled {:?} instance", vm_type);
+            return Ok(PooledVm {
+                instance,
+                lease: Some(Lease {
+                    key,
+                    size: idle.size,
+                    baseline: idle.baseline,
+                    reused: true,
+                }),
+            });
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let mut instance = create()?;
+        instance.load_code(code).await?;
+        let baseline = if self.config.enabled && self.config.verify_reset {
+            instance.snapshot().await.ok().map(|snapshot| snapshot.data)
+        } else {
+            None
+        };
+        if let Some(host) = host {
+            instance.set_host(host)?;
+        }
+        Ok(PooledVm {
+            instance,
+            lease: Some(Lease {
+                key,
+                size: code.len() as u64,
+                baseline,
+                reused: false,
+            }),
+        })
+    }
+
+    fn take_idle(&self, key: &PoolKey) -> Option<IdleVm> {
+        if !self.config.enabled {
+            return None;
+        }
+        let mut guard = self.idle.lock().unwrap();
+        let idle = &mut *guard;
+        self.expire_locked(idle, Instant::now());
+        let instances = idle.instances.get_mut(key)?;
+        // 最近归还的实例最热
+        let vm = instances.pop_back()?;
+        if instances.is_empty() {
+            idle.instances.remove(key);
+        }
+        idle.bytes -= vm.size;
+        Some(vm)
+    }
+
+    /// 重置并归还实例；不支持重置、校验失败或超出上限的实例被丢弃
+    pub async fn checkin(&self, vm: PooledVm) {
+        let PooledVm {
+            mut instance,
+            lease,
+        } = vm;
+        let Some(lease) = lease.filter(|_| self.config.enabled) else {
+            return;
+        };
+
+        if let Err(e) = instance.reset() {
+            debug!("Discarding {:?} instance: {}", lease.key.vm_type, e);
+            return;
+        }
+        if self.config.verify_reset {
+            if let Some(baseline) = &lease.baseline {
+                let clean = match instance.snapshot().await {
+                    Ok(snapshot) => snapshot.data == *baseline,
+                    Err(_) => false,
+                };
+                if !clean {
+                    self.leaks.fetch_add(1, Ordering::Relaxed);
+                    error!(
+                        "🚨 {:?} instance still holds state after reset, discarding it",
+                        lease.key.vm_type
+                    );
+                    return;
+                }
+            }
+        }
+
+        if lease.size > self.config.max_idle_bytes || self.config.max_idle_per_key == 0 {
+            return;
+        }
+        let mut guard = self.idle.lock().unwrap();
+        let idle = &mut *guard;
+        self.expire_locked(idle, Instant::now());
+        let mut evicted = 0;
+        if let Some(instances) = idle.instances.get_mut(&lease.key) {
+            while instances.len() >= self.config.max_idle_per_key {
+                let vm = instances.pop_front().expect("non-empty");
+                idle.bytes -= vm.size;
+                evicted += 1;
+            }
+        }
+        while idle.bytes + lease.size > self.config.max_idle_bytes && idle.evict_oldest() {
+            evicted += 1;
+        }
+        self.evictions.fetch_add(evicted, Ordering::Relaxed);
+
+        idle.bytes += lease.size;
+        idle.instances
+            .entry(lease.key)
+            .or_default()
+            .push_back(IdleVm {
+                instance,
+                size: lease.size,
+                baseline: lease.baseline,
+                idle_since: Instant::now(),
+            });
+    }
+
+    /// 丢弃过期的空闲实例，返回丢弃数量
+    pub fn evict_idle(&self, now: Instant) -> usize {
+        let mut idle = self.idle.lock().unwrap();
+        self.expire_locked(&mut idle, now) as usize
+    }
+
+    fn expire_locked(&self, idle: &mut Idle, now: Instant) -> u64 {
+        let expired = idle.expire(Duration::from_secs(self.config.idle_ttl_secs), now);
+        self.evictions.fetch_add(expired, Ordering::Relaxed);
+        expired
+    }
+
+    pub fn stats(&self) -> VmPoolStats {
+        let idle = self.idle.lock().unwrap();
+        VmPoolStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            idle_instances: idle.len(),
+            idle_bytes: idle.bytes,
+            evictions: self.evictions.load(Ordering::Relaxed),
+            leaks: self.leaks.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::VmResult;
+    use crate::types::*;
+    use async_trait::async_trait;
+
+    /// 只记录加载的状态；`leaky` 为真时重置不清除状态
+    #[derive(Default)]
+    struct StubVm {
+        state: Vec<u8>,
+        leaky: bool,
+    }
+
+    #[async_trait]
+    impl VmInstance for StubVm {
+        async fn load_code(&mut self, _code: &[u8]) -> VmResult<()> {
+            Ok(())
+        }
+
+        async fn load_state(&mut self, region: StateRegion) -> VmResult<()> {
+            self.state.extend_from_slice(&region.data);
+            Ok(())
+        }
+
+        async fn execute(&mut self, _input: &[u8]) -> VmResult<ExecutionResult> {
+            Ok(ExecutionResult {
+                success: true,
+                output: self.state.clone(),
+                ..Default::default()
+            })
+        }
+
+        async fn snapshot(&self) -> VmResult<VmSnapshot> {
+            Ok(VmSnapshot {
+                data: self.state.clone(),
+                vm_type: VmType::CkbVM,
+            })
+        }
+
+        async fn restore(&mut self, snapshot: &VmSnapshot) -> VmResult<()> {
+            self.state = snapshot.data.clone();
+            Ok(())
+        }
+
+        fn vm_type(&self) -> VmType {
+            VmType::CkbVM
+        }
+
+        fn set_limits(&mut self, _limits: ExecutionLimits) {}
+
+        fn reset(&mut self) -> VmResult<()> {
+            if !self.leaky {
+                self.state.clear();
+            }
+            Ok(())
+        }
+    }
+
+    fn pool(config: VmPoolConfig) -> VmPool {
+        VmPool::new(Arc::new(VmManager::new(VmType::CkbVM)), config)
+    }
+
+    async fn checkout_stub(pool: &VmPool, code: &[u8], leaky: bool) -> PooledVm {
+        pool.checkout_with(VmType::CkbVM, code, None, || {
+            Ok(Box::new(StubVm {
+                state: vec![],
+                leaky,
+            }))
+        })
+        .await
+        .unwrap()
+    }
+
+    #[cfg(feature = "ckb-vm")]
+    #[tokio::test]
+    async fn test_sequential_checkouts_are_isolated() {
+        use dubhe_loader::abi::{SYS_EXIT, SYS_STATE_READ, SYS_WRITE_OUTPUT};
+        use dubhe_loader::riscv::{self, *};
+
+        // 读取状态区域 "slot"，输出 SYS_STATE_READ 的返回值（长度或 STATE_NOT_FOUND）
+        let mut words = vec![addi(SP, SP, -256)];
+        for (i, byte) in b"slot".iter().enumerate() {
+            words.extend(li(T0, *byte as i32));
+            words.push(sb(T0, SP, i as i32));
+        }
+        words.extend([
+            addi(A0, SP, 0),
+            addi(A1, ZERO, 4),
+            addi(A2, SP, 64),
+            addi(A3, ZERO, 128),
+            addi(A4, ZERO, 0),
+        ]);
+        words.extend(syscall(SYS_STATE_READ));
+        words.extend([sd(A0, SP, 32), addi(A0, SP, 32), addi(A1, ZERO, 8)]);
+        words.extend(syscall(SYS_WRITE_OUTPUT));
+        words.push(addi(A0, ZERO, 0));
+        words.extend(syscall(SYS_EXIT));
+        let code = riscv::assemble(&words);
+
+        let pool = pool(VmPoolConfig {
+            verify_reset: true,
+            ..Default::default()
+        });
+        let mut first = pool.checkout(None, &code, None).await.unwrap();
+        assert!(!first.reused());
+        first
+            .load_state(StateRegion::read_write("slot", b"secret".to_vec()))
+            .await
+            .unwrap();
+        let result = first.execute(&[]).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, 6u64.to_le_bytes().to_vec());
+        pool.checkin(first).await;
+
+        // 复用同一个实例，看不到上一次签出加载的状态
+        let mut second = pool.checkout(None, &code, None).await.unwrap();
+        assert!(second.reused());
+        let result = second.execute(&[]).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, u64::MAX.to_le_bytes().to_vec());
+        pool.checkin(second).await;
+
+        let stats = pool.stats();
+        assert_eq!((stats.hits, stats.misses, stats.leaks), (1, 1, 0));
+        assert_eq!(stats.idle_instances, 1);
+        assert_eq!(stats.idle_bytes, code.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_leaking_instance_is_discarded() {
+        let pool = pool(VmPoolConfig {
+            verify_reset: true,
+            ..Default::default()
+        });
+        let mut vm = checkout_stub(&pool, b"code", true).await;
+        vm.load_state(StateRegion::read_write("slot", b"secret".to_vec()))
+            .await
+            .unwrap();
+        pool.checkin(vm).await;
+
+        let stats = pool.stats();
+        assert_eq!((stats.leaks, stats.idle_instances), (1, 0));
+        assert!(!checkout_stub(&pool, b"code", false).await.reused());
+    }
+
+    #[tokio::test]
+    async fn test_idle_limits_and_expiry() {
+        let pool = pool(VmPoolConfig {
+            max_idle_per_key: 2,
+            max_idle_bytes: 10,
+            idle_ttl_secs: 60,
+            ..Default::default()
+        });
+
+        // 每键上限：第三个实例归还时淘汰最早归还的一个
+        let a: Vec<PooledVm> = vec![
+            checkout_stub(&pool, b"aaaa", false).await,
+            checkout_stub(&pool, b"aaaa", false).await,
+            checkout_stub(&pool, b"aaaa", false).await,
+        ];
+        for vm in a {
+            pool.checkin(vm).await;
+        }
+        let stats = pool.stats();
+        assert_eq!((stats.idle_instances, stats.idle_bytes), (2, 8));
+        assert_eq!(stats.evictions, 1);
+
+        // 全局预算：另一个键的实例挤掉最久未用的实例
+        let b = checkout_stub(&pool, b"bbbbbb", false).await;
+        pool.checkin(b).await;
+        let stats = pool.stats();
+        assert_eq!((stats.idle_instances, stats.idle_bytes), (2, 10));
+        assert!(checkout_stub(&pool, b"bbbbbb", false).await.reused());
+
+        // 超过单个实例预算的代码不入池
+        let big = checkout_stub(&pool, &[0; 11], false).await;
+        pool.checkin(big).await;
+        assert_eq!(pool.stats().idle_instances, 1);
+
+        assert_eq!(pool.evict_idle(Instant::now()), 0);
+        assert_eq!(pool.evict_idle(Instant::now() + Duration::from_secs(60)), 1);
+        assert_eq!(pool.stats().idle_instances, 0);
+    }
+}
@@ -0,0 +1,174 @@
+//! 指令级执行跟踪
+//!
+//! 调试 Move → RISC-V 编译产物时以跟踪模式执行（见 [`VmInstance::execute_traced`]）：
+//! 每条执行的指令记录 PC 与助记符，并按配置记录变化的寄存器与内存读取。
+//! 跟踪保存在容量为 `max_steps` 的环形缓冲区中，超出时丢弃最早的步骤并标记截断，
+//! 保留的是结束前最近执行的指令。序列化时步骤逐个写出，大跟踪可用
+//! [`ExecutionTrace::write_json_lines`] 直接流式写入文件或连接
+//!
+//! [`VmInstance::execute_traced`]: crate::traits::VmInstance::execute_traced
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Write;
+
+/// 环形缓冲区预分配的最多步数，更大的上限按需增长
+const PREALLOCATED_STEPS: usize = 4096;
+
+/// RISC-V 寄存器的 ABI 名称
+const REGISTER_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+
+/// 跟踪配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TraceConfig {
+    /// 保留的最多步数
+    pub max_steps: usize,
+    /// 记录 load 指令读取的内存
+    pub capture_memory_reads: bool,
+    /// 记录每步变化的寄存器
+    pub capture_registers: bool,
+}
+
+impl Default for TraceConfig {
+    fn default() -> Self {
+        Self {
+            max_steps: 10_000,
+            capture_memory_reads: false,
+            capture_registers: true,
+        }
+    }
+}
+
+/// 一步执行后变化的寄存器
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisterChange {
+    pub register: String,
+    pub value: u64,
+}
+
+impl RegisterChange {
+    pub fn new(index: usize, value: u64) -> Self {
+        Self {
+            register: REGISTER_NAMES
+                .get(index)
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| format!("x{}", index)),
+            value,
+        }
+    }
+}
+
+/// load 指令读取的内存
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryRead {
+    pub address: u64,
+    pub data: Vec<u8>,
+}
+
+/// 一条执行的指令
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceStep {
+    pub pc: u64,
+    /// 小写助记符，如 `addi`
+    pub opcode: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub registers: Vec<RegisterChange>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub memory_reads: Vec<MemoryRead>,
+}
+
+impl TraceStep {
+    pub fn new(pc: u64, opcode: impl Into<String>) -> Self {
+        Self {
+            pc,
+            opcode: opcode.into(),
+            registers: Vec::new(),
+            memory_reads: Vec::new(),
+        }
+    }
+}
+
+/// 执行过程中写入的环形缓冲区
+#[derive(Debug)]
+pub struct TraceBuffer {
+    capacity: usize,
+    steps: VecDeque<TraceStep>,
+    total_steps: u64,
+}
+
+impl TraceBuffer {
+    pub fn new(config: &TraceConfig) -> Self {
+        Self {
+            capacity: config.max_steps,
+            steps: VecDeque::with_capacity(config.max_steps.min(PREALLOCATED_STEPS)),
+            total_steps: 0,
+        }
+    }
+
+    /// 追加一步，缓冲区已满时丢弃最早的一步
+    pub fn push(&mut self, step: TraceStep) {
+        self.total_steps += 1;
+        if self.capacity == 0 {
+            return;
+        }
+        if self.steps.len() == self.capacity {
+            self.steps.pop_front();
+        }
+        self.steps.push_back(step);
+    }
+
+    /// 最近追加的一步，用于执行后补充寄存器变化
+    pub fn last_mut(&mut self) -> Option<&mut TraceStep> {
+        self.steps.back_mut()
+    }
+
+    pub fn finish(self) -> ExecutionTrace {
+        ExecutionTrace {
+            truncated: self.total_steps > self.steps.len() as u64,
+            total_steps: self.total_steps,
+            steps: self.steps,
+        }
+    }
+}
+
+/// 一次执行的跟踪
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionTrace {
+    /// 保留的步骤，按执行顺序排列
+    pub steps: VecDeque<TraceStep>,
+    /// 实际执行的总步数
+    pub total_steps: u64,
+    /// 超出 `max_steps`，最早的步骤已被丢弃
+    pub truncated: bool,
+}
+
+impl ExecutionTrace {
+    /// 按执行顺序排列的 PC
+    pub fn pcs(&self) -> Vec<u64> {
+        self.steps.iter().map(|step| step.pc).collect()
+    }
+
+    /// 以 JSON Lines 逐步写出：首行为摘要，之后每行一步
+    pub fn write_json_lines<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        serde_json::to_writer(
+            &mut writer,
+            &serde_json::json!({
+                "totalSteps": self.total_steps,
+                "truncated": self.truncated,
+            }),
+        )?;
+        writer.write_all(b"\n")?;
+        for step in &self.steps {
+            serde_json::to_writer(&mut writer, step)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()
+    }
+}
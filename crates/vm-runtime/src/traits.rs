@@ -1,28 +1,85 @@
 //! VM Runtime Traits
 
-use anyhow::Result;
 use async_trait::async_trait;
+use std::sync::Arc;
 
+use dubhe_loader::ArtifactKind;
+
+use crate::error::{VmError, VmResult};
+use crate::trace::{ExecutionTrace, TraceConfig};
 use crate::types::*;
 
+/// 宿主函数
+///
+/// guest 通过系统调用访问内存镜像之外的存储、事件与哈希；
+/// 在 `VmManager::create_instance_with_host` 创建实例时注入
+pub trait HostFunctions: Send + Sync {
+    /// 读取存储，键不存在时返回 `None`
+    fn storage_read(&self, key: &[u8]) -> Option<Vec<u8>>;
+
+    /// 写入存储
+    fn storage_write(&self, key: &[u8], value: &[u8]) -> VmResult<()>;
+
+    /// 接收 guest 发出的事件；返回错误时执行中止
+    fn emit_event(&self, topic: &[u8], data: &[u8]) -> VmResult<()>;
+
+    fn keccak256(&self, data: &[u8]) -> [u8; 32] {
+        crate::host::keccak256(data)
+    }
+
+    fn blake2b256(&self, data: &[u8]) -> [u8; 32] {
+        crate::host::blake2b256(data)
+    }
+}
+
 /// VM 实例 trait  
 #[async_trait]
 pub trait VmInstance {
     /// 加载代码到 VM
-    async fn load_code(&mut self, code: &[u8]) -> Result<()>;
+    async fn load_code(&mut self, code: &[u8]) -> VmResult<()>;
+
+    /// 加载状态区域，执行期间 guest 可按键读取（同键覆盖）
+    async fn load_state(&mut self, region: StateRegion) -> VmResult<()>;
     
     /// 执行代码
-    async fn execute(&mut self, input: &[u8]) -> Result<ExecutionResult>;
+    async fn execute(&mut self, input: &[u8]) -> VmResult<ExecutionResult>;
+
+    /// 以跟踪模式执行，同时返回指令级跟踪；默认不支持
+    async fn execute_traced(
+        &mut self,
+        input: &[u8],
+        config: &TraceConfig,
+    ) -> VmResult<(ExecutionResult, ExecutionTrace)> {
+        let _ = (input, config);
+        Err(VmError::TracingUnsupported(self.vm_type()))
+    }
     
     /// 创建快照
-    async fn snapshot(&self) -> Result<VmSnapshot>;
+    async fn snapshot(&self) -> VmResult<VmSnapshot>;
     
     /// 从快照恢复
-    async fn restore(&mut self, snapshot: &VmSnapshot) -> Result<()>;
+    async fn restore(&mut self, snapshot: &VmSnapshot) -> VmResult<()>;
     
     /// 获取 VM 类型
     fn vm_type(&self) -> VmType;
     
     /// 设置执行限制
     fn set_limits(&mut self, limits: ExecutionLimits);
+
+    /// 能否执行该类编译产物；默认只支持原生 RISC-V 代码
+    fn supports_artifact(&self, kind: ArtifactKind) -> bool {
+        kind == ArtifactKind::NativeRiscV
+    }
+
+    /// 清除上一次使用留下的全部状态（寄存器、可写状态区域、宿主函数与执行限制），
+    /// 只保留已加载的代码，供 [`VmPool`](crate::pool::VmPool) 复用；默认不支持，这类实例不入池
+    fn reset(&mut self) -> VmResult<()> {
+        Err(VmError::ResetUnsupported(self.vm_type()))
+    }
+
+    /// 替换宿主函数；默认不支持
+    fn set_host(&mut self, host: Arc<dyn HostFunctions>) -> VmResult<()> {
+        let _ = host;
+        Err(VmError::HostFunctionsUnsupported(self.vm_type()))
+    }
 } 
\ No newline at end of file
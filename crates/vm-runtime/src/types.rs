@@ -3,7 +3,7 @@
 use serde::{Deserialize, Serialize};
 
 /// VM 类型
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum VmType {
     PolkaVM, // PolkaVM RV32 Harvard 架构
     CkbVM,   // CKB-VM RV64 全指令集
@@ -11,13 +11,56 @@ pub enum VmType {
 }
 
 /// 执行结果
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ExecutionResult {
     pub success: bool,
     pub output: Vec<u8>,
     pub gas_used: u64,
     pub cycles_used: u64,
     pub error: Option<String>,
+    /// guest 按发出顺序产生的事件
+    #[serde(default)]
+    pub events: Vec<VmEvent>,
+}
+
+/// guest 通过宿主函数发出的事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VmEvent {
+    pub topic: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+/// 状态区域访问模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StateAccess {
+    ReadOnly,
+    ReadWrite,
+}
+
+/// 加载到 VM 的一段状态（例如一个对象的 BCS 数据），guest 通过键访问
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateRegion {
+    pub key: String,
+    pub data: Vec<u8>,
+    pub access: StateAccess,
+}
+
+impl StateRegion {
+    pub fn read_only(key: impl Into<String>, data: Vec<u8>) -> Self {
+        Self {
+            key: key.into(),
+            data,
+            access: StateAccess::ReadOnly,
+        }
+    }
+
+    pub fn read_write(key: impl Into<String>, data: Vec<u8>) -> Self {
+        Self {
+            key: key.into(),
+            data,
+            access: StateAccess::ReadWrite,
+        }
+    }
 }
 
 /// VM 快照
@@ -27,6 +70,31 @@ pub struct VmSnapshot {
     pub vm_type: VmType,
 }
 
+/// Gas 计价：VM 周期与 gas 的换算比例
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasSchedule {
+    /// 每单位 gas 对应的 VM 周期数
+    pub cycles_per_gas: u64,
+}
+
+impl Default for GasSchedule {
+    fn default() -> Self {
+        Self { cycles_per_gas: 2 }
+    }
+}
+
+impl GasSchedule {
+    /// gas 预算可消耗的最大周期数
+    pub fn gas_to_cycles(&self, gas: u64) -> u64 {
+        gas.saturating_mul(self.cycles_per_gas.max(1))
+    }
+
+    /// 已消耗周期折算的 gas，不足一单位按一单位计
+    pub fn cycles_to_gas(&self, cycles: u64) -> u64 {
+        cycles.div_ceil(self.cycles_per_gas.max(1))
+    }
+}
+
 /// 执行限制
 #[derive(Debug, Clone)]
 pub struct ExecutionLimits {
@@ -34,6 +102,7 @@ pub struct ExecutionLimits {
     pub max_cycles: u64,
     pub max_stack: u64,
     pub timeout_ms: u64,
+    pub gas_schedule: GasSchedule,
 }
 
 impl Default for ExecutionLimits {
@@ -43,6 +112,23 @@ impl Default for ExecutionLimits {
             max_cycles: 1_000_000,        // 1M cycles
             max_stack: 1 * 1024 * 1024,   // 1MB
             timeout_ms: 30_000,           // 30 seconds
+            gas_schedule: GasSchedule::default(),
+        }
+    }
+}
+
+impl ExecutionLimits {
+    /// 按 gas 预算设置最大周期数，其余限制取默认值
+    pub fn for_gas_budget(gas_budget: u64, gas_schedule: GasSchedule) -> Self {
+        Self {
+            max_cycles: gas_schedule.gas_to_cycles(gas_budget),
+            gas_schedule,
+            ..Self::default()
         }
     }
+
+    /// 当前周期上限对应的 gas 预算
+    pub fn gas_limit(&self) -> u64 {
+        self.max_cycles / self.gas_schedule.cycles_per_gas.max(1)
+    }
 }
@@ -0,0 +1,331 @@
+//! 宿主侧 WASM 解释器
+//!
+//! 执行 WASM 解释器包：引导代码通过 `SYS_WASM_INTERPRET` 把模块交给这里，
+//! 由 wasmi 按燃料计量执行。调用输入遵循入口调用约定，序号对应按名称排序的导出函数。
+//! 模块已在加载时校验（见 `dubhe_loader::WasmCompiler`），这里只做运行期检查
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+use wasmi::core::{Trap, ValueType};
+use wasmi::{Caller, Config, Engine, Extern, ExternType, Linker, Module, Store, Value};
+
+use dubhe_loader::abi::{ENTRY_INDEX_SIZE, WASM_HOST_MODULE};
+
+use crate::traits::HostFunctions;
+use crate::types::VmEvent;
+
+/// 每单位燃料折算的 VM 周期数
+pub const WASM_FUEL_CYCLES: u64 = 4;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum WasmError {
+    #[error("WASM fuel exhausted")]
+    FuelExhausted,
+
+    #[error("Invalid WASM module: {0}")]
+    InvalidModule(String),
+
+    #[error("Unknown WASM entry {0}")]
+    UnknownEntry(u32),
+
+    #[error("Invalid WASM call input: {0}")]
+    InvalidInput(String),
+
+    #[error("WASM trap: {0}")]
+    Trap(String),
+}
+
+/// 一次解释执行的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmOutcome {
+    /// 返回值的小端编码
+    pub output: Vec<u8>,
+    pub fuel_used: u64,
+    pub events: Vec<VmEvent>,
+}
+
+/// 宿主函数可见的执行状态
+struct WasmHost {
+    functions: Option<Arc<dyn HostFunctions>>,
+    /// 未注入宿主函数时，存储写入只在本次执行内可见
+    storage: HashMap<Vec<u8>, Vec<u8>>,
+    events: Vec<VmEvent>,
+}
+
+impl WasmHost {
+    fn storage_read(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.storage.get(key).cloned().or_else(|| {
+            self.functions
+                .as_ref()
+                .and_then(|functions| functions.storage_read(key))
+        })
+    }
+}
+
+/// 以 `input` 调用模块的导出函数
+pub fn interpret(
+    code: &[u8],
+    input: &[u8],
+    functions: Option<Arc<dyn HostFunctions>>,
+    fuel: u64,
+) -> Result<WasmOutcome, WasmError> {
+    let invalid = |e: wasmi::Error| WasmError::InvalidModule(e.to_string());
+
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+    let module = Module::new(&engine, &mut &code[..]).map_err(invalid)?;
+
+    // 导出函数按名称排序，与编译器记录的入口顺序一致
+    let mut exports: Vec<&str> = module
+        .exports()
+        .filter(|export| matches!(export.ty(), ExternType::Func(_)))
+        .map(|export| export.name())
+        .collect();
+    exports.sort_unstable();
+
+    let Some((index, mut args)) = input.split_first_chunk::<ENTRY_INDEX_SIZE>() else {
+        return Err(WasmError::InvalidInput("missing entry index".to_string()));
+    };
+    let index = u32::from_le_bytes(*index);
+    let name = *exports
+        .get(index as usize)
+        .ok_or(WasmError::UnknownEntry(index))?;
+
+    let mut store = Store::new(
+        &engine,
+        WasmHost {
+            functions,
+            storage: HashMap::new(),
+            events: Vec::new(),
+        },
+    );
+    store
+        .add_fuel(fuel)
+        .map_err(|e| WasmError::InvalidModule(e.to_string()))?;
+
+    let mut linker = <Linker<WasmHost>>::new(&engine);
+    link_host_functions(&mut linker)?;
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .and_then(|pre| pre.start(&mut store))
+        .map_err(|e| trap_or_fuel(&store, fuel, e.to_string()))?;
+    let func = instance
+        .get_func(&store, name)
+        .ok_or(WasmError::UnknownEntry(index))?;
+
+    let ty = func.ty(&store);
+    let mut params = Vec::with_capacity(ty.params().len());
+    for param in ty.params() {
+        let value = match param {
+            ValueType::I32 => take(&mut args).map(|bytes| Value::I32(i32::from_le_bytes(bytes))),
+            ValueType::I64 => take(&mut args).map(|bytes| Value::I64(i64::from_le_bytes(bytes))),
+            other => {
+                return Err(WasmError::InvalidModule(format!(
+                    "{} takes unsupported parameter type {:?}",
+                    name, other
+                )))
+            }
+        };
+        params.push(value.ok_or_else(|| {
+            WasmError::InvalidInput(format!("arguments of {} are truncated", name))
+        })?);
+    }
+    if !args.is_empty() {
+        return Err(WasmError::InvalidInput(format!(
+            "{} trailing argument bytes for {}",
+            args.len(),
+            name
+        )));
+    }
+
+    let mut results: Vec<Value> = ty
+        .results()
+        .iter()
+        .map(|result| Value::default(*result))
+        .collect();
+    func.call(&mut store, &params, &mut results)
+        .map_err(|e| trap_or_fuel(&store, fuel, e.to_string()))?;
+
+    let mut output = Vec::new();
+    for result in results {
+        match result {
+            Value::I32(value) => output.extend_from_slice(&value.to_le_bytes()),
+            Value::I64(value) => output.extend_from_slice(&value.to_le_bytes()),
+            other => {
+                return Err(WasmError::InvalidModule(format!(
+                    "{} returns unsupported value {:?}",
+                    name, other
+                )))
+            }
+        }
+    }
+
+    let fuel_used = store.fuel_consumed().unwrap_or(0);
+    let events = std::mem::take(&mut store.data_mut().events);
+    Ok(WasmOutcome {
+        output,
+        fuel_used,
+        events,
+    })
+}
+
+/// 从参数字节中取出定长的一段
+fn take<const N: usize>(args: &mut &[u8]) -> Option<[u8; N]> {
+    let (head, rest) = args.split_first_chunk::<N>()?;
+    *args = rest;
+    Some(*head)
+}
+
+/// 燃料耗尽时 wasmi 以陷入结束，按剩余燃料区分
+fn trap_or_fuel(store: &Store<WasmHost>, fuel: u64, message: String) -> WasmError {
+    if store.fuel_consumed().unwrap_or(0) >= fuel {
+        WasmError::FuelExhausted
+    } else {
+        WasmError::Trap(message)
+    }
+}
+
+/// 读取 guest 导出的线性内存
+fn read_memory(caller: &Caller<'_, WasmHost>, ptr: i32, len: i32) -> Result<Vec<u8>, Trap> {
+    let memory = caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| Trap::new("module exports no memory"))?;
+    let mut buffer = vec![0u8; len as u32 as usize];
+    memory
+        .read(caller, ptr as u32 as usize, &mut buffer)
+        .map_err(|e| Trap::new(e.to_string()))?;
+    Ok(buffer)
+}
+
+fn link_host_functions(linker: &mut Linker<WasmHost>) -> Result<(), WasmError> {
+    let link_error = |e: wasmi::errors::LinkerError| WasmError::InvalidModule(e.to_string());
+
+    linker
+        .func_wrap(
+            WASM_HOST_MODULE,
+            "storage_read",
+            |mut caller: Caller<'_, WasmHost>,
+             key: i32,
+             key_len: i32,
+             buf: i32,
+             buf_len: i32|
+             -> Result<i64, Trap> {
+                let key = read_memory(&caller, key, key_len)?;
+                let Some(value) = caller.data().storage_read(&key) else {
+                    return Ok(-1);
+                };
+                let copied = value.len().min(buf_len as u32 as usize);
+                let memory = caller
+                    .get_export("memory")
+                    .and_then(Extern::into_memory)
+                    .ok_or_else(|| Trap::new("module exports no memory"))?;
+                memory
+                    .write(&mut caller, buf as u32 as usize, &value[..copied])
+                    .map_err(|e| Trap::new(e.to_string()))?;
+                Ok(value.len() as i64)
+            },
+        )
+        .map_err(link_error)?;
+
+    linker
+        .func_wrap(
+            WASM_HOST_MODULE,
+            "storage_write",
+            |mut caller: Caller<'_, WasmHost>,
+             key: i32,
+             key_len: i32,
+             data: i32,
+             data_len: i32|
+             -> Result<i32, Trap> {
+                let key = read_memory(&caller, key, key_len)?;
+                let value = read_memory(&caller, data, data_len)?;
+                let host = caller.data_mut();
+                if let Some(functions) = &host.functions {
+                    functions
+                        .storage_write(&key, &value)
+                        .map_err(|e| Trap::new(e.to_string()))?;
+                }
+                host.storage.insert(key, value);
+                Ok(0)
+            },
+        )
+        .map_err(link_error)?;
+
+    linker
+        .func_wrap(
+            WASM_HOST_MODULE,
+            "emit_event",
+            |mut caller: Caller<'_, WasmHost>,
+             topic: i32,
+             topic_len: i32,
+             data: i32,
+             data_len: i32|
+             -> Result<i32, Trap> {
+                let topic = read_memory(&caller, topic, topic_len)?;
+                let data = read_memory(&caller, data, data_len)?;
+                let host = caller.data_mut();
+                if let Some(functions) = &host.functions {
+                    functions
+                        .emit_event(&topic, &data)
+                        .map_err(|e| Trap::new(e.to_string()))?;
+                }
+                host.events.push(VmEvent { topic, data });
+                Ok(0)
+            },
+        )
+        .map_err(link_error)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::MemoryHost;
+
+    fn counter() -> Vec<u8> {
+        wat::parse_str(include_str!("../../../tests/fixtures/counter.wat")).unwrap()
+    }
+
+    fn call(index: u32, args: &[u8]) -> Vec<u8> {
+        let mut input = index.to_le_bytes().to_vec();
+        input.extend_from_slice(args);
+        input
+    }
+
+    #[test]
+    fn test_counter_persists_through_host_storage() {
+        let host = Arc::new(MemoryHost::new());
+        let increment = call(1, &5i64.to_le_bytes());
+
+        let outcome = interpret(&counter(), &increment, Some(host.clone()), 100_000).unwrap();
+        assert_eq!(outcome.output, 5i64.to_le_bytes());
+        assert!(outcome.fuel_used > 0);
+        let outcome = interpret(&counter(), &increment, Some(host.clone()), 100_000).unwrap();
+        assert_eq!(outcome.output, 10i64.to_le_bytes());
+        assert_eq!(host.get(b"count"), Some(10i64.to_le_bytes().to_vec()));
+
+        let outcome = interpret(&counter(), &call(0, &[]), Some(host), 100_000).unwrap();
+        assert_eq!(outcome.output, 10i64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_rejects_bad_input_and_exhausted_fuel() {
+        let code = counter();
+        assert_eq!(
+            interpret(&code, &call(7, &[]), None, 100_000),
+            Err(WasmError::UnknownEntry(7))
+        );
+        assert!(matches!(
+            interpret(&code, &call(1, &[1, 2]), None, 100_000),
+            Err(WasmError::InvalidInput(_))
+        ));
+        assert_eq!(
+            interpret(&code, &call(1, &1i64.to_le_bytes()), None, 3),
+            Err(WasmError::FuelExhausted)
+        );
+    }
+}
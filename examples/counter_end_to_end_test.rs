@@ -78,6 +78,7 @@ async fn run_simulation_test() -> Result<()> {
             ],
             shared_objects: vec!["0xabcdef1234567890".to_string()],
             gas_budget,
+            read_only: false,
         };
 
         // Since this is a simulation test, we expect certain errors
@@ -130,6 +131,7 @@ async fn run_real_contract_test() -> Result<()> {
 async fn create_test_system() -> Result<(Arc<DubheNode>, Arc<OffchainExecutionManager>)> {
     // Configure Sui adapter
     let sui_config = SuiConfig {
+        enabled: true,
         rpc_url: "https://fullnode.testnet.sui.io".to_string(),
         ws_url: None,
         network_type: SuiNetworkType::Testnet,
@@ -137,6 +139,9 @@ async fn create_test_system() -> Result<(Arc<DubheNode>, Arc<OffchainExecutionMa
             "0x1".to_string(), // Sui Framework
             "0x2".to_string(), // Sui System
         ],
+        signer: None,
+        rpc_urls: vec![],
+        health_check: dubhe_adapter::HealthCheckConfig::default(),
     };
 
     let sui_adapter = Arc::new(SuiAdapter::new(sui_config).await?);
@@ -192,6 +197,7 @@ mod tests {
             arguments: vec![serde_json::json!("0xabc")],
             shared_objects: vec!["0xabc".to_string()],
             gas_budget: 10000,
+            read_only: false,
         };
 
         assert_eq!(request.function_name, "counter::increment");
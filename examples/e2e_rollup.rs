@@ -0,0 +1,115 @@
+/// 端到端 rollup 示例
+/// adapter → scheduler → VM → state → sync 的最小完整流程
+///
+/// 使用本地 mock 开发链，无需网络，CI 中作为整条管线的集成测试运行
+use anyhow::{ensure, Result};
+use log::info;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Instant;
+
+use dubhe_adapter::ChainAdapter;
+use dubhe_node::devnet::{counter_package_meta, DevChain};
+use dubhe_node::rollup::{counter_increment_tx, RollupConfig, RollupPipeline};
+
+const COUNTER_PACKAGE: &str = "0xc0ffee";
+const COUNTERS: [&str; 3] = ["0xc1", "0xc2", "0xc3"];
+const TRANSACTIONS: u64 = 48;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::init();
+    let started = Instant::now();
+
+    info!("🚀 Dubhe Channel 端到端 rollup 示例");
+
+    // 1. 启动 mock 开发链并部署 counter 包
+    let chain = Arc::new(DevChain::new());
+    let package_id = chain
+        .deploy_package(counter_package_meta(COUNTER_PACKAGE))
+        .await;
+    for counter in COUNTERS {
+        chain.create_object(counter, json!({ "value": 0 })).await;
+    }
+    info!("📦 已部署 counter 包: {}", package_id);
+
+    // 2. 组装管线：loader 编译 → scheduler 规划 → CKB-VM 执行 → 状态层
+    let cache_dir = tempfile::tempdir()?;
+    let mut pipeline = RollupPipeline::new(
+        RollupConfig {
+            block_size: 16,
+            ..RollupConfig::default()
+        },
+        chain.clone(),
+        cache_dir.path(),
+    )?;
+    let compiled = pipeline.load_package(&package_id).await?;
+    info!(
+        "⚙️  编译完成: {} 字节 RISC-V",
+        compiled.risc_v_code.len()
+    );
+
+    // 3. 提交交易流
+    let transactions = (0..TRANSACTIONS)
+        .map(|nonce| {
+            let counter = COUNTERS[nonce as usize % COUNTERS.len()];
+            counter_increment_tx(&package_id, counter, nonce)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    info!("📨 提交 {} 笔交易", transactions.len());
+
+    let blocks = pipeline.submit(transactions).await?;
+
+    // 4. 每个伪区块的批次哈希与 gas 统计
+    let mut total_gas = 0;
+    for sealed in &blocks {
+        let block = &sealed.block;
+        total_gas += block.gas_used;
+        info!("🧱 区块 #{}", block.number);
+        info!("   区块哈希: {}", sealed.block_hash);
+        info!("   批次结果哈希: {}", block.batch_hash);
+        info!("   状态根: {}", block.state_root);
+        info!(
+            "   交易: {}/{} 成功, gas: {} (累计 {})",
+            block.successful_txs, block.tx_count, block.gas_used, total_gas
+        );
+        ensure!(
+            block.successful_txs == block.tx_count,
+            "block #{} has failed transactions",
+            block.number
+        );
+    }
+
+    // 5. 最终状态回写开发链
+    let report = pipeline.sync_to_chain().await?;
+    info!(
+        "⬆️  回写 {} 个对象, 交易: {:?}",
+        report.objects_updated, report.tx_hash
+    );
+
+    let expected = TRANSACTIONS / COUNTERS.len() as u64;
+    for counter in COUNTERS {
+        let object = chain
+            .get_object(counter)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("counter {} missing", counter))?;
+        info!(
+            "🔢 {} = {} (version {})",
+            counter, object.content["value"], object.version
+        );
+        ensure!(
+            object.content["value"] == json!(expected),
+            "counter {} expected {}, got {}",
+            counter,
+            expected,
+            object.content["value"]
+        );
+    }
+
+    if let Some(tx_hash) = &report.tx_hash {
+        chain.get_transaction_receipt(tx_hash).await?;
+    }
+
+    info!("✅ 端到端流程完成，用时 {:?}", started.elapsed());
+    Ok(())
+}
@@ -88,10 +88,14 @@ async fn demo_step_2_multi_chain_adapters() -> Result<()> {
 
     // 注册 Sui 适配器
     let sui_config = dubhe_adapter::SuiConfig {
+        enabled: true,
         rpc_url: "https://fullnode.testnet.sui.io".to_string(),
         ws_url: None,
         network_type: dubhe_adapter::SuiNetworkType::Testnet,
         package_ids: vec!["0x1".to_string(), "0x2".to_string()],
+        signer: None,
+        rpc_urls: vec![],
+        health_check: dubhe_adapter::HealthCheckConfig::default(),
     };
 
     let sui_adapter = dubhe_adapter::sui::SuiAdapter::new(sui_config).await?;
@@ -141,6 +145,8 @@ async fn demo_step_3_contract_loading() -> Result<()> {
         compiler_version: Some("solc-0.8.19".to_string()),
         created_at: chrono::Utc::now().timestamp() as u64,
         creator: Some("0xCreator".to_string()),
+        abi_source: None,
+        modules: vec![],
     };
 
     info!("📝 Loading contract: {}", contract_meta.address);
@@ -189,6 +195,8 @@ async fn demo_step_4_parallel_scheduling() -> Result<()> {
             nonce: 0,
             read_set: vec!["0xAccount1".to_string()],
             write_set: vec!["0xAccount1".to_string()],
+            access_list: None,
+            max_priority_fee: None,
         },
         Transaction {
             hash: "0xdef456".to_string(),
@@ -200,6 +208,8 @@ async fn demo_step_4_parallel_scheduling() -> Result<()> {
             nonce: 1,
             read_set: vec!["0xAccount2".to_string()],
             write_set: vec!["0xAccount2".to_string()],
+            access_list: None,
+            max_priority_fee: None,
         },
         Transaction {
             hash: "0xghi789".to_string(),
@@ -211,6 +221,8 @@ async fn demo_step_4_parallel_scheduling() -> Result<()> {
             nonce: 2,
             read_set: vec!["0xAccount1".to_string(), "0xAccount3".to_string()],
             write_set: vec!["0xAccount3".to_string()],
+            access_list: None,
+            max_priority_fee: None,
         },
     ];
 
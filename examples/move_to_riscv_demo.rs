@@ -27,6 +27,7 @@ async fn main() -> Result<()> {
 
     // 第一步：连接 Sui 网络获取 Move 包
     let sui_config = SuiConfig {
+        enabled: true,
         rpc_url: "https://fullnode.testnet.sui.io".to_string(),
         ws_url: None,
         network_type: SuiNetworkType::Testnet,
@@ -34,6 +35,9 @@ async fn main() -> Result<()> {
             "0x1".to_string(), // Sui Framework
             "0x2".to_string(), // Sui System
         ],
+        signer: None,
+        rpc_urls: vec![],
+        health_check: dubhe_adapter::HealthCheckConfig::default(),
     };
 
     let sui_adapter = SuiAdapter::new(sui_config).await?;
@@ -49,6 +49,7 @@ async fn setup_dubhe_system() -> Result<(Arc<DubheNode>, Arc<OffchainExecutionMa
 
     // 配置 Sui 适配器
     let sui_config = SuiConfig {
+        enabled: true,
         rpc_url: "https://fullnode.testnet.sui.io".to_string(),
         ws_url: None,
         network_type: SuiNetworkType::Testnet,
@@ -58,6 +59,9 @@ async fn setup_dubhe_system() -> Result<(Arc<DubheNode>, Arc<OffchainExecutionMa
             "0x5".to_string(),   // Clock object
             "0x403".to_string(), // System state
         ],
+        signer: None,
+        rpc_urls: vec![],
+        health_check: dubhe_adapter::HealthCheckConfig::default(),
     };
 
     let sui_adapter = Arc::new(SuiAdapter::new(sui_config).await?);
@@ -103,6 +107,7 @@ async fn demo_single_transaction(manager: &Arc<OffchainExecutionManager>) -> Res
         arguments: vec![json!(counter_object_id)],
         shared_objects: vec![counter_object_id.to_string()],
         gas_budget: 10000,
+        read_only: false,
     };
 
     info!("   🔄 执行请求: counter::increment");
@@ -167,6 +172,7 @@ async fn demo_batch_transactions(manager: &Arc<OffchainExecutionManager>) -> Res
             arguments: vec![json!(object_id)],
             shared_objects: vec![object_id.clone()],
             gas_budget: 8000,
+            read_only: false,
         };
 
         let handle = tokio::spawn(async move { manager_clone.execute_offchain(request).await });
@@ -244,6 +250,7 @@ async fn demo_performance_analysis(manager: &Arc<OffchainExecutionManager>) -> R
             arguments: vec![json!("0x123456789abcdef")],
             shared_objects: vec!["0x123456789abcdef".to_string()],
             gas_budget,
+            read_only: false,
         };
 
         let start = Instant::now();
@@ -289,6 +296,7 @@ async fn demo_error_handling(manager: &Arc<OffchainExecutionManager>) -> Result<
         arguments: vec![],
         shared_objects: vec!["0x123456789abcdef".to_string()],
         gas_budget: 5000,
+        read_only: false,
     };
 
     match manager.execute_offchain(invalid_request).await {
@@ -315,6 +323,7 @@ async fn demo_error_handling(manager: &Arc<OffchainExecutionManager>) -> Result<
         arguments: vec![],
         shared_objects: vec!["0x123456789abcdef".to_string()],
         gas_budget: 5000,
+        read_only: false,
     };
 
     match manager.execute_offchain(invalid_function_request).await {
@@ -341,6 +350,7 @@ async fn demo_error_handling(manager: &Arc<OffchainExecutionManager>) -> Result<
         arguments: vec![json!("0x123456789abcdef")],
         shared_objects: vec!["0x123456789abcdef".to_string()],
         gas_budget: 6000,
+        read_only: false,
     };
 
     match manager.execute_offchain(recovery_request).await {
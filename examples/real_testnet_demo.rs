@@ -55,10 +55,14 @@ async fn setup_real_dubhe_system() -> Result<Arc<OffchainExecutionManager>> {
 
     // 1. 配置真实的 Sui 适配器
     let sui_config = SuiConfig {
+        enabled: true,
         rpc_url: TESTNET_RPC.to_string(),
         ws_url: Some("wss://fullnode.testnet.sui.io:443".to_string()),
         network_type: SuiNetworkType::Testnet,
         package_ids: vec![PACKAGE_ID.to_string()],
+        signer: None,
+        rpc_urls: vec![],
+        health_check: dubhe_adapter::HealthCheckConfig::default(),
     };
 
     let sui_adapter = Arc::new(SuiAdapter::new(sui_config).await?);
@@ -94,6 +98,7 @@ async fn demo_real_single_transaction(manager: &Arc<OffchainExecutionManager>) -
         arguments: vec![json!(COUNTER_OBJECT_ID)],
         shared_objects: vec![COUNTER_OBJECT_ID.to_string()],
         gas_budget: 10000,
+        read_only: false,
     };
 
     info!("🎯 执行请求:");
@@ -176,6 +181,7 @@ async fn demo_real_counter_operations(manager: &Arc<OffchainExecutionManager>) -
             arguments: args,
             shared_objects: vec![COUNTER_OBJECT_ID.to_string()],
             gas_budget,
+            read_only: false,
         };
 
         match manager.execute_offchain(request).await {
@@ -227,6 +233,7 @@ async fn demo_real_batch_execution(manager: &Arc<OffchainExecutionManager>) -> R
             arguments: vec![json!(COUNTER_OBJECT_ID)],
             shared_objects: vec![COUNTER_OBJECT_ID.to_string()],
             gas_budget: 8000,
+            read_only: false,
         };
 
         match manager.execute_offchain(request).await {
@@ -312,6 +319,7 @@ mod tests {
             arguments: vec![json!(COUNTER_OBJECT_ID)],
             shared_objects: vec![COUNTER_OBJECT_ID.to_string()],
             gas_budget: 10000,
+            read_only: false,
         };
 
         let result = manager.execute_offchain(request).await.unwrap();
@@ -25,10 +25,14 @@ async fn main() -> Result<()> {
 
     // 1. 配置真实的 Sui 适配器
     let sui_config = SuiConfig {
+        enabled: true,
         rpc_url: TESTNET_RPC.to_string(),
         ws_url: Some("wss://fullnode.testnet.sui.io:443".to_string()),
         network_type: SuiNetworkType::Testnet,
         package_ids: vec![PACKAGE_ID.to_string()],
+        signer: None,
+        rpc_urls: vec![],
+        health_check: dubhe_adapter::HealthCheckConfig::default(),
     };
 
     let sui_adapter = SuiAdapter::new(sui_config).await?;
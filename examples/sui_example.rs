@@ -20,6 +20,7 @@ async fn main() -> Result<()> {
 
     // Configure Sui adapter
     let config = SuiConfig {
+        enabled: true,
         rpc_url: "https://fullnode.mainnet.sui.io".to_string(),
         ws_url: None,
         network_type: SuiNetworkType::Mainnet,
@@ -27,6 +28,9 @@ async fn main() -> Result<()> {
             "0x0000000000000000000000000000000000000000000000000000000000000001".to_string(),
             "0x0000000000000000000000000000000000000000000000000000000000000002".to_string(),
         ],
+        signer: None,
+        rpc_urls: vec![],
+        health_check: dubhe_adapter::HealthCheckConfig::default(),
     };
 
     // Create Sui adapter
@@ -21,6 +21,7 @@ async fn main() -> Result<()> {
 
     // 创建 Sui 测试网配置
     let config = SuiConfig {
+        enabled: true,
         rpc_url: "https://fullnode.testnet.sui.io".to_string(),
         ws_url: None,
         network_type: SuiNetworkType::Testnet,
@@ -29,6 +30,9 @@ async fn main() -> Result<()> {
             "0x2".to_string(), // Sui System
                                // 添加您自己的包ID进行测试
         ],
+        signer: None,
+        rpc_urls: vec![],
+        health_check: dubhe_adapter::HealthCheckConfig::default(),
     };
 
     // 创建 Sui 适配器
@@ -42,10 +42,14 @@ async fn main() -> Result<()> {
 
     // 1. 初始化 Sui 适配器
     let sui_config = SuiConfig {
+        enabled: true,
         rpc_url: TESTNET_RPC.to_string(),
         ws_url: Some("wss://fullnode.testnet.sui.io:443".to_string()),
         network_type: SuiNetworkType::Testnet,
         package_ids: vec![PACKAGE_ID.to_string()],
+        signer: None,
+        rpc_urls: vec![],
+        health_check: dubhe_adapter::HealthCheckConfig::default(),
     };
 
     let sui_adapter = SuiAdapter::new(sui_config).await?;
@@ -206,7 +210,7 @@ async fn execute_offchain_modifications(
 async fn build_sync_transaction(
     sui_adapter: &SuiAdapter,
     offchain_state: &OffchainCounterState,
-) -> Result<serde_json::Value> {
+) -> Result<Vec<u8>> {
     info!("🏗️ 构建set_value({})交易", offchain_state.current_value);
 
     let sender = &offchain_state.owner;
@@ -239,7 +243,7 @@ async fn build_sync_transaction(
 /// 验证同步交易
 async fn verify_sync_transaction(
     sui_adapter: &SuiAdapter,
-    tx_data: &serde_json::Value,
+    tx_data: &[u8],
 ) -> Result<bool> {
     info!("🔍 执行交易干跑验证...");
 
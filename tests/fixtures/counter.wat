@@ -0,0 +1,24 @@
+;; 计数器合约：计数以小端 i64 存在宿主存储的 "count" 键下
+(module
+  (import "env" "storage_read" (func $storage_read (param i32 i32 i32 i32) (result i64)))
+  (import "env" "storage_write" (func $storage_write (param i32 i32 i32 i32) (result i32)))
+  (memory (export "memory") 1 1)
+  (data (i32.const 0) "count")
+
+  (func $load (result i64)
+    (if (result i64)
+      (i64.eq
+        (call $storage_read (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 8))
+        (i64.const 8))
+      (then (i64.load (i32.const 16)))
+      (else (i64.const 0))))
+
+  (func (export "get") (result i64)
+    (call $load))
+
+  (func (export "increment") (param $by i64) (result i64)
+    (local $value i64)
+    (local.set $value (i64.add (call $load) (local.get $by)))
+    (i64.store (i32.const 16) (local.get $value))
+    (drop (call $storage_write (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 8)))
+    (local.get $value)))
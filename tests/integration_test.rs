@@ -35,10 +35,12 @@ async fn test_multi_chain_adapters() -> Result<()> {
 
     // 测试 Sui 适配器
     let sui_config = dubhe_adapter::SuiConfig {
+        enabled: true,
         rpc_url: "https://fullnode.testnet.sui.io".to_string(),
         ws_url: None,
         network_type: dubhe_adapter::SuiNetworkType::Testnet,
         package_ids: vec!["0x1".to_string()],
+        signer: None,
     };
 
     let sui_adapter = dubhe_adapter::sui::SuiAdapter::new(sui_config).await?;
@@ -84,6 +86,8 @@ async fn test_contract_loading() -> Result<()> {
         compiler_version: Some("test".to_string()),
         created_at: chrono::Utc::now().timestamp() as u64,
         creator: None,
+        abi_source: None,
+        modules: vec![],
     };
 
     let compiled = loader.load_contract(&contract_meta).await?;
@@ -120,6 +124,8 @@ async fn test_parallel_scheduler() -> Result<()> {
             nonce: 0,
             read_set: vec!["0xA".to_string()],
             write_set: vec!["0xB".to_string()],
+            access_list: None,
+            max_priority_fee: None,
         },
         Transaction {
             hash: "0x2".to_string(),
@@ -131,6 +137,8 @@ async fn test_parallel_scheduler() -> Result<()> {
             nonce: 0,
             read_set: vec!["0xC".to_string()],
             write_set: vec!["0xD".to_string()],
+            access_list: None,
+            max_priority_fee: None,
         },
     ];
 
@@ -210,6 +218,8 @@ async fn test_end_to_end_integration() -> Result<()> {
         compiler_version: Some("test-0.1.0".to_string()),
         created_at: chrono::Utc::now().timestamp() as u64,
         creator: Some("0xCreator".to_string()),
+        abi_source: None,
+        modules: vec![],
     };
 
     let compiled_contract = loader.load_contract(&contract_meta).await?;
@@ -226,6 +236,8 @@ async fn test_end_to_end_integration() -> Result<()> {
         nonce: 42,
         read_set: vec![contract_meta.address.clone()],
         write_set: vec![contract_meta.address.clone()],
+        access_list: None,
+        max_priority_fee: None,
     };
 
     let batch_result = scheduler.submit_batch(vec![transaction]).await?;
@@ -272,6 +284,8 @@ async fn test_system_load() -> Result<()> {
             nonce: i as u64,
             read_set: vec![format!("0xAccount{}", i % 10)],
             write_set: vec![format!("0xAccount{}", (i + 1) % 10)],
+            access_list: None,
+            max_priority_fee: None,
         });
     }
 
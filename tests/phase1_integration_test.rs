@@ -30,6 +30,7 @@ async fn test_phase1_complete_flow() -> Result<()> {
             "0x403".to_string(), // System state
         ],
         gas_budget: 10000,
+        read_only: false,
     };
 
     // 执行链下交易
@@ -64,6 +65,7 @@ async fn test_concurrent_sessions() -> Result<()> {
             arguments: vec![serde_json::json!(i)],
             shared_objects: vec!["0x5".to_string()],
             gas_budget: 5000,
+            read_only: false,
         };
 
         let handle = tokio::spawn(async move {
@@ -105,6 +107,7 @@ async fn test_state_locking_mechanism() -> Result<()> {
         arguments: vec![],
         shared_objects: shared_objects.clone(),
         gas_budget: 8000,
+        read_only: false,
     };
 
     let request2 = ExecutionRequest {
@@ -114,6 +117,7 @@ async fn test_state_locking_mechanism() -> Result<()> {
         arguments: vec![],
         shared_objects,
         gas_budget: 8000,
+        read_only: false,
     };
 
     // 并发执行使用相同对象的请求
@@ -143,6 +147,7 @@ async fn test_gas_savings_calculation() -> Result<()> {
             arguments: vec![serde_json::json!(i * 100)],
             shared_objects: vec!["0x5".to_string()],
             gas_budget: 12000,
+            read_only: false,
         };
 
         let _result = offchain_manager.execute_offchain(request).await?;
@@ -173,6 +178,7 @@ async fn test_error_recovery() -> Result<()> {
         arguments: vec![],
         shared_objects: vec!["0x5".to_string()],
         gas_budget: 5000,
+        read_only: false,
     };
 
     // 执行应该失败的请求
@@ -199,6 +205,7 @@ async fn test_error_recovery() -> Result<()> {
         arguments: vec![],
         shared_objects: vec!["0x5".to_string()],
         gas_budget: 6000,
+        read_only: false,
     };
 
     let recovery_result = offchain_manager.execute_offchain(normal_request).await?;
@@ -212,6 +219,7 @@ async fn test_error_recovery() -> Result<()> {
 async fn setup_test_environment() -> Result<(Arc<OffchainExecutionManager>, TestContext)> {
     // 初始化 Sui 适配器
     let sui_config = SuiConfig {
+        enabled: true,
         rpc_url: "https://fullnode.testnet.sui.io".to_string(),
         ws_url: None,
         network_type: SuiNetworkType::Testnet,
@@ -219,6 +227,7 @@ async fn setup_test_environment() -> Result<(Arc<OffchainExecutionManager>, Test
             "0x1".to_string(), // Sui Framework
             "0x2".to_string(), // Sui System
         ],
+        signer: None,
     };
 
     let sui_adapter = Arc::new(SuiAdapter::new(sui_config).await?);